@@ -1,19 +1,20 @@
-// 构建脚本: 链接FFmpeg依赖库
+// 构建脚本: 生成 gRPC 服务代码与 C FFI 头文件
 fn main() {
-    // 仅在Windows MSVC环境下添加FFmpeg相关库
-    #[cfg(all(target_os = "windows", target_env = "msvc"))]
-    {
-        // Intel QSV (Quick Sync Video) 硬件加速
-        println!("cargo:rustc-link-lib=dylib=libmfx");
-
-        // x264 编码器
-        println!("cargo:rustc-link-lib=dylib=libx264");
+    // gRPC: 编译 proto 生成消息与服务代码
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/detector.proto").expect("编译 proto/detector.proto 失败");
 
-        // OLE 自动化和VFW
-        println!("cargo:rustc-link-lib=dylib=oleaut32");
-        println!("cargo:rustc-link-lib=dylib=vfw32");
-
-        // Secure Channel (TLS/SSL)
-        println!("cargo:rustc-link-lib=dylib=secur32");
+    // C FFI: 生成 C/C++ 头文件
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        match cbindgen::generate(&crate_dir) {
+            Ok(bindings) => {
+                bindings.write_to_file(format!("{}/include/yolov8_rs.h", crate_dir));
+            }
+            Err(e) => println!("cargo:warning=cbindgen 生成头文件失败: {}", e),
+        }
     }
 }
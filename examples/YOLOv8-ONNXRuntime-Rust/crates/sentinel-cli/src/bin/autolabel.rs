@@ -0,0 +1,123 @@
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+// 离线自动标注: 用大模型预标注图片目录, 导出 YOLO 数据集 (用于微调小模型)
+// 运行: cargo run --bin autolabel --release -- --model models/yolov8x.onnx --input images/ --output dataset
+
+use clap::Parser;
+
+use yolov8_rs::dataset::{autolabel, read_class_file, AutoLabelConfig, DATASET_DIR};
+use yolov8_rs::models::{load_detect_model, CalibrationConfig, ModelType, CALIBRATION_CONFIG_PATH};
+use yolov8_rs::Args;
+
+#[derive(Parser)]
+#[command(author, version, about = "离线自动标注图片目录, 导出 YOLO 数据集", long_about = None)]
+struct AutoLabelArgs {
+    /// ONNX 检测模型路径 (建议使用大模型, 如 yolov8x)
+    #[arg(long)]
+    model: String,
+
+    /// 图片目录 (不递归)
+    #[arg(long)]
+    input: String,
+
+    /// 数据集输出目录 (已有数据集时继续追加)
+    #[arg(long, default_value = DATASET_DIR)]
+    output: String,
+
+    /// 置信度阈值, 低于此值的框不导出
+    #[arg(long, default_value_t = 0.5)]
+    conf: f32,
+
+    /// IOU 阈值 (默认按模型类型)
+    #[arg(long)]
+    iou: Option<f32>,
+
+    /// 推理输入尺寸
+    #[arg(long, default_value_t = 640)]
+    size: u32,
+
+    /// 置信度校准配置 (--conf 为校准后的阈值), 不存在时不校准
+    #[arg(long, default_value = CALIBRATION_CONFIG_PATH)]
+    calibration: String,
+
+    /// 类别文件 (每行一个类别名, 行号即 class_id), 为空时使用模型元数据中的 names
+    #[arg(long, default_value = "")]
+    classes: String,
+
+    /// 按类别导出目标裁剪图 (crops/<类别名>/)
+    #[arg(long)]
+    crops: bool,
+
+    /// 使用 CUDA EP
+    #[arg(long)]
+    cuda: bool,
+
+    /// 使用 TensorRT EP
+    #[arg(long)]
+    trt: bool,
+
+    /// TensorRT FP16
+    #[arg(long)]
+    fp16: bool,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = AutoLabelArgs::parse();
+    if let Some(config) = CalibrationConfig::load(&args.calibration) {
+        config.apply();
+    }
+    let model_type = ModelType::from_path(&args.model);
+    let mut model = load_detect_model(
+        model_type,
+        Args {
+            model: args.model.clone(),
+            source: String::new(),
+            device_id: 0,
+            trt: args.trt,
+            cuda: args.cuda,
+            batch: 1,
+            batch_min: 1,
+            batch_max: 1,
+            fp16: args.fp16,
+            dla_core: None,
+            task: None,
+            nc: None,
+            nk: None,
+            nm: None,
+            width: Some(args.size),
+            height: Some(args.size),
+            conf: args.conf,
+            iou: args
+                .iou
+                .unwrap_or_else(|| model_type.default_iou_threshold()),
+            kconf: 0.55,
+            profile: false,
+        },
+    )?;
+    println!("✅ {:?} 模型加载成功: {}", model_type, args.model);
+
+    let classes = if args.classes.is_empty() {
+        Vec::new()
+    } else {
+        read_class_file(&args.classes)?
+    };
+
+    let report = autolabel(
+        model.as_mut(),
+        &args.input,
+        &args.output,
+        AutoLabelConfig {
+            conf: args.conf,
+            classes,
+            crops: args.crops,
+        },
+    )?;
+    println!(
+        "✅ 自动标注完成: {} 张图片, {} 个框, 失败 {} 张",
+        report.images,
+        report.boxes,
+        report.failed.len()
+    );
+    println!("📊 统计报告: {}", report.stats.display());
+    Ok(())
+}
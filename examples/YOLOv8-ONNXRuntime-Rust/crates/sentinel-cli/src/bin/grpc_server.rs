@@ -0,0 +1,119 @@
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+// gRPC 推理服务 (proto/detector.proto)
+// 运行: cargo run --bin grpc-server --release --features grpc -- --model models/yolov8n.onnx
+
+use std::time::Duration;
+
+use clap::Parser;
+
+use yolov8_rs::models::{load_detect_model, CalibrationConfig, ModelType, CALIBRATION_CONFIG_PATH};
+use yolov8_rs::server::{
+    BatchConfig, BatchScheduler, DetectorService, QosConfig, BATCHING_CONFIG_PATH,
+    DEFAULT_LISTEN_ADDR,
+};
+use yolov8_rs::Args;
+
+#[derive(Parser)]
+#[command(author, version, about = "yolov8-rs gRPC 推理服务", long_about = None)]
+struct ServerArgs {
+    /// ONNX 检测模型路径
+    #[arg(long)]
+    model: String,
+
+    /// 监听地址
+    #[arg(long, default_value = DEFAULT_LISTEN_ADDR)]
+    listen: String,
+
+    /// 推理输入尺寸
+    #[arg(long, default_value_t = 640)]
+    size: u32,
+
+    /// 单个 batch 的最大帧数 (需要动态 batch 的模型)
+    #[arg(long, default_value_t = 8)]
+    max_batch: u32,
+
+    /// 凑 batch 的最长等待 (毫秒)
+    #[arg(long, default_value_t = 5)]
+    max_wait_ms: u64,
+
+    /// 按流的调度配置 (优先级/等待上限/队列上限), 不存在时各流平等轮询
+    #[arg(long, default_value = BATCHING_CONFIG_PATH)]
+    batching: String,
+
+    /// 置信度阈值 (默认按模型类型)
+    #[arg(long)]
+    conf: Option<f32>,
+
+    /// 置信度校准配置 (--conf 为校准后的阈值), 不存在时不校准
+    #[arg(long, default_value = CALIBRATION_CONFIG_PATH)]
+    calibration: String,
+
+    /// 使用 CUDA EP
+    #[arg(long)]
+    cuda: bool,
+
+    /// 使用 TensorRT EP
+    #[arg(long)]
+    trt: bool,
+
+    /// TensorRT FP16
+    #[arg(long)]
+    fp16: bool,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = ServerArgs::parse();
+    if let Some(config) = CalibrationConfig::load(&args.calibration) {
+        config.apply();
+    }
+    let model_type = ModelType::from_path(&args.model);
+    let max_batch = args.max_batch.max(1);
+    let model = load_detect_model(
+        model_type,
+        Args {
+            model: args.model.clone(),
+            source: String::new(),
+            device_id: 0,
+            trt: args.trt,
+            cuda: args.cuda,
+            batch: max_batch,
+            batch_min: 1,
+            batch_max: max_batch,
+            fp16: args.fp16,
+            dla_core: None,
+            task: None,
+            nc: None,
+            nk: None,
+            nm: None,
+            width: Some(args.size),
+            height: Some(args.size),
+            conf: args
+                .conf
+                .unwrap_or_else(|| model_type.default_conf_threshold()),
+            iou: model_type.default_iou_threshold(),
+            kconf: 0.55,
+            profile: false,
+        },
+    )?;
+    println!("✅ {:?} 模型加载成功: {}", model_type, args.model);
+
+    let scheduler = BatchScheduler::start(
+        model,
+        BatchConfig {
+            max_batch: max_batch as usize,
+            max_wait: Duration::from_millis(args.max_wait_ms),
+            qos: QosConfig::load(&args.batching).unwrap_or_default(),
+            ..Default::default()
+        },
+    )?;
+
+    let addr = args.listen.parse()?;
+    println!("🌐 gRPC 推理服务监听: {}", addr);
+    tonic::transport::Server::builder()
+        .add_service(DetectorService::new(scheduler).into_server())
+        .serve(addr)
+        .await?;
+    Ok(())
+}
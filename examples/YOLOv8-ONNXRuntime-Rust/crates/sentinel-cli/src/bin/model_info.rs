@@ -0,0 +1,47 @@
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+// ONNX 模型信息与兼容性检查
+// 运行: cargo run --bin model-info -- --model models/yolov8n.onnx
+
+use clap::Parser;
+
+use yolov8_rs::models::{ModelInfo, ModelType};
+
+#[derive(Parser)]
+#[command(author, version, about = "查看 ONNX 模型信息并检查能否加载", long_about = None)]
+struct InfoArgs {
+    /// ONNX 模型路径
+    #[arg(long)]
+    model: String,
+}
+
+fn main() {
+    let args = InfoArgs::parse();
+    let info = match ModelInfo::load(&args.model) {
+        Ok(info) => info,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(2);
+        }
+    };
+    print!("{}", info);
+
+    match info.guess_model_type() {
+        Some(guess) => println!(
+            "🔍 推测模型类型: {:?} (置信度 {:.2}) — {}",
+            guess.model_type, guess.confidence, guess.reason
+        ),
+        None => println!("🔍 无法从输出布局推测模型类型"),
+    }
+
+    // 加载时按文件名确定类型, 与 Detector / gRPC 服务一致
+    let model_type = ModelType::from_path(&args.model);
+    if let Err(e) = info.validate(model_type) {
+        eprintln!("❌ 按文件名将以 {:?} 加载, 但检查未通过: {}", model_type, e);
+        std::process::exit(1);
+    }
+    if info.metadata("task").is_none() {
+        println!("⚠️ 模型缺少 `task` 元数据, 单独使用 YOLOv8 时需用 `--task` 指定");
+    }
+    println!("✅ 按文件名将以 {:?} 加载, 兼容性检查通过", model_type);
+}
@@ -0,0 +1,831 @@
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
+/// 数字卫兵 (Digital Sentinel)
+///
+/// 智能视频监控系统
+///
+/// 系统架构:
+/// 1. 采集线程: 视频解码与预处理 (独立工作线程)
+/// 2. 检测线程: 目标检测与追踪 (独立工作线程)
+/// 3. 主线程:   渲染显示 (macroquad事件循环)
+//
+// 使用 mimalloc 替代系统默认分配器 (性能提升 10-30%)
+#[global_allocator]
+static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
+
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser};
+use egui_macroquad::egui;
+use macroquad::prelude::*;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use yolov8_rs::analytics::{
+    overlap, BestShotGallery, EventVerifier, GroundCalibration, LeftBehindConfig,
+    LeftBehindMonitor, MarkerAnchor, MarkerDictionary, OverlapConfig, RunReport, TamperConfig,
+    TamperMonitor, VerifyConfig, VisitorConfig, VisitorCounter, ZoneConfig, ZoneEngine,
+    GROUND_CALIBRATION_PATH, OVERLAP_CONFIG_PATH,
+};
+use yolov8_rs::api::{ApiAuth, API_TOKENS_PATH};
+use yolov8_rs::audit::{self, EVENTS_DB_PATH};
+use yolov8_rs::clips::{ClipConfig, CLIPS_CONFIG_PATH};
+use yolov8_rs::dataset::DATASET_DIR;
+use yolov8_rs::detection::border::{self, BorderConfig, BORDER_CONFIG_PATH};
+use yolov8_rs::detection::box_filter::{self, BoxFilterConfig, BOX_FILTER_CONFIG_PATH};
+use yolov8_rs::detection::gpu_placement::{self, GpuPlacementConfig, GPU_PLACEMENT_CONFIG_PATH};
+use yolov8_rs::detection::journal::JOURNAL_INTERVAL;
+use yolov8_rs::detection::tile_cache::{self, TileConfig, TILE_CONFIG_PATH};
+use yolov8_rs::detection::{GlobalIdConfig, GlobalIdManager, TrackJournal, INF_SIZE};
+use yolov8_rs::hot_reload::{ConfigKind, ConfigReloaded, ConfigUpdate, ConfigWatcher};
+use yolov8_rs::i18n::{set_language, Language};
+use yolov8_rs::input::{RtspSecurity, RTSP_SECURITY_CONFIG_PATH};
+use yolov8_rs::memory_guard::{MemoryGuardConfig, MEMORY_GUARD_CONFIG_PATH};
+use yolov8_rs::models::world::{self, WorldConfig, WORLD_CONFIG_PATH};
+use yolov8_rs::models::{CalibrationConfig, MOCK_MODEL_PREFIX};
+#[cfg(feature = "ndi")]
+use yolov8_rs::ndi::{NdiOptions, NdiPublisher};
+use yolov8_rs::notifier::{Notifier, NotifierConfig, NOTIFIER_CONFIG_PATH};
+use yolov8_rs::onvif::{OnvifPublisher, DEFAULT_METADATA_FRAMES};
+use yolov8_rs::renderer::Renderer;
+use yolov8_rs::restream::{RestreamConfig, RestreamServer, RESTREAM_CONFIG_PATH};
+use yolov8_rs::retention::{RetentionConfig, RETENTION_CONFIG_PATH};
+use yolov8_rs::runtime_config::RuntimeConfig;
+use yolov8_rs::scheduler::{ScheduleConfig, Scheduler, SCHEDULE_CONFIG_PATH};
+use yolov8_rs::shm_output::{ResultsFormat, ShmPublisher};
+use yolov8_rs::ui_config::{
+    ProfileConfig, SessionState, PROFILES_CONFIG_PATH, SESSION_STATE_PATH, TRACKER_CONFIG_PATH,
+};
+use yolov8_rs::utils::fisheye::{fisheye_config, FisheyeConfig};
+use yolov8_rs::utils::jetson::{JetsonMonitor, ThermalPolicy};
+#[cfg(feature = "vcam")]
+use yolov8_rs::vcam::{VcamConfig, VcamOutput, VCAM_CONFIG_PATH};
+use yolov8_rs::watchdog::{self, Component, Watchdog, WatchdogConfig, WATCHDOG_CONFIG_PATH};
+use yolov8_rs::xbus;
+
+/// 数字卫兵参数
+#[derive(Parser, Debug)]
+#[command(author, version, about = "数字卫兵 - 智能视频监控系统", long_about = None)]
+struct Args {
+    /// 检测模型 (n/s/m/l/x/v10n/v10s/v10m/v11n/v11s/v11m/fastest/fastest-xl/n-int8/m-int8/v5n/v5s/v5m/nanodet/nanodet-m/nanodet-plus/yolox_s/yolox_m/yolox_l), mock:夹具.json 为模拟模型
+    #[arg(short, long, default_value = "n")]
+    model: String,
+
+    /// 跟踪算法 (deepsort/bytetrack/none)
+    #[arg(short = 't', long, default_value = "none")]
+    tracker: String,
+
+    /// 启用姿态估计 (需要pose模型支持)
+    #[arg(short = 'p', long, default_value_t = false)]
+    pose: bool,
+
+    /// 两阶段姿态模型 (ViTPose/RTMPose ONNX路径), 对检测出的人框单独做姿态估计
+    #[arg(long, default_value = "")]
+    pose_model: String,
+
+    /// 深度估计模型 (MiDaS/Depth-Anything ONNX路径), 在标签中显示近似距离
+    #[arg(long, default_value = "")]
+    depth_model: String,
+
+    /// 行人属性模型 (多标签分类 ONNX路径, 同名 .txt 为标签列表), 识别衣着颜色/背包/安全帽, 需要跟踪器
+    #[arg(long, default_value = "")]
+    attribute_model: String,
+
+    /// 车辆属性模型 (车型/颜色分类 ONNX路径, 同名 .txt 为标签列表), 需要跟踪器且检测类别包含车辆 (2/5/7)
+    #[arg(long, default_value = "")]
+    vehicle_model: String,
+
+    /// CLIP 模型目录 (visual.onnx/textual.onnx/vocab.json/merges.txt), 启用文本提示检索
+    #[arg(long, default_value = "")]
+    clip_model: String,
+
+    /// 启用跨摄像头全局ID (需要ReID特征: DeepSort, 或 ByteTrack 且 ReID 权重大于0)
+    #[arg(long, default_value_t = false)]
+    global_id: bool,
+
+    /// 检测模型使用的 DLA 核心 (Jetson, 经 TensorRT EP 部署)
+    #[arg(long)]
+    dla_core: Option<u32>,
+
+    /// 多 GPU 分配配置 (检测模型按逻辑流/模型固定到 GPU, 或按推理耗时与显存自动分配), 不存在时在 CPU 上推理
+    #[arg(long, default_value = GPU_PLACEMENT_CONFIG_PATH)]
+    gpu_placement: String,
+
+    /// 按类别的检测框尺寸与宽高比过滤配置 (可按逻辑流覆盖), 不存在时不过滤
+    #[arg(long, default_value = BOX_FILTER_CONFIG_PATH)]
+    box_filters: String,
+
+    /// 贴边检测框的处理策略 (保留/丢弃/标记为不完整并放大跟踪观测噪声), 不存在时照常处理
+    #[arg(long, default_value = BORDER_CONFIG_PATH)]
+    border_policy: String,
+
+    /// 静止机位分块推理配置 (缓存背景与检测结果, 只推理有运动的区域), 不存在时每帧整帧推理
+    #[arg(long, default_value = TILE_CONFIG_PATH)]
+    tile_inference: String,
+
+    /// 开放词汇检测配置 (YOLO-World 的文本编码器目录与初始类别词表), 不存在时词表为 person
+    #[arg(long, default_value = WORLD_CONFIG_PATH)]
+    open_vocabulary: String,
+
+    /// 运行时线程配置文件 (ORT/rayon 线程数与绑核), 不存在时使用默认值
+    #[arg(long, default_value = yolov8_rs::runtime_config::RUNTIME_CONFIG_PATH)]
+    runtime_config: String,
+
+    /// 置信度校准配置 (按模型的温度/Platt 参数, 使同一阈值在不同模型上一致), 不存在时不校准
+    #[arg(long, default_value = yolov8_rs::models::CALIBRATION_CONFIG_PATH)]
+    calibration: String,
+
+    /// 启用 tegrastats 功耗/温度监控, 过热时自动降低推理频率 (Jetson)
+    #[arg(long, default_value_t = false)]
+    tegrastats: bool,
+
+    /// RTSP 只解码关键帧 (I/IDR), 检测频率随 GOP 降到约 1fps, 用于低功耗/多路部署
+    #[arg(long, default_value_t = false)]
+    keyframes_only: bool,
+
+    /// 工业相机 Bayer 原始帧的去马赛克算法 (bilinear 更快, malvar 边缘更锐利)
+    #[arg(long, value_enum, default_value_t = yolov8_rs::input::Demosaic::Malvar)]
+    demosaic: yolov8_rs::input::Demosaic,
+
+    /// 整帧缩放到推理输入的算法 (nearest 最快, bilinear/lanczos 小目标更清晰), 控制面板可切换
+    #[arg(long, value_enum, default_value_t = yolov8_rs::utils::resize::ResizeQuality::Nearest)]
+    resize: yolov8_rs::utils::resize::ResizeQuality,
+
+    /// 切换输入源时保留跟踪轨迹 (默认随缓存一起重置, 同一场景换路时可保留ID)
+    #[arg(long, default_value_t = false)]
+    keep_tracks_on_switch: bool,
+
+    /// 文件名、报表、通知与界面时间的时区: IANA 名称 (如 Asia/Shanghai)、偏移 (+08:00, UTC) 或 local
+    #[arg(long, default_value = "local")]
+    timezone: String,
+
+    /// 鱼眼标定与虚拟视图配置文件 (JSON), 设置后每个虚拟视图作为独立逻辑流检测, 为空不去畸变
+    #[arg(long, default_value = "")]
+    fisheye_config: String,
+
+    /// 区域规则配置文件 (JSON, 地面坐标多边形 + 规则), 需先完成地面标定; 文件不存在时不启用
+    #[arg(long, default_value = yolov8_rs::analytics::ZONE_CONFIG_PATH)]
+    zones: String,
+
+    /// 重叠视野去重配置 (各逻辑流到共同地面的标定 + 重叠区域): 同一地面位置的检测只计数一次; 文件不存在时不启用
+    #[arg(long, default_value = OVERLAP_CONFIG_PATH)]
+    overlap: String,
+
+    /// 区域事件二次复核模型 (如 models/yolov8m.onnx): 裁剪事件目标用大模型重新检测, 误报不告警; 为空不复核
+    #[arg(long, default_value = "")]
+    verify_model: String,
+
+    /// 二次复核置信度阈值
+    #[arg(long, default_value_t = 0.5)]
+    verify_conf: f32,
+
+    /// 遗留物/看护物体移除检测配置文件 (JSON), 文件不存在时使用默认参数, 为空不启用
+    #[arg(long, default_value = "")]
+    left_behind: String,
+
+    /// 摄像头篡改检测配置文件 (JSON, 遮挡/失焦/移位), 文件不存在时使用默认参数, 为空不启用
+    #[arg(long, default_value = "")]
+    tamper: String,
+
+    /// 轨迹快照图库目录 (每条轨迹结束后保存最佳裁剪图与元数据, 生成 index.html; 需要跟踪器), 为空不启用
+    #[arg(long, default_value = "")]
+    gallery: String,
+
+    /// 基准标记字典 ("builtin" 为内置 4×4 字典, 或 OpenCV 导出的 JSON), 相机偏移后按标记重新锚定地面标定, 为空不启用
+    #[arg(long, default_value = "")]
+    markers: String,
+
+    /// 基准标记检测间隔 (秒)
+    #[arg(long, default_value_t = 1.0)]
+    marker_interval: f32,
+
+    /// 将 --markers 字典中的全部标记导出为 PNG 到该目录后退出 (用于打印)
+    #[arg(long, default_value = "")]
+    export_markers: String,
+
+    /// 人群密度模型路径 (CSRNet 等密度图回归 ONNX), 为空不启用
+    #[arg(long, default_value = "")]
+    crowd_model: String,
+
+    /// 分阶段延迟指标输出文件 (Prometheus 文本格式, 每秒覆盖写入), 为空不输出
+    #[arg(long, default_value = "")]
+    metrics_file: String,
+
+    /// 调试 HTTP API 监听地址 (如 127.0.0.1:8090, GET /debug/xbus 查看事件总线订阅者), 为空不启用
+    #[arg(long, default_value = "")]
+    api_addr: String,
+
+    /// API 令牌文件 (viewer/operator/admin 角色), 不存在时不鉴权 (只应监听本机地址)
+    #[arg(long, default_value = API_TOKENS_PATH)]
+    api_tokens: String,
+
+    /// 事件库 (SQLite, 审计表记录面板/API/配置热加载的控制操作, 另有去重访客每小时统计), 为空时审计记录只保留在内存中
+    #[arg(long, default_value = EVENTS_DB_PATH)]
+    events_db: String,
+
+    /// RTSP 传输安全配置 (rtsps:// 证书校验/CA/客户端证书, 凭据文件位置), 不存在时使用默认值
+    #[arg(long, default_value = RTSP_SECURITY_CONFIG_PATH)]
+    rtsp_security: String,
+
+    /// 服务模式: 启动看门狗 (解码/检测/渲染卡死检测, 卡死时重启解码器或以区分原因的退出码退出),
+    /// 向 systemd (sd_notify) 或 Windows 服务控制管理器报告状态
+    #[arg(long, default_value_t = false)]
+    service: bool,
+
+    /// 看门狗配置文件 (各组件无心跳的超时秒数, 解码器最多重启次数), 不存在时使用默认值
+    #[arg(long, default_value = WATCHDOG_CONFIG_PATH)]
+    watchdog_config: String,
+
+    /// 跟踪状态日志 (如 tracks.json; 每 2 秒保存活跃轨迹, 启动时恢复, 短时间重启后轨迹ID延续), 为空不保存
+    #[arg(long, default_value = "")]
+    track_journal: String,
+
+    /// 跟踪状态日志的有效期 (秒), 重启间隔超过该值时不恢复
+    #[arg(long, default_value_t = 60)]
+    track_journal_max_age: u64,
+
+    /// 会话状态文件 (模型/阈值/缩放/输入源等, 运行中自动保存, 启动时恢复; 命令行显式指定的参数优先), 为空不保存
+    #[arg(long, default_value = SESSION_STATE_PATH)]
+    session: String,
+
+    /// 配置档案文件 (成组的模型/分辨率/阈值/类别/区域/跟踪参数, 可按时段自动切换), 不存在时无档案
+    #[arg(long, default_value = PROFILES_CONFIG_PATH)]
+    profiles: String,
+
+    /// 布防计划文件 (按逻辑流的布防时段, 撤防期间不检测; 面板/API 可手动覆盖), 不存在时始终布防
+    #[arg(long, default_value = SCHEDULE_CONFIG_PATH)]
+    schedule: String,
+
+    /// 存储保留策略文件 (快照/录像等目录的最长保留时间与容量上限, 超出时从最旧开始删除), 不存在时不清理
+    #[arg(long, default_value = RETENTION_CONFIG_PATH)]
+    retention: String,
+
+    /// 内存防护配置文件 (RSS/帧池/纹理/ORT 会话统计, 增长超过阈值时清缓存/重建纹理), 不存在时使用默认值, 为空不监控
+    #[arg(long, default_value = MEMORY_GUARD_CONFIG_PATH)]
+    memory_guard: String,
+
+    /// 片段导出配置文件 (录像分段/检测记录/输出目录; 启用后记录检测结果, 经 API 按时间范围导出 MP4), 不存在时不启用
+    #[arg(long, default_value = CLIPS_CONFIG_PATH)]
+    clips: String,
+
+    /// 告警通知配置文件 (Webhook/Telegram/邮件通道, 限流与免打扰时段), 不存在时不通知
+    #[arg(long, default_value = NOTIFIER_CONFIG_PATH)]
+    notifier: String,
+
+    /// 共享内存输出文件 (最新标注帧 + 检测结果, 顺序锁保护, 供外部渲染程序映射), 为空不输出
+    #[arg(long, default_value = "")]
+    shm_output: String,
+
+    /// 共享内存输出的逻辑流编号 (0 为原始画面, 鱼眼虚拟视图从 1 开始)
+    #[arg(long, default_value_t = 0)]
+    shm_output_view: u32,
+
+    /// 共享内存输出的结果编码 (json 或 compact: 量化坐标的紧凑二进制, 见 proto/detections.proto)
+    #[arg(long, value_enum, default_value_t = ResultsFormat::Json)]
+    shm_output_format: ResultsFormat,
+
+    /// 输出 ONVIF 分析元数据 (Profile M), 经调试 API 的 GET /onvif/metadata 拉取 (需要 --api-addr)
+    #[arg(long, default_value_t = false)]
+    onvif_metadata: bool,
+
+    /// 转推配置文件 (标注画面编码为 H.264, 推 RTSP 到外部服务器和/或输出 HLS), 不存在时不转推
+    #[arg(long, default_value = RESTREAM_CONFIG_PATH)]
+    restream: String,
+
+    /// 界面语言 (zh-CN/en-US), 为空时沿用会话状态 (默认中文)
+    #[arg(long, default_value = "")]
+    lang: String,
+
+    /// 检测统计报告路径 (不含扩展名, 生成 .json 与 .html, 每分钟更新), 为空不统计
+    #[arg(long, default_value = "")]
+    report: String,
+
+    /// 去重访客计数配置文件 (JSON, 按 ReID 特征在周期内去重, 每小时追加 CSV; 需要 ReID 特征),
+    /// 文件不存在时使用默认参数, 为空不启用
+    #[arg(long, default_value = "")]
+    visitors: String,
+
+    /// 标注模式导出目录 (images/ + labels/ + annotations.json, 类别表为 classes.txt)
+    #[arg(long, default_value = DATASET_DIR)]
+    dataset_dir: String,
+
+    /// 不监视配置文件 (默认保存档案/区域/跟踪参数/告警通知配置后自动热加载)
+    #[arg(long, default_value_t = false)]
+    no_hot_reload: bool,
+
+    /// 忽略已保存的会话状态, 以命令行参数启动 (运行中仍会保存新的会话状态)
+    #[arg(long, default_value_t = false)]
+    fresh: bool,
+
+    /// RTSP 使用 NVDEC 硬件解码 + CUDA 端到端检测 (需以 --features cuda 编译)
+    #[cfg(feature = "cuda")]
+    #[arg(long, default_value_t = false)]
+    nvdec: bool,
+
+    /// NDI 源名称 (发布标注画面供 vMix/OBS 接入, 需以 --features ndi 编译并安装 NDI 运行库), 为空不输出
+    #[cfg(feature = "ndi")]
+    #[arg(long, default_value = "")]
+    ndi: String,
+
+    /// NDI 输出的逻辑流编号
+    #[cfg(feature = "ndi")]
+    #[arg(long, default_value_t = 0)]
+    ndi_view: u32,
+
+    /// 另外发布未标注的干净画面 (源名称加 " Clean")
+    #[cfg(feature = "ndi")]
+    #[arg(long, default_value_t = false)]
+    ndi_clean: bool,
+
+    /// 另外发布透明背景的检测框叠加层 (源名称加 " Overlay", 导播端键控叠加)
+    #[cfg(feature = "ndi")]
+    #[arg(long, default_value_t = false)]
+    ndi_overlay: bool,
+
+    /// 虚拟摄像头配置文件 (标注画面输出到 v4l2loopback/softcam, 需以 --features vcam 编译), 不存在时不输出
+    #[cfg(feature = "vcam")]
+    #[arg(long, default_value = VCAM_CONFIG_PATH)]
+    virtual_camera: String,
+}
+
+/// 导出字典中的全部标记 (每格 40 像素, 含白色静区)
+fn export_markers(spec: &str, dir: &str) {
+    let spec = if spec.is_empty() { "builtin" } else { spec };
+    let dictionary = match MarkerDictionary::from_spec(spec) {
+        Ok(dictionary) => dictionary,
+        Err(e) => return eprintln!("❌ 基准标记字典加载失败: {:#}", e),
+    };
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        return eprintln!("❌ 创建目录 {} 失败: {}", dir, e);
+    }
+    for id in 0..dictionary.codes.len() as u32 {
+        let path = format!("{}/{}_{}.png", dir, dictionary.name, id);
+        let saved = yolov8_rs::analytics::markers::render_marker(&dictionary, id, 40)
+            .map(|image| image.save(&path));
+        if let Some(Err(e)) = saved {
+            return eprintln!("❌ 保存 {} 失败: {}", path, e);
+        }
+    }
+    println!("🎯 已导出 {} 个标记到 {}", dictionary.codes.len(), dir);
+}
+
+fn window_conf() -> Conf {
+    // 窗口尺寸须在创建窗口前确定, 此处单独读取会话状态
+    let (window_width, window_height) = Args::try_parse()
+        .ok()
+        .filter(|args| !args.session.is_empty() && !args.fresh)
+        .and_then(|args| SessionState::load(&args.session))
+        .and_then(|state| state.window_size)
+        .unwrap_or((1280, 720));
+    Conf {
+        window_title: "数字卫兵 - Digital Sentinel".to_owned(),
+        window_width,
+        window_height,
+        window_resizable: true,
+        ..Default::default()
+    }
+}
+
+#[macroquad::main(window_conf)]
+async fn main() {
+    let matches = Args::command().get_matches();
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    if !args.export_markers.is_empty() {
+        export_markers(&args.markers, &args.export_markers);
+        return;
+    }
+    // 线程配置须在首次使用 rayon 与创建 ORT 会话之前生效
+    RuntimeConfig::load(&args.runtime_config).apply();
+    // 校准配置须在加载检测模型之前生效
+    if let Some(config) = CalibrationConfig::load(&args.calibration) {
+        config.apply();
+    }
+    // 鱼眼配置须在创建渲染器 (检测线程数/默认视图) 与启动解码器之前生效
+    if !args.fisheye_config.is_empty() {
+        if let Some(config) = FisheyeConfig::load(&args.fisheye_config) {
+            config.apply();
+        }
+    }
+    // 加载中文字体
+    let font_data = match std::fs::read("assets/font/msyh.ttc") {
+        Ok(data) => {
+            println!("✅ 中文字体加载成功: 微软雅黑");
+            Some(data)
+        }
+        Err(e) => {
+            eprintln!("⚠️  中文字体加载失败: {}, 将使用默认字体", e);
+            None
+        }
+    };
+
+    // 设置 egui 中文字体
+    if let Some(font_bytes) = font_data {
+        egui_macroquad::cfg(|ctx| {
+            let mut fonts = egui::FontDefinitions::default();
+            fonts.font_data.insert(
+                "msyh".to_owned(),
+                std::sync::Arc::new(egui::FontData::from_owned(font_bytes)),
+            );
+
+            // 将中文字体设置为优先字体
+            fonts
+                .families
+                .entry(egui::FontFamily::Proportional)
+                .or_default()
+                .insert(0, "msyh".to_owned());
+
+            fonts
+                .families
+                .entry(egui::FontFamily::Monospace)
+                .or_default()
+                .push("msyh".to_owned());
+
+            ctx.set_fonts(fonts);
+            ctx.set_pixels_per_point(ctx.zoom_factor());
+        });
+    }
+
+    // 构建模型路径
+    let fastest_variant = if args.model == "fastest" || args.model == "fastestv2" {
+        "yolo-fastestv2-opt"
+    } else {
+        "yolo-fastest-1.1"
+    };
+
+    let detect_model = if args.model.starts_with(MOCK_MODEL_PREFIX) {
+        args.model.clone()
+    } else if args.model.starts_with("yolox") {
+        format!("models/{}.onnx", args.model)
+    } else if args.model.starts_with("v10") {
+        let variant = args.model.trim_start_matches("v10");
+        format!("models/yolov10{}.onnx", variant)
+    } else if args.model.starts_with("v11") {
+        let variant = args.model.trim_start_matches("v11");
+        format!("models/yolov11{}.onnx", variant)
+    } else if args.model == "fastest" || args.model.starts_with("fastest") {
+        format!("models/{}.onnx", fastest_variant)
+    } else if args.model.starts_with("nanodet") {
+        if args.model == "nanodet" || args.model == "nanodet-m" {
+            "models/nanodet-m.onnx".to_string()
+        } else if args.model == "nanodet-plus" {
+            "models/nanodet-plus-m_320.onnx".to_string()
+        } else if args.model == "nanodet-plus-416" {
+            "models/nanodet-plus-m_416.onnx".to_string()
+        } else if args.model == "nanodet-plus-1.5x" {
+            "models/nanodet-plus-m-1.5x_320.onnx".to_string()
+        } else if args.model == "nanodet-plus-1.5x-416" {
+            "models/nanodet-plus-m-1.5x_416.onnx".to_string()
+        } else {
+            format!("models/{}.onnx", args.model)
+        }
+    } else if args.model.starts_with("v5") {
+        let variant = args.model.trim_start_matches("v5");
+        format!("models/yolov5{}.onnx", variant)
+    } else if args.model.ends_with("-int8") {
+        let base = args.model.trim_end_matches("-int8");
+        format!("models/yolov8{}_int8.onnx", base)
+    } else {
+        if args.model.starts_with("yolov8") {
+            format!("models/{}.onnx", args.model)
+        } else {
+            format!("models/yolov8{}.onnx", args.model)
+        }
+    };
+
+    println!("🚀 数字卫兵系统启动");
+    println!("📦 默认检测模型: {}", detect_model);
+    println!("🎯 默认跟踪算法: {}", args.tracker);
+    println!(
+        "🧍 默认姿态估计: {}",
+        if args.pose { "启用" } else { "禁用" }
+    );
+    if !args.pose_model.is_empty() {
+        println!("🧍 两阶段姿态模型: {}", args.pose_model);
+    }
+    println!("\n💡 请在UI中配置输入源,检测模块将在启动视频流时自动启动");
+    println!();
+
+    // 不再自动启动解码器和检测器,等待用户在UI中配置
+    // 解码器和检测器将通过 switch_decoder_source() 函数启动
+
+    // 不在这里启动检测线程,而是在首次启动解码器时启动
+    // 这样可以避免不必要的资源占用
+
+    // 提取干净的模型名称
+    let detect_model_name = detect_model.replace("models/", "").replace(".onnx", "");
+
+    let mut renderer = Renderer::new(
+        detect_model_name,
+        args.pose_model.clone(),
+        args.tracker.clone(),
+    );
+    if !args.depth_model.is_empty() {
+        println!("📏 深度估计模型: {}", args.depth_model);
+        renderer.set_depth_model(args.depth_model.clone());
+    }
+    if !args.attribute_model.is_empty() {
+        println!("🦺 行人属性模型: {}", args.attribute_model);
+        renderer.set_attribute_model(args.attribute_model.clone());
+    }
+    if !args.vehicle_model.is_empty() {
+        println!("🚗 车辆属性模型: {}", args.vehicle_model);
+        renderer.set_vehicle_model(args.vehicle_model.clone());
+    }
+    if !args.crowd_model.is_empty() {
+        println!("👥 人群密度模型: {}", args.crowd_model);
+        renderer.set_crowd_model(args.crowd_model.clone());
+    }
+    if !args.clip_model.is_empty() {
+        println!("🔎 CLIP模型: {}", args.clip_model);
+        renderer.set_clip_model(args.clip_model.clone());
+    }
+    if args.global_id {
+        println!("🌐 跨摄像头全局ID已启用");
+        renderer.set_global_id_manager(Arc::new(Mutex::new(GlobalIdManager::new(
+            GlobalIdConfig::default(),
+        ))));
+    }
+    if let Some(core) = args.dla_core {
+        println!("🧠 检测模型部署到 DLA 核心 {}", core);
+        renderer.set_dla_core(core);
+    }
+    if let Some(config) = GpuPlacementConfig::load(&args.gpu_placement) {
+        gpu_placement::install(config);
+    }
+    if let Some(config) = BoxFilterConfig::load(&args.box_filters) {
+        box_filter::install(config);
+    }
+    if let Some(config) = BorderConfig::load(&args.border_policy) {
+        border::install(config);
+    }
+    if let Some(config) = TileConfig::load(&args.tile_inference) {
+        tile_cache::install(config);
+    }
+    if let Some(config) = WorldConfig::load(&args.open_vocabulary) {
+        world::install(config);
+    }
+    if args.tegrastats {
+        match JetsonMonitor::spawn(1000, ThermalPolicy::default()) {
+            Ok(monitor) => renderer.set_jetson_monitor(monitor),
+            Err(e) => eprintln!("⚠️ tegrastats 启动失败 (非 Jetson 设备?): {}", e),
+        }
+    }
+    RtspSecurity::load(&args.rtsp_security).install();
+    if !args.track_journal.is_empty() {
+        println!("📒 跟踪状态日志: {}", args.track_journal);
+        renderer.set_track_journal(TrackJournal::new(
+            &args.track_journal,
+            JOURNAL_INTERVAL,
+            Duration::from_secs(args.track_journal_max_age),
+        ));
+    }
+    if !args.events_db.is_empty() {
+        if let Err(e) = audit::init(&args.events_db) {
+            eprintln!("⚠️ 事件库打开失败 ({}): {}", args.events_db, e);
+        }
+    }
+    if args.keyframes_only {
+        println!("🔑 低功耗模式: RTSP 仅解码关键帧");
+        yolov8_rs::input::decoder::set_keyframes_only(true);
+    }
+    yolov8_rs::input::set_demosaic(args.demosaic);
+    yolov8_rs::utils::resize::set_default_quality(args.resize);
+    yolov8_rs::detection::detector::set_keep_tracks_on_switch(args.keep_tracks_on_switch);
+    match yolov8_rs::utils::clock::DisplayZone::parse(&args.timezone) {
+        Ok(yolov8_rs::utils::clock::DisplayZone::Local) => {}
+        Ok(zone) => {
+            println!("🕒 显示时区: {}", zone);
+            yolov8_rs::utils::clock::set_display_zone(zone);
+        }
+        Err(e) => eprintln!("⚠️ {}, 使用系统本地时区", e),
+    }
+    if !args.metrics_file.is_empty() {
+        renderer.set_metrics_file(args.metrics_file.clone());
+    }
+    if !args.api_addr.is_empty() {
+        let auth = ApiAuth::load(&args.api_tokens);
+        if let Err(e) = yolov8_rs::api::spawn(args.api_addr.as_str(), auth) {
+            eprintln!("⚠️ 调试 API 启动失败 ({}): {}", args.api_addr, e);
+        }
+    }
+    #[cfg(feature = "cuda")]
+    if args.nvdec {
+        println!("🚀 NVDEC + CUDA 端到端检测已启用");
+        yolov8_rs::input::decoder::DecoderPreference::set_prefer_nvdec(true);
+    }
+
+    // 区域规则引擎 (订阅检测结果, 订阅须在主循环期间保持); 配置档案可切换区域时也须启动
+    let profiles = ProfileConfig::load(&args.profiles);
+    let zone_config = ZoneConfig::load(&args.zones);
+    if let Some(config) = OverlapConfig::load(&args.overlap) {
+        overlap::install(config);
+    }
+    renderer.set_zones(zone_config.zones.clone());
+    // 热加载时区域文件可能在运行中才添加区域, 区域引擎须先启动
+    let zones_switchable =
+        !args.no_hot_reload || profiles.profiles.iter().any(|p| p.zones.is_some());
+    let _zone_sub = (!zone_config.zones.is_empty() || zones_switchable).then(|| {
+        let mut engine = ZoneEngine::new(zone_config);
+        if !args.verify_model.is_empty() {
+            let config = VerifyConfig {
+                confidence: args.verify_conf,
+                ..VerifyConfig::new(&args.verify_model)
+            };
+            match EventVerifier::start(config) {
+                Ok(verifier) => engine.set_verifier(verifier),
+                Err(e) => eprintln!("❌ 二次复核模型加载失败: {}, 事件不复核", e),
+            }
+        }
+        engine.start()
+    });
+    renderer.set_profiles(profiles, args.profiles.clone());
+
+    // 布防调度器 (每个逻辑流独立求值, 检测线程启动时读取当前状态)
+    let views: Vec<u32> = match fisheye_config() {
+        Some(fisheye) => (1..=fisheye.views.len() as u32).collect(),
+        None => vec![0],
+    };
+    Scheduler::new(ScheduleConfig::load(&args.schedule), &views).start();
+
+    // 遗留物/移除检测 (独立线程, 订阅须在主循环期间保持)
+    let _left_behind = (!args.left_behind.is_empty())
+        .then(|| LeftBehindMonitor::start(LeftBehindConfig::load(&args.left_behind)));
+
+    // 存储保留策略 (后台线程定期清理, 占用经 API 与指标文件输出)
+    if let Some(config) = RetentionConfig::load(&args.retention) {
+        yolov8_rs::retention::start(config);
+    }
+
+    // 内存占用统计与泄漏防护 (后台线程定期采样, 占用经 API 与指标文件输出)
+    if !args.memory_guard.is_empty() {
+        yolov8_rs::memory_guard::start(MemoryGuardConfig::load(&args.memory_guard));
+    }
+
+    // 片段导出 (检测记录订阅须在主循环期间保持, 导出任务经 API 提交)
+    let _journal = ClipConfig::load(&args.clips).map(yolov8_rs::clips::start);
+
+    // 摄像头篡改检测 (独立线程, 订阅须在主循环期间保持)
+    let _tamper =
+        (!args.tamper.is_empty()).then(|| TamperMonitor::start(TamperConfig::load(&args.tamper)));
+
+    // 轨迹最佳快照图库 (独立线程, 订阅须在主循环期间保持)
+    let _gallery =
+        (!args.gallery.is_empty()).then(|| BestShotGallery::start(args.gallery.clone().into()));
+
+    // 基准标记锚定 (独立线程, 订阅须在主循环期间保持)
+    let _marker_anchor = (!args.markers.is_empty())
+        .then(|| match MarkerDictionary::from_spec(&args.markers) {
+            Ok(dictionary) => Some(MarkerAnchor::start(
+                dictionary,
+                GroundCalibration::load(GROUND_CALIBRATION_PATH),
+                args.marker_interval,
+            )),
+            Err(e) => {
+                eprintln!("⚠️ 基准标记字典加载失败: {:#}", e);
+                None
+            }
+        })
+        .flatten();
+
+    // 告警通知 (订阅区域/遗留物事件, 订阅须在主循环期间保持); 配置热加载后按新配置重启
+    let notifier = Arc::new(Mutex::new(Notifier::start(NotifierConfig::load(
+        &args.notifier,
+    ))));
+    let _notifier_reload = {
+        let notifier = Arc::clone(&notifier);
+        xbus::subscribe::<ConfigReloaded, _>(move |event| {
+            if let ConfigUpdate::Notifier(config) = &event.update {
+                let mut notifier = notifier.lock().unwrap();
+                // 先停止旧通知再启动, 避免同一事件被发送两次
+                *notifier = None;
+                *notifier = Notifier::start(config.clone());
+            }
+        })
+    };
+
+    // 配置热加载 (监视器须在主循环期间保持)
+    let _config_watcher = (!args.no_hot_reload)
+        .then(|| {
+            ConfigWatcher::start(&[
+                (ConfigKind::Profiles, args.profiles.as_str()),
+                (ConfigKind::Zones, args.zones.as_str()),
+                (ConfigKind::Tracker, TRACKER_CONFIG_PATH),
+                (ConfigKind::Notifier, args.notifier.as_str()),
+            ])
+            .map_err(|e| eprintln!("⚠️ 配置热加载启动失败: {:#}", e))
+            .ok()
+        })
+        .flatten();
+
+    // 共享内存输出 (订阅帧与检测结果, 订阅须在主循环期间保持)
+    let _shm_output = (!args.shm_output.is_empty())
+        .then(|| {
+            ShmPublisher::start(
+                std::path::Path::new(&args.shm_output),
+                args.shm_output_view,
+                args.shm_output_format,
+            )
+            .map_err(|e| eprintln!("⚠️ 共享内存输出启动失败: {:#}", e))
+            .ok()
+        })
+        .flatten();
+
+    // NDI 输出 (订阅帧与检测结果, 订阅须在主循环期间保持)
+    #[cfg(feature = "ndi")]
+    let _ndi = (!args.ndi.is_empty())
+        .then(|| {
+            NdiPublisher::start(NdiOptions {
+                name: args.ndi.clone(),
+                view: args.ndi_view,
+                clean: args.ndi_clean,
+                overlay: args.ndi_overlay,
+            })
+            .map_err(|e| eprintln!("⚠️ NDI 输出启动失败: {:#}", e))
+            .ok()
+        })
+        .flatten();
+
+    // 虚拟摄像头 (订阅帧与检测结果, 订阅须在主循环期间保持)
+    #[cfg(feature = "vcam")]
+    let _vcam = VcamConfig::load(&args.virtual_camera).and_then(|config| {
+        VcamOutput::start(config)
+            .map_err(|e| eprintln!("⚠️ 虚拟摄像头启动失败: {:#}", e))
+            .ok()
+    });
+
+    // ONVIF 分析元数据 (订阅帧与检测结果, 订阅须在主循环期间保持)
+    if args.onvif_metadata && args.api_addr.is_empty() {
+        eprintln!("⚠️ ONVIF 元数据经调试 API 拉取, 未指定 --api-addr 时无法读取");
+    }
+    let _onvif = args
+        .onvif_metadata
+        .then(|| OnvifPublisher::start(DEFAULT_METADATA_FRAMES));
+
+    // 标注视频转推 (订阅帧与检测结果, 订阅须在主循环期间保持)
+    let _restream = RestreamConfig::load(&args.restream)
+        .map(|config| RestreamServer::start(config, ApiAuth::load(&args.api_tokens)));
+
+    // 检测统计报告 (订阅检测结果, 订阅须在主循环期间保持)
+    let _report =
+        (!args.report.is_empty()).then(|| RunReport::new("实时视频").start(args.report.as_str()));
+
+    // 去重访客计数 (订阅检测结果, 订阅须在主循环期间保持)
+    let _visitors = (!args.visitors.is_empty())
+        .then(|| VisitorCounter::start(VisitorConfig::load(&args.visitors), &args.events_db));
+
+    // 保存检测器启动参数,供后续使用
+    renderer.set_detector_params(
+        detect_model.clone(),
+        INF_SIZE,
+        args.tracker.clone(),
+        args.pose,
+    );
+
+    renderer.set_dataset_dir(&args.dataset_dir);
+
+    // 恢复上次的会话 (命令行显式指定的模型/跟踪器/姿态开关优先)
+    if !args.session.is_empty() {
+        let session = (!args.fresh)
+            .then(|| SessionState::load(&args.session))
+            .flatten();
+        if let Some(mut session) = session {
+            let explicit = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+            if explicit("model") {
+                session.model = None;
+            }
+            if explicit("tracker") {
+                session.tracker = None;
+            }
+            if explicit("pose") {
+                session.pose_enabled = args.pose;
+            }
+            renderer.restore_session(&session);
+        }
+        renderer.set_session_file(args.session.clone());
+    }
+    if !args.lang.is_empty() {
+        match Language::from_code(&args.lang) {
+            Some(language) => set_language(language),
+            None => eprintln!("⚠️ 不支持的界面语言: {}, 可选 zh-CN/en-US", args.lang),
+        }
+    }
+
+    let _watchdog = args
+        .service
+        .then(|| Watchdog::start(WatchdogConfig::load(&args.watchdog_config)));
+
+    println!("✅ 系统就绪,等待配置输入源...\n");
+
+    // 主循环
+    loop {
+        if args.service {
+            watchdog::beat(Component::Render);
+            if watchdog::stop_requested() {
+                watchdog::shutdown();
+            }
+        }
+        renderer.update();
+        renderer.handle_input();
+        renderer.draw();
+        renderer.draw_egui();
+
+        next_frame().await;
+    }
+}
@@ -0,0 +1,284 @@
+//! 地面标定 (Ground-plane calibration)
+//!
+//! 用户在画面上标记 4 个以上地面点并填写其真实坐标 (米), 最小二乘求解图像 → 地面的
+//! 单应矩阵. 检测框底边中点 (脚点) 经单应映射得到平面图坐标, 供小地图、速度估计与
+//! 区域规则中的真实距离使用
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use super::markers::Marker;
+use crate::detection::types::BBox;
+
+/// 默认标定文件路径
+pub const GROUND_CALIBRATION_PATH: &str = "ground_calibration.json";
+
+/// 标定点: 图像像素坐标 ↔ 地面坐标 (米)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GroundPoint {
+    pub image: [f32; 2],
+    pub world: [f32; 2],
+}
+
+/// 图像平面 → 地面平面的单应矩阵
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Homography {
+    m: [[f64; 3]; 3],
+    // 标定点处齐次坐标 w 的符号, 符号相反的点位于地平线之上, 无法映射到地面
+    sign: f64,
+}
+
+impl Homography {
+    /// 由对应点最小二乘求解 (至少4个点, 先做 Hartley 归一化), 点共线等退化情况返回 None
+    pub fn from_points(points: &[GroundPoint]) -> Option<Self> {
+        if points.len() < 4 {
+            return None;
+        }
+        let src: Vec<[f64; 2]> = points.iter().map(|p| p.image.map(f64::from)).collect();
+        let dst: Vec<[f64; 2]> = points.iter().map(|p| p.world.map(f64::from)).collect();
+        let t_src = normalization(&src)?;
+        let t_dst = normalization(&dst)?;
+
+        // 固定 h33 = 1, 每个点贡献两行方程, 组成 8 元正规方程
+        let mut ata = [[0.0f64; 8]; 8];
+        let mut atb = [0.0f64; 8];
+        for (s, d) in src.iter().zip(&dst) {
+            let (x, y) = apply(&t_src, s[0], s[1]);
+            let (u, v) = apply(&t_dst, d[0], d[1]);
+            let rows = [
+                ([x, y, 1.0, 0.0, 0.0, 0.0, -x * u, -y * u], u),
+                ([0.0, 0.0, 0.0, x, y, 1.0, -x * v, -y * v], v),
+            ];
+            for (a, b) in rows {
+                for i in 0..8 {
+                    atb[i] += a[i] * b;
+                    for j in 0..8 {
+                        ata[i][j] += a[i] * a[j];
+                    }
+                }
+            }
+        }
+        let h = solve8(ata, atb)?;
+        let normalized = [[h[0], h[1], h[2]], [h[3], h[4], h[5]], [h[6], h[7], 1.0]];
+
+        // 反归一化: H = T_dst⁻¹ · Hn · T_src
+        let m = mat_mul(&mat_mul(&invert_similarity(&t_dst), &normalized), &t_src);
+        if m[2][2].abs() < 1e-12 {
+            return None;
+        }
+        let m = m.map(|row| row.map(|v| v / m[2][2]));
+        let p = points[0].image;
+        let w = m[2][0] * p[0] as f64 + m[2][1] * p[1] as f64 + m[2][2];
+        Some(Self {
+            m,
+            sign: w.signum(),
+        })
+    }
+
+    /// 图像坐标 → 地面坐标 (米), 地平线之上的点返回 None
+    pub fn project(&self, x: f32, y: f32) -> Option<(f32, f32)> {
+        let (x, y) = (x as f64, y as f64);
+        let m = &self.m;
+        let w = m[2][0] * x + m[2][1] * y + m[2][2];
+        if w * self.sign <= 1e-12 {
+            return None;
+        }
+        Some((
+            ((m[0][0] * x + m[0][1] * y + m[0][2]) / w) as f32,
+            ((m[1][0] * x + m[1][1] * y + m[1][2]) / w) as f32,
+        ))
+    }
+
+    /// 检测框脚点 (底边中点) 的地面坐标
+    pub fn project_box(&self, bbox: &BBox) -> Option<(f32, f32)> {
+        self.project((bbox.x1 + bbox.x2) / 2.0, bbox.y2)
+    }
+
+    /// 复合映射: 先经 `first` 再经自身 (如 当前画面 → 标定时画面 → 地面)
+    pub fn compose(&self, first: &Homography) -> Self {
+        Self {
+            m: mat_mul(&self.m, &first.m),
+            sign: self.sign * first.sign,
+        }
+    }
+
+    /// 标定点的均方根重投影误差 (米)
+    pub fn rms_error(&self, points: &[GroundPoint]) -> f32 {
+        if points.is_empty() {
+            return 0.0;
+        }
+        let sum: f32 = points
+            .iter()
+            .map(|p| match self.project(p.image[0], p.image[1]) {
+                Some((x, y)) => (x - p.world[0]).powi(2) + (y - p.world[1]).powi(2),
+                None => f32::INFINITY,
+            })
+            .sum();
+        (sum / points.len() as f32).sqrt()
+    }
+}
+
+/// 标定配置 (ground_calibration.json)
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GroundCalibration {
+    pub points: Vec<GroundPoint>,
+    // 标定时画面中的基准标记 (锚定参考), 见 markers 模块
+    pub markers: Vec<Marker>,
+}
+
+impl GroundCalibration {
+    /// 从文件加载配置, 文件不存在时为空标定
+    pub fn load(path: &str) -> Self {
+        match fs::read_to_string(path) {
+            Ok(json) => match serde_json::from_str::<Self>(&json) {
+                Ok(config) => {
+                    println!(
+                        "✅ 地面标定已从 {} 加载 ({}个点)",
+                        path,
+                        config.points.len()
+                    );
+                    config
+                }
+                Err(e) => {
+                    eprintln!("⚠️  地面标定解析失败: {}, 使用空标定", e);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// 保存配置到文件
+    pub fn save(&self, path: &str) {
+        match serde_json::to_string_pretty(self) {
+            Ok(json) => {
+                if let Err(e) = fs::write(path, json) {
+                    eprintln!("❌ 保存地面标定失败: {}", e);
+                } else {
+                    println!("💾 地面标定已保存到 {}", path);
+                }
+            }
+            Err(e) => eprintln!("❌ 序列化地面标定失败: {}", e),
+        }
+    }
+
+    /// 当前标定点求得的单应矩阵
+    pub fn homography(&self) -> Option<Homography> {
+        Homography::from_points(&self.points)
+    }
+}
+
+/// Hartley 归一化: 平移到质心, 缩放使平均距离为 √2
+fn normalization(points: &[[f64; 2]]) -> Option<[[f64; 3]; 3]> {
+    let n = points.len() as f64;
+    let cx = points.iter().map(|p| p[0]).sum::<f64>() / n;
+    let cy = points.iter().map(|p| p[1]).sum::<f64>() / n;
+    let mean_dist = points
+        .iter()
+        .map(|p| ((p[0] - cx).powi(2) + (p[1] - cy).powi(2)).sqrt())
+        .sum::<f64>()
+        / n;
+    if mean_dist < 1e-9 {
+        return None;
+    }
+    let s = std::f64::consts::SQRT_2 / mean_dist;
+    Some([[s, 0.0, -s * cx], [0.0, s, -s * cy], [0.0, 0.0, 1.0]])
+}
+
+fn apply(t: &[[f64; 3]; 3], x: f64, y: f64) -> (f64, f64) {
+    (t[0][0] * x + t[0][2], t[1][1] * y + t[1][2])
+}
+
+/// 归一化矩阵 (各向同性缩放 + 平移) 的逆
+fn invert_similarity(t: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let s = t[0][0];
+    [
+        [1.0 / s, 0.0, -t[0][2] / s],
+        [0.0, 1.0 / s, -t[1][2] / s],
+        [0.0, 0.0, 1.0],
+    ]
+}
+
+fn mat_mul(a: &[[f64; 3]; 3], b: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let mut out = [[0.0; 3]; 3];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, v) in row.iter_mut().enumerate() {
+            *v = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    out
+}
+
+/// 列主元高斯消元求解 8×8 线性方程组, 奇异时返回 None
+fn solve8(mut a: [[f64; 8]; 8], mut b: [f64; 8]) -> Option<[f64; 8]> {
+    for col in 0..8 {
+        let pivot = (col..8).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        for row in col + 1..8 {
+            let factor = a[row][col] / a[col][col];
+            let (upper, lower) = a.split_at_mut(row);
+            for (v, p) in lower[0].iter_mut().zip(&upper[col]).skip(col) {
+                *v -= factor * p;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+    let mut x = [0.0; 8];
+    for row in (0..8).rev() {
+        let tail: f64 = (row + 1..8).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - tail) / a[row][row];
+    }
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 透视畸变的已知映射: 由对应点恢复单应矩阵并正确映射新点
+    #[test]
+    fn test_homography_recovers_perspective() {
+        // 地面 (米) → 图像 (像素) 的透视投影, 用其生成标定点
+        let to_image = |x: f32, y: f32| {
+            let w = 0.002 * y + 1.0;
+            ((400.0 + 60.0 * x) / w, (300.0 + 40.0 * y) / w)
+        };
+        let points: Vec<GroundPoint> =
+            [(0.0, 0.0), (5.0, 0.0), (5.0, 10.0), (0.0, 10.0), (2.0, 4.0)]
+                .iter()
+                .map(|&(x, y)| {
+                    let (u, v) = to_image(x, y);
+                    GroundPoint {
+                        image: [u, v],
+                        world: [x, y],
+                    }
+                })
+                .collect();
+        let h = Homography::from_points(&points).unwrap();
+        assert!(h.rms_error(&points) < 1e-3);
+
+        let (u, v) = to_image(3.5, 7.0);
+        let (x, y) = h.project(u, v).unwrap();
+        assert!(
+            (x - 3.5).abs() < 1e-3 && (y - 7.0).abs() < 1e-3,
+            "({}, {})",
+            x,
+            y
+        );
+
+        // 少于4点或共线时无法求解
+        assert!(Homography::from_points(&points[..3]).is_none());
+        let collinear: Vec<GroundPoint> = (0..4)
+            .map(|i| GroundPoint {
+                image: [i as f32 * 10.0, i as f32 * 10.0],
+                world: [i as f32, 0.0],
+            })
+            .collect();
+        assert!(Homography::from_points(&collinear).is_none());
+    }
+}
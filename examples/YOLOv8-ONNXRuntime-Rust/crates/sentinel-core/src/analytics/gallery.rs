@@ -0,0 +1,566 @@
+//! 轨迹最佳快照图库 (Best-shot gallery)
+//!
+//! 每条轨迹在跟踪期间按固定间隔采样目标裁剪图并打分 (尺寸、清晰度、正面程度), 轨迹结束
+//! (一段时间未再出现) 后保存得分最高的裁剪图与元数据, 并重写图库页面 `index.html`,
+//! 操作员不必回放录像即可查看经过的人员. 最近的条目经调试 API (`GET /api/gallery`) 查询.
+//!
+//! 需要启用跟踪器 (class_id 为轨迹ID); 正面程度取自姿态关键点, 未启用姿态时按中性分计.
+//!
+//! 目录结构: `<dir>/view<N>/<结束时间>_track<ID>.jpg` 与同名 `.json`
+
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use image::RgbImage;
+use serde::{Deserialize, Serialize};
+
+use crate::analytics::report::escape;
+use crate::detection::detector::DetectionResult;
+use crate::detection::types::{BBox, DecodedFrame, PoseKeypoints};
+use crate::models::PreprocessSpec;
+use crate::utils::clock;
+use crate::xbus::{self, Subscription};
+
+/// 默认图库目录
+pub const GALLERY_DIR: &str = "gallery";
+
+/// 同一轨迹两次采样的最小间隔
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// 轨迹超过该时长未出现视为结束
+const TRACK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 采样次数少于该值的轨迹 (多为误检) 不入库
+const MIN_SAMPLES: u32 = 3;
+
+/// 索引与图库页面保留的条目数
+const MAX_ENTRIES: usize = 200;
+
+/// 裁剪时向四周扩展的比例
+const CROP_PADDING: f32 = 0.1;
+
+/// 尺寸得分满分对应的框边长 (面积开方, 像素)
+const FULL_SIZE: f32 = 160.0;
+
+/// 清晰度归一化: 拉普拉斯方差等于该值时得 0.5 分
+const SHARPNESS_HALF: f32 = 200.0;
+
+/// 关键点可见的置信度阈值
+const KEYPOINT_CONF: f32 = 0.3;
+
+/// 每个逻辑流缓存的最近帧数 (按解码时间戳匹配检测结果)
+const FRAME_HISTORY: usize = 8;
+
+/// 最近入库的条目 (新条目在前), 图库未启用时为 None
+static INDEX: OnceLock<Mutex<Vec<GalleryEntry>>> = OnceLock::new();
+
+/// 快照质量 (各项 0~1)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ShotQuality {
+    pub size: f32,
+    pub sharpness: f32,
+    pub frontal: f32,
+    pub score: f32, // 加权总分
+}
+
+impl ShotQuality {
+    /// 按框尺寸、裁剪图清晰度与姿态打分
+    pub fn assess(crop: &RgbImage, bbox: &BBox, pose: Option<&PoseKeypoints>) -> Self {
+        let area = (bbox.x2 - bbox.x1).max(0.0) * (bbox.y2 - bbox.y1).max(0.0);
+        let size = (area.sqrt() / FULL_SIZE).min(1.0);
+        let sharpness = sharpness(crop);
+        let frontal = frontal_score(pose);
+        Self {
+            size,
+            sharpness,
+            frontal,
+            score: 0.4 * size + 0.35 * sharpness + 0.25 * frontal,
+        }
+    }
+}
+
+/// 清晰度: 亮度拉普拉斯响应的方差, 归一化到 0~1
+pub fn sharpness(image: &RgbImage) -> f32 {
+    let (w, h) = (image.width() as usize, image.height() as usize);
+    if w < 3 || h < 3 {
+        return 0.0;
+    }
+    let gray: Vec<f32> = image
+        .pixels()
+        .map(|p| PreprocessSpec::luma(p.0) as f32)
+        .collect();
+    let (mut sum, mut sum_sq) = (0.0f64, 0.0f64);
+    for y in 1..h - 1 {
+        for x in 1..w - 1 {
+            let i = y * w + x;
+            let lap =
+                (gray[i - 1] + gray[i + 1] + gray[i - w] + gray[i + w] - 4.0 * gray[i]) as f64;
+            sum += lap;
+            sum_sq += lap * lap;
+        }
+    }
+    let n = ((w - 2) * (h - 2)) as f64;
+    let variance = (sum_sq / n - (sum / n).powi(2)) as f32;
+    variance / (variance + SHARPNESS_HALF)
+}
+
+/// 正面程度 (COCO 关键点: 0 鼻, 1 左眼, 2 右眼)
+///
+/// 双眼与鼻子都可见时按鼻子到两眼的水平距离是否对称打分; 只见一只眼为侧脸,
+/// 看不到鼻子为背影. 没有姿态时返回中性分 0.5
+pub fn frontal_score(pose: Option<&PoseKeypoints>) -> f32 {
+    let Some(pose) = pose else {
+        return 0.5;
+    };
+    let visible = |i: usize| pose.points.get(i).filter(|p| p.2 >= KEYPOINT_CONF);
+    let Some(nose) = visible(0) else {
+        return 0.0;
+    };
+    match (visible(1), visible(2)) {
+        (Some(left), Some(right)) => {
+            let (dl, dr) = ((nose.0 - left.0).abs(), (nose.0 - right.0).abs());
+            1.0 - (dl - dr).abs() / (dl + dr).max(1e-3)
+        }
+        (Some(_), None) | (None, Some(_)) => 0.3,
+        (None, None) => 0.1,
+    }
+}
+
+/// 与检测框对应的姿态 (落在框内的可见关键点最多者)
+///
+/// 跟踪器输出的框顺序与姿态顺序不一致, 只能按位置匹配
+pub fn pose_for<'a>(bbox: &BBox, poses: &'a [PoseKeypoints]) -> Option<&'a PoseKeypoints> {
+    let inside = |pose: &PoseKeypoints| {
+        pose.points
+            .iter()
+            .filter(|p| p.2 >= KEYPOINT_CONF)
+            .filter(|p| p.0 >= bbox.x1 && p.0 <= bbox.x2 && p.1 >= bbox.y1 && p.1 <= bbox.y2)
+            .count()
+    };
+    poses
+        .iter()
+        .map(|pose| (inside(pose), pose))
+        .filter(|(n, _)| *n > 0)
+        .max_by_key(|(n, _)| *n)
+        .map(|(_, pose)| pose)
+}
+
+/// 从 RGBA 帧裁剪检测框 (四周扩展 [`CROP_PADDING`]), 框在画面外时为 None
+pub fn crop_rgba(rgba: &[u8], width: u32, height: u32, bbox: &BBox) -> Option<RgbImage> {
+    if rgba.len() < (width * height * 4) as usize {
+        return None;
+    }
+    let pad_x = (bbox.x2 - bbox.x1) * CROP_PADDING;
+    let pad_y = (bbox.y2 - bbox.y1) * CROP_PADDING;
+    let x1 = (bbox.x1 - pad_x).max(0.0) as u32;
+    let y1 = (bbox.y1 - pad_y).max(0.0) as u32;
+    let x2 = ((bbox.x2 + pad_x).max(0.0) as u32).min(width);
+    let y2 = ((bbox.y2 + pad_y).max(0.0) as u32).min(height);
+    if x2 <= x1 + 1 || y2 <= y1 + 1 {
+        return None;
+    }
+    Some(RgbImage::from_fn(x2 - x1, y2 - y1, |x, y| {
+        let i = (((y1 + y) * width + x1 + x) * 4) as usize;
+        image::Rgb([rgba[i], rgba[i + 1], rgba[i + 2]])
+    }))
+}
+
+/// 轨迹当前的最佳快照
+#[derive(Clone)]
+pub struct BestShot {
+    pub quality: ShotQuality,
+    pub crop: RgbImage,
+    pub bbox: [f32; 4],
+    pub time: DateTime<Local>,
+}
+
+/// 单条轨迹的采样状态
+#[derive(Clone)]
+pub struct TrackShots {
+    pub first_seen: DateTime<Local>,
+    pub last_seen: DateTime<Local>,
+    pub samples: u32,
+    pub best: Option<BestShot>,
+    last_ts: Instant,
+    last_sample: Option<Instant>,
+}
+
+/// 单个逻辑流的最佳快照选择 (按轨迹ID)
+#[derive(Default)]
+pub struct ShotSelector {
+    tracks: HashMap<u32, TrackShots>,
+}
+
+impl ShotSelector {
+    /// 记录轨迹出现; 距上次采样足够久时调用 `sample` 裁剪打分, 得分更高则替换最佳快照
+    pub fn observe(
+        &mut self,
+        id: u32,
+        ts: Instant,
+        time: DateTime<Local>,
+        bbox: [f32; 4],
+        sample: impl FnOnce() -> Option<(ShotQuality, RgbImage)>,
+    ) {
+        let track = self.tracks.entry(id).or_insert_with(|| TrackShots {
+            first_seen: time,
+            last_seen: time,
+            samples: 0,
+            best: None,
+            last_ts: ts,
+            last_sample: None,
+        });
+        track.last_ts = ts;
+        track.last_seen = time;
+        if track
+            .last_sample
+            .is_some_and(|t| ts.saturating_duration_since(t) < SAMPLE_INTERVAL)
+        {
+            return;
+        }
+        let Some((quality, crop)) = sample() else {
+            return;
+        };
+        track.last_sample = Some(ts);
+        track.samples += 1;
+        if track
+            .best
+            .as_ref()
+            .is_none_or(|best| quality.score > best.quality.score)
+        {
+            track.best = Some(BestShot {
+                quality,
+                crop,
+                bbox,
+                time,
+            });
+        }
+    }
+
+    /// 取出已结束的轨迹 (超时未出现), 采样过少或没有快照的直接丢弃
+    pub fn finish_lost(&mut self, now: Instant) -> Vec<(u32, TrackShots)> {
+        let lost: Vec<u32> = self
+            .tracks
+            .iter()
+            .filter(|(_, t)| now.saturating_duration_since(t.last_ts) > TRACK_TIMEOUT)
+            .map(|(id, _)| *id)
+            .collect();
+        let mut finished: Vec<(u32, TrackShots)> = lost
+            .into_iter()
+            .filter_map(|id| self.tracks.remove(&id).map(|t| (id, t)))
+            .filter(|(_, t)| t.samples >= MIN_SAMPLES && t.best.is_some())
+            .collect();
+        finished.sort_by_key(|(_, t)| t.last_seen);
+        finished
+    }
+}
+
+/// 图库条目 (与快照同名的 .json 元数据)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GalleryEntry {
+    pub track_id: u32,
+    pub view: u32,
+    pub first_seen: DateTime<Local>,
+    pub last_seen: DateTime<Local>,
+    pub best_time: DateTime<Local>,
+    pub samples: u32,
+    pub bbox: [f32; 4], // 最佳快照时的检测框 (画面像素)
+    pub quality: ShotQuality,
+    pub image: String, // 相对图库目录的路径
+}
+
+/// 最近入库的条目 (新条目在前), 图库未启用时为 None
+pub fn gallery_entries() -> Option<Vec<GalleryEntry>> {
+    INDEX.get().map(|index| index.lock().unwrap().clone())
+}
+
+/// 保存快照与元数据, 返回条目
+fn save_shot(dir: &Path, view: u32, id: u32, track: &TrackShots) -> Result<GalleryEntry> {
+    let best = track.best.as_ref().context("轨迹没有快照")?;
+    let stem = format!(
+        "{}_track{}",
+        clock::format(track.last_seen, "%Y%m%d_%H%M%S"),
+        id
+    );
+    let relative = format!("view{}/{}.jpg", view, stem);
+    let path = dir.join(&relative);
+    fs::create_dir_all(path.parent().unwrap())?;
+    best.crop
+        .save_with_format(&path, image::ImageFormat::Jpeg)
+        .with_context(|| format!("保存快照失败: {}", path.display()))?;
+    let entry = GalleryEntry {
+        track_id: id,
+        view,
+        first_seen: track.first_seen,
+        last_seen: track.last_seen,
+        best_time: best.time,
+        samples: track.samples,
+        bbox: best.bbox,
+        quality: best.quality,
+        image: relative,
+    };
+    fs::write(
+        path.with_extension("json"),
+        serde_json::to_string_pretty(&entry)?,
+    )?;
+    Ok(entry)
+}
+
+/// 读取已有的元数据 (启动时恢复索引), 新条目在前
+pub fn load_entries(dir: &Path) -> Vec<GalleryEntry> {
+    let mut entries: Vec<GalleryEntry> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .flat_map(|view_dir| {
+            fs::read_dir(view_dir.path())
+                .into_iter()
+                .flatten()
+                .flatten()
+        })
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "json"))
+        .filter_map(|p| serde_json::from_str(&fs::read_to_string(p).ok()?).ok())
+        .collect();
+    entries.sort_by_key(|e: &GalleryEntry| std::cmp::Reverse(e.last_seen));
+    entries.truncate(MAX_ENTRIES);
+    entries
+}
+
+/// 自包含的图库页面 (快照按结束时间倒序平铺)
+pub fn to_html(entries: &[GalleryEntry]) -> String {
+    let mut html = String::new();
+    html.push_str(concat!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>轨迹快照图库</title>\n",
+        "<style>body{font-family:sans-serif;margin:2em}.grid{display:flex;flex-wrap:wrap;gap:12px}",
+        "figure{margin:0;width:180px}img{width:180px;height:240px;object-fit:contain;background:#222}",
+        "figcaption{font-size:12px;color:#444}</style></head><body>\n",
+    ));
+    html.push_str(&format!(
+        "<h1>轨迹快照图库</h1>\n<p>最近 {} 条轨迹</p>\n<div class=\"grid\">\n",
+        entries.len()
+    ));
+    for e in entries {
+        html.push_str(&format!(
+            "<figure><a href=\"{0}\"><img src=\"{0}\" loading=\"lazy\"></a><figcaption>逻辑流 {1} · 轨迹 #{2}<br>{3} ~ {4}<br>得分 {5:.2} (尺寸 {6:.2} 清晰 {7:.2} 正面 {8:.2})</figcaption></figure>\n",
+            escape(&e.image),
+            e.view,
+            e.track_id,
+            clock::format(e.first_seen, "%m-%d %H:%M:%S"),
+            clock::format(e.last_seen, "%H:%M:%S"),
+            e.quality.score,
+            e.quality.size,
+            e.quality.sharpness,
+            e.quality.frontal,
+        ));
+    }
+    html.push_str("</div>\n</body></html>\n");
+    html
+}
+
+/// 一次检测结果及其对应的帧 (只保留图库需要的字段)
+struct Observation {
+    frame: Option<DecodedFrame>,
+    view: u32,
+    ts: Instant,
+    boxes: Vec<BBox>,
+    poses: Vec<PoseKeypoints>,
+}
+
+/// 最佳快照图库 (独立线程, 订阅保持期间运行)
+pub struct BestShotGallery {
+    _frame_sub: Subscription,
+    _result_sub: Subscription,
+}
+
+impl BestShotGallery {
+    pub fn start(dir: PathBuf) -> Self {
+        println!("🖼️ 轨迹快照图库: {}", dir.join("index.html").display());
+        let _ = INDEX.set(Mutex::new(load_entries(&dir)));
+
+        // 各逻辑流最近的帧, 检测结果按解码时间戳找回原帧
+        let frames: Arc<Mutex<HashMap<u32, VecDeque<DecodedFrame>>>> = Arc::default();
+        let history = frames.clone();
+        let frame_sub = xbus::subscribe::<DecodedFrame, _>(move |frame| {
+            let mut history = history.lock().unwrap();
+            let recent = history.entry(frame.view).or_default();
+            if recent.len() >= FRAME_HISTORY {
+                recent.pop_front();
+            }
+            recent.push_back(frame.clone());
+        });
+
+        let (tx, rx) = crossbeam_channel::bounded::<Observation>(2);
+        let result_sub = xbus::subscribe::<DetectionResult, _>(move |result| {
+            if !result.tracked {
+                return;
+            }
+            let ts = result.trace.decode_ts;
+            let frame = frames.lock().unwrap().get(&result.view).and_then(|recent| {
+                recent
+                    .iter()
+                    .rev()
+                    .find(|f| f.trace.decode_ts == ts)
+                    .cloned()
+            });
+            let _ = tx.try_send(Observation {
+                frame,
+                view: result.view,
+                ts,
+                boxes: result.bboxes.clone(),
+                poses: result.keypoints.clone(),
+            });
+        });
+
+        std::thread::spawn(move || {
+            let mut selectors: HashMap<u32, ShotSelector> = HashMap::new();
+            for obs in rx {
+                let now = Local::now();
+                let selector = selectors.entry(obs.view).or_default();
+                for b in &obs.boxes {
+                    selector.observe(b.class_id, obs.ts, now, [b.x1, b.y1, b.x2, b.y2], || {
+                        let frame = obs.frame.as_ref()?;
+                        let crop = crop_rgba(&frame.rgba_data, frame.width, frame.height, b)?;
+                        let quality = ShotQuality::assess(&crop, b, pose_for(b, &obs.poses));
+                        Some((quality, crop))
+                    });
+                }
+                let finished = selector.finish_lost(obs.ts);
+                if finished.is_empty() {
+                    continue;
+                }
+                for (id, track) in &finished {
+                    match save_shot(&dir, obs.view, *id, track) {
+                        Ok(entry) => {
+                            println!(
+                                "🖼️ 轨迹 #{} 最佳快照已保存 (得分 {:.2}, {}次采样)",
+                                id, entry.quality.score, entry.samples
+                            );
+                            if let Some(index) = INDEX.get() {
+                                let mut index = index.lock().unwrap();
+                                index.insert(0, entry);
+                                index.truncate(MAX_ENTRIES);
+                            }
+                        }
+                        Err(e) => eprintln!("⚠️ {:#}", e),
+                    }
+                }
+                if let Some(entries) = gallery_entries() {
+                    if let Err(e) = fs::write(dir.join("index.html"), to_html(&entries)) {
+                        eprintln!("⚠️ 图库页面保存失败: {}", e);
+                    }
+                }
+            }
+            println!("🖼️ 轨迹快照图库线程退出");
+        });
+
+        Self {
+            _frame_sub: frame_sub,
+            _result_sub: result_sub,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bbox(x1: f32, y1: f32, x2: f32, y2: f32) -> BBox {
+        BBox {
+            x1,
+            y1,
+            x2,
+            y2,
+            confidence: 0.9,
+            class_id: 1,
+        }
+    }
+
+    /// 棋盘格纹理 (格边长 4 像素)
+    fn checker(size: u32) -> RgbImage {
+        RgbImage::from_fn(size, size, |x, y| {
+            let v = if (x / 4 + y / 4) % 2 == 0 { 30 } else { 220 };
+            image::Rgb([v, v, v])
+        })
+    }
+
+    fn pose(points: &[(f32, f32, f32)]) -> PoseKeypoints {
+        PoseKeypoints {
+            points: points.to_vec(),
+        }
+    }
+
+    /// 清晰纹理得分高于模糊画面; 正脸 > 侧脸 > 背影; 大框尺寸分更高
+    #[test]
+    fn test_shot_quality() {
+        let sharp = checker(64);
+        let flat = RgbImage::from_pixel(64, 64, image::Rgb([128, 128, 128]));
+        assert!(sharpness(&sharp) > 0.9);
+        assert_eq!(sharpness(&flat), 0.0);
+
+        let front = pose(&[(50.0, 20.0, 0.9), (45.0, 18.0, 0.9), (55.0, 18.0, 0.9)]);
+        let side = pose(&[(50.0, 20.0, 0.9), (45.0, 18.0, 0.9), (0.0, 0.0, 0.1)]);
+        let back = pose(&[(0.0, 0.0, 0.1), (0.0, 0.0, 0.1), (0.0, 0.0, 0.1)]);
+        let (f, s, b) = (
+            frontal_score(Some(&front)),
+            frontal_score(Some(&side)),
+            frontal_score(Some(&back)),
+        );
+        assert!(f > 0.99 && f > s && s > b);
+        assert_eq!(frontal_score(None), 0.5);
+
+        let large = ShotQuality::assess(&sharp, &bbox(0.0, 0.0, 100.0, 200.0), None);
+        let small = ShotQuality::assess(&sharp, &bbox(0.0, 0.0, 20.0, 40.0), None);
+        assert!(large.score > small.score);
+
+        // 姿态按落在框内的关键点匹配
+        let poses = [pose(&[(500.0, 500.0, 0.9)]), front.clone()];
+        assert_eq!(
+            pose_for(&bbox(40.0, 10.0, 60.0, 90.0), &poses).map(|p| p.points.len()),
+            Some(3)
+        );
+        assert!(pose_for(&bbox(200.0, 200.0, 210.0, 210.0), &poses).is_none());
+    }
+
+    /// 按采样间隔采样并保留最高分快照; 轨迹超时后才结束, 采样过少的轨迹丢弃
+    #[test]
+    fn test_selector_keeps_best_until_lost() {
+        let mut selector = ShotSelector::default();
+        let t0 = Instant::now();
+        let now = Local::now();
+        let shot = |score: f32| {
+            move || {
+                Some((
+                    ShotQuality {
+                        score,
+                        ..ShotQuality::default()
+                    },
+                    RgbImage::new(2, 2),
+                ))
+            }
+        };
+        let scores = [0.3, 0.8, 0.5, 0.9, 0.4];
+        for (i, score) in scores.into_iter().enumerate() {
+            let ts = t0 + Duration::from_millis(100 * i as u64);
+            selector.observe(7, ts, now, [0.0; 4], shot(score));
+        }
+        // 100ms 一帧, 250ms 间隔只采到 0ms/300ms 两次 (0.3, 0.9)
+        selector.observe(9, t0, now, [0.0; 4], shot(1.0));
+        assert!(selector.finish_lost(t0 + Duration::from_secs(1)).is_empty());
+
+        let ts = t0 + Duration::from_millis(600);
+        selector.observe(7, ts, now, [0.0; 4], shot(0.2));
+        let finished = selector.finish_lost(ts + TRACK_TIMEOUT + Duration::from_millis(1));
+        assert_eq!(finished.len(), 1);
+        let (id, track) = &finished[0];
+        assert_eq!((*id, track.samples), (7, 3));
+        assert_eq!(track.best.as_ref().unwrap().quality.score, 0.9);
+        // 轨迹 9 只采样一次, 结束时丢弃
+        assert!(selector.tracks.is_empty());
+    }
+}
@@ -0,0 +1,593 @@
+//! 遗留物 / 移除物检测 (Object left-behind / removed)
+//!
+//! 双背景模型: 按网格单元维护长期背景 (时间常数约1分钟) 与短期背景 (约1秒) 的亮度.
+//! 与长期背景不同、但与短期背景一致的单元是"新出现且已静止"的前景; 被检测框覆盖的
+//! 单元不计入 (人站着不动不算遗留). 静止前景连通块持续超过驻留时间即为遗留物.
+//! 配置的静态物体区域与首帧参考外观持续不一致 (且未被遮挡) 则为移除.
+//! 事件附带触发时刻的整帧快照路径
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::detection::detector::DetectionResult;
+use crate::detection::types::{BBox, DecodedFrame};
+use crate::utils::clock;
+use crate::xbus::{self, Subscription};
+
+/// 默认配置文件路径
+pub const LEFT_BEHIND_CONFIG_PATH: &str = "left_behind.json";
+
+/// 需要看护的静态物体 (图像坐标矩形, 像素)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StaticObject {
+    pub name: String,
+    pub rect: [f32; 4], // x1, y1, x2, y2
+}
+
+/// 遗留/移除检测配置 (left_behind.json)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LeftBehindConfig {
+    pub cell_size: u32,      // 网格单元边长 (像素)
+    pub diff_threshold: f32, // 单元亮度差阈值 (0-255)
+    pub dwell_secs: f32,     // 静止/缺失持续多久触发事件
+    pub absorb_secs: f32,    // 静止超过该时长并入长期背景 (不再报警)
+    pub min_cells: usize,    // 遗留物最小面积 (单元数)
+    pub long_tau_secs: f32,  // 长期背景时间常数
+    pub short_tau_secs: f32, // 短期背景时间常数
+    pub process_fps: f32,    // 背景模型更新频率
+    pub view: u32,           // 处理的逻辑流
+    pub static_objects: Vec<StaticObject>,
+    pub snapshot_dir: String,
+}
+
+impl Default for LeftBehindConfig {
+    fn default() -> Self {
+        Self {
+            cell_size: 16,
+            diff_threshold: 30.0,
+            dwell_secs: 10.0,
+            absorb_secs: 300.0,
+            min_cells: 4,
+            long_tau_secs: 60.0,
+            short_tau_secs: 1.0,
+            process_fps: 5.0,
+            view: 0,
+            static_objects: Vec::new(),
+            snapshot_dir: String::from("snapshots"),
+        }
+    }
+}
+
+impl LeftBehindConfig {
+    /// 从文件加载配置, 文件不存在时使用默认配置
+    pub fn load(path: &str) -> Self {
+        match fs::read_to_string(path) {
+            Ok(json) => match serde_json::from_str::<Self>(&json) {
+                Ok(config) => {
+                    println!(
+                        "✅ 遗留物检测配置已从 {} 加载 ({}个看护物体)",
+                        path,
+                        config.static_objects.len()
+                    );
+                    config
+                }
+                Err(e) => {
+                    eprintln!("⚠️  遗留物检测配置解析失败: {}, 使用默认配置", e);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+}
+
+/// 场景变化类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SceneChange {
+    /// 新出现并长时间静止的物体
+    LeftBehind,
+    /// 看护物体不见了
+    Removed,
+}
+
+impl SceneChange {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SceneChange::LeftBehind => "遗留物",
+            SceneChange::Removed => "物体移除",
+        }
+    }
+}
+
+/// 遗留/移除事件
+#[derive(Clone, Debug)]
+pub struct LeftBehindEvent {
+    pub kind: SceneChange,
+    pub name: String,              // 看护物体名称, 遗留物为空
+    pub rect: [f32; 4],            // 图像坐标 (像素)
+    pub view: u32,                 // 逻辑流编号
+    pub snapshot: Option<PathBuf>, // 触发时刻的整帧快照
+    pub time: chrono::DateTime<chrono::Local>,
+}
+
+/// 看护物体的状态
+struct StaticState {
+    cells: [usize; 4], // 单元范围 x1, y1, x2, y2 (不含 x2/y2)
+    reference: Option<Vec<f32>>,
+    missing_secs: f32,
+    reported: bool,
+}
+
+/// 网格双背景模型 (不含 IO, 便于测试)
+pub struct BackgroundModel {
+    config: LeftBehindConfig,
+    grid_w: usize,
+    grid_h: usize,
+    long: Vec<f32>,
+    short: Vec<f32>,
+    static_secs: Vec<f32>,
+    reported: Vec<[usize; 4]>,
+    objects: Vec<StaticState>,
+    watched: Vec<bool>, // 看护物体覆盖的单元 (不参与遗留判断)
+}
+
+impl BackgroundModel {
+    pub fn new(config: LeftBehindConfig, width: u32, height: u32) -> Self {
+        let cell = config.cell_size.max(1);
+        let (grid_w, grid_h) = ((width / cell) as usize, (height / cell) as usize);
+        let objects = config
+            .static_objects
+            .iter()
+            .map(|o| StaticState {
+                cells: rect_to_cells(o.rect, cell, grid_w, grid_h),
+                reference: None,
+                missing_secs: 0.0,
+                reported: false,
+            })
+            .collect::<Vec<StaticState>>();
+        let mut watched = vec![false; grid_w * grid_h];
+        for state in &objects {
+            let [x1, y1, x2, y2] = state.cells;
+            for y in y1..y2 {
+                watched[y * grid_w + x1..y * grid_w + x2].fill(true);
+            }
+        }
+        Self {
+            config,
+            grid_w,
+            grid_h,
+            long: Vec::new(),
+            short: Vec::new(),
+            static_secs: vec![0.0; grid_w * grid_h],
+            reported: Vec::new(),
+            objects,
+            watched,
+        }
+    }
+
+    pub fn grid_size(&self) -> (usize, usize) {
+        (self.grid_w, self.grid_h)
+    }
+
+    /// 输入一帧的单元亮度与被检测框覆盖的单元, 返回新触发的 (类型, 名称, 像素矩形)
+    pub fn update(
+        &mut self,
+        cells: &[f32],
+        occupied: &[bool],
+        dt: f32,
+    ) -> Vec<(SceneChange, String, [f32; 4])> {
+        let mut changes = Vec::new();
+        if self.long.len() != cells.len() {
+            self.long = cells.to_vec();
+            self.short = cells.to_vec();
+            self.static_secs = vec![0.0; cells.len()];
+            return changes;
+        }
+
+        let thr = self.config.diff_threshold;
+        let a_short = 1.0 - (-dt / self.config.short_tau_secs.max(1e-3)).exp();
+        let a_long = 1.0 - (-dt / self.config.long_tau_secs.max(1e-3)).exp();
+        for i in 0..cells.len() {
+            let p = cells[i];
+            self.short[i] += a_short * (p - self.short[i]);
+            if occupied[i] || self.watched[i] {
+                // 被目标遮挡或属于看护物体: 既不累计静止时间也不更新长期背景
+                self.static_secs[i] = 0.0;
+                continue;
+            }
+            let foreground = (p - self.long[i]).abs() > thr;
+            let stable = (p - self.short[i]).abs() < thr;
+            if foreground && stable {
+                self.static_secs[i] += dt;
+                if self.static_secs[i] >= self.config.absorb_secs {
+                    // 长时间不变的变化视为场景本身的改变
+                    self.long[i] = p;
+                    self.static_secs[i] = 0.0;
+                }
+            } else {
+                self.static_secs[i] = 0.0;
+                self.long[i] += a_long * (p - self.long[i]);
+            }
+        }
+
+        // 遗留物: 超过驻留时间的静止单元连通块
+        let dwell = self.config.dwell_secs;
+        let blobs = self.static_blobs(dwell);
+        self.reported
+            .retain(|r| blobs.iter().any(|b| rects_overlap(r, b)));
+        for blob in blobs {
+            if self.reported.iter().any(|r| rects_overlap(r, &blob)) {
+                continue;
+            }
+            self.reported.push(blob);
+            changes.push((
+                SceneChange::LeftBehind,
+                String::new(),
+                self.cells_to_rect(blob),
+            ));
+        }
+
+        // 移除: 看护区域的短期外观与参考外观持续不一致
+        let grid_w = self.grid_w;
+        for (state, object) in self.objects.iter_mut().zip(&self.config.static_objects) {
+            let [x1, y1, x2, y2] = state.cells;
+            let indices: Vec<usize> = (y1..y2)
+                .flat_map(|y| (x1..x2).map(move |x| y * grid_w + x))
+                .collect();
+            if indices.is_empty() || indices.iter().any(|&i| occupied[i]) {
+                continue;
+            }
+            let current: Vec<f32> = indices.iter().map(|&i| self.short[i]).collect();
+            let Some(reference) = &state.reference else {
+                state.reference = Some(current);
+                continue;
+            };
+            let diff = current
+                .iter()
+                .zip(reference)
+                .map(|(a, b)| (a - b).abs())
+                .sum::<f32>()
+                / current.len() as f32;
+            if diff > thr {
+                state.missing_secs += dt;
+                if state.missing_secs >= dwell && !state.reported {
+                    state.reported = true;
+                    changes.push((SceneChange::Removed, object.name.clone(), object.rect));
+                }
+            } else {
+                if state.reported {
+                    println!("✅ 看护物体已恢复: {}", object.name);
+                }
+                state.missing_secs = 0.0;
+                state.reported = false;
+            }
+        }
+        changes
+    }
+
+    /// 静止时间超过阈值的单元的4邻域连通块 (单元矩形)
+    fn static_blobs(&self, dwell: f32) -> Vec<[usize; 4]> {
+        let (w, h) = (self.grid_w, self.grid_h);
+        let mut visited = vec![false; w * h];
+        let mut blobs = Vec::new();
+        for start in 0..w * h {
+            if visited[start] || self.static_secs[start] < dwell {
+                continue;
+            }
+            visited[start] = true;
+            let mut queue = VecDeque::from([start]);
+            let (mut count, mut rect) = (0, [w, h, 0, 0]);
+            while let Some(i) = queue.pop_front() {
+                let (x, y) = (i % w, i / w);
+                count += 1;
+                rect = [
+                    rect[0].min(x),
+                    rect[1].min(y),
+                    rect[2].max(x + 1),
+                    rect[3].max(y + 1),
+                ];
+                let neighbours = [
+                    (x > 0).then(|| i - 1),
+                    (x + 1 < w).then(|| i + 1),
+                    (y > 0).then(|| i - w),
+                    (y + 1 < h).then(|| i + w),
+                ];
+                for n in neighbours.into_iter().flatten() {
+                    if !visited[n] && self.static_secs[n] >= dwell {
+                        visited[n] = true;
+                        queue.push_back(n);
+                    }
+                }
+            }
+            if count >= self.config.min_cells {
+                blobs.push(rect);
+            }
+        }
+        blobs
+    }
+
+    fn cells_to_rect(&self, cells: [usize; 4]) -> [f32; 4] {
+        let cell = self.config.cell_size.max(1) as f32;
+        cells.map(|c| c as f32 * cell)
+    }
+}
+
+/// 像素矩形 → 单元范围 (裁剪到网格内)
+fn rect_to_cells(rect: [f32; 4], cell: u32, grid_w: usize, grid_h: usize) -> [usize; 4] {
+    let cell = cell as f32;
+    let clamp = |v: f32, max: usize| (v.max(0.0) as usize).min(max);
+    [
+        clamp(rect[0] / cell, grid_w),
+        clamp(rect[1] / cell, grid_h),
+        clamp((rect[2] / cell).ceil(), grid_w),
+        clamp((rect[3] / cell).ceil(), grid_h),
+    ]
+}
+
+fn rects_overlap(a: &[usize; 4], b: &[usize; 4]) -> bool {
+    a[0] < b[2] && b[0] < a[2] && a[1] < b[3] && b[1] < a[3]
+}
+
+/// RGBA 帧 → 单元平均亮度 (隔行隔列采样)
+pub fn sample_cells(rgba: &[u8], width: u32, cell: u32, grid_w: usize, grid_h: usize) -> Vec<f32> {
+    let (width, cell) = (width as usize, cell.max(1) as usize);
+    let mut cells = Vec::with_capacity(grid_w * grid_h);
+    for gy in 0..grid_h {
+        for gx in 0..grid_w {
+            let (mut sum, mut n) = (0u32, 0u32);
+            for y in (gy * cell..(gy + 1) * cell).step_by(2) {
+                for x in (gx * cell..(gx + 1) * cell).step_by(2) {
+                    let p = (y * width + x) * 4;
+                    // BT.601 近似亮度 (整数运算)
+                    sum +=
+                        (77 * rgba[p] as u32 + 150 * rgba[p + 1] as u32 + 29 * rgba[p + 2] as u32)
+                            >> 8;
+                    n += 1;
+                }
+            }
+            cells.push(sum as f32 / n.max(1) as f32);
+        }
+    }
+    cells
+}
+
+/// 检测框覆盖的单元 (框外扩一个单元, 覆盖阴影与边缘)
+pub fn occupied_cells(boxes: &[BBox], cell: u32, grid_w: usize, grid_h: usize) -> Vec<bool> {
+    let mut occupied = vec![false; grid_w * grid_h];
+    let margin = cell as f32;
+    for b in boxes {
+        let [x1, y1, x2, y2] = rect_to_cells(
+            [b.x1 - margin, b.y1 - margin, b.x2 + margin, b.y2 + margin],
+            cell,
+            grid_w,
+            grid_h,
+        );
+        for y in y1..y2 {
+            occupied[y * grid_w + x1..y * grid_w + x2].fill(true);
+        }
+    }
+    occupied
+}
+
+/// 保存整帧快照 (JPEG), 返回文件路径
+fn save_snapshot(dir: &str, kind: SceneChange, frame: &DecodedFrame) -> Option<PathBuf> {
+    if let Err(e) = fs::create_dir_all(dir) {
+        eprintln!("❌ 创建快照目录失败: {}", e);
+        return None;
+    }
+    let tag = match kind {
+        SceneChange::LeftBehind => "left_behind",
+        SceneChange::Removed => "removed",
+    };
+    let path = PathBuf::from(dir).join(format!(
+        "{}_{}.jpg",
+        tag,
+        clock::now().format("%Y%m%d_%H%M%S%.3f")
+    ));
+    let image = image::RgbaImage::from_raw(frame.width, frame.height, frame.rgba_data.to_vec())?;
+    match image::DynamicImage::ImageRgba8(image).to_rgb8().save(&path) {
+        Ok(()) => Some(path),
+        Err(e) => {
+            eprintln!("❌ 保存快照失败: {}", e);
+            None
+        }
+    }
+}
+
+/// 遗留/移除检测 (独立线程, 订阅保持期间运行)
+pub struct LeftBehindMonitor {
+    _frame_sub: Subscription,
+    _result_sub: Subscription,
+}
+
+impl LeftBehindMonitor {
+    /// 启动检测线程; 检测框来自同一逻辑流的最新检测结果
+    pub fn start(config: LeftBehindConfig) -> Self {
+        println!(
+            "🧳 遗留物检测启动: 驻留{:.0}s, {}个看护物体",
+            config.dwell_secs,
+            config.static_objects.len()
+        );
+        let view = config.view;
+        let (tx, rx) = crossbeam_channel::bounded::<DecodedFrame>(1);
+        let boxes: Arc<Mutex<Vec<BBox>>> = Arc::new(Mutex::new(Vec::new()));
+
+        // 只在解码线程上转交帧, 检测线程忙时直接丢弃
+        let frame_sub = xbus::subscribe::<DecodedFrame, _>(move |frame| {
+            if frame.view == view {
+                let _ = tx.try_send(frame.clone());
+            }
+        });
+        let latest = Arc::clone(&boxes);
+        let result_sub = xbus::subscribe::<DetectionResult, _>(move |result| {
+            if result.view == view {
+                *latest.lock().unwrap() = result.bboxes.clone();
+            }
+        });
+
+        std::thread::spawn(move || {
+            let interval = 1.0 / config.process_fps.max(0.1);
+            let mut model: Option<BackgroundModel> = None;
+            let mut last: Option<Instant> = None;
+            for frame in rx {
+                let now = frame.trace.decode_ts;
+                let dt = match last {
+                    Some(t) => now.saturating_duration_since(t).as_secs_f32(),
+                    None => 0.0,
+                };
+                if last.is_some() && dt < interval {
+                    continue;
+                }
+                if frame.rgba_data.len() < (frame.width * frame.height * 4) as usize {
+                    continue;
+                }
+                last = Some(now);
+
+                // 分辨率变化 (切换输入源) 时重建模型
+                let cell = config.cell_size.max(1);
+                let grid = (
+                    (frame.width / cell) as usize,
+                    (frame.height / cell) as usize,
+                );
+                if model.as_ref().map(BackgroundModel::grid_size) != Some(grid) {
+                    model = Some(BackgroundModel::new(
+                        config.clone(),
+                        frame.width,
+                        frame.height,
+                    ));
+                }
+                let model = model.as_mut().unwrap();
+
+                let cells = sample_cells(&frame.rgba_data, frame.width, cell, grid.0, grid.1);
+                let occupied = occupied_cells(&boxes.lock().unwrap(), cell, grid.0, grid.1);
+                for (kind, name, rect) in model.update(&cells, &occupied, dt) {
+                    let snapshot = save_snapshot(&config.snapshot_dir, kind, &frame);
+                    println!(
+                        "🧳 {} {} ({:.0}, {:.0})-({:.0}, {:.0}) 快照: {:?}",
+                        kind.label(),
+                        name,
+                        rect[0],
+                        rect[1],
+                        rect[2],
+                        rect[3],
+                        snapshot
+                    );
+                    xbus::post(LeftBehindEvent {
+                        kind,
+                        name,
+                        rect,
+                        view,
+                        snapshot,
+                        time: chrono::Local::now(),
+                    });
+                }
+            }
+            println!("🧳 遗留物检测线程退出");
+        });
+
+        Self {
+            _frame_sub: frame_sub,
+            _result_sub: result_sub,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> LeftBehindConfig {
+        LeftBehindConfig {
+            cell_size: 10,
+            dwell_secs: 3.0,
+            min_cells: 4,
+            static_objects: vec![StaticObject {
+                name: "灭火器".to_string(),
+                rect: [0.0, 0.0, 20.0, 20.0],
+            }],
+            ..LeftBehindConfig::default()
+        }
+    }
+
+    /// 新放下的静止物体在驻留时间后报告一次, 被检测框覆盖时不计入
+    #[test]
+    fn test_left_behind() {
+        let mut model = BackgroundModel::new(config(), 100, 100);
+        let (w, h) = model.grid_size();
+        let mut frame = vec![100.0; w * h];
+        let free = vec![false; w * h];
+        for _ in 0..10 {
+            assert!(model.update(&frame, &free, 0.2).is_empty());
+        }
+
+        // 在 (5..7, 5..7) 放下一个亮物体, 有人站在旁边时不计静止时间
+        for y in 5..7 {
+            for x in 5..7 {
+                frame[y * w + x] = 220.0;
+            }
+        }
+        let occupied = occupied_cells(
+            &[BBox {
+                x1: 45.0,
+                y1: 45.0,
+                x2: 75.0,
+                y2: 75.0,
+                confidence: 0.9,
+                class_id: 0,
+            }],
+            10,
+            w,
+            h,
+        );
+        for _ in 0..30 {
+            assert!(model.update(&frame, &occupied, 0.2).is_empty());
+        }
+
+        // 人离开后: 短期背景先跟上, 之后静止满驻留时间触发一次
+        let mut events = Vec::new();
+        for _ in 0..40 {
+            events.extend(model.update(&frame, &free, 0.2));
+        }
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].0, SceneChange::LeftBehind);
+        assert_eq!(events[0].2, [50.0, 50.0, 70.0, 70.0]);
+    }
+
+    /// 看护物体区域外观持续改变时报告移除, 恢复后可再次报告
+    #[test]
+    fn test_removed() {
+        let mut model = BackgroundModel::new(config(), 100, 100);
+        let (w, h) = model.grid_size();
+        let free = vec![false; w * h];
+        let mut frame = vec![100.0; w * h];
+        for i in [0, 1, w, w + 1] {
+            frame[i] = 30.0; // 看护物体 (深色)
+        }
+        for _ in 0..10 {
+            model.update(&frame, &free, 0.2);
+        }
+
+        let background = vec![100.0; w * h];
+        let removed: Vec<_> = (0..40)
+            .flat_map(|_| model.update(&background, &free, 0.2))
+            .filter(|(kind, ..)| *kind == SceneChange::Removed)
+            .collect();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].1, "灭火器");
+
+        for _ in 0..20 {
+            model.update(&frame, &free, 0.2);
+        }
+        let again = (0..40)
+            .flat_map(|_| model.update(&background, &free, 0.2))
+            .filter(|(kind, ..)| *kind == SceneChange::Removed)
+            .count();
+        assert_eq!(again, 1);
+    }
+}
@@ -0,0 +1,628 @@
+//! 基准标记检测与区域锚定 (Fiducial markers: ArUco / AprilTag)
+//!
+//! 纯 Rust 实现的方形基准标记检测: 局部均值二值化 → 轮廓 → 四边形拟合 → 按单应采样
+//! 比特格 → 字典匹配 (含4个旋转与纠错). 标记由 1 格黑边包围 bits×bits 的数据格, 比特按行
+//! 优先、左上为最高位, 白色为 1 (与 OpenCV ArUco 约定一致), 字典可从 OpenCV 导出为 JSON.
+//!
+//! 地面标定时记录画面中标记的角点作为参考; 相机被碰歪后, 由当前角点 → 参考角点求得
+//! 画面偏移单应, 与地面单应复合后重新下发, 区域 (地面坐标) 随之回到原来的物理位置
+
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use anyhow::{ensure, Context, Result};
+use image::{GrayImage, Luma};
+use imageproc::contours::{find_contours, BorderType};
+use imageproc::geometry::{approximate_polygon_dp, arc_length};
+use imageproc::integral_image::{integral_image, sum_image_pixels};
+use imageproc::point::Point;
+use serde::{Deserialize, Serialize};
+
+use super::calibration::{GroundCalibration, GroundPoint, Homography};
+use crate::detection::types::{ControlMessage, DecodedFrame};
+use crate::models::PreprocessSpec;
+use crate::xbus::{self, Subscription};
+
+/// 二值化阈值偏移: 比局部均值暗这么多才算前景 (黑)
+const THRESHOLD_OFFSET: i32 = 7;
+
+/// 比特格最小明暗差, 低于此值视为非标记
+const MIN_CONTRAST: f32 = 30.0;
+
+/// 每格最少像素 (边长), 太小的四边形无法可靠采样
+const MIN_CELL_PX: f32 = 2.0;
+
+/// 画面相对上次下发移动超过此值 (像素, 角点平均) 才重新下发单应
+const MIN_UPDATE_PX: f32 = 1.0;
+
+/// 检测到的标记: 角点按标记自身的 左上 → 右上 → 右下 → 左下 顺序 (图像像素)
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Marker {
+    pub id: u32,
+    pub corners: [[f32; 2]; 4],
+}
+
+impl Marker {
+    /// 四边形面积 (像素²)
+    pub fn area(&self) -> f32 {
+        let c = &self.corners;
+        (0..4)
+            .map(|i| {
+                let (a, b) = (c[i], c[(i + 1) % 4]);
+                a[0] * b[1] - b[0] * a[1]
+            })
+            .sum::<f32>()
+            .abs()
+            / 2.0
+    }
+}
+
+/// 一帧的标记检测结果 (xbus 事件)
+#[derive(Clone, Debug, Default)]
+pub struct MarkerDetections {
+    pub view: u32,
+    pub markers: Vec<Marker>,
+    // 相对标定时参考位置的平均角点偏移 (像素), 无参考标记匹配时为 None
+    pub shift_px: Option<f32>,
+}
+
+/// 标记字典
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MarkerDictionary {
+    pub name: String,
+    pub bits: u32,           // 数据格边长 (不含黑边), bits² ≤ 64
+    pub codes: Vec<u64>,     // 下标即标记ID
+    pub max_correction: u32, // 最多纠正的错误比特数
+}
+
+impl MarkerDictionary {
+    /// 内置字典: 4×4 数据格, 50 个标记, 旋转后最小汉明距离 4 (纠正 1 比特)
+    pub fn builtin() -> Self {
+        Self::generate("builtin_4x4_50", 4, 50, 4)
+    }
+
+    /// 从 JSON 文件加载字典
+    pub fn load(path: &str) -> Result<Self> {
+        let json = fs::read_to_string(path).with_context(|| format!("读取标记字典 {}", path))?;
+        let dictionary: Self =
+            serde_json::from_str(&json).with_context(|| format!("解析标记字典 {}", path))?;
+        ensure!(
+            (1..=8).contains(&dictionary.bits),
+            "标记字典 {} 的 bits 须为 1~8",
+            path
+        );
+        ensure!(!dictionary.codes.is_empty(), "标记字典 {} 为空", path);
+        Ok(dictionary)
+    }
+
+    /// 按名称或路径取字典: "builtin" 为内置字典, 其余按 JSON 文件加载
+    pub fn from_spec(spec: &str) -> Result<Self> {
+        if spec == "builtin" {
+            Ok(Self::builtin())
+        } else {
+            Self::load(spec)
+        }
+    }
+
+    /// 确定性生成字典: 伪随机候选中贪心挑选, 与已选编码 (含自身) 的所有旋转保持最小汉明距离
+    pub fn generate(name: &str, bits: u32, count: usize, min_distance: u32) -> Self {
+        assert!((1..=8).contains(&bits), "bits 须为 1~8");
+        let n = bits * bits;
+        let mask = if n == 64 { u64::MAX } else { (1u64 << n) - 1 };
+        let mut codes: Vec<u64> = Vec::with_capacity(count);
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        for _ in 0..(1usize << n.min(20)) * 4 {
+            if codes.len() >= count {
+                break;
+            }
+            // xorshift64*
+            state ^= state >> 12;
+            state ^= state << 25;
+            state ^= state >> 27;
+            let code = state.wrapping_mul(0x2545_F491_4F6C_DD1D) & mask;
+
+            // 至少 bits 个白格, 保证格内有足够明暗对比
+            if code.count_ones() < bits || code.count_ones() > n - bits / 2 {
+                continue;
+            }
+            let self_distance = (1..4)
+                .map(|k| (rotate(code, bits, k) ^ code).count_ones())
+                .min()
+                .unwrap_or(n);
+            if self_distance < min_distance {
+                continue;
+            }
+            if codes
+                .iter()
+                .all(|&c| (0..4).all(|k| (rotate(code, bits, k) ^ c).count_ones() >= min_distance))
+            {
+                codes.push(code);
+            }
+        }
+        Self {
+            name: name.to_string(),
+            bits,
+            codes,
+            max_correction: (min_distance.max(1) - 1) / 2,
+        }
+    }
+
+    /// 匹配采样到的比特: 返回 (ID, 顺时针旋转次数 k), 使 rotate(sampled, k) 与字典编码一致
+    fn identify(&self, sampled: u64) -> Option<(u32, u32)> {
+        let mut best: Option<(u32, u32, u32)> = None;
+        for k in 0..4 {
+            let rotated = rotate(sampled, self.bits, k);
+            for (id, &code) in self.codes.iter().enumerate() {
+                let distance = (rotated ^ code).count_ones();
+                if distance <= self.max_correction && best.is_none_or(|b| distance < b.2) {
+                    best = Some((id as u32, k, distance));
+                }
+            }
+        }
+        best.map(|(id, k, _)| (id, k))
+    }
+}
+
+/// 比特格顺时针旋转 k 次 (行优先, 左上为最高位)
+fn rotate(code: u64, bits: u32, k: u32) -> u64 {
+    let n = bits as usize;
+    let bit = |code: u64, r: usize, c: usize| (code >> (n * n - 1 - (r * n + c))) & 1;
+    let mut code = code;
+    for _ in 0..k % 4 {
+        let mut out = 0u64;
+        for r in 0..n {
+            for c in 0..n {
+                // 顺时针: 新 (r, c) 取自旧 (n-1-c, r)
+                out = (out << 1) | bit(code, n - 1 - c, r);
+            }
+        }
+        code = out;
+    }
+    code
+}
+
+/// 渲染标记图像 (含 1 格白色静区), 用于打印
+pub fn render_marker(dictionary: &MarkerDictionary, id: u32, cell_px: u32) -> Option<GrayImage> {
+    let code = *dictionary.codes.get(id as usize)?;
+    let bits = dictionary.bits;
+    let cells = bits + 4;
+    Some(GrayImage::from_fn(
+        cells * cell_px,
+        cells * cell_px,
+        |x, y| {
+            let (cx, cy) = (x / cell_px, y / cell_px);
+            let white = if cx == 0 || cy == 0 || cx == cells - 1 || cy == cells - 1 {
+                true
+            } else if cx == 1 || cy == 1 || cx == cells - 2 || cy == cells - 2 {
+                false
+            } else {
+                let (r, c) = ((cy - 2) as usize, (cx - 2) as usize);
+                let n = bits as usize;
+                (code >> (n * n - 1 - (r * n + c))) & 1 == 1
+            };
+            Luma([if white { 255 } else { 0 }])
+        },
+    ))
+}
+
+/// RGBA 帧 → 灰度图
+pub fn rgba_to_gray(rgba: &[u8], width: u32, height: u32) -> GrayImage {
+    let pixels = rgba
+        .chunks_exact(4)
+        .map(|px| PreprocessSpec::luma([px[0], px[1], px[2]]))
+        .collect();
+    GrayImage::from_raw(width, height, pixels).expect("RGBA 帧尺寸不匹配")
+}
+
+/// 检测灰度图中的标记, 同一ID出现多次时保留面积最大者
+pub fn detect_markers(gray: &GrayImage, dictionary: &MarkerDictionary) -> Vec<Marker> {
+    let (width, height) = gray.dimensions();
+    let cells = (dictionary.bits + 2) as f32;
+    let min_side = cells * MIN_CELL_PX;
+    if (width.min(height) as f32) < min_side + 2.0 {
+        return Vec::new();
+    }
+    let binary = threshold_dark(gray, (width.max(height) / 40).clamp(5, 50));
+
+    let mut found: HashMap<u32, Marker> = HashMap::new();
+    for contour in find_contours::<i32>(&binary) {
+        if contour.border_type != BorderType::Outer || contour.points.len() < 4 {
+            continue;
+        }
+        // 接触画面边缘的轮廓可能被截断
+        if contour
+            .points
+            .iter()
+            .any(|p| p.x <= 0 || p.y <= 0 || p.x >= width as i32 - 1 || p.y >= height as i32 - 1)
+        {
+            continue;
+        }
+        let perimeter = arc_length(&contour.points, true);
+        if perimeter < 4.0 * min_side as f64 {
+            continue;
+        }
+        let Some(quad) = fit_quad(&contour.points, perimeter, min_side) else {
+            continue;
+        };
+        if let Some(marker) = decode_quad(gray, &quad, dictionary) {
+            let keep = found
+                .get(&marker.id)
+                .is_none_or(|m| m.area() < marker.area());
+            if keep {
+                found.insert(marker.id, marker);
+            }
+        }
+    }
+    let mut markers: Vec<Marker> = found.into_values().collect();
+    markers.sort_by_key(|m| m.id);
+    markers
+}
+
+/// 局部均值二值化: 明显暗于邻域均值的像素为前景 (255)
+fn threshold_dark(gray: &GrayImage, radius: u32) -> GrayImage {
+    let (width, height) = gray.dimensions();
+    let integral = integral_image::<_, u32>(gray);
+    GrayImage::from_fn(width, height, |x, y| {
+        let (x0, y0) = (x.saturating_sub(radius), y.saturating_sub(radius));
+        let (x1, y1) = ((x + radius).min(width - 1), (y + radius).min(height - 1));
+        let count = (x1 - x0 + 1) * (y1 - y0 + 1);
+        let mean = sum_image_pixels(&integral, x0, y0, x1, y1)[0] / count;
+        let dark = (gray.get_pixel(x, y)[0] as i32) < mean as i32 - THRESHOLD_OFFSET;
+        Luma([if dark { 255 } else { 0 }])
+    })
+}
+
+/// 轮廓拟合为凸四边形, 角点按图像中的顺时针顺序 (y 轴向下)
+fn fit_quad(points: &[Point<i32>], perimeter: f64, min_side: f32) -> Option<[[f32; 2]; 4]> {
+    let polygon = approximate_polygon_dp(points, perimeter * 0.05, true);
+    if polygon.len() != 4 {
+        return None;
+    }
+    let mut quad = [[0.0f32; 2]; 4];
+    for (q, p) in quad.iter_mut().zip(&polygon) {
+        *q = [p.x as f32, p.y as f32];
+    }
+    // 各边叉积同号即为凸四边形
+    let cross = |i: usize| {
+        let (a, b, c) = (quad[i], quad[(i + 1) % 4], quad[(i + 2) % 4]);
+        (b[0] - a[0]) * (c[1] - b[1]) - (b[1] - a[1]) * (c[0] - b[0])
+    };
+    let signs: Vec<f32> = (0..4).map(cross).collect();
+    if !(signs.iter().all(|&s| s > 0.0) || signs.iter().all(|&s| s < 0.0)) {
+        return None;
+    }
+    if signs[0] < 0.0 {
+        quad.reverse();
+    }
+    let shortest = (0..4)
+        .map(|i| {
+            let (a, b) = (quad[i], quad[(i + 1) % 4]);
+            ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt()
+        })
+        .fold(f32::INFINITY, f32::min);
+    (shortest >= min_side).then_some(quad)
+}
+
+/// 按四边形采样比特格并与字典匹配, 角点顺序校正为标记自身的左上起
+fn decode_quad(
+    gray: &GrayImage,
+    quad: &[[f32; 2]; 4],
+    dictionary: &MarkerDictionary,
+) -> Option<Marker> {
+    let n = dictionary.bits as usize + 2;
+    let size = n as f32;
+    // 格坐标 → 图像像素
+    let to_image = Homography::from_points(
+        &[[0.0, 0.0], [size, 0.0], [size, size], [0.0, size]]
+            .iter()
+            .zip(quad)
+            .map(|(&cell, &pixel)| GroundPoint {
+                image: cell,
+                world: pixel,
+            })
+            .collect::<Vec<_>>(),
+    )?;
+
+    // 每格取中心附近 3×3 个采样点的均值
+    let (width, height) = gray.dimensions();
+    let mut means = vec![0.0f32; n * n];
+    for (i, mean) in means.iter_mut().enumerate() {
+        let (r, c) = (i / n, i % n);
+        let mut sum = 0.0;
+        for (dx, dy) in [-0.25f32, 0.0, 0.25]
+            .iter()
+            .flat_map(|&dx| [-0.25f32, 0.0, 0.25].map(|dy| (dx, dy)))
+        {
+            let (x, y) = to_image.project(c as f32 + 0.5 + dx, r as f32 + 0.5 + dy)?;
+            let (x, y) = (x.round(), y.round());
+            if x < 0.0 || y < 0.0 || x >= width as f32 || y >= height as f32 {
+                return None;
+            }
+            sum += gray.get_pixel(x as u32, y as u32)[0] as f32;
+        }
+        *mean = sum / 9.0;
+    }
+
+    let (min, max) = means
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        });
+    if max - min < MIN_CONTRAST {
+        return None;
+    }
+    let threshold = (min + max) / 2.0;
+
+    // 外圈须全黑
+    let border = (0..n * n).filter(|i| {
+        let (r, c) = (i / n, i % n);
+        r == 0 || c == 0 || r == n - 1 || c == n - 1
+    });
+    if border.map(|i| means[i]).any(|v| v >= threshold) {
+        return None;
+    }
+    let mut code = 0u64;
+    for r in 1..n - 1 {
+        for c in 1..n - 1 {
+            code = (code << 1) | (means[r * n + c] >= threshold) as u64;
+        }
+    }
+
+    // 采样到的格旋转 k 次与字典一致, 则标记左上角位于 quad[(4 - k) % 4]
+    let (id, k) = dictionary.identify(code)?;
+    let corners = std::array::from_fn(|j| quad[(j + 4 - k as usize) % 4]);
+    Some(Marker { id, corners })
+}
+
+/// 标记锚定结果
+#[derive(Clone, Copy, Debug)]
+pub struct Anchor {
+    pub homography: Homography,
+    pub shift_px: f32, // 当前角点相对参考角点的平均偏移
+    pub matched: usize,
+}
+
+/// 由当前标记与标定时的参考标记求得锚定后的地面单应; 无匹配标记或标定无效时为 None
+pub fn anchored_homography(calibration: &GroundCalibration, markers: &[Marker]) -> Option<Anchor> {
+    let base = calibration.homography()?;
+    let mut pairs = Vec::new();
+    for marker in markers {
+        if let Some(reference) = calibration.markers.iter().find(|r| r.id == marker.id) {
+            for (current, reference) in marker.corners.iter().zip(&reference.corners) {
+                pairs.push(GroundPoint {
+                    image: *current,
+                    world: *reference,
+                });
+            }
+        }
+    }
+    // 当前画面 → 标定时画面
+    let shift = Homography::from_points(&pairs)?;
+    Some(Anchor {
+        homography: base.compose(&shift),
+        shift_px: mean_distance(&pairs),
+        matched: pairs.len() / 4,
+    })
+}
+
+fn mean_distance(pairs: &[GroundPoint]) -> f32 {
+    if pairs.is_empty() {
+        return 0.0;
+    }
+    pairs
+        .iter()
+        .map(|p| ((p.image[0] - p.world[0]).powi(2) + (p.image[1] - p.world[1]).powi(2)).sqrt())
+        .sum::<f32>()
+        / pairs.len() as f32
+}
+
+/// 标记锚定状态: 当前标定与上次下发单应时的标记位置
+struct AnchorState {
+    calibration: GroundCalibration,
+    applied: Vec<Marker>,
+}
+
+/// 标记检测与区域锚定 (独立线程, 订阅保持期间运行)
+pub struct MarkerAnchor {
+    _frame_sub: Subscription,
+    _calibration_sub: Subscription,
+}
+
+impl MarkerAnchor {
+    /// 启动检测线程; 每 `interval_secs` 秒检测一次主画面 (view 0)
+    pub fn start(
+        dictionary: MarkerDictionary,
+        calibration: GroundCalibration,
+        interval_secs: f32,
+    ) -> Self {
+        println!(
+            "🎯 基准标记锚定启动: 字典 {} ({}个标记), 参考标记{}个, 间隔{:.1}s",
+            dictionary.name,
+            dictionary.codes.len(),
+            calibration.markers.len(),
+            interval_secs
+        );
+        let (tx, rx) = crossbeam_channel::bounded::<DecodedFrame>(1);
+        let state = Arc::new(Mutex::new(AnchorState {
+            calibration,
+            applied: Vec::new(),
+        }));
+
+        // 只在解码线程上转交帧, 检测线程忙时直接丢弃
+        let frame_sub = xbus::subscribe::<DecodedFrame, _>(move |frame| {
+            if frame.view == 0 {
+                let _ = tx.try_send(frame.clone());
+            }
+        });
+        // 控制面板重新标定: 面板已下发未锚定的单应, 以新参考为准
+        let latest = Arc::clone(&state);
+        let calibration_sub = xbus::subscribe::<GroundCalibration, _>(move |calibration| {
+            let mut state = latest.lock().unwrap();
+            state.calibration = calibration.clone();
+            state.applied = calibration.markers.clone();
+        });
+
+        std::thread::spawn(move || {
+            let mut last: Option<Instant> = None;
+            for frame in rx {
+                let now = frame.trace.decode_ts;
+                if last
+                    .is_some_and(|t| now.saturating_duration_since(t).as_secs_f32() < interval_secs)
+                {
+                    continue;
+                }
+                if frame.rgba_data.len() < (frame.width * frame.height * 4) as usize {
+                    continue;
+                }
+                last = Some(now);
+
+                let gray = rgba_to_gray(&frame.rgba_data, frame.width, frame.height);
+                let markers = detect_markers(&gray, &dictionary);
+                let mut state = state.lock().unwrap();
+                let anchor = anchored_homography(&state.calibration, &markers);
+                if let Some(anchor) = anchor {
+                    if moved(&state.applied, &markers) > MIN_UPDATE_PX {
+                        println!(
+                            "🎯 画面偏移 {:.1}px ({}个标记), 重新锚定地面标定",
+                            anchor.shift_px, anchor.matched
+                        );
+                        ControlMessage::SetGroundHomography(Some(anchor.homography)).post();
+                        state.applied = markers.clone();
+                    }
+                }
+                drop(state);
+                xbus::post(MarkerDetections {
+                    view: 0,
+                    markers,
+                    shift_px: anchor.map(|a| a.shift_px),
+                });
+            }
+            println!("🎯 基准标记检测线程退出");
+        });
+
+        Self {
+            _frame_sub: frame_sub,
+            _calibration_sub: calibration_sub,
+        }
+    }
+}
+
+/// 当前标记相对上次下发时的平均角点移动 (像素); 没有共同标记时视为无穷大
+fn moved(applied: &[Marker], markers: &[Marker]) -> f32 {
+    let pairs: Vec<GroundPoint> = markers
+        .iter()
+        .filter_map(|m| Some((m, applied.iter().find(|a| a.id == m.id)?)))
+        .flat_map(|(m, a)| {
+            m.corners
+                .iter()
+                .zip(&a.corners)
+                .map(|(&image, &world)| GroundPoint { image, world })
+        })
+        .collect();
+    if pairs.is_empty() {
+        f32::INFINITY
+    } else {
+        mean_distance(&pairs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 把标记图像贴到灰色背景的 (x, y) 处, 顺时针旋转 k×90°
+    fn scene(marker: &GrayImage, x: u32, y: u32, k: u32) -> GrayImage {
+        let mut marker = marker.clone();
+        for _ in 0..k {
+            marker = image::imageops::rotate90(&marker);
+        }
+        let mut image = GrayImage::from_pixel(320, 240, Luma([128]));
+        image::imageops::overlay(&mut image, &marker, x as i64, y as i64);
+        image
+    }
+
+    /// 渲染的标记在4个旋转下都能识别出正确的ID与左上角点
+    #[test]
+    fn test_detect_rendered_marker_all_rotations() {
+        let dictionary = MarkerDictionary::builtin();
+        assert_eq!(dictionary.codes.len(), 50);
+        let marker = render_marker(&dictionary, 7, 12).unwrap();
+        // 静区 1 格: 黑边外角位于 (x+12, y+12), 边长 6×12
+        let (x, y, side) = (100.0, 60.0, 72.0 - 1.0);
+        let expected = [
+            [x + 12.0, y + 12.0],
+            [x + 12.0 + side, y + 12.0],
+            [x + 12.0 + side, y + 12.0 + side],
+            [x + 12.0, y + 12.0 + side],
+        ];
+        for k in 0..4 {
+            let found = detect_markers(&scene(&marker, 100, 60, k), &dictionary);
+            assert_eq!(found.len(), 1, "rotation {}", k);
+            assert_eq!(found[0].id, 7);
+            // 顺时针旋转 k 次后, 标记左上角落在第 k 个外角
+            let tl = expected[k as usize];
+            let corner = found[0].corners[0];
+            assert!(
+                (corner[0] - tl[0]).abs() <= 1.0 && (corner[1] - tl[1]).abs() <= 1.0,
+                "rotation {}: {:?} vs {:?}",
+                k,
+                corner,
+                tl
+            );
+        }
+    }
+
+    /// 相机平移后, 锚定单应把新画面中的点映射到与原画面相同的地面坐标
+    #[test]
+    fn test_anchor_recovers_camera_shift() {
+        let to_image = |x: f32, y: f32| {
+            let w = 0.002 * y + 1.0;
+            [(400.0 + 60.0 * x) / w, (300.0 + 40.0 * y) / w]
+        };
+        let points = [(0.0, 0.0), (5.0, 0.0), (5.0, 10.0), (0.0, 10.0)]
+            .map(|(x, y)| GroundPoint {
+                image: to_image(x, y),
+                world: [x, y],
+            })
+            .to_vec();
+        let reference = Marker {
+            id: 3,
+            corners: [
+                [100.0, 100.0],
+                [160.0, 102.0],
+                [158.0, 160.0],
+                [99.0, 158.0],
+            ],
+        };
+        let calibration = GroundCalibration {
+            points,
+            markers: vec![reference],
+        };
+
+        // 画面整体右移 25px、上移 12px
+        let shifted = |p: [f32; 2]| [p[0] + 25.0, p[1] - 12.0];
+        let current = Marker {
+            id: 3,
+            corners: reference.corners.map(shifted),
+        };
+        let anchor = anchored_homography(&calibration, &[current]).unwrap();
+        assert_eq!(anchor.matched, 1);
+        assert!((anchor.shift_px - (25.0f32.powi(2) + 12.0f32.powi(2)).sqrt()).abs() < 1e-3);
+
+        let p = to_image(3.0, 6.0);
+        let [u, v] = shifted(p);
+        let (gx, gy) = anchor.homography.project(u, v).unwrap();
+        assert!(
+            (gx - 3.0).abs() < 1e-2 && (gy - 6.0).abs() < 1e-2,
+            "({}, {})",
+            gx,
+            gy
+        );
+
+        // 未知ID不参与锚定
+        let other = Marker { id: 9, ..current };
+        assert!(anchored_homography(&calibration, &[other]).is_none());
+    }
+}
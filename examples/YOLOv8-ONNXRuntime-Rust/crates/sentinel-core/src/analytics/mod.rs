@@ -0,0 +1,57 @@
+//! 场景分析 (Scene Analytics)
+//!
+//! 基于检测/跟踪结果的上层分析
+//! - GroundCalibration: 图像 → 地面平面单应映射 (米)
+//! - SpeedEstimator: 按轨迹的地面速度估计 (米/秒)
+//! - BestShotGallery: 每条轨迹的最佳快照 (尺寸/清晰度/正面程度打分), 生成图库页面
+//! - MarkerAnchor: ArUco/AprilTag 基准标记检测, 相机偏移后按标记重新锚定地面标定
+//! - LeftBehindMonitor: 遗留物 / 看护物体移除检测
+//! - TamperMonitor: 摄像头遮挡 / 失焦 / 移位检测, 触发 TamperEvent
+//! - ZoneEngine: 区域规则引擎, 触发 ZoneEvent
+//! - EventVerifier: 区域事件的二次复核 (大模型重新检测目标裁剪图)
+//! - RunReport: 一次运行的检测统计 (类别/尺寸/按小时), 导出 JSON 与 HTML
+//! - VisitorCounter: 按 ReID 特征在统计周期内去重的访客计数, 每小时导出 CSV
+//! - OverlapDedup: 重叠视野的跨摄像头检测去重 (地面坐标相近的检测归并到编号小的流)
+//!
+//! 订阅检测结果或加载模型的分析 (图库/遗留物/复核/区域/统计/访客) 需要 `ort` 功能
+
+pub mod calibration;
+#[cfg(feature = "ort")]
+pub mod gallery;
+#[cfg(feature = "ort")]
+pub mod left_behind;
+pub mod markers;
+pub mod overlap;
+#[cfg(feature = "ort")]
+pub mod report;
+pub mod speed;
+pub mod tamper;
+#[cfg(feature = "ort")]
+pub mod verify;
+#[cfg(feature = "ort")]
+pub mod visitors;
+#[cfg(feature = "ort")]
+pub mod zones;
+
+// Re-exports
+pub use calibration::{GroundCalibration, GroundPoint, Homography, GROUND_CALIBRATION_PATH};
+#[cfg(feature = "ort")]
+pub use gallery::{BestShotGallery, GalleryEntry, ShotQuality, GALLERY_DIR};
+#[cfg(feature = "ort")]
+pub use left_behind::{
+    LeftBehindConfig, LeftBehindEvent, LeftBehindMonitor, SceneChange, LEFT_BEHIND_CONFIG_PATH,
+};
+pub use markers::{Marker, MarkerAnchor, MarkerDetections, MarkerDictionary};
+pub use overlap::{OverlapConfig, OverlapDedup, OVERLAP_CONFIG_PATH};
+#[cfg(feature = "ort")]
+pub use report::{ClassStats, RunReport};
+pub use speed::SpeedEstimator;
+pub use tamper::{TamperConfig, TamperEvent, TamperKind, TamperMonitor, TAMPER_CONFIG_PATH};
+#[cfg(feature = "ort")]
+pub use verify::{EventVerifier, Verification, VerifyConfig};
+#[cfg(feature = "ort")]
+pub use visitors::{
+    HourlyVisitors, VisitorConfig, VisitorCounter, VisitorState, VISITOR_CONFIG_PATH,
+};
+#[cfg(feature = "ort")]
+pub use zones::{Zone, ZoneConfig, ZoneEngine, ZoneEvent, ZoneObject, ZoneRule, ZONE_CONFIG_PATH};
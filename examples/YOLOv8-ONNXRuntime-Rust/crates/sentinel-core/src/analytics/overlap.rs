@@ -0,0 +1,284 @@
+//! 重叠视野的跨摄像头检测去重
+//!
+//! 两路摄像头看到同一个门口时, 同一个人在两路结果中各出现一次, 区域计数与事件随之重复.
+//! 每路逻辑流标定自己的画面到共同地面坐标系 (米) 的单应映射, 检测框脚点投影到地面后:
+//! 落在重叠区域 (`regions`, 为空时为整个地面) 内、与编号更小的逻辑流最近 `max_age_ms`
+//! 内的某个检测相距不超过 `tolerance_m` 的检测视为同一目标, 归并到编号更小的流.
+//! 重复的检测仍然显示和跟踪, 但区域规则、检测统计与访客计数不再计入.
+//! 每个检测最多与另一路的一个检测归并 (按距离从近到远一一配对), 人群中不会吞掉相邻的人
+//!
+//! ```json
+//! {
+//!   "tolerance_m": 0.6,
+//!   "max_age_ms": 300,
+//!   "regions": [[[0, 0], [4, 0], [4, 3], [0, 3]]],
+//!   "views": {
+//!     "0": [{ "image": [120, 680], "world": [0, 0] }, ...],
+//!     "1": [{ "image": [1710, 640], "world": [0, 0] }, ...]
+//!   }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::calibration::{GroundPoint, Homography};
+use crate::detection::types::BBox;
+
+/// 默认配置文件路径
+pub const OVERLAP_CONFIG_PATH: &str = "overlap.json";
+
+/// 进程内的去重状态 (各检测线程共享), 未启用时为 None
+static DEDUP: OnceLock<Mutex<OverlapDedup>> = OnceLock::new();
+
+/// 重叠视野去重配置 (overlap.json)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlapConfig {
+    pub tolerance_m: f32,            // 地面距离不超过该值视为同一目标 (米)
+    pub max_age_ms: u64,             // 其他流的检测超过该时长后不再参与比较
+    pub regions: Vec<Vec<[f32; 2]>>, // 重叠区域 (共同地面坐标多边形), 为空时不限区域
+    pub views: BTreeMap<u32, Vec<GroundPoint>>, // 逻辑流 → 画面到共同地面的标定点 (至少4个)
+}
+
+impl Default for OverlapConfig {
+    fn default() -> Self {
+        Self {
+            tolerance_m: 0.6,
+            max_age_ms: 300,
+            regions: Vec::new(),
+            views: BTreeMap::new(),
+        }
+    }
+}
+
+impl OverlapConfig {
+    /// 从文件加载配置, 文件不存在或解析失败时为 None (不去重)
+    pub fn load(path: &str) -> Option<Self> {
+        let json = fs::read_to_string(path).ok()?;
+        match serde_json::from_str::<Self>(&json) {
+            Ok(config) => {
+                println!(
+                    "✅ 重叠视野去重配置已从 {} 加载 ({} 路流, 容差 {:.2}m)",
+                    path,
+                    config.views.len(),
+                    config.tolerance_m
+                );
+                Some(config)
+            }
+            Err(e) => {
+                eprintln!("⚠️  重叠视野去重配置解析失败: {}, 不启用", e);
+                None
+            }
+        }
+    }
+}
+
+/// 射线法判断点是否在多边形内
+fn polygon_contains(polygon: &[[f32; 2]], x: f32, y: f32) -> bool {
+    let n = polygon.len();
+    if n < 3 {
+        return false;
+    }
+    let mut inside = false;
+    let mut j = n - 1;
+    for i in 0..n {
+        let [xi, yi] = polygon[i];
+        let [xj, yj] = polygon[j];
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// 某路流最近一帧的检测脚点 (共同地面坐标)
+struct ViewFrame {
+    time: Instant,
+    points: Vec<(f32, f32)>,
+}
+
+/// 跨逻辑流的检测去重
+pub struct OverlapDedup {
+    config: OverlapConfig,
+    homographies: BTreeMap<u32, Homography>,
+    recent: BTreeMap<u32, ViewFrame>,
+    merged: u64,
+}
+
+impl OverlapDedup {
+    /// 按各流的标定点求解单应映射, 标定无效的流不参与去重
+    pub fn new(config: OverlapConfig) -> Self {
+        let mut homographies = BTreeMap::new();
+        for (&view, points) in &config.views {
+            match Homography::from_points(points) {
+                Some(h) => {
+                    homographies.insert(view, h);
+                }
+                None => eprintln!(
+                    "⚠️  逻辑流 {} 的重叠视野标定无效 (至少4个不共线的点), 不参与去重",
+                    view
+                ),
+            }
+        }
+        Self {
+            config,
+            homographies,
+            recent: BTreeMap::new(),
+            merged: 0,
+        }
+    }
+
+    /// 累计归并的检测数
+    pub fn merged(&self) -> u64 {
+        self.merged
+    }
+
+    fn in_region(&self, (x, y): (f32, f32)) -> bool {
+        self.config.regions.is_empty()
+            || self
+                .config
+                .regions
+                .iter()
+                .any(|region| polygon_contains(region, x, y))
+    }
+
+    /// 记入逻辑流 view 的一帧检测, 返回每个检测是否与编号更小的流重复;
+    /// 该流未标定时返回空表
+    pub fn mark(&mut self, view: u32, bboxes: &[BBox], now: Instant) -> Vec<bool> {
+        let Some(homography) = self.homographies.get(&view) else {
+            return Vec::new();
+        };
+        let points: Vec<Option<(f32, f32)>> =
+            bboxes.iter().map(|b| homography.project_box(b)).collect();
+
+        // 与编号更小的流中仍然新鲜的检测配对, 按距离从近到远一一归并
+        let max_age = Duration::from_millis(self.config.max_age_ms);
+        let tolerance = self.config.tolerance_m;
+        let mut candidates = Vec::new();
+        for (&other, frame) in self.recent.range(..view) {
+            if now.saturating_duration_since(frame.time) > max_age {
+                continue;
+            }
+            for (i, p) in points.iter().enumerate() {
+                let Some(p) = p.filter(|&p| self.in_region(p)) else {
+                    continue;
+                };
+                for (j, q) in frame.points.iter().enumerate() {
+                    let d = ((p.0 - q.0).powi(2) + (p.1 - q.1).powi(2)).sqrt();
+                    if d <= tolerance {
+                        candidates.push((d, i, other, j));
+                    }
+                }
+            }
+        }
+        candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut duplicates = vec![false; bboxes.len()];
+        let mut taken = Vec::new();
+        for (_, i, other, j) in candidates {
+            if duplicates[i] || taken.contains(&(other, j)) {
+                continue;
+            }
+            duplicates[i] = true;
+            taken.push((other, j));
+        }
+        self.merged += taken.len() as u64;
+
+        self.recent.insert(
+            view,
+            ViewFrame {
+                time: now,
+                points: points.into_iter().flatten().collect(),
+            },
+        );
+        duplicates
+    }
+}
+
+/// 启用重叠视野去重 (之后处理的帧生效)
+pub fn install(config: OverlapConfig) {
+    let _ = DEDUP.set(Mutex::new(OverlapDedup::new(config)));
+}
+
+/// 记入逻辑流的一帧检测, 返回每个检测是否重复; 未启用或该流未标定时返回空表
+pub fn mark_duplicates(view: u32, bboxes: &[BBox]) -> Vec<bool> {
+    DEDUP.get().map_or_else(Vec::new, |dedup| {
+        dedup.lock().unwrap().mark(view, bboxes, Instant::now())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 画面像素 / 100 = 米, 再平移 offset
+    fn points(offset: f32) -> Vec<GroundPoint> {
+        [(0.0, 0.0), (100.0, 0.0), (0.0, 100.0), (100.0, 100.0)]
+            .iter()
+            .map(|&(x, y)| GroundPoint {
+                image: [x, y],
+                world: [x / 100.0 + offset, y / 100.0],
+            })
+            .collect()
+    }
+
+    /// 脚点 (底边中点) 在 (x, y) 像素的框
+    fn foot(x: f32, y: f32) -> BBox {
+        BBox {
+            x1: x - 10.0,
+            y1: y - 50.0,
+            x2: x + 10.0,
+            y2: y,
+            confidence: 0.9,
+            class_id: 0,
+        }
+    }
+
+    fn dedup(regions: Vec<Vec<[f32; 2]>>) -> OverlapDedup {
+        OverlapDedup::new(OverlapConfig {
+            regions,
+            views: [(0, points(0.0)), (1, points(2.0))].into_iter().collect(),
+            ..Default::default()
+        })
+    }
+
+    /// 两路看到同一地面位置的目标时归并到编号小的流, 未标定的流与过期的检测不参与
+    #[test]
+    fn test_merge_into_lower_view() {
+        let t0 = Instant::now();
+        let mut dedup = dedup(Vec::new());
+        // 地面 (2.5, 0.5): 流 0 的像素 (250, 50), 流 1 的像素 (50, 50)
+        assert_eq!(dedup.mark(0, &[foot(250.0, 50.0)], t0), vec![false]);
+        assert_eq!(
+            dedup.mark(1, &[foot(60.0, 50.0), foot(50.0, 300.0)], t0),
+            vec![true, false]
+        );
+        assert_eq!(dedup.merged(), 1);
+        assert!(dedup.mark(2, &[foot(50.0, 50.0)], t0).is_empty());
+
+        let late = t0 + Duration::from_millis(400);
+        assert_eq!(dedup.mark(1, &[foot(50.0, 50.0)], late), vec![false]);
+    }
+
+    /// 只在重叠区域内去重, 一个检测只吸收另一路的一个检测
+    #[test]
+    fn test_region_and_one_to_one() {
+        let t0 = Instant::now();
+        let mut dedup = dedup(vec![vec![[2.0, 0.0], [3.0, 0.0], [3.0, 1.0], [2.0, 1.0]]]);
+        dedup.mark(0, &[foot(250.0, 50.0), foot(350.0, 50.0)], t0);
+        // 两人并排站在流 0 的一个检测附近, 只有更近的一个被归并; 区域外的不比较
+        assert_eq!(
+            dedup.mark(
+                1,
+                &[foot(70.0, 50.0), foot(50.0, 50.0), foot(150.0, 50.0)],
+                t0
+            ),
+            vec![false, true, false]
+        );
+    }
+}
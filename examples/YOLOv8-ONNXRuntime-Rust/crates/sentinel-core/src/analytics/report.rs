@@ -0,0 +1,309 @@
+//! 运行统计报告 (Run report)
+//!
+//! 处理完一个图片目录或一段视频后汇总检测结果: 各类别检测数与平均置信度、目标尺寸分布、
+//! 按小时的检测数, 保存为 JSON (便于脚本比对) 与自包含的 HTML (浏览器直接打开),
+//! 用于检查模型在用户自己素材上的表现是否符合预期
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::detection::detector::DetectionResult;
+use crate::utils::clock;
+use crate::xbus::{self, Subscription};
+
+/// 实时视频统计的保存间隔 (进程随窗口关闭退出, 只能定期落盘)
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 尺寸分档边界 (框面积开方, 像素): <16, 16-32, 32-64, 64-128, 128-256, ≥256
+pub const SIZE_BIN_EDGES: [f32; 5] = [16.0, 32.0, 64.0, 128.0, 256.0];
+
+/// 尺寸分档数
+pub const SIZE_BINS: usize = SIZE_BIN_EDGES.len() + 1;
+
+/// 单个类别的统计
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClassStats {
+    pub count: u64,
+    pub mean_confidence: f32,
+    pub sizes: [u64; SIZE_BINS], // 按 SIZE_BIN_EDGES 分档的检测数
+}
+
+/// 一次运行的统计报告
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunReport {
+    pub source: String,   // 输入目录或视频地址
+    pub started: String,  // 开始时间 (RFC 3339)
+    pub finished: String, // 最近一次保存的时间 (RFC 3339)
+    pub frames: u64,      // 处理的图片/帧数
+    pub frames_with_detections: u64,
+    pub detections: u64,
+    pub classes: BTreeMap<String, ClassStats>,
+    pub sizes: [u64; SIZE_BINS],
+    pub per_hour: BTreeMap<String, u64>, // "YYYY-MM-DD HH:00" → 检测数, 时间未知的帧不计入
+}
+
+/// 框的尺寸分档
+pub fn size_bin(width: f32, height: f32) -> usize {
+    let side = (width.max(0.0) * height.max(0.0)).sqrt();
+    SIZE_BIN_EDGES
+        .iter()
+        .position(|&edge| side < edge)
+        .unwrap_or(SIZE_BIN_EDGES.len())
+}
+
+/// 尺寸分档名称 (如 "16-32")
+pub fn size_bin_label(bin: usize) -> String {
+    match bin {
+        0 => format!("<{}", SIZE_BIN_EDGES[0]),
+        b if b >= SIZE_BIN_EDGES.len() => format!("≥{}", SIZE_BIN_EDGES[SIZE_BIN_EDGES.len() - 1]),
+        b => format!("{}-{}", SIZE_BIN_EDGES[b - 1], SIZE_BIN_EDGES[b]),
+    }
+}
+
+impl RunReport {
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            started: clock::now().to_rfc3339(),
+            ..Default::default()
+        }
+    }
+
+    /// 记录一帧的检测结果, 每个检测为 (类别名, 置信度, 宽, 高)
+    ///
+    /// `time` 为帧的拍摄时间, 用于按小时统计; 未知时传 None
+    pub fn record<'a>(
+        &mut self,
+        time: Option<DateTime<Local>>,
+        detections: impl IntoIterator<Item = (&'a str, f32, f32, f32)>,
+    ) {
+        let mut count = 0;
+        for (class, confidence, width, height) in detections {
+            let bin = size_bin(width, height);
+            let stats = self.classes.entry(class.to_string()).or_default();
+            stats.count += 1;
+            stats.mean_confidence += (confidence - stats.mean_confidence) / stats.count as f32;
+            stats.sizes[bin] += 1;
+            self.sizes[bin] += 1;
+            count += 1;
+        }
+        self.frames += 1;
+        self.detections += count;
+        if count > 0 {
+            self.frames_with_detections += 1;
+        }
+        if let Some(time) = time {
+            *self
+                .per_hour
+                .entry(clock::format(time, "%Y-%m-%d %H:00"))
+                .or_default() += count;
+        }
+    }
+
+    /// 订阅检测结果做实时视频统计, 按墙钟时间分小时, 每分钟保存到 `<stem>.json/html`
+    ///
+    /// 启用跟踪器时 class_id 为轨迹ID, 这些框统一计入 "跟踪目标"; 否则按 class_id 计入 "#<id>"
+    /// (检测线程不知道类别名). 回调在检测线程上执行, 订阅须在运行期间保持
+    pub fn start(self, stem: impl Into<PathBuf>) -> Subscription {
+        let stem = stem.into();
+        println!("📊 检测统计报告: {}.html (每分钟更新)", stem.display());
+        let state = Mutex::new((self, Instant::now()));
+        xbus::subscribe::<DetectionResult, _>(move |result| {
+            let mut state = state.lock().unwrap();
+            let (report, last_save) = &mut *state;
+            let labels: Vec<String> = result
+                .bboxes
+                .iter()
+                .map(|b| {
+                    if result.tracked {
+                        "跟踪目标".to_string()
+                    } else {
+                        format!("#{}", b.class_id)
+                    }
+                })
+                .collect();
+            // 重叠视野中的重复检测已由编号更小的流计入
+            let duplicate = |i: usize| result.duplicates.get(i).copied().unwrap_or(false);
+            report.record(
+                Some(Local::now()),
+                result
+                    .bboxes
+                    .iter()
+                    .zip(&labels)
+                    .enumerate()
+                    .filter(|&(i, _)| !duplicate(i))
+                    .map(|(_, (b, label))| {
+                        (label.as_str(), b.confidence, b.x2 - b.x1, b.y2 - b.y1)
+                    }),
+            );
+            if last_save.elapsed() >= SAVE_INTERVAL {
+                *last_save = Instant::now();
+                if let Err(e) = report.save(&stem) {
+                    eprintln!("⚠️ {:#}", e);
+                }
+            }
+        })
+    }
+
+    /// 保存为 `<stem>.json` 与 `<stem>.html`, 返回 HTML 路径
+    pub fn save(&mut self, stem: impl AsRef<Path>) -> Result<PathBuf> {
+        self.finished = clock::now().to_rfc3339();
+        let stem = stem.as_ref();
+        let json_path = stem.with_extension("json");
+        let html_path = stem.with_extension("html");
+        fs::write(&json_path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("保存统计报告失败: {}", json_path.display()))?;
+        fs::write(&html_path, self.to_html())
+            .with_context(|| format!("保存统计报告失败: {}", html_path.display()))?;
+        Ok(html_path)
+    }
+
+    /// 自包含的 HTML 报告 (内联样式, 用横条表示占比)
+    pub fn to_html(&self) -> String {
+        let mut classes: Vec<(&String, &ClassStats)> = self.classes.iter().collect();
+        classes.sort_by(|a, b| b.1.count.cmp(&a.1.count).then(a.0.cmp(b.0)));
+        let max_class = classes.first().map_or(0, |c| c.1.count);
+
+        let mut html = String::new();
+        html.push_str(concat!(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>检测统计报告</title>\n",
+            "<style>body{font-family:sans-serif;margin:2em}table{border-collapse:collapse;margin-bottom:2em}",
+            "td,th{padding:4px 10px;border-bottom:1px solid #ddd;text-align:left}",
+            ".bar{background:#4a90d9;height:12px;min-width:1px}</style></head><body>\n",
+        ));
+        html.push_str(&format!(
+            "<h1>检测统计报告</h1>\n<p>输入: {}<br>时间: {} ~ {}<br>帧数: {} (有检测 {}), 检测数: {}</p>\n",
+            escape(&self.source),
+            escape(&self.started),
+            escape(&self.finished),
+            self.frames,
+            self.frames_with_detections,
+            self.detections
+        ));
+
+        html.push_str("<h2>各类别检测数</h2>\n<table><tr><th>类别</th><th>数量</th><th>平均置信度</th><th></th>");
+        for bin in 0..SIZE_BINS {
+            html.push_str(&format!("<th>{}</th>", escape(&size_bin_label(bin))));
+        }
+        html.push_str("</tr>\n");
+        for (name, stats) in &classes {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{:.3}</td><td>{}</td>",
+                escape(name),
+                stats.count,
+                stats.mean_confidence,
+                bar(stats.count, max_class)
+            ));
+            for count in stats.sizes {
+                html.push_str(&format!("<td>{}</td>", count));
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+
+        let max_size = self.sizes.iter().copied().max().unwrap_or(0);
+        html.push_str("<h2>尺寸分布 (框面积开方, 像素)</h2>\n<table><tr><th>尺寸</th><th>数量</th><th></th></tr>\n");
+        for (bin, &count) in self.sizes.iter().enumerate() {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                escape(&size_bin_label(bin)),
+                count,
+                bar(count, max_size)
+            ));
+        }
+        html.push_str("</table>\n");
+
+        if !self.per_hour.is_empty() {
+            let max_hour = self.per_hour.values().copied().max().unwrap_or(0);
+            html.push_str(
+                "<h2>按小时检测数</h2>\n<table><tr><th>时间</th><th>数量</th><th></th></tr>\n",
+            );
+            for (hour, &count) in &self.per_hour {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    escape(hour),
+                    count,
+                    bar(count, max_hour)
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+        html.push_str("</body></html>\n");
+        html
+    }
+}
+
+/// 占比横条 (最长 300 像素)
+fn bar(value: u64, max: u64) -> String {
+    let width = (value * 300).checked_div(max).unwrap_or(0);
+    format!("<div class=\"bar\" style=\"width:{}px\"></div>", width)
+}
+
+/// HTML 转义 (类别名/路径来自用户数据)
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// 类别计数/平均置信度/尺寸分档/按小时统计累加正确
+    #[test]
+    fn test_record() {
+        let mut report = RunReport::new("clips/gate.mp4");
+        let t1 = Local.with_ymd_and_hms(2024, 5, 1, 8, 15, 0).unwrap();
+        let t2 = Local.with_ymd_and_hms(2024, 5, 1, 8, 59, 0).unwrap();
+        report.record(
+            Some(t1),
+            [("person", 0.9, 10.0, 10.0), ("person", 0.5, 40.0, 40.0)],
+        );
+        report.record(Some(t2), [("car", 0.8, 300.0, 300.0)]);
+        report.record(None, []);
+
+        assert_eq!((report.frames, report.frames_with_detections), (3, 2));
+        assert_eq!(report.detections, 3);
+        let person = &report.classes["person"];
+        assert_eq!(person.count, 2);
+        assert!((person.mean_confidence - 0.7).abs() < 1e-6);
+        assert_eq!(person.sizes, [1, 0, 1, 0, 0, 0]);
+        assert_eq!(report.sizes, [1, 0, 1, 0, 0, 1]);
+        assert_eq!(report.per_hour["2024-05-01 08:00"], 3);
+        assert_eq!(size_bin_label(0), "<16");
+        assert_eq!(size_bin_label(2), "32-64");
+        assert_eq!(size_bin_label(5), "≥256");
+    }
+
+    /// JSON 可读回, HTML 按数量排序并转义类别名
+    #[test]
+    fn test_save() {
+        let mut report = RunReport::new("images/");
+        report.record(
+            None,
+            [("a<b>", 0.6, 20.0, 20.0), ("person", 0.9, 20.0, 20.0)],
+        );
+        report.record(None, [("person", 0.9, 20.0, 20.0)]);
+        let stem = std::env::temp_dir().join(format!("run_report_{}", std::process::id()));
+        let html_path = report.save(&stem).unwrap();
+
+        let json = fs::read_to_string(stem.with_extension("json")).unwrap();
+        assert_eq!(serde_json::from_str::<RunReport>(&json).unwrap(), report);
+        let html = fs::read_to_string(&html_path).unwrap();
+        assert!(html.contains("a&lt;b&gt;"));
+        assert!(html.find("person").unwrap() < html.find("a&lt;b&gt;").unwrap());
+        assert!(!html.contains("按小时检测数"));
+        fs::remove_file(stem.with_extension("json")).unwrap();
+        fs::remove_file(html_path).unwrap();
+    }
+}
@@ -0,0 +1,111 @@
+//! 速度估计 (Per-track speed estimation)
+//!
+//! 按轨迹ID记录上一次的地面坐标与采集时间戳, 相邻两次观测的位移/时间差得到瞬时速度,
+//! 再对速度向量做指数平滑抑制脚点抖动. 时间戳取解码时刻, 检测线程积压时不会把排队
+//! 延迟算进速度
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// 速度向量的指数平滑系数 (越小越平滑, 响应越慢)
+const SPEED_SMOOTHING: f32 = 0.3;
+
+/// 两次观测间隔超过该值视为轨迹中断, 重新开始估计
+const MAX_OBSERVATION_GAP: Duration = Duration::from_secs(1);
+
+/// 单条轨迹的运动状态
+struct TrackMotion {
+    position: (f32, f32),
+    time: Instant,
+    velocity: Option<(f32, f32)>, // 平滑后的速度向量 (米/秒)
+}
+
+/// 按轨迹的速度估计器
+#[derive(Default)]
+pub struct SpeedEstimator {
+    tracks: HashMap<u32, TrackMotion>,
+}
+
+impl SpeedEstimator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 清空全部轨迹 (切换跟踪器或标定变化时ID/坐标不再连续)
+    pub fn reset(&mut self) {
+        self.tracks.clear();
+    }
+
+    /// 更新一帧的 (轨迹ID, 地面坐标), 返回与输入一一对应的速度 (米/秒)
+    ///
+    /// 新轨迹的第一帧以及没有地面坐标的目标速度为 None
+    pub fn update(
+        &mut self,
+        objects: &[(u32, Option<(f32, f32)>)],
+        time: Instant,
+    ) -> Vec<Option<f32>> {
+        self.tracks
+            .retain(|_, m| time.saturating_duration_since(m.time) <= MAX_OBSERVATION_GAP);
+
+        objects
+            .iter()
+            .map(|&(id, position)| {
+                let position = position?;
+                let motion = self.tracks.entry(id).or_insert(TrackMotion {
+                    position,
+                    time,
+                    velocity: None,
+                });
+                let dt = time.saturating_duration_since(motion.time).as_secs_f32();
+                if dt < 1e-3 {
+                    // 同一时间戳 (新轨迹或重复帧): 沿用上次的估计
+                    return motion.velocity.map(|(vx, vy)| (vx * vx + vy * vy).sqrt());
+                }
+                let raw = (
+                    (position.0 - motion.position.0) / dt,
+                    (position.1 - motion.position.1) / dt,
+                );
+                let (vx, vy) = match motion.velocity {
+                    Some((vx, vy)) => (
+                        vx + SPEED_SMOOTHING * (raw.0 - vx),
+                        vy + SPEED_SMOOTHING * (raw.1 - vy),
+                    ),
+                    None => raw,
+                };
+                motion.position = position;
+                motion.time = time;
+                motion.velocity = Some((vx, vy));
+                Some((vx * vx + vy * vy).sqrt())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 匀速运动收敛到真实速度, 无地面坐标的目标没有速度, 中断后重新估计
+    #[test]
+    fn test_constant_speed() {
+        let mut estimator = SpeedEstimator::new();
+        let start = Instant::now();
+        let mut speeds = Vec::new();
+        for i in 0..30 {
+            let t = i as f32 * 0.1;
+            // 轨迹1以 2m/s 沿 x 移动, 叠加 ±5cm 的脚点抖动
+            let jitter = if i % 2 == 0 { 0.05 } else { -0.05 };
+            speeds = estimator.update(
+                &[(1, Some((2.0 * t + jitter, 3.0))), (2, None)],
+                start + Duration::from_secs_f32(t),
+            );
+        }
+        let speed = speeds[0].unwrap();
+        assert!((speed - 2.0).abs() < 0.5, "{}", speed);
+        assert!(speeds[1].is_none());
+
+        // 超过最大间隔后轨迹重新开始, 第一帧没有速度
+        let speeds = estimator.update(&[(1, Some((20.0, 3.0)))], start + Duration::from_secs(10));
+        assert_eq!(speeds, vec![None]);
+    }
+}
@@ -0,0 +1,489 @@
+//! 摄像头篡改 / 画面突变检测 (Camera tamper detection)
+//!
+//! 与目标检测无关, 只看整帧统计量: 降采样灰度图的亮度直方图、平均梯度 (边缘能量) 与
+//! 粗网格亮度布局. 与缓慢自适应的参考相比:
+//! - 遮挡: 直方图集中在很窄的亮度区间 (手掌、喷漆、镜头盖、强光致盲)
+//! - 失焦: 边缘能量大幅下降但画面布局不变
+//! - 移位: 画面布局与参考的相关系数过低 (被转动、碰歪)
+//!
+//! 异常持续超过驻留时间触发 TamperEvent, 恢复同样需要持续驻留时间
+
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::detection::types::DecodedFrame;
+use crate::models::PreprocessSpec;
+use crate::xbus::{self, Subscription};
+
+/// 默认配置文件路径
+pub const TAMPER_CONFIG_PATH: &str = "tamper.json";
+
+/// 统计用灰度图的最大宽度 (点采样)
+const SAMPLE_WIDTH: usize = 320;
+
+/// 布局网格尺寸
+const LAYOUT_W: usize = 32;
+const LAYOUT_H: usize = 18;
+
+/// 直方图分箱数
+const HIST_BINS: usize = 32;
+
+/// 遮挡判定时统计的相邻分箱数
+const PEAK_BINS: usize = 3;
+
+/// 篡改检测配置 (tamper.json)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TamperConfig {
+    pub process_fps: f32,       // 统计频率
+    pub dwell_secs: f32,        // 异常/恢复持续多久才报告
+    pub learn_secs: f32,        // 参考的自适应时间常数 (跟随光照缓变)
+    pub covered_fraction: f32,  // 最集中的 3 个亮度分箱占比超过该值视为遮挡
+    pub defocus_ratio: f32,     // 边缘能量低于参考的该比例视为失焦
+    pub moved_correlation: f32, // 布局相关系数低于该值视为移位
+    pub view: u32,              // 处理的逻辑流
+}
+
+impl Default for TamperConfig {
+    fn default() -> Self {
+        Self {
+            process_fps: 2.0,
+            dwell_secs: 3.0,
+            learn_secs: 300.0,
+            covered_fraction: 0.85,
+            defocus_ratio: 0.4,
+            moved_correlation: 0.5,
+            view: 0,
+        }
+    }
+}
+
+impl TamperConfig {
+    /// 从文件加载配置, 文件不存在时使用默认配置
+    pub fn load(path: &str) -> Self {
+        match fs::read_to_string(path) {
+            Ok(json) => match serde_json::from_str::<Self>(&json) {
+                Ok(config) => {
+                    println!("✅ 篡改检测配置已从 {} 加载", path);
+                    config
+                }
+                Err(e) => {
+                    eprintln!("⚠️  篡改检测配置解析失败: {}, 使用默认配置", e);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+}
+
+/// 篡改类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TamperKind {
+    /// 镜头被遮挡 / 致盲
+    Covered,
+    /// 失焦 / 镜头脏污
+    Defocused,
+    /// 画面被转动或移位
+    Moved,
+}
+
+impl TamperKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TamperKind::Covered => "镜头遮挡",
+            TamperKind::Defocused => "画面失焦",
+            TamperKind::Moved => "画面移位",
+        }
+    }
+}
+
+/// 篡改事件: 异常开始 (active) 或恢复
+#[derive(Clone, Debug)]
+pub struct TamperEvent {
+    pub kind: TamperKind,
+    pub active: bool,
+    // 触发指标: 遮挡为亮度集中度, 失焦为边缘能量比, 移位为布局相关系数
+    pub score: f32,
+    pub view: u32,
+    pub time: chrono::DateTime<chrono::Local>,
+}
+
+/// 一帧的全局统计量
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameStats {
+    pub histogram: Vec<f32>, // 归一化亮度直方图
+    pub edge: f32,           // 相邻采样点平均亮度差
+    pub layout: Vec<f32>,    // 粗网格平均亮度
+}
+
+impl FrameStats {
+    /// 由 RGBA 帧计算 (点采样到不超过 SAMPLE_WIDTH 宽)
+    pub fn from_rgba(rgba: &[u8], width: u32, height: u32) -> Self {
+        let (width, height) = (width as usize, height as usize);
+        let step = width.div_ceil(SAMPLE_WIDTH).max(1);
+        let (sw, sh) = (width / step, height / step);
+        let gray: Vec<u8> = (0..sh)
+            .flat_map(|y| (0..sw).map(move |x| ((y * step) * width + x * step) * 4))
+            .map(|p| PreprocessSpec::luma([rgba[p], rgba[p + 1], rgba[p + 2]]))
+            .collect();
+        Self::from_gray(&gray, sw, sh)
+    }
+
+    /// 由灰度图计算
+    pub fn from_gray(gray: &[u8], width: usize, height: usize) -> Self {
+        let mut histogram = vec![0.0f32; HIST_BINS];
+        for &v in gray {
+            histogram[v as usize * HIST_BINS / 256] += 1.0;
+        }
+        let total = gray.len().max(1) as f32;
+        histogram.iter_mut().for_each(|h| *h /= total);
+
+        let (mut edge, mut pairs) = (0u64, 0u64);
+        for y in 0..height.saturating_sub(1) {
+            for x in 0..width.saturating_sub(1) {
+                let v = gray[y * width + x] as i32;
+                edge += ((gray[y * width + x + 1] as i32 - v).abs()
+                    + (gray[(y + 1) * width + x] as i32 - v).abs()) as u64;
+                pairs += 2;
+            }
+        }
+
+        let mut layout = vec![0.0f32; LAYOUT_W * LAYOUT_H];
+        let mut counts = vec![0u32; LAYOUT_W * LAYOUT_H];
+        for y in 0..height {
+            for x in 0..width {
+                let cell = (y * LAYOUT_H / height) * LAYOUT_W + x * LAYOUT_W / width;
+                layout[cell] += gray[y * width + x] as f32;
+                counts[cell] += 1;
+            }
+        }
+        for (v, &n) in layout.iter_mut().zip(&counts) {
+            *v /= n.max(1) as f32;
+        }
+
+        Self {
+            histogram,
+            edge: edge as f32 / pairs.max(1) as f32,
+            layout,
+        }
+    }
+
+    /// 亮度最集中的 PEAK_BINS 个相邻分箱的占比
+    fn concentration(&self) -> f32 {
+        self.histogram
+            .windows(PEAK_BINS)
+            .map(|w| w.iter().sum::<f32>())
+            .fold(0.0, f32::max)
+    }
+
+    /// 与另一帧布局的归一化相关系数 (对整体亮度/对比度变化不敏感)
+    fn correlation(&self, other: &FrameStats) -> f32 {
+        let n = self.layout.len().min(other.layout.len());
+        if n == 0 {
+            return 1.0;
+        }
+        let mean = |v: &[f32]| v[..n].iter().sum::<f32>() / n as f32;
+        let (ma, mb) = (mean(&self.layout), mean(&other.layout));
+        let (mut ab, mut aa, mut bb) = (0.0, 0.0, 0.0);
+        for (a, b) in self.layout[..n].iter().zip(&other.layout[..n]) {
+            let (a, b) = (a - ma, b - mb);
+            ab += a * b;
+            aa += a * a;
+            bb += b * b;
+        }
+        if aa < 1e-3 || bb < 1e-3 {
+            // 平坦画面没有布局可比
+            return if aa < 1e-3 && bb < 1e-3 { 1.0 } else { 0.0 };
+        }
+        ab / (aa * bb).sqrt()
+    }
+
+    /// 按权重向另一帧靠近 (参考自适应)
+    fn blend(&mut self, other: &FrameStats, alpha: f32) {
+        let mix = |a: &mut f32, b: f32| *a += (b - *a) * alpha;
+        self.histogram
+            .iter_mut()
+            .zip(&other.histogram)
+            .for_each(|(a, &b)| mix(a, b));
+        self.layout
+            .iter_mut()
+            .zip(&other.layout)
+            .for_each(|(a, &b)| mix(a, b));
+        mix(&mut self.edge, other.edge);
+    }
+}
+
+/// 参考统计量与篡改状态机 (不含 IO, 便于测试)
+pub struct TamperDetector {
+    config: TamperConfig,
+    reference: Option<FrameStats>,
+    candidate: Option<(TamperKind, f32)>, // 疑似异常及其持续时间
+    active: Option<TamperKind>,
+    clear_secs: f32, // 异常期间恢复正常的持续时间
+}
+
+impl TamperDetector {
+    pub fn new(config: TamperConfig) -> Self {
+        Self {
+            config,
+            reference: None,
+            candidate: None,
+            active: None,
+            clear_secs: 0.0,
+        }
+    }
+
+    /// 当前的异常类型
+    pub fn active(&self) -> Option<TamperKind> {
+        self.active
+    }
+
+    /// 与参考比较, 返回异常类型与指标
+    fn classify(&self, stats: &FrameStats) -> Option<(TamperKind, f32)> {
+        let reference = self.reference.as_ref()?;
+        let c = &self.config;
+        let concentration = stats.concentration();
+        // 参考本身很平坦 (如夜间黑画面) 时不判遮挡
+        if concentration >= c.covered_fraction
+            && reference.concentration() < c.covered_fraction - 0.2
+        {
+            return Some((TamperKind::Covered, concentration));
+        }
+        let correlation = stats.correlation(reference);
+        if correlation < c.moved_correlation {
+            return Some((TamperKind::Moved, correlation));
+        }
+        let ratio = stats.edge / reference.edge.max(1e-3);
+        if ratio < c.defocus_ratio {
+            return Some((TamperKind::Defocused, ratio));
+        }
+        None
+    }
+
+    /// 输入一帧统计量与距上次的秒数, 返回异常开始或恢复的 (类型, 是否异常, 指标)
+    pub fn update(&mut self, stats: &FrameStats, dt: f32) -> Option<(TamperKind, bool, f32)> {
+        if self.reference.is_none() {
+            self.reference = Some(stats.clone());
+            return None;
+        }
+        let detected = self.classify(stats);
+        let dwell = self.config.dwell_secs;
+
+        if let Some(kind) = self.active {
+            if detected.is_some() {
+                self.clear_secs = 0.0;
+                return None;
+            }
+            self.clear_secs += dt;
+            if self.clear_secs < dwell {
+                return None;
+            }
+            self.active = None;
+            self.candidate = None;
+            return Some((kind, false, 0.0));
+        }
+
+        match detected {
+            Some((kind, score)) => {
+                let secs = match self.candidate {
+                    Some((k, secs)) if k == kind => secs + dt,
+                    _ => 0.0,
+                };
+                self.candidate = Some((kind, secs));
+                if secs >= dwell {
+                    self.active = Some(kind);
+                    self.clear_secs = 0.0;
+                    return Some((kind, true, score));
+                }
+            }
+            None => {
+                // 只在正常时跟随光照缓变
+                self.candidate = None;
+                let alpha = (dt / self.config.learn_secs.max(1.0)).min(1.0);
+                if let Some(reference) = self.reference.as_mut() {
+                    reference.blend(stats, alpha);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// 篡改检测 (独立线程, 订阅保持期间运行)
+pub struct TamperMonitor {
+    _frame_sub: Subscription,
+}
+
+impl TamperMonitor {
+    pub fn start(config: TamperConfig) -> Self {
+        println!(
+            "📷 篡改检测启动: 驻留{:.0}s, 处理{:.1}fps",
+            config.dwell_secs, config.process_fps
+        );
+        let view = config.view;
+        let (tx, rx) = crossbeam_channel::bounded::<DecodedFrame>(1);
+
+        // 只在解码线程上转交帧, 检测线程忙时直接丢弃
+        let frame_sub = xbus::subscribe::<DecodedFrame, _>(move |frame| {
+            if frame.view == view {
+                let _ = tx.try_send(frame.clone());
+            }
+        });
+
+        std::thread::spawn(move || {
+            let interval = 1.0 / config.process_fps.max(0.1);
+            let mut detector = TamperDetector::new(config);
+            let mut last: Option<std::time::Instant> = None;
+            let mut size = (0, 0);
+            for frame in rx {
+                let now = frame.trace.decode_ts;
+                let dt = match last {
+                    Some(t) => now.saturating_duration_since(t).as_secs_f32(),
+                    None => 0.0,
+                };
+                if last.is_some() && dt < interval {
+                    continue;
+                }
+                if frame.rgba_data.len() < (frame.width * frame.height * 4) as usize {
+                    continue;
+                }
+                last = Some(now);
+
+                // 分辨率变化 (切换输入源) 时重新学习参考
+                if size != (frame.width, frame.height) {
+                    size = (frame.width, frame.height);
+                    detector = TamperDetector::new(detector.config.clone());
+                }
+                let stats = FrameStats::from_rgba(&frame.rgba_data, frame.width, frame.height);
+                if let Some((kind, active, score)) = detector.update(&stats, dt) {
+                    if active {
+                        println!("📷 {} (逻辑流 {}, 指标 {:.2})", kind.label(), view, score);
+                    } else {
+                        println!("📷 {} 已恢复 (逻辑流 {})", kind.label(), view);
+                    }
+                    xbus::post(TamperEvent {
+                        kind,
+                        active,
+                        score,
+                        view,
+                        time: chrono::Local::now(),
+                    });
+                }
+            }
+            println!("📷 篡改检测线程退出");
+        });
+
+        Self {
+            _frame_sub: frame_sub,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 伪随机亮度块组成的纹理画面 (块边长 8 像素)
+    fn scene(width: usize, height: usize) -> Vec<u8> {
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x / 8, y / 8)))
+            .map(|(bx, by)| {
+                let h = (bx as u32).wrapping_mul(73_856_093) ^ (by as u32).wrapping_mul(19_349_663);
+                (h.wrapping_mul(2_654_435_761) >> 24) as u8
+            })
+            .collect()
+    }
+
+    /// 水平方向盒式模糊 + 垂直方向盒式模糊
+    fn blur(gray: &[u8], width: usize, height: usize, radius: usize) -> Vec<u8> {
+        let pass = |src: &[u8], horizontal: bool| -> Vec<u8> {
+            (0..height * width)
+                .map(|i| {
+                    let (x, y) = (i % width, i / width);
+                    let (mut sum, mut n) = (0u32, 0u32);
+                    for d in 0..=2 * radius {
+                        let (sx, sy) = if horizontal {
+                            ((x + d).checked_sub(radius), Some(y))
+                        } else {
+                            (Some(x), (y + d).checked_sub(radius))
+                        };
+                        if let (Some(sx), Some(sy)) = (sx, sy) {
+                            if sx < width && sy < height {
+                                sum += src[sy * width + sx] as u32;
+                                n += 1;
+                            }
+                        }
+                    }
+                    (sum / n) as u8
+                })
+                .collect()
+        };
+        pass(&pass(gray, true), false)
+    }
+
+    /// 连续输入同一统计量 (每次 0.5s), 收集事件
+    fn feed(
+        detector: &mut TamperDetector,
+        stats: &FrameStats,
+        times: usize,
+    ) -> Vec<(TamperKind, bool, f32)> {
+        (0..times)
+            .filter_map(|_| detector.update(stats, 0.5))
+            .collect()
+    }
+
+    /// 遮挡持续驻留时间后报告一次, 移开后恢复; 亮度整体变暗不算篡改
+    #[test]
+    fn test_covered_and_restored() {
+        let (w, h) = (320, 180);
+        let normal = FrameStats::from_gray(&scene(w, h), w, h);
+        let mut detector = TamperDetector::new(TamperConfig::default());
+        assert!(feed(&mut detector, &normal, 4).is_empty());
+
+        let dim: Vec<u8> = scene(w, h).iter().map(|v| v / 2).collect();
+        assert!(feed(&mut detector, &FrameStats::from_gray(&dim, w, h), 20).is_empty());
+
+        // 手掌挡住镜头: 暗且几乎均匀
+        let covered: Vec<u8> = (0..w * h).map(|i| 10 + (i % 3) as u8).collect();
+        let events = feed(&mut detector, &FrameStats::from_gray(&covered, w, h), 20);
+        assert_eq!(events.len(), 1);
+        assert_eq!((events[0].0, events[0].1), (TamperKind::Covered, true));
+        assert_eq!(detector.active(), Some(TamperKind::Covered));
+
+        let events = feed(&mut detector, &normal, 20);
+        assert_eq!(events.len(), 1);
+        assert_eq!((events[0].0, events[0].1), (TamperKind::Covered, false));
+        assert_eq!(detector.active(), None);
+    }
+
+    /// 模糊画面判为失焦, 平移画面判为移位
+    #[test]
+    fn test_defocused_and_moved() {
+        let (w, h) = (640, 360);
+        let gray = scene(w, h);
+        let stats = |g: &[u8]| {
+            let rgba: Vec<u8> = g.iter().flat_map(|&v| [v, v, v, 255]).collect();
+            FrameStats::from_rgba(&rgba, w as u32, h as u32)
+        };
+        let normal = stats(&gray);
+
+        let mut detector = TamperDetector::new(TamperConfig::default());
+        feed(&mut detector, &normal, 4);
+        let events = feed(&mut detector, &stats(&blur(&gray, w, h, 10)), 20);
+        assert_eq!(events.len(), 1);
+        assert_eq!((events[0].0, events[0].1), (TamperKind::Defocused, true));
+        assert!(events[0].2 < 0.4);
+
+        // 相机转动: 画面整体平移 1/4 宽
+        let shifted: Vec<u8> = (0..w * h)
+            .map(|i| gray[(i / w) * w + (i % w + w / 4) % w])
+            .collect();
+        let mut detector = TamperDetector::new(TamperConfig::default());
+        feed(&mut detector, &normal, 4);
+        let events = feed(&mut detector, &stats(&shifted), 20);
+        assert_eq!(events.len(), 1);
+        assert_eq!((events[0].0, events[0].1), (TamperKind::Moved, true));
+    }
+}
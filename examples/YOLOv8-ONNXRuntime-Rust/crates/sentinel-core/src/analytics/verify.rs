@@ -0,0 +1,267 @@
+//! 二次复核 (Two-pass verification)
+//!
+//! 快速模型触发区域事件后, 按事件涉及目标的检测框 (外扩一圈) 从该逻辑流的最新帧裁剪,
+//! 用更大的模型 (如 yolov8m) 重新检测. 裁剪图中与原框重叠、类别在复核类别内的最高置信度
+//! 即该目标的二次置信度; 全部目标都不低于阈值时确认, 否则判为误报.
+//! 复核结果写入 [`ZoneEvent::verification`] 后再发布, 告警通知只发送确认的事件.
+//! 人数规则没有具体目标, 不经复核直接发布
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crossbeam_channel::Sender;
+use image::{DynamicImage, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use super::ZoneEvent;
+use crate::detection::types::{DecodedFrame, DETECT_CLASSES};
+use crate::models::{load_detect_model, Model, ModelType};
+use crate::xbus::{self, Subscription};
+use crate::Bbox;
+
+/// 复核队列长度, 复核线程积压时事件不经复核直接发布
+const QUEUE_SIZE: usize = 8;
+
+/// 复核结果
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Verification {
+    pub confirmed: bool,
+    pub confidence: f32, // 二次置信度 (各目标中的最小值)
+}
+
+/// 复核配置
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerifyConfig {
+    pub model: String,     // 复核模型 (ONNX)
+    pub input_size: u32,   // 复核模型输入尺寸
+    pub confidence: f32,   // 二次置信度阈值
+    pub padding: f32,      // 裁剪外扩比例 (相对框宽高)
+    pub min_overlap: f32,  // 复核框与原框的最小 IoU (裁剪图坐标)
+    pub classes: Vec<u32>, // 复核类别, 为空表示全部
+}
+
+impl VerifyConfig {
+    pub fn new(model: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            input_size: 640,
+            confidence: 0.5,
+            padding: 0.3,
+            min_overlap: 0.3,
+            classes: DETECT_CLASSES.to_vec(),
+        }
+    }
+}
+
+/// 按外扩比例计算裁剪区域 (x, y, 宽, 高), 限制在图像内; 区域为空时为 None
+pub fn crop_rect(bbox: [f32; 4], padding: f32, width: u32, height: u32) -> Option<[u32; 4]> {
+    let [x1, y1, x2, y2] = bbox;
+    let pad_x = (x2 - x1).max(0.0) * padding;
+    let pad_y = (y2 - y1).max(0.0) * padding;
+    let left = (x1 - pad_x).clamp(0.0, width as f32) as u32;
+    let top = (y1 - pad_y).clamp(0.0, height as f32) as u32;
+    let right = (x2 + pad_x).clamp(0.0, width as f32).ceil() as u32;
+    let bottom = (y2 + pad_y).clamp(0.0, height as f32).ceil() as u32;
+    (right > left && bottom > top).then(|| [left, top, right - left, bottom - top])
+}
+
+/// 二次置信度: 与目标框 (裁剪图坐标) 重叠足够且类别符合的复核框中的最高置信度, 没有时为 0
+pub fn secondary_confidence(
+    target: [f32; 4],
+    detections: &[Bbox],
+    classes: &[u32],
+    min_overlap: f32,
+) -> f32 {
+    let [x1, y1, x2, y2] = target;
+    let target = Bbox::new(x1, y1, x2 - x1, y2 - y1, 0, 0.0);
+    detections
+        .iter()
+        .filter(|b| classes.is_empty() || classes.contains(&(b.id() as u32)))
+        .filter(|b| b.iou(&target) >= min_overlap)
+        .map(Bbox::confidence)
+        .fold(0.0, f32::max)
+}
+
+/// 二次复核 (独立线程), 由区域规则引擎提交事件
+pub struct EventVerifier {
+    tx: Sender<ZoneEvent>,
+    _frame_sub: Subscription,
+}
+
+impl EventVerifier {
+    /// 加载复核模型并启动复核线程
+    pub fn start(config: VerifyConfig) -> anyhow::Result<Self> {
+        let model_type = ModelType::from_path(&config.model);
+        let model = load_detect_model(
+            model_type,
+            crate::Args {
+                model: config.model.clone(),
+                source: String::new(),
+                device_id: 0,
+                trt: false,
+                cuda: false,
+                batch: 1,
+                batch_min: 1,
+                batch_max: 1,
+                fp16: false,
+                dla_core: None,
+                task: None,
+                nc: None,
+                nk: None,
+                nm: None,
+                width: Some(config.input_size),
+                height: Some(config.input_size),
+                // 低于阈值的框也保留, 由复核阈值判定
+                conf: (config.confidence * 0.5).min(0.25),
+                iou: model_type.default_iou_threshold(),
+                kconf: 0.55,
+                profile: false,
+            },
+        )?;
+        println!(
+            "🔍 二次复核启用: {} (阈值 {:.2}, 外扩 {:.0}%)",
+            config.model,
+            config.confidence,
+            config.padding * 100.0
+        );
+
+        // 各逻辑流的最新帧 (区域事件来自最近一帧的检测结果)
+        let latest: Arc<Mutex<HashMap<u32, DecodedFrame>>> = Arc::new(Mutex::new(HashMap::new()));
+        let frames = Arc::clone(&latest);
+        let frame_sub = xbus::subscribe::<DecodedFrame, _>(move |frame| {
+            frames.lock().unwrap().insert(frame.view, frame.clone());
+        });
+
+        let (tx, rx) = crossbeam_channel::bounded::<ZoneEvent>(QUEUE_SIZE);
+        let spawned = std::thread::Builder::new()
+            .name("event-verifier".to_string())
+            .spawn(move || {
+                let mut model = model;
+                for mut event in rx {
+                    let frame = latest.lock().unwrap().get(&event.view).cloned();
+                    match frame.and_then(|f| verify(model.as_mut(), &config, &f, &event.boxes)) {
+                        Some(verification) => {
+                            println!(
+                                "🔍 复核 [{}] {}: {} ({:.2})",
+                                event.zone,
+                                event.rule,
+                                if verification.confirmed {
+                                    "确认"
+                                } else {
+                                    "误报"
+                                },
+                                verification.confidence
+                            );
+                            event.verification = Some(verification);
+                        }
+                        None => eprintln!(
+                            "⚠️  复核失败 [{}] {}, 未复核直接发布",
+                            event.zone, event.rule
+                        ),
+                    }
+                    xbus::post(event);
+                }
+                println!("🔍 复核线程退出");
+            });
+        if let Err(e) = spawned {
+            anyhow::bail!("复核线程启动失败: {}", e);
+        }
+
+        Ok(Self {
+            tx,
+            _frame_sub: frame_sub,
+        })
+    }
+
+    /// 提交事件复核, 队列已满时不经复核直接发布
+    pub fn submit(&self, event: ZoneEvent) {
+        if let Err(e) = self.tx.try_send(event) {
+            eprintln!("⚠️  复核队列已满, 事件未复核直接发布");
+            xbus::post(e.into_inner());
+        }
+    }
+}
+
+/// 逐个目标裁剪复核, 取最小的二次置信度; 帧没有 RGBA 数据或推理失败时为 None
+fn verify(
+    model: &mut (dyn Model + Send),
+    config: &VerifyConfig,
+    frame: &DecodedFrame,
+    boxes: &[[f32; 4]],
+) -> Option<Verification> {
+    if frame.rgba_data.len() < (frame.width * frame.height * 4) as usize {
+        return None;
+    }
+    let image = RgbaImage::from_raw(frame.width, frame.height, frame.rgba_data.to_vec())?;
+    let image = DynamicImage::ImageRgba8(image);
+    let mut confidence = f32::MAX;
+    for &bbox in boxes {
+        let [x, y, w, h] = crop_rect(bbox, config.padding, frame.width, frame.height)?;
+        let crop = image.crop_imm(x, y, w, h);
+        let results = match model.forward(std::slice::from_ref(&crop)) {
+            Ok(results) => results,
+            Err(e) => {
+                eprintln!("❌ 复核推理失败: {}", e);
+                return None;
+            }
+        };
+        let detections = results
+            .first()
+            .and_then(|r| r.bboxes())
+            .cloned()
+            .unwrap_or_default();
+        let target = [
+            bbox[0] - x as f32,
+            bbox[1] - y as f32,
+            bbox[2] - x as f32,
+            bbox[3] - y as f32,
+        ];
+        confidence = confidence.min(secondary_confidence(
+            target,
+            &detections,
+            &config.classes,
+            config.min_overlap,
+        ));
+    }
+    (!boxes.is_empty()).then_some(Verification {
+        confirmed: confidence >= config.confidence,
+        confidence,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 裁剪区域按框宽高外扩并限制在图像内
+    #[test]
+    fn test_crop_rect() {
+        assert_eq!(
+            crop_rect([100.0, 100.0, 200.0, 300.0], 0.5, 640, 480),
+            Some([50, 0, 200, 400])
+        );
+        assert_eq!(
+            crop_rect([600.0, 400.0, 700.0, 500.0], 0.0, 640, 480),
+            Some([600, 400, 40, 80])
+        );
+        assert_eq!(crop_rect([700.0, 0.0, 800.0, 10.0], 0.1, 640, 480), None);
+    }
+
+    /// 只取与目标重叠且类别符合的复核框, 没有匹配时为 0
+    #[test]
+    fn test_secondary_confidence() {
+        let target = [10.0, 10.0, 50.0, 90.0];
+        let detections = [
+            Bbox::new(12.0, 8.0, 38.0, 84.0, 0, 0.72),  // 同一目标, 人
+            Bbox::new(10.0, 10.0, 40.0, 80.0, 1, 0.95), // 同位置, 自行车
+            Bbox::new(60.0, 10.0, 30.0, 80.0, 0, 0.9),  // 旁边的人
+        ];
+        let confidence = secondary_confidence(target, &detections, &[0], 0.3);
+        assert!((confidence - 0.72).abs() < 1e-6);
+        assert!((secondary_confidence(target, &detections, &[], 0.3) - 0.95).abs() < 1e-6);
+        assert_eq!(
+            secondary_confidence(target, &detections[2..], &[0], 0.3),
+            0.0
+        );
+    }
+}
@@ -0,0 +1,520 @@
+//! 去重访客计数 (Unique visitors)
+//!
+//! 轨迹数不等于人数: 同一人离开后再进入、被遮挡后换了轨迹ID或经过多路画面都会产生新轨迹.
+//! 本模块按 ReID 外观特征把一个统计周期 (默认一天) 内的轨迹聚为访客:
+//! - 轨迹累积 `min_track_frames` 个带特征的帧后, 取平均特征与本周期已有访客比较余弦相似度,
+//!   不低于阈值归入最相似的访客 (特征按滑动平均更新), 否则计为新访客
+//! - 每到整点输出上一小时的去重人数、其中的新访客数与周期累计人数,
+//!   追加到 CSV, 写入事件库 (events.db) 的 `visitors_hourly` 表并发布 [`HourlyVisitors`] 事件
+//! - 访客特征与当前小时的统计定期保存, 重启后在同一周期内继续去重
+//!
+//! 需要 ReID 特征 (DeepSort, 或 ReID 权重大于 0 的 ByteTrack). 周期按本地时间从零点起划分
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::detection::bytetrack::cosine_similarity;
+use crate::detection::detector::DetectionResult;
+use crate::utils::clock;
+use crate::xbus::{self, Subscription};
+
+/// 默认配置文件路径
+pub const VISITOR_CONFIG_PATH: &str = "visitors.json";
+
+/// 状态保存间隔 (进程随窗口关闭退出, 只能定期落盘)
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 轨迹超过该时长未出现后丢弃 (之后同一轨迹ID按新轨迹累积)
+const TRACK_TIMEOUT: chrono::Duration = chrono::Duration::seconds(10);
+
+/// 访客特征滑动平均系数
+const FEATURE_MOMENTUM: f32 = 0.9;
+
+/// 事件库中的每小时统计表 (time 为写入时间, UTC)
+const DB_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS visitors_hourly (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        time TEXT NOT NULL,
+        hour TEXT NOT NULL,
+        unique_visitors INTEGER NOT NULL,
+        new_visitors INTEGER NOT NULL,
+        period_total INTEGER NOT NULL
+    );
+";
+
+/// 去重访客配置 (visitors.json)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VisitorConfig {
+    pub period_hours: u32,         // 去重周期 (小时), 周期内同一人只计一次
+    pub similarity_threshold: f32, // 与已有访客特征的余弦相似度不低于该值视为同一人
+    pub min_track_frames: u32,     // 带特征的帧数少于该值的轨迹不计 (多为误检或一闪而过)
+    pub csv_path: String,          // 每小时统计 (追加写入)
+    pub state_path: String,        // 访客特征与当前小时统计, 重启后恢复; 为空不保存
+}
+
+impl Default for VisitorConfig {
+    fn default() -> Self {
+        Self {
+            period_hours: 24,
+            similarity_threshold: 0.75,
+            min_track_frames: 10,
+            csv_path: "visitors.csv".to_string(),
+            state_path: "visitors_state.json".to_string(),
+        }
+    }
+}
+
+impl VisitorConfig {
+    /// 从文件加载配置, 文件不存在时使用默认配置
+    pub fn load(path: &str) -> Self {
+        match fs::read_to_string(path) {
+            Ok(json) => match serde_json::from_str::<Self>(&json) {
+                Ok(config) => {
+                    println!("✅ 去重访客配置已从 {} 加载", path);
+                    config
+                }
+                Err(e) => {
+                    eprintln!("⚠️  去重访客配置解析失败: {}, 使用默认配置", e);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+}
+
+/// 一小时的去重访客统计 (整点后发布到事件总线, 追加到 CSV 并写入事件库)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HourlyVisitors {
+    pub hour: String,      // "YYYY-MM-DD HH:00" (本地时间)
+    pub unique: u32,       // 该小时出现过的访客数
+    pub new: u32,          // 其中本周期首次出现的
+    pub period_total: u32, // 截至该小时的周期累计访客数
+}
+
+impl HourlyVisitors {
+    pub const CSV_HEADER: &'static str = "hour,unique,new,period_total";
+
+    pub fn csv_row(&self) -> String {
+        format!(
+            "{},{},{},{}",
+            self.hour, self.unique, self.new, self.period_total
+        )
+    }
+
+    /// 追加到 CSV, 文件不存在时先写表头
+    pub fn append_csv(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let exists = path.exists();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("无法打开 {}", path.display()))?;
+        if !exists {
+            writeln!(file, "{}", Self::CSV_HEADER)?;
+        }
+        writeln!(file, "{}", self.csv_row())?;
+        Ok(())
+    }
+
+    /// 打开 (必要时创建) 事件库中的 `visitors_hourly` 表
+    pub fn open_db(path: &str) -> Result<Connection> {
+        let db = Connection::open(path).with_context(|| format!("无法打开事件库 {}", path))?;
+        db.execute_batch(DB_SCHEMA)?;
+        Ok(db)
+    }
+
+    /// 写入事件库的 `visitors_hourly` 表
+    pub fn insert_db(&self, db: &Connection, time: DateTime<Utc>) -> Result<()> {
+        db.execute(
+            "INSERT INTO visitors_hourly (time, hour, unique_visitors, new_visitors, period_total) \
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                time.to_rfc3339_opts(SecondsFormat::Millis, true),
+                self.hour,
+                self.unique,
+                self.new,
+                self.period_total
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+/// 本周期内的一个访客
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Visitor {
+    id: u32,
+    feature: Vec<f32>, // L2 归一化
+}
+
+/// 当前小时的统计
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct HourState {
+    hour: String,
+    seen: BTreeSet<u32>, // 出现过的访客ID
+    new: u32,
+}
+
+/// 可保存的计数状态 (周期序号、访客特征与当前小时统计)
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct VisitorState {
+    period: i64,
+    visitors: Vec<Visitor>,
+    current: HourState,
+}
+
+impl VisitorState {
+    /// 读取保存的状态, 文件不存在或解析失败时为 None
+    pub fn load(path: impl AsRef<Path>) -> Option<Self> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&json)
+            .map_err(|e| eprintln!("⚠️ 去重访客状态解析失败 ({}): {}", path.display(), e))
+            .ok()
+    }
+
+    /// 先写临时文件再重命名, 写入中途崩溃不会损坏上一次的状态
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec(self)?)
+            .with_context(|| format!("保存去重访客状态失败: {}", tmp.display()))?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+/// 累积中的轨迹
+struct TrackAccum {
+    sum: Vec<f32>, // 归一化特征之和
+    frames: u32,
+    visitor: Option<u32>, // 已归入的访客
+    last_seen: DateTime<Local>,
+}
+
+/// 去重访客计数 (不含 IO, 便于测试)
+pub struct VisitorCounter {
+    config: VisitorConfig,
+    state: VisitorState,
+    tracks: HashMap<(u32, u32), TrackAccum>, // (逻辑流, 轨迹ID)
+}
+
+impl VisitorCounter {
+    pub fn new(config: VisitorConfig) -> Self {
+        Self {
+            config,
+            state: VisitorState {
+                period: i64::MIN,
+                ..Default::default()
+            },
+            tracks: HashMap::new(),
+        }
+    }
+
+    /// 从保存的状态继续 (状态属于 now 所在的周期时才恢复)
+    pub fn with_state(config: VisitorConfig, state: VisitorState, now: DateTime<Local>) -> Self {
+        let mut counter = Self::new(config);
+        if state.period == counter.period(now) {
+            counter.state = state;
+        }
+        counter
+    }
+
+    pub fn state(&self) -> &VisitorState {
+        &self.state
+    }
+
+    /// 本周期累计访客数
+    pub fn period_total(&self) -> u32 {
+        self.state.visitors.len() as u32
+    }
+
+    /// 周期序号: 本地时间按 period_hours 划分 (24 时每天零点开始新周期)
+    fn period(&self, now: DateTime<Local>) -> i64 {
+        let hours = now.naive_local().and_utc().timestamp().div_euclid(3600);
+        hours.div_euclid(self.config.period_hours.max(1) as i64)
+    }
+
+    /// 记入一帧: 各轨迹的 (轨迹ID, ReID 特征); 进入新的小时后返回上一小时的统计
+    pub fn observe(
+        &mut self,
+        now: DateTime<Local>,
+        view: u32,
+        tracks: &[(u32, &[f32])],
+    ) -> Option<HourlyVisitors> {
+        let finished = self.roll(now);
+        for &(id, feature) in tracks {
+            let Some(feature) = normalize(feature) else {
+                continue;
+            };
+            let track = self.tracks.entry((view, id)).or_insert_with(|| TrackAccum {
+                sum: vec![0.0; feature.len()],
+                frames: 0,
+                visitor: None,
+                last_seen: now,
+            });
+            track.last_seen = now;
+            if let Some(visitor) = track.visitor {
+                self.state.current.seen.insert(visitor);
+                continue;
+            }
+            if track.sum.len() != feature.len() {
+                continue;
+            }
+            for (s, f) in track.sum.iter_mut().zip(&feature) {
+                *s += f;
+            }
+            track.frames += 1;
+            if track.frames < self.config.min_track_frames.max(1) {
+                continue;
+            }
+            let Some(mean) = normalize(&track.sum) else {
+                continue;
+            };
+            let (visitor, new) = resolve(
+                &mut self.state.visitors,
+                mean,
+                self.config.similarity_threshold,
+            );
+            track.visitor = Some(visitor);
+            self.state.current.seen.insert(visitor);
+            if new {
+                self.state.current.new += 1;
+            }
+        }
+        self.tracks
+            .retain(|_, t| now.signed_duration_since(t.last_seen) < TRACK_TIMEOUT);
+        finished
+    }
+
+    /// 进入新的小时: 结束当前小时并返回其统计, 进入新周期时清空访客
+    fn roll(&mut self, now: DateTime<Local>) -> Option<HourlyVisitors> {
+        let hour = clock::format(now, "%Y-%m-%d %H:00");
+        if self.state.current.hour == hour {
+            return None;
+        }
+        let current = std::mem::take(&mut self.state.current);
+        let finished = (!current.hour.is_empty()).then(|| HourlyVisitors {
+            hour: current.hour,
+            unique: current.seen.len() as u32,
+            new: current.new,
+            period_total: self.period_total(),
+        });
+        let period = self.period(now);
+        if period != self.state.period {
+            self.state.period = period;
+            self.state.visitors.clear();
+            // 轨迹已归入的访客属于上一周期, 重新累积
+            self.tracks.clear();
+        }
+        self.state.current.hour = hour;
+        finished
+    }
+
+    /// 订阅检测结果做去重访客计数, 每小时追加 CSV、写入事件库并发布 [`HourlyVisitors`]
+    ///
+    /// `events_db` 为空或打开失败时只写 CSV. 回调在检测线程上执行, 订阅须在运行期间保持
+    pub fn start(config: VisitorConfig, events_db: &str) -> Subscription {
+        println!(
+            "👥 去重访客计数: 周期 {} 小时, 相似度阈值 {:.2}, 统计写入 {}",
+            config.period_hours, config.similarity_threshold, config.csv_path
+        );
+        let state_path = config.state_path.clone();
+        let csv_path = config.csv_path.clone();
+        let counter = match (!state_path.is_empty())
+            .then(|| VisitorState::load(&state_path))
+            .flatten()
+        {
+            Some(state) => {
+                let counter = Self::with_state(config, state, Local::now());
+                if counter.period_total() > 0 {
+                    println!("👥 已恢复本周期的 {} 位访客", counter.period_total());
+                }
+                counter
+            }
+            None => Self::new(config),
+        };
+        let db = match events_db {
+            "" => None,
+            path => HourlyVisitors::open_db(path)
+                .map_err(|e| eprintln!("⚠️ 去重访客统计不写入事件库: {:#}", e))
+                .ok(),
+        };
+        let state = Mutex::new((counter, Instant::now(), db));
+        xbus::subscribe::<DetectionResult, _>(move |result| {
+            let mut state = state.lock().unwrap();
+            let (counter, last_save, db) = &mut *state;
+            // 未跟踪时 class_id 不是轨迹ID; 重叠视野中的重复检测已由编号更小的流计入
+            let tracks: Vec<(u32, &[f32])> = if result.tracked {
+                result
+                    .bboxes
+                    .iter()
+                    .zip(&result.reid_features)
+                    .enumerate()
+                    .filter(|&(i, _)| !result.duplicates.get(i).copied().unwrap_or(false))
+                    .map(|(_, (b, f))| (b.class_id, f.as_slice()))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            let finished = counter.observe(Local::now(), result.view, &tracks);
+            if let Some(hourly) = &finished {
+                println!(
+                    "👥 {} 去重访客 {} (新 {}), 本周期累计 {}",
+                    hourly.hour, hourly.unique, hourly.new, hourly.period_total
+                );
+                if let Err(e) = hourly.append_csv(&csv_path) {
+                    eprintln!("⚠️ 去重访客统计写入失败: {:#}", e);
+                }
+                if let Some(db) = db.as_ref() {
+                    if let Err(e) = hourly.insert_db(db, Utc::now()) {
+                        eprintln!("⚠️ 去重访客统计写入事件库失败: {:#}", e);
+                    }
+                }
+                xbus::post(hourly.clone());
+            }
+            if !state_path.is_empty()
+                && (finished.is_some() || last_save.elapsed() >= SAVE_INTERVAL)
+            {
+                *last_save = Instant::now();
+                if let Err(e) = counter.state().save(&state_path) {
+                    eprintln!("⚠️ {:#}", e);
+                }
+            }
+        })
+    }
+}
+
+/// 按特征归入最相似的访客 (相似度不低于阈值), 否则新建访客: 返回 (访客ID, 是否新访客)
+fn resolve(visitors: &mut Vec<Visitor>, feature: Vec<f32>, threshold: f32) -> (u32, bool) {
+    let best = visitors
+        .iter_mut()
+        .map(|v| (cosine_similarity(&v.feature, &feature), v))
+        .filter(|(similarity, _)| *similarity >= threshold)
+        .max_by(|a, b| a.0.total_cmp(&b.0));
+    if let Some((_, visitor)) = best {
+        let merged: Vec<f32> = visitor
+            .feature
+            .iter()
+            .zip(&feature)
+            .map(|(&old, &new)| FEATURE_MOMENTUM * old + (1.0 - FEATURE_MOMENTUM) * new)
+            .collect();
+        if let Some(merged) = normalize(&merged) {
+            visitor.feature = merged;
+        }
+        return (visitor.id, false);
+    }
+    let id = visitors.len() as u32 + 1;
+    visitors.push(Visitor { id, feature });
+    (id, true)
+}
+
+/// L2 归一化, 空、全零或非有限特征 (未加载 ReID 模型) 返回 None
+fn normalize(feature: &[f32]) -> Option<Vec<f32>> {
+    let norm = feature.iter().map(|v| v * v).sum::<f32>().sqrt();
+    (norm.is_finite() && norm > 1e-6).then(|| feature.iter().map(|v| v / norm).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2024, 5, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn config() -> VisitorConfig {
+        VisitorConfig {
+            min_track_frames: 2,
+            ..Default::default()
+        }
+    }
+
+    /// 同一人换了轨迹ID或出现在另一路画面只计一次; 整点输出上一小时的统计
+    #[test]
+    fn test_dedup_and_hourly() {
+        let (a, b) = ([1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        let mut counter = VisitorCounter::new(config());
+        assert_eq!(counter.observe(at(1, 9, 0), 0, &[(1, &a), (2, &b)]), None);
+        counter.observe(at(1, 9, 1), 0, &[(1, &a), (2, &b)]);
+        assert_eq!(counter.period_total(), 2);
+
+        // 人 a 以新轨迹ID再次进入, 随后出现在逻辑流 1
+        let a2 = [0.95, 0.1, 0.0];
+        counter.observe(at(1, 9, 30), 0, &[(7, &a2)]);
+        counter.observe(at(1, 9, 31), 0, &[(7, &a2)]);
+        counter.observe(at(1, 9, 40), 1, &[(1, &a)]);
+        counter.observe(at(1, 9, 41), 1, &[(1, &a)]);
+        assert_eq!(counter.period_total(), 2);
+
+        let hourly = counter.observe(at(1, 10, 5), 0, &[]).unwrap();
+        assert_eq!(
+            hourly,
+            HourlyVisitors {
+                hour: at(1, 9, 0).format("%Y-%m-%d %H:00").to_string(),
+                unique: 2,
+                new: 2,
+                period_total: 2,
+            }
+        );
+        assert_eq!(hourly.csv_row().split(',').count(), 4);
+
+        // 同一行写入事件库的 visitors_hourly 表
+        let path = std::env::temp_dir().join(format!("visitors_{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let db = HourlyVisitors::open_db(path.to_str().unwrap()).unwrap();
+        hourly.insert_db(&db, Utc::now()).unwrap();
+        let row: (String, u32, u32, u32) = db
+            .query_row(
+                "SELECT hour, unique_visitors, new_visitors, period_total FROM visitors_hourly",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(row, (hourly.hour.clone(), 2, 2, 2));
+        drop(db);
+        fs::remove_file(&path).unwrap();
+
+        // 下一小时再次出现的访客计入去重人数, 但不是新访客
+        counter.observe(at(1, 10, 10), 0, &[(9, &b)]);
+        counter.observe(at(1, 10, 11), 0, &[(9, &b)]);
+        let hourly = counter.observe(at(1, 11, 0), 0, &[]).unwrap();
+        assert_eq!((hourly.unique, hourly.new, hourly.period_total), (1, 0, 2));
+    }
+
+    /// 帧数不足的轨迹不计; 新周期清空访客; 同一周期内从保存的状态恢复
+    #[test]
+    fn test_period_and_restore() {
+        let a = [1.0, 0.0];
+        let mut counter = VisitorCounter::new(config());
+        counter.observe(at(1, 23, 0), 0, &[(1, &a)]);
+        assert_eq!(counter.period_total(), 0);
+        counter.observe(at(1, 23, 0), 0, &[(1, &a)]);
+        assert_eq!(counter.period_total(), 1);
+
+        let saved = counter.state().clone();
+        let restored = VisitorCounter::with_state(config(), saved.clone(), at(1, 23, 30));
+        assert_eq!(restored.period_total(), 1);
+        let next_day = VisitorCounter::with_state(config(), saved, at(2, 0, 30));
+        assert_eq!(next_day.period_total(), 0);
+
+        let hourly = counter.observe(at(2, 0, 0), 0, &[(1, &a)]).unwrap();
+        assert_eq!((hourly.unique, hourly.period_total), (1, 1));
+        assert_eq!(counter.period_total(), 0);
+    }
+}
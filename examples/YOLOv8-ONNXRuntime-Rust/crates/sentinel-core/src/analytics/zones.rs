@@ -0,0 +1,468 @@
+//! 区域规则引擎 (Zone rule engine)
+//!
+//! 区域多边形定义在地面坐标系 (米, 与地面标定一致), 每个区域挂若干规则.
+//! 引擎订阅 `DetectionResult`, 按检测框脚点的地面坐标判断归属并求值规则 (距离/速度/人数/行人属性),
+//! 触发的 [`ZoneEvent`] 发布到 xbus; 同一规则对同一组目标在冷却时间内只触发一次.
+//! 切换配置档案时在 xbus 上发布新的 [`ZoneConfig`], 引擎随之替换区域.
+//! 设置了 [`EventVerifier`] 时, 涉及具体目标的事件先经大模型复核再发布
+
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::verify::{EventVerifier, Verification};
+use crate::detection::detector::DetectionResult;
+use crate::models::attributes::{attribute_label, PersonAttributes};
+use crate::models::crowd::DensityMap;
+use crate::models::vehicle::VehicleInfo;
+use crate::xbus::{self, Subscription};
+
+/// 默认区域配置文件路径
+pub const ZONE_CONFIG_PATH: &str = "zones.json";
+
+/// 区域规则
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ZoneRule {
+    /// 区域内两个目标的地面距离小于阈值 (米)
+    MinDistance { meters: f32 },
+    /// 区域内目标速度超过阈值 (千米/小时), 需要启用跟踪
+    MaxSpeed { kmh: f32 },
+    /// 区域内人数超过阈值; 有地面映射的人群密度图时按密度积分, 否则按目标数
+    MaxCount { count: f32 },
+    /// 区域内目标具有 (`present`) 或缺少某项行人属性, 需要属性模型;
+    /// 属性名为 `hardhat`/`backpack`/`upper_<颜色>`/`lower_<颜色>`, 概率不低于 0.5 视为具有
+    Attribute { attribute: String, present: bool },
+}
+
+impl ZoneRule {
+    /// 规则描述 (事件与面板显示)
+    pub fn describe(&self) -> String {
+        match self {
+            ZoneRule::MinDistance { meters } => format!("间距 < {:.1}m", meters),
+            ZoneRule::MaxSpeed { kmh } => format!("速度 > {:.0}km/h", kmh),
+            ZoneRule::MaxCount { count } => format!("人数 > {:.0}", count),
+            ZoneRule::Attribute { attribute, present } => {
+                format!(
+                    "{}{}",
+                    if *present { "有" } else { "无" },
+                    attribute_label(attribute)
+                )
+            }
+        }
+    }
+}
+
+/// 区域: 地面坐标多边形 + 规则
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Zone {
+    pub name: String,
+    pub polygon: Vec<[f32; 2]>, // 地面坐标 (米), 按顺序连接
+    #[serde(default)]
+    pub rules: Vec<ZoneRule>,
+}
+
+impl Zone {
+    /// 射线法判断点是否在多边形内
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        let n = self.polygon.len();
+        if n < 3 {
+            return false;
+        }
+        let mut inside = false;
+        let mut j = n - 1;
+        for i in 0..n {
+            let [xi, yi] = self.polygon[i];
+            let [xj, yj] = self.polygon[j];
+            if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+                inside = !inside;
+            }
+            j = i;
+        }
+        inside
+    }
+}
+
+/// 区域配置 (zones.json)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ZoneConfig {
+    pub zones: Vec<Zone>,
+    pub cooldown_secs: f32, // 同一规则对同一组目标的重复触发间隔
+}
+
+impl Default for ZoneConfig {
+    fn default() -> Self {
+        Self {
+            zones: Vec::new(),
+            cooldown_secs: 5.0,
+        }
+    }
+}
+
+impl ZoneConfig {
+    /// 从文件加载配置, 文件不存在时为空配置
+    pub fn load(path: &str) -> Self {
+        match fs::read_to_string(path) {
+            Ok(json) => match serde_json::from_str::<Self>(&json) {
+                Ok(config) => {
+                    println!(
+                        "✅ 区域配置已从 {} 加载 ({}个区域)",
+                        path,
+                        config.zones.len()
+                    );
+                    config
+                }
+                Err(e) => {
+                    eprintln!("⚠️  区域配置解析失败: {}, 不启用区域规则", e);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+}
+
+/// 区域事件 (区域引擎 → 渲染/告警)
+#[derive(Clone, Debug)]
+pub struct ZoneEvent {
+    pub zone: String,
+    pub rule: String,        // 规则描述
+    pub track_ids: Vec<u32>, // 涉及的目标 (启用跟踪时为轨迹ID), 人数规则为空
+    pub value: f32,          // 触发时的测量值 (距离为米, 速度为千米/小时, 人数)
+    pub view: u32,           // 逻辑流编号
+    pub time: chrono::DateTime<chrono::Local>,
+    // 涉及目标的检测框 (图像坐标, 与 track_ids 一一对应), 人数规则为空
+    pub boxes: Vec<[f32; 4]>,
+    // 二次复核结果, 未启用复核或事件不涉及具体目标时为 None
+    pub verification: Option<Verification>,
+    // 涉及目标的车型/车身颜色 (与 track_ids 一一对应), 非车辆或未启用车辆属性模型时为 None
+    pub vehicles: Vec<Option<VehicleInfo>>,
+}
+
+/// 参与规则求值的目标
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ZoneObject {
+    pub id: u32,              // 启用跟踪时为轨迹ID
+    pub position: (f32, f32), // 地面坐标 (米)
+    pub speed: Option<f32>,   // 速度 (米/秒), 未估计时为 None
+    pub bbox: [f32; 4],       // 图像坐标检测框 (像素)
+    // 行人属性, 未启用属性模型或尚未识别时为 None
+    pub attributes: Option<PersonAttributes>,
+    // 车型/车身颜色, 非车辆或未启用车辆属性模型时为 None
+    pub vehicle: Option<VehicleInfo>,
+}
+
+/// 区域规则引擎
+pub struct ZoneEngine {
+    zones: Vec<Zone>,
+    cooldown: Duration,
+    // (区域, 规则, 逻辑流, 目标) → 上次触发时间
+    last_fired: HashMap<(usize, usize, u32, Vec<u32>), Instant>,
+    verifier: Option<Arc<EventVerifier>>,
+}
+
+impl ZoneEngine {
+    pub fn new(config: ZoneConfig) -> Self {
+        Self {
+            zones: config.zones,
+            cooldown: Duration::from_secs_f32(config.cooldown_secs.max(0.0)),
+            last_fired: HashMap::new(),
+            verifier: None,
+        }
+    }
+
+    /// 设置二次复核 (事件经复核后由复核线程发布)
+    pub fn set_verifier(&mut self, verifier: EventVerifier) {
+        self.verifier = Some(Arc::new(verifier));
+    }
+
+    pub fn zones(&self) -> &[Zone] {
+        &self.zones
+    }
+
+    /// 替换区域与冷却时间, 清空触发记录 (保留复核设置)
+    pub fn reload(&mut self, config: ZoneConfig) {
+        let verifier = self.verifier.take();
+        *self = Self::new(config);
+        self.verifier = verifier;
+    }
+
+    /// 对一帧的目标 (及可选的人群密度图) 求值全部规则
+    pub fn evaluate(
+        &mut self,
+        objects: &[ZoneObject],
+        density: Option<&DensityMap>,
+        view: u32,
+        now: Instant,
+    ) -> Vec<ZoneEvent> {
+        let cooldown = self.cooldown;
+        self.last_fired
+            .retain(|_, t| now.saturating_duration_since(*t) < cooldown);
+
+        let mut events = Vec::new();
+        for (zi, zone) in self.zones.iter().enumerate() {
+            let inside: Vec<&ZoneObject> = objects
+                .iter()
+                .filter(|o| zone.contains(o.position.0, o.position.1))
+                .collect();
+            for (ri, rule) in zone.rules.iter().enumerate() {
+                let mut hits: Vec<(Vec<&ZoneObject>, f32)> = Vec::new();
+                match rule {
+                    ZoneRule::MinDistance { meters } => {
+                        for (i, a) in inside.iter().enumerate() {
+                            for b in &inside[i + 1..] {
+                                let (xa, ya) = a.position;
+                                let (xb, yb) = b.position;
+                                let d = ((xa - xb).powi(2) + (ya - yb).powi(2)).sqrt();
+                                if d < *meters {
+                                    let pair = if a.id <= b.id { [*a, *b] } else { [*b, *a] };
+                                    hits.push((pair.to_vec(), d));
+                                }
+                            }
+                        }
+                    }
+                    ZoneRule::MaxSpeed { kmh } => {
+                        for o in &inside {
+                            if let Some(speed) = o.speed.map(|s| s * 3.6) {
+                                if speed > *kmh {
+                                    hits.push((vec![*o], speed));
+                                }
+                            }
+                        }
+                    }
+                    ZoneRule::MaxCount { count } => {
+                        // 区域级规则: 冷却按区域计, 与具体目标无关
+                        let n = match density.filter(|d| !d.ground.is_empty()) {
+                            Some(d) => d.count_where(|x, y| zone.contains(x, y)),
+                            None => inside.len() as f32,
+                        };
+                        if n > *count {
+                            hits.push((Vec::new(), n));
+                        }
+                    }
+                    ZoneRule::Attribute { attribute, present } => {
+                        // 尚未识别属性的目标不参与, 避免未识别即判为"无"
+                        for o in &inside {
+                            let prob = o.attributes.and_then(|a| a.probability(attribute));
+                            if let Some(p) = prob.filter(|p| (*p >= 0.5) == *present) {
+                                hits.push((vec![*o], p));
+                            }
+                        }
+                    }
+                }
+                for (targets, value) in hits {
+                    let track_ids: Vec<u32> = targets.iter().map(|o| o.id).collect();
+                    let key = (zi, ri, view, track_ids.clone());
+                    if self.last_fired.contains_key(&key) {
+                        continue;
+                    }
+                    self.last_fired.insert(key, now);
+                    events.push(ZoneEvent {
+                        zone: zone.name.clone(),
+                        rule: rule.describe(),
+                        track_ids,
+                        value,
+                        view,
+                        time: chrono::Local::now(),
+                        boxes: targets.iter().map(|o| o.bbox).collect(),
+                        verification: None,
+                        vehicles: targets.iter().map(|o| o.vehicle).collect(),
+                    });
+                }
+            }
+        }
+        events
+    }
+
+    /// 订阅检测结果, 触发的事件发布到 xbus (回调在检测线程上执行)
+    ///
+    /// 同时订阅 [`ZoneConfig`] 以便运行中替换区域, 该订阅随返回的订阅凭证一同失效
+    pub fn start(self) -> Subscription {
+        println!("🗺️ 区域规则引擎启动: {}个区域", self.zones.len());
+        let engine = Arc::new(Mutex::new(self));
+        xbus::subscribe_weak::<ZoneConfig, _, _>(&engine, |engine, config| {
+            println!("🗺️ 区域规则已替换: {}个区域", config.zones.len());
+            engine.lock().unwrap().reload(config.clone());
+        })
+        .detach();
+        xbus::subscribe::<DetectionResult, _>(move |result| {
+            let density = result.density.as_deref().filter(|d| !d.ground.is_empty());
+            if result.ground_points.is_empty() && density.is_none() {
+                return;
+            }
+            let objects: Vec<ZoneObject> = result
+                .bboxes
+                .iter()
+                .zip(&result.ground_points)
+                .enumerate()
+                // 重叠视野中已由编号更小的流计入的目标不重复求值
+                .filter(|(i, _)| !result.duplicates.get(*i).copied().unwrap_or(false))
+                .filter_map(|(i, (b, p))| {
+                    p.map(|position| ZoneObject {
+                        id: b.class_id,
+                        position,
+                        speed: result.speeds.get(i).copied().flatten(),
+                        bbox: [b.x1, b.y1, b.x2, b.y2],
+                        attributes: result.attributes.get(i).copied().flatten(),
+                        vehicle: result.vehicles.get(i).copied().flatten(),
+                    })
+                })
+                .collect();
+            // 先释放锁再发布, 事件订阅者可以替换区域配置
+            let (events, verifier) = {
+                let mut engine = engine.lock().unwrap();
+                let events = engine.evaluate(&objects, density, result.view, Instant::now());
+                (events, engine.verifier.clone())
+            };
+            for event in events {
+                println!(
+                    "🚨 区域事件 [{}] {} 目标{:?} ({:.2})",
+                    event.zone, event.rule, event.track_ids, event.value
+                );
+                match &verifier {
+                    Some(verifier) if !event.boxes.is_empty() => verifier.submit(event),
+                    _ => xbus::post(event),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::attributes::Color;
+    use crate::models::vehicle::VehicleType;
+
+    fn engine() -> ZoneEngine {
+        ZoneEngine::new(ZoneConfig {
+            zones: vec![Zone {
+                name: "A".to_string(),
+                polygon: vec![[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0]],
+                rules: vec![
+                    ZoneRule::MinDistance { meters: 1.5 },
+                    ZoneRule::MaxSpeed { kmh: 30.0 },
+                ],
+            }],
+            cooldown_secs: 5.0,
+        })
+    }
+
+    /// 区域内距离过近的目标触发一次, 冷却期内不重复, 区域外不参与
+    #[test]
+    fn test_min_distance_rule() {
+        let mut engine = engine();
+        let now = Instant::now();
+        let object = |id, x, y| ZoneObject {
+            id,
+            position: (x, y),
+            speed: None,
+            bbox: [0.0; 4],
+            attributes: None,
+            vehicle: None,
+        };
+        let objects = [
+            object(1, 2.0, 2.0),
+            object(2, 3.0, 2.0),
+            object(3, 12.0, 2.0),
+            object(4, 11.5, 2.5),
+        ];
+        let events = engine.evaluate(&objects, None, 0, now);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].track_ids, vec![1, 2]);
+        assert!((events[0].value - 1.0).abs() < 1e-6);
+
+        assert!(engine
+            .evaluate(&objects, None, 0, now + Duration::from_secs(1))
+            .is_empty());
+        assert_eq!(
+            engine
+                .evaluate(&objects, None, 0, now + Duration::from_secs(6))
+                .len(),
+            1
+        );
+    }
+
+    /// 区域内超速的目标触发速度规则, 区域外或未估计速度的不触发; 事件带上车辆属性
+    #[test]
+    fn test_max_speed_rule() {
+        let mut engine = engine();
+        let truck = VehicleInfo {
+            vehicle_type: Some(VehicleType::Truck),
+            color: Some(Color::White),
+        };
+        let objects = [
+            ZoneObject {
+                id: 7,
+                position: (5.0, 5.0),
+                speed: Some(12.0), // 43.2 km/h
+                bbox: [0.0; 4],
+                attributes: None,
+                vehicle: Some(truck),
+            },
+            ZoneObject {
+                id: 8,
+                position: (5.0, 8.0),
+                speed: Some(5.0),
+                bbox: [0.0; 4],
+                attributes: None,
+                vehicle: None,
+            },
+            ZoneObject {
+                id: 9,
+                position: (15.0, 5.0),
+                speed: Some(20.0),
+                bbox: [0.0; 4],
+                attributes: None,
+                vehicle: None,
+            },
+        ];
+        let events = engine.evaluate(&objects, None, 0, Instant::now());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].track_ids, vec![7]);
+        assert!((events[0].value - 43.2).abs() < 1e-3);
+        assert_eq!(events[0].vehicles, vec![Some(truck)]);
+    }
+
+    /// 未戴安全帽的目标触发属性规则, 戴安全帽或尚未识别属性的不触发
+    #[test]
+    fn test_attribute_rule() {
+        let mut engine = ZoneEngine::new(ZoneConfig {
+            zones: vec![Zone {
+                name: "工地".to_string(),
+                polygon: vec![[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0]],
+                rules: vec![ZoneRule::Attribute {
+                    attribute: "hardhat".to_string(),
+                    present: false,
+                }],
+            }],
+            cooldown_secs: 5.0,
+        });
+        assert_eq!(engine.zones()[0].rules[0].describe(), "无安全帽");
+        let object = |id, hardhat: Option<f32>| ZoneObject {
+            id,
+            position: (5.0, 5.0),
+            speed: None,
+            bbox: [0.0; 4],
+            attributes: hardhat.map(|hardhat| PersonAttributes {
+                hardhat,
+                ..PersonAttributes::default()
+            }),
+            vehicle: None,
+        };
+        let objects = [object(1, Some(0.9)), object(2, Some(0.1)), object(3, None)];
+        let events = engine.evaluate(&objects, None, 0, Instant::now());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].track_ids, vec![2]);
+        assert_eq!(events[0].rule, "无安全帽");
+
+        let rule: ZoneRule =
+            serde_json::from_str(r#"{"type":"Attribute","attribute":"hardhat","present":false}"#)
+                .unwrap();
+        assert_eq!(rule, engine.zones()[0].rules[0]);
+    }
+}
@@ -0,0 +1,303 @@
+//! 操作审计日志 (Audit log)
+//!
+//! 记录每一次控制操作 (切换模型/阈值/输入源/区域/布防/配置档案等) 的时间、来源与内容:
+//! - 来源: 控制面板 (ui)、API 令牌持有者 (api)、配置文件热加载 (config)
+//! - 写入事件库 (events.db, SQLite) 的 `audit_log` 表, 每条记录单独提交 (写入后即落盘);
+//!   表上的触发器拒绝 UPDATE, DELETE 只能删除最旧的记录 (供存储保留策略按时长/容量裁剪),
+//!   程序也不提供修改与删除接口
+//! - 时间为 UTC, 显示时按显示时区换算
+//! - 最近的记录保留在内存中, 供控制面板审计视图与 `GET /api/audit` (admin) 查询
+//!
+//! 未设置事件库时只保留在内存中. 记录中的输入源地址不含密码.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Mutex;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// 默认事件库文件
+pub const EVENTS_DB_PATH: &str = "events.db";
+
+/// 内存中保留的最近记录条数
+pub const RECENT_CAPACITY: usize = 500;
+
+/// 审计表 (只追加, 只能从最旧的记录开始删除)
+pub(crate) const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS audit_log (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        time TEXT NOT NULL,
+        source TEXT NOT NULL,
+        action TEXT NOT NULL,
+        detail TEXT NOT NULL
+    );
+    CREATE TRIGGER IF NOT EXISTS audit_log_no_update BEFORE UPDATE ON audit_log
+        BEGIN SELECT RAISE(ABORT, 'audit_log is append-only'); END;
+    DROP TRIGGER IF EXISTS audit_log_no_delete;
+    CREATE TRIGGER IF NOT EXISTS audit_log_oldest_first BEFORE DELETE ON audit_log
+        WHEN old.id > (SELECT MIN(id) FROM audit_log)
+        BEGIN SELECT RAISE(ABORT, 'audit_log can only drop its oldest records'); END;
+";
+
+static AUDIT: Mutex<AuditLog> = Mutex::new(AuditLog {
+    db: None,
+    recent: VecDeque::new(),
+});
+
+struct AuditLog {
+    db: Option<Connection>,
+    recent: VecDeque<AuditRecord>,
+}
+
+/// 操作来源
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditSource {
+    Ui,                      // 控制面板
+    Api { user: String },    // API 令牌持有者 (未启用鉴权时为 anonymous)
+    Config { path: String }, // 配置文件热加载
+}
+
+impl fmt::Display for AuditSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AuditSource::Ui => write!(f, "ui"),
+            AuditSource::Api { user } => write!(f, "api:{}", user),
+            AuditSource::Config { path } => write!(f, "config:{}", path),
+        }
+    }
+}
+
+/// 操作类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    ModelSwitch,
+    TrackerSwitch,
+    ThresholdChange,
+    StreamSwitch,
+    ZoneEdit,
+    ProfileSwitch,
+    ArmMode,
+    ConfigReload,
+    ClipExport,
+    TrackFlag,
+    Profiling,
+    Debug,
+}
+
+impl AuditAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AuditAction::ModelSwitch => "model_switch",
+            AuditAction::TrackerSwitch => "tracker_switch",
+            AuditAction::ThresholdChange => "threshold_change",
+            AuditAction::StreamSwitch => "stream_switch",
+            AuditAction::ZoneEdit => "zone_edit",
+            AuditAction::ProfileSwitch => "profile_switch",
+            AuditAction::ArmMode => "arm_mode",
+            AuditAction::ConfigReload => "config_reload",
+            AuditAction::ClipExport => "clip_export",
+            AuditAction::TrackFlag => "track_flag",
+            AuditAction::Profiling => "profiling",
+            AuditAction::Debug => "debug",
+        }
+    }
+}
+
+/// 一条审计记录
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub time: DateTime<Utc>,
+    pub source: AuditSource,
+    pub action: AuditAction,
+    pub detail: String,
+}
+
+/// 打开 (必要时创建) 事件库, 并载入最近的审计记录供审计视图显示
+pub fn init(path: &str) -> anyhow::Result<()> {
+    let db = Connection::open(path)?;
+    db.execute_batch(SCHEMA)?;
+    let mut recent = load_recent(&db, RECENT_CAPACITY)?;
+
+    let mut audit = AUDIT.lock().unwrap();
+    // 打开事件库之前的记录补写入库
+    for record in &audit.recent {
+        insert_record(&db, record);
+    }
+    recent.extend(audit.recent.drain(..));
+    while recent.len() > RECENT_CAPACITY {
+        recent.pop_front();
+    }
+    audit.recent = recent;
+    audit.db = Some(db);
+    println!("📜 审计日志: {} (audit_log)", path);
+    Ok(())
+}
+
+/// 库中最近的 limit 条记录 (旧记录在前), 无法解析的行跳过
+fn load_recent(db: &Connection, limit: usize) -> anyhow::Result<VecDeque<AuditRecord>> {
+    let mut statement = db.prepare(
+        "SELECT time, source, action, detail FROM audit_log ORDER BY id DESC LIMIT ?1",
+    )?;
+    let rows = statement.query_map([limit as i64], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+        ))
+    })?;
+    let mut recent = VecDeque::new();
+    for (time, source, action, detail) in rows.filter_map(Result::ok) {
+        if let Some(record) = parse_row(&time, &source, action, detail) {
+            recent.push_front(record);
+        }
+    }
+    Ok(recent)
+}
+
+fn parse_row(time: &str, source: &str, action: String, detail: String) -> Option<AuditRecord> {
+    Some(AuditRecord {
+        time: DateTime::parse_from_rfc3339(time).ok()?.with_timezone(&Utc),
+        source: serde_json::from_str(source).ok()?,
+        action: serde_json::from_value(serde_json::Value::String(action)).ok()?,
+        detail,
+    })
+}
+
+fn insert_record(db: &Connection, record: &AuditRecord) {
+    let result = serde_json::to_string(&record.source)
+        .map_err(anyhow::Error::from)
+        .and_then(|source| {
+            db.execute(
+                "INSERT INTO audit_log (time, source, action, detail) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    record.time.to_rfc3339_opts(SecondsFormat::Millis, true),
+                    source,
+                    record.action.name(),
+                    record.detail
+                ],
+            )?;
+            Ok(())
+        });
+    if let Err(e) = result {
+        eprintln!("⚠️ 审计日志写入失败: {}", e);
+    }
+}
+
+/// 记录一次控制操作
+pub fn record(source: AuditSource, action: AuditAction, detail: impl Into<String>) {
+    let record = AuditRecord {
+        time: Utc::now(),
+        source,
+        action,
+        detail: detail.into(),
+    };
+    println!(
+        "📜 审计 [{}] {} {}",
+        record.source,
+        record.action.name(),
+        record.detail
+    );
+    let mut audit = AUDIT.lock().unwrap();
+    if let Some(db) = audit.db.as_ref() {
+        insert_record(db, &record);
+    }
+    if audit.recent.len() == RECENT_CAPACITY {
+        audit.recent.pop_front();
+    }
+    audit.recent.push_back(record);
+}
+
+/// 最近的审计记录 (新记录在前, 最多 limit 条)
+pub fn recent(limit: usize) -> Vec<AuditRecord> {
+    AUDIT
+        .lock()
+        .unwrap()
+        .recent
+        .iter()
+        .rev()
+        .take(limit)
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 记录写入事件库, 重新打开后载入历史记录 (最多 RECENT_CAPACITY 条), 查询时新记录在前
+    #[test]
+    fn test_record_and_reload() {
+        let path = std::env::temp_dir().join(format!("audit_test_{}.db", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+
+        init(path).unwrap();
+        record(AuditSource::Ui, AuditAction::ModelSwitch, "yolov8s");
+        record(
+            AuditSource::Api {
+                user: "shift-lead".to_string(),
+            },
+            AuditAction::ArmMode,
+            "POST /api/schedule/arm",
+        );
+
+        // 其他测试可能同时写入审计记录, 按内容查找
+        let db = Connection::open(path).unwrap();
+        let stored = load_recent(&db, i64::MAX as usize).unwrap();
+        let position = |records: &[AuditRecord], detail: &str| {
+            records.iter().position(|r| r.detail == detail).unwrap()
+        };
+        let lines: Vec<AuditRecord> = stored.into_iter().collect();
+        let model = position(&lines, "yolov8s");
+        let arm = position(&lines, "POST /api/schedule/arm");
+        assert!(model < arm);
+        assert_eq!(lines[model].action, AuditAction::ModelSwitch);
+        assert_eq!(lines[arm].source.to_string(), "api:shift-lead");
+        let action: String = db
+            .query_row(
+                "SELECT action FROM audit_log WHERE detail = ?1",
+                ["POST /api/schedule/arm"],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(action, "arm_mode");
+
+        // 只追加: 修改与删除较新的记录被触发器拒绝
+        assert!(db
+            .execute(
+                "DELETE FROM audit_log WHERE detail = ?1",
+                ["POST /api/schedule/arm"]
+            )
+            .is_err());
+        assert!(db
+            .execute("UPDATE audit_log SET detail = 'x'", [])
+            .is_err());
+
+        // 重新打开时只载入最近 RECENT_CAPACITY 条, 新记录在前
+        db.execute_batch("BEGIN").unwrap();
+        for i in 0..RECENT_CAPACITY {
+            insert_record(
+                &db,
+                &AuditRecord {
+                    time: Utc::now(),
+                    source: AuditSource::Ui,
+                    action: AuditAction::ThresholdChange,
+                    detail: format!("confidence={}", i),
+                },
+            );
+        }
+        db.execute_batch("COMMIT").unwrap();
+        AUDIT.lock().unwrap().recent.clear();
+        init(path).unwrap();
+        let latest = recent(usize::MAX);
+        assert!(latest.len() <= RECENT_CAPACITY);
+        assert!(!latest.iter().any(|r| r.detail == "yolov8s"));
+        assert!(position(&latest, "confidence=499") < position(&latest, "confidence=0"));
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,630 @@
+//! 检测结果的紧凑二进制编码 (网络/共享内存输出)
+//!
+//! JSON 每个框约 100 字节, 多路高帧率输出时带宽与解析开销都不小. 这里的编码:
+//! - 坐标按帧宽高量化为 16 位定点 (`x / width * 65535`), 置信度量化为 0..=255
+//! - 跟踪结果按轨迹ID做差量: 相对上一帧只发送新轨迹、变化量 (zigzag 变长整数, 通常 1 字节)
+//!   与消失的轨迹ID, 未变化的轨迹不发送; 每 `keyframe_interval` 帧发送一次完整的关键帧
+//! - 负载为 protobuf 编码 (schema 见 `proto/detections.proto`), 任何语言的 protobuf 库都能解码,
+//!   新增字段时旧消费者按 protobuf 规则跳过未知字段
+//!
+//! 每条消息前有 8 字节的版本头 (小端):
+//! magic `YD` | version u8 | kind u8 (0 = 关键帧, 1 = 差量帧) | payload_len u32
+//!
+//! 只有不兼容的改动才提升 version, 消费者遇到更高的版本应拒绝解码. 差量帧依赖上一帧,
+//! 只用于可靠有序的传输 (gRPC 流); 只保留最新一帧的共享内存输出全部使用关键帧
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{bail, Result};
+
+/// 消息头魔数
+pub const CODEC_MAGIC: [u8; 2] = *b"YD";
+/// 编码版本 (不兼容的改动才提升)
+pub const CODEC_VERSION: u8 = 1;
+/// 消息头长度
+pub const HEADER_SIZE: usize = 8;
+/// 默认关键帧间隔 (帧)
+pub const DEFAULT_KEYFRAME_INTERVAL: u32 = 30;
+
+/// 坐标量化的满量程
+const COORD_SCALE: f32 = 65535.0;
+/// 置信度量化的满量程
+const CONF_SCALE: f32 = 255.0;
+
+/// 消息类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameKind {
+    Key,   // 完整结果
+    Delta, // 相对上一帧的变化
+}
+
+/// 一个检测框 (解码后为量化精度)
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompactBox {
+    pub id: u32, // 经过跟踪器时为轨迹ID, 否则为框序号
+    pub class_id: u32,
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+    pub confidence: f32,
+    pub global_id: Option<u32>, // 跨摄像头全局ID
+}
+
+/// 一帧的检测结果
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CompactFrame {
+    pub view: u32,
+    pub sequence: u64, // 编码器按帧递增 (编码时忽略输入值)
+    pub pts_us: u64,
+    pub width: u32, // 坐标量化的参考尺寸
+    pub height: u32,
+    pub tracked: bool, // 是否经过跟踪器 (只有跟踪结果做差量)
+    pub inference_ms: f32,
+    pub boxes: Vec<CompactBox>,
+}
+
+/// 量化后的框 (编码器与解码器各保留一份上一帧的状态)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct QBox {
+    class_id: u32,
+    coords: [i32; 4],
+    confidence: i32,
+    global_id: u32, // 全局ID + 1, 0 表示没有
+}
+
+impl QBox {
+    fn quantize(b: &CompactBox, width: u32, height: u32) -> Self {
+        let qx = |v: f32| (v / width.max(1) as f32 * COORD_SCALE).round() as i32;
+        let qy = |v: f32| (v / height.max(1) as f32 * COORD_SCALE).round() as i32;
+        Self {
+            class_id: b.class_id,
+            coords: [qx(b.x1), qy(b.y1), qx(b.x2), qy(b.y2)],
+            confidence: (b.confidence.clamp(0.0, 1.0) * CONF_SCALE).round() as i32,
+            global_id: b.global_id.map_or(0, |g| g + 1),
+        }
+    }
+
+    fn restore(&self, id: u32, width: u32, height: u32) -> CompactBox {
+        let x = |q: i32| q as f32 / COORD_SCALE * width as f32;
+        let y = |q: i32| q as f32 / COORD_SCALE * height as f32;
+        CompactBox {
+            id,
+            class_id: self.class_id,
+            x1: x(self.coords[0]),
+            y1: y(self.coords[1]),
+            x2: x(self.coords[2]),
+            y2: y(self.coords[3]),
+            confidence: self.confidence as f32 / CONF_SCALE,
+            global_id: self.global_id.checked_sub(1),
+        }
+    }
+}
+
+/// 上一帧 (差量的基准)
+#[derive(Clone, Debug, Default)]
+struct Base {
+    sequence: u64,
+    width: u32,
+    height: u32,
+    tracked: bool,
+    boxes: Vec<(u32, QBox)>, // 按出现顺序
+}
+
+// protobuf 字段号 (与 proto/detections.proto 一致)
+const F_VIEW: u32 = 1;
+const F_SEQUENCE: u32 = 2;
+const F_BASE_SEQUENCE: u32 = 3;
+const F_PTS_US: u32 = 4;
+const F_WIDTH: u32 = 5;
+const F_HEIGHT: u32 = 6;
+const F_TRACKED: u32 = 7;
+const F_INFERENCE_US: u32 = 8;
+const F_BOXES: u32 = 9;
+const F_REMOVED: u32 = 10;
+
+const B_ID: u32 = 1;
+const B_CLASS_ID: u32 = 2;
+const B_COORDS: [u32; 4] = [3, 4, 5, 6];
+const B_CONFIDENCE: u32 = 7;
+const B_GLOBAL_ID: u32 = 8;
+const B_DELTA: u32 = 9;
+
+const WIRE_VARINT: u32 = 0;
+const WIRE_FIXED64: u32 = 1;
+const WIRE_LEN: u32 = 2;
+const WIRE_FIXED32: u32 = 5;
+
+fn put_varint(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        out.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+fn zigzag(v: i32) -> u64 {
+    ((v << 1) ^ (v >> 31)) as u32 as u64
+}
+
+fn unzigzag(v: u64) -> i32 {
+    let v = v as u32;
+    (v >> 1) as i32 ^ -((v & 1) as i32)
+}
+
+/// 写入变长整数字段, 0 按 proto3 规则省略
+fn put_field(out: &mut Vec<u8>, field: u32, v: u64) {
+    if v != 0 {
+        put_varint(out, (field << 3 | WIRE_VARINT) as u64);
+        put_varint(out, v);
+    }
+}
+
+fn put_bytes(out: &mut Vec<u8>, field: u32, bytes: &[u8]) {
+    put_varint(out, (field << 3 | WIRE_LEN) as u64);
+    put_varint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+/// protobuf 读取 (只支持本 schema 用到的类型, 其余字段跳过)
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn done(&self) -> bool {
+        self.pos >= self.buf.len()
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut v = 0u64;
+        for shift in (0..64).step_by(7) {
+            let Some(&byte) = self.buf.get(self.pos) else {
+                bail!("变长整数被截断");
+            };
+            self.pos += 1;
+            v |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(v);
+            }
+        }
+        bail!("变长整数过长")
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8]> {
+        let len = self.varint()? as usize;
+        let end = self.pos.checked_add(len).filter(|&e| e <= self.buf.len());
+        let Some(end) = end else {
+            bail!("字段长度超出消息");
+        };
+        let bytes = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    /// 下一个字段的 (字段号, wire 类型)
+    fn key(&mut self) -> Result<(u32, u32)> {
+        let key = self.varint()?;
+        Ok(((key >> 3) as u32, (key & 7) as u32))
+    }
+
+    /// 跳过未知字段
+    fn skip(&mut self, wire: u32) -> Result<()> {
+        let len = match wire {
+            WIRE_VARINT => return self.varint().map(|_| ()),
+            WIRE_LEN => return self.bytes().map(|_| ()),
+            WIRE_FIXED64 => 8,
+            WIRE_FIXED32 => 4,
+            _ => bail!("不支持的 wire 类型 {}", wire),
+        };
+        if self.pos + len > self.buf.len() {
+            bail!("字段长度超出消息");
+        }
+        self.pos += len;
+        Ok(())
+    }
+}
+
+/// 编码器 (每个输出连接一个, 保存差量的基准帧)
+#[derive(Debug)]
+pub struct Encoder {
+    keyframe_interval: u32,
+    since_key: u32,
+    sequence: u64,
+    base: Option<Base>,
+}
+
+impl Encoder {
+    /// `keyframe_interval` 为关键帧间隔, 1 表示只发关键帧 (不可靠或只保留最新帧的传输)
+    pub fn new(keyframe_interval: u32) -> Self {
+        Self {
+            keyframe_interval: keyframe_interval.max(1),
+            since_key: 0,
+            sequence: 0,
+            base: None,
+        }
+    }
+
+    /// 下一帧强制为关键帧 (如消费者重连)
+    pub fn force_keyframe(&mut self) {
+        self.base = None;
+    }
+
+    /// 编码一帧 (含版本头); 条件允许时发送差量帧
+    pub fn encode(&mut self, frame: &CompactFrame) -> Vec<u8> {
+        self.sequence += 1;
+        let boxes: Vec<(u32, QBox)> = frame
+            .boxes
+            .iter()
+            .map(|b| (b.id, QBox::quantize(b, frame.width, frame.height)))
+            .collect();
+        let delta_base = self.base.take().filter(|base| {
+            frame.tracked
+                && base.tracked
+                && (base.width, base.height) == (frame.width, frame.height)
+                && self.since_key < self.keyframe_interval
+        });
+        let kind = if delta_base.is_some() {
+            FrameKind::Delta
+        } else {
+            FrameKind::Key
+        };
+
+        let mut payload = Vec::with_capacity(32 + boxes.len() * 24);
+        put_field(&mut payload, F_VIEW, frame.view as u64);
+        put_field(&mut payload, F_SEQUENCE, self.sequence);
+        if let Some(base) = &delta_base {
+            put_field(&mut payload, F_BASE_SEQUENCE, base.sequence);
+        }
+        put_field(&mut payload, F_PTS_US, frame.pts_us);
+        put_field(&mut payload, F_WIDTH, frame.width as u64);
+        put_field(&mut payload, F_HEIGHT, frame.height as u64);
+        put_field(&mut payload, F_TRACKED, frame.tracked as u64);
+        put_field(
+            &mut payload,
+            F_INFERENCE_US,
+            (frame.inference_ms.max(0.0) * 1000.0).round() as u64,
+        );
+
+        let previous: HashMap<u32, QBox> = delta_base
+            .as_ref()
+            .map(|base| base.boxes.iter().copied().collect())
+            .unwrap_or_default();
+        let mut message = Vec::new();
+        for (id, q) in &boxes {
+            let old = previous.get(id);
+            if old == Some(q) {
+                continue; // 未变化的轨迹不发送
+            }
+            message.clear();
+            put_field(&mut message, B_ID, *id as u64);
+            put_field(&mut message, B_CLASS_ID, q.class_id as u64);
+            let from = old.copied().unwrap_or_default();
+            for (field, (v, o)) in B_COORDS.iter().zip(q.coords.iter().zip(from.coords)) {
+                put_field(&mut message, *field, zigzag(v.wrapping_sub(o)));
+            }
+            put_field(
+                &mut message,
+                B_CONFIDENCE,
+                zigzag(q.confidence - from.confidence),
+            );
+            put_field(&mut message, B_GLOBAL_ID, q.global_id as u64);
+            put_field(&mut message, B_DELTA, old.is_some() as u64);
+            put_bytes(&mut payload, F_BOXES, &message);
+        }
+        if let Some(base) = &delta_base {
+            let current: HashSet<u32> = boxes.iter().map(|(id, _)| *id).collect();
+            message.clear();
+            for (id, _) in &base.boxes {
+                if !current.contains(id) {
+                    put_varint(&mut message, *id as u64);
+                }
+            }
+            if !message.is_empty() {
+                put_bytes(&mut payload, F_REMOVED, &message);
+            }
+        }
+
+        self.since_key = match kind {
+            FrameKind::Key => 1,
+            FrameKind::Delta => self.since_key + 1,
+        };
+        // 基准按解码端的顺序保存: 保留下来的轨迹在前, 新轨迹追加在后
+        let mut order: Vec<(u32, QBox)> = match delta_base {
+            Some(base) => {
+                let current: HashMap<u32, QBox> = boxes.iter().copied().collect();
+                base.boxes
+                    .iter()
+                    .filter_map(|(id, _)| current.get(id).map(|q| (*id, *q)))
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+        for (id, q) in boxes {
+            if !order.iter().any(|(known, _)| *known == id) {
+                order.push((id, q));
+            }
+        }
+        self.base = Some(Base {
+            sequence: self.sequence,
+            width: frame.width,
+            height: frame.height,
+            tracked: frame.tracked,
+            boxes: order,
+        });
+
+        let mut out = Vec::with_capacity(HEADER_SIZE + payload.len());
+        out.extend_from_slice(&CODEC_MAGIC);
+        out.push(CODEC_VERSION);
+        out.push(match kind {
+            FrameKind::Key => 0,
+            FrameKind::Delta => 1,
+        });
+        out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        out.extend_from_slice(&payload);
+        out
+    }
+}
+
+impl Default for Encoder {
+    fn default() -> Self {
+        Self::new(DEFAULT_KEYFRAME_INTERVAL)
+    }
+}
+
+/// 读取消息头: (类型, 负载); 魔数或版本不符、长度不足时返回错误
+pub fn parse_header(bytes: &[u8]) -> Result<(FrameKind, &[u8])> {
+    if bytes.len() < HEADER_SIZE || bytes[..2] != CODEC_MAGIC {
+        bail!("检测结果消息头无效 (魔数不匹配)");
+    }
+    if bytes[2] > CODEC_VERSION {
+        bail!("不支持的检测结果编码版本 {}", bytes[2]);
+    }
+    let kind = match bytes[3] {
+        0 => FrameKind::Key,
+        1 => FrameKind::Delta,
+        k => bail!("未知的消息类型 {}", k),
+    };
+    let len = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+    let Some(payload) = bytes.get(HEADER_SIZE..HEADER_SIZE + len) else {
+        bail!("检测结果消息被截断");
+    };
+    Ok((kind, payload))
+}
+
+/// 解码器 (每个输入连接一个, 保存差量的基准帧)
+#[derive(Debug, Default)]
+pub struct Decoder {
+    base: Option<Base>,
+}
+
+impl Decoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 解码一条消息; 差量帧的基准不是上一帧 (丢帧或从中途开始) 时返回错误, 等待下一个关键帧
+    pub fn decode(&mut self, bytes: &[u8]) -> Result<CompactFrame> {
+        let (kind, payload) = parse_header(bytes)?;
+        let mut frame = CompactFrame::default();
+        let mut base_sequence = 0;
+        let mut updates: Vec<(u32, QBox, bool)> = Vec::new();
+        let mut removed: Vec<u32> = Vec::new();
+
+        let mut r = Reader::new(payload);
+        while !r.done() {
+            let (field, wire) = r.key()?;
+            match (field, wire) {
+                (F_VIEW, WIRE_VARINT) => frame.view = r.varint()? as u32,
+                (F_SEQUENCE, WIRE_VARINT) => frame.sequence = r.varint()?,
+                (F_BASE_SEQUENCE, WIRE_VARINT) => base_sequence = r.varint()?,
+                (F_PTS_US, WIRE_VARINT) => frame.pts_us = r.varint()?,
+                (F_WIDTH, WIRE_VARINT) => frame.width = r.varint()? as u32,
+                (F_HEIGHT, WIRE_VARINT) => frame.height = r.varint()? as u32,
+                (F_TRACKED, WIRE_VARINT) => frame.tracked = r.varint()? != 0,
+                (F_INFERENCE_US, WIRE_VARINT) => frame.inference_ms = r.varint()? as f32 / 1000.0,
+                (F_BOXES, WIRE_LEN) => updates.push(decode_box(r.bytes()?)?),
+                (F_REMOVED, WIRE_LEN) => {
+                    let mut ids = Reader::new(r.bytes()?);
+                    while !ids.done() {
+                        removed.push(ids.varint()? as u32);
+                    }
+                }
+                (_, wire) => r.skip(wire)?,
+            }
+        }
+
+        let mut boxes: Vec<(u32, QBox)> = match kind {
+            FrameKind::Key => Vec::new(),
+            FrameKind::Delta => match self.base.take() {
+                Some(base) if base.sequence == base_sequence => base.boxes,
+                _ => bail!(
+                    "差量帧 {} 缺少基准帧 {}, 等待关键帧",
+                    frame.sequence,
+                    base_sequence
+                ),
+            },
+        };
+        boxes.retain(|(id, _)| !removed.contains(id));
+        for (id, q, delta) in updates {
+            match boxes.iter_mut().find(|(known, _)| *known == id) {
+                Some((_, old)) if delta => {
+                    for (c, d) in old.coords.iter_mut().zip(q.coords) {
+                        *c = c.wrapping_add(d);
+                    }
+                    old.confidence += q.confidence;
+                    old.class_id = q.class_id;
+                    old.global_id = q.global_id;
+                }
+                Some((_, old)) => *old = q,
+                None if delta => bail!("差量帧引用了不存在的轨迹 {}", id),
+                None => boxes.push((id, q)),
+            }
+        }
+
+        frame.boxes = boxes
+            .iter()
+            .map(|(id, q)| q.restore(*id, frame.width, frame.height))
+            .collect();
+        self.base = Some(Base {
+            sequence: frame.sequence,
+            width: frame.width,
+            height: frame.height,
+            tracked: frame.tracked,
+            boxes,
+        });
+        Ok(frame)
+    }
+}
+
+/// 解码一个框: (ID, 量化值或差量, 是否差量)
+fn decode_box(bytes: &[u8]) -> Result<(u32, QBox, bool)> {
+    let mut id = 0;
+    let mut q = QBox::default();
+    let mut delta = false;
+    let mut r = Reader::new(bytes);
+    while !r.done() {
+        let (field, wire) = r.key()?;
+        if wire != WIRE_VARINT {
+            r.skip(wire)?;
+            continue;
+        }
+        let v = r.varint()?;
+        match field {
+            B_ID => id = v as u32,
+            B_CLASS_ID => q.class_id = v as u32,
+            B_CONFIDENCE => q.confidence = unzigzag(v),
+            B_GLOBAL_ID => q.global_id = v as u32,
+            B_DELTA => delta = v != 0,
+            f => {
+                if let Some(i) = B_COORDS.iter().position(|&c| c == f) {
+                    q.coords[i] = unzigzag(v);
+                }
+            }
+        }
+    }
+    Ok((id, q, delta))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(tracked: bool, boxes: &[(u32, f32)]) -> CompactFrame {
+        CompactFrame {
+            view: 3,
+            pts_us: 1_700_000_000_000_000,
+            width: 1920,
+            height: 1080,
+            tracked,
+            inference_ms: 6.5,
+            boxes: boxes
+                .iter()
+                .map(|&(id, x)| CompactBox {
+                    id,
+                    class_id: id % 3,
+                    x1: x,
+                    y1: 100.0,
+                    x2: x + 80.0,
+                    y2: 300.0,
+                    confidence: 0.8,
+                    global_id: (id == 7).then_some(42),
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn assert_close(decoded: &CompactFrame, expected: &CompactFrame) {
+        assert_eq!(
+            (decoded.view, decoded.tracked, decoded.boxes.len()),
+            (expected.view, expected.tracked, expected.boxes.len())
+        );
+        for e in &expected.boxes {
+            let d = decoded.boxes.iter().find(|d| d.id == e.id).unwrap();
+            assert_eq!((d.class_id, d.global_id), (e.class_id, e.global_id));
+            assert!((d.x1 - e.x1).abs() < 0.05 && (d.y2 - e.y2).abs() < 0.05);
+            assert!((d.confidence - e.confidence).abs() < 0.5 / 255.0 + 1e-6);
+        }
+    }
+
+    /// 关键帧往返: 坐标误差在量化精度内, 远小于 JSON 的体积
+    #[test]
+    fn test_keyframe_roundtrip() {
+        let input = frame(false, &[(0, 10.0), (1, 500.5), (2, 1800.25)]);
+        let bytes = Encoder::new(1).encode(&input);
+        assert_eq!(&bytes[..4], &[b'Y', b'D', CODEC_VERSION, 0]);
+        assert!(bytes.len() < 100, "{} 字节", bytes.len());
+
+        let decoded = Decoder::new().decode(&bytes).unwrap();
+        assert_close(&decoded, &input);
+        assert_eq!((decoded.sequence, decoded.pts_us), (1, input.pts_us));
+        assert!((decoded.inference_ms - 6.5).abs() < 1e-3);
+    }
+
+    /// 跟踪结果按轨迹做差量: 未变化的轨迹不发送, 新增/消失/移动的轨迹都能还原; 丢帧后等待关键帧
+    #[test]
+    fn test_delta_frames() {
+        let mut encoder = Encoder::new(3);
+        let mut decoder = Decoder::new();
+        let frames = [
+            frame(true, &[(5, 100.0), (7, 400.0), (9, 800.0)]),
+            frame(true, &[(5, 100.0), (7, 402.0), (9, 800.0)]),
+            frame(true, &[(7, 404.0), (9, 800.0), (11, 1200.0)]),
+        ];
+        let encoded: Vec<Vec<u8>> = frames.iter().map(|f| encoder.encode(f)).collect();
+        assert_eq!(encoded[1][3], 1);
+        for (bytes, expected) in encoded.iter().zip(&frames) {
+            assert_close(&decoder.decode(bytes).unwrap(), expected);
+        }
+
+        // 达到关键帧间隔后重新发送关键帧
+        let key = encoder.encode(&frames[2]);
+        assert_eq!(key[3], 0);
+        let lost = encoder.encode(&frames[1]);
+        let next = encoder.encode(&frames[0]);
+        let mut late = Decoder::new();
+        assert!(late.decode(&lost).is_err());
+        assert_close(&late.decode(&key).unwrap(), &frames[2]);
+        assert!(late.decode(&next).is_err());
+
+        // 十个轨迹只有一个移动了 2 像素: 差量帧远小于关键帧
+        let tracks: Vec<(u32, f32)> = (0..10).map(|i| (i, i as f32 * 150.0)).collect();
+        let mut moved = tracks.clone();
+        moved[4].1 += 2.0;
+        let mut encoder = Encoder::default();
+        let key = encoder.encode(&frame(true, &tracks));
+        let delta = encoder.encode(&frame(true, &moved));
+        assert!(
+            delta.len() * 4 < key.len(),
+            "{} / {}",
+            delta.len(),
+            key.len()
+        );
+    }
+
+    /// 版本头: 更高版本与截断的消息被拒绝, 负载中的未知字段被跳过
+    #[test]
+    fn test_header_and_unknown_fields() {
+        let mut bytes = Encoder::new(1).encode(&frame(false, &[(0, 10.0)]));
+        // 追加一个未知的变长字段 (字段号 15) 与一个未知的长度字段 (字段号 16)
+        let mut extra = Vec::new();
+        put_field(&mut extra, 15, 300);
+        put_bytes(&mut extra, 16, b"future");
+        bytes.extend_from_slice(&extra);
+        let len = (bytes.len() - HEADER_SIZE) as u32;
+        bytes[4..8].copy_from_slice(&len.to_le_bytes());
+        assert_eq!(Decoder::new().decode(&bytes).unwrap().boxes.len(), 1);
+
+        let mut newer = bytes.clone();
+        newer[2] = CODEC_VERSION + 1;
+        assert!(Decoder::new().decode(&newer).is_err());
+        assert!(Decoder::new().decode(&bytes[..bytes.len() - 1]).is_err());
+        assert!(parse_header(b"YOUT").is_err());
+    }
+}
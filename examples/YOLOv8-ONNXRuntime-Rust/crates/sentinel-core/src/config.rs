@@ -3,8 +3,17 @@
 // 模型配置参数
 // 用于命令行解析和程序化配置
 
-use crate::YOLOTask;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+
+/// 模型任务 (不依赖 ONNX Runtime, 核心库的后处理器也使用)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum YOLOTask {
+    // YOLO tasks
+    Classify,
+    Detect,
+    Pose,
+    Segment,
+}
 
 /// YOLOv8 模型配置参数 (用于命令行和手动配置)
 #[derive(Parser, Clone)]
@@ -46,6 +55,10 @@ pub struct Args {
     #[arg(long)]
     pub fp16: bool,
 
+    /// place TensorRT EP on a DLA core (Jetson), implies --trt
+    #[arg(long)]
+    pub dla_core: Option<u32>,
+
     /// specify YOLO task
     #[arg(long, value_enum)]
     pub task: Option<YOLOTask>,
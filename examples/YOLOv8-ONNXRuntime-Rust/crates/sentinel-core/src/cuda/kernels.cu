@@ -0,0 +1,161 @@
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+// CUDA 端到端管线内核 (运行时由 NVRTC 编译)
+//
+// 数值与 CPU 路径逐值一致:
+// - 颜色转换: BT.601 定点系数 (同 decode_filter / yuv_preprocess)
+// - 缩放: 最近邻, 映射 min((int)(i * (src / dst)), src - 1) (同 nearest_map)
+// - 解码: 每个 anchor 取首个最大类别分数 (同 decode_candidates)
+// - NMS: 按置信度降序 (同分按 anchor 升序) 的类别无关贪心 NMS, IoU 公式同 Bbox::iou
+
+// 候选框字段: x, y, w, h, score, class, anchor
+#define CAND 7
+// 输出框字段: x, y, w, h, score, class
+#define BOX 6
+
+__device__ __forceinline__ int nearest(int i, int src, int dst) {
+    float scale = (float)src / (float)dst;
+    return min((int)((float)i * scale), src - 1);
+}
+
+__device__ __forceinline__ void nv12_rgb(
+    const unsigned char* nv12, int w, int h, int x, int y, int* rgb) {
+    int cw = (w + 1) / 2;
+    const unsigned char* uv = nv12 + w * h + (y >> 1) * cw * 2 + (x >> 1) * 2;
+    int yv = nv12[y * w + x];
+    int u = uv[0] - 128;
+    int v = uv[1] - 128;
+    rgb[0] = min(max(yv + ((v * 179) >> 7), 0), 255);
+    rgb[1] = min(max(yv - ((u * 44) >> 7) - ((v * 91) >> 7), 0), 255);
+    rgb[2] = min(max(yv + ((u * 227) >> 7), 0), 255);
+}
+
+// 紧凑 NV12 → 归一化 NCHW (lut: 3×256, 按张量通道排列; c0..c2: 各通道取 RGB 的哪一个分量)
+extern "C" __global__ void nv12_to_nchw(
+    const unsigned char* nv12, int src_w, int src_h,
+    float* out, int dst_w, int dst_h,
+    const float* lut, int c0, int c1, int c2) {
+    int x = blockIdx.x * blockDim.x + threadIdx.x;
+    int y = blockIdx.y * blockDim.y + threadIdx.y;
+    if (x >= dst_w || y >= dst_h) return;
+
+    int rgb[3];
+    nv12_rgb(nv12, src_w, src_h, nearest(x, src_w, dst_w), nearest(y, src_h, dst_h), rgb);
+    int plane = dst_w * dst_h;
+    int i = y * dst_w + x;
+    out[i] = lut[rgb[c0]];
+    out[plane + i] = lut[256 + rgb[c1]];
+    out[2 * plane + i] = lut[512 + rgb[c2]];
+}
+
+// 紧凑 NV12 → RGBA (仅用于界面预览)
+extern "C" __global__ void nv12_to_rgba(
+    const unsigned char* nv12, int w, int h, unsigned char* rgba) {
+    int x = blockIdx.x * blockDim.x + threadIdx.x;
+    int y = blockIdx.y * blockDim.y + threadIdx.y;
+    if (x >= w || y >= h) return;
+
+    int rgb[3];
+    nv12_rgb(nv12, w, h, x, y, rgb);
+    unsigned char* p = rgba + (y * w + x) * 4;
+    p[0] = (unsigned char)rgb[0];
+    p[1] = (unsigned char)rgb[1];
+    p[2] = (unsigned char)rgb[2];
+    p[3] = 255;
+}
+
+// YOLOv8 输出 [4 + nc, na] → 候选框 (坐标乘以 rx/ry 并裁剪到 [0, max_w/max_h])
+extern "C" __global__ void yolo_decode(
+    const float* raw, int na, int nc, float conf,
+    float rx, float ry, float max_w, float max_h,
+    float* cand, int* count) {
+    int i = blockIdx.x * blockDim.x + threadIdx.x;
+    if (i >= na) return;
+
+    float best = raw[4 * na + i];
+    int id = 0;
+    for (int c = 1; c < nc; ++c) {
+        float v = raw[(4 + c) * na + i];
+        if (v > best) {
+            best = v;
+            id = c;
+        }
+    }
+    if (!(best >= conf)) return;
+
+    float w = raw[2 * na + i] * rx;
+    float h = raw[3 * na + i] * ry;
+    float x = raw[i] * rx - w / 2.0f;
+    float y = raw[na + i] * ry - h / 2.0f;
+
+    int k = atomicAdd(count, 1);
+    float* p = cand + k * CAND;
+    p[0] = fminf(fmaxf(x, 0.0f), max_w);
+    p[1] = fminf(fmaxf(y, 0.0f), max_h);
+    p[2] = w;
+    p[3] = h;
+    p[4] = best;
+    p[5] = (float)id;
+    p[6] = (float)i;
+}
+
+// 按置信度降序排名 (同分按 anchor 升序, 结果与 CPU 稳定排序一致)
+extern "C" __global__ void rank_sort(const float* cand, const int* count, float* sorted) {
+    int i = blockIdx.x * blockDim.x + threadIdx.x;
+    int n = *count;
+    if (i >= n) return;
+
+    float s = cand[i * CAND + 4];
+    float a = cand[i * CAND + 6];
+    int rank = 0;
+    for (int j = 0; j < n; ++j) {
+        float sj = cand[j * CAND + 4];
+        if (sj > s || (sj == s && cand[j * CAND + 6] < a)) ++rank;
+    }
+    for (int f = 0; f < CAND; ++f) sorted[rank * CAND + f] = cand[i * CAND + f];
+}
+
+__device__ __forceinline__ float box_iou(const float* a, const float* b) {
+    float l = fmaxf(a[0], b[0]);
+    float r = fminf(a[0] + a[2], b[0] + b[2]);
+    float t = fmaxf(a[1], b[1]);
+    float btm = fminf(a[1] + a[3], b[1] + b[3]);
+    float inter = fmaxf(r - l + 1.0f, 0.0f) * fmaxf(btm - t + 1.0f, 0.0f);
+    return inter / (a[2] * a[3] + b[2] * b[3] - inter);
+}
+
+// 抑制矩阵: mask[i * words + w] 的第 k 位表示 j = w * 64 + k (j > i) 与 i 的 IoU 超过阈值
+extern "C" __global__ void nms_mask(
+    const float* sorted, const int* count, float iou, unsigned long long* mask, int words) {
+    int i = blockIdx.x * blockDim.x + threadIdx.x;
+    int w = blockIdx.y;
+    int n = *count;
+    if (i >= n || w * 64 >= n) return;
+
+    unsigned long long bits = 0;
+    int end = min(w * 64 + 64, n);
+    for (int j = max(w * 64, i + 1); j < end; ++j) {
+        if (box_iou(sorted + i * CAND, sorted + j * CAND) > iou) bits |= 1ULL << (j - w * 64);
+    }
+    mask[i * words + w] = bits;
+}
+
+// 单线程贪心扫描抑制矩阵, out[0] 为保留数, 之后每框 BOX 个字段
+extern "C" __global__ void nms_reduce(
+    const float* sorted, const int* count, const unsigned long long* mask, int words,
+    unsigned long long* removed, float* out, int max_det) {
+    if (blockIdx.x != 0 || threadIdx.x != 0) return;
+
+    int n = *count;
+    int used = (n + 63) / 64;
+    for (int w = 0; w < used; ++w) removed[w] = 0;
+
+    int kept = 0;
+    for (int i = 0; i < n && kept < max_det; ++i) {
+        if ((removed[i >> 6] >> (i & 63)) & 1ULL) continue;
+        for (int f = 0; f < BOX; ++f) out[1 + kept * BOX + f] = sorted[i * CAND + f];
+        ++kept;
+        for (int w = i >> 6; w < used; ++w) removed[w] |= mask[i * words + w];
+    }
+    out[0] = (float)kept;
+}
@@ -0,0 +1,488 @@
+//! CUDA 端到端检测管线 (feature = "cuda")
+//!
+//! 面向 Jetson/IGX 部署, 帧数据全程留在显存:
+//! ```text
+//! NVDEC 设备帧 → 紧凑 NV12 (D2D) → nv12_to_nchw → TensorRT (IoBinding)
+//!             → yolo_decode → rank_sort → nms_mask → nms_reduce → 仅回传最终框
+//! ```
+//! 内核源码见 `kernels.cu`, 首次使用时由 NVRTC 编译.
+//! 目前支持 YOLOv8 系列检测头输出 `[1, 4 + nc, na]` (YOLOv5u/v8/v11),
+//! 其他模型或 FP16 输入由检测器回退到 CPU 路径.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use cudarc::driver::{
+    sys, CudaDevice, CudaFunction, CudaSlice, DevicePtr, LaunchAsync, LaunchConfig,
+};
+use ort::tensor::TensorElementType;
+
+use crate::models::PreprocessSpec;
+use crate::ort_backend::{Batch, OrtBackend, OrtConfig, OrtEP};
+use crate::{Bbox, DetectionResult, YOLOTask};
+
+const MODULE: &str = "yolo_cuda";
+const KERNELS: [&str; 6] = [
+    "nv12_to_nchw",
+    "nv12_to_rgba",
+    "yolo_decode",
+    "rank_sort",
+    "nms_mask",
+    "nms_reduce",
+];
+
+/// 每帧最多保留的检测框数
+pub const MAX_DET: usize = 1000;
+/// 候选框字段数 (x, y, w, h, score, class, anchor), 与 kernels.cu 的 CAND 一致
+const CAND: usize = 7;
+/// 输出框字段数 (x, y, w, h, score, class), 与 kernels.cu 的 BOX 一致
+const BOX: usize = 6;
+
+/// 打开设备并编译内核 (已加载时直接复用)
+pub fn device(ordinal: usize) -> Result<Arc<CudaDevice>> {
+    let dev = CudaDevice::new(ordinal)?;
+    if !dev.has_func(MODULE, KERNELS[0]) {
+        let ptx = cudarc::nvrtc::compile_ptx(include_str!("kernels.cu"))
+            .map_err(|e| anyhow!("NVRTC 编译内核失败: {:?}", e))?;
+        dev.load_ptx(ptx, MODULE, &KERNELS)?;
+    }
+    Ok(dev)
+}
+
+fn kernel(dev: &Arc<CudaDevice>, name: &str) -> Result<CudaFunction> {
+    dev.get_func(MODULE, name)
+        .ok_or_else(|| anyhow!("CUDA 内核未加载: {}", name))
+}
+
+/// 二维内核启动配置 (16×16 线程块)
+fn grid_2d(width: usize, height: usize) -> LaunchConfig {
+    LaunchConfig {
+        grid_dim: (width.div_ceil(16) as u32, height.div_ceil(16) as u32, 1),
+        block_dim: (16, 16, 1),
+        shared_mem_bytes: 0,
+    }
+}
+
+/// 显存中的紧凑 NV12 帧 (Y 平面 `w×h`, 交错 UV 平面 `ceil(w/2)×2 × ceil(h/2)`)
+pub struct DeviceNv12Frame {
+    dev: Arc<CudaDevice>,
+    data: CudaSlice<u8>,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl DeviceNv12Frame {
+    pub fn new(dev: Arc<CudaDevice>, width: usize, height: usize) -> Result<Self> {
+        let data = dev.alloc_zeros::<u8>(Self::byte_len(width, height))?;
+        Ok(Self {
+            dev,
+            data,
+            width,
+            height,
+        })
+    }
+
+    fn byte_len(width: usize, height: usize) -> usize {
+        width * height + width.div_ceil(2) * 2 * height.div_ceil(2)
+    }
+
+    /// UV 平面每行字节数
+    fn uv_pitch(&self) -> usize {
+        self.width.div_ceil(2) * 2
+    }
+
+    /// 从带步长的设备平面拷贝 (D2D, 不经过主机内存)
+    ///
+    /// 指针来自 FFmpeg 的 CUDA 硬件帧 (`AV_PIX_FMT_CUDA`, 底层为 NV12).
+    /// 源内存类型按统一寻址推断, FFmpeg 使用独立上下文时同样适用
+    ///
+    /// # Safety
+    /// 调用方保证两个平面在 `pitch * rows` 范围内有效, 且拷贝期间不被释放
+    pub unsafe fn copy_from_device_planes(
+        &mut self,
+        y_plane: sys::CUdeviceptr,
+        uv_plane: sys::CUdeviceptr,
+        y_pitch: usize,
+        uv_pitch: usize,
+    ) -> Result<()> {
+        self.dev.bind_to_thread()?;
+        let base = *self.data.device_ptr();
+        let planes = [
+            (y_plane, y_pitch, base, self.width, self.width, self.height),
+            (
+                uv_plane,
+                uv_pitch,
+                base + (self.width * self.height) as u64,
+                self.uv_pitch(),
+                self.uv_pitch(),
+                self.height.div_ceil(2),
+            ),
+        ];
+        for (src, src_pitch, dst, dst_pitch, width_bytes, rows) in planes {
+            let mut copy: sys::CUDA_MEMCPY2D = std::mem::zeroed();
+            copy.srcMemoryType = sys::CUmemorytype::CU_MEMORYTYPE_UNIFIED;
+            copy.srcDevice = src;
+            copy.srcPitch = src_pitch;
+            copy.dstMemoryType = sys::CUmemorytype::CU_MEMORYTYPE_DEVICE;
+            copy.dstDevice = dst;
+            copy.dstPitch = dst_pitch;
+            copy.WidthInBytes = width_bytes;
+            copy.Height = rows;
+            sys::lib().cuMemcpy2D_v2(&copy).result()?;
+        }
+        Ok(())
+    }
+
+    /// 转换为 RGBA 并回传主机 (仅界面预览使用, 检测路径不需要)
+    pub fn download_rgba(&self, rgba: &mut Vec<u8>) -> Result<()> {
+        let (w, h) = (self.width, self.height);
+        let mut out = self.dev.alloc_zeros::<u8>(w * h * 4)?;
+        let f = kernel(&self.dev, "nv12_to_rgba")?;
+        unsafe { f.launch(grid_2d(w, h), (&self.data, w as i32, h as i32, &mut out)) }?;
+        rgba.resize(w * h * 4, 0);
+        self.dev.dtoh_sync_copy_into(&out, rgba)?;
+        Ok(())
+    }
+}
+
+/// GPU 检测管线: 预处理 + TensorRT 推理 + 解码 + NMS
+pub struct CudaPipeline {
+    dev: Arc<CudaDevice>,
+    backend: OrtBackend,
+    device_id: i32,
+    input_w: usize,
+    input_h: usize,
+    nc: usize,
+    na: usize,
+    /// 源通道下标 (张量通道 c 取 RGB 的第几个分量)
+    channels: [i32; 3],
+    lut: CudaSlice<f32>,
+    input: CudaSlice<f32>,
+    output: CudaSlice<f32>,
+    candidates: CudaSlice<f32>,
+    sorted: CudaSlice<f32>,
+    count: CudaSlice<i32>,
+    mask: CudaSlice<u64>,
+    removed: CudaSlice<u64>,
+    result: CudaSlice<f32>,
+}
+
+impl CudaPipeline {
+    /// 以 TensorRT (不可用时 CUDA EP) 加载模型并分配所有设备缓冲
+    pub fn new(
+        model_path: &str,
+        device_id: usize,
+        input_size: u32,
+        spec: &PreprocessSpec,
+    ) -> Result<Self> {
+        let dev = device(device_id)?;
+        let backend = OrtBackend::build(OrtConfig {
+            f: model_path.to_string(),
+            task: Some(YOLOTask::Detect),
+            ep: OrtEP::Trt(device_id as i32),
+            trt_fp16: false,
+            dla_core: None,
+            batch: Batch::default(),
+            image_size: (Some(input_size), Some(input_size)),
+        })?;
+        if matches!(backend.ep(), OrtEP::CPU) {
+            return Err(anyhow!("CUDA/TensorRT 执行器不可用"));
+        }
+        if backend.dtype() != TensorElementType::Float32 {
+            return Err(anyhow!("GPU 管线仅支持 FP32 输入模型"));
+        }
+        if backend.channels() != 3 {
+            return Err(anyhow!("GPU 管线仅支持 3 通道输入模型"));
+        }
+
+        // 输出须为 [1, 4 + nc, na]
+        let shapes = backend.output_shapes();
+        let (nc, na) = match shapes.first().map(Vec::as_slice) {
+            Some(&[1, c, n]) if c > 4 && n > 0 => (c as usize - 4, n as usize),
+            other => return Err(anyhow!("GPU 管线不支持的输出形状: {:?}", other)),
+        };
+        let (input_w, input_h) = (backend.width() as usize, backend.height() as usize);
+        let words = na.div_ceil(64);
+
+        let lut: Vec<f32> = spec.lut().iter().flatten().copied().collect();
+        let channels = std::array::from_fn(|c| spec.source_channel(c) as i32);
+        println!(
+            "🚀 CUDA 端到端管线就绪: {}x{} | {}类 | {}锚点 | {:?}",
+            input_w,
+            input_h,
+            nc,
+            na,
+            backend.ep()
+        );
+
+        Ok(Self {
+            lut: dev.htod_copy(lut)?,
+            input: dev.alloc_zeros::<f32>(3 * input_w * input_h)?,
+            output: dev.alloc_zeros::<f32>((4 + nc) * na)?,
+            candidates: dev.alloc_zeros::<f32>(na * CAND)?,
+            sorted: dev.alloc_zeros::<f32>(na * CAND)?,
+            count: dev.alloc_zeros::<i32>(1)?,
+            mask: dev.alloc_zeros::<u64>(na * words)?,
+            removed: dev.alloc_zeros::<u64>(words)?,
+            result: dev.alloc_zeros::<f32>(1 + MAX_DET * BOX)?,
+            dev,
+            backend,
+            device_id: device_id as i32,
+            input_w,
+            input_h,
+            nc,
+            na,
+            channels,
+        })
+    }
+
+    /// 对一帧设备图像做检测, 返回框的坐标位于 `out_w × out_h` 画布
+    /// (检测器传入推理尺寸, 与 CPU 路径的后处理输出一致)
+    pub fn detect(
+        &mut self,
+        frame: &DeviceNv12Frame,
+        conf: f32,
+        iou: f32,
+        out_w: u32,
+        out_h: u32,
+    ) -> Result<Vec<DetectionResult>> {
+        let dev = self.dev.clone();
+        let (w, h) = (self.input_w, self.input_h);
+        let words = self.na.div_ceil(64);
+
+        // 1. NV12 → NCHW
+        let f = kernel(&dev, "nv12_to_nchw")?;
+        let [c0, c1, c2] = self.channels;
+        unsafe {
+            f.launch(
+                grid_2d(w, h),
+                (
+                    &frame.data,
+                    frame.width as i32,
+                    frame.height as i32,
+                    &mut self.input,
+                    w as i32,
+                    h as i32,
+                    &self.lut,
+                    c0,
+                    c1,
+                    c2,
+                ),
+            )
+        }?;
+
+        // 2. TensorRT 推理, 输入输出都绑定到已分配的设备缓冲
+        dev.synchronize()?;
+        self.backend.run_device(
+            self.device_id,
+            *self.input.device_ptr(),
+            *self.output.device_ptr(),
+            &[1, (4 + self.nc) as i64, self.na as i64],
+        )?;
+
+        // 3. 解码候选框
+        dev.memset_zeros(&mut self.count)?;
+        let f = kernel(&dev, "yolo_decode")?;
+        unsafe {
+            f.launch(
+                LaunchConfig::for_num_elems(self.na as u32),
+                (
+                    &self.output,
+                    self.na as i32,
+                    self.nc as i32,
+                    conf,
+                    out_w as f32 / w as f32,
+                    out_h as f32 / h as f32,
+                    out_w as f32,
+                    out_h as f32,
+                    &mut self.candidates,
+                    &mut self.count,
+                ),
+            )
+        }?;
+
+        // 4. 排序 + 抑制矩阵 + 贪心扫描
+        let f = kernel(&dev, "rank_sort")?;
+        unsafe {
+            f.launch(
+                LaunchConfig::for_num_elems(self.na as u32),
+                (&self.candidates, &self.count, &mut self.sorted),
+            )
+        }?;
+        let f = kernel(&dev, "nms_mask")?;
+        let cfg = LaunchConfig {
+            grid_dim: (self.na.div_ceil(256) as u32, words as u32, 1),
+            block_dim: (256, 1, 1),
+            shared_mem_bytes: 0,
+        };
+        unsafe {
+            f.launch(
+                cfg,
+                (&self.sorted, &self.count, iou, &mut self.mask, words as i32),
+            )
+        }?;
+        let f = kernel(&dev, "nms_reduce")?;
+        unsafe {
+            f.launch(
+                LaunchConfig::for_num_elems(1),
+                (
+                    &self.sorted,
+                    &self.count,
+                    &self.mask,
+                    words as i32,
+                    &mut self.removed,
+                    &mut self.result,
+                    MAX_DET as i32,
+                ),
+            )
+        }?;
+
+        // 5. 只回传保留数与最终框
+        let kept = dev.dtoh_sync_copy(&self.result.slice(0..1))?[0] as usize;
+        let boxes = if kept > 0 {
+            dev.dtoh_sync_copy(&self.result.slice(1..1 + kept * BOX))?
+        } else {
+            Vec::new()
+        };
+        let bboxes: Vec<Bbox> = boxes
+            .chunks_exact(BOX)
+            .map(|b| Bbox::new(b[0], b[1], b[2], b[3], b[5] as usize, b[4]))
+            .collect();
+        Ok(vec![DetectionResult::new(None, Some(bboxes), None, None)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{YOLOv8Config, YOLOv8Postprocessor};
+    use image::DynamicImage;
+    use ndarray::{Array, IxDyn};
+
+    /// GPU 解码 + NMS 与 CPU 后处理结果一致 (无 CUDA 设备时跳过)
+    #[test]
+    fn test_gpu_nms_matches_cpu() {
+        let Ok(dev) = device(0) else {
+            eprintln!("⚠️ 无 CUDA 设备, 跳过");
+            return;
+        };
+
+        // 密集候选: 2 类, 300 个 anchor, 大量重叠框
+        let (nc, na, size) = (2usize, 300usize, 320u32);
+        let mut seed = 7u32;
+        let mut next = || {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 8) as f32 / (1u32 << 24) as f32
+        };
+        let mut raw = vec![0f32; (4 + nc) * na];
+        for i in 0..na {
+            raw[i] = 40.0 + next() * 240.0;
+            raw[na + i] = 40.0 + next() * 240.0;
+            raw[2 * na + i] = 20.0 + next() * 60.0;
+            raw[3 * na + i] = 20.0 + next() * 60.0;
+            for c in 0..nc {
+                raw[(4 + c) * na + i] = (next() * 100.0).round() / 100.0;
+            }
+        }
+        let (conf, iou) = (0.25, 0.45);
+
+        // CPU 参考
+        let config = YOLOv8Config::new(
+            YOLOTask::Detect,
+            nc,
+            size as usize,
+            size as usize,
+            conf,
+            iou,
+        );
+        let xs = vec![Array::from_shape_vec(IxDyn(&[1, 4 + nc, na]), raw.clone()).unwrap()];
+        let expected = YOLOv8Postprocessor::new(config)
+            .postprocess(xs, &[DynamicImage::new_luma8(size, size)])
+            .unwrap();
+        let expected = expected[0].bboxes().cloned().unwrap_or_default();
+
+        // GPU: 直接从解码内核开始
+        let words = na.div_ceil(64);
+        let output = dev.htod_copy(raw).unwrap();
+        let mut candidates = dev.alloc_zeros::<f32>(na * CAND).unwrap();
+        let mut sorted = dev.alloc_zeros::<f32>(na * CAND).unwrap();
+        let mut count = dev.alloc_zeros::<i32>(1).unwrap();
+        let mut mask = dev.alloc_zeros::<u64>(na * words).unwrap();
+        let mut removed = dev.alloc_zeros::<u64>(words).unwrap();
+        let mut result = dev.alloc_zeros::<f32>(1 + MAX_DET * BOX).unwrap();
+        let s = size as f32;
+        unsafe {
+            kernel(&dev, "yolo_decode")
+                .unwrap()
+                .launch(
+                    LaunchConfig::for_num_elems(na as u32),
+                    (
+                        &output,
+                        na as i32,
+                        nc as i32,
+                        conf,
+                        1.0f32,
+                        1.0f32,
+                        s,
+                        s,
+                        &mut candidates,
+                        &mut count,
+                    ),
+                )
+                .unwrap();
+            kernel(&dev, "rank_sort")
+                .unwrap()
+                .launch(
+                    LaunchConfig::for_num_elems(na as u32),
+                    (&candidates, &count, &mut sorted),
+                )
+                .unwrap();
+            kernel(&dev, "nms_mask")
+                .unwrap()
+                .launch(
+                    LaunchConfig {
+                        grid_dim: (na.div_ceil(256) as u32, words as u32, 1),
+                        block_dim: (256, 1, 1),
+                        shared_mem_bytes: 0,
+                    },
+                    (&sorted, &count, iou, &mut mask, words as i32),
+                )
+                .unwrap();
+            kernel(&dev, "nms_reduce")
+                .unwrap()
+                .launch(
+                    LaunchConfig::for_num_elems(1),
+                    (
+                        &sorted,
+                        &count,
+                        &mask,
+                        words as i32,
+                        &mut removed,
+                        &mut result,
+                        MAX_DET as i32,
+                    ),
+                )
+                .unwrap();
+        }
+        let out = dev.dtoh_sync_copy(&result).unwrap();
+        let kept = out[0] as usize;
+        assert_eq!(kept, expected.len());
+        for (b, e) in out[1..1 + kept * BOX].chunks_exact(BOX).zip(&expected) {
+            let got = [b[0], b[1], b[2], b[3], b[5], b[4]];
+            let want = [
+                e.xmin(),
+                e.ymin(),
+                e.width(),
+                e.height(),
+                e.id() as f32,
+                e.confidence(),
+            ];
+            assert!(
+                got.iter().zip(&want).all(|(g, w)| (g - w).abs() < 1e-4),
+                "{:?} != {:?}",
+                got,
+                want
+            );
+        }
+    }
+}
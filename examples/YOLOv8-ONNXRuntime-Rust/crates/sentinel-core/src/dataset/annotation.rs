@@ -0,0 +1,386 @@
+//! 标注框与数据集导出
+//!
+//! 目录结构与 Ultralytics 训练脚本兼容:
+//! ```text
+//! dataset/
+//!   images/20240101_120000.123.jpg
+//!   labels/20240101_120000.123.txt   # 每行: class cx cy w h (按画面尺寸归一化)
+//!   classes.txt                      # 每行一个类别名, 行号即 class_id
+//!   annotations.json                 # COCO 格式, 每次导出追加 (category_id 与 class_id 相同, 从 0 开始)
+//! ```
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::utils::clock;
+
+/// 默认数据集目录
+pub const DATASET_DIR: &str = "dataset";
+
+/// 标注框最小边长 (像素), 更小的视为误触, 不导出
+const MIN_BOX_SIZE: f32 = 2.0;
+
+/// 标注框 (原始帧像素坐标, 左上/右下角)
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+    pub class_id: u32,
+}
+
+impl Annotation {
+    /// 由任意两个对角点构造 (自动排序)
+    pub fn from_corners(a: (f32, f32), b: (f32, f32), class_id: u32) -> Self {
+        Self {
+            x1: a.0.min(b.0),
+            y1: a.1.min(b.1),
+            x2: a.0.max(b.0),
+            y2: a.1.max(b.1),
+            class_id,
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.x2 - self.x1
+    }
+
+    pub fn height(&self) -> f32 {
+        self.y2 - self.y1
+    }
+
+    /// 裁剪到画面内
+    pub fn clamped(self, width: f32, height: f32) -> Self {
+        Self {
+            x1: self.x1.clamp(0.0, width),
+            y1: self.y1.clamp(0.0, height),
+            x2: self.x2.clamp(0.0, width),
+            y2: self.y2.clamp(0.0, height),
+            ..self
+        }
+    }
+
+    /// 尺寸是否足以导出
+    pub fn is_valid(&self) -> bool {
+        self.width() >= MIN_BOX_SIZE && self.height() >= MIN_BOX_SIZE
+    }
+
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        (self.x1..=self.x2).contains(&x) && (self.y1..=self.y2).contains(&y)
+    }
+
+    /// 四个角点 (左上, 右上, 右下, 左下)
+    pub fn corners(&self) -> [(f32, f32); 4] {
+        [
+            (self.x1, self.y1),
+            (self.x2, self.y1),
+            (self.x2, self.y2),
+            (self.x1, self.y2),
+        ]
+    }
+
+    /// 距 (x, y) 最近且在 tolerance 以内的角点序号
+    pub fn corner_at(&self, x: f32, y: f32, tolerance: f32) -> Option<usize> {
+        self.corners()
+            .iter()
+            .map(|(cx, cy)| (cx - x).hypot(cy - y))
+            .enumerate()
+            .filter(|(_, d)| *d <= tolerance)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    /// 把角点拖到 (x, y), 对角点保持不动
+    pub fn move_corner(&mut self, corner: usize, x: f32, y: f32) {
+        let (ox, oy) = self.corners()[(corner + 2) % 4];
+        *self = Self::from_corners((ox, oy), (x, y), self.class_id);
+    }
+
+    /// 整体平移
+    pub fn translate(&mut self, dx: f32, dy: f32) {
+        self.x1 += dx;
+        self.x2 += dx;
+        self.y1 += dy;
+        self.y2 += dy;
+    }
+
+    /// YOLO 标签行: `class cx cy w h` (按画面尺寸归一化)
+    pub fn to_yolo(&self, width: u32, height: u32) -> String {
+        let (w, h) = (width as f32, height as f32);
+        format!(
+            "{} {:.6} {:.6} {:.6} {:.6}",
+            self.class_id,
+            (self.x1 + self.x2) / 2.0 / w,
+            (self.y1 + self.y2) / 2.0 / h,
+            self.width() / w,
+            self.height() / h
+        )
+    }
+}
+
+/// COCO 图像条目
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CocoImage {
+    pub id: u64,
+    pub file_name: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// COCO 标注条目 (bbox 为 [x, y, w, h])
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CocoAnnotation {
+    pub id: u64,
+    pub image_id: u64,
+    pub category_id: u32,
+    pub bbox: [f32; 4],
+    pub area: f32,
+    pub iscrowd: u8,
+}
+
+/// COCO 类别条目
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CocoCategory {
+    pub id: u32,
+    pub name: String,
+}
+
+/// COCO 数据集 (annotations.json)
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CocoDataset {
+    pub images: Vec<CocoImage>,
+    pub annotations: Vec<CocoAnnotation>,
+    pub categories: Vec<CocoCategory>,
+}
+
+impl CocoDataset {
+    /// 追加一张图像及其标注, 返回图像 id
+    pub fn push(
+        &mut self,
+        file_name: String,
+        width: u32,
+        height: u32,
+        annotations: &[Annotation],
+    ) -> u64 {
+        let image_id = self.images.iter().map(|i| i.id + 1).max().unwrap_or(1);
+        let first_id = self.annotations.iter().map(|a| a.id + 1).max().unwrap_or(1);
+        self.images.push(CocoImage {
+            id: image_id,
+            file_name,
+            width,
+            height,
+        });
+        for (id, a) in (first_id..).zip(annotations) {
+            self.annotations.push(CocoAnnotation {
+                id,
+                image_id,
+                category_id: a.class_id,
+                bbox: [a.x1, a.y1, a.width(), a.height()],
+                area: a.width() * a.height(),
+                iscrowd: 0,
+            });
+        }
+        image_id
+    }
+
+    /// 按类别列表重建类别表
+    pub fn set_categories(&mut self, classes: &[String]) {
+        self.categories = classes
+            .iter()
+            .enumerate()
+            .map(|(id, name)| CocoCategory {
+                id: id as u32,
+                name: name.clone(),
+            })
+            .collect();
+    }
+}
+
+/// 读取数据集目录下的 classes.txt, 不存在时为空
+pub fn read_classes(dir: impl AsRef<Path>) -> Vec<String> {
+    read_class_file(dir.as_ref().join("classes.txt")).unwrap_or_default()
+}
+
+/// 读取类别文件: 每行一个类别名, 行号即 class_id, 忽略空行
+pub fn read_class_file(path: impl AsRef<Path>) -> Result<Vec<String>> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("读取类别文件失败: {}", path.display()))?;
+    Ok(content
+        .lines()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect())
+}
+
+/// 数据集写入器
+pub struct DatasetWriter {
+    dir: PathBuf,
+    coco: CocoDataset,
+}
+
+impl DatasetWriter {
+    /// 打开数据集目录 (不存在时创建), 已有的 annotations.json 继续追加
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        for sub in ["images", "labels"] {
+            fs::create_dir_all(dir.join(sub))
+                .with_context(|| format!("创建数据集目录失败: {}", dir.join(sub).display()))?;
+        }
+        let coco = match fs::read_to_string(dir.join("annotations.json")) {
+            Ok(json) => serde_json::from_str(&json).context("annotations.json 解析失败")?,
+            Err(_) => CocoDataset::default(),
+        };
+        Ok(Self { dir, coco })
+    }
+
+    /// 已导出的图像数
+    pub fn len(&self) -> usize {
+        self.coco.images.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.coco.images.is_empty()
+    }
+
+    /// 写入一帧: JPEG 图像 + YOLO 标签 + 追加 COCO 标注 + classes.txt, 返回图像路径
+    ///
+    /// 过小的框被忽略, 没有框时仍导出图像与空标签 (负样本)
+    pub fn write(
+        &mut self,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        annotations: &[Annotation],
+        classes: &[String],
+    ) -> Result<PathBuf> {
+        let image =
+            image::RgbaImage::from_raw(width, height, rgba.to_vec()).context("帧数据与尺寸不符")?;
+        let timestamp = clock::now().format("%Y%m%d_%H%M%S%.3f").to_string();
+        let image_path = self.add(
+            &timestamp,
+            &image::DynamicImage::ImageRgba8(image),
+            annotations,
+        )?;
+        self.save(classes)?;
+        Ok(image_path)
+    }
+
+    /// 写入图像与 YOLO 标签并追加 COCO 条目 (不落盘 annotations.json, 批量写入后调用 [`save`](Self::save))
+    ///
+    /// 同名文件已存在时追加序号, 避免覆盖
+    pub fn add(
+        &mut self,
+        stem: &str,
+        image: &image::DynamicImage,
+        annotations: &[Annotation],
+    ) -> Result<PathBuf> {
+        let (width, height) = (image.width(), image.height());
+        let annotations: Vec<Annotation> = annotations
+            .iter()
+            .map(|a| a.clamped(width as f32, height as f32))
+            .filter(Annotation::is_valid)
+            .collect();
+
+        let images = self.dir.join("images");
+        let mut unique = stem.to_string();
+        let mut n = 1;
+        while images.join(format!("{}.jpg", unique)).exists() {
+            unique = format!("{}_{}", stem, n);
+            n += 1;
+        }
+        let file_name = format!("{}.jpg", unique);
+        let image_path = images.join(&file_name);
+        image
+            .to_rgb8()
+            .save(&image_path)
+            .with_context(|| format!("保存图像失败: {}", image_path.display()))?;
+
+        let labels: Vec<String> = annotations
+            .iter()
+            .map(|a| a.to_yolo(width, height))
+            .collect();
+        fs::write(
+            self.dir.join("labels").join(format!("{}.txt", unique)),
+            labels.join("\n"),
+        )?;
+
+        self.coco.push(file_name, width, height, &annotations);
+        Ok(image_path)
+    }
+
+    /// 落盘 annotations.json 与 classes.txt
+    pub fn save(&mut self, classes: &[String]) -> Result<()> {
+        self.coco.set_categories(classes);
+        fs::write(
+            self.dir.join("annotations.json"),
+            serde_json::to_string_pretty(&self.coco)?,
+        )?;
+        fs::write(self.dir.join("classes.txt"), classes.join("\n"))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 对角点任意顺序构造, 拖动角点时对角固定, YOLO 行按画面归一化
+    #[test]
+    fn test_annotation_edit() {
+        let mut a = Annotation::from_corners((60.0, 80.0), (20.0, 40.0), 2);
+        assert_eq!((a.x1, a.y1, a.x2, a.y2), (20.0, 40.0, 60.0, 80.0));
+        assert_eq!(a.corner_at(58.0, 79.0, 5.0), Some(2));
+        assert_eq!(a.corner_at(40.0, 60.0, 5.0), None);
+
+        // 右下角拖过左上角: 框翻转, 左上角 (20, 40) 成为右下角
+        a.move_corner(2, 10.0, 30.0);
+        assert_eq!((a.x1, a.y1, a.x2, a.y2), (10.0, 30.0, 20.0, 40.0));
+
+        a.translate(10.0, 10.0);
+        assert_eq!(a.to_yolo(100, 100), "2 0.250000 0.450000 0.100000 0.100000");
+        assert!(!Annotation::from_corners((5.0, 5.0), (6.0, 50.0), 0).is_valid());
+    }
+
+    /// 导出图像/标签/类别, COCO 标注跨多次导出累积, id 连续
+    #[test]
+    fn test_dataset_writer() {
+        let dir = std::env::temp_dir().join(format!("dataset_test_{}", std::process::id()));
+        let classes = vec!["person".to_string(), "helmet".to_string()];
+        let rgba = vec![128u8; 8 * 4 * 4];
+        let boxes = [
+            Annotation::from_corners((0.0, 0.0), (4.0, 2.0), 1),
+            Annotation::from_corners((3.0, 1.0), (20.0, 9.0), 0), // 超出画面部分被裁剪
+        ];
+
+        let mut writer = DatasetWriter::open(&dir).unwrap();
+        let image = writer.write(&rgba, 8, 4, &boxes, &classes).unwrap();
+        assert!(image.exists());
+        let label = image
+            .with_extension("txt")
+            .to_string_lossy()
+            .replace("images", "labels");
+        assert_eq!(fs::read_to_string(label).unwrap().lines().count(), 2);
+
+        // 重新打开后继续追加
+        let mut writer = DatasetWriter::open(&dir).unwrap();
+        writer.write(&rgba, 8, 4, &boxes[..1], &classes).unwrap();
+        let coco: CocoDataset =
+            serde_json::from_str(&fs::read_to_string(dir.join("annotations.json")).unwrap())
+                .unwrap();
+        assert_eq!(coco.images.len(), 2);
+        assert_eq!(
+            coco.annotations.iter().map(|a| a.id).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(coco.annotations[1].bbox, [3.0, 1.0, 5.0, 3.0]);
+        assert_eq!(coco.categories[1].name, "helmet");
+        assert_eq!(read_classes(&dir), classes);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,325 @@
+//! 离线自动标注: 用大模型预标注图片目录, 导出 YOLO 数据集
+//!
+//! 输出与标注模式相同的目录结构 (images/ + labels/ + annotations.json + classes.txt),
+//! 可再用标注模式或其他工具人工校对. 启用裁剪时额外输出 `crops/<类别名>/<图像名>_<序号>.jpg`,
+//! 便于训练分类/ReID 模型. 结束时在输出目录写入本次运行的统计报告 (report.json/report.html)
+
+use super::annotation::{Annotation, DatasetWriter};
+use crate::analytics::RunReport;
+use crate::models::Model;
+use crate::Bbox;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use image::DynamicImage;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// 支持的图片扩展名 (小写)
+const IMAGE_EXTENSIONS: [&str; 6] = ["jpg", "jpeg", "png", "bmp", "webp", "tiff"];
+
+/// annotations.json 落盘间隔 (张), 中途中断时已处理的图片不丢失 COCO 条目
+const SAVE_INTERVAL: usize = 100;
+
+/// 自动标注参数
+#[derive(Clone, Debug)]
+pub struct AutoLabelConfig {
+    pub conf: f32,            // 置信度阈值, 低于此值的框不导出
+    pub classes: Vec<String>, // 类别名 (下标即 class_id), 为空时使用模型元数据中的 names
+    pub crops: bool,          // 是否按类别导出目标裁剪图
+}
+
+impl Default for AutoLabelConfig {
+    fn default() -> Self {
+        Self {
+            conf: 0.5,
+            classes: Vec::new(),
+            crops: false,
+        }
+    }
+}
+
+/// 自动标注统计
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AutoLabelReport {
+    pub images: usize,                  // 已导出的图片数
+    pub boxes: usize,                   // 已导出的标注框数
+    pub per_class: Vec<usize>,          // 各类别框数 (下标即 class_id)
+    pub failed: Vec<(PathBuf, String)>, // 读取或推理失败的图片及原因
+    pub stats: PathBuf,                 // 统计报告 (HTML) 路径
+}
+
+/// 列出目录下的图片 (不递归), 按文件名排序
+pub fn list_images(dir: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("读取图片目录失败: {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        })
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// 自动标注写入器: 逐张接收检测结果, 写入数据集与裁剪图
+pub struct AutoLabeler {
+    writer: DatasetWriter,
+    output: PathBuf,
+    config: AutoLabelConfig,
+    report: AutoLabelReport,
+    stats: RunReport,
+}
+
+impl AutoLabeler {
+    /// `source` 为输入目录, 仅用于统计报告
+    pub fn new(
+        source: impl AsRef<Path>,
+        output: impl AsRef<Path>,
+        config: AutoLabelConfig,
+    ) -> Result<Self> {
+        let output = output.as_ref().to_path_buf();
+        Ok(Self {
+            stats: RunReport::new(source.as_ref().display().to_string()),
+            writer: DatasetWriter::open(&output)?,
+            report: AutoLabelReport {
+                per_class: vec![0; config.classes.len()],
+                ..Default::default()
+            },
+            output,
+            config,
+        })
+    }
+
+    /// 写入一张图片: 过滤低置信度与类别表外的框, 图像名沿用源文件名
+    pub fn label(&mut self, source: &Path, image: &DynamicImage, bboxes: &[Bbox]) -> Result<()> {
+        let (annotations, confidences): (Vec<Annotation>, Vec<f32>) = bboxes
+            .iter()
+            .filter(|b| b.confidence() >= self.config.conf && b.id() < self.config.classes.len())
+            .map(|b| {
+                let a = Annotation::from_corners(
+                    (b.xmin(), b.ymin()),
+                    (b.xmax(), b.ymax()),
+                    b.id() as u32,
+                );
+                (
+                    a.clamped(image.width() as f32, image.height() as f32),
+                    b.confidence(),
+                )
+            })
+            .filter(|(a, _)| a.is_valid())
+            .unzip();
+
+        let stem = source
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| format!("{:06}", self.report.images));
+        let image_path = self.writer.add(&stem, image, &annotations)?;
+        if self.config.crops {
+            // 与图像同名 (重名时 add 已追加序号)
+            let stem = image_path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or(stem);
+            self.save_crops(&stem, image, &annotations)?;
+        }
+
+        for a in &annotations {
+            self.report.per_class[a.class_id as usize] += 1;
+        }
+        // 图片没有拍摄时间, 按文件修改时间统计 (视频抽帧的图片基本对应录制时间)
+        let modified = fs::metadata(source)
+            .and_then(|m| m.modified())
+            .ok()
+            .map(DateTime::<Local>::from);
+        let classes = &self.config.classes;
+        self.stats.record(
+            modified,
+            annotations
+                .iter()
+                .zip(&confidences)
+                .map(|(a, &confidence)| {
+                    (
+                        classes[a.class_id as usize].as_str(),
+                        confidence,
+                        a.width(),
+                        a.height(),
+                    )
+                }),
+        );
+        self.report.images += 1;
+        self.report.boxes += annotations.len();
+        if self.report.images.is_multiple_of(SAVE_INTERVAL) {
+            self.writer.save(&self.config.classes)?;
+        }
+        Ok(())
+    }
+
+    /// 记录失败的图片 (跳过, 不中断整个目录)
+    pub fn fail(&mut self, source: &Path, error: &anyhow::Error) {
+        eprintln!("⚠️ 跳过 {}: {:#}", source.display(), error);
+        self.report
+            .failed
+            .push((source.to_path_buf(), format!("{:#}", error)));
+    }
+
+    /// 落盘 annotations.json 与 classes.txt 及统计报告, 返回统计
+    pub fn finish(mut self) -> Result<AutoLabelReport> {
+        self.writer.save(&self.config.classes)?;
+        self.report.stats = self.stats.save(self.output.join("report"))?;
+        Ok(self.report)
+    }
+
+    fn save_crops(
+        &self,
+        stem: &str,
+        image: &DynamicImage,
+        annotations: &[Annotation],
+    ) -> Result<()> {
+        for (i, a) in annotations.iter().enumerate() {
+            let dir = self
+                .output
+                .join("crops")
+                .join(&self.config.classes[a.class_id as usize]);
+            fs::create_dir_all(&dir)
+                .with_context(|| format!("创建裁剪目录失败: {}", dir.display()))?;
+            let crop = image.crop_imm(
+                a.x1 as u32,
+                a.y1 as u32,
+                a.width().ceil() as u32,
+                a.height().ceil() as u32,
+            );
+            let path = dir.join(format!("{}_{}.jpg", stem, i));
+            crop.to_rgb8()
+                .save(&path)
+                .with_context(|| format!("保存裁剪图失败: {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// 对目录下的每张图片推理并导出 YOLO 数据集
+///
+/// 类别名为空时取模型元数据中的 names; 单张图片读取或推理失败只记录并跳过
+pub fn autolabel(
+    model: &mut dyn Model,
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    mut config: AutoLabelConfig,
+) -> Result<AutoLabelReport> {
+    let paths = list_images(&input)?;
+    if config.classes.is_empty() {
+        config.classes = model
+            .engine_mut()
+            .and_then(|engine| engine.names())
+            .context("模型缺少 names 元数据, 请用 --classes 指定类别文件")?;
+    }
+    // 模型内部阈值与导出阈值一致, 减少无用的后处理
+    model.set_conf(config.conf);
+    println!(
+        "🏷️ 自动标注: {} 张图片, {} 个类别 → {}",
+        paths.len(),
+        config.classes.len(),
+        output.as_ref().display()
+    );
+
+    let mut labeler = AutoLabeler::new(&input, &output, config)?;
+    let start = Instant::now();
+    for (i, path) in paths.iter().enumerate() {
+        let result = image::ImageReader::open(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|r| Ok(r.with_guessed_format()?.decode()?))
+            .and_then(|image| {
+                let results = model.forward(std::slice::from_ref(&image))?;
+                let bboxes = results
+                    .first()
+                    .and_then(|r| r.bboxes())
+                    .cloned()
+                    .unwrap_or_default();
+                labeler.label(path, &image, &bboxes)
+            });
+        if let Err(e) = result {
+            labeler.fail(path, &e);
+        }
+        if (i + 1).is_multiple_of(SAVE_INTERVAL) {
+            println!(
+                "⏳ {}/{} ({:.1} 张/秒)",
+                i + 1,
+                paths.len(),
+                (i + 1) as f64 / start.elapsed().as_secs_f64()
+            );
+        }
+    }
+    labeler.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 只列出图片文件, 按文件名排序, 扩展名不区分大小写
+    #[test]
+    fn test_list_images() {
+        let dir = std::env::temp_dir().join(format!("autolabel_list_{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub.jpg")).unwrap();
+        for name in ["b.JPG", "a.png", "notes.txt", "c"] {
+            fs::write(dir.join(name), b"").unwrap();
+        }
+        let names: Vec<String> = list_images(&dir)
+            .unwrap()
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, ["a.png", "b.JPG"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// 低置信度与类别表外的框被过滤, 图像沿用源文件名, 按类别输出裁剪图
+    #[test]
+    fn test_autolabeler() {
+        let dir = std::env::temp_dir().join(format!("autolabel_out_{}", std::process::id()));
+        let config = AutoLabelConfig {
+            conf: 0.5,
+            classes: vec!["person".to_string(), "car".to_string()],
+            crops: true,
+        };
+        let mut labeler = AutoLabeler::new("/data/raw", &dir, config).unwrap();
+        let image = DynamicImage::new_rgb8(64, 48);
+        let bboxes = [
+            Bbox::new(4.0, 4.0, 10.0, 20.0, 0, 0.9),
+            Bbox::new(30.0, 10.0, 40.0, 20.0, 1, 0.8), // 超出画面部分被裁剪
+            Bbox::new(0.0, 0.0, 10.0, 10.0, 1, 0.3),   // 置信度过低
+            Bbox::new(0.0, 0.0, 10.0, 10.0, 7, 0.9),   // 类别表外
+        ];
+        labeler
+            .label(Path::new("/data/raw/frame_001.png"), &image, &bboxes)
+            .unwrap();
+        let report = labeler.finish().unwrap();
+        assert_eq!(report.images, 1);
+        assert_eq!(report.boxes, 2);
+        assert_eq!(report.per_class, [1, 1]);
+
+        assert!(dir.join("images/frame_001.jpg").exists());
+        let labels = fs::read_to_string(dir.join("labels/frame_001.txt")).unwrap();
+        assert_eq!(labels.lines().count(), 2);
+        assert!(labels.starts_with("0 0.140625 0.291667"));
+        let car = image::open(dir.join("crops/car/frame_001_1.jpg")).unwrap();
+        assert_eq!((car.width(), car.height()), (34, 20));
+        assert!(dir.join("crops/person/frame_001_0.jpg").exists());
+        assert_eq!(
+            fs::read_to_string(dir.join("classes.txt")).unwrap(),
+            "person\ncar"
+        );
+        assert_eq!(report.stats, dir.join("report.html"));
+        let stats: RunReport =
+            serde_json::from_str(&fs::read_to_string(dir.join("report.json")).unwrap()).unwrap();
+        assert_eq!(stats.classes["car"].count, 1);
+        assert_eq!(stats.sizes.iter().sum::<u64>(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,18 @@
+//! 数据集采集 (Dataset Collection)
+//!
+//! 把实时画面变成训练数据
+//! - Annotation: 标注框 (帧像素坐标) 与编辑操作
+//! - DatasetWriter: 导出图像 + YOLO txt 标签 + COCO JSON
+//! - autolabel: 离线用大模型预标注图片目录 (需要 `ort` 功能)
+
+pub mod annotation;
+#[cfg(feature = "ort")]
+pub mod autolabel;
+
+// Re-exports
+pub use annotation::{
+    read_class_file, read_classes, Annotation, CocoAnnotation, CocoCategory, CocoDataset,
+    CocoImage, DatasetWriter, DATASET_DIR,
+};
+#[cfg(feature = "ort")]
+pub use autolabel::{autolabel, list_images, AutoLabelConfig, AutoLabelReport, AutoLabeler};
@@ -0,0 +1,140 @@
+//! 贴边检测框的处理策略
+//!
+//! 进出口摄像头的画面边缘不断出现半身、半辆车: 框只覆盖目标的可见部分, 随目标进出画面收缩,
+//! 按完整观测融合会拉偏卡尔曼的位置与速度估计. 框的任一边距画面边缘不超过 `margin` 像素时为贴边:
+//! - `keep`: 与其他检测相同 (默认)
+//! - `drop`: 后处理时丢弃, 不进入跟踪与分析
+//! - `partial`: 保留并标记为不完整目标 (`DetectionResult::partial`), 跟踪器融合时观测噪声乘以 `noise_scale`
+//!
+//! ```json
+//! { "policy": "partial", "margin": 2, "noise_scale": 4, "streams": { "1": "drop" } }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use super::tracker::PartialObservation;
+
+/// 默认配置文件路径
+pub const BORDER_CONFIG_PATH: &str = "border_policy.json";
+
+/// 进程内的贴边策略配置, 未启用时为 None
+static CONFIG: OnceLock<BorderConfig> = OnceLock::new();
+
+/// 贴边检测框的处理方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BorderPolicy {
+    #[default]
+    Keep,
+    Drop,
+    Partial,
+}
+
+/// 贴边策略配置 (border_policy.json)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BorderConfig {
+    pub policy: BorderPolicy,
+    pub margin: f32,                          // 距画面边缘不超过该像素视为贴边
+    pub noise_scale: f32,                     // partial: 贴边观测的观测噪声倍数
+    pub streams: BTreeMap<u32, BorderPolicy>, // 逻辑流 → 策略
+}
+
+impl Default for BorderConfig {
+    fn default() -> Self {
+        Self {
+            policy: BorderPolicy::Keep,
+            margin: 2.0,
+            noise_scale: 4.0,
+            streams: BTreeMap::new(),
+        }
+    }
+}
+
+impl BorderConfig {
+    /// 从文件加载配置, 文件不存在或解析失败时为 None (贴边框照常处理)
+    pub fn load(path: &str) -> Option<Self> {
+        let json = fs::read_to_string(path).ok()?;
+        match serde_json::from_str::<Self>(&json) {
+            Ok(config) => {
+                println!(
+                    "✅ 贴边策略配置已从 {} 加载 ({:?}, 边距 {}px, {} 路流单独配置)",
+                    path,
+                    config.policy,
+                    config.margin,
+                    config.streams.len()
+                );
+                Some(config)
+            }
+            Err(e) => {
+                eprintln!("⚠️  贴边策略配置解析失败: {}, 不启用", e);
+                None
+            }
+        }
+    }
+
+    /// 逻辑流的策略, 未单独配置时为 `policy`
+    pub fn policy(&self, view: u32) -> BorderPolicy {
+        self.streams.get(&view).copied().unwrap_or(self.policy)
+    }
+
+    /// width×height 画面上的贴边判定
+    pub fn edges(&self, width: u32, height: u32) -> PartialObservation {
+        PartialObservation {
+            width: width as f32,
+            height: height as f32,
+            margin: self.margin,
+            noise_scale: self.noise_scale,
+        }
+    }
+}
+
+/// 启用贴边策略 (之后处理的帧生效)
+pub fn install(config: BorderConfig) {
+    let _ = CONFIG.set(config);
+}
+
+/// 已启用的配置, 未启用时为 None
+pub fn config() -> Option<&'static BorderConfig> {
+    CONFIG.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detection::types::BBox;
+
+    fn bbox(x1: f32, y1: f32, x2: f32, y2: f32) -> BBox {
+        BBox {
+            x1,
+            y1,
+            x2,
+            y2,
+            confidence: 0.9,
+            class_id: 0,
+        }
+    }
+
+    /// 按逻辑流选择策略, 任一边在边距内即为贴边
+    #[test]
+    fn test_policy_and_edges() {
+        let config: BorderConfig =
+            serde_json::from_str(r#"{"policy": "partial", "margin": 4, "streams": {"1": "drop"}}"#)
+                .unwrap();
+        assert_eq!(config.policy(0), BorderPolicy::Partial);
+        assert_eq!(config.policy(1), BorderPolicy::Drop);
+        assert_eq!(config.noise_scale, 4.0);
+        assert_eq!(BorderConfig::default().policy(1), BorderPolicy::Keep);
+
+        let edges = config.edges(1920, 1080);
+        assert!(!edges.touches(&bbox(10.0, 10.0, 200.0, 400.0)));
+        assert!(edges.touches(&bbox(3.0, 10.0, 200.0, 400.0)));
+        assert!(edges.touches(&bbox(1700.0, 600.0, 1920.0, 1077.0)));
+        assert_eq!(edges.noise_scale(&bbox(0.0, 10.0, 50.0, 400.0)), 4.0);
+        assert_eq!(edges.noise_scale(&bbox(10.0, 10.0, 50.0, 400.0)), 1.0);
+    }
+}
@@ -0,0 +1,242 @@
+//! 按类别的检测框几何过滤 (尺寸与宽高比)
+//!
+//! 传感器噪点、反光常被检测成只有几个像素高的 "人", 或细长得不可能是车的 "车".
+//! 后处理时 (缩放回原始分辨率后、跟踪之前) 按类别检查框的宽、高与宽高比, 不合理的框直接丢弃:
+//! - 宽高上下限写数字为像素, 写 `"3%"` 这样的字符串为画面宽 (或高) 的百分比
+//! - 宽高比为 宽/高, 如人的 `max_aspect: 1.5` 过滤横躺的噪点框
+//! - `streams` 按逻辑流覆盖, 某个类别在流内有配置时整体替换 `classes` 中该类别的限制
+//!
+//! ```json
+//! {
+//!   "classes": { "0": { "min_height": 24, "max_height": "90%", "max_aspect": 1.5 },
+//!                "2": { "min_width": "2%", "min_aspect": 0.5, "max_aspect": 4 } },
+//!   "streams": { "1": { "0": { "min_height": "5%" } } }
+//! }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use super::types::BBox;
+
+/// 默认配置文件路径
+pub const BOX_FILTER_CONFIG_PATH: &str = "box_filters.json";
+
+/// 进程内的几何过滤配置, 未启用时为 None
+static CONFIG: OnceLock<BoxFilterConfig> = OnceLock::new();
+
+/// 尺寸限制: 像素或画面尺寸的百分比
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "ExtentRepr", into = "ExtentRepr")]
+pub enum Extent {
+    Pixels(f32),
+    Percent(f32),
+}
+
+/// JSON 中的写法: 数字 (像素) 或 "N%"
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ExtentRepr {
+    Pixels(f32),
+    Text(String),
+}
+
+impl TryFrom<ExtentRepr> for Extent {
+    type Error = String;
+
+    fn try_from(repr: ExtentRepr) -> Result<Self, Self::Error> {
+        match repr {
+            ExtentRepr::Pixels(px) => Ok(Extent::Pixels(px)),
+            ExtentRepr::Text(text) => {
+                let text = text.trim();
+                let value = match text.strip_suffix('%') {
+                    Some(pct) => pct.trim().parse().map(Extent::Percent),
+                    None => text.parse().map(Extent::Pixels),
+                };
+                value.map_err(|_| format!("无效的尺寸 \"{}\" (应为像素数或 \"N%\")", text))
+            }
+        }
+    }
+}
+
+impl From<Extent> for ExtentRepr {
+    fn from(extent: Extent) -> Self {
+        match extent {
+            Extent::Pixels(px) => ExtentRepr::Pixels(px),
+            Extent::Percent(pct) => ExtentRepr::Text(format!("{}%", pct)),
+        }
+    }
+}
+
+impl Extent {
+    /// 换算为像素, `frame` 为画面对应方向的尺寸
+    pub fn pixels(&self, frame: f32) -> f32 {
+        match *self {
+            Extent::Pixels(px) => px,
+            Extent::Percent(pct) => frame * pct / 100.0,
+        }
+    }
+}
+
+/// 单个类别的几何限制, 未设置的项不检查
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClassLimits {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_width: Option<Extent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_width: Option<Extent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_height: Option<Extent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_height: Option<Extent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_aspect: Option<f32>, // 宽/高
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_aspect: Option<f32>,
+}
+
+impl ClassLimits {
+    /// 框在 frame_w×frame_h 的画面上是否满足限制
+    pub fn allows(&self, bbox: &BBox, frame_w: f32, frame_h: f32) -> bool {
+        let w = bbox.x2 - bbox.x1;
+        let h = bbox.y2 - bbox.y1;
+        let within = |value: f32, min: Option<Extent>, max: Option<Extent>, frame: f32| {
+            min.is_none_or(|m| value >= m.pixels(frame))
+                && max.is_none_or(|m| value <= m.pixels(frame))
+        };
+        if !within(w, self.min_width, self.max_width, frame_w)
+            || !within(h, self.min_height, self.max_height, frame_h)
+        {
+            return false;
+        }
+        if self.min_aspect.is_none() && self.max_aspect.is_none() {
+            return true;
+        }
+        if h <= 0.0 {
+            return false;
+        }
+        let aspect = w / h;
+        self.min_aspect.is_none_or(|m| aspect >= m) && self.max_aspect.is_none_or(|m| aspect <= m)
+    }
+}
+
+/// 几何过滤配置 (box_filters.json)
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BoxFilterConfig {
+    pub classes: HashMap<u32, ClassLimits>, // 类别ID → 限制
+    pub streams: BTreeMap<u32, HashMap<u32, ClassLimits>>, // 逻辑流 → 按类别覆盖
+}
+
+impl BoxFilterConfig {
+    /// 从文件加载配置, 文件不存在或解析失败时为 None (不过滤)
+    pub fn load(path: &str) -> Option<Self> {
+        let json = fs::read_to_string(path).ok()?;
+        match serde_json::from_str::<Self>(&json) {
+            Ok(config) => {
+                println!(
+                    "✅ 检测框几何过滤配置已从 {} 加载 ({} 个类别, {} 路流单独配置)",
+                    path,
+                    config.classes.len(),
+                    config.streams.len()
+                );
+                Some(config)
+            }
+            Err(e) => {
+                eprintln!("⚠️  检测框几何过滤配置解析失败: {}, 不启用", e);
+                None
+            }
+        }
+    }
+
+    /// 逻辑流上某个类别的限制, 流内配置优先
+    pub fn limits(&self, view: u32, class_id: u32) -> Option<&ClassLimits> {
+        self.streams
+            .get(&view)
+            .and_then(|classes| classes.get(&class_id))
+            .or_else(|| self.classes.get(&class_id))
+    }
+
+    /// 框在逻辑流 view 的 frame_w×frame_h 画面上是否保留
+    pub fn allows(&self, view: u32, bbox: &BBox, frame_w: u32, frame_h: u32) -> bool {
+        self.limits(view, bbox.class_id)
+            .is_none_or(|limits| limits.allows(bbox, frame_w as f32, frame_h as f32))
+    }
+}
+
+/// 启用几何过滤 (之后处理的帧生效)
+pub fn install(config: BoxFilterConfig) {
+    let _ = CONFIG.set(config);
+}
+
+/// 已启用的配置, 未启用时为 None
+pub fn config() -> Option<&'static BoxFilterConfig> {
+    CONFIG.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bbox(class_id: u32, w: f32, h: f32) -> BBox {
+        BBox {
+            x1: 100.0,
+            y1: 100.0,
+            x2: 100.0 + w,
+            y2: 100.0 + h,
+            confidence: 0.9,
+            class_id,
+        }
+    }
+
+    /// 像素与百分比写法都能解析, 按画面尺寸换算后检查宽高与宽高比
+    #[test]
+    fn test_class_limits() {
+        let config: BoxFilterConfig = serde_json::from_str(
+            r#"{"classes": {"0": {"min_height": 24, "max_height": "50%", "max_aspect": 1.5},
+                            "2": {"min_width": " 2 % "}}}"#,
+        )
+        .unwrap();
+        let person = config.limits(0, 0).unwrap();
+        assert_eq!(person.min_height, Some(Extent::Pixels(24.0)));
+        assert_eq!(person.max_height, Some(Extent::Percent(50.0)));
+
+        assert!(config.allows(0, &bbox(0, 30.0, 80.0), 1920, 1080));
+        assert!(!config.allows(0, &bbox(0, 4.0, 8.0), 1920, 1080)); // 噪点
+        assert!(!config.allows(0, &bbox(0, 200.0, 600.0), 1920, 1080)); // 超过画面一半高
+        assert!(!config.allows(0, &bbox(0, 120.0, 60.0), 1920, 1080)); // 横躺
+        assert!(!config.allows(0, &bbox(2, 30.0, 30.0), 1920, 1080)); // 窄于 2% 画面宽
+        assert!(config.allows(0, &bbox(2, 40.0, 30.0), 1920, 1080));
+        assert!(config.allows(0, &bbox(5, 1.0, 1.0), 1920, 1080)); // 未配置的类别不过滤
+
+        assert!(serde_json::from_str::<BoxFilterConfig>(
+            r#"{"classes": {"0": {"min_height": "tall"}}}"#
+        )
+        .is_err());
+    }
+
+    /// 逻辑流内的配置整体替换该类别的默认限制, 其他类别与其他流不受影响
+    #[test]
+    fn test_stream_override() {
+        let config: BoxFilterConfig = serde_json::from_str(
+            r#"{"classes": {"0": {"min_height": 24, "max_aspect": 1.5}, "2": {"min_width": 40}},
+                "streams": {"1": {"0": {"min_height": "10%"}}}}"#,
+        )
+        .unwrap();
+        let lying = bbox(0, 180.0, 110.0);
+        assert!(!config.allows(0, &lying, 1920, 1080));
+        assert!(config.allows(1, &lying, 1920, 1080)); // 流 1 不限制宽高比
+        assert!(!config.allows(1, &bbox(0, 30.0, 80.0), 1920, 1080));
+        assert!(!config.allows(1, &bbox(2, 30.0, 30.0), 1920, 1080));
+
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(
+            serde_json::from_str::<BoxFilterConfig>(&json).unwrap(),
+            config
+        );
+    }
+}
@@ -0,0 +1,910 @@
+//! ByteTrack 算法实现
+//! ByteTrack: Simple and effective multi-object tracking
+//!
+//! 核心思想:
+//! 1. 高低分检测框分开处理
+//! 2. 高分框优先匹配 (IOU)
+//! 3. 低分框救援丢失的轨迹
+//! 4. 默认纯运动模型; 可选融合掩码 IOU / ReID 外观线索, 减少人群重叠时的 ID 交换
+
+use super::deepsort::{PersonTracker, Session};
+use super::journal::{TrackState, TrackerSnapshot};
+use super::tracker::{
+    compute_iou, KalmanBoxFilter, PartialObservation, TrackPoint, TrackStats, TrackerParams,
+};
+use super::types::BBox;
+
+/// 外观特征 EMA 动量 (旧特征权重)
+const FEATURE_MOMENTUM: f32 = 0.9;
+
+/// 关联代价权重
+///
+/// 相似度 = 各线索的加权平均, 代价 = 1 - 相似度, 高低分匹配阈值作用于融合后的相似度。
+/// 检测或轨迹缺少某项线索(非分割模型/未加载ReID)时, 该项不参与加权,
+/// 因此默认权重下与原纯 IOU 匹配完全一致
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AssociationWeights {
+    /// 框 IOU 权重
+    pub iou: f32,
+    /// 掩码 IOU 权重 (分割模型启用时生效)
+    pub mask_iou: f32,
+    /// ReID 余弦相似度权重 (需要 OSNet 模型)
+    pub reid: f32,
+}
+
+impl Default for AssociationWeights {
+    fn default() -> Self {
+        Self {
+            iou: 1.0,
+            mask_iou: 0.0,
+            reid: 0.0,
+        }
+    }
+}
+
+impl AssociationWeights {
+    pub fn uses_masks(&self) -> bool {
+        self.mask_iou > 0.0
+    }
+
+    pub fn uses_reid(&self) -> bool {
+        self.reid > 0.0
+    }
+
+    /// 融合相似度 (缺失的线索不参与加权)
+    fn fuse(&self, iou: f32, mask_iou: Option<f32>, reid: Option<f32>) -> f32 {
+        let mut sum = self.iou.max(0.0) * iou;
+        let mut total = self.iou.max(0.0);
+        if let (Some(m), true) = (mask_iou, self.uses_masks()) {
+            sum += self.mask_iou * m;
+            total += self.mask_iou;
+        }
+        if let (Some(r), true) = (reid, self.uses_reid()) {
+            sum += self.reid * r;
+            total += self.reid;
+        }
+        if total > 0.0 {
+            sum / total
+        } else {
+            iou
+        }
+    }
+}
+
+/// 实例掩码 (下采样的二值网格, 用于掩码 IOU 关联)
+#[derive(Clone, Debug)]
+pub struct InstanceMask {
+    width: usize,
+    height: usize,
+    /// 每个网格在帧坐标系中的宽高 (像素)
+    cell_w: f32,
+    cell_h: f32,
+    data: Vec<u8>,
+    area: usize,
+}
+
+impl InstanceMask {
+    /// 从模型输出的灰度掩码构建
+    ///
+    /// - `luma`: `src_w × src_h` 灰度掩码, >127 视为前景
+    /// - `step`: 下采样步长 (源像素), 640 输入取 4 即 160×160 网格
+    /// - `scale_x`/`scale_y`: 掩码坐标 → 帧坐标的缩放, 与检测框一致
+    ///
+    /// 掩码为空时返回 None
+    pub fn from_luma(
+        luma: &[u8],
+        src_w: usize,
+        src_h: usize,
+        step: usize,
+        scale_x: f32,
+        scale_y: f32,
+    ) -> Option<Self> {
+        let step = step.max(1);
+        if src_w == 0 || src_h == 0 || luma.len() < src_w * src_h {
+            return None;
+        }
+        let (width, height) = (src_w.div_ceil(step), src_h.div_ceil(step));
+        let mut data = vec![0u8; width * height];
+        for y in 0..height {
+            let row = &luma[y * step * src_w..(y * step + 1) * src_w];
+            for x in 0..width {
+                data[y * width + x] = (row[x * step] > 127) as u8;
+            }
+        }
+        let area = data.iter().filter(|&&v| v != 0).count();
+        if area == 0 {
+            return None;
+        }
+        Some(Self {
+            width,
+            height,
+            cell_w: step as f32 * scale_x,
+            cell_h: step as f32 * scale_y,
+            data,
+            area,
+        })
+    }
+
+    /// 前景网格数
+    pub fn area(&self) -> usize {
+        self.area
+    }
+
+    /// 将自身平移 (dx, dy) 帧像素后与 `other` 的 IOU
+    ///
+    /// 轨迹掩码按卡尔曼预测的位移平移, 补偿目标在两帧间的运动
+    pub fn iou_shifted(&self, other: &InstanceMask, dx: f32, dy: f32) -> f32 {
+        if self.width != other.width || self.height != other.height {
+            return 0.0;
+        }
+        let sx = (dx / self.cell_w.max(1e-6)).round() as isize;
+        let sy = (dy / self.cell_h.max(1e-6)).round() as isize;
+        let (w, h) = (self.width as isize, self.height as isize);
+
+        let mut inter = 0usize;
+        for y in 0..h {
+            let ty = y + sy;
+            if ty < 0 || ty >= h {
+                continue;
+            }
+            let src = &self.data[(y * w) as usize..((y + 1) * w) as usize];
+            let dst = &other.data[(ty * w) as usize..((ty + 1) * w) as usize];
+            for (x, &v) in src.iter().enumerate() {
+                let tx = x as isize + sx;
+                if v != 0 && tx >= 0 && tx < w && dst[tx as usize] != 0 {
+                    inter += 1;
+                }
+            }
+        }
+        let union = self.area + other.area - inter;
+        if union == 0 {
+            0.0
+        } else {
+            inter as f32 / union as f32
+        }
+    }
+}
+
+/// 余弦相似度 (截断到 [0, 1])
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a < 1e-6 || norm_b < 1e-6 {
+        return 0.0;
+    }
+    (dot / (norm_a * norm_b)).clamp(0.0, 1.0)
+}
+
+/// ByteTrack 跟踪对象
+#[derive(Clone)]
+pub struct ByteTrackedPerson {
+    /// 唯一跟踪ID
+    pub id: u32,
+
+    /// 当前边界框 (卡尔曼滤波平滑后)
+    pub bbox: BBox,
+
+    /// 卡尔曼滤波器
+    kalman: KalmanBoxFilter,
+
+    /// 历史轨迹 (中心点)
+    pub trajectory: Vec<TrackPoint>,
+
+    /// 连续丢失帧数
+    pub frames_lost: u32,
+
+    /// 显示颜色
+    pub color: (u8, u8, u8),
+
+    /// 总共被跟踪的帧数
+    pub total_frames: u32,
+
+    /// 检测置信度 (用于判断是否为高分轨迹)
+    pub score: f32,
+
+    /// 已达到最小命中次数 (未确认轨迹不输出)
+    pub confirmed: bool,
+
+    /// 是否静止
+    is_stationary: bool,
+
+    /// 最近一次匹配的实例掩码
+    mask: Option<InstanceMask>,
+
+    /// 掩码对应的框中心 (用于按预测位移平移掩码)
+    mask_center: (f32, f32),
+
+    /// 外观特征 (EMA 平滑, L2 归一化)
+    features: Option<Vec<f32>>,
+}
+
+impl ByteTrackedPerson {
+    fn new(id: u32, bbox: BBox, color: (u8, u8, u8)) -> Self {
+        // ByteTrack优化: 降低观测噪声(r=0.5),更信任检测结果,快速响应移动
+        let kalman = KalmanBoxFilter::new(&bbox, 0.1, 0.5);
+        let smoothed_bbox = kalman.get_state_bbox();
+
+        let center = TrackPoint {
+            x: (smoothed_bbox.x1 + smoothed_bbox.x2) / 2.0,
+            y: (smoothed_bbox.y1 + smoothed_bbox.y2) / 2.0,
+        };
+
+        Self {
+            id,
+            bbox: smoothed_bbox,
+            kalman,
+            trajectory: vec![center],
+            frames_lost: 0,
+            color,
+            total_frames: 1,
+            score: bbox.confidence,
+            confirmed: false,
+            is_stationary: false,
+            mask: None,
+            mask_center: (0.0, 0.0),
+            features: None,
+        }
+    }
+
+    /// 外观特征 (仅 ReID 权重大于0时提取)
+    pub fn features(&self) -> Option<&[f32]> {
+        self.features.as_deref()
+    }
+
+    /// 记录本帧匹配检测的掩码与外观特征
+    fn update_cues(&mut self, bbox: &BBox, mask: Option<&InstanceMask>, features: Option<&[f32]>) {
+        if let Some(mask) = mask {
+            self.mask = Some(mask.clone());
+            self.mask_center = ((bbox.x1 + bbox.x2) / 2.0, (bbox.y1 + bbox.y2) / 2.0);
+        }
+        if let Some(new) = features {
+            let mut merged: Vec<f32> = match &self.features {
+                Some(old) if old.len() == new.len() => old
+                    .iter()
+                    .zip(new)
+                    .map(|(o, n)| FEATURE_MOMENTUM * o + (1.0 - FEATURE_MOMENTUM) * n)
+                    .collect(),
+                _ => new.to_vec(),
+            };
+            let norm: f32 = merged.iter().map(|x| x * x).sum::<f32>().sqrt();
+            if norm > 1e-6 {
+                merged.iter_mut().for_each(|x| *x /= norm);
+            }
+            self.features = Some(merged);
+        }
+    }
+
+    /// 与检测的融合相似度
+    fn similarity(
+        &self,
+        detection: &BBox,
+        mask: Option<&InstanceMask>,
+        features: Option<&[f32]>,
+        weights: &AssociationWeights,
+    ) -> f32 {
+        let predicted = self.get_predicted_bbox();
+        let iou = compute_iou(detection, &predicted);
+
+        let mask_iou = match (&self.mask, mask) {
+            (Some(track_mask), Some(det_mask)) if weights.uses_masks() => {
+                let dx = (predicted.x1 + predicted.x2) / 2.0 - self.mask_center.0;
+                let dy = (predicted.y1 + predicted.y2) / 2.0 - self.mask_center.1;
+                Some(track_mask.iou_shifted(det_mask, dx, dy))
+            }
+            _ => None,
+        };
+        let reid = match (&self.features, features) {
+            (Some(a), Some(b)) if weights.uses_reid() => Some(cosine_similarity(a, b)),
+            _ => None,
+        };
+
+        weights.fuse(iou, mask_iou, reid)
+    }
+
+    fn predict(&mut self) {
+        self.kalman.predict();
+        self.bbox = self.kalman.get_state_bbox();
+    }
+
+    /// 融合匹配的检测, `noise_scale` 为观测噪声倍数 (贴边的不完整观测大于1)
+    fn update(&mut self, bbox: BBox, noise_scale: f32) {
+        // 检测是否静止
+        let predicted = self.kalman.get_predicted_bbox();
+        let dx = (bbox.x1 + bbox.x2) / 2.0 - (predicted.x1 + predicted.x2) / 2.0;
+        let dy = (bbox.y1 + bbox.y2) / 2.0 - (predicted.y1 + predicted.y2) / 2.0;
+        let movement = (dx * dx + dy * dy).sqrt();
+
+        self.is_stationary = movement < 3.0; // 小于3像素视为静止
+
+        self.kalman.update_with_noise(&bbox, noise_scale);
+        self.bbox = self.kalman.get_state_bbox();
+        self.frames_lost = 0;
+        self.total_frames += 1;
+        self.score = bbox.confidence;
+
+        // 添加轨迹点
+        let center = TrackPoint {
+            x: (self.bbox.x1 + self.bbox.x2) / 2.0,
+            y: (self.bbox.y1 + self.bbox.y2) / 2.0,
+        };
+        self.trajectory.push(center);
+
+        // 只保留最近50个点
+        if self.trajectory.len() > 50 {
+            self.trajectory.remove(0);
+        }
+    }
+
+    fn mark_lost(&mut self) {
+        self.frames_lost += 1;
+        self.predict();
+    }
+
+    fn get_predicted_bbox(&self) -> BBox {
+        self.kalman.get_predicted_bbox()
+    }
+
+    /// 日志中保存的状态
+    fn state(&self) -> TrackState {
+        TrackState {
+            id: self.id,
+            bbox: [self.bbox.x1, self.bbox.y1, self.bbox.x2, self.bbox.y2],
+            score: self.score,
+            frames_lost: self.frames_lost,
+            total_frames: self.total_frames,
+            confirmed: self.confirmed,
+            color: self.color,
+            trajectory: self.trajectory.iter().map(|p| (p.x, p.y)).collect(),
+            features: self.features.clone(),
+        }
+    }
+
+    /// 从日志恢复 (卡尔曼滤波器从保存的位置重新开始)
+    fn from_state(state: &TrackState) -> Self {
+        let mut track = Self::new(state.id, state.to_bbox(), state.color);
+        track.frames_lost = state.frames_lost;
+        track.total_frames = state.total_frames;
+        track.confirmed = state.confirmed;
+        track.trajectory = state
+            .trajectory
+            .iter()
+            .map(|&(x, y)| TrackPoint { x, y })
+            .collect();
+        track.features = state.features.clone();
+        track
+    }
+}
+
+/// ByteTrack 追踪器
+pub struct ByteTracker {
+    /// 当前跟踪的人
+    tracked_persons: Vec<ByteTrackedPerson>,
+
+    /// 下一个分配的ID
+    next_id: u32,
+
+    /// 生命周期参数 (丢失帧数/命中次数/匹配与分数阈值)
+    params: TrackerParams,
+
+    /// 累计删除的轨迹数
+    removed_count: u64,
+
+    /// 预定义颜色表
+    color_palette: Vec<(u8, u8, u8)>,
+
+    /// 关联代价权重
+    association: AssociationWeights,
+
+    /// OSNet ReID模型 (ReID 权重 > 0 时加载)
+    reid_model: Option<Session>,
+
+    /// 贴边观测的判定与噪声倍数 (边界策略为 partial 时)
+    partial: Option<PartialObservation>,
+}
+
+impl ByteTracker {
+    pub fn new() -> Self {
+        let color_palette = vec![
+            (255, 64, 64),   // 红色
+            (64, 255, 64),   // 绿色
+            (64, 64, 255),   // 蓝色
+            (255, 255, 64),  // 黄色
+            (255, 64, 255),  // 品红
+            (64, 255, 255),  // 青色
+            (255, 128, 0),   // 橙色
+            (128, 0, 255),   // 紫色
+            (255, 128, 192), // 粉色
+            (128, 255, 128), // 浅绿
+        ];
+
+        Self {
+            tracked_persons: Vec::new(),
+            next_id: 1,
+            params: TrackerParams::bytetrack(),
+            removed_count: 0,
+            color_palette,
+            association: AssociationWeights::default(),
+            reid_model: None,
+            partial: None,
+        }
+    }
+
+    /// 设置生命周期参数 (下一帧生效)
+    pub fn set_params(&mut self, params: TrackerParams) {
+        println!(
+            "🔧 ByteTrack参数: 最大丢失{}帧 | 最小命中{} | IOU {:.2}/{:.2} | 分数 {:.2}/{:.2}",
+            params.max_lost_frames,
+            params.min_hits,
+            params.high_iou_threshold,
+            params.low_iou_threshold,
+            params.high_score_threshold,
+            params.low_score_threshold
+        );
+        self.params = params;
+    }
+
+    pub fn params(&self) -> TrackerParams {
+        self.params
+    }
+
+    /// 轨迹统计
+    pub fn stats(&self) -> TrackStats {
+        let lost = self
+            .tracked_persons
+            .iter()
+            .filter(|t| t.frames_lost > 0)
+            .count();
+        TrackStats {
+            active: self.tracked_persons.len() - lost,
+            lost,
+            removed: self.removed_count,
+        }
+    }
+
+    /// 跟踪状态快照 (写入跟踪状态日志)
+    pub fn snapshot(&self) -> TrackerSnapshot {
+        TrackerSnapshot {
+            tracker: "bytetrack".to_string(),
+            saved_at: chrono::Utc::now(),
+            next_id: self.next_id,
+            removed: self.removed_count,
+            tracks: self.tracked_persons.iter().map(|t| t.state()).collect(),
+        }
+    }
+
+    /// 从快照恢复轨迹, 之后新建的轨迹ID接着快照中的编号
+    pub fn restore(&mut self, snapshot: &TrackerSnapshot) {
+        self.tracked_persons = snapshot
+            .tracks
+            .iter()
+            .map(ByteTrackedPerson::from_state)
+            .collect();
+        self.next_id = snapshot.resume_id();
+        self.removed_count = snapshot.removed;
+    }
+
+    /// 设置关联代价权重, ReID 权重大于0时按需加载 OSNet 模型
+    pub fn set_association(&mut self, weights: AssociationWeights) {
+        if weights.uses_reid() && self.reid_model.is_none() {
+            self.reid_model = PersonTracker::load_reid_model();
+        }
+        println!(
+            "🔗 ByteTrack关联权重: IOU={:.2} 掩码IOU={:.2} ReID={:.2}",
+            weights.iou, weights.mask_iou, weights.reid
+        );
+        self.association = weights;
+    }
+
+    pub fn association(&self) -> AssociationWeights {
+        self.association
+    }
+
+    /// 设置贴边观测的处理 (None 时所有观测同等对待), 画面尺寸变化时需重新设置
+    pub fn set_partial_observation(&mut self, partial: Option<PartialObservation>) {
+        self.partial = partial;
+    }
+
+    /// 提取检测框的 ReID 特征
+    ///
+    /// 未启用 ReID 权重、未加载模型或没有帧数据时返回空表;
+    /// 只为达到低分阈值(会参与匹配)的检测提取
+    pub fn extract_features(
+        &mut self,
+        detections: &[BBox],
+        frame_rgba: Option<(&[u8], u32, u32)>,
+    ) -> Vec<Option<Vec<f32>>> {
+        let (model, (data, width, height)) = match (&mut self.reid_model, frame_rgba) {
+            (Some(model), Some(frame)) if self.association.uses_reid() => (model, frame),
+            _ => return Vec::new(),
+        };
+        detections
+            .iter()
+            .map(|det| {
+                if det.confidence < self.params.low_score_threshold {
+                    return None;
+                }
+                let features = PersonTracker::extract_reid_features_from_image(
+                    model, data, width, height, det,
+                );
+                // 提取失败时返回零向量, 视为缺失
+                features.iter().any(|v| v.abs() > 1e-6).then_some(features)
+            })
+            .collect()
+    }
+
+    /// 更新跟踪 (ByteTrack 三步匹配, 纯框 IOU)
+    pub fn update(&mut self, detections: &[BBox]) -> &[ByteTrackedPerson] {
+        self.update_with_cues(detections, &[], &[])
+    }
+
+    /// 更新跟踪, 同时使用掩码/外观线索
+    ///
+    /// `masks`/`features` 与 `detections` 一一对应, 可为空表或含 None
+    pub fn update_with_cues(
+        &mut self,
+        detections: &[BBox],
+        masks: &[Option<InstanceMask>],
+        features: &[Option<Vec<f32>>],
+    ) -> &[ByteTrackedPerson] {
+        let mask_of = |idx: usize| masks.get(idx).and_then(|m| m.as_ref());
+        let features_of = |idx: usize| features.get(idx).and_then(|f| f.as_deref());
+        let partial = self.partial;
+        let noise_of = |idx: usize| partial.map_or(1.0, |p| p.noise_scale(&detections[idx]));
+
+        // 1. 所有轨迹先预测
+        for tracked in &mut self.tracked_persons {
+            tracked.predict();
+        }
+
+        // 2. 分离高低分检测框
+        let mut high_dets: Vec<(usize, &BBox)> = Vec::new();
+        let mut low_dets: Vec<(usize, &BBox)> = Vec::new();
+
+        for (idx, det) in detections.iter().enumerate() {
+            if det.confidence >= self.params.high_score_threshold {
+                high_dets.push((idx, det));
+            } else if det.confidence >= self.params.low_score_threshold {
+                low_dets.push((idx, det));
+            }
+        }
+
+        // 3. 第一轮匹配: 高分检测 + 所有轨迹
+        let mut matched_det = vec![false; detections.len()];
+        let mut matched_track = vec![false; self.tracked_persons.len()];
+
+        let assignments = self.match_detections_to_tracks(
+            &high_dets,
+            &(0..self.tracked_persons.len()).collect::<Vec<_>>(),
+            self.params.high_iou_threshold,
+            masks,
+            features,
+        );
+
+        for (det_idx, track_idx) in assignments {
+            matched_det[det_idx] = true;
+            matched_track[track_idx] = true;
+            let track = &mut self.tracked_persons[track_idx];
+            track.update(detections[det_idx].clone(), noise_of(det_idx));
+            track.update_cues(&detections[det_idx], mask_of(det_idx), features_of(det_idx));
+        }
+
+        // 4. 第二轮匹配: 低分检测 + 未匹配的轨迹 (救援)
+        let unmatched_tracks: Vec<usize> = (0..self.tracked_persons.len())
+            .filter(|&idx| !matched_track[idx])
+            .collect();
+
+        let low_assignments = self.match_detections_to_tracks(
+            &low_dets,
+            &unmatched_tracks,
+            self.params.low_iou_threshold,
+            masks,
+            features,
+        );
+
+        for (det_idx, track_idx) in low_assignments {
+            matched_det[det_idx] = true;
+            matched_track[track_idx] = true;
+            let track = &mut self.tracked_persons[track_idx];
+            track.update(detections[det_idx].clone(), noise_of(det_idx));
+            track.update_cues(&detections[det_idx], mask_of(det_idx), features_of(det_idx));
+        }
+
+        // 5. 未匹配的高分检测 → 新建轨迹
+        for (det_idx, &matched) in matched_det.iter().enumerate() {
+            if !matched && detections[det_idx].confidence >= self.params.high_score_threshold {
+                let color = self.color_palette[self.next_id as usize % self.color_palette.len()];
+                let mut tracked =
+                    ByteTrackedPerson::new(self.next_id, detections[det_idx].clone(), color);
+                tracked.update_cues(&detections[det_idx], mask_of(det_idx), features_of(det_idx));
+                self.tracked_persons.push(tracked);
+                self.next_id += 1;
+            }
+        }
+
+        // 6. 未匹配的轨迹 → 标记丢失
+        for (track_idx, &matched) in matched_track.iter().enumerate() {
+            if !matched {
+                self.tracked_persons[track_idx].mark_lost();
+            }
+        }
+
+        // 7. 命中次数达标的轨迹确认
+        for track in &mut self.tracked_persons {
+            if track.total_frames >= self.params.min_hits {
+                track.confirmed = true;
+            }
+        }
+
+        // 8. 删除丢失太久的轨迹
+        let before = self.tracked_persons.len();
+        let max_lost_frames = self.params.max_lost_frames;
+        self.tracked_persons
+            .retain(|t| t.frames_lost <= max_lost_frames);
+        self.removed_count += (before - self.tracked_persons.len()) as u64;
+
+        &self.tracked_persons
+    }
+
+    /// 融合相似度匹配 (默认权重下即 IOU 匹配)
+    fn match_detections_to_tracks(
+        &self,
+        detections: &[(usize, &BBox)],
+        track_indices: &[usize],
+        iou_threshold: f32,
+        masks: &[Option<InstanceMask>],
+        features: &[Option<Vec<f32>>],
+    ) -> Vec<(usize, usize)> {
+        if detections.is_empty() || track_indices.is_empty() {
+            return Vec::new();
+        }
+
+        // 计算融合代价矩阵
+        let mut candidates = Vec::new();
+        for (local_det_idx, (det_idx, detection)) in detections.iter().enumerate() {
+            let mask = masks.get(*det_idx).and_then(|m| m.as_ref());
+            let feats = features.get(*det_idx).and_then(|f| f.as_deref());
+            for (local_track_idx, &track_idx) in track_indices.iter().enumerate() {
+                let track = &self.tracked_persons[track_idx];
+                let similarity = track.similarity(detection, mask, feats, &self.association);
+
+                if similarity >= iou_threshold {
+                    let cost = 1.0 - similarity;
+                    candidates.push((cost, *det_idx, local_det_idx, track_idx, local_track_idx));
+                }
+            }
+        }
+
+        // 贪心匹配: 按代价排序
+        candidates.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        let mut assignments = Vec::new();
+        let mut used_det = vec![false; detections.len()];
+        let mut used_track = vec![false; track_indices.len()];
+
+        for (_, det_idx, local_det_idx, track_idx, local_track_idx) in candidates {
+            if !used_det[local_det_idx] && !used_track[local_track_idx] {
+                assignments.push((det_idx, track_idx));
+                used_det[local_det_idx] = true;
+                used_track[local_track_idx] = true;
+            }
+        }
+
+        assignments
+    }
+
+    /// 获取跟踪统计信息
+    pub fn get_stats(&self) -> String {
+        format!(
+            "跟踪: {} 人 | 总ID: {}",
+            self.tracked_persons.len(),
+            self.next_id - 1
+        )
+    }
+}
+
+impl Default for ByteTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bbox(x1: f32, x2: f32) -> BBox {
+        BBox {
+            x1,
+            y1: 0.0,
+            x2,
+            y2: 100.0,
+            confidence: 0.9,
+            class_id: 0,
+        }
+    }
+
+    /// 200×100 掩码, [x1, x2) 列为前景
+    fn mask(x1: usize, x2: usize) -> Option<InstanceMask> {
+        let mut luma = vec![0u8; 200 * 100];
+        for row in luma.chunks_exact_mut(200) {
+            row[x1..x2].fill(255);
+        }
+        InstanceMask::from_luma(&luma, 200, 100, 2, 1.0, 1.0)
+    }
+
+    /// 两人前后重叠: 纯 IOU 关联发生 ID 交换, 加入掩码 IOU 后保持
+    #[test]
+    fn test_mask_iou_prevents_id_swap() {
+        let run = |weights: AssociationWeights| {
+            let mut tracker = ByteTracker::new();
+            tracker.set_association(weights);
+            let (masks_a, masks_b) = (mask(0, 50), mask(90, 140));
+            for _ in 0..5 {
+                tracker.update_with_cues(
+                    &[bbox(0.0, 100.0), bbox(40.0, 140.0)],
+                    &[masks_a.clone(), masks_b.clone()],
+                    &[],
+                );
+            }
+            // 框位置互相靠近对方, 掩码仍在原位置 (掩码记录的中心随检测更新)
+            let tracked = tracker.update_with_cues(
+                &[bbox(30.0, 130.0), bbox(10.0, 110.0)],
+                &[masks_a, masks_b],
+                &[],
+            );
+            // 按匹配到的检测框中心区分 (30..130 → 80, 10..110 → 60)
+            let id_at = |cx: f32| {
+                tracked
+                    .iter()
+                    .find(|t| t.mask_center.0 == cx)
+                    .map(|t| t.id)
+                    .unwrap()
+            };
+            (id_at(80.0), id_at(60.0))
+        };
+
+        assert_eq!(run(AssociationWeights::default()), (2, 1));
+        let weights = AssociationWeights {
+            mask_iou: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(run(weights), (1, 2));
+    }
+
+    /// 缺失的线索不参与加权
+    #[test]
+    fn test_fuse_ignores_missing_cues() {
+        let weights = AssociationWeights {
+            iou: 1.0,
+            mask_iou: 2.0,
+            reid: 1.0,
+        };
+        assert_eq!(weights.fuse(0.5, None, None), 0.5);
+        assert_eq!(weights.fuse(0.5, Some(1.0), None), 2.5 / 3.0);
+        assert_eq!(
+            AssociationWeights::default().fuse(0.5, Some(0.0), Some(0.0)),
+            0.5
+        );
+    }
+
+    /// 掩码按预测位移平移后比较
+    #[test]
+    fn test_mask_iou_shifted() {
+        let (a, b) = (mask(0, 50).unwrap(), mask(20, 70).unwrap());
+        assert_eq!(a.iou_shifted(&a, 0.0, 0.0), 1.0);
+        assert_eq!(a.iou_shifted(&b, 20.0, 0.0), 1.0);
+        assert!((a.iou_shifted(&b, 0.0, 0.0) - 30.0 / 70.0).abs() < 1e-6);
+        assert!(mask(0, 0).is_none());
+    }
+
+    /// 命中次数达到 min_hits 后才确认轨迹
+    #[test]
+    fn test_min_hits_confirmation() {
+        let mut tracker = ByteTracker::new();
+        tracker.set_params(TrackerParams {
+            min_hits: 3,
+            ..TrackerParams::bytetrack()
+        });
+        for expected in [false, false, true] {
+            let tracked = tracker.update(&[bbox(0.0, 100.0)]);
+            assert_eq!(tracked.len(), 1);
+            assert_eq!(tracked[0].confirmed, expected);
+        }
+    }
+
+    /// 丢失计数与超过 max_lost_frames 后的删除统计
+    #[test]
+    fn test_stats_lost_and_removed() {
+        let mut tracker = ByteTracker::new();
+        tracker.set_params(TrackerParams {
+            max_lost_frames: 2,
+            ..TrackerParams::bytetrack()
+        });
+        tracker.update(&[bbox(0.0, 50.0), bbox(100.0, 150.0)]);
+        assert_eq!(
+            tracker.stats(),
+            TrackStats {
+                active: 2,
+                lost: 0,
+                removed: 0
+            }
+        );
+
+        // 第二个目标消失: 先进入丢失状态, 超过 2 帧后删除
+        for _ in 0..2 {
+            tracker.update(&[bbox(0.0, 50.0)]);
+        }
+        assert_eq!(tracker.stats().lost, 1);
+        tracker.update(&[bbox(0.0, 50.0)]);
+        assert_eq!(
+            tracker.stats(),
+            TrackStats {
+                active: 1,
+                lost: 0,
+                removed: 1
+            }
+        );
+    }
+
+    /// 从快照恢复后原位置的目标沿用原ID, 新目标的ID接着快照编号
+    #[test]
+    fn test_snapshot_restore_keeps_ids() {
+        let mut tracker = ByteTracker::new();
+        tracker.update(&[bbox(0.0, 50.0)]);
+        tracker.update(&[bbox(0.0, 50.0), bbox(200.0, 250.0)]);
+        let snapshot = tracker.snapshot();
+        assert_eq!(snapshot.next_id, 3);
+
+        let mut restored = ByteTracker::new();
+        restored.restore(&snapshot);
+        assert_eq!(restored.stats(), tracker.stats());
+        let tracked = restored.update(&[bbox(201.0, 251.0), bbox(400.0, 450.0)]);
+        let ids: Vec<u32> = tracked
+            .iter()
+            .filter(|t| t.frames_lost == 0)
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(ids, vec![2, 3]);
+        let resumed = tracked.iter().find(|t| t.id == 2).unwrap();
+        assert_eq!(resumed.total_frames, 2);
+    }
+
+    /// 贴边的半身框放大观测噪声: 轨迹中心被截断框拉偏得更少, 不贴边的观测不受影响
+    #[test]
+    fn test_partial_observation_noise() {
+        let edge = PartialObservation {
+            width: 640.0,
+            height: 480.0,
+            margin: 2.0,
+            noise_scale: 4.0,
+        };
+        let person = |x1: f32, x2: f32| BBox {
+            y1: 100.0,
+            y2: 300.0,
+            ..bbox(x1, x2)
+        };
+        let run = |partial: Option<PartialObservation>, last: BBox| {
+            let mut tracker = ByteTracker::new();
+            tracker.set_partial_observation(partial);
+            for _ in 0..5 {
+                tracker.update(&[person(5.0, 105.0)]);
+            }
+            let tracked = tracker.update(&[last]);
+            assert_eq!(tracked.len(), 1);
+            (tracked[0].bbox.x1 + tracked[0].bbox.x2) / 2.0
+        };
+
+        // 走出画面左侧, 只剩 0..80 可见
+        let normal = run(None, person(0.0, 80.0));
+        let partial = run(Some(edge), person(0.0, 80.0));
+        assert!(normal < partial && partial < 55.0);
+        assert_eq!(
+            run(None, person(10.0, 110.0)),
+            run(Some(edge), person(10.0, 110.0))
+        );
+    }
+}
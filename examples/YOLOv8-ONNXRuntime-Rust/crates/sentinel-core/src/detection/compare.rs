@@ -0,0 +1,363 @@
+//! 模型对比 (Model Comparison)
+//!
+//! 同一帧同时送入两个模型 (各占一个线程并行推理), 检测框与各自耗时一并发布,
+//! 渲染器按叠加 (不同颜色)、左右分屏或画中画显示, 帮助用户按实际画面挑选模型.
+//! 对比线程只保留最新一帧, 推理跟不上时丢帧, 两个模型始终处理同一帧.
+//! 对比结果不经过跟踪与区域规则
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use image::{DynamicImage, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use super::types::{BBox, DecodedFrame};
+use crate::models::{load_detect_model, Model, ModelType};
+use crate::utils::coords::ImageToWindow;
+use crate::xbus::{self, Subscription};
+
+/// 两个模型的框视为同一目标的最小 IoU
+pub const MATCH_IOU: f32 = 0.5;
+
+/// 平均耗时的统计窗口 (帧)
+const LATENCY_WINDOW: usize = 30;
+
+/// 画中画小窗相对主画面的比例
+const PIP_SCALE: f32 = 0.3;
+
+/// 画中画小窗与屏幕边缘的距离 (像素)
+const PIP_MARGIN: f32 = 16.0;
+
+/// 对比结果的显示方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompareLayout {
+    #[default]
+    Overlay, // 同一画面, 两个模型的框用不同颜色
+    SideBySide,       // 左右分屏, 左为模型 A, 右为模型 B
+    PictureInPicture, // 主画面为模型 A, 右下角小窗为模型 B
+}
+
+impl CompareLayout {
+    pub const ALL: [CompareLayout; 3] = [
+        CompareLayout::Overlay,
+        CompareLayout::SideBySide,
+        CompareLayout::PictureInPicture,
+    ];
+
+    /// 两路画面在屏幕上的变换 (画面坐标 → 窗口坐标), 保持宽高比; 叠加模式两路相同
+    pub fn viewports(self, screen: (f32, f32), frame: (f32, f32)) -> [ImageToWindow; 2] {
+        match self {
+            CompareLayout::Overlay => {
+                [ImageToWindow::fit(frame, (0.0, 0.0, screen.0, screen.1)); 2]
+            }
+            CompareLayout::SideBySide => {
+                let half = screen.0 / 2.0;
+                [
+                    ImageToWindow::fit(frame, (0.0, 0.0, half, screen.1)),
+                    ImageToWindow::fit(frame, (half, 0.0, half, screen.1)),
+                ]
+            }
+            CompareLayout::PictureInPicture => {
+                let main = ImageToWindow::fit(frame, (0.0, 0.0, screen.0, screen.1));
+                let inset = main.scale().0 * PIP_SCALE;
+                let corner = (
+                    screen.0 - frame.0 * inset - PIP_MARGIN,
+                    screen.1 - frame.1 * inset - PIP_MARGIN,
+                );
+                [main, ImageToWindow::new((inset, inset), corner)]
+            }
+        }
+    }
+}
+
+/// 对比参数 (控制面板的阈值与类别, 实时生效)
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompareParams {
+    pub conf: f32,
+    pub iou: f32,
+    pub classes: Vec<u32>, // 为空表示全部类别
+}
+
+/// 单个模型在一帧上的结果
+#[derive(Clone, Debug, Default)]
+pub struct CompareSide {
+    pub model: String,
+    pub bboxes: Vec<BBox>,
+    pub inference_ms: f64, // 本帧耗时 (预处理 + 推理 + 后处理)
+    pub mean_ms: f64,      // 最近窗口平均耗时
+}
+
+/// 两个模型检测结果的一致程度
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Agreement {
+    pub matched: usize, // 两个模型都检出的目标
+    pub only_a: usize,  // 仅模型 A 检出
+    pub only_b: usize,  // 仅模型 B 检出
+}
+
+/// 一帧的对比结果 (xbus 消息)
+#[derive(Clone, Debug)]
+pub struct ComparisonResult {
+    pub sides: [CompareSide; 2],
+    pub agreement: Agreement,
+    pub view: u32,
+}
+
+/// 按置信度从高到低贪心匹配同类别、IoU 不低于阈值的框
+pub fn agreement(a: &[BBox], b: &[BBox], min_iou: f32) -> Agreement {
+    let mut order: Vec<usize> = (0..a.len()).collect();
+    order.sort_by(|&i, &j| a[j].confidence.total_cmp(&a[i].confidence));
+    let mut used = vec![false; b.len()];
+    let mut matched = 0;
+    for i in order {
+        let best = b
+            .iter()
+            .enumerate()
+            .filter(|(j, bb)| !used[*j] && bb.class_id == a[i].class_id)
+            .map(|(j, bb)| (j, a[i].iou(bb)))
+            .filter(|(_, v)| *v >= min_iou)
+            .max_by(|x, y| x.1.total_cmp(&y.1));
+        if let Some((j, _)) = best {
+            used[j] = true;
+            matched += 1;
+        }
+    }
+    Agreement {
+        matched,
+        only_a: a.len() - matched,
+        only_b: b.len() - matched,
+    }
+}
+
+/// 模型对比 (独立线程), 释放后订阅取消, 对比线程随之退出
+pub struct ModelComparison {
+    models: [String; 2],
+    view: u32,
+    params: Arc<Mutex<CompareParams>>,
+    _frame_sub: Subscription,
+}
+
+impl ModelComparison {
+    /// 启动对比线程 (模型在线程内加载, 不阻塞渲染), 只处理指定逻辑流
+    pub fn start(
+        models: [String; 2],
+        inf_size: u32,
+        view: u32,
+        params: CompareParams,
+    ) -> anyhow::Result<Self> {
+        let params = Arc::new(Mutex::new(params));
+        let (tx, rx) = crossbeam_channel::bounded::<DecodedFrame>(1);
+        let frame_sub = xbus::subscribe::<DecodedFrame, _>(move |frame| {
+            if frame.view == view {
+                let _ = tx.try_send(frame.clone());
+            }
+        });
+
+        let paths = models.clone();
+        let shared = Arc::clone(&params);
+        let spawned = std::thread::Builder::new()
+            .name("model-compare".to_string())
+            .spawn(move || {
+                let loaded = paths
+                    .iter()
+                    .map(|path| load_model(path, inf_size))
+                    .collect::<anyhow::Result<Vec<_>>>();
+                let mut loaded = match loaded {
+                    Ok(models) => models,
+                    Err(e) => {
+                        eprintln!("❌ 对比模型加载失败: {:#}", e);
+                        return;
+                    }
+                };
+                println!("⚖️ 模型对比启动: {} vs {}", paths[0], paths[1]);
+
+                let mut windows = [VecDeque::new(), VecDeque::new()];
+                for frame in rx {
+                    let Some(image) = to_image(&frame) else {
+                        continue;
+                    };
+                    let params = shared.lock().unwrap().clone();
+                    let (a, b) = loaded.split_at_mut(1);
+                    let (side_a, side_b) = std::thread::scope(|s| {
+                        let task_b = s.spawn(|| run_side(b[0].as_mut(), &image, &params));
+                        let side_a = run_side(a[0].as_mut(), &image, &params);
+                        (side_a, task_b.join().unwrap_or_default())
+                    });
+
+                    let mut sides = [side_a, side_b];
+                    for (i, side) in sides.iter_mut().enumerate() {
+                        side.model = paths[i].clone();
+                        let window = &mut windows[i];
+                        window.push_back(side.inference_ms);
+                        if window.len() > LATENCY_WINDOW {
+                            window.pop_front();
+                        }
+                        side.mean_ms = window.iter().sum::<f64>() / window.len() as f64;
+                    }
+                    let agreement = agreement(&sides[0].bboxes, &sides[1].bboxes, MATCH_IOU);
+                    xbus::post(ComparisonResult {
+                        sides,
+                        agreement,
+                        view,
+                    });
+                }
+                println!("⚖️ 模型对比线程退出");
+            });
+        if let Err(e) = spawned {
+            anyhow::bail!("模型对比线程启动失败: {}", e);
+        }
+
+        Ok(Self {
+            models,
+            view,
+            params,
+            _frame_sub: frame_sub,
+        })
+    }
+
+    /// 对比的两个模型路径
+    pub fn models(&self) -> &[String; 2] {
+        &self.models
+    }
+
+    /// 对比的逻辑流
+    pub fn view(&self) -> u32 {
+        self.view
+    }
+
+    /// 更新阈值与类别 (下一帧生效)
+    pub fn set_params(&self, params: CompareParams) {
+        let mut current = self.params.lock().unwrap();
+        if *current != params {
+            *current = params;
+        }
+    }
+}
+
+fn load_model(path: &str, inf_size: u32) -> anyhow::Result<Box<dyn Model + Send>> {
+    let model_type = ModelType::from_path(path);
+    load_detect_model(
+        model_type,
+        crate::Args {
+            model: path.to_string(),
+            source: String::new(),
+            device_id: 0,
+            trt: false,
+            cuda: false,
+            batch: 1,
+            batch_min: 1,
+            batch_max: 1,
+            fp16: false,
+            dla_core: None,
+            task: None,
+            nc: None,
+            nk: None,
+            nm: None,
+            width: Some(inf_size),
+            height: Some(inf_size),
+            conf: model_type.default_conf_threshold(),
+            iou: model_type.default_iou_threshold(),
+            kconf: 0.55,
+            profile: false,
+        },
+    )
+}
+
+/// 帧没有 RGBA 数据时 (如 NVDEC 设备帧) 为 None
+fn to_image(frame: &DecodedFrame) -> Option<DynamicImage> {
+    if frame.rgba_data.len() < (frame.width * frame.height * 4) as usize {
+        return None;
+    }
+    RgbaImage::from_raw(frame.width, frame.height, frame.rgba_data.to_vec())
+        .map(DynamicImage::ImageRgba8)
+}
+
+fn run_side(
+    model: &mut (dyn Model + Send),
+    image: &DynamicImage,
+    params: &CompareParams,
+) -> CompareSide {
+    model.set_conf(params.conf);
+    model.set_iou(params.iou);
+    let start = Instant::now();
+    let results = match model.forward(std::slice::from_ref(image)) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("❌ 对比推理失败: {}", e);
+            Vec::new()
+        }
+    };
+    let inference_ms = start.elapsed().as_secs_f64() * 1000.0;
+    let bboxes = results
+        .iter()
+        .filter_map(|r| r.bboxes())
+        .flatten()
+        .filter(|b| params.classes.is_empty() || params.classes.contains(&(b.id() as u32)))
+        .map(|b| BBox {
+            x1: b.xmin(),
+            y1: b.ymin(),
+            x2: b.xmax(),
+            y2: b.ymax(),
+            confidence: b.confidence(),
+            class_id: b.id() as u32,
+        })
+        .collect();
+    CompareSide {
+        model: String::new(),
+        bboxes,
+        inference_ms,
+        mean_ms: inference_ms,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bbox(x: f32, class_id: u32, confidence: f32) -> BBox {
+        BBox {
+            x1: x,
+            y1: 0.0,
+            x2: x + 10.0,
+            y2: 10.0,
+            confidence,
+            class_id,
+        }
+    }
+
+    /// 同类别且重叠的框计为一致, 每个框最多匹配一次
+    #[test]
+    fn test_agreement() {
+        let a = [bbox(0.0, 0, 0.9), bbox(1.0, 0, 0.5), bbox(50.0, 2, 0.8)];
+        let b = [bbox(0.5, 0, 0.7), bbox(50.0, 0, 0.9), bbox(100.0, 0, 0.6)];
+        let result = agreement(&a, &b, MATCH_IOU);
+        assert_eq!(
+            result,
+            Agreement {
+                matched: 1,
+                only_a: 2,
+                only_b: 2
+            }
+        );
+        assert_eq!(agreement(&[], &b, MATCH_IOU).only_b, 3);
+    }
+
+    /// 分屏两路各占半屏且保持宽高比, 画中画小窗贴右下角
+    #[test]
+    fn test_viewports() {
+        let [a, b] = CompareLayout::SideBySide.viewports((1600.0, 900.0), (1600.0, 900.0));
+        assert_eq!(a, ImageToWindow::new((0.5, 0.5), (0.0, 225.0)));
+        assert_eq!(b, ImageToWindow::new((0.5, 0.5), (800.0, 225.0)));
+
+        let [main, inset] =
+            CompareLayout::PictureInPicture.viewports((1600.0, 900.0), (800.0, 450.0));
+        assert_eq!(main, ImageToWindow::new((2.0, 2.0), (0.0, 0.0)));
+        assert!((inset.scale().0 - 0.6).abs() < 1e-6);
+        assert!((inset.offset().0 + 800.0 * inset.scale().0 + PIP_MARGIN - 1600.0).abs() < 1e-3);
+
+        let [a, b] = CompareLayout::Overlay.viewports((1000.0, 1000.0), (500.0, 250.0));
+        assert_eq!(a, b);
+        assert_eq!(a, ImageToWindow::new((2.0, 2.0), (0.0, 250.0)));
+    }
+}
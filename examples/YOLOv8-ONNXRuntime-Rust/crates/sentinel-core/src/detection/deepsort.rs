@@ -9,13 +9,24 @@
 //! 5. 融合匹配: 运动+外观双重验证
 //! 6. 虚拟轨迹: 长时遮挡鲁棒
 
-use super::tracker::{KalmanBoxFilter, TrackPoint};
+use super::journal::{TrackState, TrackerSnapshot};
+use super::tracker::{KalmanBoxFilter, PartialObservation, TrackPoint, TrackStats, TrackerParams};
 use super::types::{BBox, PoseKeypoints};
+#[cfg(feature = "ort")]
+use crate::ort_backend::session_builder;
+#[cfg(feature = "ort")]
 use image::{DynamicImage, ImageBuffer, Rgb};
+#[cfg(feature = "ort")]
 use ndarray::Array4;
-use ort::session::Session;
+#[cfg(feature = "ort")]
+pub(crate) use ort::session::Session;
+#[cfg(feature = "ort")]
 use ort::value::Value;
 
+/// 未启用 ort 功能时没有 ReID 模型, 跟踪只使用运动与几何/关键点特征
+#[cfg(not(feature = "ort"))]
+pub(crate) enum Session {}
+
 /// 被跟踪的人
 #[derive(Clone)]
 pub struct TrackedPerson {
@@ -111,12 +122,13 @@ impl TrackedPerson {
         self.bbox = self.kalman.get_state_bbox();
     }
 
-    /// 更新位置 (融合观测)
+    /// 更新位置 (融合观测, `noise_scale` 为观测噪声倍数)
     fn update(
         &mut self,
         bbox: BBox,
         keypoints: Option<&PoseKeypoints>,
         min_confirmation_hits: u32,
+        noise_scale: f32,
     ) {
         // 检测是否静止 (检测框和预测框的距离)
         let predicted = self.kalman.get_predicted_bbox();
@@ -127,7 +139,7 @@ impl TrackedPerson {
         self.is_stationary = movement < 3.0; // 小于3像素视为静止
 
         // 卡尔曼滤波更新
-        self.kalman.update(&bbox);
+        self.kalman.update_with_noise(&bbox, noise_scale);
         self.bbox = self.kalman.get_state_bbox();
 
         self.frames_lost = 0;
@@ -178,6 +190,7 @@ impl TrackedPerson {
         keypoints: Option<&PoseKeypoints>,
         reid_features: Option<Vec<f32>>,
         min_confirmation_hits: u32,
+        noise_scale: f32,
     ) {
         // 检测是否静止
         let predicted = self.kalman.get_predicted_bbox();
@@ -188,7 +201,7 @@ impl TrackedPerson {
         self.is_stationary = movement < 3.0;
 
         // 卡尔曼滤波更新
-        self.kalman.update(&bbox);
+        self.kalman.update_with_noise(&bbox, noise_scale);
         self.bbox = self.kalman.get_state_bbox();
 
         self.frames_lost = 0;
@@ -251,6 +264,45 @@ impl TrackedPerson {
         self.kalman.get_predicted_bbox()
     }
 
+    /// 日志中保存的状态
+    fn state(&self) -> TrackState {
+        TrackState {
+            id: self.id,
+            bbox: [self.bbox.x1, self.bbox.y1, self.bbox.x2, self.bbox.y2],
+            score: self.bbox.confidence,
+            frames_lost: self.frames_lost,
+            total_frames: self.total_frames,
+            confirmed: self.confirmed,
+            color: self.color,
+            trajectory: self.trajectory.iter().map(|p| (p.x, p.y)).collect(),
+            features: Some(self.appearance_features.clone()),
+        }
+    }
+
+    /// 从日志恢复 (卡尔曼滤波器从保存的位置重新开始)
+    fn from_state(state: &TrackState, min_confirmation_hits: u32) -> Self {
+        let mut track = Self::new(
+            state.id,
+            state.to_bbox(),
+            state.color,
+            None,
+            state.features.clone(),
+        );
+        track.frames_lost = state.frames_lost;
+        track.time_since_update = state.frames_lost;
+        track.total_frames = state.total_frames;
+        track.confirmed = state.confirmed;
+        if state.confirmed {
+            track.consecutive_matches = min_confirmation_hits;
+        }
+        track.trajectory = state
+            .trajectory
+            .iter()
+            .map(|&(x, y)| TrackPoint { x, y })
+            .collect();
+        track
+    }
+
     /// 获取当前速度向量
     #[allow(dead_code)]
     fn get_velocity(&self) -> (f32, f32) {
@@ -376,10 +428,12 @@ pub struct PersonTracker {
     /// 最大允许丢失帧数
     max_lost_frames: u32,
 
-    /// IOU 匹配阈值
-    #[allow(dead_code)]
+    /// 级联匹配 IOU 门控阈值
     iou_threshold: f32,
 
+    /// 未确认轨迹的 IOU 匹配阈值
+    unconfirmed_iou_threshold: f32,
+
     /// 马氏距离阈值 (DeepSort运动门控)
     #[allow(dead_code)]
     mahalanobis_threshold: f32,
@@ -402,6 +456,12 @@ pub struct PersonTracker {
 
     /// 帧计数器(用于跳帧ReID提取)
     frame_counter: u32,
+
+    /// 累计删除的轨迹数
+    removed_count: u64,
+
+    /// 贴边观测的判定与噪声倍数 (边界策略为 partial 时)
+    partial: Option<PartialObservation>,
 }
 
 impl PersonTracker {
@@ -419,27 +479,104 @@ impl PersonTracker {
             (128, 255, 128), // 浅绿
         ];
 
+        let params = TrackerParams::deepsort();
         Self {
             tracked_persons: Vec::new(),
             next_id: 1,
-            max_lost_frames: 90, // 90帧(约3秒) - DeepSort可利用ReID特征长时间恢复
-            iou_threshold: 0.2,  // 降低IOU阈值,提高匹配成功率
+            max_lost_frames: params.max_lost_frames,
+            iou_threshold: params.high_iou_threshold,
+            unconfirmed_iou_threshold: params.low_iou_threshold,
             mahalanobis_threshold: 9.4, // 标准DeepSort值 (运动一致性检查)
             appearance_threshold: 0.15, // 降低外观阈值,更容易匹配
-            max_cascade_depth: 30, // 标准级联深度
-            min_confirmation_hits: 2, // 降低到2帧,更快确认,减少初期漂移
+            max_cascade_depth: 30,      // 标准级联深度
+            min_confirmation_hits: params.min_hits,
             color_palette,
             reid_model: Self::load_reid_model(),
             frame_counter: 0,
+            removed_count: 0,
+            partial: None,
+        }
+    }
+
+    /// 设置贴边观测的处理 (None 时所有观测同等对待), 画面尺寸变化时需重新设置
+    pub fn set_partial_observation(&mut self, partial: Option<PartialObservation>) {
+        self.partial = partial;
+    }
+
+    /// 检测的观测噪声倍数 (贴边的不完整观测大于1)
+    fn noise_scale(&self, detection: &BBox) -> f32 {
+        self.partial.map_or(1.0, |p| p.noise_scale(detection))
+    }
+
+    /// 设置生命周期参数 (下一帧生效, 分数阈值对 DeepSort 无效)
+    pub fn set_params(&mut self, params: TrackerParams) {
+        println!(
+            "🔧 DeepSort参数: 最大丢失{}帧 | 最小命中{} | IOU {:.2}/{:.2}",
+            params.max_lost_frames,
+            params.min_hits,
+            params.high_iou_threshold,
+            params.low_iou_threshold
+        );
+        self.max_lost_frames = params.max_lost_frames;
+        self.min_confirmation_hits = params.min_hits;
+        self.iou_threshold = params.high_iou_threshold;
+        self.unconfirmed_iou_threshold = params.low_iou_threshold;
+    }
+
+    pub fn params(&self) -> TrackerParams {
+        TrackerParams {
+            max_lost_frames: self.max_lost_frames,
+            min_hits: self.min_confirmation_hits,
+            high_iou_threshold: self.iou_threshold,
+            low_iou_threshold: self.unconfirmed_iou_threshold,
+            ..TrackerParams::deepsort()
+        }
+    }
+
+    /// 轨迹统计
+    pub fn stats(&self) -> TrackStats {
+        let lost = self
+            .tracked_persons
+            .iter()
+            .filter(|t| t.frames_lost > 0)
+            .count();
+        TrackStats {
+            active: self.tracked_persons.len() - lost,
+            lost,
+            removed: self.removed_count,
         }
     }
 
+    /// 跟踪状态快照 (写入跟踪状态日志)
+    pub fn snapshot(&self) -> TrackerSnapshot {
+        TrackerSnapshot {
+            tracker: "deepsort".to_string(),
+            saved_at: chrono::Utc::now(),
+            next_id: self.next_id,
+            removed: self.removed_count,
+            tracks: self.tracked_persons.iter().map(|t| t.state()).collect(),
+        }
+    }
+
+    /// 从快照恢复轨迹与外观特征, 之后新建的轨迹ID接着快照中的编号
+    pub fn restore(&mut self, snapshot: &TrackerSnapshot) {
+        let min_hits = self.min_confirmation_hits;
+        self.tracked_persons = snapshot
+            .tracks
+            .iter()
+            .map(|state| TrackedPerson::from_state(state, min_hits))
+            .collect();
+        self.next_id = snapshot.resume_id();
+        self.removed_count = snapshot.removed;
+    }
+
     /// 加载OSNet-AIN ReID模型 (x1.0跨域泛化最强版本)
     /// 性能指标: Rank-1 94.7%, mAP 84.9% (跨域场景表现最优)
-    fn load_reid_model() -> Option<Session> {
+    #[cfg(feature = "ort")]
+    pub(crate) fn load_reid_model() -> Option<Session> {
         println!("[DeepSort] 尝试加载ReID模型: models/osnet_ain_x1_0.onnx");
 
-        match Session::builder() {
+        match session_builder() {
             Ok(builder) => match builder.commit_from_file("models/osnet_ain_x1_0.onnx") {
                 Ok(session) => {
                     println!("[DeepSort] ✓ ReID模型加载成功! 使用深度ReID特征 (95% IOU + 5% ReID)");
@@ -459,6 +596,11 @@ impl PersonTracker {
         }
     }
 
+    #[cfg(not(feature = "ort"))]
+    pub(crate) fn load_reid_model() -> Option<Session> {
+        None
+    }
+
     /// 检查是否已加载深度ReID模型
     pub fn has_reid_model(&self) -> bool {
         self.reid_model.is_some()
@@ -468,7 +610,8 @@ impl PersonTracker {
     /// frame_rgba: 原始RGBA图像数据
     /// width, height: 图像尺寸
     /// bbox: 检测框
-    fn extract_reid_features_from_image(
+    #[cfg(feature = "ort")]
+    pub(crate) fn extract_reid_features_from_image(
         reid_model: &mut Session,
         frame_rgba: &[u8],
         width: u32,
@@ -555,6 +698,17 @@ impl PersonTracker {
         }
     }
 
+    #[cfg(not(feature = "ort"))]
+    pub(crate) fn extract_reid_features_from_image(
+        reid_model: &mut Session,
+        _frame_rgba: &[u8],
+        _width: u32,
+        _height: u32,
+        _bbox: &BBox,
+    ) -> Vec<f32> {
+        match *reid_model {}
+    }
+
     /// 更新跟踪 (DeepSort级联匹配)
     pub fn update(
         &mut self,
@@ -642,11 +796,13 @@ impl PersonTracker {
                 };
 
                 let kpts = keypoints.get(det_idx);
+                let noise_scale = self.noise_scale(&detections[det_idx]);
                 self.tracked_persons[track_idx].update_with_reid(
                     detections[det_idx].clone(),
                     kpts,
                     reid_features,
                     self.min_confirmation_hits,
+                    noise_scale,
                 );
             }
         }
@@ -660,7 +816,8 @@ impl PersonTracker {
 
         if !unmatched_dets.is_empty() && !unconfirmed_indices.is_empty() {
             let cost_matrix = self.compute_iou_cost_matrix(&unmatched_dets, &unconfirmed_indices);
-            let assignments = hungarian_algorithm_simple(&cost_matrix, 0.05); // 更宽松: IOU>0.05即可
+            let assignments =
+                hungarian_algorithm_simple(&cost_matrix, self.unconfirmed_iou_threshold); // 更宽松
 
             for (local_det_idx, local_track_idx) in assignments {
                 let det_idx = unmatched_dets[local_det_idx].0;
@@ -668,10 +825,12 @@ impl PersonTracker {
                 matched_det[det_idx] = true;
                 matched_track[track_idx] = true;
                 let kpts = keypoints.get(det_idx);
+                let noise_scale = self.noise_scale(&detections[det_idx]);
                 self.tracked_persons[track_idx].update(
                     detections[det_idx].clone(),
                     kpts,
                     self.min_confirmation_hits,
+                    noise_scale,
                 );
             }
         }
@@ -716,8 +875,11 @@ impl PersonTracker {
         }
 
         // 7. 删除丢失太久的轨迹
+        let before = self.tracked_persons.len();
+        let max_lost_frames = self.max_lost_frames;
         self.tracked_persons
-            .retain(|t| t.frames_lost <= self.max_lost_frames);
+            .retain(|t| t.frames_lost <= max_lost_frames);
+        self.removed_count += (before - self.tracked_persons.len()) as u64;
 
         &self.tracked_persons
     }
@@ -738,6 +900,11 @@ impl PersonTracker {
 
                 let iou = Self::compute_iou(detection, &track.get_predicted_bbox());
 
+                // IOU 门控: 距离过远的轨迹不参与匹配
+                if iou < self.iou_threshold {
+                    continue;
+                }
+
                 // 计算代价
                 let cost =
                     if let (Some(reid), Some((rgba, w, h))) = (&mut self.reid_model, frame_rgba) {
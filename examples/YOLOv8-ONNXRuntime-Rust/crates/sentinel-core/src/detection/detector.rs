@@ -0,0 +1,2355 @@
+//! 检测器 (Detector)
+//! 职责: 订阅DecodedFrame → YOLO检测 → 发送DetectionResult消息
+
+use std::any::Any;
+use std::collections::{BTreeMap, BTreeSet};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use image::{DynamicImage, ImageBuffer, RgbImage, Rgba};
+use ndarray::{Array, IxDyn};
+
+use super::border::{self, BorderPolicy};
+use super::box_filter;
+use super::gpu_placement::{self, GpuLease, GpuProvider};
+use super::journal::TrackJournal;
+use super::resolution::ResolutionLadder;
+use super::smoothing::{ghost_boxes, retain_visible};
+use super::tile_cache::{self, TileCache, TilePlan};
+use super::trace::FrameTrace;
+use super::types::{Backpressure, DecodedFrame, DetectorStatus};
+use super::{
+    AssociationWeights, ByteTracker, DisplaySmoother, GlobalIdManager, InstanceMask, PersonTracker,
+    TrackStats, TrackerParams,
+};
+use crate::analytics::calibration::Homography;
+use crate::analytics::overlap;
+use crate::analytics::speed::SpeedEstimator;
+use crate::detection::types::{self, ControlMessage};
+use crate::models::clip::match_regions;
+use crate::models::vehicle::source_classes;
+use crate::models::world;
+use crate::models::{
+    load_detect_model, AttributeRecognizer, ClipModel, CrowdCounter, DensityMap, DepthEstimator,
+    Model, ModelType, PersonAttributes, PreprocessSpec, TopDownPose, VehicleInfo,
+    VehicleRecognizer, Vocabulary,
+};
+use crate::utils::coords::{ModelPoint, ModelRect, ModelToImage};
+use crate::utils::hdr::yuv420_16_to_nchw;
+use crate::utils::jetson::{JetsonMonitor, JetsonStatus, ThrottleLevel};
+use crate::utils::resize::{self, FrameResizer, ResizeQuality};
+use crate::utils::yuv_preprocess::{nearest_map, yuv420_to_nchw};
+use crate::{scheduler, xbus, Args, Bbox, DetectorError, Embedding, Point2, YOLOTask};
+
+#[cfg(feature = "cuda")]
+use crate::cuda::CudaPipeline;
+#[cfg(feature = "gpu")]
+use crate::utils::affine_transform::{AffineMatrix, BorderMode, InterpolationMethod};
+#[cfg(feature = "gpu")]
+use crate::utils::affine_transform_wgpu::WgpuAffineTransform;
+
+/// 检测结果 (检测模块 → 渲染模块)
+#[derive(Clone, Debug)]
+pub struct DetectionResult {
+    pub bboxes: Vec<types::BBox>,
+    pub keypoints: Vec<types::PoseKeypoints>,
+    pub inference_fps: f64,
+    pub inference_ms: f64,
+    pub tracker_fps: f64,                 // 追踪器FPS
+    pub tracker_ms: f64,                  // 追踪器耗时
+    pub resized_image: Option<Vec<u8>>,   // Resize后的RGB图像数据 (用于右下角显示)
+    pub resized_size: u32,                // Resize后的图像尺寸
+    pub reid_features: Vec<Vec<f32>>,     // 每个bbox对应的ReID特征向量
+    pub distances: Vec<Option<f32>>,      // 每个bbox对应的近似距离(米), 未启用深度模型时为空
+    pub prompt_matches: Vec<Option<f32>>, // 每个bbox与文本提示的相似度, 命中为Some, 未启用时为空
+    pub track_stats: TrackStats,          // 轨迹统计 (活跃/丢失/已删除)
+    pub tracked: bool,                    // 是否经过跟踪器 (是则 bbox.class_id 为轨迹ID)
+    pub global_ids: Vec<Option<u32>>,     // 每个bbox的跨摄像头全局ID, 未启用或无ReID特征时为空
+    pub jetson: Option<JetsonStatus>,     // 推理时的功耗/温度/降频状态, 未启用时为None
+    pub trace: FrameTrace,                // 各阶段时间戳, 渲染线程提交叠加框后计入延迟统计
+    pub view: u32,                        // 逻辑流编号 (与 DecodedFrame::view 一致)
+    // 每个bbox脚点的地面坐标(米), 未做地面标定时为空
+    pub ground_points: Vec<Option<(f32, f32)>>,
+    // 每个bbox的平滑地面速度(米/秒), 需要地面标定与跟踪器, 否则为空
+    pub speeds: Vec<Option<f32>>,
+    // 人群密度图与估计人数, 未加载密度模型或已关闭时为None
+    pub density: Option<Arc<DensityMap>>,
+    // 每个bbox(轨迹)的行人属性, 需要属性模型与跟踪器, 尚未识别的为None, 未启用时为空
+    pub attributes: Vec<Option<PersonAttributes>>,
+    // 每个bbox(轨迹)的车型/车身颜色, 需要车辆属性模型与跟踪器, 非车辆或尚未识别的为None, 未启用时为空
+    pub vehicles: Vec<Option<VehicleInfo>>,
+    // 阈值预览: 低于显示阈值而未显示的检测 (虚影), 未启用时为空
+    pub ghosts: Vec<types::BBox>,
+    // 每个bbox(轨迹)按 IoU 找回的检测类别, 未跟踪时为空 (bbox.class_id 即类别)
+    pub classes: Vec<Option<u32>>,
+    // 开放词汇模型的类别名与颜色 (类别ID为词表下标), 其他模型为None
+    pub vocabulary: Option<Arc<Vocabulary>>,
+    // 每个bbox是否贴边 (只看到目标的一部分), 贴边策略为 partial 时提供, 否则为空
+    pub partial: Vec<bool>,
+    // 每个bbox是否与编号更小的逻辑流重复 (重叠视野去重), 未启用或本流未标定时为空
+    pub duplicates: Vec<bool>,
+}
+
+/// 无帧时检查控制消息的间隔
+const CONTROL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 看门狗: 连续 panic 超过该次数后不再自动重启, 等待界面切换模型
+const MAX_RESTARTS: u32 = 3;
+
+/// 看门狗: 检测循环整体 panic 后的重启退避 (按连续次数线性增长)
+const RESTART_BACKOFF: Duration = Duration::from_millis(500);
+
+/// 分割掩码下采样步长 (640 输入 → 160×160 网格)
+const MASK_STEP: usize = 4;
+
+/// 切换输入源时是否保留轨迹 (默认重建跟踪器, 启动参数设置)
+static KEEP_TRACKS_ON_SWITCH: AtomicBool = AtomicBool::new(false);
+
+/// 设置切换输入源时是否保留轨迹 (如同一摄像头主/子码流切换)
+pub fn set_keep_tracks_on_switch(keep: bool) {
+    KEEP_TRACKS_ON_SWITCH.store(keep, Ordering::Relaxed);
+}
+
+/// 跟踪器类型
+enum TrackerType {
+    DeepSort(PersonTracker),
+    ByteTrack(ByteTracker),
+    None,
+}
+
+impl TrackerType {
+    /// 同类型的空跟踪器 (带上关联权重与生命周期参数)
+    fn fresh(&self, association: AssociationWeights, params: Option<TrackerParams>) -> Self {
+        let mut tracker = match self {
+            TrackerType::DeepSort(_) => TrackerType::DeepSort(PersonTracker::new()),
+            TrackerType::ByteTrack(_) => {
+                let mut tracker = ByteTracker::new();
+                tracker.set_association(association);
+                TrackerType::ByteTrack(tracker)
+            }
+            TrackerType::None => TrackerType::None,
+        };
+        tracker.set_params(params);
+        tracker
+    }
+
+    fn set_params(&mut self, params: Option<TrackerParams>) {
+        let Some(params) = params else {
+            return;
+        };
+        match self {
+            TrackerType::DeepSort(tracker) => tracker.set_params(params),
+            TrackerType::ByteTrack(tracker) => tracker.set_params(params),
+            TrackerType::None => {}
+        }
+    }
+}
+
+/// 单个逻辑流的检测状态
+///
+/// 轨迹、缩放缓存与统计按流隔离, 一个检测线程处理多路流时轨迹ID与运动轨迹不会串到别的摄像头;
+/// 模型、阈值等设置仍由检测线程内的各流共用
+struct StreamContext {
+    view: u32,
+    tracker: TrackerType,
+    speed: SpeedEstimator,
+    smoother: DisplaySmoother,
+    // 本流的跟踪状态日志 (按逻辑流编号区分文件)
+    journal: Option<TrackJournal>,
+    // 最近处理的帧的解码器代数ID (切换输入源后重置缓存), 首帧前为 None
+    generation: Option<usize>,
+    // 静止机位的分块局部推理 (tile_inference.json 启用时, 仅 CPU 路径)
+    tile_cache: Option<TileCache>,
+    crowd_density: Option<Arc<DensityMap>>,
+
+    // YUV 融合预处理: 预计算的最近邻映射表
+    resize_x_map: Vec<usize>,
+    resize_y_map: Vec<usize>,
+    src_width: usize,
+    src_height: usize,
+
+    // 统计
+    count: u64,
+    last: Instant,
+    current_fps: f64,
+
+    // 跟踪统计
+    tracker_count: u64,
+    tracker_last: Instant,
+    tracker_current_fps: f64,
+}
+
+impl StreamContext {
+    fn new(view: u32, tracker: TrackerType, smoother: DisplaySmoother) -> Self {
+        Self {
+            view,
+            tracker,
+            speed: SpeedEstimator::new(),
+            smoother,
+            journal: None,
+            generation: None,
+            tile_cache: tile_cache::config().cloned().map(TileCache::new),
+            crowd_density: None,
+            // 初始化为空映射表,首帧时更新
+            resize_x_map: Vec::new(),
+            resize_y_map: Vec::new(),
+            src_width: 0,
+            src_height: 0,
+            count: 0,
+            last: Instant::now(),
+            current_fps: 0.0,
+            tracker_count: 0,
+            tracker_last: Instant::now(),
+            tracker_current_fps: 0.0,
+        }
+    }
+
+    /// 重建当前类型的跟踪器 (保留关联权重与生命周期参数), 清空速度与显示平滑状态
+    fn reset_tracking(&mut self, association: AssociationWeights, params: Option<TrackerParams>) {
+        self.tracker = self.tracker.fresh(association, params);
+        self.speed.reset();
+        self.smoother.reset();
+    }
+
+    /// 从跟踪状态日志恢复本流的轨迹, 之后定期保存到本流的日志文件
+    fn restore_journal(&mut self, journal: &TrackJournal) {
+        let journal = journal.for_view(self.view);
+        let restored = match &mut self.tracker {
+            TrackerType::DeepSort(tracker) => journal.load("deepsort").map(|snapshot| {
+                tracker.restore(&snapshot);
+                snapshot.tracks.len()
+            }),
+            TrackerType::ByteTrack(tracker) => journal.load("bytetrack").map(|snapshot| {
+                tracker.restore(&snapshot);
+                snapshot.tracks.len()
+            }),
+            TrackerType::None => None,
+        };
+        if let Some(count) = restored {
+            println!("📒 已从 {} 恢复 {} 条轨迹", journal.path().display(), count);
+        }
+        self.journal = Some(journal);
+    }
+
+    /// 到达保存间隔时写入跟踪状态日志
+    fn save_journal(&mut self) {
+        let Some(journal) = self.journal.as_mut() else {
+            return;
+        };
+        if !journal.due(Instant::now()) {
+            return;
+        }
+        let snapshot = match &self.tracker {
+            TrackerType::DeepSort(tracker) => tracker.snapshot(),
+            TrackerType::ByteTrack(tracker) => tracker.snapshot(),
+            TrackerType::None => return,
+        };
+        if let Err(e) = journal.save(&snapshot) {
+            eprintln!(
+                "⚠️ 跟踪状态日志写入失败 ({}): {}",
+                journal.path().display(),
+                e
+            );
+        }
+    }
+}
+
+pub struct Detector {
+    detect_model_path: String,
+    inf_size: u32,
+    // 正在处理的逻辑流的状态, 本线程的其余逻辑流按编号存放在 streams (各流首帧到达时创建)
+    stream: StreamContext,
+    streams: BTreeMap<u32, StreamContext>,
+    // 主逻辑流之外本线程接收的逻辑流
+    extra_views: BTreeSet<u32>,
+    // ByteTrack 关联权重 (切换跟踪器时保留)
+    association: AssociationWeights,
+    // 界面设置的显示阈值, 启用显示平滑 (跟踪后的滞回与新目标确认) 时模型按更低的保持阈值推理
+    display_conf: Option<f32>,
+    // 阈值预览的虚影下限, 启用时模型按不高于该下限的阈值推理
+    ghost_floor: Option<f32>,
+    // 动态分辨率阶梯 (按目标大小升降推理输入尺寸, 需要动态轴模型)
+    ladder: ResolutionLadder,
+    pose_enabled: bool,
+    detection_enabled: bool,
+    // 检测的类别ID (空表示全部类别)
+    classes: Vec<u32>,
+    // 开放词汇模型: 界面设置的类别词表 (空表示配置中的词表) 与当前模型的词表
+    vocabulary_names: Vec<String>,
+    vocabulary: Option<Arc<Vocabulary>>,
+    // 布防状态 (布防调度器控制, 与界面的检测开关同时满足才检测)
+    armed: bool,
+    // 两阶段姿态估计 (检测人框 → 裁剪 → 独立姿态模型)
+    pose_model_path: Option<String>,
+    pose_model: Option<TopDownPose>,
+    // 单目深度估计 (低频运行, 为检测框提供近似距离)
+    depth_model_path: Option<String>,
+    depth_model: Option<DepthEstimator>,
+    // 行人属性识别 (按轨迹低频运行, 衣着颜色/背包/安全帽)
+    attribute_model_path: Option<String>,
+    attribute_model: Option<AttributeRecognizer>,
+    // 车辆属性识别 (按轨迹低频运行, 车型/车身颜色)
+    vehicle_model_path: Option<String>,
+    vehicle_model: Option<VehicleRecognizer>,
+    // 人群密度估计 (极密集场景替代逐人检测, 低频运行)
+    crowd_model_path: Option<String>,
+    crowd_model: Option<CrowdCounter>,
+    crowd_enabled: bool,
+    // CLIP 文本提示检索
+    clip_model_path: Option<String>,
+    clip_model: Option<ClipModel>,
+    text_prompt: String,
+    text_embedding: Option<Embedding>,
+    // 跨摄像头全局ID (多路检测线程共享) 与本路摄像头编号
+    global_ids: Option<(Arc<Mutex<GlobalIdManager>>, u32)>,
+    // 跟踪状态日志 (定期保存活跃轨迹, 重启后恢复轨迹ID), 各逻辑流按编号使用各自的文件
+    journal: Option<TrackJournal>,
+    // 本线程的主逻辑流 (鱼眼虚拟视图各自一个检测线程), 控制消息、布防与 GPU 分配按它区分
+    view: u32,
+    // 主逻辑流的地面标定: 检测框脚点 → 地面坐标
+    ground: Option<Homography>,
+    // Jetson: 检测模型放到 DLA 核心, tegrastats 功耗/温控监控
+    dla_core: Option<u32>,
+    // 多 GPU 分配: 检测模型所在的 GPU (安装了分配器时加载模型前取得)
+    gpu: Mutex<Option<GpuLease>>,
+    jetson: Option<JetsonMonitor>,
+    throttle: ThrottleLevel,
+    frame_index: u64,
+    // CUDA 端到端管线 (收到设备帧时延迟创建, 切换模型时重建)
+    #[cfg(feature = "cuda")]
+    cuda_pipeline: Option<CudaPipeline>,
+    #[cfg(feature = "cuda")]
+    cuda_failed: bool,
+    config_rx: Option<Receiver<ControlMessage>>,
+    // 最近一次发布的错误状态 (同一错误连续出现只报告一次)
+    last_error: Option<DetectorError>,
+    // 看门狗: 最近一次正常处理过帧的模型与跟踪器参数, panic 后据此重启
+    last_good_model_path: Option<String>,
+    tracker_params: Option<TrackerParams>,
+    // 连续 panic 次数 (正常处理一帧后清零)
+    restarts: u32,
+
+    // 整帧缩放 (算法可切换, 最近邻时优先走 YUV 融合采样)
+    resizer: FrameResizer,
+    // 融合预处理时传给 postprocess 的尺寸占位图 (仅读取宽高)
+    size_probe: Vec<DynamicImage>,
+    // 当前检测模型的预处理参数 (融合路径按此归一化)
+    preprocess_spec: PreprocessSpec,
+
+    // GPU加速支持
+    #[cfg(feature = "gpu")]
+    gpu_transform: Option<WgpuAffineTransform>,
+}
+impl Detector {
+    pub fn new(
+        detect_model: String,
+        inf_size: u32,
+        tracker_name: String,
+        pose_enabled: bool,
+    ) -> Self {
+        // 根据跟踪器名称初始化
+        let tracker = match tracker_name.to_lowercase().as_str() {
+            "deepsort" => {
+                println!("🎯 跟踪器: DeepSort (级联匹配 + 外观特征)");
+                TrackerType::DeepSort(PersonTracker::new())
+            }
+            "bytetrack" => {
+                println!("🎯 跟踪器: ByteTrack (高低分分开处理)");
+                TrackerType::ByteTrack(ByteTracker::new())
+            }
+            _ => {
+                println!("🎯 跟踪器: 禁用");
+                TrackerType::None
+            }
+        };
+
+        Self {
+            detect_model_path: detect_model,
+            inf_size,
+            stream: StreamContext::new(0, tracker, DisplaySmoother::default()),
+            streams: BTreeMap::new(),
+            extra_views: BTreeSet::new(),
+            association: AssociationWeights::default(),
+            display_conf: None,
+            ghost_floor: None,
+            ladder: ResolutionLadder::default(),
+            pose_enabled,
+            detection_enabled: true,
+            classes: types::DETECT_CLASSES.to_vec(),
+            vocabulary_names: Vec::new(),
+            vocabulary: None,
+            armed: true,
+            pose_model_path: None,
+            pose_model: None,
+            depth_model_path: None,
+            depth_model: None,
+            attribute_model_path: None,
+            attribute_model: None,
+            vehicle_model_path: None,
+            vehicle_model: None,
+            crowd_model_path: None,
+            crowd_model: None,
+            crowd_enabled: true,
+            clip_model_path: None,
+            clip_model: None,
+            text_prompt: String::new(),
+            text_embedding: None,
+            global_ids: None,
+            journal: None,
+            view: 0,
+            ground: None,
+            dla_core: None,
+            gpu: Mutex::new(None),
+            jetson: None,
+            throttle: ThrottleLevel::Normal,
+            frame_index: 0,
+            #[cfg(feature = "cuda")]
+            cuda_pipeline: None,
+            #[cfg(feature = "cuda")]
+            cuda_failed: false,
+            config_rx: None,
+            last_error: None,
+            last_good_model_path: None,
+            tracker_params: None,
+            restarts: 0,
+            resizer: FrameResizer::new(resize::default_quality()),
+            size_probe: vec![DynamicImage::new_luma8(inf_size, inf_size)],
+            preprocess_spec: PreprocessSpec::default(),
+            // 尝试初始化GPU加速
+            #[cfg(feature = "gpu")]
+            gpu_transform: WgpuAffineTransform::new().ok(),
+        }
+    }
+
+    /// 融合预处理: YUV420 平面直接采样为归一化 NCHW 张量
+    ///
+    /// 按最近邻映射表采样, 结果与最近邻缩放的 RGBA → RGB → preprocess 流程一致,
+    /// 省去 RGB 缩放图与 DynamicImage 两次整帧拷贝. `sample` 按映射表写入张量
+    /// (8 位平面见 [`yuv420_to_nchw`], 10-bit 源的 16 位平面见 [`yuv420_16_to_nchw`])
+    fn fused_yuv_input(
+        &mut self,
+        (width, height): (usize, usize),
+        dst_size: usize,
+        sample: impl FnOnce(&[usize], &[usize], &PreprocessSpec, &mut [f32]),
+    ) -> Option<Array<f32, IxDyn>> {
+        if self.stream.src_width != width || self.stream.src_height != height {
+            self.stream.resize_x_map = nearest_map(width, dst_size);
+            self.stream.resize_y_map = nearest_map(height, dst_size);
+            self.stream.src_width = width;
+            self.stream.src_height = height;
+            eprintln!(
+                "📐 YUV融合预处理映射表已更新: {}x{} → {}",
+                width, height, dst_size
+            );
+        }
+
+        let channels = self.preprocess_spec.channels;
+        let mut data = vec![0f32; channels * dst_size * dst_size];
+        sample(
+            &self.stream.resize_x_map,
+            &self.stream.resize_y_map,
+            &self.preprocess_spec,
+            &mut data,
+        );
+        match Array::from_shape_vec(IxDyn(&[1, channels, dst_size, dst_size]), data) {
+            Ok(x) => Some(x),
+            Err(e) => {
+                eprintln!("❌ YUV融合预处理失败: {}", e);
+                None
+            }
+        }
+    }
+
+    /// 设置控制消息通道 (通常由 [`types::control_receiver`] 从 xbus 桥接)
+    pub fn set_config_receiver(&mut self, rx: Receiver<ControlMessage>) {
+        self.config_rx = Some(rx);
+    }
+
+    /// 设置两阶段姿态模型 (ViTPose/RTMPose), 首帧时与检测模型一起加载
+    pub fn set_pose_model(&mut self, model_path: String) {
+        self.pose_model_path = Some(model_path);
+    }
+
+    /// 设置深度估计模型 (MiDaS/Depth-Anything), 首帧时加载
+    pub fn set_depth_model(&mut self, model_path: String) {
+        self.depth_model_path = Some(model_path);
+    }
+
+    /// 设置行人属性模型 (多标签分类), 首帧时加载, 需要跟踪器
+    pub fn set_attribute_model(&mut self, model_path: String) {
+        self.attribute_model_path = Some(model_path);
+    }
+
+    /// 设置车辆属性模型 (车型/颜色分类), 首帧时加载, 需要跟踪器且检测类别包含车辆
+    pub fn set_vehicle_model(&mut self, model_path: String) {
+        self.vehicle_model_path = Some(model_path);
+    }
+
+    /// 设置人群密度模型 (CSRNet 风格密度图回归), 首帧时加载
+    pub fn set_crowd_model(&mut self, model_path: String) {
+        self.crowd_model_path = Some(model_path);
+    }
+
+    /// 设置 CLIP 模型目录 (visual.onnx/textual.onnx/vocab.json/merges.txt), 首帧时加载
+    pub fn set_clip_model(&mut self, model_dir: String) {
+        self.clip_model_path = Some(model_dir);
+    }
+
+    /// 启用跨摄像头全局ID, 多路检测线程传入同一个管理器和各自的摄像头编号
+    pub fn set_global_ids(&mut self, manager: Arc<Mutex<GlobalIdManager>>, camera_id: u32) {
+        self.global_ids = Some((manager, camera_id));
+    }
+
+    /// 启用跟踪状态日志: 立即从日志恢复各逻辑流跟踪器的轨迹, 之后定期保存
+    ///
+    /// 需在 [`Self::set_view`] 之后调用, 每个逻辑流使用各自的日志文件
+    pub fn set_track_journal(&mut self, journal: &TrackJournal) {
+        for stream in self.streams_mut() {
+            stream.restore_journal(journal);
+        }
+        self.journal = Some(journal.clone());
+    }
+
+    /// 只处理指定逻辑流的帧 (0 为原始画面, 1..=N 为鱼眼虚拟视图)
+    pub fn set_view(&mut self, view: u32) {
+        self.view = view;
+        self.stream.view = view;
+    }
+
+    /// 本线程额外接收的逻辑流: 共用模型与设置, 轨迹、缩放缓存与统计各自独立 (该流首帧到达时创建)
+    ///
+    /// 控制消息、布防与地面标定仍按主逻辑流 ([`Self::set_view`]) 接收
+    pub fn add_view(&mut self, view: u32) {
+        if view != self.view {
+            self.extra_views.insert(view);
+        }
+    }
+
+    /// 本线程接收的全部逻辑流
+    fn views(&self) -> Vec<u32> {
+        std::iter::once(self.view)
+            .chain(self.extra_views.iter().copied())
+            .collect()
+    }
+
+    /// 本线程全部逻辑流的状态 (设置类控制消息对每一路生效)
+    fn streams_mut(&mut self) -> impl Iterator<Item = &mut StreamContext> {
+        std::iter::once(&mut self.stream).chain(self.streams.values_mut())
+    }
+
+    /// 切换到帧所属逻辑流的状态, 当前流的状态放回 streams; 该流的首帧到达时创建
+    fn select_stream(&mut self, view: u32) {
+        if view == self.stream.view {
+            return;
+        }
+        let next = match self.streams.remove(&view) {
+            Some(next) => next,
+            None => self.new_stream(view),
+        };
+        let previous = std::mem::replace(&mut self.stream, next);
+        self.streams.insert(previous.view, previous);
+    }
+
+    /// 新逻辑流的状态: 跟踪器类型与平滑设置沿用当前流, 启用跟踪状态日志时从本流的文件恢复
+    fn new_stream(&self, view: u32) -> StreamContext {
+        println!("🎞️ 逻辑流 {} 首帧到达, 创建独立的跟踪状态", view);
+        let tracker = self
+            .stream
+            .tracker
+            .fresh(self.association, self.tracker_params);
+        let smoother = DisplaySmoother::new(self.stream.smoother.config().clone());
+        let mut stream = StreamContext::new(view, tracker, smoother);
+        if let Some(journal) = &self.journal {
+            stream.restore_journal(journal);
+        }
+        stream
+    }
+
+    /// 检测模型使用 TensorRT DLA 核心 (Jetson), 需在加载模型前设置
+    pub fn set_dla_core(&mut self, core: u32) {
+        self.dla_core = Some(core);
+    }
+
+    /// 启用 tegrastats 监控: 结果附带功耗/温度, 温度过高时按降频信号跳帧
+    pub fn set_jetson_monitor(&mut self, monitor: JetsonMonitor) {
+        self.jetson = Some(monitor);
+    }
+
+    /// 按降频信号决定本帧是否推理
+    fn should_infer(&mut self) -> bool {
+        let Some(monitor) = &self.jetson else {
+            return true;
+        };
+        let level = monitor.throttle();
+        if level != self.throttle {
+            println!(
+                "🌡️ 推理频率调整: {} → {} (每{}帧推理一次)",
+                self.throttle.name(),
+                level.name(),
+                level.frame_stride()
+            );
+            self.throttle = level;
+        }
+        self.frame_index += 1;
+        self.frame_index.is_multiple_of(level.frame_stride())
+    }
+
+    /// 更新文本提示并计算文本嵌入
+    fn update_text_prompt(&mut self) {
+        self.text_embedding = None;
+        if self.text_prompt.is_empty() {
+            println!("🔎 文本检索已关闭");
+            return;
+        }
+        match self.clip_model.as_mut() {
+            Some(clip) => match clip.encode_text(&self.text_prompt) {
+                Ok(emb) => {
+                    println!("🔎 文本检索: \"{}\"", self.text_prompt);
+                    self.text_embedding = Some(emb);
+                }
+                Err(e) => eprintln!("❌ 文本编码失败: {}", e),
+            },
+            None => println!("⚠️ 未加载CLIP模型,文本提示将在模型加载后生效"),
+        }
+    }
+
+    /// 开放词汇模型: 应用界面设置的类别词表, 并记录模型当前的词表 (渲染显示类别名);
+    /// 加载模型后与收到 SetVocabulary 时调用, 其他模型只清除记录
+    fn sync_vocabulary(&mut self, model: &mut dyn Model) {
+        if model.vocabulary().is_some() {
+            let names = if self.vocabulary_names.is_empty() {
+                world::configured_classes()
+            } else {
+                self.vocabulary_names.clone()
+            };
+            match model.set_vocabulary(&names) {
+                Ok(()) => println!("🏷️ 类别词表: {:?}", names),
+                Err(e) => eprintln!("❌ 类别词表更新失败: {}", e),
+            }
+        }
+        self.vocabulary = model.vocabulary().cloned().map(Arc::new);
+    }
+
+    /// 姿态估计是否可用: 检测模型自带pose头, 或已加载两阶段姿态模型
+    fn pose_supported(&self, model: &dyn Model) -> bool {
+        model.supports_task(YOLOTask::Pose) || self.pose_model.is_some()
+    }
+
+    fn load_pose_model(&self, model_path: &str) -> Option<TopDownPose> {
+        let pose_args = Args {
+            model: model_path.to_string(),
+            width: None,
+            height: None,
+            conf: 0.0,
+            iou: 0.0,
+            source: String::new(),
+            device_id: 0,
+            trt: false,
+            cuda: false,
+            batch: 1,
+            batch_min: 1,
+            batch_max: 8,
+            fp16: false,
+            dla_core: None,
+            task: Some(YOLOTask::Pose),
+            nc: None,
+            nk: Some(17),
+            nm: None,
+            kconf: 0.3,
+            profile: false,
+        };
+
+        match TopDownPose::new(pose_args) {
+            Ok(m) => {
+                println!("✅ 两阶段姿态模型加载成功: {} ({:?})", model_path, m.head());
+                Some(m)
+            }
+            Err(e) => {
+                eprintln!("❌ 两阶段姿态模型加载失败: {}", e);
+                None
+            }
+        }
+    }
+
+    /// 检测模型的 GPU: 沿用已有的租约, 切换到固定在其他卡上的模型时重新分配; DLA 优先
+    fn place_gpu(&self, model_path: &str) -> Option<(GpuProvider, i32)> {
+        let balancer = gpu_placement::balancer().filter(|_| self.dla_core.is_none())?;
+        let mut gpu = self.gpu.lock().unwrap();
+        let pinned = balancer.config().pinned(self.view, model_path);
+        if gpu
+            .as_ref()
+            .is_none_or(|lease| pinned.is_some_and(|device| device != lease.device))
+        {
+            // 先归还旧租约, 自动分配不把自己算作负载
+            *gpu = None;
+            *gpu = Some(balancer.assign(self.view, model_path));
+        }
+        gpu.as_ref().map(|lease| (lease.provider, lease.device))
+    }
+
+    fn load_model(
+        &self,
+        model_path: &str,
+    ) -> Result<Arc<Mutex<Box<dyn Model + Send>>>, DetectorError> {
+        // 识别模型类型
+        let model_type = ModelType::from_path(model_path);
+        let gpu = self.place_gpu(model_path);
+
+        // 加载检测模型
+        let detect_args = Args {
+            model: model_path.to_string(),
+            width: Some(self.inf_size),
+            height: Some(self.inf_size),
+            conf: self.model_conf(
+                self.display_conf
+                    .unwrap_or(model_type.default_conf_threshold()),
+            ),
+            iou: model_type.default_iou_threshold(),
+            source: String::new(),
+            device_id: gpu.map_or(0, |(_, device)| device),
+            trt: matches!(gpu, Some((GpuProvider::Trt, _))),
+            cuda: matches!(gpu, Some((GpuProvider::Cuda, _))),
+            batch: 1,
+            batch_min: 1,
+            batch_max: 1,
+            fp16: false,
+            dla_core: self.dla_core,
+            task: Some(YOLOTask::Detect),
+            nc: None,
+            nk: None,
+            nm: None,
+            kconf: 0.55,
+            profile: false,
+        };
+
+        match load_detect_model(model_type, detect_args) {
+            Ok(m) => {
+                println!("✅ {:?} 检测模型加载成功: {}", model_type, model_path);
+                Ok(Arc::new(Mutex::new(m)))
+            }
+            Err(e) => Err(DetectorError::from_anyhow(&e, DetectorError::ModelLoad)),
+        }
+    }
+
+    pub fn run(&mut self) {
+        println!("🔍 检测模块启动");
+
+        // 延迟加载模型 - 等待第一帧数据时才加载
+        let mut detect_model: Option<Arc<Mutex<Box<dyn Model + Send>>>> = None;
+        let mut model_loaded = false;
+
+        // 订阅解码帧 - 仅将任务放入队列
+        // 进一步减小队列长度以降低内存占用 (5 -> 2)
+        // 牺牲少量延迟稳定性换取更低的内存占用
+        let (tx, rx): (Sender<DecodedFrame>, Receiver<DecodedFrame>) =
+            crossbeam_channel::bounded(2);
+
+        // 背压: 队列满时通知解码端暂停转换/发布, 取走一帧后解除 (只在状态变化时发布)
+        let saturated = Arc::new(AtomicBool::new(false));
+        let saturated_flag = Arc::clone(&saturated);
+        let views = self.views();
+        let queue_tx = tx.clone();
+        let _sub = xbus::subscribe::<DecodedFrame, _>(move |frame| {
+            if !views.contains(&frame.view) {
+                return;
+            }
+            // 轻量级操作：仅将帧放入工作队列
+            if let Err(_) = tx.try_send(frame.clone()) {
+                //eprintln!("❌ 目标检测队列发送失败: {}", e);
+            }
+            if tx.is_full() && !saturated_flag.swap(true, Ordering::Relaxed) {
+                xbus::post(Backpressure { saturated: true });
+            }
+        })
+        .with_queue(&queue_tx);
+
+        println!("✅ 检测模块已订阅DecodedFrame,等待视频流启动...");
+
+        // 初始布防状态 (此后的变化经控制消息到达)
+        self.armed = scheduler::is_armed(self.view);
+        if !self.armed {
+            println!("🔕 逻辑流 {} 当前撤防, 布防后开始检测", self.view);
+        }
+
+        // 工作线程: 异步处理检测任务
+        loop {
+            // 检查配置更新 (持有接收端的副本, 处理消息时可以修改 self)
+            if let Some(rx) = self.config_rx.clone() {
+                while let Ok(msg) = rx.try_recv() {
+                    // 参数/模型变化后缓存的检测结果不再可信, 下一帧整帧推理
+                    for stream in self.streams_mut() {
+                        if let Some(cache) = &mut stream.tile_cache {
+                            cache.invalidate();
+                        }
+                    }
+                    match msg {
+                        ControlMessage::UpdateParams {
+                            conf_threshold,
+                            iou_threshold,
+                        } => {
+                            self.display_conf = Some(conf_threshold);
+                            if let Some(ref model) = detect_model {
+                                let mut m = model.lock().unwrap();
+                                m.set_conf(self.model_conf(conf_threshold));
+                                m.set_iou(iou_threshold);
+                            }
+                        }
+                        ControlMessage::SwitchModel(model_path) => {
+                            println!("🔄 正在切换模型: {}", model_path);
+                            let new_model = match self.load_model(&model_path) {
+                                Ok(m) => m,
+                                Err(e) => {
+                                    self.report(Some(e));
+                                    continue;
+                                }
+                            };
+                            self.report(None);
+                            detect_model = Some(new_model);
+                            self.detect_model_path = model_path.clone();
+                            model_loaded = true;
+                            #[cfg(feature = "cuda")]
+                            {
+                                self.cuda_pipeline = None;
+                                self.cuda_failed = false;
+                            }
+
+                            // 重新检查姿态估计支持
+                            let mut m = detect_model.as_ref().unwrap().lock().unwrap();
+                            self.preprocess_spec = m.preprocess_spec();
+                            self.sync_vocabulary(&mut **m);
+                            if self.pose_enabled && !self.pose_supported(&**m) {
+                                println!("⚠️ 新模型不支持姿态估计,已自动禁用");
+                                self.pose_enabled = false;
+                            }
+                        }
+                        ControlMessage::SwitchTracker(tracker_name) => {
+                            println!("🔄 正在切换跟踪器: {}", tracker_name);
+                            let kind = match tracker_name.to_lowercase().as_str() {
+                                "deepsort" => TrackerType::DeepSort(PersonTracker::new()),
+                                "bytetrack" => TrackerType::ByteTrack(ByteTracker::new()),
+                                _ => TrackerType::None,
+                            };
+                            // 新跟踪器的轨迹ID与旧的无关
+                            self.tracker_params = None;
+                            let association = self.association;
+                            for stream in self.streams_mut() {
+                                stream.tracker = kind.fresh(association, None);
+                                stream.speed.reset();
+                                stream.smoother.reset();
+                            }
+                        }
+                        ControlMessage::SetAssociation(weights) => {
+                            self.association = weights;
+                            for stream in self.streams_mut() {
+                                if let TrackerType::ByteTrack(tracker) = &mut stream.tracker {
+                                    tracker.set_association(weights);
+                                }
+                            }
+                        }
+                        ControlMessage::SetGroundHomography(homography) => {
+                            println!(
+                                "📐 地面标定{}",
+                                if homography.is_some() {
+                                    "已更新"
+                                } else {
+                                    "已清除"
+                                }
+                            );
+                            self.ground = homography;
+                            let view = self.view;
+                            for stream in self.streams_mut().filter(|s| s.view == view) {
+                                stream.speed.reset();
+                            }
+                        }
+                        ControlMessage::SetTrackerParams(params) => {
+                            self.tracker_params = Some(params);
+                            self.apply_tracker_params();
+                        }
+                        ControlMessage::SetSmoothing(config) => {
+                            for stream in self.streams_mut() {
+                                stream.smoother.set_config(config.clone());
+                            }
+                            if let Some(ref model) = detect_model {
+                                let conf = self.model_conf(self.show_conf());
+                                model.lock().unwrap().set_conf(conf);
+                            }
+                        }
+                        ControlMessage::SetGhostFloor(floor) => {
+                            self.ghost_floor = floor;
+                            if let Some(ref model) = detect_model {
+                                let conf = self.model_conf(self.show_conf());
+                                model.lock().unwrap().set_conf(conf);
+                            }
+                        }
+                        ControlMessage::TogglePose(enabled) => {
+                            self.pose_enabled = enabled;
+                            if enabled {
+                                if let Some(ref model) = detect_model {
+                                    let m = model.lock().unwrap();
+                                    if !self.pose_supported(&**m) {
+                                        println!("⚠️ 当前模型不支持姿态估计,无法启用");
+                                        self.pose_enabled = false;
+                                    } else {
+                                        println!("✅ 姿态估计已启用");
+                                    }
+                                }
+                            } else {
+                                println!("🚫 姿态估计已禁用");
+                            }
+                        }
+                        ControlMessage::SetTextPrompt(prompt) => {
+                            self.text_prompt = prompt.trim().to_string();
+                            self.update_text_prompt();
+                        }
+                        ControlMessage::ToggleCrowd(enabled) => {
+                            self.crowd_enabled = enabled;
+                            if !enabled {
+                                for stream in self.streams_mut() {
+                                    stream.crowd_density = None;
+                                }
+                            }
+                            println!(
+                                "👥 人群密度估计: {}",
+                                if enabled { "已启用" } else { "已禁用" }
+                            );
+                        }
+                        ControlMessage::SetClasses(classes) => {
+                            println!("🏷️ 检测类别: {:?}", classes);
+                            self.classes = classes;
+                        }
+                        ControlMessage::SetVocabulary(names) => {
+                            self.vocabulary_names = names;
+                            match detect_model {
+                                Some(ref model) if self.vocabulary.is_some() => {
+                                    let mut m = model.lock().unwrap();
+                                    self.sync_vocabulary(&mut **m);
+                                }
+                                _ => println!("⚠️ 当前模型不是开放词汇模型,类别词表将在加载 YOLO-World 后生效"),
+                            }
+                        }
+                        ControlMessage::SetInputSize(size) => {
+                            if size != self.inf_size {
+                                println!("📐 推理输入尺寸: {} → {}", self.inf_size, size);
+                                self.set_inf_size(size);
+                            }
+                        }
+                        ControlMessage::SetResizeQuality(quality) => {
+                            if quality != self.resizer.quality() {
+                                println!(
+                                    "📐 缩放算法: {} → {}",
+                                    self.resizer.quality().label(),
+                                    quality.label()
+                                );
+                                self.resizer.set_quality(quality);
+                            }
+                        }
+                        ControlMessage::SetResolutionLadder(config) => {
+                            if config.enabled && !self.ladder.config().enabled {
+                                println!("📐 动态分辨率已启用: {:?}", config.rungs);
+                            }
+                            self.ladder.set_config(config);
+                        }
+                        ControlMessage::SetArmed(armed) => {
+                            self.armed = armed;
+                        }
+                        ControlMessage::ToggleDetection(enabled) => {
+                            self.detection_enabled = enabled;
+                            if enabled {
+                                println!("✅ 目标检测已启用");
+                            } else {
+                                println!("🚫 目标检测已禁用");
+                            }
+                        }
+                        ControlMessage::Shutdown => {
+                            println!("🛑 检测模块退出 (逻辑流 {})", self.view);
+                            return;
+                        }
+                    }
+                }
+            }
+
+            // 超时返回以便及时处理控制消息 (无帧时也能退出)
+            match rx.recv_timeout(CONTROL_POLL_INTERVAL) {
+                Ok(frame) => {
+                    if !rx.is_full() && saturated.swap(false, Ordering::Relaxed) {
+                        xbus::post(Backpressure { saturated: false });
+                    }
+                    // 多路流: 换到帧所属逻辑流的轨迹与缓存
+                    self.select_stream(frame.view);
+                    // 切换输入源: 旧解码器的残留帧丢弃, 新输入源的首帧前重置缓存
+                    if self.stream.generation.is_some_and(|g| frame.generation < g) {
+                        continue;
+                    }
+                    if self.stream.generation != Some(frame.generation) {
+                        self.switch_generation(frame.generation);
+                    }
+                    // 延迟加载: 收到第一帧时才加载模型
+                    if !model_loaded {
+                        println!("📥 收到第一帧数据,开始加载模型: {}", self.detect_model_path);
+                        if let Some(path) = self.pose_model_path.clone() {
+                            self.pose_model = self.load_pose_model(&path);
+                        }
+                        if let Some(path) = self.depth_model_path.clone() {
+                            // 深度变化缓慢, 每5帧估计一次
+                            match DepthEstimator::new(&path, 5) {
+                                Ok(m) => {
+                                    println!("✅ 深度模型加载成功: {} ({:?})", path, m.kind());
+                                    self.depth_model = Some(m);
+                                }
+                                Err(e) => eprintln!("❌ 深度模型加载失败: {}", e),
+                            }
+                        }
+                        if let Some(path) = self.attribute_model_path.clone() {
+                            // 衣着属性基本不变, 每条轨迹每2秒识别一次
+                            match AttributeRecognizer::new(&path, Duration::from_secs(2)) {
+                                Ok(m) => {
+                                    println!(
+                                        "✅ 行人属性模型加载成功: {} ({}个标签)",
+                                        path,
+                                        m.labels().len()
+                                    );
+                                    self.attribute_model = Some(m);
+                                }
+                                Err(e) => eprintln!("❌ 行人属性模型加载失败: {}", e),
+                            }
+                        }
+                        if let Some(path) = self.vehicle_model_path.clone() {
+                            match VehicleRecognizer::new(&path, Duration::from_secs(2)) {
+                                Ok(m) => {
+                                    println!(
+                                        "✅ 车辆属性模型加载成功: {} ({}个标签)",
+                                        path,
+                                        m.labels().len()
+                                    );
+                                    self.vehicle_model = Some(m);
+                                }
+                                Err(e) => eprintln!("❌ 车辆属性模型加载失败: {}", e),
+                            }
+                        }
+                        if let Some(path) = self.crowd_model_path.clone() {
+                            // 人群整体变化较慢, 每5帧估计一次
+                            match CrowdCounter::new(&path, 5) {
+                                Ok(m) => {
+                                    println!("✅ 人群密度模型加载成功: {}", path);
+                                    self.crowd_model = Some(m);
+                                }
+                                Err(e) => eprintln!("❌ 人群密度模型加载失败: {}", e),
+                            }
+                        }
+                        if let Some(path) = self.clip_model_path.clone() {
+                            match ClipModel::new(&path) {
+                                Ok(m) => {
+                                    println!("✅ CLIP模型加载成功: {}", path);
+                                    self.clip_model = Some(m);
+                                    if !self.text_prompt.is_empty() {
+                                        self.update_text_prompt();
+                                    }
+                                }
+                                Err(e) => eprintln!("❌ CLIP模型加载失败: {}", e),
+                            }
+                        }
+                        match self.load_model(&self.detect_model_path) {
+                            Ok(model) => {
+                                // 检查姿态估计支持
+                                {
+                                    let mut m = model.lock().unwrap();
+                                    self.preprocess_spec = m.preprocess_spec();
+                                    self.sync_vocabulary(&mut **m);
+                                    if self.pose_enabled && !self.pose_supported(&**m) {
+                                        println!("⚠️ 姿态估计: 已请求但模型不支持,将禁用");
+                                        self.pose_enabled = false;
+                                    } else if self.pose_enabled {
+                                        println!("✅ 姿态估计: 已启用");
+                                    }
+                                }
+                                detect_model = Some(model);
+                                model_loaded = true;
+                                println!("✅ 模型加载完成,开始处理视频流");
+                            }
+                            Err(e) => {
+                                // 不再逐帧重试, 等待界面切换模型
+                                self.report(Some(e));
+                                model_loaded = true;
+                                continue;
+                            }
+                        }
+                    }
+
+                    if self.detection_enabled && self.armed {
+                        if !self.should_infer() {
+                            continue;
+                        }
+                        if let Some(model) = detect_model.clone() {
+                            let inf_size = self.inf_size;
+                            // 看门狗: 单帧 panic 不结束线程, 用最近一次正常的配置重启检测
+                            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                                self.process_frame(frame, &model, inf_size)
+                            }));
+                            match result {
+                                Ok(()) => self.mark_good(),
+                                Err(payload) => detect_model = self.restart(&model, &*payload),
+                            }
+                        }
+                    } else {
+                        // 如果检测被禁用，仍然需要发送空结果以维持FPS统计和画面更新
+                        // 或者直接跳过处理，取决于架构设计。
+                        // 这里我们选择发送一个空的检测结果，以便渲染线程知道没有检测到物体
+                        // 但为了节省资源，我们不进行任何图像处理 (人群密度模式除外)
+                        let density = self.update_crowd_density(&frame);
+                        xbus::post(DetectionResult {
+                            bboxes: Vec::new(),
+                            keypoints: Vec::new(),
+                            inference_fps: 0.0,
+                            inference_ms: 0.0,
+                            tracker_fps: 0.0,
+                            tracker_ms: 0.0,
+                            resized_image: None,
+                            resized_size: self.inf_size,
+                            reid_features: Vec::new(),
+                            distances: Vec::new(),
+                            prompt_matches: Vec::new(),
+                            attributes: Vec::new(),
+                            vehicles: Vec::new(),
+                            track_stats: TrackStats::default(),
+                            tracked: false,
+                            global_ids: Vec::new(),
+                            jetson: self.jetson.as_ref().and_then(JetsonMonitor::status),
+                            trace: FrameTrace {
+                                publish_ts: Some(Instant::now()),
+                                ..frame.trace
+                            },
+                            view: self.stream.view,
+                            ground_points: Vec::new(),
+                            speeds: Vec::new(),
+                            density,
+                            ghosts: Vec::new(),
+                            classes: Vec::new(),
+                            vocabulary: self.vocabulary.clone(),
+                            partial: Vec::new(),
+                            duplicates: Vec::new(),
+                        });
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(e) => {
+                    eprintln!("❌ 目标检测队列接收失败: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// 带看门狗运行检测循环: 循环本身 panic (如控制消息处理) 时按退避重新进入,
+    /// 模型回退到最近一次正常工作的配置, 连续失败超过 [`MAX_RESTARTS`] 次后退出线程
+    pub fn run_supervised(&mut self) {
+        loop {
+            match panic::catch_unwind(AssertUnwindSafe(|| self.run())) {
+                Ok(()) => return,
+                Err(payload) => {
+                    self.restarts += 1;
+                    self.report(Some(DetectorError::Process(format!(
+                        "检测线程 panic: {}",
+                        panic_message(&*payload)
+                    ))));
+                    if self.restarts > MAX_RESTARTS {
+                        eprintln!("🛑 检测线程连续 {} 次 panic, 停止重启", self.restarts);
+                        return;
+                    }
+                    if let Some(path) = self.last_good_model_path.clone() {
+                        self.detect_model_path = path;
+                    }
+                    std::thread::sleep(RESTART_BACKOFF * self.restarts);
+                    println!("♻️ 重启检测线程 (第 {} 次)", self.restarts);
+                }
+            }
+        }
+    }
+
+    /// 记录当前模型可正常处理帧, 作为看门狗重启时回退的配置
+    fn mark_good(&mut self) {
+        self.restarts = 0;
+        if self.last_good_model_path.as_deref() != Some(self.detect_model_path.as_str()) {
+            self.last_good_model_path = Some(self.detect_model_path.clone());
+        }
+    }
+
+    /// 单帧处理 panic 后重启检测: 发布错误, 重置跟踪状态, 重新加载最近一次正常的模型
+    ///
+    /// 返回新的模型; 连续 panic 过多或加载失败时返回 None, 等待界面切换模型
+    fn restart(
+        &mut self,
+        old_model: &Arc<Mutex<Box<dyn Model + Send>>>,
+        payload: &(dyn Any + Send),
+    ) -> Option<Arc<Mutex<Box<dyn Model + Send>>>> {
+        self.restarts += 1;
+        self.report(Some(DetectorError::Process(format!(
+            "检测线程 panic: {}",
+            panic_message(payload)
+        ))));
+        if self.restarts > MAX_RESTARTS {
+            eprintln!("🛑 连续 {} 次 panic, 暂停检测, 等待切换模型", self.restarts);
+            return None;
+        }
+
+        // panic 可能发生在跟踪器/缩放表更新途中, 状态不可信, 全部重建
+        self.reset_tracking();
+        for stream in self.streams_mut() {
+            stream.src_width = 0;
+            stream.src_height = 0;
+        }
+        #[cfg(feature = "cuda")]
+        {
+            self.cuda_pipeline = None;
+        }
+        // 全局ID管理器由多路检测线程共享, 不能因本线程 panic 让其他线程 unwrap 失败
+        if let Some((manager, _)) = &self.global_ids {
+            manager.clear_poison();
+        }
+
+        // 持锁 panic 的旧模型已中毒, 只取出界面设置的阈值, 模型重新加载而不是复用
+        let (conf, iou) = {
+            let m = old_model.lock().unwrap_or_else(|e| e.into_inner());
+            (m.conf(), m.iou())
+        };
+        let path = self
+            .last_good_model_path
+            .clone()
+            .unwrap_or_else(|| self.detect_model_path.clone());
+        println!("♻️ 重启检测 (第 {} 次), 模型: {}", self.restarts, path);
+        let model = match self.load_model(&path) {
+            Ok(model) => model,
+            Err(e) => {
+                self.report(Some(e));
+                return None;
+            }
+        };
+        self.detect_model_path = path;
+        {
+            let mut m = model.lock().unwrap();
+            m.set_conf(conf);
+            m.set_iou(iou);
+            self.preprocess_spec = m.preprocess_spec();
+            self.sync_vocabulary(&mut **m);
+            if self.pose_enabled && !self.pose_supported(&**m) {
+                println!("⚠️ 重启后的模型不支持姿态估计,已自动禁用");
+                self.pose_enabled = false;
+            }
+        }
+        Some(model)
+    }
+
+    /// 显示阈值: 界面设置的置信度阈值, 尚未收到时取模型类型的推荐值
+    fn show_conf(&self) -> f32 {
+        self.display_conf.unwrap_or_else(|| {
+            ModelType::from_path(&self.detect_model_path).default_conf_threshold()
+        })
+    }
+
+    /// 模型的推理阈值: 平滑的最低保持阈值, 阈值预览时再降到虚影下限
+    fn model_conf(&self, show_conf: f32) -> f32 {
+        let conf = self.stream.smoother.config().inference_conf(show_conf);
+        self.ghost_floor.map_or(conf, |floor| conf.min(floor))
+    }
+
+    /// 重建全部逻辑流的跟踪器 (保留关联权重与生命周期参数), 清空速度与显示平滑状态
+    fn reset_tracking(&mut self) {
+        let (association, params) = (self.association, self.tracker_params);
+        for stream in self.streams_mut() {
+            stream.reset_tracking(association, params);
+        }
+    }
+
+    /// 帧的解码器代数ID变化 (切换了输入源): 按旧画面尺寸缓存的缩放表、分块缓存与
+    /// CUDA 管线全部作废, 帧率统计重新开始; 默认同时重建跟踪器, 旧轨迹不会匹配到新画面的目标
+    fn switch_generation(&mut self, generation: usize) {
+        let first = self.stream.generation.is_none();
+        self.stream.generation = Some(generation);
+        if first {
+            return;
+        }
+        let keep_tracks = KEEP_TRACKS_ON_SWITCH.load(Ordering::Relaxed);
+        println!(
+            "🔄 逻辑流 {} 输入源已切换 (代数 {}), 重置缓存{}",
+            self.stream.view,
+            generation,
+            if keep_tracks { "" } else { "与轨迹" }
+        );
+        self.stream.resize_x_map.clear();
+        self.stream.resize_y_map.clear();
+        self.stream.src_width = 0;
+        self.stream.src_height = 0;
+        if let Some(cache) = &mut self.stream.tile_cache {
+            cache.invalidate();
+        }
+        #[cfg(feature = "cuda")]
+        {
+            self.cuda_pipeline = None;
+            self.cuda_failed = false;
+        }
+        self.stream.crowd_density = None;
+
+        let now = Instant::now();
+        self.stream.count = 0;
+        self.stream.last = now;
+        self.stream.current_fps = 0.0;
+        self.stream.tracker_count = 0;
+        self.stream.tracker_last = now;
+        self.stream.tracker_current_fps = 0.0;
+
+        if !keep_tracks {
+            self.stream
+                .reset_tracking(self.association, self.tracker_params);
+        }
+    }
+
+    /// 将保存的生命周期参数应用到当前跟踪器
+    fn apply_tracker_params(&mut self) {
+        let params = self.tracker_params;
+        for stream in self.streams_mut() {
+            stream.tracker.set_params(params);
+        }
+    }
+
+    /// 错误状态变化时打印并发布 [`DetectorStatus`], None 表示已恢复
+    fn report(&mut self, error: Option<DetectorError>) {
+        if self.last_error == error {
+            return;
+        }
+        match &error {
+            Some(e) => eprintln!("❌ 检测模块错误: {}", e),
+            None => println!("✅ 检测模块已恢复"),
+        }
+        self.last_error = error.clone();
+        xbus::post(DetectorStatus {
+            view: self.view,
+            error,
+        });
+    }
+
+    /// 按运行间隔更新人群密度图, 有地面标定时同时映射到地面 (区域人数规则)
+    fn update_crowd_density(&mut self, frame: &DecodedFrame) -> Option<Arc<DensityMap>> {
+        let counter = self.crowd_model.as_mut().filter(|_| self.crowd_enabled)?;
+        if let (true, Some(map)) = counter.update(&frame.rgba_data, frame.width, frame.height) {
+            let mut map = map.clone();
+            if let Some(h) = self
+                .ground
+                .as_ref()
+                .filter(|_| self.stream.view == self.view)
+            {
+                map.project_to_ground(h, frame.width, frame.height);
+            }
+            self.stream.crowd_density = Some(Arc::new(map));
+        }
+        self.stream.crowd_density.clone()
+    }
+
+    /// CPU 路径: 缩放 (或 YUV 融合预处理) → ORT 推理 → 后处理
+    ///
+    /// 返回 (检测结果, 缩放耗时ms, 推理耗时ms), 图像转换或推理失败时返回 None
+    fn host_detect(
+        &mut self,
+        frame: &DecodedFrame,
+        detect_model: &Arc<Mutex<Box<dyn Model + Send>>>,
+        inf_size: u32,
+        trace: &mut FrameTrace,
+    ) -> Option<(Vec<crate::DetectionResult>, f64, f64)> {
+        // 自带预处理的模型 (NanoDet/FastestV2) 不走通用缩放
+        if detect_model.lock().unwrap().native_preprocess() {
+            return self.native_detect(frame, detect_model, inf_size, trace);
+        }
+
+        // 2. Resize: 动态分辨率 → 640x640 (算法见 utils::resize)
+        let t2 = Instant::now();
+
+        let src_w = frame.width as usize;
+        let src_h = frame.height as usize;
+        let dst_size = inf_size as usize;
+
+        // 最近邻时优先走 YUV → NCHW 融合路径 (10-bit 源采样 16 位平面),
+        // 无YUV平面或选择了插值算法时走 RGBA → RGB → DynamicImage
+        let size = (src_w, src_h);
+        let nearest = self.resizer.quality() == ResizeQuality::Nearest;
+        let hdr = frame
+            .yuv16
+            .as_deref()
+            .filter(|f| nearest && (f.width, f.height) == size);
+        let yuv = frame
+            .yuv
+            .as_deref()
+            .filter(|f| nearest && (f.width, f.height) == size);
+        let fused_input = if let Some(hdr) = hdr {
+            self.fused_yuv_input(size, dst_size, |xs, ys, spec, out| {
+                yuv420_16_to_nchw(hdr, xs, ys, spec, out)
+            })
+        } else if let Some(yuv) = yuv {
+            self.fused_yuv_input(size, dst_size, |xs, ys, spec, out| {
+                yuv420_to_nchw(yuv, xs, ys, spec, out)
+            })
+        } else {
+            None
+        };
+
+        let rgb_images: Vec<DynamicImage>;
+        let images: &[DynamicImage] = if fused_input.is_some() {
+            &self.size_probe
+        } else {
+            // fast_image_resize SIMD 缩放 (避免GPU数据传输开销)
+            let rgb_data = match self.resizer.rgba_to_rgb(
+                &frame.rgba_data,
+                frame.width,
+                frame.height,
+                (inf_size, inf_size),
+            ) {
+                Ok(rgb) => rgb,
+                Err(e) => {
+                    eprintln!("❌ 缩放失败: {:#}", e);
+                    return None;
+                }
+            };
+
+            // 3. RGB → DynamicImage (零拷贝)
+            let rgb_img = match RgbImage::from_raw(inf_size, inf_size, rgb_data) {
+                Some(img) => img,
+                None => {
+                    eprintln!("❌ RGB图像转换失败");
+                    return None;
+                }
+            };
+            rgb_images = vec![DynamicImage::ImageRgb8(rgb_img)]; // 只创建一次Vec,避免重复clone
+            &rgb_images
+        };
+
+        let resize_ms = t2.elapsed().as_secs_f64() * 1000.0;
+
+        // 5. YOLO检测 (统一处理所有模型类型)
+        let t5_preprocess = Instant::now();
+
+        // 方式1: 细粒度控制 - 分步调用以便计时
+        // 方式2: 简化版 - model.forward(&images) (内部自动调用三步)
+        let mut model = detect_model.lock().unwrap();
+        let xs = match fused_input {
+            Some(x) => Ok(vec![x]),
+            None => model.preprocess(images),
+        };
+        let preprocess_time = t5_preprocess.elapsed().as_secs_f64() * 1000.0;
+
+        let t5_inference = Instant::now();
+        trace.infer_start = Some(t5_inference);
+        let ys = xs.and_then(|xs| model.run(xs, false));
+        let inference_time = t5_inference.elapsed().as_secs_f64() * 1000.0;
+        trace.infer_end = Some(Instant::now());
+
+        let t5_postprocess = Instant::now();
+        let detect_results = ys.and_then(|ys| model.postprocess(ys, images));
+        let postprocess_time = t5_postprocess.elapsed().as_secs_f64() * 1000.0;
+        drop(model);
+
+        // 出错的帧不发布结果, 错误经 xbus 报告给界面
+        let detect_results = match detect_results {
+            Ok(results) => {
+                self.report(None);
+                results
+            }
+            Err(e) => {
+                self.report(Some(DetectorError::from_anyhow(&e, DetectorError::Process)));
+                return None;
+            }
+        };
+
+        let (_preprocess_ms, inference_ms, _postprocess_ms) =
+            (preprocess_time, inference_time, postprocess_time);
+
+        Some((detect_results, resize_ms, inference_ms))
+    }
+
+    /// 自带预处理的模型: 整帧原图交给 preprocess (官方缩放/letterbox 与归一化)
+    ///
+    /// 模型输出原图坐标, 换算到推理输入坐标后与通用路径一致
+    fn native_detect(
+        &mut self,
+        frame: &DecodedFrame,
+        detect_model: &Arc<Mutex<Box<dyn Model + Send>>>,
+        inf_size: u32,
+        trace: &mut FrameTrace,
+    ) -> Option<(Vec<crate::DetectionResult>, f64, f64)> {
+        let t = Instant::now();
+        let Some(rgba) = ImageBuffer::<Rgba<u8>, _>::from_raw(
+            frame.width,
+            frame.height,
+            frame.rgba_data.to_vec(),
+        ) else {
+            eprintln!("❌ RGBA图像转换失败");
+            return None;
+        };
+        let images = [DynamicImage::ImageRgba8(rgba)];
+
+        let mut model = detect_model.lock().unwrap();
+        let xs = model.preprocess(&images);
+        let preprocess_ms = t.elapsed().as_secs_f64() * 1000.0;
+
+        let t_inference = Instant::now();
+        trace.infer_start = Some(t_inference);
+        let ys = xs.and_then(|xs| model.run(xs, false));
+        let inference_ms = t_inference.elapsed().as_secs_f64() * 1000.0;
+        trace.infer_end = Some(Instant::now());
+
+        let detect_results = ys.and_then(|ys| model.postprocess(ys, &images));
+        drop(model);
+
+        let mut detect_results = match detect_results {
+            Ok(results) => {
+                self.report(None);
+                results
+            }
+            Err(e) => {
+                self.report(Some(DetectorError::from_anyhow(&e, DetectorError::Process)));
+                return None;
+            }
+        };
+        let sx = inf_size as f32 / frame.width.max(1) as f32;
+        let sy = inf_size as f32 / frame.height.max(1) as f32;
+        for result in &mut detect_results {
+            scale_result(result, sx, sy);
+        }
+        Some((detect_results, preprocess_ms, inference_ms))
+    }
+
+    /// 分块局部推理: 静止时复用缓存, 局部运动时只推理运动区域并与缓存合并
+    ///
+    /// 未启用时等同 [`Self::host_detect`]; 返回的结果与整帧推理一样在推理输入坐标下
+    fn tiled_detect(
+        &mut self,
+        frame: &DecodedFrame,
+        detect_model: &Arc<Mutex<Box<dyn Model + Send>>>,
+        inf_size: u32,
+        trace: &mut FrameTrace,
+    ) -> Option<(Vec<crate::DetectionResult>, f64, f64)> {
+        let Some(mut cache) = self.stream.tile_cache.take() else {
+            return self.host_detect(frame, detect_model, inf_size, trace);
+        };
+        let result = self.tiled_detect_with(&mut cache, frame, detect_model, inf_size, trace);
+        self.stream.tile_cache = Some(cache);
+        result
+    }
+
+    /// [`Self::tiled_detect`] 的实现 (缓存已从 self 取出, 推理期间可借用 self)
+    fn tiled_detect_with(
+        &mut self,
+        cache: &mut TileCache,
+        frame: &DecodedFrame,
+        detect_model: &Arc<Mutex<Box<dyn Model + Send>>>,
+        inf_size: u32,
+        trace: &mut FrameTrace,
+    ) -> Option<(Vec<crate::DetectionResult>, f64, f64)> {
+        let (width, height) = (frame.width, frame.height);
+        if frame.rgba_data.len() < (width * height * 4) as usize {
+            return self.host_detect(frame, detect_model, inf_size, trace);
+        }
+        // 推理输入坐标 → 画面坐标
+        let scale = (
+            width as f32 / inf_size as f32,
+            height as f32 / inf_size as f32,
+        );
+        let now = Instant::now();
+        match cache.plan(&frame.rgba_data, width, height, now) {
+            TilePlan::Full => {
+                let (results, resize_ms, inference_ms) =
+                    self.host_detect(frame, detect_model, inf_size, trace)?;
+                cache.store_full(
+                    tile_cache::from_model_results(&results, scale, (0.0, 0.0)),
+                    now,
+                );
+                Some((results, resize_ms, inference_ms))
+            }
+            TilePlan::Reuse => Some((
+                vec![tile_cache::to_model_result(cache.cached(), scale)],
+                0.0,
+                0.0,
+            )),
+            TilePlan::Regions(regions) => {
+                let (mut resize_ms, mut inference_ms) = (0.0, 0.0);
+                let mut fresh = Vec::new();
+                for rect in &regions {
+                    let crop = DecodedFrame {
+                        rgba_data: Arc::new(tile_cache::crop_rgba(&frame.rgba_data, width, rect)),
+                        width: rect.width,
+                        height: rect.height,
+                        yuv: None,
+                        yuv16: None,
+                        #[cfg(feature = "cuda")]
+                        device: None,
+                        ..frame.clone()
+                    };
+                    let (results, resize, inference) =
+                        self.host_detect(&crop, detect_model, inf_size, trace)?;
+                    resize_ms += resize;
+                    inference_ms += inference;
+                    let crop_scale = (
+                        rect.width as f32 / inf_size as f32,
+                        rect.height as f32 / inf_size as f32,
+                    );
+                    fresh.extend(tile_cache::from_model_results(
+                        &results,
+                        crop_scale,
+                        (rect.x as f32, rect.y as f32),
+                    ));
+                }
+                let boxes = cache.stitch(&regions, fresh);
+                Some((
+                    vec![tile_cache::to_model_result(&boxes, scale)],
+                    resize_ms,
+                    inference_ms,
+                ))
+            }
+        }
+    }
+
+    /// CUDA 路径: 设备帧 → GPU 预处理 → TensorRT → GPU NMS, 只回传最终框
+    ///
+    /// 仅支持 YOLOv8 系列检测头; 无设备帧、模型不支持或管线出错时返回 None 回退到 CPU 路径
+    #[cfg(feature = "cuda")]
+    fn device_detect(
+        &mut self,
+        frame: &DecodedFrame,
+        detect_model: &Arc<Mutex<Box<dyn Model + Send>>>,
+        trace: &mut FrameTrace,
+    ) -> Option<(Vec<crate::DetectionResult>, f64, f64)> {
+        let device = frame.device.as_deref()?;
+        if self.cuda_failed {
+            return None;
+        }
+        if self.cuda_pipeline.is_none() {
+            let supported = matches!(
+                ModelType::from_path(&self.detect_model_path),
+                ModelType::YOLOv8 | ModelType::YOLOv5 | ModelType::YOLOv11
+            );
+            if !supported {
+                println!("⚠️ 当前模型不支持 CUDA 端到端管线, 使用CPU路径");
+                self.cuda_failed = true;
+                return None;
+            }
+            match CudaPipeline::new(
+                &self.detect_model_path,
+                0,
+                self.inf_size,
+                &self.preprocess_spec,
+            ) {
+                Ok(pipeline) => self.cuda_pipeline = Some(pipeline),
+                Err(e) => {
+                    eprintln!("❌ CUDA 管线初始化失败, 回退到CPU路径: {}", e);
+                    self.cuda_failed = true;
+                    return None;
+                }
+            }
+        }
+
+        // 管线在原始分数空间过滤, 输出置信度再按模型校准
+        let (calibration, conf, iou) = {
+            let model = detect_model.lock().unwrap();
+            let calibration = model.calibration();
+            (calibration, calibration.invert(model.conf()), model.iou())
+        };
+        let t = Instant::now();
+        let pipeline = self.cuda_pipeline.as_mut()?;
+        trace.infer_start = Some(t);
+        match pipeline.detect(device, conf, iou, self.inf_size, self.inf_size) {
+            Ok(mut results) => {
+                calibration.apply_results(&mut results);
+                trace.infer_end = Some(Instant::now());
+                Some((results, 0.0, t.elapsed().as_secs_f64() * 1000.0))
+            }
+            Err(e) => {
+                eprintln!("❌ CUDA 检测失败: {}", e);
+                None
+            }
+        }
+    }
+
+    /// 更新推理输入尺寸 (占位图与缩放映射表按新尺寸重建)
+    fn set_inf_size(&mut self, size: u32) {
+        self.inf_size = size;
+        self.size_probe = vec![DynamicImage::new_luma8(size, size)];
+        // 下一帧按新尺寸重建缩放映射表
+        for stream in self.streams_mut() {
+            stream.src_width = 0;
+            stream.src_height = 0;
+        }
+    }
+
+    /// 动态分辨率阶梯: 统计窗口内目标偏小/偏大时就地切换模型输入尺寸, 下一帧生效
+    ///
+    /// 模型不支持就地切换 (静态轴/TensorRT/CUDA 端到端管线) 时关闭阶梯
+    fn update_resolution(
+        &mut self,
+        bboxes: &[types::BBox],
+        frame: &DecodedFrame,
+        detect_model: &Arc<Mutex<Box<dyn Model + Send>>>,
+    ) {
+        let frame_area = (frame.width * frame.height) as f32;
+        if !self.ladder.config().enabled || frame_area <= 0.0 {
+            return;
+        }
+        let areas: Vec<f32> = bboxes
+            .iter()
+            .map(|b| (b.x2 - b.x1) * (b.y2 - b.y1) / frame_area)
+            .collect();
+        let Some(step) = self.ladder.observe(&areas, self.inf_size, Instant::now()) else {
+            return;
+        };
+        #[cfg(feature = "cuda")]
+        let device_path = self.cuda_pipeline.is_some();
+        #[cfg(not(feature = "cuda"))]
+        let device_path = false;
+        let size = step.size;
+        if !device_path && detect_model.lock().unwrap().set_input_size(size, size) {
+            println!(
+                "📐 动态分辨率: {} → {} (框面积中位数 {:.2}%)",
+                self.inf_size,
+                size,
+                step.median_area * 100.0
+            );
+            self.set_inf_size(size);
+        } else {
+            eprintln!("⚠️ 当前模型输入尺寸固定 (静态轴/TensorRT), 动态分辨率已关闭");
+            self.ladder.disable();
+        }
+    }
+
+    /// 处理单帧检测 (在工作线程中执行)
+    fn process_frame(
+        &mut self,
+        frame: DecodedFrame,
+        detect_model: &Arc<Mutex<Box<dyn Model + Send>>>,
+        inf_size: u32,
+    ) {
+        let start_total = Instant::now();
+        let mut trace = frame.trace;
+        trace.resize_ts = Some(start_total);
+
+        // 2~5. 设备帧走 CUDA 端到端管线, 否则 CPU 缩放 + ORT 推理 + 后处理 (启用时按运动分块推理)
+        #[cfg(feature = "cuda")]
+        let device_results = self.device_detect(&frame, detect_model, &mut trace);
+        #[cfg(not(feature = "cuda"))]
+        let device_results = None;
+
+        let (detect_results, resize_ms, inference_ms) = match device_results {
+            Some(results) => results,
+            None => match self.tiled_detect(&frame, detect_model, inf_size, &mut trace) {
+                Some(results) => results,
+                None => return,
+            },
+        };
+        // 所有分块都复用缓存时没有推理, 不计入 GPU 延迟
+        if trace.infer_end.is_some() {
+            if let Some(lease) = self.gpu.lock().unwrap().as_ref() {
+                lease.report(inference_ms);
+            }
+        }
+
+        // 6. 提取检测框并缩放到原始分辨率 (推理输入为拉伸缩放)
+        let to_image = ModelToImage::stretch((inf_size, inf_size), (frame.width, frame.height));
+        let (scale_x, scale_y) = to_image.scale();
+
+        let mut bboxes = Vec::new();
+        // 分割掩码 (ByteTrack 启用掩码 IOU 关联时收集, 与 bboxes 一一对应)
+        let collect_masks = matches!(
+            &self.stream.tracker,
+            TrackerType::ByteTrack(t) if t.association().uses_masks()
+        );
+        let mut masks: Vec<Option<InstanceMask>> = Vec::new();
+        // 阈值预览: 只因虚影下限才通过模型阈值的检测, 不进入跟踪与分析
+        let active_conf = self
+            .stream
+            .smoother
+            .config()
+            .inference_conf(self.show_conf());
+        let mut below_active = Vec::new();
+        let mut all_detections_count = 0; // 调试: 统计所有类别的检测数
+        let mut person_detections_count = 0; // 调试: 统计人的检测数
+
+        // 按类别的尺寸/宽高比过滤 (box_filters.json 启用时)
+        let box_filter = box_filter::config();
+        let mut implausible_count = 0;
+        // 贴边策略 (border_policy.json 启用时)
+        let edge_policy = border::config().map(|c| {
+            (
+                c.policy(self.stream.view),
+                c.edges(frame.width, frame.height),
+            )
+        });
+        let mut border_dropped = 0;
+
+        for result in &detect_results {
+            if let Some(boxes) = result.bboxes() {
+                all_detections_count += boxes.len();
+                for (i, bbox) in boxes.iter().enumerate() {
+                    // 检测指定类别 (默认只检测人, 由控制面板/配置档案修改);
+                    // 开放词汇模型的类别ID是词表下标, 词表本身即类别选择
+                    if self.vocabulary.is_some()
+                        || self.classes.is_empty()
+                        || self.classes.contains(&(bbox.id() as u32))
+                    {
+                        if bbox.id() == 0 {
+                            person_detections_count += 1;
+                        }
+                        if bbox.confidence() >= 0.01 {
+                            let rect =
+                                ModelRect::new(bbox.xmin(), bbox.ymin(), bbox.xmax(), bbox.ymax());
+                            let b = types::BBox::from_rect(
+                                to_image.rect(rect),
+                                bbox.confidence(),
+                                bbox.id() as u32,
+                            );
+                            if box_filter.is_some_and(|f| {
+                                !f.allows(self.stream.view, &b, frame.width, frame.height)
+                            }) {
+                                implausible_count += 1;
+                                continue;
+                            }
+                            if edge_policy.is_some_and(|(policy, edges)| {
+                                policy == BorderPolicy::Drop && edges.touches(&b)
+                            }) {
+                                border_dropped += 1;
+                                continue;
+                            }
+                            if self.ghost_floor.is_some() && b.confidence < active_conf {
+                                below_active.push(b);
+                                continue;
+                            }
+                            bboxes.push(b);
+                            if collect_masks {
+                                masks.push(result.masks().and_then(|m| m.get(i)).and_then(|m| {
+                                    InstanceMask::from_luma(
+                                        m,
+                                        inf_size as usize,
+                                        inf_size as usize,
+                                        MASK_STEP,
+                                        scale_x,
+                                        scale_y,
+                                    )
+                                }));
+                            }
+                        } else if self.stream.count.is_multiple_of(30) && bbox.id() == 0 {
+                            eprintln!("⚠️ 极低置信度人检测被过滤: conf={:.3}", bbox.confidence());
+                        }
+                    }
+                }
+            }
+        }
+
+        // 调试日志 - 统计各类别分布
+        if self.stream.count.is_multiple_of(30) && all_detections_count > 0 {
+            use std::collections::HashMap;
+            let mut class_counts: HashMap<usize, usize> = HashMap::new();
+            for result in &detect_results {
+                if let Some(boxes) = result.bboxes() {
+                    for bbox in boxes {
+                        *class_counts.entry(bbox.id()).or_insert(0) += 1;
+                    }
+                }
+            }
+            let mut sorted: Vec<_> = class_counts.iter().collect();
+            sorted.sort_by(|a, b| b.1.cmp(a.1));
+            let top3: Vec<String> = sorted
+                .iter()
+                .take(3)
+                .map(|(k, v)| format!("c{}:{}", k, v))
+                .collect();
+            eprintln!(
+                "🔍 原始检测: 总{}个 (top3: {}) | 人{}个 | 尺寸过滤{}个 | 贴边丢弃{}个 | 通过阈值{}个",
+                all_detections_count,
+                top3.join(" "),
+                person_detections_count,
+                implausible_count,
+                border_dropped,
+                bboxes.len()
+            );
+        }
+
+        // 7. 姿态估计
+        let mut keypoints = Vec::new();
+        if self.pose_enabled {
+            if let Some(pose_model) = self.pose_model.as_mut() {
+                // 两阶段: 在原始分辨率的人框裁剪上运行独立姿态模型, 小目标关键点更准确
+                match pose_model.estimate(&frame.rgba_data, frame.width, frame.height, &bboxes) {
+                    Ok(kpts) => keypoints = kpts,
+                    Err(e) => {
+                        if self.stream.count.is_multiple_of(30) {
+                            eprintln!("❌ 两阶段姿态估计失败: {}", e);
+                        }
+                    }
+                }
+            } else {
+                for result in &detect_results {
+                    if let Some(kpts) = result.keypoints() {
+                        for kpt in kpts {
+                            // 转换关键点数据: Vec<Point2> -> Vec<(f32, f32, f32)>, 与检测框一样缩放到原始分辨率
+                            let points: Vec<(f32, f32, f32)> = kpt
+                                .iter()
+                                .map(|p| {
+                                    let q = to_image.point(ModelPoint::new(p.x(), p.y()));
+                                    (q.x, q.y, p.confidence())
+                                })
+                                .collect();
+                            keypoints.push(types::PoseKeypoints { points });
+                        }
+                    }
+                }
+            }
+        }
+
+        // 8. 跟踪器更新 (贴边策略为 partial 时, 贴边检测以放大的观测噪声融合)
+        let partial_edges = edge_policy
+            .and_then(|(policy, edges)| (policy == BorderPolicy::Partial).then_some(edges));
+        let tracker_start = Instant::now();
+        let (tracked_bboxes, reid_features) = match &mut self.stream.tracker {
+            TrackerType::DeepSort(tracker) => {
+                // 传入原始图像数据以启用ReID特征提取
+                // 注意: 这里需要传入原始图像数据,我们直接使用Arc切片
+                let frame_data = Some((frame.rgba_data.as_slice(), frame.width, frame.height));
+                tracker.set_partial_observation(partial_edges);
+                let tracked = tracker.update(&bboxes, &keypoints, frame_data);
+
+                // 将跟踪结果转换为BBox格式(保持原有结构)
+                let bboxes: Vec<types::BBox> = tracked
+                    .iter()
+                    .map(|t| types::BBox {
+                        x1: t.bbox.x1,
+                        y1: t.bbox.y1,
+                        x2: t.bbox.x2,
+                        y2: t.bbox.y2,
+                        confidence: t.bbox.confidence,
+                        class_id: t.id, // 使用跟踪ID替换class_id
+                    })
+                    .collect();
+
+                // 获取ReID特征
+                let reid_feats = tracker.get_reid_features();
+                (bboxes, reid_feats)
+            }
+            TrackerType::ByteTrack(tracker) => {
+                // 掩码/ReID 线索仅在对应权重大于0时提供, 默认退化为纯 IOU
+                let frame_data = Some((frame.rgba_data.as_slice(), frame.width, frame.height));
+                let features = tracker.extract_features(&bboxes, frame_data);
+                tracker.set_partial_observation(partial_edges);
+                let tracked = tracker.update_with_cues(&bboxes, &masks, &features);
+                // 未达到最小命中次数的新轨迹暂不输出
+                let bboxes = tracked
+                    .iter()
+                    .filter(|t| t.confirmed)
+                    .map(|t| types::BBox {
+                        x1: t.bbox.x1,
+                        y1: t.bbox.y1,
+                        x2: t.bbox.x2,
+                        y2: t.bbox.y2,
+                        confidence: t.bbox.confidence,
+                        class_id: t.id,
+                    })
+                    .collect();
+                // ReID 权重为0时不提取特征
+                let reid_feats = tracked
+                    .iter()
+                    .filter(|t| t.confirmed)
+                    .map(|t| t.features().map(<[f32]>::to_vec).unwrap_or_default())
+                    .collect();
+                (bboxes, reid_feats)
+            }
+            TrackerType::None => (bboxes.clone(), Vec::new()), // 不使用跟踪器,直接返回检测结果
+        };
+        let tracker_ms = tracker_start.elapsed().as_secs_f64() * 1000.0;
+        let track_stats = match &self.stream.tracker {
+            TrackerType::DeepSort(tracker) => tracker.stats(),
+            TrackerType::ByteTrack(tracker) => tracker.stats(),
+            TrackerType::None => TrackStats::default(),
+        };
+
+        // 更新跟踪器统计
+        if !matches!(self.stream.tracker, TrackerType::None) {
+            self.stream.tracker_count += 1;
+            let now_tracker = Instant::now();
+            let elapsed = now_tracker.duration_since(self.stream.tracker_last);
+            if elapsed.as_secs() >= 1 {
+                self.stream.tracker_current_fps =
+                    self.stream.tracker_count as f64 / elapsed.as_secs_f64();
+                self.stream.tracker_count = 0;
+                self.stream.tracker_last = now_tracker;
+            }
+            self.stream.save_journal();
+        }
+
+        // 使用跟踪后的结果替换原始检测框 (保留检测框, 按 IoU 找回检测类别供二级分类与轨迹详情)
+        let detections = bboxes;
+        let mut bboxes = tracked_bboxes;
+        let mut reid_features = reid_features;
+        let tracked = !matches!(self.stream.tracker, TrackerType::None);
+        let mut classes = if tracked {
+            source_classes(&bboxes, &detections, tracked)
+        } else {
+            Vec::new()
+        };
+
+        // 显示平滑: 新轨迹确认 N 帧后显示, 已显示的轨迹按较低的保持阈值保留
+        let visible = self
+            .stream
+            .smoother
+            .update(&bboxes, &classes, tracked, self.show_conf());
+        retain_visible(&mut bboxes, &visible);
+        retain_visible(&mut reid_features, &visible);
+        retain_visible(&mut classes, &visible);
+        if !tracked {
+            // 未跟踪时关键点与检测框一一对应
+            retain_visible(&mut keypoints, &visible);
+        }
+
+        // 阈值预览: 低于显示阈值且未显示的检测作为虚影
+        let ghosts = match self.ghost_floor {
+            Some(floor) => {
+                below_active.extend(detections);
+                ghost_boxes(&below_active, &bboxes, floor, self.show_conf())
+            }
+            None => Vec::new(),
+        };
+
+        // 动态分辨率: 按目标大小升降推理输入尺寸
+        self.update_resolution(&bboxes, &frame, detect_model);
+
+        // 跨摄像头全局ID: 按 ReID 特征接力 (class_id 已替换为本地轨迹ID)
+        let global_ids = match &self.global_ids {
+            Some((manager, camera_id)) if !matches!(self.stream.tracker, TrackerType::None) => {
+                let tracks: Vec<(u32, Option<&[f32]>)> = bboxes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, b)| (b.class_id, reid_features.get(i).map(Vec::as_slice)))
+                    .collect();
+                manager
+                    .lock()
+                    .unwrap()
+                    .assign(*camera_id, &tracks, Instant::now())
+            }
+            _ => Vec::new(),
+        };
+
+        // 地面映射: 检测框脚点经单应矩阵投影到平面图 (米), 标定只属于主逻辑流
+        let ground = self
+            .ground
+            .as_ref()
+            .filter(|_| self.stream.view == self.view);
+        let ground_points = match ground {
+            Some(h) => bboxes.iter().map(|b| h.project_box(b)).collect(),
+            None => Vec::new(),
+        };
+
+        // 重叠视野去重: 与编号更小的逻辑流投影到同一地面位置的检测不再计数
+        let duplicates = overlap::mark_duplicates(self.stream.view, &bboxes);
+
+        // 速度估计: 按轨迹ID跟踪地面坐标, 时间取解码时刻
+        let speeds = if ground_points.is_empty() || matches!(self.stream.tracker, TrackerType::None)
+        {
+            Vec::new()
+        } else {
+            let objects: Vec<(u32, Option<(f32, f32)>)> = bboxes
+                .iter()
+                .zip(&ground_points)
+                .map(|(b, p)| (b.class_id, *p))
+                .collect();
+            self.stream.speed.update(&objects, frame.trace.decode_ts)
+        };
+
+        let density = self.update_crowd_density(&frame);
+
+        // 深度融合: 每个框取中心区域深度中位数作为近似距离
+        let distances = match self.depth_model.as_mut() {
+            Some(depth_model) => {
+                let scale = depth_model.depth_scale();
+                match depth_model.update(&frame.rgba_data, frame.width, frame.height) {
+                    Some(map) => bboxes
+                        .iter()
+                        .map(|b| map.distance_for_box(b, frame.width, frame.height, scale))
+                        .collect(),
+                    None => Vec::new(),
+                }
+            }
+            None => Vec::new(),
+        };
+
+        // 二级分类 (行人属性/车辆属性): 按轨迹ID调度, 无跟踪器时无法跨帧平滑, 不运行
+        let attributes = match self.attribute_model.as_mut() {
+            Some(model) if tracked => model.update(
+                &frame.rgba_data,
+                frame.width,
+                frame.height,
+                &bboxes,
+                &classes,
+                Instant::now(),
+            ),
+            _ => Vec::new(),
+        };
+        let vehicles = match self.vehicle_model.as_mut() {
+            Some(model) if tracked => model.update(
+                &frame.rgba_data,
+                frame.width,
+                frame.height,
+                &bboxes,
+                &classes,
+                Instant::now(),
+            ),
+            _ => Vec::new(),
+        };
+
+        // 文本提示检索: 区域嵌入与文本嵌入余弦相似度, 命中的框高亮
+        const CLIP_MATCH_THRESHOLD: f32 = 0.22;
+        const CLIP_MAX_REGIONS: usize = 16;
+        let mut prompt_matches = Vec::new();
+        if let (Some(clip), Some(text)) = (self.clip_model.as_mut(), self.text_embedding.as_ref()) {
+            let regions = &bboxes[..bboxes.len().min(CLIP_MAX_REGIONS)];
+            match clip.encode_regions(&frame.rgba_data, frame.width, frame.height, regions) {
+                Ok(embeddings) if embeddings.len() == regions.len() => {
+                    prompt_matches = vec![None; bboxes.len()];
+                    for (idx, sim) in
+                        match_regions(&embeddings, text, CLIP_MATCH_THRESHOLD, regions.len())
+                    {
+                        prompt_matches[idx] = Some(sim);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("❌ CLIP区域编码失败: {}", e),
+            }
+        }
+
+        self.stream.count += 1;
+        let now = Instant::now();
+        if now.duration_since(self.stream.last).as_secs() >= 1 {
+            self.stream.current_fps =
+                self.stream.count as f64 / now.duration_since(self.stream.last).as_secs_f64();
+            self.stream.count = 0;
+            self.stream.last = now;
+        }
+
+        // 计算总耗时 (移除未使用的tracker_ms变量)
+        let total_ms = start_total.elapsed().as_secs_f64() * 1000.0;
+
+        // 性能监控日志 (每60帧打印一次简洁信息)
+        if self.stream.count.is_multiple_of(60) {
+            if matches!(self.stream.tracker, TrackerType::None) {
+                eprintln!(
+                    "🎯 检测: {}人 | {:.1}ms/帧 | {:.1}fps (Resize:{:.1}ms | 推理:{:.1}ms)",
+                    bboxes.len(),
+                    total_ms,
+                    self.stream.current_fps,
+                    resize_ms,
+                    inference_ms
+                );
+            } else {
+                eprintln!(
+                    "🎯 检测+跟踪: {}人 | {:.1}ms/帧 | {:.1}fps (Resize:{:.1}ms | 推理:{:.1}ms | 跟踪:{:.1}ms)",
+                    bboxes.len(),
+                    total_ms,
+                    self.stream.current_fps,
+                    resize_ms,
+                    inference_ms,
+                    tracker_ms
+                );
+            }
+        }
+
+        // 10. 发送检测结果到XBus
+        let partial = partial_edges
+            .map(|edges| bboxes.iter().map(|b| edges.touches(b)).collect())
+            .unwrap_or_default();
+        trace.publish_ts = Some(Instant::now());
+        // 移除 resized_image 以节省内存 (每帧 640x640x4 = 1.6MB)
+        xbus::post(DetectionResult {
+            bboxes,
+            keypoints,
+            inference_fps: self.stream.current_fps,
+            inference_ms,
+            tracker_fps: self.stream.tracker_current_fps,
+            tracker_ms,
+            resized_image: None, // 不再传输预览图像,节省内存
+            resized_size: inf_size,
+            reid_features,
+            distances,
+            prompt_matches,
+            attributes,
+            vehicles,
+            track_stats,
+            tracked,
+            global_ids,
+            jetson: self.jetson.as_ref().and_then(JetsonMonitor::status),
+            trace,
+            view: self.stream.view,
+            ground_points,
+            speeds,
+            density,
+            ghosts,
+            classes,
+            vocabulary: self.vocabulary.clone(),
+            partial,
+            duplicates,
+        });
+    }
+}
+
+/// 按比例缩放检测结果的框与关键点 (原图坐标 → 推理输入坐标)
+fn scale_result(result: &mut crate::DetectionResult, sx: f32, sy: f32) {
+    if let Some(bboxes) = result.bboxes.as_mut() {
+        for b in bboxes.iter_mut() {
+            *b = Bbox::new(
+                b.xmin() * sx,
+                b.ymin() * sy,
+                b.width() * sx,
+                b.height() * sy,
+                b.id(),
+                b.confidence(),
+            );
+        }
+    }
+    if let Some(keypoints) = result.keypoints.as_mut() {
+        for p in keypoints.iter_mut().flatten() {
+            *p = Point2::new_with_conf(p.x() * sx, p.y() * sy, p.confidence());
+        }
+    }
+}
+
+/// 提取 panic 负载中的消息 (`panic!` 的字符串参数), 其他类型返回占位说明
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "未知 panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `panic!` 字面量与格式化消息都能取出, 其他负载类型给出占位说明
+    #[test]
+    fn test_panic_message() {
+        let payload = panic::catch_unwind(|| panic!("形状错误")).unwrap_err();
+        assert_eq!(panic_message(&*payload), "形状错误");
+
+        let dims = [1, 84, 8400];
+        let payload = panic::catch_unwind(|| panic!("输出形状 {:?}", dims)).unwrap_err();
+        assert_eq!(panic_message(&*payload), "输出形状 [1, 84, 8400]");
+
+        let payload = panic::catch_unwind(|| panic::panic_any(42u32)).unwrap_err();
+        assert_eq!(panic_message(&*payload), "未知 panic");
+    }
+
+    /// 自带预处理模型的原图坐标按比例换算到推理输入坐标, 关键点随框缩放
+    #[test]
+    fn test_scale_result() {
+        let mut result = crate::DetectionResult::new(
+            None,
+            Some(vec![Bbox::new(100.0, 50.0, 200.0, 100.0, 3, 0.8)]),
+            Some(vec![vec![Point2::new_with_conf(150.0, 75.0, 0.9)]]),
+            None,
+        );
+        scale_result(&mut result, 0.5, 2.0);
+        let b = &result.bboxes().unwrap()[0];
+        assert_eq!(
+            (b.xmin(), b.ymin(), b.width(), b.height()),
+            (50.0, 100.0, 100.0, 200.0)
+        );
+        assert_eq!((b.id(), b.confidence()), (3, 0.8));
+        let p = &result.keypoints().unwrap()[0][0];
+        assert_eq!((p.x(), p.y(), p.confidence()), (75.0, 150.0, 0.9));
+    }
+
+    /// 首帧只记录代数; 代数变化时清空按旧画面尺寸缓存的映射表与帧率统计
+    #[test]
+    fn test_switch_generation_resets_caches() {
+        let mut detector = Detector::new("yolov8n.onnx".into(), 640, "bytetrack".into(), false);
+        detector.switch_generation(3);
+        assert_eq!(detector.stream.generation, Some(3));
+
+        detector.stream.resize_x_map = vec![0; 640];
+        detector.stream.resize_y_map = vec![0; 640];
+        (detector.stream.src_width, detector.stream.src_height) = (1920, 1080);
+        (detector.stream.count, detector.stream.current_fps) = (12, 25.0);
+        detector.switch_generation(4);
+        assert_eq!(detector.stream.generation, Some(4));
+        assert!(detector.stream.resize_x_map.is_empty() && detector.stream.resize_y_map.is_empty());
+        assert_eq!(
+            (detector.stream.src_width, detector.stream.src_height),
+            (0, 0)
+        );
+        assert_eq!(
+            (detector.stream.count, detector.stream.current_fps),
+            (0, 0.0)
+        );
+        assert!(matches!(detector.stream.tracker, TrackerType::ByteTrack(_)));
+    }
+
+    /// 同一检测线程的多路流: 首帧到达时创建各自的状态, 统计与缓存切换后互不影响
+    #[test]
+    fn test_streams_are_isolated() {
+        let mut detector = Detector::new("yolov8n.onnx".into(), 640, "bytetrack".into(), false);
+        detector.set_view(2);
+        detector.add_view(5);
+        detector.add_view(2);
+        assert_eq!(detector.views(), vec![2, 5]);
+        assert!(detector.streams.is_empty());
+
+        (detector.stream.count, detector.stream.src_width) = (7, 1920);
+        detector.select_stream(5);
+        assert_eq!(detector.stream.view, 5);
+        assert_eq!((detector.stream.count, detector.stream.src_width), (0, 0));
+        assert_eq!(detector.stream.generation, None);
+        assert!(matches!(detector.stream.tracker, TrackerType::ByteTrack(_)));
+
+        detector.stream.count = 3;
+        detector.select_stream(2);
+        assert_eq!(
+            (detector.stream.count, detector.stream.src_width),
+            (7, 1920)
+        );
+        detector.select_stream(5);
+        assert_eq!(detector.stream.count, 3);
+        assert_eq!(
+            detector.streams.keys().copied().collect::<Vec<_>>(),
+            vec![2]
+        );
+    }
+
+    /// 重启与重置跟踪作用于全部逻辑流, 不只是当前流
+    #[test]
+    fn test_reset_tracking_covers_all_streams() {
+        fn track_count(stream: &StreamContext) -> usize {
+            match &stream.tracker {
+                TrackerType::ByteTrack(tracker) => tracker.snapshot().tracks.len(),
+                _ => unreachable!(),
+            }
+        }
+
+        let mut detector = Detector::new("yolov8n.onnx".into(), 640, "bytetrack".into(), false);
+        detector.add_view(1);
+        detector.select_stream(1);
+        let person = types::BBox {
+            x1: 100.0,
+            y1: 100.0,
+            x2: 200.0,
+            y2: 300.0,
+            confidence: 0.9,
+            class_id: 0,
+        };
+        for stream in detector.streams_mut() {
+            if let TrackerType::ByteTrack(tracker) = &mut stream.tracker {
+                tracker.update(std::slice::from_ref(&person));
+            }
+        }
+        assert!(detector.streams.values().all(|s| track_count(s) > 0));
+        assert!(track_count(&detector.stream) > 0);
+
+        detector.reset_tracking();
+        assert_eq!(track_count(&detector.stream), 0);
+        assert!(detector.streams.values().all(|s| track_count(s) == 0));
+    }
+}
@@ -0,0 +1,256 @@
+//! 跨摄像头全局ID (多路流之间的轨迹接力)
+//!
+//! 各路跟踪器只保证本路内 ID 稳定. `GlobalIdManager` 以 (摄像头, 本地轨迹ID) 为键,
+//! 用 ReID 外观特征 + 时间约束把各路轨迹归并为同一人的全局身份:
+//! - 已绑定的轨迹沿用全局ID, 并以滑动平均更新身份特征
+//! - 新轨迹只与当前未被任何活跃轨迹占用的身份比较余弦相似度
+//! - 跨摄像头接力需间隔至少 `min_transit` (同一人不会瞬间出现在另一路画面)
+//! - 超过 `max_gap` 未出现的身份被清理, 之后再出现分配新ID
+//!
+//! 管理器通过 `Arc<Mutex<_>>` 在多个检测线程间共享
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// 全局ID匹配参数
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlobalIdConfig {
+    /// 外观余弦相似度阈值
+    pub similarity_threshold: f32,
+    /// 跨摄像头最短转移时间
+    pub min_transit: Duration,
+    /// 身份最长保留时间 (最后一次出现起)
+    pub max_gap: Duration,
+    /// 身份特征滑动平均系数
+    pub feature_momentum: f32,
+}
+
+impl Default for GlobalIdConfig {
+    fn default() -> Self {
+        Self {
+            similarity_threshold: 0.7,
+            min_transit: Duration::from_secs(2),
+            max_gap: Duration::from_secs(600),
+            feature_momentum: 0.9,
+        }
+    }
+}
+
+/// 全局身份
+struct GlobalIdentity {
+    id: u32,
+    /// L2 归一化的外观特征
+    feature: Vec<f32>,
+    /// 最后出现的摄像头
+    camera: u32,
+    last_seen: Instant,
+}
+
+impl GlobalIdentity {
+    fn observe(&mut self, camera: u32, feature: Option<&[f32]>, now: Instant, momentum: f32) {
+        if let Some(new) = feature {
+            if new.len() == self.feature.len() {
+                let merged: Vec<f32> = self
+                    .feature
+                    .iter()
+                    .zip(new)
+                    .map(|(&old, &new)| momentum * old + (1.0 - momentum) * new)
+                    .collect();
+                if let Some(merged) = normalize(&merged) {
+                    self.feature = merged;
+                }
+            }
+        }
+        self.camera = camera;
+        self.last_seen = now;
+    }
+}
+
+/// L2 归一化, 全零或非有限特征 (未加载 ReID 模型) 返回 None
+fn normalize(feature: &[f32]) -> Option<Vec<f32>> {
+    let norm = feature.iter().map(|v| v * v).sum::<f32>().sqrt();
+    (norm.is_finite() && norm > 1e-6).then(|| feature.iter().map(|v| v / norm).collect())
+}
+
+/// 跨摄像头全局ID管理器
+pub struct GlobalIdManager {
+    config: GlobalIdConfig,
+    identities: Vec<GlobalIdentity>,
+    /// (摄像头, 本地轨迹ID) → 全局ID
+    bindings: HashMap<(u32, u32), u32>,
+    next_id: u32,
+}
+
+impl GlobalIdManager {
+    pub fn new(config: GlobalIdConfig) -> Self {
+        Self {
+            config,
+            identities: Vec::new(),
+            bindings: HashMap::new(),
+            next_id: 1,
+        }
+    }
+
+    pub fn config(&self) -> GlobalIdConfig {
+        self.config
+    }
+
+    /// 当前保留的身份数
+    pub fn identity_count(&self) -> usize {
+        self.identities.len()
+    }
+
+    /// 为一路流本帧的全部轨迹分配全局ID
+    ///
+    /// - `tracks`: (本地轨迹ID, ReID特征), 须包含该路所有存活轨迹,
+    ///   未出现的轨迹视为已结束并释放其身份
+    /// - 返回值与 `tracks` 一一对应; 尚无有效特征的新轨迹为 None
+    pub fn assign(
+        &mut self,
+        camera: u32,
+        tracks: &[(u32, Option<&[f32]>)],
+        now: Instant,
+    ) -> Vec<Option<u32>> {
+        let momentum = self.config.feature_momentum;
+
+        // 1. 释放本路已结束轨迹的绑定, 清理过期身份
+        let live: HashSet<u32> = tracks.iter().map(|&(id, _)| id).collect();
+        self.bindings
+            .retain(|&(cam, track), _| cam != camera || live.contains(&track));
+        let bound: HashSet<u32> = self.bindings.values().copied().collect();
+        let max_gap = self.config.max_gap;
+        self.identities.retain(|identity| {
+            bound.contains(&identity.id) || now.duration_since(identity.last_seen) <= max_gap
+        });
+
+        // 2. 已绑定的轨迹沿用全局ID
+        let mut result = vec![None; tracks.len()];
+        let mut pending = Vec::new();
+        for (i, &(track, feature)) in tracks.iter().enumerate() {
+            let feature = feature.and_then(normalize);
+            if let Some(&gid) = self.bindings.get(&(camera, track)) {
+                if let Some(identity) = self.identities.iter_mut().find(|g| g.id == gid) {
+                    identity.observe(camera, feature.as_deref(), now, momentum);
+                }
+                result[i] = Some(gid);
+            } else if let Some(feature) = feature {
+                pending.push((i, feature));
+            }
+        }
+
+        // 3. 新轨迹与空闲身份按相似度贪心匹配
+        let mut candidates = Vec::new();
+        for (p, (_, feature)) in pending.iter().enumerate() {
+            for (g, identity) in self.identities.iter().enumerate() {
+                if bound.contains(&identity.id) || identity.feature.len() != feature.len() {
+                    continue;
+                }
+                if identity.camera != camera
+                    && now.duration_since(identity.last_seen) < self.config.min_transit
+                {
+                    continue;
+                }
+                let sim: f32 = identity
+                    .feature
+                    .iter()
+                    .zip(feature)
+                    .map(|(a, b)| a * b)
+                    .sum();
+                if sim >= self.config.similarity_threshold {
+                    candidates.push((sim, p, g));
+                }
+            }
+        }
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut used_pending = vec![false; pending.len()];
+        let mut used_identity = vec![false; self.identities.len()];
+        for (_, p, g) in candidates {
+            if used_pending[p] || used_identity[g] {
+                continue;
+            }
+            used_pending[p] = true;
+            used_identity[g] = true;
+            let (i, feature) = &pending[p];
+            let identity = &mut self.identities[g];
+            identity.observe(camera, Some(feature), now, momentum);
+            self.bindings.insert((camera, tracks[*i].0), identity.id);
+            result[*i] = Some(identity.id);
+        }
+
+        // 4. 未匹配的新轨迹创建新身份
+        for (p, (i, feature)) in pending.into_iter().enumerate() {
+            if used_pending[p] {
+                continue;
+            }
+            let id = self.next_id;
+            self.next_id += 1;
+            self.identities.push(GlobalIdentity {
+                id,
+                feature,
+                camera,
+                last_seen: now,
+            });
+            self.bindings.insert((camera, tracks[i].0), id);
+            result[i] = Some(id);
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: [f32; 3] = [1.0, 0.0, 0.0];
+    const A2: [f32; 3] = [0.95, 0.2, 0.0];
+    const B: [f32; 3] = [0.0, 1.0, 0.0];
+
+    /// 离开摄像头0的人在摄像头1出现时沿用全局ID, 外观不同的人分配新ID
+    #[test]
+    fn test_handoff_across_cameras() {
+        let mut manager = GlobalIdManager::new(GlobalIdConfig::default());
+        let t0 = Instant::now();
+        assert_eq!(manager.assign(0, &[(1, Some(&A))], t0), vec![Some(1)]);
+        // 仍在画面中: 同一本地轨迹保持ID, 无特征也不丢失
+        assert_eq!(
+            manager.assign(0, &[(1, None)], t0 + Duration::from_secs(1)),
+            vec![Some(1)]
+        );
+        // 离开摄像头0
+        manager.assign(0, &[], t0 + Duration::from_secs(2));
+
+        let ids = manager.assign(
+            1,
+            &[(7, Some(&A2)), (8, Some(&B)), (9, None)],
+            t0 + Duration::from_secs(10),
+        );
+        assert_eq!(ids, vec![Some(1), Some(2), None]);
+        assert_eq!(manager.identity_count(), 2);
+    }
+
+    /// 时间约束: 仍被占用或转移时间过短的身份不参与匹配, 过期身份被清理
+    #[test]
+    fn test_temporal_constraints() {
+        let mut manager = GlobalIdManager::new(GlobalIdConfig::default());
+        let t0 = Instant::now();
+        manager.assign(0, &[(1, Some(&A))], t0);
+
+        // 摄像头0的轨迹仍存活: 摄像头1的相似外观不能复用
+        let ids = manager.assign(1, &[(5, Some(&A))], t0 + Duration::from_secs(10));
+        assert_eq!(ids, vec![Some(2)]);
+
+        // 轨迹1离开后 1 秒即出现在摄像头2: 小于最短转移时间
+        manager.assign(0, &[(1, None)], t0 + Duration::from_secs(20));
+        manager.assign(0, &[], t0 + Duration::from_secs(20));
+        let ids = manager.assign(2, &[(3, Some(&A))], t0 + Duration::from_secs(21));
+        assert_eq!(ids, vec![Some(3)]);
+
+        // 超过最长保留时间的身份被清理
+        manager.assign(1, &[], t0 + Duration::from_secs(30));
+        manager.assign(2, &[], t0 + Duration::from_secs(30));
+        manager.assign(0, &[], t0 + Duration::from_secs(1000));
+        assert_eq!(manager.identity_count(), 0);
+    }
+}
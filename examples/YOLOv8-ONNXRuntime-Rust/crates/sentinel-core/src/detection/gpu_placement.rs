@@ -0,0 +1,384 @@
+//! 多 GPU 分配 (Multi-GPU placement)
+//!
+//! 多卡服务器上把各逻辑流的检测模型放到指定或最空闲的 GPU:
+//! - 固定分配: 按逻辑流 (`streams`) 或模型文件名 (`models`) 指定设备号, 逻辑流优先
+//! - 自动分配: 其余检测线程加载模型时选负载最低的 GPU, 负载为该卡上各检测线程推理耗时 (EMA) 之和,
+//!   还没有测量值的线程按已知耗时的平均值计; 显存占用超过 `max_memory_fraction` 的卡不再分配
+//!   (都超过时仍选负载最低的一张)
+//! - 显存占用由后台 `nvidia-smi -l` 采样
+//!
+//! 分配发生在加载检测模型时 (启动、切换模型、看门狗重启), 已运行的检测线程不会迁移.
+//! 只放置检测模型, 姿态/属性等辅助模型仍在 CPU 上.
+//!
+//! 配置文件 (gpu_placement.json), 不存在时不启用 (检测模型在 CPU 上运行):
+//! ```json
+//! {
+//!   "devices": [0, 1],
+//!   "provider": "cuda",
+//!   "streams": { "1": 0 },
+//!   "models": { "yolov8x.onnx": 1 }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+pub const GPU_PLACEMENT_CONFIG_PATH: &str = "gpu_placement.json";
+
+/// 推理耗时 EMA 的平滑系数
+const LATENCY_ALPHA: f64 = 0.1;
+
+/// 已安装的分配器, 未启用时为 None
+static BALANCER: OnceLock<Arc<GpuBalancer>> = OnceLock::new();
+
+/// GPU 执行后端
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GpuProvider {
+    #[default]
+    Cuda,
+    Trt, // TensorRT (首次加载需要构建引擎)
+}
+
+/// 多 GPU 分配配置 (gpu_placement.json)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GpuPlacementConfig {
+    pub devices: Vec<i32>, // 参与分配的 CUDA 设备号
+    pub provider: GpuProvider,
+    pub streams: BTreeMap<u32, i32>,   // 逻辑流 → 固定设备
+    pub models: BTreeMap<String, i32>, // 模型文件名 → 固定设备
+    pub max_memory_fraction: f32,      // 显存占用高于该比例的卡不再自动分配
+    pub memory_poll_secs: u32,         // 显存采样间隔, 0 不采样
+}
+
+impl Default for GpuPlacementConfig {
+    fn default() -> Self {
+        Self {
+            devices: vec![0],
+            provider: GpuProvider::Cuda,
+            streams: BTreeMap::new(),
+            models: BTreeMap::new(),
+            max_memory_fraction: 0.9,
+            memory_poll_secs: 5,
+        }
+    }
+}
+
+impl GpuPlacementConfig {
+    /// 从文件加载配置, 文件不存在或解析失败时为 None (不启用)
+    pub fn load(path: &str) -> Option<Self> {
+        let json = fs::read_to_string(path).ok()?;
+        match serde_json::from_str::<Self>(&json) {
+            Ok(config) if !config.devices.is_empty() => {
+                println!(
+                    "✅ 多 GPU 分配配置已从 {} 加载 (设备 {:?}, {:?})",
+                    path, config.devices, config.provider
+                );
+                Some(config)
+            }
+            Ok(_) => {
+                eprintln!("⚠️  多 GPU 分配配置没有设备, 不启用");
+                None
+            }
+            Err(e) => {
+                eprintln!("⚠️  多 GPU 分配配置解析失败: {}, 不启用", e);
+                None
+            }
+        }
+    }
+
+    /// 固定分配的设备: 逻辑流优先, 其次按模型文件名
+    pub fn pinned(&self, view: u32, model_path: &str) -> Option<i32> {
+        let file_name = std::path::Path::new(model_path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(model_path);
+        self.streams
+            .get(&view)
+            .or_else(|| self.models.get(file_name))
+            .copied()
+    }
+}
+
+/// 一张 GPU 的负载 (日志与调试用)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GpuLoad {
+    pub device: i32,
+    pub streams: usize,
+    pub latency_ms: f64, // 该卡上各检测线程推理耗时之和 (未测量的按平均值计)
+    pub memory: Option<(u64, u64)>, // 已用/总显存 (MiB)
+}
+
+impl GpuLoad {
+    fn memory_fraction(&self) -> Option<f32> {
+        self.memory
+            .filter(|&(_, total)| total > 0)
+            .map(|(used, total)| used as f32 / total as f32)
+    }
+}
+
+/// 分配记录
+#[derive(Default)]
+struct BalancerState {
+    leases: BTreeMap<u64, (i32, Option<f64>)>, // 租约 → (设备, 推理耗时 EMA)
+    memory: BTreeMap<i32, (u64, u64)>,
+    next_lease: u64,
+}
+
+/// 多 GPU 分配器
+pub struct GpuBalancer {
+    config: GpuPlacementConfig,
+    state: Mutex<BalancerState>,
+}
+
+impl GpuBalancer {
+    pub fn new(config: GpuPlacementConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::default(),
+        }
+    }
+
+    pub fn config(&self) -> &GpuPlacementConfig {
+        &self.config
+    }
+
+    /// 各卡当前负载 (按配置中的设备顺序)
+    pub fn loads(&self) -> Vec<GpuLoad> {
+        let state = self.state.lock().unwrap();
+        let known: Vec<f64> = state.leases.values().filter_map(|(_, ms)| *ms).collect();
+        // 还没有测量值的线程按已知耗时的平均值计 (都没有时每个线程计 1)
+        let unknown = if known.is_empty() {
+            1.0
+        } else {
+            known.iter().sum::<f64>() / known.len() as f64
+        };
+        self.config
+            .devices
+            .iter()
+            .map(|&device| {
+                let on_device = state.leases.values().filter(|(d, _)| *d == device);
+                GpuLoad {
+                    device,
+                    streams: on_device.clone().count(),
+                    latency_ms: on_device.map(|(_, ms)| ms.unwrap_or(unknown)).sum(),
+                    memory: state.memory.get(&device).copied(),
+                }
+            })
+            .collect()
+    }
+
+    /// 自动分配: 显存未超限的卡中负载最低的 (负载相同时线程少的、设备号小的优先)
+    pub fn least_loaded(&self) -> i32 {
+        let loads = self.loads();
+        let limit = self.config.max_memory_fraction;
+        let roomy: Vec<&GpuLoad> = loads
+            .iter()
+            .filter(|l| l.memory_fraction().is_none_or(|f| f < limit))
+            .collect();
+        let candidates = if roomy.is_empty() {
+            loads.iter().collect()
+        } else {
+            roomy
+        };
+        candidates
+            .into_iter()
+            .min_by(|a, b| {
+                a.latency_ms
+                    .total_cmp(&b.latency_ms)
+                    .then(a.streams.cmp(&b.streams))
+            })
+            .map_or(self.config.devices[0], |l| l.device)
+    }
+
+    /// 为逻辑流的检测模型分配设备, 租约释放时归还
+    pub fn assign(self: &Arc<Self>, view: u32, model_path: &str) -> GpuLease {
+        let pinned = self.config.pinned(view, model_path);
+        let device = pinned.unwrap_or_else(|| self.least_loaded());
+        let mut state = self.state.lock().unwrap();
+        let id = state.next_lease;
+        state.next_lease += 1;
+        state.leases.insert(id, (device, None));
+        drop(state);
+        println!(
+            "🎮 逻辑流 {} → GPU {} ({:?}, {})",
+            view,
+            device,
+            self.config.provider,
+            if pinned.is_some() { "固定" } else { "自动" }
+        );
+        GpuLease {
+            balancer: Arc::clone(self),
+            id,
+            device,
+            provider: self.config.provider,
+        }
+    }
+
+    fn report(&self, id: u64, inference_ms: f64) {
+        if let Some((_, ema)) = self.state.lock().unwrap().leases.get_mut(&id) {
+            *ema = Some(match *ema {
+                Some(prev) => prev + LATENCY_ALPHA * (inference_ms - prev),
+                None => inference_ms,
+            });
+        }
+    }
+
+    fn release(&self, id: u64) {
+        self.state.lock().unwrap().leases.remove(&id);
+    }
+
+    /// 记入显存采样 (MiB)
+    pub fn set_memory(&self, device: i32, used: u64, total: u64) {
+        self.state
+            .lock()
+            .unwrap()
+            .memory
+            .insert(device, (used, total));
+    }
+
+    /// 后台运行 `nvidia-smi -l` 采样各卡显存
+    fn spawn_memory_monitor(self: &Arc<Self>) {
+        let secs = self.config.memory_poll_secs;
+        if secs == 0 {
+            return;
+        }
+        let child = Command::new("nvidia-smi")
+            .args([
+                "--query-gpu=index,memory.used,memory.total",
+                "--format=csv,noheader,nounits",
+                "-l",
+                &secs.to_string(),
+            ])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) => {
+                eprintln!("⚠️ nvidia-smi 不可用 ({}), 自动分配只按推理耗时", e);
+                return;
+            }
+        };
+        let stdout = child.stdout.take().expect("nvidia-smi stdout");
+        let balancer = Arc::clone(self);
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if let Some((device, used, total)) = parse_memory_line(&line) {
+                    balancer.set_memory(device, used, total);
+                }
+            }
+            let _ = child.wait();
+        });
+    }
+}
+
+/// 解析 nvidia-smi 的一行 `index, memory.used, memory.total` (MiB)
+pub fn parse_memory_line(line: &str) -> Option<(i32, u64, u64)> {
+    let mut fields = line.split(',').map(str::trim);
+    let device = fields.next()?.parse().ok()?;
+    let used = fields.next()?.parse().ok()?;
+    let total = fields.next()?.parse().ok()?;
+    Some((device, used, total))
+}
+
+/// 一个检测线程占用的 GPU, 释放时从分配器中移除
+pub struct GpuLease {
+    balancer: Arc<GpuBalancer>,
+    id: u64,
+    pub device: i32,
+    pub provider: GpuProvider,
+}
+
+impl GpuLease {
+    /// 记入一次推理耗时, 作为自动分配的负载
+    pub fn report(&self, inference_ms: f64) {
+        self.balancer.report(self.id, inference_ms);
+    }
+}
+
+impl Drop for GpuLease {
+    fn drop(&mut self) {
+        self.balancer.release(self.id);
+    }
+}
+
+/// 安装全局分配器并开始采样显存 (只能安装一次, 之后加载的检测模型按它分配)
+pub fn install(config: GpuPlacementConfig) -> Arc<GpuBalancer> {
+    let balancer = BALANCER.get_or_init(|| {
+        let balancer = Arc::new(GpuBalancer::new(config));
+        balancer.spawn_memory_monitor();
+        balancer
+    });
+    Arc::clone(balancer)
+}
+
+/// 已安装的分配器, 未启用时为 None
+pub fn balancer() -> Option<Arc<GpuBalancer>> {
+    BALANCER.get().cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_gpus(config: GpuPlacementConfig) -> Arc<GpuBalancer> {
+        Arc::new(GpuBalancer::new(GpuPlacementConfig {
+            devices: vec![0, 1],
+            memory_poll_secs: 0,
+            ..config
+        }))
+    }
+
+    /// 按逻辑流或模型文件名固定分配, 逻辑流优先
+    #[test]
+    fn test_pinned() {
+        let config: GpuPlacementConfig = serde_json::from_str(
+            r#"{"devices": [0, 1], "provider": "trt", "streams": {"2": 0}, "models": {"yolov8x.onnx": 1}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.provider, GpuProvider::Trt);
+        assert_eq!(config.pinned(2, "models/yolov8x.onnx"), Some(0));
+        assert_eq!(config.pinned(0, "models/yolov8x.onnx"), Some(1));
+        assert_eq!(config.pinned(0, "models/yolov8n.onnx"), None);
+
+        let balancer = two_gpus(config);
+        let lease = balancer.assign(0, "models/yolov8x.onnx");
+        assert_eq!((lease.device, lease.provider), (1, GpuProvider::Trt));
+    }
+
+    /// 自动分配按推理耗时之和选最空闲的卡, 显存超限的卡跳过, 租约释放后归还
+    #[test]
+    fn test_least_loaded() {
+        let balancer = two_gpus(GpuPlacementConfig::default());
+        let a = balancer.assign(0, "a.onnx");
+        let b = balancer.assign(1, "a.onnx");
+        assert_eq!((a.device, b.device), (0, 1));
+
+        // GPU 0 上的模型更慢, 新的线程放到 GPU 1
+        a.report(30.0);
+        b.report(10.0);
+        let c = balancer.assign(2, "a.onnx");
+        assert_eq!(c.device, 1);
+        // c 还没有测量值, 按已知耗时的平均值 (20ms) 计
+        assert_eq!(balancer.loads()[1].latency_ms, 30.0);
+
+        // GPU 1 显存将满, 放回 GPU 0
+        balancer.set_memory(1, 7500, 8000);
+        assert_eq!(balancer.least_loaded(), 0);
+        // 都超限时仍按负载选, 耗时相同时线程少的优先
+        balancer.set_memory(0, 7900, 8000);
+        assert_eq!(balancer.least_loaded(), 0);
+
+        drop(c);
+        assert_eq!(balancer.loads()[1].streams, 1);
+        assert_eq!(parse_memory_line("1, 7500, 8192"), Some((1, 7500, 8192)));
+        assert_eq!(parse_memory_line("[N/A]"), None);
+    }
+}
@@ -0,0 +1,226 @@
+//! 跟踪状态日志 (Track journal)
+//!
+//! 定期把跟踪器的活跃轨迹 (ID/位置/外观特征/年龄) 写入文件, 启动时恢复:
+//! 短时间重启 (看门狗重启/升级/断电) 后轨迹ID延续, 按轨迹ID计的区域规则冷却与统计不会重置.
+//! - 先写临时文件再重命名, 写入中途崩溃不会损坏上一次的日志
+//! - 每个逻辑流一个文件 (`tracks.json`, 鱼眼虚拟视图为 `tracks.view1.json` ...)
+//! - 日志早于 `max_age` 或跟踪器类型不同时不恢复
+//! - 恢复的轨迹从保存时的位置重新开始预测 (速度清零), 下一帧重新关联, 超过最大丢失帧数后删除
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::types::BBox;
+
+/// 默认日志文件
+pub const TRACK_JOURNAL_PATH: &str = "tracks.json";
+
+/// 默认保存间隔
+pub const JOURNAL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 单条轨迹的状态
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TrackState {
+    pub id: u32,
+    pub bbox: [f32; 4], // 滤波后的边界框 (x1, y1, x2, y2)
+    pub score: f32,
+    pub frames_lost: u32,
+    pub total_frames: u32, // 轨迹年龄 (被跟踪的帧数)
+    pub confirmed: bool,
+    pub color: (u8, u8, u8),
+    pub trajectory: Vec<(f32, f32)>,
+    // 外观特征 (ReID/几何特征), 没有时为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub features: Option<Vec<f32>>,
+}
+
+impl TrackState {
+    pub fn to_bbox(&self) -> BBox {
+        let [x1, y1, x2, y2] = self.bbox;
+        BBox {
+            x1,
+            y1,
+            x2,
+            y2,
+            confidence: self.score,
+            class_id: 0,
+        }
+    }
+}
+
+/// 跟踪器状态快照
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TrackerSnapshot {
+    pub tracker: String, // 跟踪器类型 (bytetrack/deepsort)
+    pub saved_at: DateTime<Utc>,
+    pub next_id: u32,
+    pub removed: u64,
+    pub tracks: Vec<TrackState>,
+}
+
+impl TrackerSnapshot {
+    /// 恢复后的下一个ID (不小于已有轨迹的最大ID + 1)
+    pub fn resume_id(&self) -> u32 {
+        let max_id = self.tracks.iter().map(|t| t.id).max().unwrap_or(0);
+        self.next_id.max(max_id + 1)
+    }
+}
+
+/// 某个逻辑流的日志文件 (逻辑流 0 使用原路径)
+pub fn view_path(base: &Path, view: u32) -> PathBuf {
+    if view == 0 {
+        return base.to_path_buf();
+    }
+    let stem = base
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("tracks");
+    let name = match base.extension().and_then(|e| e.to_str()) {
+        Some(ext) => format!("{}.view{}.{}", stem, view, ext),
+        None => format!("{}.view{}", stem, view),
+    };
+    base.with_file_name(name)
+}
+
+/// 跟踪状态日志
+#[derive(Clone, Debug)]
+pub struct TrackJournal {
+    path: PathBuf,
+    interval: Duration,
+    max_age: Duration,
+    last_saved: Option<Instant>,
+}
+
+impl TrackJournal {
+    /// `max_age`: 超过该时长的日志视为过期, 不再恢复
+    pub fn new(path: impl Into<PathBuf>, interval: Duration, max_age: Duration) -> Self {
+        Self {
+            path: path.into(),
+            interval,
+            max_age,
+            last_saved: None,
+        }
+    }
+
+    /// 指定逻辑流的日志
+    pub fn for_view(&self, view: u32) -> Self {
+        Self {
+            path: view_path(&self.path, view),
+            last_saved: None,
+            ..self.clone()
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 读取未过期且跟踪器类型相同的快照
+    pub fn load(&self, tracker: &str) -> Option<TrackerSnapshot> {
+        let json = fs::read_to_string(&self.path).ok()?;
+        let snapshot: TrackerSnapshot = match serde_json::from_str(&json) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                eprintln!("⚠️ 跟踪状态日志解析失败 ({}): {}", self.path.display(), e);
+                return None;
+            }
+        };
+        let age = (Utc::now() - snapshot.saved_at)
+            .to_std()
+            .unwrap_or_default();
+        if age > self.max_age {
+            println!("ℹ️ 跟踪状态日志已过期 ({}s 前保存), 不恢复", age.as_secs());
+            return None;
+        }
+        if snapshot.tracker != tracker {
+            println!(
+                "ℹ️ 跟踪状态日志来自 {} 跟踪器, 当前为 {}, 不恢复",
+                snapshot.tracker, tracker
+            );
+            return None;
+        }
+        Some(snapshot)
+    }
+
+    /// 距上次保存已超过保存间隔
+    pub fn due(&self, now: Instant) -> bool {
+        self.last_saved
+            .is_none_or(|t| now.saturating_duration_since(t) >= self.interval)
+    }
+
+    /// 写入快照 (临时文件 + 重命名)
+    pub fn save(&mut self, snapshot: &TrackerSnapshot) -> anyhow::Result<()> {
+        self.last_saved = Some(Instant::now());
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec(snapshot)?)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(saved_at: DateTime<Utc>) -> TrackerSnapshot {
+        TrackerSnapshot {
+            tracker: "bytetrack".to_string(),
+            saved_at,
+            next_id: 3,
+            removed: 1,
+            tracks: vec![TrackState {
+                id: 7,
+                bbox: [10.0, 20.0, 60.0, 120.0],
+                score: 0.8,
+                frames_lost: 0,
+                total_frames: 42,
+                confirmed: true,
+                color: (255, 64, 64),
+                trajectory: vec![(35.0, 70.0)],
+                features: Some(vec![0.6, 0.8]),
+            }],
+        }
+    }
+
+    /// 保存后按类型读回; 过期或跟踪器类型不同的日志不恢复
+    #[test]
+    fn test_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("track_journal_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut journal = TrackJournal::new(
+            dir.join(TRACK_JOURNAL_PATH),
+            JOURNAL_INTERVAL,
+            Duration::from_secs(60),
+        );
+
+        assert!(journal.due(Instant::now()));
+        journal.save(&snapshot(Utc::now())).unwrap();
+        assert!(!journal.due(Instant::now()));
+        assert!(!dir.join("tracks.tmp").exists());
+
+        let loaded = journal.load("bytetrack").unwrap();
+        assert_eq!(loaded.tracks, snapshot(loaded.saved_at).tracks);
+        // 下一个ID不与恢复的轨迹冲突
+        assert_eq!(loaded.resume_id(), 8);
+        assert!(journal.load("deepsort").is_none());
+
+        journal
+            .save(&snapshot(Utc::now() - chrono::Duration::seconds(600)))
+            .unwrap();
+        assert!(journal.load("bytetrack").is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    /// 鱼眼虚拟视图各自一个日志文件
+    #[test]
+    fn test_view_path() {
+        let base = Path::new("state/tracks.json");
+        assert_eq!(view_path(base, 0), base);
+        assert_eq!(view_path(base, 2), Path::new("state/tracks.view2.json"));
+        assert_eq!(view_path(Path::new("tracks"), 1), Path::new("tracks.view1"));
+    }
+}
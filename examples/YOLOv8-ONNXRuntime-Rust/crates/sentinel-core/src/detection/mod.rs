@@ -0,0 +1,64 @@
+//! 检测系统 (Detection System)
+//!
+//! 独立工作线程,负责智能分析
+//! - Detector: 目标检测
+//! - Tracker:  目标追踪
+//! - GlobalIdManager: 跨摄像头全局ID
+//! - DisplaySmoother: 显示平滑 (跟踪后的滞回与新目标确认)
+//! - ResolutionLadder: 动态分辨率阶梯 (按目标大小升降推理输入尺寸)
+//! - GpuBalancer: 多 GPU 分配 (按逻辑流/模型固定, 或按推理耗时与显存自动分配)
+//! - TileCache: 静止机位的分块局部推理 (背景缓存, 只推理有运动的区域)
+//! - BoxFilterConfig: 按类别的检测框尺寸与宽高比过滤 (可按逻辑流覆盖)
+//! - BorderConfig: 贴边检测框的处理策略 (保留/丢弃/标记为不完整并放大跟踪观测噪声)
+//! - ModelComparison: 两个模型同帧对比 (检测框与耗时)
+//! - FrameTrace: 帧延迟追踪
+//! - TrackJournal: 跟踪状态日志 (重启后恢复轨迹ID)
+//! - profiler: 按需性能剖析 (chrome://tracing 时间线)
+//!
+//! Detector 与 ModelComparison 需要 `ort` 功能, 跟踪器与数据类型属于核心库
+
+pub mod border;
+pub mod box_filter;
+pub mod bytetrack;
+#[cfg(feature = "ort")]
+pub mod compare;
+pub mod deepsort;
+#[cfg(feature = "ort")]
+pub mod detector;
+pub mod global_id;
+pub mod gpu_placement;
+pub mod journal;
+pub mod profiler;
+pub mod resolution;
+pub mod smoothing;
+pub mod tile_cache;
+pub mod trace;
+pub mod tracker;
+pub mod types;
+
+// Re-exports
+pub use border::{BorderConfig, BorderPolicy, BORDER_CONFIG_PATH};
+pub use box_filter::{BoxFilterConfig, ClassLimits, Extent, BOX_FILTER_CONFIG_PATH};
+pub use bytetrack::{AssociationWeights, ByteTrackedPerson, ByteTracker, InstanceMask};
+#[cfg(feature = "ort")]
+pub use compare::{CompareLayout, CompareParams, ComparisonResult, ModelComparison};
+pub use deepsort::{PersonTracker, TrackedPerson};
+#[cfg(feature = "ort")]
+pub use detector::Detector;
+pub use global_id::{GlobalIdConfig, GlobalIdManager};
+pub use gpu_placement::{
+    GpuBalancer, GpuLease, GpuLoad, GpuPlacementConfig, GpuProvider, GPU_PLACEMENT_CONFIG_PATH,
+};
+pub use journal::{TrackJournal, TrackerSnapshot, TRACK_JOURNAL_PATH};
+pub use resolution::{LadderConfig, LadderStep, ResolutionLadder};
+pub use smoothing::{ClassSmoothing, DisplaySmoother, SmoothingConfig};
+pub use tile_cache::{TileCache, TileConfig, TilePlan, TILE_CONFIG_PATH};
+pub use trace::{FrameTrace, LatencyStage, LatencyStats, StageSummary};
+pub use tracker::{
+    compute_iou, id_to_color, KalmanBoxFilter, PartialObservation, TrackPoint, TrackStats,
+    TrackedObject, Tracker, TrackerParams,
+};
+pub use types::{
+    BBox, DecodedFrame, InferredFrame, PoseKeypoints, ResizedFrame, TrackerType, DETECT_CLASSES,
+    INF_SIZE,
+};
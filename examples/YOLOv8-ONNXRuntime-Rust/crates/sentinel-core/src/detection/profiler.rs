@@ -0,0 +1,307 @@
+//! 按需性能剖析 (On-demand profiling)
+//!
+//! 控制面板"剖析 10 秒"按钮或 `POST /api/profile?secs=10` 开始一次剖析:
+//! 渲染线程收尾每帧 [`FrameTrace`] 时交给剖析器, 到时后在后台写出
+//! chrome://tracing (Trace Event Format) JSON, 可直接拖进 chrome://tracing 或 Perfetto 查看.
+//! 客户机器上出现卡顿时不用挂调试器/采样器, 就能看出每帧的时间花在哪个阶段
+//!
+//! 时间线按线程分三条轨道: 解码/排队、检测 (缩放/推理/后处理)、渲染;
+//! 每个事件的参数带帧序号与端到端延迟, `otherData` 中附带各阶段统计
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use super::trace::{FrameTrace, LatencyStage, LatencyStats};
+use crate::utils::clock;
+
+/// 剖析报告输出目录
+pub const PROFILE_DIR: &str = "profiles";
+
+/// 默认剖析时长 (秒)
+pub const DEFAULT_PROFILE_SECS: u64 = 10;
+
+/// 最长剖析时长 (秒), 避免误操作长时间占用内存
+pub const MAX_PROFILE_SECS: u64 = 120;
+
+/// 时间线轨道 (tid, 名称)
+const TRACK_DECODE: (u32, &str) = (1, "decode / queue");
+const TRACK_DETECT: (u32, &str) = (2, "detect");
+const TRACK_RENDER: (u32, &str) = (3, "render");
+
+/// 是否有剖析在进行 (渲染线程每帧检查, 未剖析时不加锁)
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+static PROFILER: Mutex<ProfilerState> = Mutex::new(ProfilerState {
+    session: None,
+    last_report: None,
+});
+
+struct ProfilerState {
+    session: Option<ProfileSession>,
+    last_report: Option<PathBuf>,
+}
+
+/// 剖析状态 (控制面板与 API 查询)
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ProfileStatus {
+    pub running: bool,
+    pub remaining_secs: f64,
+    pub frames: usize,
+    pub last_report: Option<String>, // 最近一次写出的报告路径
+}
+
+/// 一次剖析: 记录窗口内渲染完成的帧
+pub struct ProfileSession {
+    started: Instant,
+    duration: Duration,
+    traces: Vec<FrameTrace>,
+}
+
+impl ProfileSession {
+    pub fn new(duration: Duration, now: Instant) -> Self {
+        Self {
+            started: now,
+            duration,
+            traces: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, trace: &FrameTrace) {
+        self.traces.push(*trace);
+    }
+
+    pub fn frames(&self) -> usize {
+        self.traces.len()
+    }
+
+    pub fn is_due(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.started) >= self.duration
+    }
+
+    pub fn remaining(&self, now: Instant) -> Duration {
+        self.duration
+            .saturating_sub(now.saturating_duration_since(self.started))
+    }
+
+    /// 转为 Trace Event Format (时间单位为微秒, 以剖析开始为零点)
+    pub fn to_chrome_trace(&self) -> Value {
+        let us = |t: Instant| {
+            let offset = if t >= self.started {
+                (t - self.started).as_secs_f64()
+            } else {
+                -(self.started - t).as_secs_f64()
+            };
+            offset * 1e6
+        };
+
+        let mut events = vec![json!({
+            "name": "process_name", "ph": "M", "pid": 1,
+            "args": { "name": "sentinel" }
+        })];
+        for (tid, name) in [TRACK_DECODE, TRACK_DETECT, TRACK_RENDER] {
+            events.push(json!({
+                "name": "thread_name", "ph": "M", "pid": 1, "tid": tid,
+                "args": { "name": name }
+            }));
+        }
+
+        for (frame, trace) in self.traces.iter().enumerate() {
+            let total_ms = trace.stage_ms(LatencyStage::Total);
+            events.push(json!({
+                "name": "frame", "cat": "decode", "ph": "i", "s": "t",
+                "ts": us(trace.decode_ts), "pid": 1, "tid": TRACK_DECODE.0,
+                "args": { "frame": frame }
+            }));
+
+            let spans = [
+                (LatencyStage::Queue, Some(trace.decode_ts), TRACK_DECODE.0),
+                (LatencyStage::Resize, trace.resize_ts, TRACK_DETECT.0),
+                (LatencyStage::Inference, trace.infer_start, TRACK_DETECT.0),
+                (LatencyStage::Postprocess, trace.infer_end, TRACK_DETECT.0),
+                (LatencyStage::Render, trace.publish_ts, TRACK_RENDER.0),
+            ];
+            for (stage, start, tid) in spans {
+                let (Some(start), Some(dur_ms)) = (start, trace.stage_ms(stage)) else {
+                    continue;
+                };
+                events.push(json!({
+                    "name": stage.key(), "cat": "frame", "ph": "X",
+                    "ts": us(start), "dur": dur_ms * 1000.0, "pid": 1, "tid": tid,
+                    "args": { "frame": frame, "total_ms": total_ms }
+                }));
+            }
+        }
+
+        let mut stats = LatencyStats::new(self.traces.len());
+        for trace in &self.traces {
+            stats.record(trace);
+        }
+        let summary: serde_json::Map<String, Value> = stats
+            .report()
+            .into_iter()
+            .map(|(stage, s)| {
+                let value = json!({ "mean_ms": s.mean_ms, "p95_ms": s.p95_ms, "max_ms": s.max_ms });
+                (stage.key().to_string(), value)
+            })
+            .collect();
+
+        json!({
+            "traceEvents": events,
+            "displayTimeUnit": "ms",
+            "otherData": {
+                "frames": self.traces.len(),
+                "duration_secs": self.duration.as_secs_f64(),
+                "stages": summary,
+            }
+        })
+    }
+}
+
+/// 开始剖析 (时长限制在 1..=MAX_PROFILE_SECS 秒), 已有剖析在进行时返回 false
+pub fn start(secs: u64) -> bool {
+    let mut state = PROFILER.lock().unwrap();
+    if state.session.is_some() {
+        return false;
+    }
+    let secs = secs.clamp(1, MAX_PROFILE_SECS);
+    state.session = Some(ProfileSession::new(
+        Duration::from_secs(secs),
+        Instant::now(),
+    ));
+    ACTIVE.store(true, Ordering::Release);
+    println!("📊 开始性能剖析: {} 秒", secs);
+    true
+}
+
+/// 渲染线程收尾一帧时调用; 剖析到时后在后台写出报告
+pub fn record(trace: &FrameTrace) {
+    if !ACTIVE.load(Ordering::Acquire) {
+        return;
+    }
+    if let Some(session) = PROFILER.lock().unwrap().session.as_mut() {
+        session.record(trace);
+    }
+    tick();
+}
+
+/// 检查剖析是否到时 (没有新帧时由渲染线程定期调用)
+pub fn tick() {
+    if !ACTIVE.load(Ordering::Acquire) {
+        return;
+    }
+    let mut state = PROFILER.lock().unwrap();
+    if !state
+        .session
+        .as_ref()
+        .is_some_and(|s| s.is_due(Instant::now()))
+    {
+        return;
+    }
+    let Some(session) = state.session.take() else {
+        return;
+    };
+    ACTIVE.store(false, Ordering::Release);
+    let path = Path::new(PROFILE_DIR).join(format!(
+        "profile_{}.json",
+        clock::now().format("%Y%m%d_%H%M%S")
+    ));
+    state.last_report = Some(path.clone());
+    drop(state);
+
+    // 序列化与写盘放到后台, 不阻塞渲染
+    std::thread::spawn(move || match write_report(&session, &path) {
+        Ok(()) => println!(
+            "📊 性能剖析已写入: {} ({} 帧)",
+            path.display(),
+            session.frames()
+        ),
+        Err(e) => eprintln!("⚠️ 写入性能剖析失败: {}", e),
+    });
+}
+
+fn write_report(session: &ProfileSession, path: &Path) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string(&session.to_chrome_trace())?)?;
+    Ok(())
+}
+
+/// 当前剖析状态
+pub fn status() -> ProfileStatus {
+    let state = PROFILER.lock().unwrap();
+    let now = Instant::now();
+    ProfileStatus {
+        running: state.session.is_some(),
+        remaining_secs: state
+            .session
+            .as_ref()
+            .map_or(0.0, |s| s.remaining(now).as_secs_f64()),
+        frames: state.session.as_ref().map_or(0, |s| s.frames()),
+        last_report: state.last_report.as_ref().map(|p| p.display().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    /// 每帧在三条轨道上生成阶段事件, 缺失的阶段不输出, otherData 附带阶段统计
+    #[test]
+    fn test_chrome_trace_events() {
+        let start = Instant::now();
+        let mut session = ProfileSession::new(MS * 100, start);
+        let mut trace = FrameTrace::new();
+        trace.decode_ts = start + MS * 10;
+        trace.resize_ts = Some(start + MS * 12);
+        trace.infer_start = Some(start + MS * 15);
+        trace.infer_end = Some(start + MS * 30);
+        trace.publish_ts = Some(start + MS * 34);
+        trace.render_ts = Some(start + MS * 50);
+        session.record(&trace);
+        // 检测被禁用的帧: 只有渲染阶段
+        let mut skipped = FrameTrace::new();
+        skipped.decode_ts = start + MS * 60;
+        skipped.publish_ts = Some(start + MS * 61);
+        skipped.render_ts = Some(start + MS * 70);
+        session.record(&skipped);
+
+        let value = session.to_chrome_trace();
+        let events = value["traceEvents"].as_array().unwrap();
+        let spans: Vec<_> = events.iter().filter(|e| e["ph"] == "X").collect();
+        assert_eq!(spans.len(), 6);
+
+        let inference = spans.iter().find(|e| e["name"] == "inference").unwrap();
+        assert_eq!(inference["tid"], TRACK_DETECT.0);
+        assert!((inference["ts"].as_f64().unwrap() - 15_000.0).abs() < 1.0);
+        assert!((inference["dur"].as_f64().unwrap() - 15_000.0).abs() < 1.0);
+        assert!((inference["args"]["total_ms"].as_f64().unwrap() - 40.0).abs() < 1e-6);
+
+        let render = spans.last().unwrap();
+        assert_eq!(
+            (render["name"].as_str(), render["tid"].as_u64()),
+            (Some("render"), Some(3))
+        );
+        assert_eq!(render["args"]["frame"], 1);
+        assert_eq!(value["otherData"]["frames"], 2);
+        assert!(value["otherData"]["stages"]["inference"]["mean_ms"].is_number());
+    }
+
+    /// 到时判断与剩余时间
+    #[test]
+    fn test_session_window() {
+        let start = Instant::now();
+        let session = ProfileSession::new(MS * 100, start);
+        assert!(!session.is_due(start + MS * 99));
+        assert_eq!(session.remaining(start + MS * 40), MS * 60);
+        assert!(session.is_due(start + MS * 100));
+        assert_eq!(session.remaining(start + MS * 200), Duration::ZERO);
+    }
+}
@@ -0,0 +1,171 @@
+//! 动态分辨率阶梯 (Resolution ladder)
+//!
+//! 按场景中目标的大小自动升降推理输入尺寸, 在精度与速度之间取舍:
+//! - 每帧取检测框面积 (占画面比例) 的中位数, 累积一个窗口后再取中位数
+//! - 目标偏小 (远处) 时升到上一档, 提高小目标召回; 目标偏大 (近处) 时降到下一档, 节省推理时间
+//! - 两个阈值之间保持不动, 每次切换后冷却一段时间, 避免来回跳档
+//!
+//! 只有输入高宽为动态轴的模型可以就地切换 (见 [`Model::set_input_size`]), 不需要重建会话;
+//! 静态尺寸或 TensorRT 构建的模型不支持, 检测线程随即关闭阶梯.
+//!
+//! [`Model::set_input_size`]: crate::models::Model::set_input_size
+
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+/// 分辨率阶梯配置 (tracker_config.json 的 `resolution_ladder`)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LadderConfig {
+    pub enabled: bool,
+    pub rungs: Vec<u32>,      // 可选的推理输入尺寸 (32 的倍数)
+    pub small_area: f32,      // 框面积中位数低于画面的该比例时升档
+    pub large_area: f32,      // 高于该比例时降档
+    pub window_frames: usize, // 统计窗口 (有目标的帧数)
+    pub cooldown_secs: f32,   // 两次切换的最小间隔
+}
+
+impl Default for LadderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rungs: vec![416, 640, 960, 1280],
+            small_area: 0.002,
+            large_area: 0.03,
+            window_frames: 30,
+            cooldown_secs: 10.0,
+        }
+    }
+}
+
+/// 一次档位切换
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LadderStep {
+    pub size: u32,        // 新的推理输入尺寸
+    pub median_area: f32, // 触发切换的窗口面积中位数 (占画面比例)
+}
+
+/// 分辨率阶梯状态
+#[derive(Default)]
+pub struct ResolutionLadder {
+    config: LadderConfig,
+    medians: Vec<f32>,
+    last_switch: Option<Instant>,
+}
+
+impl ResolutionLadder {
+    pub fn config(&self) -> &LadderConfig {
+        &self.config
+    }
+
+    /// 更新配置, 重新开始统计
+    pub fn set_config(&mut self, config: LadderConfig) {
+        self.config = config;
+        self.medians.clear();
+    }
+
+    /// 当前模型不支持就地切换尺寸时关闭 (下一次下发配置时重新尝试)
+    pub fn disable(&mut self) {
+        self.config.enabled = false;
+        self.medians.clear();
+    }
+
+    /// 记入一帧的检测框面积 (占画面比例), 需要切换档位时返回新尺寸
+    ///
+    /// 没有目标的帧不计入窗口; `current` 不在阶梯上时按大小取相邻的档
+    pub fn observe(&mut self, areas: &[f32], current: u32, now: Instant) -> Option<LadderStep> {
+        if !self.config.enabled || areas.is_empty() {
+            return None;
+        }
+        self.medians.push(median(areas));
+        if self.medians.len() < self.config.window_frames.max(1) {
+            return None;
+        }
+        let area = median(&self.medians);
+        self.medians.clear();
+
+        let cooling = self
+            .last_switch
+            .is_some_and(|t| now.duration_since(t).as_secs_f32() < self.config.cooldown_secs);
+        if cooling {
+            return None;
+        }
+        let rungs = self.config.rungs.iter().copied();
+        let size = if area < self.config.small_area {
+            rungs.filter(|&r| r > current).min()
+        } else if area > self.config.large_area {
+            rungs.filter(|&r| r < current).max()
+        } else {
+            None
+        }?;
+        self.last_switch = Some(now);
+        Some(LadderStep {
+            size,
+            median_area: area,
+        })
+    }
+}
+
+/// 中位数 (偶数个时取较大的一个), values 不能为空
+fn median(values: &[f32]) -> f32 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f32::total_cmp);
+    sorted[sorted.len() / 2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn ladder(window_frames: usize) -> ResolutionLadder {
+        let mut ladder = ResolutionLadder::default();
+        ladder.set_config(LadderConfig {
+            enabled: true,
+            window_frames,
+            ..Default::default()
+        });
+        ladder
+    }
+
+    /// 小目标升档, 大目标降档, 中间保持; 到达阶梯两端后不再切换
+    #[test]
+    fn test_step_up_and_down() {
+        let now = Instant::now();
+        let mut ladder = ladder(1);
+        let step = ladder.observe(&[0.001, 0.0005, 0.5], 640, now).unwrap();
+        assert_eq!(step.size, 960);
+        assert_eq!(step.median_area, 0.001);
+
+        let later = now + Duration::from_secs(60);
+        assert_eq!(ladder.observe(&[0.01], 960, later), None);
+        assert_eq!(ladder.observe(&[0.1], 512, later).unwrap().size, 416);
+        let much_later = later + Duration::from_secs(60);
+        assert_eq!(ladder.observe(&[0.1], 416, much_later), None);
+        assert_eq!(ladder.observe(&[0.001], 1280, much_later), None);
+    }
+
+    /// 窗口未满、没有目标或冷却期内不切换; 未启用时不统计
+    #[test]
+    fn test_window_and_cooldown() {
+        let now = Instant::now();
+        let mut ladder = ladder(3);
+        assert_eq!(ladder.observe(&[0.001], 640, now), None);
+        assert_eq!(ladder.observe(&[], 640, now), None);
+        assert_eq!(ladder.observe(&[0.001], 640, now), None);
+        assert_eq!(ladder.observe(&[0.001], 640, now).unwrap().size, 960);
+
+        let soon = now + Duration::from_secs(1);
+        for _ in 0..3 {
+            assert_eq!(ladder.observe(&[0.001], 960, soon), None);
+        }
+
+        ladder.disable();
+        let later = now + Duration::from_secs(60);
+        for _ in 0..3 {
+            assert_eq!(ladder.observe(&[0.001], 960, later), None);
+        }
+        assert!(!ladder.config().enabled);
+    }
+}
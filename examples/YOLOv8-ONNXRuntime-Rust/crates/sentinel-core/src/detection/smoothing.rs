@@ -0,0 +1,301 @@
+//! 显示平滑 (Display Smoothing)
+//!
+//! 置信度在阈值附近波动的目标会逐帧闪烁. 跟踪之后按轨迹做滞回:
+//! - 新轨迹连续 `confirm_frames` 帧达到显示阈值才显示
+//! - 已显示的轨迹只需达到较低的保持阈值 (`显示阈值 × keep_ratio`)
+//! - 跟踪器短暂未输出的已显示轨迹, 重新出现时无需再次确认
+//!
+//! 显示阈值即控制面板的置信度阈值; 启用时检测模型按最低保持阈值推理,
+//! 低于显示阈值的检测只用于维持已显示的轨迹. 未启用跟踪时没有轨迹可依,
+//! 只按显示阈值过滤
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::types::BBox;
+
+/// 轨迹状态保留帧数, 超过后重新出现的轨迹需要再次确认
+const TRACK_EXPIRY_FRAMES: u64 = 30;
+
+/// 虚影与已显示框的交并比超过该值时视为同一目标, 不再显示虚影
+const GHOST_OVERLAP_IOU: f32 = 0.5;
+
+/// 单个类别的平滑参数
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClassSmoothing {
+    pub keep_ratio: f32,     // 保持阈值 = 显示阈值 × keep_ratio (0~1)
+    pub confirm_frames: u32, // 新轨迹显示前需连续达到显示阈值的帧数
+}
+
+impl Default for ClassSmoothing {
+    fn default() -> Self {
+        Self {
+            keep_ratio: 0.5,
+            confirm_frames: 2,
+        }
+    }
+}
+
+/// 显示平滑配置 (tracker_config.json 的 `display_smoothing`)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmoothingConfig {
+    pub enabled: bool,
+    pub keep_ratio: f32,                       // 默认保持阈值比例
+    pub confirm_frames: u32,                   // 默认确认帧数
+    pub classes: HashMap<u32, ClassSmoothing>, // 按类别ID覆盖默认参数
+}
+
+impl Default for SmoothingConfig {
+    fn default() -> Self {
+        let ClassSmoothing {
+            keep_ratio,
+            confirm_frames,
+        } = ClassSmoothing::default();
+        Self {
+            enabled: true,
+            keep_ratio,
+            confirm_frames,
+            classes: HashMap::new(),
+        }
+    }
+}
+
+impl SmoothingConfig {
+    /// 类别的平滑参数, 类别未知或未单独配置时取默认值
+    pub fn params(&self, class: Option<u32>) -> ClassSmoothing {
+        class
+            .and_then(|c| self.classes.get(&c))
+            .copied()
+            .unwrap_or(ClassSmoothing {
+                keep_ratio: self.keep_ratio,
+                confirm_frames: self.confirm_frames,
+            })
+    }
+
+    /// 检测模型的推理阈值: 启用时为各类别中最低的保持阈值
+    pub fn inference_conf(&self, show_conf: f32) -> f32 {
+        if !self.enabled {
+            return show_conf;
+        }
+        let ratio = self
+            .classes
+            .values()
+            .map(|p| p.keep_ratio)
+            .chain(std::iter::once(self.keep_ratio))
+            .map(|r| r.clamp(0.0, 1.0))
+            .fold(1.0, f32::min);
+        show_conf * ratio
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct TrackState {
+    class: Option<u32>,
+    hits: u32,
+    shown: bool,
+    last_frame: u64,
+}
+
+/// 按轨迹ID维护显示状态
+#[derive(Default)]
+pub struct DisplaySmoother {
+    config: SmoothingConfig,
+    tracks: HashMap<u32, TrackState>,
+    frame: u64,
+}
+
+impl DisplaySmoother {
+    pub fn new(config: SmoothingConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    pub fn config(&self) -> &SmoothingConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: SmoothingConfig) {
+        self.config = config;
+    }
+
+    /// 清空轨迹状态 (切换跟踪器后轨迹ID与旧的无关)
+    pub fn reset(&mut self) {
+        self.tracks.clear();
+    }
+
+    /// 处理一帧, 返回每个框是否显示
+    ///
+    /// - `bboxes`: 跟踪后的框, `tracked` 时 `class_id` 为轨迹ID
+    /// - `classes`: 每个框的检测类别 (见 `models::vehicle::source_classes`), 可为空
+    /// - `show_conf`: 显示阈值 (控制面板的置信度阈值)
+    pub fn update(
+        &mut self,
+        bboxes: &[BBox],
+        classes: &[Option<u32>],
+        tracked: bool,
+        show_conf: f32,
+    ) -> Vec<bool> {
+        self.frame += 1;
+        if !self.config.enabled {
+            return vec![true; bboxes.len()];
+        }
+        if !tracked {
+            return bboxes.iter().map(|b| b.confidence >= show_conf).collect();
+        }
+
+        let frame = self.frame;
+        let visible = bboxes
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                let state = self.tracks.entry(b.class_id).or_default();
+                let consecutive = state.last_frame + 1 == frame;
+                state.last_frame = frame;
+                state.class = classes.get(i).copied().flatten().or(state.class);
+                let params = self.config.params(state.class);
+
+                if state.shown {
+                    state.shown = b.confidence >= show_conf * params.keep_ratio.clamp(0.0, 1.0);
+                    if !state.shown {
+                        state.hits = 0;
+                    }
+                } else {
+                    let hits = if consecutive { state.hits } else { 0 };
+                    state.hits = if b.confidence >= show_conf {
+                        hits + 1
+                    } else {
+                        0
+                    };
+                    state.shown = state.hits >= params.confirm_frames.max(1);
+                }
+                state.shown
+            })
+            .collect();
+
+        self.tracks
+            .retain(|_, s| frame - s.last_frame <= TRACK_EXPIRY_FRAMES);
+        visible
+    }
+}
+
+/// 阈值预览的虚影: 置信度在 [下限, 显示阈值) 内且未被显示的检测
+///
+/// `detections` 为跟踪前的检测框, `shown` 为最终显示的框 (含平滑保持的轨迹);
+/// 与显示框重叠的检测属于同一目标, 不计入虚影
+pub fn ghost_boxes(detections: &[BBox], shown: &[BBox], floor: f32, show_conf: f32) -> Vec<BBox> {
+    detections
+        .iter()
+        .filter(|d| (floor..show_conf).contains(&d.confidence))
+        .filter(|d| shown.iter().all(|s| s.iou(d) < GHOST_OVERLAP_IOU))
+        .cloned()
+        .collect()
+}
+
+/// 按显示标记保留与框一一对应的数据 (长度不一致的可选数据保持原样)
+pub fn retain_visible<T>(items: &mut Vec<T>, visible: &[bool]) {
+    if items.len() != visible.len() {
+        return;
+    }
+    let mut flags = visible.iter();
+    items.retain(|_| *flags.next().unwrap_or(&true));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(id: u32, confidence: f32) -> BBox {
+        BBox {
+            x1: 0.0,
+            y1: 0.0,
+            x2: 10.0,
+            y2: 10.0,
+            confidence,
+            class_id: id,
+        }
+    }
+
+    /// 新轨迹连续两帧达到显示阈值才显示, 之后降到保持阈值以上仍显示, 低于保持阈值隐藏
+    #[test]
+    fn test_confirmation_and_hysteresis() {
+        let mut smoother = DisplaySmoother::new(SmoothingConfig::default());
+        let mut step = |conf: f32| smoother.update(&[track(7, conf)], &[Some(0)], true, 0.4)[0];
+
+        assert!(!step(0.5), "首帧尚未确认");
+        assert!(step(0.5), "连续两帧后显示");
+        assert!(step(0.25), "高于保持阈值 0.2, 继续显示");
+        assert!(!step(0.15), "低于保持阈值, 隐藏");
+        assert!(!step(0.3), "隐藏后需重新确认");
+        assert!(!step(0.45));
+        assert!(step(0.45));
+    }
+
+    /// 按类别覆盖参数, 推理阈值取最低的保持阈值; 未跟踪时只按显示阈值过滤
+    #[test]
+    fn test_per_class_params() {
+        let config: SmoothingConfig = serde_json::from_str(
+            r#"{"confirm_frames": 2, "classes": {"2": {"keep_ratio": 0.25, "confirm_frames": 1}}}"#,
+        )
+        .unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.params(Some(2)).confirm_frames, 1);
+        assert_eq!(config.params(None), ClassSmoothing::default());
+        assert_eq!(config.inference_conf(0.4), 0.1);
+
+        let mut smoother = DisplaySmoother::new(config);
+        let boxes = [track(1, 0.5), track(2, 0.5)];
+        let visible = smoother.update(&boxes, &[Some(0), Some(2)], true, 0.4);
+        assert_eq!(visible, vec![false, true]);
+
+        let mut smoother = DisplaySmoother::default();
+        let visible = smoother.update(&[track(0, 0.3), track(0, 0.5)], &[], false, 0.4);
+        assert_eq!(visible, vec![false, true]);
+
+        let mut items = vec!["a", "b"];
+        retain_visible(&mut items, &visible);
+        assert_eq!(items, vec!["b"]);
+    }
+
+    /// 已显示轨迹短暂缺失后重新出现仍显示, 超过保留帧数后需重新确认
+    #[test]
+    fn test_gap_and_expiry() {
+        let mut smoother = DisplaySmoother::default();
+        smoother.update(&[track(3, 0.5)], &[], true, 0.4);
+        assert!(smoother.update(&[track(3, 0.5)], &[], true, 0.4)[0]);
+        for _ in 0..5 {
+            smoother.update(&[], &[], true, 0.4);
+        }
+        assert!(smoother.update(&[track(3, 0.3)], &[], true, 0.4)[0]);
+
+        for _ in 0..=TRACK_EXPIRY_FRAMES {
+            smoother.update(&[], &[], true, 0.4);
+        }
+        assert!(!smoother.update(&[track(3, 0.5)], &[], true, 0.4)[0]);
+    }
+
+    /// 虚影: 只取下限到显示阈值之间的检测, 与显示框重叠的 (平滑保持的轨迹) 不计入
+    #[test]
+    fn test_ghost_boxes() {
+        let shifted = |x: f32, confidence: f32| BBox {
+            x1: x,
+            x2: x + 10.0,
+            ..track(0, confidence)
+        };
+        let detections = [
+            shifted(0.0, 0.3),  // 与显示框重叠
+            shifted(50.0, 0.3), // 虚影
+            shifted(80.0, 0.05),
+            shifted(100.0, 0.6),
+        ];
+        let shown = [shifted(1.0, 0.3), shifted(100.0, 0.6)];
+        let ghosts = ghost_boxes(&detections, &shown, 0.1, 0.4);
+        assert_eq!(ghosts.len(), 1);
+        assert_eq!(ghosts[0].x1, 50.0);
+        assert_eq!(ghost_boxes(&detections, &[], 0.1, 0.4).len(), 2);
+    }
+}
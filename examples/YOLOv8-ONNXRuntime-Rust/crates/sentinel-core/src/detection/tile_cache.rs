@@ -0,0 +1,630 @@
+//! 静止机位的分块局部推理 (背景缓存)
+//!
+//! 固定摄像头的画面大部分时间没有变化, 空场景下每帧整帧推理是浪费. 启用后 (tile_inference.json):
+//! - 背景模型: 画面按 `cols`×`rows` 分块, 每隔 `sample_step` 像素采样亮度, 按 `learning_rate`
+//!   指数滑动平均为背景 (停下的目标逐渐并入背景, 其检测框保留在缓存中)
+//! - 运动块: 与背景亮度差超过 `pixel_threshold` 的采样点占比超过 `motion_fraction` 的块
+//! - 没有运动块: 直接复用缓存的检测结果, 不推理
+//! - 有运动块: 相连的运动块合并为矩形并向外扩展 `margin` 像素, 每个矩形裁剪后单独推理;
+//!   缓存中中心落在推理区域之外的框保留, 与新结果合并后做 NMS
+//! - 运动块占比超过 `max_motion_ratio`、矩形多于 `max_regions`、距上次整帧推理超过
+//!   `refresh_secs` 或参数/模型变化后, 整帧推理并重建缓存
+//!
+//! 只作用于 CPU 推理路径 (CUDA 端到端管线照常整帧推理). 复用与局部推理的结果不含分割掩码
+//!
+//! ```json
+//! { "cols": 8, "rows": 6, "sample_step": 4, "pixel_threshold": 20, "motion_fraction": 0.02,
+//!   "learning_rate": 0.05, "margin": 32, "max_motion_ratio": 0.4, "max_regions": 4, "refresh_secs": 5 }
+//! ```
+
+use std::fs;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::PreprocessSpec;
+use crate::{non_max_suppression, Bbox, DetectionResult, Point2};
+
+/// 默认配置文件路径
+pub const TILE_CONFIG_PATH: &str = "tile_inference.json";
+
+/// 合并缓存与新结果时的 NMS 阈值
+const STITCH_IOU: f32 = 0.5;
+
+/// 进程内的分块推理配置, 未启用时为 None
+static CONFIG: OnceLock<TileConfig> = OnceLock::new();
+
+/// 分块推理配置 (tile_inference.json)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TileConfig {
+    pub cols: u32,
+    pub rows: u32,
+    pub sample_step: u32,
+    pub pixel_threshold: f32, // 亮度差 (0~255)
+    pub motion_fraction: f32, // 块内超过亮度差的采样点占比
+    pub learning_rate: f32,
+    pub margin: u32, // 推理区域向外扩展的像素 (覆盖跨块的目标)
+    pub max_motion_ratio: f32,
+    pub max_regions: usize,
+    pub refresh_secs: f32,
+}
+
+impl Default for TileConfig {
+    fn default() -> Self {
+        Self {
+            cols: 8,
+            rows: 6,
+            sample_step: 4,
+            pixel_threshold: 20.0,
+            motion_fraction: 0.02,
+            learning_rate: 0.05,
+            margin: 32,
+            max_motion_ratio: 0.4,
+            max_regions: 4,
+            refresh_secs: 5.0,
+        }
+    }
+}
+
+impl TileConfig {
+    /// 从文件加载配置, 文件不存在或解析失败时为 None (每帧整帧推理)
+    pub fn load(path: &str) -> Option<Self> {
+        let json = fs::read_to_string(path).ok()?;
+        match serde_json::from_str::<Self>(&json) {
+            Ok(config) => {
+                println!(
+                    "✅ 分块推理配置已从 {} 加载 ({}×{} 块, 每 {}s 整帧刷新)",
+                    path, config.cols, config.rows, config.refresh_secs
+                );
+                Some(config)
+            }
+            Err(e) => {
+                eprintln!("⚠️  分块推理配置解析失败: {}, 不启用", e);
+                None
+            }
+        }
+    }
+}
+
+/// 启用分块推理 (之后创建的检测线程生效)
+pub fn install(config: TileConfig) {
+    let _ = CONFIG.set(config);
+}
+
+/// 已启用的配置, 未启用时为 None
+pub fn config() -> Option<&'static TileConfig> {
+    CONFIG.get()
+}
+
+/// 画面上的矩形 (像素)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TileRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl TileRect {
+    fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x as f32
+            && y >= self.y as f32
+            && x < (self.x + self.width) as f32
+            && y < (self.y + self.height) as f32
+    }
+
+    fn overlaps(&self, other: &TileRect) -> bool {
+        self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
+    }
+
+    fn union(&self, other: &TileRect) -> TileRect {
+        let (x, y) = (self.x.min(other.x), self.y.min(other.y));
+        TileRect {
+            x,
+            y,
+            width: (self.x + self.width).max(other.x + other.width) - x,
+            height: (self.y + self.height).max(other.y + other.height) - y,
+        }
+    }
+}
+
+/// 分块的亮度背景
+#[derive(Clone, Debug)]
+pub struct BackgroundModel {
+    width: u32,
+    height: u32,
+    samples: Vec<f32>, // 采样点的背景亮度 (按行优先)
+}
+
+impl BackgroundModel {
+    /// 以第一帧为初始背景
+    pub fn new(config: &TileConfig, rgba: &[u8], width: u32, height: u32) -> Self {
+        let samples = sample_points(config, width, height)
+            .map(|(x, y, _)| luma_at(rgba, width, x, y))
+            .collect();
+        Self {
+            width,
+            height,
+            samples,
+        }
+    }
+
+    /// 更新背景并返回各块是否有运动 (按行优先, cols×rows)
+    pub fn update(&mut self, config: &TileConfig, rgba: &[u8]) -> Vec<bool> {
+        let tiles = (config.cols * config.rows) as usize;
+        let mut moving = vec![0u32; tiles];
+        let mut total = vec![0u32; tiles];
+        let rate = config.learning_rate.clamp(0.0, 1.0);
+        for ((x, y, tile), background) in
+            sample_points(config, self.width, self.height).zip(self.samples.iter_mut())
+        {
+            let luma = luma_at(rgba, self.width, x, y);
+            total[tile] += 1;
+            if (luma - *background).abs() > config.pixel_threshold {
+                moving[tile] += 1;
+            }
+            *background += (luma - *background) * rate;
+        }
+        moving
+            .iter()
+            .zip(&total)
+            .map(|(&m, &t)| t > 0 && m as f32 > t as f32 * config.motion_fraction)
+            .collect()
+    }
+}
+
+/// 采样点 (x, y, 所在块下标)
+fn sample_points(
+    config: &TileConfig,
+    width: u32,
+    height: u32,
+) -> impl Iterator<Item = (u32, u32, usize)> + '_ {
+    let step = config.sample_step.max(1) as usize;
+    let (cols, rows) = (config.cols.max(1), config.rows.max(1));
+    (0..height).step_by(step).flat_map(move |y| {
+        let row = (y * rows / height) as usize;
+        (0..width).step_by(step).map(move |x| {
+            let col = (x * cols / width) as usize;
+            (x, y, row * cols as usize + col)
+        })
+    })
+}
+
+fn luma_at(rgba: &[u8], width: u32, x: u32, y: u32) -> f32 {
+    let i = (y as usize * width as usize + x as usize) * 4;
+    PreprocessSpec::luma([rgba[i], rgba[i + 1], rgba[i + 2]]) as f32
+}
+
+/// 运动块的推理区域: 相连 (上下左右) 的运动块合并为矩形, 向外扩展 margin 后重叠的再合并
+pub fn motion_regions(
+    motion: &[bool],
+    config: &TileConfig,
+    width: u32,
+    height: u32,
+) -> Vec<TileRect> {
+    let (cols, rows) = (config.cols.max(1) as usize, config.rows.max(1) as usize);
+    let mut seen = vec![false; motion.len()];
+    let mut regions: Vec<TileRect> = Vec::new();
+    for start in 0..motion.len() {
+        if !motion[start] || seen[start] {
+            continue;
+        }
+        // 连通块的行列范围
+        let (mut c0, mut c1, mut r0, mut r1) = (cols, 0, rows, 0);
+        let mut stack = vec![start];
+        seen[start] = true;
+        while let Some(i) = stack.pop() {
+            let (r, c) = (i / cols, i % cols);
+            (c0, c1, r0, r1) = (c0.min(c), c1.max(c), r0.min(r), r1.max(r));
+            let neighbours = [
+                (r > 0).then(|| i - cols),
+                (r + 1 < rows).then(|| i + cols),
+                (c > 0).then(|| i - 1),
+                (c + 1 < cols).then(|| i + 1),
+            ];
+            for n in neighbours.into_iter().flatten() {
+                if motion[n] && !seen[n] {
+                    seen[n] = true;
+                    stack.push(n);
+                }
+            }
+        }
+        let edge = |i: usize, n: usize, size: u32| (i as u64 * size as u64 / n as u64) as u32;
+        let (x0, x1) = (edge(c0, cols, width), edge(c1 + 1, cols, width));
+        let (y0, y1) = (edge(r0, rows, height), edge(r1 + 1, rows, height));
+        let (x0, y0) = (
+            x0.saturating_sub(config.margin),
+            y0.saturating_sub(config.margin),
+        );
+        let (x1, y1) = (
+            (x1 + config.margin).min(width),
+            (y1 + config.margin).min(height),
+        );
+        let mut rect = TileRect {
+            x: x0,
+            y: y0,
+            width: x1 - x0,
+            height: y1 - y0,
+        };
+        // 扩展后重叠的区域合并, 避免同一目标推理两次
+        while let Some(i) = regions.iter().position(|r| r.overlaps(&rect)) {
+            rect = rect.union(&regions.swap_remove(i));
+        }
+        regions.push(rect);
+    }
+    regions
+}
+
+/// 裁剪 RGBA 画面
+pub fn crop_rgba(rgba: &[u8], width: u32, rect: &TileRect) -> Vec<u8> {
+    let stride = width as usize * 4;
+    let mut out = Vec::with_capacity(rect.width as usize * rect.height as usize * 4);
+    for y in rect.y..rect.y + rect.height {
+        let start = y as usize * stride + rect.x as usize * 4;
+        out.extend_from_slice(&rgba[start..start + rect.width as usize * 4]);
+    }
+    out
+}
+
+/// 画面坐标的检测框 (缓存与合并用)
+#[derive(Clone, Debug, PartialEq)]
+pub struct CachedBox {
+    pub bbox: Bbox,
+    pub keypoints: Option<Vec<Point2>>,
+}
+
+/// 模型输出 (推理输入坐标) → 画面坐标: 乘以 scale 后平移到区域原点
+pub fn from_model_results(
+    results: &[DetectionResult],
+    scale: (f32, f32),
+    origin: (f32, f32),
+) -> Vec<CachedBox> {
+    let (sx, sy) = scale;
+    let (ox, oy) = origin;
+    let map = |p: &Point2| Point2::new_with_conf(p.x() * sx + ox, p.y() * sy + oy, p.confidence());
+    let mut boxes = Vec::new();
+    for result in results {
+        let Some(bboxes) = result.bboxes() else {
+            continue;
+        };
+        for (i, b) in bboxes.iter().enumerate() {
+            boxes.push(CachedBox {
+                bbox: Bbox::new(
+                    b.xmin() * sx + ox,
+                    b.ymin() * sy + oy,
+                    b.width() * sx,
+                    b.height() * sy,
+                    b.id(),
+                    b.confidence(),
+                ),
+                keypoints: result
+                    .keypoints()
+                    .and_then(|k| k.get(i))
+                    .map(|k| k.iter().map(map).collect()),
+            });
+        }
+    }
+    boxes
+}
+
+/// 画面坐标 → 整帧推理坐标的模型输出 (除以 scale); 所有框都有关键点时才输出关键点
+pub fn to_model_result(boxes: &[CachedBox], scale: (f32, f32)) -> DetectionResult {
+    let (sx, sy) = (scale.0.max(f32::EPSILON), scale.1.max(f32::EPSILON));
+    let bboxes = boxes
+        .iter()
+        .map(|c| {
+            let b = &c.bbox;
+            Bbox::new(
+                b.xmin() / sx,
+                b.ymin() / sy,
+                b.width() / sx,
+                b.height() / sy,
+                b.id(),
+                b.confidence(),
+            )
+        })
+        .collect();
+    let keypoints = boxes
+        .iter()
+        .map(|c| {
+            c.keypoints.as_ref().map(|k| {
+                k.iter()
+                    .map(|p| Point2::new_with_conf(p.x() / sx, p.y() / sy, p.confidence()))
+                    .collect()
+            })
+        })
+        .collect::<Option<Vec<Vec<Point2>>>>()
+        .filter(|k| !k.is_empty());
+    DetectionResult::new(None, Some(bboxes), keypoints, None)
+}
+
+/// 本帧的推理方式
+#[derive(Clone, Debug, PartialEq)]
+pub enum TilePlan {
+    Full,                   // 整帧推理
+    Reuse,                  // 没有运动, 复用缓存
+    Regions(Vec<TileRect>), // 只推理这些区域
+}
+
+/// 分块推理统计 (每次整帧刷新时打印)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TileStats {
+    pub full: u64,
+    pub partial: u64,
+    pub reused: u64,
+}
+
+/// 一个逻辑流的背景模型与检测缓存
+#[derive(Debug)]
+pub struct TileCache {
+    config: TileConfig,
+    background: Option<BackgroundModel>,
+    cached: Option<Vec<CachedBox>>,
+    last_full: Option<Instant>,
+    stats: TileStats,
+}
+
+impl TileCache {
+    pub fn new(config: TileConfig) -> Self {
+        Self {
+            config,
+            background: None,
+            cached: None,
+            last_full: None,
+            stats: TileStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> TileStats {
+        self.stats
+    }
+
+    /// 参数/模型变化: 缓存的检测结果作废, 下一帧整帧推理
+    pub fn invalidate(&mut self) {
+        self.cached = None;
+    }
+
+    /// 按运动决定本帧的推理方式 (同时更新背景)
+    pub fn plan(&mut self, rgba: &[u8], width: u32, height: u32, now: Instant) -> TilePlan {
+        let config = &self.config;
+        let background = match &mut self.background {
+            Some(bg) if (bg.width, bg.height) == (width, height) => bg,
+            _ => {
+                // 首帧或分辨率变化: 重建背景
+                self.background = Some(BackgroundModel::new(config, rgba, width, height));
+                self.cached = None;
+                return TilePlan::Full;
+            }
+        };
+        let motion = background.update(config, rgba);
+        let refresh = Duration::from_secs_f32(config.refresh_secs.max(0.0));
+        let due = self
+            .last_full
+            .is_none_or(|last| now.duration_since(last) >= refresh);
+        if self.cached.is_none() || due {
+            return TilePlan::Full;
+        }
+        let moving = motion.iter().filter(|&&m| m).count();
+        if moving == 0 {
+            self.stats.reused += 1;
+            return TilePlan::Reuse;
+        }
+        if moving as f32 > motion.len() as f32 * config.max_motion_ratio {
+            return TilePlan::Full;
+        }
+        let regions = motion_regions(&motion, config, width, height);
+        if regions.len() > config.max_regions {
+            return TilePlan::Full;
+        }
+        TilePlan::Regions(regions)
+    }
+
+    /// 整帧推理的结果成为新的缓存
+    pub fn store_full(&mut self, boxes: Vec<CachedBox>, now: Instant) {
+        self.stats.full += 1;
+        if self.stats.full.is_multiple_of(100) {
+            let TileStats {
+                full,
+                partial,
+                reused,
+            } = self.stats;
+            println!(
+                "🧩 分块推理: 整帧{} | 局部{} | 复用{}",
+                full, partial, reused
+            );
+        }
+        self.cached = Some(boxes);
+        self.last_full = Some(now);
+    }
+
+    /// 缓存的检测结果 (复用时)
+    pub fn cached(&self) -> &[CachedBox] {
+        self.cached.as_deref().unwrap_or_default()
+    }
+
+    /// 合并局部推理结果: 推理区域外的缓存框保留, 与新结果一起 NMS, 结果成为新的缓存
+    pub fn stitch(&mut self, regions: &[TileRect], fresh: Vec<CachedBox>) -> Vec<CachedBox> {
+        self.stats.partial += 1;
+        let kept = self.cached().iter().filter(|c| {
+            let center = c.bbox.cxcy();
+            !regions.iter().any(|r| r.contains(center.x(), center.y()))
+        });
+        let mut xs: Vec<(Bbox, Option<Vec<Point2>>, Option<Vec<f32>>)> = kept
+            .cloned()
+            .chain(fresh)
+            .map(|c| (c.bbox, c.keypoints, None))
+            .collect();
+        non_max_suppression(&mut xs, STITCH_IOU);
+        let boxes: Vec<CachedBox> = xs
+            .into_iter()
+            .map(|(bbox, keypoints, _)| CachedBox { bbox, keypoints })
+            .collect();
+        self.cached = Some(boxes.clone());
+        boxes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> TileConfig {
+        TileConfig {
+            cols: 4,
+            rows: 2,
+            sample_step: 2,
+            margin: 0,
+            ..Default::default()
+        }
+    }
+
+    /// 80×40 灰色画面, 在 (x, y, w, h) 处画白块
+    fn frame(rects: &[(u32, u32, u32, u32)]) -> Vec<u8> {
+        let mut rgba = vec![100u8; 80 * 40 * 4];
+        for &(x0, y0, w, h) in rects {
+            for y in y0..y0 + h {
+                for x in x0..x0 + w {
+                    let i = (y as usize * 80 + x as usize) * 4;
+                    rgba[i..i + 3].copy_from_slice(&[255, 255, 255]);
+                }
+            }
+        }
+        rgba
+    }
+
+    fn cached(x: f32, y: f32, confidence: f32) -> CachedBox {
+        CachedBox {
+            bbox: Bbox::new(x, y, 10.0, 10.0, 0, confidence),
+            keypoints: None,
+        }
+    }
+
+    /// 只有亮度变化的块判为运动, 相连的运动块合并为一个区域
+    #[test]
+    fn test_motion_regions() {
+        let config = config();
+        let mut background = BackgroundModel::new(&config, &frame(&[]), 80, 40);
+        let motion = background.update(&config, &frame(&[(2, 2, 6, 6), (20, 22, 10, 10)]));
+        // 4×2 块, 每块 20×20: 左上块与第二行第二块
+        assert_eq!(
+            motion,
+            vec![true, false, false, false, false, true, false, false]
+        );
+        let regions = motion_regions(&motion, &config, 80, 40);
+        assert_eq!(regions.len(), 2);
+
+        let motion = [true, true, false, false, false, true, false, false];
+        let regions = motion_regions(&motion, &config, 80, 40);
+        assert_eq!(
+            regions,
+            vec![TileRect {
+                x: 0,
+                y: 0,
+                width: 40,
+                height: 40
+            }]
+        );
+        // 扩展后重叠的两个区域合并
+        let separate = [true, false, true, false, false, false, false, false];
+        let merged = TileConfig {
+            margin: 12,
+            ..config.clone()
+        };
+        assert_eq!(motion_regions(&separate, &config, 80, 40).len(), 2);
+        assert_eq!(motion_regions(&separate, &merged, 80, 40).len(), 1);
+    }
+
+    /// 首帧与到期时整帧, 静止时复用, 局部运动时只推理运动区域
+    #[test]
+    fn test_plan() {
+        let start = Instant::now();
+        let mut cache = TileCache::new(config());
+        let still = frame(&[]);
+        assert_eq!(cache.plan(&still, 80, 40, start), TilePlan::Full);
+        cache.store_full(vec![cached(50.0, 5.0, 0.9)], start);
+
+        let at = |ms| start + Duration::from_millis(ms);
+        assert_eq!(cache.plan(&still, 80, 40, at(40)), TilePlan::Reuse);
+        assert_eq!(cache.cached().len(), 1);
+        let plan = cache.plan(&frame(&[(2, 2, 6, 6)]), 80, 40, at(80));
+        assert!(matches!(plan, TilePlan::Regions(ref r) if r.len() == 1));
+        assert_eq!(
+            cache.plan(&frame(&[(0, 0, 80, 40)]), 80, 40, at(120)),
+            TilePlan::Full
+        );
+        assert_eq!(cache.plan(&still, 80, 40, at(6000)), TilePlan::Full);
+        cache.invalidate();
+        assert_eq!(cache.plan(&still, 80, 40, at(6040)), TilePlan::Full);
+        assert_eq!(cache.plan(&still, 40, 40, at(6080)), TilePlan::Full);
+    }
+
+    /// 推理区域外的缓存框保留, 区域内的由新结果替代, 重叠框经 NMS 合并
+    #[test]
+    fn test_stitch() {
+        let mut cache = TileCache::new(config());
+        cache.store_full(
+            vec![cached(5.0, 5.0, 0.8), cached(50.0, 25.0, 0.9)],
+            Instant::now(),
+        );
+        let region = TileRect {
+            x: 0,
+            y: 0,
+            width: 20,
+            height: 20,
+        };
+        let fresh = vec![cached(6.0, 6.0, 0.7), cached(7.0, 6.0, 0.6)];
+        let boxes = cache.stitch(&[region], fresh);
+        assert_eq!(boxes.len(), 2);
+        assert_eq!(boxes[0].bbox.xmin(), 50.0);
+        assert_eq!(boxes[1].bbox.xmin(), 6.0);
+        assert_eq!(cache.cached().len(), 2);
+    }
+
+    /// 模型输出与画面坐标互相换算, 关键点随框换算
+    #[test]
+    fn test_coordinate_round_trip() {
+        let result = DetectionResult::new(
+            None,
+            Some(vec![Bbox::new(10.0, 20.0, 30.0, 40.0, 2, 0.5)]),
+            Some(vec![vec![Point2::new_with_conf(15.0, 25.0, 0.9)]]),
+            None,
+        );
+        let boxes = from_model_results(&[result], (2.0, 0.5), (100.0, 10.0));
+        let b = &boxes[0].bbox;
+        assert_eq!(
+            (b.xmin(), b.ymin(), b.width(), b.height()),
+            (120.0, 20.0, 60.0, 20.0)
+        );
+        assert_eq!(boxes[0].keypoints.as_ref().unwrap()[0].x(), 130.0);
+
+        let back = to_model_result(&boxes, (4.0, 2.0));
+        let b = &back.bboxes().unwrap()[0];
+        assert_eq!(
+            (b.xmin(), b.ymin(), b.width(), b.height()),
+            (30.0, 10.0, 15.0, 10.0)
+        );
+        assert_eq!(back.keypoints().unwrap()[0][0].y(), 11.25);
+        assert!(to_model_result(&[cached(0.0, 0.0, 0.5)], (1.0, 1.0))
+            .keypoints()
+            .is_none());
+        assert_eq!(
+            crop_rgba(
+                &frame(&[]),
+                80,
+                &TileRect {
+                    x: 2,
+                    y: 3,
+                    width: 4,
+                    height: 5
+                }
+            )
+            .len(),
+            80
+        );
+    }
+}
@@ -0,0 +1,248 @@
+//! 帧延迟追踪 (Frame latency tracing)
+//!
+//! 每帧携带 [`FrameTrace`] 依次经过 解码 → 检测 → 渲染, 各阶段打上时间戳;
+//! 渲染线程提交叠加框时收尾并计入 [`LatencyStats`], 用于定位"画面到叠加框"的延迟来源.
+//! 所有时间戳来自同一进程的单调时钟, 跨线程可直接相减; 另记采集时的 UTC 墙上时钟供事件与导出使用
+
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+
+/// 单帧各阶段时间戳
+#[derive(Clone, Copy, Debug)]
+pub struct FrameTrace {
+    pub decode_ts: Instant,           // 解码器输出帧 (采集时间戳)
+    pub resize_ts: Option<Instant>,   // 检测线程取出帧, 开始缩放/预处理
+    pub infer_start: Option<Instant>, // 预处理完成, 开始推理
+    pub infer_end: Option<Instant>,   // 推理完成
+    pub publish_ts: Option<Instant>,  // 检测结果发布 (后处理/跟踪/深度/CLIP 之后)
+    pub render_ts: Option<Instant>,   // 渲染线程提交叠加框
+    pub captured_at: DateTime<Utc>,   // 采集时的墙上时钟 (UTC, 事件/导出/元数据时间)
+}
+
+impl Default for FrameTrace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameTrace {
+    /// 以当前时刻作为解码时间戳
+    pub fn new() -> Self {
+        Self {
+            decode_ts: Instant::now(),
+            resize_ts: None,
+            infer_start: None,
+            infer_end: None,
+            publish_ts: None,
+            render_ts: None,
+            captured_at: Utc::now(),
+        }
+    }
+
+    /// 某阶段耗时 (ms), 阶段任一端未打时间戳时为 None (如检测被禁用)
+    pub fn stage_ms(&self, stage: LatencyStage) -> Option<f64> {
+        let decode = Some(self.decode_ts);
+        let (start, end) = match stage {
+            LatencyStage::Queue => (decode, self.resize_ts),
+            LatencyStage::Resize => (self.resize_ts, self.infer_start),
+            LatencyStage::Inference => (self.infer_start, self.infer_end),
+            LatencyStage::Postprocess => (self.infer_end, self.publish_ts),
+            LatencyStage::Render => (self.publish_ts, self.render_ts),
+            LatencyStage::Total => (decode, self.render_ts),
+        };
+        Some(end?.saturating_duration_since(start?).as_secs_f64() * 1000.0)
+    }
+}
+
+/// 延迟分解的阶段
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LatencyStage {
+    Queue,       // 解码 → 检测线程取帧
+    Resize,      // 缩放/预处理
+    Inference,   // 模型推理
+    Postprocess, // 后处理 + 跟踪 + 深度/CLIP → 发布
+    Render,      // 发布 → 渲染线程提交叠加框
+    Total,       // 端到端 (解码 → 叠加框)
+}
+
+impl LatencyStage {
+    pub const ALL: [LatencyStage; 6] = [
+        LatencyStage::Queue,
+        LatencyStage::Resize,
+        LatencyStage::Inference,
+        LatencyStage::Postprocess,
+        LatencyStage::Render,
+        LatencyStage::Total,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LatencyStage::Queue => "排队",
+            LatencyStage::Resize => "缩放",
+            LatencyStage::Inference => "推理",
+            LatencyStage::Postprocess => "后处理",
+            LatencyStage::Render => "渲染",
+            LatencyStage::Total => "端到端",
+        }
+    }
+
+    /// 指标导出时的标签值
+    pub fn key(&self) -> &'static str {
+        match self {
+            LatencyStage::Queue => "queue",
+            LatencyStage::Resize => "resize",
+            LatencyStage::Inference => "inference",
+            LatencyStage::Postprocess => "postprocess",
+            LatencyStage::Render => "render",
+            LatencyStage::Total => "total",
+        }
+    }
+}
+
+/// 单阶段统计 (ms)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StageSummary {
+    pub mean_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+/// 最近 N 帧的分阶段延迟统计
+pub struct LatencyStats {
+    window: usize,
+    traces: VecDeque<FrameTrace>,
+}
+
+impl LatencyStats {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            traces: VecDeque::with_capacity(window),
+        }
+    }
+
+    /// 记录一帧已完成的追踪
+    pub fn record(&mut self, trace: &FrameTrace) {
+        if self.traces.len() == self.window {
+            self.traces.pop_front();
+        }
+        self.traces.push_back(*trace);
+    }
+
+    pub fn len(&self) -> usize {
+        self.traces.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.traces.is_empty()
+    }
+
+    /// 某阶段的均值/P95/最大值, 窗口内无该阶段数据时为 None
+    pub fn summary(&self, stage: LatencyStage) -> Option<StageSummary> {
+        let mut values: Vec<f64> = self
+            .traces
+            .iter()
+            .filter_map(|t| t.stage_ms(stage))
+            .collect();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(|a, b| a.total_cmp(b));
+        let p95_index = ((values.len() as f64 * 0.95).ceil() as usize).saturating_sub(1);
+        Some(StageSummary {
+            mean_ms: values.iter().sum::<f64>() / values.len() as f64,
+            p95_ms: values[p95_index],
+            max_ms: values[values.len() - 1],
+        })
+    }
+
+    /// 全部阶段的统计 (控制面板显示)
+    pub fn report(&self) -> Vec<(LatencyStage, StageSummary)> {
+        LatencyStage::ALL
+            .iter()
+            .filter_map(|&stage| self.summary(stage).map(|s| (stage, s)))
+            .collect()
+    }
+
+    /// Prometheus 文本格式 (node_exporter textfile collector 可直接采集)
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        out.push_str(
+            "# HELP sentinel_frame_latency_ms Per-stage frame latency over recent frames\n",
+        );
+        out.push_str("# TYPE sentinel_frame_latency_ms gauge\n");
+        for (stage, s) in self.report() {
+            for (stat, value) in [("mean", s.mean_ms), ("p95", s.p95_ms), ("max", s.max_ms)] {
+                let _ = writeln!(
+                    out,
+                    "sentinel_frame_latency_ms{{stage=\"{}\",stat=\"{}\"}} {:.3}",
+                    stage.key(),
+                    stat,
+                    value
+                );
+            }
+        }
+        out.push_str("# HELP sentinel_frame_latency_samples Frames in the latency window\n");
+        out.push_str("# TYPE sentinel_frame_latency_samples gauge\n");
+        let _ = writeln!(out, "sentinel_frame_latency_samples {}", self.len());
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn trace_with(offsets_ms: [u64; 5]) -> FrameTrace {
+        let mut trace = FrameTrace::new();
+        let at = |ms: u64| Some(trace.decode_ts + Duration::from_millis(ms));
+        trace.resize_ts = at(offsets_ms[0]);
+        trace.infer_start = at(offsets_ms[1]);
+        trace.infer_end = at(offsets_ms[2]);
+        trace.publish_ts = at(offsets_ms[3]);
+        trace.render_ts = at(offsets_ms[4]);
+        trace
+    }
+
+    /// 各阶段耗时由相邻时间戳相减, 缺失时间戳的阶段不计入
+    #[test]
+    fn test_stage_breakdown() {
+        let trace = trace_with([2, 5, 20, 24, 40]);
+        assert_eq!(trace.stage_ms(LatencyStage::Queue), Some(2.0));
+        assert_eq!(trace.stage_ms(LatencyStage::Resize), Some(3.0));
+        assert_eq!(trace.stage_ms(LatencyStage::Inference), Some(15.0));
+        assert_eq!(trace.stage_ms(LatencyStage::Postprocess), Some(4.0));
+        assert_eq!(trace.stage_ms(LatencyStage::Render), Some(16.0));
+        assert_eq!(trace.stage_ms(LatencyStage::Total), Some(40.0));
+
+        // 检测被禁用: 只有发布与渲染时间戳
+        let mut disabled = FrameTrace::new();
+        disabled.publish_ts = Some(disabled.decode_ts + Duration::from_millis(1));
+        assert_eq!(disabled.stage_ms(LatencyStage::Inference), None);
+        assert_eq!(disabled.stage_ms(LatencyStage::Total), None);
+    }
+
+    /// 滑动窗口统计与指标导出
+    #[test]
+    fn test_latency_stats_window() {
+        let mut stats = LatencyStats::new(20);
+        for i in 0..40u64 {
+            stats.record(&trace_with([1, 2, 2 + i, 3 + i, 4 + i]));
+        }
+        assert_eq!(stats.len(), 20);
+        // 窗口内推理耗时为 20..=39ms
+        let s = stats.summary(LatencyStage::Inference).unwrap();
+        assert_eq!(s.mean_ms, 29.5);
+        assert_eq!(s.p95_ms, 38.0);
+        assert_eq!(s.max_ms, 39.0);
+        assert_eq!(stats.report().len(), LatencyStage::ALL.len());
+
+        let text = stats.to_prometheus();
+        assert!(text.contains("sentinel_frame_latency_ms{stage=\"inference\",stat=\"max\"} 39.000"));
+        assert!(text.contains("sentinel_frame_latency_samples 20"));
+    }
+}
@@ -54,6 +54,62 @@ impl TrackedObject {
     }
 }
 
+// ========== 轨迹生命周期 ==========
+
+/// 轨迹生命周期参数 (运行时可通过 `ControlMessage::SetTrackerParams` 调整)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrackerParams {
+    /// 最大丢失帧数 (max age), 超过后删除轨迹
+    pub max_lost_frames: u32,
+    /// 确认轨迹所需的最小命中次数 (min hits)
+    pub min_hits: u32,
+    /// 匹配 IOU 阈值 (ByteTrack 高分轮 / DeepSort 级联匹配)
+    pub high_iou_threshold: f32,
+    /// 宽松匹配 IOU 阈值 (ByteTrack 低分救援轮 / DeepSort 未确认轨迹)
+    pub low_iou_threshold: f32,
+    /// 高分检测阈值 (仅 ByteTrack)
+    pub high_score_threshold: f32,
+    /// 低分检测阈值 (仅 ByteTrack)
+    pub low_score_threshold: f32,
+}
+
+impl TrackerParams {
+    /// ByteTrack 默认参数
+    pub fn bytetrack() -> Self {
+        Self {
+            max_lost_frames: 60,       // 60帧(约2秒) - 提高遮挡容忍度
+            min_hits: 1,               // 新轨迹立即输出
+            high_iou_threshold: 0.4,   // 高分匹配阈值 (提高避免误匹配)
+            low_iou_threshold: 0.3,    // 低分匹配阈值 (降低救援更宽松)
+            high_score_threshold: 0.4, // 高分阈值 (降低让更多框参与)
+            low_score_threshold: 0.1,  // 低分阈值 (救援用)
+        }
+    }
+
+    /// DeepSort 默认参数
+    pub fn deepsort() -> Self {
+        Self {
+            max_lost_frames: 90, // 90帧(约3秒) - DeepSort可利用ReID特征长时间恢复
+            min_hits: 2,         // 2帧确认,减少初期漂移
+            high_iou_threshold: 0.2,
+            low_iou_threshold: 0.05, // 未确认轨迹: IOU>0.05即可
+            high_score_threshold: 0.0,
+            low_score_threshold: 0.0,
+        }
+    }
+}
+
+/// 轨迹统计 (UI 实时显示)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TrackStats {
+    /// 本帧已匹配的轨迹数
+    pub active: usize,
+    /// 丢失中(仅预测, 尚未删除)的轨迹数
+    pub lost: usize,
+    /// 累计删除的轨迹数
+    pub removed: u64,
+}
+
 // ========== 卡尔曼滤波器 ==========
 
 /// 简化卡尔曼滤波器 (用于单个边界框的位置和尺寸平滑)
@@ -148,6 +204,12 @@ impl KalmanBoxFilter {
 
     /// 更新 (融合观测值,自适应噪声调整)
     pub fn update(&mut self, bbox: &BBox) {
+        self.update_with_noise(bbox, 1.0);
+    }
+
+    /// 按倍数放大观测噪声后更新 (不完整的观测, 如贴边的半身框)
+    pub fn update_with_noise(&mut self, bbox: &BBox, noise_scale: f32) {
+        let r = self.r * noise_scale.max(1.0);
         let cx = (bbox.x1 + bbox.x2) / 2.0;
         let cy = (bbox.y1 + bbox.y2) / 2.0;
         let w = bbox.x2 - bbox.x1;
@@ -165,13 +227,13 @@ impl KalmanBoxFilter {
         let residual_norm = (y[0] * y[0] + y[1] * y[1]).sqrt();
         let adaptive_r = if residual_norm < self.stationary_threshold {
             // 静止或小幅移动:降低观测噪声,更信任观测值
-            self.r * 0.3
+            r * 0.3
         } else if residual_norm < 10.0 {
             // 正常运动
-            self.r
+            r
         } else {
             // 大幅跳变:增加观测噪声,更信任预测值
-            self.r * 3.0
+            r * 3.0
         };
 
         // 卡尔曼增益: K = P / (P + R)
@@ -264,6 +326,37 @@ impl KalmanBoxFilter {
     }
 }
 
+/// 贴边的不完整观测
+///
+/// 触及画面边缘的检测只看到目标的一部分 (进出口摄像头常见的半身), 框的中心与尺寸
+/// 随目标进出画面而收缩, 按正常观测融合会拉偏卡尔曼的位置与速度估计
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PartialObservation {
+    pub width: f32, // 画面尺寸
+    pub height: f32,
+    pub margin: f32,      // 距边缘不超过该像素视为贴边
+    pub noise_scale: f32, // 贴边观测的观测噪声倍数
+}
+
+impl PartialObservation {
+    /// 框是否触及画面边缘
+    pub fn touches(&self, bbox: &BBox) -> bool {
+        bbox.x1 <= self.margin
+            || bbox.y1 <= self.margin
+            || bbox.x2 >= self.width - self.margin
+            || bbox.y2 >= self.height - self.margin
+    }
+
+    /// 观测噪声倍数: 贴边为 noise_scale, 否则为 1
+    pub fn noise_scale(&self, bbox: &BBox) -> f32 {
+        if self.touches(bbox) {
+            self.noise_scale
+        } else {
+            1.0
+        }
+    }
+}
+
 // ========== 跟踪器统一接口 ==========
 
 /// 多目标跟踪器 Trait
@@ -1,4 +1,19 @@
 use std::sync::Arc;
+
+use crossbeam_channel::Receiver;
+
+use crate::analytics::calibration::Homography;
+use crate::detection::bytetrack::AssociationWeights;
+use crate::detection::resolution::LadderConfig;
+use crate::detection::smoothing::SmoothingConfig;
+use crate::detection::trace::FrameTrace;
+use crate::detection::tracker::TrackerParams;
+use crate::utils::coords::ImageRect;
+use crate::utils::hdr::Yuv420Frame16;
+use crate::utils::resize::ResizeQuality;
+use crate::utils::yuv_preprocess::Yuv420Frame;
+use crate::xbus::{self, Subscription};
+use crate::DetectorError;
 /// RTSP检测系统数据结构定义
 /// Data structures for RTSP detection system
 
@@ -7,6 +22,9 @@ use std::sync::Arc;
 /// YOLOv8推理输入尺寸
 pub const INF_SIZE: u32 = 640;
 
+/// 默认检测的类别 (COCO: 0=person, 39=bottle, 41=cup, 56=chair, 62=tv, 63=laptop, 73=book, 76=scissors)
+pub const DETECT_CLASSES: &[u32] = &[0];
+
 // ========== 枚举类型 ==========
 
 /// 追踪器类型
@@ -29,6 +47,40 @@ pub struct BBox {
     pub class_id: u32,
 }
 
+impl BBox {
+    /// 原始画面坐标的框 (跟踪器与分析模块的输入)
+    pub fn from_rect(rect: ImageRect, confidence: f32, class_id: u32) -> Self {
+        Self {
+            x1: rect.x1,
+            y1: rect.y1,
+            x2: rect.x2,
+            y2: rect.y2,
+            confidence,
+            class_id,
+        }
+    }
+
+    /// 框在原始画面坐标中的矩形
+    pub fn rect(&self) -> ImageRect {
+        ImageRect::new(self.x1, self.y1, self.x2, self.y2)
+    }
+
+    /// 与另一个框的交并比
+    pub fn iou(&self, other: &BBox) -> f32 {
+        let w = (self.x2.min(other.x2) - self.x1.max(other.x1)).max(0.0);
+        let h = (self.y2.min(other.y2) - self.y1.max(other.y1)).max(0.0);
+        let inter = w * h;
+        let union = (self.x2 - self.x1) * (self.y2 - self.y1)
+            + (other.x2 - other.x1) * (other.y2 - other.y1)
+            - inter;
+        if union > 0.0 {
+            inter / union
+        } else {
+            0.0
+        }
+    }
+}
+
 /// 姿态关键点 (Pose keypoints)
 #[derive(Clone, Debug)]
 pub struct PoseKeypoints {
@@ -42,7 +94,16 @@ pub struct DecodedFrame {
     pub width: u32,
     pub height: u32,
     pub decode_fps: f64,
-    pub decoder_name: String, // 使用的解码器名称
+    pub decoder_name: String,          // 使用的解码器名称
+    pub yuv: Option<Arc<Yuv420Frame>>, // 原始YUV420平面, 检测线程可直接采样为NCHW张量
+    pub keyframe: bool,                // 是否为关键帧 (I/IDR)
+    pub trace: FrameTrace,             // 延迟追踪 (解码时打下采集时间戳)
+    pub view: u32,                     // 逻辑流编号: 0 为原始画面, 1..=N 为鱼眼虚拟视图
+    pub generation: usize,             // 解码器代数ID (切换输入源时递增, 检测线程据此重置缓存)
+    // 10-bit 源的 16 位平面 (未色调截断), 检测线程优先采样以避免色带
+    pub yuv16: Option<Arc<Yuv420Frame16>>,
+    #[cfg(feature = "cuda")]
+    pub device: Option<Arc<crate::cuda::DeviceNv12Frame>>, // NVDEC 设备帧, 检测线程走 CUDA 端到端管线
 }
 
 /// 缩放后的帧 (渲染线程 → 推理线程)
@@ -60,7 +121,43 @@ pub struct InferredFrame {
     pub inference_ms: f64,
 }
 
-/// 配置更新消息 (渲染线程 → 推理线程)
+/// 摄像头时钟漂移 (解码线程 → 界面), 每秒发布一次
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClockDrift {
+    pub generation: usize, // 解码器代数ID, 切换输入源后界面忽略旧解码器的报告
+    pub offset_ms: f64,    // 摄像头 RTP 时钟相对主机超前的毫秒数 (负数为落后)
+    pub ppm: f64,          // 平均走时偏差 (百万分之一)
+}
+
+/// 检测队列背压 (检测线程 → 解码线程), 仅在状态变化时发布
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Backpressure {
+    pub saturated: bool, // 队列已满, 新帧注定被丢弃
+}
+
+/// 检测线程错误状态 (检测线程 → 界面), 仅在状态变化时发布
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DetectorStatus {
+    pub view: u32,
+    pub error: Option<DetectorError>, // None 表示已恢复
+}
+
+/// 云台跟随请求 (轨迹详情面板 → 云台控制), 仅在跟随目标变化时发布
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FollowTrack {
+    pub view: u32,
+    pub track_id: Option<u32>, // None 表示停止跟随
+}
+
+/// 轨迹标记变化 (轨迹详情面板 → 日志/外部订阅方)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TrackFlagged {
+    pub view: u32,
+    pub track_id: u32,
+    pub flagged: bool,
+}
+
+/// 控制消息 (控制面板/嵌入宿主 → 检测线程), 经 xbus 以 [`ControlEvent`] 发布
 #[derive(Clone, Debug)]
 pub enum ControlMessage {
     UpdateParams {
@@ -71,6 +168,89 @@ pub enum ControlMessage {
     SwitchTracker(String),
     TogglePose(bool),
     ToggleDetection(bool),
+    /// CLIP 文本提示 (空字符串表示关闭检索)
+    SetTextPrompt(String),
+    /// ByteTrack 关联代价权重 (IOU / 掩码IOU / ReID)
+    SetAssociation(AssociationWeights),
+    /// 当前跟踪器的生命周期参数 (最大丢失帧数/最小命中/IOU与分数阈值)
+    SetTrackerParams(TrackerParams),
+    /// 显示平滑参数 (保持阈值比例/新目标确认帧数, 可按类别配置)
+    SetSmoothing(SmoothingConfig),
+    /// 阈值预览的虚影下限: 低于显示阈值、不低于下限的检测作为虚影返回 (None 表示关闭)
+    SetGhostFloor(Option<f32>),
+    /// 图像 → 地面单应矩阵 (None 表示清除地面标定)
+    SetGroundHomography(Option<Homography>),
+    /// 人群密度估计开关 (需加载密度模型)
+    ToggleCrowd(bool),
+    /// 检测的类别ID (空表示全部类别), 默认只检测人
+    SetClasses(Vec<u32>),
+    /// 开放词汇模型 (YOLO-World) 的类别词表, 不重新加载模型 (空表示恢复配置中的词表)
+    SetVocabulary(Vec<String>),
+    /// 推理输入尺寸, 下一次加载模型时生效 (控制面板随后发送 SwitchModel)
+    SetInputSize(u32),
+    /// 动态分辨率阶梯: 按目标大小自动升降推理输入尺寸 (需要动态轴模型)
+    SetResolutionLadder(LadderConfig),
+    /// 整帧缩放到推理输入的算法 (下一帧生效)
+    SetResizeQuality(ResizeQuality),
+    /// 布防/撤防 (布防调度器发送), 撤防期间不检测
+    SetArmed(bool),
+    /// 退出检测线程 (嵌入式调用方释放管线时发送)
+    Shutdown,
+}
+
+/// 控制消息在 xbus 上的载体
+#[derive(Clone, Debug)]
+pub struct ControlEvent {
+    pub view: Option<u32>, // 目标逻辑流, None 表示广播到所有检测线程
+    pub message: ControlMessage,
+}
+
+impl ControlMessage {
+    /// 广播到所有检测线程 (控制面板的参数对每个逻辑流都生效)
+    pub fn post(self) {
+        xbus::post(ControlEvent {
+            view: None,
+            message: self,
+        });
+    }
+
+    /// 发送到指定逻辑流的检测线程 (如某条 FFI 管线)
+    pub fn post_to(self, view: u32) {
+        xbus::post(ControlEvent {
+            view: Some(view),
+            message: self,
+        });
+    }
+}
+
+/// 订阅发往 `view` 的控制消息 (含广播) 并转入通道, 供检测线程在处理帧的间隙收取
+///
+/// 须在启动检测线程之前调用, 否则线程就绪前发布的初始参数会丢失.
+/// 通道不限长度, 控制消息不因检测线程繁忙而被丢弃; 订阅凭证应由检测线程持有
+pub fn control_receiver(view: u32) -> (Subscription, Receiver<ControlMessage>) {
+    let (tx, rx) = crossbeam_channel::unbounded();
+    let queue_tx = tx.clone();
+    let sub = xbus::subscribe::<ControlEvent, _>(move |event| {
+        if event.view.is_none() || event.view == Some(view) {
+            let _ = tx.send(event.message.clone());
+        }
+    })
+    .with_queue(&queue_tx);
+    (sub, rx)
+}
+
+/// 兼容桥: 将旧接口的控制通道转发到 xbus, 通道关闭后线程退出
+///
+/// 仍持有 `Sender<ControlMessage>` 的调用方无需修改即可控制检测线程
+pub fn bridge_control_channel(
+    rx: Receiver<ControlMessage>,
+    view: Option<u32>,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        for message in rx {
+            xbus::post(ControlEvent { view, message });
+        }
+    })
 }
 
 impl PoseKeypoints {
@@ -312,3 +492,40 @@ impl PoseKeypoints {
         sum / points.len() as f32
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// 控制消息按逻辑流投递: 广播每个检测线程都收到, 定向消息只到目标线程, 旧通道经桥接同样生效
+    #[test]
+    fn test_control_routing() {
+        let (_sub_a, rx_a) = control_receiver(0x2000_0001);
+        let (_sub_b, rx_b) = control_receiver(0x2000_0002);
+
+        ControlMessage::ToggleDetection(false).post();
+        ControlMessage::Shutdown.post_to(0x2000_0002);
+        assert!(matches!(
+            rx_a.try_recv(),
+            Ok(ControlMessage::ToggleDetection(false))
+        ));
+        assert!(rx_a.try_recv().is_err());
+        assert!(matches!(
+            rx_b.try_recv(),
+            Ok(ControlMessage::ToggleDetection(false))
+        ));
+        assert!(matches!(rx_b.try_recv(), Ok(ControlMessage::Shutdown)));
+
+        let (legacy_tx, legacy_rx) = crossbeam_channel::bounded(1);
+        let bridge = bridge_control_channel(legacy_rx, Some(0x2000_0001));
+        legacy_tx.send(ControlMessage::TogglePose(true)).unwrap();
+        drop(legacy_tx);
+        bridge.join().unwrap();
+        assert!(matches!(
+            rx_a.recv_timeout(Duration::from_secs(1)),
+            Ok(ControlMessage::TogglePose(true))
+        ));
+        assert!(rx_b.try_recv().is_err());
+    }
+}
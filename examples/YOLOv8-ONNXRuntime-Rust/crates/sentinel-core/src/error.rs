@@ -0,0 +1,54 @@
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+// 检测器错误类型
+// 模型构建与前后处理统一返回 DetectorError (经 anyhow 传递, 可 downcast 还原),
+// 检测线程据此分类后发布到 xbus, 由界面显示而不是 panic 结束线程
+
+use thiserror::Error;
+
+/// 检测器错误
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum DetectorError {
+    /// 模型文件/会话创建失败, 或缺少必要的元数据
+    #[error("模型加载失败: {0}")]
+    ModelLoad(String),
+    /// 输入输出形状与模型类型或配置不符
+    #[error("形状不匹配: {0}")]
+    ShapeMismatch(String),
+    /// 任务类型 (classify/detect/pose/segment) 不支持或无法确定
+    #[error("不支持的任务: {0}")]
+    UnsupportedTask(String),
+    /// 预处理/推理/后处理失败
+    #[error("处理失败: {0}")]
+    Process(String),
+}
+
+impl DetectorError {
+    /// 从 anyhow 错误还原类型化错误, 不是 DetectorError 时按 `fallback` 归类
+    pub fn from_anyhow(err: &anyhow::Error, fallback: fn(String) -> Self) -> Self {
+        match err.downcast_ref::<DetectorError>() {
+            Some(e) => e.clone(),
+            None => fallback(format!("{:#}", err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 经 anyhow 传递后仍能还原原始类型, 其他错误按调用方指定的类别归类
+    #[test]
+    fn test_from_anyhow() {
+        let err: anyhow::Error = DetectorError::ShapeMismatch("[1, 300, 6]".into()).into();
+        assert_eq!(
+            DetectorError::from_anyhow(&err, DetectorError::Process),
+            DetectorError::ShapeMismatch("[1, 300, 6]".into())
+        );
+
+        let err = anyhow::anyhow!("ort 会话失败");
+        let typed = DetectorError::from_anyhow(&err, DetectorError::ModelLoad);
+        assert_eq!(typed, DetectorError::ModelLoad("ort 会话失败".into()));
+        assert_eq!(typed.to_string(), "模型加载失败: ort 会话失败");
+    }
+}
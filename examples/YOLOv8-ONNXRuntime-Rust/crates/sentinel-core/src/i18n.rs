@@ -0,0 +1,651 @@
+//! 界面多语言 - 控制面板/渲染器字符串目录
+//!
+//! 每种语言一张静态键值表 (zh-CN 为基准), 当前语言存放在全局原子变量中,
+//! 切换后下一帧即生效. 带参数的字符串用 `{}` 占位, 由 [`tr_fmt`] 按顺序替换.
+//! 日志输出 (println!/eprintln!) 不经过此目录
+
+use phf::phf_map;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// 界面语言
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "en-US")]
+    EnUs,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::ZhCn, Language::EnUs];
+
+    /// 语言代码 (BCP 47)
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::ZhCn => "zh-CN",
+            Language::EnUs => "en-US",
+        }
+    }
+
+    /// 语言自身的名称 (切换菜单中显示)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::ZhCn => "简体中文",
+            Language::EnUs => "English",
+        }
+    }
+
+    /// 按语言代码解析, 忽略大小写, 只看主语言 (zh/en)
+    pub fn from_code(code: &str) -> Option<Self> {
+        let primary = code.split(['-', '_']).next()?.to_lowercase();
+        match primary.as_str() {
+            "zh" => Some(Language::ZhCn),
+            "en" => Some(Language::EnUs),
+            _ => None,
+        }
+    }
+
+    fn catalog(&self) -> &'static phf::Map<&'static str, &'static str> {
+        match self {
+            Language::ZhCn => &ZH_CN,
+            Language::EnUs => &EN_US,
+        }
+    }
+}
+
+/// 当前界面语言 (Language 的序号)
+static LANGUAGE: AtomicU8 = AtomicU8::new(0);
+
+/// 当前界面语言
+pub fn language() -> Language {
+    match LANGUAGE.load(Ordering::Relaxed) {
+        1 => Language::EnUs,
+        _ => Language::ZhCn,
+    }
+}
+
+/// 切换界面语言 (立即生效)
+pub fn set_language(language: Language) {
+    LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+/// 按当前语言查找字符串, 缺失时回退到中文, 仍缺失时返回键本身
+pub fn tr(key: &'static str) -> &'static str {
+    language()
+        .catalog()
+        .get(key)
+        .or_else(|| ZH_CN.get(key))
+        .copied()
+        .unwrap_or(key)
+}
+
+/// 带参数的字符串: 依次替换 `{}` 占位符, 多余的参数忽略
+pub fn tr_fmt(key: &'static str, args: &[&dyn Display]) -> String {
+    let template = tr(key);
+    let mut out = String::with_capacity(template.len() + 16);
+    let mut args = args.iter();
+    let mut rest = template;
+    while let Some(pos) = rest.find("{}") {
+        out.push_str(&rest[..pos]);
+        match args.next() {
+            Some(arg) => out.push_str(&arg.to_string()),
+            None => out.push_str("{}"),
+        }
+        rest = &rest[pos + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+static ZH_CN: phf::Map<&'static str, &'static str> = phf_map! {
+    // 面板
+    "panel.title" => "🎯 控制面板",
+    "panel.language" => "🌐 语言 / Language",
+    "common.apply" => "应用",
+    "common.clear" => "清除",
+    "common.no_events" => "暂无事件",
+    "common.unknown" => "未知",
+
+    // 系统状态
+    "status.header" => "📊 系统状态",
+    "status.render_fps" => "渲染 FPS:",
+    "status.decode_fps" => "| 解码 FPS:",
+    "status.detect_fps" => "| 检测 FPS:",
+    "status.presented" => "呈现:",
+    "status.dropped" => "| 丢弃:",
+    "status.late" => "| 延迟呈现:",
+    "status.pacing_delay" => "| 播放延迟 {} ms",
+    "status.tracks_active" => "轨迹 活跃:",
+    "status.tracks_lost" => "| 丢失:",
+    "status.tracks_removed" => "| 已删除:",
+    "status.power" => "功耗:",
+    "status.temperature" => "| 温度:",
+    "status.model" => "当前模型: {}",
+    "status.clock_drift" => "⏰ 摄像头时钟漂移 {} ms ({} ppm), 请检查 NTP 校时",
+    "time.datetime" => "%m-%d %H:%M:%S",
+    "throttle.normal" => "正常",
+    "throttle.warm" => "偏热",
+    "throttle.throttled" => "降频",
+    "latency.header" => "⏱️ 延迟分解 (均值 / P95 ms)",
+    "latency.queue" => "排队",
+    "latency.resize" => "缩放",
+    "latency.inference" => "推理",
+    "latency.postprocess" => "后处理",
+    "latency.render" => "渲染",
+    "latency.total" => "端到端",
+    "metrics.header" => "📈 最近 60 秒",
+    "metrics.decode_fps" => "解码FPS",
+    "metrics.detect_fps" => "检测FPS",
+    "metrics.render_fps" => "渲染FPS",
+    "metrics.inference_ms" => "推理 ms",
+    "metrics.confidence" => "平均置信度",
+    "profile.start" => "📊 剖析 {} 秒",
+    "profile.start_hint" => "记录各阶段耗时, 写出 chrome://tracing 时间线 (profiles/)",
+    "profile.running" => "📊 剖析中... 剩余 {} 秒, 已记录 {} 帧",
+    "profile.last" => "最近报告: {}",
+    "profile.copy" => "复制报告路径",
+
+    // 输入源
+    "input.header" => "🎥 输入源配置",
+    "input.rtsp" => "RTSP",
+    "input.camera" => "摄像头",
+    "input.desktop" => "桌面",
+    "input.rtsp_url" => "RTSP 地址:",
+    "input.history" => "选择历史记录...",
+    "input.history_hint" => "左键:填充并播放 | 右键:仅复制到剪贴板",
+    "input.rtsp_hint" => "输入 RTSP 地址后按回车...",
+    "input.keyframes" => "🔑 仅解码关键帧 (低功耗)",
+    "input.keyframes_hint" => "检测频率随 GOP 降到约 1fps, 适合电池供电或多路部署",
+    "audit.header" => "📜 审计日志",
+    "audit.filter" => "筛选 (来源/操作/内容)...",
+    "audit.empty" => "暂无记录",
+    "vault.header" => "🔐 凭据库",
+    "vault.hint" => "密码保存在系统钥匙串, 地址中用 $ID 引用: rtsp://$ID@主机/路径",
+    "vault.empty" => "暂无凭据",
+    "vault.id" => "ID",
+    "vault.username" => "用户名",
+    "vault.password" => "密码",
+    "vault.save" => "💾 保存",
+    "vault.delete" => "🗑",
+    "input.refresh_devices" => "🔄 刷新设备列表",
+    "input.no_devices" => "未找到设备",
+    "input.retry" => "🔄 重试",
+    "input.select_device" => "选择设备",
+    "input.desktop_capture" => "桌面捕获 (gdigrab)",
+    "input.gige" => "工业相机",
+    "input.gige_disabled" => "工业相机输入需要以 aravis 功能构建",
+    "input.demosaic" => "去马赛克: {} (启动参数 --demosaic)",
+    "input.shm" => "共享内存",
+    "input.shm_path" => "映射文件 (回车启动):",
+    "input.synthetic" => "合成场景",
+    "input.synthetic_scene" => "场景文件 (回车启动):",
+    "input.synthetic_builtin" => "留空使用内置场景",
+    "input.orientation" => "画面方向:",
+    "input.mirror" => "镜像",
+    "input.panorama" => "全景",
+    "input.view" => "视图{}",
+    "input.display_view" => "🐟 显示视图:",
+
+    // 模型与参数
+    "model.header" => "⚙️ 模型与参数",
+    "model.detect_model" => "检测模型:",
+    "model.model" => "模型",
+    "model.tracker_label" => "跟踪算法:",
+    "model.tracker" => "跟踪",
+    "model.pose" => "启用姿态估计",
+    "model.detection" => "启用目标检测",
+    "model.crowd" => "人群密度热力图",
+    "model.crowd_hint" => "需要 --crowd-model (CSRNet 等密度图模型)",
+    "model.crowd_count" => "约 {} 人",
+    "model.clip" => "文本检索 (CLIP):",
+    "model.clip_hint" => "例如: red backpack",
+    "model.vocabulary" => "开放词汇类别 (YOLO-World):",
+    "model.vocabulary_hint" => "逗号分隔, 例如: person, red car",
+    "model.thresholds" => "阈值设置:",
+    "model.confidence" => "置信度",
+    "model.iou" => "IOU",
+    "model.ghost_preview" => "虚影预览",
+    "model.ghost_floor" => "下限",
+    "model.auto_resolution" => "自动分辨率",
+    "model.inference_size" => "推理尺寸:",
+    "model.resize" => "缩放算法:",
+    "tracker.none" => "无",
+    "tracker.lifecycle" => "轨迹生命周期",
+    "tracker.save" => "💾 保存到配置文件",
+    "tracker.max_lost" => "最大丢失帧数",
+    "tracker.min_hits" => "最小命中次数",
+    "tracker.match_iou" => "匹配IOU阈值",
+    "tracker.loose_iou" => "宽松IOU阈值",
+    "tracker.loose_iou_hint" => "ByteTrack: 低分救援轮 | DeepSort: 未确认轨迹",
+    "tracker.high_score" => "高分阈值",
+    "tracker.low_score" => "低分阈值",
+    "tracker.mask_iou_weight" => "掩码IOU权重",
+    "tracker.mask_iou_hint" => "需要分割模型 (-seg)",
+    "tracker.reid_weight" => "ReID权重",
+    "tracker.reid_hint" => "需要 models/osnet_ain_x1_0.onnx",
+
+    // 地面标定与事件
+    "calib.header" => "📐 地面标定",
+    "calib.mode" => "标定模式 (左键点击画面添加地面点)",
+    "calib.solve" => "计算并应用",
+    "calib.need_points" => "至少需要4个标定点",
+    "calib.error" => "重投影误差: {} m",
+    "calib.markers" => "基准标记: 检测到 {} 个, 参考 {} 个",
+    "calib.marker_shift" => "画面偏移: {} px (已按标记锚定)",
+    "zones.header" => "🚨 区域事件",
+    "zones.event" => "{} [{}] {} 目标{} ({})",
+    "zones.verified" => "✔ 复核确认 {}",
+    "zones.rejected" => "✖ 复核误报 {}",
+    "left_behind.header" => "🧳 遗留/移除",
+    "left_behind.left_behind" => "遗留物",
+    "left_behind.removed" => "物体移除",
+    "left_behind.copy_snapshot" => "复制快照路径",
+    "tamper.header" => "📷 摄像头篡改",
+    "tamper.covered" => "镜头遮挡",
+    "tamper.defocused" => "画面失焦",
+    "tamper.moved" => "画面移位",
+    "tamper.restored" => "已恢复",
+
+    // 标注模式
+    "profile.header" => "🗂️ 配置档案",
+    "profile.profile" => "档案",
+    "profile.none" => "(未选择)",
+    "profile.scheduled" => "当前时段档案: {}",
+    "profile.name_hint" => "档案名称",
+    "profile.save" => "💾 保存当前参数",
+    "schedule.header" => "⏰ 布防计划",
+    "schedule.not_running" => "布防调度器未启动",
+    "schedule.auto" => "按计划",
+    "schedule.arm" => "强制布防",
+    "schedule.disarm" => "强制撤防",
+    "schedule.view_armed" => "逻辑流 {}: 🔔 布防",
+    "schedule.view_disarmed" => "逻辑流 {}: 🔕 撤防",
+    "schedule.event_armed" => "{} 逻辑流 {} 已布防 ({})",
+    "schedule.event_disarmed" => "{} 逻辑流 {} 已撤防 ({})",
+    "schedule.by_schedule" => "计划",
+    "schedule.by_manual" => "手动",
+    "annotate.header" => "🏷️ 标注",
+    "annotate.mode" => "标注模式 (冻结画面)",
+    "annotate.hint" => "左键拖动画框 | 拖动角点调整 | 拖动框内移动 | 右键删除",
+    "annotate.class" => "类别",
+    "annotate.new_class_hint" => "新类别名",
+    "annotate.add_class" => "➕ 添加",
+    "annotate.count" => "当前画面 {} 个框",
+    "annotate.export" => "💾 导出并继续",
+    "annotate.skip" => "⏭ 跳过此帧",
+    "annotate.exported" => "已导出 {} 张: {}",
+
+    // 视图
+    "view.header" => "👁️ 视图控制",
+    "view.reset_zoom" => "重置缩放 (R)",
+    "view.palette" => "调色板",
+    "palette.none" => "原始画面",
+    "palette.ironbow" => "铁红 (热成像)",
+    "view.pacing" => "帧呈现",
+
+    // 模型对比
+    "compare.header" => "⚖️ 模型对比",
+    "compare.enable" => "启用对比 (同一帧运行两个模型)",
+    "compare.model_b" => "对比模型",
+    "compare.layout" => "显示方式",
+    "compare.overlay" => "叠加 (不同颜色)",
+    "compare.side_by_side" => "左右分屏",
+    "compare.pip" => "画中画",
+    "compare.waiting" => "等待对比结果...",
+    "compare.latency" => "{} ms (均值 {})",
+    "compare.boxes" => "{} 个框",
+    "compare.agreement" => "一致 {} | 仅 A {} | 仅 B {}",
+    "pacing.latest" => "最新帧 (最低延迟)",
+    "pacing.paced" => "按时间戳",
+    "pacing.smooth" => "平滑 (帧间混合)",
+
+    // 画面叠加文字
+    "render.crowd_count" => "估计人数: {}",
+    "render.no_source" => "请在右侧控制面板选择输入源并启动",
+    "render.background_failed" => "⚠️ 背景图片加载失败",
+    "render.zoom" => "缩放: {}x (按R键重置)",
+
+    // 回放控制与检视
+    "playback.paused" => "⏸ 已暂停 (帧 {} / 缓冲 {} 帧) 空格继续, ←/→ 逐帧, 点击检测框查看详情",
+    "playback.not_exact" => " [叠加框来自更早一帧]",
+    "playback.step_back" => "⏮ 后退",
+    "playback.resume" => "▶ 继续",
+    "playback.step_forward" => "⏭ 前进",
+    "inspect.title" => "🔍 检视",
+    "inspect.track" => "轨迹ID",
+    "inspect.class" => "类别",
+    "inspect.confidence" => "置信度",
+    "inspect.box" => "框 (x1, y1) - (x2, y2)",
+    "inspect.size" => "宽 x 高",
+    "inspect.distance" => "距离",
+    "inspect.global_id" => "全局ID",
+    "inspect.ground" => "地面坐标",
+    "inspect.speed" => "速度",
+    "inspect.attributes" => "行人属性",
+    "inspect.vehicle" => "车辆",
+    "inspect.prompt" => "文本提示相似度",
+    "inspect.reid" => "ReID 特征维度",
+    "inspect.frame" => "结果来源",
+    "inspect.frame_exact" => "本帧",
+    "inspect.frame_earlier" => "更早一帧 (本帧未检测)",
+    "track.title" => "🧭 轨迹 {}",
+    "track.class" => "类别",
+    "track.age" => "存在时长",
+    "track.first_seen" => "首次出现",
+    "track.frames" => "帧数",
+    "track.velocity" => "画面速度",
+    "track.speed" => "地面速度",
+    "track.keypoints" => "关键点质量",
+    "track.reid" => "ReID 相似度",
+    "track.reid_history" => "ReID 相似度历史",
+    "track.thumbnails" => "缩略图",
+    "track.follow" => "🎥 云台跟随",
+    "track.unfollow" => "🎥 停止跟随",
+    "track.export_clip" => "🎬 导出片段",
+    "track.flag" => "🚩 标记",
+    "track.unflag" => "🏳 取消标记",
+    "track.close" => "关闭",
+    "track.following" => "已请求云台跟随轨迹 {}",
+    "track.follow_stopped" => "已停止云台跟随",
+    "track.clip_requested" => "已提交导出任务 #{}",
+    "track.clip_failed" => "导出失败: {}",
+};
+
+static EN_US: phf::Map<&'static str, &'static str> = phf_map! {
+    // Panel
+    "panel.title" => "🎯 Control Panel",
+    "panel.language" => "🌐 语言 / Language",
+    "common.apply" => "Apply",
+    "common.clear" => "Clear",
+    "common.no_events" => "No events",
+    "common.unknown" => "Unknown",
+
+    // System status
+    "status.header" => "📊 System Status",
+    "status.render_fps" => "Render FPS:",
+    "status.decode_fps" => "| Decode FPS:",
+    "status.detect_fps" => "| Detect FPS:",
+    "status.presented" => "Presented:",
+    "status.dropped" => "| Dropped:",
+    "status.late" => "| Late:",
+    "status.pacing_delay" => "| Playout delay {} ms",
+    "status.tracks_active" => "Tracks active:",
+    "status.tracks_lost" => "| Lost:",
+    "status.tracks_removed" => "| Removed:",
+    "status.power" => "Power:",
+    "status.temperature" => "| Temp:",
+    "status.model" => "Model: {}",
+    "status.clock_drift" => "⏰ Camera clock drift {} ms ({} ppm), check NTP sync",
+    "time.datetime" => "%b %d %H:%M:%S",
+    "throttle.normal" => "Normal",
+    "throttle.warm" => "Warm",
+    "throttle.throttled" => "Throttled",
+    "latency.header" => "⏱️ Latency Breakdown (mean / P95 ms)",
+    "latency.queue" => "Queue",
+    "latency.resize" => "Resize",
+    "latency.inference" => "Inference",
+    "latency.postprocess" => "Postprocess",
+    "latency.render" => "Render",
+    "latency.total" => "End-to-end",
+    "metrics.header" => "📈 Last 60 Seconds",
+    "metrics.decode_fps" => "Decode FPS",
+    "metrics.detect_fps" => "Detect FPS",
+    "metrics.render_fps" => "Render FPS",
+    "metrics.inference_ms" => "Inference ms",
+    "metrics.confidence" => "Mean confidence",
+    "profile.start" => "📊 Profile {}s",
+    "profile.start_hint" => "Record per-stage timings and write a chrome://tracing timeline (profiles/)",
+    "profile.running" => "📊 Profiling... {}s left, {} frames recorded",
+    "profile.last" => "Last report: {}",
+    "profile.copy" => "Copy report path",
+
+    // Input source
+    "input.header" => "🎥 Input Source",
+    "input.rtsp" => "RTSP",
+    "input.camera" => "Camera",
+    "input.desktop" => "Desktop",
+    "input.rtsp_url" => "RTSP URL:",
+    "input.history" => "Select from history...",
+    "input.history_hint" => "Left click: fill in and play | Right click: copy to clipboard",
+    "input.rtsp_hint" => "Enter an RTSP URL and press Enter...",
+    "input.keyframes" => "🔑 Decode keyframes only (low power)",
+    "input.keyframes_hint" => "Detection drops to about 1 fps with the GOP; suits battery-powered or multi-camera setups",
+    "audit.header" => "📜 Audit Log",
+    "audit.filter" => "Filter (source/action/detail)...",
+    "audit.empty" => "No records",
+    "vault.header" => "🔐 Credentials",
+    "vault.hint" => "Passwords are kept in the system keychain. Reference them as $ID: rtsp://$ID@host/path",
+    "vault.empty" => "No credentials yet",
+    "vault.id" => "ID",
+    "vault.username" => "Username",
+    "vault.password" => "Password",
+    "vault.save" => "💾 Save",
+    "vault.delete" => "🗑",
+    "input.refresh_devices" => "🔄 Refresh devices",
+    "input.no_devices" => "No devices found",
+    "input.retry" => "🔄 Retry",
+    "input.select_device" => "Device",
+    "input.desktop_capture" => "Desktop capture (gdigrab)",
+    "input.gige" => "Industrial",
+    "input.gige_disabled" => "Industrial camera input requires building with the aravis feature",
+    "input.demosaic" => "Demosaicing: {} (startup flag --demosaic)",
+    "input.shm" => "Shared memory",
+    "input.shm_path" => "Mapped file (press Enter to start):",
+    "input.synthetic" => "Synthetic scene",
+    "input.synthetic_scene" => "Scene file (press Enter to start):",
+    "input.synthetic_builtin" => "Leave empty for the built-in scene",
+    "input.orientation" => "Orientation:",
+    "input.mirror" => "Mirror",
+    "input.panorama" => "Panorama",
+    "input.view" => "View {}",
+    "input.display_view" => "🐟 Display view:",
+
+    // Model and parameters
+    "model.header" => "⚙️ Model & Parameters",
+    "model.detect_model" => "Detection model:",
+    "model.model" => "Model",
+    "model.tracker_label" => "Tracker:",
+    "model.tracker" => "Tracker",
+    "model.pose" => "Pose estimation",
+    "model.detection" => "Object detection",
+    "model.crowd" => "Crowd density heatmap",
+    "model.crowd_hint" => "Requires --crowd-model (a density-map model such as CSRNet)",
+    "model.crowd_count" => "~{} people",
+    "model.clip" => "Text search (CLIP):",
+    "model.clip_hint" => "e.g. red backpack",
+    "model.vocabulary" => "Open-vocabulary classes (YOLO-World):",
+    "model.vocabulary_hint" => "comma separated, e.g. person, red car",
+    "model.thresholds" => "Thresholds:",
+    "model.confidence" => "Confidence",
+    "model.iou" => "IoU",
+    "model.ghost_preview" => "Ghost preview",
+    "model.ghost_floor" => "Floor",
+    "model.auto_resolution" => "Auto resolution",
+    "model.inference_size" => "Input size:",
+    "model.resize" => "Resize:",
+    "tracker.none" => "None",
+    "tracker.lifecycle" => "Track lifecycle",
+    "tracker.save" => "💾 Save to config file",
+    "tracker.max_lost" => "Max lost frames",
+    "tracker.min_hits" => "Min hits",
+    "tracker.match_iou" => "Match IoU threshold",
+    "tracker.loose_iou" => "Loose IoU threshold",
+    "tracker.loose_iou_hint" => "ByteTrack: low-score rescue round | DeepSort: unconfirmed tracks",
+    "tracker.high_score" => "High score threshold",
+    "tracker.low_score" => "Low score threshold",
+    "tracker.mask_iou_weight" => "Mask IoU weight",
+    "tracker.mask_iou_hint" => "Requires a segmentation model (-seg)",
+    "tracker.reid_weight" => "ReID weight",
+    "tracker.reid_hint" => "Requires models/osnet_ain_x1_0.onnx",
+
+    // Ground calibration and events
+    "calib.header" => "📐 Ground Calibration",
+    "calib.mode" => "Calibration mode (left click the video to add ground points)",
+    "calib.solve" => "Solve and apply",
+    "calib.need_points" => "At least 4 points are required",
+    "calib.error" => "Reprojection error: {} m",
+    "calib.markers" => "Markers: {} detected, {} reference",
+    "calib.marker_shift" => "Camera shift: {} px (anchored to markers)",
+    "zones.header" => "🚨 Zone Events",
+    "zones.event" => "{} [{}] {} tracks {} ({})",
+    "zones.verified" => "✔ verified {}",
+    "zones.rejected" => "✖ rejected {}",
+    "left_behind.header" => "🧳 Left Behind / Removed",
+    "left_behind.left_behind" => "Left behind",
+    "left_behind.removed" => "Removed",
+    "left_behind.copy_snapshot" => "Copy snapshot path",
+    "tamper.header" => "📷 Camera Tamper",
+    "tamper.covered" => "Lens covered",
+    "tamper.defocused" => "Out of focus",
+    "tamper.moved" => "Camera moved",
+    "tamper.restored" => "restored",
+
+    // Annotation mode
+    "profile.header" => "🗂️ Profiles",
+    "profile.profile" => "Profile",
+    "profile.none" => "(none)",
+    "profile.scheduled" => "Scheduled profile: {}",
+    "profile.name_hint" => "Profile name",
+    "profile.save" => "💾 Save current settings",
+    "schedule.header" => "⏰ Arm Schedule",
+    "schedule.not_running" => "Arm scheduler is not running",
+    "schedule.auto" => "Scheduled",
+    "schedule.arm" => "Force arm",
+    "schedule.disarm" => "Force disarm",
+    "schedule.view_armed" => "Stream {}: 🔔 armed",
+    "schedule.view_disarmed" => "Stream {}: 🔕 disarmed",
+    "schedule.event_armed" => "{} stream {} armed ({})",
+    "schedule.event_disarmed" => "{} stream {} disarmed ({})",
+    "schedule.by_schedule" => "schedule",
+    "schedule.by_manual" => "manual",
+    "annotate.header" => "🏷️ Annotation",
+    "annotate.mode" => "Annotation mode (freeze frame)",
+    "annotate.hint" => "Left drag: draw box | drag corner: resize | drag inside: move | right click: delete",
+    "annotate.class" => "Class",
+    "annotate.new_class_hint" => "New class name",
+    "annotate.add_class" => "➕ Add",
+    "annotate.count" => "{} boxes on this frame",
+    "annotate.export" => "💾 Export and continue",
+    "annotate.skip" => "⏭ Skip frame",
+    "annotate.exported" => "Exported {} images: {}",
+
+    // View
+    "view.header" => "👁️ View",
+    "view.reset_zoom" => "Reset zoom (R)",
+    "view.palette" => "Palette",
+    "palette.none" => "Original",
+    "palette.ironbow" => "Ironbow (thermal)",
+    "view.pacing" => "Frame pacing",
+
+    // Model comparison
+    "compare.header" => "⚖️ Model Comparison",
+    "compare.enable" => "Compare (run two models on the same frame)",
+    "compare.model_b" => "Compare with",
+    "compare.layout" => "Layout",
+    "compare.overlay" => "Overlay (two colors)",
+    "compare.side_by_side" => "Side by side",
+    "compare.pip" => "Picture-in-picture",
+    "compare.waiting" => "Waiting for results...",
+    "compare.latency" => "{} ms (mean {})",
+    "compare.boxes" => "{} boxes",
+    "compare.agreement" => "Matched {} | A only {} | B only {}",
+    "pacing.latest" => "Latest frame (lowest latency)",
+    "pacing.paced" => "By timestamp",
+    "pacing.smooth" => "Smooth (blend frames)",
+
+    // Video overlay text
+    "render.crowd_count" => "Estimated count: {}",
+    "render.no_source" => "Select an input source in the control panel to start",
+    "render.background_failed" => "⚠️ Failed to load the background image",
+    "render.zoom" => "Zoom: {}x (press R to reset)",
+
+    // 回放控制与检视
+    "playback.paused" => "⏸ Paused (frame {} of {} buffered) Space resumes, ←/→ steps, click a box to inspect",
+    "playback.not_exact" => " [overlay from an earlier frame]",
+    "playback.step_back" => "⏮ Back",
+    "playback.resume" => "▶ Resume",
+    "playback.step_forward" => "⏭ Step",
+    "inspect.title" => "🔍 Inspect",
+    "inspect.track" => "Track ID",
+    "inspect.class" => "Class",
+    "inspect.confidence" => "Confidence",
+    "inspect.box" => "Box (x1, y1) - (x2, y2)",
+    "inspect.size" => "Width x height",
+    "inspect.distance" => "Distance",
+    "inspect.global_id" => "Global ID",
+    "inspect.ground" => "Ground position",
+    "inspect.speed" => "Speed",
+    "inspect.attributes" => "Person attributes",
+    "inspect.vehicle" => "Vehicle",
+    "inspect.prompt" => "Prompt similarity",
+    "inspect.reid" => "ReID feature size",
+    "inspect.frame" => "Result from",
+    "inspect.frame_exact" => "This frame",
+    "inspect.frame_earlier" => "An earlier frame (this one was not detected)",
+    "track.title" => "🧭 Track {}",
+    "track.class" => "Class",
+    "track.age" => "Age",
+    "track.first_seen" => "First seen",
+    "track.frames" => "Frames",
+    "track.velocity" => "Image velocity",
+    "track.speed" => "Ground speed",
+    "track.keypoints" => "Keypoint quality",
+    "track.reid" => "ReID similarity",
+    "track.reid_history" => "ReID similarity history",
+    "track.thumbnails" => "Thumbnails",
+    "track.follow" => "🎥 Follow with PTZ",
+    "track.unfollow" => "🎥 Stop following",
+    "track.export_clip" => "🎬 Export clip",
+    "track.flag" => "🚩 Flag",
+    "track.unflag" => "🏳 Unflag",
+    "track.close" => "Close",
+    "track.following" => "PTZ follow requested for track {}",
+    "track.follow_stopped" => "PTZ follow stopped",
+    "track.clip_requested" => "Export job #{} submitted",
+    "track.clip_failed" => "Export failed: {}",
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 各语言目录的键集合一致, 同一键的占位符个数相同
+    #[test]
+    fn test_catalogs_complete() {
+        for language in Language::ALL {
+            let catalog = language.catalog();
+            assert_eq!(catalog.len(), ZH_CN.len(), "{}", language.code());
+            for (key, zh) in ZH_CN.entries() {
+                let text = catalog
+                    .get(key)
+                    .unwrap_or_else(|| panic!("{} 缺少 {}", language.code(), key));
+                assert_eq!(
+                    text.matches("{}").count(),
+                    zh.matches("{}").count(),
+                    "{}",
+                    key
+                );
+            }
+        }
+    }
+
+    /// 占位符按顺序替换, 语言代码解析只看主语言
+    #[test]
+    fn test_tr_fmt() {
+        set_language(Language::EnUs);
+        assert_eq!(
+            tr_fmt("calib.error", &[&format!("{:.3}", 0.1234)]),
+            "Reprojection error: 0.123 m"
+        );
+        assert_eq!(tr("no.such.key"), "no.such.key");
+        set_language(Language::ZhCn);
+        assert_eq!(tr_fmt("status.model", &[&"yolov8n"]), "当前模型: yolov8n");
+
+        assert_eq!(Language::from_code("en_GB.UTF-8"), Some(Language::EnUs));
+        assert_eq!(Language::from_code("ZH-cn"), Some(Language::ZhCn));
+        assert_eq!(Language::from_code("fr"), None);
+    }
+}
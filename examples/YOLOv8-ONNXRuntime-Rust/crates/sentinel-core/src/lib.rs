@@ -0,0 +1,484 @@
+#![allow(clippy::type_complexity)]
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+//! 数字卫兵核心库
+//!
+//! 默认不依赖 ONNX Runtime、FFmpeg 与 GUI, 可作为服务端、测试工具或其他项目的依赖:
+//! - 检测数据类型 ([`Bbox`]/[`DetectionResult`]/[`Point2`]) 与 NMS
+//! - 跟踪器、后处理器、模拟模型 ([`models::MockModel`]) 与模型元数据
+//! - 地面标定、布防计划、审计日志、存储保留、内存防护与事件总线
+//! - 检测结果的紧凑二进制编码 ([`codec`])
+//!
+//! 功能分层 (Cargo features):
+//! - ort: ONNX Runtime 推理 (完整模型/检测线程/ReID/场景分析)
+//! - gpu: wgpu 仿射变换
+//! - cuda: CUDA 端到端检测管线
+//!
+//! 视频输入与服务输出在 `sentinel-pipeline`, 渲染器与控制面板在 `sentinel-gui`
+pub mod analytics; // 场景分析 (地面标定/区域规则)
+pub mod audit; // 操作审计日志 (控制面板/API/配置热加载的控制操作)
+pub mod codec; // 检测结果的紧凑二进制编码 (量化坐标 + 轨迹差量, 带版本头)
+pub mod config; // 模型配置参数
+#[cfg(feature = "cuda")]
+pub mod cuda; // CUDA 端到端检测管线
+pub mod dataset; // 数据集采集 (标注导出)
+pub mod detection; // 智能检测系统
+pub mod error; // 检测器错误类型
+pub mod i18n; // 界面多语言 (zh-CN/en-US 字符串目录)
+pub mod memory_guard; // 内存占用统计与泄漏防护 (RSS/帧池/纹理/ORT 会话)
+pub mod models; // 模型接口与具体实现
+#[cfg(feature = "ort")]
+pub mod ort_backend; // ONNX Runtime 推理后端
+pub mod retention; // 存储保留策略 (按时长/容量清理快照与录像)
+pub mod runtime_config; // 运行时线程配置 (ORT/rayon 线程数与绑核)
+pub mod scheduler; // 布防计划 (按时段启停检测)
+pub mod utils; // 工具模块
+pub mod xbus;
+
+pub use crate::config::{Args, YOLOTask};
+pub use crate::error::DetectorError;
+#[cfg(feature = "ort")]
+pub use crate::models::YOLOv8;
+pub use crate::models::{
+    FastestV2Config, FastestV2Postprocessor, Model, NanoDetConfig, NanoDetPostprocessor,
+};
+#[cfg(feature = "ort")]
+pub use crate::ort_backend::{Batch, OrtBackend, OrtConfig, OrtEP};
+
+/// 候选框数量超过该阈值时改用 rayon 并行位图 NMS
+pub const NMS_PARALLEL_THRESHOLD: usize = 256;
+
+/// 非极大值抑制 (按置信度降序贪心保留)
+///
+/// 候选框较少时使用 O(n²) 串行版本; 人群等密集场景(>256个候选)自动切换到
+/// 并行位图版本, 两者结果完全一致
+pub fn non_max_suppression(
+    xs: &mut Vec<(Bbox, Option<Vec<Point2>>, Option<Vec<f32>>)>,
+    iou_threshold: f32,
+) {
+    xs.sort_by(|b1, b2| b2.0.confidence().partial_cmp(&b1.0.confidence()).unwrap());
+
+    if xs.len() > NMS_PARALLEL_THRESHOLD {
+        nms_bitset_sorted(xs, iou_threshold);
+    } else {
+        nms_greedy_sorted(xs, iou_threshold);
+    }
+}
+
+/// 对 `[x1, y1, x2, y2]` 框与分数做 NMS, 返回保留框的下标 (按分数降序)
+///
+/// 供 Python/C 绑定等不持有 [`Bbox`] 的调用方使用, 内部与 [`non_max_suppression`] 一致
+pub fn nms_indices(boxes: &[[f32; 4]], scores: &[f32], iou_threshold: f32) -> Vec<usize> {
+    let mut xs: Vec<(Bbox, Option<Vec<Point2>>, Option<Vec<f32>>)> = boxes
+        .iter()
+        .zip(scores)
+        .enumerate()
+        .map(|(i, (b, &score))| {
+            let bbox = Bbox::new(b[0], b[1], b[2] - b[0], b[3] - b[1], i, score);
+            (bbox, None, None)
+        })
+        .collect();
+    non_max_suppression(&mut xs, iou_threshold);
+    xs.iter().map(|x| x.0.id()).collect()
+}
+
+/// 串行贪心 NMS (输入已按置信度降序排列)
+fn nms_greedy_sorted(
+    xs: &mut Vec<(Bbox, Option<Vec<Point2>>, Option<Vec<f32>>)>,
+    iou_threshold: f32,
+) {
+    let mut current_index = 0;
+    for index in 0..xs.len() {
+        let mut drop = false;
+        for prev_index in 0..current_index {
+            let iou = xs[prev_index].0.iou(&xs[index].0);
+            if iou > iou_threshold {
+                drop = true;
+                break;
+            }
+        }
+        if !drop {
+            xs.swap(current_index, index);
+            current_index += 1;
+        }
+    }
+    xs.truncate(current_index);
+}
+
+/// 并行位图 NMS (输入已按置信度降序排列)
+///
+/// 1. rayon 并行计算抑制矩阵: 第 i 行的位图记录所有 j > i 且 IoU(i, j) > 阈值的框
+/// 2. 串行扫描: 未被抑制的框保留, 并将其整行位图合并到已抑制集合
+fn nms_bitset_sorted(
+    xs: &mut Vec<(Bbox, Option<Vec<Point2>>, Option<Vec<f32>>)>,
+    iou_threshold: f32,
+) {
+    use rayon::prelude::*;
+
+    let n = xs.len();
+    let words = n.div_ceil(64);
+    let boxes: Vec<&Bbox> = xs.iter().map(|x| &x.0).collect();
+
+    let masks: Vec<Vec<u64>> = (0..n)
+        .into_par_iter()
+        .map(|i| {
+            let mut row = vec![0u64; words];
+            for j in (i + 1)..n {
+                if boxes[i].iou(boxes[j]) > iou_threshold {
+                    row[j / 64] |= 1u64 << (j % 64);
+                }
+            }
+            row
+        })
+        .collect();
+
+    let mut removed = vec![0u64; words];
+    let mut keep = vec![false; n];
+    for i in 0..n {
+        if removed[i / 64] & (1u64 << (i % 64)) != 0 {
+            continue;
+        }
+        keep[i] = true;
+        for (r, m) in removed.iter_mut().zip(&masks[i]) {
+            *r |= *m;
+        }
+    }
+
+    let mut idx = 0;
+    xs.retain(|_| {
+        let k = keep[idx];
+        idx += 1;
+        k
+    });
+}
+
+/// 显示时区 (`--timezone`, 默认系统本地时区) 的当前时间串, 用于输出文件名
+pub fn gen_time_string(delimiter: &str) -> String {
+    let fmt = format!(
+        "%Y{}%m{}%d{}%H{}%M{}%S{}%f",
+        delimiter, delimiter, delimiter, delimiter, delimiter, delimiter
+    );
+    utils::clock::format(chrono::Utc::now(), &fmt)
+}
+
+pub const SKELETON: [(usize, usize); 16] = [
+    (0, 1),
+    (0, 2),
+    (1, 3),
+    (2, 4),
+    (5, 6),
+    (5, 11),
+    (6, 12),
+    (11, 12),
+    (5, 7),
+    (6, 8),
+    (7, 9),
+    (8, 10),
+    (11, 13),
+    (12, 14),
+    (13, 15),
+    (14, 16),
+];
+
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+
+use ndarray::{Array, Axis, IxDyn};
+
+#[derive(Clone, PartialEq, Default)]
+pub struct DetectionResult {
+    // YOLO tasks results of an image
+    pub probs: Option<Embedding>,
+    pub bboxes: Option<Vec<Bbox>>,
+    pub keypoints: Option<Vec<Vec<Point2>>>,
+    pub masks: Option<Vec<Vec<u8>>>,
+}
+
+impl std::fmt::Debug for DetectionResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("YOLOResult")
+            .field(
+                "Probs(top5)",
+                &format_args!("{:?}", self.probs().map(|probs| probs.topk(5))),
+            )
+            .field("Bboxes", &self.bboxes)
+            .field("Keypoints", &self.keypoints)
+            .field(
+                "Masks",
+                &format_args!("{:?}", self.masks().map(|masks| masks.len())),
+            )
+            .finish()
+    }
+}
+
+impl DetectionResult {
+    pub fn new(
+        probs: Option<Embedding>,
+        bboxes: Option<Vec<Bbox>>,
+        keypoints: Option<Vec<Vec<Point2>>>,
+        masks: Option<Vec<Vec<u8>>>,
+    ) -> Self {
+        Self {
+            probs,
+            bboxes,
+            keypoints,
+            masks,
+        }
+    }
+
+    pub fn probs(&self) -> Option<&Embedding> {
+        self.probs.as_ref()
+    }
+
+    pub fn keypoints(&self) -> Option<&Vec<Vec<Point2>>> {
+        self.keypoints.as_ref()
+    }
+
+    pub fn masks(&self) -> Option<&Vec<Vec<u8>>> {
+        self.masks.as_ref()
+    }
+
+    pub fn bboxes(&self) -> Option<&Vec<Bbox>> {
+        self.bboxes.as_ref()
+    }
+
+    pub fn bboxes_mut(&mut self) -> Option<&mut Vec<Bbox>> {
+        self.bboxes.as_mut()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Point2 {
+    // A point2d with x, y, conf
+    x: f32,
+    y: f32,
+    confidence: f32,
+}
+
+impl Point2 {
+    pub fn new_with_conf(x: f32, y: f32, confidence: f32) -> Self {
+        Self { x, y, confidence }
+    }
+
+    pub fn new(x: f32, y: f32) -> Self {
+        Self {
+            x,
+            y,
+            ..Default::default()
+        }
+    }
+
+    pub fn x(&self) -> f32 {
+        self.x
+    }
+
+    pub fn y(&self) -> f32 {
+        self.y
+    }
+
+    pub fn confidence(&self) -> f32 {
+        self.confidence
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Embedding {
+    // An float32 n-dims tensor
+    data: Array<f32, IxDyn>,
+}
+
+impl Embedding {
+    pub fn new(data: Array<f32, IxDyn>) -> Self {
+        Self { data }
+    }
+
+    pub fn data(&self) -> &Array<f32, IxDyn> {
+        &self.data
+    }
+
+    pub fn topk(&self, k: usize) -> Vec<(usize, f32)> {
+        let mut probs = self
+            .data
+            .iter()
+            .enumerate()
+            .map(|(a, b)| (a, *b))
+            .collect::<Vec<_>>();
+        probs.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        let mut topk = Vec::new();
+        for &(id, confidence) in probs.iter().take(k) {
+            topk.push((id, confidence));
+        }
+        topk
+    }
+
+    pub fn norm(&self) -> Array<f32, IxDyn> {
+        let std_ = self.data.mapv(|x| x * x).sum_axis(Axis(0)).mapv(f32::sqrt);
+        self.data.clone() / std_
+    }
+
+    pub fn top1(&self) -> (usize, f32) {
+        self.topk(1)[0]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Bbox {
+    // a bounding box around an object
+    xmin: f32,
+    ymin: f32,
+    width: f32,
+    height: f32,
+    id: usize,
+    confidence: f32,
+}
+
+impl Bbox {
+    pub fn new_from_xywh(xmin: f32, ymin: f32, width: f32, height: f32) -> Self {
+        Self {
+            xmin,
+            ymin,
+            width,
+            height,
+            ..Default::default()
+        }
+    }
+
+    pub fn new(xmin: f32, ymin: f32, width: f32, height: f32, id: usize, confidence: f32) -> Self {
+        Self {
+            xmin,
+            ymin,
+            width,
+            height,
+            id,
+            confidence,
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    pub fn xmin(&self) -> f32 {
+        self.xmin
+    }
+
+    pub fn ymin(&self) -> f32 {
+        self.ymin
+    }
+
+    pub fn xmax(&self) -> f32 {
+        self.xmin + self.width
+    }
+
+    pub fn ymax(&self) -> f32 {
+        self.ymin + self.height
+    }
+
+    pub fn tl(&self) -> Point2 {
+        Point2::new(self.xmin, self.ymin)
+    }
+
+    pub fn br(&self) -> Point2 {
+        Point2::new(self.xmax(), self.ymax())
+    }
+
+    pub fn cxcy(&self) -> Point2 {
+        Point2::new(self.xmin + self.width / 2., self.ymin + self.height / 2.)
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn confidence(&self) -> f32 {
+        self.confidence
+    }
+
+    pub fn set_confidence(&mut self, confidence: f32) {
+        self.confidence = confidence;
+    }
+
+    pub fn area(&self) -> f32 {
+        self.width * self.height
+    }
+
+    pub fn intersection_area(&self, another: &Bbox) -> f32 {
+        let l = self.xmin.max(another.xmin);
+        let r = (self.xmin + self.width).min(another.xmin + another.width);
+        let t = self.ymin.max(another.ymin);
+        let b = (self.ymin + self.height).min(another.ymin + another.height);
+        (r - l + 1.).max(0.) * (b - t + 1.).max(0.)
+    }
+
+    pub fn union(&self, another: &Bbox) -> f32 {
+        self.area() + another.area() - self.intersection_area(another)
+    }
+
+    pub fn iou(&self, another: &Bbox) -> f32 {
+        self.intersection_area(another) / self.union(another)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 生成确定性的密集候选框 (简单LCG, 不依赖随机种子)
+    fn dense_candidates(n: usize) -> Vec<(Bbox, Option<Vec<Point2>>, Option<Vec<f32>>)> {
+        let mut seed = 0x2545_f491_u64;
+        let mut next = || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((seed >> 33) as f32) / (1u64 << 31) as f32
+        };
+        (0..n)
+            .map(|_| {
+                let (x, y) = (next() * 600.0, next() * 400.0);
+                let (w, h) = (20.0 + next() * 60.0, 40.0 + next() * 120.0);
+                (Bbox::new(x, y, w, h, 0, next()), None, None)
+            })
+            .collect()
+    }
+
+    /// 并行位图 NMS 与串行贪心 NMS 结果完全一致
+    #[test]
+    fn test_nms_bitset_matches_greedy() {
+        for &n in &[1usize, 63, 64, 65, 300, 800] {
+            for &thr in &[0.3f32, 0.45, 0.7] {
+                let mut a = dense_candidates(n);
+                a.sort_by(|b1, b2| b2.0.confidence().partial_cmp(&b1.0.confidence()).unwrap());
+                let mut b = a.clone();
+                nms_greedy_sorted(&mut a, thr);
+                nms_bitset_sorted(&mut b, thr);
+                assert_eq!(a, b, "n={} thr={}", n, thr);
+            }
+        }
+    }
+
+    /// 下标接口: 重叠框只保留高分者, 结果按分数降序
+    #[test]
+    fn test_nms_indices() {
+        let boxes = [
+            [0.0, 0.0, 100.0, 100.0],
+            [5.0, 5.0, 105.0, 105.0],
+            [200.0, 200.0, 250.0, 260.0],
+        ];
+        assert_eq!(nms_indices(&boxes, &[0.6, 0.9, 0.7], 0.45), vec![1, 2]);
+        assert!(nms_indices(&[], &[], 0.45).is_empty());
+    }
+
+    /// 超过阈值时自动走并行路径, 且保留结果按置信度降序
+    #[test]
+    fn test_nms_dispatch_sorted_output() {
+        let mut xs = dense_candidates(NMS_PARALLEL_THRESHOLD * 2);
+        non_max_suppression(&mut xs, 0.45);
+        assert!(!xs.is_empty());
+        assert!(xs
+            .windows(2)
+            .all(|w| w[0].0.confidence() >= w[1].0.confidence()));
+    }
+}
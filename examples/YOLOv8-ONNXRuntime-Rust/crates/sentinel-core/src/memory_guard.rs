@@ -0,0 +1,414 @@
+//! 内存占用统计与泄漏防护 (长时间运行)
+//!
+//! 多日运行时内存缓慢增长却无从观察, 后台线程定期采样各项占用并在增长过多时采取防护措施:
+//! - 进程常驻内存 (RSS, Linux 读取 `/proc/self/status`, 其他平台不统计)
+//! - 帧缓冲池占用 (MJPEG 快速路径的解码帧池: 被其他线程持有的帧数 / 池大小)
+//! - 渲染纹理数与显存估算 (渲染线程每秒上报)
+//! - ORT 会话: 检测后端数与已加载模型文件大小 (ort 未提供分配器内部统计, 以权重大小近似)
+//!
+//! 防护: 预热期结束后以当时的 RSS 为基线, 增长超过 `rss_growth_mb` (或超过 `rss_limit_mb`)
+//! 时广播 [`MemoryAction::FlushCaches`], 纹理数超过 `max_textures` 时广播
+//! [`MemoryAction::RebuildTextures`]. 渲染线程收到后清空回放缓冲/重建纹理, 并归还堆内存给系统.
+//! 每次处理后以当前 RSS 为新基线, 两次处理至少间隔 `cooldown_secs`
+//!
+//! 占用经 `GET /api/memory` 与指标文件 (`sentinel_memory_*`) 输出.
+//!
+//! 配置文件 (memory_guard.json), 不存在时使用默认值:
+//! ```json
+//! { "interval_secs": 30, "warmup_secs": 600, "rss_growth_mb": 1024, "rss_limit_mb": 0, "max_textures": 32, "cooldown_secs": 600 }
+//! ```
+
+use std::fmt::Write as _;
+use std::fs;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::xbus;
+
+/// 默认配置文件路径
+pub const MEMORY_GUARD_CONFIG_PATH: &str = "memory_guard.json";
+
+const MB: u64 = 1024 * 1024;
+
+/// 最近一次采样 (后台线程更新, API/指标读取)
+static STATUS: OnceLock<Mutex<MemoryStatus>> = OnceLock::new();
+
+static FRAME_POOL_IN_USE: AtomicUsize = AtomicUsize::new(0);
+static FRAME_POOL_CAPACITY: AtomicUsize = AtomicUsize::new(0);
+static TEXTURES: AtomicUsize = AtomicUsize::new(0);
+static TEXTURE_BYTES: AtomicU64 = AtomicU64::new(0);
+static ORT_SESSIONS: AtomicUsize = AtomicUsize::new(0);
+static ORT_MODEL_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// 内存防护配置 (memory_guard.json)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemoryGuardConfig {
+    pub interval_secs: u64,
+    pub warmup_secs: u64,   // 启动后多久取基线 (模型加载、缓冲填满之后)
+    pub rss_growth_mb: u64, // 相对基线的增长上限, 0 不检查
+    pub rss_limit_mb: u64,  // RSS 绝对上限, 0 不检查
+    pub max_textures: usize,
+    pub cooldown_secs: u64,
+}
+
+impl Default for MemoryGuardConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 30,
+            warmup_secs: 600,
+            rss_growth_mb: 1024,
+            rss_limit_mb: 0,
+            max_textures: 32,
+            cooldown_secs: 600,
+        }
+    }
+}
+
+impl MemoryGuardConfig {
+    /// 从文件加载配置, 文件不存在时使用默认值
+    pub fn load(path: &str) -> Self {
+        match fs::read_to_string(path) {
+            Ok(json) => match serde_json::from_str(&json) {
+                Ok(config) => {
+                    println!("✅ 内存防护配置已从 {} 加载", path);
+                    config
+                }
+                Err(e) => {
+                    eprintln!("⚠️  内存防护配置解析失败: {}, 使用默认值", e);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+}
+
+/// 防护措施 (xbus 广播, 由持有缓存/纹理的线程执行)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum MemoryAction {
+    FlushCaches,     // 清空可重建的缓存并归还堆内存
+    RebuildTextures, // 释放全部纹理, 下一帧重新创建
+}
+
+/// 一次采样
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MemoryStatus {
+    pub rss_bytes: Option<u64>,
+    pub baseline_rss_bytes: Option<u64>,
+    pub frame_pool_in_use: usize,
+    pub frame_pool_capacity: usize,
+    pub textures: usize,
+    pub texture_bytes: u64,
+    pub ort_sessions: usize,
+    pub ort_model_bytes: u64,
+    pub actions: u64, // 累计防护次数
+    pub time: DateTime<Local>,
+}
+
+impl MemoryStatus {
+    /// 当前各项占用
+    pub fn sample() -> Self {
+        Self {
+            rss_bytes: read_rss(),
+            baseline_rss_bytes: None,
+            frame_pool_in_use: FRAME_POOL_IN_USE.load(Ordering::Relaxed),
+            frame_pool_capacity: FRAME_POOL_CAPACITY.load(Ordering::Relaxed),
+            textures: TEXTURES.load(Ordering::Relaxed),
+            texture_bytes: TEXTURE_BYTES.load(Ordering::Relaxed),
+            ort_sessions: ORT_SESSIONS.load(Ordering::Relaxed),
+            ort_model_bytes: ORT_MODEL_BYTES.load(Ordering::Relaxed),
+            actions: 0,
+            time: Local::now(),
+        }
+    }
+
+    /// Prometheus 文本格式 (追加在延迟指标之后)
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        let mut gauge = |name: &str, help: &str, value: u64| {
+            let _ = writeln!(out, "# HELP sentinel_memory_{} {}", name, help);
+            let _ = writeln!(out, "# TYPE sentinel_memory_{} gauge", name);
+            let _ = writeln!(out, "sentinel_memory_{} {}", name, value);
+        };
+        if let Some(rss) = self.rss_bytes {
+            gauge("rss_bytes", "Resident set size of the process", rss);
+        }
+        if let Some(baseline) = self.baseline_rss_bytes {
+            gauge(
+                "baseline_rss_bytes",
+                "RSS baseline of the leak guard",
+                baseline,
+            );
+        }
+        gauge(
+            "frame_pool_in_use",
+            "Pooled frame buffers held by other threads",
+            self.frame_pool_in_use as u64,
+        );
+        gauge(
+            "frame_pool_capacity",
+            "Frame buffer pool size",
+            self.frame_pool_capacity as u64,
+        );
+        gauge("textures", "Live render textures", self.textures as u64);
+        gauge(
+            "texture_bytes",
+            "Estimated size of live render textures",
+            self.texture_bytes,
+        );
+        gauge(
+            "ort_sessions",
+            "ONNX Runtime detector backends",
+            self.ort_sessions as u64,
+        );
+        gauge(
+            "ort_model_bytes",
+            "Size of model files loaded into ONNX Runtime",
+            self.ort_model_bytes,
+        );
+        gauge("guard_actions", "Leak guard actions taken", self.actions);
+        out
+    }
+}
+
+/// 帧缓冲池占用 (解码线程每帧上报)
+pub fn set_frame_pool(in_use: usize, capacity: usize) {
+    FRAME_POOL_IN_USE.store(in_use, Ordering::Relaxed);
+    FRAME_POOL_CAPACITY.store(capacity, Ordering::Relaxed);
+}
+
+/// 渲染纹理数与估算字节数 (渲染线程每秒上报)
+pub fn set_textures(count: usize, bytes: u64) {
+    TEXTURES.store(count, Ordering::Relaxed);
+    TEXTURE_BYTES.store(bytes, Ordering::Relaxed);
+}
+
+/// ORT 检测后端的占用记录, 后端释放时随之减去
+#[derive(Debug)]
+pub struct OrtUsage {
+    model_bytes: u64,
+}
+
+impl OrtUsage {
+    /// 记录一个检测后端; model_bytes 为本后端加载的模型文件大小 (复用共享会话时为 0)
+    pub fn new(model_bytes: u64) -> Self {
+        ORT_SESSIONS.fetch_add(1, Ordering::Relaxed);
+        ORT_MODEL_BYTES.fetch_add(model_bytes, Ordering::Relaxed);
+        Self { model_bytes }
+    }
+}
+
+impl Drop for OrtUsage {
+    fn drop(&mut self) {
+        ORT_SESSIONS.fetch_sub(1, Ordering::Relaxed);
+        ORT_MODEL_BYTES.fetch_sub(self.model_bytes, Ordering::Relaxed);
+    }
+}
+
+/// `/proc/self/status` 中的 VmRSS (字节)
+pub fn parse_vm_rss(status: &str) -> Option<u64> {
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kb: u64 = line["VmRSS:".len()..]
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+/// 进程常驻内存, 不支持的平台为 None
+pub fn read_rss() -> Option<u64> {
+    if cfg!(target_os = "linux") {
+        parse_vm_rss(&fs::read_to_string("/proc/self/status").ok()?)
+    } else {
+        None
+    }
+}
+
+/// 把空闲的堆内存归还系统 (glibc 之外无操作)
+pub fn release_heap() {
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    // SAFETY: malloc_trim 只整理 glibc 堆, 可在任意线程调用
+    unsafe {
+        libc::malloc_trim(0);
+    }
+}
+
+/// 泄漏防护状态: 基线与冷却
+#[derive(Debug)]
+pub struct LeakGuard {
+    config: MemoryGuardConfig,
+    started: Instant,
+    baseline: Option<u64>,
+    last_action: Option<Instant>,
+}
+
+impl LeakGuard {
+    pub fn new(config: MemoryGuardConfig, started: Instant) -> Self {
+        Self {
+            config,
+            started,
+            baseline: None,
+            last_action: None,
+        }
+    }
+
+    pub fn baseline(&self) -> Option<u64> {
+        self.baseline
+    }
+
+    /// 检查一次采样, 返回需要采取的措施
+    pub fn check(&mut self, status: &MemoryStatus, now: Instant) -> Vec<MemoryAction> {
+        if now.duration_since(self.started) < Duration::from_secs(self.config.warmup_secs) {
+            return Vec::new();
+        }
+        let cooldown = Duration::from_secs(self.config.cooldown_secs);
+        if self
+            .last_action
+            .is_some_and(|last| now.duration_since(last) < cooldown)
+        {
+            return Vec::new();
+        }
+
+        let mut actions = Vec::new();
+        if let Some(rss) = status.rss_bytes {
+            let baseline = *self.baseline.get_or_insert(rss);
+            let growth = self.config.rss_growth_mb > 0
+                && rss.saturating_sub(baseline) > self.config.rss_growth_mb * MB;
+            let limit = self.config.rss_limit_mb > 0 && rss > self.config.rss_limit_mb * MB;
+            if growth || limit {
+                actions.push(MemoryAction::FlushCaches);
+            }
+        }
+        if self.config.max_textures > 0 && status.textures > self.config.max_textures {
+            actions.push(MemoryAction::RebuildTextures);
+        }
+        if !actions.is_empty() {
+            // 处理后以当前占用为新基线: 持续泄漏时每增长一个阈值再报告一次
+            self.baseline = status.rss_bytes;
+            self.last_action = Some(now);
+        }
+        actions
+    }
+}
+
+/// 最近一次采样, 监控未启动时为 None
+pub fn memory_status() -> Option<MemoryStatus> {
+    STATUS.get().map(|s| s.lock().unwrap().clone())
+}
+
+/// 启动后台监控线程 (进程内只启动一次)
+pub fn start(config: MemoryGuardConfig) {
+    if STATUS.get().is_some() {
+        return;
+    }
+    let _ = STATUS.set(Mutex::new(MemoryStatus::sample()));
+    let interval = Duration::from_secs(config.interval_secs.max(1));
+    let mut guard = LeakGuard::new(config, Instant::now());
+    let mut total_actions = 0;
+    let spawned = std::thread::Builder::new()
+        .name("memory-guard".to_string())
+        .spawn(move || loop {
+            std::thread::sleep(interval);
+            let mut status = MemoryStatus::sample();
+            let actions = guard.check(&status, Instant::now());
+            for action in &actions {
+                eprintln!(
+                    "⚠️ 内存增长超过阈值 (RSS {:.1}MB, 纹理 {}), 执行 {:?}",
+                    status.rss_bytes.unwrap_or(0) as f64 / MB as f64,
+                    status.textures,
+                    action
+                );
+                if *action == MemoryAction::FlushCaches {
+                    release_heap();
+                }
+                xbus::post(*action);
+            }
+            total_actions += actions.len() as u64;
+            status.baseline_rss_bytes = guard.baseline();
+            status.actions = total_actions;
+            if let Some(current) = STATUS.get() {
+                *current.lock().unwrap() = status;
+            }
+        });
+    if let Err(e) = spawned {
+        eprintln!("❌ 内存监控线程启动失败: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(rss_mb: u64, textures: usize) -> MemoryStatus {
+        MemoryStatus {
+            rss_bytes: Some(rss_mb * MB),
+            baseline_rss_bytes: None,
+            frame_pool_in_use: 0,
+            frame_pool_capacity: 0,
+            textures,
+            texture_bytes: 0,
+            ort_sessions: 0,
+            ort_model_bytes: 0,
+            actions: 0,
+            time: Local::now(),
+        }
+    }
+
+    /// VmRSS 以 kB 为单位, 缺少该行时为 None
+    #[test]
+    fn test_parse_vm_rss() {
+        let proc_status =
+            "Name:\tsentinel\nVmPeak:\t 2048000 kB\nVmRSS:\t  524288 kB\nThreads:\t12\n";
+        assert_eq!(parse_vm_rss(proc_status), Some(512 * MB));
+        assert_eq!(parse_vm_rss("Name:\tsentinel\n"), None);
+    }
+
+    /// 预热期内不检查; 之后以首次采样为基线, 增长超过阈值时清缓存并重设基线, 冷却期内不重复
+    #[test]
+    fn test_leak_guard() {
+        let start = Instant::now();
+        let config = MemoryGuardConfig {
+            warmup_secs: 60,
+            rss_growth_mb: 100,
+            max_textures: 4,
+            cooldown_secs: 30,
+            ..Default::default()
+        };
+        let mut guard = LeakGuard::new(config, start);
+        let at = |secs| start + Duration::from_secs(secs);
+
+        assert!(guard.check(&status(900, 0), at(10)).is_empty());
+        assert!(guard.check(&status(400, 0), at(60)).is_empty());
+        assert_eq!(guard.baseline(), Some(400 * MB));
+        assert!(guard.check(&status(500, 4), at(90)).is_empty());
+        assert_eq!(
+            guard.check(&status(520, 6), at(120)),
+            vec![MemoryAction::FlushCaches, MemoryAction::RebuildTextures]
+        );
+        assert_eq!(guard.baseline(), Some(520 * MB));
+        assert!(guard.check(&status(700, 6), at(140)).is_empty());
+        assert_eq!(
+            guard.check(&status(700, 2), at(150)),
+            vec![MemoryAction::FlushCaches]
+        );
+    }
+
+    /// 指标按 sentinel_memory_* 输出, 不支持 RSS 的平台省略该项
+    #[test]
+    fn test_prometheus() {
+        let mut s = status(1, 3);
+        let text = s.to_prometheus();
+        assert!(text.contains("sentinel_memory_rss_bytes 1048576\n"));
+        assert!(text.contains("sentinel_memory_textures 3\n"));
+        assert!(!text.contains("baseline_rss_bytes"));
+        s.rss_bytes = None;
+        assert!(!s.to_prometheus().contains("sentinel_memory_rss_bytes"));
+    }
+}
@@ -0,0 +1,548 @@
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+// 行人属性识别 (Pedestrian attribute recognition)
+// 对人框裁剪图运行多标签分类器: 上装/下装颜色、是否背包、是否佩戴安全帽
+// 低频运行: 每条轨迹每隔一段时间重新识别一次, 概率按指数滑动平均平滑后挂到轨迹上
+//
+// 模型约定:
+// - 输入 [N, 3, H, W] (常见 256x128), RGB, ImageNet 归一化
+// - 输出 [N, K] 逻辑值, 每个标签独立 sigmoid (多标签)
+// - 标签顺序默认为 DEFAULT_LABELS, 可用模型同名 .txt 文件覆盖 (每行一个标签),
+//   不认识的标签忽略
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use ndarray::Array4;
+use ort::session::Session;
+use ort::value::{Value, ValueType};
+use serde::{Deserialize, Serialize};
+
+use crate::detection::types::BBox;
+use crate::ort_backend::session_builder;
+
+/// ImageNet 均值/方差
+const MEAN: [f32; 3] = [0.485, 0.456, 0.406];
+const STD: [f32; 3] = [0.229, 0.224, 0.225];
+
+/// 每帧最多识别的人框数 (其余轨迹留到后续帧)
+const MAX_PER_FRAME: usize = 4;
+
+/// 轨迹超过该时长未出现时丢弃缓存的属性
+const TRACK_EXPIRY: Duration = Duration::from_secs(10);
+
+/// 新识别结果的平滑权重
+const SMOOTHING: f32 = 0.5;
+
+/// 颜色概率低于该值时视为无法判断
+const MIN_COLOR_PROB: f32 = 0.3;
+
+/// 默认标签顺序 (上装 8 色, 下装 8 色, 背包, 安全帽)
+pub const DEFAULT_LABELS: [&str; 18] = [
+    "upper_black",
+    "upper_white",
+    "upper_gray",
+    "upper_red",
+    "upper_green",
+    "upper_blue",
+    "upper_yellow",
+    "upper_brown",
+    "lower_black",
+    "lower_white",
+    "lower_gray",
+    "lower_red",
+    "lower_green",
+    "lower_blue",
+    "lower_yellow",
+    "lower_brown",
+    "backpack",
+    "hardhat",
+];
+
+/// 衣着颜色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Color {
+    Black,
+    White,
+    Gray,
+    Red,
+    Green,
+    Blue,
+    Yellow,
+    Brown,
+}
+
+impl Color {
+    pub const ALL: [Color; 8] = [
+        Color::Black,
+        Color::White,
+        Color::Gray,
+        Color::Red,
+        Color::Green,
+        Color::Blue,
+        Color::Yellow,
+        Color::Brown,
+    ];
+
+    /// 标签中的颜色名 (小写英文)
+    pub fn name(self) -> &'static str {
+        match self {
+            Color::Black => "black",
+            Color::White => "white",
+            Color::Gray => "gray",
+            Color::Red => "red",
+            Color::Green => "green",
+            Color::Blue => "blue",
+            Color::Yellow => "yellow",
+            Color::Brown => "brown",
+        }
+    }
+
+    /// 中文颜色名 (事件描述)
+    pub fn label(self) -> &'static str {
+        match self {
+            Color::Black => "黑",
+            Color::White => "白",
+            Color::Gray => "灰",
+            Color::Red => "红",
+            Color::Green => "绿",
+            Color::Blue => "蓝",
+            Color::Yellow => "黄",
+            Color::Brown => "棕",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Color::ALL.into_iter().find(|c| c.name() == name)
+    }
+}
+
+/// 一条轨迹的行人属性 (平滑后)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PersonAttributes {
+    pub upper: Option<Color>, // 上装颜色, 无法判断时为 None
+    pub lower: Option<Color>, // 下装颜色
+    pub backpack: f32,        // 背包概率
+    pub hardhat: f32,         // 安全帽概率
+}
+
+impl PersonAttributes {
+    /// 按属性名取概率: `backpack`/`hardhat`, 或 `upper_<颜色>`/`lower_<颜色>`
+    /// (颜色已判断时为 1/0); 未知属性或颜色无法判断时为 None
+    pub fn probability(&self, attribute: &str) -> Option<f32> {
+        match attribute {
+            "backpack" => Some(self.backpack),
+            "hardhat" => Some(self.hardhat),
+            _ => {
+                let (part, color) = attribute.split_once('_')?;
+                let color = Color::from_name(color)?;
+                let actual = match part {
+                    "upper" => self.upper?,
+                    "lower" => self.lower?,
+                    _ => return None,
+                };
+                Some(if actual == color { 1.0 } else { 0.0 })
+            }
+        }
+    }
+
+    /// 标签上的简短描述, 如 `red/blue +bag -hat`
+    pub fn short_label(&self) -> String {
+        let color = |c: Option<Color>| c.map_or("?", Color::name);
+        format!(
+            "{}/{} {}bag {}hat",
+            color(self.upper),
+            color(self.lower),
+            if self.backpack >= 0.5 { '+' } else { '-' },
+            if self.hardhat >= 0.5 { '+' } else { '-' },
+        )
+    }
+}
+
+/// 属性的中文名 (区域规则描述), 未知属性原样返回
+pub fn attribute_label(attribute: &str) -> String {
+    match attribute {
+        "backpack" => "背包".to_string(),
+        "hardhat" => "安全帽".to_string(),
+        _ => match attribute
+            .split_once('_')
+            .and_then(|(part, c)| Some((part, Color::from_name(c)?)))
+        {
+            Some(("upper", c)) => format!("{}色上装", c.label()),
+            Some(("lower", c)) => format!("{}色下装", c.label()),
+            _ => attribute.to_string(),
+        },
+    }
+}
+
+/// 各属性的概率 (sigmoid 之后, 未输出的标签为 0)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AttributeScores {
+    pub upper: [f32; 8],
+    pub lower: [f32; 8],
+    pub backpack: f32,
+    pub hardhat: f32,
+}
+
+impl AttributeScores {
+    /// 按标签名把模型逻辑值映射到各属性
+    pub fn from_logits(labels: &[String], logits: &[f32]) -> Self {
+        let mut scores = Self::default();
+        for (label, &logit) in labels.iter().zip(logits) {
+            let p = 1.0 / (1.0 + (-logit).exp());
+            match label.as_str() {
+                "backpack" => scores.backpack = p,
+                "hardhat" => scores.hardhat = p,
+                _ => {
+                    let Some((part, color)) = label.split_once('_') else {
+                        continue;
+                    };
+                    let Some(i) = Color::from_name(color).map(|c| c as usize) else {
+                        continue;
+                    };
+                    match part {
+                        "upper" => scores.upper[i] = p,
+                        "lower" => scores.lower[i] = p,
+                        _ => {}
+                    }
+                }
+            }
+        }
+        scores
+    }
+
+    /// 取概率最高的颜色, 概率过低时无法判断
+    pub fn attributes(&self) -> PersonAttributes {
+        let color = |probs: &[f32; 8]| {
+            let (i, p) = probs.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1))?;
+            (*p >= MIN_COLOR_PROB).then_some(Color::ALL[i])
+        };
+        PersonAttributes {
+            upper: color(&self.upper),
+            lower: color(&self.lower),
+            backpack: self.backpack,
+            hardhat: self.hardhat,
+        }
+    }
+}
+
+impl TrackScores for AttributeScores {
+    fn blend(&mut self, other: &Self, alpha: f32) {
+        let mix = |a: &mut f32, b: f32| *a += (b - *a) * alpha;
+        for (a, b) in self.upper.iter_mut().zip(other.upper) {
+            mix(a, b);
+        }
+        for (a, b) in self.lower.iter_mut().zip(other.lower) {
+            mix(a, b);
+        }
+        mix(&mut self.backpack, other.backpack);
+        mix(&mut self.hardhat, other.hardhat);
+    }
+}
+
+/// 可按轨迹平滑的识别分数
+pub trait TrackScores: Copy {
+    /// 与新结果按权重混合
+    fn blend(&mut self, other: &Self, alpha: f32);
+}
+
+/// 单条轨迹的识别状态
+#[derive(Debug, Clone, Copy)]
+struct TrackState<S> {
+    scores: S,
+    last_run: Instant,
+    last_seen: Instant,
+}
+
+/// 按轨迹的识别调度与平滑 (不含模型, 便于单独测试)
+#[derive(Debug)]
+pub struct AttributeTracks<S = AttributeScores> {
+    interval: Duration,
+    tracks: HashMap<u32, TrackState<S>>,
+}
+
+impl<S: TrackScores> AttributeTracks<S> {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            tracks: HashMap::new(),
+        }
+    }
+
+    /// 本帧需要识别的轨迹下标: 未识别过的优先, 其次按上次识别时间从早到晚, 最多 [`MAX_PER_FRAME`] 个
+    pub fn due(&self, ids: &[u32], now: Instant) -> Vec<usize> {
+        let mut due: Vec<(Option<Instant>, usize)> = ids
+            .iter()
+            .enumerate()
+            .filter_map(|(i, id)| match self.tracks.get(id) {
+                None => Some((None, i)),
+                Some(t) if now.saturating_duration_since(t.last_run) >= self.interval => {
+                    Some((Some(t.last_run), i))
+                }
+                Some(_) => None,
+            })
+            .collect();
+        due.sort();
+        due.into_iter()
+            .take(MAX_PER_FRAME)
+            .map(|(_, i)| i)
+            .collect()
+    }
+
+    /// 记录一次识别结果 (与已有结果平滑)
+    pub fn record(&mut self, id: u32, scores: S, now: Instant) {
+        self.tracks
+            .entry(id)
+            .and_modify(|t| {
+                t.scores.blend(&scores, SMOOTHING);
+                t.last_run = now;
+            })
+            .or_insert(TrackState {
+                scores,
+                last_run: now,
+                last_seen: now,
+            });
+    }
+
+    /// 与 `ids` 一一对应的平滑分数 (尚未识别的为 None), 同时清理久未出现的轨迹
+    pub fn scores(&mut self, ids: &[u32], now: Instant) -> Vec<Option<S>> {
+        let result = ids
+            .iter()
+            .map(|id| {
+                let track = self.tracks.get_mut(id)?;
+                track.last_seen = now;
+                Some(track.scores)
+            })
+            .collect();
+        self.tracks
+            .retain(|_, t| now.saturating_duration_since(t.last_seen) < TRACK_EXPIRY);
+        result
+    }
+}
+
+/// 目标裁剪分类模型 (行人属性/车辆属性共用): 裁剪框 → 缩放归一化 → 逻辑值
+pub(crate) struct CropClassifier {
+    session: Session,
+    labels: Vec<String>,
+    input_w: usize,
+    input_h: usize,
+}
+
+impl CropClassifier {
+    /// 加载模型, 动态输入尺寸时使用 `default_size` (宽, 高); 同名 .txt 存在时作为标签列表
+    pub(crate) fn new(
+        model_path: &str,
+        default_size: (usize, usize),
+        default_labels: &[&str],
+    ) -> Result<Self> {
+        let session = session_builder()?.commit_from_file(model_path)?;
+        let (input_h, input_w) = match session.inputs.first().map(|i| &i.input_type) {
+            Some(ValueType::Tensor { shape, .. }) if shape.len() == 4 => (
+                if shape[2] > 0 {
+                    shape[2] as usize
+                } else {
+                    default_size.1
+                },
+                if shape[3] > 0 {
+                    shape[3] as usize
+                } else {
+                    default_size.0
+                },
+            ),
+            _ => return Err(anyhow!("分类模型输入格式不支持: {}", model_path)),
+        };
+        let labels = match std::fs::read_to_string(Path::new(model_path).with_extension("txt")) {
+            Ok(text) => text
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(str::to_string)
+                .collect(),
+            Err(_) => default_labels.iter().map(|l| l.to_string()).collect(),
+        };
+        Ok(Self {
+            session,
+            labels,
+            input_w,
+            input_h,
+        })
+    }
+
+    pub(crate) fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    /// 对一个检测框运行分类器, 返回原始逻辑值 (与标签一一对应)
+    pub(crate) fn run(
+        &mut self,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        bbox: &BBox,
+    ) -> Result<Vec<f32>> {
+        let (iw, ih) = (self.input_w, self.input_h);
+        let (w, h) = (width as usize, height as usize);
+        if rgba.len() < w * h * 4 {
+            return Err(anyhow!("帧数据长度不足"));
+        }
+        let x1 = bbox.x1.clamp(0.0, w as f32 - 1.0);
+        let y1 = bbox.y1.clamp(0.0, h as f32 - 1.0);
+        let bw = (bbox.x2.min(w as f32) - x1).max(1.0);
+        let bh = (bbox.y2.min(h as f32) - y1).max(1.0);
+
+        // 最近邻缩放裁剪框 + 归一化, 直接从RGBA写入NCHW
+        let mut input = Array4::<f32>::zeros((1, 3, ih, iw));
+        for y in 0..ih {
+            let sy = ((y1 + (y as f32 + 0.5) * bh / ih as f32) as usize).min(h - 1);
+            for x in 0..iw {
+                let sx = ((x1 + (x as f32 + 0.5) * bw / iw as f32) as usize).min(w - 1);
+                let idx = (sy * w + sx) * 4;
+                for c in 0..3 {
+                    input[[0, c, y, x]] = (rgba[idx + c] as f32 / 255.0 - MEAN[c]) / STD[c];
+                }
+            }
+        }
+
+        let input_value = Value::from_array(input)?;
+        let outputs = self.session.run(ort::inputs![input_value])?;
+        let (_, value) = outputs
+            .iter()
+            .next()
+            .ok_or_else(|| anyhow!("分类模型无输出"))?;
+        let (_, data) = value.try_extract_tensor::<f32>()?;
+        Ok(data.to_vec())
+    }
+}
+
+/// 行人属性识别器
+pub struct AttributeRecognizer {
+    classifier: CropClassifier,
+    tracks: AttributeTracks,
+}
+
+impl AttributeRecognizer {
+    /// 加载属性模型
+    ///
+    /// # 参数
+    /// - `model_path`: ONNX 模型路径, 同名 .txt 存在时作为标签列表
+    /// - `interval`: 同一轨迹的重新识别间隔, 衣着属性基本不变, 一般 1~3 秒即可
+    pub fn new(model_path: &str, interval: Duration) -> Result<Self> {
+        Ok(Self {
+            classifier: CropClassifier::new(model_path, (128, 256), &DEFAULT_LABELS)?,
+            tracks: AttributeTracks::new(interval),
+        })
+    }
+
+    pub fn labels(&self) -> &[String] {
+        self.classifier.labels()
+    }
+
+    /// 按轨迹更新属性 (bbox.class_id 为轨迹ID), 返回与 `bboxes` 一一对应的属性
+    ///
+    /// `classes` 为各框的检测类别 (见 [`super::vehicle::source_classes`]), 只识别人 (类别0)
+    pub fn update(
+        &mut self,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        bboxes: &[BBox],
+        classes: &[Option<u32>],
+        now: Instant,
+    ) -> Vec<Option<PersonAttributes>> {
+        let persons: Vec<usize> = (0..bboxes.len())
+            .filter(|&i| classes.get(i).copied().flatten() == Some(0))
+            .collect();
+        let ids: Vec<u32> = persons.iter().map(|&i| bboxes[i].class_id).collect();
+        for k in self.tracks.due(&ids, now) {
+            match self
+                .classifier
+                .run(rgba, width, height, &bboxes[persons[k]])
+            {
+                Ok(logits) => {
+                    let scores = AttributeScores::from_logits(self.classifier.labels(), &logits);
+                    self.tracks.record(ids[k], scores, now);
+                }
+                Err(e) => {
+                    eprintln!("❌ 行人属性识别失败: {}", e);
+                    break;
+                }
+            }
+        }
+        let mut result = vec![None; bboxes.len()];
+        for (i, scores) in persons.into_iter().zip(self.tracks.scores(&ids, now)) {
+            result[i] = scores.map(|s| s.attributes());
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels() -> Vec<String> {
+        DEFAULT_LABELS.iter().map(|l| l.to_string()).collect()
+    }
+
+    /// 逻辑值按标签映射: 颜色取最高概率, 过低时无法判断; 属性名查询与描述
+    #[test]
+    fn test_scores_to_attributes() {
+        let mut logits = vec![-4.0; DEFAULT_LABELS.len()];
+        logits[3] = 3.0; // upper_red
+        logits[8 + 5] = 1.0; // lower_blue
+        logits[16] = -2.0; // backpack
+        logits[17] = 2.5; // hardhat
+        let attrs = AttributeScores::from_logits(&labels(), &logits).attributes();
+        assert_eq!(attrs.upper, Some(Color::Red));
+        assert_eq!(attrs.lower, Some(Color::Blue));
+        assert!(attrs.backpack < 0.5 && attrs.hardhat > 0.9);
+        assert_eq!(attrs.probability("upper_red"), Some(1.0));
+        assert_eq!(attrs.probability("lower_red"), Some(0.0));
+        assert_eq!(attrs.probability("helmet"), None);
+        assert_eq!(attrs.short_label(), "red/blue -bag +hat");
+        assert_eq!(attribute_label("upper_red"), "红色上装");
+
+        // 自定义标签顺序, 未知标签忽略; 颜色概率都低时无法判断
+        let custom: Vec<String> = ["hardhat", "glasses"].map(String::from).to_vec();
+        let attrs = AttributeScores::from_logits(&custom, &[-3.0, 5.0]).attributes();
+        assert_eq!((attrs.upper, attrs.probability("upper_red")), (None, None));
+        assert!(attrs.hardhat < 0.1);
+    }
+
+    /// 新轨迹优先识别, 间隔内不重复; 结果平滑, 久未出现的轨迹被清理
+    #[test]
+    fn test_track_schedule_and_smoothing() {
+        let mut tracks = AttributeTracks::new(Duration::from_secs(2));
+        let t0 = Instant::now();
+        let ids: Vec<u32> = (1..=6).collect();
+        assert_eq!(tracks.due(&ids, t0), vec![0, 1, 2, 3]);
+
+        let hat = |p: f32| AttributeScores {
+            hardhat: p,
+            ..AttributeScores::default()
+        };
+        for id in 1..=4 {
+            tracks.record(id, hat(1.0), t0);
+        }
+        let t1 = t0 + Duration::from_secs(1);
+        assert_eq!(tracks.due(&ids, t1), vec![4, 5]);
+        tracks.record(5, hat(1.0), t1);
+        tracks.record(6, hat(1.0), t1);
+
+        // 间隔到期后按上次识别时间从早到晚
+        let t2 = t0 + Duration::from_secs(3);
+        assert_eq!(tracks.due(&ids, t2), vec![0, 1, 2, 3]);
+        tracks.record(1, hat(0.0), t2);
+        let attrs = tracks.scores(&[1, 2, 9], t2);
+        assert_eq!(attrs[0].unwrap().hardhat, 0.5);
+        assert_eq!(attrs[1].unwrap().hardhat, 1.0);
+        assert!(attrs[2].is_none());
+
+        let later = t2 + TRACK_EXPIRY;
+        tracks.scores(&[2], later);
+        assert_eq!(tracks.tracks.keys().copied().collect::<Vec<_>>(), vec![2]);
+    }
+}
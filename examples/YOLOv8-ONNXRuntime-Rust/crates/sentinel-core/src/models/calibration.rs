@@ -0,0 +1,314 @@
+//! 置信度校准 (Confidence calibration)
+//!
+//! 不同模型的分数分布差别很大 (见 `ModelType::default_conf_threshold`: v10 端到端模型
+//! 与 v8/NanoDet 需要不同阈值). 按模型配置温度缩放或 Platt 参数, 把原始分数映射为
+//! 校准后的概率, 界面上的同一个阈值在不同模型上表现一致.
+//!
+//! 校准函数单调递增, 因此阈值先反变换到原始分数空间交给后处理器比较, 输出框的置信度
+//! 再正变换, 结果与 "先校准再过滤" 完全一致, 各后处理器无需改动.
+//!
+//! 配置文件 (calibration.json), 键为模型文件名 (不含扩展名) 或模型类型, 文件名优先:
+//! ```json
+//! {
+//!   "models": {
+//!     "yolov8n": { "method": "temperature", "temperature": 1.8 },
+//!     "YOLOv10": { "method": "platt", "a": 1.2, "b": -0.4 }
+//!   }
+//! }
+//! ```
+
+use anyhow::Result;
+use image::DynamicImage;
+use ndarray::{Array, IxDyn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use super::{Model, ModelType, PreprocessSpec, Vocabulary};
+#[cfg(feature = "ort")]
+use crate::OrtBackend;
+use crate::{DetectionResult, YOLOTask};
+
+/// 默认校准配置文件
+pub const CALIBRATION_CONFIG_PATH: &str = "calibration.json";
+
+/// 全局校准配置 (启动时设置一次, 加载模型时查询)
+static CALIBRATION: OnceLock<CalibrationConfig> = OnceLock::new();
+
+/// logit 计算时的分数裁剪, 避免 0/1 处无穷大
+const EPS: f32 = 1e-6;
+
+/// 单个模型的校准变换
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum Calibration {
+    /// 不校准
+    #[default]
+    Identity,
+    /// 温度缩放: sigmoid(logit(p) / T), T > 1 压低过度自信的分数
+    Temperature { temperature: f32 },
+    /// Platt 缩放: sigmoid(a · logit(p) + b)
+    Platt { a: f32, b: f32 },
+}
+
+fn logit(p: f32) -> f32 {
+    let p = p.clamp(EPS, 1.0 - EPS);
+    (p / (1.0 - p)).ln()
+}
+
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+impl Calibration {
+    /// 参数是否保证单调递增 (温度/斜率必须为正)
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            Calibration::Identity => true,
+            Calibration::Temperature { temperature } => temperature > 0.0,
+            Calibration::Platt { a, .. } => a > 0.0,
+        }
+    }
+
+    /// 原始分数 → 校准后的置信度
+    pub fn apply(&self, score: f32) -> f32 {
+        match *self {
+            Calibration::Identity => score,
+            Calibration::Temperature { temperature } => sigmoid(logit(score) / temperature),
+            Calibration::Platt { a, b } => sigmoid(a * logit(score) + b),
+        }
+    }
+
+    /// 校准后的置信度 → 原始分数 (阈值换算)
+    pub fn invert(&self, confidence: f32) -> f32 {
+        match *self {
+            Calibration::Identity => confidence,
+            Calibration::Temperature { temperature } => sigmoid(logit(confidence) * temperature),
+            Calibration::Platt { a, b } => sigmoid((logit(confidence) - b) / a),
+        }
+    }
+
+    /// 校准检测结果中所有框的置信度
+    pub fn apply_results(&self, results: &mut [DetectionResult]) {
+        if *self == Calibration::Identity {
+            return;
+        }
+        for bbox in results.iter_mut().filter_map(|r| r.bboxes_mut()).flatten() {
+            bbox.set_confidence(self.apply(bbox.confidence()));
+        }
+    }
+}
+
+/// 各模型的校准参数
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalibrationConfig {
+    pub models: HashMap<String, Calibration>,
+}
+
+impl CalibrationConfig {
+    /// 从文件加载, 文件不存在时返回 None (不校准); 参数非法的条目忽略
+    pub fn load(path: &str) -> Option<Self> {
+        let json = fs::read_to_string(path).ok()?;
+        match serde_json::from_str::<Self>(&json) {
+            Ok(mut config) => {
+                config.models.retain(|name, c| {
+                    let valid = c.is_valid();
+                    if !valid {
+                        eprintln!("⚠️  校准参数非法 ({}: {:?}), 该模型不校准", name, c);
+                    }
+                    valid
+                });
+                println!(
+                    "✅ 置信度校准已从 {} 加载 ({}个模型)",
+                    path,
+                    config.models.len()
+                );
+                Some(config)
+            }
+            Err(e) => {
+                eprintln!("⚠️  置信度校准解析失败: {}, 不校准", e);
+                None
+            }
+        }
+    }
+
+    /// 设为全局配置 (须在加载模型之前)
+    pub fn apply(self) {
+        if CALIBRATION.set(self).is_err() {
+            eprintln!("⚠️ 置信度校准已生效, 忽略重复设置");
+        }
+    }
+
+    /// 查找模型的校准参数: 先按文件名 (不含扩展名), 再按模型类型 (不区分大小写)
+    pub fn lookup(&self, model_path: &str, model_type: ModelType) -> Calibration {
+        let stem = Path::new(model_path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or(model_path);
+        let type_name = format!("{:?}", model_type);
+        self.models
+            .get(stem)
+            .or_else(|| {
+                self.models
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(&type_name))
+                    .map(|(_, c)| c)
+            })
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// 全局配置中模型的校准参数, 未配置时不校准
+pub fn calibration_for(model_path: &str, model_type: ModelType) -> Calibration {
+    CALIBRATION
+        .get()
+        .map(|config| config.lookup(model_path, model_type))
+        .unwrap_or_default()
+}
+
+/// 带置信度校准的模型: 阈值在校准空间设置/读取, 后处理输出校准后的置信度
+pub struct CalibratedModel {
+    inner: Box<dyn Model + Send>,
+    calibration: Calibration,
+}
+
+impl CalibratedModel {
+    /// `inner` 当前的阈值视为校准空间的阈值
+    pub fn new(inner: Box<dyn Model + Send>, calibration: Calibration) -> Self {
+        let mut model = Self { inner, calibration };
+        let conf = model.inner.conf();
+        model.set_conf(conf);
+        model
+    }
+}
+
+impl Model for CalibratedModel {
+    fn preprocess(&mut self, images: &[DynamicImage]) -> Result<Vec<Array<f32, IxDyn>>> {
+        self.inner.preprocess(images)
+    }
+
+    fn run(&mut self, xs: Vec<Array<f32, IxDyn>>, profile: bool) -> Result<Vec<Array<f32, IxDyn>>> {
+        self.inner.run(xs, profile)
+    }
+
+    fn postprocess(
+        &self,
+        xs: Vec<Array<f32, IxDyn>>,
+        xs0: &[DynamicImage],
+    ) -> Result<Vec<DetectionResult>> {
+        let mut results = self.inner.postprocess(xs, xs0)?;
+        self.calibration.apply_results(&mut results);
+        Ok(results)
+    }
+
+    #[cfg(feature = "ort")]
+    fn engine_mut(&mut self) -> Option<&mut OrtBackend> {
+        self.inner.engine_mut()
+    }
+
+    fn summary(&self) {
+        self.inner.summary();
+        println!("🎚️ 置信度校准: {:?}", self.calibration);
+    }
+
+    fn supports_task(&self, task: YOLOTask) -> bool {
+        self.inner.supports_task(task)
+    }
+
+    fn set_conf(&mut self, val: f32) {
+        self.inner.set_conf(self.calibration.invert(val));
+    }
+
+    fn conf(&self) -> f32 {
+        self.calibration.apply(self.inner.conf())
+    }
+
+    fn set_iou(&mut self, val: f32) {
+        self.inner.set_iou(val);
+    }
+
+    fn iou(&self) -> f32 {
+        self.inner.iou()
+    }
+
+    fn preprocess_spec(&self) -> PreprocessSpec {
+        self.inner.preprocess_spec()
+    }
+
+    fn calibration(&self) -> Calibration {
+        self.calibration
+    }
+
+    fn native_preprocess(&self) -> bool {
+        self.inner.native_preprocess()
+    }
+
+    fn set_input_size(&mut self, width: u32, height: u32) -> bool {
+        self.inner.set_input_size(width, height)
+    }
+
+    fn vocabulary(&self) -> Option<&Vocabulary> {
+        self.inner.vocabulary()
+    }
+
+    fn set_vocabulary(&mut self, classes: &[String]) -> Result<()> {
+        self.inner.set_vocabulary(classes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 正/反变换互逆且单调, 温度 > 1 压低高分、抬高低分
+    #[test]
+    fn test_calibration_roundtrip() {
+        let calibrations = [
+            Calibration::Identity,
+            Calibration::Temperature { temperature: 2.0 },
+            Calibration::Platt { a: 1.5, b: -0.7 },
+        ];
+        for c in calibrations {
+            let mut last = 0.0;
+            for i in 1..20 {
+                let p = i as f32 / 20.0;
+                let q = c.apply(p);
+                assert!(q > last, "{:?} 非单调", c);
+                assert!((c.invert(q) - p).abs() < 1e-4, "{:?} {}", c, p);
+                last = q;
+            }
+        }
+        let t = Calibration::Temperature { temperature: 2.0 };
+        assert!(t.apply(0.9) < 0.9 && t.apply(0.1) > 0.1);
+        assert!((t.apply(0.5) - 0.5).abs() < 1e-6);
+        assert!(!Calibration::Platt { a: -1.0, b: 0.0 }.is_valid());
+    }
+
+    /// 文件名优先于模型类型, 模型类型不区分大小写, 未配置时不校准
+    #[test]
+    fn test_lookup() {
+        let config: CalibrationConfig = serde_json::from_str(
+            r#"{"models": {
+                "yolov8n": {"method": "temperature", "temperature": 1.8},
+                "yolov8": {"method": "platt", "a": 1.0, "b": 0.5}
+            }}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.lookup("models/yolov8n.onnx", ModelType::YOLOv8),
+            Calibration::Temperature { temperature: 1.8 }
+        );
+        assert_eq!(
+            config.lookup("models/yolov8s.onnx", ModelType::YOLOv8),
+            Calibration::Platt { a: 1.0, b: 0.5 }
+        );
+        assert_eq!(
+            config.lookup("models/nanodet-m.onnx", ModelType::NanoDet),
+            Calibration::Identity
+        );
+    }
+}
@@ -0,0 +1,412 @@
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+// CLIP 开放词汇检索
+// 对检测框区域提取图像嵌入, 与文本提示("red backpack")的文本嵌入做余弦相似度,
+// 命中的检测框在画面中高亮显示
+//
+// 模型目录结构 (HuggingFace openai/clip-vit-base-patch32 导出):
+//   {dir}/visual.onnx   - 图像编码器  pixel_values [n, 3, 224, 224] → [n, 512]
+//   {dir}/textual.onnx  - 文本编码器  input_ids [1, 77] (+attention_mask) → [1, 512]
+//   {dir}/vocab.json    - BPE 词表
+//   {dir}/merges.txt    - BPE 合并规则
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use image::{imageops::FilterType, DynamicImage, ImageBuffer, Rgb};
+use ndarray::{Array, Array2, Array4};
+use ort::session::Session;
+use ort::tensor::TensorElementType;
+use ort::value::{Value, ValueType};
+use regex::Regex;
+
+use crate::detection::types::BBox;
+use crate::ort_backend::session_builder;
+use crate::Embedding;
+
+/// CLIP 官方图像归一化参数
+const MEAN: [f32; 3] = [0.481_454_7, 0.457_827_5, 0.408_210_7];
+const STD: [f32; 3] = [0.268_629_5, 0.261_302_6, 0.275_777_1];
+
+/// 文本上下文长度
+const CONTEXT_LEN: usize = 77;
+
+/// CLIP BPE 分词器
+pub struct ClipTokenizer {
+    encoder: HashMap<String, i64>,
+    bpe_ranks: HashMap<(String, String), usize>,
+    byte_encoder: Vec<char>,
+    pattern: Regex,
+    sot: i64,
+    eot: i64,
+}
+
+impl ClipTokenizer {
+    /// 从 HuggingFace 格式的 vocab.json + merges.txt 加载
+    pub fn from_files(vocab: &Path, merges: &Path) -> Result<Self> {
+        let encoder: HashMap<String, i64> = serde_json::from_str(&std::fs::read_to_string(vocab)?)?;
+        let merges = std::fs::read_to_string(merges)?
+            .lines()
+            .filter(|l| !l.starts_with("#version") && !l.trim().is_empty())
+            .filter_map(|l| {
+                let mut it = l.split_whitespace();
+                Some((it.next()?.to_string(), it.next()?.to_string()))
+            })
+            .collect();
+        Self::new(encoder, merges)
+    }
+
+    /// 从内存中的词表与合并规则创建
+    pub fn new(encoder: HashMap<String, i64>, merges: Vec<(String, String)>) -> Result<Self> {
+        let sot = *encoder
+            .get("<|startoftext|>")
+            .ok_or_else(|| anyhow!("词表缺少 <|startoftext|>"))?;
+        let eot = *encoder
+            .get("<|endoftext|>")
+            .ok_or_else(|| anyhow!("词表缺少 <|endoftext|>"))?;
+        let bpe_ranks = merges
+            .into_iter()
+            .enumerate()
+            .map(|(rank, pair)| (pair, rank))
+            .collect();
+        let pattern = Regex::new(
+            r"<\|startoftext\|>|<\|endoftext\|>|'s|'t|'re|'ve|'m|'ll|'d|\p{L}+|\p{N}|[^\s\p{L}\p{N}]+",
+        )?;
+
+        Ok(Self {
+            encoder,
+            bpe_ranks,
+            byte_encoder: bytes_to_unicode(),
+            pattern,
+            sot,
+            eot,
+        })
+    }
+
+    /// 对单个词做 BPE 合并
+    fn bpe(&self, token: &str) -> Vec<String> {
+        let chars: Vec<char> = token.chars().collect();
+        if chars.is_empty() {
+            return Vec::new();
+        }
+        let mut word: Vec<String> = chars.iter().map(|c| c.to_string()).collect();
+        let last = word.len() - 1;
+        word[last].push_str("</w>");
+
+        loop {
+            // 找到排名最靠前(最先学到)的相邻对
+            let best = word
+                .windows(2)
+                .filter_map(|w| {
+                    self.bpe_ranks
+                        .get(&(w[0].clone(), w[1].clone()))
+                        .map(|&r| (r, w[0].clone(), w[1].clone()))
+                })
+                .min_by_key(|(r, _, _)| *r);
+            let Some((_, first, second)) = best else {
+                break;
+            };
+
+            let mut merged = Vec::with_capacity(word.len());
+            let mut i = 0;
+            while i < word.len() {
+                if i + 1 < word.len() && word[i] == first && word[i + 1] == second {
+                    merged.push(format!("{}{}", first, second));
+                    i += 2;
+                } else {
+                    merged.push(word[i].clone());
+                    i += 1;
+                }
+            }
+            word = merged;
+            if word.len() == 1 {
+                break;
+            }
+        }
+        word
+    }
+
+    /// 文本 → token id (不含起止符)
+    pub fn encode(&self, text: &str) -> Vec<i64> {
+        let text = text
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        let mut ids = Vec::new();
+        for m in self.pattern.find_iter(&text) {
+            let token: String = m
+                .as_str()
+                .bytes()
+                .map(|b| self.byte_encoder[b as usize])
+                .collect();
+            for piece in self.bpe(&token) {
+                if let Some(&id) = self.encoder.get(&piece) {
+                    ids.push(id);
+                }
+            }
+        }
+        ids
+    }
+
+    /// 文本 → 定长 [77] 的 token 序列 (起始符 + token + 结束符 + 0填充)
+    pub fn tokenize(&self, text: &str) -> (Vec<i64>, usize) {
+        let mut ids = vec![self.sot];
+        let mut tokens = self.encode(text);
+        tokens.truncate(CONTEXT_LEN - 2);
+        ids.extend(tokens);
+        ids.push(self.eot);
+        let len = ids.len();
+        ids.resize(CONTEXT_LEN, 0);
+        (ids, len)
+    }
+}
+
+/// GPT-2/CLIP 字节 → 可见 unicode 字符映射
+fn bytes_to_unicode() -> Vec<char> {
+    let mut table = vec!['\0'; 256];
+    let mut n = 0u32;
+    for b in 0..256u32 {
+        let printable = (b'!' as u32..=b'~' as u32).contains(&b)
+            || (0xA1..=0xAC).contains(&b)
+            || (0xAE..=0xFF).contains(&b);
+        table[b as usize] = if printable {
+            char::from_u32(b).unwrap_or('\0')
+        } else {
+            n += 1;
+            char::from_u32(255 + n).unwrap_or('\0')
+        };
+    }
+    table
+}
+
+/// 计算区域嵌入与文本嵌入的相似度, 返回命中的 (框索引, 相似度)
+///
+/// 先按 `threshold` 过滤, 再用 `Embedding::topk` 取最相似的前 `k` 个
+pub fn match_regions(
+    regions: &[Embedding],
+    text: &Embedding,
+    threshold: f32,
+    k: usize,
+) -> Vec<(usize, f32)> {
+    let t = text.norm();
+    let sims: Vec<f32> = regions.iter().map(|r| (r.norm() * &t).sum()).collect();
+    let sims = Embedding::new(Array::from_vec(sims).into_dyn());
+    sims.topk(k)
+        .into_iter()
+        .filter(|(_, s)| *s >= threshold)
+        .collect()
+}
+
+/// CLIP 文本编码器 (textual.onnx + BPE 分词器)
+///
+/// 文本检索与 YOLO-World 的类别词表共用
+pub struct ClipTextEncoder {
+    textual: Session,
+    tokenizer: ClipTokenizer,
+    text_dtype: TensorElementType,
+}
+
+impl ClipTextEncoder {
+    /// 从模型目录加载 (textual.onnx/vocab.json/merges.txt)
+    pub fn new(dir: &str) -> Result<Self> {
+        let dir = Path::new(dir);
+        let textual = session_builder()?.commit_from_file(dir.join("textual.onnx"))?;
+        let tokenizer =
+            ClipTokenizer::from_files(&dir.join("vocab.json"), &dir.join("merges.txt"))?;
+        let text_dtype = match textual.inputs.first().map(|i| &i.input_type) {
+            Some(ValueType::Tensor { ty, .. }) => *ty,
+            _ => TensorElementType::Int64,
+        };
+        Ok(Self {
+            textual,
+            tokenizer,
+            text_dtype,
+        })
+    }
+
+    /// 文本 → 文本嵌入 (未归一化)
+    pub fn encode(&mut self, prompt: &str) -> Result<Embedding> {
+        let (ids, len) = self.tokenizer.tokenize(prompt);
+        let mask: Vec<i64> = (0..CONTEXT_LEN).map(|i| (i < len) as i64).collect();
+        let with_mask = self.textual.inputs.len() >= 2;
+
+        let outputs = if self.text_dtype == TensorElementType::Int32 {
+            let ids =
+                Array2::from_shape_vec((1, CONTEXT_LEN), ids.iter().map(|&v| v as i32).collect())?;
+            let mask =
+                Array2::from_shape_vec((1, CONTEXT_LEN), mask.iter().map(|&v| v as i32).collect())?;
+            if with_mask {
+                self.textual.run(ort::inputs![
+                    Value::from_array(ids)?,
+                    Value::from_array(mask)?
+                ])?
+            } else {
+                self.textual.run(ort::inputs![Value::from_array(ids)?])?
+            }
+        } else {
+            let ids = Array2::from_shape_vec((1, CONTEXT_LEN), ids)?;
+            let mask = Array2::from_shape_vec((1, CONTEXT_LEN), mask)?;
+            if with_mask {
+                self.textual.run(ort::inputs![
+                    Value::from_array(ids)?,
+                    Value::from_array(mask)?
+                ])?
+            } else {
+                self.textual.run(ort::inputs![Value::from_array(ids)?])?
+            }
+        };
+
+        // 取第一个二维输出 [1, D] (跳过 last_hidden_state 等三维输出)
+        for (_, value) in outputs.iter() {
+            if let Ok((shape, data)) = value.try_extract_tensor::<f32>() {
+                if shape.len() == 2 {
+                    return Ok(Embedding::new(Array::from_vec(data.to_vec()).into_dyn()));
+                }
+            }
+        }
+        Err(anyhow!("文本编码器没有 [1, D] 输出"))
+    }
+}
+
+/// CLIP 图像/文本双塔模型
+pub struct ClipModel {
+    visual: Session,
+    text: ClipTextEncoder,
+    input_size: u32,
+}
+
+impl ClipModel {
+    /// 从模型目录加载
+    pub fn new(dir: &str) -> Result<Self> {
+        let visual = session_builder()?.commit_from_file(Path::new(dir).join("visual.onnx"))?;
+        let text = ClipTextEncoder::new(dir)?;
+
+        let input_size = match visual.inputs.first().map(|i| &i.input_type) {
+            Some(ValueType::Tensor { shape, .. }) if shape.len() == 4 && shape[3] > 0 => {
+                shape[3] as u32
+            }
+            _ => 224,
+        };
+
+        Ok(Self {
+            visual,
+            text,
+            input_size,
+        })
+    }
+
+    /// 文本提示 → 文本嵌入
+    pub fn encode_text(&mut self, prompt: &str) -> Result<Embedding> {
+        self.text.encode(prompt)
+    }
+
+    /// 检测框区域 → 图像嵌入 (逐框推理)
+    pub fn encode_regions(
+        &mut self,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        bboxes: &[BBox],
+    ) -> Result<Vec<Embedding>> {
+        let size = self.input_size;
+        let mut embeddings = Vec::with_capacity(bboxes.len());
+        for bbox in bboxes {
+            let x1 = (bbox.x1.max(0.0) as u32).min(width.saturating_sub(1));
+            let y1 = (bbox.y1.max(0.0) as u32).min(height.saturating_sub(1));
+            let x2 = (bbox.x2.max(0.0) as u32).clamp(x1 + 1, width);
+            let y2 = (bbox.y2.max(0.0) as u32).clamp(y1 + 1, height);
+            let (cw, ch) = (x2 - x1, y2 - y1);
+
+            // 裁剪 RGBA → RGB
+            let mut crop = Vec::with_capacity((cw * ch * 3) as usize);
+            for y in y1..y2 {
+                let row = ((y * width + x1) * 4) as usize;
+                for px in rgba[row..row + (cw * 4) as usize].chunks_exact(4) {
+                    crop.extend_from_slice(&px[..3]);
+                }
+            }
+            let img = match ImageBuffer::<Rgb<u8>, _>::from_raw(cw, ch, crop) {
+                Some(img) => DynamicImage::ImageRgb8(img),
+                None => continue,
+            };
+            let resized = img.resize_exact(size, size, FilterType::Triangle).to_rgb8();
+
+            let s = size as usize;
+            let mut input = Array4::<f32>::zeros((1, 3, s, s));
+            for (x, y, p) in resized.enumerate_pixels() {
+                for c in 0..3 {
+                    input[[0, c, y as usize, x as usize]] =
+                        (p[c] as f32 / 255.0 - MEAN[c]) / STD[c];
+                }
+            }
+
+            let outputs = self.visual.run(ort::inputs![Value::from_array(input)?])?;
+            let (_, value) = outputs
+                .iter()
+                .next()
+                .ok_or_else(|| anyhow!("图像编码器无输出"))?;
+            let (_, data) = value.try_extract_tensor::<f32>()?;
+            embeddings.push(Embedding::new(Array::from_vec(data.to_vec()).into_dyn()));
+        }
+        Ok(embeddings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiny_tokenizer() -> ClipTokenizer {
+        let vocab = [
+            ("<|startoftext|>", 49406),
+            ("<|endoftext|>", 49407),
+            ("r", 81),
+            ("e", 68),
+            ("d</w>", 330),
+            ("re", 1001),
+            ("red</w>", 736),
+        ];
+        let encoder = vocab.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+        let merges = vec![
+            ("r".to_string(), "e".to_string()),
+            ("re".to_string(), "d</w>".to_string()),
+        ];
+        ClipTokenizer::new(encoder, merges).unwrap()
+    }
+
+    /// BPE 按合并规则把 "red" 合并成一个 token
+    #[test]
+    fn test_bpe_merge() {
+        let tok = tiny_tokenizer();
+        assert_eq!(tok.encode("RED"), vec![736]);
+        let (ids, len) = tok.tokenize("red");
+        assert_eq!(&ids[..3], &[49406, 736, 49407]);
+        assert_eq!(len, 3);
+        assert_eq!(ids.len(), CONTEXT_LEN);
+    }
+
+    /// 可见 ASCII 字节映射为自身, 空格映射为 'Ġ'
+    #[test]
+    fn test_bytes_to_unicode() {
+        let table = bytes_to_unicode();
+        assert_eq!(table[b'a' as usize], 'a');
+        assert_eq!(table[b' ' as usize], 'Ġ');
+    }
+
+    /// 余弦相似度过滤 + topk
+    #[test]
+    fn test_match_regions() {
+        let text = Embedding::new(Array::from_vec(vec![1.0, 0.0]).into_dyn());
+        let regions = vec![
+            Embedding::new(Array::from_vec(vec![0.0, 1.0]).into_dyn()),
+            Embedding::new(Array::from_vec(vec![2.0, 0.1]).into_dyn()),
+            Embedding::new(Array::from_vec(vec![1.0, 1.0]).into_dyn()),
+        ];
+        let hits = match_regions(&regions, &text, 0.5, 2);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].0, 1);
+        assert_eq!(hits[1].0, 2);
+    }
+}
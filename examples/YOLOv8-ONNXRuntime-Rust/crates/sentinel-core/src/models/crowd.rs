@@ -0,0 +1,245 @@
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+// 人群密度估计 (CSRNet / DM-Count 风格的密度图回归模型)
+// 极密集场景下逐人检测漏检严重, 密度图对每个像素回归"人数密度", 全图求和即人数.
+// 与深度模型一样低频运行(每N帧一次), 缓存最近一次的密度图
+//
+// 注意:
+// - 输出分辨率一般为输入的 1/8, 求和与分辨率无关
+// - 输入为动态尺寸时按帧宽高比缩放到长边 DEFAULT_LONG_SIDE (取8的倍数)
+
+use anyhow::{anyhow, Result};
+use ndarray::Array4;
+use ort::session::Session;
+use ort::value::{Value, ValueType};
+
+use crate::analytics::calibration::Homography;
+use crate::ort_backend::session_builder;
+
+/// ImageNet 均值/方差 (CSRNet 官方预处理)
+const MEAN: [f32; 3] = [0.485, 0.456, 0.406];
+const STD: [f32; 3] = [0.229, 0.224, 0.225];
+
+/// 动态输入时的长边尺寸
+const DEFAULT_LONG_SIDE: usize = 640;
+
+/// 低于该密度的单元不参与地面映射 (背景噪声)
+const MIN_CELL_DENSITY: f32 = 1e-4;
+
+/// 密度图 (模型输出分辨率)
+#[derive(Clone, Debug, Default)]
+pub struct DensityMap {
+    pub data: Vec<f32>,
+    pub width: usize,
+    pub height: usize,
+    /// 全图估计人数 (密度图求和)
+    pub count: f32,
+    /// 各单元中心的地面坐标 (米) 与人数, 未做地面标定时为空
+    pub ground: Vec<((f32, f32), f32)>,
+}
+
+impl DensityMap {
+    pub fn new(data: Vec<f32>, width: usize, height: usize) -> Self {
+        let count = data
+            .iter()
+            .filter(|v| v.is_finite())
+            .map(|v| v.max(0.0))
+            .sum();
+        Self {
+            data,
+            width,
+            height,
+            count,
+            ground: Vec::new(),
+        }
+    }
+
+    /// 矩形区域内的估计人数 (帧像素坐标)
+    pub fn count_in_rect(&self, rect: [f32; 4], frame_w: u32, frame_h: u32) -> f32 {
+        if self.data.is_empty() || frame_w == 0 || frame_h == 0 {
+            return 0.0;
+        }
+        let sx = self.width as f32 / frame_w as f32;
+        let sy = self.height as f32 / frame_h as f32;
+        let x1 = (rect[0] * sx).max(0.0) as usize;
+        let y1 = (rect[1] * sy).max(0.0) as usize;
+        let x2 = ((rect[2] * sx).ceil() as usize).min(self.width);
+        let y2 = ((rect[3] * sy).ceil() as usize).min(self.height);
+        (y1..y2)
+            .flat_map(|y| self.data[y * self.width + x1..y * self.width + x2].iter())
+            .map(|v| v.max(0.0))
+            .sum()
+    }
+
+    /// 将各单元中心投影到地面, 供区域人数规则使用
+    pub fn project_to_ground(&mut self, homography: &Homography, frame_w: u32, frame_h: u32) {
+        let cell_w = frame_w as f32 / self.width.max(1) as f32;
+        let cell_h = frame_h as f32 / self.height.max(1) as f32;
+        self.ground = self
+            .data
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| **v > MIN_CELL_DENSITY)
+            .filter_map(|(i, v)| {
+                let (x, y) = ((i % self.width) as f32, (i / self.width) as f32);
+                homography
+                    .project((x + 0.5) * cell_w, (y + 0.5) * cell_h)
+                    .map(|p| (p, *v))
+            })
+            .collect();
+    }
+
+    /// 热力图 (RGBA, 与密度图同分辨率): 按最大密度归一化, 蓝 → 绿 → 黄 → 红, 低密度透明
+    pub fn heatmap_rgba(&self) -> Vec<u8> {
+        let max = self.data.iter().copied().fold(0.0f32, f32::max);
+        let mut rgba = Vec::with_capacity(self.data.len() * 4);
+        for v in &self.data {
+            let t = if max > 0.0 {
+                (v / max).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let (r, g, b) = if t < 0.33 {
+                (0.0, t / 0.33, 1.0 - t / 0.33)
+            } else if t < 0.66 {
+                ((t - 0.33) / 0.33, 1.0, 0.0)
+            } else {
+                (1.0, 1.0 - (t - 0.66) / 0.34, 0.0)
+            };
+            rgba.extend_from_slice(&[
+                (r * 255.0) as u8,
+                (g * 255.0) as u8,
+                (b * 255.0) as u8,
+                (t.sqrt() * 160.0) as u8,
+            ]);
+        }
+        rgba
+    }
+
+    /// 地面多边形内的估计人数 (需先 project_to_ground)
+    pub fn count_where(&self, inside: impl Fn(f32, f32) -> bool) -> f32 {
+        self.ground
+            .iter()
+            .filter(|((x, y), _)| inside(*x, *y))
+            .map(|(_, v)| *v)
+            .sum()
+    }
+}
+
+/// 人群密度估计器
+pub struct CrowdCounter {
+    session: Session,
+    /// 固定输入尺寸 (宽, 高), 动态输入时为 None
+    input_size: Option<(usize, usize)>,
+    /// 每隔多少帧运行一次
+    interval: u32,
+    frame_counter: u32,
+    last_map: Option<DensityMap>,
+}
+
+impl CrowdCounter {
+    /// 加载密度估计模型
+    ///
+    /// # 参数
+    /// - `model_path`: ONNX 模型路径 (输入 [1,3,H,W], 输出 [1,1,h,w] 或 [1,h,w])
+    /// - `interval`: 运行间隔(帧), 人群整体变化较慢, 一般 5~10 帧一次即可
+    pub fn new(model_path: &str, interval: u32) -> Result<Self> {
+        let session = session_builder()?.commit_from_file(model_path)?;
+        let input_size = match session.inputs.first().map(|i| &i.input_type) {
+            Some(ValueType::Tensor { shape, .. }) if shape.len() == 4 => {
+                (shape[2] > 0 && shape[3] > 0).then(|| (shape[3] as usize, shape[2] as usize))
+            }
+            _ => return Err(anyhow!("人群密度模型输入格式不支持: {}", model_path)),
+        };
+        Ok(Self {
+            session,
+            input_size,
+            interval: interval.max(1),
+            frame_counter: 0,
+            last_map: None,
+        })
+    }
+
+    /// 按运行间隔更新密度图, 返回最近一次的密度图 (本次是否新估计, 密度图)
+    pub fn update(&mut self, rgba: &[u8], width: u32, height: u32) -> (bool, Option<&DensityMap>) {
+        let mut fresh = false;
+        if self.frame_counter.is_multiple_of(self.interval) || self.last_map.is_none() {
+            match self.estimate(rgba, width, height) {
+                Ok(map) => {
+                    self.last_map = Some(map);
+                    fresh = true;
+                }
+                Err(e) => eprintln!("❌ 人群密度估计失败: {}", e),
+            }
+        }
+        self.frame_counter = self.frame_counter.wrapping_add(1);
+        (fresh, self.last_map.as_ref())
+    }
+
+    /// 对一帧运行密度估计
+    pub fn estimate(&mut self, rgba: &[u8], width: u32, height: u32) -> Result<DensityMap> {
+        let (w, h) = (width as usize, height as usize);
+        if w == 0 || h == 0 || rgba.len() < w * h * 4 {
+            return Err(anyhow!("帧数据长度不足"));
+        }
+        let (iw, ih) = self.input_size.unwrap_or_else(|| dynamic_input_size(w, h));
+
+        // 最近邻缩放 + 归一化, 直接从RGBA写入NCHW
+        let mut input = Array4::<f32>::zeros((1, 3, ih, iw));
+        for y in 0..ih {
+            let sy = (y * h / ih).min(h - 1);
+            for x in 0..iw {
+                let sx = (x * w / iw).min(w - 1);
+                let idx = (sy * w + sx) * 4;
+                for c in 0..3 {
+                    input[[0, c, y, x]] = (rgba[idx + c] as f32 / 255.0 - MEAN[c]) / STD[c];
+                }
+            }
+        }
+
+        let input_value = Value::from_array(input)?;
+        let outputs = self.session.run(ort::inputs![input_value])?;
+        let (_, value) = outputs
+            .iter()
+            .next()
+            .ok_or_else(|| anyhow!("人群密度模型无输出"))?;
+        let (shape, data) = value.try_extract_tensor::<f32>()?;
+
+        // 输出 [1, H, W] 或 [1, 1, H, W]
+        let dims: Vec<usize> = shape.iter().map(|&d| d as usize).collect();
+        let (out_h, out_w) = match dims.as_slice() {
+            [.., oh, ow] => (*oh, *ow),
+            _ => return Err(anyhow!("人群密度模型输出维度异常: {:?}", dims)),
+        };
+        Ok(DensityMap::new(data.to_vec(), out_w, out_h))
+    }
+}
+
+/// 动态输入尺寸: 保持宽高比, 长边 DEFAULT_LONG_SIDE, 两边取8的倍数
+fn dynamic_input_size(width: usize, height: usize) -> (usize, usize) {
+    let scale = DEFAULT_LONG_SIDE as f32 / width.max(height) as f32;
+    let round8 = |v: usize| ((v as f32 * scale / 8.0).round() as usize).max(1) * 8;
+    (round8(width), round8(height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 全图/矩形计数为密度求和, 负值按0处理
+    #[test]
+    fn test_density_counts() {
+        // 4x2 密度图, 对应 400x200 的帧
+        let map = DensityMap::new(vec![0.5, 0.5, 0.0, -0.1, 1.0, 1.0, 0.0, 2.0], 4, 2);
+        assert!((map.count - 5.0).abs() < 1e-6);
+        let left = map.count_in_rect([0.0, 0.0, 200.0, 200.0], 400, 200);
+        assert!((left - 3.0).abs() < 1e-6);
+        assert_eq!(dynamic_input_size(1920, 1080), (640, 360));
+
+        // 热力图: 最大值为红色, 零值全透明
+        let heatmap = map.heatmap_rgba();
+        assert_eq!(heatmap.len(), 4 * 2 * 4);
+        assert_eq!(&heatmap[7 * 4..], &[255, 0, 0, 160]);
+        assert_eq!(heatmap[2 * 4 + 3], 0);
+    }
+}
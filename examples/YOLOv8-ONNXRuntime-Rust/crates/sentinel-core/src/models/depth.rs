@@ -0,0 +1,302 @@
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+// 单目深度估计 (MiDaS / Depth-Anything)
+// 低频运行(每N帧一次), 缓存深度图, 为每个检测框提供近似距离
+//
+// 注意:
+// - 相对深度模型(MiDaS, Depth-Anything relative) 输出的是视差(越大越近),
+//   距离 = depth_scale / 视差, depth_scale 需按场景标定
+// - metric 模型(文件名含 "metric") 直接输出米制深度
+
+use anyhow::{anyhow, Result};
+use ndarray::Array4;
+use ort::session::Session;
+use ort::value::{Value, ValueType};
+
+use crate::detection::types::BBox;
+use crate::ort_backend::session_builder;
+
+/// ImageNet 均值/方差 (MiDaS 与 Depth-Anything 官方预处理一致)
+const MEAN: [f32; 3] = [0.485, 0.456, 0.406];
+const STD: [f32; 3] = [0.229, 0.224, 0.225];
+
+/// 深度模型类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthModelKind {
+    /// MiDaS v2.1/v3 (相对视差)
+    MiDaS,
+    /// Depth-Anything v1/v2 (相对视差或米制深度)
+    DepthAnything,
+}
+
+impl DepthModelKind {
+    /// 从模型路径推断类型
+    pub fn from_path(path: &str) -> Self {
+        let lower = path.to_lowercase();
+        if lower.contains("depth_anything") || lower.contains("depth-anything") {
+            DepthModelKind::DepthAnything
+        } else {
+            DepthModelKind::MiDaS
+        }
+    }
+}
+
+/// 深度图 (模型输出分辨率)
+#[derive(Clone, Debug)]
+pub struct DepthMap {
+    pub data: Vec<f32>,
+    pub width: usize,
+    pub height: usize,
+    /// true: 米制深度, false: 相对视差
+    pub metric: bool,
+}
+
+impl DepthMap {
+    /// 计算检测框中心区域的深度中位数
+    ///
+    /// 只取框内中间 50% 区域, 减少背景像素干扰
+    pub fn median_in_box(&self, bbox: &BBox, frame_w: u32, frame_h: u32) -> Option<f32> {
+        if self.data.is_empty() || frame_w == 0 || frame_h == 0 {
+            return None;
+        }
+        let sx = self.width as f32 / frame_w as f32;
+        let sy = self.height as f32 / frame_h as f32;
+
+        let (w, h) = (bbox.x2 - bbox.x1, bbox.y2 - bbox.y1);
+        let x1 = ((bbox.x1 + w * 0.25) * sx).max(0.0) as usize;
+        let y1 = ((bbox.y1 + h * 0.25) * sy).max(0.0) as usize;
+        let x2 = (((bbox.x2 - w * 0.25) * sx).ceil() as usize).min(self.width);
+        let y2 = (((bbox.y2 - h * 0.25) * sy).ceil() as usize).min(self.height);
+        if x2 <= x1 || y2 <= y1 {
+            return None;
+        }
+
+        let mut values: Vec<f32> = (y1..y2)
+            .flat_map(|y| {
+                self.data[y * self.width + x1..y * self.width + x2]
+                    .iter()
+                    .copied()
+            })
+            .filter(|v| v.is_finite())
+            .collect();
+        if values.is_empty() {
+            return None;
+        }
+        let mid = values.len() / 2;
+        let (_, median, _) = values.select_nth_unstable_by(mid, |a, b| a.total_cmp(b));
+        Some(*median)
+    }
+
+    /// 检测框的近似距离 (米)
+    ///
+    /// - metric 模型: 直接返回深度中位数
+    /// - 相对模型: `depth_scale / 视差中位数`
+    pub fn distance_for_box(
+        &self,
+        bbox: &BBox,
+        frame_w: u32,
+        frame_h: u32,
+        depth_scale: f32,
+    ) -> Option<f32> {
+        let v = self.median_in_box(bbox, frame_w, frame_h)?;
+        if self.metric {
+            Some(v)
+        } else if v > 1e-6 {
+            Some(depth_scale / v)
+        } else {
+            None
+        }
+    }
+}
+
+/// 单目深度估计器
+pub struct DepthEstimator {
+    session: Session,
+    kind: DepthModelKind,
+    input_w: usize,
+    input_h: usize,
+    metric: bool,
+    /// 相对深度 → 距离的标定系数
+    depth_scale: f32,
+    /// 每隔多少帧运行一次
+    interval: u32,
+    frame_counter: u32,
+    last_map: Option<DepthMap>,
+}
+
+impl DepthEstimator {
+    /// 加载深度模型
+    ///
+    /// # 参数
+    /// - `model_path`: ONNX 模型路径
+    /// - `interval`: 运行间隔(帧), 深度变化缓慢, 一般 5~10 帧一次即可
+    pub fn new(model_path: &str, interval: u32) -> Result<Self> {
+        let session = session_builder()?.commit_from_file(model_path)?;
+
+        // 输入尺寸: 动态维度时使用官方默认值 (MiDaS 256 / Depth-Anything 518)
+        let kind = DepthModelKind::from_path(model_path);
+        let default_size = match kind {
+            DepthModelKind::MiDaS => 256,
+            DepthModelKind::DepthAnything => 518,
+        };
+        let (input_h, input_w) = match session.inputs.first().map(|i| &i.input_type) {
+            Some(ValueType::Tensor { shape, .. }) if shape.len() == 4 => (
+                if shape[2] > 0 {
+                    shape[2] as usize
+                } else {
+                    default_size
+                },
+                if shape[3] > 0 {
+                    shape[3] as usize
+                } else {
+                    default_size
+                },
+            ),
+            _ => return Err(anyhow!("深度模型输入格式不支持: {}", model_path)),
+        };
+
+        Ok(Self {
+            session,
+            kind,
+            input_w,
+            input_h,
+            metric: model_path.to_lowercase().contains("metric"),
+            depth_scale: 1.0,
+            interval: interval.max(1),
+            frame_counter: 0,
+            last_map: None,
+        })
+    }
+
+    /// 设置相对深度标定系数 (距离 = depth_scale / 视差)
+    pub fn set_depth_scale(&mut self, scale: f32) {
+        self.depth_scale = scale;
+    }
+
+    pub fn depth_scale(&self) -> f32 {
+        self.depth_scale
+    }
+
+    pub fn kind(&self) -> DepthModelKind {
+        self.kind
+    }
+
+    /// 按运行间隔更新深度图, 返回最近一次的深度图
+    pub fn update(&mut self, rgba: &[u8], width: u32, height: u32) -> Option<&DepthMap> {
+        if self.frame_counter.is_multiple_of(self.interval) || self.last_map.is_none() {
+            match self.estimate(rgba, width, height) {
+                Ok(map) => self.last_map = Some(map),
+                Err(e) => eprintln!("❌ 深度估计失败: {}", e),
+            }
+        }
+        self.frame_counter = self.frame_counter.wrapping_add(1);
+        self.last_map.as_ref()
+    }
+
+    /// 对一帧运行深度估计
+    pub fn estimate(&mut self, rgba: &[u8], width: u32, height: u32) -> Result<DepthMap> {
+        let (iw, ih) = (self.input_w, self.input_h);
+        let (w, h) = (width as usize, height as usize);
+        if rgba.len() < w * h * 4 {
+            return Err(anyhow!("帧数据长度不足"));
+        }
+
+        // 最近邻缩放 + 归一化, 直接从RGBA写入NCHW
+        let mut input = Array4::<f32>::zeros((1, 3, ih, iw));
+        for y in 0..ih {
+            let sy = (y * h / ih).min(h - 1);
+            for x in 0..iw {
+                let sx = (x * w / iw).min(w - 1);
+                let idx = (sy * w + sx) * 4;
+                for c in 0..3 {
+                    input[[0, c, y, x]] = (rgba[idx + c] as f32 / 255.0 - MEAN[c]) / STD[c];
+                }
+            }
+        }
+
+        let input_value = Value::from_array(input)?;
+        let outputs = self.session.run(ort::inputs![input_value])?;
+        let (_, value) = outputs
+            .iter()
+            .next()
+            .ok_or_else(|| anyhow!("深度模型无输出"))?;
+        let (shape, data) = value.try_extract_tensor::<f32>()?;
+
+        // 输出 [1, H, W] 或 [1, 1, H, W]
+        let dims: Vec<usize> = shape.iter().map(|&d| d as usize).collect();
+        let (out_h, out_w) = match dims.as_slice() {
+            [.., oh, ow] => (*oh, *ow),
+            _ => return Err(anyhow!("深度模型输出维度异常: {:?}", dims)),
+        };
+
+        Ok(DepthMap {
+            data: data.to_vec(),
+            width: out_w,
+            height: out_h,
+            metric: self.metric,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bbox(x1: f32, y1: f32, x2: f32, y2: f32) -> BBox {
+        BBox {
+            x1,
+            y1,
+            x2,
+            y2,
+            confidence: 0.9,
+            class_id: 0,
+        }
+    }
+
+    /// 中位数只统计框中心区域
+    #[test]
+    fn test_median_in_box() {
+        // 4x4 深度图, 中心 2x2 为 2.0, 其余为 100.0
+        let mut data = vec![100.0; 16];
+        for (y, x) in [(1, 1), (1, 2), (2, 1), (2, 2)] {
+            data[y * 4 + x] = 2.0;
+        }
+        let map = DepthMap {
+            data,
+            width: 4,
+            height: 4,
+            metric: true,
+        };
+        let d = map.median_in_box(&bbox(0.0, 0.0, 400.0, 400.0), 400, 400);
+        assert_eq!(d, Some(2.0));
+        assert_eq!(
+            map.distance_for_box(&bbox(0.0, 0.0, 400.0, 400.0), 400, 400, 1.0),
+            Some(2.0)
+        );
+    }
+
+    /// 相对视差按标定系数换算距离
+    #[test]
+    fn test_relative_distance() {
+        let map = DepthMap {
+            data: vec![4.0; 16],
+            width: 4,
+            height: 4,
+            metric: false,
+        };
+        let d = map.distance_for_box(&bbox(0.0, 0.0, 40.0, 40.0), 40, 40, 10.0);
+        assert_eq!(d, Some(2.5));
+    }
+
+    #[test]
+    fn test_kind_from_path() {
+        assert_eq!(
+            DepthModelKind::from_path("models/depth_anything_v2_vits.onnx"),
+            DepthModelKind::DepthAnything
+        );
+        assert_eq!(
+            DepthModelKind::from_path("models/midas_v21_small_256.onnx"),
+            DepthModelKind::MiDaS
+        );
+    }
+}
@@ -0,0 +1,394 @@
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+// 图内 NMS 的端到端导出模型 (TensorRT EfficientNMS / YOLOv8 end2end)
+// 输出已是最终检测结果四元组, 跳过 Rust 端的解码与 NMS:
+// - num_dets    [N, 1]      每张图的有效检测数
+// - det_boxes   [N, K, 4]   x1, y1, x2, y2 (模型输入坐标)
+// - det_scores  [N, K]
+// - det_classes [N, K]
+// 其中 K 为导出时的 max_det, 第 num_dets 个之后为填充
+// 完整模型需要 ort 功能, 后处理器 (End2EndPostprocessor) 属于核心库
+
+use anyhow::Result;
+use image::{DynamicImage, GenericImageView};
+use ndarray::{Array, IxDyn};
+
+use crate::models::ModelInfo;
+#[cfg(feature = "ort")]
+use crate::models::{model_info, ModelType, PreprocessSpec};
+#[cfg(feature = "ort")]
+use crate::{Batch, OrtBackend, OrtConfig, OrtEP, YOLOTask};
+use crate::{Bbox, DetectionResult, DetectorError};
+
+/// 只能由 TensorRT 执行的 NMS 插件算子
+#[cfg(feature = "ort")]
+const TRT_NMS_OPS: [&str; 2] = ["EfficientNMS_TRT", "BatchedNMSDynamic_TRT"];
+
+/// 无 `names` 元数据时的类别数 (COCO)
+#[cfg(feature = "ort")]
+const DEFAULT_NC: u32 = 80;
+
+/// NMS 四元组在模型输出中的下标
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NmsOutputs {
+    pub num_dets: usize,
+    pub boxes: usize,
+    pub scores: usize,
+    pub classes: usize,
+}
+
+impl NmsOutputs {
+    /// 按输出形状识别 (动态维为 -1): 恰好 4 个输出, 含 `[N, 1]`、`[N, K, 4]` 与两个 `[N, K]`,
+    /// 两个 `[N, K]` 按 EfficientNMS 的顺序先分数后类别
+    pub fn from_shapes(shapes: &[Vec<i64>]) -> Option<Self> {
+        if shapes.len() != 4 {
+            return None;
+        }
+        let boxes = shapes.iter().position(|s| s.len() == 3 && s[2] == 4)?;
+        let num_dets = shapes.iter().position(|s| s.len() == 2 && s[1] == 1)?;
+        let mut rest = (0..shapes.len()).filter(|&i| i != boxes && i != num_dets);
+        let (scores, classes) = (rest.next()?, rest.next()?);
+        if shapes[scores].len() != 2 || shapes[classes].len() != 2 {
+            return None;
+        }
+        Some(Self {
+            num_dets,
+            boxes,
+            scores,
+            classes,
+        })
+    }
+
+    /// 按运行时张量识别
+    pub fn from_arrays(xs: &[Array<f32, IxDyn>]) -> Option<Self> {
+        let shapes: Vec<Vec<i64>> = xs
+            .iter()
+            .map(|x| x.shape().iter().map(|&d| d as i64).collect())
+            .collect();
+        Self::from_shapes(&shapes)
+    }
+}
+
+/// 模型文件的输出是否为 NMS 四元组 (无法解析时视为否)
+pub fn has_nms_outputs(path: &str) -> bool {
+    ModelInfo::load(path)
+        .map(|info| NmsOutputs::from_shapes(&info.output_shapes()).is_some())
+        .unwrap_or(false)
+}
+
+/// 端到端模型结构
+#[cfg(feature = "ort")]
+pub struct End2End {
+    engine: OrtBackend,
+    nc: u32,
+    height: u32,
+    width: u32,
+    batch: u32,
+    conf: f32,
+    iou: f32,
+    names: Vec<String>,
+    profile: bool,
+}
+
+#[cfg(feature = "ort")]
+impl End2End {
+    /// 从配置创建端到端模型
+    ///
+    /// 图中含 TensorRT NMS 插件时 CPU/CUDA 执行器无法运行, 自动切换到 TensorRT
+    pub fn new(config: crate::Args) -> Result<Self> {
+        let trt_plugin = ModelInfo::load(&config.model)
+            .map(|info| {
+                info.op_types
+                    .iter()
+                    .any(|op| TRT_NMS_OPS.contains(&op.as_str()))
+            })
+            .unwrap_or(false);
+
+        // execution provider
+        let ep = if config.trt || config.dla_core.is_some() {
+            OrtEP::Trt(config.device_id)
+        } else if trt_plugin {
+            println!("🔧 模型包含 TensorRT NMS 插件, 使用 TensorRT 执行器");
+            OrtEP::Trt(config.device_id)
+        } else if config.cuda {
+            OrtEP::CUDA(config.device_id)
+        } else {
+            OrtEP::CPU
+        };
+
+        // batch
+        let batch = Batch {
+            opt: config.batch,
+            min: config.batch_min,
+            max: config.batch_max,
+        };
+
+        // build ort engine
+        let ort_args = OrtConfig {
+            ep,
+            batch,
+            f: config.model,
+            task: Some(YOLOTask::Detect),
+            trt_fp16: config.fp16,
+            dla_core: config.dla_core,
+            image_size: (config.height, config.width),
+        };
+        let engine = OrtBackend::build(ort_args)?;
+        model_info::check_layout(
+            ModelType::End2End,
+            &engine.input_shapes()[0],
+            &engine.output_shapes(),
+        )?;
+
+        // 类别数只用于过滤越界类别, 输出形状中没有, 取 names 元数据
+        let (batch, height, width) = (engine.batch(), engine.height(), engine.width());
+        let names = engine.names().unwrap_or_default();
+        let nc = match names.len() {
+            0 => config.nc.unwrap_or(DEFAULT_NC),
+            n => n as u32,
+        };
+
+        Ok(Self {
+            engine,
+            nc,
+            height,
+            width,
+            batch,
+            conf: config.conf,
+            iou: config.iou,
+            names,
+            profile: config.profile,
+        })
+    }
+
+    /// 后处理器 (与 ONNX 引擎解耦, 可单独用 golden 张量测试)
+    pub fn postprocessor(&self) -> End2EndPostprocessor {
+        End2EndPostprocessor {
+            nc: self.nc,
+            conf: self.conf,
+            width: self.width,
+            height: self.height,
+            profile: self.profile,
+        }
+    }
+
+    pub fn names(&self) -> &Vec<String> {
+        &self.names
+    }
+}
+
+#[cfg(feature = "ort")]
+impl crate::models::Model for End2End {
+    /// 预处理: 与 YOLOv8 相同的左上角 letterbox
+    fn preprocess(&mut self, xs: &[DynamicImage]) -> Result<Vec<Array<f32, IxDyn>>> {
+        let spec = self.preprocess_spec();
+        let mut ys = Array::ones((
+            xs.len(),
+            spec.channels,
+            self.height as usize,
+            self.width as usize,
+        ))
+        .into_dyn();
+        spec.fill_tensor(&mut ys);
+
+        for (idx, x) in xs.iter().enumerate() {
+            let (w0, h0) = x.dimensions();
+            let r = (self.width as f32 / w0 as f32).min(self.height as f32 / h0 as f32);
+            let img = x.resize_exact(
+                (w0 as f32 * r).round() as u32,
+                (h0 as f32 * r).round() as u32,
+                image::imageops::FilterType::Triangle,
+            );
+            for (x, y, rgb) in img.pixels() {
+                let [r, g, b, _] = rgb.0;
+                spec.write_pixel(&mut ys, idx, y as usize, x as usize, [r, g, b]);
+            }
+        }
+
+        Ok(vec![ys])
+    }
+
+    /// 推理: 调用ONNX Runtime
+    fn run(&mut self, xs: Vec<Array<f32, IxDyn>>, profile: bool) -> Result<Vec<Array<f32, IxDyn>>> {
+        self.profile = profile;
+        let all_results: Vec<Vec<_>> = xs
+            .into_iter()
+            .map(|x| self.engine.run(x, profile))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(all_results.into_iter().flatten().collect())
+    }
+
+    /// 后处理: 直接读取 NMS 四元组, 不再做 NMS
+    fn postprocess(
+        &self,
+        xs: Vec<Array<f32, IxDyn>>,
+        xs0: &[DynamicImage],
+    ) -> Result<Vec<DetectionResult>> {
+        self.postprocessor().postprocess(xs, xs0)
+    }
+
+    fn engine_mut(&mut self) -> Option<&mut OrtBackend> {
+        Some(&mut self.engine)
+    }
+
+    fn summary(&self) {
+        println!("\n模型摘要:");
+        println!("┌─────────────────────────────────────────┐");
+        println!("│ Model: End-to-End (NMS in graph)        │");
+        println!("│ Task: Object Detection                  │");
+        println!("├─────────────────────────────────────────┤");
+        println!(
+            "│ Input: [{}, 3, {}, {}]           │",
+            self.batch, self.height, self.width
+        );
+        println!("│ Classes: {}                              │", self.nc);
+        println!("│ Confidence: {}                         │", self.conf);
+        println!("│ NMS: Inside Model (EfficientNMS)        │");
+        println!("└─────────────────────────────────────────┘\n");
+    }
+
+    fn supports_task(&self, task: YOLOTask) -> bool {
+        matches!(task, YOLOTask::Detect)
+    }
+
+    fn set_conf(&mut self, val: f32) {
+        self.conf = val;
+    }
+
+    fn conf(&self) -> f32 {
+        self.conf
+    }
+
+    fn set_iou(&mut self, val: f32) {
+        self.iou = val;
+    }
+
+    fn iou(&self) -> f32 {
+        self.iou
+    }
+
+    fn preprocess_spec(&self) -> PreprocessSpec {
+        PreprocessSpec::default().with_channels(self.engine.channels())
+    }
+}
+
+/// 端到端模型后处理器
+///
+/// 框坐标为 letterbox 后的模型输入坐标, 按缩放比还原并裁剪到原图;
+/// 模型内 NMS 的分数阈值通常较低, 这里再按 `conf` 过滤
+pub struct End2EndPostprocessor {
+    pub nc: u32,
+    pub conf: f32,
+    pub width: u32,
+    pub height: u32,
+    pub profile: bool,
+}
+
+impl End2EndPostprocessor {
+    pub fn postprocess(
+        &self,
+        xs: Vec<Array<f32, IxDyn>>,
+        xs0: &[DynamicImage],
+    ) -> Result<Vec<DetectionResult>> {
+        let outputs = NmsOutputs::from_arrays(&xs).ok_or_else(|| {
+            let shapes: Vec<&[usize]> = xs.iter().map(|x| x.shape()).collect();
+            DetectorError::ShapeMismatch(format!(
+                "端到端模型输出 {:?} 不是 (num_dets, boxes, scores, classes)",
+                shapes
+            ))
+        })?;
+        let (num_dets, boxes) = (&xs[outputs.num_dets], &xs[outputs.boxes]);
+        let (scores, classes) = (&xs[outputs.scores], &xs[outputs.classes]);
+        let max_det = boxes.shape()[1];
+
+        let mut ys = Vec::with_capacity(xs0.len());
+        for (idx, x0) in xs0.iter().enumerate() {
+            let (width_original, height_original) = x0.dimensions();
+            let (width_original, height_original) = (width_original as f32, height_original as f32);
+            let ratio =
+                (self.width as f32 / width_original).min(self.height as f32 / height_original);
+
+            let n = (num_dets[[idx, 0]].max(0.0) as usize).min(max_det);
+            let mut bboxes = Vec::with_capacity(n);
+            for i in 0..n {
+                let confidence = scores[[idx, i]];
+                if confidence < self.conf {
+                    continue;
+                }
+                let class_id = classes[[idx, i]];
+                if class_id < 0.0 || class_id as u32 >= self.nc {
+                    continue;
+                }
+
+                let x1 = (boxes[[idx, i, 0]] / ratio).clamp(0.0, width_original);
+                let y1 = (boxes[[idx, i, 1]] / ratio).clamp(0.0, height_original);
+                let x2 = (boxes[[idx, i, 2]] / ratio).clamp(0.0, width_original);
+                let y2 = (boxes[[idx, i, 3]] / ratio).clamp(0.0, height_original);
+                if x2 <= x1 || y2 <= y1 {
+                    continue;
+                }
+                bboxes.push(Bbox::new(
+                    x1,
+                    y1,
+                    x2 - x1,
+                    y2 - y1,
+                    class_id as usize,
+                    confidence,
+                ));
+            }
+
+            if self.profile {
+                println!("  检测到 {} 个目标 (模型内 NMS, 有效 {})", bboxes.len(), n);
+            }
+
+            ys.push(DetectionResult {
+                probs: None,
+                bboxes: if bboxes.is_empty() {
+                    None
+                } else {
+                    Some(bboxes)
+                },
+                keypoints: None,
+                masks: None,
+            });
+        }
+
+        Ok(ys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 识别 EfficientNMS 四元组 (含动态维与非默认顺序), 拒绝其他布局
+    #[test]
+    fn test_nms_outputs_from_shapes() {
+        let efficient_nms = vec![vec![-1, 1], vec![-1, 100, 4], vec![-1, 100], vec![-1, 100]];
+        assert_eq!(
+            NmsOutputs::from_shapes(&efficient_nms),
+            Some(NmsOutputs {
+                num_dets: 0,
+                boxes: 1,
+                scores: 2,
+                classes: 3,
+            })
+        );
+
+        let reordered = vec![vec![1, 100, 4], vec![1, 100], vec![1, 100], vec![1, 1]];
+        assert_eq!(
+            NmsOutputs::from_shapes(&reordered),
+            Some(NmsOutputs {
+                num_dets: 3,
+                boxes: 0,
+                scores: 1,
+                classes: 2,
+            })
+        );
+
+        assert_eq!(NmsOutputs::from_shapes(&[vec![1, 84, 8400]]), None);
+        assert_eq!(NmsOutputs::from_shapes(&[vec![1, 300, 6]]), None);
+        let no_num_dets = vec![vec![1, 100], vec![1, 100, 4], vec![1, 100], vec![1, 100]];
+        assert_eq!(NmsOutputs::from_shapes(&no_num_dets), None);
+    }
+}
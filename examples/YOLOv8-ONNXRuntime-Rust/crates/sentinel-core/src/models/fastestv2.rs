@@ -1,14 +1,18 @@
 // Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
 //
-// YOLO-FastestV2 后处理模块
+// YOLO-FastestV2 模型 (预处理 + 后处理)
 // 基于官方NCNN实现: https://github.com/dog-qiuqiu/Yolo-FastestV2
 //
-// 注意: FastestV2 当前仅实现后处理器，通过 detection::PostprocessorFactory 统一管理
-//       完整的模型加载、预处理由 detector.rs 中的 OrtBackend 处理
-//       如需完整 Model trait 实现，可参考 yolov8.rs
+// 官方流程:
+// - 预处理: cv2.resize 直接拉伸到输入尺寸 (默认 352x352, 双线性), BGR, /255
+// - 输出: 2 个 [1, h, w, 95] 特征图 (bbox/obj 已 sigmoid, 类别已 softmax)
+// - 解码: 与 YOLOv5 相同的 anchor 解码, 按 x/y 缩放比例还原到原图
+//
+// FastestV2 完整实现 Model trait (需要 ort 功能), 检测器把整帧原图交给 preprocess.
+// FastestV2Postprocessor 作为解码阶段单独导出 (golden 测试/外部绑定使用)
 
 use anyhow::Result;
-use image::{DynamicImage, GenericImageView};
+use image::DynamicImage;
 use ndarray::{s, Array, IxDyn};
 
 use crate::{non_max_suppression, Bbox, DetectionResult, Point2};
@@ -107,7 +111,7 @@ impl FastestV2Postprocessor {
                     let (class_id, &class_score) = class_scores
                         .iter()
                         .enumerate()
-                        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+                        .max_by(|(_, a), (_, b)| a.total_cmp(b))
                         .unwrap();
 
                     // 综合置信度 = obj * class_score
@@ -232,12 +236,14 @@ mod tests {
 }
 
 // ========================================
-// 完整 FastestV2 模型实现 (实现 Model trait)
+// 完整 FastestV2 模型实现 (实现 Model trait, 需要 ort 功能)
 // ========================================
 
+#[cfg(feature = "ort")]
 use crate::{Batch, OrtBackend, OrtConfig, OrtEP};
 
 /// YOLO-FastestV2 完整模型
+#[cfg(feature = "ort")]
 pub struct FastestV2 {
     engine: OrtBackend,
     postprocessor: FastestV2Postprocessor,
@@ -245,11 +251,12 @@ pub struct FastestV2 {
     height: u32,
 }
 
+#[cfg(feature = "ort")]
 impl FastestV2 {
     /// 从配置创建 FastestV2 模型
     pub fn new(config: crate::Args) -> Result<Self> {
         // execution provider
-        let ep = if config.trt {
+        let ep = if config.trt || config.dla_core.is_some() {
             OrtEP::Trt(config.device_id)
         } else if config.cuda {
             OrtEP::CUDA(config.device_id)
@@ -271,6 +278,7 @@ impl FastestV2 {
             f: config.model,
             task: Some(crate::YOLOTask::Detect), // FastestV2 only supports detection
             trt_fp16: config.fp16,
+            dla_core: config.dla_core,
             image_size: (config.height, config.width),
         };
         let engine = OrtBackend::build(ort_args)?;
@@ -304,34 +312,19 @@ impl FastestV2 {
 }
 
 // 实现 Model trait
+#[cfg(feature = "ort")]
 impl super::Model for FastestV2 {
     fn preprocess(&mut self, images: &[DynamicImage]) -> Result<Vec<Array<f32, IxDyn>>> {
-        // 复用 YOLOv8 的预处理逻辑 (letterbox + normalize)
-        let mut ys =
-            Array::ones((images.len(), 3, self.height as usize, self.width as usize)).into_dyn();
-        ys.fill(144.0 / 255.0);
-
-        for (idx, img) in images.iter().enumerate() {
-            let (w0, h0) = img.dimensions();
-            let w0 = w0 as f32;
-            let h0 = h0 as f32;
-            let r = (self.width as f32 / w0).min(self.height as f32 / h0);
-            let w_new = (w0 * r).round() as u32;
-            let h_new = (h0 * r).round() as u32;
-
-            let resized = img.resize_exact(w_new, h_new, image::imageops::FilterType::Triangle);
-
-            for (x, y, rgb) in resized.pixels() {
-                let x = x as usize;
-                let y = y as usize;
-                let [r, g, b, _] = rgb.0;
-                ys[[idx, 0, y, x]] = (r as f32) / 255.0;
-                ys[[idx, 1, y, x]] = (g as f32) / 255.0;
-                ys[[idx, 2, y, x]] = (b as f32) / 255.0;
-            }
-        }
-
-        Ok(vec![ys])
+        // FastestV2 官方预处理: 直接拉伸 (与后处理按 x/y 分别缩放一致), BGR, /255
+        let spec = super::ModelType::FastestV2
+            .preprocess_spec()
+            .with_channels(self.engine.channels());
+        Ok(vec![spec.resize_tensor(
+            images,
+            self.width,
+            self.height,
+            false,
+        )])
     }
 
     fn run(&mut self, xs: Vec<Array<f32, IxDyn>>, profile: bool) -> Result<Vec<Array<f32, IxDyn>>> {
@@ -346,8 +339,8 @@ impl super::Model for FastestV2 {
         self.postprocessor.postprocess(xs, xs0)
     }
 
-    fn engine_mut(&mut self) -> &mut OrtBackend {
-        &mut self.engine
+    fn engine_mut(&mut self) -> Option<&mut OrtBackend> {
+        Some(&mut self.engine)
     }
 
     fn summary(&self) {
@@ -380,4 +373,14 @@ impl super::Model for FastestV2 {
     fn iou(&self) -> f32 {
         self.postprocessor.config.iou_threshold
     }
+
+    fn preprocess_spec(&self) -> super::PreprocessSpec {
+        super::ModelType::FastestV2
+            .preprocess_spec()
+            .with_channels(self.engine.channels())
+    }
+
+    fn native_preprocess(&self) -> bool {
+        true
+    }
 }
@@ -0,0 +1,312 @@
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+// 后处理 golden 张量回归测试
+//
+// 夹具位于 testdata/postprocess/*.json, 每个文件记录:
+// - 模型原始输出张量 (稠密 data 或 fill + 稀疏 values)
+// - 输入尺寸、原图尺寸与阈值
+// - 期望的 DetectionResult (bbox 与关键点)
+//
+// 测试不依赖 ONNX Runtime, 直接构造各模型的后处理器并逐框比对,
+// 修改解码/NMS 逻辑导致的任何数值或排序变化都会在这里暴露.
+// 夹具格式与录制方法见 testdata/README.md
+
+use std::path::{Path, PathBuf};
+
+use image::DynamicImage;
+use ndarray::{Array, IxDyn};
+use serde::Deserialize;
+
+use crate::models::{
+    End2EndPostprocessor, FastestV2Config, FastestV2Postprocessor, NanoDetConfig,
+    NanoDetPostprocessor, YOLOXPostprocessor, YOLOv10Postprocessor, YOLOv8Config,
+    YOLOv8Postprocessor,
+};
+use crate::{DetectionResult, YOLOTask};
+
+/// 坐标与置信度的比较容差
+const TOLERANCE: f32 = 1e-3;
+
+/// 单个输出张量
+#[derive(Deserialize)]
+struct TensorFixture {
+    shape: Vec<usize>,
+    /// 稠密数据 (行主序)
+    #[serde(default)]
+    data: Option<Vec<f32>>,
+    /// 稀疏表示: 未列出的元素取 fill
+    #[serde(default)]
+    fill: f32,
+    /// 稀疏表示: [扁平下标, 值]
+    #[serde(default)]
+    values: Vec<(usize, f32)>,
+}
+
+impl TensorFixture {
+    fn to_array(&self, name: &str) -> Array<f32, IxDyn> {
+        let len: usize = self.shape.iter().product();
+        let data = match &self.data {
+            Some(data) => {
+                assert_eq!(data.len(), len, "{}: 稠密数据长度与 shape 不符", name);
+                data.clone()
+            }
+            None => {
+                let mut data = vec![self.fill; len];
+                for &(i, v) in &self.values {
+                    assert!(i < len, "{}: 稀疏下标 {} 越界", name, i);
+                    data[i] = v;
+                }
+                data
+            }
+        };
+        Array::from_shape_vec(IxDyn(&self.shape), data).unwrap()
+    }
+}
+
+/// 单张图片的期望结果
+#[derive(Deserialize)]
+struct ExpectedResult {
+    /// [xmin, ymin, width, height, id, confidence]
+    #[serde(default)]
+    bboxes: Vec<[f32; 6]>,
+    /// 每个框的关键点 [x, y, confidence]
+    #[serde(default)]
+    keypoints: Vec<Vec<[f32; 3]>>,
+}
+
+/// 后处理夹具
+#[derive(Deserialize)]
+struct Fixture {
+    name: String,
+    /// yolov8 | yolov10 | end2end | yolox | nanodet | fastestv2
+    postprocessor: String,
+    /// YOLOv8 系列任务: detect | pose
+    #[serde(default)]
+    task: Option<String>,
+    nc: usize,
+    #[serde(default)]
+    nk: usize,
+    #[serde(default)]
+    kconf: f32,
+    /// NanoDet / FastestV2 的特征层步长
+    #[serde(default)]
+    strides: Vec<usize>,
+    /// 模型输入 [宽, 高]
+    input: [usize; 2],
+    /// 每张原图 [宽, 高]
+    images: Vec<[u32; 2]>,
+    conf: f32,
+    iou: f32,
+    outputs: Vec<TensorFixture>,
+    expected: Vec<ExpectedResult>,
+}
+
+impl Fixture {
+    fn load(path: &Path) -> Self {
+        let text = std::fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("读取夹具失败 {}: {}", path.display(), e));
+        serde_json::from_str(&text)
+            .unwrap_or_else(|e| panic!("解析夹具失败 {}: {}", path.display(), e))
+    }
+
+    /// 运行对应的后处理器
+    fn run(&self) -> Vec<DetectionResult> {
+        let xs: Vec<Array<f32, IxDyn>> = self
+            .outputs
+            .iter()
+            .map(|t| t.to_array(&self.name))
+            .collect();
+        // 后处理只读取原图尺寸, 用最小内存的灰度图占位
+        let xs0: Vec<DynamicImage> = self
+            .images
+            .iter()
+            .map(|&[w, h]| DynamicImage::new_luma8(w, h))
+            .collect();
+        let [width, height] = self.input;
+
+        let result = match self.postprocessor.as_str() {
+            "yolov8" => {
+                let task = match self.task.as_deref() {
+                    Some("pose") => YOLOTask::Pose,
+                    _ => YOLOTask::Detect,
+                };
+                let mut config =
+                    YOLOv8Config::new(task, self.nc, width, height, self.conf, self.iou);
+                config.nk = self.nk;
+                config.kconf = self.kconf;
+                YOLOv8Postprocessor::new(config).postprocess(xs, &xs0)
+            }
+            "yolov10" => YOLOv10Postprocessor {
+                nc: self.nc as u32,
+                conf: self.conf,
+                width: width as u32,
+                height: height as u32,
+                profile: false,
+            }
+            .postprocess(xs, &xs0),
+            "end2end" => End2EndPostprocessor {
+                nc: self.nc as u32,
+                conf: self.conf,
+                width: width as u32,
+                height: height as u32,
+                profile: false,
+            }
+            .postprocess(xs, &xs0),
+            "yolox" => YOLOXPostprocessor {
+                conf: self.conf,
+                iou: self.iou,
+                width: width as u32,
+                height: height as u32,
+            }
+            .postprocess(xs, &xs0),
+            "nanodet" => {
+                let config = NanoDetConfig {
+                    num_classes: self.nc,
+                    strides: self.strides.clone(),
+                    conf_threshold: self.conf,
+                    iou_threshold: self.iou,
+                    ..Default::default()
+                };
+                NanoDetPostprocessor::new(config, width, height).postprocess(xs, &xs0)
+            }
+            "fastestv2" => {
+                let config = FastestV2Config {
+                    num_classes: self.nc,
+                    strides: self.strides.clone(),
+                    conf_threshold: self.conf,
+                    iou_threshold: self.iou,
+                    ..Default::default()
+                };
+                FastestV2Postprocessor::new(config, width, height).postprocess(xs, &xs0)
+            }
+            other => panic!("{}: 未知后处理器 {}", self.name, other),
+        };
+        result.unwrap_or_else(|e| panic!("{}: 后处理失败: {}", self.name, e))
+    }
+
+    /// 逐图逐框比对, 返回所有不一致之处
+    fn diff(&self, ys: &[DetectionResult]) -> Vec<String> {
+        let close = |a: f32, b: f32| (a - b).abs() <= TOLERANCE;
+        let mut errors = Vec::new();
+        if ys.len() != self.expected.len() {
+            errors.push(format!(
+                "结果数 {} != 期望 {}",
+                ys.len(),
+                self.expected.len()
+            ));
+            return errors;
+        }
+
+        for (i, (y, exp)) in ys.iter().zip(&self.expected).enumerate() {
+            let bboxes = y.bboxes().map(|b| b.as_slice()).unwrap_or_default();
+            if bboxes.len() != exp.bboxes.len() {
+                errors.push(format!(
+                    "图{}: 框数 {} != 期望 {} ({:?})",
+                    i,
+                    bboxes.len(),
+                    exp.bboxes.len(),
+                    bboxes
+                ));
+                continue;
+            }
+            for (j, (b, e)) in bboxes.iter().zip(&exp.bboxes).enumerate() {
+                let got = [
+                    b.xmin(),
+                    b.ymin(),
+                    b.width(),
+                    b.height(),
+                    b.id() as f32,
+                    b.confidence(),
+                ];
+                if !got.iter().zip(e).all(|(&g, &e)| close(g, e)) {
+                    errors.push(format!("图{} 框{}: {:?} != 期望 {:?}", i, j, got, e));
+                }
+            }
+
+            let kpts = y.keypoints().map(|k| k.as_slice()).unwrap_or_default();
+            if kpts.len() != exp.keypoints.len() {
+                errors.push(format!(
+                    "图{}: 关键点组数 {} != 期望 {}",
+                    i,
+                    kpts.len(),
+                    exp.keypoints.len()
+                ));
+                continue;
+            }
+            for (j, (ks, es)) in kpts.iter().zip(&exp.keypoints).enumerate() {
+                let got: Vec<[f32; 3]> =
+                    ks.iter().map(|k| [k.x(), k.y(), k.confidence()]).collect();
+                let same = got.len() == es.len()
+                    && got
+                        .iter()
+                        .zip(es)
+                        .all(|(g, e)| g.iter().zip(e).all(|(&g, &e)| close(g, e)));
+                if !same {
+                    errors.push(format!("图{} 关键点{}: {:?} != 期望 {:?}", i, j, got, es));
+                }
+            }
+        }
+        errors
+    }
+}
+
+fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/postprocess")
+}
+
+/// 所有夹具, 按文件名排序
+fn fixtures() -> Vec<(PathBuf, Fixture)> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(fixture_dir())
+        .expect("缺少 testdata/postprocess 目录")
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|p| {
+            let f = Fixture::load(&p);
+            (p, f)
+        })
+        .collect()
+}
+
+/// 每个夹具的后处理输出与录制的期望结果一致
+#[test]
+fn test_postprocess_golden() {
+    let fixtures = fixtures();
+    assert!(!fixtures.is_empty(), "未找到后处理夹具");
+
+    let mut failures = Vec::new();
+    for (path, fixture) in &fixtures {
+        let errors = fixture.diff(&fixture.run());
+        if !errors.is_empty() {
+            failures.push(format!("{}:\n  {}", path.display(), errors.join("\n  ")));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "golden 不一致:\n{}",
+        failures.join("\n")
+    );
+}
+
+/// 每类后处理器至少有一个夹具
+#[test]
+fn test_golden_covers_all_postprocessors() {
+    let fixtures = fixtures();
+    for kind in [
+        "yolov8",
+        "yolov10",
+        "end2end",
+        "yolox",
+        "nanodet",
+        "fastestv2",
+    ] {
+        assert!(
+            fixtures.iter().any(|(_, f)| f.postprocessor == kind),
+            "缺少 {} 夹具",
+            kind
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//! 模拟模型 (Mock Model) - 不加载 ONNX, 按脚本输出检测结果
+//!
+//! 跟踪器、输出端、渲染与统计等下游代码的测试/基准不需要模型文件与推理引擎:
+//! - 夹具 (JSON): 按帧列出检测框, `repeat` 为 true 时循环播放, 否则播完后输出空结果
+//! - 闭包: `(帧序号, 输入图片) → 检测结果`, 可按合成场景真值等动态生成
+//! - 模型路径写为 `mock:夹具文件` 时由 [`super::load_detect_model`] 加载, 检测线程无需改动
+//! - 置信度阈值与真实模型一致地生效; `latency` 模拟推理耗时
+//!
+//! 夹具格式 (bbox 为 x1, y1, x2, y2, 默认是模型输入图片的像素坐标, `normalized` 时为 0~1):
+//! ```json
+//! {
+//!   "repeat": true,
+//!   "frames": [
+//!     [{ "bbox": [100, 120, 160, 300], "class_id": 0, "confidence": 0.9 }],
+//!     [{ "bbox": [104, 120, 164, 300], "class_id": 0, "confidence": 0.88 }]
+//!   ]
+//! }
+//! ```
+
+use std::fs;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use image::{DynamicImage, GenericImageView};
+use ndarray::{Array, IxDyn};
+use serde::{Deserialize, Serialize};
+
+use super::Model;
+#[cfg(feature = "ort")]
+use crate::OrtBackend;
+use crate::{Bbox, DetectionResult, YOLOTask};
+
+/// `mock:` 前缀的模型路径按夹具加载 (如 `mock:tests/fixtures/walk.json`)
+pub const MOCK_MODEL_PREFIX: &str = "mock:";
+
+/// 夹具中的一个检测框
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MockDetection {
+    pub bbox: [f32; 4], // x1, y1, x2, y2
+    #[serde(default)]
+    pub class_id: usize,
+    #[serde(default = "default_confidence")]
+    pub confidence: f32,
+}
+
+fn default_confidence() -> f32 {
+    1.0
+}
+
+/// 检测结果夹具
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MockFixture {
+    pub frames: Vec<Vec<MockDetection>>,
+    pub repeat: bool,     // 播完后从第一帧重新开始
+    pub normalized: bool, // bbox 为相对输入图片宽高的 0~1 坐标
+}
+
+impl MockFixture {
+    pub fn load(path: &str) -> Result<Self> {
+        let json = fs::read_to_string(path).with_context(|| format!("无法读取夹具 {}", path))?;
+        serde_json::from_str(&json).with_context(|| format!("夹具 {} 解析失败", path))
+    }
+
+    /// 第 frame 帧的检测框 (超出夹具长度且不循环时为空)
+    pub fn detections(&self, frame: u64) -> &[MockDetection] {
+        let n = self.frames.len() as u64;
+        if n == 0 || (!self.repeat && frame >= n) {
+            return &[];
+        }
+        &self.frames[(frame % n) as usize]
+    }
+
+    /// 第 frame 帧在指定图片上的检测结果 (坐标裁剪到图片内)
+    fn result(&self, frame: u64, image: &DynamicImage) -> DetectionResult {
+        let (w, h) = image.dimensions();
+        let (w, h) = (w as f32, h as f32);
+        let (sx, sy) = if self.normalized { (w, h) } else { (1.0, 1.0) };
+        let bboxes = self
+            .detections(frame)
+            .iter()
+            .filter_map(|d| {
+                let [x1, y1, x2, y2] = d.bbox;
+                let (x1, x2) = ((x1 * sx).clamp(0.0, w), (x2 * sx).clamp(0.0, w));
+                let (y1, y2) = ((y1 * sy).clamp(0.0, h), (y2 * sy).clamp(0.0, h));
+                (x2 > x1 && y2 > y1)
+                    .then(|| Bbox::new(x1, y1, x2 - x1, y2 - y1, d.class_id, d.confidence))
+            })
+            .collect();
+        DetectionResult::new(None, Some(bboxes), None, None)
+    }
+}
+
+/// 脚本: (帧序号, 输入图片) → 检测结果
+pub type MockScript = Box<dyn FnMut(u64, &DynamicImage) -> DetectionResult + Send>;
+
+/// 模拟模型
+///
+/// 每张输入图片算一帧; 批量输入时依次取连续的帧
+pub struct MockModel {
+    script: Mutex<MockScript>,
+    frame: Mutex<u64>,
+    conf: f32,
+    iou: f32,
+    latency: Duration,
+}
+
+impl MockModel {
+    /// 按闭包生成检测结果
+    pub fn from_fn(
+        script: impl FnMut(u64, &DynamicImage) -> DetectionResult + Send + 'static,
+    ) -> Self {
+        Self {
+            script: Mutex::new(Box::new(script)),
+            frame: Mutex::new(0),
+            conf: 0.0,
+            iou: 0.45,
+            latency: Duration::ZERO,
+        }
+    }
+
+    /// 按夹具播放检测结果
+    pub fn from_fixture(fixture: MockFixture) -> Self {
+        Self::from_fn(move |frame, image| fixture.result(frame, image))
+    }
+
+    /// 加载夹具文件
+    pub fn load(path: &str) -> Result<Self> {
+        Ok(Self::from_fixture(MockFixture::load(path)?))
+    }
+
+    /// 每次 run 模拟的推理耗时 (基准测试使用)
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// 已输出的帧数
+    pub fn frames(&self) -> u64 {
+        *self.frame.lock().unwrap()
+    }
+}
+
+impl Model for MockModel {
+    /// 不做预处理, 每张图片对应一个空张量
+    fn preprocess(&mut self, images: &[DynamicImage]) -> Result<Vec<Array<f32, IxDyn>>> {
+        Ok(images
+            .iter()
+            .map(|_| Array::zeros(IxDyn(&[1, 0])))
+            .collect())
+    }
+
+    fn run(
+        &mut self,
+        xs: Vec<Array<f32, IxDyn>>,
+        _profile: bool,
+    ) -> Result<Vec<Array<f32, IxDyn>>> {
+        if !self.latency.is_zero() {
+            std::thread::sleep(self.latency);
+        }
+        Ok(xs)
+    }
+
+    fn postprocess(
+        &self,
+        _xs: Vec<Array<f32, IxDyn>>,
+        xs0: &[DynamicImage],
+    ) -> Result<Vec<DetectionResult>> {
+        let mut script = self.script.lock().unwrap();
+        let mut frame = self.frame.lock().unwrap();
+        Ok(xs0
+            .iter()
+            .map(|image| {
+                let mut result = script(*frame, image);
+                *frame += 1;
+                if let Some(bboxes) = result.bboxes_mut() {
+                    bboxes.retain(|b| b.confidence() >= self.conf);
+                }
+                result
+            })
+            .collect())
+    }
+
+    #[cfg(feature = "ort")]
+    fn engine_mut(&mut self) -> Option<&mut OrtBackend> {
+        None
+    }
+
+    fn summary(&self) {
+        println!("🧪 模拟模型: 已输出 {} 帧", self.frames());
+    }
+
+    fn supports_task(&self, task: YOLOTask) -> bool {
+        matches!(task, YOLOTask::Detect)
+    }
+
+    fn set_conf(&mut self, val: f32) {
+        self.conf = val;
+    }
+
+    fn conf(&self) -> f32 {
+        self.conf
+    }
+
+    fn set_iou(&mut self, val: f32) {
+        self.iou = val;
+    }
+
+    fn iou(&self) -> f32 {
+        self.iou
+    }
+
+    /// 不做预处理, 任意输入尺寸都可以
+    fn set_input_size(&mut self, _width: u32, _height: u32) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image() -> Vec<DynamicImage> {
+        vec![DynamicImage::new_rgb8(640, 640)]
+    }
+
+    /// 夹具按帧播放, 置信度阈值生效, 不循环时播完输出空结果
+    #[test]
+    fn test_fixture_playback() {
+        let fixture: MockFixture = serde_json::from_str(
+            r#"{"frames": [
+                [{"bbox": [100, 120, 160, 300], "confidence": 0.9},
+                 {"bbox": [400, 10, 700, 90], "class_id": 2, "confidence": 0.3}],
+                [{"bbox": [104, 120, 164, 300]}]
+            ]}"#,
+        )
+        .unwrap();
+        let mut model = MockModel::from_fixture(fixture);
+        model.set_conf(0.5);
+
+        let results = model.forward(&image()).unwrap();
+        let bboxes = results[0].bboxes().unwrap();
+        assert_eq!(bboxes.len(), 1);
+        assert_eq!((bboxes[0].xmin(), bboxes[0].width()), (100.0, 60.0));
+
+        model.set_conf(0.0);
+        let bboxes = model.forward(&image()).unwrap()[0]
+            .bboxes()
+            .unwrap()
+            .clone();
+        assert_eq!(bboxes[0].confidence(), 1.0);
+        assert!(model.forward(&image()).unwrap()[0]
+            .bboxes()
+            .unwrap()
+            .is_empty());
+        assert_eq!(model.frames(), 3);
+        #[cfg(feature = "ort")]
+        assert!(model.engine_mut().is_none());
+    }
+
+    /// 归一化坐标按输入图片尺寸换算并裁剪到图片内; 循环夹具从头开始
+    #[test]
+    fn test_normalized_repeat() {
+        let fixture = MockFixture {
+            frames: vec![vec![MockDetection {
+                bbox: [0.5, 0.25, 1.2, 0.75],
+                class_id: 0,
+                confidence: 0.8,
+            }]],
+            repeat: true,
+            normalized: true,
+        };
+        let mut model = MockModel::from_fixture(fixture);
+        for _ in 0..3 {
+            let results = model.forward(&image()).unwrap();
+            let b = &results[0].bboxes().unwrap()[0];
+            assert_eq!(
+                (b.xmin(), b.ymin(), b.width(), b.height()),
+                (320.0, 160.0, 320.0, 320.0)
+            );
+        }
+    }
+}
@@ -0,0 +1,636 @@
+/// 模型统一接口与实现
+///
+/// # 架构说明
+///
+/// ## 完整模型实现 (Full Model Implementation)
+/// - **YOLOv8**: 包含完整的 struct + impl Model trait
+///   - 模型加载 (new)
+///   - 预处理 (preprocess)  
+///   - 推理 (run)
+///   - 后处理 (postprocess)
+///   - 文件: `yolov8.rs`
+///
+/// ## 自带预处理的完整模型 (Native Preprocess)
+/// - **FastestV2/NanoDet**: 完整实现 Model trait, 预处理按各自官方流程
+///   - FastestV2: 直接拉伸到输入尺寸, BGR, /255
+///   - NanoDet: 拉伸 (NanoDet-Plus) 或居中 letterbox (`keep_ratio`), BGR, mean/std 归一化
+///   - `native_preprocess()` 为 true: 检测器不做通用缩放/YUV 融合预处理, 整帧交给模型
+///   - 文件: `fastestv2.rs`, `nanodet.rs`
+///
+/// ## 开放词汇模型 (Open Vocabulary)
+/// - **YOLOWorld**: 类别文本嵌入作为第二个输入 (txt_feats), 文本由 CLIP 文本编码器生成
+///   - `set_vocabulary()` 运行时替换类别词表, 不重建检测会话; `vocabulary()` 为当前类别名与颜色
+///   - 文件: `world.rs`
+///
+/// ## 后处理器模式 (Postprocessor Pattern, 已弃用)
+/// - 只提供后处理器、预处理依赖检测器通用缩放的做法已弃用, 新模型应完整实现 Model trait
+/// - `FastestV2Postprocessor`/`NanoDetPostprocessor` 仍导出, 作为完整模型的解码阶段
+///   供 golden 测试与外部绑定直接解码输出张量
+///
+/// ## 模拟模型 (Mock Model)
+/// - **MockModel**: 按 JSON 夹具或闭包输出检测结果, 不加载 ONNX
+///   - 下游代码 (跟踪/输出端/渲染/统计) 的测试与基准使用
+///   - 模型路径 `mock:夹具文件` 时由 `load_detect_model` 加载
+///   - 文件: `mock.rs`
+///
+/// ## 功能开关
+/// - 核心库 (无 `ort` 功能): Model trait、各模型后处理器、校准、模拟模型与 ONNX 模型信息解析
+/// - `ort` 功能: 基于 OrtBackend 的完整模型、辅助模型 (姿态/属性/深度/密度/CLIP) 与 `load_detect_model`
+///
+/// ## Model Trait
+/// 统一的模型接口，定义标准流程: preprocess → run → postprocess
+///
+/// ## 使用示例
+/// ```rust
+/// use yolov8_rs::models::{Model, YOLOv8};
+/// use yolov8_rs::Args;
+///
+/// // 方式1: 使用完整模型 (推荐)
+/// let mut model = YOLOv8::new(args)?;
+/// let results = model.run(&images)?;
+///
+/// // 方式2: 使用 Model trait (灵活)
+/// let results = model.forward(&images)?;
+/// ```
+use anyhow::Result;
+use image::DynamicImage;
+use ndarray::{s, Array, IxDyn};
+
+use crate::{DetectionResult, YOLOTask};
+#[cfg(feature = "ort")]
+use crate::OrtBackend;
+
+/// 模型类型枚举（用于自动识别模型）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelType {
+    /// YOLOv8 标准模型
+    YOLOv8,
+    /// YOLOv5 模型
+    YOLOv5,
+    /// YOLOv10 端到端模型 (NMS-Free)
+    YOLOv10,
+    /// YOLOv11 改进模型 (C3k2 + SPPF)
+    YOLOv11,
+    /// YOLOX 无锚点模型
+    YOLOX,
+    /// YOLO-FastestV2 超轻量级模型
+    FastestV2,
+    /// NanoDet 系列模型
+    NanoDet,
+    /// 图内 NMS 的端到端导出 (TensorRT EfficientNMS / YOLOv8 end2end)
+    End2End,
+    /// YOLO-World 开放词汇模型 (类别文本嵌入作为输入)
+    World,
+}
+
+impl ModelType {
+    /// 从模型路径推断模型类型
+    pub fn from_path(path: &str) -> Self {
+        if path.contains("world") {
+            ModelType::World
+        } else if path.contains("end2end") || path.contains("nms") {
+            ModelType::End2End
+        } else if path.contains("yolov10") || path.contains("v10") {
+            ModelType::YOLOv10
+        } else if path.contains("yolov11") || path.contains("v11") {
+            ModelType::YOLOv11
+        } else if path.contains("yolox") {
+            ModelType::YOLOX
+        } else if path.contains("fastestv2") {
+            ModelType::FastestV2
+        } else if path.contains("nanodet") {
+            ModelType::NanoDet
+        } else if path.contains("v5") {
+            ModelType::YOLOv5
+        } else {
+            ModelType::YOLOv8
+        }
+    }
+
+    /// 获取模型推荐的置信度阈值
+    pub fn default_conf_threshold(&self) -> f32 {
+        match self {
+            ModelType::YOLOv10 => 0.20, // v10端到端模型已过滤
+            ModelType::YOLOv11 => 0.10, // v11降低阈值检测静止目标
+            ModelType::YOLOX => 0.25,
+            ModelType::FastestV2 => 0.10,
+            ModelType::NanoDet => 0.35,
+            ModelType::YOLOv5 => 0.25,
+            ModelType::YOLOv8 => 0.10,  // 降低阈值检测静止目标
+            ModelType::End2End => 0.25, // 模型内 NMS 已按导出阈值过滤
+            ModelType::World => 0.10,   // 开放词汇的相似度分数整体偏低
+        }
+    }
+
+    /// 获取模型推荐的IOU阈值
+    pub fn default_iou_threshold(&self) -> f32 {
+        match self {
+            ModelType::NanoDet => 0.6,
+            _ => 0.45,
+        }
+    }
+
+    /// 获取模型官方的预处理参数 (填充色/归一化/通道顺序)
+    pub fn preprocess_spec(&self) -> PreprocessSpec {
+        match self {
+            // YOLOX 官方: 114 填充, BGR, 不做归一化 (输入 0~255)
+            ModelType::YOLOX => PreprocessSpec {
+                fill: [114, 114, 114],
+                mean: [0.0; 3],
+                std: [1.0; 3],
+                channel_order: ChannelOrder::Bgr,
+                channels: 3,
+            },
+            // NanoDet 官方: 黑色填充, BGR, mean/std 按 BGR 顺序
+            ModelType::NanoDet => PreprocessSpec {
+                fill: [0, 0, 0],
+                mean: [103.53, 116.28, 123.675],
+                std: [57.375, 57.12, 58.395],
+                channel_order: ChannelOrder::Bgr,
+                channels: 3,
+            },
+            // FastestV2 官方 (cv2 读图直接缩放): BGR, /255
+            ModelType::FastestV2 => PreprocessSpec {
+                fill: [0, 0, 0],
+                mean: [0.0; 3],
+                std: [255.0; 3],
+                channel_order: ChannelOrder::Bgr,
+                channels: 3,
+            },
+            _ => PreprocessSpec::default(),
+        }
+    }
+}
+
+/// 输入张量的通道顺序
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelOrder {
+    Rgb,
+    Bgr,
+}
+
+/// 预处理参数
+///
+/// 像素值按 `(v - mean[c]) / std[c]` 归一化, `v` 为 0~255 原始值,
+/// `mean`/`std` 按输入张量的通道顺序给出; `fill` 为 letterbox 填充色 (RGB).
+/// 单通道模型 (热成像/灰度) 输入 BT.601 亮度, 只使用 `mean[0]`/`std[0]`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PreprocessSpec {
+    pub fill: [u8; 3],
+    pub mean: [f32; 3],
+    pub std: [f32; 3],
+    pub channel_order: ChannelOrder,
+    pub channels: usize, // 张量通道数: 3 或 1 (单通道模型)
+}
+
+impl Default for PreprocessSpec {
+    /// Ultralytics 系列 (YOLOv5/v8/v10/v11): 144 灰色填充, RGB, /255
+    fn default() -> Self {
+        Self {
+            fill: [144, 144, 144],
+            mean: [0.0; 3],
+            std: [255.0; 3],
+            channel_order: ChannelOrder::Rgb,
+            channels: 3,
+        }
+    }
+}
+
+impl PreprocessSpec {
+    /// 按模型输入通道数设置 (1 为单通道, 其余按 3 通道)
+    pub fn with_channels(mut self, channels: usize) -> Self {
+        self.channels = if channels == 1 { 1 } else { 3 };
+        self
+    }
+
+    /// RGB → BT.601 亮度 (定点), 与 YUV 的 Y 平面一致; 灰度帧 (R=G=B) 保持原值
+    pub fn luma(rgb: [u8; 3]) -> u8 {
+        let [r, g, b] = rgb.map(u32::from);
+        ((77 * r + 150 * g + 29 * b + 128) >> 8) as u8
+    }
+
+    /// 输入张量第 `c` 个通道对应的 RGB 分量下标
+    pub fn source_channel(&self, c: usize) -> usize {
+        match self.channel_order {
+            ChannelOrder::Rgb => c,
+            ChannelOrder::Bgr => 2 - c,
+        }
+    }
+
+    /// RGB 像素 → 按张量通道顺序排列的归一化值
+    pub fn normalize(&self, rgb: [u8; 3]) -> [f32; 3] {
+        std::array::from_fn(|c| (rgb[self.source_channel(c)] as f32 - self.mean[c]) / self.std[c])
+    }
+
+    /// 每个张量通道的 0~255 查找表 (融合预处理使用)
+    pub fn lut(&self) -> [[f32; 256]; 3] {
+        std::array::from_fn(|c| std::array::from_fn(|v| (v as f32 - self.mean[c]) / self.std[c]))
+    }
+
+    /// 以 letterbox 填充色初始化 NCHW 张量
+    pub fn fill_tensor(&self, ys: &mut Array<f32, IxDyn>) {
+        if self.channels == 1 {
+            let gray = (Self::luma(self.fill) as f32 - self.mean[0]) / self.std[0];
+            ys.slice_mut(s![.., 0, .., ..]).fill(gray);
+            return;
+        }
+        let fill = self.normalize(self.fill);
+        for (c, value) in fill.iter().enumerate() {
+            ys.slice_mut(s![.., c, .., ..]).fill(*value);
+        }
+    }
+
+    /// 写入一个像素的全部张量通道 (单通道模型写入亮度)
+    pub fn write_pixel(&self, ys: &mut Array<f32, IxDyn>, idx: usize, y: usize, x: usize, rgb: [u8; 3]) {
+        if self.channels == 1 {
+            ys[[idx, 0, y, x]] = (Self::luma(rgb) as f32 - self.mean[0]) / self.std[0];
+            return;
+        }
+        for (c, v) in self.normalize(rgb).into_iter().enumerate() {
+            ys[[idx, c, y, x]] = v;
+        }
+    }
+
+    /// 图片 → NCHW 张量 (双线性缩放): keep_ratio 时居中 letterbox 并以 fill 填充, 否则直接拉伸
+    pub fn resize_tensor(
+        &self,
+        images: &[DynamicImage],
+        width: u32,
+        height: u32,
+        keep_ratio: bool,
+    ) -> Array<f32, IxDyn> {
+        let mut ys =
+            Array::zeros((images.len(), self.channels, height as usize, width as usize)).into_dyn();
+        self.fill_tensor(&mut ys);
+        for (idx, img) in images.iter().enumerate() {
+            let (w0, h0) = (img.width(), img.height());
+            let fit = if keep_ratio {
+                Letterbox::centered((w0, h0), (width, height))
+            } else {
+                Letterbox::stretch((width, height))
+            };
+            let resized = img
+                .resize_exact(fit.width, fit.height, image::imageops::FilterType::Triangle)
+                .to_rgb8();
+            for (x, y, rgb) in resized.enumerate_pixels() {
+                let (x, y) = ((x + fit.pad_x) as usize, (y + fit.pad_y) as usize);
+                self.write_pixel(&mut ys, idx, y, x, rgb.0);
+            }
+        }
+        ys
+    }
+}
+
+/// 原图在模型输入中的位置 (缩放后尺寸与左上角偏移)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Letterbox {
+    pub width: u32,
+    pub height: u32,
+    pub pad_x: u32,
+    pub pad_y: u32,
+}
+
+impl Letterbox {
+    /// 直接拉伸到输入尺寸
+    pub fn stretch((width, height): (u32, u32)) -> Self {
+        Self {
+            width,
+            height,
+            pad_x: 0,
+            pad_y: 0,
+        }
+    }
+
+    /// 保持宽高比缩放后居中 (NanoDet keep_ratio)
+    pub fn centered((w0, h0): (u32, u32), (width, height): (u32, u32)) -> Self {
+        let r = (width as f32 / w0.max(1) as f32).min(height as f32 / h0.max(1) as f32);
+        let w = ((w0 as f32 * r).round() as u32).clamp(1, width);
+        let h = ((h0 as f32 * r).round() as u32).clamp(1, height);
+        Self {
+            width: w,
+            height: h,
+            pad_x: (width - w) / 2,
+            pad_y: (height - h) / 2,
+        }
+    }
+
+    /// 模型输入坐标 → 原图坐标: (x - pad) * 缩放
+    pub fn to_original(&self, (w0, h0): (u32, u32)) -> (f32, f32, f32, f32) {
+        (
+            w0 as f32 / self.width as f32,
+            h0 as f32 / self.height as f32,
+            self.pad_x as f32,
+            self.pad_y as f32,
+        )
+    }
+}
+
+/// 统一的深度学习模型接口
+///
+/// 所有模型(YOLOv8, YOLOv5, FastestV2, NanoDet等)都应实现此 trait
+///
+/// ## 核心流程
+/// ```text
+/// 原始图片 → preprocess → ndarray张量
+///          ↓
+///     推理引擎 run
+///          ↓
+///     原始输出 → postprocess → 检测结果
+/// ```
+pub trait Model {
+    /// 预处理: 图片 → ndarray 张量
+    ///
+    /// # Arguments
+    /// * `images` - 输入图片数组(支持批量处理)
+    ///
+    /// # Returns
+    /// * `Vec<Array<f32, IxDyn>>` - NCHW格式的张量,准备送入推理引擎
+    fn preprocess(&mut self, images: &[DynamicImage]) -> Result<Vec<Array<f32, IxDyn>>>;
+
+    /// 推理: 执行模型前向传播
+    ///
+    /// # Arguments
+    /// * `xs` - 预处理后的张量
+    /// * `profile` - 是否启用性能分析
+    ///
+    /// # Returns
+    /// * `Vec<Array<f32, IxDyn>>` - 模型原始输出(未解码)
+    fn run(&mut self, xs: Vec<Array<f32, IxDyn>>, profile: bool) -> Result<Vec<Array<f32, IxDyn>>>;
+
+    /// 后处理: 原始输出 → 检测结果
+    ///
+    /// # Arguments
+    /// * `xs` - 模型原始输出
+    /// * `xs0` - 原始图片(用于坐标还原)
+    ///
+    /// # Returns
+    /// * `Vec<DetectionResult>` - 解码后的检测结果(bbox、keypoint、mask等)
+    fn postprocess(
+        &self,
+        xs: Vec<Array<f32, IxDyn>>,
+        xs0: &[DynamicImage],
+    ) -> Result<Vec<DetectionResult>>;
+
+    /// 完整的推理流程: preprocess → run → postprocess
+    ///
+    /// 默认实现调用上面三个方法,子类可以重写以优化性能
+    fn forward(&mut self, images: &[DynamicImage]) -> Result<Vec<DetectionResult>> {
+        let xs = self.preprocess(images)?;
+        let ys = self.run(xs, false)?;
+        self.postprocess(ys, images)
+    }
+
+    /// 获取底层推理引擎的可变引用
+    ///
+    /// 用于直接调用 OrtBackend::run (绕过 Model::run 的封装); 没有推理引擎的模型 (MockModel) 为 None
+    #[cfg(feature = "ort")]
+    fn engine_mut(&mut self) -> Option<&mut OrtBackend>;
+
+    /// 打印模型信息
+    fn summary(&self);
+
+    /// 检查模型是否支持指定任务
+    ///
+    /// # Arguments
+    /// * `task` - 要检查的任务类型(Detect/Pose/Segment/Classify)
+    ///
+    /// # Returns
+    /// * `bool` - true表示支持该任务, false表示不支持
+    ///
+    /// # Examples
+    /// ```
+    /// if model.supports_task(YOLOTask::Pose) {
+    ///     // 执行姿态估计
+    /// }
+    /// ```
+    fn supports_task(&self, task: YOLOTask) -> bool;
+
+    /// 设置置信度阈值
+    fn set_conf(&mut self, val: f32);
+
+    /// 获取置信度阈值
+    fn conf(&self) -> f32;
+
+    /// 设置IOU阈值
+    fn set_iou(&mut self, val: f32);
+
+    /// 获取IOU阈值
+    fn iou(&self) -> f32;
+
+    /// 预处理参数 (检测器快速路径据此归一化, 需与 preprocess 一致, 含模型输入通道数)
+    fn preprocess_spec(&self) -> PreprocessSpec {
+        PreprocessSpec::default()
+    }
+
+    /// 置信度校准 (绕过 postprocess 的快速路径据此换算阈值与输出置信度)
+    fn calibration(&self) -> Calibration {
+        Calibration::Identity
+    }
+
+    /// 模型自带预处理 (官方缩放/letterbox 与归一化)
+    ///
+    /// 为 true 时检测器不做通用缩放与 YUV 融合预处理, 把整帧原图交给 preprocess,
+    /// postprocess 输出原图坐标
+    fn native_preprocess(&self) -> bool {
+        false
+    }
+
+    /// 就地切换推理输入尺寸 (动态轴模型按新形状绑定输入, 不重建会话), 下一次 preprocess 生效
+    ///
+    /// 输入高宽为静态轴或 TensorRT 按固定尺寸构建时不支持, 返回 false
+    fn set_input_size(&mut self, _width: u32, _height: u32) -> bool {
+        false
+    }
+
+    /// 开放词汇模型当前的类别词表 (名称与颜色, 下标即类别ID), 其他模型为 None
+    fn vocabulary(&self) -> Option<&Vocabulary> {
+        None
+    }
+
+    /// 运行时替换类别词表 (重新生成文本嵌入, 不重建会话), 下一次推理生效
+    ///
+    /// 非开放词汇模型返回错误; 失败时保留原词表
+    fn set_vocabulary(&mut self, _classes: &[String]) -> Result<()> {
+        anyhow::bail!("当前模型不是开放词汇模型, 不支持设置类别词表")
+    }
+}
+
+// 各模型的具体实现
+#[cfg(feature = "ort")]
+pub mod attributes; // 行人属性识别 (衣着颜色/背包/安全帽, 按轨迹低频运行)
+pub mod calibration; // 置信度校准 (温度/Platt 缩放, 统一各模型阈值)
+#[cfg(feature = "ort")]
+pub mod clip; // CLIP 开放词汇检索 (文本提示高亮)
+#[cfg(feature = "ort")]
+pub mod crowd; // 人群密度估计 (CSRNet 密度图)
+#[cfg(feature = "ort")]
+pub mod depth; // 单目深度估计 (MiDaS/Depth-Anything)
+pub mod end2end; // 图内 NMS 的端到端导出 (EfficientNMS)
+pub mod fastestv2;
+pub mod mock; // 模拟模型 (按夹具/闭包输出检测结果, 不需要 ONNX Runtime)
+pub mod model_info; // ONNX 模型信息与加载前兼容性检查
+pub mod nanodet;
+#[cfg(feature = "ort")]
+pub mod pose; // Top-Down 两阶段姿态估计 (ViTPose/RTMPose)
+#[cfg(feature = "ort")]
+pub mod vehicle; // 车辆属性识别 (车型/车身颜色, 按轨迹低频运行)
+pub mod world; // YOLO-World 开放词汇检测 (运行时类别词表)
+pub mod yolov10; // YOLOv10 端到端模型 (NMS-Free)
+#[cfg(feature = "ort")]
+pub mod yolov11; // YOLOv11 改进模型
+pub mod yolov8; // YOLOv8 完整模型 + 实现 Model trait
+pub mod yolox; // YOLOX 无锚点模型
+
+// Re-exports
+#[cfg(feature = "ort")]
+pub use attributes::{AttributeRecognizer, Color, PersonAttributes};
+pub use calibration::{
+    calibration_for, CalibratedModel, Calibration, CalibrationConfig, CALIBRATION_CONFIG_PATH,
+};
+#[cfg(feature = "ort")]
+pub use clip::{ClipModel, ClipTextEncoder, ClipTokenizer};
+#[cfg(feature = "ort")]
+pub use crowd::{CrowdCounter, DensityMap};
+#[cfg(feature = "ort")]
+pub use depth::{DepthEstimator, DepthMap, DepthModelKind};
+#[cfg(feature = "ort")]
+pub use end2end::End2End;
+pub use end2end::{End2EndPostprocessor, NmsOutputs};
+#[cfg(feature = "ort")]
+pub use fastestv2::FastestV2;
+pub use fastestv2::{FastestV2Config, FastestV2Postprocessor};
+pub use mock::{MockDetection, MockFixture, MockModel, MOCK_MODEL_PREFIX};
+pub use model_info::{ModelGuess, ModelInfo};
+#[cfg(feature = "ort")]
+pub use nanodet::NanoDet;
+pub use nanodet::{NanoDetConfig, NanoDetPostprocessor};
+#[cfg(feature = "ort")]
+pub use pose::{PoseHead, TopDownPose};
+#[cfg(feature = "ort")]
+pub use vehicle::{VehicleInfo, VehicleRecognizer, VehicleType};
+#[cfg(feature = "ort")]
+pub use world::YOLOWorld;
+pub use world::{Vocabulary, WorldConfig, WORLD_CONFIG_PATH};
+#[cfg(feature = "ort")]
+pub use yolov10::YOLOv10;
+pub use yolov10::YOLOv10Postprocessor;
+#[cfg(feature = "ort")]
+pub use yolov11::YOLOv11;
+#[cfg(feature = "ort")]
+pub use yolov8::YOLOv8;
+pub use yolov8::{YOLOv8Config, YOLOv8Postprocessor};
+#[cfg(feature = "ort")]
+pub use yolox::YOLOX;
+pub use yolox::YOLOXPostprocessor;
+
+/// 按模型类型加载检测模型 (Python 绑定/gRPC 服务等独立于检测线程的调用方使用)
+///
+/// 全局校准配置中有该模型时包装为 [`CalibratedModel`], `args.conf` 视为校准后的阈值;
+/// 路径为 `mock:夹具文件` 时加载 [`MockModel`]
+#[cfg(feature = "ort")]
+pub fn load_detect_model(
+    model_type: ModelType,
+    args: crate::Args,
+) -> Result<Box<dyn Model + Send>> {
+    if let Some(fixture) = args.model.strip_prefix(MOCK_MODEL_PREFIX) {
+        let mut model = MockModel::load(fixture)?;
+        model.set_conf(args.conf);
+        model.set_iou(args.iou);
+        return Ok(Box::new(model));
+    }
+    // 文件名未注明 end2end, 但输出为 NMS 四元组时按端到端模型加载
+    let model_type = match model_type {
+        ModelType::YOLOv8 | ModelType::YOLOv5 | ModelType::YOLOv11
+            if end2end::has_nms_outputs(&args.model) =>
+        {
+            println!("🔍 {} 输出为 NMS 四元组, 按端到端模型加载", args.model);
+            ModelType::End2End
+        }
+        // 导出时已固化类别的 YOLO-World 只有图像输入, 与 YOLOv8 相同
+        ModelType::World if !world::has_text_input(&args.model) => {
+            println!("🔍 {} 没有文本嵌入输入, 按 YOLOv8 加载", args.model);
+            ModelType::YOLOv8
+        }
+        model_type => model_type,
+    };
+    let calibration = calibration_for(&args.model, model_type);
+    let model: Box<dyn Model + Send> = match model_type {
+        ModelType::YOLOv8 | ModelType::YOLOv5 => Box::new(YOLOv8::new(args)?),
+        ModelType::FastestV2 => Box::new(FastestV2::new(args)?),
+        ModelType::NanoDet => Box::new(NanoDet::new(args)?),
+        ModelType::YOLOv10 => Box::new(YOLOv10::new(args)?),
+        ModelType::YOLOv11 => Box::new(YOLOv11::new(args)?),
+        ModelType::YOLOX => Box::new(YOLOX::new(args)?),
+        ModelType::End2End => Box::new(End2End::new(args)?),
+        ModelType::World => Box::new(YOLOWorld::new(args)?),
+    };
+    Ok(match calibration {
+        Calibration::Identity => model,
+        calibration => Box::new(CalibratedModel::new(model, calibration)),
+    })
+}
+
+#[cfg(test)]
+mod golden; // 后处理 golden 张量回归测试 (testdata/postprocess)
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 默认参数与原 /255 归一化逐值一致
+    #[test]
+    fn test_default_spec_matches_div255() {
+        let spec = PreprocessSpec::default();
+        assert_eq!(spec.normalize([0, 128, 255]), [0.0, 128.0 / 255.0, 1.0]);
+        assert_eq!(spec.lut()[1][144], 144.0 / 255.0);
+    }
+
+    /// BGR 通道顺序与 mean/std 按张量通道生效
+    #[test]
+    fn test_nanodet_spec_bgr() {
+        let spec = ModelType::NanoDet.preprocess_spec();
+        let v = spec.normalize([10, 20, 30]);
+        assert_eq!(v[0], (30.0 - 103.53) / 57.375);
+        assert_eq!(v[2], (10.0 - 123.675) / 58.395);
+
+        let mut ys = Array::zeros((1, 3, 2, 2)).into_dyn();
+        ModelType::YOLOX.preprocess_spec().fill_tensor(&mut ys);
+        assert!(ys.iter().all(|&x| x == 114.0));
+    }
+
+    /// 单通道模型: 张量只有一个通道, 写入亮度, 灰度像素与 3 通道归一化一致
+    #[test]
+    fn test_single_channel_spec() {
+        let spec = PreprocessSpec::default().with_channels(1);
+        let mut ys = Array::zeros((1, 1, 2, 2)).into_dyn();
+        spec.fill_tensor(&mut ys);
+        assert!(ys.iter().all(|&x| x == 144.0 / 255.0));
+
+        spec.write_pixel(&mut ys, 0, 0, 1, [200, 200, 200]);
+        assert_eq!(ys[[0, 0, 0, 1]], 200.0 / 255.0);
+        spec.write_pixel(&mut ys, 0, 1, 0, [255, 0, 0]);
+        assert_eq!(ys[[0, 0, 1, 0]], PreprocessSpec::luma([255, 0, 0]) as f32 / 255.0);
+        assert_eq!(PreprocessSpec::luma([255, 0, 0]), 77);
+        assert_eq!(PreprocessSpec::default().with_channels(4).channels, 3);
+    }
+
+    /// 官方预处理: 拉伸铺满输入; 保持宽高比时居中, 其余为填充色; FastestV2 为 BGR /255
+    #[test]
+    fn test_resize_tensor() {
+        let red =
+            DynamicImage::ImageRgb8(image::RgbImage::from_pixel(8, 4, image::Rgb([255, 0, 0])));
+        let spec = ModelType::FastestV2.preprocess_spec();
+        let ys = spec.resize_tensor(std::slice::from_ref(&red), 4, 4, false);
+        assert_eq!(ys.shape(), &[1, 3, 4, 4]);
+        // BGR: 红色在第 3 个通道
+        assert!(ys.slice(s![0, 2, .., ..]).iter().all(|&v| v == 1.0));
+        assert!(ys.slice(s![0, 0, .., ..]).iter().all(|&v| v == 0.0));
+
+        let fit = Letterbox::centered((8, 4), (4, 4));
+        assert_eq!((fit.width, fit.height, fit.pad_x, fit.pad_y), (4, 2, 0, 1));
+        assert_eq!(fit.to_original((8, 4)), (2.0, 2.0, 0.0, 1.0));
+        let ys = spec.resize_tensor(&[red], 4, 4, true);
+        let rows: Vec<f32> = (0..4).map(|y| ys[[0, 2, y, 0]]).collect();
+        assert_eq!(rows, vec![0.0, 1.0, 1.0, 0.0]);
+    }
+}
@@ -0,0 +1,822 @@
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+// ONNX 模型信息与兼容性检查
+// 直接解析 ONNX protobuf (不创建 ORT 会话), 列出输入输出/动态轴/opset/量化方式/元数据,
+// 并按输出布局推测对应的 ModelType. 加载前用 check_layout 拒绝明显不匹配的模型,
+// 给出可操作的提示而不是在后处理或 YOLOv8::new 中 panic
+//
+// 注意:
+// - 模型类型由文件名决定 (ModelType::from_path), 布局不符时提示应包含的文件名关键字
+// - 外部数据 (>2GB) 模型只解析图结构, 不读取权重
+
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::models::{ModelType, NmsOutputs};
+use crate::DetectorError;
+
+/// 检测头的下采样步长 (anchor-free 网格)
+const STRIDES: [i64; 3] = [8, 16, 32];
+
+/// NanoDet-Plus 检测头的步长 (单输出 [N, points, nc + 32])
+const NANODET_PLUS_STRIDES: [i64; 4] = [8, 16, 32, 64];
+
+/// 端到端模型 max_det 的合理上限
+const MAX_END2END_DETECTIONS: i64 = 1000;
+
+/// 输入/输出张量描述
+#[derive(Clone, Debug, Default)]
+pub struct TensorInfo {
+    pub name: String,
+    pub dtype: &'static str,
+    /// 形状, 动态维为 -1
+    pub shape: Vec<i64>,
+    /// 各维的符号名 (如 `batch`), 非符号维为空串
+    pub dim_params: Vec<String>,
+}
+
+impl TensorInfo {
+    /// 动态维下标
+    pub fn dynamic_axes(&self) -> Vec<usize> {
+        (0..self.shape.len())
+            .filter(|&i| self.shape[i] < 0)
+            .collect()
+    }
+
+    fn shape_string(&self) -> String {
+        let dims: Vec<String> = self
+            .shape
+            .iter()
+            .enumerate()
+            .map(|(i, &d)| match self.dim_params.get(i) {
+                _ if d >= 0 => d.to_string(),
+                Some(p) if !p.is_empty() => p.clone(),
+                _ => "?".to_string(),
+            })
+            .collect();
+        format!("[{}]", dims.join(", "))
+    }
+}
+
+/// 量化方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quantization {
+    /// 未量化 FP32
+    None,
+    /// FP16 权重/输入
+    Fp16,
+    /// QuantizeLinear/DequantizeLinear 节点对 (QDQ 格式)
+    Qdq,
+    /// QLinearConv/ConvInteger 等整数算子 (QOperator 格式)
+    QOperator,
+}
+
+impl fmt::Display for Quantization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Quantization::None => "无 (FP32)",
+            Quantization::Fp16 => "FP16",
+            Quantization::Qdq => "INT8 (QDQ)",
+            Quantization::QOperator => "INT8 (QOperator)",
+        })
+    }
+}
+
+/// 模型类型推测结果
+#[derive(Clone, Debug)]
+pub struct ModelGuess {
+    pub model_type: ModelType,
+    /// 置信度 0~1
+    pub confidence: f32,
+    pub reason: &'static str,
+}
+
+/// ONNX 模型信息
+#[derive(Clone, Debug, Default)]
+pub struct ModelInfo {
+    pub path: String,
+    pub ir_version: i64,
+    pub producer: String,
+    /// (域, 版本), 默认域为空串
+    pub opsets: Vec<(String, i64)>,
+    pub inputs: Vec<TensorInfo>,
+    pub outputs: Vec<TensorInfo>,
+    /// 自定义元数据 (ultralytics 导出的 names/task/stride 等)
+    pub metadata: Vec<(String, String)>,
+    /// 图中出现过的算子类型 (去重)
+    pub op_types: Vec<String>,
+}
+
+impl ModelInfo {
+    /// 读取并解析模型文件
+    pub fn load(path: &str) -> Result<Self> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| DetectorError::ModelLoad(format!("读取 {} 失败: {}", path, e)))?;
+        let info = Self::parse(path, &bytes).map_err(|e| {
+            DetectorError::ModelLoad(format!("{} 不是有效的 ONNX 模型: {}", path, e))
+        })?;
+        Ok(info)
+    }
+
+    /// 解析 ModelProto
+    pub fn parse(path: &str, bytes: &[u8]) -> Result<Self> {
+        let mut info = ModelInfo {
+            path: path.to_string(),
+            ..Default::default()
+        };
+        let mut graph = None;
+        let mut producer_version = String::new();
+        for (field, wire) in fields(bytes)? {
+            match (field, wire) {
+                (1, Wire::Varint(v)) => info.ir_version = v as i64,
+                (2, Wire::Bytes(b)) => info.producer = text(b),
+                (3, Wire::Bytes(b)) => producer_version = text(b),
+                (7, Wire::Bytes(b)) => graph = Some(b),
+                (8, Wire::Bytes(b)) => info.opsets.push(parse_opset(b)?),
+                (14, Wire::Bytes(b)) => info.metadata.push(parse_entry(b)?),
+                _ => {}
+            }
+        }
+        if !producer_version.is_empty() {
+            info.producer = format!("{} {}", info.producer, producer_version);
+        }
+        let graph = graph.ok_or_else(|| anyhow!("缺少计算图"))?;
+
+        let mut initializers = Vec::new();
+        let mut inputs = Vec::new();
+        for (field, wire) in fields(graph)? {
+            let Wire::Bytes(b) = wire else { continue };
+            match field {
+                1 => {
+                    let op = node_op_type(b)?;
+                    if !info.op_types.contains(&op) {
+                        info.op_types.push(op);
+                    }
+                }
+                5 => initializers.push(initializer_name(b)?),
+                11 => inputs.push(parse_value_info(b)?),
+                12 => info.outputs.push(parse_value_info(b)?),
+                _ => {}
+            }
+        }
+        // 旧版导出会把权重也列为输入
+        info.inputs = inputs
+            .into_iter()
+            .filter(|i| !initializers.contains(&i.name))
+            .collect();
+        if info.inputs.is_empty() || info.outputs.is_empty() {
+            bail!("计算图缺少输入或输出");
+        }
+        Ok(info)
+    }
+
+    /// 默认域 (ai.onnx) 的 opset 版本
+    pub fn opset(&self) -> Option<i64> {
+        self.opsets
+            .iter()
+            .find(|(domain, _)| domain.is_empty() || domain == "ai.onnx")
+            .map(|(_, v)| *v)
+    }
+
+    /// 自定义元数据
+    pub fn metadata(&self, key: &str) -> Option<&str> {
+        self.metadata
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// 量化方式: 整数算子优先于 QDQ, 其次看输入是否为 FP16
+    pub fn quantization(&self) -> Quantization {
+        let has = |pred: fn(&str) -> bool| self.op_types.iter().any(|op| pred(op));
+        if has(|op| op.starts_with("QLinear") || op.ends_with("Integer")) {
+            Quantization::QOperator
+        } else if has(|op| op == "DequantizeLinear") {
+            Quantization::Qdq
+        } else if self.inputs.iter().any(|i| i.dtype == "float16") {
+            Quantization::Fp16
+        } else {
+            Quantization::None
+        }
+    }
+
+    pub fn output_shapes(&self) -> Vec<Vec<i64>> {
+        self.outputs.iter().map(|o| o.shape.clone()).collect()
+    }
+
+    /// 按文件名与输出布局推测模型类型; 带 [1, nc, D] 文本嵌入输入的为 YOLO-World
+    pub fn guess_model_type(&self) -> Option<ModelGuess> {
+        if self.inputs.iter().skip(1).any(|i| i.shape.len() == 3) {
+            return Some(ModelGuess {
+                model_type: ModelType::World,
+                confidence: 0.85,
+                reason: "图像 + [1, nc, D] 文本嵌入两个输入 (YOLO-World)",
+            });
+        }
+        guess_model_type(&self.path, &self.inputs[0].shape, &self.output_shapes())
+    }
+
+    /// 检查模型能否按 `model_type` 加载, 不能时返回可操作的错误信息
+    pub fn validate(&self, model_type: ModelType) -> Result<(), DetectorError> {
+        let input = &self.inputs[0];
+        if input.dtype != "float32" && input.dtype != "float16" {
+            return Err(DetectorError::ModelLoad(format!(
+                "输入 `{}` 类型为 {}, 只支持 FP32/FP16 输入; INT8 模型请以 QDQ 格式量化 (输入保持 FP32)",
+                input.name, input.dtype
+            )));
+        }
+        let outputs = self.output_shapes();
+        check_layout(model_type, &input.shape, &outputs)?;
+
+        let yolov8_family = matches!(
+            model_type,
+            ModelType::YOLOv8 | ModelType::YOLOv5 | ModelType::YOLOv11
+        );
+        if yolov8_family
+            && self.metadata("names").is_none()
+            && outputs[0].len() == 3
+            && outputs[0][1] < 0
+        {
+            return Err(DetectorError::ModelLoad(
+                "无法确定类别数: 模型缺少 `names` 元数据且输出通道维为动态, 请用 `--nc` 指定或导出固定形状的模型".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ModelInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let opsets: Vec<String> = self
+            .opsets
+            .iter()
+            .map(|(domain, v)| {
+                let domain = if domain.is_empty() { "ai.onnx" } else { domain };
+                format!("{}={}", domain, v)
+            })
+            .collect();
+        writeln!(f, "📦 模型: {}", self.path)?;
+        writeln!(
+            f,
+            "   IR 版本: {}, 生产者: {}",
+            self.ir_version, self.producer
+        )?;
+        writeln!(f, "   opset: {}", opsets.join(", "))?;
+        writeln!(f, "   量化: {}", self.quantization())?;
+        for (title, tensors) in [("输入", &self.inputs), ("输出", &self.outputs)] {
+            writeln!(f, "{}:", title)?;
+            for t in tensors {
+                write!(f, "   {:<12} {:<8} {}", t.name, t.dtype, t.shape_string())?;
+                let axes = t.dynamic_axes();
+                if axes.is_empty() {
+                    writeln!(f)?;
+                } else {
+                    writeln!(f, "  动态轴: {:?}", axes)?;
+                }
+            }
+        }
+        if !self.metadata.is_empty() {
+            writeln!(f, "元数据:")?;
+            for (k, v) in &self.metadata {
+                let v: String = v.chars().take(80).collect();
+                writeln!(f, "   {} = {}", k, v)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 第一个输出的布局
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Layout {
+    /// [N, 4+nc(+nm/3*nk), anchors]: YOLOv8/v11 及 ultralytics 导出的 v5u
+    ChannelsFirst,
+    /// [N, anchors, 5+nc]: YOLOX 或原版 anchor-based YOLOv5
+    AnchorsFirst { anchors: i64 },
+    /// [N, max_det, 6]: YOLOv10 端到端
+    EndToEnd,
+    /// (num_dets, boxes, scores, classes): 图内 EfficientNMS
+    Nms,
+    /// [N, nc]: 分类
+    Classify,
+    /// 全部为 4D 多尺度特征图: FastestV2 / NanoDet
+    MultiScale,
+    /// 动态维过多, 无法判断
+    Unknown,
+}
+
+fn layout(outputs: &[Vec<i64>]) -> Layout {
+    let Some(first) = outputs.first() else {
+        return Layout::Unknown;
+    };
+    if NmsOutputs::from_shapes(outputs).is_some() {
+        return Layout::Nms;
+    }
+    match *first.as_slice() {
+        [_, _] => Layout::Classify,
+        [_, n, 6] if (1..=MAX_END2END_DETECTIONS).contains(&n) => Layout::EndToEnd,
+        [_, a, c] if c > 0 && a > c => Layout::AnchorsFirst { anchors: a },
+        [_, c, a] if a > 0 && (c < 0 || a > c) => Layout::ChannelsFirst,
+        _ if outputs.iter().all(|s| s.len() == 4) => Layout::MultiScale,
+        _ => Layout::Unknown,
+    }
+}
+
+/// anchor-free 检测头的网格单元总数 (640x640 → 8400)
+fn grid_cells(height: i64, width: i64) -> i64 {
+    STRIDES
+        .iter()
+        .map(|s| ((height + s - 1) / s) * ((width + s - 1) / s))
+        .sum()
+}
+
+/// NanoDet-Plus 单输出的点数 (320x320 → 2125)
+fn nanodet_plus_points(height: i64, width: i64) -> i64 {
+    NANODET_PLUS_STRIDES
+        .iter()
+        .map(|s| ((height + s - 1) / s) * ((width + s - 1) / s))
+        .sum()
+}
+
+/// 文件名中明确给出的模型类型 (from_path 的 YOLOv8 兜底不算)
+fn path_hint(path: &str) -> Option<ModelType> {
+    let name = Path::new(path)
+        .file_name()?
+        .to_string_lossy()
+        .to_lowercase();
+    let model_type = ModelType::from_path(&name);
+    (model_type != ModelType::YOLOv8 || name.contains("v8")).then_some(model_type)
+}
+
+/// 让 `ModelType::from_path` 识别为该类型的文件名关键字
+fn path_keyword(model_type: ModelType) -> &'static str {
+    match model_type {
+        ModelType::YOLOv8 => "yolov8",
+        ModelType::YOLOv5 => "yolov5",
+        ModelType::YOLOv10 => "yolov10",
+        ModelType::YOLOv11 => "yolov11",
+        ModelType::YOLOX => "yolox",
+        ModelType::FastestV2 => "fastestv2",
+        ModelType::NanoDet => "nanodet",
+        ModelType::End2End => "end2end",
+        ModelType::World => "world",
+    }
+}
+
+/// 按输出布局推测模型类型, 文件名与布局一致时提高置信度
+///
+/// 原版 anchor-based YOLOv5 (anchors = 3 × 网格数) 没有对应的后处理, 返回 None
+pub fn guess_model_type(path: &str, input: &[i64], outputs: &[Vec<i64>]) -> Option<ModelGuess> {
+    let hint = path_hint(path);
+    let input_size = match *input {
+        [_, _, h, w] if h > 0 && w > 0 => Some((h, w)),
+        _ => None,
+    };
+    let (model_type, confidence, reason) = match layout(outputs) {
+        Layout::ChannelsFirst => {
+            let model_type = hint
+                .filter(|t| {
+                    matches!(
+                        t,
+                        ModelType::YOLOv8 | ModelType::YOLOv5 | ModelType::YOLOv11
+                    )
+                })
+                .unwrap_or(ModelType::YOLOv8);
+            (
+                model_type,
+                0.75,
+                "[N, 4+nc, anchors] 输出 (YOLOv8/v11 布局)",
+            )
+        }
+        Layout::EndToEnd => (ModelType::YOLOv10, 0.8, "[N, max_det, 6] 端到端输出"),
+        Layout::Nms => (
+            ModelType::End2End,
+            0.85,
+            "(num_dets, boxes, scores, classes) 图内 NMS 输出",
+        ),
+        Layout::AnchorsFirst { anchors } => match input_size {
+            Some((h, w)) if anchors == grid_cells(h, w) => (
+                ModelType::YOLOX,
+                0.8,
+                "[N, anchors, 5+nc] 输出, 网格数与输入尺寸吻合",
+            ),
+            Some((h, w)) if anchors == nanodet_plus_points(h, w) => (
+                ModelType::NanoDet,
+                0.7,
+                "[N, points, nc+32] 输出, 点数与 NanoDet-Plus 的 4 个步长吻合",
+            ),
+            Some((h, w)) if anchors == 3 * grid_cells(h, w) => return None,
+            _ => (
+                ModelType::YOLOX,
+                0.5,
+                "[N, anchors, 5+nc] 输出, 输入尺寸动态无法核对网格数",
+            ),
+        },
+        Layout::Classify => (ModelType::YOLOv8, 0.6, "[N, nc] 分类输出"),
+        Layout::MultiScale if outputs.len() == 6 => (
+            ModelType::NanoDet,
+            0.7,
+            "6 个多尺度输出 (cls/dis × 3 个步长)",
+        ),
+        Layout::MultiScale => (ModelType::FastestV2, 0.6, "多尺度 [N, h, w, C] 输出"),
+        Layout::Unknown => return None,
+    };
+    let confidence = if hint == Some(model_type) {
+        (confidence + 0.15f32).min(0.95)
+    } else {
+        confidence
+    };
+    Some(ModelGuess {
+        model_type,
+        confidence,
+        reason,
+    })
+}
+
+/// 检查输入输出布局是否符合 `model_type` 的后处理
+///
+/// 动态维过多无法判断时放行; 不符时给出看起来像的模型类型及应使用的文件名关键字
+pub fn check_layout(
+    model_type: ModelType,
+    input: &[i64],
+    outputs: &[Vec<i64>],
+) -> Result<(), DetectorError> {
+    if input.len() != 4 || (input[1] != 3 && input[1] != 1 && input[1] >= 0) {
+        return Err(DetectorError::ShapeMismatch(format!(
+            "模型输入形状 {:?} 不是 [N, 3, H, W] 或 [N, 1, H, W] 图像输入",
+            input
+        )));
+    }
+    let actual = layout(outputs);
+    let expected = match model_type {
+        ModelType::YOLOv8 | ModelType::YOLOv5 | ModelType::YOLOv11 | ModelType::World => {
+            match actual {
+                Layout::ChannelsFirst | Layout::Classify => true,
+                // 转置导出 [N, anchors, 4+nc]: anchor 数须与输入网格数吻合, 排除原版 anchor-based YOLOv5
+                Layout::AnchorsFirst { anchors } => match *input {
+                    [_, _, h, w] if h > 0 && w > 0 => anchors == grid_cells(h, w),
+                    _ => true,
+                },
+                _ => false,
+            }
+        }
+        ModelType::YOLOv10 => actual == Layout::EndToEnd,
+        ModelType::YOLOX => matches!(actual, Layout::AnchorsFirst { .. }),
+        ModelType::FastestV2 => actual == Layout::MultiScale,
+        // 旧版 6 个特征图, 或 NanoDet-Plus 单输出
+        ModelType::NanoDet => match actual {
+            Layout::MultiScale => true,
+            Layout::AnchorsFirst { anchors } => match *input {
+                [_, _, h, w] if h > 0 && w > 0 => anchors == nanodet_plus_points(h, w),
+                _ => true,
+            },
+            _ => false,
+        },
+        ModelType::End2End => actual == Layout::Nms,
+    };
+    if expected || actual == Layout::Unknown {
+        return Ok(());
+    }
+
+    let mut msg = format!("模型输出 {:?} 与 {:?} 的输出布局不符", outputs, model_type);
+    match guess_model_type("", input, outputs) {
+        Some(guess) => msg.push_str(&format!(
+            ", 看起来是 {:?} 模型 ({}); 文件名包含 `{}` 即可按该类型加载",
+            guess.model_type,
+            guess.reason,
+            path_keyword(guess.model_type)
+        )),
+        None if matches!(actual, Layout::AnchorsFirst { .. }) => msg.push_str(
+            "; 疑似原版 anchor-based YOLOv5 导出, 请用 ultralytics 重新导出 (如 yolov5nu.pt)",
+        ),
+        None => msg.push_str("; 可运行 `model-info --model <模型>` 查看输入输出"),
+    }
+    Err(DetectorError::ShapeMismatch(msg))
+}
+
+/// protobuf 线格式字段值
+enum Wire<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+fn read_varint(buf: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&b, rest) = buf.split_first()?;
+        *buf = rest;
+        value |= ((b & 0x7f) as u64) << shift;
+        if b & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// 拆分一条 protobuf 消息的全部字段 (字段号, 值), 长度字段只借用不拷贝
+fn fields(mut buf: &[u8]) -> Result<Vec<(u64, Wire<'_>)>> {
+    let truncated = || anyhow!("protobuf 数据截断");
+    let mut out = Vec::new();
+    while !buf.is_empty() {
+        let key = read_varint(&mut buf).ok_or_else(truncated)?;
+        let wire = match key & 7 {
+            0 => Wire::Varint(read_varint(&mut buf).ok_or_else(truncated)?),
+            1 | 5 => {
+                let n = if key & 7 == 1 { 8 } else { 4 };
+                buf = buf.get(n..).ok_or_else(truncated)?;
+                Wire::Fixed
+            }
+            2 => {
+                let len = read_varint(&mut buf).ok_or_else(truncated)? as usize;
+                if buf.len() < len {
+                    return Err(truncated());
+                }
+                let (data, rest) = buf.split_at(len);
+                buf = rest;
+                Wire::Bytes(data)
+            }
+            t => bail!("不支持的 protobuf 线类型 {}", t),
+        };
+        out.push((key >> 3, wire));
+    }
+    Ok(out)
+}
+
+fn text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// 消息中第一个指定字段号的字符串
+fn string_field(buf: &[u8], field: u64) -> Result<String> {
+    Ok(fields(buf)?
+        .into_iter()
+        .find_map(|(f, w)| match w {
+            Wire::Bytes(b) if f == field => Some(text(b)),
+            _ => None,
+        })
+        .unwrap_or_default())
+}
+
+/// OperatorSetIdProto: domain=1, version=2
+fn parse_opset(buf: &[u8]) -> Result<(String, i64)> {
+    let mut opset = (String::new(), 0);
+    for (field, wire) in fields(buf)? {
+        match (field, wire) {
+            (1, Wire::Bytes(b)) => opset.0 = text(b),
+            (2, Wire::Varint(v)) => opset.1 = v as i64,
+            _ => {}
+        }
+    }
+    Ok(opset)
+}
+
+/// StringStringEntryProto: key=1, value=2
+fn parse_entry(buf: &[u8]) -> Result<(String, String)> {
+    Ok((string_field(buf, 1)?, string_field(buf, 2)?))
+}
+
+/// NodeProto.op_type=4
+fn node_op_type(buf: &[u8]) -> Result<String> {
+    string_field(buf, 4)
+}
+
+/// TensorProto.name=8
+fn initializer_name(buf: &[u8]) -> Result<String> {
+    string_field(buf, 8)
+}
+
+/// TensorProto.DataType 名称
+fn dtype_name(elem_type: u64) -> &'static str {
+    match elem_type {
+        1 => "float32",
+        2 => "uint8",
+        3 => "int8",
+        4 => "uint16",
+        5 => "int16",
+        6 => "int32",
+        7 => "int64",
+        8 => "string",
+        9 => "bool",
+        10 => "float16",
+        11 => "float64",
+        12 => "uint32",
+        13 => "uint64",
+        16 => "bfloat16",
+        _ => "unknown",
+    }
+}
+
+/// ValueInfoProto{name=1, type=2} → TypeProto{tensor_type=1} → Tensor{elem_type=1, shape=2}
+/// → TensorShapeProto{dim=1} → Dimension{dim_value=1, dim_param=2}
+fn parse_value_info(buf: &[u8]) -> Result<TensorInfo> {
+    let mut tensor = TensorInfo {
+        dtype: "unknown",
+        ..Default::default()
+    };
+    for (field, wire) in fields(buf)? {
+        match (field, wire) {
+            (1, Wire::Bytes(b)) => tensor.name = text(b),
+            (2, Wire::Bytes(type_proto)) => {
+                for (field, wire) in fields(type_proto)? {
+                    if let (1, Wire::Bytes(tensor_type)) = (field, wire) {
+                        parse_tensor_type(tensor_type, &mut tensor)?;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(tensor)
+}
+
+fn parse_tensor_type(buf: &[u8], tensor: &mut TensorInfo) -> Result<()> {
+    for (field, wire) in fields(buf)? {
+        match (field, wire) {
+            (1, Wire::Varint(v)) => tensor.dtype = dtype_name(v),
+            (2, Wire::Bytes(shape)) => {
+                for (field, wire) in fields(shape)? {
+                    let (1, Wire::Bytes(dim)) = (field, wire) else {
+                        continue;
+                    };
+                    let (mut value, mut param) = (-1, String::new());
+                    for (field, wire) in fields(dim)? {
+                        match (field, wire) {
+                            (1, Wire::Varint(v)) => value = v as i64,
+                            (2, Wire::Bytes(b)) => param = text(b),
+                            _ => {}
+                        }
+                    }
+                    tensor.shape.push(value);
+                    tensor.dim_params.push(param);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn varint(mut v: u64, out: &mut Vec<u8>) {
+        loop {
+            let b = (v & 0x7f) as u8;
+            v >>= 7;
+            if v == 0 {
+                out.push(b);
+                return;
+            }
+            out.push(b | 0x80);
+        }
+    }
+
+    fn num(field: u64, v: u64) -> Vec<u8> {
+        let mut out = Vec::new();
+        varint(field << 3, &mut out);
+        varint(v, &mut out);
+        out
+    }
+
+    fn msg(field: u64, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        varint((field << 3) | 2, &mut out);
+        varint(data.len() as u64, &mut out);
+        out.extend_from_slice(data);
+        out
+    }
+
+    /// ValueInfoProto: 正数为固定维, 字符串为符号维
+    fn value_info(name: &str, elem_type: u64, dims: &[Result<u64, &str>]) -> Vec<u8> {
+        let shape: Vec<u8> = dims
+            .iter()
+            .flat_map(|d| match d {
+                Ok(v) => msg(1, &num(1, *v)),
+                Err(p) => msg(1, &msg(2, p.as_bytes())),
+            })
+            .collect();
+        let tensor = [num(1, elem_type), msg(2, &shape)].concat();
+        [msg(1, name.as_bytes()), msg(2, &msg(1, &tensor))].concat()
+    }
+
+    /// 手工编码的 ModelProto: 输入输出/动态轴/opset/元数据/QDQ 量化均能解析
+    #[test]
+    fn test_parse_model_proto() {
+        let graph = [
+            msg(1, &msg(4, b"DequantizeLinear")),
+            msg(1, &msg(4, b"Conv")),
+            msg(5, &msg(8, b"weight")),
+            msg(
+                11,
+                &value_info("images", 1, &[Err("batch"), Ok(3), Ok(640), Ok(640)]),
+            ),
+            msg(11, &value_info("weight", 1, &[Ok(16)])),
+            msg(
+                12,
+                &value_info("output0", 1, &[Err("batch"), Ok(84), Ok(8400)]),
+            ),
+        ]
+        .concat();
+        let model = [
+            num(1, 8),
+            msg(2, b"pytorch"),
+            msg(7, &graph),
+            msg(8, &[msg(1, b""), num(2, 17)].concat()),
+            msg(14, &[msg(1, b"task"), msg(2, b"detect")].concat()),
+        ]
+        .concat();
+
+        let info = ModelInfo::parse("yolov8n.onnx", &model).unwrap();
+        assert_eq!(info.ir_version, 8);
+        assert_eq!(info.opset(), Some(17));
+        assert_eq!(info.metadata("task"), Some("detect"));
+        assert_eq!(info.quantization(), Quantization::Qdq);
+        assert_eq!(info.inputs.len(), 1, "权重不应计入输入");
+        assert_eq!(info.inputs[0].shape, vec![-1, 3, 640, 640]);
+        assert_eq!(info.inputs[0].dynamic_axes(), vec![0]);
+        assert_eq!(info.inputs[0].shape_string(), "[batch, 3, 640, 640]");
+        assert_eq!(info.outputs[0].shape, vec![-1, 84, 8400]);
+
+        let guess = info.guess_model_type().unwrap();
+        assert_eq!(guess.model_type, ModelType::YOLOv8);
+        assert!(guess.confidence > 0.85);
+        assert!(info.validate(ModelType::YOLOv8).is_ok());
+        assert!(ModelInfo::parse("x.onnx", &model[..model.len() - 3]).is_err());
+    }
+
+    /// 各模型的输出布局推测与加载前的兼容性检查
+    #[test]
+    fn test_guess_and_check_layout() {
+        let input = [1, 3, 640, 640];
+        let guess = |path: &str, outputs: &[Vec<i64>]| {
+            guess_model_type(path, &input, outputs).map(|g| g.model_type)
+        };
+        assert_eq!(
+            guess("a.onnx", &[vec![1, 84, 8400]]),
+            Some(ModelType::YOLOv8)
+        );
+        assert_eq!(
+            guess("yolov11n.onnx", &[vec![1, 84, 8400]]),
+            Some(ModelType::YOLOv11)
+        );
+        assert_eq!(
+            guess("a.onnx", &[vec![1, 300, 6]]),
+            Some(ModelType::YOLOv10)
+        );
+        assert_eq!(
+            guess("a.onnx", &[vec![1, 8400, 85]]),
+            Some(ModelType::YOLOX)
+        );
+        assert_eq!(guess("a.onnx", &[vec![1, 1000]]), Some(ModelType::YOLOv8));
+        // 原版 YOLOv5: 3 个 anchor × 8400 网格
+        assert_eq!(guess("a.onnx", &[vec![1, 25200, 85]]), None);
+
+        assert!(check_layout(ModelType::YOLOv8, &input, &[vec![1, 84, 8400]]).is_ok());
+        assert!(check_layout(ModelType::YOLOv8, &input, &[vec![1, -1, -1]]).is_ok());
+        // 转置导出, 由后处理自动转置
+        assert!(check_layout(ModelType::YOLOv8, &input, &[vec![1, 8400, 84]]).is_ok());
+        let err = check_layout(ModelType::YOLOv8, &input, &[vec![1, 300, 6]]).unwrap_err();
+        assert!(err.to_string().contains("`yolov10`"));
+        let err = check_layout(ModelType::YOLOv8, &input, &[vec![1, 25200, 85]]).unwrap_err();
+        assert!(err.to_string().contains("yolov5nu"));
+        assert!(check_layout(ModelType::YOLOX, &input, &[vec![1, 84, 8400]]).is_err());
+
+        // NanoDet-Plus 单输出: 640 输入 4 个步长共 8500 个点
+        let plus = [vec![1, 8500, 112]];
+        assert_eq!(guess("a.onnx", &plus), Some(ModelType::NanoDet));
+        assert!(check_layout(ModelType::NanoDet, &input, &plus).is_ok());
+        assert!(check_layout(ModelType::NanoDet, &input, &[vec![1, 8400, 85]]).is_err());
+        assert!(check_layout(ModelType::YOLOv8, &[1, 640, 640, 3], &[vec![1, 84, 8400]]).is_err());
+
+        // EfficientNMS 四元组
+        let nms = [vec![1, 1], vec![1, 100, 4], vec![1, 100], vec![1, 100]];
+        assert_eq!(guess("a.onnx", &nms), Some(ModelType::End2End));
+        assert!(check_layout(ModelType::End2End, &input, &nms).is_ok());
+        let err = check_layout(ModelType::YOLOv8, &input, &nms).unwrap_err();
+        assert!(err.to_string().contains("`end2end`"));
+
+        // YOLO-World: 图像 + 文本嵌入两个输入, 输出与 YOLOv8 相同
+        let tensor = |shape: Vec<i64>| TensorInfo {
+            shape,
+            ..Default::default()
+        };
+        let world = ModelInfo {
+            inputs: vec![tensor(input.to_vec()), tensor(vec![1, -1, 512])],
+            outputs: vec![tensor(vec![1, -1, 8400])],
+            ..Default::default()
+        };
+        assert_eq!(
+            ModelType::from_path("yolov8s-worldv2.onnx"),
+            ModelType::World
+        );
+        assert_eq!(
+            world.guess_model_type().map(|g| g.model_type),
+            Some(ModelType::World)
+        );
+        assert!(check_layout(ModelType::World, &input, &world.output_shapes()).is_ok());
+    }
+}
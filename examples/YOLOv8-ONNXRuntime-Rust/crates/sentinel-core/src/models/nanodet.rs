@@ -0,0 +1,639 @@
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+// NanoDet 模型 (预处理 + 后处理)
+// 基于官方实现: https://github.com/RangiLyu/nanodet
+// NanoDet是FCOS-style anchor-free单阶段目标检测器
+//
+// 官方流程:
+// - 预处理: NanoDet-Plus 直接拉伸到输入尺寸 (keep_ratio: False), 旧版 NanoDet 保持宽高比居中
+//   (keep_ratio: True, 黑色填充); BGR, mean/std 归一化
+// - 输出: 旧版导出为 3 个步长的 (cls, dis) 共 6 个 [1, C, h, w] 特征图;
+//   NanoDet-Plus 导出为单个 [1, num_points, nc + 4*(reg_max+1)], 分类分数已 sigmoid
+// - 解码: 距离分布 softmax + DFL 积分 × 步长
+//
+// NanoDet 完整实现 Model trait (需要 ort 功能), 检测器把整帧原图交给 preprocess.
+// NanoDetPostprocessor 作为解码阶段单独导出 (golden 测试/外部绑定使用)
+
+use anyhow::{bail, Result};
+use image::{DynamicImage, GenericImageView};
+use ndarray::{s, Array, ArrayView2, IxDyn};
+
+use super::Letterbox;
+use crate::{non_max_suppression, Bbox, DetectionResult, Point2};
+
+/// NanoDet-Plus 的特征层步长 (比旧版多一个 64)
+pub const NANODET_PLUS_STRIDES: [usize; 4] = [8, 16, 32, 64];
+
+/// NanoDet 配置
+pub struct NanoDetConfig {
+    pub num_classes: usize,
+    pub strides: Vec<usize>,
+    pub conf_threshold: f32,
+    pub iou_threshold: f32,
+    pub reg_max: usize,   // Distribution Focal Loss参数,默认7
+    pub keep_ratio: bool, // 保持宽高比居中 letterbox (旧版 NanoDet), 否则直接拉伸 (NanoDet-Plus)
+}
+
+impl Default for NanoDetConfig {
+    fn default() -> Self {
+        Self {
+            num_classes: 80,          // COCO
+            strides: vec![8, 16, 32], // NanoDet-Plus三个特征层
+            conf_threshold: 0.35,     // NanoDet推荐0.35-0.4
+            iou_threshold: 0.6,       // NanoDet推荐0.5-0.6
+            reg_max: 7,               // DFL参数
+            keep_ratio: false,        // NanoDet-Plus 官方配置
+        }
+    }
+}
+
+impl NanoDetConfig {
+    /// 按文件名区分旧版 NanoDet 与 NanoDet-Plus (旧版官方预处理保持宽高比)
+    pub fn for_model(path: &str) -> Self {
+        let name = path.to_lowercase();
+        Self {
+            keep_ratio: !name.contains("plus"),
+            ..Default::default()
+        }
+    }
+}
+
+/// 原图坐标换算: 原图 x = (x - pad_x) * scale_w
+#[derive(Clone, Copy, Debug)]
+struct ToOriginal {
+    scale_w: f32,
+    scale_h: f32,
+    pad_x: f32,
+    pad_y: f32,
+}
+
+impl ToOriginal {
+    fn bbox(&self, x1: f32, y1: f32, x2: f32, y2: f32) -> (f32, f32, f32, f32) {
+        (
+            (x1 - self.pad_x) * self.scale_w,
+            (y1 - self.pad_y) * self.scale_h,
+            (x2 - self.pad_x) * self.scale_w,
+            (y2 - self.pad_y) * self.scale_h,
+        )
+    }
+}
+
+/// NanoDet 后处理器
+///
+/// NanoDet输出格式 (anchor-free):
+/// - cls_pred_stride8: [batch, num_classes, h, w] - 分类分数
+/// - dis_pred_stride8: [batch, 32, h, w] - 边界框分布 (4边×8个bin)
+/// - 对应stride 16, 32的输出同理
+pub struct NanoDetPostprocessor {
+    config: NanoDetConfig,
+    input_width: usize,
+    input_height: usize,
+}
+
+impl NanoDetPostprocessor {
+    pub fn new(config: NanoDetConfig, input_width: usize, input_height: usize) -> Self {
+        Self {
+            config,
+            input_width,
+            input_height,
+        }
+    }
+
+    /// Distribution Focal Loss (DFL) 解码
+    ///
+    /// 将分布预测转换为实际距离值
+    /// dis: [reg_max+1] - 单边的分布概率
+    fn dfl_decode(&self, dis: &[f32]) -> f32 {
+        let mut distance = 0.0;
+        for (i, &prob) in dis.iter().enumerate() {
+            distance += i as f32 * prob;
+        }
+        distance
+    }
+
+    /// Softmax激活
+    fn softmax(x: &[f32]) -> Vec<f32> {
+        let max_val = x.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        let exp_sum: f32 = x.iter().map(|&v| (v - max_val).exp()).sum();
+        x.iter().map(|&v| (v - max_val).exp() / exp_sum).collect()
+    }
+
+    /// 解码单个特征图
+    ///
+    /// # 参数
+    /// - cls_pred: [1, num_classes, h, w] - 分类预测
+    /// - dis_pred: [1, 4*(reg_max+1), h, w] - 距离预测分布
+    /// - stride: 下采样步长
+    fn decode_feature_map(
+        &self,
+        cls_pred: &Array<f32, IxDyn>,
+        dis_pred: &Array<f32, IxDyn>,
+        stride: usize,
+        map: ToOriginal,
+    ) -> Vec<(Bbox, Option<Vec<Point2>>, Option<Vec<f32>>)> {
+        let mut results = Vec::new();
+
+        let cls_shape = cls_pred.shape();
+        let height = cls_shape[2];
+        let width = cls_shape[3];
+
+        let reg_max_plus_1 = self.config.reg_max + 1;
+
+        // 遍历空间位置
+        for h in 0..height {
+            for w in 0..width {
+                // 提取分类分数 [num_classes]
+                let cls_scores = cls_pred.slice(s![0, .., h, w]);
+
+                // Sigmoid激活
+                let cls_scores: Vec<f32> = cls_scores
+                    .iter()
+                    .map(|&x| 1.0 / (1.0 + (-x).exp()))
+                    .collect();
+
+                // 找到最大类别
+                let (class_id, &confidence) = cls_scores
+                    .iter()
+                    .enumerate()
+                    .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                    .unwrap();
+
+                // 置信度过滤
+                if confidence < self.config.conf_threshold {
+                    continue;
+                }
+
+                // 提取距离预测 [4*(reg_max+1)]
+                let dis_preds = dis_pred.slice(s![0, .., h, w]);
+
+                // 计算中心点坐标
+                let cx = (w as f32 + 0.5) * stride as f32;
+                let cy = (h as f32 + 0.5) * stride as f32;
+
+                // 解码4条边的距离 (left, top, right, bottom)
+                let mut distances = Vec::with_capacity(4);
+                for i in 0..4 {
+                    let start = i * reg_max_plus_1;
+                    let end = start + reg_max_plus_1;
+                    let dis_slice: Vec<f32> =
+                        dis_preds.slice(s![start..end]).iter().cloned().collect();
+
+                    // Softmax + DFL解码
+                    let dis_softmax = Self::softmax(&dis_slice);
+                    let distance = self.dfl_decode(&dis_softmax);
+                    distances.push(distance * stride as f32);
+                }
+
+                // distances: [left, top, right, bottom]
+                let (x1, y1, x2, y2) = map.bbox(
+                    cx - distances[0],
+                    cy - distances[1],
+                    cx + distances[2],
+                    cy + distances[3],
+                );
+
+                let bbox = Bbox::new(
+                    x1.max(0.0),
+                    y1.max(0.0),
+                    (x2 - x1).max(0.0),
+                    (y2 - y1).max(0.0),
+                    class_id,
+                    confidence,
+                );
+
+                results.push((bbox, None, None));
+            }
+        }
+
+        results
+    }
+
+    /// 特征层网格尺寸 (官方按输入尺寸向上取整)
+    fn grid(&self, stride: usize) -> (usize, usize) {
+        (
+            self.input_width.div_ceil(stride),
+            self.input_height.div_ceil(stride),
+        )
+    }
+
+    /// 单输出的步长: 点数与配置的步长吻合时使用配置, 否则按 NanoDet-Plus 的 4 个步长
+    fn flat_strides(&self, num_points: usize) -> Result<Vec<usize>> {
+        let points = |strides: &[usize]| -> usize {
+            strides
+                .iter()
+                .map(|&s| {
+                    let (w, h) = self.grid(s);
+                    w * h
+                })
+                .sum()
+        };
+        if points(&self.config.strides) == num_points {
+            Ok(self.config.strides.clone())
+        } else if points(&NANODET_PLUS_STRIDES) == num_points {
+            Ok(NANODET_PLUS_STRIDES.to_vec())
+        } else {
+            bail!(
+                "NanoDet 输出点数 {} 与输入尺寸 {}x{} 的网格不符",
+                num_points,
+                self.input_width,
+                self.input_height
+            )
+        }
+    }
+
+    /// 解码 NanoDet-Plus 单输出 [num_points, nc + 4*(reg_max+1)]
+    ///
+    /// 点按步长从小到大、行优先排列, 中心先验为 (x*stride, y*stride) (官方无 0.5 偏移);
+    /// 分类分数已 sigmoid, 框按官方 distance2bbox 截断到输入范围
+    fn decode_points(
+        &self,
+        pred: ArrayView2<f32>,
+        map: ToOriginal,
+    ) -> Result<Vec<(Bbox, Option<Vec<Point2>>, Option<Vec<f32>>)>> {
+        let nc = self.config.num_classes;
+        let bins = self.config.reg_max + 1;
+        if pred.ncols() != nc + 4 * bins {
+            bail!(
+                "NanoDet 输出通道数 {} != 类别数 {} + 4×{}",
+                pred.ncols(),
+                nc,
+                bins
+            );
+        }
+        let (max_x, max_y) = (self.input_width as f32, self.input_height as f32);
+        let mut results = Vec::new();
+        let mut row = 0;
+        for stride in self.flat_strides(pred.nrows())? {
+            let (w, h) = self.grid(stride);
+            for y in 0..h {
+                for x in 0..w {
+                    let p = pred.row(row);
+                    row += 1;
+                    let (class_id, &confidence) = p
+                        .slice(s![..nc])
+                        .iter()
+                        .enumerate()
+                        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                        .unwrap();
+                    if confidence < self.config.conf_threshold {
+                        continue;
+                    }
+                    let d: Vec<f32> = (0..4)
+                        .map(|i| {
+                            let start = nc + i * bins;
+                            let dis: Vec<f32> = p.slice(s![start..start + bins]).to_vec();
+                            self.dfl_decode(&Self::softmax(&dis)) * stride as f32
+                        })
+                        .collect();
+                    let (cx, cy) = ((x * stride) as f32, (y * stride) as f32);
+                    let (x1, y1, x2, y2) = map.bbox(
+                        (cx - d[0]).clamp(0.0, max_x),
+                        (cy - d[1]).clamp(0.0, max_y),
+                        (cx + d[2]).clamp(0.0, max_x),
+                        (cy + d[3]).clamp(0.0, max_y),
+                    );
+                    let bbox = Bbox::new(
+                        x1.max(0.0),
+                        y1.max(0.0),
+                        (x2 - x1).max(0.0),
+                        (y2 - y1).max(0.0),
+                        class_id,
+                        confidence,
+                    );
+                    results.push((bbox, None, None));
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    /// 后处理主函数
+    ///
+    /// # 参数
+    /// - `outputs`: 模型输出, 旧版导出为 [cls_8, dis_8, cls_16, dis_16, cls_32, dis_32]
+    ///   (每个stride对应(cls_pred, dis_pred)), NanoDet-Plus 导出为单个 [N, num_points, C]
+    /// - `original_images`: 原始输入图像
+    pub fn postprocess(
+        &self,
+        outputs: Vec<Array<f32, IxDyn>>,
+        original_images: &[DynamicImage],
+    ) -> Result<Vec<DetectionResult>> {
+        let mut results = Vec::new();
+        let flat = outputs.len() == 1 && outputs[0].ndim() == 3;
+
+        // 对每张图片处理
+        for (idx, img) in original_images.iter().enumerate() {
+            // 预处理的缩放方式决定坐标换算
+            let (w0, h0) = img.dimensions();
+            let input = (self.input_width as u32, self.input_height as u32);
+            let fit = if self.config.keep_ratio {
+                Letterbox::centered((w0, h0), input)
+            } else {
+                Letterbox::stretch(input)
+            };
+            let (scale_w, scale_h, pad_x, pad_y) = fit.to_original((w0, h0));
+            let map = ToOriginal {
+                scale_w,
+                scale_h,
+                pad_x,
+                pad_y,
+            };
+
+            let mut all_detections: Vec<(Bbox, Option<Vec<Point2>>, Option<Vec<f32>>)> = Vec::new();
+
+            if flat {
+                let pred = outputs[0].slice(s![idx, .., ..]);
+                all_detections = self.decode_points(pred, map)?;
+            }
+
+            // 旧版输出: [cls_8, dis_8, cls_16, dis_16, cls_32, dis_32]
+            let num_strides = if flat { 0 } else { self.config.strides.len() };
+
+            for i in 0..num_strides {
+                let cls_idx = i * 2;
+                let dis_idx = i * 2 + 1;
+
+                if outputs.len() <= dis_idx {
+                    break;
+                }
+
+                let cls_pred = &outputs[cls_idx];
+                let dis_pred = &outputs[dis_idx];
+                let stride = self.config.strides[i];
+
+                let mut dets = self.decode_feature_map(cls_pred, dis_pred, stride, map);
+                all_detections.append(&mut dets);
+            }
+
+            // NMS
+            non_max_suppression(&mut all_detections, self.config.iou_threshold);
+
+            // 提取bbox
+            let bboxes: Vec<Bbox> = all_detections
+                .into_iter()
+                .map(|(bbox, _, _)| bbox)
+                .collect();
+
+            let result = DetectionResult::new(
+                None,
+                if !bboxes.is_empty() {
+                    Some(bboxes)
+                } else {
+                    None
+                },
+                None,
+                None,
+            );
+
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_default() {
+        let config = NanoDetConfig::default();
+        assert_eq!(config.num_classes, 80);
+        assert_eq!(config.strides.len(), 3);
+        assert_eq!(config.reg_max, 7);
+    }
+
+    #[test]
+    fn test_softmax() {
+        let x = vec![1.0, 2.0, 3.0];
+        let result = NanoDetPostprocessor::softmax(&x);
+        let sum: f32 = result.iter().sum();
+        assert!((sum - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_dfl_decode() {
+        let config = NanoDetConfig::default();
+        let processor = NanoDetPostprocessor::new(config, 320, 320);
+
+        // 均匀分布应该返回中间值
+        let dis = vec![0.125; 8];
+        let distance = processor.dfl_decode(&dis);
+        assert!((distance - 3.5).abs() < 0.1);
+    }
+
+    /// NanoDet-Plus 单输出: 4 个步长的点按行优先排列, 中心先验无 0.5 偏移
+    #[test]
+    fn test_plus_single_output() {
+        // 32x32 输入: 步长 8/16/32/64 共 16+4+1+1 个点, 2 类 + 4×8 个距离 bin
+        let mut output = Array::zeros((1, 22, 34)).into_dyn();
+        // 步长 8 的 (x=1, y=2): 中心 (8, 16), 四边距离 1×8
+        output[[0, 9, 1]] = 0.9;
+        for side in 0..4 {
+            output[[0, 9, 2 + side * 8 + 1]] = 20.0;
+        }
+        let config = NanoDetConfig {
+            num_classes: 2,
+            conf_threshold: 0.5,
+            ..Default::default()
+        };
+        let processor = NanoDetPostprocessor::new(config, 32, 32);
+        let results = processor
+            .postprocess(vec![output.clone()], &[DynamicImage::new_luma8(64, 64)])
+            .unwrap();
+        let b = &results[0].bboxes().unwrap()[0];
+        let close = |a: f32, b: f32| (a - b).abs() < 0.01;
+        assert_eq!((b.id(), b.confidence()), (1, 0.9));
+        // 拉伸: 原图坐标 = 输入坐标 × 2
+        assert!(close(b.xmin(), 0.0) && close(b.ymin(), 16.0));
+        assert!(close(b.width(), 32.0) && close(b.height(), 32.0));
+
+        // 点数对不上时报错
+        let short = Array::zeros((1, 20, 34)).into_dyn();
+        assert!(processor
+            .postprocess(vec![short], &[DynamicImage::new_luma8(64, 64)])
+            .is_err());
+
+        // 保持宽高比: 64x32 原图缩放 0.5 后上下各填充 8
+        let config = NanoDetConfig {
+            num_classes: 2,
+            conf_threshold: 0.5,
+            keep_ratio: true,
+            ..Default::default()
+        };
+        let results = NanoDetPostprocessor::new(config, 32, 32)
+            .postprocess(vec![output], &[DynamicImage::new_luma8(64, 32)])
+            .unwrap();
+        let b = &results[0].bboxes().unwrap()[0];
+        assert!(close(b.ymin(), 0.0) && close(b.height(), 32.0));
+    }
+
+    /// 旧版 NanoDet 官方预处理保持宽高比, NanoDet-Plus 直接拉伸
+    #[test]
+    fn test_config_for_model() {
+        assert!(NanoDetConfig::for_model("models/nanodet-m.onnx").keep_ratio);
+        assert!(!NanoDetConfig::for_model("models/NanoDet-Plus-m_320.onnx").keep_ratio);
+    }
+}
+
+// ========================================
+// 完整 NanoDet 模型实现 (实现 Model trait, 需要 ort 功能)
+// ========================================
+
+#[cfg(feature = "ort")]
+use crate::{Batch, OrtBackend, OrtConfig, OrtEP};
+
+/// NanoDet 完整模型
+#[cfg(feature = "ort")]
+pub struct NanoDet {
+    engine: OrtBackend,
+    postprocessor: NanoDetPostprocessor,
+    width: u32,
+    height: u32,
+}
+
+#[cfg(feature = "ort")]
+impl NanoDet {
+    /// 从配置创建 NanoDet 模型
+    pub fn new(config: crate::Args) -> Result<Self> {
+        // execution provider
+        let ep = if config.trt || config.dla_core.is_some() {
+            OrtEP::Trt(config.device_id)
+        } else if config.cuda {
+            OrtEP::CUDA(config.device_id)
+        } else {
+            OrtEP::CPU
+        };
+
+        // batch
+        let batch = Batch {
+            opt: config.batch,
+            min: config.batch_min,
+            max: config.batch_max,
+        };
+
+        // 旧版与 Plus 的官方预处理不同, 按文件名区分
+        let keep_ratio = NanoDetConfig::for_model(&config.model).keep_ratio;
+
+        // build ort engine
+        let ort_args = OrtConfig {
+            ep,
+            batch,
+            f: config.model,
+            task: Some(crate::YOLOTask::Detect), // NanoDet only supports detection
+            trt_fp16: config.fp16,
+            dla_core: config.dla_core,
+            image_size: (config.height, config.width),
+        };
+        let engine = OrtBackend::build(ort_args)?;
+
+        let width = engine.width();
+        let height = engine.height();
+
+        // NanoDet 后处理器配置
+        let postprocessor_config = NanoDetConfig {
+            num_classes: config.nc.unwrap_or(80) as usize,
+            strides: vec![8, 16, 32],
+            conf_threshold: config.conf,
+            iou_threshold: config.iou,
+            reg_max: 7,
+            keep_ratio,
+        };
+
+        let postprocessor =
+            NanoDetPostprocessor::new(postprocessor_config, width as usize, height as usize);
+
+        Ok(Self {
+            engine,
+            postprocessor,
+            width,
+            height,
+        })
+    }
+}
+
+// 实现 Model trait
+#[cfg(feature = "ort")]
+impl super::Model for NanoDet {
+    fn preprocess(&mut self, images: &[DynamicImage]) -> Result<Vec<Array<f32, IxDyn>>> {
+        // NanoDet 官方预处理: 拉伸 (Plus) 或居中 letterbox (旧版, 黑色填充),
+        // BGR, mean=[103.53, 116.28, 123.675], std=[57.375, 57.12, 58.395]
+        let spec = super::ModelType::NanoDet
+            .preprocess_spec()
+            .with_channels(self.engine.channels());
+        let keep_ratio = self.postprocessor.config.keep_ratio;
+        Ok(vec![spec.resize_tensor(
+            images,
+            self.width,
+            self.height,
+            keep_ratio,
+        )])
+    }
+
+    fn run(&mut self, xs: Vec<Array<f32, IxDyn>>, profile: bool) -> Result<Vec<Array<f32, IxDyn>>> {
+        self.engine.run(xs[0].clone(), profile)
+    }
+
+    fn postprocess(
+        &self,
+        xs: Vec<Array<f32, IxDyn>>,
+        xs0: &[DynamicImage],
+    ) -> Result<Vec<DetectionResult>> {
+        self.postprocessor.postprocess(xs, xs0)
+    }
+
+    fn engine_mut(&mut self) -> Option<&mut OrtBackend> {
+        Some(&mut self.engine)
+    }
+
+    fn summary(&self) {
+        println!("\n[NanoDet 模型信息]");
+        println!("  任务类型: Detection (Anchor-Free)");
+        println!("  输入尺寸: {}x{}", self.width, self.height);
+        println!("  类别数量: {}", self.postprocessor.config.num_classes);
+        println!("  特征层strides: {:?}", self.postprocessor.config.strides);
+        println!("  DFL reg_max: {}", self.postprocessor.config.reg_max);
+        println!(
+            "  预处理: {}",
+            if self.postprocessor.config.keep_ratio {
+                "保持宽高比居中"
+            } else {
+                "拉伸"
+            }
+        );
+        println!("  置信度阈值: {}", self.postprocessor.config.conf_threshold);
+        println!("  IOU阈值: {}", self.postprocessor.config.iou_threshold);
+    }
+
+    fn supports_task(&self, task: crate::YOLOTask) -> bool {
+        // NanoDet 仅支持目标检测
+        matches!(task, crate::YOLOTask::Detect)
+    }
+
+    fn set_conf(&mut self, val: f32) {
+        self.postprocessor.config.conf_threshold = val;
+    }
+
+    fn conf(&self) -> f32 {
+        self.postprocessor.config.conf_threshold
+    }
+
+    fn set_iou(&mut self, val: f32) {
+        self.postprocessor.config.iou_threshold = val;
+    }
+
+    fn iou(&self) -> f32 {
+        self.postprocessor.config.iou_threshold
+    }
+
+    fn preprocess_spec(&self) -> super::PreprocessSpec {
+        super::ModelType::NanoDet
+            .preprocess_spec()
+            .with_channels(self.engine.channels())
+    }
+
+    fn native_preprocess(&self) -> bool {
+        true
+    }
+}
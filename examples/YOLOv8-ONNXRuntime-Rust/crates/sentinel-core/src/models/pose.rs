@@ -0,0 +1,432 @@
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+// 两阶段 Top-Down 姿态估计 (ViTPose / RTMPose)
+// 流程: 检测器输出人框 → 仿射裁剪 → 批量送入姿态模型 → 关键点映射回原图
+//
+// 相比 YOLOv8-pose 单阶段模型, 对小目标(远处行人)的关键点精度明显更好
+
+use anyhow::Result;
+use ndarray::{s, Array, ArrayView1, Axis, Ix3, Ix4, IxDyn};
+use rayon::prelude::*;
+
+use crate::detection::types::{BBox, PoseKeypoints};
+use crate::utils::affine_transform::{AffineMatrix, BorderMode, InterpolationMethod};
+use crate::utils::affine_transform_simd::warp_affine_rgb_simd;
+use crate::{Batch, OrtBackend, OrtConfig, OrtEP, YOLOTask};
+
+/// ImageNet 均值/方差 (ViTPose 与 RTMPose 官方预处理一致, RGB顺序)
+const MEAN: [f32; 3] = [123.675, 116.28, 103.53];
+const STD: [f32; 3] = [58.395, 57.12, 57.375];
+
+/// 姿态模型输出头类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoseHead {
+    /// 热力图输出 [b, k, h/4, w/4] (ViTPose / SimpleBaseline)
+    Heatmap,
+    /// SimCC 坐标分类输出 x:[b, k, w*r], y:[b, k, h*r] (RTMPose)
+    SimCC,
+}
+
+/// Top-Down 姿态估计模型
+pub struct TopDownPose {
+    engine: OrtBackend,
+    height: u32,
+    width: u32,
+    nk: u32,
+    head: PoseHead,
+    simcc_split_ratio: f32,
+    bbox_padding: f32,
+    max_batch: usize,
+    profile: bool,
+}
+
+impl TopDownPose {
+    /// 从配置创建姿态模型
+    pub fn new(config: crate::Args) -> Result<Self> {
+        // execution provider
+        let ep = if config.trt || config.dla_core.is_some() {
+            OrtEP::Trt(config.device_id)
+        } else if config.cuda {
+            OrtEP::CUDA(config.device_id)
+        } else {
+            OrtEP::CPU
+        };
+
+        // batch
+        let batch = Batch {
+            opt: config.batch,
+            min: config.batch_min,
+            max: config.batch_max,
+        };
+
+        // build ort engine
+        let ort_args = OrtConfig {
+            ep,
+            batch,
+            f: config.model,
+            task: Some(YOLOTask::Pose),
+            trt_fp16: config.fp16,
+            dla_core: config.dla_core,
+            image_size: (config.height, config.width),
+        };
+        let engine = OrtBackend::build(ort_args)?;
+        let (height, width) = (engine.height(), engine.width());
+
+        // 根据输出数量判断输出头: 2个输出为SimCC, 1个输出为热力图
+        let out_shapes = engine.output_shapes();
+        let head = if out_shapes.len() >= 2 {
+            PoseHead::SimCC
+        } else {
+            PoseHead::Heatmap
+        };
+
+        // 关键点数量
+        let nk = if out_shapes[0][1] > 0 {
+            out_shapes[0][1] as u32
+        } else {
+            config.nk.unwrap_or(17)
+        };
+
+        // SimCC 细分比例 (RTMPose 默认 2.0)
+        let simcc_split_ratio = match head {
+            PoseHead::SimCC if out_shapes[0][2] > 0 => out_shapes[0][2] as f32 / width as f32,
+            _ => 2.0,
+        };
+
+        // 动态batch时一次最多处理 batch_max 个人, 否则按模型固定batch分块
+        let max_batch = if engine.is_batch_dynamic() {
+            config.batch_max.max(1) as usize
+        } else {
+            engine.batch().max(1) as usize
+        };
+
+        Ok(Self {
+            engine,
+            height,
+            width,
+            nk,
+            head,
+            simcc_split_ratio,
+            bbox_padding: 1.25,
+            max_batch,
+            profile: config.profile,
+        })
+    }
+
+    /// 对一帧中的人框进行姿态估计
+    ///
+    /// # 参数
+    /// - `rgba`: 原始帧 RGBA 数据
+    /// - `frame_w` / `frame_h`: 原始帧尺寸
+    /// - `bboxes`: 原图坐标系下的人框
+    ///
+    /// # 返回
+    /// 与 `bboxes` 一一对应的关键点 (原图坐标系)
+    pub fn estimate(
+        &mut self,
+        rgba: &[u8],
+        frame_w: u32,
+        frame_h: u32,
+        bboxes: &[BBox],
+    ) -> Result<Vec<PoseKeypoints>> {
+        if bboxes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // RGBA → RGB (仿射裁剪工具按3通道处理)
+        let t = std::time::Instant::now();
+        let rgb = rgba_to_rgb(rgba);
+        let (in_w, in_h) = (self.width as usize, self.height as usize);
+
+        // 每个人框计算仿射矩阵并裁剪
+        let transforms: Vec<AffineMatrix> = bboxes
+            .iter()
+            .map(|b| crop_transform(b, self.width, self.height, self.bbox_padding))
+            .collect();
+        let crops: Vec<Vec<u8>> = transforms
+            .par_iter()
+            .map(|m| {
+                warp_affine_rgb_simd(
+                    &rgb,
+                    frame_w as usize,
+                    frame_h as usize,
+                    m,
+                    (in_w, in_h),
+                    InterpolationMethod::Bilinear,
+                    BorderMode::Constant(0),
+                )
+            })
+            .collect();
+        if self.profile {
+            println!("[Pose Crop]: {:?}", t.elapsed());
+        }
+
+        // 分块批量推理
+        let mut results = Vec::with_capacity(bboxes.len());
+        for (chunk_idx, chunk) in crops.chunks(self.max_batch).enumerate() {
+            let xs = self.preprocess(chunk);
+            let ys = self.engine.run(xs, self.profile)?;
+            let points = match self.head {
+                PoseHead::Heatmap => decode_heatmaps(&ys[0], self.width, self.height),
+                PoseHead::SimCC => decode_simcc(&ys[0], &ys[1], self.simcc_split_ratio),
+            };
+
+            // 映射回原图坐标
+            for (i, kpts) in points.into_iter().take(chunk.len()).enumerate() {
+                let inv = transforms[chunk_idx * self.max_batch + i]
+                    .inverse()
+                    .unwrap_or_else(AffineMatrix::identity);
+                let points = kpts
+                    .into_iter()
+                    .map(|(x, y, c)| {
+                        let (ox, oy) = inv.transform_point(x, y);
+                        (ox, oy, c)
+                    })
+                    .collect();
+                results.push(PoseKeypoints { points });
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// 裁剪图 → NCHW 张量 (ImageNet 归一化)
+    fn preprocess(&self, crops: &[Vec<u8>]) -> Array<f32, IxDyn> {
+        let (w, h) = (self.width as usize, self.height as usize);
+        // 固定batch模型需要补齐到模型batch
+        let n = if self.engine.is_batch_dynamic() {
+            crops.len()
+        } else {
+            self.max_batch
+        };
+        let mut xs = Array::zeros((n, 3, h, w)).into_dyn();
+        for (idx, crop) in crops.iter().enumerate() {
+            let mut view = xs.slice_mut(s![idx, .., .., ..]);
+            for (i, px) in crop.chunks_exact(3).enumerate() {
+                let (y, x) = (i / w, i % w);
+                for c in 0..3 {
+                    view[[c, y, x]] = (px[c] as f32 - MEAN[c]) / STD[c];
+                }
+            }
+        }
+        xs
+    }
+
+    pub fn head(&self) -> PoseHead {
+        self.head
+    }
+
+    pub fn nk(&self) -> u32 {
+        self.nk
+    }
+
+    pub fn summary(&self) {
+        println!(
+            "\nSummary:\n\
+            > Task: TopDown Pose ({:?})\n\
+            > EP: {:?}\n\
+            > Keypoints: {}\n\
+            > Input: {}x{} (batch ≤ {})",
+            self.head,
+            self.engine.ep(),
+            self.nk,
+            self.width,
+            self.height,
+            self.max_batch,
+        );
+    }
+}
+
+/// 计算人框 → 模型输入的仿射矩阵
+///
+/// 按模型输入宽高比扩展人框并外扩 `padding` 倍, 避免拉伸变形
+pub fn crop_transform(bbox: &BBox, input_w: u32, input_h: u32, padding: f32) -> AffineMatrix {
+    let cx = (bbox.x1 + bbox.x2) * 0.5;
+    let cy = (bbox.y1 + bbox.y2) * 0.5;
+    let mut w = (bbox.x2 - bbox.x1).max(1.0);
+    let mut h = (bbox.y2 - bbox.y1).max(1.0);
+
+    // 保持模型输入宽高比
+    let aspect = input_w as f32 / input_h as f32;
+    if w > h * aspect {
+        h = w / aspect;
+    } else {
+        w = h * aspect;
+    }
+    w *= padding;
+    h *= padding;
+
+    let scale = AffineMatrix::scale(input_w as f32 / w, input_h as f32 / h);
+    let shift = AffineMatrix::translation(-(cx - w * 0.5), -(cy - h * 0.5));
+    scale.compose(&shift)
+}
+
+/// 热力图解码: argmax + 1/4像素偏移修正
+///
+/// 输入 [b, k, hh, hw], 返回模型输入坐标系下的关键点
+pub fn decode_heatmaps(
+    heatmaps: &Array<f32, IxDyn>,
+    input_w: u32,
+    input_h: u32,
+) -> Vec<Vec<(f32, f32, f32)>> {
+    let Ok(heatmaps) = heatmaps.view().into_dimensionality::<Ix4>() else {
+        return Vec::new();
+    };
+    let (hh, hw) = (heatmaps.shape()[2], heatmaps.shape()[3]);
+    let stride_x = input_w as f32 / hw as f32;
+    let stride_y = input_h as f32 / hh as f32;
+
+    heatmaps
+        .axis_iter(Axis(0))
+        .map(|person| {
+            person
+                .axis_iter(Axis(0))
+                .map(|hm| {
+                    let (mut best, mut bx, mut by) = (f32::NEG_INFINITY, 0usize, 0usize);
+                    for ((y, x), &v) in hm.indexed_iter() {
+                        if v > best {
+                            best = v;
+                            bx = x;
+                            by = y;
+                        }
+                    }
+
+                    // 根据相邻像素梯度做 0.25 像素偏移
+                    let shift = |d: f32| {
+                        if d > 0.0 {
+                            0.25
+                        } else if d < 0.0 {
+                            -0.25
+                        } else {
+                            0.0
+                        }
+                    };
+                    let mut fx = bx as f32;
+                    let mut fy = by as f32;
+                    if bx > 0 && bx + 1 < hw {
+                        fx += shift(hm[[by, bx + 1]] - hm[[by, bx - 1]]);
+                    }
+                    if by > 0 && by + 1 < hh {
+                        fy += shift(hm[[by + 1, bx]] - hm[[by - 1, bx]]);
+                    }
+
+                    (
+                        (fx + 0.5) * stride_x,
+                        (fy + 0.5) * stride_y,
+                        best.clamp(0.0, 1.0),
+                    )
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// SimCC 解码: x/y 两个一维分类分布分别取 argmax
+///
+/// 置信度取两个方向最大值中的较小者 (与 mmpose 一致)
+pub fn decode_simcc(
+    simcc_x: &Array<f32, IxDyn>,
+    simcc_y: &Array<f32, IxDyn>,
+    split_ratio: f32,
+) -> Vec<Vec<(f32, f32, f32)>> {
+    let (Ok(simcc_x), Ok(simcc_y)) = (
+        simcc_x.view().into_dimensionality::<Ix3>(),
+        simcc_y.view().into_dimensionality::<Ix3>(),
+    ) else {
+        return Vec::new();
+    };
+
+    let argmax = |v: ArrayView1<f32>| {
+        v.iter()
+            .enumerate()
+            .fold((0usize, f32::NEG_INFINITY), |acc, (i, &x)| {
+                if x > acc.1 {
+                    (i, x)
+                } else {
+                    acc
+                }
+            })
+    };
+
+    simcc_x
+        .axis_iter(Axis(0))
+        .zip(simcc_y.axis_iter(Axis(0)))
+        .map(|(px, py)| {
+            px.axis_iter(Axis(0))
+                .zip(py.axis_iter(Axis(0)))
+                .map(|(vx, vy)| {
+                    let (ix, cx) = argmax(vx);
+                    let (iy, cy) = argmax(vy);
+                    (
+                        ix as f32 / split_ratio,
+                        iy as f32 / split_ratio,
+                        cx.min(cy).clamp(0.0, 1.0),
+                    )
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// RGBA → RGB (并行)
+fn rgba_to_rgb(rgba: &[u8]) -> Vec<u8> {
+    let mut rgb = vec![0u8; rgba.len() / 4 * 3];
+    rgb.par_chunks_exact_mut(3)
+        .zip(rgba.par_chunks_exact(4))
+        .for_each(|(dst, src)| dst.copy_from_slice(&src[..3]));
+    rgb
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 裁剪矩阵应将人框中心映射到模型输入中心
+    #[test]
+    fn test_crop_transform_center() {
+        let bbox = BBox {
+            x1: 100.0,
+            y1: 50.0,
+            x2: 148.0,
+            y2: 178.0,
+            confidence: 0.9,
+            class_id: 0,
+        };
+        let m = crop_transform(&bbox, 192, 256, 1.25);
+        let (x, y) = m.transform_point(124.0, 114.0);
+        assert!((x - 96.0).abs() < 1e-3);
+        assert!((y - 128.0).abs() < 1e-3);
+
+        // 逆变换回原图
+        let inv = m.inverse().unwrap();
+        let (ox, oy) = inv.transform_point(96.0, 128.0);
+        assert!((ox - 124.0).abs() < 1e-3);
+        assert!((oy - 114.0).abs() < 1e-3);
+    }
+
+    /// 热力图峰值位置解码
+    #[test]
+    fn test_decode_heatmaps_peak() {
+        let mut hm = Array::zeros((1, 1, 64, 48)).into_dyn();
+        hm[[0, 0, 10, 20]] = 0.9;
+        let kpts = decode_heatmaps(&hm, 192, 256);
+        let (x, y, c) = kpts[0][0];
+        assert!((x - 82.0).abs() < 1e-3); // (20 + 0.5) * 4
+        assert!((y - 42.0).abs() < 1e-3); // (10 + 0.5) * 4
+        assert!((c - 0.9).abs() < 1e-6);
+    }
+
+    /// SimCC 解码使用 split_ratio 还原坐标
+    #[test]
+    fn test_decode_simcc() {
+        let mut sx = Array::zeros((1, 2, 384)).into_dyn();
+        let mut sy = Array::zeros((1, 2, 512)).into_dyn();
+        sx[[0, 1, 100]] = 0.8;
+        sy[[0, 1, 300]] = 0.6;
+        let kpts = decode_simcc(&sx, &sy, 2.0);
+        assert_eq!(kpts[0].len(), 2);
+        let (x, y, c) = kpts[0][1];
+        assert_eq!((x, y), (50.0, 150.0));
+        assert!((c - 0.6).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,361 @@
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+// 车辆属性识别 (Vehicle type / color classification)
+// 对车辆框 (COCO 2=car, 5=bus, 7=truck) 的裁剪图运行分类器, 输出车型与车身颜色,
+// 按轨迹低频运行并平滑 (调度与行人属性共用 AttributeTracks), 供停车场等交通场景的区域事件使用
+//
+// 模型约定:
+// - 输入 [N, 3, H, W] (常见 224x224), RGB, ImageNet 归一化
+// - 输出 [N, K] 逻辑值, 按标签前缀分组: `type_<车型>` 与 `color_<颜色>` 两个头,
+//   组内各自 softmax (单标签)
+// - 标签顺序默认为 DEFAULT_LABELS, 可用模型同名 .txt 文件覆盖, 不认识的标签忽略;
+//   银色/香槟色等常见车漆请在训练时归入 gray/yellow
+
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use super::attributes::{AttributeTracks, Color, CropClassifier, TrackScores};
+use crate::detection::tracker::compute_iou;
+use crate::detection::types::BBox;
+
+/// 参与车辆识别的检测类别 (COCO: 2=car, 5=bus, 7=truck)
+pub const VEHICLE_CLASSES: [u32; 3] = [2, 5, 7];
+
+/// 车型/颜色概率低于该值时视为无法判断
+const MIN_PROB: f32 = 0.4;
+
+/// 跟踪框与检测框的最小 IoU (找回检测类别)
+const CLASS_MATCH_IOU: f32 = 0.5;
+
+/// 默认标签顺序 (6 种车型, 8 种颜色)
+pub const DEFAULT_LABELS: [&str; 14] = [
+    "type_car",
+    "type_suv",
+    "type_van",
+    "type_pickup",
+    "type_bus",
+    "type_truck",
+    "color_black",
+    "color_white",
+    "color_gray",
+    "color_red",
+    "color_green",
+    "color_blue",
+    "color_yellow",
+    "color_brown",
+];
+
+/// 车型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VehicleType {
+    Car,
+    Suv,
+    Van,
+    Pickup,
+    Bus,
+    Truck,
+}
+
+impl VehicleType {
+    pub const ALL: [VehicleType; 6] = [
+        VehicleType::Car,
+        VehicleType::Suv,
+        VehicleType::Van,
+        VehicleType::Pickup,
+        VehicleType::Bus,
+        VehicleType::Truck,
+    ];
+
+    /// 标签中的车型名 (小写英文)
+    pub fn name(self) -> &'static str {
+        match self {
+            VehicleType::Car => "car",
+            VehicleType::Suv => "suv",
+            VehicleType::Van => "van",
+            VehicleType::Pickup => "pickup",
+            VehicleType::Bus => "bus",
+            VehicleType::Truck => "truck",
+        }
+    }
+
+    /// 中文车型名 (事件描述)
+    pub fn label(self) -> &'static str {
+        match self {
+            VehicleType::Car => "轿车",
+            VehicleType::Suv => "SUV",
+            VehicleType::Van => "面包车",
+            VehicleType::Pickup => "皮卡",
+            VehicleType::Bus => "客车",
+            VehicleType::Truck => "货车",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        VehicleType::ALL.into_iter().find(|t| t.name() == name)
+    }
+}
+
+/// 一条车辆轨迹的属性 (平滑后)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VehicleInfo {
+    pub vehicle_type: Option<VehicleType>, // 车型, 无法判断时为 None
+    pub color: Option<Color>,              // 车身颜色
+}
+
+impl VehicleInfo {
+    /// 标签上的简短描述, 如 `white suv`
+    pub fn short_label(&self) -> String {
+        format!(
+            "{} {}",
+            self.color.map_or("?", Color::name),
+            self.vehicle_type.map_or("vehicle", VehicleType::name)
+        )
+    }
+
+    /// 中文描述 (事件与告警), 如 `白色SUV`
+    pub fn describe(&self) -> String {
+        let color = self
+            .color
+            .map(|c| format!("{}色", c.label()))
+            .unwrap_or_default();
+        format!(
+            "{}{}",
+            color,
+            self.vehicle_type.map_or("车辆", VehicleType::label)
+        )
+    }
+}
+
+/// 车型/颜色概率 (组内 softmax 之后, 未输出的标签为 0)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct VehicleScores {
+    pub types: [f32; 6],
+    pub colors: [f32; 8],
+}
+
+impl VehicleScores {
+    /// 按标签前缀分组做 softmax, 映射到车型/颜色
+    pub fn from_logits(labels: &[String], logits: &[f32]) -> Self {
+        let mut types = Vec::new();
+        let mut colors = Vec::new();
+        for (label, &logit) in labels.iter().zip(logits) {
+            match label.split_once('_') {
+                Some(("type", name)) => {
+                    if let Some(t) = VehicleType::from_name(name) {
+                        types.push((t as usize, logit));
+                    }
+                }
+                Some(("color", name)) => {
+                    if let Some(c) = Color::ALL.into_iter().find(|c| c.name() == name) {
+                        colors.push((c as usize, logit));
+                    }
+                }
+                _ => {}
+            }
+        }
+        let mut scores = Self::default();
+        softmax_into(&types, &mut scores.types);
+        softmax_into(&colors, &mut scores.colors);
+        scores
+    }
+
+    /// 取各组概率最高的一项, 概率过低时无法判断
+    pub fn info(&self) -> VehicleInfo {
+        let best = |probs: &[f32]| {
+            let (i, p) = probs.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1))?;
+            (*p >= MIN_PROB).then_some(i)
+        };
+        VehicleInfo {
+            vehicle_type: best(&self.types).map(|i| VehicleType::ALL[i]),
+            color: best(&self.colors).map(|i| Color::ALL[i]),
+        }
+    }
+}
+
+impl TrackScores for VehicleScores {
+    fn blend(&mut self, other: &Self, alpha: f32) {
+        for (a, b) in self
+            .types
+            .iter_mut()
+            .chain(self.colors.iter_mut())
+            .zip(other.types.iter().chain(&other.colors))
+        {
+            *a += (b - *a) * alpha;
+        }
+    }
+}
+
+/// 一组 (下标, 逻辑值) 做 softmax 写入 `out`
+fn softmax_into(group: &[(usize, f32)], out: &mut [f32]) {
+    let max = group
+        .iter()
+        .map(|(_, l)| *l)
+        .fold(f32::NEG_INFINITY, f32::max);
+    let sum: f32 = group.iter().map(|(_, l)| (l - max).exp()).sum();
+    for &(i, l) in group {
+        out[i] = (l - max).exp() / sum;
+    }
+}
+
+/// 找回每个输出框的检测类别
+///
+/// 启用跟踪时 `bbox.class_id` 为轨迹ID, 按 IoU 与本帧检测框匹配取其类别 (匹配不到为 None);
+/// 未跟踪时直接取 `class_id`
+pub fn source_classes(bboxes: &[BBox], detections: &[BBox], tracked: bool) -> Vec<Option<u32>> {
+    if !tracked {
+        return bboxes.iter().map(|b| Some(b.class_id)).collect();
+    }
+    bboxes
+        .iter()
+        .map(|b| {
+            detections
+                .iter()
+                .map(|d| (compute_iou(b, d), d.class_id))
+                .filter(|(iou, _)| *iou >= CLASS_MATCH_IOU)
+                .max_by(|a, b| a.0.total_cmp(&b.0))
+                .map(|(_, class)| class)
+        })
+        .collect()
+}
+
+/// 车辆属性识别器
+pub struct VehicleRecognizer {
+    classifier: CropClassifier,
+    tracks: AttributeTracks<VehicleScores>,
+}
+
+impl VehicleRecognizer {
+    /// 加载车辆属性模型
+    ///
+    /// # 参数
+    /// - `model_path`: ONNX 模型路径, 同名 .txt 存在时作为标签列表
+    /// - `interval`: 同一轨迹的重新识别间隔
+    pub fn new(model_path: &str, interval: Duration) -> Result<Self> {
+        Ok(Self {
+            classifier: CropClassifier::new(model_path, (224, 224), &DEFAULT_LABELS)?,
+            tracks: AttributeTracks::new(interval),
+        })
+    }
+
+    pub fn labels(&self) -> &[String] {
+        self.classifier.labels()
+    }
+
+    /// 按轨迹更新车辆属性 (bbox.class_id 为轨迹ID), 返回与 `bboxes` 一一对应的结果
+    ///
+    /// `classes` 为各框的检测类别 (见 [`source_classes`]), 只识别 [`VEHICLE_CLASSES`]
+    pub fn update(
+        &mut self,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        bboxes: &[BBox],
+        classes: &[Option<u32>],
+        now: Instant,
+    ) -> Vec<Option<VehicleInfo>> {
+        let vehicles: Vec<usize> = (0..bboxes.len())
+            .filter(|&i| {
+                classes
+                    .get(i)
+                    .copied()
+                    .flatten()
+                    .is_some_and(|c| VEHICLE_CLASSES.contains(&c))
+            })
+            .collect();
+        let ids: Vec<u32> = vehicles.iter().map(|&i| bboxes[i].class_id).collect();
+        for k in self.tracks.due(&ids, now) {
+            match self
+                .classifier
+                .run(rgba, width, height, &bboxes[vehicles[k]])
+            {
+                Ok(logits) => {
+                    let scores = VehicleScores::from_logits(self.classifier.labels(), &logits);
+                    self.tracks.record(ids[k], scores, now);
+                }
+                Err(e) => {
+                    eprintln!("❌ 车辆属性识别失败: {}", e);
+                    break;
+                }
+            }
+        }
+        let mut result = vec![None; bboxes.len()];
+        for (i, scores) in vehicles.into_iter().zip(self.tracks.scores(&ids, now)) {
+            result[i] = scores.map(|s| s.info());
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bbox(x1: f32, y1: f32, x2: f32, y2: f32, class_id: u32) -> BBox {
+        BBox {
+            x1,
+            y1,
+            x2,
+            y2,
+            confidence: 0.9,
+            class_id,
+        }
+    }
+
+    /// 车型与颜色两个头分别 softmax, 自定义标签中的未知项被忽略, 概率过低时无法判断
+    #[test]
+    fn test_grouped_softmax() {
+        let labels: Vec<String> = DEFAULT_LABELS.iter().map(|l| l.to_string()).collect();
+        let mut logits = vec![0.0; DEFAULT_LABELS.len()];
+        logits[1] = 4.0; // type_suv
+        logits[6 + 1] = 3.0; // color_white
+        let scores = VehicleScores::from_logits(&labels, &logits);
+        assert!((scores.types.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+        assert!((scores.colors.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+        let info = scores.info();
+        assert_eq!(info.vehicle_type, Some(VehicleType::Suv));
+        assert_eq!(info.color, Some(Color::White));
+        assert_eq!(info.short_label(), "white suv");
+        assert_eq!(info.describe(), "白色SUV");
+
+        // 三种颜色持平 (1/3 < 0.4), 只判断车型
+        let custom: Vec<String> = [
+            "type_bus",
+            "type_tank",
+            "color_red",
+            "color_blue",
+            "color_gray",
+        ]
+        .map(String::from)
+        .to_vec();
+        let info = VehicleScores::from_logits(&custom, &[1.0, 9.0, 0.0, 0.0, 0.0]).info();
+        assert_eq!(info.vehicle_type, Some(VehicleType::Bus));
+        assert_eq!(info.color, None);
+        assert_eq!(info.describe(), "客车");
+    }
+
+    /// 跟踪框按 IoU 找回检测类别, 只有车辆类别的框参与识别
+    #[test]
+    fn test_source_classes() {
+        let detections = [
+            bbox(0.0, 0.0, 10.0, 10.0, 0),
+            bbox(100.0, 100.0, 200.0, 160.0, 2),
+        ];
+        // 跟踪框 (class_id 为轨迹ID)
+        let tracked = [
+            bbox(1.0, 0.0, 11.0, 10.0, 41),
+            bbox(102.0, 101.0, 201.0, 160.0, 42),
+            bbox(500.0, 500.0, 520.0, 520.0, 43),
+        ];
+        assert_eq!(
+            source_classes(&tracked, &detections, true),
+            vec![Some(0), Some(2), None]
+        );
+        assert_eq!(
+            source_classes(&detections, &detections, false),
+            vec![Some(0), Some(2)]
+        );
+    }
+}
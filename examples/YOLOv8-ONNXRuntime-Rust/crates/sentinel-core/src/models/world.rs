@@ -0,0 +1,541 @@
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+// YOLO-World 开放词汇检测
+// 类别由文本决定: 各类别名经 CLIP 文本编码器得到嵌入, 作为模型的第二个输入
+// (txt_feats [1, nc, D]), 检测头按区域特征与类别嵌入的相似度打分, 输出与 YOLOv8 相同的 [N, 4+nc, anchors]
+//
+// - 运行时替换类别词表 (set_vocabulary): 只重新生成文本嵌入, 不重建检测会话
+// - 文本嵌入按类别名缓存, 词表增删时只编码新出现的类别
+// - txt_feats 的类别维为静态时 (如按 80 类导出), 词表之外的位置补零, 解码时只取词表内的类别
+// - 导出时已固化类别 (只有图像输入) 的模型没有文本输入, 按 YOLOv8 加载
+//
+// 配置文件 (open_vocabulary.json), 不存在时文本编码器取模型同目录的 clip/, 词表为 ["person"]:
+// { "text_encoder": "models/clip", "classes": ["person", "backpack", "red car"] }
+// 完整模型 (YOLOWorld) 需要 ort 功能, 词表与文本嵌入的整理属于核心库
+
+use std::fs;
+use std::sync::OnceLock;
+
+use anyhow::{bail, Result};
+use ndarray::{s, Array, ArrayD};
+use serde::{Deserialize, Serialize};
+
+use crate::models::yolov8::OutputLayout;
+
+#[cfg(feature = "ort")]
+use std::collections::HashMap;
+
+#[cfg(feature = "ort")]
+use image::{DynamicImage, GenericImageView};
+#[cfg(feature = "ort")]
+use ndarray::IxDyn;
+#[cfg(feature = "ort")]
+use ort::session::Session;
+#[cfg(feature = "ort")]
+use ort::tensor::TensorElementType;
+#[cfg(feature = "ort")]
+use ort::value::{Value, ValueType};
+
+#[cfg(feature = "ort")]
+use crate::models::{ClipTextEncoder, PreprocessSpec, YOLOv8Config, YOLOv8Postprocessor};
+#[cfg(feature = "ort")]
+use crate::ort_backend::session_builder;
+#[cfg(feature = "ort")]
+use crate::{DetectionResult, DetectorError, OrtBackend, YOLOTask};
+
+/// 默认配置文件路径
+pub const WORLD_CONFIG_PATH: &str = "open_vocabulary.json";
+
+/// 类别颜色 (按词表下标循环, 与 YOLOv8 的调色板一致)
+const PALETTE: [(u8, u8, u8); 12] = [
+    (255, 0, 0),
+    (0, 255, 0),
+    (0, 0, 255),
+    (255, 255, 0),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 128, 0),
+    (255, 0, 128),
+    (128, 255, 0),
+    (0, 128, 255),
+    (255, 255, 255),
+    (128, 0, 255),
+];
+
+/// 进程内的开放词汇配置, 未安装时为 None
+static CONFIG: OnceLock<WorldConfig> = OnceLock::new();
+
+/// 开放词汇检测配置 (open_vocabulary.json)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldConfig {
+    pub text_encoder: String, // CLIP 文本编码器目录 (textual.onnx/vocab.json/merges.txt), 空为模型同目录的 clip/
+    pub classes: Vec<String>, // 初始类别词表
+}
+
+impl Default for WorldConfig {
+    fn default() -> Self {
+        Self {
+            text_encoder: String::new(),
+            classes: vec!["person".to_string()],
+        }
+    }
+}
+
+impl WorldConfig {
+    /// 从文件加载配置, 文件不存在或解析失败时为 None (使用默认值)
+    pub fn load(path: &str) -> Option<Self> {
+        let json = fs::read_to_string(path).ok()?;
+        match serde_json::from_str::<Self>(&json) {
+            Ok(config) => {
+                println!(
+                    "✅ 开放词汇配置已从 {} 加载 ({} 个类别)",
+                    path,
+                    config.classes.len()
+                );
+                Some(config)
+            }
+            Err(e) => {
+                eprintln!("⚠️  开放词汇配置解析失败: {}, 不启用", e);
+                None
+            }
+        }
+    }
+
+    /// 文本编码器目录: 未配置时取模型同目录的 clip/
+    pub fn text_encoder_dir(&self, model_path: &str) -> String {
+        if !self.text_encoder.is_empty() {
+            return self.text_encoder.clone();
+        }
+        let dir = std::path::Path::new(model_path)
+            .parent()
+            .unwrap_or_else(|| std::path::Path::new(""));
+        dir.join("clip").to_string_lossy().into_owned()
+    }
+}
+
+/// 安装全局配置 (只能安装一次, 之后加载的 YOLO-World 模型按它初始化)
+pub fn install(config: WorldConfig) {
+    let _ = CONFIG.set(config);
+}
+
+/// 已安装的配置, 未安装时为 None
+pub fn config() -> Option<&'static WorldConfig> {
+    CONFIG.get()
+}
+
+/// 配置中的初始类别词表 (界面清空词表时恢复到它)
+pub fn configured_classes() -> Vec<String> {
+    config().cloned().unwrap_or_default().classes
+}
+
+/// 解析逗号分隔的类别列表, 见 [`normalize_classes`]
+pub fn parse_classes(text: &str) -> Vec<String> {
+    normalize_classes(text.split(','))
+}
+
+/// 整理类别词表: 去掉首尾空白与空项, 重复的类别 (不分大小写) 只保留第一个
+pub fn normalize_classes<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut classes: Vec<String> = Vec::new();
+    for name in names.into_iter().map(str::trim).filter(|n| !n.is_empty()) {
+        if !classes.iter().any(|c| c.eq_ignore_ascii_case(name)) {
+            classes.push(name.to_string());
+        }
+    }
+    classes
+}
+
+/// 类别词表: 名称与显示颜色, 下标即 class_id
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Vocabulary {
+    pub names: Vec<String>,
+    pub colors: Vec<(u8, u8, u8)>,
+}
+
+impl Vocabulary {
+    pub fn new(names: Vec<String>) -> Self {
+        let colors = (0..names.len())
+            .map(|i| PALETTE[i % PALETTE.len()])
+            .collect();
+        Self { names, colors }
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// 类别名, 超出词表时为 None
+    pub fn name(&self, class_id: u32) -> Option<&str> {
+        self.names.get(class_id as usize).map(String::as_str)
+    }
+
+    /// 类别颜色, 超出词表时为 None
+    pub fn color(&self, class_id: u32) -> Option<(u8, u8, u8)> {
+        self.colors.get(class_id as usize).copied()
+    }
+}
+
+/// 各类别的文本嵌入 → txt_feats 张量 [1, slots, D]
+///
+/// 每个嵌入按 L2 归一化 (与 YOLO-World 训练时一致); `slots` 为模型的静态类别数,
+/// 词表之外的位置补零, None (动态轴) 时等于词表大小
+pub fn text_features(embeddings: &[&[f32]], slots: Option<usize>) -> Result<ArrayD<f32>> {
+    let Some(dim) = embeddings.first().map(|e| e.len()) else {
+        bail!("类别词表为空");
+    };
+    let slots = slots.unwrap_or(embeddings.len());
+    if embeddings.len() > slots {
+        bail!(
+            "类别数 {} 超过模型导出时的类别数 {}",
+            embeddings.len(),
+            slots
+        );
+    }
+    let mut feats = Array::zeros((1, slots, dim));
+    for (i, embedding) in embeddings.iter().enumerate() {
+        if embedding.len() != dim {
+            bail!("文本嵌入维度不一致: {} 与 {}", embedding.len(), dim);
+        }
+        let norm = embedding
+            .iter()
+            .map(|v| v * v)
+            .sum::<f32>()
+            .sqrt()
+            .max(1e-12);
+        for (d, v) in embedding.iter().enumerate() {
+            feats[[0, i, d]] = v / norm;
+        }
+    }
+    Ok(feats.into_dyn())
+}
+
+/// 检测头输出只保留词表内的类别: [N, 4+slots, anchors] (或转置导出) → [N, 4+nc, anchors]
+pub fn select_classes(preds: &ArrayD<f32>, slots: usize, nc: usize) -> ArrayD<f32> {
+    let shape: Vec<i64> = preds.shape().iter().map(|&d| d as i64).collect();
+    let layout = OutputLayout::from_shape(&shape, 4 + slots).unwrap_or(OutputLayout::ChannelsFirst);
+    let preds = layout.channels_first(preds);
+    let nc = nc.min(preds.shape()[1].saturating_sub(4));
+    preds.slice(s![.., ..4 + nc, ..]).to_owned().into_dyn()
+}
+
+/// YOLO-World 开放词汇检测模型
+#[cfg(feature = "ort")]
+pub struct YOLOWorld {
+    session: Session,
+    text_encoder: ClipTextEncoder,
+    // 类别名 → 文本嵌入 (未归一化), 替换词表时复用
+    embeddings: HashMap<String, Vec<f32>>,
+    vocabulary: Vocabulary,
+    txt_feats: ArrayD<f32>,
+    slots: Option<usize>, // txt_feats 的静态类别数, 动态轴为 None
+    text_first: bool,     // 文本输入排在图像输入之前
+    width: u32,
+    height: u32,
+    conf: f32,
+    iou: f32,
+}
+
+#[cfg(feature = "ort")]
+impl YOLOWorld {
+    /// 加载检测模型与文本编码器, 按配置 (或默认) 的词表生成初始文本嵌入
+    pub fn new(args: crate::Args) -> Result<Self> {
+        let mut builder = session_builder()?;
+        if args.cuda || args.trt {
+            // 文本输入的类别维随词表变化, 不构建 TensorRT 引擎
+            let (_, provider) = OrtBackend::set_ep_cuda(args.device_id);
+            builder = builder.with_execution_providers([provider])?;
+        }
+        let session = builder.commit_from_file(&args.model)?;
+
+        let (mut image, mut text) = (None, None);
+        for (i, input) in session.inputs.iter().enumerate() {
+            let ValueType::Tensor { ty, shape, .. } = &input.input_type else {
+                continue;
+            };
+            if *ty != TensorElementType::Float32 {
+                return Err(DetectorError::ModelLoad(format!(
+                    "输入 `{}` 类型为 {:?}, YOLO-World 只支持 FP32 输入",
+                    input.name, ty
+                ))
+                .into());
+            }
+            match shape.len() {
+                4 => image = Some((i, shape.to_vec())),
+                3 => text = Some((i, shape.to_vec())),
+                _ => {}
+            }
+        }
+        let Some((image_index, image_shape)) = image else {
+            return Err(DetectorError::ModelLoad("缺少 [N, 3, H, W] 图像输入".into()).into());
+        };
+        let Some((text_index, text_shape)) = text else {
+            return Err(DetectorError::ModelLoad(
+                "缺少 [1, nc, D] 文本嵌入输入 (导出时已固化类别的模型请按 YOLOv8 加载)".into(),
+            )
+            .into());
+        };
+        let height = match image_shape[2] {
+            h if h > 0 => h as u32,
+            _ => args.height.unwrap_or(640),
+        };
+        let width = match image_shape[3] {
+            w if w > 0 => w as u32,
+            _ => args.width.unwrap_or(640),
+        };
+        let slots = (text_shape[1] > 0).then_some(text_shape[1] as usize);
+
+        let config = config().cloned().unwrap_or_default();
+        let encoder_dir = config.text_encoder_dir(&args.model);
+        let text_encoder = ClipTextEncoder::new(&encoder_dir).map_err(|e| {
+            DetectorError::ModelLoad(format!("文本编码器 {} 加载失败: {}", encoder_dir, e))
+        })?;
+
+        let mut model = Self {
+            session,
+            text_encoder,
+            embeddings: HashMap::new(),
+            vocabulary: Vocabulary::default(),
+            txt_feats: ArrayD::zeros(IxDyn(&[1, 0, 0])),
+            slots,
+            text_first: text_index < image_index,
+            width,
+            height,
+            conf: args.conf,
+            iou: args.iou,
+        };
+        model.set_vocabulary(&config.classes)?;
+        println!(
+            "🌍 YOLO-World: 文本编码器 {}, 词表 {:?}{}",
+            encoder_dir,
+            model.vocabulary.names,
+            match slots {
+                Some(n) => format!(" (最多 {} 类)", n),
+                None => String::new(),
+            }
+        );
+        Ok(model)
+    }
+
+    /// 替换类别词表: 编码新出现的类别, 重建 txt_feats; 失败时保留原词表
+    pub fn set_vocabulary(&mut self, classes: &[String]) -> Result<()> {
+        let names = normalize_classes(classes.iter().map(String::as_str));
+        if names.is_empty() {
+            bail!("类别词表为空");
+        }
+        for name in &names {
+            if !self.embeddings.contains_key(name) {
+                let embedding = self.text_encoder.encode(name)?;
+                let values = embedding.data().iter().copied().collect();
+                self.embeddings.insert(name.clone(), values);
+            }
+        }
+        let embeddings: Vec<&[f32]> = names
+            .iter()
+            .map(|name| self.embeddings[name].as_slice())
+            .collect();
+        self.txt_feats = text_features(&embeddings, self.slots)?;
+        // 只保留当前词表的嵌入, 避免反复修改词表时缓存无限增长
+        self.embeddings.retain(|name, _| names.contains(name));
+        self.vocabulary = Vocabulary::new(names);
+        Ok(())
+    }
+
+    pub fn vocabulary(&self) -> &Vocabulary {
+        &self.vocabulary
+    }
+
+    /// 与 YOLOv8 相同的左上角对齐 letterbox (144 灰色填充, RGB, /255)
+    fn preprocess_images(&self, images: &[DynamicImage]) -> Array<f32, IxDyn> {
+        let spec = PreprocessSpec::default();
+        let (width, height) = (self.width, self.height);
+        let mut ys = Array::zeros((images.len(), 3, height as usize, width as usize)).into_dyn();
+        spec.fill_tensor(&mut ys);
+        for (idx, img) in images.iter().enumerate() {
+            let (w0, h0) = img.dimensions();
+            let r = (width as f32 / w0 as f32).min(height as f32 / h0 as f32);
+            let (w, h) = (
+                ((w0 as f32 * r).round() as u32).clamp(1, width),
+                ((h0 as f32 * r).round() as u32).clamp(1, height),
+            );
+            let resized = img
+                .resize_exact(w, h, image::imageops::FilterType::Triangle)
+                .to_rgb8();
+            for (x, y, rgb) in resized.enumerate_pixels() {
+                spec.write_pixel(&mut ys, idx, y as usize, x as usize, rgb.0);
+            }
+        }
+        ys
+    }
+}
+
+#[cfg(feature = "ort")]
+impl super::Model for YOLOWorld {
+    fn preprocess(&mut self, images: &[DynamicImage]) -> Result<Vec<Array<f32, IxDyn>>> {
+        Ok(vec![self.preprocess_images(images)])
+    }
+
+    fn run(&mut self, xs: Vec<Array<f32, IxDyn>>, profile: bool) -> Result<Vec<Array<f32, IxDyn>>> {
+        let t = std::time::Instant::now();
+        let images = xs
+            .into_iter()
+            .next()
+            .ok_or_else(|| DetectorError::Process("缺少图像输入".into()))?;
+        let images = Value::from_array(images)?;
+        let txt_feats = Value::from_array(self.txt_feats.clone())?;
+        let outputs = if self.text_first {
+            self.session.run(ort::inputs![txt_feats, images])?
+        } else {
+            self.session.run(ort::inputs![images, txt_feats])?
+        };
+        if profile {
+            println!("[ORT Inference]: {:?}", t.elapsed());
+        }
+        let (_, value) = outputs
+            .iter()
+            .next()
+            .ok_or_else(|| DetectorError::Process("YOLO-World 无输出".into()))?;
+        let (shape, data) = value.try_extract_tensor::<f32>()?;
+        let dims: Vec<usize> = shape.iter().map(|&d| d as usize).collect();
+        let y = Array::from_shape_vec(IxDyn(&dims), data.to_vec())
+            .map_err(|e| DetectorError::ShapeMismatch(format!("输出 0: {}", e)))?;
+        Ok(vec![y])
+    }
+
+    fn postprocess(
+        &self,
+        xs: Vec<Array<f32, IxDyn>>,
+        xs0: &[DynamicImage],
+    ) -> Result<Vec<DetectionResult>> {
+        let Some(preds) = xs.first() else {
+            bail!("YOLO-World 无输出");
+        };
+        let nc = self.vocabulary.len();
+        let preds = select_classes(preds, self.slots.unwrap_or(nc), nc);
+        let mut config = YOLOv8Config::new(
+            YOLOTask::Detect,
+            nc,
+            self.width as usize,
+            self.height as usize,
+            self.conf,
+            self.iou,
+        );
+        config.layout = Some(OutputLayout::ChannelsFirst);
+        YOLOv8Postprocessor::new(config).postprocess(vec![preds], xs0)
+    }
+
+    fn engine_mut(&mut self) -> Option<&mut OrtBackend> {
+        None
+    }
+
+    fn summary(&self) {
+        println!(
+            "\nSummary:\n\
+            > Model: YOLO-World (open vocabulary)\n\
+            > Height: {}, Width: {}, txt_feats: {}\n\
+            > Classes: {:?}\n\
+            > conf: {}, iou: {}\n",
+            self.height,
+            self.width,
+            match self.slots {
+                Some(n) => format!("[1, {}, D] (Const)", n),
+                None => "[1, nc, D] (Dynamic)".to_string(),
+            },
+            self.vocabulary.names,
+            self.conf,
+            self.iou,
+        );
+    }
+
+    fn supports_task(&self, task: YOLOTask) -> bool {
+        task == YOLOTask::Detect
+    }
+
+    fn set_conf(&mut self, val: f32) {
+        self.conf = val;
+    }
+
+    fn conf(&self) -> f32 {
+        self.conf
+    }
+
+    fn set_iou(&mut self, val: f32) {
+        self.iou = val;
+    }
+
+    fn iou(&self) -> f32 {
+        self.iou
+    }
+
+    fn vocabulary(&self) -> Option<&Vocabulary> {
+        Some(&self.vocabulary)
+    }
+
+    fn set_vocabulary(&mut self, classes: &[String]) -> Result<()> {
+        YOLOWorld::set_vocabulary(self, classes)
+    }
+}
+
+/// 模型是否带文本嵌入输入 (3 维的第二个输入); 读取失败时按否处理
+pub fn has_text_input(path: &str) -> bool {
+    crate::models::ModelInfo::load(path)
+        .map(|info| info.inputs.iter().skip(1).any(|i| i.shape.len() == 3))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 逗号分隔, 去空白/空项, 重复类别只保留第一个
+    #[test]
+    fn test_parse_classes() {
+        assert_eq!(
+            parse_classes(" person, red car,,Person ,dog "),
+            vec!["person", "red car", "dog"]
+        );
+        assert!(parse_classes(" , ").is_empty());
+
+        let vocab = Vocabulary::new(parse_classes("a,b"));
+        assert_eq!(vocab.name(1), Some("b"));
+        assert_eq!(vocab.color(0), Some(PALETTE[0]));
+        assert_eq!(vocab.name(2), None);
+    }
+
+    /// 嵌入逐个 L2 归一化, 静态类别数时补零, 超出时报错
+    #[test]
+    fn test_text_features() {
+        let a = [3.0, 4.0];
+        let b = [0.0, 2.0];
+        let feats = text_features(&[&a, &b], None).unwrap();
+        assert_eq!(feats.shape(), &[1, 2, 2]);
+        assert!((feats[[0, 0, 0]] - 0.6).abs() < 1e-6);
+        assert!((feats[[0, 1, 1]] - 1.0).abs() < 1e-6);
+
+        let padded = text_features(&[&a], Some(3)).unwrap();
+        assert_eq!(padded.shape(), &[1, 3, 2]);
+        assert_eq!(padded[[0, 2, 1]], 0.0);
+
+        assert!(text_features(&[&a, &b], Some(1)).is_err());
+        assert!(text_features(&[], None).is_err());
+        assert!(text_features(&[&a, &[1.0]], None).is_err());
+    }
+
+    /// 只保留词表内的类别通道, 转置导出先转为通道在前
+    #[test]
+    fn test_select_classes() {
+        // [1, 4+3, 2]: 第 c 个通道的值为 c
+        let preds = Array::from_shape_fn((1, 7, 2), |(_, c, _)| c as f32).into_dyn();
+        let y = select_classes(&preds, 3, 2);
+        assert_eq!(y.shape(), &[1, 6, 2]);
+        assert_eq!(y[[0, 5, 1]], 5.0);
+
+        let transposed = Array::from_shape_fn((1, 2, 7), |(_, _, c)| c as f32).into_dyn();
+        let y = select_classes(&transposed, 3, 1);
+        assert_eq!(y.shape(), &[1, 5, 2]);
+        assert_eq!(y[[0, 4, 0]], 4.0);
+    }
+}
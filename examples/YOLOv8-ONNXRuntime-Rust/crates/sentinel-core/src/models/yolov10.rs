@@ -2,16 +2,22 @@
 //
 // YOLOv10 模型实现 (NMS-Free端到端检测)
 // 特性: 无需NMS后处理, 直接输出最终检测框
+// 完整模型需要 ort 功能, 后处理器 (YOLOv10Postprocessor) 属于核心库
 
 use anyhow::Result;
-use image::{DynamicImage, GenericImageView, ImageBuffer};
+#[cfg(feature = "ort")]
+use image::ImageBuffer;
+use image::{DynamicImage, GenericImageView};
 use ndarray::{s, Array, IxDyn};
 
-use crate::{
-    Batch, Bbox, DetectionResult, OrtBackend, OrtConfig, OrtEP, YOLOTask,
-};
+#[cfg(feature = "ort")]
+use crate::models::{model_info, ModelType};
+use crate::{Bbox, DetectionResult};
+#[cfg(feature = "ort")]
+use crate::{Batch, DetectorError, OrtBackend, OrtConfig, OrtEP, YOLOTask};
 
 /// YOLOv10 模型结构
+#[cfg(feature = "ort")]
 pub struct YOLOv10 {
     engine: OrtBackend,
     nc: u32,
@@ -25,11 +31,12 @@ pub struct YOLOv10 {
     profile: bool,
 }
 
+#[cfg(feature = "ort")]
 impl YOLOv10 {
     /// 从配置创建 YOLOv10 模型
     pub fn new(config: crate::Args) -> Result<Self> {
         // execution provider
-        let ep = if config.trt {
+        let ep = if config.trt || config.dla_core.is_some() {
             OrtEP::Trt(config.device_id)
         } else if config.cuda {
             OrtEP::CUDA(config.device_id)
@@ -51,15 +58,24 @@ impl YOLOv10 {
             f: config.model,
             task: Some(YOLOTask::Detect),  // YOLOv10 only supports detection
             trt_fp16: config.fp16,
+            dla_core: config.dla_core,
             image_size: (config.height, config.width),
         };
         let engine = OrtBackend::build(ort_args)?;
+        model_info::check_layout(
+            ModelType::YOLOv10,
+            &engine.input_shapes()[0],
+            &engine.output_shapes(),
+        )?;
 
         // get batch, height, width, nc
         let (batch, height, width) = (engine.batch(), engine.height(), engine.width());
-        let nc = engine.nc().or(config.nc).unwrap_or_else(|| {
-            panic!("Failed to get num_classes, make it explicit with `--nc`");
-        });
+        let nc = engine.nc().or(config.nc).ok_or_else(|| {
+            DetectorError::ModelLoad(
+                "Failed to get num_classes (no `names` metadata), make it explicit with `--nc`"
+                    .into(),
+            )
+        })?;
 
         // class names
         let names = engine.names().unwrap_or(vec!["Unknown".to_string()]);
@@ -89,11 +105,13 @@ impl YOLOv10 {
     }
 }
 
+#[cfg(feature = "ort")]
 impl crate::models::Model for YOLOv10 {
     /// 预处理: 图像缩放与归一化 (与YOLOv8相同)
     fn preprocess(&mut self, xs: &[DynamicImage]) -> Result<Vec<Array<f32, IxDyn>>> {
-        let mut ys = Array::ones((xs.len(), 3, self.height as usize, self.width as usize)).into_dyn();
-        ys.fill(144.0 / 255.0);  // YOLOv8填充值
+        let spec = crate::models::PreprocessSpec::default().with_channels(self.engine.channels());
+        let mut ys = Array::ones((xs.len(), spec.channels, self.height as usize, self.width as usize)).into_dyn();
+        spec.fill_tensor(&mut ys);  // YOLOv8填充值
 
         for (idx, x) in xs.iter().enumerate() {
             let img = x.resize_exact(
@@ -102,13 +120,10 @@ impl crate::models::Model for YOLOv10 {
                 image::imageops::FilterType::Triangle,
             );
             let img: ImageBuffer<image::Rgb<u8>, Vec<u8>> = ImageBuffer::from_raw(self.width, self.height, img.to_rgb8().into_raw())
-                .expect("Failed to create image buffer");
+                .ok_or_else(|| DetectorError::Process("Failed to create image buffer".into()))?;
 
             for (x, y, pixel) in img.enumerate_pixels() {
-                let [r, g, b] = pixel.0;
-                ys[[idx, 0, y as usize, x as usize]] = r as f32 / 255.0;
-                ys[[idx, 1, y as usize, x as usize]] = g as f32 / 255.0;
-                ys[[idx, 2, y as usize, x as usize]] = b as f32 / 255.0;
+                spec.write_pixel(&mut ys, idx, y as usize, x as usize, pixel.0);
             }
         }
 
@@ -133,6 +148,79 @@ impl crate::models::Model for YOLOv10 {
     /// - YOLOv8: 输出 [batch, num_boxes, 4+num_classes], 需要NMS
     /// - YOLOv10: 输出 [batch, num_boxes, 6], 已经过模型内部NMS
     fn postprocess(&self, xs: Vec<Array<f32, IxDyn>>, xs0: &[DynamicImage]) -> Result<Vec<DetectionResult>> {
+        self.postprocessor().postprocess(xs, xs0)
+    }
+
+    fn engine_mut(&mut self) -> Option<&mut OrtBackend> {
+        Some(&mut self.engine)
+    }
+
+    fn summary(&self) {
+        println!("\n模型摘要:");
+        println!("┌─────────────────────────────────────────┐");
+        println!("│ Model: YOLOv10 (NMS-Free)               │");
+        println!("│ Task: Object Detection                  │");
+        println!("├─────────────────────────────────────────┤");
+        println!("│ Input: [{}, 3, {}, {}]           │", self.batch, self.height, self.width);
+        println!("│ Classes: {}                              │", self.nc);
+        println!("│ Confidence: {}                         │", self.conf);
+        println!("│ NMS: Not Required (End-to-End)         │");
+        println!("└─────────────────────────────────────────┘\n");
+    }
+
+    fn supports_task(&self, task: YOLOTask) -> bool {
+        matches!(task, YOLOTask::Detect)
+    }
+
+    fn set_conf(&mut self, val: f32) {
+        self.conf = val;
+    }
+
+    fn conf(&self) -> f32 {
+        self.conf
+    }
+
+    fn set_iou(&mut self, val: f32) {
+        self.iou = val;
+    }
+
+    fn iou(&self) -> f32 {
+        self.iou
+    }
+
+    fn preprocess_spec(&self) -> crate::models::PreprocessSpec {
+        crate::models::PreprocessSpec::default().with_channels(self.engine.channels())
+    }
+}
+
+#[cfg(feature = "ort")]
+impl YOLOv10 {
+    /// 后处理器 (与 ONNX 引擎解耦, 可单独用 golden 张量测试)
+    pub fn postprocessor(&self) -> YOLOv10Postprocessor {
+        YOLOv10Postprocessor {
+            nc: self.nc,
+            conf: self.conf,
+            width: self.width,
+            height: self.height,
+            profile: self.profile,
+        }
+    }
+}
+
+/// YOLOv10 后处理器 (NMS-Free)
+///
+/// YOLOv10输出格式: [batch, num_boxes, 6]
+/// 其中 6 = [x1, y1, x2, y2, confidence, class_id]
+pub struct YOLOv10Postprocessor {
+    pub nc: u32,
+    pub conf: f32,
+    pub width: u32,
+    pub height: u32,
+    pub profile: bool,
+}
+
+impl YOLOv10Postprocessor {
+    pub fn postprocess(&self, xs: Vec<Array<f32, IxDyn>>, xs0: &[DynamicImage]) -> Result<Vec<DetectionResult>> {
         if self.profile {
             println!("\n[YOLOv10 后处理 - NMS-Free]");
         }
@@ -207,41 +295,4 @@ impl crate::models::Model for YOLOv10 {
 
         Ok(ys)
     }
-
-    fn engine_mut(&mut self) -> &mut OrtBackend {
-        &mut self.engine
-    }
-
-    fn summary(&self) {
-        println!("\n模型摘要:");
-        println!("┌─────────────────────────────────────────┐");
-        println!("│ Model: YOLOv10 (NMS-Free)               │");
-        println!("│ Task: Object Detection                  │");
-        println!("├─────────────────────────────────────────┤");
-        println!("│ Input: [{}, 3, {}, {}]           │", self.batch, self.height, self.width);
-        println!("│ Classes: {}                              │", self.nc);
-        println!("│ Confidence: {}                         │", self.conf);
-        println!("│ NMS: Not Required (End-to-End)         │");
-        println!("└─────────────────────────────────────────┘\n");
-    }
-
-    fn supports_task(&self, task: YOLOTask) -> bool {
-        matches!(task, YOLOTask::Detect)
-    }
-
-    fn set_conf(&mut self, val: f32) {
-        self.conf = val;
-    }
-
-    fn conf(&self) -> f32 {
-        self.conf
-    }
-
-    fn set_iou(&mut self, val: f32) {
-        self.iou = val;
-    }
-
-    fn iou(&self) -> f32 {
-        self.iou
-    }
 }
@@ -47,8 +47,8 @@ impl crate::models::Model for YOLOv11 {
         self.inner.postprocess(xs, xs0)
     }
 
-    fn engine_mut(&mut self) -> &mut crate::OrtBackend {
-        self.inner.engine_mut()
+    fn engine_mut(&mut self) -> Option<&mut crate::OrtBackend> {
+        Some(self.inner.engine_mut())
     }
 
     fn summary(&self) {
@@ -79,4 +79,12 @@ impl crate::models::Model for YOLOv11 {
     fn iou(&self) -> f32 {
         self.inner.iou()
     }
+
+    fn preprocess_spec(&self) -> crate::models::PreprocessSpec {
+        crate::models::Model::preprocess_spec(&self.inner)
+    }
+
+    fn set_input_size(&mut self, width: u32, height: u32) -> bool {
+        self.inner.set_input_size(width, height)
+    }
 }
@@ -0,0 +1,977 @@
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+// YOLOv8 完整模型实现
+// 包含: 模型加载、预处理、推理、后处理
+// 完整模型 (YOLOv8) 需要 ort 功能, 后处理器 (YOLOv8Postprocessor) 属于核心库
+
+#[cfg(feature = "ort")]
+use std::sync::OnceLock;
+
+use anyhow::Result;
+#[cfg(feature = "ort")]
+use image::GenericImageView;
+use image::{DynamicImage, ImageBuffer};
+use ndarray::{s, Array, ArrayD, ArrayViewD, Axis, IxDyn};
+use rayon::prelude::*;
+
+#[cfg(feature = "ort")]
+use crate::models::{model_info, ModelType, PreprocessSpec};
+use crate::{
+    non_max_suppression, Bbox, DetectionResult, DetectorError, Embedding, Point2, YOLOTask,
+};
+#[cfg(feature = "ort")]
+use crate::{Batch, OrtBackend, OrtConfig, OrtEP};
+
+/// YOLOv8 完整模型结构
+#[cfg(feature = "ort")]
+pub struct YOLOv8 {
+    engine: OrtBackend,
+    nc: u32,
+    nk: u32,
+    nm: u32,
+    height: u32,
+    width: u32,
+    batch: u32,
+    task: YOLOTask,
+    conf: f32,
+    kconf: f32,
+    iou: f32,
+    names: Vec<String>,
+    color_palette: Vec<(u8, u8, u8)>,
+    profile: bool,
+    // 检测头输出布局, 首次推理时按形状与数值确认
+    layout: OnceLock<OutputLayout>,
+}
+
+#[cfg(feature = "ort")]
+impl YOLOv8 {
+    /// 从配置创建 YOLOv8 模型
+    pub fn new(config: crate::Args) -> Result<Self> {
+        // execution provider
+        let ep = if config.trt || config.dla_core.is_some() {
+            OrtEP::Trt(config.device_id)
+        } else if config.cuda {
+            OrtEP::CUDA(config.device_id)
+        } else {
+            OrtEP::CPU
+        };
+
+        // batch
+        let batch = Batch {
+            opt: config.batch,
+            min: config.batch_min,
+            max: config.batch_max,
+        };
+
+        // build ort engine
+        let ort_args = OrtConfig {
+            ep,
+            batch,
+            f: config.model,
+            task: config.task,
+            trt_fp16: config.fp16,
+            dla_core: config.dla_core,
+            image_size: (config.height, config.width),
+        };
+        let engine = OrtBackend::build(ort_args)?;
+        model_info::check_layout(
+            ModelType::YOLOv8,
+            &engine.input_shapes()[0],
+            &engine.output_shapes(),
+        )?;
+
+        //  get batch, height, width, tasks, nc, nk, nm
+        let (batch, height, width, task) = (
+            engine.batch(),
+            engine.height(),
+            engine.width(),
+            engine.task(),
+        );
+        let nc = engine.nc().or(config.nc).ok_or_else(|| {
+            DetectorError::ModelLoad(
+                "Failed to get num_classes (no `names` metadata), make it explicit with `--nc`"
+                    .into(),
+            )
+        })?;
+        let (nk, nm) = match task {
+            YOLOTask::Pose => {
+                let nk = engine.nk().or(config.nk).ok_or_else(|| {
+                    DetectorError::ModelLoad(
+                        "Failed to get num_keypoints, make it explicit with `--nk`".into(),
+                    )
+                })?;
+                (nk, 0)
+            }
+            YOLOTask::Segment => {
+                let nm = engine.nm().or(config.nm).ok_or_else(|| {
+                    DetectorError::ModelLoad(
+                        "Failed to get num_masks, make it explicit with `--nm`".into(),
+                    )
+                })?;
+                (0, nm)
+            }
+            _ => (0, 0),
+        };
+
+        // output layout: 按元数据形状预判, 首次推理时再按实际数值确认
+        if task != YOLOTask::Classify {
+            let shapes = engine.output_shapes();
+            let shape = &shapes[0];
+            let channels = head_channels(&task, nc as usize, nk as usize, nm as usize);
+            match OutputLayout::from_shape(shape, channels) {
+                Some(OutputLayout::ChannelsFirst) => {}
+                Some(OutputLayout::ChannelsLast) => println!(
+                    "📐 输出形状 {:?} 为转置导出 [N, anchors, {}], 后处理自动转置",
+                    shape, channels
+                ),
+                None => println!(
+                    "📐 输出形状 {:?} 无法确定布局 (期望通道数 {}), 首次推理时按数值判断",
+                    shape, channels
+                ),
+            }
+        }
+
+        // class names
+        let names = engine.names().unwrap_or(vec!["Unknown".to_string()]);
+
+        // color palette
+        let bright_colors = vec![
+            (255, 0, 0),     // 红色
+            (0, 255, 0),     // 绿色
+            (0, 0, 255),     // 蓝色
+            (255, 255, 0),   // 黄色
+            (255, 0, 255),   // 品红
+            (0, 255, 255),   // 青色
+            (255, 128, 0),   // 橙色
+            (255, 0, 128),   // 粉红
+            (128, 255, 0),   // 黄绿
+            (0, 128, 255),   // 天蓝
+            (255, 255, 255), // 白色
+            (128, 0, 255),   // 紫色
+        ];
+
+        let color_palette: Vec<_> = names
+            .iter()
+            .enumerate()
+            .map(|(i, _)| bright_colors[i % bright_colors.len()])
+            .collect();
+
+        Ok(Self {
+            engine,
+            names,
+            conf: config.conf,
+            kconf: config.kconf,
+            iou: config.iou,
+            color_palette,
+            profile: config.profile,
+            nc,
+            nk,
+            nm,
+            height,
+            width,
+            batch,
+            task,
+            layout: OnceLock::new(),
+        })
+    }
+
+    fn scale_wh(&self, w0: f32, h0: f32, w1: f32, h1: f32) -> (f32, f32, f32) {
+        let r = (w1 / w0).min(h1 / h0);
+        (r, (w0 * r).round(), (h0 * r).round())
+    }
+
+    pub fn preprocess(&mut self, xs: &Vec<DynamicImage>) -> Result<Array<f32, IxDyn>> {
+        let spec = PreprocessSpec::default().with_channels(self.engine.channels());
+        let mut ys = Array::ones((
+            xs.len(),
+            spec.channels,
+            self.height() as usize,
+            self.width() as usize,
+        ))
+        .into_dyn();
+        spec.fill_tensor(&mut ys);
+        for (idx, x) in xs.iter().enumerate() {
+            let img = match self.task() {
+                YOLOTask::Classify => x.resize_exact(
+                    self.width(),
+                    self.height(),
+                    image::imageops::FilterType::Triangle,
+                ),
+                _ => {
+                    let (w0, h0) = x.dimensions();
+                    let w0 = w0 as f32;
+                    let h0 = h0 as f32;
+                    let (_, w_new, h_new) =
+                        self.scale_wh(w0, h0, self.width() as f32, self.height() as f32);
+                    x.resize_exact(
+                        w_new as u32,
+                        h_new as u32,
+                        if let YOLOTask::Segment = self.task() {
+                            image::imageops::FilterType::CatmullRom
+                        } else {
+                            image::imageops::FilterType::Triangle
+                        },
+                    )
+                }
+            };
+
+            for (x, y, rgb) in img.pixels() {
+                let x = x as usize;
+                let y = y as usize;
+                let [r, g, b, _] = rgb.0;
+                spec.write_pixel(&mut ys, idx, y, x, [r, g, b]);
+            }
+        }
+
+        Ok(ys)
+    }
+
+    pub fn run(&mut self, xs: &Vec<DynamicImage>) -> Result<Vec<DetectionResult>> {
+        let t_pre = std::time::Instant::now();
+        let xs_ = self.preprocess(xs)?;
+        if self.profile {
+            println!("[Model Preprocess]: {:?}", t_pre.elapsed());
+        }
+
+        let t_run = std::time::Instant::now();
+        let ys = self.engine.run(xs_, self.profile)?;
+        if self.profile {
+            println!("[Model Inference]: {:?}", t_run.elapsed());
+        }
+
+        let t_post = std::time::Instant::now();
+        let ys = self.postprocess(ys, xs)?;
+        if self.profile {
+            println!("[Model Postprocess]: {:?}", t_post.elapsed());
+        }
+
+        Ok(ys)
+    }
+
+    pub fn postprocess(
+        &self,
+        xs: Vec<Array<f32, IxDyn>>,
+        xs0: &[DynamicImage],
+    ) -> Result<Vec<DetectionResult>> {
+        if self.task != YOLOTask::Classify && self.layout.get().is_none() {
+            if let Some(preds) = xs.first() {
+                let channels = head_channels(
+                    &self.task,
+                    self.nc as usize,
+                    self.nk as usize,
+                    self.nm as usize,
+                );
+                let layout = OutputLayout::detect(preds, channels, self.nc as usize);
+                if self.layout.set(layout).is_ok() {
+                    println!(
+                        "📐 检测输出布局: {:?} (形状 {:?}, 期望通道数 {})",
+                        layout,
+                        preds.shape(),
+                        channels
+                    );
+                }
+            }
+        }
+        YOLOv8Postprocessor::new(self.postprocess_config()).postprocess(xs, xs0)
+    }
+
+    /// 后处理参数 (解码逻辑在 [`YOLOv8Postprocessor`] 中, 可脱离 ONNX 单独测试)
+    pub fn postprocess_config(&self) -> YOLOv8Config {
+        YOLOv8Config {
+            task: self.task().clone(),
+            nc: self.nc() as usize,
+            nk: self.nk() as usize,
+            nm: self.nm() as usize,
+            conf: self.conf,
+            kconf: self.kconf,
+            iou: self.iou,
+            width: self.width() as usize,
+            height: self.height() as usize,
+            layout: self.layout.get().copied(),
+        }
+    }
+
+    pub fn summary(&self) {
+        println!(
+            "\nSummary:\n\
+            > Task: {:?}{}\n\
+            > EP: {:?} {}\n\
+            > Dtype: {:?}\n\
+            > Batch: {} ({}), Height: {} ({}), Width: {} ({})\n\
+            > nc: {} nk: {}, nm: {}, conf: {}, kconf: {}, iou: {}\n\
+            ",
+            self.task(),
+            match self.engine.author().zip(self.engine.version()) {
+                Some((author, ver)) => format!(" ({} {})", author, ver),
+                None => String::from(""),
+            },
+            self.engine.ep(),
+            if let OrtEP::CPU = self.engine.ep() {
+                ""
+            } else {
+                "(May still fall back to CPU)"
+            },
+            self.engine.dtype(),
+            self.batch(),
+            if self.engine.is_batch_dynamic() {
+                "Dynamic"
+            } else {
+                "Const"
+            },
+            self.height(),
+            if self.engine.is_height_dynamic() {
+                "Dynamic"
+            } else {
+                "Const"
+            },
+            self.width(),
+            if self.engine.is_width_dynamic() {
+                "Dynamic"
+            } else {
+                "Const"
+            },
+            self.nc(),
+            self.nk(),
+            self.nm(),
+            self.conf,
+            self.kconf,
+            self.iou,
+        );
+    }
+
+    pub fn engine(&self) -> &OrtBackend {
+        &self.engine
+    }
+
+    pub fn engine_mut(&mut self) -> &mut OrtBackend {
+        &mut self.engine
+    }
+
+    pub fn conf(&self) -> f32 {
+        self.conf
+    }
+
+    pub fn set_conf(&mut self, val: f32) {
+        self.conf = val;
+    }
+
+    pub fn conf_mut(&mut self) -> &mut f32 {
+        &mut self.conf
+    }
+
+    pub fn kconf(&self) -> f32 {
+        self.kconf
+    }
+
+    pub fn iou(&self) -> f32 {
+        self.iou
+    }
+
+    pub fn set_iou(&mut self, val: f32) {
+        self.iou = val;
+    }
+
+    pub fn task(&self) -> &YOLOTask {
+        &self.task
+    }
+
+    pub fn batch(&self) -> u32 {
+        self.batch
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// 动态轴模型就地切换输入尺寸, 见 [`super::Model::set_input_size`]
+    pub fn set_input_size(&mut self, width: u32, height: u32) -> bool {
+        if !self.engine.set_input_size(width, height) {
+            return false;
+        }
+        self.width = width;
+        self.height = height;
+        true
+    }
+
+    pub fn nc(&self) -> u32 {
+        self.nc
+    }
+
+    pub fn nk(&self) -> u32 {
+        self.nk
+    }
+
+    pub fn nm(&self) -> u32 {
+        self.nm
+    }
+
+    pub fn names(&self) -> &Vec<String> {
+        &self.names
+    }
+
+    pub fn color_palette(&self) -> &Vec<(u8, u8, u8)> {
+        &self.color_palette
+    }
+}
+
+// 实现统一的 Model trait
+#[cfg(feature = "ort")]
+impl super::Model for YOLOv8 {
+    fn preprocess(&mut self, images: &[DynamicImage]) -> Result<Vec<Array<f32, IxDyn>>> {
+        let batch = YOLOv8::preprocess(self, &images.to_vec())?;
+        Ok(vec![batch])
+    }
+
+    fn run(&mut self, xs: Vec<Array<f32, IxDyn>>, profile: bool) -> Result<Vec<Array<f32, IxDyn>>> {
+        self.engine.run(xs[0].clone(), profile)
+    }
+
+    fn postprocess(
+        &self,
+        xs: Vec<Array<f32, IxDyn>>,
+        xs0: &[DynamicImage],
+    ) -> Result<Vec<DetectionResult>> {
+        YOLOv8::postprocess(self, xs, xs0)
+    }
+
+    fn engine_mut(&mut self) -> Option<&mut OrtBackend> {
+        Some(&mut self.engine)
+    }
+
+    fn summary(&self) {
+        YOLOv8::summary(self)
+    }
+
+    fn supports_task(&self, task: YOLOTask) -> bool {
+        // YOLOv8 支持所有任务类型
+        matches!(
+            task,
+            YOLOTask::Detect | YOLOTask::Pose | YOLOTask::Segment | YOLOTask::Classify
+        )
+    }
+
+    fn set_conf(&mut self, val: f32) {
+        self.conf = val;
+    }
+
+    fn conf(&self) -> f32 {
+        self.conf
+    }
+
+    fn set_iou(&mut self, val: f32) {
+        self.iou = val;
+    }
+
+    fn iou(&self) -> f32 {
+        self.iou
+    }
+
+    fn preprocess_spec(&self) -> PreprocessSpec {
+        PreprocessSpec::default().with_channels(self.engine.channels())
+    }
+
+    fn set_input_size(&mut self, width: u32, height: u32) -> bool {
+        YOLOv8::set_input_size(self, width, height)
+    }
+}
+
+/// 求每个 anchor 的最大类别分数, 返回超过阈值的 `(anchor, class_id, confidence)`
+///
+/// `raw` 为单张图 `[C, N]` 行主序输出, 类别分数位于 `cls_offset..cls_offset + nc` 行。
+/// anchor 按块交给 rayon 并行, 块内按类别行顺序比较 (连续内存, 可自动向量化),
+/// 结果按 anchor 升序排列, 与逐 anchor 遍历一致
+pub fn decode_candidates(
+    raw: &[f32],
+    na: usize,
+    cls_offset: usize,
+    nc: usize,
+    conf: f32,
+) -> Vec<(usize, usize, f32)> {
+    const CHUNK: usize = 1024;
+    if nc == 0 || na == 0 || raw.len() < (cls_offset + nc) * na {
+        return Vec::new();
+    }
+
+    (0..na.div_ceil(CHUNK))
+        .into_par_iter()
+        .flat_map_iter(|chunk| {
+            let start = chunk * CHUNK;
+            let end = (start + CHUNK).min(na);
+            let row = |c: usize| &raw[(cls_offset + c) * na + start..(cls_offset + c) * na + end];
+
+            let mut best = row(0).to_vec();
+            let mut ids = vec![0usize; end - start];
+            for c in 1..nc {
+                for ((b, id), &v) in best.iter_mut().zip(ids.iter_mut()).zip(row(c)) {
+                    if v > *b {
+                        *b = v;
+                        *id = c;
+                    }
+                }
+            }
+
+            best.into_iter()
+                .zip(ids)
+                .enumerate()
+                .filter(move |(_, (confidence, _))| *confidence >= conf)
+                .map(move |(k, (confidence, id))| (start + k, id, confidence))
+        })
+        .collect()
+}
+
+/// 输出张量布局
+///
+/// 不同工具链导出的模型会转置输出: 检测头 `[N, C, anchors]` 与 `[N, anchors, C]`,
+/// 分割掩码原型 `[N, nm, h, w]` (NCHW) 与 `[N, h, w, nm]` (NHWC)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputLayout {
+    /// 通道在前 (Ultralytics 官方导出)
+    ChannelsFirst,
+    /// 通道在后 (转置导出)
+    ChannelsLast,
+}
+
+impl OutputLayout {
+    /// 按形状判断通道维位置 (动态维为 -1), 首尾两维都等于或都不等于 `channels` 时无法判断
+    pub fn from_shape(shape: &[i64], channels: usize) -> Option<Self> {
+        if shape.len() < 3 {
+            return None;
+        }
+        let c = channels as i64;
+        match (shape[1] == c, shape[shape.len() - 1] == c) {
+            (true, false) => Some(OutputLayout::ChannelsFirst),
+            (false, true) => Some(OutputLayout::ChannelsLast),
+            _ => None,
+        }
+    }
+
+    /// 检测头布局: 先按形状判断, 再检查类别分数是否落在 [0, 1];
+    /// 形状无法区分 (通道数与 anchor 数相同, 或类别数有误) 时以数值为准
+    pub fn detect(preds: &ArrayD<f32>, channels: usize, nc: usize) -> Self {
+        let shape: Vec<i64> = preds.shape().iter().map(|&d| d as i64).collect();
+        let plausible = |layout: Self| scores_plausible(&layout.channels_first(preds), nc);
+        match Self::from_shape(&shape, channels) {
+            Some(layout) if plausible(layout) => layout,
+            by_shape => [OutputLayout::ChannelsFirst, OutputLayout::ChannelsLast]
+                .into_iter()
+                .find(|&layout| plausible(layout))
+                .or(by_shape)
+                .unwrap_or(OutputLayout::ChannelsFirst),
+        }
+    }
+
+    /// 整理为通道在前的视图 (不拷贝)
+    pub fn channels_first<'a>(&self, x: &'a ArrayD<f32>) -> ArrayViewD<'a, f32> {
+        match self {
+            OutputLayout::ChannelsFirst => x.view(),
+            OutputLayout::ChannelsLast => {
+                let mut axes: Vec<usize> = (0..x.ndim()).collect();
+                if axes.len() > 2 {
+                    axes[1..].rotate_right(1);
+                }
+                x.view().permuted_axes(axes)
+            }
+        }
+    }
+}
+
+/// 检测头通道数: 4 (cxcywh) + nc, 姿态加 3×nk, 分割加 nm
+fn head_channels(task: &YOLOTask, nc: usize, nk: usize, nm: usize) -> usize {
+    match task {
+        YOLOTask::Pose => 4 + nc + 3 * nk,
+        YOLOTask::Segment => 4 + nc + nm,
+        _ => 4 + nc,
+    }
+}
+
+/// 按 `[N, C, anchors]` 解读时, 第一张图前若干 anchor 的类别分数是否都在 [0, 1]
+fn scores_plausible(preds: &ArrayViewD<f32>, nc: usize) -> bool {
+    const SAMPLE: usize = 512;
+    if preds.ndim() != 3 || nc == 0 || preds.shape()[0] == 0 || preds.shape()[1] < 4 + nc {
+        return false;
+    }
+    let na = preds.shape()[2].min(SAMPLE);
+    preds
+        .slice(s![0, 4..4 + nc, ..na])
+        .iter()
+        .all(|v| (-1e-3..=1.0 + 1e-3).contains(v))
+}
+
+// ========================================
+// YOLOv8Postprocessor: 与 ONNX 引擎解耦的后处理器
+// YOLOv8/v5/v11 模型的 postprocess 均委托于此, 便于用 golden 张量测试
+// ========================================
+
+/// YOLOv8 后处理配置
+pub struct YOLOv8Config {
+    pub task: YOLOTask,
+    pub nc: usize,
+    pub nk: usize,
+    pub nm: usize,
+    pub conf: f32,
+    pub kconf: f32,
+    pub iou: f32,
+    pub width: usize,
+    pub height: usize,
+    /// 检测头输出布局, None 时每次按形状与数值判断
+    pub layout: Option<OutputLayout>,
+}
+
+impl YOLOv8Config {
+    pub fn new(
+        task: YOLOTask,
+        nc: usize,
+        width: usize,
+        height: usize,
+        conf: f32,
+        iou: f32,
+    ) -> Self {
+        Self {
+            task,
+            nc,
+            nk: 17,
+            nm: 32,
+            conf,
+            kconf: 0.55,
+            iou,
+            width,
+            height,
+            layout: None,
+        }
+    }
+}
+
+/// YOLOv8 后处理器
+pub struct YOLOv8Postprocessor {
+    config: YOLOv8Config,
+}
+
+impl YOLOv8Postprocessor {
+    pub fn new(config: YOLOv8Config) -> Self {
+        Self { config }
+    }
+
+    fn scale_wh(&self, w0: f32, h0: f32, w1: f32, h1: f32) -> (f32, f32, f32) {
+        let r = (w1 / w0).min(h1 / h0);
+        (r, (w0 * r).round(), (h0 * r).round())
+    }
+
+    pub fn postprocess(
+        &self,
+        xs: Vec<Array<f32, IxDyn>>,
+        xs0: &[DynamicImage],
+    ) -> Result<Vec<DetectionResult>> {
+        if let YOLOTask::Classify = self.config.task {
+            let mut ys = Vec::new();
+            let preds = &xs[0];
+            for batch in preds.axis_iter(Axis(0)) {
+                ys.push(DetectionResult::new(
+                    Some(Embedding::new(batch.into_owned())),
+                    None,
+                    None,
+                    None,
+                ));
+            }
+            Ok(ys)
+        } else {
+            const CXYWH_OFFSET: usize = 4;
+            const KPT_STEP: usize = 3;
+            let (nc, nk, nm) = (self.config.nc, self.config.nk, self.config.nm);
+            let layout = self.config.layout.unwrap_or_else(|| {
+                OutputLayout::detect(&xs[0], head_channels(&self.config.task, nc, nk, nm), nc)
+            });
+            let preds = layout.channels_first(&xs[0]);
+            // 掩码原型 NCHW/NHWC 按 nm 所在维判断
+            let protos = xs.get(1).map(|protos| {
+                let shape: Vec<i64> = protos.shape().iter().map(|&d| d as i64).collect();
+                OutputLayout::from_shape(&shape, nm)
+                    .unwrap_or(OutputLayout::ChannelsFirst)
+                    .channels_first(protos)
+            });
+            let mut ys = Vec::new();
+            for (idx, anchor) in preds.axis_iter(Axis(0)).enumerate() {
+                let width_original = xs0[idx].width() as f32;
+                let height_original = xs0[idx].height() as f32;
+                let ratio = (self.config.width as f32 / width_original)
+                    .min(self.config.height as f32 / height_original);
+
+                // [C, N] 转为行主序连续内存, 先并行求各 anchor 的最大类别分数,
+                // 只对超过阈值的少量候选读取坐标/关键点/掩码系数
+                let anchor = anchor.as_standard_layout();
+                let (nch, na) = (anchor.shape()[0], anchor.shape()[1]);
+                let raw = match anchor.as_slice() {
+                    Some(raw) => raw,
+                    None => continue,
+                };
+                let candidates = decode_candidates(raw, na, CXYWH_OFFSET, nc, self.config.conf);
+
+                let mut data: Vec<(Bbox, Option<Vec<Point2>>, Option<Vec<f32>>)> =
+                    Vec::with_capacity(candidates.len());
+                for (i, id, confidence) in candidates {
+                    let at = |c: usize| raw[c * na + i];
+
+                    let cx = at(0) / ratio;
+                    let cy = at(1) / ratio;
+                    let w = at(2) / ratio;
+                    let h = at(3) / ratio;
+                    let x = cx - w / 2.;
+                    let y = cy - h / 2.;
+                    let y_bbox = Bbox::new(
+                        x.max(0.0f32).min(width_original),
+                        y.max(0.0f32).min(height_original),
+                        w,
+                        h,
+                        id,
+                        confidence,
+                    );
+
+                    let y_kpts = {
+                        if let YOLOTask::Pose = self.config.task {
+                            let kpt_offset = nch - KPT_STEP * nk;
+                            let mut kpts_ = Vec::with_capacity(nk);
+                            for k in 0..nk {
+                                let kx = at(kpt_offset + KPT_STEP * k) / ratio;
+                                let ky = at(kpt_offset + KPT_STEP * k + 1) / ratio;
+                                let kconf = at(kpt_offset + KPT_STEP * k + 2);
+                                if kconf < self.config.kconf {
+                                    kpts_.push(Point2::default());
+                                } else {
+                                    kpts_.push(Point2::new_with_conf(
+                                        kx.max(0.0f32).min(width_original),
+                                        ky.max(0.0f32).min(height_original),
+                                        kconf,
+                                    ));
+                                }
+                            }
+                            Some(kpts_)
+                        } else {
+                            None
+                        }
+                    };
+
+                    let coefs = {
+                        if let YOLOTask::Segment = self.config.task {
+                            Some((nch - nm..nch).map(at).collect())
+                        } else {
+                            None
+                        }
+                    };
+
+                    data.push((y_bbox, y_kpts, coefs));
+                }
+
+                non_max_suppression(&mut data, self.config.iou);
+
+                let mut y_bboxes: Vec<Bbox> = Vec::new();
+                let mut y_kpts: Vec<Vec<Point2>> = Vec::new();
+                let mut y_masks: Vec<Vec<u8>> = Vec::new();
+                for elem in data.into_iter() {
+                    if let Some(kpts) = elem.1 {
+                        y_kpts.push(kpts)
+                    }
+
+                    if let Some(coefs) = elem.2 {
+                        let proto = protos
+                            .as_ref()
+                            .ok_or_else(|| {
+                                DetectorError::ShapeMismatch("分割模型缺少掩码原型输出".into())
+                            })?
+                            .slice(s![idx, .., .., ..]);
+                        let (nm, nh, nw) = proto.dim();
+
+                        let coefs = Array::from_shape_vec((1, nm), coefs)?;
+                        let proto = proto.to_owned();
+                        let proto = proto.to_shape((nm, nh * nw))?;
+                        let mask = coefs.dot(&proto);
+                        let mask = mask.to_shape((nh, nw, 1))?;
+
+                        let mask_im: ImageBuffer<image::Luma<_>, Vec<f32>> = ImageBuffer::from_raw(
+                            nw as u32,
+                            nh as u32,
+                            mask.to_owned().into_raw_vec_and_offset().0,
+                        )
+                        .ok_or_else(|| {
+                            DetectorError::Process("can not create image from ndarray".into())
+                        })?;
+                        let mut mask_im = image::DynamicImage::from(mask_im);
+
+                        let (_, w_mask, h_mask) =
+                            self.scale_wh(width_original, height_original, nw as f32, nh as f32);
+                        let mask_cropped = mask_im.crop(0, 0, w_mask as u32, h_mask as u32);
+                        let mask_original = mask_cropped.resize_exact(
+                            width_original as u32,
+                            height_original as u32,
+                            match self.config.task {
+                                YOLOTask::Segment => image::imageops::FilterType::CatmullRom,
+                                _ => image::imageops::FilterType::Triangle,
+                            },
+                        );
+
+                        let mut mask_original_cropped = mask_original.into_luma8();
+                        for y in 0..height_original as usize {
+                            for x in 0..width_original as usize {
+                                if x < elem.0.xmin() as usize
+                                    || x > elem.0.xmax() as usize
+                                    || y < elem.0.ymin() as usize
+                                    || y > elem.0.ymax() as usize
+                                {
+                                    mask_original_cropped.put_pixel(
+                                        x as u32,
+                                        y as u32,
+                                        image::Luma([0u8]),
+                                    );
+                                }
+                            }
+                        }
+                        y_masks.push(mask_original_cropped.into_raw());
+                    }
+                    y_bboxes.push(elem.0);
+                }
+
+                let y = DetectionResult {
+                    probs: None,
+                    bboxes: if !y_bboxes.is_empty() {
+                        Some(y_bboxes)
+                    } else {
+                        None
+                    },
+                    keypoints: if !y_kpts.is_empty() {
+                        Some(y_kpts)
+                    } else {
+                        None
+                    },
+                    masks: if !y_masks.is_empty() {
+                        Some(y_masks)
+                    } else {
+                        None
+                    },
+                };
+                ys.push(y);
+            }
+
+            Ok(ys)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 逐 anchor 遍历的参考实现 (旧版逻辑)
+    fn decode_naive(
+        raw: &[f32],
+        na: usize,
+        cls_offset: usize,
+        nc: usize,
+        conf: f32,
+    ) -> Vec<(usize, usize, f32)> {
+        let preds = Array::from_shape_vec((raw.len() / na, na), raw.to_vec()).unwrap();
+        let mut ys = Vec::new();
+        for (i, pred) in preds.axis_iter(Axis(1)).enumerate() {
+            let clss = pred.slice(s![cls_offset..cls_offset + nc]);
+            let (id, &confidence) = clss
+                .into_iter()
+                .enumerate()
+                .reduce(|max, x| if x.1 > max.1 { x } else { max })
+                .unwrap();
+            if confidence >= conf {
+                ys.push((i, id, confidence));
+            }
+        }
+        ys
+    }
+
+    /// 向量化解码与逐 anchor 遍历结果一致 (含跨块边界)
+    #[test]
+    fn test_decode_candidates_matches_naive() {
+        let (nc, na) = (80usize, 8400usize);
+        let mut seed = 7u32;
+        let raw: Vec<f32> = (0..(4 + nc) * na)
+            .map(|_| {
+                seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                (seed >> 8) as f32 / (1u32 << 24) as f32
+            })
+            .collect();
+
+        for conf in [0.5f32, 0.95, 0.99] {
+            assert_eq!(
+                decode_candidates(&raw, na, 4, nc, conf),
+                decode_naive(&raw, na, 4, nc, conf)
+            );
+        }
+    }
+
+    /// 同分时取第一个类别
+    #[test]
+    fn test_decode_candidates_tie() {
+        // 4 行坐标 + 3 类, 2 个 anchor
+        let raw = vec![
+            0., 0., 0., 0., 0., 0., 0., 0., // cxcywh
+            0.6, 0.1, // cls 0
+            0.6, 0.9, // cls 1
+            0.2, 0.9, // cls 2
+        ];
+        assert_eq!(
+            decode_candidates(&raw, 2, 4, 3, 0.25),
+            vec![(0, 0, 0.6), (1, 1, 0.9)]
+        );
+    }
+
+    /// 按形状判断布局, 动态维与首尾同值时无法判断
+    #[test]
+    fn test_output_layout_from_shape() {
+        assert_eq!(
+            OutputLayout::from_shape(&[1, 84, 8400], 84),
+            Some(OutputLayout::ChannelsFirst)
+        );
+        assert_eq!(
+            OutputLayout::from_shape(&[1, 8400, 84], 84),
+            Some(OutputLayout::ChannelsLast)
+        );
+        assert_eq!(
+            OutputLayout::from_shape(&[1, 160, 160, 32], 32),
+            Some(OutputLayout::ChannelsLast)
+        );
+        assert_eq!(OutputLayout::from_shape(&[-1, -1, -1], 84), None);
+        assert_eq!(OutputLayout::from_shape(&[1, 84, 84], 84), None);
+        assert_eq!(OutputLayout::from_shape(&[1, 84], 84), None);
+    }
+
+    /// 转置输出经自动判断后与原布局一致, 方阵时按类别分数取值范围判断
+    #[test]
+    fn test_output_layout_detect_transposed() {
+        let nc = 2usize;
+        // [1, C, N]: 坐标取较大值, 类别分数在 [0, 1]
+        let make = |na: usize| {
+            Array::from_shape_fn(IxDyn(&[1, 4 + nc, na]), |d| {
+                if d[1] < 4 {
+                    (100 + d[1] * 10 + d[2]) as f32
+                } else {
+                    (d[1] * na + d[2]) as f32 / 100.0
+                }
+            })
+        };
+
+        for na in [8usize, 4 + nc] {
+            let first = make(na);
+            let last = first.view().permuted_axes(IxDyn(&[0, 2, 1])).to_owned();
+            assert_eq!(
+                OutputLayout::detect(&first, 4 + nc, nc),
+                OutputLayout::ChannelsFirst
+            );
+            assert_eq!(
+                OutputLayout::detect(&last, 4 + nc, nc),
+                OutputLayout::ChannelsLast
+            );
+            assert_eq!(
+                OutputLayout::ChannelsLast.channels_first(&last),
+                first.view()
+            );
+        }
+    }
+}
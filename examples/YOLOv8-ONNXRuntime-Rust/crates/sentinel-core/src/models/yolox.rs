@@ -5,17 +5,21 @@
 //! - Anchor-Free: 无需预设锚框
 //! - Decoupled Head: 解耦检测头
 //! - SimOTA: 先进的标签分配策略
+//!
+//! 完整模型需要 `ort` 功能, 后处理器 (YOLOXPostprocessor) 属于核心库
 
 use anyhow::Result;
 use image::DynamicImage;
 use ndarray::{Array, Axis, IxDyn};
 
-use crate::{
-    non_max_suppression, Batch, Bbox, DetectionResult, OrtBackend, OrtConfig, OrtEP, Point2,
-    YOLOTask,
-};
+#[cfg(feature = "ort")]
+use crate::models::{model_info, ModelType, PreprocessSpec};
+use crate::{non_max_suppression, Bbox, DetectionResult, Point2};
+#[cfg(feature = "ort")]
+use crate::{Batch, OrtBackend, OrtConfig, OrtEP, YOLOTask};
 
 /// YOLOX 模型结构
+#[cfg(feature = "ort")]
 pub struct YOLOX {
     engine: OrtBackend,
     nc: u32,
@@ -29,11 +33,12 @@ pub struct YOLOX {
     profile: bool,
 }
 
+#[cfg(feature = "ort")]
 impl YOLOX {
     /// 从配置创建 YOLOX 模型
     pub fn new(config: crate::Args) -> Result<Self> {
         // execution provider
-        let ep = if config.trt {
+        let ep = if config.trt || config.dla_core.is_some() {
             OrtEP::Trt(config.device_id)
         } else if config.cuda {
             OrtEP::CUDA(config.device_id)
@@ -55,9 +60,15 @@ impl YOLOX {
             f: config.model,
             task: Some(YOLOTask::Detect), // YOLOX only supports detection
             trt_fp16: config.fp16,
+            dla_core: config.dla_core,
             image_size: (config.height, config.width),
         };
         let engine = OrtBackend::build(ort_args)?;
+        model_info::check_layout(
+            ModelType::YOLOX,
+            &engine.input_shapes()[0],
+            &engine.output_shapes(),
+        )?;
 
         // get batch, height, width
         let (batch, height, width) = (engine.batch(), engine.height(), engine.width());
@@ -191,6 +202,16 @@ impl YOLOX {
         })
     }
 
+    /// 后处理器 (解码 + NMS)
+    pub fn postprocessor(&self) -> YOLOXPostprocessor {
+        YOLOXPostprocessor {
+            conf: self.conf,
+            iou: self.iou,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
     fn scale_wh(&self, w0: f32, h0: f32, w1: f32, h1: f32) -> (f32, f32, f32) {
         let r = (w1 / w0).min(h1 / h0);
         (r, (w0 * r).round(), (h0 * r).round())
@@ -207,11 +228,20 @@ impl YOLOX {
     }
 }
 
+#[cfg(feature = "ort")]
 impl crate::models::Model for YOLOX {
     fn preprocess(&mut self, xs: &[DynamicImage]) -> Result<Vec<Array<f32, IxDyn>>> {
-        let mut ys =
-            Array::ones((xs.len(), 3, self.height as usize, self.width as usize)).into_dyn();
-        ys.fill(114.0 / 255.0); // YOLOX uses 114 as padding value
+        let spec = ModelType::YOLOX
+            .preprocess_spec()
+            .with_channels(self.engine.channels());
+        let mut ys = Array::ones((
+            xs.len(),
+            spec.channels,
+            self.height as usize,
+            self.width as usize,
+        ))
+        .into_dyn();
+        spec.fill_tensor(&mut ys); // YOLOX uses 114 as padding value
 
         for (idx, x) in xs.iter().enumerate() {
             let (w0, h0) = (x.width() as f32, x.height() as f32);
@@ -226,10 +256,7 @@ impl crate::models::Model for YOLOX {
             for (x, y, rgb) in img.to_rgb8().enumerate_pixels() {
                 let x = x as usize;
                 let y = y as usize;
-                let [r, g, b] = rgb.0;
-                ys[[idx, 0, y, x]] = (r as f32) / 255.0;
-                ys[[idx, 1, y, x]] = (g as f32) / 255.0;
-                ys[[idx, 2, y, x]] = (b as f32) / 255.0;
+                spec.write_pixel(&mut ys, idx, y, x, rgb.0);
             }
         }
 
@@ -247,6 +274,74 @@ impl crate::models::Model for YOLOX {
         &self,
         xs: Vec<Array<f32, IxDyn>>,
         xs0: &[DynamicImage],
+    ) -> Result<Vec<DetectionResult>> {
+        self.postprocessor().postprocess(xs, xs0)
+    }
+
+    fn engine_mut(&mut self) -> Option<&mut OrtBackend> {
+        Some(&mut self.engine)
+    }
+
+    fn summary(&self) {
+        println!(
+            "\n📋 YOLOX Model Summary\n\
+             Task: {:?}\n\
+             Batch: {}\n\
+             Width: {}\n\
+             Height: {}\n\
+             Classes: {}\n\
+             Conf Threshold: {}\n\
+             IoU Threshold: {}\n",
+            YOLOTask::Detect,
+            self.batch,
+            self.width,
+            self.height,
+            self.nc,
+            self.conf,
+            self.iou
+        );
+    }
+
+    fn supports_task(&self, task: YOLOTask) -> bool {
+        matches!(task, YOLOTask::Detect)
+    }
+
+    fn set_conf(&mut self, val: f32) {
+        self.conf = val;
+    }
+
+    fn conf(&self) -> f32 {
+        self.conf
+    }
+
+    fn set_iou(&mut self, val: f32) {
+        self.iou = val;
+    }
+
+    fn iou(&self) -> f32 {
+        self.iou
+    }
+
+    fn preprocess_spec(&self) -> PreprocessSpec {
+        ModelType::YOLOX
+            .preprocess_spec()
+            .with_channels(self.engine.channels())
+    }
+}
+
+/// YOLOX 后处理器 (与 ONNX 引擎解耦, 可单独用 golden 张量测试)
+pub struct YOLOXPostprocessor {
+    pub conf: f32,
+    pub iou: f32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl YOLOXPostprocessor {
+    pub fn postprocess(
+        &self,
+        xs: Vec<Array<f32, IxDyn>>,
+        xs0: &[DynamicImage],
     ) -> Result<Vec<DetectionResult>> {
         if xs.is_empty() {
             return Ok(vec![]);
@@ -281,7 +376,7 @@ impl crate::models::Model for YOLOX {
                 let (class_id, &class_conf) = clss
                     .iter()
                     .enumerate()
-                    .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+                    .max_by(|a, b| a.1.total_cmp(b.1))
                     .unwrap();
 
                 // confidence = objectness * class_confidence
@@ -349,48 +444,4 @@ impl crate::models::Model for YOLOX {
 
         Ok(ys)
     }
-
-    fn engine_mut(&mut self) -> &mut OrtBackend {
-        &mut self.engine
-    }
-
-    fn summary(&self) {
-        println!(
-            "\n📋 YOLOX Model Summary\n\
-             Task: {:?}\n\
-             Batch: {}\n\
-             Width: {}\n\
-             Height: {}\n\
-             Classes: {}\n\
-             Conf Threshold: {}\n\
-             IoU Threshold: {}\n",
-            YOLOTask::Detect,
-            self.batch,
-            self.width,
-            self.height,
-            self.nc,
-            self.conf,
-            self.iou
-        );
-    }
-
-    fn supports_task(&self, task: YOLOTask) -> bool {
-        matches!(task, YOLOTask::Detect)
-    }
-
-    fn set_conf(&mut self, val: f32) {
-        self.conf = val;
-    }
-
-    fn conf(&self) -> f32 {
-        self.conf
-    }
-
-    fn set_iou(&mut self, val: f32) {
-        self.iou = val;
-    }
-
-    fn iou(&self) -> f32 {
-        self.iou
-    }
 }
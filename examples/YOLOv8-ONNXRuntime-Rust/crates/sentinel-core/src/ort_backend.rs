@@ -1,7 +1,11 @@
 // Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
 
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+
 use anyhow::Result;
-use clap::ValueEnum;
 use half::f16;
 use ndarray::{Array, CowArray, IxDyn};
 use ort::execution_providers::{
@@ -13,16 +17,24 @@ use ort::session::Session;
 use ort::tensor::TensorElementType;
 use ort::value::ValueType;
 use regex::Regex;
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
-pub enum YOLOTask {
-    // YOLO tasks
-    Classify,
-    Detect,
-    Pose,
-    Segment,
+
+use crate::memory_guard::OrtUsage;
+use crate::{DetectorError, YOLOTask};
+
+/// 按运行时配置 (runtime_config.json) 设置线程数的会话构建器, 所有 ORT 会话都应经此创建
+pub fn session_builder() -> ort::Result<SessionBuilder> {
+    let config = crate::runtime_config::runtime();
+    let mut builder = SessionBuilder::new()?;
+    if config.ort_intra_threads > 0 {
+        builder = builder.with_intra_threads(config.ort_intra_threads)?;
+    }
+    if config.ort_inter_threads > 0 {
+        builder = builder.with_inter_threads(config.ort_inter_threads)?;
+    }
+    Ok(builder)
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum OrtEP {
     // ONNXRuntime execution provider
     CPU,
@@ -58,7 +70,7 @@ pub struct OrtInputs {
 }
 
 impl OrtInputs {
-    pub fn new(session: &Session) -> Self {
+    pub fn new(session: &Session) -> Result<Self, DetectorError> {
         let mut shapes = Vec::new();
         let mut dtypes = Vec::new();
         let mut names = Vec::new();
@@ -78,17 +90,26 @@ impl OrtInputs {
                 let shape = shape.to_vec().clone();
                 shapes.push(shape);
             } else {
-                panic!("不支持的数据格式, {} - {}", file!(), line!());
+                return Err(DetectorError::ModelLoad(format!(
+                    "输入 `{}` 不是张量",
+                    i.name
+                )));
             }
             //dtypes.push(i.input_type);
             names.push(i.name.clone());
         }
-        Self {
+        if shapes.first().is_none_or(|s| s.len() != 4) {
+            return Err(DetectorError::ShapeMismatch(format!(
+                "模型输入应为 [N, 3, H, W] 或 [N, 1, H, W], 实际 {:?}",
+                shapes.first()
+            )));
+        }
+        Ok(Self {
             shapes,
             dtypes,
             names,
             ..Default::default()
-        }
+        })
     }
 }
 
@@ -99,18 +120,88 @@ pub struct OrtConfig {
     pub task: Option<YOLOTask>,
     pub ep: OrtEP,
     pub trt_fp16: bool,
+    pub dla_core: Option<u32>, // Jetson DLA 核心 (仅 TensorRT EP)
     pub batch: Batch,
     pub image_size: (Option<u32>, Option<u32>),
 }
 
+/// 多个检测线程共享的会话 (推理时加锁, 同一引擎上的请求串行执行)
+pub type SharedSession = Arc<Mutex<Session>>;
+
+/// 会话注册表: 按键复用已加载的会话, 只保存弱引用, 最后一个使用者释放后会话随之卸载
+pub struct SessionRegistry<K, T> {
+    entries: Mutex<HashMap<K, Weak<Mutex<T>>>>,
+}
+
+impl<K: Eq + Hash, T> SessionRegistry<K, T> {
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 取已加载的会话, 不存在时调用 `create` 加载; 返回 (会话, 是否复用)
+    ///
+    /// 加载期间持有注册表锁, 多个流同时启动同一模型时只加载一次 (TensorRT 引擎构建较慢)
+    pub fn get_or_create(
+        &self,
+        key: K,
+        create: impl FnOnce() -> Result<T>,
+    ) -> Result<(Arc<Mutex<T>>, bool)> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, weak| weak.strong_count() > 0);
+        if let Some(shared) = entries.get(&key).and_then(Weak::upgrade) {
+            return Ok((shared, true));
+        }
+        let shared = Arc::new(Mutex::new(create()?));
+        entries.insert(key, Arc::downgrade(&shared));
+        Ok((shared, false))
+    }
+
+    /// 仍在使用的会话数
+    pub fn len(&self) -> usize {
+        let entries = self.entries.lock().unwrap();
+        entries.values().filter(|w| w.strong_count() > 0).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K: Eq + Hash, T> Default for SessionRegistry<K, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 共享会话的键: 同一模型文件, 执行后端与引擎参数 (输入尺寸/批大小/精度) 相同才能复用
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct SessionKey {
+    path: PathBuf,
+    ep: OrtEP,
+    trt_fp16: bool,
+    dla_core: Option<u32>,
+    height: u32,
+    width: u32,
+    batch: (u32, u32, u32),
+}
+
+/// 检测模型会话注册表 (进程内全局)
+fn sessions() -> &'static SessionRegistry<SessionKey, Session> {
+    static SESSIONS: OnceLock<SessionRegistry<SessionKey, Session>> = OnceLock::new();
+    SESSIONS.get_or_init(SessionRegistry::new)
+}
+
 #[derive(Debug)]
 pub struct OrtBackend {
-    // ORT engine
-    session: Session,
+    // ORT engine (runtime_config.share_sessions 开启时与其他检测线程共享)
+    session: SharedSession,
     task: YOLOTask,
     ep: OrtEP,
     batch: Batch,
     inputs: OrtInputs,
+    _usage: OrtUsage, // 内存统计 (后端数与模型大小)
 }
 
 impl OrtBackend {
@@ -126,18 +217,39 @@ impl OrtBackend {
         //let session = SessionBuilder::new(&env)?.with_model_from_file(&args.f)?;
 
         // get inputs
-        let mut inputs = OrtInputs::new(&session);
+        let mut inputs = OrtInputs::new(&session)?;
+        if let Some(output) = session
+            .outputs
+            .iter()
+            .find(|o| !matches!(o.output_type, ValueType::Tensor { .. }))
+        {
+            return Err(
+                DetectorError::ModelLoad(format!("输出 `{}` 不是张量", output.name)).into(),
+            );
+        }
+        if !matches!(
+            inputs.dtypes[0],
+            TensorElementType::Float32 | TensorElementType::Float16
+        ) {
+            return Err(DetectorError::ModelLoad(format!(
+                "输入类型 {:?} 不受支持, 只支持 FP32/FP16 输入",
+                inputs.dtypes[0]
+            ))
+            .into());
+        }
 
         // batch size
         let mut batch = args.batch;
         let batch = if inputs.shapes[0][0] == -1 {
             batch
         } else {
-            assert_eq!(
-                inputs.shapes[0][0] as u32, batch.opt,
-                "Expected batch size: {}, got {}. Try using `--batch {}`.",
-                inputs.shapes[0][0] as u32, batch.opt, inputs.shapes[0][0] as u32
-            );
+            if inputs.shapes[0][0] as u32 != batch.opt {
+                return Err(DetectorError::ShapeMismatch(format!(
+                    "Expected batch size: {}, got {}. Try using `--batch {}`.",
+                    inputs.shapes[0][0] as u32, batch.opt, inputs.shapes[0][0] as u32
+                ))
+                .into());
+            }
             batch.opt = inputs.shapes[0][0] as u32;
             batch
         };
@@ -146,7 +258,12 @@ impl OrtBackend {
         let height = if inputs.shapes[0][2] == -1 {
             match args.image_size.0 {
                 Some(height) => height,
-                None => panic!("Failed to get model height. Make it explicit with `--height`"),
+                None => {
+                    return Err(DetectorError::ShapeMismatch(
+                        "Failed to get model height. Make it explicit with `--height`".into(),
+                    )
+                    .into())
+                }
             }
         } else {
             inputs.shapes[0][2] as u32
@@ -154,7 +271,12 @@ impl OrtBackend {
         let width = if inputs.shapes[0][3] == -1 {
             match args.image_size.1 {
                 Some(width) => width,
-                None => panic!("Failed to get model width. Make it explicit with `--width`"),
+                None => {
+                    return Err(DetectorError::ShapeMismatch(
+                        "Failed to get model width. Make it explicit with `--width`".into(),
+                    )
+                    .into())
+                }
             }
         } else {
             inputs.shapes[0][3] as u32
@@ -164,7 +286,9 @@ impl OrtBackend {
         // build provider
         let (ep, provider) = match args.ep {
             OrtEP::CUDA(device_id) => Self::set_ep_cuda(device_id),
-            OrtEP::Trt(device_id) => Self::set_ep_trt(device_id, args.trt_fp16, &batch, &inputs),
+            OrtEP::Trt(device_id) => {
+                Self::set_ep_trt(device_id, args.trt_fp16, args.dla_core, &batch, &inputs)?
+            }
             _ => (
                 OrtEP::CPU,
                 ExecutionProviderDispatch::from(CPUExecutionProvider::default()),
@@ -172,31 +296,65 @@ impl OrtBackend {
         };
 
         // build session again with the new provider
-        let session = SessionBuilder::new()?
-            .with_intra_threads(4)? // Enable intra-op parallelism (4 threads)
-            .with_inter_threads(2)? // Enable inter-op parallelism (2 threads)
-            .with_execution_providers([provider])?
-            .commit_from_file(args.f)?;
+        // 多路流使用同一模型时复用已加载的会话, 避免每路各占一份显存
+        let build = || -> Result<Session> {
+            Ok(session_builder()?
+                .with_execution_providers([provider])?
+                .commit_from_file(&args.f)?)
+        };
+        let model_bytes = std::fs::metadata(&args.f).map_or(0, |m| m.len());
+        let (session, loaded) = if crate::runtime_config::runtime().share_sessions {
+            let key = SessionKey {
+                path: std::fs::canonicalize(&args.f).unwrap_or_else(|_| PathBuf::from(&args.f)),
+                ep: ep.clone(),
+                trt_fp16: args.trt_fp16,
+                dla_core: args.dla_core,
+                height,
+                width,
+                batch: (batch.opt, batch.min, batch.max),
+            };
+            let (session, reused) = sessions().get_or_create(key, build)?;
+            if reused {
+                println!(
+                    "♻️ 复用已加载的会话: {} ({}个使用者)",
+                    args.f,
+                    Arc::strong_count(&session)
+                );
+            }
+            (session, !reused)
+        } else {
+            (Arc::new(Mutex::new(build()?)), true)
+        };
 
         // task: using given one or guessing
         let task = match args.task {
             Some(task) => task,
-            None => match session.metadata() {
-                Err(_) => panic!("No metadata found. Try making it explicit by `--task`"),
-                Ok(metadata) => match metadata.custom("task") {
-                    Err(_) => panic!("Can not get custom value. Try making it explicit by `--task`"),
-                    Ok(value) => match value {
-                        None => panic!("No corresponding value of `task` found in metadata. Make it explicit by `--task`"),
-                        Some(task) => match task.as_str() {
-                            "classify" => YOLOTask::Classify,
-                            "detect" => YOLOTask::Detect,
-                            "pose" => YOLOTask::Pose,
-                            "segment" => YOLOTask::Segment,
-                            x => todo!("{:?} is not supported for now!", x),
-                        },
-                    },
-                },
-            },
+            None => {
+                let task = session
+                    .lock()
+                    .unwrap()
+                    .metadata()
+                    .ok()
+                    .and_then(|metadata| metadata.custom("task").ok().flatten())
+                    .ok_or_else(|| {
+                        DetectorError::UnsupportedTask(
+                            "No `task` found in metadata. Make it explicit by `--task`".into(),
+                        )
+                    })?;
+                match task.as_str() {
+                    "classify" => YOLOTask::Classify,
+                    "detect" => YOLOTask::Detect,
+                    "pose" => YOLOTask::Pose,
+                    "segment" => YOLOTask::Segment,
+                    x => {
+                        return Err(DetectorError::UnsupportedTask(format!(
+                            "{:?} is not supported for now!",
+                            x
+                        ))
+                        .into())
+                    }
+                }
+            }
         };
 
         Ok(Self {
@@ -205,12 +363,13 @@ impl OrtBackend {
             ep,
             batch,
             inputs,
+            _usage: OrtUsage::new(if loaded { model_bytes } else { 0 }),
         })
     }
 
     pub fn fetch_inputs_from_session(
         session: &Session,
-    ) -> (Vec<Vec<i64>>, Vec<TensorElementType>, Vec<String>) {
+    ) -> Result<(Vec<Vec<i64>>, Vec<TensorElementType>, Vec<String>), DetectorError> {
         // get inputs attrs from ONNX model
         let mut shapes = Vec::new();
         let mut dtypes = Vec::new();
@@ -226,11 +385,14 @@ impl OrtBackend {
                 let shape = shape.to_vec().clone();
                 shapes.push(shape);
             } else {
-                panic!("不支持的数据格式, {} - {}", file!(), line!());
+                return Err(DetectorError::ModelLoad(format!(
+                    "输入 `{}` 不是张量",
+                    i.name
+                )));
             }
             names.push(i.name.clone());
         }
-        (shapes, dtypes, names)
+        Ok((shapes, dtypes, names))
     }
 
     pub fn set_ep_cuda(device_id: i32) -> (OrtEP, ExecutionProviderDispatch) {
@@ -252,9 +414,10 @@ impl OrtBackend {
     pub fn set_ep_trt(
         device_id: i32,
         fp16: bool,
+        dla_core: Option<u32>,
         batch: &Batch,
         inputs: &OrtInputs,
-    ) -> (OrtEP, ExecutionProviderDispatch) {
+    ) -> Result<(OrtEP, ExecutionProviderDispatch), DetectorError> {
         // set TensorRT
         let trt_provider = TensorRTExecutionProvider::default().with_device_id(device_id);
 
@@ -262,10 +425,10 @@ impl OrtBackend {
         if let Ok(true) = trt_provider.is_available() {
             let (height, width) = (inputs.sizes[0][0], inputs.sizes[0][1]);
             if inputs.dtypes[0] == TensorElementType::Float16 && !fp16 {
-                panic!(
+                return Err(DetectorError::ModelLoad(format!(
                     "Dtype mismatch! Expected: Float32, got: {:?}. You should use `--fp16`",
                     inputs.dtypes[0]
-                );
+                )));
             }
             // dynamic shape: input_tensor_1:dim_1xdim_2x...,input_tensor_2:dim_3xdim_4x...,...
             let mut opt_string = String::new();
@@ -283,25 +446,34 @@ impl OrtBackend {
             let _ = min_string.pop();
             let _ = max_string.pop();
 
-            let trt_provider = trt_provider
+            let mut trt_provider = trt_provider
                 .with_profile_opt_shapes(opt_string)
                 .with_profile_min_shapes(min_string)
                 .with_profile_max_shapes(max_string)
                 .with_fp16(fp16)
                 .with_timing_cache(true);
-            (
+
+            // DLA 只支持 FP16/INT8, 不支持的层由 TensorRT 回退到 GPU
+            if let Some(core) = dla_core {
+                println!("> TensorRT on DLA core {} (FP16, GPU fallback)", core);
+                trt_provider = trt_provider
+                    .with_dla(true)
+                    .with_dla_core(core)
+                    .with_fp16(true);
+            }
+            Ok((
                 OrtEP::Trt(device_id),
                 ExecutionProviderDispatch::from(trt_provider),
-            )
+            ))
         } else {
             println!("> TensorRT is not available! Try using CUDA...");
-            Self::set_ep_cuda(device_id)
+            Ok(Self::set_ep_cuda(device_id))
         }
     }
 
     pub fn fetch_from_metadata(&self, key: &str) -> Option<String> {
         // fetch value from onnx model file by key
-        match self.session.metadata() {
+        match self.session.lock().unwrap().metadata() {
             Err(_) => None,
             Ok(metadata) => match metadata.custom(key) {
                 Err(_) => None,
@@ -315,7 +487,7 @@ impl OrtBackend {
         match self.dtype() {
             TensorElementType::Float16 => self.run_fp16(xs, profile),
             TensorElementType::Float32 => self.run_fp32(xs, profile),
-            _ => todo!(),
+            dtype => Err(DetectorError::Process(format!("不支持的输入类型 {:?}", dtype)).into()),
         }
     }
 
@@ -348,22 +520,31 @@ impl OrtBackend {
         // compute output shapes before calling session.run to avoid borrowing self immutably while session is mutably borrowed
         let out_shapes = self.output_shapes();
 
-        // run
+        // run (共享会话时等待其他检测线程的推理完成)
         let t = std::time::Instant::now();
-        let ys = self.session.run(ort::inputs![input])?;
+        let mut session = self.session.lock().unwrap();
+        let ys = session.run(ort::inputs![input])?;
         if profile {
             println!("[ORT Inference]: {:?}", t.elapsed());
         }
 
         // d2h
-        Ok(ys
-            .iter()
+        ys.iter()
             .enumerate()
-            .map(|(idx, (_k, v))| {
+            .map(|(idx, (_k, v))| -> Result<Array<f32, IxDyn>> {
+                // 整数输出 (EfficientNMS 的 num_dets/det_classes) 不做 f16 转换
+                if let Some(y) = integer_output(&v, &out_shapes[idx]) {
+                    return y.map_err(|e| {
+                        DetectorError::ShapeMismatch(format!("输出 {}: {}", idx, e)).into()
+                    });
+                }
+
                 // d2h
                 let t = std::time::Instant::now();
                 // try_extract_tensor for f16 returns (shape, slice)
-                let (_shape, slice) = v.try_extract_tensor::<f16>().unwrap();
+                let (_shape, slice) = v
+                    .try_extract_tensor::<f16>()
+                    .map_err(|e| DetectorError::Process(format!("读取输出失败: {}", e)))?;
                 if profile {
                     println!("[ORT D2H]: {:?}", t.elapsed());
                 }
@@ -373,14 +554,15 @@ impl OrtBackend {
                 // build ndarray from the returned slice using the runtime output shape
                 let out_shape = out_shapes[idx].clone();
                 let dims = out_shape.iter().map(|&d| d as usize).collect::<Vec<_>>();
-                let arr_f16 = Array::from_shape_vec(IxDyn(&dims), slice.to_vec()).unwrap();
+                let arr_f16 = Array::from_shape_vec(IxDyn(&dims), slice.to_vec())
+                    .map_err(|e| DetectorError::ShapeMismatch(format!("输出 {}: {}", idx, e)))?;
                 let v = arr_f16.mapv(f16::to_f32);
                 if profile {
                     println!("[ORT f16->f32]: {:?}", t_.elapsed());
                 }
-                v
+                Ok(v)
             })
-            .collect::<Vec<Array<_, _>>>())
+            .collect()
     }
 
     pub fn run_fp32(
@@ -405,21 +587,29 @@ impl OrtBackend {
         // compute output shapes before calling session.run to avoid borrowing self immutably while session is mutably borrowed
         let out_shapes = self.output_shapes();
 
-        // run
+        // run (共享会话时等待其他检测线程的推理完成)
         let t = std::time::Instant::now();
-        let ys = self.session.run(ort::inputs![input])?;
+        let mut session = self.session.lock().unwrap();
+        let ys = session.run(ort::inputs![input])?;
         if profile {
             println!("[ORT Inference]: {:?}", t.elapsed());
         }
 
         // d2h
-        Ok(ys
-            .iter()
+        ys.iter()
             .enumerate()
-            .map(|(idx, (_k, v))| {
+            .map(|(idx, (_k, v))| -> Result<Array<f32, IxDyn>> {
+                if let Some(y) = integer_output(&v, &out_shapes[idx]) {
+                    return y.map_err(|e| {
+                        DetectorError::ShapeMismatch(format!("输出 {}: {}", idx, e)).into()
+                    });
+                }
+
                 let t = std::time::Instant::now();
                 // try_extract_tensor for f32 returns (shape, slice)
-                let (_shape, slice) = v.try_extract_tensor::<f32>().unwrap();
+                let (_shape, slice) = v
+                    .try_extract_tensor::<f32>()
+                    .map_err(|e| DetectorError::Process(format!("读取输出失败: {}", e)))?;
                 if profile {
                     println!("[ORT D2H]: {:?}", t.elapsed());
                 }
@@ -427,18 +617,64 @@ impl OrtBackend {
                 // build ndarray from the returned slice using the runtime output shape
                 let out_shape = out_shapes[idx].clone();
                 let dims = out_shape.iter().map(|&d| d as usize).collect::<Vec<_>>();
-                Array::from_shape_vec(IxDyn(&dims), slice.to_vec()).unwrap()
+                let y = Array::from_shape_vec(IxDyn(&dims), slice.to_vec())
+                    .map_err(|e| DetectorError::ShapeMismatch(format!("输出 {}: {}", idx, e)))?;
+                Ok(y)
             })
-            .collect::<Vec<Array<f32, IxDyn>>>())
+            .collect()
+    }
+
+    /// 输入输出均已在显存中的推理 (IoBinding, 不经过主机内存)
+    ///
+    /// - `input`/`output`: 设备指针, 分别为 FP32 `[1, 3, h, w]` 与 `output_shape`
+    /// - 调用方保证两块缓冲在推理期间有效, 且输入已写入完成 (已同步)
+    #[cfg(feature = "cuda")]
+    pub fn run_device(
+        &mut self,
+        device_id: i32,
+        input: u64,
+        output: u64,
+        output_shape: &[i64],
+    ) -> Result<()> {
+        use ort::memory::{AllocationDevice, AllocatorType, MemoryInfo, MemoryType};
+        use ort::value::TensorRefMut;
+
+        let info = MemoryInfo::new(
+            AllocationDevice::CUDA,
+            device_id,
+            AllocatorType::Device,
+            MemoryType::Default,
+        )?;
+        let input_shape = vec![1, 3, self.height() as i64, self.width() as i64];
+        let (input, output) = unsafe {
+            (
+                TensorRefMut::<f32>::from_raw(
+                    info.clone(),
+                    input as *mut std::ffi::c_void,
+                    input_shape,
+                )?,
+                TensorRefMut::<f32>::from_raw(
+                    info,
+                    output as *mut std::ffi::c_void,
+                    output_shape.to_vec(),
+                )?,
+            )
+        };
+
+        let session = self.session.lock().unwrap();
+        let mut binding = session.create_binding()?;
+        binding.bind_input(&self.inputs.names[0], &input)?;
+        binding.bind_output(&session.outputs[0].name, output)?;
+        binding.run()?;
+        Ok(())
     }
 
     pub fn output_shapes(&self) -> Vec<Vec<i64>> {
+        // 非张量输出已在 build 中拒绝
         let mut shapes = Vec::new();
-        for output in &self.session.outputs {
+        for output in &self.session.lock().unwrap().outputs {
             if let ValueType::Tensor { shape, .. } = &output.output_type {
                 shapes.push(shape.to_vec().clone());
-            } else {
-                panic!("not support data format, {} - {}", file!(), line!());
             }
         }
         shapes
@@ -446,7 +682,7 @@ impl OrtBackend {
 
     pub fn output_dtypes(&self) -> Vec<TensorElementType> {
         let mut dtypes = Vec::new();
-        for output in &self.session.outputs {
+        for output in &self.session.lock().unwrap().outputs {
             if let ValueType::Tensor {
                 ty,
                 shape: _,
@@ -454,8 +690,6 @@ impl OrtBackend {
             } = &output.output_type
             {
                 dtypes.push(ty.clone());
-            } else {
-                panic!("not support data format, {} - {}", file!(), line!());
             }
         }
         dtypes
@@ -485,6 +719,15 @@ impl OrtBackend {
         self.inputs.sizes[0][1]
     }
 
+    /// 输入通道数: 1 为单通道 (热成像/灰度) 模型, 其余按 3 通道
+    pub fn channels(&self) -> usize {
+        if self.input_shapes()[0][1] == 1 {
+            1
+        } else {
+            3
+        }
+    }
+
     pub fn is_height_dynamic(&self) -> bool {
         self.input_shapes()[0][2] == -1
     }
@@ -497,6 +740,16 @@ impl OrtBackend {
         self.batch.opt
     }
 
+    /// 输入高宽为动态轴时按新尺寸绑定输入 (TensorRT 的优化配置按加载时的尺寸构建, 不支持)
+    pub fn set_input_size(&mut self, width: u32, height: u32) -> bool {
+        let dynamic = self.is_height_dynamic() && self.is_width_dynamic();
+        if !dynamic || matches!(self.ep, OrtEP::Trt(_)) {
+            return false;
+        }
+        self.inputs.sizes[0] = vec![height, width];
+        true
+    }
+
     pub fn is_batch_dynamic(&self) -> bool {
         self.input_shapes()[0][0] == -1
     }
@@ -531,8 +784,11 @@ impl OrtBackend {
             None => None,
             Some(kpt_string) => {
                 let re = Regex::new(r"([0-9]+), ([0-9]+)").unwrap();
-                let caps = re.captures(&kpt_string).unwrap();
-                Some(caps.get(1).unwrap().as_str().parse::<u32>().unwrap())
+                re.captures(&kpt_string)?
+                    .get(1)?
+                    .as_str()
+                    .parse::<u32>()
+                    .ok()
             }
         }
     }
@@ -608,3 +864,56 @@ impl OrtBackend {
         self.fetch_from_metadata("version")
     }
 }
+
+/// 整数输出 (EfficientNMS 的 num_dets/det_classes 等) 按 f32 读取, 浮点输出返回 None
+fn integer_output(
+    v: &ort::value::DynValue,
+    shape: &[i64],
+) -> Option<Result<Array<f32, IxDyn>, ndarray::ShapeError>> {
+    let data: Vec<f32> = if let Ok((_, slice)) = v.try_extract_tensor::<i32>() {
+        slice.iter().map(|&x| x as f32).collect()
+    } else if let Ok((_, slice)) = v.try_extract_tensor::<i64>() {
+        slice.iter().map(|&x| x as f32).collect()
+    } else {
+        return None;
+    };
+    let dims: Vec<usize> = shape.iter().map(|&d| d as usize).collect();
+    Some(Array::from_shape_vec(IxDyn(&dims), data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 相同键只加载一次, 不同键各自加载; 全部使用者释放后重新加载
+    #[test]
+    fn test_session_registry_shares_by_key() {
+        let registry: SessionRegistry<(&str, u32), u32> = SessionRegistry::new();
+        let mut loads = 0;
+        let mut load = |v| {
+            loads += 1;
+            Ok(v)
+        };
+
+        let (a, reused_a) = registry.get_or_create(("n.onnx", 640), || load(1)).unwrap();
+        let (b, reused_b) = registry.get_or_create(("n.onnx", 640), || load(2)).unwrap();
+        assert!(!reused_a && reused_b);
+        assert!(Arc::ptr_eq(&a, &b));
+        let (c, _) = registry.get_or_create(("n.onnx", 320), || load(3)).unwrap();
+        assert_eq!(*c.lock().unwrap(), 3);
+        assert_eq!(registry.len(), 2);
+
+        drop((a, b));
+        assert_eq!(registry.len(), 1);
+        let (d, reused_d) = registry.get_or_create(("n.onnx", 640), || load(4)).unwrap();
+        assert!(!reused_d);
+        assert_eq!(*d.lock().unwrap(), 4);
+        assert_eq!(loads, 3);
+
+        // 加载失败不留下记录
+        assert!(registry
+            .get_or_create(("s.onnx", 640), || Err(anyhow::anyhow!("load")))
+            .is_err());
+        assert_eq!(registry.len(), 2);
+    }
+}
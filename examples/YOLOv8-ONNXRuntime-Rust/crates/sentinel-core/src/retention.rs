@@ -0,0 +1,569 @@
+//! 存储保留策略 (Retention manager)
+//!
+//! 按目标 (快照目录、录像目录、报告文件等) 配置最长保留时间与最大占用, 后台线程定期扫描,
+//! 先删除超龄文件, 再按修改时间从旧到新删除直到不超过上限; 另有所有目标合计的总上限.
+//! 当前占用经调试 API (`GET /api/storage`) 与延迟指标文件 (Prometheus) 输出.
+//!
+//! 目标为单个文件时只统计占用, 从不删除文件; 若是 SQLite 事件库 (events.db), 按同样的时长与
+//! 容量上限从最旧的记录开始删除 `audit_log` 与 `visitors_hourly` 表中的行, 再整理 (VACUUM) 以归还空间.
+//! 配置文件不存在时不启用.
+//!
+//! 配置文件 (retention.json):
+//! ```json
+//! {
+//!   "targets": [
+//!     { "name": "snapshots", "path": "snapshots", "max_age_days": 30, "max_mb": 2048 },
+//!     { "name": "report", "path": "report.json" }
+//!   ],
+//!   "max_total_mb": 4096,
+//!   "interval_secs": 300
+//! }
+//! ```
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+/// 默认配置文件路径
+pub const RETENTION_CONFIG_PATH: &str = "retention.json";
+
+/// 最近一次清理后的占用 (后台线程更新, API/指标读取)
+static STATUS: OnceLock<Mutex<RetentionStatus>> = OnceLock::new();
+
+const MB: u64 = 1024 * 1024;
+
+/// SQLite 文件头
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// 事件库中按行清理的表 (表名, 时间列); 时间列为 RFC 3339 UTC 文本, 行号随写入递增
+const EVENT_TABLES: &[(&str, &str)] = &[("audit_log", "time"), ("visitors_hourly", "time")];
+
+/// 保留目标
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RetentionTarget {
+    pub name: String,
+    pub path: String,
+    #[serde(default)]
+    pub max_age_days: f32, // 0 不限
+    #[serde(default)]
+    pub max_mb: u64, // 0 不限
+    #[serde(default)]
+    pub extensions: Vec<String>, // 只管理这些扩展名 (不含点), 为空表示全部
+}
+
+/// 保留策略配置 (retention.json)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    pub targets: Vec<RetentionTarget>,
+    pub max_total_mb: u64, // 所有目标合计上限, 0 不限
+    pub interval_secs: u64,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            targets: Vec::new(),
+            max_total_mb: 0,
+            interval_secs: 300,
+        }
+    }
+}
+
+impl RetentionConfig {
+    /// 从文件加载配置, 文件不存在或解析失败时为 None (不删除任何文件)
+    pub fn load(path: &str) -> Option<Self> {
+        let json = fs::read_to_string(path).ok()?;
+        match serde_json::from_str::<Self>(&json) {
+            Ok(config) => {
+                println!(
+                    "✅ 存储保留策略已从 {} 加载 ({}个目标)",
+                    path,
+                    config.targets.len()
+                );
+                Some(config)
+            }
+            Err(e) => {
+                eprintln!("⚠️  存储保留策略解析失败: {}, 不启用", e);
+                None
+            }
+        }
+    }
+}
+
+/// 单个目标的占用与本次清理量
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct TargetUsage {
+    pub name: String,
+    pub path: String,
+    pub files: usize,
+    pub bytes: u64,
+    pub deleted_files: usize,
+    pub deleted_bytes: u64,
+    pub deleted_rows: usize, // 事件库删除的记录数
+}
+
+/// 一次清理后的存储状态
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RetentionStatus {
+    pub targets: Vec<TargetUsage>,
+    pub total_bytes: u64,
+    pub max_total_bytes: u64, // 0 不限
+    pub time: DateTime<Local>,
+}
+
+impl RetentionStatus {
+    /// Prometheus 文本格式 (追加在延迟指标之后)
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# HELP sentinel_storage_bytes Disk usage per retention target\n");
+        out.push_str("# TYPE sentinel_storage_bytes gauge\n");
+        for t in &self.targets {
+            let _ = writeln!(
+                out,
+                "sentinel_storage_bytes{{target=\"{}\"}} {}",
+                t.name, t.bytes
+            );
+        }
+        out.push_str("# HELP sentinel_storage_files Files per retention target\n");
+        out.push_str("# TYPE sentinel_storage_files gauge\n");
+        for t in &self.targets {
+            let _ = writeln!(
+                out,
+                "sentinel_storage_files{{target=\"{}\"}} {}",
+                t.name, t.files
+            );
+        }
+        out.push_str("# HELP sentinel_storage_limit_bytes Total retention limit (0 = unlimited)\n");
+        out.push_str("# TYPE sentinel_storage_limit_bytes gauge\n");
+        let _ = writeln!(out, "sentinel_storage_limit_bytes {}", self.max_total_bytes);
+        out
+    }
+}
+
+/// 最近一次清理后的存储状态, 保留策略未启用时为 None
+pub fn retention_status() -> Option<RetentionStatus> {
+    STATUS.get().map(|s| s.lock().unwrap().clone())
+}
+
+/// 受管文件
+#[derive(Clone, Debug)]
+struct Entry {
+    path: PathBuf,
+    bytes: u64,
+    modified: SystemTime,
+    target: usize,
+}
+
+/// 目标下的全部受管文件 (递归), 路径不存在时为空
+fn scan(target: &RetentionTarget, index: usize) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut dirs = vec![PathBuf::from(&target.path)];
+    while let Some(dir) = dirs.pop() {
+        let Ok(read) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in read.flatten() {
+            let path = entry.path();
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            if meta.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let managed = target.extensions.is_empty()
+                || path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| target.extensions.iter().any(|x| x.eq_ignore_ascii_case(e)));
+            if managed {
+                entries.push(Entry {
+                    path,
+                    bytes: meta.len(),
+                    modified: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                    target: index,
+                });
+            }
+        }
+    }
+    entries
+}
+
+/// 按配置清理一次, 返回清理后的状态
+pub fn enforce(config: &RetentionConfig, now: SystemTime) -> RetentionStatus {
+    let mut usage: Vec<TargetUsage> = config
+        .targets
+        .iter()
+        .map(|t| TargetUsage {
+            name: t.name.clone(),
+            path: t.path.clone(),
+            ..TargetUsage::default()
+        })
+        .collect();
+    let mut kept: Vec<Entry> = Vec::new();
+
+    for (index, target) in config.targets.iter().enumerate() {
+        // 单个文件只统计占用, 事件库按行清理
+        if Path::new(&target.path).is_file() {
+            if is_sqlite(Path::new(&target.path)) {
+                match prune_events_db(target, now) {
+                    Ok(rows) => usage[index].deleted_rows = rows,
+                    Err(e) => eprintln!("⚠️ 事件库 {} 清理失败: {}", target.path, e),
+                }
+            }
+            let bytes = fs::metadata(&target.path).map(|m| m.len()).unwrap_or(0);
+            usage[index].files = 1;
+            usage[index].bytes = bytes;
+            continue;
+        }
+        let mut entries = scan(target, index);
+        entries.sort_by_key(|e| e.modified);
+
+        let max_age = Duration::from_secs_f32(target.max_age_days.max(0.0) * 86_400.0);
+        let limit = target.max_mb * MB;
+        let mut bytes: u64 = entries.iter().map(|e| e.bytes).sum();
+        for entry in entries {
+            let age = now.duration_since(entry.modified).unwrap_or_default();
+            let expired = target.max_age_days > 0.0 && age > max_age;
+            let over = limit > 0 && bytes > limit;
+            if (expired || over) && remove(&entry, &mut usage) {
+                bytes -= entry.bytes;
+            } else {
+                kept.push(entry);
+            }
+        }
+    }
+
+    // 合计上限: 跨目标按修改时间从旧到新删除
+    let max_total = config.max_total_mb * MB;
+    let file_bytes: u64 = usage
+        .iter()
+        .zip(&config.targets)
+        .filter(|(_, t)| Path::new(&t.path).is_file())
+        .map(|(u, _)| u.bytes)
+        .sum();
+    let mut total = file_bytes + kept.iter().map(|e| e.bytes).sum::<u64>();
+    if max_total > 0 && total > max_total {
+        kept.sort_by_key(|e| e.modified);
+        let mut remaining = Vec::with_capacity(kept.len());
+        for entry in kept {
+            if total > max_total && remove(&entry, &mut usage) {
+                total -= entry.bytes;
+            } else {
+                remaining.push(entry);
+            }
+        }
+        kept = remaining;
+    }
+
+    for entry in &kept {
+        usage[entry.target].files += 1;
+        usage[entry.target].bytes += entry.bytes;
+    }
+    RetentionStatus {
+        total_bytes: usage.iter().map(|u| u.bytes).sum(),
+        targets: usage,
+        max_total_bytes: max_total,
+        time: Local::now(),
+    }
+}
+
+fn is_sqlite(path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .is_ok()
+        && &header == SQLITE_HEADER
+}
+
+/// 事件库按行清理: 先删除超龄记录, 再从最旧的记录开始删除直到已用页不超过上限, 返回删除的行数
+///
+/// 只删除各表行号最小的一段 (审计表的触发器只允许删除最旧的记录).
+fn prune_events_db(target: &RetentionTarget, now: SystemTime) -> rusqlite::Result<usize> {
+    let db = Connection::open(&target.path)?;
+    db.busy_timeout(Duration::from_secs(5))?;
+    let mut tables = Vec::new();
+    for &(table, column) in EVENT_TABLES {
+        let exists: bool = db.query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+            [table],
+            |row| row.get(0),
+        )?;
+        if exists {
+            tables.push((table, column));
+        }
+    }
+
+    let mut deleted = 0;
+    let max_age = Duration::from_secs_f32(target.max_age_days.max(0.0) * 86_400.0);
+    if let Some(cutoff) = now
+        .checked_sub(max_age)
+        .filter(|_| target.max_age_days > 0.0)
+    {
+        let cutoff = DateTime::<Utc>::from(cutoff).to_rfc3339_opts(SecondsFormat::Millis, true);
+        for &(table, column) in &tables {
+            deleted += db.execute(
+                &format!(
+                    "DELETE FROM {table} WHERE rowid <= \
+                     (SELECT MAX(rowid) FROM {table} WHERE {column} < ?1)"
+                ),
+                [&cutoff],
+            )?;
+        }
+    }
+
+    let limit = target.max_mb * MB;
+    loop {
+        let used: u64 = db.query_row(
+            "SELECT (page_count - freelist_count) * page_size \
+             FROM pragma_page_count(), pragma_freelist_count(), pragma_page_size()",
+            [],
+            |row| row.get::<_, i64>(0),
+        )? as u64;
+        if limit == 0 || used <= limit {
+            break;
+        }
+        // 每轮按超出比例删除各表最旧的记录
+        let mut removed = 0;
+        for &(table, _) in &tables {
+            let rows: i64 = db.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                row.get(0)
+            })?;
+            if rows == 0 {
+                continue;
+            }
+            let batch = (rows as u64 * (used - limit))
+                .div_ceil(used)
+                .clamp(1, rows as u64);
+            removed += db.execute(
+                &format!(
+                    "DELETE FROM {table} WHERE rowid <= \
+                     (SELECT rowid FROM {table} ORDER BY rowid LIMIT 1 OFFSET ?1)"
+                ),
+                [batch as i64 - 1],
+            )?;
+        }
+        if removed == 0 {
+            break;
+        }
+        deleted += removed;
+    }
+
+    if deleted > 0 {
+        db.execute_batch("VACUUM")?;
+    }
+    Ok(deleted)
+}
+
+/// 删除文件并计入清理量, 失败时保留
+fn remove(entry: &Entry, usage: &mut [TargetUsage]) -> bool {
+    match fs::remove_file(&entry.path) {
+        Ok(()) => {
+            usage[entry.target].deleted_files += 1;
+            usage[entry.target].deleted_bytes += entry.bytes;
+            true
+        }
+        Err(e) => {
+            eprintln!("⚠️ 删除 {} 失败: {}", entry.path.display(), e);
+            false
+        }
+    }
+}
+
+/// 启动后台清理线程 (进程内只启动一次)
+pub fn start(config: RetentionConfig) {
+    if STATUS.get().is_some() {
+        return;
+    }
+    let status = enforce(&config, SystemTime::now());
+    log(&status);
+    let _ = STATUS.set(Mutex::new(status));
+    let interval = Duration::from_secs(config.interval_secs.max(10));
+    let spawned = std::thread::Builder::new()
+        .name("retention".to_string())
+        .spawn(move || loop {
+            std::thread::sleep(interval);
+            let status = enforce(&config, SystemTime::now());
+            log(&status);
+            if let Some(current) = STATUS.get() {
+                *current.lock().unwrap() = status;
+            }
+        });
+    if let Err(e) = spawned {
+        eprintln!("❌ 存储保留线程启动失败: {}", e);
+    }
+}
+
+fn log(status: &RetentionStatus) {
+    for t in status.targets.iter().filter(|t| t.deleted_rows > 0) {
+        println!(
+            "🧹 {} 清理 {} 条记录, 剩余 {:.1}MB",
+            t.name,
+            t.deleted_rows,
+            t.bytes as f64 / MB as f64
+        );
+    }
+    for t in status.targets.iter().filter(|t| t.deleted_files > 0) {
+        println!(
+            "🧹 {} 清理 {} 个文件 ({:.1}MB), 剩余 {:.1}MB",
+            t.name,
+            t.deleted_files,
+            t.deleted_bytes as f64 / MB as f64,
+            t.bytes as f64 / MB as f64
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 在目录中创建指定大小与修改时间 (距 now 的秒数) 的文件
+    fn create(dir: &Path, name: &str, bytes: usize, age_secs: u64, now: SystemTime) {
+        let path = dir.join(name);
+        fs::write(&path, vec![0u8; bytes]).unwrap();
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(now - Duration::from_secs(age_secs))
+            .unwrap();
+    }
+
+    fn target(name: &str, dir: &Path) -> RetentionTarget {
+        RetentionTarget {
+            name: name.to_string(),
+            path: dir.to_string_lossy().into_owned(),
+            max_age_days: 0.0,
+            max_mb: 0,
+            extensions: Vec::new(),
+        }
+    }
+
+    /// 先删超龄文件, 再从最旧开始删到不超过目标上限; 非受管扩展名不动
+    #[test]
+    fn test_age_and_size_limits() {
+        let dir = std::env::temp_dir().join(format!("retention_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        let now = SystemTime::now();
+        let day = 86_400;
+        create(&dir, "old.jpg", 10, 40 * day, now);
+        create(&dir, "a.jpg", MB as usize, 3 * day, now);
+        create(&dir.join("sub"), "b.jpg", MB as usize, 2 * day, now);
+        create(&dir, "c.jpg", MB as usize, day, now);
+        create(&dir, "keep.txt", 10, 50 * day, now);
+
+        let config = RetentionConfig {
+            targets: vec![RetentionTarget {
+                max_age_days: 30.0,
+                max_mb: 2,
+                extensions: vec!["jpg".to_string()],
+                ..target("snapshots", &dir)
+            }],
+            ..RetentionConfig::default()
+        };
+        let status = enforce(&config, now);
+        let usage = &status.targets[0];
+        assert_eq!((usage.deleted_files, usage.files), (2, 2));
+        assert_eq!(usage.bytes, 2 * MB);
+        assert!(!dir.join("old.jpg").exists() && !dir.join("a.jpg").exists());
+        assert!(dir.join("sub/b.jpg").exists() && dir.join("c.jpg").exists());
+        assert!(dir.join("keep.txt").exists());
+        assert!(status.to_prometheus().contains(&format!(
+            "sentinel_storage_bytes{{target=\"snapshots\"}} {}",
+            2 * MB
+        )));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// 合计上限跨目标删除最旧的文件; 单文件目标只统计不删除
+    #[test]
+    fn test_total_limit_across_targets() {
+        let root = std::env::temp_dir().join(format!("retention_total_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let (a, b) = (root.join("a"), root.join("b"));
+        fs::create_dir_all(&a).unwrap();
+        fs::create_dir_all(&b).unwrap();
+        let now = SystemTime::now();
+        create(&a, "1", MB as usize, 400, now);
+        create(&b, "2", MB as usize, 300, now);
+        create(&a, "3", MB as usize, 200, now);
+        create(&root, "events.db", MB as usize, 500, now);
+
+        let config = RetentionConfig {
+            targets: vec![
+                target("a", &a),
+                target("b", &b),
+                target("db", &root.join("events.db")),
+            ],
+            max_total_mb: 2,
+            ..RetentionConfig::default()
+        };
+        let status = enforce(&config, now);
+        assert_eq!(status.total_bytes, 2 * MB);
+        assert!(!a.join("1").exists() && !b.join("2").exists());
+        assert!(a.join("3").exists() && root.join("events.db").exists());
+        assert_eq!(status.targets[2].files, 1);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    /// 事件库: 先删超龄审计记录, 再从最旧的记录删到不超过上限; 文件本身保留
+    #[test]
+    fn test_events_db_rows() {
+        let path = std::env::temp_dir().join(format!("retention_events_{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let now = SystemTime::now();
+        let time = |age_secs: u64| {
+            DateTime::<Utc>::from(now - Duration::from_secs(age_secs))
+                .to_rfc3339_opts(SecondsFormat::Millis, true)
+        };
+        let db = Connection::open(&path).unwrap();
+        db.execute_batch(crate::audit::SCHEMA).unwrap();
+        db.execute_batch("BEGIN").unwrap();
+        let detail = "x".repeat(1024);
+        for i in 0..3000u64 {
+            let age = if i < 10 { 40 * 86_400 } else { 3000 - i };
+            db.execute(
+                "INSERT INTO audit_log (time, source, action, detail) VALUES (?1, '\"ui\"', 'debug', ?2)",
+                rusqlite::params![time(age), format!("{}:{}", i, detail)],
+            )
+            .unwrap();
+        }
+        db.execute_batch("COMMIT").unwrap();
+        drop(db);
+        assert!(fs::metadata(&path).unwrap().len() > 2 * MB);
+
+        let config = RetentionConfig {
+            targets: vec![RetentionTarget {
+                max_age_days: 30.0,
+                max_mb: 1,
+                ..target("events", &path)
+            }],
+            ..RetentionConfig::default()
+        };
+        let status = enforce(&config, now);
+        let usage = &status.targets[0];
+        assert_eq!((usage.files, usage.deleted_files), (1, 0));
+        assert!(usage.bytes <= MB && usage.bytes == fs::metadata(&path).unwrap().len());
+
+        let db = Connection::open(&path).unwrap();
+        let (rows, oldest, newest): (i64, i64, i64) = db
+            .query_row(
+                "SELECT COUNT(*), MIN(id), MAX(id) FROM audit_log",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(usage.deleted_rows as i64, 3000 - rows);
+        assert!(rows > 0 && oldest > 10 && newest == 3000);
+        assert_eq!(newest - oldest + 1, rows);
+        drop(db);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,178 @@
+//! 运行时线程配置 - 通过JSON文件调整 ORT/rayon 线程数与核心绑定
+//!
+//! 默认情况下 rayon 占满所有核心, 与解码线程争抢 CPU. 配置在启动时通过
+//! [`RuntimeConfig::apply`] 生效一次:
+//! - ORT 会话的 intra/inter-op 线程数 (所有会话经 `ort_backend::session_builder` 创建)
+//! - 多路流使用同一检测模型时是否共享会话 (共享显存, 推理串行)
+//! - rayon 全局线程池大小 (缩放/预处理/NMS), 可绑定到指定核心
+//! - 解码/推理线程的核心绑定: Linux 上子线程继承父线程的亲和性,
+//!   因此 FFmpeg 解码线程与 ORT 线程池会落在各自线程绑定的核心上
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::sync::OnceLock;
+
+/// 默认配置文件路径
+pub const RUNTIME_CONFIG_PATH: &str = "runtime_config.json";
+
+/// 已生效的运行时配置
+static RUNTIME: OnceLock<RuntimeConfig> = OnceLock::new();
+
+/// 线程角色
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThreadRole {
+    Decode,
+    Infer,
+}
+
+/// 运行时线程配置 (0 或空列表表示使用默认值/不绑定)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuntimeConfig {
+    pub ort_intra_threads: usize, // ORT 算子内并行线程数
+    pub ort_inter_threads: usize, // ORT 算子间并行线程数
+    pub rayon_threads: usize,     // rayon 全局线程池大小
+    pub rayon_cores: Vec<usize>,  // rayon 工作线程绑定的核心 (按序轮流分配)
+    pub decode_cores: Vec<usize>, // 解码线程绑定的核心
+    pub infer_cores: Vec<usize>,  // 检测线程 (含 ORT 线程池) 绑定的核心
+    pub share_sessions: bool,     // 相同模型/后端/输入尺寸的检测线程共享一个 ORT 会话
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            ort_intra_threads: 4,
+            ort_inter_threads: 2,
+            rayon_threads: 0,
+            rayon_cores: Vec::new(),
+            decode_cores: Vec::new(),
+            infer_cores: Vec::new(),
+            share_sessions: true,
+        }
+    }
+}
+
+impl RuntimeConfig {
+    /// 从文件加载配置, 文件不存在时使用默认值
+    pub fn load(path: &str) -> Self {
+        match fs::read_to_string(path) {
+            Ok(json) => match serde_json::from_str(&json) {
+                Ok(config) => {
+                    println!("✅ 运行时配置已从 {} 加载", path);
+                    config
+                }
+                Err(e) => {
+                    eprintln!("⚠️  运行时配置解析失败: {}, 使用默认值", e);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// 保存配置到文件
+    pub fn save(&self, path: &str) {
+        match serde_json::to_string_pretty(self) {
+            Ok(json) => {
+                if let Err(e) = fs::write(path, json) {
+                    eprintln!("❌ 保存运行时配置失败: {}", e);
+                } else {
+                    println!("💾 运行时配置已保存到 {}", path);
+                }
+            }
+            Err(e) => eprintln!("❌ 序列化运行时配置失败: {}", e),
+        }
+    }
+
+    /// 全局生效 (进程内只生效一次, 须在首次使用 rayon 与创建 ORT 会话之前调用)
+    pub fn apply(self) {
+        let mut builder = rayon::ThreadPoolBuilder::new();
+        if self.rayon_threads > 0 {
+            builder = builder.num_threads(self.rayon_threads);
+        }
+        if !self.rayon_cores.is_empty() {
+            let cores = self.rayon_cores.clone();
+            builder = builder.start_handler(move |i| pin_to_cores(&[cores[i % cores.len()]]));
+        }
+        if let Err(e) = builder.build_global() {
+            eprintln!("⚠️ rayon 线程池已初始化, 配置未生效: {}", e);
+        }
+
+        println!(
+            "⚙️ 运行时: ORT intra={} inter={} 共享会话={} | rayon={} | 绑核 解码{:?} 推理{:?} rayon{:?}",
+            self.ort_intra_threads,
+            self.ort_inter_threads,
+            self.share_sessions,
+            rayon::current_num_threads(),
+            self.decode_cores,
+            self.infer_cores,
+            self.rayon_cores
+        );
+        if RUNTIME.set(self).is_err() {
+            eprintln!("⚠️ 运行时配置已生效, 忽略重复设置");
+        }
+    }
+
+    /// 某类线程绑定的核心
+    pub fn cores(&self, role: ThreadRole) -> &[usize] {
+        match role {
+            ThreadRole::Decode => &self.decode_cores,
+            ThreadRole::Infer => &self.infer_cores,
+        }
+    }
+}
+
+/// 当前生效的运行时配置 (未调用 apply 时为默认值)
+pub fn runtime() -> &'static RuntimeConfig {
+    RUNTIME.get_or_init(RuntimeConfig::default)
+}
+
+/// 按配置绑定当前线程 (未配置核心时不做任何事)
+pub fn pin_current_thread(role: ThreadRole) {
+    let cores = runtime().cores(role);
+    if !cores.is_empty() {
+        pin_to_cores(cores);
+        println!("📌 {:?} 线程绑定到核心 {:?}", role, cores);
+    }
+}
+
+/// 将当前线程绑定到一组核心
+#[cfg(target_os = "linux")]
+fn pin_to_cores(cores: &[usize]) {
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        for &core in cores {
+            libc::CPU_SET(core, &mut set);
+        }
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            eprintln!(
+                "⚠️ 绑定核心 {:?} 失败: {}",
+                cores,
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pin_to_cores(cores: &[usize]) {
+    eprintln!("⚠️ 当前平台不支持绑定核心 {:?}, 已忽略", cores);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 旧配置文件缺少的字段按默认值补齐
+    #[test]
+    fn test_partial_config_uses_defaults() {
+        let config: RuntimeConfig =
+            serde_json::from_str(r#"{"rayon_threads": 3, "decode_cores": [0, 1]}"#).unwrap();
+        assert_eq!(config.rayon_threads, 3);
+        assert_eq!(config.cores(ThreadRole::Decode), &[0, 1]);
+        assert!(config.cores(ThreadRole::Infer).is_empty());
+        assert_eq!(config.ort_intra_threads, 4);
+        assert_eq!(config.ort_inter_threads, 2);
+        assert!(config.share_sessions);
+    }
+}
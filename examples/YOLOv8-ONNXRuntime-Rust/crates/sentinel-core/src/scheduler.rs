@@ -0,0 +1,356 @@
+//! 布防计划 (Arm schedule)
+//!
+//! 按逻辑流配置布防时段 (星期 + 本地时间), 只在布防期间检测. 后台线程每秒求值一次,
+//! 状态变化时向对应检测线程发送 `ControlMessage::SetArmed`, 并在 xbus 上发布
+//! [`ArmStateChanged`] (打印日志, 面板显示最近事件). 控制面板与 HTTP API 可手动强制布防/撤防,
+//! 恢复自动后重新按时段求值.
+//!
+//! 配置文件 (schedule.json), 未单独配置的逻辑流使用 `windows`, 没有任何时段时始终布防:
+//! ```json
+//! {
+//!   "windows": [{ "start": "22:00", "end": "06:00" }],
+//!   "views": { "2": [{ "start": "08:00", "end": "18:00", "days": ["sat", "sun"] }] }
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::detection::types::ControlMessage;
+use crate::xbus;
+
+/// 默认布防计划文件
+pub const SCHEDULE_CONFIG_PATH: &str = "schedule.json";
+
+/// 求值间隔
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 全局调度器 (启动后设置, 面板/API 经此手动覆盖)
+static SCHEDULER: OnceLock<Mutex<Scheduler>> = OnceLock::new();
+
+/// 布防时段 [start, end), 结束早于开始时跨越午夜 (星期按开始当天计)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ArmWindow {
+    pub start: String, // 本地时间 "HH:MM"
+    pub end: String,
+    #[serde(default)]
+    pub days: Vec<String>, // 星期 ("mon".."sun"), 为空表示每天
+}
+
+impl ArmWindow {
+    fn times(&self) -> Option<(NaiveTime, NaiveTime)> {
+        let parse = |s: &str| NaiveTime::parse_from_str(s, "%H:%M").ok();
+        Some((parse(&self.start)?, parse(&self.end)?))
+    }
+
+    fn weekdays(&self) -> Option<Vec<Weekday>> {
+        self.days.iter().map(|d| d.parse().ok()).collect()
+    }
+
+    /// 时间与星期格式是否正确
+    pub fn is_valid(&self) -> bool {
+        self.times().is_some() && self.weekdays().is_some()
+    }
+
+    /// 时刻是否在时段内, 格式错误时不匹配
+    pub fn contains(&self, now: NaiveDateTime) -> bool {
+        let (Some((start, end)), Some(days)) = (self.times(), self.weekdays()) else {
+            return false;
+        };
+        let on = |day: Weekday| days.is_empty() || days.contains(&day);
+        let time = now.time();
+        let today = now.weekday();
+        if start <= end {
+            on(today) && start <= time && time < end
+        } else {
+            (on(today) && time >= start) || (on(today.pred()) && time < end)
+        }
+    }
+}
+
+/// 布防计划配置
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScheduleConfig {
+    pub windows: Vec<ArmWindow>,             // 默认时段
+    pub views: HashMap<u32, Vec<ArmWindow>>, // 按逻辑流单独配置的时段
+}
+
+impl ScheduleConfig {
+    /// 从文件加载, 文件不存在时为空 (始终布防); 格式错误的时段忽略
+    pub fn load(path: &str) -> Self {
+        let Ok(json) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+        match serde_json::from_str::<Self>(&json) {
+            Ok(mut config) => {
+                let valid = |w: &ArmWindow| {
+                    if !w.is_valid() {
+                        eprintln!(
+                            "⚠️  布防时段无效 ({}-{} {:?}), 已忽略",
+                            w.start, w.end, w.days
+                        );
+                    }
+                    w.is_valid()
+                };
+                config.windows.retain(valid);
+                for windows in config.views.values_mut() {
+                    windows.retain(valid);
+                }
+                println!(
+                    "✅ 布防计划已从 {} 加载 ({}个默认时段, {}个逻辑流单独配置)",
+                    path,
+                    config.windows.len(),
+                    config.views.len()
+                );
+                config
+            }
+            Err(e) => {
+                eprintln!("⚠️  布防计划解析失败: {}, 始终布防", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// 逻辑流在该时刻是否处于布防时段 (没有任何时段时始终布防)
+    pub fn armed(&self, view: u32, now: NaiveDateTime) -> bool {
+        let windows = self.views.get(&view).unwrap_or(&self.windows);
+        windows.is_empty() || windows.iter().any(|w| w.contains(now))
+    }
+}
+
+/// 布防模式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArmMode {
+    /// 按计划
+    #[default]
+    Auto,
+    /// 手动强制布防
+    Armed,
+    /// 手动强制撤防
+    Disarmed,
+}
+
+/// 布防状态变化 (调度器 → 日志/面板)
+#[derive(Clone, Debug)]
+pub struct ArmStateChanged {
+    pub view: u32,
+    pub armed: bool,
+    pub mode: ArmMode, // 变化时的模式 (Auto 为计划触发, 其余为手动)
+    pub time: DateTime<Local>,
+}
+
+/// 逻辑流的布防状态
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ViewArmState {
+    pub view: u32,
+    pub armed: bool,
+}
+
+/// 调度器状态 (面板与 API 显示)
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ScheduleStatus {
+    pub mode: ArmMode,
+    pub views: Vec<ViewArmState>,
+}
+
+/// 布防调度器
+pub struct Scheduler {
+    config: ScheduleConfig,
+    mode: ArmMode,
+    armed: Vec<(u32, bool)>, // 各逻辑流当前状态
+}
+
+impl Scheduler {
+    /// 初始状态均为布防 (检测线程的默认状态), 首次求值时再按计划切换
+    pub fn new(config: ScheduleConfig, views: &[u32]) -> Self {
+        Self {
+            config,
+            mode: ArmMode::Auto,
+            armed: views.iter().map(|&view| (view, true)).collect(),
+        }
+    }
+
+    pub fn mode(&self) -> ArmMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: ArmMode) {
+        self.mode = mode;
+    }
+
+    pub fn is_armed(&self, view: u32) -> bool {
+        self.armed
+            .iter()
+            .find(|(v, _)| *v == view)
+            .is_none_or(|(_, armed)| *armed)
+    }
+
+    /// 求值各逻辑流的布防状态, 返回发生变化的逻辑流
+    pub fn tick(&mut self, now: NaiveDateTime) -> Vec<(u32, bool)> {
+        let mut changed = Vec::new();
+        for (view, armed) in &mut self.armed {
+            let target = match self.mode {
+                ArmMode::Auto => self.config.armed(*view, now),
+                ArmMode::Armed => true,
+                ArmMode::Disarmed => false,
+            };
+            if target != *armed {
+                *armed = target;
+                changed.push((*view, target));
+            }
+        }
+        changed
+    }
+
+    pub fn status(&self) -> ScheduleStatus {
+        ScheduleStatus {
+            mode: self.mode,
+            views: self
+                .armed
+                .iter()
+                .map(|&(view, armed)| ViewArmState { view, armed })
+                .collect(),
+        }
+    }
+
+    /// 设为全局调度器并启动求值线程 (只能启动一次)
+    pub fn start(self) {
+        if SCHEDULER.set(Mutex::new(self)).is_err() {
+            eprintln!("⚠️ 布防调度器已启动, 忽略重复启动");
+            return;
+        }
+        println!("⏰ 布防调度器启动");
+        let spawned = std::thread::Builder::new()
+            .name("arm-scheduler".to_string())
+            .spawn(|| loop {
+                evaluate();
+                std::thread::sleep(TICK_INTERVAL);
+            });
+        if let Err(e) = spawned {
+            eprintln!("❌ 布防调度线程启动失败: {}", e);
+        }
+    }
+}
+
+/// 按当前时间求值全局调度器, 通知状态变化的检测线程
+fn evaluate() {
+    let Some(scheduler) = SCHEDULER.get() else {
+        return;
+    };
+    let now = Local::now();
+    // 先释放锁再通知, 订阅回调中可以查询调度器状态
+    let (mode, changed) = {
+        let mut scheduler = scheduler.lock().unwrap();
+        (scheduler.mode(), scheduler.tick(now.naive_local()))
+    };
+    for (view, armed) in changed {
+        let how = if mode == ArmMode::Auto {
+            "计划"
+        } else {
+            "手动"
+        };
+        println!(
+            "{} 逻辑流 {} 已{} ({})",
+            if armed { "🔔" } else { "🔕" },
+            view,
+            if armed { "布防" } else { "撤防" },
+            how
+        );
+        ControlMessage::SetArmed(armed).post_to(view);
+        xbus::post(ArmStateChanged {
+            view,
+            armed,
+            mode,
+            time: now,
+        });
+    }
+}
+
+/// 逻辑流当前是否布防 (调度器未启动时始终布防), 检测线程启动时读取初始状态
+pub fn is_armed(view: u32) -> bool {
+    SCHEDULER
+        .get()
+        .is_none_or(|s| s.lock().unwrap().is_armed(view))
+}
+
+/// 调度器状态, 未启动时为 None
+pub fn schedule_status() -> Option<ScheduleStatus> {
+    SCHEDULER.get().map(|s| s.lock().unwrap().status())
+}
+
+/// 手动覆盖布防模式并立即求值, 调度器未启动时返回 false
+pub fn set_arm_mode(mode: ArmMode) -> bool {
+    let Some(scheduler) = SCHEDULER.get() else {
+        return false;
+    };
+    println!("⏰ 布防模式: {:?}", mode);
+    scheduler.lock().unwrap().set_mode(mode);
+    evaluate();
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, h: u32, m: u32) -> NaiveDateTime {
+        // 2024-01-01 为星期一
+        NaiveDate::from_ymd_opt(2024, 1, day)
+            .unwrap()
+            .and_hms_opt(h, m, 0)
+            .unwrap()
+    }
+
+    /// 跨午夜的时段星期按开始当天计, 格式错误的时段不匹配
+    #[test]
+    fn test_arm_window() {
+        let window = ArmWindow {
+            start: "22:00".to_string(),
+            end: "06:00".to_string(),
+            days: vec!["fri".to_string()],
+        };
+        assert!(window.contains(at(5, 23, 0))); // 周五夜间
+        assert!(window.contains(at(6, 5, 59))); // 周六凌晨仍属周五的时段
+        assert!(!window.contains(at(6, 6, 0)));
+        assert!(!window.contains(at(6, 23, 0))); // 周六夜间
+        assert!(!window.contains(at(4, 3, 0))); // 周四凌晨属周三的时段
+
+        let invalid = ArmWindow {
+            days: vec!["someday".to_string()],
+            ..window
+        };
+        assert!(!invalid.is_valid());
+        assert!(!invalid.contains(at(5, 23, 0)));
+    }
+
+    /// 按逻辑流求值, 只报告变化; 手动覆盖优先于计划, 恢复自动后重新按计划
+    #[test]
+    fn test_scheduler_tick() {
+        let config: ScheduleConfig = serde_json::from_str(
+            r#"{
+                "windows": [{"start": "22:00", "end": "06:00"}],
+                "views": {"2": []}
+            }"#,
+        )
+        .unwrap();
+        let mut scheduler = Scheduler::new(config, &[1, 2]);
+        assert_eq!(scheduler.tick(at(1, 12, 0)), [(1, false)]);
+        assert_eq!(scheduler.tick(at(1, 13, 0)), []);
+        assert_eq!(scheduler.tick(at(1, 22, 0)), [(1, true)]);
+
+        scheduler.set_mode(ArmMode::Disarmed);
+        assert_eq!(scheduler.tick(at(1, 23, 0)), [(1, false), (2, false)]);
+        scheduler.set_mode(ArmMode::Auto);
+        assert_eq!(scheduler.tick(at(1, 23, 0)), [(1, true), (2, true)]);
+        assert!(scheduler.is_armed(2));
+        assert!(scheduler.is_armed(7)); // 未知逻辑流不受计划控制
+    }
+}
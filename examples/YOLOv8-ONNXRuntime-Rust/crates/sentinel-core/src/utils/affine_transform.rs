@@ -1,6 +1,7 @@
 /// 仿射变换工具模块
 /// 实现类似于 cv2::warpAffine 的功能
 use ndarray::Array2;
+use rayon::prelude::*;
 
 /// 仿射变换矩阵 (2x3)
 /// | a11 a12 b1 |
@@ -250,6 +251,58 @@ pub fn warp_affine_rgb(
     dst
 }
 
+/// 按逐像素映射表重采样单通道平面 (类似 cv2::remap)
+///
+/// 仿射变换只能表达全局线性映射; 鱼眼去畸变等径向模型需要逐像素的源坐标,
+/// 由调用方预先计算映射表, 此处只负责插值与边界处理
+///
+/// # 参数
+/// - `src`: 源平面 (height x width, 无行填充)
+/// - `map`: 每个目标像素对应的源坐标 (非有限值视为越界)
+/// - `dst`: 目标平面, 长度与 `map` 相同, 行宽为 `dst_width`
+#[allow(clippy::too_many_arguments)]
+pub fn remap_plane(
+    src: &[u8],
+    src_width: usize,
+    src_height: usize,
+    map: &[(f32, f32)],
+    dst: &mut [u8],
+    dst_width: usize,
+    interpolation: InterpolationMethod,
+    border_mode: BorderMode,
+) {
+    assert_eq!(map.len(), dst.len(), "映射表与目标平面尺寸不一致");
+    if dst_width == 0 {
+        return;
+    }
+    dst.par_chunks_mut(dst_width)
+        .zip(map.par_chunks(dst_width))
+        .for_each(|(row, map_row)| {
+            for (px, &(x, y)) in row.iter_mut().zip(map_row) {
+                *px = if !x.is_finite() || !y.is_finite() {
+                    match border_mode {
+                        BorderMode::Constant(val) => val,
+                        _ => 0,
+                    }
+                } else {
+                    match interpolation {
+                        InterpolationMethod::Nearest => get_border_pixel_plane(
+                            src,
+                            x.round() as i32,
+                            y.round() as i32,
+                            src_width,
+                            src_height,
+                            border_mode,
+                        ),
+                        InterpolationMethod::Bilinear => {
+                            get_pixel_bilinear_plane(src, x, y, src_width, src_height, border_mode)
+                        }
+                    }
+                };
+            }
+        });
+}
+
 /// 最近邻插值 (灰度图)
 fn get_pixel_nearest(
     src: &Array2<u8>,
@@ -315,6 +368,56 @@ fn get_border_pixel(
     }
 }
 
+/// 双线性插值 (单通道平面)
+fn get_pixel_bilinear_plane(
+    src: &[u8],
+    x: f32,
+    y: f32,
+    width: usize,
+    height: usize,
+    border_mode: BorderMode,
+) -> u8 {
+    let x0 = x.floor() as i32;
+    let y0 = y.floor() as i32;
+    let x1 = x0 + 1;
+    let y1 = y0 + 1;
+
+    let fx = x - x0 as f32;
+    let fy = y - y0 as f32;
+
+    let p00 = get_border_pixel_plane(src, x0, y0, width, height, border_mode) as f32;
+    let p01 = get_border_pixel_plane(src, x0, y1, width, height, border_mode) as f32;
+    let p10 = get_border_pixel_plane(src, x1, y0, width, height, border_mode) as f32;
+    let p11 = get_border_pixel_plane(src, x1, y1, width, height, border_mode) as f32;
+
+    let v0 = p00 * (1.0 - fx) + p10 * fx;
+    let v1 = p01 * (1.0 - fx) + p11 * fx;
+    let result = v0 * (1.0 - fy) + v1 * fy;
+
+    result.clamp(0.0, 255.0) as u8
+}
+
+/// 边界处理 (单通道平面)
+fn get_border_pixel_plane(
+    src: &[u8],
+    x: i32,
+    y: i32,
+    width: usize,
+    height: usize,
+    border_mode: BorderMode,
+) -> u8 {
+    let (bx, by) = handle_border(x, y, width, height, border_mode);
+
+    if bx >= 0 && bx < width as i32 && by >= 0 && by < height as i32 {
+        src[by as usize * width + bx as usize]
+    } else {
+        match border_mode {
+            BorderMode::Constant(val) => val,
+            _ => 0,
+        }
+    }
+}
+
 /// 最近邻插值 (RGB)
 fn get_pixel_nearest_rgb(
     src: &[u8],
@@ -557,4 +660,23 @@ mod tests {
         assert!((composed.b1).abs() < 1e-6);
         assert!((composed.b2).abs() < 1e-6);
     }
+
+    #[test]
+    fn test_remap_plane() {
+        // 2x2 源平面, 映射: 水平翻转 / 两像素中点 / 越界 / NaN
+        let src = [10u8, 20, 30, 40];
+        let map = [(1.0, 0.0), (0.5, 1.0), (5.0, 0.0), (f32::NAN, 0.0)];
+        let mut dst = [0u8; 4];
+        remap_plane(
+            &src,
+            2,
+            2,
+            &map,
+            &mut dst,
+            2,
+            InterpolationMethod::Bilinear,
+            BorderMode::Constant(7),
+        );
+        assert_eq!(dst, [20, 35, 7, 7]);
+    }
 }
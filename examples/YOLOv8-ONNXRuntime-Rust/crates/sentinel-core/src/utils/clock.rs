@@ -0,0 +1,336 @@
+//! 时间戳、时区与摄像头时钟漂移
+//!
+//! 帧与检测结果同时携带两种时间 (见 [`crate::detection::trace::FrameTrace`]):
+//! - 单调时钟 (`Instant`): 延迟统计与帧间隔, 不受校时影响, 跨线程可直接相减
+//! - 墙上时钟 (`DateTime<Utc>`): 事件、导出与元数据一律存 UTC, 显示时换算到
+//!   `--timezone` 设置的时区 (默认系统本地时区)
+//!
+//! 文件名、报表、通知与界面上的时间都经 [`format`] / [`to_display`] 换算, 不要直接用
+//! `Local::now()` 格式化. IANA 时区 (`Asia/Shanghai`) 按 chrono-tz 的规则处理夏令时
+//!
+//! [`DriftEstimator`] 比较摄像头 RTP 时间戳 (解码帧 pts) 与主机单调时钟的走时:
+//! 摄像头未做 NTP 校时或晶振偏差大时两者逐渐分开, 按媒体时间对齐的录像与事件随之错位
+
+use std::fmt;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::i18n::tr;
+
+/// 漂移超过该值 (毫秒) 时告警
+pub const DRIFT_WARN_MS: f64 = 200.0;
+
+/// 启动后用于确定基线的时长: 取这段时间内网络延迟最小的帧作为对齐点
+const DRIFT_WARMUP: Duration = Duration::from_secs(2);
+
+/// 漂移报告间隔
+const DRIFT_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 媒体时间与主机时间相差超过该值 (秒) 视为时间戳跳变 (摄像头重启/码流切换), 重新对齐
+const DRIFT_DISCONTINUITY_SECS: f64 = 10.0;
+
+/// 显示/导出用的时区
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DisplayZone {
+    #[default]
+    Local, // 系统本地时区
+    Fixed(FixedOffset), // 固定偏移, 无夏令时
+    Named(Tz),          // IANA 时区, 按规则切换夏令时
+}
+
+impl DisplayZone {
+    /// 解析时区: `local` / `UTC` / `+08:00` / `-05:30` / `Asia/Shanghai`
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        if text.is_empty() || text.eq_ignore_ascii_case("local") {
+            return Ok(DisplayZone::Local);
+        }
+        if text.starts_with(['+', '-']) || text.eq_ignore_ascii_case("z") {
+            return parse_offset(text).map(DisplayZone::Fixed);
+        }
+        if text.eq_ignore_ascii_case("utc") {
+            return Ok(DisplayZone::Fixed(Utc.fix()));
+        }
+        text.parse::<Tz>()
+            .map(DisplayZone::Named)
+            .map_err(|e| anyhow!("未知时区 {}: {}", text, e))
+    }
+
+    /// UTC 时刻 utc 在该时区的偏移
+    pub fn offset_at(&self, utc: &NaiveDateTime) -> FixedOffset {
+        match self {
+            DisplayZone::Local => Local.offset_from_utc_datetime(utc).fix(),
+            DisplayZone::Fixed(offset) => *offset,
+            DisplayZone::Named(tz) => tz.offset_from_utc_datetime(utc).fix(),
+        }
+    }
+
+    /// 换算到该时区
+    pub fn convert<T: TimeZone>(&self, time: DateTime<T>) -> DateTime<FixedOffset> {
+        time.with_timezone(&self.offset_at(&time.naive_utc()))
+    }
+
+    /// 该时区的本地时间换算为 UTC; 夏令时回拨的重复时刻取较早的一个, 跳过的时刻为 None
+    pub fn from_local(&self, local: &NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            DisplayZone::Local => Local
+                .from_local_datetime(local)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc)),
+            DisplayZone::Fixed(offset) => offset
+                .from_local_datetime(local)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc)),
+            DisplayZone::Named(tz) => tz
+                .from_local_datetime(local)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc)),
+        }
+    }
+}
+
+impl fmt::Display for DisplayZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisplayZone::Local => write!(f, "local"),
+            DisplayZone::Fixed(offset) => write!(f, "UTC{}", offset),
+            DisplayZone::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+/// 进程级显示时区 (启动参数 `--timezone`)
+static DISPLAY_ZONE: RwLock<DisplayZone> = RwLock::new(DisplayZone::Local);
+
+/// 设置显示/导出用的时区
+pub fn set_display_zone(zone: DisplayZone) {
+    *DISPLAY_ZONE.write().unwrap() = zone;
+}
+
+/// 当前的显示/导出时区
+pub fn display_zone() -> DisplayZone {
+    *DISPLAY_ZONE.read().unwrap()
+}
+
+/// 任意时区的时间换算到显示时区
+pub fn to_display<T: TimeZone>(time: DateTime<T>) -> DateTime<FixedOffset> {
+    display_zone().convert(time)
+}
+
+/// 显示时区的当前时间
+pub fn now() -> DateTime<FixedOffset> {
+    to_display(Utc::now())
+}
+
+/// 换算到显示时区后按 strftime 格式化 (文件名、报表、通知)
+pub fn format<T: TimeZone>(time: DateTime<T>, fmt: &str) -> String {
+    to_display(time).format(fmt).to_string()
+}
+
+/// 界面上的日期时间, 格式随界面语言 (`time.datetime`)
+pub fn format_ui<T: TimeZone>(time: DateTime<T>) -> String {
+    format(time, tr("time.datetime"))
+}
+
+/// 显示时区的本地时间 (如按本地时间命名的录像分段) 换算为 UTC
+pub fn from_display(local: &NaiveDateTime) -> Option<DateTime<Utc>> {
+    display_zone().from_local(local)
+}
+
+/// 解析时区偏移: `+08:00` / `-05:30` / `+8` / `UTC` / `Z`
+pub fn parse_offset(text: &str) -> Result<FixedOffset> {
+    let text = text.trim();
+    if text.eq_ignore_ascii_case("utc") || text.eq_ignore_ascii_case("z") {
+        return Ok(Utc.fix());
+    }
+    let (sign, rest) = match text.split_at_checked(1) {
+        Some(("+", rest)) => (1, rest),
+        Some(("-", rest)) => (-1, rest),
+        _ => bail!("时区偏移须以 + 或 - 开头: {}", text),
+    };
+    let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+    let hours: i32 = hours
+        .parse()
+        .with_context(|| format!("无效的时区偏移: {}", text))?;
+    let minutes: i32 = minutes
+        .parse()
+        .with_context(|| format!("无效的时区偏移: {}", text))?;
+    if !(0..60).contains(&minutes) {
+        bail!("无效的时区偏移: {}", text);
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+        .with_context(|| format!("时区偏移超出范围: {}", text))
+}
+
+/// 一次漂移报告
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DriftReport {
+    pub offset_ms: f64, // 摄像头时钟相对主机超前的毫秒数 (负数为落后)
+    pub ppm: f64,       // 平均走时偏差 (百万分之一)
+}
+
+impl DriftReport {
+    /// 是否超过告警阈值
+    pub fn exceeds(&self, limit_ms: f64) -> bool {
+        self.offset_ms.abs() > limit_ms
+    }
+}
+
+/// 摄像头媒体时钟相对主机单调时钟的漂移估计
+///
+/// 每帧的 `主机走时 - 媒体走时` 包含网络/解码延迟与两个时钟的差. 延迟只会让帧晚到,
+/// 所以预热期内取最小值作为基线, 之后每个报告间隔内取最小值与基线比较, 抖动不计入漂移
+#[derive(Clone, Debug)]
+pub struct DriftEstimator {
+    // 首帧的主机时刻与媒体时间 (秒), 首帧前 origin 为 None
+    start: Instant,
+    origin: Option<f64>,
+    last_media: f64,
+    // 预热期基线 (秒) 与确定基线的时刻, 预热结束前为 None
+    baseline: Option<(f64, Instant)>,
+    warmup_min: f64,
+    window_min: f64,
+    window_start: Instant,
+}
+
+impl Default for DriftEstimator {
+    fn default() -> Self {
+        Self::new(Instant::now())
+    }
+}
+
+impl DriftEstimator {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            start: now,
+            origin: None,
+            last_media: 0.0,
+            baseline: None,
+            warmup_min: f64::MAX,
+            window_min: f64::MAX,
+            window_start: now,
+        }
+    }
+
+    /// 记入一帧 (媒体时间秒, 主机到达时刻), 每个报告间隔返回一次漂移
+    pub fn observe(&mut self, media_secs: f64, host: Instant) -> Option<DriftReport> {
+        let Some(origin) = self.origin else {
+            *self = Self::new(host);
+            self.origin = Some(media_secs);
+            self.last_media = media_secs;
+            self.warmup_min = 0.0;
+            return None;
+        };
+        let host_secs = host.saturating_duration_since(self.start).as_secs_f64();
+        let delay = host_secs - (media_secs - origin);
+
+        // 时间戳回退或跳变 (摄像头重启/码流切换): 以本帧重新对齐
+        let reference = self.baseline.map_or(self.warmup_min, |(b, _)| b);
+        if media_secs < self.last_media || (delay - reference).abs() > DRIFT_DISCONTINUITY_SECS {
+            self.origin = None;
+            return self.observe(media_secs, host);
+        }
+        self.last_media = media_secs;
+
+        let Some((baseline, since)) = self.baseline else {
+            self.warmup_min = self.warmup_min.min(delay);
+            if host.saturating_duration_since(self.window_start) >= DRIFT_WARMUP {
+                self.baseline = Some((self.warmup_min, host));
+                self.window_start = host;
+            }
+            return None;
+        };
+        self.window_min = self.window_min.min(delay);
+        if host.saturating_duration_since(self.window_start) < DRIFT_REPORT_INTERVAL {
+            return None;
+        }
+        let offset = baseline - self.window_min;
+        let elapsed = host.saturating_duration_since(since).as_secs_f64();
+        self.window_min = f64::MAX;
+        self.window_start = host;
+        Some(DriftReport {
+            offset_ms: offset * 1000.0,
+            ppm: offset / elapsed.max(1e-9) * 1e6,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 时区偏移解析与换算
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("+08:00").unwrap().local_minus_utc(), 8 * 3600);
+        assert_eq!(
+            parse_offset("-05:30").unwrap().local_minus_utc(),
+            -(5 * 3600 + 1800)
+        );
+        assert_eq!(parse_offset("+9").unwrap().local_minus_utc(), 9 * 3600);
+        assert_eq!(parse_offset("UTC").unwrap().local_minus_utc(), 0);
+        assert!(parse_offset("08:00").is_err());
+        assert!(parse_offset("+08:75").is_err());
+
+        let zone = DisplayZone::parse("+08:00").unwrap();
+        let time = DateTime::from_timestamp(1_760_000_000, 0).unwrap();
+        assert_eq!(zone.convert(time).to_rfc3339(), "2025-10-09T16:53:20+08:00");
+    }
+
+    /// IANA 时区按日期切换夏令时; 本地时间换算回 UTC, 跳过的时刻无效
+    #[test]
+    fn test_named_zone() {
+        assert_eq!(DisplayZone::parse("local").unwrap(), DisplayZone::Local);
+        assert_eq!(
+            DisplayZone::parse("UTC").unwrap(),
+            DisplayZone::Fixed(Utc.fix())
+        );
+        assert!(DisplayZone::parse("Mars/Olympus").is_err());
+
+        let zone = DisplayZone::parse("America/New_York").unwrap();
+        assert_eq!(zone.to_string(), "America/New_York");
+        let winter = DateTime::from_timestamp(1_736_942_400, 0).unwrap(); // 2025-01-15 12:00Z
+        let summer = DateTime::from_timestamp(1_752_580_800, 0).unwrap(); // 2025-07-15 12:00Z
+        assert_eq!(
+            zone.convert(winter).format("%H:%M %z").to_string(),
+            "07:00 -0500"
+        );
+        assert_eq!(
+            zone.convert(summer).format("%H:%M %z").to_string(),
+            "08:00 -0400"
+        );
+
+        let local = zone.convert(summer).naive_local();
+        assert_eq!(zone.from_local(&local), Some(summer));
+        // 2025-03-09 02:30 在纽约被夏令时跳过
+        let skipped = NaiveDateTime::parse_from_str("2025-03-09 02:30", "%Y-%m-%d %H:%M").unwrap();
+        assert_eq!(zone.from_local(&skipped), None);
+    }
+
+    /// 摄像头时钟快 1000ppm: 抖动不计入, 漂移随时间累积; 时间戳回退后重新对齐
+    #[test]
+    fn test_drift_estimator() {
+        let t0 = Instant::now();
+        let mut drift = DriftEstimator::new(t0);
+        let mut reports = Vec::new();
+        for i in 0..250u64 {
+            let host = t0 + Duration::from_millis(i * 40 + (i % 3) * 15);
+            let media = i as f64 * 0.040 * 1.001;
+            reports.extend(drift.observe(media, host));
+        }
+        // 10 秒, 预热 2 秒后每秒报告一次
+        assert_eq!(reports.len(), 7);
+        let last = reports.last().unwrap();
+        assert!((6.0..9.0).contains(&last.offset_ms), "{:?}", last);
+        assert!((last.ppm - 1000.0).abs() < 150.0, "{:?}", last);
+        assert!(!last.exceeds(DRIFT_WARN_MS));
+
+        let later = t0 + Duration::from_secs(11);
+        assert_eq!(drift.observe(0.0, later), None);
+        assert_eq!(drift.origin, Some(0.0));
+    }
+}
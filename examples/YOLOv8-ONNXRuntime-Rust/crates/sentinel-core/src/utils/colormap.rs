@@ -0,0 +1,129 @@
+//! 伪彩色调色板 (热成像显示)
+//!
+//! 热成像/灰度画面按 BT.601 亮度查 256 级色表着色, 只影响渲染纹理, 检测输入不变.
+//! 铁红 (ironbow) 色表由若干控制点线性插值: 黑 → 深蓝紫 → 品红 → 橙 → 黄 → 白
+
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::PreprocessSpec;
+
+/// 铁红色表控制点 (位置 0~1, RGB)
+const IRONBOW_STOPS: [(f32, [u8; 3]); 7] = [
+    (0.00, [0, 0, 0]),
+    (0.15, [32, 0, 140]),
+    (0.35, [145, 0, 160]),
+    (0.55, [225, 60, 45]),
+    (0.75, [250, 150, 0]),
+    (0.90, [255, 220, 60]),
+    (1.00, [255, 255, 255]),
+];
+
+/// 显示调色板
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Palette {
+    #[default]
+    None, // 原始画面
+    Ironbow, // 铁红伪彩色
+}
+
+impl Palette {
+    pub const ALL: [Palette; 2] = [Palette::None, Palette::Ironbow];
+
+    /// 256 级色表, 原始画面为 None
+    pub fn lut(self) -> Option<&'static [[u8; 3]; 256]> {
+        static IRONBOW: OnceLock<[[u8; 3]; 256]> = OnceLock::new();
+        match self {
+            Palette::None => None,
+            Palette::Ironbow => Some(IRONBOW.get_or_init(|| interpolate(&IRONBOW_STOPS))),
+        }
+    }
+
+    /// 按亮度对 RGBA 帧着色 (保留 alpha), 原始画面时不拷贝
+    pub fn colorize(self, rgba: &[u8]) -> Cow<'_, [u8]> {
+        let Some(lut) = self.lut() else {
+            return Cow::Borrowed(rgba);
+        };
+        let mut out = rgba.to_vec();
+        out.par_chunks_exact_mut(4).for_each(|px| {
+            let color = lut[PreprocessSpec::luma([px[0], px[1], px[2]]) as usize];
+            px[..3].copy_from_slice(&color);
+        });
+        Cow::Owned(out)
+    }
+
+    /// 着色结果直接写入 dst (长度与 rgba 相同, 如映射的纹理上传缓冲), 原始画面时直接拷贝
+    pub fn colorize_into(self, rgba: &[u8], dst: &mut [u8]) {
+        let Some(lut) = self.lut() else {
+            dst.copy_from_slice(rgba);
+            return;
+        };
+        dst.par_chunks_exact_mut(4)
+            .zip(rgba.par_chunks_exact(4))
+            .for_each(|(out, px)| {
+                let color = lut[PreprocessSpec::luma([px[0], px[1], px[2]]) as usize];
+                out[..3].copy_from_slice(&color);
+                out[3] = px[3];
+            });
+    }
+}
+
+/// 控制点之间线性插值出 256 级色表
+fn interpolate(stops: &[(f32, [u8; 3])]) -> [[u8; 3]; 256] {
+    std::array::from_fn(|i| {
+        let t = i as f32 / 255.0;
+        let upper = stops
+            .iter()
+            .position(|(pos, _)| *pos >= t)
+            .unwrap_or(stops.len() - 1)
+            .max(1);
+        let (p0, c0) = stops[upper - 1];
+        let (p1, c1) = stops[upper];
+        let k = ((t - p0) / (p1 - p0)).clamp(0.0, 1.0);
+        std::array::from_fn(|c| (c0[c] as f32 + (c1[c] as f32 - c0[c] as f32) * k).round() as u8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 铁红色表两端为黑/白, 控制点处取控制点颜色, 亮度单调上升
+    #[test]
+    fn test_ironbow_lut() {
+        let lut = Palette::Ironbow.lut().unwrap();
+        assert_eq!(lut[0], [0, 0, 0]);
+        assert_eq!(lut[255], [255, 255, 255]);
+        // 0.75 * 255 ≈ 191, 接近橙色控制点
+        assert!(lut[191]
+            .iter()
+            .zip([250, 150, 0])
+            .all(|(a, b)| a.abs_diff(b) <= 1));
+        let lumas: Vec<u8> = lut.iter().map(|c| PreprocessSpec::luma(*c)).collect();
+        assert!(lumas.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    /// 着色按亮度查表并保留 alpha; 原始画面不拷贝
+    #[test]
+    fn test_colorize() {
+        let rgba = [0u8, 0, 0, 7, 255, 255, 255, 9];
+        let out = Palette::Ironbow.colorize(&rgba);
+        assert_eq!(&out[..], &[0, 0, 0, 7, 255, 255, 255, 9]);
+        assert!(matches!(Palette::None.colorize(&rgba), Cow::Borrowed(_)));
+    }
+
+    /// 写入目标缓冲与 colorize 结果一致, 原始画面原样拷贝
+    #[test]
+    fn test_colorize_into() {
+        let rgba = [10u8, 200, 30, 7, 255, 255, 255, 9, 90, 90, 90, 255];
+        let mut dst = [0u8; 12];
+        Palette::Ironbow.colorize_into(&rgba, &mut dst);
+        assert_eq!(&dst[..], &Palette::Ironbow.colorize(&rgba)[..]);
+        Palette::None.colorize_into(&rgba, &mut dst);
+        assert_eq!(dst, rgba);
+    }
+}
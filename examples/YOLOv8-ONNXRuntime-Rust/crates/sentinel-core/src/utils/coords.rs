@@ -0,0 +1,232 @@
+//! 坐标空间与空间之间的变换
+//!
+//! 同一个目标会出现在三种坐标里:
+//! - `ModelSpace`: 推理输入 (inf_size×inf_size, letterbox 时含填充)
+//! - `ImageSpace`: 原始画面像素, 跟踪、分析、标注与标定都用它
+//! - `WindowSpace`: 窗口像素, 含控制面板的缩放 (zoom) 与平移 (pan)
+//!
+//! 点与框带上所属空间的标记类型, 只能经 [`Transform`] 换到另一个空间,
+//! 避免手工 `x * scale + offset` 时漏掉平移或用反方向
+
+use std::marker::PhantomData;
+
+/// 推理输入坐标
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ModelSpace;
+
+/// 原始画面坐标
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ImageSpace;
+
+/// 窗口 (屏幕) 坐标
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindowSpace;
+
+/// 坐标空间 S 中的点
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Point<S> {
+    pub x: f32,
+    pub y: f32,
+    space: PhantomData<S>,
+}
+
+impl<S> Point<S> {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self {
+            x,
+            y,
+            space: PhantomData,
+        }
+    }
+}
+
+/// 坐标空间 S 中的轴对齐矩形 (左上 x1,y1, 右下 x2,y2)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect<S> {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+    space: PhantomData<S>,
+}
+
+impl<S> Rect<S> {
+    pub fn new(x1: f32, y1: f32, x2: f32, y2: f32) -> Self {
+        Self {
+            x1,
+            y1,
+            x2,
+            y2,
+            space: PhantomData,
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.x2 - self.x1
+    }
+
+    pub fn height(&self) -> f32 {
+        self.y2 - self.y1
+    }
+}
+
+pub type ModelPoint = Point<ModelSpace>;
+pub type ImagePoint = Point<ImageSpace>;
+pub type WindowPoint = Point<WindowSpace>;
+pub type ModelRect = Rect<ModelSpace>;
+pub type ImageRect = Rect<ImageSpace>;
+pub type WindowRect = Rect<WindowSpace>;
+
+/// 从空间 F 到空间 T 的轴对齐变换: `dst = src * scale + offset`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform<F, T> {
+    scale: (f32, f32),
+    offset: (f32, f32),
+    spaces: PhantomData<(F, T)>,
+}
+
+/// 推理输入 → 原始画面
+pub type ModelToImage = Transform<ModelSpace, ImageSpace>;
+/// 原始画面 → 窗口
+pub type ImageToWindow = Transform<ImageSpace, WindowSpace>;
+
+impl<F, T> Transform<F, T> {
+    /// 按缩放 (水平, 垂直) 与平移 (左, 上) 构造
+    pub fn new(scale: (f32, f32), offset: (f32, f32)) -> Self {
+        Self {
+            scale,
+            offset,
+            spaces: PhantomData,
+        }
+    }
+
+    /// 缩放 (水平, 垂直)
+    pub fn scale(&self) -> (f32, f32) {
+        self.scale
+    }
+
+    /// 源空间原点在目标空间的位置 (左, 上)
+    pub fn offset(&self) -> (f32, f32) {
+        self.offset
+    }
+
+    pub fn point(&self, p: Point<F>) -> Point<T> {
+        Point::new(
+            p.x * self.scale.0 + self.offset.0,
+            p.y * self.scale.1 + self.offset.1,
+        )
+    }
+
+    pub fn rect(&self, r: Rect<F>) -> Rect<T> {
+        let min = self.point(Point::new(r.x1, r.y1));
+        let max = self.point(Point::new(r.x2, r.y2));
+        Rect::new(min.x, min.y, max.x, max.y)
+    }
+
+    /// 源空间的尺寸 (宽, 高) 在目标空间的大小 (不含平移)
+    pub fn size(&self, (w, h): (f32, f32)) -> (f32, f32) {
+        (w * self.scale.0, h * self.scale.1)
+    }
+
+    /// 反方向的变换, 缩放为 0 时按 1 处理
+    pub fn inverse(&self) -> Transform<T, F> {
+        let inv = |s: f32| if s == 0.0 { 1.0 } else { 1.0 / s };
+        let scale = (inv(self.scale.0), inv(self.scale.1));
+        Transform::new(scale, (-self.offset.0 * scale.0, -self.offset.1 * scale.1))
+    }
+
+    /// 先做本变换再做 next
+    pub fn then<U>(&self, next: &Transform<T, U>) -> Transform<F, U> {
+        Transform::new(
+            (self.scale.0 * next.scale.0, self.scale.1 * next.scale.1),
+            (
+                self.offset.0 * next.scale.0 + next.offset.0,
+                self.offset.1 * next.scale.1 + next.offset.1,
+            ),
+        )
+    }
+}
+
+impl ModelToImage {
+    /// 拉伸缩放: 预处理直接把画面 resize 到推理尺寸 (宽高比不保持)
+    pub fn stretch(model: (u32, u32), image: (u32, u32)) -> Self {
+        let sx = image.0 as f32 / model.0.max(1) as f32;
+        let sy = image.1 as f32 / model.1.max(1) as f32;
+        Self::new((sx, sy), (0.0, 0.0))
+    }
+
+    /// letterbox: 画面等比缩放后居中放入推理尺寸, 四周填充
+    pub fn letterbox(model: (u32, u32), image: (u32, u32)) -> Self {
+        let (mw, mh) = (model.0.max(1) as f32, model.1.max(1) as f32);
+        let (iw, ih) = (image.0 as f32, image.1 as f32);
+        let ratio = (mw / iw.max(1.0)).min(mh / ih.max(1.0));
+        let pad = ((mw - iw * ratio) / 2.0, (mh - ih * ratio) / 2.0);
+        Transform::<ImageSpace, ModelSpace>::new((ratio, ratio), pad).inverse()
+    }
+}
+
+impl ImageToWindow {
+    /// 主画面: 画面拉伸铺满窗口, 再按 zoom 以窗口中心缩放、按 pan 平移
+    pub fn view(image: (f32, f32), window: (f32, f32), zoom: f32, pan: (f32, f32)) -> Self {
+        let scale_x = window.0 / image.0 * zoom;
+        let scale_y = window.1 / image.1 * zoom;
+        let left = (window.0 - image.0 * scale_x) / 2.0 + pan.0;
+        let top = (window.1 - image.1 * scale_y) / 2.0 + pan.1;
+        Self::new((scale_x, scale_y), (left, top))
+    }
+
+    /// 画面保持宽高比放入窗口中的区域 (左, 上, 宽, 高), 居中
+    pub fn fit(image: (f32, f32), area: (f32, f32, f32, f32)) -> Self {
+        let (x, y, w, h) = area;
+        let scale = (w / image.0).min(h / image.1);
+        let left = x + (w - image.0 * scale) / 2.0;
+        let top = y + (h - image.1 * scale) / 2.0;
+        Self::new((scale, scale), (left, top))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: (f32, f32), b: (f32, f32)) -> bool {
+        (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3
+    }
+
+    /// letterbox 去掉填充并还原缩放, 往返变换回到原点
+    #[test]
+    fn test_letterbox_round_trip() {
+        // 1920×1080 放入 640×640: 缩放 1/3, 上下各填充 140
+        let to_image = ModelToImage::letterbox((640, 640), (1920, 1080));
+        let p = to_image.point(ModelPoint::new(320.0, 140.0));
+        assert!(close((p.x, p.y), (960.0, 0.0)));
+        let r = to_image.rect(ModelRect::new(0.0, 140.0, 640.0, 500.0));
+        assert!(close((r.width(), r.height()), (1920.0, 1080.0)));
+
+        let back = to_image.inverse().point(ImagePoint::new(1920.0, 1080.0));
+        assert!(close((back.x, back.y), (640.0, 500.0)));
+
+        let stretch = ModelToImage::stretch((640, 640), (1280, 320));
+        assert_eq!(stretch.scale(), (2.0, 0.5));
+    }
+
+    /// 窗口变换包含缩放与平移, 鼠标位置经反变换回到画面坐标
+    #[test]
+    fn test_view_zoom_pan() {
+        let view = ImageToWindow::view((1920.0, 1080.0), (960.0, 540.0), 2.0, (10.0, -20.0));
+        assert_eq!(view.scale(), (1.0, 1.0));
+        assert_eq!(view.offset(), (-470.0, -290.0));
+        let center = view.point(ImagePoint::new(960.0, 540.0));
+        assert!(close((center.x, center.y), (490.0, 250.0)));
+        let mouse = view.inverse().point(WindowPoint::new(490.0, 250.0));
+        assert!(close((mouse.x, mouse.y), (960.0, 540.0)));
+
+        // 推理输入直接到窗口
+        let to_window = ModelToImage::stretch((640, 640), (1920, 1080)).then(&view);
+        let corner = to_window.point(ModelPoint::new(640.0, 640.0));
+        assert!(close((corner.x, corner.y), (1450.0, 790.0)));
+
+        let fit = ImageToWindow::fit((1600.0, 900.0), (800.0, 0.0, 800.0, 900.0));
+        assert_eq!(fit, ImageToWindow::new((0.5, 0.5), (800.0, 225.0)));
+    }
+}
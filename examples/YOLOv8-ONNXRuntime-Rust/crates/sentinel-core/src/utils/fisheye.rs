@@ -0,0 +1,469 @@
+/// 鱼眼去畸变 (360° 吊装半球摄像头)
+/// Fisheye dewarping into virtual perspective views
+///
+/// 标定参数描述鱼眼投影 (等距模型 r = f·θ, 或 Mei 全向模型), 每个虚拟视图是一台
+/// 绕光轴方位角 yaw、偏离光轴 pitch 的针孔相机. 映射表在源分辨率变化时重建一次,
+/// 逐帧只做 [`remap_plane`] 插值; 每个视图作为独立的逻辑流送入检测器
+use crate::utils::affine_transform::{remap_plane, BorderMode, InterpolationMethod};
+use crate::utils::yuv_preprocess::Yuv420Frame;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::FRAC_PI_2;
+use std::fs;
+use std::sync::OnceLock;
+
+/// 默认配置文件路径
+pub const FISHEYE_CONFIG_PATH: &str = "fisheye_config.json";
+
+/// 已生效的鱼眼配置 (未设置表示不去畸变)
+static FISHEYE: OnceLock<FisheyeConfig> = OnceLock::new();
+
+/// 视图外区域的填充值 (Y=16 黑, UV=128 无色)
+const LUMA_FILL: u8 = 16;
+const CHROMA_FILL: u8 = 128;
+
+/// 鱼眼投影模型
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FisheyeModel {
+    /// 等距投影 r = f·θ (大多数半球摄像头)
+    #[default]
+    Equidistant,
+    /// Mei 统一全向模型 (单位球 → 沿光轴平移 ξ → 针孔投影)
+    Omnidirectional,
+}
+
+/// 鱼眼标定参数 (像素单位, 对应标定时的分辨率)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FisheyeCalibration {
+    pub model: FisheyeModel,
+    pub image_width: u32,  // 标定分辨率, 实际帧尺寸不同时按比例缩放
+    pub image_height: u32, //
+    pub cx: f32,           // 主点 (鱼眼圆心)
+    pub cy: f32,           //
+    pub focal: f32,        // 等距模型: 像素/弧度; 全向模型: 广义焦距 γ
+    pub xi: f32,           // 全向模型镜面参数 ξ (等距模型忽略)
+}
+
+impl Default for FisheyeCalibration {
+    /// 1920×1920 画面, 180° 视场恰好铺满内切圆
+    fn default() -> Self {
+        Self {
+            model: FisheyeModel::Equidistant,
+            image_width: 1920,
+            image_height: 1920,
+            cx: 960.0,
+            cy: 960.0,
+            focal: 960.0 / FRAC_PI_2,
+            xi: 1.0,
+        }
+    }
+}
+
+impl FisheyeCalibration {
+    /// 相机坐标系射线 → 鱼眼像素坐标 (z 为光轴), 无法成像时为 None
+    pub fn project(&self, ray: [f32; 3]) -> Option<(f32, f32)> {
+        let norm = (ray[0] * ray[0] + ray[1] * ray[1] + ray[2] * ray[2]).sqrt();
+        if norm < 1e-9 {
+            return None;
+        }
+        let [x, y, z] = ray.map(|v| v / norm);
+        match self.model {
+            FisheyeModel::Equidistant => {
+                let rho = (x * x + y * y).sqrt();
+                if rho < 1e-9 {
+                    return Some((self.cx, self.cy));
+                }
+                let r = self.focal * z.clamp(-1.0, 1.0).acos();
+                Some((self.cx + r * x / rho, self.cy + r * y / rho))
+            }
+            FisheyeModel::Omnidirectional => {
+                let d = z + self.xi;
+                if d < 1e-6 {
+                    return None;
+                }
+                Some((self.cx + self.focal * x / d, self.cy + self.focal * y / d))
+            }
+        }
+    }
+
+    /// 鱼眼像素坐标 → 单位射线
+    pub fn unproject(&self, u: f32, v: f32) -> [f32; 3] {
+        let (dx, dy) = (u - self.cx, v - self.cy);
+        match self.model {
+            FisheyeModel::Equidistant => {
+                let r = (dx * dx + dy * dy).sqrt();
+                if r < 1e-9 {
+                    return [0.0, 0.0, 1.0];
+                }
+                let theta = r / self.focal;
+                let s = theta.sin();
+                [s * dx / r, s * dy / r, theta.cos()]
+            }
+            FisheyeModel::Omnidirectional => {
+                let (mx, my) = (dx / self.focal, dy / self.focal);
+                let r2 = mx * mx + my * my;
+                let k =
+                    (self.xi + (1.0 + (1.0 - self.xi * self.xi) * r2).max(0.0).sqrt()) / (r2 + 1.0);
+                [k * mx, k * my, k - self.xi]
+            }
+        }
+    }
+}
+
+/// 虚拟透视视图 (针孔相机)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VirtualView {
+    pub name: String,
+    pub yaw_deg: f32,   // 绕光轴的方位角
+    pub pitch_deg: f32, // 视线偏离光轴的角度 (0 = 正对光轴, 吊装时即正下方)
+    pub roll_deg: f32,  // 绕视线旋转 (朝上安装时设为 180)
+    pub fov_deg: f32,   // 水平视场角
+    pub width: u32,     // 输出尺寸 (按 YUV420 取偶数)
+    pub height: u32,    //
+}
+
+impl Default for VirtualView {
+    fn default() -> Self {
+        Self {
+            name: String::from("视图"),
+            yaw_deg: 0.0,
+            pitch_deg: 55.0,
+            roll_deg: 0.0,
+            fov_deg: 90.0,
+            width: 640,
+            height: 480,
+        }
+    }
+}
+
+impl VirtualView {
+    /// 视图坐标系 → 相机坐标系的旋转 Rz(yaw)·Rx(pitch)·Rz(roll)
+    fn rotation(&self) -> [[f32; 3]; 3] {
+        let rz = |deg: f32| {
+            let (s, c) = deg.to_radians().sin_cos();
+            [[c, -s, 0.0], [s, c, 0.0], [0.0, 0.0, 1.0]]
+        };
+        let (s, c) = self.pitch_deg.to_radians().sin_cos();
+        let rx = [[1.0, 0.0, 0.0], [0.0, c, -s], [0.0, s, c]];
+        mat_mul(&mat_mul(&rz(self.yaw_deg), &rx), &rz(self.roll_deg))
+    }
+
+    /// 输出尺寸 (YUV420 要求偶数)
+    fn size(&self) -> (usize, usize) {
+        (
+            (self.width.max(2) & !1) as usize,
+            (self.height.max(2) & !1) as usize,
+        )
+    }
+}
+
+fn mat_mul(a: &[[f32; 3]; 3], b: &[[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    let mut out = [[0.0; 3]; 3];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, v) in row.iter_mut().enumerate() {
+            *v = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    out
+}
+
+/// 鱼眼配置: 标定参数 + 虚拟视图列表
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FisheyeConfig {
+    pub calibration: FisheyeCalibration,
+    pub views: Vec<VirtualView>,
+}
+
+impl Default for FisheyeConfig {
+    /// 四个方向各一个 90° 视图
+    fn default() -> Self {
+        let views = ["前", "右", "后", "左"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| VirtualView {
+                name: name.to_string(),
+                yaw_deg: i as f32 * 90.0,
+                ..VirtualView::default()
+            })
+            .collect();
+        Self {
+            calibration: FisheyeCalibration::default(),
+            views,
+        }
+    }
+}
+
+impl FisheyeConfig {
+    /// 从文件加载配置, 文件不存在或解析失败时返回 None
+    pub fn load(path: &str) -> Option<Self> {
+        match fs::read_to_string(path) {
+            Ok(json) => match serde_json::from_str::<Self>(&json) {
+                Ok(config) => {
+                    println!(
+                        "✅ 鱼眼配置已从 {} 加载 ({:?}, {}个视图)",
+                        path,
+                        config.calibration.model,
+                        config.views.len()
+                    );
+                    Some(config)
+                }
+                Err(e) => {
+                    eprintln!("⚠️  鱼眼配置解析失败: {}, 不启用去畸变", e);
+                    None
+                }
+            },
+            Err(e) => {
+                eprintln!("⚠️  读取鱼眼配置 {} 失败: {}, 不启用去畸变", path, e);
+                None
+            }
+        }
+    }
+
+    /// 保存配置到文件
+    pub fn save(&self, path: &str) {
+        match serde_json::to_string_pretty(self) {
+            Ok(json) => {
+                if let Err(e) = fs::write(path, json) {
+                    eprintln!("❌ 保存鱼眼配置失败: {}", e);
+                } else {
+                    println!("💾 鱼眼配置已保存到 {}", path);
+                }
+            }
+            Err(e) => eprintln!("❌ 序列化鱼眼配置失败: {}", e),
+        }
+    }
+
+    /// 全局生效 (须在启动解码器之前调用, 进程内只生效一次)
+    pub fn apply(self) {
+        if self.views.is_empty() {
+            eprintln!("⚠️ 鱼眼配置没有视图, 不启用去畸变");
+            return;
+        }
+        if FISHEYE.set(self).is_err() {
+            eprintln!("⚠️ 鱼眼配置已生效, 忽略重复设置");
+        }
+    }
+}
+
+/// 当前生效的鱼眼配置
+pub fn fisheye_config() -> Option<&'static FisheyeConfig> {
+    FISHEYE.get()
+}
+
+/// 单个虚拟视图的映射表 (对应某一源分辨率)
+#[derive(Clone)]
+pub struct DewarpMap {
+    pub width: usize,
+    pub height: usize,
+    luma: Vec<(f32, f32)>,
+    chroma: Vec<(f32, f32)>,
+    calibration: FisheyeCalibration,
+    rotation: [[f32; 3]; 3],
+    focal: f32,        // 虚拟针孔相机焦距 (像素)
+    scale: (f32, f32), // 标定分辨率 → 实际源分辨率
+}
+
+impl DewarpMap {
+    pub fn new(
+        calibration: &FisheyeCalibration,
+        view: &VirtualView,
+        src_width: usize,
+        src_height: usize,
+    ) -> Self {
+        let (width, height) = view.size();
+        let mut map = Self {
+            width,
+            height,
+            luma: Vec::with_capacity(width * height),
+            chroma: Vec::with_capacity(width * height / 4),
+            calibration: calibration.clone(),
+            rotation: view.rotation(),
+            focal: width as f32 / 2.0 / (view.fov_deg.clamp(1.0, 179.0).to_radians() / 2.0).tan(),
+            scale: (
+                src_width as f32 / calibration.image_width.max(1) as f32,
+                src_height as f32 / calibration.image_height.max(1) as f32,
+            ),
+        };
+
+        // 映射表存采样坐标 (整数为像素中心), 无法成像的像素为 NaN (按边界填充)
+        for y in 0..height {
+            for x in 0..width {
+                let p = map.view_to_source(x as f32 + 0.5, y as f32 + 0.5);
+                map.luma
+                    .push(p.map_or((f32::NAN, f32::NAN), |(u, v)| (u - 0.5, v - 0.5)));
+            }
+        }
+        for y in 0..height / 2 {
+            for x in 0..width / 2 {
+                let p = map.view_to_source(2.0 * x as f32 + 1.0, 2.0 * y as f32 + 1.0);
+                map.chroma.push(p.map_or((f32::NAN, f32::NAN), |(u, v)| {
+                    (u / 2.0 - 0.5, v / 2.0 - 0.5)
+                }));
+            }
+        }
+        map
+    }
+
+    /// 视图坐标 → 鱼眼源画面坐标 (连续坐标), 用于把视图内的检测框映射回全景
+    pub fn view_to_source(&self, x: f32, y: f32) -> Option<(f32, f32)> {
+        let d = [
+            (x - self.width as f32 / 2.0) / self.focal,
+            (y - self.height as f32 / 2.0) / self.focal,
+            1.0,
+        ];
+        let r = &self.rotation;
+        let ray = [0, 1, 2].map(|i| r[i][0] * d[0] + r[i][1] * d[1] + r[i][2] * d[2]);
+        self.calibration
+            .project(ray)
+            .map(|(u, v)| (u * self.scale.0, v * self.scale.1))
+    }
+
+    /// 重采样三个平面
+    pub fn remap_yuv(&self, src: &Yuv420Frame, dst: &mut Yuv420Frame) {
+        let (cw, ch) = (src.chroma_width(), src.chroma_height());
+        dst.width = self.width;
+        dst.height = self.height;
+        dst.y.resize(self.luma.len(), 0);
+        dst.u.resize(self.chroma.len(), 0);
+        dst.v.resize(self.chroma.len(), 0);
+        let bilinear = InterpolationMethod::Bilinear;
+        let luma = BorderMode::Constant(LUMA_FILL);
+        let chroma = BorderMode::Constant(CHROMA_FILL);
+        remap_plane(
+            &src.y, src.width, src.height, &self.luma, &mut dst.y, self.width, bilinear, luma,
+        );
+        remap_plane(
+            &src.u,
+            cw,
+            ch,
+            &self.chroma,
+            &mut dst.u,
+            self.width / 2,
+            bilinear,
+            chroma,
+        );
+        remap_plane(
+            &src.v,
+            cw,
+            ch,
+            &self.chroma,
+            &mut dst.v,
+            self.width / 2,
+            bilinear,
+            chroma,
+        );
+    }
+}
+
+/// 鱼眼去畸变器: 按源分辨率缓存各视图映射表
+#[derive(Clone)]
+pub struct Dewarper {
+    config: FisheyeConfig,
+    maps: Vec<DewarpMap>,
+    src_size: (usize, usize),
+}
+
+impl Dewarper {
+    pub fn new(config: FisheyeConfig) -> Self {
+        Self {
+            config,
+            maps: Vec::new(),
+            src_size: (0, 0),
+        }
+    }
+
+    pub fn view_count(&self) -> usize {
+        self.config.views.len()
+    }
+
+    /// 某视图的映射表 (源分辨率变化时全部重建)
+    pub fn map(&mut self, view: usize, src_width: usize, src_height: usize) -> &DewarpMap {
+        if self.src_size != (src_width, src_height) || self.maps.is_empty() {
+            let start = std::time::Instant::now();
+            self.maps = self
+                .config
+                .views
+                .iter()
+                .map(|v| DewarpMap::new(&self.config.calibration, v, src_width, src_height))
+                .collect();
+            self.src_size = (src_width, src_height);
+            println!(
+                "🐟 鱼眼映射表已重建: 源{}x{} → {}个视图 ({:.1}ms)",
+                src_width,
+                src_height,
+                self.maps.len(),
+                start.elapsed().as_secs_f64() * 1000.0
+            );
+        }
+        &self.maps[view]
+    }
+
+    /// 生成某个虚拟视图
+    pub fn dewarp(&mut self, src: &Yuv420Frame, view: usize, dst: &mut Yuv420Frame) {
+        self.map(view, src.width, src.height).remap_yuv(src, dst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 两种模型的投影/反投影互逆
+    #[test]
+    fn test_project_round_trip() {
+        for model in [FisheyeModel::Equidistant, FisheyeModel::Omnidirectional] {
+            let calibration = FisheyeCalibration {
+                model,
+                ..FisheyeCalibration::default()
+            };
+            for (u, v) in [(960.0, 960.0), (1200.0, 700.0), (300.0, 1500.0)] {
+                let ray = calibration.unproject(u, v);
+                let (pu, pv) = calibration.project(ray).unwrap();
+                assert!(
+                    (pu - u).abs() < 0.05 && (pv - v).abs() < 0.05,
+                    "{:?}",
+                    model
+                );
+            }
+        }
+    }
+
+    /// 视图中心落在视线方向上, 映射表随源分辨率缩放
+    #[test]
+    fn test_view_center() {
+        let calibration = FisheyeCalibration::default();
+        let down = VirtualView {
+            pitch_deg: 0.0,
+            ..VirtualView::default()
+        };
+        let map = DewarpMap::new(&calibration, &down, 960, 960);
+        let (u, v) = map.view_to_source(320.0, 240.0).unwrap();
+        assert!((u - 480.0).abs() < 1e-3 && (v - 480.0).abs() < 1e-3);
+
+        // 偏离光轴 90°: 落在 180° 鱼眼圆的边缘
+        let horizon = VirtualView {
+            pitch_deg: 90.0,
+            ..VirtualView::default()
+        };
+        let map = DewarpMap::new(&calibration, &horizon, 1920, 1920);
+        let (u, v) = map.view_to_source(320.0, 240.0).unwrap();
+        assert!((u - 960.0).abs() < 1e-2 && v.abs() < 1e-2, "({}, {})", u, v);
+
+        // 纯色鱼眼画面去畸变后视图内仍为纯色
+        let src = Yuv420Frame {
+            y: vec![200; 1920 * 1920],
+            u: vec![90; 960 * 960],
+            v: vec![150; 960 * 960],
+            width: 1920,
+            height: 1920,
+        };
+        let mut dewarper = Dewarper::new(FisheyeConfig::default());
+        let mut dst = Yuv420Frame::default();
+        dewarper.dewarp(&src, 1, &mut dst);
+        assert_eq!((dst.width, dst.height), (640, 480));
+        assert_eq!(dst.y[240 * 640 + 320], 200);
+        assert_eq!(dst.u[120 * 320 + 160], 90);
+    }
+}
@@ -0,0 +1,351 @@
+//! 高位深 / HDR 帧 (10-bit HEVC, P010)
+//!
+//! 解码过滤器把 10-bit 帧拷贝为 16 位平面 ([`Yuv420Frame16`]), 不先截断为 8 位:
+//! - 检测线程按色调曲线把亮度映射为 0~255 浮点值后直接归一化, 不产生色带
+//! - 界面显示、鱼眼与画面方向校正仍使用色调映射后的 8 位 [`Yuv420Frame`]
+//!
+//! 色调曲线按传输特性选择: SDR 按位深缩放 (10-bit 码值 / 4);
+//! PQ (SMPTE 2084) 与 HLG (ARIB STD-B67) 先换算为相对参考白 (203 nit) 的线性亮度,
+//! 再做 Reinhard 压缩与 2.2 gamma 编码
+
+use std::sync::Arc;
+
+use rayon::prelude::*;
+
+use super::yuv_preprocess::Yuv420Frame;
+use crate::models::PreprocessSpec;
+
+/// PQ 参考白亮度 (nit, ITU-R BT.2408)
+const REFERENCE_WHITE_NITS: f32 = 203.0;
+/// PQ 压缩的峰值亮度 (nit), 更亮的高光饱和为白色
+const PEAK_NITS: f32 = 1000.0;
+
+/// 传输特性 (AVFrame.color_trc, 取值见 ITU-T H.273)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Transfer {
+    #[default]
+    Sdr,
+    Pq,  // SMPTE ST 2084 (HDR10)
+    Hlg, // ARIB STD-B67
+}
+
+impl Transfer {
+    /// 由 H.273 传输特性码值识别, 其他码值按 SDR 处理
+    pub fn from_av(color_trc: i32) -> Self {
+        match color_trc {
+            16 => Transfer::Pq,
+            18 => Transfer::Hlg,
+            _ => Transfer::Sdr,
+        }
+    }
+
+    /// 码值 (0~1) → 相对参考白的线性亮度
+    fn to_linear(self, e: f32) -> f32 {
+        match self {
+            Transfer::Sdr => e,
+            Transfer::Pq => {
+                let (m1, m2) = (0.159_301_76, 78.843_75);
+                let (c1, c2, c3) = (0.835_937_5, 18.851_563, 18.6875);
+                let p = e.max(0.0).powf(1.0 / m2);
+                let nits = 10000.0 * ((p - c1).max(0.0) / (c2 - c3 * p)).powf(1.0 / m1);
+                nits / REFERENCE_WHITE_NITS
+            }
+            Transfer::Hlg => {
+                let (a, b, c) = (0.178_832_77, 0.284_668_92, 0.559_910_7);
+                let scene = if e <= 0.5 {
+                    e * e / 3.0
+                } else {
+                    (((e - c) / a).exp() + b) / 12.0
+                };
+                // 系统 gamma 1.2, 75% 码值为参考白
+                scene.powf(1.2) / hlg_reference_white()
+            }
+        }
+    }
+
+    /// 压缩后对应白色的线性亮度
+    fn white_point(self) -> f32 {
+        match self {
+            Transfer::Sdr => 1.0,
+            Transfer::Pq => PEAK_NITS / REFERENCE_WHITE_NITS,
+            Transfer::Hlg => 1.0 / hlg_reference_white(),
+        }
+    }
+}
+
+/// HLG 75% 码值 (参考白) 的显示线性亮度
+fn hlg_reference_white() -> f32 {
+    let (a, b, c) = (0.178_832_77_f32, 0.284_668_92, 0.559_910_7);
+    ((((0.75 - c) / a).exp() + b) / 12.0).powf(1.2)
+}
+
+/// 色调曲线: 每个亮度码值 → 0~255 SDR 亮度 (浮点, 检测使用; 显示时取整)
+#[derive(Clone, Debug, PartialEq)]
+pub struct ToneCurve {
+    pub transfer: Transfer,
+    pub bits: u32,
+    luma: Vec<f32>,
+}
+
+impl ToneCurve {
+    pub fn new(transfer: Transfer, bits: u32) -> Self {
+        let max = ((1u32 << bits) - 1) as f32;
+        let scale = (1u32 << (bits - 8)) as f32;
+        let white = transfer.white_point();
+        let luma = (0..1u32 << bits)
+            .map(|code| match transfer {
+                Transfer::Sdr => (code as f32 / scale).min(255.0),
+                _ => {
+                    // 扩展 Reinhard: 参考白附近接近线性, white 处压缩为 1
+                    let x = transfer.to_linear(code as f32 / max);
+                    let y = x * (1.0 + x / (white * white)) / (1.0 + x);
+                    y.clamp(0.0, 1.0).powf(1.0 / 2.2) * 255.0
+                }
+            })
+            .collect();
+        Self {
+            transfer,
+            bits,
+            luma,
+        }
+    }
+
+    /// 亮度码值 → 0~255 浮点亮度 (超出位深的码值按最大值处理)
+    pub fn luma(&self, code: u16) -> f32 {
+        self.luma[(code as usize).min(self.luma.len() - 1)]
+    }
+
+    /// 色度码值 → 0~255 浮点色度 (按位深缩放, 中性值仍为 128)
+    pub fn chroma(&self, code: u16) -> f32 {
+        (code as f32 / (1u32 << (self.bits - 8)) as f32).min(255.0)
+    }
+}
+
+/// 紧凑存储的 16 位 YUV420P 帧 (样本为原始码值, 低位对齐)
+#[derive(Clone, Debug)]
+pub struct Yuv420Frame16 {
+    pub y: Vec<u16>,
+    pub u: Vec<u16>,
+    pub v: Vec<u16>,
+    pub width: usize,
+    pub height: usize,
+    pub curve: Arc<ToneCurve>,
+}
+
+impl Yuv420Frame16 {
+    pub fn new(curve: Arc<ToneCurve>) -> Self {
+        Self {
+            y: Vec::new(),
+            u: Vec::new(),
+            v: Vec::new(),
+            width: 0,
+            height: 0,
+            curve,
+        }
+    }
+
+    /// 色度平面宽度 (奇数宽度向上取整)
+    pub fn chroma_width(&self) -> usize {
+        self.width.div_ceil(2)
+    }
+
+    /// 色度平面高度 (奇数高度向上取整)
+    pub fn chroma_height(&self) -> usize {
+        self.height.div_ceil(2)
+    }
+
+    /// 从带步长 (字节) 的 yuv420p10le 平面指针拷贝, 复用已有缓冲
+    ///
+    /// # Safety
+    /// 调用方保证各平面指针按 2 字节对齐且在 `stride * rows` 字节范围内可读
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe fn copy_from_planes(
+        &mut self,
+        y_plane: *const u8,
+        u_plane: *const u8,
+        v_plane: *const u8,
+        y_stride: usize,
+        uv_stride: usize,
+        width: usize,
+        height: usize,
+    ) {
+        self.width = width;
+        self.height = height;
+        let (cw, ch) = (self.chroma_width(), self.chroma_height());
+        self.y.resize(width * height, 0);
+        self.u.resize(cw * ch, 0);
+        self.v.resize(cw * ch, 0);
+
+        for row in 0..height {
+            let src = std::slice::from_raw_parts(y_plane.add(row * y_stride) as *const u16, width);
+            self.y[row * width..(row + 1) * width].copy_from_slice(src);
+        }
+        for row in 0..ch {
+            let src_u = std::slice::from_raw_parts(u_plane.add(row * uv_stride) as *const u16, cw);
+            let src_v = std::slice::from_raw_parts(v_plane.add(row * uv_stride) as *const u16, cw);
+            self.u[row * cw..(row + 1) * cw].copy_from_slice(src_u);
+            self.v[row * cw..(row + 1) * cw].copy_from_slice(src_v);
+        }
+    }
+
+    /// 色调映射为 8 位帧 (界面显示与 8 位处理链使用)
+    pub fn tone_map(&self, dst: &mut Yuv420Frame) {
+        let curve = &self.curve;
+        dst.width = self.width;
+        dst.height = self.height;
+        dst.y.resize(self.y.len(), 0);
+        dst.u.resize(self.u.len(), 0);
+        dst.v.resize(self.v.len(), 0);
+        dst.y
+            .par_iter_mut()
+            .zip(&self.y)
+            .for_each(|(d, &s)| *d = curve.luma(s).round() as u8);
+        for (d, &s) in dst
+            .u
+            .iter_mut()
+            .zip(&self.u)
+            .chain(dst.v.iter_mut().zip(&self.v))
+        {
+            *d = curve.chroma(s).round() as u8;
+        }
+    }
+}
+
+/// 16 位 YUV420P → 归一化 NCHW (与 [`super::yuv_preprocess::yuv420_to_nchw`] 相同的采样与
+/// 通道处理, 颜色转换使用浮点 BT.601 系数)
+///
+/// - `out`: 长度为 `spec.channels * y_map.len() * x_map.len()` 的 CHW 缓冲
+pub fn yuv420_16_to_nchw(
+    frame: &Yuv420Frame16,
+    x_map: &[usize],
+    y_map: &[usize],
+    spec: &PreprocessSpec,
+    out: &mut [f32],
+) {
+    let (dw, dh) = (x_map.len(), y_map.len());
+    let plane = dw * dh;
+    assert_eq!(out.len(), plane * spec.channels, "NCHW 缓冲长度不匹配");
+
+    let curve = &frame.curve;
+    if spec.channels == 1 {
+        out.par_chunks_exact_mut(dw)
+            .enumerate()
+            .for_each(|(row, out_row)| {
+                let sy = y_map[row];
+                let y_row = &frame.y[sy * frame.width..(sy + 1) * frame.width];
+                for (dst, &sx) in out_row.iter_mut().zip(x_map) {
+                    *dst = (curve.luma(y_row[sx]) - spec.mean[0]) / spec.std[0];
+                }
+            });
+        return;
+    }
+
+    let src: [usize; 3] = std::array::from_fn(|c| spec.source_channel(c));
+    let cw = frame.chroma_width();
+    let (c0_plane, rest) = out.split_at_mut(plane);
+    let (c1_plane, c2_plane) = rest.split_at_mut(plane);
+    c0_plane
+        .par_chunks_exact_mut(dw)
+        .zip(c1_plane.par_chunks_exact_mut(dw))
+        .zip(c2_plane.par_chunks_exact_mut(dw))
+        .enumerate()
+        .for_each(|(row, ((c0_row, c1_row), c2_row))| {
+            let sy = y_map[row];
+            let y_row = &frame.y[sy * frame.width..(sy + 1) * frame.width];
+            let u_row = &frame.u[(sy >> 1) * cw..((sy >> 1) + 1) * cw];
+            let v_row = &frame.v[(sy >> 1) * cw..((sy >> 1) + 1) * cw];
+            for (k, &sx) in x_map.iter().enumerate() {
+                let rgb = bt601(
+                    curve.luma(y_row[sx]),
+                    curve.chroma(u_row[sx >> 1]),
+                    curve.chroma(v_row[sx >> 1]),
+                );
+                c0_row[k] = (rgb[src[0]] - spec.mean[0]) / spec.std[0];
+                c1_row[k] = (rgb[src[1]] - spec.mean[1]) / spec.std[1];
+                c2_row[k] = (rgb[src[2]] - spec.mean[2]) / spec.std[2];
+            }
+        });
+}
+
+/// 浮点 BT.601 YUV → RGB (0~255, 与解码过滤器定点系数相同)
+fn bt601(y: f32, u: f32, v: f32) -> [f32; 3] {
+    let (u, v) = (u - 128.0, v - 128.0);
+    [
+        (y + 1.402 * v).clamp(0.0, 255.0),
+        (y - 0.344 * u - 0.714 * v).clamp(0.0, 255.0),
+        (y + 1.772 * u).clamp(0.0, 255.0),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(curve: ToneCurve, y: Vec<u16>, u: u16, v: u16) -> Yuv420Frame16 {
+        let mut frame = Yuv420Frame16::new(Arc::new(curve));
+        frame.width = y.len();
+        frame.height = 1;
+        frame.u = vec![u; frame.chroma_width()];
+        frame.v = vec![v; frame.chroma_width()];
+        frame.y = y;
+        frame
+    }
+
+    /// 色调曲线两端为黑/白且单调; SDR 为线性缩放; PQ 参考白接近 SDR 白的 80%
+    #[test]
+    fn test_tone_curves() {
+        for transfer in [Transfer::Sdr, Transfer::Pq, Transfer::Hlg] {
+            let curve = ToneCurve::new(transfer, 10);
+            assert_eq!(curve.luma(0), 0.0, "{:?}", transfer);
+            assert!((curve.luma(1023) - 255.0).abs() < 0.5, "{:?}", transfer);
+            assert!((0..1023).all(|c| curve.luma(c) <= curve.luma(c + 1)));
+        }
+        let sdr = ToneCurve::new(Transfer::Sdr, 10);
+        assert_eq!(sdr.luma(401), 100.25);
+        assert_eq!(sdr.chroma(512), 128.0);
+
+        // PQ 码值 0.58 ≈ 203 nit 参考白
+        let pq = ToneCurve::new(Transfer::Pq, 10);
+        let white = pq.luma((0.58 * 1023.0) as u16);
+        assert!((180.0..230.0).contains(&white), "{}", white);
+        assert_eq!(Transfer::from_av(16), Transfer::Pq);
+        assert_eq!(Transfer::from_av(1), Transfer::Sdr);
+    }
+
+    /// 相邻 10-bit 码值在检测输入中仍可区分 (8 位截断会合并为同一值)
+    #[test]
+    fn test_no_banding() {
+        let frame = frame(
+            ToneCurve::new(Transfer::Sdr, 10),
+            vec![400, 401, 402, 403],
+            512,
+            512,
+        );
+        let spec = PreprocessSpec::default().with_channels(1);
+        let mut out = vec![0f32; 4];
+        yuv420_16_to_nchw(&frame, &[0, 1, 2, 3], &[0], &spec, &mut out);
+        assert!(out.windows(2).all(|w| w[0] < w[1]), "{:?}", out);
+
+        let mut sdr = Yuv420Frame::default();
+        frame.tone_map(&mut sdr);
+        assert_eq!(sdr.y, vec![100, 100, 101, 101]);
+        assert_eq!(sdr.u, vec![128, 128]);
+    }
+
+    /// 3 通道输出与 8 位帧的浮点转换一致 (灰色像素各通道相同, BGR 通道顺序生效)
+    #[test]
+    fn test_rgb_channels() {
+        let frame = frame(ToneCurve::new(Transfer::Sdr, 10), vec![1023, 0], 1023, 512);
+        let mut out = vec![0f32; 3 * 2];
+        let spec = PreprocessSpec::default();
+        yuv420_16_to_nchw(&frame, &[0, 1], &[0], &spec, &mut out);
+        // 纯蓝偏色: R=Y, G<Y, B 饱和
+        assert!((out[0] - 1.0).abs() < 1e-6);
+        assert!(out[2] < 1.0);
+        assert_eq!(out[4], 1.0);
+
+        let bgr = crate::models::ModelType::YOLOX.preprocess_spec();
+        yuv420_16_to_nchw(&frame, &[0, 1], &[0], &bgr, &mut out);
+        assert_eq!(out[0], 255.0);
+    }
+}
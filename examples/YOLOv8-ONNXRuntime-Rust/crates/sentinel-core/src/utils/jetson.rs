@@ -0,0 +1,278 @@
+/// Jetson 功耗与温度监控 (tegrastats)
+/// Jetson power/thermal monitoring via tegrastats
+///
+/// 后台线程运行 `tegrastats --interval <ms>` 并逐行解析温度与电源轨功耗,
+/// 按带回差的温度阈值得到降频信号 [`ThrottleLevel`]. 检测线程按
+/// [`ThrottleLevel::frame_stride`] 降低推理频率, 在设备真正触发硬件降频前主动减负
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+/// 降频信号
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ThrottleLevel {
+    #[default]
+    Normal,
+    /// 温度偏高, 隔帧推理
+    Warm,
+    /// 接近降频温度, 每4帧推理一次
+    Throttled,
+}
+
+impl ThrottleLevel {
+    /// 每隔多少帧推理一次
+    pub fn frame_stride(&self) -> u64 {
+        match self {
+            ThrottleLevel::Normal => 1,
+            ThrottleLevel::Warm => 2,
+            ThrottleLevel::Throttled => 4,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ThrottleLevel::Normal => "正常",
+            ThrottleLevel::Warm => "偏热",
+            ThrottleLevel::Throttled => "降频",
+        }
+    }
+}
+
+/// 温度阈值 (°C), 升级立即生效, 降级需低于阈值 `hysteresis`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ThermalPolicy {
+    pub warm: f32,
+    pub throttle: f32,
+    pub hysteresis: f32,
+}
+
+impl Default for ThermalPolicy {
+    /// Orin NX 结温约 99°C 开始软件降频, 提前减负
+    fn default() -> Self {
+        Self {
+            warm: 80.0,
+            throttle: 90.0,
+            hysteresis: 5.0,
+        }
+    }
+}
+
+impl ThermalPolicy {
+    /// 根据当前等级与最高温度计算下一等级
+    pub fn next(&self, current: ThrottleLevel, temperature: f32) -> ThrottleLevel {
+        let target = if temperature >= self.throttle {
+            ThrottleLevel::Throttled
+        } else if temperature >= self.warm {
+            ThrottleLevel::Warm
+        } else {
+            ThrottleLevel::Normal
+        };
+        if target >= current {
+            return target;
+        }
+        // 降级: 温度须低于当前等级阈值减回差
+        let threshold = match current {
+            ThrottleLevel::Throttled => self.throttle,
+            ThrottleLevel::Warm => self.warm,
+            ThrottleLevel::Normal => return target,
+        };
+        if temperature < threshold - self.hysteresis {
+            target
+        } else {
+            current
+        }
+    }
+}
+
+/// tegrastats 单行采样
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TegraSample {
+    /// (传感器, °C)
+    pub temperatures: Vec<(String, f32)>,
+    /// (电源轨, 瞬时功耗 mW)
+    pub rails: Vec<(String, f32)>,
+    /// GPU 负载 (%)
+    pub gpu_load: Option<f32>,
+}
+
+impl TegraSample {
+    /// 解析一行 tegrastats 输出 (兼容 Nano/Xavier/Orin 格式), 无任何读数时返回 None
+    pub fn parse(line: &str) -> Option<Self> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let mut sample = TegraSample::default();
+        for (i, token) in tokens.iter().enumerate() {
+            // 温度: cpu@47.5C / CPU@48C
+            if let Some((name, value)) = token.split_once('@') {
+                if let Some(value) = value.strip_suffix('C') {
+                    if let Ok(t) = value.parse::<f32>() {
+                        sample.temperatures.push((name.to_string(), t));
+                    }
+                }
+                continue;
+            }
+            // 功耗: VDD_IN 5012mW/4980mW 或 POM_5V_IN 3416/3416
+            if let Some((now, avg)) = token.split_once('/') {
+                let rail = i.checked_sub(1).map(|p| tokens[p]);
+                let power = now.trim_end_matches("mW").parse::<f32>();
+                let avg_ok = avg.trim_end_matches("mW").parse::<f32>().is_ok();
+                if let (Some(rail), Ok(power), true) = (rail, power, avg_ok) {
+                    if rail.starts_with("VDD_") || rail.starts_with("POM_") {
+                        sample.rails.push((rail.to_string(), power));
+                    }
+                }
+                continue;
+            }
+            // GPU 负载: GR3D_FREQ 12% 或 GR3D_FREQ 12%@[905]
+            if *token == "GR3D_FREQ" {
+                sample.gpu_load = tokens
+                    .get(i + 1)
+                    .and_then(|v| v.split(['%', '@']).next())
+                    .and_then(|v| v.parse().ok());
+            }
+        }
+        let empty = sample.temperatures.is_empty() && sample.rails.is_empty();
+        (!empty || sample.gpu_load.is_some()).then_some(sample)
+    }
+
+    /// 最高温度 (忽略固定上报的 PMIC 与无效的 -256°C 读数)
+    pub fn max_temperature(&self) -> Option<f32> {
+        self.temperatures
+            .iter()
+            .filter(|(name, t)| !name.eq_ignore_ascii_case("PMIC") && *t > -100.0)
+            .map(|&(_, t)| t)
+            .reduce(f32::max)
+    }
+
+    /// 整机输入功耗 (mW): 优先 VDD_IN / POM_5V_IN, 否则各电源轨之和
+    pub fn total_power_mw(&self) -> Option<f32> {
+        if self.rails.is_empty() {
+            return None;
+        }
+        self.rails
+            .iter()
+            .find(|(name, _)| name == "VDD_IN" || name == "POM_5V_IN")
+            .map(|&(_, p)| p)
+            .or_else(|| Some(self.rails.iter().map(|&(_, p)| p).sum()))
+    }
+}
+
+/// 最近一次采样的摘要 (随检测结果发送给渲染线程)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct JetsonStatus {
+    pub power_mw: Option<f32>,
+    pub temperature_c: Option<f32>,
+    pub gpu_load: Option<f32>,
+    pub throttle: ThrottleLevel,
+}
+
+/// tegrastats 后台监控 (克隆后共享同一采样)
+#[derive(Clone)]
+pub struct JetsonMonitor {
+    status: Arc<Mutex<Option<JetsonStatus>>>,
+}
+
+impl JetsonMonitor {
+    /// 启动 tegrastats, 非 Jetson 设备 (无 tegrastats) 返回错误
+    pub fn spawn(interval_ms: u32, policy: ThermalPolicy) -> std::io::Result<Self> {
+        let mut child = Command::new("tegrastats")
+            .args(["--interval", &interval_ms.to_string()])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdout = child.stdout.take().expect("tegrastats stdout");
+        let status = Arc::new(Mutex::new(None));
+        let shared = Arc::clone(&status);
+
+        std::thread::spawn(move || {
+            println!("🌡️ tegrastats 监控启动 (间隔 {}ms)", interval_ms);
+            let mut level = ThrottleLevel::Normal;
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                let Some(sample) = TegraSample::parse(&line) else {
+                    continue;
+                };
+                let temperature = sample.max_temperature();
+                if let Some(t) = temperature {
+                    let next = policy.next(level, t);
+                    if next != level {
+                        println!(
+                            "🌡️ 温控状态: {} → {} ({:.1}°C)",
+                            level.name(),
+                            next.name(),
+                            t
+                        );
+                        level = next;
+                    }
+                }
+                *shared.lock().unwrap() = Some(JetsonStatus {
+                    power_mw: sample.total_power_mw(),
+                    temperature_c: temperature,
+                    gpu_load: sample.gpu_load,
+                    throttle: level,
+                });
+            }
+            let _ = child.wait();
+            println!("❌ tegrastats 监控退出");
+        });
+
+        Ok(Self { status })
+    }
+
+    /// 最近一次采样, 尚无数据时为 None
+    pub fn status(&self) -> Option<JetsonStatus> {
+        *self.status.lock().unwrap()
+    }
+
+    /// 当前降频信号
+    pub fn throttle(&self) -> ThrottleLevel {
+        self.status().map(|s| s.throttle).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Orin 与 Nano 两种 tegrastats 格式
+    #[test]
+    fn test_parse_tegrastats() {
+        let orin = "10-16-2026 14:03:11 RAM 2448/7620MB (lfb 4x4MB) SWAP 0/3810MB (cached 0MB) \
+            CPU [12%@1984,8%@1984,off,off,3%@729,2%@729] EMC_FREQ 0%@3199 GR3D_FREQ 61%@[918,0] \
+            cpu@71.5C soc2@68.2C soc0@69.9C gpu@74.3C tj@86.1C soc1@68.4C \
+            VDD_IN 14862mW/13950mW VDD_CPU_GPU_CV 6421mW/5977mW VDD_SOC 3612mW/3520mW";
+        let s = TegraSample::parse(orin).unwrap();
+        assert_eq!(s.temperatures.len(), 6);
+        assert_eq!(s.max_temperature(), Some(86.1));
+        assert_eq!(s.total_power_mw(), Some(14862.0));
+        assert_eq!(s.rails.len(), 3);
+        assert_eq!(s.gpu_load, Some(61.0));
+
+        let nano = "RAM 1795/3956MB (lfb 92x4MB) SWAP 0/1978MB (cached 0MB) \
+            CPU [9%@1479,5%@1479,6%@1479,4%@1479] EMC_FREQ 0% GR3D_FREQ 0% \
+            PLL@36C CPU@38.5C PMIC@100C GPU@37C AO@44.5C thermal@37.75C \
+            POM_5V_IN 2556/2556 POM_5V_GPU 0/0 POM_5V_CPU 412/412";
+        let s = TegraSample::parse(nano).unwrap();
+        assert_eq!(s.max_temperature(), Some(44.5));
+        assert_eq!(s.total_power_mw(), Some(2556.0));
+        assert_eq!(s.gpu_load, Some(0.0));
+
+        assert_eq!(TegraSample::parse("tegrastats: command not found"), None);
+    }
+
+    /// 降频信号: 升级立即生效, 降级需要回差
+    #[test]
+    fn test_thermal_policy_hysteresis() {
+        let policy = ThermalPolicy::default();
+        let mut level = ThrottleLevel::Normal;
+        let mut trace = Vec::new();
+        for t in [70.0, 82.0, 91.0, 88.0, 86.0, 84.0, 78.0, 74.0] {
+            level = policy.next(level, t);
+            trace.push(level);
+        }
+        use ThrottleLevel::*;
+        assert_eq!(
+            trace,
+            vec![Normal, Warm, Throttled, Throttled, Throttled, Warm, Warm, Normal]
+        );
+        assert_eq!(Throttled.frame_stride(), 4);
+    }
+}
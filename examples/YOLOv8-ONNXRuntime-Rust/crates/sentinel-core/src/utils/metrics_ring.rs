@@ -0,0 +1,117 @@
+//! 指标环形缓冲 (Metrics Ring)
+//!
+//! 渲染线程每秒写入一次解码/检测/渲染帧率、推理耗时与平均置信度,
+//! 控制面板读取最近 60 秒画迷你曲线 (sparkline). 缓冲经 Arc<Mutex> 共享,
+//! 其他线程也可读取同一份历史
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// 保留的采样数 (每秒一个, 即最近 60 秒)
+pub const HISTORY_SECS: usize = 60;
+
+/// 记录的指标
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    DecodeFps,
+    DetectFps,
+    RenderFps,
+    InferenceMs,    // 推理阶段平均耗时
+    MeanConfidence, // 当前检测框的平均置信度
+}
+
+impl Metric {
+    pub const ALL: [Metric; 5] = [
+        Metric::DecodeFps,
+        Metric::DetectFps,
+        Metric::RenderFps,
+        Metric::InferenceMs,
+        Metric::MeanConfidence,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// 各指标最近 N 个采样
+#[derive(Clone, Debug)]
+pub struct MetricsRing {
+    capacity: usize,
+    series: [VecDeque<f32>; Metric::ALL.len()],
+}
+
+/// 渲染线程与控制面板共享的指标缓冲
+pub type SharedMetrics = Arc<Mutex<MetricsRing>>;
+
+impl Default for MetricsRing {
+    fn default() -> Self {
+        Self::new(HISTORY_SECS)
+    }
+}
+
+impl MetricsRing {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            series: Default::default(),
+        }
+    }
+
+    pub fn shared() -> SharedMetrics {
+        Arc::new(Mutex::new(Self::default()))
+    }
+
+    /// 追加一个采样, 超出容量时丢弃最旧的
+    pub fn push(&mut self, metric: Metric, value: f32) {
+        let series = &mut self.series[metric.index()];
+        series.push_back(if value.is_finite() { value } else { 0.0 });
+        while series.len() > self.capacity {
+            series.pop_front();
+        }
+    }
+
+    /// 按时间顺序 (旧 → 新) 的采样
+    pub fn series(&self, metric: Metric) -> &VecDeque<f32> {
+        &self.series[metric.index()]
+    }
+
+    /// 最新采样
+    pub fn latest(&self, metric: Metric) -> Option<f32> {
+        self.series(metric).back().copied()
+    }
+
+    /// 采样范围 (最小值, 最大值), 没有采样时为 None
+    pub fn range(&self, metric: Metric) -> Option<(f32, f32)> {
+        let series = self.series(metric);
+        let min = series.iter().copied().reduce(f32::min)?;
+        let max = series.iter().copied().reduce(f32::max)?;
+        Some((min, max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 超出容量时丢弃最旧的采样, 各指标互不影响, 非有限值记为 0
+    #[test]
+    fn test_ring_capacity() {
+        let mut ring = MetricsRing::new(3);
+        for v in [1.0, 2.0, 3.0, 4.0] {
+            ring.push(Metric::DecodeFps, v);
+        }
+        ring.push(Metric::InferenceMs, f32::NAN);
+        assert_eq!(
+            ring.series(Metric::DecodeFps)
+                .iter()
+                .copied()
+                .collect::<Vec<_>>(),
+            vec![2.0, 3.0, 4.0]
+        );
+        assert_eq!(ring.latest(Metric::DecodeFps), Some(4.0));
+        assert_eq!(ring.range(Metric::DecodeFps), Some((2.0, 4.0)));
+        assert_eq!(ring.latest(Metric::InferenceMs), Some(0.0));
+        assert_eq!(ring.range(Metric::RenderFps), None);
+    }
+}
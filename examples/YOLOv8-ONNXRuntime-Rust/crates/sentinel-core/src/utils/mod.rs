@@ -0,0 +1,17 @@
+/// 工具模块
+/// Utility modules
+pub mod affine_transform;
+pub mod affine_transform_simd;
+pub mod clock; // 时间戳 (单调时钟/UTC 墙上时钟)、显示时区与摄像头时钟漂移
+pub mod colormap; // 热成像伪彩色调色板
+pub mod coords; // 坐标空间 (推理输入/画面/窗口) 与变换
+pub mod fisheye; // 鱼眼去畸变 (虚拟透视视图)
+pub mod hdr; // 10-bit/HDR 帧与色调映射
+pub mod jetson; // Jetson tegrastats 功耗/温控监控
+pub mod metrics_ring; // 指标环形缓冲 (控制面板迷你曲线)
+pub mod orientation; // 画面旋转/镜像校正
+pub mod resize; // 检测输入缩放 (最近邻/双线性/Lanczos 预设)
+pub mod yuv_preprocess; // YUV420 → NCHW 融合预处理
+
+#[cfg(feature = "gpu")]
+pub mod affine_transform_wgpu;
@@ -0,0 +1,288 @@
+/// 画面方向校正 (吊装/侧装摄像头)
+/// Camera orientation: clockwise rotation + horizontal mirror
+///
+/// 解码过滤器先在紧凑 YUV 平面上 (1.5字节/像素) 完成旋转/镜像, 再转 RGBA,
+/// 检测/跟踪/渲染都工作在校正后的画面上, 检测框坐标天然一致.
+/// 需要回到传感器原始坐标 (如 PTZ 控制、外部标注) 时用 [`Orientation::box_to_source`]
+use std::collections::HashMap;
+use std::fs;
+
+use crate::detection::types::BBox;
+use crate::utils::yuv_preprocess::Yuv420Frame;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 画面方向配置文件路径
+pub const ORIENTATION_CONFIG_PATH: &str = "orientation_config.json";
+
+/// 顺时针旋转角度
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Rotation {
+    #[default]
+    R0,
+    R90,
+    R180,
+    R270,
+}
+
+impl Rotation {
+    pub const ALL: [Rotation; 4] = [Rotation::R0, Rotation::R90, Rotation::R180, Rotation::R270];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rotation::R0 => "0°",
+            Rotation::R90 => "90°",
+            Rotation::R180 => "180°",
+            Rotation::R270 => "270°",
+        }
+    }
+
+    /// 是否交换宽高
+    pub fn transposes(&self) -> bool {
+        matches!(self, Rotation::R90 | Rotation::R270)
+    }
+}
+
+/// 画面方向: 先顺时针旋转, 再水平镜像
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Orientation {
+    pub rotation: Rotation,
+    pub mirror: bool,
+}
+
+impl Orientation {
+    pub fn is_identity(&self) -> bool {
+        self.rotation == Rotation::R0 && !self.mirror
+    }
+
+    /// 编码为单字节 (供原子变量跨线程共享)
+    pub fn to_bits(self) -> u8 {
+        self.rotation as u8 | (self.mirror as u8) << 2
+    }
+
+    pub fn from_bits(bits: u8) -> Self {
+        Self {
+            rotation: Rotation::ALL[(bits & 0b11) as usize],
+            mirror: bits & 0b100 != 0,
+        }
+    }
+
+    /// 校正后的画面尺寸
+    pub fn output_size(&self, w: usize, h: usize) -> (usize, usize) {
+        if self.rotation.transposes() {
+            (h, w)
+        } else {
+            (w, h)
+        }
+    }
+
+    /// 校正后像素 (x, y) 对应的源像素, 源画面尺寸 w×h
+    pub fn source_pixel(&self, x: usize, y: usize, w: usize, h: usize) -> (usize, usize) {
+        let (out_w, _) = self.output_size(w, h);
+        let x = if self.mirror { out_w - 1 - x } else { x };
+        match self.rotation {
+            Rotation::R0 => (x, y),
+            Rotation::R90 => (y, h - 1 - x),
+            Rotation::R180 => (w - 1 - x, h - 1 - y),
+            Rotation::R270 => (w - 1 - y, x),
+        }
+    }
+
+    /// 源画面坐标 → 校正后坐标 (连续坐标, 源画面尺寸 w×h)
+    pub fn point_from_source(&self, x: f32, y: f32, w: f32, h: f32) -> (f32, f32) {
+        let (x, y) = match self.rotation {
+            Rotation::R0 => (x, y),
+            Rotation::R90 => (h - y, x),
+            Rotation::R180 => (w - x, h - y),
+            Rotation::R270 => (y, w - x),
+        };
+        let out_w = if self.rotation.transposes() { h } else { w };
+        if self.mirror {
+            (out_w - x, y)
+        } else {
+            (x, y)
+        }
+    }
+
+    /// 校正后坐标 → 源画面坐标 (连续坐标, 源画面尺寸 w×h)
+    pub fn point_to_source(&self, x: f32, y: f32, w: f32, h: f32) -> (f32, f32) {
+        let out_w = if self.rotation.transposes() { h } else { w };
+        let x = if self.mirror { out_w - x } else { x };
+        match self.rotation {
+            Rotation::R0 => (x, y),
+            Rotation::R90 => (y, h - x),
+            Rotation::R180 => (w - x, h - y),
+            Rotation::R270 => (w - y, x),
+        }
+    }
+
+    /// 校正后画面上的检测框映射回源画面 (源画面尺寸 w×h)
+    pub fn box_to_source(&self, bbox: &BBox, w: f32, h: f32) -> BBox {
+        let (ax, ay) = self.point_to_source(bbox.x1, bbox.y1, w, h);
+        let (bx, by) = self.point_to_source(bbox.x2, bbox.y2, w, h);
+        BBox {
+            x1: ax.min(bx),
+            y1: ay.min(by),
+            x2: ax.max(bx),
+            y2: ay.max(by),
+            ..bbox.clone()
+        }
+    }
+
+    /// 旋转/镜像单个平面 (w×h, 无行填充), `dst` 按输出尺寸重新分配
+    pub fn apply_plane(&self, src: &[u8], w: usize, h: usize, dst: &mut Vec<u8>) {
+        let (out_w, out_h) = self.output_size(w, h);
+        dst.resize(out_w * out_h, 0);
+        if out_w == 0 {
+            return;
+        }
+        dst.par_chunks_mut(out_w).enumerate().for_each(|(y, row)| {
+            for (x, px) in row.iter_mut().enumerate() {
+                let (sx, sy) = self.source_pixel(x, y, w, h);
+                *px = src[sy * w + sx];
+            }
+        });
+    }
+
+    /// 旋转/镜像 YUV420P 帧的三个平面
+    pub fn apply_yuv(&self, src: &Yuv420Frame, dst: &mut Yuv420Frame) {
+        let (out_w, out_h) = self.output_size(src.width, src.height);
+        let (cw, ch) = (src.chroma_width(), src.chroma_height());
+        self.apply_plane(&src.y, src.width, src.height, &mut dst.y);
+        self.apply_plane(&src.u, cw, ch, &mut dst.u);
+        self.apply_plane(&src.v, cw, ch, &mut dst.v);
+        dst.width = out_w;
+        dst.height = out_h;
+    }
+}
+
+/// 按输入源保存的画面方向 (键为 `InputSource::key`)
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrientationConfig {
+    pub sources: HashMap<String, Orientation>,
+}
+
+impl OrientationConfig {
+    /// 从JSON文件加载, 文件不存在时为空
+    pub fn load(path: &str) -> Self {
+        match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("⚠️  画面方向配置解析失败: {}, 使用默认值", e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// 保存到JSON文件
+    pub fn save(&self, path: &str) {
+        match serde_json::to_string_pretty(self) {
+            Ok(json) => {
+                if let Err(e) = fs::write(path, json) {
+                    eprintln!("❌ 保存画面方向配置失败: {}", e);
+                }
+            }
+            Err(e) => eprintln!("❌ 序列化画面方向配置失败: {}", e),
+        }
+    }
+
+    /// 输入源的画面方向, 未配置时不旋转
+    pub fn get(&self, source_key: &str) -> Orientation {
+        self.sources.get(source_key).copied().unwrap_or_default()
+    }
+
+    /// 设置输入源的画面方向 (不旋转时移除条目)
+    pub fn set(&mut self, source_key: String, orientation: Orientation) {
+        if orientation.is_identity() {
+            self.sources.remove(&source_key);
+        } else {
+            self.sources.insert(source_key, orientation);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all() -> Vec<Orientation> {
+        Rotation::ALL
+            .iter()
+            .flat_map(|&rotation| [false, true].map(|mirror| Orientation { rotation, mirror }))
+            .collect()
+    }
+
+    /// 3×2 平面旋转 90° 后为 2×3, 像素位置与坐标映射一致
+    #[test]
+    fn test_apply_plane() {
+        // 0 1 2
+        // 3 4 5
+        let src = [0u8, 1, 2, 3, 4, 5];
+        let mut dst = Vec::new();
+        let cw90 = Orientation {
+            rotation: Rotation::R90,
+            mirror: false,
+        };
+        cw90.apply_plane(&src, 3, 2, &mut dst);
+        assert_eq!(dst, vec![3, 0, 4, 1, 5, 2]);
+
+        let mirrored = Orientation {
+            rotation: Rotation::R0,
+            mirror: true,
+        };
+        mirrored.apply_plane(&src, 3, 2, &mut dst);
+        assert_eq!(dst, vec![2, 1, 0, 5, 4, 3]);
+
+        // 每个源像素中心经连续坐标映射后落在对应输出像素上
+        for o in all() {
+            o.apply_plane(&src, 3, 2, &mut dst);
+            let (out_w, _) = o.output_size(3, 2);
+            for (i, &v) in src.iter().enumerate() {
+                let (sx, sy) = ((i % 3) as f32 + 0.5, (i / 3) as f32 + 0.5);
+                let (x, y) = o.point_from_source(sx, sy, 3.0, 2.0);
+                assert_eq!(dst[y as usize * out_w + x as usize], v, "{:?}", o);
+            }
+        }
+    }
+
+    /// 坐标往返映射与位编码
+    #[test]
+    fn test_round_trip() {
+        let bbox = BBox {
+            x1: 10.0,
+            y1: 20.0,
+            x2: 110.0,
+            y2: 60.0,
+            confidence: 0.9,
+            class_id: 0,
+        };
+        for o in all() {
+            assert_eq!(Orientation::from_bits(o.to_bits()), o);
+            let (x, y) = o.point_from_source(30.0, 40.0, 640.0, 480.0);
+            let (sx, sy) = o.point_to_source(x, y, 640.0, 480.0);
+            assert!(
+                (sx - 30.0).abs() < 1e-4 && (sy - 40.0).abs() < 1e-4,
+                "{:?}",
+                o
+            );
+
+            // 源框 → 校正后框 → 源框
+            let (ax, ay) = o.point_from_source(bbox.x1, bbox.y1, 640.0, 480.0);
+            let (bx, by) = o.point_from_source(bbox.x2, bbox.y2, 640.0, 480.0);
+            let oriented = BBox {
+                x1: ax.min(bx),
+                y1: ay.min(by),
+                x2: ax.max(bx),
+                y2: ay.max(by),
+                ..bbox.clone()
+            };
+            let back = o.box_to_source(&oriented, 640.0, 480.0);
+            assert_eq!(
+                (back.x1, back.y1, back.x2, back.y2),
+                (10.0, 20.0, 110.0, 60.0)
+            );
+        }
+    }
+}
@@ -0,0 +1,198 @@
+//! 检测输入缩放 (fast_image_resize SIMD)
+//!
+//! 整帧拉伸缩放到推理输入尺寸的算法, 运行时可切换 (控制面板或启动参数 `--resize`):
+//! - `nearest`: 最快, 可与 YUV 融合预处理合并 (默认)
+//! - `bilinear`: 远处小目标的边缘更平滑, 耗时约为最近邻的数倍
+//! - `lanczos`: Lanczos3 卷积, 质量最高, 最慢
+//!
+//! 非最近邻算法不走 YUV 融合采样, 由 RGBA 整帧缩放.
+//! [`benchmark`] 在同一帧上比较各算法耗时 (`cargo run --release --example resize_benchmark`)
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use fast_image_resize as fr;
+use fr::images::{Image, ImageRef};
+use serde::{Deserialize, Serialize};
+
+/// 缩放算法预设
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ResizeQuality {
+    #[default]
+    Nearest,
+    Bilinear,
+    Lanczos,
+}
+
+impl ResizeQuality {
+    pub const ALL: [ResizeQuality; 3] = [
+        ResizeQuality::Nearest,
+        ResizeQuality::Bilinear,
+        ResizeQuality::Lanczos,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ResizeQuality::Nearest => "Nearest",
+            ResizeQuality::Bilinear => "Bilinear",
+            ResizeQuality::Lanczos => "Lanczos3",
+        }
+    }
+
+    fn algorithm(self) -> fr::ResizeAlg {
+        match self {
+            ResizeQuality::Nearest => fr::ResizeAlg::Nearest,
+            ResizeQuality::Bilinear => fr::ResizeAlg::Convolution(fr::FilterType::Bilinear),
+            ResizeQuality::Lanczos => fr::ResizeAlg::Convolution(fr::FilterType::Lanczos3),
+        }
+    }
+}
+
+/// 进程级默认算法 (启动参数设置, 之后创建的检测线程生效)
+static DEFAULT_QUALITY: AtomicU8 = AtomicU8::new(ResizeQuality::Nearest as u8);
+
+/// 设置检测线程的默认缩放算法
+pub fn set_default_quality(quality: ResizeQuality) {
+    DEFAULT_QUALITY.store(quality as u8, Ordering::Relaxed);
+}
+
+/// 当前的默认缩放算法
+pub fn default_quality() -> ResizeQuality {
+    match DEFAULT_QUALITY.load(Ordering::Relaxed) {
+        1 => ResizeQuality::Bilinear,
+        2 => ResizeQuality::Lanczos,
+        _ => ResizeQuality::Nearest,
+    }
+}
+
+/// 整帧缩放器 (复用 fast_image_resize 的卷积系数缓存与输出缓冲)
+pub struct FrameResizer {
+    quality: ResizeQuality,
+    resizer: fr::Resizer,
+    rgba: Image<'static>,
+}
+
+impl FrameResizer {
+    pub fn new(quality: ResizeQuality) -> Self {
+        Self {
+            quality,
+            resizer: fr::Resizer::new(),
+            rgba: Image::new(1, 1, fr::PixelType::U8x4),
+        }
+    }
+
+    pub fn quality(&self) -> ResizeQuality {
+        self.quality
+    }
+
+    pub fn set_quality(&mut self, quality: ResizeQuality) {
+        self.quality = quality;
+    }
+
+    /// RGBA 整帧 (width×height) 拉伸缩放为 dst 尺寸的 RGB
+    pub fn rgba_to_rgb(
+        &mut self,
+        src: &[u8],
+        width: u32,
+        height: u32,
+        (dst_w, dst_h): (u32, u32),
+    ) -> Result<Vec<u8>> {
+        let src = ImageRef::new(width, height, src, fr::PixelType::U8x4)
+            .with_context(|| format!("RGBA 缓冲与 {}x{} 不匹配", width, height))?;
+        if (self.rgba.width(), self.rgba.height()) != (dst_w, dst_h) {
+            self.rgba = Image::new(dst_w, dst_h, fr::PixelType::U8x4);
+        }
+        // 解码帧不透明, 不做 alpha 预乘
+        let options = fr::ResizeOptions::new()
+            .resize_alg(self.quality.algorithm())
+            .use_alpha(false);
+        self.resizer
+            .resize(&src, &mut self.rgba, &options)
+            .with_context(|| format!("{} 缩放失败", self.quality.label()))?;
+
+        let mut rgb = Vec::with_capacity(dst_w as usize * dst_h as usize * 3);
+        for pixel in self.rgba.buffer().chunks_exact(4) {
+            rgb.extend_from_slice(&pixel[..3]);
+        }
+        Ok(rgb)
+    }
+}
+
+/// 单个算法的缩放耗时
+#[derive(Clone, Copy, Debug)]
+pub struct ResizeTiming {
+    pub quality: ResizeQuality,
+    pub mean_ms: f64,
+    pub min_ms: f64,
+}
+
+/// 同一 RGBA 帧按各算法各缩放 iterations 次 (先预热一次), 返回每种算法的耗时
+pub fn benchmark(
+    src: &[u8],
+    width: u32,
+    height: u32,
+    dst: (u32, u32),
+    iterations: usize,
+) -> Result<Vec<ResizeTiming>> {
+    let iterations = iterations.max(1);
+    let mut timings = Vec::new();
+    for quality in ResizeQuality::ALL {
+        let mut resizer = FrameResizer::new(quality);
+        resizer.rgba_to_rgb(src, width, height, dst)?;
+        let (mut total, mut min) = (0.0, f64::MAX);
+        for _ in 0..iterations {
+            let start = Instant::now();
+            resizer.rgba_to_rgb(src, width, height, dst)?;
+            let ms = start.elapsed().as_secs_f64() * 1000.0;
+            total += ms;
+            min = min.min(ms);
+        }
+        timings.push(ResizeTiming {
+            quality,
+            mean_ms: total / iterations as f64,
+            min_ms: min,
+        });
+    }
+    Ok(timings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 纯色帧经任一算法缩放后颜色不变, alpha 通道被去掉
+    #[test]
+    fn test_rgba_to_rgb() {
+        let src: Vec<u8> = [10u8, 20, 30, 255].repeat(64 * 48);
+        for quality in ResizeQuality::ALL {
+            let mut resizer = FrameResizer::new(quality);
+            let rgb = resizer.rgba_to_rgb(&src, 64, 48, (32, 32)).unwrap();
+            assert_eq!(rgb.len(), 32 * 32 * 3);
+            assert!(
+                rgb.chunks_exact(3).all(|p| p == [10, 20, 30]),
+                "{:?}",
+                quality
+            );
+        }
+        let mut resizer = FrameResizer::new(ResizeQuality::Bilinear);
+        assert!(resizer.rgba_to_rgb(&src, 64, 64, (32, 32)).is_err());
+    }
+
+    /// 基准测试覆盖所有预设; 默认算法可按进程设置
+    #[test]
+    fn test_benchmark_and_default() {
+        let src = vec![128u8; 80 * 60 * 4];
+        let timings = benchmark(&src, 80, 60, (40, 40), 2).unwrap();
+        let qualities: Vec<_> = timings.iter().map(|t| t.quality).collect();
+        assert_eq!(qualities, ResizeQuality::ALL);
+        assert!(timings.iter().all(|t| t.min_ms <= t.mean_ms));
+
+        assert_eq!(default_quality(), ResizeQuality::Nearest);
+        set_default_quality(ResizeQuality::Lanczos);
+        assert_eq!(default_quality(), ResizeQuality::Lanczos);
+        set_default_quality(ResizeQuality::Nearest);
+    }
+}
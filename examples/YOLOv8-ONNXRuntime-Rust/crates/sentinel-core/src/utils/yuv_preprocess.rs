@@ -0,0 +1,302 @@
+/// YUV420P → NCHW 融合预处理
+/// Fused YUV420P to NCHW preprocessing
+///
+/// 原流程: YUV → RGBA(解码) → RGB缩放(检测) → DynamicImage → f32 NCHW(模型预处理)
+/// 融合后: YUV平面按最近邻映射表直接采样, 一次写入归一化的 NCHW 浮点缓冲
+///
+/// 颜色转换与解码过滤器使用相同的 BT.601 定点系数, 采样与检测器的
+/// CPU resize 映射表一致, 归一化与通道顺序由模型的 [`PreprocessSpec`] 决定,
+/// 因此结果与原流程逐值相同
+use rayon::prelude::*;
+
+use crate::models::PreprocessSpec;
+
+/// 紧凑存储的 YUV420P 帧 (无行填充, 与 FFmpeg linesize 解耦)
+#[derive(Clone, Debug, Default)]
+pub struct Yuv420Frame {
+    pub y: Vec<u8>,
+    pub u: Vec<u8>,
+    pub v: Vec<u8>,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Yuv420Frame {
+    /// 色度平面宽度 (奇数宽度向上取整)
+    pub fn chroma_width(&self) -> usize {
+        self.width.div_ceil(2)
+    }
+
+    /// 色度平面高度 (奇数高度向上取整)
+    pub fn chroma_height(&self) -> usize {
+        self.height.div_ceil(2)
+    }
+
+    /// 从带步长的平面指针拷贝, 复用已有缓冲
+    ///
+    /// # Safety
+    /// 调用方保证各平面指针在 `stride * rows` 范围内可读
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe fn copy_from_planes(
+        &mut self,
+        y_plane: *const u8,
+        u_plane: *const u8,
+        v_plane: *const u8,
+        y_stride: usize,
+        uv_stride: usize,
+        width: usize,
+        height: usize,
+    ) {
+        self.width = width;
+        self.height = height;
+        let (cw, ch) = (self.chroma_width(), self.chroma_height());
+        self.y.resize(width * height, 0);
+        self.u.resize(cw * ch, 0);
+        self.v.resize(cw * ch, 0);
+
+        for row in 0..height {
+            let src = std::slice::from_raw_parts(y_plane.add(row * y_stride), width);
+            self.y[row * width..(row + 1) * width].copy_from_slice(src);
+        }
+        for row in 0..ch {
+            let src_u = std::slice::from_raw_parts(u_plane.add(row * uv_stride), cw);
+            let src_v = std::slice::from_raw_parts(v_plane.add(row * uv_stride), cw);
+            self.u[row * cw..(row + 1) * cw].copy_from_slice(src_u);
+            self.v[row * cw..(row + 1) * cw].copy_from_slice(src_v);
+        }
+    }
+
+    /// 从 NV12 (Y 平面 + UV 交错平面, 两者同一步长) 拷贝并拆分色度, 复用已有缓冲
+    pub fn copy_from_nv12(
+        &mut self,
+        y: &[u8],
+        uv: &[u8],
+        stride: usize,
+        width: usize,
+        height: usize,
+    ) {
+        self.width = width;
+        self.height = height;
+        let (cw, ch) = (self.chroma_width(), self.chroma_height());
+        self.y.resize(width * height, 0);
+        self.u.resize(cw * ch, 0);
+        self.v.resize(cw * ch, 0);
+
+        for row in 0..height {
+            self.y[row * width..(row + 1) * width]
+                .copy_from_slice(&y[row * stride..row * stride + width]);
+        }
+        for row in 0..ch {
+            let src = &uv[row * stride..row * stride + cw * 2];
+            for (i, pair) in src.chunks_exact(2).enumerate() {
+                self.u[row * cw + i] = pair[0];
+                self.v[row * cw + i] = pair[1];
+            }
+        }
+    }
+}
+
+/// 最近邻映射表 (与检测器 CPU resize 相同的取整方式)
+pub fn nearest_map(src: usize, dst: usize) -> Vec<usize> {
+    let scale = src as f32 / dst as f32;
+    (0..dst)
+        .map(|i| ((i as f32 * scale) as usize).min(src - 1))
+        .collect()
+}
+
+/// 每次向量化处理的像素数
+const LANES: usize = 8;
+
+/// YUV420P → 归一化 NCHW
+///
+/// - `x_map`/`y_map`: 目标像素到源像素的映射表, 见 [`nearest_map`]
+/// - `spec`: 模型预处理参数 (mean/std/通道顺序)
+/// - `out`: 长度为 `spec.channels * y_map.len() * x_map.len()` 的 CHW 缓冲
+///
+/// 单通道模型直接取 Y 平面 (即 BT.601 亮度), 不做色彩转换
+///
+/// 按行 rayon 并行; 行内每 8 像素先收集 Y/U/V 到定长数组, 再做定点运算,
+/// 便于编译器生成 SIMD 指令
+pub fn yuv420_to_nchw(
+    frame: &Yuv420Frame,
+    x_map: &[usize],
+    y_map: &[usize],
+    spec: &PreprocessSpec,
+    out: &mut [f32],
+) {
+    let (dw, dh) = (x_map.len(), y_map.len());
+    let plane = dw * dh;
+    assert_eq!(out.len(), plane * spec.channels, "NCHW 缓冲长度不匹配");
+
+    let lut = spec.lut();
+    if spec.channels == 1 {
+        out.par_chunks_exact_mut(dw)
+            .enumerate()
+            .for_each(|(row, out_row)| {
+                let sy = y_map[row];
+                let y_row = &frame.y[sy * frame.width..(sy + 1) * frame.width];
+                for (dst, &sx) in out_row.iter_mut().zip(x_map) {
+                    *dst = lut[0][y_row[sx] as usize];
+                }
+            });
+        return;
+    }
+    let src: [usize; 3] = std::array::from_fn(|c| spec.source_channel(c));
+    let cw = frame.chroma_width();
+    let (c0_plane, rest) = out.split_at_mut(plane);
+    let (c1_plane, c2_plane) = rest.split_at_mut(plane);
+
+    c0_plane
+        .par_chunks_exact_mut(dw)
+        .zip(c1_plane.par_chunks_exact_mut(dw))
+        .zip(c2_plane.par_chunks_exact_mut(dw))
+        .enumerate()
+        .for_each(|(row, ((c0_row, c1_row), c2_row))| {
+            let sy = y_map[row];
+            let y_row = &frame.y[sy * frame.width..(sy + 1) * frame.width];
+            let u_row = &frame.u[(sy >> 1) * cw..((sy >> 1) + 1) * cw];
+            let v_row = &frame.v[(sy >> 1) * cw..((sy >> 1) + 1) * cw];
+
+            for start in (0..dw).step_by(LANES) {
+                let n = LANES.min(dw - start);
+                let mut yv = [0i32; LANES];
+                let mut uv = [0i32; LANES];
+                let mut vv = [0i32; LANES];
+                for k in 0..n {
+                    let sx = x_map[start + k];
+                    yv[k] = y_row[sx] as i32;
+                    uv[k] = u_row[sx >> 1] as i32 - 128;
+                    vv[k] = v_row[sx >> 1] as i32 - 128;
+                }
+
+                let mut rgb = [[0u8; LANES]; 3];
+                for k in 0..LANES {
+                    rgb[0][k] = (yv[k] + ((vv[k] * 179) >> 7)).clamp(0, 255) as u8;
+                    rgb[1][k] =
+                        (yv[k] - ((uv[k] * 44) >> 7) - ((vv[k] * 91) >> 7)).clamp(0, 255) as u8;
+                    rgb[2][k] = (yv[k] + ((uv[k] * 227) >> 7)).clamp(0, 255) as u8;
+                }
+
+                for k in 0..n {
+                    c0_row[start + k] = lut[0][rgb[src[0]][k] as usize];
+                    c1_row[start + k] = lut[1][rgb[src[1]][k] as usize];
+                    c2_row[start + k] = lut[2][rgb[src[2]][k] as usize];
+                }
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ModelType;
+
+    /// 参考实现: YUV → RGBA (解码过滤器标量公式) → 最近邻缩放 → 按 spec 归一化
+    fn reference(frame: &Yuv420Frame, dw: usize, dh: usize, spec: &PreprocessSpec) -> Vec<f32> {
+        let (w, h, cw) = (frame.width, frame.height, frame.chroma_width());
+        let mut rgba = vec![255u8; w * h * 4];
+        for y in 0..h {
+            for x in 0..w {
+                let y_val = frame.y[y * w + x] as i32;
+                let u_val = frame.u[(y >> 1) * cw + (x >> 1)] as i32 - 128;
+                let v_val = frame.v[(y >> 1) * cw + (x >> 1)] as i32 - 128;
+                let i = (y * w + x) * 4;
+                rgba[i] = (y_val + ((v_val * 179) >> 7)).clamp(0, 255) as u8;
+                rgba[i + 1] =
+                    (y_val - ((u_val * 44) >> 7) - ((v_val * 91) >> 7)).clamp(0, 255) as u8;
+                rgba[i + 2] = (y_val + ((u_val * 227) >> 7)).clamp(0, 255) as u8;
+            }
+        }
+
+        let (x_map, y_map) = (nearest_map(w, dw), nearest_map(h, dh));
+        let mut out = vec![0f32; 3 * dw * dh];
+        for (dy, &sy) in y_map.iter().enumerate() {
+            for (dx, &sx) in x_map.iter().enumerate() {
+                let i = (sy * w + sx) * 4;
+                let v = spec.normalize([rgba[i], rgba[i + 1], rgba[i + 2]]);
+                for (c, value) in v.iter().enumerate() {
+                    out[c * dw * dh + dy * dw + dx] = *value;
+                }
+            }
+        }
+        out
+    }
+
+    /// 融合预处理与 RGBA 中转流程逐值一致 (含奇数尺寸、非 8 对齐的输出宽度与 BGR 模型)
+    #[test]
+    fn test_fused_matches_rgba_path() {
+        let (w, h) = (37usize, 23usize);
+        let mut seed = 11u32;
+        let mut next = || {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 16) as u8
+        };
+        let (cw, ch) = (w.div_ceil(2), h.div_ceil(2));
+        let frame = Yuv420Frame {
+            y: (0..w * h).map(|_| next()).collect(),
+            u: (0..cw * ch).map(|_| next()).collect(),
+            v: (0..cw * ch).map(|_| next()).collect(),
+            width: w,
+            height: h,
+        };
+
+        let specs = [
+            PreprocessSpec::default(),
+            ModelType::NanoDet.preprocess_spec(),
+        ];
+        for spec in &specs {
+            for (dw, dh) in [(20, 20), (13, 30)] {
+                let mut out = vec![0f32; 3 * dw * dh];
+                yuv420_to_nchw(
+                    &frame,
+                    &nearest_map(w, dw),
+                    &nearest_map(h, dh),
+                    spec,
+                    &mut out,
+                );
+                assert_eq!(out, reference(&frame, dw, dh, spec));
+            }
+        }
+    }
+
+    /// 单通道模型直接取 Y 平面并按 mean[0]/std[0] 归一化
+    #[test]
+    fn test_single_channel_uses_luma_plane() {
+        let frame = Yuv420Frame {
+            y: vec![0, 51, 102, 153, 204, 255],
+            u: vec![0, 255],
+            v: vec![255, 0],
+            width: 3,
+            height: 2,
+        };
+        let spec = PreprocessSpec::default().with_channels(1);
+        let mut out = vec![0f32; 2 * 2];
+        yuv420_to_nchw(&frame, &[0, 2], &[0, 1], &spec, &mut out);
+        assert_eq!(out, [0u8, 102, 153, 255].map(|v| v as f32 / 255.0));
+    }
+
+    /// 带步长的平面拷贝去除行填充
+    #[test]
+    fn test_copy_from_planes_strips_padding() {
+        let (w, h, y_stride, uv_stride) = (3usize, 3usize, 8usize, 4usize);
+        let y: Vec<u8> = (0..y_stride * h).map(|i| i as u8).collect();
+        let u: Vec<u8> = (0..uv_stride * 2).map(|i| 100 + i as u8).collect();
+        let v: Vec<u8> = (0..uv_stride * 2).map(|i| 200 + i as u8).collect();
+
+        let mut frame = Yuv420Frame::default();
+        unsafe {
+            frame.copy_from_planes(
+                y.as_ptr(),
+                u.as_ptr(),
+                v.as_ptr(),
+                y_stride,
+                uv_stride,
+                w,
+                h,
+            );
+        }
+        assert_eq!(frame.y, vec![0, 1, 2, 8, 9, 10, 16, 17, 18]);
+        assert_eq!(frame.u, vec![100, 101, 104, 105]);
+        assert_eq!(frame.v, vec![200, 201, 204, 205]);
+    }
+}
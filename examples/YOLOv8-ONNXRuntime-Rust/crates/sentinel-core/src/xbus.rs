@@ -9,6 +9,9 @@
 //! - **跨运行时**: 可在 GPUI、Actix、Tokio 等不同运行时间安全通信
 //! - **灵活订阅**: 支持特定类型订阅和通用订阅两种模式
 //! - **自动清理**: 订阅凭证析构时自动取消订阅，避免内存泄漏
+//! - **弱引用订阅**: 回调只持有目标的弱引用，目标释放后订阅者在下次发布时被清理
+//! - **总线自省**: [`snapshot`] 列出各事件类型的订阅者 (订阅位置、投递/panic 次数、
+//!   回调耗时、转发队列长度)，调试 API 以 JSON 提供，用于排查扇出问题
 //!
 //! ## 架构设计
 //!
@@ -71,9 +74,15 @@
 //! - **并发**: 无锁读写，支持高并发访问
 
 use crossbeam_skiplist::SkipMap;
+use serde::Serialize;
 use std::{
     any::{Any, TypeId},
-    sync::{atomic::AtomicUsize, Arc, OnceLock, Weak},
+    panic::Location,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, OnceLock, Weak,
+    },
+    time::{Duration, Instant},
 };
 
 /// 全局事件总线实例
@@ -178,6 +187,7 @@ pub fn post<E: Any + 'static + Send + Sync>(event: E) {
 ///
 /// // 订阅在 _subscription drop 时自动取消
 /// ```
+#[track_caller]
 pub fn subscribe<E: Any + 'static + Send + Sync, F: Fn(&E) + Send + Sync + 'static>(
     f: F,
 ) -> Subscription {
@@ -185,6 +195,65 @@ pub fn subscribe<E: Any + 'static + Send + Sync, F: Fn(&E) + Send + Sync + 'stat
     bus.subscribe(f)
 }
 
+/// 以弱引用方式订阅特定类型的事件
+///
+/// 回调只持有 `target` 的弱引用，不会延长其生命周期 (如界面面板重建后旧面板可被释放)。
+/// 目标释放后回调不再被调用，订阅者在该类型下次发布时从总线移除，
+/// 即使订阅凭证已通过 [`Subscription::detach`] 交出也不会残留。
+///
+/// # 示例
+///
+/// ```rust
+/// use std::sync::{Arc, Mutex};
+/// use sentinel_core::xbus;
+///
+/// struct DetectorStatus { fps: f32 }
+///
+/// #[derive(Default)]
+/// struct StatusPanel { fps: Mutex<f32> }
+///
+/// let panel = Arc::new(StatusPanel::default());
+/// xbus::subscribe_weak::<DetectorStatus, _, _>(&panel, |panel, status| {
+///     *panel.fps.lock().unwrap() = status.fps;
+/// })
+/// .detach();
+///
+/// xbus::post(DetectorStatus { fps: 25.0 });
+/// assert_eq!(*panel.fps.lock().unwrap(), 25.0);
+/// ```
+#[track_caller]
+pub fn subscribe_weak<E, T, F>(target: &Arc<T>, f: F) -> Subscription
+where
+    E: Any + 'static + Send + Sync,
+    T: Send + Sync + 'static,
+    F: Fn(&T, &E) + Send + Sync + 'static,
+{
+    let bus = BUS.get_or_init(EventBus::new);
+    bus.subscribe_weak(target, f)
+}
+
+/// 将特定类型的事件转发到有界队列
+///
+/// 队列满时丢弃事件并计数，队列接收端关闭后订阅者在下次发布时被清理。
+/// 队列长度与丢弃数显示在 [`snapshot`] 中。
+#[track_caller]
+pub fn forward<E: Any + Clone + Send + Sync>(tx: crossbeam_channel::Sender<E>) -> Subscription {
+    let bus = BUS.get_or_init(EventBus::new);
+    bus.forward(tx)
+}
+
+/// 获取全局事件总线的订阅者快照
+pub fn snapshot() -> BusSnapshot {
+    let bus = BUS.get_or_init(EventBus::new);
+    bus.snapshot()
+}
+
+/// 清理全局事件总线中已失效的订阅者, 返回清理数量
+pub fn prune() -> usize {
+    let bus = BUS.get_or_init(EventBus::new);
+    bus.prune()
+}
+
 /// 订阅所有类型的事件
 ///
 /// 创建一个通用事件订阅，接收所有类型的事件。
@@ -229,11 +298,21 @@ pub fn subscribe<E: Any + 'static + Send + Sync, F: Fn(&E) + Send + Sync + 'stat
 ///     }
 /// });
 /// ```
+#[track_caller]
 pub fn subscribe_any<F: Fn(TypeId, &dyn Any) + Send + Sync + 'static>(f: F) -> Subscription {
     let bus = BUS.get_or_init(EventBus::new);
     bus.subscribe_any(f)
 }
 
+/// 类型化订阅回调 (接收 `&dyn Any`, 内部 downcast 为具体类型)
+type Callback = Arc<dyn Fn(&dyn Any) + Send + Sync + 'static>;
+
+/// 通用订阅回调
+type AnyCallback = Arc<dyn Fn(TypeId, &dyn Any) + Send + Sync + 'static>;
+
+/// 队列探针, 返回 (当前长度, 容量)
+type QueueProbe = Box<dyn Fn() -> (usize, Option<usize>) + Send + Sync>;
+
 /// 类型化事件订阅者
 ///
 /// 存储特定类型事件的订阅者信息。每个订阅者包含：
@@ -254,7 +333,10 @@ struct Subscriber {
     ///
     /// 包装在 `Arc` 中以支持多线程共享，接收 `&dyn Any` 参数
     /// 在实际调用时会进行类型 downcast 转换为具体类型
-    callback: Arc<dyn Fn(&dyn Any) + Send + Sync + 'static>,
+    callback: Callback,
+
+    /// 订阅位置、存活探针与投递统计 (总线自省用)
+    meta: Arc<SubscriberMeta>,
 }
 
 /// 手动实现 Sync，因为 `dyn Fn` 默认不是 Sync
@@ -301,7 +383,10 @@ struct AnySubscriber {
     /// 接收 `(TypeId, &dyn Any)` 参数：
     /// - `TypeId`: 用于识别事件的具体类型
     /// - `&dyn Any`: 事件数据的动态引用
-    callback: Arc<dyn Fn(TypeId, &dyn Any) + Send + Sync + 'static>,
+    callback: AnyCallback,
+
+    /// 订阅位置与投递统计
+    meta: Arc<SubscriberMeta>,
 }
 
 unsafe impl Sync for AnySubscriber {}
@@ -327,6 +412,140 @@ impl PartialEq for AnySubscriber {
 
 impl Eq for AnySubscriber {}
 
+/// 订阅者元数据与投递统计
+///
+/// 统计使用 `Relaxed` 原子计数，只用于调试展示，不参与同步。
+struct SubscriberMeta {
+    /// 调用 `subscribe` 的源码位置，用于在快照中识别订阅者
+    location: &'static Location<'static>,
+
+    /// 存活探针 (弱引用订阅/转发队列)，返回 false 时订阅者在下次发布时被清理
+    alive: Option<Box<dyn Fn() -> bool + Send + Sync>>,
+
+    /// 队列探针，返回 (当前长度, 容量)，无界队列容量为 None
+    queue: OnceLock<QueueProbe>,
+
+    /// 成功投递次数
+    delivered: AtomicU64,
+    /// 回调 panic 次数
+    panicked: AtomicU64,
+    /// 转发队列已满被丢弃的事件数
+    dropped: AtomicU64,
+    /// 回调累计耗时与单次最大耗时 (纳秒)
+    busy_ns: AtomicU64,
+    max_ns: AtomicU64,
+}
+
+impl SubscriberMeta {
+    fn new(location: &'static Location<'static>) -> Self {
+        SubscriberMeta {
+            location,
+            alive: None,
+            queue: OnceLock::new(),
+            delivered: AtomicU64::new(0),
+            panicked: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            busy_ns: AtomicU64::new(0),
+            max_ns: AtomicU64::new(0),
+        }
+    }
+
+    fn is_alive(&self) -> bool {
+        match &self.alive {
+            Some(alive) => alive(),
+            None => true,
+        }
+    }
+
+    /// 记录一次回调的耗时与结果
+    fn record(&self, elapsed: Duration, ok: bool) {
+        let ns = elapsed.as_nanos() as u64;
+        self.busy_ns.fetch_add(ns, Ordering::Relaxed);
+        self.max_ns.fetch_max(ns, Ordering::Relaxed);
+        if ok {
+            self.delivered.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.panicked.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn stats(&self, id: usize) -> SubscriberStats {
+        let delivered = self.delivered.load(Ordering::Relaxed);
+        let calls = delivered + self.panicked.load(Ordering::Relaxed);
+        let busy_us = self.busy_ns.load(Ordering::Relaxed) as f64 / 1000.0;
+        let (queue_len, queue_capacity) = match self.queue.get() {
+            Some(probe) => {
+                let (len, capacity) = probe();
+                (Some(len), capacity)
+            }
+            None => (None, None),
+        };
+        SubscriberStats {
+            id,
+            location: self.location.to_string(),
+            weak: self.alive.is_some(),
+            alive: self.is_alive(),
+            delivered,
+            panicked: self.panicked.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            avg_us: if calls > 0 {
+                busy_us / calls as f64
+            } else {
+                0.0
+            },
+            max_us: self.max_ns.load(Ordering::Relaxed) as f64 / 1000.0,
+            queue_len,
+            queue_capacity,
+        }
+    }
+}
+
+/// 单个事件类型的订阅者列表
+struct TypeEntry {
+    /// 事件类型名 (`std::any::type_name`)，仅用于展示
+    name: &'static str,
+    /// 该类型的发布次数
+    posted: AtomicU64,
+    /// 订阅者，键为订阅者 ID
+    subscribers: SkipMap<usize, Subscriber>,
+}
+
+/// 单个订阅者的统计
+#[derive(Clone, Debug, Serialize)]
+pub struct SubscriberStats {
+    pub id: usize,
+    /// 订阅位置 (`文件:行:列`)
+    pub location: String,
+    /// 是否为弱引用/转发订阅 (目标释放后自动清理)
+    pub weak: bool,
+    /// 目标是否仍然存活, false 表示等待清理
+    pub alive: bool,
+    pub delivered: u64,
+    pub panicked: u64,
+    pub dropped: u64,
+    /// 回调平均/最大耗时 (微秒), 同步回调耗时会直接阻塞发布方
+    pub avg_us: f64,
+    pub max_us: f64,
+    /// 关联队列的当前长度与容量, 未关联队列时为 None
+    pub queue_len: Option<usize>,
+    pub queue_capacity: Option<usize>,
+}
+
+/// 单个事件类型的统计
+#[derive(Clone, Debug, Serialize)]
+pub struct TopicStats {
+    pub event_type: &'static str,
+    pub posted: u64,
+    pub subscribers: Vec<SubscriberStats>,
+}
+
+/// 事件总线快照
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct BusSnapshot {
+    pub topics: Vec<TopicStats>,
+    pub any_subscribers: Vec<SubscriberStats>,
+}
+
 /// 统一的订阅凭证枚举
 ///
 /// 使用枚举统一管理两种不同类型的订阅：
@@ -339,6 +558,7 @@ impl Eq for AnySubscriber {}
 /// 3. **资源管理**: 统一的 Drop 实现，自动清理资源
 /// 4. **调试友好**: 提供方法查询订阅状态和类型
 #[derive(Debug)]
+#[must_use = "订阅凭证被 drop 时立即取消订阅"]
 pub enum Subscription {
     /// 类型化订阅
     ///
@@ -374,16 +594,12 @@ impl Drop for Subscription {
             Subscription::Typed { tyid, id, bus } => {
                 // 尝试升级弱引用，如果事件总线还存在
                 if let Some(bus) = bus.upgrade() {
-                    // 找到对应类型的订阅者列表
-                    if let Some(list) = bus.subscribers.get(tyid) {
-                        // 移除特定的订阅者
-                        list.value().remove(id);
-
-                        // 性能优化：如果订阅者列表为空，清理整个类型条目
-                        // 这避免了空列表占用内存，提高查找性能
-                        if list.value().is_empty() {
-                            bus.subscribers.remove(tyid);
-                        }
+                    // 找到对应类型的订阅者列表并移除特定的订阅者
+                    //
+                    // 空列表保留不删: 判空与删除之间若有新订阅插入同一列表，
+                    // 删除类型条目会让新订阅者静默失效。条目数量以事件类型数为上限
+                    if let Some(entry) = bus.subscribers.get(tyid) {
+                        entry.value().subscribers.remove(id);
                     }
                 }
             }
@@ -444,6 +660,49 @@ impl Subscription {
             Subscription::Any { bus, .. } => bus.strong_count() > 0,
         }
     }
+
+    /// 显式取消订阅
+    ///
+    /// 与 drop 效果相同，用于在作用域结束前明确释放订阅。
+    pub fn unsubscribe(self) {}
+
+    /// 交出订阅凭证，订阅不再随凭证 drop 取消
+    ///
+    /// 用于弱引用/转发订阅: 生命周期由目标对象或队列接收端决定，
+    /// 目标释放后订阅者在下次发布时被清理。普通订阅 detach 后将一直保留。
+    pub fn detach(self) {
+        std::mem::forget(self);
+    }
+
+    /// 关联订阅者写入的队列，快照中显示其当前长度与容量
+    ///
+    /// 订阅回调通常只把事件放入工作队列，队列积压是扇出问题最直接的信号。
+    pub fn with_queue<T: Send + 'static>(self, tx: &crossbeam_channel::Sender<T>) -> Self {
+        if let Some(meta) = self.meta() {
+            let tx = tx.clone();
+            let _ = meta.queue.set(Box::new(move || (tx.len(), tx.capacity())));
+        }
+        self
+    }
+
+    /// 查找订阅者的元数据
+    fn meta(&self) -> Option<Arc<SubscriberMeta>> {
+        match self {
+            Subscription::Typed { tyid, id, bus } => {
+                let bus = bus.upgrade()?;
+                let entry = bus.subscribers.get(tyid)?;
+                let subscriber = entry.value().subscribers.get(id)?;
+                let meta = subscriber.value().meta.clone();
+                Some(meta)
+            }
+            Subscription::Any { id, bus } => {
+                let bus = bus.upgrade()?;
+                let subscriber = bus.any_subscribers.get(id)?;
+                let meta = subscriber.value().meta.clone();
+                Some(meta)
+            }
+        }
+    }
 }
 
 /// 事件总线内部数据结构
@@ -459,17 +718,17 @@ impl Subscription {
 pub struct EventBusInner {
     /// 类型化事件订阅者存储
     ///
-    /// 结构：`TypeId -> Arc<SkipMap<usize, Subscriber>>`
+    /// 结构：`TypeId -> Arc<TypeEntry>`
     ///
     /// - 外层 SkipMap: 按事件类型索引，键为 `TypeId`
-    /// - 内层 SkipMap: 存储该类型的所有订阅者，键为订阅者 ID
-    /// - 使用 `Arc` 包装内层 SkipMap，支持多线程共享
+    /// - 内层 SkipMap (`TypeEntry::subscribers`): 存储该类型的所有订阅者，键为订阅者 ID
+    /// - 使用 `Arc` 包装类型条目，支持多线程共享
     ///
     /// 这种两层结构的优势：
     /// 1. 事件发布时只需要查找特定类型的订阅者
     /// 2. 不同类型的事件处理相互独立，减少锁争用
     /// 3. 可以方便地统计每种类型的订阅者数量
-    subscribers: SkipMap<TypeId, Arc<SkipMap<usize, Subscriber>>>,
+    subscribers: SkipMap<TypeId, Arc<TypeEntry>>,
 
     /// 通用事件订阅者存储
     ///
@@ -577,12 +836,11 @@ impl EventBus {
     /// # 返回值
     ///
     /// 返回类型化订阅凭证
+    #[track_caller]
     pub fn subscribe<E: Any + Send + Sync, F: Fn(&E) + Send + Sync + 'static>(
         &self,
         f: F,
     ) -> Subscription {
-        let tyid = TypeId::of::<E>();
-
         // 将类型化回调包装为通用回调
         // 这个闭包会在事件发布时被调用，进行类型 downcast
         let callback = Arc::new(move |e: &dyn Any| {
@@ -590,18 +848,85 @@ impl EventBus {
                 f(e);
             }
         });
+        self.insert::<E>(callback, Arc::new(SubscriberMeta::new(Location::caller())))
+    }
+
+    /// 以弱引用方式订阅特定类型的事件
+    ///
+    /// 回调只持有 `target` 的弱引用；目标释放后不再调用回调，
+    /// 订阅者在该类型下次发布 (或 [`EventBus::prune`]) 时被移除。
+    #[track_caller]
+    pub fn subscribe_weak<E, T, F>(&self, target: &Arc<T>, f: F) -> Subscription
+    where
+        E: Any + Send + Sync,
+        T: Send + Sync + 'static,
+        F: Fn(&T, &E) + Send + Sync + 'static,
+    {
+        let weak = Arc::downgrade(target);
+        let probe = weak.clone();
+        let callback = Arc::new(move |e: &dyn Any| {
+            if let (Some(e), Some(target)) = (e.downcast_ref::<E>(), weak.upgrade()) {
+                f(&target, e);
+            }
+        });
+        let mut meta = SubscriberMeta::new(Location::caller());
+        meta.alive = Some(Box::new(move || probe.strong_count() > 0));
+        self.insert::<E>(callback, Arc::new(meta))
+    }
+
+    /// 将特定类型的事件转发到有界队列
+    ///
+    /// 队列满时丢弃事件并计入 `dropped`；接收端关闭后订阅者视为失效并被清理。
+    #[track_caller]
+    pub fn forward<E: Any + Clone + Send + Sync>(
+        &self,
+        tx: crossbeam_channel::Sender<E>,
+    ) -> Subscription {
+        let connected = Arc::new(AtomicBool::new(true));
+        let mut meta = SubscriberMeta::new(Location::caller());
+        let flag = connected.clone();
+        meta.alive = Some(Box::new(move || flag.load(Ordering::Relaxed)));
+        let probe = tx.clone();
+        let _ = meta
+            .queue
+            .set(Box::new(move || (probe.len(), probe.capacity())));
+        let meta = Arc::new(meta);
+
+        let stats = meta.clone();
+        let callback = Arc::new(move |e: &dyn Any| {
+            if let Some(e) = e.downcast_ref::<E>() {
+                match tx.try_send(e.clone()) {
+                    Ok(()) => {}
+                    Err(crossbeam_channel::TrySendError::Full(_)) => {
+                        stats.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(crossbeam_channel::TrySendError::Disconnected(_)) => {
+                        connected.store(false, Ordering::Relaxed);
+                    }
+                }
+            }
+        });
+        self.insert::<E>(callback, meta)
+    }
+
+    /// 将订阅者插入对应类型的列表并返回凭证
+    fn insert<E: Any>(&self, callback: Callback, meta: Arc<SubscriberMeta>) -> Subscription {
+        let tyid = TypeId::of::<E>();
 
         // 获取或创建该类型的订阅者列表
-        let list = self
-            .inner
-            .subscribers
-            .get_or_insert(tyid, Arc::new(SkipMap::new()));
+        let entry = self.inner.subscribers.get_or_insert_with(tyid, || {
+            Arc::new(TypeEntry {
+                name: std::any::type_name::<E>(),
+                posted: AtomicU64::new(0),
+                subscribers: SkipMap::new(),
+            })
+        });
 
         let id = self.next_id();
-        let subscriber = Subscriber { id, callback };
+        let subscriber = Subscriber { id, callback, meta };
 
         // 将订阅者插入到列表中
-        list.value().insert(id, subscriber);
+        entry.value().subscribers.insert(id, subscriber);
 
         // 返回订阅凭证，包含弱引用避免循环引用
         Subscription::Typed {
@@ -635,13 +960,15 @@ impl EventBus {
     /// # 返回值
     ///
     /// 返回通用订阅凭证
+    #[track_caller]
     pub fn subscribe_any<F: Fn(TypeId, &dyn Any) + Send + Sync + 'static>(
         &self,
         f: F,
     ) -> Subscription {
         let callback = Arc::new(f);
         let id = self.next_id();
-        let subscriber = AnySubscriber { id, callback };
+        let meta = Arc::new(SubscriberMeta::new(Location::caller()));
+        let subscriber = AnySubscriber { id, callback, meta };
 
         // 直接插入到通用订阅者存储中
         self.inner.any_subscribers.insert(id, subscriber);
@@ -697,24 +1024,38 @@ impl EventBus {
         let event_ref = &event as &dyn Any;
 
         // 第一阶段：通知特定类型的订阅者
-        if let Some(list) = self.inner.subscribers.get(&tyid) {
+        if let Some(entry) = self.inner.subscribers.get(&tyid) {
+            let entry = entry.value();
+            entry.posted.fetch_add(1, Ordering::Relaxed);
+
             // 性能优化：先收集所有回调引用，避免在调用时持有 SkipMap 的锁
             // 这允许回调函数中进行新的订阅/取消订阅操作，而不会死锁
-            let callbacks: Vec<_> = list
-                .value()
+            let callbacks: Vec<_> = entry
+                .subscribers
                 .iter()
-                .map(|entry| entry.value().callback.clone())
+                .map(|s| (*s.key(), s.value().callback.clone(), s.value().meta.clone()))
                 .collect();
 
-            // 依次调用所有回调函数
-            for callback in callbacks {
+            // 依次调用所有回调函数, 目标已释放的弱引用订阅者跳过并清理
+            let mut dead = Vec::new();
+            for (id, callback, meta) in callbacks {
+                if !meta.is_alive() {
+                    dead.push(id);
+                    continue;
+                }
                 // 异常隔离：单个回调的 panic 不影响其他回调
-                if let Err(e) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                let start = Instant::now();
+                let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                     callback(event_ref);
-                })) {
-                    eprintln!("Event handler panicked: {:?}", e);
+                }));
+                meta.record(start.elapsed(), result.is_ok());
+                if let Err(e) = result {
+                    eprintln!("Event handler panicked: {:?} ({})", e, meta.location);
                 }
             }
+            for id in dead {
+                entry.subscribers.remove(&id);
+            }
         }
 
         // 第二阶段：通知所有通用订阅者
@@ -722,14 +1063,17 @@ impl EventBus {
             .inner
             .any_subscribers
             .iter()
-            .map(|entry| entry.value().callback.clone())
+            .map(|entry| (entry.value().callback.clone(), entry.value().meta.clone()))
             .collect();
 
-        for callback in any_callbacks {
-            if let Err(e) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        for (callback, meta) in any_callbacks {
+            let start = Instant::now();
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                 callback(tyid, event_ref);
-            })) {
-                eprintln!("Any event handler panicked: {:?}", e);
+            }));
+            meta.record(start.elapsed(), result.is_ok());
+            if let Err(e) = result {
+                eprintln!("Any event handler panicked: {:?} ({})", e, meta.location);
             }
         }
     }
@@ -750,7 +1094,7 @@ impl EventBus {
         self.inner
             .subscribers
             .get(&tyid)
-            .map(|list| list.value().len())
+            .map(|entry| entry.value().subscribers.len())
             .unwrap_or(0)
     }
 
@@ -793,9 +1137,63 @@ impl EventBus {
         self.inner
             .subscribers
             .iter()
-            .map(|entry| (*entry.key(), entry.value().len()))
+            .filter(|entry| !entry.value().subscribers.is_empty())
+            .map(|entry| (*entry.key(), entry.value().subscribers.len()))
             .collect()
     }
+
+    /// 获取所有事件类型的订阅者快照
+    ///
+    /// 按事件类型列出每个订阅者的订阅位置、投递/panic/丢弃次数、回调耗时与关联队列长度，
+    /// 用于排查 "谁订阅了这个事件"、"哪个订阅者拖慢了发布方" 之类的扇出问题。
+    pub fn snapshot(&self) -> BusSnapshot {
+        let topics = self
+            .inner
+            .subscribers
+            .iter()
+            .map(|entry| {
+                let entry = entry.value();
+                TopicStats {
+                    event_type: entry.name,
+                    posted: entry.posted.load(Ordering::Relaxed),
+                    subscribers: entry
+                        .subscribers
+                        .iter()
+                        .map(|s| s.value().meta.stats(*s.key()))
+                        .collect(),
+                }
+            })
+            .collect();
+        let any_subscribers = self
+            .inner
+            .any_subscribers
+            .iter()
+            .map(|s| s.value().meta.stats(*s.key()))
+            .collect();
+        BusSnapshot {
+            topics,
+            any_subscribers,
+        }
+    }
+
+    /// 清理所有目标已释放的弱引用/转发订阅者
+    ///
+    /// 发布时会顺带清理当前类型，此方法用于处理很少发布的事件类型。
+    ///
+    /// # 返回值
+    ///
+    /// 被清理的订阅者数量
+    pub fn prune(&self) -> usize {
+        let mut pruned = 0;
+        for entry in self.inner.subscribers.iter() {
+            for s in entry.value().subscribers.iter() {
+                if !s.value().meta.is_alive() && s.remove() {
+                    pruned += 1;
+                }
+            }
+        }
+        pruned
+    }
 }
 
 impl Default for EventBus {
@@ -1034,4 +1432,74 @@ mod tests {
         assert!(another_sub.id() > typed_sub.id());
         assert!(another_sub.id() > any_sub.id());
     }
+
+    /// 测试显式取消订阅: 取消后不再收到事件, 同类型的新订阅仍能正常接收
+    #[test]
+    fn test_unsubscribe() {
+        let bus = EventBus::new();
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let r = received.clone();
+        let first = bus.subscribe::<TestEvent2, _>(move |e| r.lock().unwrap().push(e.value));
+        bus.post(TestEvent2 { value: 1 });
+        first.unsubscribe();
+        assert_eq!(bus.subscriber_count::<TestEvent2>(), 0);
+        bus.post(TestEvent2 { value: 2 });
+
+        // 最后一个订阅者取消后再订阅同一类型 (界面重建)
+        let r = received.clone();
+        let _second = bus.subscribe::<TestEvent2, _>(move |e| r.lock().unwrap().push(e.value));
+        bus.post(TestEvent2 { value: 3 });
+
+        assert_eq!(*received.lock().unwrap(), vec![1, 3]);
+        assert_eq!(bus.statistics().len(), 1);
+    }
+
+    /// 测试弱引用订阅: 目标释放后不再回调, 即使凭证已 detach 也会在发布时被清理
+    #[test]
+    fn test_weak_subscription() {
+        let bus = EventBus::new();
+        let panel = Arc::new(Mutex::new(Vec::new()));
+
+        bus.subscribe_weak::<TestEvent1, _, _>(&panel, |panel, e| {
+            panel.lock().unwrap().push(e.message.clone());
+        })
+        .detach();
+        bus.post(TestEvent1 {
+            message: "a".to_string(),
+        });
+        assert_eq!(*panel.lock().unwrap(), vec!["a".to_string()]);
+        assert!(bus.snapshot().topics[0].subscribers[0].alive);
+
+        drop(panel);
+        assert!(!bus.snapshot().topics[0].subscribers[0].alive);
+        bus.post(TestEvent1 {
+            message: "b".to_string(),
+        });
+        assert_eq!(bus.subscriber_count::<TestEvent1>(), 0);
+        assert_eq!(bus.snapshot().topics[0].posted, 2);
+    }
+
+    /// 测试队列转发与快照统计: 队列满时计入丢弃, 接收端关闭后订阅者被清理
+    #[test]
+    fn test_forward_snapshot() {
+        let bus = EventBus::new();
+        let (tx, rx) = crossbeam_channel::bounded::<TestEvent2>(1);
+        bus.forward(tx).detach();
+
+        bus.post(TestEvent2 { value: 1 });
+        bus.post(TestEvent2 { value: 2 });
+        let snapshot = bus.snapshot();
+        let stats = &snapshot.topics[0].subscribers[0];
+        assert!(snapshot.topics[0].event_type.ends_with("TestEvent2"));
+        assert!(stats.location.contains("xbus.rs"));
+        assert_eq!((stats.delivered, stats.dropped), (2, 1));
+        assert_eq!((stats.queue_len, stats.queue_capacity), (Some(1), Some(1)));
+        assert_eq!(rx.recv().unwrap().value, 1);
+
+        drop(rx);
+        bus.post(TestEvent2 { value: 3 });
+        assert_eq!(bus.prune(), 1);
+        assert_eq!(bus.subscriber_count::<TestEvent2>(), 0);
+    }
 }
@@ -0,0 +1,432 @@
+//! 配置热加载 (Config hot-reload)
+//!
+//! 用 notify 监视运行期间可调整的配置文件, 保存后无需重启即生效:
+//! - 配置档案 (profiles.json): 当前档案的阈值/类别/区域/跟踪参数被修改时重新切换到该档案
+//! - 区域规则 (zones.json): 区域引擎与小地图
+//! - 跟踪器参数 (tracker_config.json): 生命周期参数与显示平滑
+//! - 告警通知 (notifier.json): 通道/模板/限流/免打扰时段, 通知模块按新配置重启
+//!
+//! 文件先完整解析再校验, 任一项不合法时整份拒绝并保留当前配置 (不会只生效一半);
+//! 生效时按字段输出变更日志. 编辑器常以"写临时文件再重命名"保存, 因此监视所在目录,
+//! 短时间内的多次事件合并为一次加载
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::analytics::ZoneConfig;
+use crate::audit::{self, AuditAction, AuditSource};
+use crate::notifier::NotifierConfig;
+use crate::ui_config::{ProfileConfig, TrackerConfig};
+use crate::xbus;
+
+/// 合并同一次保存产生的多个文件事件
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// 可热加载的配置文件
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigKind {
+    Profiles,
+    Zones,
+    Tracker,
+    Notifier,
+}
+
+impl ConfigKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConfigKind::Profiles => "配置档案",
+            ConfigKind::Zones => "区域规则",
+            ConfigKind::Tracker => "跟踪器参数",
+            ConfigKind::Notifier => "告警通知",
+        }
+    }
+}
+
+/// 校验通过的新配置
+#[derive(Clone, Debug)]
+pub enum ConfigUpdate {
+    Profiles(ProfileConfig),
+    Zones(ZoneConfig),
+    Tracker(TrackerConfig),
+    Notifier(NotifierConfig),
+}
+
+/// 配置文件已热加载 (热加载线程 → 渲染器/告警通知)
+#[derive(Clone, Debug)]
+pub struct ConfigReloaded {
+    pub path: String,
+    pub update: ConfigUpdate,
+}
+
+/// 解析并校验配置, 同时返回补齐默认值后的 JSON (用于变更对比)
+pub fn parse(kind: ConfigKind, text: &str) -> anyhow::Result<(ConfigUpdate, Value)> {
+    fn typed<T: serde::de::DeserializeOwned + Serialize>(text: &str) -> anyhow::Result<(T, Value)> {
+        let config: T = serde_json::from_str(text).context("JSON 解析失败")?;
+        let value = serde_json::to_value(&config)?;
+        Ok((config, value))
+    }
+
+    Ok(match kind {
+        ConfigKind::Profiles => {
+            let (config, value) = typed::<ProfileConfig>(text)?;
+            validate_profiles(&config)?;
+            (ConfigUpdate::Profiles(config), value)
+        }
+        ConfigKind::Zones => {
+            let (config, value) = typed::<ZoneConfig>(text)?;
+            validate_zones(&config)?;
+            (ConfigUpdate::Zones(config), value)
+        }
+        ConfigKind::Tracker => {
+            let (config, value) = typed::<TrackerConfig>(text)?;
+            validate_tracker(&config)?;
+            (ConfigUpdate::Tracker(config), value)
+        }
+        ConfigKind::Notifier => {
+            let (config, value) = typed::<NotifierConfig>(text)?;
+            validate_notifier(&config)?;
+            (ConfigUpdate::Notifier(config), value)
+        }
+    })
+}
+
+fn check_unit(name: &str, value: f32) -> anyhow::Result<()> {
+    if !(0.0..=1.0).contains(&value) {
+        bail!("{} 超出 0..1: {}", name, value);
+    }
+    Ok(())
+}
+
+fn validate_profiles(config: &ProfileConfig) -> anyhow::Result<()> {
+    let mut names = HashSet::new();
+    for profile in &config.profiles {
+        if !names.insert(profile.name.as_str()) {
+            bail!("档案重名: {}", profile.name);
+        }
+        if let Some(size) = profile.input_size.filter(|s| *s == 0 || s % 32 != 0) {
+            bail!("档案 {} 的输入尺寸 {} 不是 32 的倍数", profile.name, size);
+        }
+        if let Some(conf) = profile.confidence_threshold {
+            check_unit(&format!("档案 {} 的置信度阈值", profile.name), conf)?;
+        }
+        if let Some(iou) = profile.iou_threshold {
+            check_unit(&format!("档案 {} 的 IoU 阈值", profile.name), iou)?;
+        }
+        if let Some(zones) = profile.zones.as_deref().filter(|z| !Path::new(z).exists()) {
+            bail!("档案 {} 的区域文件不存在: {}", profile.name, zones);
+        }
+    }
+    for s in &config.schedule {
+        if !names.contains(s.profile.as_str()) {
+            bail!("时段引用了未知档案: {}", s.profile);
+        }
+        if !s.is_valid() {
+            bail!("时段时间格式错误: {}-{}", s.start, s.end);
+        }
+    }
+    Ok(())
+}
+
+fn validate_zones(config: &ZoneConfig) -> anyhow::Result<()> {
+    if config.cooldown_secs < 0.0 {
+        bail!("cooldown_secs 不能为负: {}", config.cooldown_secs);
+    }
+    let mut names = HashSet::new();
+    for zone in &config.zones {
+        if !names.insert(zone.name.as_str()) {
+            bail!("区域重名: {}", zone.name);
+        }
+        if zone.polygon.len() < 3 {
+            bail!("区域 {} 的多边形少于 3 个点", zone.name);
+        }
+    }
+    Ok(())
+}
+
+fn validate_tracker(config: &TrackerConfig) -> anyhow::Result<()> {
+    for (name, value) in [
+        ("detection_conf_threshold", config.detection_conf_threshold),
+        ("detection_iou_threshold", config.detection_iou_threshold),
+        (
+            "bytetrack_high_score_threshold",
+            config.bytetrack_high_score_threshold,
+        ),
+        (
+            "bytetrack_low_score_threshold",
+            config.bytetrack_low_score_threshold,
+        ),
+        (
+            "bytetrack_high_iou_threshold",
+            config.bytetrack_high_iou_threshold,
+        ),
+        (
+            "bytetrack_low_iou_threshold",
+            config.bytetrack_low_iou_threshold,
+        ),
+        ("deepsort_iou_threshold", config.deepsort_iou_threshold),
+    ] {
+        check_unit(name, value)?;
+    }
+    if config.bytetrack_low_score_threshold > config.bytetrack_high_score_threshold {
+        bail!("bytetrack_low_score_threshold 高于 bytetrack_high_score_threshold");
+    }
+    Ok(())
+}
+
+fn validate_notifier(config: &NotifierConfig) -> anyhow::Result<()> {
+    if config.min_interval_secs < 0.0 {
+        bail!("min_interval_secs 不能为负: {}", config.min_interval_secs);
+    }
+    if let Some(w) = config.quiet_hours.iter().find(|w| !w.is_valid()) {
+        bail!("免打扰时段无效: {}-{} {:?}", w.start, w.end, w.days);
+    }
+    Ok(())
+}
+
+/// 两份配置的字段级差异, 每行一项: `~ 路径: 旧 → 新`、`+ 路径: 新`、`- 路径: 旧`
+pub fn diff(old: &Value, new: &Value) -> Vec<String> {
+    let mut out = Vec::new();
+    diff_into(old, new, "", &mut out);
+    out
+}
+
+fn diff_into(old: &Value, new: &Value, path: &str, out: &mut Vec<String>) {
+    let child = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+    match (old, new) {
+        (Value::Object(a), Value::Object(b)) => {
+            for (key, old_value) in a {
+                match b.get(key) {
+                    Some(new_value) => diff_into(old_value, new_value, &child(key), out),
+                    None => out.push(format!("- {}: {}", child(key), old_value)),
+                }
+            }
+            for (key, new_value) in b.iter().filter(|(k, _)| !a.contains_key(*k)) {
+                out.push(format!("+ {}: {}", child(key), new_value));
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for i in 0..a.len().max(b.len()) {
+                let item = format!("{}[{}]", path, i);
+                match (a.get(i), b.get(i)) {
+                    (Some(x), Some(y)) => diff_into(x, y, &item, out),
+                    (Some(x), None) => out.push(format!("- {}: {}", item, x)),
+                    (None, Some(y)) => out.push(format!("+ {}: {}", item, y)),
+                    (None, None) => {}
+                }
+            }
+        }
+        (a, b) if a != b => out.push(format!("~ {}: {} → {}", path, a, b)),
+        _ => {}
+    }
+}
+
+/// 被监视的文件与当前生效的配置
+struct WatchedFile {
+    kind: ConfigKind,
+    path: String,
+    target: PathBuf,        // 规范化后的绝对路径 (与文件事件比较)
+    current: Option<Value>, // 当前生效的配置, 启动时文件不存在或不合法时为 None
+}
+
+impl WatchedFile {
+    /// 重新读取文件; 不合法时拒绝并保留当前配置
+    fn reload(&mut self) {
+        // 重命名保存的中间状态下文件可能暂时不存在, 等待后续事件
+        let Ok(text) = fs::read_to_string(&self.path) else {
+            return;
+        };
+        let (update, value) = match parse(self.kind, &text) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                eprintln!(
+                    "⚠️ {}热加载被拒绝 ({}): {:#}, 继续使用当前配置",
+                    self.kind.label(),
+                    self.path,
+                    e
+                );
+                return;
+            }
+        };
+        let changes = match &self.current {
+            Some(current) => diff(current, &value),
+            None => vec!["+ (新文件)".to_string()],
+        };
+        if changes.is_empty() {
+            return;
+        }
+        println!(
+            "🔄 {}已热加载: {} ({}项变更)",
+            self.kind.label(),
+            self.path,
+            changes.len()
+        );
+        for line in &changes {
+            println!("   {}", line);
+        }
+        // 告警通知配置含令牌/密码, 审计中只记录变更项数
+        let (action, detail) = match self.kind {
+            ConfigKind::Zones => (AuditAction::ZoneEdit, changes.join("; ")),
+            ConfigKind::Notifier => (
+                AuditAction::ConfigReload,
+                format!("{} ({}项变更)", self.kind.label(), changes.len()),
+            ),
+            _ => (
+                AuditAction::ConfigReload,
+                format!("{}: {}", self.kind.label(), changes.join("; ")),
+            ),
+        };
+        let source = AuditSource::Config {
+            path: self.path.clone(),
+        };
+        audit::record(source, action, detail);
+        self.current = Some(value);
+
+        // 区域引擎直接订阅区域配置
+        if let ConfigUpdate::Zones(config) = &update {
+            xbus::post(config.clone());
+        }
+        xbus::post(ConfigReloaded {
+            path: self.path.clone(),
+            update,
+        });
+    }
+}
+
+/// 文件的规范化路径 (文件本身可能暂不存在, 只规范化所在目录)
+fn resolve(path: &str) -> anyhow::Result<PathBuf> {
+    let path = Path::new(path);
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let name = path.file_name().context("配置路径缺少文件名")?;
+    Ok(fs::canonicalize(dir)?.join(name))
+}
+
+/// 配置文件监视器, 释放后停止热加载
+pub struct ConfigWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+impl ConfigWatcher {
+    /// 监视给定的配置文件, 在后台线程中加载变更
+    pub fn start(files: &[(ConfigKind, &str)]) -> anyhow::Result<Self> {
+        let (tx, rx) = crossbeam_channel::unbounded::<Vec<PathBuf>>();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event {
+                    let _ = tx.send(event.paths);
+                }
+            })?;
+
+        let mut watched = Vec::new();
+        let mut dirs = HashSet::new();
+        for &(kind, path) in files {
+            let target = resolve(path)?;
+            if let Some(dir) = target.parent() {
+                if dirs.insert(dir.to_path_buf()) {
+                    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+                }
+            }
+            let current = fs::read_to_string(path)
+                .ok()
+                .and_then(|text| parse(kind, &text).ok())
+                .map(|(_, value)| value);
+            watched.push(WatchedFile {
+                kind,
+                path: path.to_string(),
+                target,
+                current,
+            });
+        }
+
+        let names: Vec<&str> = files.iter().map(|(_, path)| *path).collect();
+        println!("👀 配置热加载已启用: {}", names.join(", "));
+        std::thread::Builder::new()
+            .name("config-watch".to_string())
+            .spawn(move || {
+                // 监视器释放后发送端关闭, 线程随之退出
+                while let Ok(mut paths) = rx.recv() {
+                    let deadline = Instant::now() + DEBOUNCE;
+                    while let Ok(more) =
+                        rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    {
+                        paths.extend(more);
+                    }
+                    for file in &mut watched {
+                        if paths.contains(&file.target) {
+                            file.reload();
+                        }
+                    }
+                }
+            })?;
+        Ok(Self { _watcher: watcher })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// 字段级差异: 修改/新增/删除, 数组按下标对比
+    #[test]
+    fn test_diff() {
+        let old =
+            json!({ "conf": 0.5, "classes": [0, 2], "zones": "a.json", "nested": { "x": 1 } });
+        let new = json!({ "conf": 0.6, "classes": [0, 2, 7], "nested": { "x": 1, "y": true } });
+        let mut lines = diff(&old, &new);
+        lines.sort();
+        assert_eq!(
+            lines,
+            vec![
+                "+ classes[2]: 7",
+                "+ nested.y: true",
+                "- zones: \"a.json\"",
+                "~ conf: 0.5 → 0.6",
+            ]
+        );
+        assert!(diff(&new, &new).is_empty());
+    }
+
+    /// 不合法的更新整份拒绝, 合法的更新补齐默认值
+    #[test]
+    fn test_parse_rejects_invalid() {
+        let zones = r#"{ "zones": [{ "name": "门口", "polygon": [[0,0],[1,0],[1,1]] }] }"#;
+        let (update, value) = parse(ConfigKind::Zones, zones).unwrap();
+        assert!(matches!(update, ConfigUpdate::Zones(c) if c.zones.len() == 1));
+        assert_eq!(value["cooldown_secs"], json!(5.0));
+
+        let line = r#"{ "zones": [{ "name": "线", "polygon": [[0,0],[1,0]] }] }"#;
+        assert!(parse(ConfigKind::Zones, line).is_err());
+        assert!(parse(ConfigKind::Zones, "{ \"zones\": [").is_err());
+
+        let profiles = r#"{
+            "profiles": [{ "name": "白天", "confidence_threshold": 1.5 }],
+            "schedule": []
+        }"#;
+        assert!(parse(ConfigKind::Profiles, profiles).is_err());
+        let schedule = r#"{
+            "profiles": [{ "name": "白天", "confidence_threshold": 0.4 }],
+            "schedule": [{ "profile": "夜间", "start": "20:00", "end": "06:00" }]
+        }"#;
+        assert!(parse(ConfigKind::Profiles, schedule).is_err());
+
+        let tracker =
+            r#"{ "bytetrack_low_score_threshold": 0.9, "bytetrack_high_score_threshold": 0.5 }"#;
+        assert!(parse(ConfigKind::Tracker, tracker).is_err());
+    }
+}
@@ -0,0 +1,11 @@
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+//! 数字卫兵界面
+//!
+//! 在 [`sentinel_pipeline`] 之上加入 macroquad + egui 渲染器、控制面板、UI 配置与热加载,
+//! 即 `sentinel` 程序使用的完整 `yolov8_rs` API
+pub use sentinel_pipeline::*;
+
+pub mod hot_reload; // 配置热加载 (档案/区域/跟踪参数/告警通知)
+pub mod renderer;
+pub mod ui_config; // UI配置面板
@@ -0,0 +1,2023 @@
+mod annotator;
+mod control_panel;
+pub mod pacing;
+mod playback;
+mod texture_stream;
+mod track_detail;
+
+use crate::analytics::{
+    LeftBehindEvent, MarkerDetections, SceneChange, TamperEvent, Zone, ZoneEvent,
+};
+use crate::api::ModelSwitchRequest;
+use crate::audit::{self, AuditAction, AuditSource};
+use crate::clips::{self, ClipRequest};
+use crate::detection::compare::{
+    CompareLayout, CompareParams, CompareSide, ComparisonResult, ModelComparison,
+};
+use crate::detection::detector::DetectionResult;
+use crate::detection::profiler;
+use crate::detection::trace::{FrameTrace, LatencyStage, LatencyStats};
+use crate::detection::types::{
+    control_receiver, BBox, ClockDrift, ControlMessage, DecodedFrame, DetectorStatus, FollowTrack,
+    TrackFlagged,
+};
+use crate::detection::{id_to_color, GlobalIdManager, TrackJournal, DETECT_CLASSES};
+use crate::hot_reload::ConfigReloaded;
+use crate::i18n::{tr, tr_fmt};
+use crate::input::decoder::DecoderPreference;
+use crate::input::vault::strip_password;
+use crate::input::{active_source, switch_decoder_source, InputSource};
+use crate::memory_guard::{self, memory_status, MemoryAction};
+use crate::models::DensityMap;
+use crate::retention::retention_status;
+use crate::runtime_config::{pin_current_thread, ThreadRole};
+use crate::scheduler::ArmStateChanged;
+use crate::ui_config::{ProfileConfig, SessionState};
+use crate::utils::clock;
+use crate::utils::coords::{ImagePoint, ImageRect, ImageToWindow, WindowPoint};
+use crate::utils::fisheye::fisheye_config;
+use crate::utils::jetson::JetsonMonitor;
+use crate::utils::metrics_ring::Metric;
+use crate::utils::resize;
+use crate::xbus::{self, Subscription};
+use crate::SKELETON;
+use annotator::{Annotator, HANDLE_RADIUS};
+use control_panel::{model_stem, ControlPanel};
+use pacing::{FramePacer, PacingMode};
+use playback::{box_at, Playback, HISTORY_FRAMES};
+use texture_stream::TextureStream;
+use track_detail::{keypoint_quality, Observation, TrackHistory};
+use chrono::{DateTime, Utc};
+use crossbeam_channel::Receiver;
+use egui_macroquad::egui;
+use macroquad::prelude::*;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// 引入 image crate 用于加载背景图
+use image;
+
+/// 延迟统计窗口 (帧)
+const LATENCY_WINDOW: usize = 300;
+
+/// 地面小地图边长 (像素)
+const GROUND_MAP_SIZE: f32 = 220.0;
+
+/// 遗留/移除事件在画面上的高亮时长 (秒)
+const LEFT_BEHIND_HIGHLIGHT_SECS: i64 = 30;
+
+/// 轨迹详情导出片段时在首次/最近出现时间前后多截取的时长 (秒)
+const TRACK_CLIP_PADDING_SECS: i64 = 2;
+
+/// 模型对比两路检测框的颜色 (模型 A, 模型 B)
+const COMPARE_COLORS: [Color; 2] = [GREEN, ORANGE];
+
+pub struct Renderer {
+    _frame_sub: Subscription,
+    _result_sub: Subscription,
+    _zone_sub: Subscription,
+    _left_behind_sub: Subscription,
+    _tamper_sub: Subscription,
+    _arm_sub: Subscription,
+    _status_sub: Subscription,
+    _drift_sub: Subscription,
+    _marker_sub: Subscription,
+    _compare_sub: Subscription,
+    _config_sub: Subscription,
+    _model_request_sub: Subscription,
+    _memory_sub: Subscription,
+    // 内存防护措施 (监控线程广播, 渲染线程执行)
+    memory_actions: Arc<Mutex<Vec<MemoryAction>>>,
+    render_frame_buffer: Receiver<RenderFrame>,
+
+    last_frame: Option<Texture2D>,
+    // 平滑呈现时的上一帧纹理 (与当前帧混合过渡)
+    prev_frame: Option<Texture2D>,
+    // 视频纹理的 PBO 双缓冲上传 (只上传变化的区域)
+    texture_stream: TextureStream,
+    // 帧节奏: 按 PTS 安排呈现, 显示器刷新周期 (秒, 指数平滑)
+    pacer: FramePacer<DecodedFrame>,
+    refresh_interval: f32,
+    last_detection: Option<DetectionResult>,
+    // 回放控制: 已呈现帧的历史缓冲与暂停/逐帧状态, 暂停帧的检测结果是否为该帧自己的
+    playback: Playback<DecodedFrame, DetectionResult>,
+    playback_exact: bool,
+    // 检视: 暂停时点选的检测框 (last_detection.bboxes 的下标)
+    inspected: Option<usize>,
+    // 轨迹详情: 各轨迹的累积统计与选中状态, 选中轨迹的缩略图纹理 (新增缩略图后重建), 操作结果提示
+    tracks: TrackHistory,
+    thumbnail_textures: Option<((u32, Option<DateTime<Utc>>), Vec<egui::TextureHandle>)>,
+    track_message: Option<String>,
+    // 人群密度热力图纹理 (密度图更新时重建)
+    density_texture: Option<(Arc<DensityMap>, Texture2D)>,
+
+    // 延迟追踪: 待渲染结果的时间戳, 最近帧统计, 指标文件
+    pending_trace: Option<FrameTrace>,
+    latency: LatencyStats,
+    metrics_file: Option<String>,
+    // 会话状态文件与最近一次写入的状态 (变化时才写入)
+    session_file: Option<String>,
+    saved_session: Option<SessionState>,
+    render_count: u64,
+    render_last: Instant,
+    show_control_panel: bool,
+
+    // 视频帧率统计
+    video_count: u64,
+    video_last: Instant,
+
+    // 画面缩放
+    is_panning: bool,
+    last_mouse_pos: Vec2,
+
+    // 窗口状态
+    is_mouse_over_ui: bool,
+    is_keyboard_over_ui: bool,
+
+    // 背景纹理
+    background_texture: Option<Texture2D>,
+
+    // 中文字体
+    chinese_font: Option<Font>,
+
+    // 区域定义 (地面坐标, 绘制在小地图上)
+    zones: Vec<Zone>,
+
+    // 检测器延迟启动参数
+    detector_model_path: Option<String>,
+    detector_inf_size: Option<u32>,
+    detector_tracker: Option<String>,
+    detector_pose_enabled: Option<bool>,
+    detector_pose_model: Option<String>,
+    detector_depth_model: Option<String>,
+    detector_attribute_model: Option<String>,
+    detector_vehicle_model: Option<String>,
+    detector_crowd_model: Option<String>,
+    detector_clip_model: Option<String>,
+    detector_global_ids: Option<Arc<Mutex<GlobalIdManager>>>,
+    detector_dla_core: Option<u32>,
+    detector_journal: Option<TrackJournal>,
+    detector_jetson: Option<JetsonMonitor>,
+    detector_started: bool,
+
+    // 模型对比线程 (面板启用时运行, 模型或逻辑流变化时重启)
+    comparison: Option<ModelComparison>,
+
+    // 控制面板(独立模块)
+    control_panel: ControlPanel,
+}
+
+enum RenderFrame {
+    Video(DecodedFrame),
+    Detection(DetectionResult),
+}
+
+impl Renderer {
+    pub fn new(detect_model: String, pose_model: String, tracker: String) -> Self {
+        println!("渲染器启动");
+        // 进一步减小队列长度以降低内存占用 (5 -> 2)
+        let (tx, rx) = crossbeam_channel::bounded(2);
+        let control_panel = ControlPanel::new(detect_model, tracker);
+
+        // 订阅DecodedFrame (多路逻辑流时只接收面板选中的一路)
+        let tx1 = tx.clone();
+        let display_view = Arc::clone(&control_panel.display_view);
+        let frame_sub = xbus::subscribe::<DecodedFrame, _>(move |frame| {
+            if frame.view != display_view.load(Ordering::Relaxed) {
+                return;
+            }
+            if let Err(err) = tx1.try_send(RenderFrame::Video(frame.clone())) {
+                eprintln!("渲染器通道发送DecodedFrame失败: {}", err);
+            }
+        });
+
+        // 订阅DetectionResult
+        let display_view = Arc::clone(&control_panel.display_view);
+        let result_sub = xbus::subscribe::<DetectionResult, _>(move |result| {
+            if result.view != display_view.load(Ordering::Relaxed) {
+                return;
+            }
+            if let Err(err) = tx.try_send(RenderFrame::Detection(result.clone())) {
+                eprintln!("渲染器通道发送DetectionResult失败: {}", err);
+            }
+        });
+
+        // 订阅区域事件 (面板显示最近事件)
+        let zone_events = Arc::clone(&control_panel.zone_events);
+        let zone_sub = xbus::subscribe::<ZoneEvent, _>(move |event| {
+            ControlPanel::push_event(&zone_events, event.clone());
+        });
+        let left_behind_events = Arc::clone(&control_panel.left_behind_events);
+        let left_behind_sub = xbus::subscribe::<LeftBehindEvent, _>(move |event| {
+            ControlPanel::push_event(&left_behind_events, event.clone());
+        });
+        let tamper_events = Arc::clone(&control_panel.tamper_events);
+        let tamper_sub = xbus::subscribe::<TamperEvent, _>(move |event| {
+            ControlPanel::push_event(&tamper_events, event.clone());
+        });
+        let arm_events = Arc::clone(&control_panel.arm_events);
+        let arm_sub = xbus::subscribe::<ArmStateChanged, _>(move |event| {
+            ControlPanel::push_event(&arm_events, event.clone());
+        });
+
+        // 订阅检测线程错误状态 (面板显示当前错误)
+        let detector_error = Arc::clone(&control_panel.detector_error);
+        let display_view = Arc::clone(&control_panel.display_view);
+        let status_sub = xbus::subscribe::<DetectorStatus, _>(move |status| {
+            if status.view == display_view.load(Ordering::Relaxed) {
+                *detector_error.lock().unwrap() = status.error.clone();
+            }
+        });
+
+        // 订阅摄像头时钟漂移 (面板在超过阈值时提示)
+        let clock_drift = Arc::clone(&control_panel.clock_drift);
+        let drift_sub = xbus::subscribe::<ClockDrift, _>(move |drift| {
+            *clock_drift.lock().unwrap() = Some(*drift);
+        });
+
+        // 订阅基准标记检测 (面板显示, 标定时记为参考)
+        let marker_detections = Arc::clone(&control_panel.marker_detections);
+        let marker_sub = xbus::subscribe::<MarkerDetections, _>(move |detections| {
+            *marker_detections.lock().unwrap() = Some(detections.clone());
+        });
+
+        // 订阅模型对比结果 (面板显示耗时, 渲染器绘制两路检测框)
+        let comparison = Arc::clone(&control_panel.comparison);
+        let display_view = Arc::clone(&control_panel.display_view);
+        let compare_sub = xbus::subscribe::<ComparisonResult, _>(move |result| {
+            if result.view == display_view.load(Ordering::Relaxed) {
+                *comparison.lock().unwrap() = Some(result.clone());
+            }
+        });
+
+        // 订阅配置热加载 (渲染线程每帧应用到面板与检测线程)
+        let config_updates = Arc::clone(&control_panel.config_updates);
+        let config_sub = xbus::subscribe::<ConfigReloaded, _>(move |event| {
+            config_updates.lock().unwrap().push(event.update.clone());
+        });
+
+        // 订阅 API 的模型切换请求
+        let model_requests = Arc::clone(&control_panel.model_requests);
+        let model_request_sub = xbus::subscribe::<ModelSwitchRequest, _>(move |request| {
+            model_requests.lock().unwrap().push(request.model.clone());
+        });
+
+        // 订阅内存防护措施 (清缓存/重建纹理)
+        let memory_actions: Arc<Mutex<Vec<MemoryAction>>> = Arc::default();
+        let actions = Arc::clone(&memory_actions);
+        let memory_sub = xbus::subscribe::<MemoryAction, _>(move |action| {
+            actions.lock().unwrap().push(*action);
+        });
+
+        // 加载背景图片
+        let background_texture = if let Ok(bytes) = std::fs::read("assets/images/background.jpg") {
+            if let Ok(img) = image::load_from_memory(&bytes) {
+                let rgba = img.to_rgba8();
+                Some(Texture2D::from_rgba8(
+                    rgba.width() as u16,
+                    rgba.height() as u16,
+                    &rgba,
+                ))
+            } else {
+                println!("⚠️ 背景图片解码失败");
+                None
+            }
+        } else {
+            println!("⚠️ 未找到背景图片: assets/images/background.jpg");
+            None
+        };
+
+        // 加载中文字体
+        let chinese_font = if let Ok(bytes) = std::fs::read("assets/font/msyh.ttc") {
+            match load_ttf_font_from_bytes(&bytes) {
+                Ok(font) => {
+                    println!("✅ 中文字体加载成功");
+                    Some(font)
+                }
+                Err(e) => {
+                    println!("⚠️ 中文字体加载失败: {}", e);
+                    None
+                }
+            }
+        } else {
+            println!("⚠️ 未找到中文字体文件: assets/font/msyh.ttc");
+            None
+        };
+
+        Self {
+            render_frame_buffer: rx,
+            last_frame: None,
+            prev_frame: None,
+            texture_stream: TextureStream::new(),
+            pacer: FramePacer::new(PacingMode::default()),
+            refresh_interval: 1.0 / 60.0,
+            last_detection: None,
+            playback: Playback::new(HISTORY_FRAMES),
+            playback_exact: true,
+            inspected: None,
+            tracks: TrackHistory::default(),
+            thumbnail_textures: None,
+            track_message: None,
+            density_texture: None,
+            pending_trace: None,
+            latency: LatencyStats::new(LATENCY_WINDOW),
+            metrics_file: None,
+            session_file: None,
+            saved_session: None,
+            _frame_sub: frame_sub,
+            _result_sub: result_sub,
+            _zone_sub: zone_sub,
+            _left_behind_sub: left_behind_sub,
+            _tamper_sub: tamper_sub,
+            _arm_sub: arm_sub,
+            _status_sub: status_sub,
+            _drift_sub: drift_sub,
+            _marker_sub: marker_sub,
+            _compare_sub: compare_sub,
+            _config_sub: config_sub,
+            _model_request_sub: model_request_sub,
+            _memory_sub: memory_sub,
+            memory_actions,
+            render_count: 0,
+            render_last: Instant::now(),
+            show_control_panel: true,
+            video_count: 0,
+            video_last: Instant::now(),
+            is_panning: false,
+            last_mouse_pos: Vec2::ZERO,
+            is_mouse_over_ui: false,
+            is_keyboard_over_ui: false,
+            background_texture,
+
+            chinese_font,
+            zones: Vec::new(),
+            detector_model_path: None,
+            detector_inf_size: None,
+            detector_tracker: None,
+            detector_pose_enabled: None,
+            // 两阶段姿态模型路径 (为空表示使用检测模型自带的pose头)
+            detector_pose_model: if pose_model.is_empty() {
+                None
+            } else {
+                Some(pose_model)
+            },
+            detector_depth_model: None,
+            detector_attribute_model: None,
+            detector_vehicle_model: None,
+            detector_crowd_model: None,
+            detector_clip_model: None,
+            detector_global_ids: None,
+            detector_dla_core: None,
+            detector_journal: None,
+            detector_jetson: None,
+            detector_started: false,
+            comparison: None,
+            control_panel,
+        }
+    }
+
+    /// 保存检测器启动参数(延迟启动)
+    pub fn set_detector_params(
+        &mut self,
+        model_path: String,
+        inf_size: u32,
+        tracker: String,
+        pose_enabled: bool,
+    ) {
+        self.detector_model_path = Some(model_path);
+        self.detector_inf_size = Some(inf_size);
+        self.detector_tracker = Some(tracker);
+        self.detector_pose_enabled = Some(pose_enabled);
+        self.control_panel.pose_enabled = pose_enabled;
+        self.control_panel.input_size = inf_size;
+    }
+
+    /// 设置深度估计模型路径(检测器启动时加载)
+    pub fn set_depth_model(&mut self, model_path: String) {
+        self.detector_depth_model = Some(model_path);
+    }
+
+    /// 设置行人属性模型路径(检测器启动时加载)
+    pub fn set_attribute_model(&mut self, model_path: String) {
+        self.detector_attribute_model = Some(model_path);
+    }
+
+    /// 设置车辆属性模型路径(检测器启动时加载)
+    pub fn set_vehicle_model(&mut self, model_path: String) {
+        self.detector_vehicle_model = Some(model_path);
+    }
+
+    /// 设置人群密度模型路径(检测器启动时加载)
+    pub fn set_crowd_model(&mut self, model_path: String) {
+        self.detector_crowd_model = Some(model_path);
+    }
+
+    /// 设置CLIP模型目录(检测器启动时加载)
+    pub fn set_clip_model(&mut self, model_dir: String) {
+        self.detector_clip_model = Some(model_dir);
+    }
+
+    /// 设置跨摄像头全局ID管理器(检测器启动时传入)
+    pub fn set_global_id_manager(&mut self, manager: Arc<Mutex<GlobalIdManager>>) {
+        self.detector_global_ids = Some(manager);
+    }
+
+    /// 设置检测模型使用的 DLA 核心(Jetson, 检测器启动时传入)
+    pub fn set_dla_core(&mut self, core: u32) {
+        self.detector_dla_core = Some(core);
+    }
+
+    /// 设置跟踪状态日志(检测器启动时恢复轨迹, 运行中定期保存)
+    pub fn set_track_journal(&mut self, journal: TrackJournal) {
+        self.detector_journal = Some(journal);
+    }
+
+    /// 设置 tegrastats 监控(检测器据此跳帧降温, 面板显示功耗/温度)
+    pub fn set_jetson_monitor(&mut self, monitor: JetsonMonitor) {
+        self.detector_jetson = Some(monitor);
+    }
+
+    /// 设置延迟指标文件(Prometheus 文本格式, 每秒覆盖写入)
+    pub fn set_metrics_file(&mut self, path: String) {
+        self.metrics_file = Some(path);
+    }
+
+    /// 设置标注数据集导出目录 (类别表从该目录的 classes.txt 读取)
+    pub fn set_dataset_dir(&mut self, path: &str) {
+        self.control_panel.annotator = Annotator::new(path);
+    }
+
+    /// 设置会话状态文件(每秒检查, 状态变化时覆盖写入)
+    pub fn set_session_file(&mut self, path: String) {
+        self.session_file = Some(path);
+    }
+
+    /// 当前会话状态 (面板参数 + 窗口尺寸 + 正在播放的输入源, RTSP 地址不含密码)
+    pub fn session_state(&self) -> SessionState {
+        SessionState {
+            window_size: Some((screen_width() as i32, screen_height() as i32)),
+            source: active_source().map(|source| match source {
+                InputSource::Rtsp(url) => InputSource::Rtsp(strip_password(&url)),
+                source => source,
+            }),
+            ..self.control_panel.session_state()
+        }
+    }
+
+    /// 恢复会话状态: 面板参数与检测器启动参数, 并自动恢复上次播放的输入源
+    ///
+    /// 须在 set_detector_params 之后调用, 会覆盖其中的模型/跟踪器/姿态开关
+    pub fn restore_session(&mut self, state: &SessionState) {
+        let source = self.control_panel.restore_session(state);
+        if state.model.is_some() {
+            self.detector_model_path = Some(self.control_panel.model_path());
+        }
+        if state.tracker.is_some() {
+            self.detector_tracker = Some(self.control_panel.tracker_name.clone());
+        }
+        self.detector_pose_enabled = Some(self.control_panel.pose_enabled);
+        println!(
+            "🔁 已恢复会话: 模型 {} | 跟踪 {}",
+            self.control_panel.detect_model_name, self.control_panel.tracker_name
+        );
+        if let Some(source) = source {
+            println!("▶️ 恢复上次的输入源: {}", source.key());
+            switch_decoder_source(source, DecoderPreference::preferred());
+        }
+    }
+
+    /// 会话状态变化时写入文件
+    fn save_session_if_changed(&mut self) {
+        let Some(path) = &self.session_file else {
+            return;
+        };
+        let state = self.session_state();
+        if self.saved_session.as_ref() != Some(&state) {
+            state.save(path);
+            self.saved_session = Some(state);
+        }
+    }
+
+    /// 设置配置档案 (控制面板切换, 按时段自动切换; 另存档案时写回 `path`)
+    pub fn set_profiles(&mut self, profiles: ProfileConfig, path: String) {
+        self.control_panel.profiles = profiles;
+        self.control_panel.profiles_path = path;
+    }
+
+    /// 设置区域定义(小地图显示, 规则由区域引擎求值)
+    pub fn set_zones(&mut self, zones: Vec<Zone>) {
+        self.zones = zones;
+    }
+
+    /// 视频画面到窗口的变换 (含控制面板的缩放与平移)
+    fn video_transform(&self) -> Option<ImageToWindow> {
+        let texture = self.last_frame.as_ref()?;
+        let pan = self.control_panel.pan_offset;
+        Some(ImageToWindow::view(
+            (texture.width(), texture.height()),
+            (screen_width(), screen_height()),
+            self.control_panel.zoom_scale,
+            (pan.x, pan.y),
+        ))
+    }
+
+    /// 每秒写入一次指标历史 (控制面板迷你曲线)
+    /// 上报纹理数与估算大小 (RGBA 每像素 4 字节)
+    fn report_textures(&self) {
+        let mut sizes: Vec<(f32, f32)> =
+            [&self.last_frame, &self.prev_frame, &self.background_texture]
+                .into_iter()
+                .flatten()
+                .map(|t| (t.width(), t.height()))
+                .collect();
+        if let Some((_, texture)) = &self.density_texture {
+            sizes.push((texture.width(), texture.height()));
+        }
+        if let Some((_, textures)) = &self.thumbnail_textures {
+            sizes.extend(textures.iter().map(|t| {
+                let [w, h] = t.size();
+                (w as f32, h as f32)
+            }));
+        }
+        let bytes = sizes.iter().map(|(w, h)| (w * h * 4.0) as u64).sum();
+        memory_guard::set_textures(sizes.len(), bytes);
+    }
+
+    /// 执行内存监控线程广播的防护措施
+    fn apply_memory_actions(&mut self) {
+        let actions = std::mem::take(&mut *self.memory_actions.lock().unwrap());
+        for action in actions {
+            match action {
+                MemoryAction::FlushCaches => {
+                    // 暂停检视时保留回放缓冲
+                    if !self.playback.is_paused() {
+                        self.playback.clear();
+                    }
+                    self.thumbnail_textures = None;
+                }
+                MemoryAction::RebuildTextures => {
+                    // 下一帧/下一次密度图、缩略图更新时重新创建
+                    self.last_frame = None;
+                    self.prev_frame = None;
+                    self.density_texture = None;
+                    self.thumbnail_textures = None;
+                    self.playback.refresh();
+                }
+            }
+            println!("🧹 内存防护: 已执行 {:?}", action);
+        }
+    }
+
+    fn record_metrics(&self) {
+        let panel = &self.control_panel;
+        let inference_ms = panel
+            .latency_report
+            .iter()
+            .find(|(stage, _)| *stage == LatencyStage::Inference)
+            .map_or(0.0, |(_, s)| s.mean_ms);
+        let boxes = self
+            .last_detection
+            .as_ref()
+            .map(|r| r.bboxes.as_slice())
+            .unwrap_or_default();
+        let mean_confidence = if boxes.is_empty() {
+            0.0
+        } else {
+            boxes.iter().map(|b| b.confidence).sum::<f32>() / boxes.len() as f32
+        };
+
+        let mut metrics = panel.metrics.lock().unwrap();
+        metrics.push(Metric::DecodeFps, panel.decode_fps as f32);
+        metrics.push(Metric::DetectFps, panel.detect_fps as f32);
+        metrics.push(Metric::RenderFps, panel.render_fps as f32);
+        metrics.push(Metric::InferenceMs, inference_ms as f32);
+        metrics.push(Metric::MeanConfidence, mean_confidence);
+    }
+
+    /// 按面板设置启停模型对比线程, 运行中同步阈值与类别
+    fn sync_comparison(&mut self) {
+        let Some(models) = self.control_panel.compare_models() else {
+            self.comparison = None;
+            return;
+        };
+        let view = self.control_panel.display_view.load(Ordering::Relaxed);
+        let params = CompareParams {
+            conf: self.control_panel.confidence_threshold,
+            iou: self.control_panel.iou_threshold,
+            classes: self.control_panel.classes.clone(),
+        };
+        let running = self
+            .comparison
+            .as_ref()
+            .filter(|c| c.models() == &models && c.view() == view);
+        if let Some(comparison) = running {
+            comparison.set_params(params);
+            return;
+        }
+
+        // 先释放旧线程的订阅, 再清空旧结果
+        self.comparison = None;
+        *self.control_panel.comparison.lock().unwrap() = None;
+        match ModelComparison::start(models, self.control_panel.input_size, view, params) {
+            Ok(comparison) => self.comparison = Some(comparison),
+            Err(e) => {
+                eprintln!("❌ {:#}", e);
+                self.control_panel.compare_enabled = false;
+            }
+        }
+    }
+
+    /// 启动检测器线程(首次启动解码器时调用)
+    fn start_detector_if_needed(&mut self) {
+        if self.detector_started {
+            return; // 已启动,跳过
+        }
+
+        // 检测器启动前切换的配置档案无法经控制消息生效, 以面板参数启动
+        if self.control_panel.active_profile.is_some() {
+            self.detector_model_path = Some(self.control_panel.model_path());
+            self.detector_inf_size = Some(self.control_panel.input_size);
+            self.detector_tracker = Some(self.control_panel.tracker_name.clone());
+        }
+
+        // 检查是否有保存的参数
+        if let (Some(model_path), Some(inf_size), Some(tracker), Some(pose_enabled)) = (
+            self.detector_model_path.clone(),
+            self.detector_inf_size,
+            self.detector_tracker.clone(),
+            self.detector_pose_enabled,
+        ) {
+            println!("🔍 检测模块启动");
+
+            // 逻辑流: 启用鱼眼去畸变时每个虚拟视图一个检测线程, 否则只处理原始画面
+            let views: Vec<u32> = match fisheye_config() {
+                Some(fisheye) => (1..=fisheye.views.len() as u32).collect(),
+                None => vec![0],
+            };
+
+            for view in views {
+                // 控制面板经 xbus 广播控制消息, 先订阅再启动线程, 初始参数不会丢失
+                let (control_sub, config_rx) = control_receiver(view);
+                let model_path = model_path.clone();
+                let tracker = tracker.clone();
+                let pose_model = self.detector_pose_model.clone();
+                let depth_model = self.detector_depth_model.clone();
+                let attribute_model = self.detector_attribute_model.clone();
+                let vehicle_model = self.detector_vehicle_model.clone();
+                let crowd_model = self.detector_crowd_model.clone();
+                let clip_model = self.detector_clip_model.clone();
+                let global_ids = self.detector_global_ids.clone();
+                let dla_core = self.detector_dla_core;
+                let journal = self.detector_journal.clone();
+                let jetson = self.detector_jetson.clone();
+
+                // 启动检测线程
+                std::thread::spawn(move || {
+                    use crate::detection;
+                    // 先绑核: ORT 会话在此线程内创建, 其线程池继承亲和性
+                    pin_current_thread(ThreadRole::Infer);
+                    let _control_sub = control_sub;
+                    let mut det =
+                        detection::Detector::new(model_path, inf_size, tracker, pose_enabled);
+                    det.set_config_receiver(config_rx);
+                    det.set_view(view);
+                    if let Some(path) = pose_model {
+                        det.set_pose_model(path);
+                    }
+                    if let Some(path) = depth_model {
+                        det.set_depth_model(path);
+                    }
+                    if let Some(path) = attribute_model {
+                        det.set_attribute_model(path);
+                    }
+                    if let Some(path) = vehicle_model {
+                        det.set_vehicle_model(path);
+                    }
+                    if let Some(path) = crowd_model {
+                        det.set_crowd_model(path);
+                    }
+                    if let Some(dir) = clip_model {
+                        det.set_clip_model(dir);
+                    }
+                    if let Some(manager) = global_ids {
+                        // 以逻辑流编号作为摄像头编号, 目标跨越相邻视图时沿用全局ID
+                        det.set_global_ids(manager, view);
+                    }
+                    if let Some(core) = dla_core {
+                        det.set_dla_core(core);
+                    }
+                    if let Some(monitor) = jetson {
+                        det.set_jetson_monitor(monitor);
+                    }
+                    if let Some(journal) = &journal {
+                        det.set_track_journal(journal);
+                    }
+                    det.run_supervised();
+                });
+            }
+
+            // 发送初始参数
+            ControlMessage::UpdateParams {
+                conf_threshold: self.control_panel.confidence_threshold,
+                iou_threshold: self.control_panel.iou_threshold,
+            }
+            .post();
+            if let Some(params) = self.control_panel.tracker_params() {
+                ControlMessage::SetTrackerParams(params).post();
+            }
+            ControlMessage::SetSmoothing(
+                self.control_panel.tracker_config.display_smoothing.clone(),
+            )
+            .post();
+            ControlMessage::SetResolutionLadder(
+                self.control_panel.tracker_config.resolution_ladder.clone(),
+            )
+            .post();
+            if let Some(floor) = self.control_panel.ghost_floor() {
+                ControlMessage::SetGhostFloor(Some(floor)).post();
+            }
+            if let Some(h) = self.control_panel.ground_calibration.homography() {
+                ControlMessage::SetGroundHomography(Some(h)).post();
+            }
+            if !self.control_panel.crowd_enabled {
+                ControlMessage::ToggleCrowd(false).post();
+            }
+            if !self.control_panel.detection_enabled {
+                ControlMessage::ToggleDetection(false).post();
+            }
+            if self.control_panel.classes != DETECT_CLASSES {
+                ControlMessage::SetClasses(self.control_panel.classes.clone()).post();
+            }
+            if self.control_panel.resize_quality != resize::default_quality() {
+                ControlMessage::SetResizeQuality(self.control_panel.resize_quality).post();
+            }
+
+            self.detector_started = true;
+        }
+    }
+
+    pub fn update(&mut self) {
+        // 首次收到视频帧时启动检测器(在处理帧之前检查)
+        let should_start_detector = !self.detector_started;
+
+        // 处理帧缓冲 - 统计所有接收到的帧以计算FPS, 视频帧交给帧节奏控制决定何时呈现
+        let mut latest_detection_result = None;
+        let mut video_frames_received = 0;
+        let mut has_video_frame = false;
+        let now = Instant::now();
+        self.pacer.set_mode(self.control_panel.pacing);
+        self.apply_memory_actions();
+
+        for frame in self.render_frame_buffer.try_iter() {
+            match frame {
+                RenderFrame::Video(decoded_frame) => {
+                    has_video_frame = true;
+                    self.pacer
+                        .push(decoded_frame.trace.decode_ts, decoded_frame, now);
+                    video_frames_received += 1;
+                }
+                RenderFrame::Detection(detection_result) => {
+                    latest_detection_result = Some(detection_result);
+                }
+            }
+        }
+
+        // 本次刷新到期的帧 (刷新周期按 V-Sync 下的帧时间平滑估计)
+        self.refresh_interval += 0.1 * (get_frame_time() - self.refresh_interval);
+        let refresh = Duration::from_secs_f32(self.refresh_interval.clamp(0.001, 0.1));
+        let latest_video_frame = self.pacer.poll(now, refresh);
+
+        // 回放控制: 暂停时实时帧不再呈现, 逐帧移动时换成历史帧
+        let latest_video_frame = self
+            .playback
+            .present(latest_video_frame.map(|f| (f.trace.decode_ts, f)));
+        if let Some(frame) = &latest_video_frame {
+            let pts = frame.trace.decode_ts;
+            self.tracks
+                .capture_thumbnail(pts, &frame.rgba_data, frame.width, frame.height);
+        }
+
+        // 收到第一帧视频时启动检测器
+        if should_start_detector && has_video_frame {
+            self.start_detector_if_needed();
+        }
+
+        // 更新解码FPS统计
+        self.video_count += video_frames_received;
+        if now.duration_since(self.video_last).as_secs() >= 1 {
+            self.control_panel.decode_fps =
+                self.video_count as f64 / now.duration_since(self.video_last).as_secs_f64();
+            self.video_count = 0;
+            self.video_last = now;
+
+            // 延迟/呈现统计随解码FPS每秒刷新
+            self.control_panel.latency_report = self.latency.report();
+            self.control_panel.pacing_stats = self.pacer.take_stats();
+            self.record_metrics();
+            self.report_textures();
+            profiler::tick();
+            if let Some(path) = &self.metrics_file {
+                let mut metrics = self.latency.to_prometheus();
+                if let Some(status) = retention_status() {
+                    metrics.push_str(&status.to_prometheus());
+                }
+                if let Some(status) = memory_status() {
+                    metrics.push_str(&status.to_prometheus());
+                }
+                if let Err(e) = std::fs::write(path, metrics) {
+                    eprintln!("⚠️ 写入延迟指标失败: {}", e);
+                }
+            }
+            self.save_session_if_changed();
+            self.control_panel.apply_scheduled_profile(chrono::Local::now().time());
+        }
+        self.sync_comparison();
+
+        self.control_panel.apply_config_updates();
+        self.control_panel.apply_model_requests();
+
+        // 配置档案切换了区域时更新小地图
+        if let Some(zones) = self.control_panel.zones.take() {
+            self.zones = zones;
+        }
+
+        // 标注模式: 冻结开启后收到的第一帧, 冻结期间不再更新纹理
+        let latest_video_frame = if self.control_panel.annotator.is_frozen() {
+            None
+        } else {
+            latest_video_frame
+        };
+        if let Some(decoded_frame) = &latest_video_frame {
+            if self.control_panel.annotator.enabled {
+                let detections = self
+                    .last_detection
+                    .as_ref()
+                    .map(|r| r.bboxes.as_slice())
+                    .unwrap_or_default();
+                let detected_class = self.control_panel.tracker_params().is_none();
+                self.control_panel
+                    .annotator
+                    .freeze(decoded_frame.clone(), detections, detected_class);
+            }
+        }
+
+        // 更新视频纹理 (热成像画面按调色板着色)
+        if let Some(decoded_frame) = latest_video_frame {
+            // 平滑呈现: 当前纹理转为上一帧, 复用更早的纹理承载新帧
+            if self.pacer.mode() == PacingMode::Smooth {
+                std::mem::swap(&mut self.last_frame, &mut self.prev_frame);
+            } else {
+                self.prev_frame = None;
+            }
+            let palette = self.control_panel.palette;
+            // 释放旧纹理（macroquad会自动管理）
+            // 只在分辨率变化时重建纹理，否则更新像素数据
+            let needs_rebuild = if let Some(ref tex) = self.last_frame {
+                tex.width() != decoded_frame.width as f32
+                    || tex.height() != decoded_frame.height as f32
+            } else {
+                true
+            };
+
+            if needs_rebuild {
+                let pixels = palette.colorize(&decoded_frame.rgba_data);
+                let texture = Texture2D::from_rgba8(
+                    decoded_frame.width as u16,
+                    decoded_frame.height as u16,
+                    &pixels,
+                );
+                texture.set_filter(FilterMode::Linear);
+                self.texture_stream
+                    .record(&texture, &decoded_frame.rgba_data, palette);
+                self.last_frame = Some(texture);
+            } else if let Some(ref tex) = self.last_frame {
+                // 更新现有纹理的像素数据（避免重新分配GPU内存）, 优先经 PBO 只上传变化的区域
+                if !self.texture_stream.update(tex, &decoded_frame, palette) {
+                    let img = Image {
+                        bytes: palette.colorize(&decoded_frame.rgba_data).into_owned(),
+                        width: decoded_frame.width as u16,
+                        height: decoded_frame.height as u16,
+                    };
+                    tex.update(&img);
+                }
+            }
+        }
+
+        // 更新检测结果 (结果按 PTS 挂到历史帧上, 暂停时叠加框取暂停帧自己的结果)
+        if let Some(result) = latest_detection_result {
+            self.observe_tracks(&result);
+            self.playback
+                .record_detection(result.trace.decode_ts, result.clone());
+            if !self.playback.is_paused() {
+                self.pending_trace = Some(result.trace);
+                self.last_detection = Some(result);
+            }
+        }
+        if let Some((result, exact)) = self.playback.detection() {
+            let stale = self
+                .last_detection
+                .as_ref()
+                .is_none_or(|d| d.trace.decode_ts != result.trace.decode_ts);
+            if stale {
+                self.last_detection = Some(result.clone());
+                self.inspected = None;
+            }
+            self.playback_exact = exact;
+        }
+
+        // 密度图更新时重建热力图纹理
+        let density = self.last_detection.as_ref().and_then(|r| r.density.clone());
+        match density {
+            Some(map) => {
+                let stale = match &self.density_texture {
+                    Some((cached, _)) => !Arc::ptr_eq(cached, &map),
+                    None => true,
+                };
+                if stale && map.width > 0 && map.height > 0 {
+                    let texture = Texture2D::from_rgba8(
+                        map.width as u16,
+                        map.height as u16,
+                        &map.heatmap_rgba(),
+                    );
+                    texture.set_filter(FilterMode::Linear);
+                    self.density_texture = Some((map, texture));
+                }
+            }
+            None => self.density_texture = None,
+        }
+
+        // 更新检测FPS
+        if let Some(result) = &self.last_detection {
+            self.control_panel.detect_fps = result.inference_fps;
+            self.control_panel.inference_size = result.resized_size;
+            self.control_panel.track_stats = result.track_stats;
+            self.control_panel.jetson_status = result.jetson;
+            self.control_panel.crowd_count = result.density.as_ref().map(|d| d.count);
+        }
+    }
+
+    /// 轨迹详情: 实时结果逐帧记入轨迹历史, 选中轨迹从对应的已呈现帧裁剪缩略图
+    fn observe_tracks(&mut self, result: &DetectionResult) {
+        // 未跟踪的框没有轨迹ID (关闭跟踪器); 检测关闭时的空结果不清除
+        if !result.tracked {
+            if !result.bboxes.is_empty() {
+                self.tracks.clear();
+            }
+            return;
+        }
+        let observations: Vec<Observation> = result
+            .bboxes
+            .iter()
+            .enumerate()
+            .map(|(i, bbox)| Observation {
+                bbox,
+                class: result.classes.get(i).copied().flatten(),
+                speed: result.speeds.get(i).copied().flatten(),
+                reid: result.reid_features.get(i).map(Vec::as_slice),
+                keypoints: keypoint_quality(bbox, &result.keypoints),
+            })
+            .collect();
+        let pts = result.trace.decode_ts;
+        self.tracks.observe(pts, result.trace.captured_at, &observations);
+        if let Some(frame) = self.playback.frame(pts) {
+            self.tracks
+                .capture_thumbnail(pts, &frame.rgba_data, frame.width, frame.height);
+        }
+    }
+
+    pub fn draw(&mut self) {
+        // 先绘制背景图（如果没有视频帧）
+        if self.last_frame.is_none() {
+            if let Some(bg) = &self.background_texture {
+                draw_texture_ex(
+                    bg,
+                    0.0,
+                    0.0,
+                    WHITE,
+                    DrawTextureParams {
+                        dest_size: Some(vec2(screen_width(), screen_height())),
+                        ..Default::default()
+                    },
+                );
+                // 叠加半透明遮罩
+                draw_rectangle(
+                    0.0,
+                    0.0,
+                    screen_width(),
+                    screen_height(),
+                    Color::new(0.0, 0.0, 0.0, 0.5),
+                );
+            } else {
+                clear_background(Color::from_rgba(20, 20, 30, 255));
+            }
+        } else {
+            clear_background(BLACK);
+        }
+
+        // 模型对比: 分屏/画中画由对比视图绘制画面与两路检测框, 叠加模式只替换检测框
+        let comparison = self.control_panel.comparison.lock().unwrap().clone();
+        let compare_layout = self.control_panel.compare_layout;
+
+        // 绘制视频帧
+        let split = comparison
+            .as_ref()
+            .filter(|_| compare_layout != CompareLayout::Overlay);
+        if let (Some(result), Some(texture)) = (split, &self.last_frame) {
+            self.draw_comparison(texture, result, compare_layout);
+        } else if let (Some(texture), Some(to_window)) = (&self.last_frame, self.video_transform())
+        {
+            let (center_x, center_y) = to_window.offset();
+            let (width, height) = to_window.size((texture.width(), texture.height()));
+            let dest_size = Some(vec2(width, height));
+            // 平滑呈现: 先画上一帧, 当前帧按混合权重叠加 (分辨率变化时直接显示当前帧)
+            let mut alpha = 1.0;
+            if let Some(prev) = &self.prev_frame {
+                if prev.size() == texture.size() {
+                    alpha = self.pacer.blend(Instant::now());
+                    draw_texture_ex(
+                        prev,
+                        center_x,
+                        center_y,
+                        WHITE,
+                        DrawTextureParams {
+                            dest_size,
+                            ..Default::default()
+                        },
+                    );
+                }
+            }
+            draw_texture_ex(
+                texture,
+                center_x,
+                center_y,
+                Color::new(1.0, 1.0, 1.0, alpha),
+                DrawTextureParams {
+                    dest_size,
+                    ..Default::default()
+                },
+            );
+
+            // 人群密度热力图 (拉伸到整个画面)
+            if let Some((map, heatmap)) = &self.density_texture {
+                draw_texture_ex(
+                    heatmap,
+                    center_x,
+                    center_y,
+                    WHITE,
+                    DrawTextureParams {
+                        dest_size,
+                        ..Default::default()
+                    },
+                );
+                let params = TextParams {
+                    font: self.chinese_font.as_ref(),
+                    font_size: 28,
+                    color: ORANGE,
+                    ..Default::default()
+                };
+                let text = tr_fmt("render.crowd_count", &[&format!("{:.0}", map.count)]);
+                let dims = measure_text(&text, self.chinese_font.as_ref(), 28, 1.0);
+                draw_text_ex(&text, (screen_width() - dims.width) / 2.0, 40.0, params);
+            }
+
+            // 绘制检测框 (标注冻结时由标注框代替, 模型对比时绘制两路结果)
+            if let Some(result) = &comparison {
+                for (i, (side, color)) in result.sides.iter().zip(COMPARE_COLORS).enumerate() {
+                    draw_compare_boxes(&side.bboxes, color, &to_window);
+                    draw_text(&compare_label(side), 10.0, 30.0 + 24.0 * i as f32, 22.0, color);
+                }
+            } else if self.control_panel.detection_enabled
+                && !self.control_panel.annotator.is_frozen()
+            {
+                if let Some(detection_result) = &self.last_detection {
+                    // 阈值预览: 低于阈值的检测画成半透明虚影 (先画, 被正常框覆盖)
+                    for ghost in &detection_result.ghosts {
+                        let r = to_window.rect(ghost.rect());
+                        let color = Color::new(0.6, 0.6, 0.6, 0.6);
+                        draw_rectangle_lines(r.x1, r.y1, r.width(), r.height(), 1.5, color);
+                        draw_text(
+                            &format!("{:.2}", ghost.confidence),
+                            r.x1,
+                            r.y1 - 4.0,
+                            16.0,
+                            color,
+                        );
+                    }
+                    for (i, bbox) in detection_result.bboxes.iter().enumerate() {
+                        let r = to_window.rect(bbox.rect());
+
+                        // 检视中的框、选中的轨迹、文本提示命中的框与已标记的轨迹高亮显示
+                        let prompt_match =
+                            detection_result.prompt_matches.get(i).copied().flatten();
+                        let track = detection_result.tracked.then_some(bbox.class_id);
+                        // 开放词汇模型: 类别名与词表颜色 (跟踪时 class_id 为轨迹ID, 类别按 IoU 找回)
+                        let vocab_class = detection_result.vocabulary.as_ref().and_then(|v| {
+                            let class = match track {
+                                Some(_) => detection_result.classes.get(i).copied().flatten()?,
+                                None => bbox.class_id,
+                            };
+                            Some((v.name(class)?, v.color(class)?))
+                        });
+                        let (color, thickness) = if self.inspected == Some(i) {
+                            (YELLOW, 5.0)
+                        } else if track.is_some() && track == self.tracks.selected_id() {
+                            (SKYBLUE, 5.0)
+                        } else if prompt_match.is_some() {
+                            (MAGENTA, 5.0)
+                        } else if track.is_some_and(|id| self.tracks.is_flagged(id)) {
+                            (ORANGE, 4.0)
+                        } else if let Some((_, (r, g, b))) = vocab_class {
+                            (Color::from_rgba(r, g, b, 255), 3.0)
+                        } else {
+                            (GREEN, 3.0)
+                        };
+
+                        // 绘制边框
+                        draw_rectangle_lines(r.x1, r.y1, r.width(), r.height(), thickness, color);
+
+                        // 绘制标签 (启用深度模型时附带近似距离)
+                        let mut label = match detection_result.distances.get(i).copied().flatten() {
+                            Some(d) => {
+                                format!("ID:{} {:.2} {:.1}m", bbox.class_id, bbox.confidence, d)
+                            }
+                            None => format!("ID:{} {:.2}", bbox.class_id, bbox.confidence),
+                        };
+                        if let Some((name, _)) = vocab_class {
+                            label.insert_str(0, &format!("{} ", name));
+                        }
+                        if let Some(gid) = detection_result.global_ids.get(i).copied().flatten() {
+                            label.push_str(&format!(" G{}", gid));
+                        }
+                        if let Some(speed) = detection_result.speeds.get(i).copied().flatten() {
+                            label.push_str(&format!(" {:.1}km/h", speed * 3.6));
+                        }
+                        if let Some(attrs) = detection_result.attributes.get(i).copied().flatten() {
+                            label.push_str(&format!(" {}", attrs.short_label()));
+                        }
+                        if let Some(vehicle) = detection_result.vehicles.get(i).copied().flatten() {
+                            label.push_str(&format!(" {}", vehicle.short_label()));
+                        }
+                        if let Some(sim) = prompt_match {
+                            label.push_str(&format!(" MATCH {:.2}", sim));
+                        }
+                        // 贴边的不完整目标 (贴边策略为 partial)
+                        if detection_result.partial.get(i).copied().unwrap_or(false) {
+                            label.push_str(" PARTIAL");
+                        }
+                        draw_text(&label, r.x1, r.y1 - 5.0, 20.0, color);
+                    }
+
+                    // 绘制姿态骨架
+                    for keypoints in &detection_result.keypoints {
+                        if keypoints.points.is_empty() {
+                            continue;
+                        }
+
+                        // 绘制关键点
+                        for (x, y, conf) in &keypoints.points {
+                            if *conf > 0.3 {
+                                let p = to_window.point(ImagePoint::new(*x, *y));
+                                draw_circle(p.x, p.y, 4.0, RED);
+                            }
+                        }
+
+                        // 绘制骨架连接
+                        for (idx1, idx2) in &SKELETON {
+                            if *idx1 < keypoints.points.len() && *idx2 < keypoints.points.len() {
+                                let (x1, y1, c1) = keypoints.points[*idx1];
+                                let (x2, y2, c2) = keypoints.points[*idx2];
+                                if c1 > 0.3 && c2 > 0.3 {
+                                    let a = to_window.point(ImagePoint::new(x1, y1));
+                                    let b = to_window.point(ImagePoint::new(x2, y2));
+                                    draw_line(a.x, a.y, b.x, b.y, 2.0, YELLOW);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        self.draw_annotations();
+        self.draw_left_behind();
+        self.draw_tamper();
+        self.draw_ground_calibration();
+        self.draw_ground_map();
+
+        // 叠加框已提交绘制: 收尾本帧延迟追踪
+        if let Some(mut trace) = self.pending_trace.take() {
+            trace.render_ts = Some(Instant::now());
+            self.latency.record(&trace);
+            profiler::record(&trace);
+        }
+
+        // 没有视频时显示提示文字
+        if self.last_frame.is_none() {
+            let text = tr("render.no_source");
+            let font_size = 40.0;
+            let text_params = TextParams {
+                font: self.chinese_font.as_ref(),
+                font_size: font_size as u16,
+                color: WHITE,
+                ..Default::default()
+            };
+            let text_dims = measure_text(text, self.chinese_font.as_ref(), font_size as u16, 1.0);
+            draw_text_ex(
+                text,
+                (screen_width() - text_dims.width) / 2.0,
+                (screen_height() - text_dims.height) / 2.0,
+                text_params,
+            );
+
+            if self.background_texture.is_none() {
+                let warning_params = TextParams {
+                    font: self.chinese_font.as_ref(),
+                    font_size: 24,
+                    color: YELLOW,
+                    ..Default::default()
+                };
+                draw_text_ex(tr("render.background_failed"), 10.0, 30.0, warning_params);
+            }
+        }
+
+        // FPS统计
+        self.render_count += 1;
+        let now = Instant::now();
+        if now.duration_since(self.render_last).as_secs() >= 1 {
+            self.control_panel.render_fps =
+                self.render_count as f64 / now.duration_since(self.render_last).as_secs_f64();
+            self.render_count = 0;
+            self.render_last = now;
+        }
+
+        // 暂停提示: 帧位置与叠加框是否为本帧结果
+        if let Some((offset, len)) = self.playback.position() {
+            let mut text = tr_fmt("playback.paused", &[&offset, &len]);
+            if !self.playback_exact {
+                text.push_str(tr("playback.not_exact"));
+            }
+            let params = TextParams {
+                font: self.chinese_font.as_ref(),
+                font_size: 24,
+                color: YELLOW,
+                ..Default::default()
+            };
+            let dims = measure_text(&text, self.chinese_font.as_ref(), 24, 1.0);
+            draw_text_ex(&text, (screen_width() - dims.width) / 2.0, 70.0, params);
+        }
+
+        // 显示缩放提示
+        if self.control_panel.zoom_scale != 1.0 {
+            let zoom_text = tr_fmt(
+                "render.zoom",
+                &[&format!("{:.1}", self.control_panel.zoom_scale)],
+            );
+            let zoom_params = TextParams {
+                font: self.chinese_font.as_ref(),
+                font_size: 20,
+                color: WHITE,
+                ..Default::default()
+            };
+            draw_text_ex(&zoom_text, 10.0, screen_height() - 10.0, zoom_params);
+        }
+    }
+
+    /// 最近的遗留/移除事件区域 (保留 LEFT_BEHIND_HIGHLIGHT_SECS 秒)
+    /// 分屏/画中画: 同一帧按布局画两次, 各自叠加对应模型的检测框与耗时
+    fn draw_comparison(
+        &self,
+        texture: &Texture2D,
+        result: &ComparisonResult,
+        layout: CompareLayout,
+    ) {
+        let screen = (screen_width(), screen_height());
+        let viewports = layout.viewports(screen, (texture.width(), texture.height()));
+        for (i, (side, viewport)) in result.sides.iter().zip(viewports).enumerate() {
+            let (left, top) = viewport.offset();
+            let (w, h) = viewport.size((texture.width(), texture.height()));
+            draw_texture_ex(
+                texture,
+                left,
+                top,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(w, h)),
+                    ..Default::default()
+                },
+            );
+            let color = COMPARE_COLORS[i];
+            if layout == CompareLayout::PictureInPicture && i == 1 {
+                draw_rectangle_lines(left, top, w, h, 2.0, color);
+            }
+            draw_compare_boxes(&side.bboxes, color, &viewport);
+            draw_text(&compare_label(side), left + 10.0, top + 30.0, 22.0, color);
+        }
+    }
+
+    fn draw_left_behind(&self) {
+        let Some(to_window) = self.video_transform() else {
+            return;
+        };
+        let view = self.control_panel.display_view.load(Ordering::Relaxed);
+        let now = chrono::Local::now();
+        for event in self.control_panel.left_behind_events.lock().unwrap().iter() {
+            if event.view != view || (now - event.time).num_seconds() > LEFT_BEHIND_HIGHLIGHT_SECS {
+                continue;
+            }
+            let [x1, y1, x2, y2] = event.rect;
+            let color = match event.kind {
+                SceneChange::LeftBehind => ORANGE,
+                SceneChange::Removed => RED,
+            };
+            let r = to_window.rect(ImageRect::new(x1, y1, x2, y2));
+            let (x, y) = (r.x1, r.y1);
+            draw_rectangle_lines(x, y, r.width(), r.height(), 3.0, color);
+            let params = TextParams {
+                font: self.chinese_font.as_ref(),
+                font_size: 18,
+                color,
+                ..Default::default()
+            };
+            let label = format!("{} {}", event.kind.label(), event.name);
+            draw_text_ex(&label, x, y - 5.0, params);
+        }
+    }
+
+    /// 当前逻辑流的篡改告警: 画面加红框并标注类型, 恢复后消失
+    fn draw_tamper(&self) {
+        let Some(to_window) = self.video_transform() else {
+            return;
+        };
+        let Some(texture) = self.last_frame.as_ref() else {
+            return;
+        };
+        let view = self.control_panel.display_view.load(Ordering::Relaxed);
+        let events = self.control_panel.tamper_events.lock().unwrap();
+        let Some(event) = events.iter().find(|e| e.view == view) else {
+            return;
+        };
+        if !event.active {
+            return;
+        }
+        let (left, top) = to_window.offset();
+        let (width, height) = to_window.size((texture.width(), texture.height()));
+        draw_rectangle_lines(left, top, width, height, 6.0, RED);
+        let params = TextParams {
+            font: self.chinese_font.as_ref(),
+            font_size: 28,
+            color: RED,
+            ..Default::default()
+        };
+        let label = control_panel::tamper_kind_name(event.kind);
+        draw_text_ex(label, left + 16.0, top + 40.0, params);
+    }
+
+    /// 标注框、选中框的角点手柄与正在画的新框 (仅画面冻结时显示)
+    fn draw_annotations(&self) {
+        let Some(to_window) = self.video_transform() else {
+            return;
+        };
+        let annotator = &self.control_panel.annotator;
+        if !annotator.is_frozen() {
+            return;
+        }
+        let to_screen = |(x, y): (f32, f32)| {
+            let p = to_window.point(ImagePoint::new(x, y));
+            (p.x, p.y)
+        };
+        for (i, ann) in annotator.boxes.iter().enumerate() {
+            let selected = annotator.selected == Some(i);
+            let color = if selected { YELLOW } else { SKYBLUE };
+            let (x1, y1) = to_screen((ann.x1, ann.y1));
+            let (x2, y2) = to_screen((ann.x2, ann.y2));
+            draw_rectangle_lines(x1, y1, x2 - x1, y2 - y1, 2.0, color);
+            if selected {
+                for corner in ann.corners() {
+                    let (cx, cy) = to_screen(corner);
+                    draw_circle(cx, cy, HANDLE_RADIUS / 2.0, color);
+                }
+            }
+            let params = TextParams {
+                font: self.chinese_font.as_ref(),
+                font_size: 20,
+                color,
+                ..Default::default()
+            };
+            draw_text_ex(&annotator.class_name(ann.class_id), x1, y1 - 5.0, params);
+        }
+        if let Some(ann) = annotator.preview() {
+            let (x1, y1) = to_screen((ann.x1, ann.y1));
+            let (x2, y2) = to_screen((ann.x2, ann.y2));
+            draw_rectangle_lines(x1, y1, x2 - x1, y2 - y1, 1.0, WHITE);
+        }
+    }
+
+    /// 画面上的地面标定点 (仅标定模式下显示)
+    fn draw_ground_calibration(&self) {
+        let Some(to_window) = self.video_transform() else {
+            return;
+        };
+        if !self.control_panel.calibration_mode {
+            return;
+        }
+        for (i, point) in self
+            .control_panel
+            .ground_calibration
+            .points
+            .iter()
+            .enumerate()
+        {
+            let WindowPoint { x, y, .. } =
+                to_window.point(ImagePoint::new(point.image[0], point.image[1]));
+            draw_circle_lines(x, y, 8.0, 2.0, ORANGE);
+            draw_line(x - 12.0, y, x + 12.0, y, 1.0, ORANGE);
+            draw_line(x, y - 12.0, x, y + 12.0, 1.0, ORANGE);
+            let label = format!("#{} ({:.1}, {:.1})m", i + 1, point.world[0], point.world[1]);
+            draw_text(&label, x + 10.0, y - 10.0, 18.0, ORANGE);
+        }
+
+        // 基准标记轮廓与ID (圆点为标记左上角)
+        let detections = self.control_panel.marker_detections.lock().unwrap();
+        for marker in detections.iter().flat_map(|d| &d.markers) {
+            let corners = marker.corners.map(|[x, y]| {
+                let p = to_window.point(ImagePoint::new(x, y));
+                (p.x, p.y)
+            });
+            for i in 0..4 {
+                let (a, b) = (corners[i], corners[(i + 1) % 4]);
+                draw_line(a.0, a.1, b.0, b.1, 2.0, SKYBLUE);
+            }
+            draw_circle(corners[0].0, corners[0].1, 4.0, SKYBLUE);
+            let label = format!("ID {}", marker.id);
+            draw_text(&label, corners[0].0 + 6.0, corners[0].1 - 6.0, 18.0, SKYBLUE);
+        }
+    }
+
+    /// 左下角地面小地图: 区域多边形、标定点与目标位置 (按轨迹ID着色)
+    fn draw_ground_map(&self) {
+        let ground_points: Vec<(u32, (f32, f32))> = match &self.last_detection {
+            Some(result) if self.control_panel.detection_enabled => result
+                .bboxes
+                .iter()
+                .zip(&result.ground_points)
+                .filter_map(|(b, p)| p.map(|p| (b.class_id, p)))
+                .collect(),
+            _ => Vec::new(),
+        };
+        let calibration = &self.control_panel.ground_calibration.points;
+        if ground_points.is_empty() && self.zones.is_empty() {
+            return;
+        }
+
+        // 地图范围: 区域、标定点与当前目标的包围盒
+        let world: Vec<(f32, f32)> = self
+            .zones
+            .iter()
+            .flat_map(|z| z.polygon.iter().map(|p| (p[0], p[1])))
+            .chain(calibration.iter().map(|p| (p.world[0], p.world[1])))
+            .chain(ground_points.iter().map(|(_, p)| *p))
+            .collect();
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+        for (x, y) in &world {
+            min_x = min_x.min(*x);
+            min_y = min_y.min(*y);
+            max_x = max_x.max(*x);
+            max_y = max_y.max(*y);
+        }
+        let span = (max_x - min_x).max(max_y - min_y).max(1.0);
+        let margin = 12.0;
+        let scale = (GROUND_MAP_SIZE - 2.0 * margin) / span;
+        let left = 10.0;
+        let top = screen_height() - GROUND_MAP_SIZE - 30.0;
+        let to_map = |x: f32, y: f32| {
+            (
+                left + margin + (x - min_x) * scale,
+                top + margin + (y - min_y) * scale,
+            )
+        };
+
+        draw_rectangle(
+            left,
+            top,
+            GROUND_MAP_SIZE,
+            GROUND_MAP_SIZE,
+            Color::new(0.0, 0.0, 0.0, 0.6),
+        );
+        draw_rectangle_lines(left, top, GROUND_MAP_SIZE, GROUND_MAP_SIZE, 1.0, GRAY);
+
+        for zone in &self.zones {
+            let n = zone.polygon.len();
+            for i in 0..n {
+                let [x1, y1] = zone.polygon[i];
+                let [x2, y2] = zone.polygon[(i + 1) % n];
+                let (ax, ay) = to_map(x1, y1);
+                let (bx, by) = to_map(x2, y2);
+                draw_line(ax, ay, bx, by, 1.5, SKYBLUE);
+            }
+            if let Some([x, y]) = zone.polygon.first() {
+                let (mx, my) = to_map(*x, *y);
+                // 区域名可能是中文, 使用中文字体
+                let params = TextParams {
+                    font: self.chinese_font.as_ref(),
+                    font_size: 14,
+                    color: SKYBLUE,
+                    ..Default::default()
+                };
+                draw_text_ex(&zone.name, mx + 2.0, my - 2.0, params);
+            }
+        }
+        for point in calibration {
+            let (mx, my) = to_map(point.world[0], point.world[1]);
+            draw_rectangle(mx - 2.0, my - 2.0, 4.0, 4.0, ORANGE);
+        }
+        for (id, (x, y)) in &ground_points {
+            let (r, g, b) = id_to_color(*id);
+            let (mx, my) = to_map(*x, *y);
+            draw_circle(mx, my, 4.0, Color::from_rgba(r, g, b, 255));
+        }
+        draw_text(
+            &format!("{:.0}m", span),
+            left + 4.0,
+            top + GROUND_MAP_SIZE - 4.0,
+            14.0,
+            GRAY,
+        );
+    }
+
+    pub fn draw_egui(&mut self) {
+        egui_macroquad::ui(|egui_ctx| {
+            self.is_mouse_over_ui = egui_ctx.wants_pointer_input();
+            self.is_keyboard_over_ui = egui_ctx.wants_keyboard_input();
+            self.control_panel
+                .show(egui_ctx, &mut self.show_control_panel);
+            self.show_playback_bar(egui_ctx);
+            self.show_inspector(egui_ctx);
+            self.show_track_detail(egui_ctx);
+        });
+
+        egui_macroquad::draw();
+    }
+
+    /// 暂停时画面底部的回放按钮 (后退 / 继续 / 前进)
+    fn show_playback_bar(&mut self, ctx: &egui::Context) {
+        if !self.playback.is_paused() {
+            return;
+        }
+        egui::Area::new(egui::Id::new("playback_bar"))
+            .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -40.0))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button(tr("playback.step_back")).clicked() {
+                        self.playback.step_backward();
+                    }
+                    if ui.button(tr("playback.resume")).clicked() {
+                        self.playback.resume();
+                    }
+                    if ui.button(tr("playback.step_forward")).clicked() {
+                        self.playback.step_forward();
+                    }
+                });
+            });
+    }
+
+    /// 检视弹窗: 点选检测框的原始置信度、类别与各分析模块的附加结果
+    fn show_inspector(&mut self, ctx: &egui::Context) {
+        let Some(i) = self.inspected else {
+            return;
+        };
+        let Some(result) = &self.last_detection else {
+            return;
+        };
+        let Some(bbox) = result.bboxes.get(i) else {
+            self.inspected = None;
+            return;
+        };
+        let mut open = true;
+        egui::Window::new(tr("inspect.title"))
+            .id(egui::Id::new("inspector"))
+            .open(&mut open)
+            .resizable(false)
+            .default_pos(egui::pos2(screen_width() - 300.0, 80.0))
+            .show(ctx, |ui| {
+                egui::Grid::new("inspect_grid")
+                    .striped(true)
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        let mut row = |key: &'static str, value: String| {
+                            ui.label(tr(key));
+                            ui.monospace(value);
+                            ui.end_row();
+                        };
+                        // 跟踪后 class_id 为轨迹ID, 否则为模型类别
+                        if result.tracked {
+                            row("inspect.track", bbox.class_id.to_string());
+                        } else {
+                            let name = self.control_panel.annotator.class_name(bbox.class_id);
+                            row("inspect.class", format!("{} ({})", name, bbox.class_id));
+                        }
+                        row("inspect.confidence", format!("{:.6}", bbox.confidence));
+                        row(
+                            "inspect.box",
+                            format!(
+                                "({:.1}, {:.1}) - ({:.1}, {:.1})",
+                                bbox.x1, bbox.y1, bbox.x2, bbox.y2
+                            ),
+                        );
+                        row(
+                            "inspect.size",
+                            format!("{:.1} x {:.1}", bbox.x2 - bbox.x1, bbox.y2 - bbox.y1),
+                        );
+                        let get = |v: &Vec<Option<f32>>| v.get(i).copied().flatten();
+                        if let Some(d) = get(&result.distances) {
+                            row("inspect.distance", format!("{:.2} m", d));
+                        }
+                        if let Some(gid) = result.global_ids.get(i).copied().flatten() {
+                            row("inspect.global_id", format!("G{}", gid));
+                        }
+                        if let Some((x, y)) = result.ground_points.get(i).copied().flatten() {
+                            row("inspect.ground", format!("({:.2}, {:.2}) m", x, y));
+                        }
+                        if let Some(speed) = get(&result.speeds) {
+                            row("inspect.speed", format!("{:.1} km/h", speed * 3.6));
+                        }
+                        if let Some(attrs) = result.attributes.get(i).copied().flatten() {
+                            row("inspect.attributes", attrs.short_label());
+                        }
+                        if let Some(vehicle) = result.vehicles.get(i).copied().flatten() {
+                            row("inspect.vehicle", vehicle.short_label());
+                        }
+                        if let Some(sim) = get(&result.prompt_matches) {
+                            row("inspect.prompt", format!("{:.4}", sim));
+                        }
+                        if let Some(feature) = result.reid_features.get(i) {
+                            row("inspect.reid", feature.len().to_string());
+                        }
+                        let frame = if self.playback_exact {
+                            tr("inspect.frame_exact")
+                        } else {
+                            tr("inspect.frame_earlier")
+                        };
+                        row("inspect.frame", frame.to_string());
+                    });
+            });
+        if !open {
+            self.inspected = None;
+        }
+    }
+
+    /// 轨迹详情侧边面板: 选中轨迹的累积统计、ReID 相似度曲线、缩略图与操作
+    fn show_track_detail(&mut self, ctx: &egui::Context) {
+        let Some(track) = self.tracks.selected() else {
+            self.thumbnail_textures = None;
+            return;
+        };
+        let key = (track.id, track.thumbnails.back().map(|t| t.time));
+        if self
+            .thumbnail_textures
+            .as_ref()
+            .is_none_or(|(cached, _)| *cached != key)
+        {
+            let textures = track
+                .thumbnails
+                .iter()
+                .enumerate()
+                .map(|(i, t)| {
+                    let size = [t.width as usize, t.height as usize];
+                    let image = egui::ColorImage::from_rgba_unmultiplied(size, &t.rgba);
+                    ctx.load_texture(
+                        format!("track_thumbnail_{}", i),
+                        image,
+                        egui::TextureOptions::LINEAR,
+                    )
+                })
+                .collect();
+            self.thumbnail_textures = Some((key, textures));
+        }
+
+        let mut action = None;
+        egui::SidePanel::right("track_detail")
+            .resizable(false)
+            .default_width(260.0)
+            .show(ctx, |ui| {
+                ui.heading(tr_fmt("track.title", &[&track.id]));
+                egui::Grid::new("track_grid")
+                    .striped(true)
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        let mut row = |key: &'static str, value: String| {
+                            ui.label(tr(key));
+                            ui.monospace(value);
+                            ui.end_row();
+                        };
+                        let class = match track.class {
+                            Some(c) => {
+                                let name = self.control_panel.annotator.class_name(c);
+                                format!("{} ({})", name, c)
+                            }
+                            None => "-".to_string(),
+                        };
+                        row("track.class", class);
+                        row("track.age", format!("{:.1} s", track.age().as_secs_f32()));
+                        let first_seen = clock::to_display(track.first_seen);
+                        row("track.first_seen", first_seen.format("%T").to_string());
+                        row("track.frames", track.frames.to_string());
+                        if let Some((vx, vy)) = track.velocity() {
+                            row("track.velocity", format!("{:.0} px/s", vx.hypot(vy)));
+                        }
+                        if let Some(speed) = track.ground_speed {
+                            row("track.speed", format!("{:.1} km/h", speed * 3.6));
+                        }
+                        if let Some(q) = track.keypoints {
+                            let value = format!("{}/{} ({:.2})", q.visible, q.total, q.mean_conf);
+                            row("track.keypoints", value);
+                        }
+                        if let Some(latest) = track.similarity.back() {
+                            let min = track.similarity.iter().copied().fold(1.0, f32::min);
+                            row("track.reid", format!("{:.3} (min {:.3})", latest, min));
+                        }
+                    });
+
+                // ReID 相似度历史 (0~1), 外观突变时明显下降
+                if track.similarity.len() >= 2 {
+                    ui.label(tr("track.reid_history"));
+                    let (rect, _) = ui.allocate_exact_size(
+                        egui::vec2(ui.available_width(), 40.0),
+                        egui::Sense::hover(),
+                    );
+                    let painter = ui.painter_at(rect);
+                    painter.rect_filled(rect, 2.0, egui::Color32::from_black_alpha(80));
+                    let step = rect.width() / (track_detail::SIMILARITY_HISTORY - 1) as f32;
+                    let x0 = rect.right() - step * (track.similarity.len() - 1) as f32;
+                    let points = track
+                        .similarity
+                        .iter()
+                        .enumerate()
+                        .map(|(i, v)| {
+                            let y = rect.bottom() - 2.0 - v * (rect.height() - 4.0);
+                            egui::pos2(x0 + step * i as f32, y)
+                        })
+                        .collect();
+                    let stroke = egui::Stroke::new(1.5, egui::Color32::LIGHT_BLUE);
+                    painter.add(egui::Shape::line(points, stroke));
+                }
+
+                // 缩略图 (最新的在右侧)
+                if let Some((_, textures)) = &self.thumbnail_textures {
+                    if !textures.is_empty() {
+                        ui.label(tr("track.thumbnails"));
+                        egui::ScrollArea::horizontal().show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                for texture in textures {
+                                    let [w, h] = texture.size();
+                                    let scale = 64.0 / h.max(1) as f32;
+                                    let size = egui::vec2(w as f32 * scale, 64.0);
+                                    ui.image((texture.id(), size));
+                                }
+                            });
+                        });
+                    }
+                }
+
+                ui.separator();
+                ui.horizontal_wrapped(|ui| {
+                    let follow = if self.tracks.following == Some(track.id) {
+                        tr("track.unfollow")
+                    } else {
+                        tr("track.follow")
+                    };
+                    if ui.button(follow).clicked() {
+                        action = Some(TrackAction::Follow);
+                    }
+                    if ui.button(tr("track.export_clip")).clicked() {
+                        action = Some(TrackAction::ExportClip);
+                    }
+                    let flag = if track.flagged {
+                        tr("track.unflag")
+                    } else {
+                        tr("track.flag")
+                    };
+                    if ui.button(flag).clicked() {
+                        action = Some(TrackAction::Flag);
+                    }
+                    if ui.button(tr("track.close")).clicked() {
+                        action = Some(TrackAction::Close);
+                    }
+                });
+                if let Some(message) = &self.track_message {
+                    ui.label(message);
+                }
+            });
+
+        let (id, from, to) = (track.id, track.first_seen, track.last_seen);
+        let view = self.last_detection.as_ref().map_or(0, |r| r.view);
+        match action {
+            Some(TrackAction::Follow) => {
+                let target = (self.tracks.following != Some(id)).then_some(id);
+                self.tracks.following = target;
+                xbus::post(FollowTrack {
+                    view,
+                    track_id: target,
+                });
+                self.track_message = Some(match target {
+                    Some(id) => tr_fmt("track.following", &[&id]),
+                    None => tr("track.follow_stopped").to_string(),
+                });
+            }
+            Some(TrackAction::ExportClip) => {
+                let padding = chrono::Duration::seconds(TRACK_CLIP_PADDING_SECS);
+                let request = ClipRequest {
+                    camera: clips::camera_name(view),
+                    from: from - padding,
+                    to: to + padding,
+                    overlay: true,
+                };
+                self.track_message = Some(match clips::request_clip(request) {
+                    Ok(job) => {
+                        let detail = format!("track {} job {}", id, job.id);
+                        audit::record(AuditSource::Ui, AuditAction::ClipExport, detail);
+                        tr_fmt("track.clip_requested", &[&job.id])
+                    }
+                    Err(e) => tr_fmt("track.clip_failed", &[&format!("{:#}", e)]),
+                });
+            }
+            Some(TrackAction::Flag) => {
+                let flagged = self.tracks.toggle_flag(id);
+                xbus::post(TrackFlagged {
+                    view,
+                    track_id: id,
+                    flagged,
+                });
+                let detail = format!("view{} track {} flagged={}", view, id, flagged);
+                audit::record(AuditSource::Ui, AuditAction::TrackFlag, detail);
+                self.track_message = None;
+            }
+            Some(TrackAction::Close) => {
+                self.tracks.select(None);
+                self.track_message = None;
+            }
+            None => {}
+        }
+    }
+
+    pub fn handle_input(&mut self) {
+        // 键盘输入
+        if is_key_pressed(KeyCode::Tab) {
+            self.show_control_panel = !self.show_control_panel;
+        }
+
+        // 鼠标滚轮缩放
+        let mouse_wheel = mouse_wheel();
+        if mouse_wheel.1 != 0.0 && !self.is_mouse_over_ui {
+            // 使用指数缩放 (更平滑自然)
+            let zoom_factor = 1.1f32;
+            let scale_mult = if mouse_wheel.1 > 0.0 {
+                zoom_factor
+            } else {
+                1.0 / zoom_factor
+            };
+
+            let new_scale = (self.control_panel.zoom_scale * scale_mult).clamp(0.1, 20.0);
+
+            // 计算实际的缩放比例 (因为可能被clamp限制)
+            let ratio = new_scale / self.control_panel.zoom_scale;
+
+            // 以鼠标位置为中心缩放
+            // 核心公式: Pan_new = Pan_old * Ratio + Mouse_rel * (1 - Ratio)
+            // 其中 Mouse_rel 是鼠标相对于屏幕中心的坐标
+            let mouse_pos = mouse_position();
+            let screen_center = Vec2::new(screen_width() / 2.0, screen_height() / 2.0);
+            let mouse_rel = Vec2::new(mouse_pos.0, mouse_pos.1) - screen_center;
+
+            self.control_panel.pan_offset =
+                self.control_panel.pan_offset * ratio + mouse_rel * (1.0 - ratio);
+            self.control_panel.zoom_scale = new_scale;
+        }
+
+        // 重置缩放 (按R键)
+        if is_key_pressed(KeyCode::R) {
+            self.control_panel.zoom_scale = 1.0;
+            self.control_panel.pan_offset = Vec2::ZERO;
+        }
+
+        // 回放控制: 空格暂停/继续, 左右方向键逐帧 (面板输入框有焦点时不响应)
+        if !self.is_keyboard_over_ui {
+            if is_key_pressed(KeyCode::Space) {
+                self.playback.toggle();
+            }
+            if is_key_pressed(KeyCode::Right) {
+                self.playback.step_forward();
+            }
+            if is_key_pressed(KeyCode::Left) {
+                self.playback.step_backward();
+            }
+        }
+        if !self.playback.is_paused() {
+            self.inspected = None;
+        }
+
+        // 检视/轨迹选择: 左键点选画面上的检测框 (标注/标定模式下让给对应操作)
+        // 暂停时打开检视弹窗; 跟踪结果的框同时选中其轨迹, 打开轨迹详情面板
+        if !self.control_panel.annotator.is_frozen()
+            && !self.control_panel.calibration_mode
+            && is_mouse_button_pressed(MouseButton::Left)
+            && !self.is_mouse_over_ui
+        {
+            if let (Some(result), Some(to_window)) = (&self.last_detection, self.video_transform())
+            {
+                let (mx, my) = mouse_position();
+                let ImagePoint { x, y, .. } = to_window.inverse().point(WindowPoint::new(mx, my));
+                let hit = box_at(&result.bboxes, x, y);
+                if self.playback.is_paused() {
+                    self.inspected = hit;
+                }
+                if let Some(i) = hit.filter(|_| result.tracked) {
+                    self.tracks.select(Some(result.bboxes[i].class_id));
+                    self.track_message = None;
+                }
+            }
+        }
+
+        // 标注模式: 鼠标操作冻结画面上的标注框 (转换为视频帧坐标)
+        // 按下需在画面上, 拖动和松开不受面板遮挡影响 (拖出面板也能结束)
+        if self.control_panel.annotator.is_frozen() {
+            if let Some(to_window) = self.video_transform() {
+                let (mx, my) = mouse_position();
+                let ImagePoint { x, y, .. } = to_window.inverse().point(WindowPoint::new(mx, my));
+                let over_ui = self.is_mouse_over_ui;
+                let annotator = &mut self.control_panel.annotator;
+                if is_mouse_button_pressed(MouseButton::Left) && !over_ui {
+                    annotator.press(x, y, HANDLE_RADIUS / to_window.scale().0);
+                } else if is_mouse_button_down(MouseButton::Left) {
+                    annotator.drag_to(x, y);
+                }
+                if is_mouse_button_released(MouseButton::Left) {
+                    annotator.release();
+                }
+                if is_mouse_button_pressed(MouseButton::Right) && !over_ui {
+                    annotator.remove_at(x, y);
+                }
+            }
+        }
+
+        // 标定模式: 左键点击画面添加地面标定点 (转换为视频帧坐标)
+        if self.control_panel.calibration_mode
+            && !self.control_panel.annotator.is_frozen()
+            && is_mouse_button_pressed(MouseButton::Left)
+            && !self.is_mouse_over_ui
+        {
+            if let (Some(texture), Some(to_window)) = (&self.last_frame, self.video_transform()) {
+                let (mx, my) = mouse_position();
+                let ImagePoint { x, y, .. } = to_window.inverse().point(WindowPoint::new(mx, my));
+                if (0.0..texture.width()).contains(&x) && (0.0..texture.height()).contains(&y) {
+                    self.control_panel.add_calibration_point(x, y);
+                }
+            }
+        }
+
+        // 鼠标中键拖动
+        if is_mouse_button_down(MouseButton::Middle) {
+            let mouse_pos = mouse_position();
+            let current_pos = Vec2::new(mouse_pos.0, mouse_pos.1);
+
+            if self.is_panning {
+                let delta = current_pos - self.last_mouse_pos;
+                self.control_panel.pan_offset += delta;
+            } else {
+                self.is_panning = true;
+            }
+            self.last_mouse_pos = current_pos;
+        } else {
+            self.is_panning = false;
+        }
+    }
+}
+
+/// 轨迹详情面板的操作
+enum TrackAction {
+    Follow,
+    ExportClip,
+    Flag,
+    Close,
+}
+
+/// 模型对比的检测框 (帧坐标按视口变换到窗口)
+fn draw_compare_boxes(bboxes: &[BBox], color: Color, viewport: &ImageToWindow) {
+    for bbox in bboxes {
+        let r = viewport.rect(bbox.rect());
+        draw_rectangle_lines(r.x1, r.y1, r.width(), r.height(), 2.0, color);
+        let label = format!("{} {:.2}", bbox.class_id, bbox.confidence);
+        draw_text(&label, r.x1, r.y1 - 4.0, 18.0, color);
+    }
+}
+
+/// 模型对比的图例: 模型名, 本帧/平均耗时, 框数
+fn compare_label(side: &CompareSide) -> String {
+    format!(
+        "{} {:.1}ms (avg {:.1}ms) {} boxes",
+        model_stem(&side.model),
+        side.inference_ms,
+        side.mean_ms,
+        side.bboxes.len()
+    )
+}
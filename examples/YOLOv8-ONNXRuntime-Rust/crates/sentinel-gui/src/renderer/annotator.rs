@@ -0,0 +1,294 @@
+//! 标注模式: 冻结当前画面, 鼠标画框/调整/删除并分配类别, 导出为数据集
+//!
+//! 坐标均为原始帧像素坐标, 渲染器负责屏幕坐标换算
+
+use crate::dataset::{read_classes, Annotation, DatasetWriter};
+use crate::detection::types::{BBox, DecodedFrame};
+use crate::i18n::{tr, tr_fmt};
+use egui_macroquad::egui;
+use std::path::PathBuf;
+
+/// 鼠标拾取角点的半径 (屏幕像素)
+pub const HANDLE_RADIUS: f32 = 8.0;
+
+/// 进行中的拖动操作
+#[derive(Clone, Copy, Debug)]
+enum Drag {
+    New { start: (f32, f32), end: (f32, f32) }, // 画新框
+    Corner { index: usize, corner: usize },     // 调整角点
+    Move { index: usize, last: (f32, f32) },    // 平移整框
+}
+
+/// 标注状态 (控制面板持有, 渲染器转发鼠标操作)
+pub struct Annotator {
+    pub enabled: bool,
+    pub frame: Option<DecodedFrame>, // 冻结的画面, 开启后收到的第一帧
+    pub boxes: Vec<Annotation>,
+    pub selected: Option<usize>,
+    pub classes: Vec<String>, // 类别名, 下标即 class_id
+    pub class_id: u32,        // 新框的类别
+    dataset_dir: String,
+    new_class: String,
+    drag: Option<Drag>,
+    writer: Option<DatasetWriter>,
+    status: Option<Result<String, String>>, // 最近一次导出的结果
+}
+
+impl Annotator {
+    /// 类别从数据集目录的 classes.txt 读取, 不存在时只有 person
+    pub fn new(dataset_dir: &str) -> Self {
+        let mut classes = read_classes(dataset_dir);
+        if classes.is_empty() {
+            classes.push("person".to_string());
+        }
+        Self {
+            enabled: false,
+            frame: None,
+            boxes: Vec::new(),
+            selected: None,
+            classes,
+            class_id: 0,
+            dataset_dir: dataset_dir.to_string(),
+            new_class: String::new(),
+            drag: None,
+            writer: None,
+            status: None,
+        }
+    }
+
+    /// 画面是否已冻结 (渲染器据此停止更新纹理并接管鼠标)
+    pub fn is_frozen(&self) -> bool {
+        self.enabled && self.frame.is_some()
+    }
+
+    /// 冻结画面, 以检测结果作为初始框
+    ///
+    /// 启用跟踪时检测框的 class_id 是轨迹ID, 此时统一使用当前类别;
+    /// 否则沿用检测类别, 超出类别表的框丢弃
+    pub fn freeze(&mut self, frame: DecodedFrame, detections: &[BBox], detected_class: bool) {
+        self.boxes = detections
+            .iter()
+            .filter(|b| !detected_class || (b.class_id as usize) < self.classes.len())
+            .map(|b| {
+                let class_id = if detected_class {
+                    b.class_id
+                } else {
+                    self.class_id
+                };
+                Annotation::from_corners((b.x1, b.y1), (b.x2, b.y2), class_id)
+                    .clamped(frame.width as f32, frame.height as f32)
+            })
+            .filter(Annotation::is_valid)
+            .collect();
+        self.frame = Some(frame);
+        self.selected = None;
+        self.drag = None;
+    }
+
+    /// 解冻, 丢弃未导出的框 (开启状态下渲染器会冻结下一帧)
+    pub fn thaw(&mut self) {
+        self.frame = None;
+        self.boxes.clear();
+        self.selected = None;
+        self.drag = None;
+    }
+
+    /// 左键按下: 优先拾取选中框的角点, 其次框内平移, 否则开始画新框
+    pub fn press(&mut self, x: f32, y: f32, tolerance: f32) {
+        if let Some(index) = self.selected {
+            if let Some(corner) = self.boxes[index].corner_at(x, y, tolerance) {
+                self.drag = Some(Drag::Corner { index, corner });
+                return;
+            }
+        }
+        // 重叠时取最小的框, 便于选中被大框包住的目标
+        let hit = self
+            .boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.contains(x, y))
+            .min_by(|a, b| (a.1.width() * a.1.height()).total_cmp(&(b.1.width() * b.1.height())))
+            .map(|(i, _)| i);
+        self.selected = hit;
+        self.drag = Some(match hit {
+            Some(index) => Drag::Move {
+                index,
+                last: (x, y),
+            },
+            None => Drag::New {
+                start: (x, y),
+                end: (x, y),
+            },
+        });
+    }
+
+    /// 左键拖动
+    pub fn drag_to(&mut self, x: f32, y: f32) {
+        match &mut self.drag {
+            Some(Drag::New { end, .. }) => *end = (x, y),
+            Some(Drag::Corner { index, corner }) => self.boxes[*index].move_corner(*corner, x, y),
+            Some(Drag::Move { index, last }) => {
+                self.boxes[*index].translate(x - last.0, y - last.1);
+                *last = (x, y);
+            }
+            None => {}
+        }
+    }
+
+    /// 左键松开: 新框足够大时加入并选中, 调整后的框裁剪到画面内
+    pub fn release(&mut self) {
+        let Some((width, height)) = self
+            .frame
+            .as_ref()
+            .map(|f| (f.width as f32, f.height as f32))
+        else {
+            return;
+        };
+        match self.drag.take() {
+            Some(Drag::New { .. }) => {
+                if let Some(new_box) = self.preview().map(|b| b.clamped(width, height)) {
+                    if new_box.is_valid() {
+                        self.boxes.push(new_box);
+                        self.selected = Some(self.boxes.len() - 1);
+                    }
+                }
+            }
+            Some(Drag::Corner { index, .. } | Drag::Move { index, .. }) => {
+                self.boxes[index] = self.boxes[index].clamped(width, height);
+            }
+            None => {}
+        }
+    }
+
+    /// 右键: 删除点中的框
+    pub fn remove_at(&mut self, x: f32, y: f32) {
+        if let Some(index) = self.boxes.iter().rposition(|b| b.contains(x, y)) {
+            self.boxes.remove(index);
+            self.selected = None;
+            self.drag = None;
+        }
+    }
+
+    /// 正在画的新框
+    pub fn preview(&self) -> Option<Annotation> {
+        match self.drag {
+            Some(Drag::New { start, end }) => {
+                Some(Annotation::from_corners(start, end, self.class_id))
+            }
+            _ => None,
+        }
+    }
+
+    /// 类别名称, 超出类别表时显示编号
+    pub fn class_name(&self, class_id: u32) -> String {
+        self.classes
+            .get(class_id as usize)
+            .cloned()
+            .unwrap_or_else(|| class_id.to_string())
+    }
+
+    /// 导出冻结画面与标注框, 成功后解冻以采集下一帧
+    fn export(&mut self) -> anyhow::Result<PathBuf> {
+        let frame = self
+            .frame
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("画面未冻结"))?;
+        if self.writer.is_none() {
+            self.writer = Some(DatasetWriter::open(&self.dataset_dir)?);
+        }
+        let writer = self.writer.as_mut().unwrap();
+        let path = writer.write(
+            &frame.rgba_data,
+            frame.width,
+            frame.height,
+            &self.boxes,
+            &self.classes,
+        )?;
+        self.thaw();
+        Ok(path)
+    }
+
+    /// 控制面板中的标注区块
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        if ui
+            .checkbox(&mut self.enabled, tr("annotate.mode"))
+            .changed()
+            && !self.enabled
+        {
+            self.thaw();
+        }
+        ui.label(tr("annotate.hint"));
+
+        // 类别: 修改时同时作用于选中的框
+        let mut class_id = self.class_id;
+        egui::ComboBox::new("annotate_class", tr("annotate.class"))
+            .selected_text(self.class_name(class_id))
+            .show_ui(ui, |ui| {
+                for (id, name) in self.classes.iter().enumerate() {
+                    ui.selectable_value(&mut class_id, id as u32, name);
+                }
+            });
+        if class_id != self.class_id {
+            self.class_id = class_id;
+            if let Some(index) = self.selected {
+                self.boxes[index].class_id = class_id;
+            }
+        }
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_class)
+                    .hint_text(tr("annotate.new_class_hint"))
+                    .desired_width(120.0),
+            );
+            let name = self.new_class.trim().to_string();
+            if ui.button(tr("annotate.add_class")).clicked()
+                && !name.is_empty()
+                && !self.classes.contains(&name)
+            {
+                self.classes.push(name);
+                self.class_id = self.classes.len() as u32 - 1;
+                self.new_class.clear();
+            }
+        });
+
+        match &self.status {
+            Some(Ok(path)) => {
+                let total = self.writer.as_ref().map_or(0, DatasetWriter::len);
+                ui.colored_label(
+                    egui::Color32::GREEN,
+                    tr_fmt("annotate.exported", &[&total, path]),
+                );
+            }
+            Some(Err(e)) => {
+                ui.colored_label(egui::Color32::RED, format!("⚠️ {}", e));
+            }
+            None => {}
+        }
+
+        if !self.is_frozen() {
+            return;
+        }
+        ui.label(tr_fmt("annotate.count", &[&self.boxes.len()]));
+        ui.horizontal(|ui| {
+            if ui.button(tr("annotate.export")).clicked() {
+                self.status = Some(match self.export() {
+                    Ok(path) => {
+                        println!("🏷️ 已导出标注: {}", path.display());
+                        Ok(path.display().to_string())
+                    }
+                    Err(e) => {
+                        eprintln!("❌ 导出标注失败: {:#}", e);
+                        Err(format!("{:#}", e))
+                    }
+                });
+            }
+            if ui.button(tr("annotate.skip")).clicked() {
+                self.thaw();
+            }
+            if ui.button(tr("common.clear")).clicked() {
+                self.boxes.clear();
+                self.selected = None;
+            }
+        });
+    }
+}
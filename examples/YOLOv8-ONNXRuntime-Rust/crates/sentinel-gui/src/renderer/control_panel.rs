@@ -0,0 +1,2349 @@
+use super::annotator::Annotator;
+use super::pacing::{PacingMode, PacingStats};
+use crate::analytics::{
+    GroundCalibration, GroundPoint, LeftBehindEvent, MarkerDetections, SceneChange, TamperEvent,
+    TamperKind, Zone, ZoneConfig, ZoneEvent, GROUND_CALIBRATION_PATH,
+};
+use crate::audit::{self, AuditAction, AuditSource};
+use crate::dataset::DATASET_DIR;
+use crate::detection::compare::{CompareLayout, ComparisonResult};
+use crate::detection::profiler::{self, DEFAULT_PROFILE_SECS};
+use crate::detection::types::{ClockDrift, ControlMessage};
+use crate::detection::{
+    AssociationWeights, LatencyStage, StageSummary, TrackStats, TrackerParams, DETECT_CLASSES,
+    INF_SIZE,
+};
+use crate::hot_reload::ConfigUpdate;
+use crate::i18n::{self, tr, tr_fmt, Language};
+use crate::input::decoder::{keyframes_only, set_keyframes_only, DecoderPreference};
+use crate::input::decoder_manager::ACTIVE_DECODER_GENERATION;
+use crate::input::rtsp_security::Credential;
+use crate::input::shm::DEFAULT_SHM_PATH;
+use crate::input::vault::{self, VaultEntry};
+use crate::input::{
+    active_orientation, active_source_key, get_video_devices, redact_url, set_active_orientation,
+    switch_decoder_source, InputSource, VideoDevice,
+};
+use crate::models::world;
+use crate::scheduler::{schedule_status, set_arm_mode, ArmMode, ArmStateChanged};
+use crate::ui_config::{
+    OrientationConfig, Profile, ProfileConfig, SessionState, TrackerConfig,
+    ORIENTATION_CONFIG_PATH, PROFILES_CONFIG_PATH, TRACKER_CONFIG_PATH,
+};
+use crate::utils::clock::{self, DRIFT_WARN_MS};
+use crate::utils::colormap::Palette;
+use crate::utils::fisheye::fisheye_config;
+use crate::utils::jetson::{JetsonStatus, ThrottleLevel};
+use crate::utils::metrics_ring::{Metric, MetricsRing, SharedMetrics, HISTORY_SECS};
+use crate::utils::orientation::Rotation;
+use crate::utils::resize::{self, ResizeQuality};
+use crate::{xbus, DetectorError};
+use chrono::NaiveTime;
+use egui_macroquad::egui::{self, TextureHandle};
+use macroquad::math::Vec2;
+use phf::phf_map;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// 复制文本到系统剪贴板 (Windows 专用，使用 clipboard-win)
+#[cfg(windows)]
+fn copy_to_clipboard(_ui: &egui::Ui, text: &str) {
+    use clipboard_win::{formats, set_clipboard};
+
+    println!("📋 复制到剪贴板: {}", text);
+
+    match set_clipboard(formats::Unicode, text) {
+        Ok(_) => {
+            println!("✅ 已成功复制到系统剪贴板!");
+            println!("💡 现在可以在 VS Code 等应用中按 Ctrl+V 粘贴");
+        }
+        Err(e) => {
+            eprintln!("❌ 复制到剪贴板失败: {:?}", e);
+        }
+    }
+}
+
+/// 复制文本到系统剪贴板 (非 Windows 平台)
+#[cfg(not(windows))]
+fn copy_to_clipboard(ui: &egui::Ui, text: &str) {
+    println!("📋 复制到剪贴板: {}", text);
+    ui.ctx().copy_text(text.to_string());
+    println!("✅ 已复制!");
+}
+
+/// 读取 RTSP 历史记录, 其中带密码的地址迁入凭据库 (迁移失败时去掉密码) 并写回文件
+fn load_rtsp_history() -> Option<Vec<String>> {
+    let content = std::fs::read_to_string("rtsp_history.txt").ok()?;
+    let lines: Vec<&str> = content
+        .lines()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if lines.is_empty() {
+        return None;
+    }
+    let history: Vec<String> = lines
+        .iter()
+        .map(|line| {
+            vault::migrate_url(line).unwrap_or_else(|e| {
+                eprintln!("⚠️ RTSP 历史记录中的密码未能迁入凭据库, 已移除: {:#}", e);
+                vault::strip_password(line)
+            })
+        })
+        .collect();
+    if history.iter().zip(&lines).any(|(new, old)| new != old) {
+        println!("🔐 RTSP 历史记录中的密码已迁入凭据库");
+        if let Err(e) = std::fs::write("rtsp_history.txt", history.join("\n")) {
+            eprintln!("⚠️ 保存 RTSP 历史记录失败: {}", e);
+        }
+    }
+    Some(history)
+}
+
+static MODELS: [&str; 25] = [
+    "yolov8n",
+    "yolov8s",
+    "yolov8m",
+    "yolov8l",
+    "yolov8x",
+    "yolov10n",
+    "yolov10s",
+    "yolov10m",
+    "yolov11n",
+    "yolov11s",
+    "yolov11m",
+    "yolov5n",
+    "yolov5s",
+    "yolov5m",
+    "yolo-fastestv2",
+    "yolo-fastest-xl",
+    "yolov8n-int8",
+    "yolov8m-int8",
+    "nanodet",
+    "nanodet-plus",
+    "yolox_nano",
+    "yolox_tiny",
+    "yolox_s",
+    "yolox_m",
+    "yolox_l",
+];
+
+static MODEL_INDICES: phf::Map<&'static str, usize> = phf_map! {
+    "yolov8n" => 0,
+    "yolov8s" => 1,
+    "yolov8m" => 2,
+    "yolov8l" => 3,
+    "yolov8x" => 4,
+    "yolov10n" => 5,
+    "yolov10s" => 6,
+    "yolov10m" => 7,
+    "yolov11n" => 8,
+    "yolov11s" => 9,
+    "yolov11m" => 10,
+    "yolov5n" => 11,
+    "yolov5s" => 12,
+    "yolov5m" => 13,
+    "yolo-fastestv2" => 14,
+    "yolo-fastest-xl" => 15,
+    "yolov8n-int8" => 16,
+    "yolov8m-int8" => 17,
+    "nanodet" => 18,
+    "nanodet-plus" => 19,
+    "yolox_nano" => 20,
+    "yolox_tiny" => 21,
+    "yolox_s" => 22,
+    "yolox_m" => 23,
+    "yolox_l" => 24,
+};
+
+/// 面板保留的最近事件数 (每类)
+const MAX_EVENTS: usize = 20;
+
+static TRACKERS: [&str; 3] = ["DeepSORT", "ByteTrack", "无"];
+static TRACKER_INDICES: phf::Map<&'static str, usize> = phf_map! {
+    "deepsort" => 0,
+    "bytetrack" => 1,
+    "none" => 2,
+    "无" => 2,
+};
+
+/// 控制面板状态
+pub struct ControlPanel {
+    // 系统配置信息
+    pub detect_model_name: String,
+    pub tracker_name: String,
+    pub detect_fps: f64,
+    pub decode_fps: f64,
+    pub render_fps: f64,
+
+    // egui 参数调整
+    pub confidence_threshold: f32,
+    pub iou_threshold: f32,
+    // 阈值预览: 低于置信度阈值、不低于下限的检测以半透明虚影显示
+    pub ghost_preview: bool,
+    pub ghost_floor: f32,
+    pub inference_size: u32, // 当前推理输入尺寸 (分辨率阶梯会自动调整)
+
+    // 输入源配置界面
+    pub input_source_type: usize, // 0=RTSP, 1=摄像头, 2=桌面捕获, 3=工业相机, 4=共享内存, 5=合成场景
+    pub rtsp_url: String,
+    pub rtsp_history: Vec<String>, // RTSP 历史记录 (不含密码, 凭据以 $ID 引用)
+    // RTSP 凭据库: 已保存的条目与新增表单
+    pub vault_entries: Vec<VaultEntry>,
+    pub vault_id: String,
+    pub vault_username: String,
+    pub vault_password: String,
+    // 审计视图的筛选关键字 (来源/操作/内容)
+    pub audit_filter: String,
+
+    // 设备列表
+    pub video_devices: Vec<VideoDevice>,
+    pub selected_device_index: usize,
+    pub devices_loaded: bool,
+    // 已发现的 GigE Vision 相机与当前选中的相机ID
+    pub gige_cameras: Vec<String>,
+    pub gige_camera: String,
+    // 共享内存输入的映射文件路径
+    pub shm_path: String,
+    // 合成场景文件路径 (为空时使用内置场景)
+    pub synthetic_scene: String,
+
+    // 模型配置
+    pub selected_model_index: usize,
+    pub selected_tracker_index: usize,
+    pub pose_enabled: bool,
+    pub detection_enabled: bool,
+    // 推理输入尺寸与检测的类别ID (空表示全部类别), 可由配置档案切换
+    pub input_size: u32,
+    pub classes: Vec<u32>,
+    pub resize_quality: ResizeQuality,   // 整帧缩放到推理输入的算法
+    pub crowd_enabled: bool,             // 人群密度估计 (需 --crowd-model)
+    pub crowd_count: Option<f32>,        // 密度图估计人数 (检测线程回传)
+    pub text_prompt: String,             // CLIP 文本提示
+    pub vocabulary: String,              // 开放词汇类别 (逗号分隔, 空为配置中的词表)
+    pub association: AssociationWeights, // ByteTrack 关联权重
+    pub tracker_config: TrackerConfig,   // 跟踪器生命周期参数 (tracker_config.json)
+    pub track_stats: TrackStats,         // 轨迹统计 (检测线程回传)
+    pub jetson_status: Option<JetsonStatus>, // Jetson 功耗/温度 (启用 tegrastats 时回传)
+    pub latency_report: Vec<(LatencyStage, StageSummary)>, // 分阶段延迟 (渲染线程每秒更新)
+    pub metrics: SharedMetrics,          // 最近 60 秒的帧率/延迟/置信度 (渲染线程每秒写入)
+    // 渲染的逻辑流 (0 为原始画面, 1..=N 为鱼眼虚拟视图), 与渲染器订阅回调共享
+    pub display_view: Arc<AtomicU32>,
+    // 显示调色板 (热成像画面可用铁红伪彩色)
+    pub palette: Palette,
+    // 模型对比: 开关, 模型 B (模型 A 为当前检测模型), 显示方式, 最近一帧对比结果
+    pub compare_enabled: bool,
+    pub compare_model_index: usize,
+    pub compare_layout: CompareLayout,
+    pub comparison: Arc<Mutex<Option<ComparisonResult>>>,
+    // 帧呈现模式与每秒呈现统计 (渲染线程更新)
+    pub pacing: PacingMode,
+    pub pacing_stats: PacingStats,
+
+    // 地面标定: 标定模式下左键点击画面添加标定点
+    pub ground_calibration: GroundCalibration,
+    pub calibration_mode: bool,
+    pub calibration_error: Option<f32>, // 最近一次求解的重投影误差 (米)
+
+    // 标注模式: 冻结画面画框并导出数据集
+    pub annotator: Annotator,
+
+    // 配置档案 (profiles.json): 一键切换或按时段自动切换
+    pub profiles: ProfileConfig,
+    pub profiles_path: String,
+    pub active_profile: Option<String>,
+    scheduled_profile: Option<String>, // 当前时段的档案, 进入新时段时才自动切换
+    new_profile_name: String,
+    // 档案切换的区域定义, 由渲染器取走 (小地图显示)
+    pub zones: Option<Vec<Zone>>,
+    // 热加载线程发布的新配置, 渲染线程每帧应用
+    pub config_updates: Arc<Mutex<Vec<ConfigUpdate>>>,
+    // API 请求切换的检测模型 (面板中的名称), 渲染线程每帧应用
+    pub model_requests: Arc<Mutex<Vec<String>>>,
+
+    // 最近的区域事件 (区域引擎在检测线程上发布)
+    pub zone_events: Arc<Mutex<VecDeque<ZoneEvent>>>,
+    // 最近的遗留/移除事件 (遗留物检测线程发布)
+    pub left_behind_events: Arc<Mutex<VecDeque<LeftBehindEvent>>>,
+    // 最近的摄像头篡改事件 (篡改检测线程发布)
+    pub tamper_events: Arc<Mutex<VecDeque<TamperEvent>>>,
+    // 最近的布防/撤防事件 (布防调度器发布)
+    pub arm_events: Arc<Mutex<VecDeque<ArmStateChanged>>>,
+    // 检测线程当前错误 (模型加载/推理失败), 恢复后清空
+    pub detector_error: Arc<Mutex<Option<DetectorError>>>,
+    // 最近一次摄像头时钟漂移报告 (解码线程发布), 超过阈值时显示
+    pub clock_drift: Arc<Mutex<Option<ClockDrift>>>,
+    // 最近一次基准标记检测 (标记锚定线程发布), 标定时记为参考标记
+    pub marker_detections: Arc<Mutex<Option<MarkerDetections>>>,
+    // 视图控制
+    pub zoom_scale: f32,
+    pub pan_offset: macroquad::prelude::Vec2,
+
+    // 背景纹理
+    pub panel_bg_egui: Option<TextureHandle>,
+    pub panel_bg_size: Option<(usize, usize)>,
+}
+
+impl ControlPanel {
+    pub fn new(detect_model: String, tracker: String) -> Self {
+        let mut bg = None;
+        let mut bg_size = None;
+        if let Ok(bytes) = std::fs::read("assets/images/panel_bg.jpg") {
+            if let Ok(img) = image::load_from_memory(&bytes) {
+                let rgba = img.to_rgba8();
+                let width = rgba.width() as usize;
+                let height = rgba.height() as usize;
+                bg_size = Some((width, height));
+                let color_image = egui::ColorImage::from_rgba_unmultiplied([width, height], &rgba);
+                egui_macroquad::cfg(|egui_ctx| {
+                    let texture = egui_ctx.load_texture(
+                        "panel_bg",
+                        color_image,
+                        egui::TextureOptions::LINEAR,
+                    );
+
+                    bg = Some(texture);
+                });
+            }
+        }
+
+        Self {
+            detect_model_name: detect_model.clone(),
+            tracker_name: tracker.clone(),
+            detect_fps: 0.0,
+            decode_fps: 0.0,
+            render_fps: 0.0,
+            confidence_threshold: 0.5,
+            iou_threshold: 0.45,
+            ghost_preview: false,
+            ghost_floor: 0.1,
+            inference_size: 0,
+            input_source_type: 0,
+            rtsp_url: String::new(),
+            rtsp_history: load_rtsp_history().unwrap_or_default(),
+            vault_entries: vault::entries(),
+            vault_id: String::new(),
+            vault_username: String::new(),
+            vault_password: String::new(),
+            audit_filter: String::new(),
+            video_devices: Vec::new(),
+            selected_device_index: 0,
+            devices_loaded: false,
+            gige_cameras: Vec::new(),
+            gige_camera: String::new(),
+            shm_path: DEFAULT_SHM_PATH.to_string(),
+            synthetic_scene: String::new(),
+            selected_model_index: *MODEL_INDICES.get(detect_model.as_str()).unwrap_or(&0),
+            selected_tracker_index: *TRACKER_INDICES
+                .get(tracker.to_lowercase().as_str())
+                .unwrap_or(&2),
+            pose_enabled: false,
+            detection_enabled: true,
+            input_size: INF_SIZE,
+            classes: DETECT_CLASSES.to_vec(),
+            resize_quality: resize::default_quality(),
+            crowd_enabled: true,
+            crowd_count: None,
+            text_prompt: String::new(),
+            vocabulary: String::new(),
+            association: AssociationWeights::default(),
+            tracker_config: TrackerConfig::load(TRACKER_CONFIG_PATH),
+            track_stats: TrackStats::default(),
+            jetson_status: None,
+            latency_report: Vec::new(),
+            metrics: MetricsRing::shared(),
+            zoom_scale: 1.0,
+            pan_offset: macroquad::prelude::Vec2::ZERO,
+            panel_bg_egui: bg,
+            panel_bg_size: bg_size,
+            // 启用鱼眼去畸变时默认显示第一个虚拟视图
+            display_view: Arc::new(AtomicU32::new(fisheye_config().is_some() as u32)),
+            palette: Palette::None,
+            compare_enabled: false,
+            compare_model_index: *MODEL_INDICES.get("yolov8s").unwrap_or(&0),
+            compare_layout: CompareLayout::default(),
+            comparison: Arc::new(Mutex::new(None)),
+            pacing: PacingMode::default(),
+            pacing_stats: PacingStats::default(),
+            ground_calibration: GroundCalibration::load(GROUND_CALIBRATION_PATH),
+            calibration_mode: false,
+            calibration_error: None,
+            annotator: Annotator::new(DATASET_DIR),
+            profiles: ProfileConfig::default(),
+            profiles_path: PROFILES_CONFIG_PATH.to_string(),
+            active_profile: None,
+            scheduled_profile: None,
+            new_profile_name: String::new(),
+            zones: None,
+            config_updates: Arc::new(Mutex::new(Vec::new())),
+            model_requests: Arc::new(Mutex::new(Vec::new())),
+            zone_events: Arc::new(Mutex::new(VecDeque::with_capacity(MAX_EVENTS))),
+            left_behind_events: Arc::new(Mutex::new(VecDeque::with_capacity(MAX_EVENTS))),
+            tamper_events: Arc::new(Mutex::new(VecDeque::with_capacity(MAX_EVENTS))),
+            arm_events: Arc::new(Mutex::new(VecDeque::with_capacity(MAX_EVENTS))),
+            detector_error: Arc::new(Mutex::new(None)),
+            clock_drift: Arc::new(Mutex::new(None)),
+            marker_detections: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// 标定模式下添加标定点 (图像坐标), 地面坐标在面板中填写
+    pub fn add_calibration_point(&mut self, x: f32, y: f32) {
+        println!("📍 添加地面标定点: ({:.0}, {:.0})", x, y);
+        self.ground_calibration.points.push(GroundPoint {
+            image: [x, y],
+            world: [0.0, 0.0],
+        });
+    }
+
+    /// 记录事件 (最新的在前, 超出上限时丢弃最旧的)
+    pub fn push_event<T>(events: &Mutex<VecDeque<T>>, event: T) {
+        let mut events = events.lock().unwrap();
+        if events.len() >= MAX_EVENTS {
+            events.pop_back();
+        }
+        events.push_front(event);
+    }
+
+    /// 阈值预览的虚影下限, 未启用时为 None
+    pub fn ghost_floor(&self) -> Option<f32> {
+        self.ghost_preview.then_some(self.ghost_floor)
+    }
+
+    /// 当前跟踪器的生命周期参数, 未启用跟踪时为 None
+    pub fn tracker_params(&self) -> Option<TrackerParams> {
+        match TRACKERS.get(self.selected_tracker_index).copied() {
+            Some("DeepSORT") => Some(self.tracker_config.deepsort_params()),
+            Some("ByteTrack") => Some(self.tracker_config.bytetrack_params()),
+            _ => None,
+        }
+    }
+
+    /// 生命周期参数滑块, 返回参数是否被修改
+    fn tracker_params_ui(ui: &mut egui::Ui, params: &mut TrackerParams, scores: bool) -> bool {
+        let mut changed = false;
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut params.max_lost_frames, 1..=300)
+                    .text(tr("tracker.max_lost")),
+            )
+            .changed();
+        changed |= ui
+            .add(egui::Slider::new(&mut params.min_hits, 1..=10).text(tr("tracker.min_hits")))
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut params.high_iou_threshold, 0.0..=1.0)
+                    .text(tr("tracker.match_iou")),
+            )
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut params.low_iou_threshold, 0.0..=1.0)
+                    .text(tr("tracker.loose_iou")),
+            )
+            .on_hover_text(tr("tracker.loose_iou_hint"))
+            .changed();
+        if scores {
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut params.high_score_threshold, 0.0..=1.0)
+                        .text(tr("tracker.high_score")),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut params.low_score_threshold, 0.0..=1.0)
+                        .text(tr("tracker.low_score")),
+                )
+                .changed();
+        }
+        changed
+    }
+
+    /// 保存 RTSP 历史记录到文件
+    fn save_rtsp_history(&self) {
+        if let Err(e) = std::fs::write("rtsp_history.txt", self.rtsp_history.join("\n")) {
+            eprintln!("⚠️ 保存 RTSP 历史记录失败: {}", e);
+        }
+    }
+
+    /// 添加 RTSP 地址到历史记录并保存 (密码不写入历史记录)
+    fn add_rtsp_to_history(&mut self, url: String) {
+        let url = vault::strip_password(&url);
+        if !self.rtsp_history.contains(&url) {
+            self.rtsp_history.insert(0, url.clone());
+            // 限制历史记录数量
+            if self.rtsp_history.len() > 10 {
+                self.rtsp_history.truncate(10);
+            }
+            println!("📝 新增 RTSP 历史记录: {}", url);
+            self.save_rtsp_history();
+        }
+    }
+
+    /// 凭据库: 已保存的条目 (删除) 与新增表单 (密码输入框打码)
+    fn show_vault(&mut self, ui: &mut egui::Ui) {
+        ui.label(egui::RichText::new(tr("vault.hint")).small().weak());
+        if self.vault_entries.is_empty() {
+            ui.label(tr("vault.empty"));
+        }
+        let mut removed = None;
+        for entry in &self.vault_entries {
+            ui.horizontal(|ui| {
+                ui.monospace(format!("${}", entry.id));
+                ui.label(&entry.username);
+                if ui.small_button(tr("vault.delete")).clicked() {
+                    removed = Some(entry.id.clone());
+                }
+            });
+        }
+        if let Some(id) = removed {
+            if let Err(e) = vault::remove(&id) {
+                eprintln!("⚠️ {:#}", e);
+            }
+            self.vault_entries = vault::entries();
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.vault_id)
+                    .desired_width(80.0)
+                    .hint_text(tr("vault.id")),
+            );
+            ui.add(
+                egui::TextEdit::singleline(&mut self.vault_username)
+                    .desired_width(80.0)
+                    .hint_text(tr("vault.username")),
+            );
+            ui.add(
+                egui::TextEdit::singleline(&mut self.vault_password)
+                    .password(true)
+                    .desired_width(80.0)
+                    .hint_text(tr("vault.password")),
+            );
+            let ready = !self.vault_id.trim().is_empty() && !self.vault_password.is_empty();
+            if ui
+                .add_enabled(ready, egui::Button::new(tr("vault.save")))
+                .clicked()
+            {
+                let credential = Credential {
+                    username: self.vault_username.trim().to_string(),
+                    password: std::mem::take(&mut self.vault_password),
+                };
+                match vault::store(self.vault_id.trim(), &credential) {
+                    Ok(()) => {
+                        self.vault_id.clear();
+                        self.vault_username.clear();
+                        self.vault_entries = vault::entries();
+                    }
+                    Err(e) => eprintln!("⚠️ {:#}", e),
+                }
+            }
+        });
+    }
+
+    fn resolve_model_path(&self, model_name: &str) -> String {
+        match model_name {
+            "yolo-fastestv2" => "models/yolo-fastestv2-opt.onnx".to_string(),
+            "yolo-fastest-xl" => "models/yolo-fastest-1.1.onnx".to_string(),
+            "nanodet" => "models/nanodet-plus-m_320.onnx".to_string(),
+            "nanodet-plus" => "models/nanodet-plus-m_416.onnx".to_string(),
+            name if name.ends_with("-int8") => format!("models/{}.onnx", name.replace("-", "_")),
+            _ => format!("models/{}.onnx", model_name),
+        }
+    }
+
+    /// 当前检测模型的路径
+    pub fn model_path(&self) -> String {
+        self.resolve_model_path(&self.detect_model_name)
+    }
+
+    /// 启用模型对比时的两个模型路径 (当前检测模型, 对比模型)
+    pub fn compare_models(&self) -> Option<[String; 2]> {
+        let other = MODELS.get(self.compare_model_index)?;
+        self.compare_enabled
+            .then(|| [self.model_path(), self.resolve_model_path(other)])
+    }
+
+    /// 面板的会话状态 (窗口尺寸与当前输入源由渲染器填写)
+    pub fn session_state(&self) -> SessionState {
+        SessionState {
+            model: Some(self.detect_model_name.clone()),
+            tracker: TRACKERS
+                .get(self.selected_tracker_index)
+                .map(|t| t.to_string()),
+            confidence_threshold: self.confidence_threshold,
+            iou_threshold: self.iou_threshold,
+            pose_enabled: self.pose_enabled,
+            detection_enabled: self.detection_enabled,
+            crowd_enabled: self.crowd_enabled,
+            language: i18n::language(),
+            zoom_scale: self.zoom_scale,
+            pan_offset: (self.pan_offset.x, self.pan_offset.y),
+            display_view: self.display_view.load(Ordering::Relaxed),
+            palette: self.palette,
+            pacing: self.pacing,
+            input_source_type: self.input_source_type,
+            rtsp_url: vault::strip_password(&self.rtsp_url),
+            camera: self
+                .video_devices
+                .get(self.selected_device_index)
+                .map(|d| d.name.clone()),
+            ..SessionState::default()
+        }
+    }
+
+    /// 恢复会话状态 (检测线程启动前调用), 返回需要自动恢复的输入源
+    ///
+    /// 摄像头按名称重新匹配索引, 设备已拔出时不恢复
+    pub fn restore_session(&mut self, state: &SessionState) -> Option<InputSource> {
+        if let Some(model) = &state.model {
+            match MODEL_INDICES.get(model.as_str()) {
+                Some(&idx) => {
+                    self.selected_model_index = idx;
+                    self.detect_model_name = model.clone();
+                }
+                None => eprintln!("⚠️ 会话中的检测模型未知: {}, 使用默认模型", model),
+            }
+        }
+        if let Some(tracker) = &state.tracker {
+            if let Some(&idx) = TRACKER_INDICES.get(tracker.to_lowercase().as_str()) {
+                self.selected_tracker_index = idx;
+                self.tracker_name = TRACKERS[idx].to_string();
+            }
+        }
+        self.confidence_threshold = state.confidence_threshold;
+        self.iou_threshold = state.iou_threshold;
+        self.pose_enabled = state.pose_enabled;
+        self.detection_enabled = state.detection_enabled;
+        self.crowd_enabled = state.crowd_enabled;
+        i18n::set_language(state.language);
+        self.zoom_scale = state.zoom_scale;
+        self.pan_offset = Vec2::new(state.pan_offset.0, state.pan_offset.1);
+        let views = fisheye_config().map_or(0, |f| f.views.len() as u32);
+        if state.display_view <= views {
+            self.display_view
+                .store(state.display_view, Ordering::Relaxed);
+        }
+        self.palette = state.palette;
+        self.pacing = state.pacing;
+        self.input_source_type = state.input_source_type.min(5);
+        match &state.source {
+            Some(InputSource::GigE(id)) => self.gige_camera = id.clone(),
+            Some(InputSource::Shm(path)) => self.shm_path = path.clone(),
+            Some(InputSource::Synthetic(path)) => self.synthetic_scene = path.clone(),
+            _ => {}
+        }
+        if !state.rtsp_url.trim().is_empty() {
+            // 旧会话中的带密码地址迁入凭据库
+            self.rtsp_url = vault::migrate_url(&state.rtsp_url).unwrap_or_else(|e| {
+                eprintln!("⚠️ 会话中的 RTSP 密码未能迁入凭据库: {:#}", e);
+                state.rtsp_url.clone()
+            });
+        }
+
+        if let Some(camera) = &state.camera {
+            if !self.devices_loaded {
+                self.video_devices = get_video_devices();
+                self.devices_loaded = true;
+            }
+            match self.video_devices.iter().position(|d| &d.name == camera) {
+                Some(idx) => self.selected_device_index = idx,
+                None => eprintln!("⚠️ 会话中的摄像头未找到: {}", camera),
+            }
+        }
+
+        match state.source.clone()? {
+            InputSource::Camera(_, name) => {
+                let device = self.video_devices.iter().find(|d| d.name == name)?;
+                Some(InputSource::Camera(device.index, name))
+            }
+            InputSource::Rtsp(url) => {
+                Some(InputSource::Rtsp(vault::migrate_url(&url).unwrap_or(url)))
+            }
+            source => Some(source),
+        }
+    }
+
+    /// 切换到配置档案: 更新面板参数并通知检测线程, 档案未设置的项保持不变
+    pub fn apply_profile(&mut self, profile: &Profile) {
+        println!("🗂️ 切换配置档案: {}", profile.name);
+
+        // 模型或输入尺寸变化时重新加载模型 (尺寸须先于 SwitchModel 到达)
+        let mut reload = false;
+        if let Some(model) = &profile.model {
+            match MODEL_INDICES.get(model.as_str()) {
+                Some(&idx) if *model != self.detect_model_name => {
+                    self.selected_model_index = idx;
+                    self.detect_model_name = model.clone();
+                    reload = true;
+                }
+                Some(_) => {}
+                None => eprintln!("⚠️ 配置档案 {} 的检测模型未知: {}", profile.name, model),
+            }
+        }
+        if let Some(size) = profile.input_size.filter(|s| *s != self.input_size) {
+            self.input_size = size;
+            ControlMessage::SetInputSize(size).post();
+            reload = true;
+        }
+        if reload {
+            ControlMessage::SwitchModel(self.model_path()).post();
+        }
+
+        if profile.confidence_threshold.is_some() || profile.iou_threshold.is_some() {
+            self.confidence_threshold = profile
+                .confidence_threshold
+                .unwrap_or(self.confidence_threshold);
+            self.iou_threshold = profile.iou_threshold.unwrap_or(self.iou_threshold);
+            ControlMessage::UpdateParams {
+                conf_threshold: self.confidence_threshold,
+                iou_threshold: self.iou_threshold,
+            }
+            .post();
+        }
+        if let Some(classes) = &profile.classes {
+            self.classes = classes.clone();
+            ControlMessage::SetClasses(classes.clone()).post();
+        }
+
+        let mut params_changed = false;
+        if let Some(tracker) = &profile.tracker {
+            match TRACKER_INDICES.get(tracker.to_lowercase().as_str()) {
+                Some(&idx) if idx != self.selected_tracker_index => {
+                    self.selected_tracker_index = idx;
+                    self.tracker_name = TRACKERS[idx].to_string();
+                    self.track_stats = TrackStats::default();
+                    ControlMessage::SwitchTracker(self.tracker_name.clone()).post();
+                    params_changed = true;
+                }
+                Some(_) => {}
+                None => eprintln!("⚠️ 配置档案 {} 的跟踪算法未知: {}", profile.name, tracker),
+            }
+        }
+        if let Some(config) = &profile.tracker_config {
+            self.tracker_config = config.clone();
+            params_changed = true;
+        }
+        if params_changed {
+            if let Some(params) = self.tracker_params() {
+                ControlMessage::SetTrackerParams(params).post();
+            }
+            ControlMessage::SetSmoothing(self.tracker_config.display_smoothing.clone()).post();
+            ControlMessage::SetResolutionLadder(self.tracker_config.resolution_ladder.clone())
+                .post();
+        }
+
+        if let Some(path) = &profile.zones {
+            let config = ZoneConfig::load(path);
+            self.zones = Some(config.zones.clone());
+            xbus::post(config);
+        }
+        self.active_profile = Some(profile.name.clone());
+    }
+
+    /// 按时段自动切换档案: 进入新时段时切换一次, 时段内手动切换的档案保持到下一个时段
+    pub fn apply_scheduled_profile(&mut self, time: NaiveTime) {
+        let scheduled = self.profiles.scheduled(time).cloned();
+        let name = scheduled.as_ref().map(|p| p.name.clone());
+        if name == self.scheduled_profile {
+            return;
+        }
+        self.scheduled_profile = name;
+        if let Some(profile) = scheduled {
+            println!("⏰ 按时段切换配置档案: {}", profile.name);
+            self.apply_profile(&profile);
+        }
+    }
+
+    /// 应用热加载的配置: 当前档案被修改时重新切换到该档案, 跟踪参数直接下发
+    pub fn apply_config_updates(&mut self) {
+        let updates = std::mem::take(&mut *self.config_updates.lock().unwrap());
+        for update in updates {
+            match update {
+                ConfigUpdate::Profiles(config) => {
+                    let active = self
+                        .active_profile
+                        .as_deref()
+                        .and_then(|name| self.profiles.get(name))
+                        .cloned();
+                    self.profiles = config;
+                    if let Some(old) = active {
+                        match self.profiles.get(&old.name).cloned() {
+                            Some(profile) if profile != old => self.apply_profile(&profile),
+                            Some(_) => {}
+                            None => {
+                                println!("🗂️ 当前配置档案 {} 已被移除", old.name);
+                                self.active_profile = None;
+                            }
+                        }
+                    }
+                }
+                ConfigUpdate::Tracker(config) => {
+                    self.tracker_config = config;
+                    if let Some(params) = self.tracker_params() {
+                        ControlMessage::SetTrackerParams(params).post();
+                    }
+                    ControlMessage::SetSmoothing(self.tracker_config.display_smoothing.clone())
+                        .post();
+                    ControlMessage::SetResolutionLadder(
+                        self.tracker_config.resolution_ladder.clone(),
+                    )
+                    .post();
+                }
+                ConfigUpdate::Zones(config) => self.zones = Some(config.zones),
+                // 告警通知由通知模块自行重启
+                ConfigUpdate::Notifier(_) => {}
+            }
+        }
+    }
+
+    /// 应用 API 请求的模型切换, 与在面板中选择模型等效
+    pub fn apply_model_requests(&mut self) {
+        let requests = std::mem::take(&mut *self.model_requests.lock().unwrap());
+        for model in requests {
+            match MODEL_INDICES.get(model.as_str()) {
+                Some(&idx) if model != self.detect_model_name => {
+                    println!("🔐 API 切换检测模型: {}", model);
+                    self.selected_model_index = idx;
+                    self.detect_model_name = model;
+                    ControlMessage::SwitchModel(self.model_path()).post();
+                }
+                Some(_) => {}
+                None => eprintln!("⚠️ API 请求的检测模型未知: {}", model),
+            }
+        }
+    }
+
+    /// 当前面板参数组成的档案 (区域沿用当前档案的区域文件)
+    fn current_profile(&self, name: String) -> Profile {
+        Profile {
+            zones: self
+                .active_profile
+                .as_deref()
+                .and_then(|active| self.profiles.get(active))
+                .and_then(|p| p.zones.clone()),
+            name,
+            model: Some(self.detect_model_name.clone()),
+            input_size: Some(self.input_size),
+            confidence_threshold: Some(self.confidence_threshold),
+            iou_threshold: Some(self.iou_threshold),
+            classes: Some(self.classes.clone()),
+            tracker: TRACKERS
+                .get(self.selected_tracker_index)
+                .map(|t| t.to_string()),
+            tracker_config: Some(self.tracker_config.clone()),
+        }
+    }
+
+    /// 配置档案区块: 选中即切换, 可将当前参数另存为档案
+    fn profiles_ui(&mut self, ui: &mut egui::Ui) {
+        let mut selected = None;
+        egui::ComboBox::new("profile", tr("profile.profile"))
+            .selected_text(self.active_profile.as_deref().unwrap_or(tr("profile.none")))
+            .show_ui(ui, |ui| {
+                for profile in &self.profiles.profiles {
+                    let active = self.active_profile.as_ref() == Some(&profile.name);
+                    if ui.selectable_label(active, &profile.name).clicked() {
+                        selected = Some(profile.clone());
+                    }
+                }
+            });
+        if let Some(profile) = selected {
+            audit::record(AuditSource::Ui, AuditAction::ProfileSwitch, &profile.name);
+            self.apply_profile(&profile);
+        }
+        if let Some(name) = &self.scheduled_profile {
+            ui.label(tr_fmt("profile.scheduled", &[name]));
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_profile_name)
+                    .hint_text(tr("profile.name_hint"))
+                    .desired_width(120.0),
+            );
+            let name = self.new_profile_name.trim().to_string();
+            if ui.button(tr("profile.save")).clicked() && !name.is_empty() {
+                let profile = self.current_profile(name.clone());
+                self.profiles.upsert(profile);
+                self.profiles.save(&self.profiles_path);
+                self.active_profile = Some(name);
+                self.new_profile_name.clear();
+            }
+        });
+    }
+
+    /// 重新扫描工业相机, 当前选中的相机已消失时改选第一台
+    #[cfg(feature = "aravis")]
+    fn refresh_gige_cameras(&mut self) {
+        self.gige_cameras = crate::input::list_gige_cameras();
+        if !self.gige_cameras.contains(&self.gige_camera) {
+            self.gige_camera = self.gige_cameras.first().cloned().unwrap_or_default();
+        }
+    }
+
+    /// 工业相机区块: 相机选择与刷新, 返回需要启动的输入源
+    #[cfg(feature = "aravis")]
+    fn gige_ui(&mut self, ui: &mut egui::Ui) -> Option<InputSource> {
+        let mut selected = None;
+        ui.horizontal(|ui| {
+            if self.gige_cameras.is_empty() {
+                ui.label(tr("input.no_devices"));
+            } else {
+                egui::ComboBox::new("gige_camera", tr("input.select_device"))
+                    .selected_text(self.gige_camera.as_str())
+                    .show_ui(ui, |ui| {
+                        for id in &self.gige_cameras {
+                            if ui
+                                .selectable_value(&mut self.gige_camera, id.clone(), id)
+                                .clicked()
+                            {
+                                selected = Some(InputSource::GigE(id.clone()));
+                            }
+                        }
+                    });
+            }
+            if ui.button(tr("input.refresh_devices")).clicked() {
+                self.refresh_gige_cameras();
+            }
+        });
+        ui.label(tr_fmt(
+            "input.demosaic",
+            &[&format!("{:?}", crate::input::demosaic_method())],
+        ));
+        selected
+    }
+
+    /// 未启用 aravis 功能时只提示 (会话中保存的工业相机输入无法恢复)
+    #[cfg(not(feature = "aravis"))]
+    fn gige_ui(&mut self, ui: &mut egui::Ui) -> Option<InputSource> {
+        ui.label(tr("input.gige_disabled"));
+        None
+    }
+
+    /// 布防计划区块: 手动覆盖模式, 各逻辑流状态与最近的状态变化
+    /// 模型对比: 选择对比模型与显示方式, 显示两个模型的耗时与一致程度
+    fn compare_ui(&mut self, ui: &mut egui::Ui) {
+        if ui
+            .checkbox(&mut self.compare_enabled, tr("compare.enable"))
+            .changed()
+            && !self.compare_enabled
+        {
+            *self.comparison.lock().unwrap() = None;
+        }
+        egui::ComboBox::new("compare_model", tr("compare.model_b"))
+            .selected_text(
+                MODELS
+                    .get(self.compare_model_index)
+                    .copied()
+                    .unwrap_or("yolov8s"),
+            )
+            .show_ui(ui, |ui| {
+                for (idx, model) in MODELS.iter().enumerate() {
+                    ui.selectable_value(&mut self.compare_model_index, idx, *model);
+                }
+            });
+        ui.horizontal(|ui| {
+            ui.label(tr("compare.layout"));
+            egui::ComboBox::from_id_salt("compare_layout")
+                .selected_text(compare_layout_name(self.compare_layout))
+                .show_ui(ui, |ui| {
+                    for layout in CompareLayout::ALL {
+                        ui.selectable_value(
+                            &mut self.compare_layout,
+                            layout,
+                            compare_layout_name(layout),
+                        );
+                    }
+                });
+        });
+
+        let comparison = self.comparison.lock().unwrap();
+        let Some(result) = comparison.as_ref() else {
+            if self.compare_enabled {
+                ui.label(tr("compare.waiting"));
+            }
+            return;
+        };
+        egui::Grid::new("compare_grid")
+            .striped(true)
+            .show(ui, |ui| {
+                let colors = [egui::Color32::GREEN, egui::Color32::from_rgb(255, 165, 0)];
+                for (side, color) in result.sides.iter().zip(colors) {
+                    ui.colored_label(color, model_stem(&side.model));
+                    ui.label(tr_fmt(
+                        "compare.latency",
+                        &[
+                            &format!("{:.1}", side.inference_ms),
+                            &format!("{:.1}", side.mean_ms),
+                        ],
+                    ));
+                    ui.label(tr_fmt("compare.boxes", &[&side.bboxes.len()]));
+                    ui.end_row();
+                }
+            });
+        let agreement = result.agreement;
+        ui.label(tr_fmt(
+            "compare.agreement",
+            &[&agreement.matched, &agreement.only_a, &agreement.only_b],
+        ));
+    }
+
+    /// 审计视图: 最近的控制操作 (新记录在前), 可按来源/操作/内容筛选
+    fn audit_ui(&mut self, ui: &mut egui::Ui) {
+        ui.add(
+            egui::TextEdit::singleline(&mut self.audit_filter)
+                .hint_text(tr("audit.filter"))
+                .desired_width(ui.available_width()),
+        );
+        let filter = self.audit_filter.trim().to_lowercase();
+        let records: Vec<_> = audit::recent(audit::RECENT_CAPACITY)
+            .into_iter()
+            .filter(|r| {
+                filter.is_empty()
+                    || format!("{} {} {}", r.source, r.action.name(), r.detail)
+                        .to_lowercase()
+                        .contains(&filter)
+            })
+            .collect();
+        if records.is_empty() {
+            ui.label(tr("audit.empty"));
+            return;
+        }
+        egui::ScrollArea::vertical()
+            .id_salt("audit_records")
+            .max_height(200.0)
+            .show(ui, |ui| {
+                egui::Grid::new("audit_grid")
+                    .striped(true)
+                    .num_columns(3)
+                    .show(ui, |ui| {
+                        for record in &records {
+                            ui.monospace(clock::format_ui(record.time));
+                            ui.label(record.source.to_string());
+                            ui.label(format!("{} {}", record.action.name(), record.detail));
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    fn schedule_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(status) = schedule_status() else {
+            ui.label(tr("schedule.not_running"));
+            return;
+        };
+        let mut mode = status.mode;
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut mode, ArmMode::Auto, tr("schedule.auto"));
+            ui.selectable_value(&mut mode, ArmMode::Armed, tr("schedule.arm"));
+            ui.selectable_value(&mut mode, ArmMode::Disarmed, tr("schedule.disarm"));
+        });
+        if mode != status.mode {
+            audit::record(AuditSource::Ui, AuditAction::ArmMode, format!("{:?}", mode));
+            set_arm_mode(mode);
+        }
+        for view in &status.views {
+            let (key, color) = if view.armed {
+                ("schedule.view_armed", egui::Color32::GREEN)
+            } else {
+                ("schedule.view_disarmed", egui::Color32::GRAY)
+            };
+            ui.colored_label(color, tr_fmt(key, &[&view.view]));
+        }
+
+        ui.separator();
+        let events = self.arm_events.lock().unwrap();
+        if events.is_empty() {
+            ui.label(tr("common.no_events"));
+        }
+        for event in events.iter() {
+            let key = if event.armed {
+                "schedule.event_armed"
+            } else {
+                "schedule.event_disarmed"
+            };
+            let how = if event.mode == ArmMode::Auto {
+                tr("schedule.by_schedule")
+            } else {
+                tr("schedule.by_manual")
+            };
+            ui.label(tr_fmt(
+                key,
+                &[&clock::format_ui(event.time), &event.view, &how],
+            ));
+        }
+    }
+
+    fn set_style(&mut self, ctx: &egui::Context) {
+        // --- 自定义 UI 样式 (透明背景) ---
+        let mut visuals = egui::Visuals::dark();
+
+        // 窗口样式 - 透明背景
+        visuals.window_fill = egui::Color32::TRANSPARENT;
+        visuals.window_stroke = egui::Stroke::new(
+            1.0,
+            egui::Color32::from_rgba_premultiplied(255, 255, 255, 30),
+        );
+
+        // 面板和区域背景 - 透明
+        visuals.panel_fill = egui::Color32::TRANSPARENT;
+        visuals.extreme_bg_color = egui::Color32::TRANSPARENT;
+
+        // 非交互控件（标签、文本等）- 透明背景，无圆角
+        visuals.widgets.noninteractive.bg_fill = egui::Color32::TRANSPARENT;
+        visuals.widgets.noninteractive.weak_bg_fill = egui::Color32::TRANSPARENT;
+        visuals.widgets.noninteractive.bg_stroke = egui::Stroke::NONE;
+        visuals.widgets.noninteractive.fg_stroke =
+            egui::Stroke::new(1.0, egui::Color32::from_rgb(200, 210, 220));
+        visuals.widgets.noninteractive.corner_radius = 0.0.into(); // 无圆角
+
+        // 未激活控件（按钮、输入框等）- 透明背景，无圆角
+        visuals.widgets.inactive.bg_fill = egui::Color32::TRANSPARENT;
+        visuals.widgets.inactive.weak_bg_fill = egui::Color32::TRANSPARENT;
+        visuals.widgets.inactive.bg_stroke = egui::Stroke::new(
+            1.0,
+            egui::Color32::from_rgba_premultiplied(180, 190, 200, 80),
+        );
+        visuals.widgets.inactive.fg_stroke =
+            egui::Stroke::new(1.0, egui::Color32::from_rgb(180, 190, 200));
+        visuals.widgets.inactive.corner_radius = 0.0.into(); // 无圆角
+
+        // 悬停控件 - 透明背景+边框，无圆角
+        visuals.widgets.hovered.bg_fill = egui::Color32::TRANSPARENT;
+        visuals.widgets.hovered.weak_bg_fill = egui::Color32::TRANSPARENT;
+        visuals.widgets.hovered.bg_stroke = egui::Stroke::new(
+            1.5,
+            egui::Color32::from_rgba_premultiplied(180, 190, 200, 150),
+        );
+        visuals.widgets.hovered.fg_stroke = egui::Stroke::new(1.5, egui::Color32::WHITE);
+        visuals.widgets.hovered.corner_radius = 0.0.into(); // 无圆角
+
+        // 激活/点击控件 - 透明背景+加粗边框，无圆角
+        visuals.widgets.active.bg_fill = egui::Color32::TRANSPARENT;
+        visuals.widgets.active.weak_bg_fill = egui::Color32::TRANSPARENT;
+        visuals.widgets.active.bg_stroke = egui::Stroke::new(
+            2.0,
+            egui::Color32::from_rgba_premultiplied(200, 210, 220, 200),
+        );
+        visuals.widgets.active.fg_stroke = egui::Stroke::new(2.0, egui::Color32::WHITE);
+        visuals.widgets.active.corner_radius = 0.0.into(); // 无圆角
+
+        // 选中状态 - 半透明
+        visuals.selection.bg_fill = egui::Color32::from_rgba_premultiplied(100, 150, 255, 100);
+        visuals.selection.stroke = egui::Stroke::new(
+            1.5,
+            egui::Color32::from_rgba_premultiplied(150, 200, 255, 150),
+        );
+
+        // 文本颜色
+        visuals.override_text_color = Some(egui::Color32::from_rgb(230, 240, 250));
+
+        ctx.set_visuals(visuals);
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        if !*open {
+            return;
+        }
+        self.set_style(ctx);
+
+        // 根据背景图像尺寸确定窗口大小
+        let window_size = if let Some((width, height)) = self.panel_bg_size {
+            egui::vec2(width as f32, height as f32)
+        } else {
+            egui::vec2(350.0, 600.0) // 默认尺寸
+        };
+
+        egui::Window::new(tr("panel.title"))
+            .id(egui::Id::new("control_panel"))
+            .default_pos(egui::pos2(10.0, 10.0))
+            .default_size(window_size)
+            .resizable(true)
+            .frame(egui::Frame::NONE)
+            .title_bar(true)
+            .show(ctx, |ui| {
+                // 先绘制背景图像到最底层,完全填充窗口
+                if let Some(tex) = self.panel_bg_egui.as_ref() {
+                    let painter = ui.painter();
+                    let rect = ui.available_rect_before_wrap();
+                    let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+                    let tint = egui::Color32::from_rgba_premultiplied(255, 255, 255, 180);
+                    painter.image(tex.id(), rect, uv, tint);
+                }
+
+                // 使用ScrollArea包裹UI内容,允许窗口垂直调整大小
+                let actions = egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2]) // 不自动收缩,允许手动调整窗口大小
+                    .show(ui, |ui| self.ui(ui))
+                    .inner;
+
+                // 处理控制面板的操作
+                if actions.reset_zoom {
+                    self.zoom_scale = 1.0;
+                    self.pan_offset = Vec2::ZERO;
+                }
+
+                // 处理启动解码器的操作
+                if let Some(input_source) = actions.start_decoder {
+                    println!("🚀 从控制面板启动解码器: {}", input_source.key());
+                    audit::record(
+                        AuditSource::Ui,
+                        AuditAction::StreamSwitch,
+                        input_source.key(),
+                    );
+                    switch_decoder_source(input_source, DecoderPreference::preferred());
+                }
+            });
+    }
+    /// 绘制控制面板UI
+    fn ui(&mut self, ui: &mut egui::Ui) -> ControlPanelActions {
+        let mut actions = ControlPanelActions::default();
+
+        ui.style_mut().visuals.collapsing_header_frame = false;
+
+        // --- 界面语言 (立即生效, 随会话状态保存) ---
+        let mut language = i18n::language();
+        ui.horizontal(|ui| {
+            ui.label(tr("panel.language"));
+            egui::ComboBox::from_id_salt("ui_language")
+                .selected_text(language.name())
+                .show_ui(ui, |ui| {
+                    for lang in Language::ALL {
+                        ui.selectable_value(&mut language, lang, lang.name());
+                    }
+                });
+        });
+        if language != i18n::language() {
+            println!("🌐 界面语言: {}", language.code());
+            i18n::set_language(language);
+        }
+
+        // --- 状态监控 ---
+        // 标题随语言变化, 折叠状态按固定 id 保存
+        egui::CollapsingHeader::new(tr("status.header"))
+            .id_salt("status")
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(tr("status.render_fps"));
+                    ui.colored_label(egui::Color32::GREEN, format!("{:.1}", self.render_fps));
+                    ui.label(tr("status.decode_fps"));
+                    ui.colored_label(egui::Color32::CYAN, format!("{:.1}", self.decode_fps));
+                    ui.label(tr("status.detect_fps"));
+                    ui.colored_label(egui::Color32::YELLOW, format!("{:.1}", self.detect_fps));
+                });
+                if self.pacing != PacingMode::Latest {
+                    let stats = self.pacing_stats;
+                    ui.horizontal(|ui| {
+                        ui.label(tr("status.presented"));
+                        ui.colored_label(egui::Color32::GREEN, stats.presented.to_string());
+                        ui.label(tr("status.dropped"));
+                        ui.colored_label(egui::Color32::YELLOW, stats.dropped.to_string());
+                        ui.label(tr("status.late"));
+                        let color = if stats.late > 0 {
+                            egui::Color32::RED
+                        } else {
+                            egui::Color32::GRAY
+                        };
+                        ui.colored_label(color, stats.late.to_string());
+                        ui.label(tr_fmt(
+                            "status.pacing_delay",
+                            &[&format!("{:.0}", stats.delay_ms)],
+                        ));
+                    });
+                }
+                if self.tracker_params().is_some() {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("status.tracks_active"));
+                        ui.colored_label(egui::Color32::GREEN, self.track_stats.active.to_string());
+                        ui.label(tr("status.tracks_lost"));
+                        ui.colored_label(egui::Color32::YELLOW, self.track_stats.lost.to_string());
+                        ui.label(tr("status.tracks_removed"));
+                        ui.colored_label(egui::Color32::GRAY, self.track_stats.removed.to_string());
+                    });
+                }
+                if let Some(status) = self.jetson_status {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("status.power"));
+                        ui.colored_label(
+                            egui::Color32::CYAN,
+                            status
+                                .power_mw
+                                .map_or("-".to_string(), |p| format!("{:.1}W", p / 1000.0)),
+                        );
+                        ui.label(tr("status.temperature"));
+                        ui.colored_label(
+                            egui::Color32::YELLOW,
+                            status
+                                .temperature_c
+                                .map_or("-".to_string(), |t| format!("{:.1}°C", t)),
+                        );
+                        let color = match status.throttle {
+                            ThrottleLevel::Normal => egui::Color32::GREEN,
+                            ThrottleLevel::Warm => egui::Color32::YELLOW,
+                            ThrottleLevel::Throttled => egui::Color32::RED,
+                        };
+                        ui.label("|");
+                        ui.colored_label(color, throttle_name(status.throttle));
+                    });
+                }
+                if !self.latency_report.is_empty() {
+                    egui::CollapsingHeader::new(tr("latency.header"))
+                        .id_salt("latency")
+                        .default_open(false)
+                        .show(ui, |ui| {
+                            egui::Grid::new("latency_grid")
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    for (stage, s) in &self.latency_report {
+                                        let color = if *stage == LatencyStage::Total {
+                                            egui::Color32::YELLOW
+                                        } else {
+                                            egui::Color32::LIGHT_GRAY
+                                        };
+                                        ui.colored_label(color, stage_name(*stage));
+                                        ui.colored_label(color, format!("{:.1}", s.mean_ms));
+                                        ui.colored_label(color, format!("{:.1}", s.p95_ms));
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                }
+                egui::CollapsingHeader::new(tr("metrics.header"))
+                    .id_salt("metrics")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        let metrics = self.metrics.lock().unwrap();
+                        for metric in Metric::ALL {
+                            sparkline(ui, &metrics, metric);
+                        }
+                    });
+                let profile = profiler::status();
+                ui.horizontal(|ui| {
+                    if profile.running {
+                        ui.colored_label(
+                            egui::Color32::YELLOW,
+                            tr_fmt(
+                                "profile.running",
+                                &[&format!("{:.0}", profile.remaining_secs), &profile.frames],
+                            ),
+                        );
+                    } else if ui
+                        .button(tr_fmt("profile.start", &[&DEFAULT_PROFILE_SECS]))
+                        .on_hover_text(tr("profile.start_hint"))
+                        .clicked()
+                    {
+                        profiler::start(DEFAULT_PROFILE_SECS);
+                    }
+                });
+                if let Some(path) = profile.last_report.filter(|_| !profile.running) {
+                    ui.horizontal(|ui| {
+                        ui.label(tr_fmt("profile.last", &[&path]));
+                        if ui
+                            .small_button("📋")
+                            .on_hover_text(tr("profile.copy"))
+                            .clicked()
+                        {
+                            copy_to_clipboard(ui, &path);
+                        }
+                    });
+                }
+                ui.label(tr_fmt("status.model", &[&self.detect_model_name]));
+                if let Some(error) = self.detector_error.lock().unwrap().as_ref() {
+                    ui.colored_label(egui::Color32::RED, format!("⚠️ {}", error));
+                }
+                let generation = ACTIVE_DECODER_GENERATION.load(Ordering::Relaxed);
+                let drift = *self.clock_drift.lock().unwrap();
+                if let Some(drift) = drift
+                    .filter(|d| d.generation == generation && d.offset_ms.abs() > DRIFT_WARN_MS)
+                {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        tr_fmt(
+                            "status.clock_drift",
+                            &[
+                                &format!("{:+.0}", drift.offset_ms),
+                                &format!("{:+.0}", drift.ppm),
+                            ],
+                        ),
+                    );
+                }
+            });
+
+        ui.separator();
+
+        // --- 输入源配置 ---
+        egui::CollapsingHeader::new(tr("input.header"))
+            .id_salt("input")
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    // 切换到RTSP
+                    if ui
+                        .radio_value(&mut self.input_source_type, 0, tr("input.rtsp"))
+                        .changed()
+                    {
+                        // 立即启动RTSP解码
+                        if !self.rtsp_url.trim().is_empty() {
+                            actions.start_decoder = Some(InputSource::Rtsp(self.rtsp_url.clone()));
+                        }
+                    }
+
+                    // 切换到摄像头
+                    if ui
+                        .radio_value(&mut self.input_source_type, 1, tr("input.camera"))
+                        .changed()
+                    {
+                        if !self.devices_loaded {
+                            self.video_devices = get_video_devices();
+                            self.devices_loaded = true;
+                            if !self.video_devices.is_empty() {
+                                self.selected_device_index = 0;
+                            }
+                        }
+                        // 立即启动摄像头解码
+                        if !self.video_devices.is_empty() {
+                            if let Some(device) = self.video_devices.get(self.selected_device_index)
+                            {
+                                actions.start_decoder =
+                                    Some(InputSource::Camera(device.index, device.name.clone()));
+                            }
+                        }
+                    }
+
+                    // 切换到桌面捕获
+                    if ui
+                        .radio_value(&mut self.input_source_type, 2, tr("input.desktop"))
+                        .changed()
+                    {
+                        // 立即启动桌面捕获
+                        actions.start_decoder = Some(InputSource::Desktop);
+                    }
+
+                    // 切换到工业相机 (首次切换时扫描, 已选相机立即启动)
+                    #[cfg(feature = "aravis")]
+                    if ui
+                        .radio_value(&mut self.input_source_type, 3, tr("input.gige"))
+                        .changed()
+                    {
+                        if self.gige_cameras.is_empty() {
+                            self.refresh_gige_cameras();
+                        }
+                        if !self.gige_camera.is_empty() {
+                            actions.start_decoder =
+                                Some(InputSource::GigE(self.gige_camera.clone()));
+                        }
+                    }
+
+                    // 切换到共享内存 (映射文件尚不存在时解码线程会等待写端创建)
+                    if ui
+                        .radio_value(&mut self.input_source_type, 4, tr("input.shm"))
+                        .changed()
+                        && !self.shm_path.trim().is_empty()
+                    {
+                        actions.start_decoder =
+                            Some(InputSource::Shm(self.shm_path.trim().to_string()));
+                    }
+
+                    // 切换到合成场景
+                    if ui
+                        .radio_value(&mut self.input_source_type, 5, tr("input.synthetic"))
+                        .changed()
+                    {
+                        actions.start_decoder = Some(InputSource::Synthetic(
+                            self.synthetic_scene.trim().to_string(),
+                        ));
+                    }
+                });
+
+                if self.input_source_type == 0 {
+                    ui.label(tr("input.rtsp_url"));
+
+                    // 历史记录下拉框 - 选择后自动播放并复制到剪贴板
+                    let mut url_to_copy: Option<String> = None;
+
+                    let _combo_response = egui::ComboBox::from_id_salt("rtsp_history")
+                        .selected_text(tr("input.history"))
+                        .show_ui(ui, |ui| {
+                            // 下拉菜单打开时重新加载历史记录
+                            if let Some(lines) = load_rtsp_history() {
+                                self.rtsp_history = lines;
+                            }
+
+                            for url in &self.rtsp_history.clone() {
+                                let response =
+                                    ui.selectable_label(self.rtsp_url == *url, redact_url(url));
+
+                                // 左键点击: 填充到输入框并自动播放
+                                if response.clicked() {
+                                    self.rtsp_url = url.clone();
+                                    // 自动启动播放
+                                    actions.start_decoder =
+                                        Some(InputSource::Rtsp(self.rtsp_url.clone()));
+
+                                    // 移到历史记录最前面(更新访问时间)
+                                    if let Some(pos) =
+                                        self.rtsp_history.iter().position(|x| x == url)
+                                    {
+                                        if pos > 0 {
+                                            let moved_url = self.rtsp_history.remove(pos);
+                                            self.rtsp_history.insert(0, moved_url);
+                                            self.save_rtsp_history();
+                                        }
+                                    }
+                                }
+
+                                // 右键点击: 标记需要复制
+                                if response.secondary_clicked() {
+                                    url_to_copy = Some(url.clone());
+                                }
+
+                                // 悬停提示
+                                response.on_hover_text(tr("input.history_hint"));
+                            }
+                        });
+
+                    // 同时写入 egui 和系统剪贴板
+                    if let Some(url) = url_to_copy {
+                        copy_to_clipboard(ui, &url);
+                    }
+
+                    // RTSP 输入框 - 支持回车键快速启动
+                    let text_response = ui.add(
+                        egui::TextEdit::singleline(&mut self.rtsp_url)
+                            .desired_width(ui.available_width())
+                            .hint_text(tr("input.rtsp_hint")),
+                    );
+
+                    // 检测回车键 - 自动保存并启动播放
+                    if text_response.lost_focus()
+                        && ui.input(|i| i.key_pressed(egui::Key::Enter))
+                        && !self.rtsp_url.trim().is_empty()
+                    {
+                        // 带密码的地址迁入凭据库, 输入框与历史记录改为 $ID 引用
+                        let typed = self.rtsp_url.trim().to_string();
+                        let url = vault::migrate_url(&typed).unwrap_or_else(|e| {
+                            eprintln!("⚠️ RTSP 密码未能迁入凭据库, 历史记录中不保存密码: {:#}", e);
+                            typed.clone()
+                        });
+                        if url != typed {
+                            self.vault_entries = vault::entries();
+                        }
+
+                        // 保存到历史记录并写入文件
+                        self.add_rtsp_to_history(url.clone());
+
+                        // 更新输入框为修剪后的地址
+                        self.rtsp_url = url.clone();
+
+                        // 触发播放
+                        actions.start_decoder = Some(InputSource::Rtsp(url.clone()));
+                        println!("🚀 回车触发播放: {}", redact_url(&url));
+                    }
+
+                    // 低功耗: 只解码关键帧, 切换后立即重启当前流
+                    let mut keyframes = keyframes_only();
+                    if ui
+                        .checkbox(&mut keyframes, tr("input.keyframes"))
+                        .on_hover_text(tr("input.keyframes_hint"))
+                        .changed()
+                    {
+                        set_keyframes_only(keyframes);
+                        if !self.rtsp_url.trim().is_empty() {
+                            actions.start_decoder =
+                                Some(InputSource::Rtsp(self.rtsp_url.trim().to_string()));
+                        }
+                    }
+
+                    egui::CollapsingHeader::new(tr("vault.header"))
+                        .id_salt("rtsp_vault")
+                        .show(ui, |ui| self.show_vault(ui));
+                } else if self.input_source_type == 1 {
+                    if !self.devices_loaded {
+                        if ui.button(tr("input.refresh_devices")).clicked() {
+                            self.video_devices = get_video_devices();
+                            self.devices_loaded = true;
+                            if !self.video_devices.is_empty() {
+                                self.selected_device_index = 0;
+                            }
+                        }
+                    } else {
+                        if self.video_devices.is_empty() {
+                            ui.label(tr("input.no_devices"));
+                            if ui.button(tr("input.retry")).clicked() {
+                                self.video_devices = get_video_devices();
+                            }
+                        } else {
+                            egui::ComboBox::new("video_device", tr("input.select_device"))
+                                .selected_text(
+                                    self.video_devices
+                                        .get(self.selected_device_index)
+                                        .map(|d| d.name.as_str())
+                                        .unwrap_or(tr("common.unknown")),
+                                )
+                                .show_ui(ui, |ui| {
+                                    for (idx, device) in self.video_devices.iter().enumerate() {
+                                        if ui
+                                            .selectable_value(
+                                                &mut self.selected_device_index,
+                                                idx,
+                                                &device.name,
+                                            )
+                                            .clicked()
+                                        {
+                                            // 选择设备后立即启动解码
+                                            actions.start_decoder = Some(InputSource::Camera(
+                                                device.index,
+                                                device.name.clone(),
+                                            ));
+                                        }
+                                    }
+                                });
+                        }
+                    }
+                } else if self.input_source_type == 2 {
+                    ui.label(tr("input.desktop_capture"));
+                } else if self.input_source_type == 3 {
+                    if let Some(source) = self.gige_ui(ui) {
+                        actions.start_decoder = Some(source);
+                    }
+                } else if self.input_source_type == 4 {
+                    ui.label(tr("input.shm_path"));
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.shm_path)
+                            .desired_width(ui.available_width())
+                            .hint_text(DEFAULT_SHM_PATH),
+                    );
+                    if response.lost_focus()
+                        && ui.input(|i| i.key_pressed(egui::Key::Enter))
+                        && !self.shm_path.trim().is_empty()
+                    {
+                        actions.start_decoder =
+                            Some(InputSource::Shm(self.shm_path.trim().to_string()));
+                    }
+                } else {
+                    ui.label(tr("input.synthetic_scene"));
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.synthetic_scene)
+                            .desired_width(ui.available_width())
+                            .hint_text(tr("input.synthetic_builtin")),
+                    );
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        actions.start_decoder = Some(InputSource::Synthetic(
+                            self.synthetic_scene.trim().to_string(),
+                        ));
+                    }
+                }
+
+                // 画面方向 (吊装/侧装摄像头): 立即生效, 按当前输入源保存
+                if let Some(source_key) = active_source_key() {
+                    let mut orientation = active_orientation();
+                    let previous = orientation;
+                    ui.horizontal(|ui| {
+                        ui.label(tr("input.orientation"));
+                        egui::ComboBox::from_id_salt("orientation_rotation")
+                            .selected_text(orientation.rotation.name())
+                            .show_ui(ui, |ui| {
+                                for rotation in Rotation::ALL {
+                                    ui.selectable_value(
+                                        &mut orientation.rotation,
+                                        rotation,
+                                        rotation.name(),
+                                    );
+                                }
+                            });
+                        ui.checkbox(&mut orientation.mirror, tr("input.mirror"));
+                    });
+                    if orientation != previous {
+                        set_active_orientation(orientation);
+                        let mut config = OrientationConfig::load(ORIENTATION_CONFIG_PATH);
+                        config.set(source_key, orientation);
+                        config.save(ORIENTATION_CONFIG_PATH);
+                        println!(
+                            "🔃 画面方向: 旋转{} 镜像{}",
+                            orientation.rotation.name(),
+                            orientation.mirror
+                        );
+                    }
+                }
+
+                // 鱼眼虚拟视图: 选择渲染哪一路 (每个视图都有独立的检测线程)
+                if let Some(fisheye) = fisheye_config() {
+                    let view_name = |view: u32| match view {
+                        0 => tr("input.panorama").to_string(),
+                        v => fisheye
+                            .views
+                            .get(v as usize - 1)
+                            .map_or_else(|| tr_fmt("input.view", &[&v]), |view| view.name.clone()),
+                    };
+                    let mut view = self.display_view.load(Ordering::Relaxed);
+                    ui.horizontal(|ui| {
+                        ui.label(tr("input.display_view"));
+                        egui::ComboBox::from_id_salt("fisheye_view")
+                            .selected_text(view_name(view))
+                            .show_ui(ui, |ui| {
+                                for v in 0..=fisheye.views.len() as u32 {
+                                    ui.selectable_value(&mut view, v, view_name(v));
+                                }
+                            });
+                    });
+                    self.display_view.store(view, Ordering::Relaxed);
+                }
+            });
+
+        ui.separator();
+
+        // --- 配置档案 ---
+        egui::CollapsingHeader::new(tr("profile.header"))
+            .id_salt("profiles")
+            .default_open(false)
+            .show(ui, |ui| self.profiles_ui(ui));
+
+        // --- 布防计划 ---
+        egui::CollapsingHeader::new(tr("schedule.header"))
+            .id_salt("schedule")
+            .default_open(false)
+            .show(ui, |ui| self.schedule_ui(ui));
+
+        // --- 审计日志 ---
+        egui::CollapsingHeader::new(tr("audit.header"))
+            .id_salt("audit")
+            .default_open(false)
+            .show(ui, |ui| self.audit_ui(ui));
+
+        // --- 模型与参数 ---
+        egui::CollapsingHeader::new(tr("model.header"))
+            .id_salt("model")
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.label(tr("model.detect_model"));
+                let mut selected_model = self.selected_model_index;
+                egui::ComboBox::new("detect_model", tr("model.model"))
+                    .selected_text(
+                        MODELS
+                            .get(self.selected_model_index)
+                            .copied()
+                            .unwrap_or("yolov8n"),
+                    )
+                    .show_ui(ui, |ui| {
+                        for (idx, model) in MODELS.iter().enumerate() {
+                            ui.selectable_value(&mut selected_model, idx, *model);
+                        }
+                    });
+
+                if selected_model != self.selected_model_index {
+                    self.selected_model_index = selected_model;
+                    let model_name = MODELS[selected_model];
+                    self.detect_model_name = model_name.to_string();
+                    let model_path = self.resolve_model_path(model_name);
+                    audit::record(AuditSource::Ui, AuditAction::ModelSwitch, model_name);
+                    ControlMessage::SwitchModel(model_path).post();
+                }
+
+                ui.label(tr("model.tracker_label"));
+                let mut selected_tracker = self.selected_tracker_index;
+                egui::ComboBox::new("tracker", tr("model.tracker"))
+                    .selected_text(
+                        TRACKERS
+                            .get(self.selected_tracker_index)
+                            .copied()
+                            .map_or(tr("tracker.none"), tracker_label),
+                    )
+                    .show_ui(ui, |ui| {
+                        for (idx, tracker) in TRACKERS.iter().enumerate() {
+                            ui.selectable_value(
+                                &mut selected_tracker,
+                                idx,
+                                tracker_label(*tracker),
+                            );
+                        }
+                    });
+
+                if selected_tracker != self.selected_tracker_index {
+                    self.selected_tracker_index = selected_tracker;
+                    let tracker_name = TRACKERS[selected_tracker];
+                    self.tracker_name = tracker_name.to_string();
+                    self.track_stats = TrackStats::default();
+                    audit::record(AuditSource::Ui, AuditAction::TrackerSwitch, tracker_name);
+                    ControlMessage::SwitchTracker(tracker_name.to_string()).post();
+                    // 新跟踪器使用配置文件中的参数
+                    if let Some(params) = self.tracker_params() {
+                        ControlMessage::SetTrackerParams(params).post();
+                    }
+                }
+
+                // 轨迹生命周期参数 (实时生效, 可保存到配置文件)
+                if let Some(mut params) = self.tracker_params() {
+                    let is_bytetrack = TRACKERS[self.selected_tracker_index] == "ByteTrack";
+                    egui::CollapsingHeader::new(tr("tracker.lifecycle"))
+                        .id_salt("tracker_lifecycle")
+                        .default_open(false)
+                        .show(ui, |ui| {
+                            if Self::tracker_params_ui(ui, &mut params, is_bytetrack) {
+                                if is_bytetrack {
+                                    self.tracker_config.set_bytetrack_params(params);
+                                } else {
+                                    self.tracker_config.set_deepsort_params(params);
+                                }
+                                ControlMessage::SetTrackerParams(params).post();
+                            }
+                            if ui.button(tr("tracker.save")).clicked() {
+                                self.tracker_config.save(TRACKER_CONFIG_PATH);
+                            }
+                        });
+                }
+
+                // ByteTrack 关联线索: 重叠人群中用掩码/外观区分身份
+                if TRACKERS[self.selected_tracker_index] == "ByteTrack" {
+                    let mut weights_changed = false;
+                    weights_changed |= ui
+                        .add(
+                            egui::Slider::new(&mut self.association.mask_iou, 0.0..=2.0)
+                                .text(tr("tracker.mask_iou_weight")),
+                        )
+                        .on_hover_text(tr("tracker.mask_iou_hint"))
+                        .changed();
+                    weights_changed |= ui
+                        .add(
+                            egui::Slider::new(&mut self.association.reid, 0.0..=2.0)
+                                .text(tr("tracker.reid_weight")),
+                        )
+                        .on_hover_text(tr("tracker.reid_hint"))
+                        .changed();
+                    if weights_changed {
+                        ControlMessage::SetAssociation(self.association).post();
+                    }
+                }
+
+                if ui
+                    .checkbox(&mut self.pose_enabled, tr("model.pose"))
+                    .changed()
+                {
+                    ControlMessage::TogglePose(self.pose_enabled).post();
+                }
+
+                if ui
+                    .checkbox(&mut self.detection_enabled, tr("model.detection"))
+                    .changed()
+                {
+                    ControlMessage::ToggleDetection(self.detection_enabled).post();
+                }
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .checkbox(&mut self.crowd_enabled, tr("model.crowd"))
+                        .on_hover_text(tr("model.crowd_hint"))
+                        .changed()
+                    {
+                        ControlMessage::ToggleCrowd(self.crowd_enabled).post();
+                    }
+                    if let Some(count) = self.crowd_count {
+                        ui.colored_label(
+                            egui::Color32::YELLOW,
+                            tr_fmt("model.crowd_count", &[&format!("{:.0}", count)]),
+                        );
+                    }
+                });
+
+                ui.separator();
+                ui.label(tr("model.clip"));
+                ui.horizontal(|ui| {
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.text_prompt)
+                            .hint_text(tr("model.clip_hint"))
+                            .desired_width(140.0),
+                    );
+                    let submitted =
+                        response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if submitted || ui.button(tr("common.apply")).clicked() {
+                        ControlMessage::SetTextPrompt(self.text_prompt.clone()).post();
+                    }
+                    if ui.button(tr("common.clear")).clicked() {
+                        self.text_prompt.clear();
+                        ControlMessage::SetTextPrompt(String::new()).post();
+                    }
+                });
+
+                ui.label(tr("model.vocabulary"));
+                ui.horizontal(|ui| {
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.vocabulary)
+                            .hint_text(tr("model.vocabulary_hint"))
+                            .desired_width(140.0),
+                    );
+                    let submitted =
+                        response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if submitted || ui.button(tr("common.apply")).clicked() {
+                        ControlMessage::SetVocabulary(world::parse_classes(&self.vocabulary))
+                            .post();
+                    }
+                    if ui.button(tr("common.clear")).clicked() {
+                        self.vocabulary.clear();
+                        ControlMessage::SetVocabulary(Vec::new()).post();
+                    }
+                });
+
+                ui.separator();
+                ui.label(tr("model.thresholds"));
+                let sliders = [
+                    ui.add(
+                        egui::Slider::new(&mut self.confidence_threshold, 0.0..=1.0)
+                            .text(tr("model.confidence")),
+                    ),
+                    ui.add(
+                        egui::Slider::new(&mut self.iou_threshold, 0.0..=1.0).text(tr("model.iou")),
+                    ),
+                ];
+
+                if sliders.iter().any(|r| r.changed()) {
+                    ControlMessage::UpdateParams {
+                        conf_threshold: self.confidence_threshold,
+                        iou_threshold: self.iou_threshold,
+                    }
+                    .post();
+                }
+                // 拖动结束 (或点击/键盘修改) 时记录一次审计, 拖动过程中不逐帧记录
+                if sliders
+                    .iter()
+                    .any(|r| r.drag_stopped() || (r.changed() && !r.dragged()))
+                {
+                    audit::record(
+                        AuditSource::Ui,
+                        AuditAction::ThresholdChange,
+                        format!(
+                            "confidence={:.2} iou={:.2}",
+                            self.confidence_threshold, self.iou_threshold
+                        ),
+                    );
+                }
+
+                // 阈值预览: 拖动置信度滑块时可看到降低阈值会多出哪些框
+                ui.horizontal(|ui| {
+                    let toggled = ui
+                        .checkbox(&mut self.ghost_preview, tr("model.ghost_preview"))
+                        .changed();
+                    let floor = ui.add_enabled(
+                        self.ghost_preview,
+                        egui::Slider::new(&mut self.ghost_floor, 0.01..=1.0)
+                            .text(tr("model.ghost_floor")),
+                    );
+                    if toggled || floor.changed() {
+                        ControlMessage::SetGhostFloor(self.ghost_floor()).post();
+                    }
+                });
+
+                // 分辨率阶梯: 按目标大小自动升降推理输入尺寸 (仅动态轴模型)
+                ui.horizontal(|ui| {
+                    let ladder = &mut self.tracker_config.resolution_ladder;
+                    if ui
+                        .checkbox(&mut ladder.enabled, tr("model.auto_resolution"))
+                        .changed()
+                    {
+                        ControlMessage::SetResolutionLadder(ladder.clone()).post();
+                    }
+                    if self.inference_size > 0 {
+                        ui.label(format!(
+                            "{} {}",
+                            tr("model.inference_size"),
+                            self.inference_size
+                        ));
+                    }
+                });
+
+                // 缩放算法: 最近邻最快, 插值算法让远处小目标更清晰
+                ui.horizontal(|ui| {
+                    ui.label(tr("model.resize"));
+                    let previous = self.resize_quality;
+                    egui::ComboBox::from_id_salt("resize_quality")
+                        .selected_text(self.resize_quality.label())
+                        .show_ui(ui, |ui| {
+                            for quality in ResizeQuality::ALL {
+                                ui.selectable_value(
+                                    &mut self.resize_quality,
+                                    quality,
+                                    quality.label(),
+                                );
+                            }
+                        });
+                    if self.resize_quality != previous {
+                        ControlMessage::SetResizeQuality(self.resize_quality).post();
+                    }
+                });
+            });
+
+        ui.separator();
+
+        // --- 模型对比 ---
+        egui::CollapsingHeader::new(tr("compare.header"))
+            .id_salt("compare")
+            .default_open(false)
+            .show(ui, |ui| self.compare_ui(ui));
+
+        // --- 地面标定 ---
+        egui::CollapsingHeader::new(tr("calib.header"))
+            .id_salt("calibration")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.checkbox(&mut self.calibration_mode, tr("calib.mode"));
+                let mut remove = None;
+                for (i, point) in self.ground_calibration.points.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!(
+                            "#{} ({:.0}, {:.0}) →",
+                            i + 1,
+                            point.image[0],
+                            point.image[1]
+                        ));
+                        ui.add(
+                            egui::DragValue::new(&mut point.world[0])
+                                .speed(0.05)
+                                .prefix("X ")
+                                .suffix(" m"),
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut point.world[1])
+                                .speed(0.05)
+                                .prefix("Y ")
+                                .suffix(" m"),
+                        );
+                        if ui.small_button("🗑").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                }
+                if let Some(i) = remove {
+                    self.ground_calibration.points.remove(i);
+                }
+
+                ui.horizontal(|ui| {
+                    let enough = self.ground_calibration.points.len() >= 4;
+                    if ui
+                        .add_enabled(enough, egui::Button::new(tr("calib.solve")))
+                        .on_disabled_hover_text(tr("calib.need_points"))
+                        .clicked()
+                    {
+                        match self.ground_calibration.homography() {
+                            Some(h) => {
+                                let error = h.rms_error(&self.ground_calibration.points);
+                                println!("📐 地面标定完成, 重投影误差 {:.3}m", error);
+                                self.calibration_error = Some(error);
+                                // 当前画面中的基准标记作为锚定参考
+                                self.ground_calibration.markers = self
+                                    .marker_detections
+                                    .lock()
+                                    .unwrap()
+                                    .as_ref()
+                                    .map(|d| d.markers.clone())
+                                    .unwrap_or_default();
+                                self.ground_calibration.save(GROUND_CALIBRATION_PATH);
+                                ControlMessage::SetGroundHomography(Some(h)).post();
+                                xbus::post(self.ground_calibration.clone());
+                            }
+                            None => {
+                                eprintln!("⚠️ 地面标定求解失败: 标定点退化 (共线或重合)");
+                                self.calibration_error = None;
+                            }
+                        }
+                    }
+                    if ui.button(tr("common.clear")).clicked() {
+                        self.ground_calibration.points.clear();
+                        self.ground_calibration.markers.clear();
+                        self.calibration_error = None;
+                        self.ground_calibration.save(GROUND_CALIBRATION_PATH);
+                        ControlMessage::SetGroundHomography(None).post();
+                        xbus::post(self.ground_calibration.clone());
+                    }
+                });
+                if let Some(error) = self.calibration_error {
+                    let color = if error < 0.2 {
+                        egui::Color32::GREEN
+                    } else {
+                        egui::Color32::YELLOW
+                    };
+                    ui.colored_label(color, tr_fmt("calib.error", &[&format!("{:.3}", error)]));
+                }
+                if let Some(detections) = self.marker_detections.lock().unwrap().as_ref() {
+                    let count = detections.markers.len().to_string();
+                    let reference = self.ground_calibration.markers.len().to_string();
+                    ui.label(tr_fmt("calib.markers", &[&count, &reference]));
+                    if let Some(shift) = detections.shift_px {
+                        ui.label(tr_fmt("calib.marker_shift", &[&format!("{:.1}", shift)]));
+                    }
+                }
+            });
+
+        // --- 标注 ---
+        egui::CollapsingHeader::new(tr("annotate.header"))
+            .id_salt("annotate")
+            .default_open(false)
+            .show(ui, |ui| self.annotator.ui(ui));
+
+        // --- 区域事件 ---
+        egui::CollapsingHeader::new(tr("zones.header"))
+            .id_salt("zone_events")
+            .default_open(false)
+            .show(ui, |ui| {
+                let events = self.zone_events.lock().unwrap();
+                if events.is_empty() {
+                    ui.label(tr("common.no_events"));
+                }
+                for event in events.iter() {
+                    let mut text = tr_fmt(
+                        "zones.event",
+                        &[
+                            &clock::format(event.time, "%H:%M:%S"),
+                            &event.zone,
+                            &event.rule,
+                            &format!("{:?}", event.track_ids),
+                            &format!("{:.2}", event.value),
+                        ],
+                    );
+                    if let Some(verification) = &event.verification {
+                        let key = if verification.confirmed {
+                            "zones.verified"
+                        } else {
+                            "zones.rejected"
+                        };
+                        text.push(' ');
+                        text.push_str(&tr_fmt(key, &[&format!("{:.2}", verification.confidence)]));
+                    }
+                    ui.label(text);
+                }
+            });
+
+        // --- 遗留/移除事件 ---
+        egui::CollapsingHeader::new(tr("left_behind.header"))
+            .id_salt("left_behind")
+            .default_open(false)
+            .show(ui, |ui| {
+                let events = self.left_behind_events.lock().unwrap();
+                if events.is_empty() {
+                    ui.label(tr("common.no_events"));
+                }
+                for event in events.iter() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!(
+                            "{} {} {}",
+                            clock::format(event.time, "%H:%M:%S"),
+                            scene_change_name(event.kind),
+                            event.name
+                        ));
+                        if let Some(path) = &event.snapshot {
+                            if ui
+                                .small_button("📋")
+                                .on_hover_text(tr("left_behind.copy_snapshot"))
+                                .clicked()
+                            {
+                                copy_to_clipboard(ui, &path.to_string_lossy());
+                            }
+                        }
+                    });
+                }
+            });
+
+        // --- 摄像头篡改事件 ---
+        egui::CollapsingHeader::new(tr("tamper.header"))
+            .id_salt("tamper")
+            .default_open(false)
+            .show(ui, |ui| {
+                let events = self.tamper_events.lock().unwrap();
+                if events.is_empty() {
+                    ui.label(tr("common.no_events"));
+                }
+                for event in events.iter() {
+                    let text = format!(
+                        "{} [{}] {}{}",
+                        clock::format(event.time, "%H:%M:%S"),
+                        event.view,
+                        tamper_kind_name(event.kind),
+                        if event.active {
+                            String::new()
+                        } else {
+                            format!(" {}", tr("tamper.restored"))
+                        }
+                    );
+                    if event.active {
+                        ui.colored_label(egui::Color32::RED, text);
+                    } else {
+                        ui.label(text);
+                    }
+                }
+            });
+
+        ui.separator();
+
+        // --- 视图控制 ---
+        egui::CollapsingHeader::new(tr("view.header"))
+            .id_salt("view")
+            .default_open(true)
+            .show(ui, |ui| {
+                if ui.button(tr("view.reset_zoom")).clicked() {
+                    actions.reset_zoom = true;
+                }
+                ui.horizontal(|ui| {
+                    ui.label(tr("view.palette"));
+                    egui::ComboBox::from_id_salt("palette")
+                        .selected_text(palette_name(self.palette))
+                        .show_ui(ui, |ui| {
+                            for palette in Palette::ALL {
+                                ui.selectable_value(
+                                    &mut self.palette,
+                                    palette,
+                                    palette_name(palette),
+                                );
+                            }
+                        });
+                });
+                ui.horizontal(|ui| {
+                    ui.label(tr("view.pacing"));
+                    egui::ComboBox::from_id_salt("pacing")
+                        .selected_text(pacing_name(self.pacing))
+                        .show_ui(ui, |ui| {
+                            for mode in PacingMode::ALL {
+                                ui.selectable_value(&mut self.pacing, mode, pacing_name(mode));
+                            }
+                        });
+                });
+            });
+
+        actions
+    }
+}
+
+/// 跟踪器的显示名称 ("无" 按当前语言显示)
+fn tracker_label(name: &'static str) -> &'static str {
+    match name {
+        "无" => tr("tracker.none"),
+        name => name,
+    }
+}
+
+/// 调色板的显示名称
+fn palette_name(palette: Palette) -> &'static str {
+    match palette {
+        Palette::None => tr("palette.none"),
+        Palette::Ironbow => tr("palette.ironbow"),
+    }
+}
+
+/// 对比显示方式的显示名称
+fn compare_layout_name(layout: CompareLayout) -> &'static str {
+    match layout {
+        CompareLayout::Overlay => tr("compare.overlay"),
+        CompareLayout::SideBySide => tr("compare.side_by_side"),
+        CompareLayout::PictureInPicture => tr("compare.pip"),
+    }
+}
+
+/// 模型文件名 (不含目录与扩展名)
+pub(crate) fn model_stem(path: &str) -> &str {
+    std::path::Path::new(path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(path)
+}
+
+/// 帧呈现模式的显示名称
+fn pacing_name(mode: PacingMode) -> &'static str {
+    match mode {
+        PacingMode::Latest => tr("pacing.latest"),
+        PacingMode::Paced => tr("pacing.paced"),
+        PacingMode::Smooth => tr("pacing.smooth"),
+    }
+}
+
+/// 延迟阶段的显示名称
+fn stage_name(stage: LatencyStage) -> &'static str {
+    match stage {
+        LatencyStage::Queue => tr("latency.queue"),
+        LatencyStage::Resize => tr("latency.resize"),
+        LatencyStage::Inference => tr("latency.inference"),
+        LatencyStage::Postprocess => tr("latency.postprocess"),
+        LatencyStage::Render => tr("latency.render"),
+        LatencyStage::Total => tr("latency.total"),
+    }
+}
+
+/// 指标的显示名称与曲线颜色
+fn metric_style(metric: Metric) -> (&'static str, egui::Color32) {
+    match metric {
+        Metric::DecodeFps => (tr("metrics.decode_fps"), egui::Color32::CYAN),
+        Metric::DetectFps => (tr("metrics.detect_fps"), egui::Color32::YELLOW),
+        Metric::RenderFps => (tr("metrics.render_fps"), egui::Color32::GREEN),
+        Metric::InferenceMs => (tr("metrics.inference_ms"), egui::Color32::LIGHT_RED),
+        Metric::MeanConfidence => (tr("metrics.confidence"), egui::Color32::LIGHT_BLUE),
+    }
+}
+
+/// 画一条迷你曲线: 横轴为最近 60 秒, 纵轴从 0 到区间最大值, 左上角标注最新值与最大值
+fn sparkline(ui: &mut egui::Ui, metrics: &MetricsRing, metric: Metric) {
+    let (label, color) = metric_style(metric);
+    let (rect, _) =
+        ui.allocate_exact_size(egui::vec2(ui.available_width(), 28.0), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, egui::Color32::from_black_alpha(80));
+
+    let series = metrics.series(metric);
+    let max = metrics.range(metric).map_or(0.0, |(_, max)| max);
+    if series.len() >= 2 {
+        let top = if max > 0.0 { max } else { 1.0 };
+        let step = rect.width() / (HISTORY_SECS - 1) as f32;
+        // 右对齐: 最新采样在最右侧
+        let x0 = rect.right() - step * (series.len() - 1) as f32;
+        let points = series
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let y = rect.bottom() - 2.0 - (v / top) * (rect.height() - 4.0);
+                egui::pos2(x0 + step * i as f32, y)
+            })
+            .collect();
+        painter.add(egui::Shape::line(points, egui::Stroke::new(1.5, color)));
+    }
+
+    let latest = metrics.latest(metric).unwrap_or(0.0);
+    painter.text(
+        rect.left_top() + egui::vec2(4.0, 2.0),
+        egui::Align2::LEFT_TOP,
+        format!("{} {:.1} (max {:.1})", label, latest, max),
+        egui::FontId::proportional(11.0),
+        egui::Color32::from_rgb(230, 240, 250),
+    );
+}
+
+/// 降频等级的显示名称
+fn throttle_name(level: ThrottleLevel) -> &'static str {
+    match level {
+        ThrottleLevel::Normal => tr("throttle.normal"),
+        ThrottleLevel::Warm => tr("throttle.warm"),
+        ThrottleLevel::Throttled => tr("throttle.throttled"),
+    }
+}
+
+/// 遗留/移除事件类型的显示名称
+fn scene_change_name(kind: SceneChange) -> &'static str {
+    match kind {
+        SceneChange::LeftBehind => tr("left_behind.left_behind"),
+        SceneChange::Removed => tr("left_behind.removed"),
+    }
+}
+
+/// 摄像头篡改类型的显示名称
+pub(crate) fn tamper_kind_name(kind: TamperKind) -> &'static str {
+    match kind {
+        TamperKind::Covered => tr("tamper.covered"),
+        TamperKind::Defocused => tr("tamper.defocused"),
+        TamperKind::Moved => tr("tamper.moved"),
+    }
+}
+
+/// 控制面板操作返回值
+#[derive(Default)]
+pub struct ControlPanelActions {
+    pub reset_zoom: bool,
+    pub start_decoder: Option<InputSource>,
+}
@@ -0,0 +1,232 @@
+//! 帧节奏控制 (Frame Pacing)
+//!
+//! 渲染循环随显示器刷新 (V-Sync) 运行, 解码帧率与刷新率不一致时"有新帧就画"会造成抖动:
+//! 有的帧停留两个刷新周期, 有的只停留一个, 有的被跳过. 这里按帧的采集时间戳 (PTS)
+//! 加播放延迟安排呈现时刻, 每次刷新呈现最近到期的一帧:
+//! - 播放延迟 = 到达延迟 + 一个帧间隔的缓冲; 帧晚于计划到达时增大, 平稳时逐帧回落
+//! - 同一刷新周期内到期的多帧只呈现最新的一帧, 其余计为丢弃
+//! - 呈现时刻晚于计划超过一个刷新周期计为延迟呈现
+//! - 平滑模式在上一帧与当前帧之间按时间混合, 代价是约一帧的额外延迟
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// 等待呈现的最大帧数, 超出时丢弃最旧的帧
+const MAX_QUEUED: usize = 4;
+
+/// 源帧间隔的平滑系数
+const INTERVAL_ALPHA: f64 = 0.1;
+
+/// 播放延迟每帧回落的幅度 (相对帧间隔)
+const DELAY_DECAY: f64 = 0.02;
+
+/// 呈现模式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PacingMode {
+    Latest, // 每次刷新直接显示最新帧 (延迟最低)
+    #[default]
+    Paced, // 按 PTS 安排呈现
+    Smooth, // 按 PTS 安排呈现, 相邻帧之间混合过渡
+}
+
+impl PacingMode {
+    pub const ALL: [PacingMode; 3] = [PacingMode::Latest, PacingMode::Paced, PacingMode::Smooth];
+}
+
+/// 呈现统计 (渲染线程每秒取出一次)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PacingStats {
+    pub presented: u32, // 呈现的新帧数
+    pub dropped: u32,   // 未呈现即被更新帧取代的帧数
+    pub late: u32,      // 晚于计划超过一个刷新周期呈现的帧数
+    pub delay_ms: f64,  // 当前播放延迟
+}
+
+/// 按 PTS 安排帧的呈现时刻
+pub struct FramePacer<T> {
+    mode: PacingMode,
+    queue: VecDeque<(Instant, T)>,
+    delay: Option<Duration>,    // 播放延迟 (PTS → 计划呈现时刻)
+    interval: Option<f64>,      // 源帧间隔 (秒, 指数平滑)
+    last_pts: Option<Instant>,  // 最近收到的帧的 PTS
+    shown_due: Option<Instant>, // 当前显示帧的计划呈现时刻
+    stats: PacingStats,
+}
+
+impl<T> FramePacer<T> {
+    pub fn new(mode: PacingMode) -> Self {
+        Self {
+            mode,
+            queue: VecDeque::with_capacity(MAX_QUEUED + 1),
+            delay: None,
+            interval: None,
+            last_pts: None,
+            shown_due: None,
+            stats: PacingStats::default(),
+        }
+    }
+
+    pub fn mode(&self) -> PacingMode {
+        self.mode
+    }
+
+    /// 切换模式, 播放延迟重新估计
+    pub fn set_mode(&mut self, mode: PacingMode) {
+        if mode != self.mode {
+            self.mode = mode;
+            self.delay = None;
+        }
+    }
+
+    /// 收到解码帧 (`pts` 为采集时间戳, 与 `now` 同一时钟)
+    pub fn push(&mut self, pts: Instant, frame: T, now: Instant) {
+        if let Some(last) = self.last_pts.filter(|&last| pts > last) {
+            let dt = (pts - last).as_secs_f64();
+            let interval = self.interval.map_or(dt, |i| i + INTERVAL_ALPHA * (dt - i));
+            self.interval = Some(interval);
+        }
+        self.last_pts = Some(pts);
+
+        let arrival = now.saturating_duration_since(pts);
+        let interval = Duration::from_secs_f64(self.interval.unwrap_or(0.0));
+        self.delay = Some(match self.delay {
+            None => arrival + interval,
+            // 晚于计划到达: 抖动变大, 留出半帧余量
+            Some(delay) if arrival > delay => arrival + interval / 2,
+            Some(delay) => delay
+                .saturating_sub(interval.mul_f64(DELAY_DECAY))
+                .max(arrival),
+        });
+
+        self.queue.push_back((pts, frame));
+        while self.queue.len() > MAX_QUEUED {
+            self.queue.pop_front();
+            self.stats.dropped += 1;
+        }
+    }
+
+    /// 每次刷新调用, 返回本次需要呈现的新帧 (没有帧到期时为 None)
+    ///
+    /// `refresh` 为显示器刷新周期, 半个周期内到期的帧在本次刷新呈现
+    pub fn poll(&mut self, now: Instant, refresh: Duration) -> Option<T> {
+        if self.mode == PacingMode::Latest {
+            let (_, frame) = self.queue.pop_back()?;
+            self.stats.dropped += self.queue.len() as u32;
+            self.stats.presented += 1;
+            self.queue.clear();
+            self.shown_due = Some(now);
+            return Some(frame);
+        }
+
+        let delay = self.delay?;
+        let horizon = now + refresh / 2;
+        let mut chosen = None;
+        while let Some((pts, _)) = self.queue.front() {
+            if *pts + delay > horizon {
+                break;
+            }
+            if chosen.is_some() {
+                self.stats.dropped += 1;
+            }
+            chosen = self.queue.pop_front();
+        }
+
+        let (pts, frame) = chosen?;
+        let due = pts + delay;
+        if now > due + refresh {
+            self.stats.late += 1;
+        }
+        self.stats.presented += 1;
+        self.shown_due = Some(due);
+        Some(frame)
+    }
+
+    /// 当前帧的混合权重 (0 = 上一帧, 1 = 当前帧), 仅平滑模式下小于 1
+    pub fn blend(&self, now: Instant) -> f32 {
+        if self.mode != PacingMode::Smooth {
+            return 1.0;
+        }
+        match (self.shown_due, self.interval) {
+            (Some(due), Some(interval)) if interval > 0.0 => {
+                let t = now.saturating_duration_since(due).as_secs_f64() / interval;
+                t.clamp(0.0, 1.0) as f32
+            }
+            _ => 1.0,
+        }
+    }
+
+    /// 取出并清零统计
+    pub fn take_stats(&mut self) -> PacingStats {
+        let mut stats = std::mem::take(&mut self.stats);
+        stats.delay_ms = self.delay.map_or(0.0, |d| d.as_secs_f64() * 1000.0);
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    /// 25fps 源在 60Hz 刷新下每帧都按计划呈现, 不丢帧不延迟
+    #[test]
+    fn test_paced_presentation() {
+        let start = Instant::now();
+        let refresh = MS * 16;
+        let mut pacer = FramePacer::new(PacingMode::Paced);
+        let (mut shown, mut next) = (Vec::new(), 0u32);
+        for tick in 0..60u32 {
+            let now = start + refresh * tick;
+            // 每 40ms 一帧, 采集后 5ms 到达
+            while start + MS * (40 * next + 5) <= now {
+                pacer.push(start + MS * 40 * next, next, now);
+                next += 1;
+            }
+            if let Some(f) = pacer.poll(now, refresh) {
+                shown.push(f);
+            }
+        }
+        let stats = pacer.take_stats();
+        assert_eq!(stats.dropped, 0);
+        assert_eq!(stats.late, 0);
+        assert_eq!(stats.presented as usize, shown.len());
+        assert!(shown.windows(2).all(|w| w[1] == w[0] + 1), "{:?}", shown);
+    }
+
+    /// 同一刷新周期内到期的多帧只呈现最新一帧, 最新模式直接取队尾
+    #[test]
+    fn test_drop_and_latest() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::new(PacingMode::Paced);
+        pacer.push(start, 0, start);
+        assert_eq!(pacer.poll(start, MS * 16), Some(0));
+        pacer.push(start + MS * 10, 1, start + MS * 10);
+        pacer.push(start + MS * 20, 2, start + MS * 20);
+        assert_eq!(pacer.poll(start + MS * 60, MS * 16), Some(2));
+        let stats = pacer.take_stats();
+        assert_eq!((stats.presented, stats.dropped, stats.late), (2, 1, 1));
+
+        pacer.set_mode(PacingMode::Latest);
+        pacer.push(start + MS * 70, 3, start + MS * 70);
+        pacer.push(start + MS * 80, 4, start + MS * 80);
+        assert_eq!(pacer.poll(start + MS * 80, MS * 16), Some(4));
+        assert_eq!(pacer.take_stats().dropped, 1);
+        assert_eq!(pacer.blend(start + MS * 80), 1.0);
+    }
+
+    /// 平滑模式的混合权重从计划呈现时刻起一个帧间隔内由 0 升到 1
+    #[test]
+    fn test_smooth_blend() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::new(PacingMode::Smooth);
+        pacer.push(start, 0, start);
+        pacer.push(start + MS * 40, 1, start + MS * 40);
+        let due = start + MS * 40 + pacer.delay.unwrap();
+        assert_eq!(pacer.poll(due, MS * 16), Some(1));
+        assert_eq!(pacer.blend(due), 0.0);
+        assert!((pacer.blend(due + MS * 20) - 0.5).abs() < 1e-3);
+        assert_eq!(pacer.blend(due + MS * 100), 1.0);
+    }
+}
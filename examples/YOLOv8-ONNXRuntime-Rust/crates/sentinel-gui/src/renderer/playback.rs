@@ -0,0 +1,292 @@
+//! 回放控制 (暂停 / 逐帧 / 检视)
+//!
+//! 渲染线程把每个实际呈现的帧记入一个小的历史缓冲, 检测结果按采集时间戳 (PTS)
+//! 挂到对应的帧上, 这样暂停时显示的叠加框就是该帧自己的检测结果:
+//! - 暂停: 画面停在当前帧, 实时帧不再呈现 (解码与检测照常运行)
+//! - 后退: 在历史缓冲内逐帧后退; 前进: 先在缓冲内前进, 到最新帧后等待下一个实时帧
+//! - 检测线程跳过的帧没有自己的结果, 使用更早一帧的结果并标记为非精确
+
+use crate::detection::types::BBox;
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// 历史缓冲帧数 (1080p RGBA 每帧约 8MB)
+pub const HISTORY_FRAMES: usize = 30;
+
+struct Entry<F, D> {
+    pts: Instant,
+    frame: F,
+    detection: Option<D>,
+}
+
+/// 帧历史与暂停/逐帧状态
+pub struct Playback<F, D> {
+    history: VecDeque<Entry<F, D>>,
+    capacity: usize,
+    paused: bool,
+    cursor: usize,   // 暂停时显示的历史帧下标
+    step_live: bool, // 暂停在最新帧时前进: 呈现下一个实时帧
+    changed: bool,   // 暂停时显示帧已变化, 需要更新纹理
+}
+
+impl<F: Clone, D> Playback<F, D> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            history: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+            paused: false,
+            cursor: 0,
+            step_live: false,
+            changed: false,
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// 暂停在当前显示的帧
+    pub fn pause(&mut self) {
+        if !self.paused {
+            self.paused = true;
+            self.cursor = self.history.len().saturating_sub(1);
+        }
+    }
+
+    /// 恢复实时播放, 下一个实时帧到来时更新画面
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.step_live = false;
+        self.changed = false;
+    }
+
+    pub fn toggle(&mut self) {
+        if self.paused {
+            self.resume();
+        } else {
+            self.pause();
+        }
+    }
+
+    /// 清空历史缓冲 (内存防护, 暂停检视时不调用)
+    pub fn clear(&mut self) {
+        self.history.clear();
+        self.cursor = 0;
+        self.step_live = false;
+    }
+
+    /// 暂停时重新呈现当前帧 (纹理释放后重建)
+    pub fn refresh(&mut self) {
+        if self.paused {
+            self.changed = true;
+        }
+    }
+
+    /// 前进一帧 (播放中时先暂停)
+    pub fn step_forward(&mut self) {
+        self.pause();
+        if self.cursor + 1 < self.history.len() {
+            self.cursor += 1;
+            self.changed = true;
+        } else {
+            self.step_live = true;
+        }
+    }
+
+    /// 在历史缓冲内后退一帧 (播放中时先暂停)
+    pub fn step_backward(&mut self) {
+        self.pause();
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.step_live = false;
+            self.changed = true;
+        }
+    }
+
+    /// 暂停时的位置: (相对最新帧的偏移, 缓冲帧数), 如 (-3, 30)
+    pub fn position(&self) -> Option<(i64, usize)> {
+        let len = self.history.len();
+        (self.paused && len > 0).then(|| (self.cursor as i64 - (len as i64 - 1), len))
+    }
+
+    /// 本次刷新要呈现的帧
+    ///
+    /// 播放时记录并原样返回实时帧; 暂停时丢弃实时帧, 仅在逐帧移动后返回历史帧
+    /// (或单步等到的下一个实时帧)
+    pub fn present(&mut self, live: Option<(Instant, F)>) -> Option<F> {
+        if !self.paused || self.step_live {
+            let (pts, frame) = live?;
+            self.record(pts, frame.clone());
+            if self.paused {
+                self.cursor = self.history.len() - 1;
+                self.step_live = false;
+            }
+            return Some(frame);
+        }
+        if !std::mem::take(&mut self.changed) {
+            return None;
+        }
+        self.history.get(self.cursor).map(|e| e.frame.clone())
+    }
+
+    fn record(&mut self, pts: Instant, frame: F) {
+        if self.history.len() == self.capacity {
+            self.history.pop_front();
+            self.cursor = self.cursor.saturating_sub(1);
+        }
+        self.history.push_back(Entry {
+            pts,
+            frame,
+            detection: None,
+        });
+    }
+
+    /// 检测结果挂到同一 PTS 的历史帧上 (该帧未呈现或已移出缓冲时忽略)
+    pub fn record_detection(&mut self, pts: Instant, detection: D) {
+        if let Some(entry) = self.history.iter_mut().rev().find(|e| e.pts == pts) {
+            entry.detection = Some(detection);
+        }
+    }
+
+    /// 缓冲内同一 PTS 的帧 (检测结果对应的画面, 已移出缓冲时为 None)
+    pub fn frame(&self, pts: Instant) -> Option<&F> {
+        self.history
+            .iter()
+            .rev()
+            .find(|e| e.pts == pts)
+            .map(|e| &e.frame)
+    }
+
+    /// 暂停帧的检测结果: (结果, 是否为该帧自己的结果)
+    ///
+    /// 该帧没有结果时回退到缓冲内更早一帧的结果; 播放时返回 None
+    pub fn detection(&self) -> Option<(&D, bool)> {
+        if !self.paused {
+            return None;
+        }
+        let pts = self.history.get(self.cursor)?.pts;
+        self.history
+            .range(..=self.cursor)
+            .rev()
+            .find_map(|e| e.detection.as_ref().map(|d| (d, e.pts == pts)))
+    }
+}
+
+/// 帧坐标 (x, y) 处的检测框下标, 多个框重叠时取面积最小的 (通常是前景目标)
+pub fn box_at(bboxes: &[BBox], x: f32, y: f32) -> Option<usize> {
+    bboxes
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| (b.x1..=b.x2).contains(&x) && (b.y1..=b.y2).contains(&y))
+        .min_by(|(_, a), (_, b)| {
+            let area = |b: &BBox| (b.x2 - b.x1) * (b.y2 - b.y1);
+            area(a).total_cmp(&area(b))
+        })
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn pts(start: Instant, i: u64) -> Instant {
+        start + Duration::from_millis(40 * i)
+    }
+
+    /// 暂停后实时帧不再呈现, 后退/前进在缓冲内移动, 到最新帧后单步取下一个实时帧
+    #[test]
+    fn test_pause_and_step() {
+        let start = Instant::now();
+        let mut playback: Playback<u64, &str> = Playback::new(4);
+        for i in 0..6 {
+            assert_eq!(playback.present(Some((pts(start, i), i))), Some(i));
+        }
+        playback.pause();
+        assert_eq!(playback.present(Some((pts(start, 6), 6))), None);
+        assert_eq!(playback.position(), Some((0, 4)));
+
+        for expected in [4, 3, 2] {
+            playback.step_backward();
+            assert_eq!(playback.present(None), Some(expected));
+        }
+        playback.step_backward(); // 已是缓冲内最早的帧
+        assert_eq!(playback.present(None), None);
+        assert_eq!(playback.position(), Some((-3, 4)));
+
+        for expected in [3, 4, 5] {
+            playback.step_forward();
+            assert_eq!(playback.present(None), Some(expected));
+        }
+        playback.step_forward();
+        assert_eq!(playback.present(None), None);
+        assert_eq!(playback.present(Some((pts(start, 7), 7))), Some(7));
+        assert_eq!(playback.present(Some((pts(start, 8), 8))), None);
+        assert_eq!(playback.position(), Some((0, 4)));
+
+        playback.resume();
+        assert_eq!(playback.position(), None);
+        assert_eq!(playback.present(Some((pts(start, 9), 9))), Some(9));
+    }
+
+    /// 暂停帧显示自己的检测结果; 没有结果的帧回退到更早一帧并标记为非精确
+    #[test]
+    fn test_detection_for_paused_frame() {
+        let start = Instant::now();
+        let mut playback: Playback<u64, &str> = Playback::new(8);
+        for i in 0..3 {
+            playback.present(Some((pts(start, i), i)));
+        }
+        playback.record_detection(pts(start, 0), "d0");
+        assert_eq!(playback.frame(pts(start, 1)), Some(&1));
+        assert_eq!(playback.frame(pts(start, 5)), None);
+        playback.record_detection(pts(start, 1), "d1");
+        assert!(playback.detection().is_none());
+
+        // 暂停后才到达的结果同样挂到对应帧
+        playback.pause();
+        assert_eq!(playback.detection(), Some((&"d1", false)));
+        playback.record_detection(pts(start, 2), "d2");
+        assert_eq!(playback.detection(), Some((&"d2", true)));
+        playback.step_backward();
+        playback.step_backward();
+        assert_eq!(playback.detection(), Some((&"d0", true)));
+    }
+
+    /// 内存防护: 清空缓冲后从新帧重新记录, 暂停时刷新重新呈现当前帧
+    #[test]
+    fn test_clear_and_refresh() {
+        let start = Instant::now();
+        let mut playback: Playback<u64, &str> = Playback::new(4);
+        for i in 0..3 {
+            playback.present(Some((pts(start, i), i)));
+        }
+        playback.refresh(); // 播放中无效
+        playback.clear();
+        assert_eq!(playback.frame(pts(start, 2)), None);
+        assert_eq!(playback.present(Some((pts(start, 3), 3))), Some(3));
+
+        playback.pause();
+        assert_eq!(playback.position(), Some((0, 1)));
+        assert_eq!(playback.present(None), None);
+        playback.refresh();
+        assert_eq!(playback.present(None), Some(3));
+    }
+
+    /// 点选取包含该点的最小框
+    #[test]
+    fn test_box_at() {
+        let bbox = |x1, y1, x2, y2| BBox {
+            x1,
+            y1,
+            x2,
+            y2,
+            confidence: 0.9,
+            class_id: 0,
+        };
+        let boxes = [bbox(0.0, 0.0, 100.0, 100.0), bbox(40.0, 40.0, 60.0, 60.0)];
+        assert_eq!(box_at(&boxes, 50.0, 50.0), Some(1));
+        assert_eq!(box_at(&boxes, 10.0, 90.0), Some(0));
+        assert_eq!(box_at(&boxes, 150.0, 50.0), None);
+    }
+}
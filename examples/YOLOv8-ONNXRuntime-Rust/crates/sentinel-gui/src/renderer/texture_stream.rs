@@ -0,0 +1,426 @@
+//! 视频纹理流式上传 (PBO 双缓冲 + 脏区域)
+//!
+//! `Texture2D::update` 每帧先拷贝出一个新的 Vec 再同步整帧上传. OpenGL 后端改走像素解包缓冲 (PBO):
+//! - 两个常驻的 PBO 轮流使用: 本帧写入其中一个, 驱动从缓冲异步拷贝到纹理, CPU 不等待上一次上传
+//! - 像素 (含调色板着色) 直接写入映射的缓冲, 不经过中间 Vec
+//! - 按 64×64 块比较新帧与纹理当前内容, 只写入并上传变化的块 (固定机位画面大部分块不变);
+//!   变化块超过一半 (镜头移动/切换) 时整帧上传, 完全相同时跳过
+//! - 非 OpenGL 后端或映射失败时返回 false, 调用方退回 `Texture2D::update`, 之后不再尝试
+//!
+//! miniquad 的 GL 绑定不含 `glBufferStorage` (GL 4.4), 缓冲常驻但每帧以 INVALIDATE 方式重新映射,
+//! 驱动可为写入方分配新的存储, 不与正在进行的上传同步
+
+use std::ffi::c_void;
+use std::sync::Arc;
+
+use macroquad::miniquad::{gl, RawId};
+use macroquad::prelude::*;
+
+use crate::detection::types::DecodedFrame;
+use crate::utils::colormap::Palette;
+
+/// 比较块边长 (像素)
+pub const TILE: u32 = 64;
+
+/// 变化面积超过该比例时整帧上传
+const FULL_UPLOAD_RATIO: f64 = 0.5;
+
+// GL 常量 (GL 3.0 / GLES 3.0)
+const PIXEL_UNPACK_BUFFER: u32 = 0x88EC;
+const STREAM_DRAW: u32 = 0x88E0;
+const MAP_WRITE_BIT: u32 = 0x0002;
+const MAP_INVALIDATE_BUFFER_BIT: u32 = 0x0008;
+const UNPACK_ROW_LENGTH: u32 = 0x0CF2;
+const TEXTURE_2D: u32 = 0x0DE1;
+const TEXTURE_BINDING_2D: u32 = 0x8069;
+const RGBA: u32 = 0x1908;
+const UNSIGNED_BYTE: u32 = 0x1401;
+
+/// 纹理上的矩形区域 (像素)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// 一帧的上传方式
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Upload {
+    Skip,                 // 与纹理内容相同
+    Full,                 // 整帧
+    Regions(Vec<Region>), // 只上传变化的区域
+}
+
+/// 与上一帧不同的块, 同一行块中相邻的变化块合并为一个区域
+///
+/// 变化面积超过 max_area 时提前结束并返回 None (整帧上传, 不必比较剩余的块)
+pub fn dirty_regions(
+    prev: &[u8],
+    cur: &[u8],
+    width: u32,
+    height: u32,
+    tile: u32,
+    max_area: u64,
+) -> Option<Vec<Region>> {
+    let stride = width as usize * 4;
+    let mut regions = Vec::new();
+    let mut area = 0u64;
+    for ty in (0..height).step_by(tile as usize) {
+        let th = tile.min(height - ty);
+        let mut run: Option<Region> = None;
+        for tx in (0..width).step_by(tile as usize) {
+            let tw = tile.min(width - tx);
+            let dirty = (ty..ty + th).any(|y| {
+                let start = y as usize * stride + tx as usize * 4;
+                let end = start + tw as usize * 4;
+                prev[start..end] != cur[start..end]
+            });
+            if !dirty {
+                regions.extend(run.take());
+                continue;
+            }
+            area += (tw * th) as u64;
+            if area > max_area {
+                return None;
+            }
+            match &mut run {
+                Some(region) => region.width += tw,
+                None => {
+                    run = Some(Region {
+                        x: tx,
+                        y: ty,
+                        width: tw,
+                        height: th,
+                    })
+                }
+            }
+        }
+        regions.extend(run);
+    }
+    Some(regions)
+}
+
+/// 决定上传方式: prev 为纹理当前内容 (未知或尺寸不同时整帧上传)
+pub fn plan(prev: Option<&[u8]>, cur: &[u8], width: u32, height: u32) -> Upload {
+    let Some(prev) = prev.filter(|p| p.len() == cur.len()) else {
+        return Upload::Full;
+    };
+    let max_area = (width as f64 * height as f64 * FULL_UPLOAD_RATIO) as u64;
+    match dirty_regions(prev, cur, width, height, TILE, max_area) {
+        None => Upload::Full,
+        Some(regions) if regions.is_empty() => Upload::Skip,
+        Some(regions) => Upload::Regions(regions),
+    }
+}
+
+/// 把区域内的像素 (着色后) 写入与帧同布局的目标缓冲; 整行宽的区域一次写入
+pub fn write_regions(dst: &mut [u8], src: &[u8], width: u32, regions: &[Region], palette: Palette) {
+    let stride = width as usize * 4;
+    for r in regions {
+        if r.x == 0 && r.width == width {
+            let start = r.y as usize * stride;
+            let end = start + r.height as usize * stride;
+            palette.colorize_into(&src[start..end], &mut dst[start..end]);
+            continue;
+        }
+        for y in r.y..r.y + r.height {
+            let start = y as usize * stride + r.x as usize * 4;
+            let end = start + r.width as usize * 4;
+            palette.colorize_into(&src[start..end], &mut dst[start..end]);
+        }
+    }
+}
+
+/// 纹理当前显示的帧 (平滑呈现时两张纹理交替使用, 各自记录)
+struct Content {
+    texture: u32,
+    frame: Arc<Vec<u8>>,
+    palette: Palette,
+}
+
+/// PBO 双缓冲的视频纹理上传
+pub struct TextureStream {
+    buffers: Option<[u32; 2]>,
+    capacity: usize,
+    next: usize,
+    contents: Vec<Content>,
+    disabled: bool,
+}
+
+impl Default for TextureStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextureStream {
+    pub fn new() -> Self {
+        Self {
+            buffers: None,
+            capacity: 0,
+            next: 0,
+            contents: Vec::with_capacity(2),
+            disabled: false,
+        }
+    }
+
+    /// 新建的纹理已包含该帧 (GL 会复用已删除纹理的编号, 覆盖旧记录)
+    pub fn record(&mut self, texture: &Texture2D, frame: &Arc<Vec<u8>>, palette: Palette) {
+        if let Some(id) = raw_texture(texture) {
+            self.remember(id, Arc::clone(frame), palette);
+        }
+    }
+
+    fn remember(&mut self, texture: u32, frame: Arc<Vec<u8>>, palette: Palette) {
+        self.contents.retain(|c| c.texture != texture);
+        if self.contents.len() == 2 {
+            self.contents.remove(0);
+        }
+        self.contents.push(Content {
+            texture,
+            frame,
+            palette,
+        });
+    }
+
+    /// 把纹理更新为该帧, 返回 false 时调用方改用 `Texture2D::update`
+    pub fn update(&mut self, texture: &Texture2D, frame: &DecodedFrame, palette: Palette) -> bool {
+        let size = frame.width as usize * frame.height as usize * 4;
+        if self.disabled {
+            return false;
+        }
+        if frame.rgba_data.len() < size {
+            // 调用方整帧更新后纹理内容不再可知, 下一帧整帧上传
+            self.contents.clear();
+            return false;
+        }
+        let Some(id) = raw_texture(texture) else {
+            self.disable("当前图形后端不是 OpenGL");
+            return false;
+        };
+        let current = self
+            .contents
+            .iter()
+            .find(|c| c.texture == id && c.palette == palette);
+        if current.is_some_and(|c| Arc::ptr_eq(&c.frame, &frame.rgba_data)) {
+            return true;
+        }
+        let upload = plan(
+            current.map(|c| c.frame.as_slice()),
+            &frame.rgba_data,
+            frame.width,
+            frame.height,
+        );
+        let regions = match upload {
+            Upload::Skip => Vec::new(),
+            Upload::Full => vec![Region {
+                x: 0,
+                y: 0,
+                width: frame.width,
+                height: frame.height,
+            }],
+            Upload::Regions(regions) => regions,
+        };
+        if !regions.is_empty() {
+            // SAFETY: 在渲染线程调用, GL 上下文有效; 绑定状态在返回前恢复
+            let uploaded = unsafe { self.upload(id, frame, palette, &regions) };
+            if !uploaded {
+                self.disable("映射上传缓冲失败");
+                return false;
+            }
+        }
+        self.remember(id, Arc::clone(&frame.rgba_data), palette);
+        true
+    }
+
+    fn disable(&mut self, reason: &str) {
+        eprintln!("⚠️ 视频纹理 PBO 上传不可用 ({}), 退回整帧更新", reason);
+        self.disabled = true;
+        self.contents.clear();
+    }
+
+    /// 写入下一个 PBO 并从中上传各区域
+    unsafe fn upload(
+        &mut self,
+        texture: u32,
+        frame: &DecodedFrame,
+        palette: Palette,
+        regions: &[Region],
+    ) -> bool {
+        let (width, height) = (frame.width, frame.height);
+        let size = width as usize * height as usize * 4;
+        // 先提交 macroquad 已批处理的绘制, 再直接操作 GL 状态
+        get_internal_gl().flush();
+
+        let buffers = *self.buffers.get_or_insert_with(|| {
+            let mut ids = [0u32; 2];
+            gl::glGenBuffers(2, ids.as_mut_ptr());
+            ids
+        });
+        if self.capacity != size {
+            for &buffer in &buffers {
+                gl::glBindBuffer(PIXEL_UNPACK_BUFFER, buffer);
+                gl::glBufferData(
+                    PIXEL_UNPACK_BUFFER,
+                    size as _,
+                    std::ptr::null(),
+                    STREAM_DRAW,
+                );
+            }
+            self.capacity = size;
+        }
+        let buffer = buffers[self.next];
+        self.next ^= 1;
+
+        gl::glBindBuffer(PIXEL_UNPACK_BUFFER, buffer);
+        let mapped = gl::glMapBufferRange(
+            PIXEL_UNPACK_BUFFER,
+            0,
+            size as _,
+            MAP_WRITE_BIT | MAP_INVALIDATE_BUFFER_BIT,
+        ) as *mut u8;
+        if mapped.is_null() {
+            gl::glBindBuffer(PIXEL_UNPACK_BUFFER, 0);
+            return false;
+        }
+        let dst = std::slice::from_raw_parts_mut(mapped, size);
+        write_regions(dst, &frame.rgba_data, width, regions, palette);
+        if gl::glUnmapBuffer(PIXEL_UNPACK_BUFFER) == 0 {
+            // 映射期间缓冲内容丢失 (如显示模式切换), 本帧放弃
+            gl::glBindBuffer(PIXEL_UNPACK_BUFFER, 0);
+            return false;
+        }
+
+        let mut bound = 0;
+        gl::glGetIntegerv(TEXTURE_BINDING_2D, &mut bound);
+        gl::glBindTexture(TEXTURE_2D, texture);
+        gl::glPixelStorei(UNPACK_ROW_LENGTH, width as _);
+        for r in regions {
+            let offset = (r.y as usize * width as usize + r.x as usize) * 4;
+            gl::glTexSubImage2D(
+                TEXTURE_2D,
+                0,
+                r.x as _,
+                r.y as _,
+                r.width as _,
+                r.height as _,
+                RGBA,
+                UNSIGNED_BYTE,
+                offset as *const c_void,
+            );
+        }
+        gl::glPixelStorei(UNPACK_ROW_LENGTH, 0);
+        gl::glBindTexture(TEXTURE_2D, bound as u32);
+        gl::glBindBuffer(PIXEL_UNPACK_BUFFER, 0);
+        true
+    }
+}
+
+impl Drop for TextureStream {
+    fn drop(&mut self) {
+        if let Some(buffers) = self.buffers {
+            // SAFETY: 缓冲在渲染线程创建, 渲染器也在渲染线程释放
+            unsafe { gl::glDeleteBuffers(2, buffers.as_ptr()) }
+        }
+    }
+}
+
+/// 纹理的 GL 编号, 非 OpenGL 后端为 None
+fn raw_texture(texture: &Texture2D) -> Option<u32> {
+    // SAFETY: 只读取纹理编号, 不改变 macroquad 的状态
+    let gl = unsafe { get_internal_gl() };
+    #[allow(irrefutable_let_patterns)] // 仅苹果平台另有 Metal 变体
+    let RawId::OpenGl(id) = gl.quad_context.texture_raw_id(texture.raw_miniquad_id()) else {
+        return None;
+    };
+    Some(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 128×64 的帧, 按 (x, y) 修改单个像素
+    fn frames(changed: &[(u32, u32)]) -> (Vec<u8>, Vec<u8>) {
+        let prev = vec![0u8; 128 * 64 * 4];
+        let mut cur = prev.clone();
+        for &(x, y) in changed {
+            cur[(y as usize * 128 + x as usize) * 4] = 255;
+        }
+        (prev, cur)
+    }
+
+    /// 变化的块按行合并为区域, 没有变化时为空
+    #[test]
+    fn test_dirty_regions() {
+        let (prev, cur) = frames(&[(5, 5), (100, 40)]);
+        let regions = dirty_regions(&prev, &cur, 128, 64, 32, u64::MAX).unwrap();
+        assert_eq!(
+            regions,
+            vec![
+                Region {
+                    x: 0,
+                    y: 0,
+                    width: 32,
+                    height: 32
+                },
+                Region {
+                    x: 96,
+                    y: 32,
+                    width: 32,
+                    height: 32
+                },
+            ]
+        );
+        let (prev, cur) = frames(&[(40, 0), (70, 0)]);
+        let regions = dirty_regions(&prev, &cur, 128, 64, 32, u64::MAX).unwrap();
+        assert_eq!(regions.len(), 1);
+        assert_eq!((regions[0].x, regions[0].width), (32, 64));
+        assert!(dirty_regions(&prev, &prev, 128, 64, 32, u64::MAX)
+            .unwrap()
+            .is_empty());
+    }
+
+    /// 内容未知或尺寸变化时整帧, 变化过多时整帧, 相同时跳过
+    #[test]
+    fn test_plan() {
+        let (prev, cur) = frames(&[(5, 5)]);
+        assert_eq!(plan(None, &cur, 128, 64), Upload::Full);
+        assert_eq!(plan(Some(&prev[..16]), &cur, 128, 64), Upload::Full);
+        assert_eq!(plan(Some(&prev), &prev, 128, 64), Upload::Skip);
+        assert!(matches!(plan(Some(&prev), &cur, 128, 64), Upload::Regions(r) if r.len() == 1));
+        // 两个 64×64 块都变化 (整帧)
+        let (prev, cur) = frames(&[(5, 5), (100, 5)]);
+        assert_eq!(plan(Some(&prev), &cur, 128, 64), Upload::Full);
+    }
+
+    /// 只写入区域内的像素, 整行宽的区域与逐行写入结果相同
+    #[test]
+    fn test_write_regions() {
+        let src: Vec<u8> = (0..8 * 4 * 4).map(|i| i as u8).collect();
+        let mut dst = vec![0u8; src.len()];
+        let region = Region {
+            x: 2,
+            y: 1,
+            width: 3,
+            height: 2,
+        };
+        write_regions(&mut dst, &src, 8, &[region], Palette::None);
+        let at = |buf: &[u8], x: usize, y: usize| buf[(y * 8 + x) * 4];
+        assert_eq!(at(&dst, 2, 1), at(&src, 2, 1));
+        assert_eq!(at(&dst, 4, 2), at(&src, 4, 2));
+        assert_eq!(at(&dst, 5, 1), 0);
+        assert_eq!(at(&dst, 2, 3), 0);
+
+        let full = Region {
+            x: 0,
+            y: 0,
+            width: 8,
+            height: 4,
+        };
+        write_regions(&mut dst, &src, 8, &[full], Palette::None);
+        assert_eq!(dst, src);
+    }
+}
@@ -0,0 +1,427 @@
+//! 轨迹详情 (点选轨迹后的侧边面板)
+//!
+//! 渲染线程按实时检测结果逐帧累积每条轨迹的统计, 跟踪器与各分析模块的数据汇总到一处:
+//! - 首次/最近出现时间、帧数、检测类别、画面速度 (像素/秒) 与地面速度
+//! - ReID 相似度历史: 相邻两次外观特征的余弦相似度, 被遮挡或 ID 切换时明显下降
+//! - 关键点质量: 框内骨架的可见关键点数与平均置信度
+//! - 缩略图: 只为选中的轨迹按间隔裁剪, 保留最近几张
+//!
+//! 长时间未出现的轨迹被清除, 选中或已标记的轨迹保留.
+
+use crate::detection::bytetrack::cosine_similarity;
+use crate::detection::types::{BBox, PoseKeypoints};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// ReID 相似度历史长度 (次)
+pub const SIMILARITY_HISTORY: usize = 60;
+
+/// 缩略图保留张数与裁剪间隔
+pub const THUMBNAIL_COUNT: usize = 8;
+const THUMBNAIL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 缩略图长边 (像素)
+pub const THUMBNAIL_SIZE: u32 = 96;
+
+/// 轨迹消失超过该时长后清除 (选中或已标记的除外)
+const TRACK_EXPIRY: Duration = Duration::from_secs(30);
+
+/// 保留最近该时长内的检测框: 画面速度按框中心位移估计, 缩略图按帧 PTS 找回检测框
+const RECENT_WINDOW: Duration = Duration::from_secs(1);
+
+/// 关键点可见的置信度阈值 (与骨架绘制一致)
+const KEYPOINT_VISIBLE_CONF: f32 = 0.3;
+
+/// 关键点质量
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KeypointQuality {
+    pub visible: usize, // 置信度不低于可见阈值的关键点数
+    pub total: usize,   // 骨架关键点总数
+    pub mean_conf: f32, // 全部关键点的平均置信度
+}
+
+/// 一帧中某条轨迹的观测 (检测结果中同一下标的各项)
+pub struct Observation<'a> {
+    pub bbox: &'a BBox, // class_id 为轨迹ID
+    pub class: Option<u32>,
+    pub speed: Option<f32>,
+    pub reid: Option<&'a [f32]>,
+    pub keypoints: Option<KeypointQuality>,
+}
+
+/// 缩略图 (RGBA)
+pub struct Thumbnail {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+    pub time: DateTime<Utc>,
+}
+
+/// 一条轨迹的累积详情
+pub struct TrackDetail {
+    pub id: u32,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    first_pts: Instant,
+    last_pts: Instant,
+    pub frames: u64,
+    pub class: Option<u32>,
+    pub bbox: BBox,
+    pub ground_speed: Option<f32>, // 米/秒, 需要地面标定
+    recent: VecDeque<(Instant, BBox)>,
+    pub similarity: VecDeque<f32>,
+    last_feature: Option<Vec<f32>>,
+    pub keypoints: Option<KeypointQuality>,
+    pub thumbnails: VecDeque<Thumbnail>,
+    last_thumbnail: Option<Instant>,
+    pub flagged: bool,
+}
+
+impl TrackDetail {
+    fn new(bbox: &BBox, pts: Instant, now: DateTime<Utc>) -> Self {
+        Self {
+            id: bbox.class_id,
+            first_seen: now,
+            last_seen: now,
+            first_pts: pts,
+            last_pts: pts,
+            frames: 0,
+            class: None,
+            bbox: bbox.clone(),
+            ground_speed: None,
+            recent: VecDeque::new(),
+            similarity: VecDeque::with_capacity(SIMILARITY_HISTORY),
+            last_feature: None,
+            keypoints: None,
+            thumbnails: VecDeque::with_capacity(THUMBNAIL_COUNT),
+            last_thumbnail: None,
+            flagged: false,
+        }
+    }
+
+    fn update(&mut self, obs: &Observation, pts: Instant, now: DateTime<Utc>) {
+        self.last_seen = now;
+        self.last_pts = pts;
+        self.frames += 1;
+        self.bbox = obs.bbox.clone();
+        self.class = obs.class.or(self.class);
+        self.ground_speed = obs.speed;
+        self.keypoints = obs.keypoints.or(self.keypoints);
+
+        self.recent.push_back((pts, obs.bbox.clone()));
+        while self
+            .recent
+            .front()
+            .is_some_and(|(t, _)| pts.duration_since(*t) > RECENT_WINDOW)
+        {
+            self.recent.pop_front();
+        }
+
+        if let Some(feature) = obs.reid.filter(|f| !f.is_empty()) {
+            if let Some(last) = &self.last_feature {
+                if self.similarity.len() == SIMILARITY_HISTORY {
+                    self.similarity.pop_front();
+                }
+                self.similarity.push_back(cosine_similarity(last, feature));
+            }
+            self.last_feature = Some(feature.to_vec());
+        }
+    }
+
+    /// 轨迹存在时长 (首次到最近一次出现)
+    pub fn age(&self) -> Duration {
+        self.last_pts.duration_since(self.first_pts)
+    }
+
+    /// 画面速度 (像素/秒), 窗口内不足两次观测时为 None
+    pub fn velocity(&self) -> Option<(f32, f32)> {
+        let center = |b: &BBox| ((b.x1 + b.x2) / 2.0, (b.y1 + b.y2) / 2.0);
+        let (t0, b0) = self.recent.front()?;
+        let (t1, b1) = self.recent.back()?;
+        let ((x0, y0), (x1, y1)) = (center(b0), center(b1));
+        let dt = t1.duration_since(*t0).as_secs_f32();
+        (dt > 0.0).then(|| ((x1 - x0) / dt, (y1 - y0) / dt))
+    }
+}
+
+/// 各轨迹的详情与选中/跟随状态
+#[derive(Default)]
+pub struct TrackHistory {
+    tracks: HashMap<u32, TrackDetail>,
+    selected: Option<u32>,
+    pub following: Option<u32>, // 已请求云台跟随的轨迹
+}
+
+impl TrackHistory {
+    /// 记入一帧的观测, 并清除过期轨迹
+    pub fn observe(&mut self, pts: Instant, now: DateTime<Utc>, observations: &[Observation]) {
+        for obs in observations {
+            self.tracks
+                .entry(obs.bbox.class_id)
+                .or_insert_with(|| TrackDetail::new(obs.bbox, pts, now))
+                .update(obs, pts, now);
+        }
+        let selected = self.selected;
+        self.tracks.retain(|id, t| {
+            t.flagged
+                || selected == Some(*id)
+                || pts.saturating_duration_since(t.last_pts) < TRACK_EXPIRY
+        });
+    }
+
+    /// 清除全部轨迹 (关闭跟踪器时轨迹ID失去意义)
+    pub fn clear(&mut self) {
+        self.tracks.clear();
+        self.selected = None;
+        self.following = None;
+    }
+
+    /// 选中轨迹 (未记录的轨迹不能选中), None 取消选中
+    pub fn select(&mut self, id: Option<u32>) {
+        self.selected = id.filter(|id| self.tracks.contains_key(id));
+    }
+
+    pub fn selected_id(&self) -> Option<u32> {
+        self.selected
+    }
+
+    pub fn selected(&self) -> Option<&TrackDetail> {
+        self.tracks.get(&self.selected?)
+    }
+
+    pub fn is_flagged(&self, id: u32) -> bool {
+        self.tracks.get(&id).is_some_and(|t| t.flagged)
+    }
+
+    /// 切换轨迹的标记, 返回新的状态
+    pub fn toggle_flag(&mut self, id: u32) -> bool {
+        match self.tracks.get_mut(&id) {
+            Some(track) => {
+                track.flagged = !track.flagged;
+                track.flagged
+            }
+            None => false,
+        }
+    }
+
+    /// 为选中的轨迹从该帧裁剪缩略图 (该帧需有此轨迹最近的观测, 且距上一张已超过间隔)
+    ///
+    /// 帧与检测结果到达渲染线程的先后不定, 呈现帧与收到结果时各调用一次
+    pub fn capture_thumbnail(&mut self, pts: Instant, rgba: &[u8], width: u32, height: u32) {
+        let Some(track) = self.selected.and_then(|id| self.tracks.get_mut(&id)) else {
+            return;
+        };
+        let due = track
+            .last_thumbnail
+            .is_none_or(|t| pts.saturating_duration_since(t) >= THUMBNAIL_INTERVAL);
+        let Some((_, bbox)) = track.recent.iter().rev().find(|(t, _)| *t == pts) else {
+            return;
+        };
+        if !due {
+            return;
+        }
+        if let Some((w, h, pixels)) = crop_thumbnail(rgba, width, height, bbox, THUMBNAIL_SIZE) {
+            if track.thumbnails.len() == THUMBNAIL_COUNT {
+                track.thumbnails.pop_front();
+            }
+            track.thumbnails.push_back(Thumbnail {
+                width: w,
+                height: h,
+                rgba: pixels,
+                time: track.last_seen
+                    - chrono::Duration::from_std(track.last_pts.saturating_duration_since(pts))
+                        .unwrap_or_default(),
+            });
+            track.last_thumbnail = Some(pts);
+        }
+    }
+}
+
+/// 框内骨架的关键点质量: 取可见关键点落在框内最多的骨架, 没有时为 None
+pub fn keypoint_quality(bbox: &BBox, skeletons: &[PoseKeypoints]) -> Option<KeypointQuality> {
+    let inside = |k: &PoseKeypoints| {
+        k.points
+            .iter()
+            .filter(|(x, y, c)| {
+                *c >= KEYPOINT_VISIBLE_CONF
+                    && (bbox.x1..=bbox.x2).contains(x)
+                    && (bbox.y1..=bbox.y2).contains(y)
+            })
+            .count()
+    };
+    let skeleton = skeletons
+        .iter()
+        .map(|k| (inside(k), k))
+        .filter(|(n, _)| *n > 0)
+        .max_by_key(|(n, _)| *n)?
+        .1;
+    let total = skeleton.points.len();
+    let visible = skeleton
+        .points
+        .iter()
+        .filter(|(_, _, c)| *c >= KEYPOINT_VISIBLE_CONF)
+        .count();
+    let mean_conf = skeleton.points.iter().map(|(_, _, c)| c).sum::<f32>() / total as f32;
+    Some(KeypointQuality {
+        visible,
+        total,
+        mean_conf,
+    })
+}
+
+/// 从 RGBA 帧裁剪检测框并按最近邻缩放到长边不超过 max_side: (宽, 高, 像素)
+pub fn crop_thumbnail(
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+    bbox: &BBox,
+    max_side: u32,
+) -> Option<(u32, u32, Vec<u8>)> {
+    if rgba.len() < (width * height * 4) as usize {
+        return None;
+    }
+    let x1 = bbox.x1.clamp(0.0, width as f32) as u32;
+    let y1 = bbox.y1.clamp(0.0, height as f32) as u32;
+    let x2 = bbox.x2.clamp(0.0, width as f32) as u32;
+    let y2 = bbox.y2.clamp(0.0, height as f32) as u32;
+    let (cw, ch) = (x2.saturating_sub(x1), y2.saturating_sub(y1));
+    if cw == 0 || ch == 0 {
+        return None;
+    }
+    let scale = (max_side as f32 / cw.max(ch) as f32).min(1.0);
+    let tw = ((cw as f32 * scale) as u32).max(1);
+    let th = ((ch as f32 * scale) as u32).max(1);
+    let mut pixels = Vec::with_capacity((tw * th * 4) as usize);
+    for ty in 0..th {
+        let sy = y1 + ty * ch / th;
+        for tx in 0..tw {
+            let sx = x1 + tx * cw / tw;
+            let i = ((sy * width + sx) * 4) as usize;
+            pixels.extend_from_slice(&rgba[i..i + 4]);
+        }
+    }
+    Some((tw, th, pixels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bbox(id: u32, x: f32) -> BBox {
+        BBox {
+            x1: x,
+            y1: 0.0,
+            x2: x + 10.0,
+            y2: 20.0,
+            confidence: 0.9,
+            class_id: id,
+        }
+    }
+
+    /// 逐帧累积帧数、画面速度与相邻特征的相似度; 过期轨迹被清除, 已标记的保留
+    #[test]
+    fn test_observe_and_expire() {
+        let start = Instant::now();
+        let now = Utc::now();
+        let mut history = TrackHistory::default();
+        let features = [vec![1.0, 0.0], vec![1.0, 0.0], vec![0.0, 1.0]];
+        for (i, feature) in features.iter().enumerate() {
+            let pts = start + Duration::from_millis(250 * i as u64);
+            let a = bbox(1, 10.0 * i as f32);
+            let b = bbox(2, 100.0);
+            let obs = [
+                Observation {
+                    bbox: &a,
+                    class: Some(0),
+                    speed: None,
+                    reid: Some(feature),
+                    keypoints: None,
+                },
+                Observation {
+                    bbox: &b,
+                    class: None,
+                    speed: None,
+                    reid: None,
+                    keypoints: None,
+                },
+            ];
+            history.observe(pts, now, &obs);
+        }
+        history.select(Some(1));
+        let track = history.selected().unwrap();
+        assert_eq!((track.frames, track.class), (3, Some(0)));
+        assert_eq!(track.age(), Duration::from_millis(500));
+        assert_eq!(track.velocity(), Some((40.0, 0.0)));
+        assert_eq!(Vec::from(track.similarity.clone()), vec![1.0, 0.0]);
+
+        assert!(history.toggle_flag(2));
+        history.select(None);
+        history.observe(start + TRACK_EXPIRY * 2, now, &[]);
+        assert!(history.selected().is_none());
+        history.select(Some(1));
+        assert_eq!(history.selected_id(), None);
+        assert!(history.is_flagged(2));
+    }
+
+    /// 缩略图只为选中轨迹裁剪, 按间隔节流, 尺寸按长边缩放; 帧晚于检测结果到达时按 PTS 找回框
+    #[test]
+    fn test_capture_thumbnail() {
+        let (w, h) = (400u32, 300u32);
+        let rgba = vec![128u8; (w * h * 4) as usize];
+        let start = Instant::now();
+        let mut history = TrackHistory::default();
+        let big = BBox {
+            x1: 0.0,
+            y1: 0.0,
+            x2: 100.0,
+            y2: 200.0,
+            confidence: 0.9,
+            class_id: 7,
+        };
+        for i in 0..4u64 {
+            let pts = start + Duration::from_millis(500 * i);
+            let obs = Observation {
+                bbox: &big,
+                class: None,
+                speed: None,
+                reid: None,
+                keypoints: None,
+            };
+            history.observe(pts, Utc::now(), &[obs]);
+            if i == 0 {
+                history.capture_thumbnail(pts, &rgba, w, h);
+                assert!(history.selected().is_none());
+                history.select(Some(7));
+            } else {
+                // 画面比检测结果晚一帧呈现
+                let frame_pts = pts - Duration::from_millis(500);
+                history.capture_thumbnail(frame_pts, &rgba, w, h);
+            }
+        }
+        history.capture_thumbnail(start + Duration::from_secs(5), &rgba, w, h);
+        let thumbnails = &history.selected().unwrap().thumbnails;
+        assert_eq!(thumbnails.len(), 2); // 0ms, 1000ms
+        assert_eq!(
+            (thumbnails[0].width, thumbnails[0].height),
+            (48, THUMBNAIL_SIZE)
+        );
+        assert_eq!(thumbnails[0].rgba.len(), 48 * 96 * 4);
+    }
+
+    /// 关键点质量取框内可见点最多的骨架
+    #[test]
+    fn test_keypoint_quality() {
+        let b = bbox(1, 0.0);
+        let outside = PoseKeypoints {
+            points: vec![(50.0, 5.0, 0.9); 3],
+        };
+        let inside = PoseKeypoints {
+            points: vec![(5.0, 5.0, 0.9), (5.0, 10.0, 0.5), (5.0, 15.0, 0.1)],
+        };
+        let q = keypoint_quality(&b, &[outside.clone(), inside]).unwrap();
+        assert_eq!((q.visible, q.total), (2, 3));
+        assert!((q.mean_conf - 0.5).abs() < 1e-6);
+        assert!(keypoint_quality(&b, &[outside]).is_none());
+    }
+}
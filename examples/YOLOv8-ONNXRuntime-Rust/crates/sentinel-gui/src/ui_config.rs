@@ -0,0 +1,504 @@
+//! 跟踪器配置 - 通过JSON文件调整参数
+//! 画面方向配置 - 按输入源保存旋转/镜像 (定义在 utils::orientation, 视频输入层也使用)
+//! 会话状态 - 退出时的模型/阈值/视图/输入源, 下次启动时恢复
+//! 配置档案 - 成组的模型/分辨率/阈值/类别/区域/跟踪参数, 一键切换或按时段自动切换
+
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::detection::{LadderConfig, SmoothingConfig, TrackerParams};
+use crate::i18n::Language;
+use crate::input::InputSource;
+use crate::renderer::pacing::PacingMode;
+use crate::utils::colormap::Palette;
+pub use crate::utils::orientation::{OrientationConfig, ORIENTATION_CONFIG_PATH};
+
+/// 默认配置文件路径
+pub const TRACKER_CONFIG_PATH: &str = "tracker_config.json";
+
+/// 会话状态文件路径
+pub const SESSION_STATE_PATH: &str = "session_state.json";
+
+/// 配置档案文件路径
+pub const PROFILES_CONFIG_PATH: &str = "profiles.json";
+
+/// 跟踪器参数配置 (旧配置文件缺少的字段按默认值补齐)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrackerConfig {
+    // === 检测参数 ===
+    pub detection_conf_threshold: f32, // 检测置信度阈值
+    pub detection_iou_threshold: f32,  // NMS IOU阈值
+    pub post_process_threshold: f32,   // 后处理置信度过滤
+
+    // === ByteTrack参数 ===
+    pub bytetrack_max_lost_frames: u32,      // 最大丢失帧数
+    pub bytetrack_min_hits: u32,             // 确认轨迹所需命中次数
+    pub bytetrack_high_score_threshold: f32, // 高分阈值
+    pub bytetrack_low_score_threshold: f32,  // 低分阈值
+    pub bytetrack_high_iou_threshold: f32,   // 高分IOU阈值
+    pub bytetrack_low_iou_threshold: f32,    // 低分IOU阈值
+    pub bytetrack_kalman_obs_noise: f32,     // 卡尔曼观测噪声
+
+    // === DeepSort参数 ===
+    pub deepsort_max_lost_frames: u32,           // 最大丢失帧数
+    pub deepsort_min_hits: u32,                  // 确认轨迹所需命中次数
+    pub deepsort_iou_threshold: f32,             // IOU阈值
+    pub deepsort_unconfirmed_iou_threshold: f32, // 未确认轨迹IOU阈值
+    pub deepsort_appearance_threshold: f32,      // 外观相似度阈值
+    pub deepsort_reid_skip_frames: u32,          // ReID跳帧间隔
+    pub deepsort_reid_max_count: usize,          // 每帧最大ReID提取数
+    pub deepsort_kalman_obs_noise: f32,          // 卡尔曼观测噪声
+
+    // === 卡尔曼滤波参数 ===
+    pub kalman_process_noise: f32,        // 过程噪声 q
+    pub kalman_velocity_decay: f32,       // 速度衰减
+    pub kalman_stationary_threshold: f32, // 静止判定阈值(像素)
+
+    // === 显示平滑 ===
+    pub display_smoothing: SmoothingConfig, // 滞回保持阈值与新目标确认帧数 (可按类别)
+
+    // === 动态分辨率 ===
+    pub resolution_ladder: LadderConfig, // 按目标大小升降推理输入尺寸
+}
+
+impl Default for TrackerConfig {
+    fn default() -> Self {
+        Self {
+            // 检测参数
+            detection_conf_threshold: 0.10,
+            detection_iou_threshold: 0.45,
+            post_process_threshold: 0.01,
+
+            // ByteTrack
+            bytetrack_max_lost_frames: 60,
+            bytetrack_min_hits: 1,
+            bytetrack_high_score_threshold: 0.4,
+            bytetrack_low_score_threshold: 0.1,
+            bytetrack_high_iou_threshold: 0.4,
+            bytetrack_low_iou_threshold: 0.3,
+            bytetrack_kalman_obs_noise: 0.5,
+
+            // DeepSort
+            deepsort_max_lost_frames: 90,
+            deepsort_min_hits: 2,
+            deepsort_iou_threshold: 0.2,
+            deepsort_unconfirmed_iou_threshold: 0.05,
+            deepsort_appearance_threshold: 0.15,
+            deepsort_reid_skip_frames: 3,
+            deepsort_reid_max_count: 5,
+            deepsort_kalman_obs_noise: 1.5,
+
+            // 卡尔曼滤波
+            kalman_process_noise: 0.1,
+            kalman_velocity_decay: 0.95,
+            kalman_stationary_threshold: 2.0,
+
+            // 显示平滑
+            display_smoothing: SmoothingConfig::default(),
+
+            // 动态分辨率
+            resolution_ladder: LadderConfig::default(),
+        }
+    }
+}
+
+impl TrackerConfig {
+    /// ByteTrack 生命周期参数
+    pub fn bytetrack_params(&self) -> TrackerParams {
+        TrackerParams {
+            max_lost_frames: self.bytetrack_max_lost_frames,
+            min_hits: self.bytetrack_min_hits,
+            high_iou_threshold: self.bytetrack_high_iou_threshold,
+            low_iou_threshold: self.bytetrack_low_iou_threshold,
+            high_score_threshold: self.bytetrack_high_score_threshold,
+            low_score_threshold: self.bytetrack_low_score_threshold,
+        }
+    }
+
+    pub fn set_bytetrack_params(&mut self, params: TrackerParams) {
+        self.bytetrack_max_lost_frames = params.max_lost_frames;
+        self.bytetrack_min_hits = params.min_hits;
+        self.bytetrack_high_iou_threshold = params.high_iou_threshold;
+        self.bytetrack_low_iou_threshold = params.low_iou_threshold;
+        self.bytetrack_high_score_threshold = params.high_score_threshold;
+        self.bytetrack_low_score_threshold = params.low_score_threshold;
+    }
+
+    /// DeepSort 生命周期参数 (分数阈值不使用)
+    pub fn deepsort_params(&self) -> TrackerParams {
+        TrackerParams {
+            max_lost_frames: self.deepsort_max_lost_frames,
+            min_hits: self.deepsort_min_hits,
+            high_iou_threshold: self.deepsort_iou_threshold,
+            low_iou_threshold: self.deepsort_unconfirmed_iou_threshold,
+            ..TrackerParams::deepsort()
+        }
+    }
+
+    pub fn set_deepsort_params(&mut self, params: TrackerParams) {
+        self.deepsort_max_lost_frames = params.max_lost_frames;
+        self.deepsort_min_hits = params.min_hits;
+        self.deepsort_iou_threshold = params.high_iou_threshold;
+        self.deepsort_unconfirmed_iou_threshold = params.low_iou_threshold;
+    }
+
+    /// 从JSON文件加载配置
+    pub fn load(path: &str) -> Self {
+        match fs::read_to_string(path) {
+            Ok(json) => match serde_json::from_str(&json) {
+                Ok(config) => {
+                    println!("✅ 配置已从 {} 加载", path);
+                    config
+                }
+                Err(e) => {
+                    eprintln!("⚠️  配置文件解析失败: {}, 使用默认值", e);
+                    Self::default()
+                }
+            },
+            Err(_) => {
+                println!("📝 配置文件不存在,创建默认配置...");
+                let config = Self::default();
+                config.save(path);
+                config
+            }
+        }
+    }
+
+    /// 保存配置到JSON文件
+    pub fn save(&self, path: &str) {
+        match serde_json::to_string_pretty(self) {
+            Ok(json) => {
+                if let Err(e) = fs::write(path, json) {
+                    eprintln!("❌ 保存配置失败: {}", e);
+                } else {
+                    println!("💾 配置已保存到 {}", path);
+                }
+            }
+            Err(e) => eprintln!("❌ 序列化配置失败: {}", e),
+        }
+    }
+
+    /// 打印当前配置
+    pub fn print_summary(&self) {
+        println!("\n🎛️  当前跟踪器配置:");
+        println!("  检测置信度: {:.2}", self.detection_conf_threshold);
+        println!("  ByteTrack最大丢失帧: {}", self.bytetrack_max_lost_frames);
+        println!("  DeepSort最大丢失帧: {}", self.deepsort_max_lost_frames);
+        println!("  ReID跳帧间隔: {}", self.deepsort_reid_skip_frames);
+        println!(
+            "  卡尔曼观测噪声(ByteTrack): {:.2}",
+            self.bytetrack_kalman_obs_noise
+        );
+        println!(
+            "  卡尔曼观测噪声(DeepSort): {:.2}",
+            self.deepsort_kalman_obs_noise
+        );
+        let smoothing = &self.display_smoothing;
+        println!(
+            "  显示平滑: {} (保持比例 {:.2}, 确认 {} 帧, {}个类别单独配置)\n",
+            if smoothing.enabled {
+                "启用"
+            } else {
+                "禁用"
+            },
+            smoothing.keep_ratio,
+            smoothing.confirm_frames,
+            smoothing.classes.len()
+        );
+    }
+}
+
+/// 界面会话状态 (旧文件缺少的字段按默认值补齐)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    // === 模型与参数 ===
+    pub model: Option<String>,   // 检测模型 (控制面板中的名称, 如 yolov8n)
+    pub tracker: Option<String>, // 跟踪算法 (DeepSORT/ByteTrack/无)
+    pub confidence_threshold: f32,
+    pub iou_threshold: f32,
+    pub pose_enabled: bool,
+    pub detection_enabled: bool,
+    pub crowd_enabled: bool,
+
+    // === 界面与视图 ===
+    pub language: Language,              // 界面语言
+    pub window_size: Option<(i32, i32)>, // 窗口尺寸 (宽, 高)
+    pub zoom_scale: f32,
+    pub pan_offset: (f32, f32),
+    pub display_view: u32,  // 渲染的逻辑流 (鱼眼虚拟视图)
+    pub palette: Palette,   // 显示调色板 (热成像伪彩色)
+    pub pacing: PacingMode, // 帧呈现模式 (最新帧/按 PTS/平滑)
+
+    // === 输入源 ===
+    pub input_source_type: usize, // 0=RTSP, 1=摄像头, 2=桌面捕获, 3=工业相机, 4=共享内存, 5=合成场景
+    pub rtsp_url: String,
+    pub camera: Option<String>, // 选中的摄像头名称 (索引可能随插拔变化)
+    pub source: Option<InputSource>, // 退出时正在播放的输入源, 启动后自动恢复
+}
+
+impl Default for SessionState {
+    fn default() -> Self {
+        Self {
+            model: None,
+            tracker: None,
+            confidence_threshold: 0.5,
+            iou_threshold: 0.45,
+            pose_enabled: false,
+            detection_enabled: true,
+            crowd_enabled: true,
+            language: Language::default(),
+            window_size: None,
+            zoom_scale: 1.0,
+            pan_offset: (0.0, 0.0),
+            display_view: 0,
+            palette: Palette::None,
+            pacing: PacingMode::default(),
+            input_source_type: 0,
+            rtsp_url: String::new(),
+            camera: None,
+            source: None,
+        }
+    }
+}
+
+impl SessionState {
+    /// 从JSON文件加载, 文件不存在或解析失败时为 None
+    pub fn load(path: &str) -> Option<Self> {
+        let json = fs::read_to_string(path).ok()?;
+        match serde_json::from_str(&json) {
+            Ok(state) => {
+                println!("✅ 会话状态已从 {} 加载", path);
+                Some(state)
+            }
+            Err(e) => {
+                eprintln!("⚠️  会话状态解析失败: {}, 使用默认值", e);
+                None
+            }
+        }
+    }
+
+    /// 保存到JSON文件 (定期调用, 成功时不打印)
+    pub fn save(&self, path: &str) {
+        match serde_json::to_string_pretty(self) {
+            Ok(json) => {
+                if let Err(e) = fs::write(path, json) {
+                    eprintln!("❌ 保存会话状态失败: {}", e);
+                }
+            }
+            Err(e) => eprintln!("❌ 序列化会话状态失败: {}", e),
+        }
+    }
+}
+
+/// 配置档案 (如 "入口-夜间"), 未设置的项切换时保持当前值
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    pub model: Option<String>,   // 检测模型 (控制面板中的名称, 如 yolov8n)
+    pub input_size: Option<u32>, // 推理输入尺寸 (32 的倍数)
+    pub confidence_threshold: Option<f32>,
+    pub iou_threshold: Option<f32>,
+    pub classes: Option<Vec<u32>>, // 检测的类别ID, 空表示全部类别
+    pub zones: Option<String>,     // 区域规则配置文件 (zones.json 格式)
+    pub tracker: Option<String>,   // 跟踪算法 (DeepSORT/ByteTrack/无)
+    pub tracker_config: Option<TrackerConfig>, // 轨迹生命周期参数
+}
+
+/// 按时段自动切换的档案 (本地时间 "HH:MM", 结束早于开始时跨越午夜)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProfileSchedule {
+    pub profile: String,
+    pub start: String,
+    pub end: String,
+}
+
+impl ProfileSchedule {
+    fn times(&self) -> Option<(NaiveTime, NaiveTime)> {
+        let parse = |s: &str| NaiveTime::parse_from_str(s, "%H:%M").ok();
+        Some((parse(&self.start)?, parse(&self.end)?))
+    }
+
+    /// 时间格式是否正确
+    pub fn is_valid(&self) -> bool {
+        self.times().is_some()
+    }
+
+    /// 时刻是否在时段 [start, end) 内, 时间格式错误时不匹配
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        match self.times() {
+            Some((start, end)) if start <= end => start <= time && time < end,
+            Some((start, end)) => time >= start || time < end,
+            None => false,
+        }
+    }
+}
+
+/// 配置档案与自动切换时段 (profiles.json)
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileConfig {
+    pub profiles: Vec<Profile>,
+    pub schedule: Vec<ProfileSchedule>,
+}
+
+impl ProfileConfig {
+    /// 从JSON文件加载, 文件不存在时为空
+    pub fn load(path: &str) -> Self {
+        match fs::read_to_string(path) {
+            Ok(json) => match serde_json::from_str::<Self>(&json) {
+                Ok(mut config) => {
+                    config.validate();
+                    println!(
+                        "✅ 配置档案已从 {} 加载 ({}个档案, {}个时段)",
+                        path,
+                        config.profiles.len(),
+                        config.schedule.len()
+                    );
+                    config
+                }
+                Err(e) => {
+                    eprintln!("⚠️  配置档案解析失败: {}, 不启用配置档案", e);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// 保存到JSON文件
+    pub fn save(&self, path: &str) {
+        match serde_json::to_string_pretty(self) {
+            Ok(json) => {
+                if let Err(e) = fs::write(path, json) {
+                    eprintln!("❌ 保存配置档案失败: {}", e);
+                } else {
+                    println!("💾 配置档案已保存到 {}", path);
+                }
+            }
+            Err(e) => eprintln!("❌ 序列化配置档案失败: {}", e),
+        }
+    }
+
+    /// 忽略非法的输入尺寸, 以及引用未知档案或时间格式错误的时段
+    fn validate(&mut self) {
+        for profile in &mut self.profiles {
+            if let Some(size) = profile.input_size.filter(|s| *s == 0 || s % 32 != 0) {
+                eprintln!(
+                    "⚠️  配置档案 {} 的输入尺寸 {} 不是 32 的倍数, 已忽略",
+                    profile.name, size
+                );
+                profile.input_size = None;
+            }
+        }
+        let names: Vec<String> = self.profiles.iter().map(|p| p.name.clone()).collect();
+        self.schedule.retain(|s| {
+            let valid = names.contains(&s.profile) && s.times().is_some();
+            if !valid {
+                eprintln!(
+                    "⚠️  配置档案时段无效 ({} {}-{}), 已忽略",
+                    s.profile, s.start, s.end
+                );
+            }
+            valid
+        });
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    /// 添加档案, 同名档案被替换
+    pub fn upsert(&mut self, profile: Profile) {
+        match self.profiles.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => *existing = profile,
+            None => self.profiles.push(profile),
+        }
+    }
+
+    /// 时刻所在时段的档案 (时段重叠时取靠前的), 不在任何时段内为 None
+    pub fn scheduled(&self, time: NaiveTime) -> Option<&Profile> {
+        self.schedule
+            .iter()
+            .find(|s| s.contains(time))
+            .and_then(|s| self.get(&s.profile))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 会话状态往返序列化一致, 旧文件缺少的字段取默认值
+    #[test]
+    fn test_session_state_roundtrip() {
+        let state = SessionState {
+            model: Some("yolov8s".to_string()),
+            tracker: Some("ByteTrack".to_string()),
+            confidence_threshold: 0.3,
+            language: Language::EnUs,
+            window_size: Some((1920, 1080)),
+            zoom_scale: 2.0,
+            pan_offset: (12.0, -8.0),
+            palette: Palette::Ironbow,
+            pacing: PacingMode::Smooth,
+            input_source_type: 1,
+            camera: Some("USB Camera".to_string()),
+            source: Some(InputSource::Camera(1, "USB Camera".to_string())),
+            ..SessionState::default()
+        };
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(serde_json::from_str::<SessionState>(&json).unwrap(), state);
+
+        let old: SessionState = serde_json::from_str(r#"{"model":"yolov8m"}"#).unwrap();
+        assert_eq!(old.model.as_deref(), Some("yolov8m"));
+        assert_eq!(old.iou_threshold, 0.45);
+        assert!(old.detection_enabled);
+        assert_eq!(old.source, None);
+        assert_eq!(old.language, Language::ZhCn);
+    }
+
+    /// 时段跨越午夜, 重叠时取靠前的时段, 引用未知档案或格式错误的时段被忽略
+    #[test]
+    fn test_profile_schedule() {
+        let mut config: ProfileConfig = serde_json::from_str(
+            r#"{
+                "profiles": [
+                    {"name": "Entrance-Night", "model": "yolov8s", "input_size": 960},
+                    {"name": "Parking-Day", "classes": [2, 7], "input_size": 500}
+                ],
+                "schedule": [
+                    {"profile": "Entrance-Night", "start": "22:00", "end": "06:00"},
+                    {"profile": "Parking-Day", "start": "06:00", "end": "22:00"},
+                    {"profile": "Parking-Day", "start": "05:00", "end": "23:00"},
+                    {"profile": "Missing", "start": "00:00", "end": "23:59"},
+                    {"profile": "Parking-Day", "start": "7am", "end": "9pm"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        config.validate();
+        assert_eq!(config.schedule.len(), 3);
+        assert_eq!(config.profiles[1].input_size, None);
+
+        let at = |h, m| {
+            config
+                .scheduled(NaiveTime::from_hms_opt(h, m, 0).unwrap())
+                .map(|p| p.name.as_str())
+        };
+        assert_eq!(at(23, 30), Some("Entrance-Night"));
+        assert_eq!(at(5, 59), Some("Entrance-Night"));
+        assert_eq!(at(6, 0), Some("Parking-Day"));
+        assert_eq!(at(21, 59), Some("Parking-Day"));
+
+        config.upsert(Profile {
+            name: "Parking-Day".to_string(),
+            ..Profile::default()
+        });
+        assert_eq!(config.profiles.len(), 2);
+        assert_eq!(config.get("Parking-Day").unwrap().classes, None);
+    }
+}
@@ -0,0 +1,850 @@
+//! 调试 HTTP API
+//!
+//! 标准库实现的调试接口 (WebSocket 握手只额外用到 sha1/base64), 在后台线程中逐个处理请求:
+//! - `GET /debug/xbus`: 事件总线快照 (各事件类型的订阅者、投递统计与队列长度)
+//! - `POST /debug/xbus/prune`: 清理目标已释放的弱引用/转发订阅者
+//! - `GET /api/schedule`: 布防模式与各逻辑流的布防状态
+//! - `POST /api/schedule/{auto,arm,disarm}`: 恢复按计划 / 手动强制布防 / 强制撤防
+//! - `GET /api/storage`: 各保留目标的磁盘占用与最近一次清理量
+//! - `GET /api/memory`: 内存占用 (RSS/帧池/纹理/ORT 会话) 与泄漏防护基线
+//! - `POST /api/clips?camera=view0&from=…&to=…&overlay=1`: 提交片段导出任务 (时间为 Unix 秒或 RFC 3339)
+//! - `GET /api/clips`: 片段导出任务列表 (状态与输出文件)
+//! - `GET /api/gallery`: 最近结束的轨迹及其最佳快照 (新条目在前)
+//! - `POST /api/profile?secs=10`: 开始按需性能剖析, 到时写出 chrome://tracing JSON
+//! - `GET /api/profile`: 剖析状态与最近一次报告路径
+//! - `POST /api/model?name=yolov8s`: 切换检测模型 (控制面板中的名称)
+//! - `GET /api/audit?limit=100`: 最近的操作审计记录 (新记录在前, 仅 admin)
+//! - `GET /onvif/metadata?since=…&view=0`: ONVIF 分析元数据拉取点 (XML, since 之后的帧)
+//! - `GET /ws/detections?view=0`: WebSocket 检测结果流 (每帧一条 JSON 文本消息, 连接在独立线程中推送)
+//!
+//! 鉴权 (api_tokens.json): 请求带 `Authorization: Bearer <令牌>` 或 `?token=<令牌>`, 按令牌的角色放行
+//! (浏览器的 WebSocket 不能设置请求头, 用查询参数传令牌):
+//! - viewer: 所有 GET (统计/状态/列表) 与 WebSocket 检测结果流
+//! - operator: 另可布防/撤防、导出片段、性能剖析、切换模型
+//! - admin: 全部接口, 包括调试、审计记录与今后的配置管理接口 (未列出的写操作默认只允许 admin)
+//!
+//! 成功的写操作记入审计日志, 来源为令牌名称 (未启用鉴权时为 anonymous)
+//!
+//! 未配置令牌时不做鉴权 (与旧版本一致), 此时只应监听本机地址
+
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use crossbeam_channel::RecvTimeoutError;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+
+use crate::analytics::gallery;
+use crate::audit::{self, AuditAction, AuditSource};
+use crate::clips::{self, ClipRequest};
+use crate::detection::detector::DetectionResult;
+use crate::detection::profiler::{self, DEFAULT_PROFILE_SECS};
+use crate::memory_guard;
+use crate::onvif::{self, MetadataObject};
+use crate::retention;
+use crate::scheduler::{self, ArmMode};
+use crate::xbus;
+
+/// 默认监听地址 (仅本机)
+pub const DEFAULT_API_ADDR: &str = "127.0.0.1:8090";
+
+/// 默认令牌文件
+pub const API_TOKENS_PATH: &str = "api_tokens.json";
+
+/// 单个请求的读写超时, 避免慢客户端阻塞后续请求
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 请求头最大行数
+const MAX_HEADER_LINES: usize = 100;
+
+/// WebSocket 检测结果流
+const WS_DETECTIONS_PATH: &str = "/ws/detections";
+
+/// 同时打开的 WebSocket 连接上限 (每个连接占一个推送线程)
+const MAX_WS_CLIENTS: usize = 8;
+
+/// 每个连接待发送的检测结果上限, 客户端读得慢时丢弃新结果, 不阻塞事件总线
+const WS_QUEUE_LEN: usize = 32;
+
+/// 没有检测结果时发送 ping 的间隔 (写失败即客户端已断开, 结束推送线程)
+const WS_PING_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 当前打开的 WebSocket 连接数
+static WS_CLIENTS: AtomicUsize = AtomicUsize::new(0);
+
+/// 已知路径 (方法不匹配时返回 405)
+const ROUTES: [&str; 15] = [
+    "/debug/xbus",
+    "/debug/xbus/prune",
+    "/api/schedule",
+    "/api/schedule/auto",
+    "/api/schedule/arm",
+    "/api/schedule/disarm",
+    "/api/storage",
+    "/api/memory",
+    "/api/clips",
+    "/api/gallery",
+    "/api/profile",
+    "/api/model",
+    "/api/audit",
+    "/onvif/metadata",
+    WS_DETECTIONS_PATH,
+];
+
+/// 角色 (按权限从低到高排序)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Viewer,   // 只读: 统计/状态/列表
+    Operator, // 日常操作: 布防撤防/片段导出/剖析/切换模型
+    Admin,    // 全部接口
+}
+
+/// 访问令牌
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub name: String, // 持有者 (日志显示, 不输出令牌本身)
+    pub token: String,
+    pub role: Role,
+}
+
+/// 令牌配置 (api_tokens.json), 没有令牌时不鉴权
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiAuth {
+    pub tokens: Vec<ApiToken>,
+}
+
+impl ApiAuth {
+    /// 从文件加载, 文件不存在时不鉴权; 解析失败时拒绝所有请求, 避免配置写错后接口裸奔
+    pub fn load(path: &str) -> Self {
+        match fs::read_to_string(path) {
+            Ok(json) => match serde_json::from_str::<Self>(&json) {
+                Ok(mut auth) => {
+                    auth.tokens.retain(|t| {
+                        if t.token.len() < 16 {
+                            eprintln!("⚠️  API 令牌 {} 过短 (至少 16 个字符), 已忽略", t.name);
+                        }
+                        t.token.len() >= 16
+                    });
+                    if auth.tokens.is_empty() {
+                        eprintln!("❌ {} 中没有有效的 API 令牌, 拒绝所有 API 请求", path);
+                        return Self::deny_all();
+                    }
+                    println!("🔐 API 令牌已从 {} 加载 ({}个)", path, auth.tokens.len());
+                    auth
+                }
+                Err(e) => {
+                    eprintln!("❌ API 令牌配置解析失败: {}, 拒绝所有 API 请求", e);
+                    Self::deny_all()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// 启用鉴权但没有任何有效令牌
+    fn deny_all() -> Self {
+        Self {
+            tokens: vec![ApiToken {
+                name: String::new(),
+                token: String::new(),
+                role: Role::Viewer,
+            }],
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.tokens.is_empty()
+    }
+
+    /// 令牌对应的持有者 (逐字节比较全部令牌, 耗时与匹配位置无关)
+    pub fn lookup(&self, token: &str) -> Option<&ApiToken> {
+        let mut found = None;
+        for t in &self.tokens {
+            if !t.token.is_empty() && constant_time_eq(t.token.as_bytes(), token.as_bytes()) {
+                found = Some(t);
+            }
+        }
+        found
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// 接口所需的最低角色: GET 为 viewer, 日常操作为 operator, 其余写操作为 admin
+pub fn required_role(method: &str, path: &str) -> Role {
+    let path = path.split_once('?').map_or(path, |(p, _)| p);
+    match (method, path) {
+        ("GET", "/debug/xbus" | "/api/audit") => Role::Admin,
+        ("GET", _) => Role::Viewer,
+        (
+            "POST",
+            "/api/schedule/auto"
+            | "/api/schedule/arm"
+            | "/api/schedule/disarm"
+            | "/api/clips"
+            | "/api/profile"
+            | "/api/model",
+        ) => Role::Operator,
+        _ => Role::Admin,
+    }
+}
+
+/// 鉴权: 通过时返回持有者名称 (未启用鉴权时为 None), 否则返回 401/403 响应
+pub fn authorize(
+    auth: &ApiAuth,
+    method: &str,
+    path: &str,
+    bearer: Option<&str>,
+) -> Result<Option<String>, Response> {
+    if !auth.is_enabled() {
+        return Ok(None);
+    }
+    let query = path.split_once('?').map_or("", |(_, q)| q);
+    let token = bearer.or_else(|| query_param(query, "token"));
+    let Some(holder) = token.and_then(|t| auth.lookup(t)) else {
+        return Err(Response::error(401, "missing or invalid token"));
+    };
+    if holder.role < required_role(method, path) {
+        return Err(Response::error(403, "insufficient role"));
+    }
+    Ok(Some(holder.name.clone()))
+}
+
+/// API 请求切换检测模型 (渲染器转交控制面板, 与面板中选择模型等效)
+#[derive(Clone, Debug)]
+pub struct ModelSwitchRequest {
+    pub model: String,
+}
+
+/// API 响应 (状态码 + 正文, 除 ONVIF 元数据外均为 JSON)
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Response {
+    fn json<T: Serialize>(value: &T) -> Self {
+        match serde_json::to_string_pretty(value) {
+            Ok(body) => Response {
+                status: 200,
+                content_type: "application/json",
+                body,
+            },
+            Err(e) => Response::error(500, &e.to_string()),
+        }
+    }
+
+    fn xml(body: String) -> Self {
+        Response {
+            status: 200,
+            content_type: "application/xml",
+            body,
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Response {
+            status,
+            content_type: "application/json",
+            body: serde_json::json!({ "error": message }).to_string(),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        reason(self.status)
+    }
+}
+
+/// 状态码对应的原因短语 (HLS 服务共用)
+pub(crate) fn reason(status: u16) -> &'static str {
+    match status {
+        101 => "Switching Protocols",
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
+
+/// 在后台线程启动调试 API, 返回实际监听地址 (端口为 0 时由系统分配)
+pub fn spawn<A: ToSocketAddrs>(addr: A, auth: ApiAuth) -> std::io::Result<SocketAddr> {
+    let listener = TcpListener::bind(addr)?;
+    let local = listener.local_addr()?;
+    if !auth.is_enabled() && !local.ip().is_loopback() {
+        eprintln!(
+            "⚠️ 调试 API 监听非本机地址 {} 且未配置令牌 ({}), 同一网络内任何人都可访问",
+            local, API_TOKENS_PATH
+        );
+    }
+    std::thread::Builder::new()
+        .name("debug-api".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                let result = stream.and_then(|s| handle(s, &auth));
+                if let Err(e) = result {
+                    eprintln!("⚠️ 调试 API 请求失败: {}", e);
+                }
+            }
+        })?;
+    println!("🌐 调试 API 已启动: http://{}/debug/xbus", local);
+    Ok(local)
+}
+
+/// 按方法与路径分发请求 (查询参数只用于片段导出)
+pub fn route(method: &str, path: &str) -> Response {
+    let (path, query) = path.split_once('?').unwrap_or((path, ""));
+    match (method, path) {
+        ("GET", "/debug/xbus") => Response::json(&xbus::snapshot()),
+        ("POST", "/debug/xbus/prune") => {
+            Response::json(&serde_json::json!({ "pruned": xbus::prune() }))
+        }
+        ("GET", "/api/schedule") => match scheduler::schedule_status() {
+            Some(status) => Response::json(&status),
+            None => Response::error(503, "scheduler not running"),
+        },
+        ("POST", "/api/schedule/auto" | "/api/schedule/arm" | "/api/schedule/disarm") => {
+            let mode = match path {
+                "/api/schedule/arm" => ArmMode::Armed,
+                "/api/schedule/disarm" => ArmMode::Disarmed,
+                _ => ArmMode::Auto,
+            };
+            if scheduler::set_arm_mode(mode) {
+                Response::json(&scheduler::schedule_status())
+            } else {
+                Response::error(503, "scheduler not running")
+            }
+        }
+        ("GET", "/api/storage") => match retention::retention_status() {
+            Some(status) => Response::json(&status),
+            None => Response::error(503, "retention not enabled"),
+        },
+        ("GET", "/api/memory") => match memory_guard::memory_status() {
+            Some(status) => Response::json(&status),
+            None => Response::error(503, "memory monitor not running"),
+        },
+        ("GET", "/api/clips") => match clips::clip_jobs() {
+            Some(jobs) => Response::json(&jobs),
+            None => Response::error(503, "clip export not enabled"),
+        },
+        ("GET", "/api/gallery") => match gallery::gallery_entries() {
+            Some(entries) => Response::json(&entries),
+            None => Response::error(503, "gallery not enabled"),
+        },
+        ("GET", "/api/profile") => Response::json(&profiler::status()),
+        ("GET", "/api/audit") => {
+            let limit = query_param(query, "limit").and_then(|n| n.parse().ok());
+            Response::json(&audit::recent(limit.unwrap_or(100)))
+        }
+        ("GET", "/onvif/metadata") => {
+            let since = match query_param(query, "since").map(clips::parse_time) {
+                Some(None) => {
+                    return Response::error(400, "since must be Unix seconds or RFC 3339")
+                }
+                since => since.flatten(),
+            };
+            let view = match query_param(query, "view").map(str::parse) {
+                Some(Err(_)) => return Response::error(400, "view must be an integer"),
+                view => view.and_then(Result::ok),
+            };
+            match onvif::pull(since, view) {
+                Some(xml) => Response::xml(xml),
+                None => Response::error(503, "onvif metadata not enabled"),
+            }
+        }
+        // 升级请求在 handle 中接管连接, 到这里说明不是 WebSocket 握手
+        ("GET", WS_DETECTIONS_PATH) => Response::error(400, "websocket upgrade required"),
+        ("POST", "/api/profile") => {
+            let secs = match query_param(query, "secs") {
+                Some(secs) => match secs.parse() {
+                    Ok(secs) => secs,
+                    Err(_) => return Response::error(400, "secs must be an integer"),
+                },
+                None => DEFAULT_PROFILE_SECS,
+            };
+            if profiler::start(secs) {
+                Response::json(&profiler::status())
+            } else {
+                Response::error(409, "profiling already running")
+            }
+        }
+        ("POST", "/api/model") => match query_param(query, "name") {
+            Some(model) if !model.is_empty() => {
+                xbus::post(ModelSwitchRequest {
+                    model: model.to_string(),
+                });
+                Response::json(&serde_json::json!({ "model": model, "status": "requested" }))
+            }
+            _ => Response::error(400, "expected name"),
+        },
+        ("POST", "/api/clips") => {
+            if clips::clip_jobs().is_none() {
+                return Response::error(503, "clip export not enabled");
+            }
+            match clip_request(query) {
+                Some(request) => match clips::request_clip(request) {
+                    Ok(job) => Response::json(&job),
+                    Err(e) => Response::error(400, &format!("{:#}", e)),
+                },
+                None => Response::error(400, "expected camera, from and to"),
+            }
+        }
+        (_, path) if ROUTES.contains(&path) => Response::error(405, "method not allowed"),
+        _ => Response::error(404, "not found"),
+    }
+}
+
+/// 写操作对应的审计操作类型
+fn audit_action(path: &str) -> AuditAction {
+    match path {
+        "/api/model" => AuditAction::ModelSwitch,
+        "/api/clips" => AuditAction::ClipExport,
+        "/api/profile" => AuditAction::Profiling,
+        p if p.starts_with("/api/schedule/") => AuditAction::ArmMode,
+        _ => AuditAction::Debug,
+    }
+}
+
+/// 审计记录中的请求 (去掉查询参数中的令牌)
+fn audit_detail(method: &str, path: &str) -> String {
+    let (path, query) = path.split_once('?').unwrap_or((path, ""));
+    let query: Vec<&str> = query
+        .split('&')
+        .filter(|pair| !pair.is_empty() && !pair.starts_with("token="))
+        .collect();
+    if query.is_empty() {
+        format!("{} {}", method, path)
+    } else {
+        format!("{} {}?{}", method, path, query.join("&"))
+    }
+}
+
+/// 查询参数中某个键的值 (不做 URL 解码)
+pub(crate) fn query_param<'a>(query: &'a str, key: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v)
+}
+
+/// 从查询参数构造片段导出请求, 缺少参数或时间无法解析时为 None
+fn clip_request(query: &str) -> Option<ClipRequest> {
+    Some(ClipRequest {
+        camera: query_param(query, "camera")?.to_string(),
+        from: clips::parse_time(query_param(query, "from")?)?,
+        to: clips::parse_time(query_param(query, "to")?)?,
+        overlay: matches!(query_param(query, "overlay"), Some("1" | "true")),
+    })
+}
+
+/// 读取请求行与请求头, 鉴权后分发, 写回响应后关闭连接
+fn handle(stream: TcpStream, auth: &ApiAuth) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // 调试接口不接收请求体, 读完请求头即可 (只保留 Authorization 与 WebSocket 握手头)
+    let mut line = String::new();
+    let mut bearer = None;
+    let mut upgrade = false;
+    let mut ws_key = None;
+    for _ in 0..MAX_HEADER_LINES {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let (name, value) = (name.trim(), value.trim());
+            if name.eq_ignore_ascii_case("authorization") {
+                bearer = value.strip_prefix("Bearer ").map(str::to_string);
+            } else if name.eq_ignore_ascii_case("upgrade") {
+                upgrade = value.eq_ignore_ascii_case("websocket");
+            } else if name.eq_ignore_ascii_case("sec-websocket-key") {
+                ws_key = Some(value.to_string());
+            }
+        }
+    }
+    let ws_key = ws_key.filter(|_| upgrade);
+
+    let mut parts = request_line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => match authorize(auth, method, path, bearer.as_deref()) {
+            // 鉴权通过的 WebSocket 握手: 连接交给推送线程
+            Ok(_) if method == "GET" && path.split('?').next() == Some(WS_DETECTIONS_PATH) => {
+                match ws_key {
+                    Some(key) => return stream_detections(stream, &key, path),
+                    None => route(method, path),
+                }
+            }
+            Ok(holder) => {
+                // 写操作记录持有者 (不记录令牌与查询参数)
+                let route_path = path.split_once('?').map_or(path, |(p, _)| p);
+                if let Some(holder) = holder.as_ref().filter(|_| method != "GET") {
+                    println!("🔐 API {} {} ({})", method, route_path, holder);
+                }
+                let response = route(method, path);
+                if method != "GET" && response.status == 200 {
+                    let user = holder.unwrap_or_else(|| "anonymous".to_string());
+                    audit::record(
+                        AuditSource::Api { user },
+                        audit_action(route_path),
+                        audit_detail(method, path),
+                    );
+                }
+                response
+            }
+            Err(response) => response,
+        },
+        _ => Response::error(400, "bad request"),
+    };
+
+    write_response(&stream, &response)
+}
+
+/// 写回响应并结束连接
+fn write_response(mut stream: &TcpStream, response: &Response) -> std::io::Result<()> {
+    let challenge = if response.status == 401 {
+        "WWW-Authenticate: Bearer\r\n"
+    } else {
+        ""
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}; charset=utf-8\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n",
+        response.status,
+        response.reason(),
+        response.content_type,
+        response.body.len(),
+        challenge
+    )?;
+    stream.write_all(response.body.as_bytes())?;
+    stream.flush()
+}
+
+/// WebSocket 推送的一帧检测结果
+#[derive(Debug, Serialize)]
+struct DetectionMessage {
+    view: u32,
+    time: DateTime<Utc>, // 采集时间
+    objects: Vec<MetadataObject>,
+}
+
+impl DetectionMessage {
+    fn from_result(result: &DetectionResult) -> Self {
+        Self {
+            view: result.view,
+            time: result.trace.captured_at,
+            objects: MetadataObject::from_result(result),
+        }
+    }
+}
+
+/// 占用一个 WebSocket 连接名额, drop 时归还
+struct WsSlot;
+
+impl WsSlot {
+    fn acquire() -> Option<Self> {
+        let taken = WS_CLIENTS.fetch_add(1, Ordering::SeqCst);
+        if taken >= MAX_WS_CLIENTS {
+            WS_CLIENTS.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(WsSlot)
+    }
+}
+
+impl Drop for WsSlot {
+    fn drop(&mut self) {
+        WS_CLIENTS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// 完成 WebSocket 握手, 在独立线程中推送检测结果 (可按 `view` 过滤), 客户端断开后结束
+fn stream_detections(stream: TcpStream, key: &str, path: &str) -> std::io::Result<()> {
+    let query = path.split_once('?').map_or("", |(_, q)| q);
+    let view = match query_param(query, "view").map(str::parse::<u32>) {
+        Some(Err(_)) => {
+            return write_response(&stream, &Response::error(400, "view must be an integer"))
+        }
+        view => view.and_then(Result::ok),
+    };
+    let Some(slot) = WsSlot::acquire() else {
+        return write_response(&stream, &Response::error(503, "too many websocket clients"));
+    };
+
+    let mut handshake = &stream;
+    write!(
+        handshake,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        websocket_accept(key)
+    )?;
+
+    let (tx, rx) = crossbeam_channel::bounded(WS_QUEUE_LEN);
+    let subscription = xbus::subscribe::<DetectionResult, _>(move |result| {
+        if view.is_none_or(|v| v == result.view) {
+            let _ = tx.try_send(DetectionMessage::from_result(result));
+        }
+    });
+    std::thread::Builder::new()
+        .name("debug-api-ws".to_string())
+        .spawn(move || {
+            let _slot = slot;
+            let _subscription = subscription;
+            loop {
+                let sent = match rx.recv_timeout(WS_PING_INTERVAL) {
+                    Ok(message) => match serde_json::to_string(&message) {
+                        Ok(json) => write_ws_frame(&stream, WS_OPCODE_TEXT, json.as_bytes()),
+                        Err(_) => continue,
+                    },
+                    Err(RecvTimeoutError::Timeout) => write_ws_frame(&stream, WS_OPCODE_PING, &[]),
+                    Err(RecvTimeoutError::Disconnected) => break,
+                };
+                if sent.is_err() {
+                    break;
+                }
+            }
+        })?;
+    Ok(())
+}
+
+const WS_OPCODE_TEXT: u8 = 0x1;
+const WS_OPCODE_PING: u8 = 0x9;
+
+/// 写一个不分片、不加掩码的服务端帧 (RFC 6455 5.2)
+fn write_ws_frame(mut stream: impl Write, opcode: u8, payload: &[u8]) -> std::io::Result<()> {
+    let mut header = vec![0x80 | opcode];
+    match payload.len() {
+        n if n < 126 => header.push(n as u8),
+        n if n <= u16::MAX as usize => {
+            header.push(126);
+            header.extend_from_slice(&(n as u16).to_be_bytes());
+        }
+        n => {
+            header.push(127);
+            header.extend_from_slice(&(n as u64).to_be_bytes());
+        }
+    }
+    stream.write_all(&header)?;
+    stream.write_all(payload)?;
+    stream.flush()
+}
+
+/// 握手应答中的 Sec-WebSocket-Accept: base64(SHA-1(key + GUID)) (RFC 6455 4.2.2)
+fn websocket_accept(key: &str) -> String {
+    const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+    let mut sha1 = Sha1::new();
+    sha1.update(key.trim().as_bytes());
+    sha1.update(GUID.as_bytes());
+    BASE64.encode(sha1.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    struct ApiProbe;
+
+    /// 总线快照经 HTTP 返回 JSON, 未知路径与错误方法分别返回 404/405
+    #[test]
+    fn test_debug_xbus() {
+        let _sub = xbus::subscribe::<ApiProbe, _>(|_| {});
+        let addr = spawn("127.0.0.1:0", ApiAuth::default()).unwrap();
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .write_all(b"GET /debug/xbus HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        let body = response.split("\r\n\r\n").nth(1).unwrap();
+        let snapshot: serde_json::Value = serde_json::from_str(body).unwrap();
+        let topics = snapshot["topics"].as_array().unwrap();
+        assert!(topics
+            .iter()
+            .any(|t| t["event_type"].as_str().unwrap().ends_with("ApiProbe")));
+
+        assert_eq!(route("GET", "/metrics").status, 404);
+        assert_eq!(route("DELETE", "/debug/xbus").status, 405);
+        assert_eq!(route("POST", "/debug/xbus/prune?all=1").status, 200);
+        assert_eq!(route("GET", "/api/schedule/arm").status, 405);
+        assert_eq!(route("POST", "/api/storage").status, 405);
+    }
+
+    /// 片段导出请求: 两种时间格式均可解析, 缺少参数时为 None
+    #[test]
+    fn test_clip_request_query() {
+        let request =
+            clip_request("camera=view0&from=1760000000&to=2025-10-09T08:54:20Z&overlay=1").unwrap();
+        assert_eq!(request.camera, "view0");
+        assert_eq!((request.to - request.from).num_seconds(), 60);
+        assert!(request.overlay);
+        assert!(
+            !clip_request("camera=view0&from=1760000000&to=1760000060")
+                .unwrap()
+                .overlay
+        );
+        assert!(clip_request("camera=view0&from=1760000000").is_none());
+        assert!(clip_request("camera=view0&from=yesterday&to=1760000060").is_none());
+        assert_eq!(route("DELETE", "/api/clips").status, 405);
+        assert_eq!(route("POST", "/api/gallery").status, 405);
+        assert_eq!(route("POST", "/api/profile?secs=ten").status, 400);
+        assert_eq!(route("DELETE", "/api/profile").status, 405);
+        assert_eq!(route("GET", "/onvif/metadata?since=yesterday").status, 400);
+        assert_eq!(route("POST", "/onvif/metadata").status, 405);
+    }
+
+    /// WebSocket 握手: 应答键与 RFC 6455 示例一致, 帧头按长度选择 7/16/64 位长度字段
+    #[test]
+    fn test_websocket_encoding() {
+        assert_eq!(
+            websocket_accept("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+
+        let frame = |len: usize| {
+            let mut out = Vec::new();
+            write_ws_frame(&mut out, WS_OPCODE_TEXT, &vec![b'x'; len]).unwrap();
+            out
+        };
+        assert_eq!(frame(5)[..2], [0x81, 5]);
+        assert_eq!(frame(300)[..4], [0x81, 126, 0x01, 0x2c]);
+        assert_eq!(frame(70_000)[..10], [0x81, 127, 0, 0, 0, 0, 0, 0x01, 0x11, 0x70]);
+        assert_eq!(frame(70_000).len(), 70_010);
+    }
+
+    /// 检测结果流与其他 GET 一样需要 viewer 令牌, 握手通过后返回 101; 普通 GET 返回 400
+    #[test]
+    fn test_websocket_auth() {
+        let auth = ApiAuth {
+            tokens: vec![ApiToken {
+                name: "wall".to_string(),
+                token: "wall-0123456789abcdef".to_string(),
+                role: Role::Viewer,
+            }],
+        };
+        let addr = spawn("127.0.0.1:0", auth).unwrap();
+        let handshake = |path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(
+                stream,
+                "GET {} HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+                 Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n",
+                path
+            )
+            .unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut head = String::new();
+            while reader.read_line(&mut head).unwrap() > 2 {}
+            head
+        };
+
+        assert!(handshake("/ws/detections").starts_with("HTTP/1.1 401"));
+        let head = handshake("/ws/detections?view=0&token=wall-0123456789abcdef");
+        assert!(head.starts_with("HTTP/1.1 101 Switching Protocols"));
+        assert!(head.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo="));
+        assert!(handshake("/ws/detections?view=x&token=wall-0123456789abcdef")
+            .starts_with("HTTP/1.1 400"));
+
+        assert_eq!(route("GET", "/ws/detections").status, 400);
+        assert_eq!(route("POST", "/ws/detections").status, 405);
+    }
+
+    /// 按令牌角色放行: 无令牌 401, 角色不足 403, 令牌可放在查询参数中; 未配置令牌时不鉴权
+    #[test]
+    fn test_authorize_roles() {
+        let token = |name: &str, role| ApiToken {
+            name: name.to_string(),
+            token: format!("{}-0123456789abcdef", name),
+            role,
+        };
+        let auth = ApiAuth {
+            tokens: vec![
+                token("wall", Role::Viewer),
+                token("guard", Role::Operator),
+                token("root", Role::Admin),
+            ],
+        };
+        let status = |method, path, bearer| {
+            authorize(&auth, method, path, bearer)
+                .err()
+                .map_or(200, |r| r.status)
+        };
+
+        assert_eq!(status("GET", "/api/storage", None), 401);
+        assert_eq!(status("GET", "/api/storage", Some("wrong")), 401);
+        assert_eq!(
+            status("GET", "/api/storage", Some("wall-0123456789abcdef")),
+            200
+        );
+        assert_eq!(
+            status("POST", "/api/schedule/arm", Some("wall-0123456789abcdef")),
+            403
+        );
+        assert_eq!(
+            status(
+                "POST",
+                "/api/model?name=yolov8s",
+                Some("guard-0123456789abcdef")
+            ),
+            200
+        );
+        assert_eq!(
+            status("POST", "/debug/xbus/prune", Some("guard-0123456789abcdef")),
+            403
+        );
+        // 审计记录只对 admin 开放, 记录中不含令牌
+        assert_eq!(
+            status("GET", "/api/audit", Some("guard-0123456789abcdef")),
+            403
+        );
+        assert_eq!(
+            status("GET", "/api/audit?limit=5", Some("root-0123456789abcdef")),
+            200
+        );
+        assert_eq!(
+            audit_detail("POST", "/api/model?token=secret&name=yolov8s"),
+            "POST /api/model?name=yolov8s"
+        );
+        assert_eq!(
+            status("GET", "/debug/xbus?token=root-0123456789abcdef", None),
+            200
+        );
+        assert_eq!(
+            authorize(&auth, "POST", "/api/clips", Some("guard-0123456789abcdef")).unwrap(),
+            Some("guard".to_string())
+        );
+
+        assert_eq!(
+            authorize(&ApiAuth::default(), "POST", "/debug/xbus/prune", None).unwrap(),
+            None
+        );
+        assert_eq!(
+            authorize(&ApiAuth::deny_all(), "GET", "/api/storage", Some(""))
+                .unwrap_err()
+                .status,
+            401
+        );
+    }
+}
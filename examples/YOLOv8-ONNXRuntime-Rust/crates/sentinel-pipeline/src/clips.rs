@@ -0,0 +1,797 @@
+//! 录像片段导出 (Clip export)
+//!
+//! 按摄像头与时间范围从录像分段中截取片段, 重新编码为 H.264 MP4 便于分享.
+//! 可选按检测记录重新绘制叠加框 (而不只是录像中烧录的画面), 因此关闭了界面叠加的录像
+//! 也能导出带框的片段.
+//!
+//! - 录像分段由外部录像程序写入, 文件名为分段起始在显示时区 (`--timezone`) 的本地时间,
+//!   录像程序的 `TZ` 须与之一致:
+//!   `<recordings_dir>/<camera>/%Y%m%d_%H%M%S.mp4`
+//!   (如 `ffmpeg -i rtsp://… -c copy -f segment -segment_time 300 -strftime 1 view0/%Y%m%d_%H%M%S.mp4`)
+//! - 检测记录由 [`DetectionJournal`] 写入, 每个逻辑流每小时一个 JSONL 文件:
+//!   `<journal_dir>/view<N>/%Y%m%d_%H.jsonl` (UTC 小时)
+//! - 导出任务在后台线程运行, 经调试 API 提交与查询 (`POST/GET /api/clips`)
+//!
+//! 叠加时间按片段起点加帧时间戳换算, 假定分段首尾相接; 录像中断处之后的框会有偏移.
+//! 配置文件不存在时不启用.
+//!
+//! 配置文件 (clips.json):
+//! ```json
+//! {
+//!   "recordings_dir": "recordings",
+//!   "journal_dir": "journal",
+//!   "output_dir": "clips",
+//!   "max_clip_secs": 600
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::ffi::{c_int, c_void};
+use std::fs;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, DurationRound, NaiveDateTime, Utc};
+use ez_ffmpeg::filter::frame_filter::FrameFilter;
+use ez_ffmpeg::filter::frame_filter_context::FrameFilterContext;
+use ez_ffmpeg::filter::frame_pipeline_builder::FramePipelineBuilder;
+use ez_ffmpeg::{AVMediaType, FfmpegContext, Frame, Input, Output};
+use serde::{Deserialize, Serialize};
+
+use crate::detection::detector::DetectionResult;
+use crate::detection::tracker::id_to_color;
+use crate::detection::types::DecodedFrame;
+use crate::utils::clock;
+use crate::xbus::{self, Subscription};
+
+/// 默认配置文件路径
+pub const CLIPS_CONFIG_PATH: &str = "clips.json";
+
+/// 叠加框最多沿用的检测记录时长 (ms), 超过则视为画面中无目标
+const MAX_ENTRY_GAP_MS: i64 = 500;
+
+/// 同时运行的导出任务数上限
+const MAX_RUNNING_JOBS: usize = 2;
+
+/// 保留的任务记录数 (超出时丢弃最早的已结束任务)
+const MAX_JOBS: usize = 50;
+
+/// AV_PIX_FMT_YUV420P
+const AV_PIX_FMT_YUV420P: i32 = 0;
+
+/// AV_NOPTS_VALUE
+const AV_NOPTS_VALUE: i64 = i64::MIN;
+
+extern "C" {
+    // libavutil (随 ez-ffmpeg 静态链接): 帧缓冲与解码器共享时复制一份再写
+    fn av_frame_make_writable(frame: *mut c_void) -> c_int;
+}
+
+/// 片段导出配置 (clips.json)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClipConfig {
+    pub recordings_dir: String,
+    pub journal_dir: String,
+    pub output_dir: String,
+    pub segment_extension: String, // 录像分段扩展名 (不含点)
+    pub max_clip_secs: f64,
+    pub encoder: String, // 视频编码器
+}
+
+impl Default for ClipConfig {
+    fn default() -> Self {
+        Self {
+            recordings_dir: "recordings".to_string(),
+            journal_dir: "journal".to_string(),
+            output_dir: "clips".to_string(),
+            segment_extension: "mp4".to_string(),
+            max_clip_secs: 600.0,
+            encoder: "libx264".to_string(),
+        }
+    }
+}
+
+impl ClipConfig {
+    /// 从文件加载配置, 文件不存在或解析失败时为 None (不记录检测、不导出)
+    pub fn load(path: &str) -> Option<Self> {
+        let json = fs::read_to_string(path).ok()?;
+        match serde_json::from_str::<Self>(&json) {
+            Ok(config) => {
+                println!(
+                    "✅ 片段导出配置已从 {} 加载 (录像: {}, 检测记录: {})",
+                    path, config.recordings_dir, config.journal_dir
+                );
+                Some(config)
+            }
+            Err(e) => {
+                eprintln!("⚠️  片段导出配置解析失败: {}, 不启用", e);
+                None
+            }
+        }
+    }
+}
+
+/// 检测记录中的一个框 (检测画面像素坐标)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JournalBox {
+    pub rect: [f32; 4], // x1, y1, x2, y2
+    pub id: u32,        // tracked 时为轨迹ID, 否则为类别ID
+    pub confidence: f32,
+    pub tracked: bool,
+}
+
+/// 一帧的检测记录
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub t_ms: i64,        // 采集时刻 (Unix 毫秒)
+    pub size: (u32, u32), // 检测坐标所在的画面尺寸
+    pub boxes: Vec<JournalBox>,
+}
+
+/// 检测记录文件 (按逻辑流、UTC 小时分文件)
+pub fn journal_path(dir: &Path, camera: &str, time: DateTime<Utc>) -> PathBuf {
+    dir.join(camera)
+        .join(format!("{}.jsonl", time.format("%Y%m%d_%H")))
+}
+
+/// 逻辑流对应的摄像头目录名
+pub fn camera_name(view: u32) -> String {
+    format!("view{}", view)
+}
+
+/// 检测记录 (独立写入线程, 订阅保持期间运行)
+///
+/// 只在有框或框刚消失时写入, 画面持续无目标时不占空间
+pub struct DetectionJournal {
+    _frame_sub: Subscription,
+    _result_sub: Subscription,
+}
+
+impl DetectionJournal {
+    pub fn start(dir: PathBuf) -> Self {
+        println!("📝 检测记录启动: {}", dir.display());
+        // 各逻辑流的画面尺寸 (检测结果不携带尺寸)
+        let sizes: Arc<Mutex<HashMap<u32, (u32, u32)>>> = Arc::default();
+        let frame_sizes = sizes.clone();
+        let frame_sub = xbus::subscribe::<DecodedFrame, _>(move |frame| {
+            let mut sizes = frame_sizes.lock().unwrap();
+            if sizes.get(&frame.view) != Some(&(frame.width, frame.height)) {
+                sizes.insert(frame.view, (frame.width, frame.height));
+            }
+        });
+
+        let (tx, rx) = crossbeam_channel::bounded::<(u32, JournalEntry)>(256);
+        let result_sub = xbus::subscribe::<DetectionResult, _>(move |result| {
+            let Some(&size) = sizes.lock().unwrap().get(&result.view) else {
+                return;
+            };
+            let entry = JournalEntry {
+                t_ms: result.trace.captured_at.timestamp_millis(),
+                size,
+                boxes: result
+                    .bboxes
+                    .iter()
+                    .map(|b| JournalBox {
+                        rect: [b.x1, b.y1, b.x2, b.y2],
+                        id: b.class_id,
+                        confidence: b.confidence,
+                        tracked: result.tracked,
+                    })
+                    .collect(),
+            };
+            let _ = tx.try_send((result.view, entry));
+        });
+
+        std::thread::spawn(move || {
+            let mut files: HashMap<u32, (PathBuf, BufWriter<fs::File>)> = HashMap::new();
+            let mut last_empty: HashMap<u32, bool> = HashMap::new();
+            for (view, entry) in &rx {
+                let empty = entry.boxes.is_empty();
+                if empty && last_empty.get(&view).copied().unwrap_or(true) {
+                    continue;
+                }
+                last_empty.insert(view, empty);
+
+                let time = Utc.timestamp_millis_opt(entry.t_ms).unwrap();
+                let path = journal_path(&dir, &camera_name(view), time);
+                if files.get(&view).map(|(p, _)| p) != Some(&path) {
+                    match open_append(&path) {
+                        Ok(file) => {
+                            files.insert(view, (path, BufWriter::new(file)));
+                        }
+                        Err(e) => {
+                            eprintln!("⚠️ 检测记录 {} 打开失败: {:#}", path.display(), e);
+                            files.remove(&view);
+                            continue;
+                        }
+                    }
+                }
+                let (_, writer) = files.get_mut(&view).unwrap();
+                if let Ok(line) = serde_json::to_string(&entry) {
+                    let _ = writeln!(writer, "{}", line);
+                }
+                // 队列清空时再落盘, 避免逐行系统调用
+                if rx.is_empty() {
+                    for (_, writer) in files.values_mut() {
+                        let _ = writer.flush();
+                    }
+                }
+            }
+            println!("📝 检测记录线程退出");
+        });
+
+        Self {
+            _frame_sub: frame_sub,
+            _result_sub: result_sub,
+        }
+    }
+}
+
+fn open_append(path: &Path) -> Result<fs::File> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(fs::File::options().create(true).append(true).open(path)?)
+}
+
+/// 读取时间范围内的检测记录 (按时间排序), 缺失的小时文件跳过
+pub fn load_journal(
+    dir: &Path,
+    camera: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<JournalEntry> {
+    // 片段开头沿用前一条记录, 多读一个间隔
+    let (from_ms, to_ms) = (
+        from.timestamp_millis() - MAX_ENTRY_GAP_MS,
+        to.timestamp_millis(),
+    );
+    let mut entries = Vec::new();
+    let mut hour = (from - Duration::milliseconds(MAX_ENTRY_GAP_MS))
+        .duration_trunc(Duration::hours(1))
+        .unwrap_or(from);
+    while hour <= to {
+        if let Ok(file) = fs::File::open(journal_path(dir, camera, hour)) {
+            entries.extend(
+                BufReader::new(file)
+                    .lines()
+                    .map_while(|line| line.ok())
+                    .filter_map(|line| serde_json::from_str::<JournalEntry>(&line).ok())
+                    .filter(|e| (from_ms..=to_ms).contains(&e.t_ms)),
+            );
+        }
+        hour += Duration::hours(1);
+    }
+    entries.sort_by_key(|e| e.t_ms);
+    entries
+}
+
+/// 某时刻应显示的检测记录 (不晚于该时刻的最近一条, 超过间隔上限则无)
+pub fn entry_at(entries: &[JournalEntry], t_ms: i64) -> Option<&JournalEntry> {
+    let index = entries.partition_point(|e| e.t_ms <= t_ms);
+    let entry = entries.get(index.checked_sub(1)?)?;
+    (t_ms - entry.t_ms <= MAX_ENTRY_GAP_MS).then_some(entry)
+}
+
+/// 录像分段
+#[derive(Clone, Debug, PartialEq)]
+pub struct Segment {
+    pub path: PathBuf,
+    pub start: DateTime<Utc>,
+}
+
+/// 列出摄像头目录下的录像分段 (按起始时间排序), 文件名不是时间的跳过
+pub fn list_segments(dir: &Path, extension: &str) -> Vec<Segment> {
+    let Ok(read) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut segments: Vec<Segment> = read
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| e.eq_ignore_ascii_case(extension))
+        })
+        .filter_map(|path| {
+            let stem = path.file_stem()?.to_str()?;
+            let naive = NaiveDateTime::parse_from_str(stem, "%Y%m%d_%H%M%S").ok()?;
+            let start = clock::from_display(&naive)?;
+            Some(Segment { path, start })
+        })
+        .collect();
+    segments.sort_by_key(|s| s.start);
+    segments
+}
+
+/// 单个分段的截取区间 (相对分段起点的秒数)
+#[derive(Clone, Debug, PartialEq)]
+pub struct SegmentCut {
+    pub path: PathBuf,
+    pub inpoint: f64,
+    pub outpoint: Option<f64>, // None 表示到分段结尾
+}
+
+/// 时间范围覆盖的分段与截取区间; 分段视为持续到下一分段开始 (最后一段到文件结尾)
+pub fn plan_cuts(segments: &[Segment], from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<SegmentCut> {
+    let secs = |d: Duration| d.num_milliseconds() as f64 / 1000.0;
+    segments
+        .iter()
+        .enumerate()
+        .filter_map(|(i, segment)| {
+            let end = segments.get(i + 1).map(|next| next.start);
+            if segment.start >= to || end.is_some_and(|end| end <= from) {
+                return None;
+            }
+            let inpoint = secs(from - segment.start).max(0.0);
+            let outpoint = match end {
+                Some(end) if end <= to => None,
+                _ => Some(secs(to - segment.start)),
+            };
+            Some(SegmentCut {
+                path: segment.path.clone(),
+                inpoint,
+                outpoint,
+            })
+        })
+        .collect()
+}
+
+/// FFmpeg concat 分离器的列表文件内容
+pub fn concat_list(cuts: &[SegmentCut]) -> String {
+    let mut list = String::from("ffconcat version 1.0\n");
+    for cut in cuts {
+        let path = cut.path.to_string_lossy().replace('\'', r"'\''");
+        list.push_str(&format!("file '{}'\n", path));
+        if cut.inpoint > 0.0 {
+            list.push_str(&format!("inpoint {:.3}\n", cut.inpoint));
+        }
+        if let Some(outpoint) = cut.outpoint {
+            list.push_str(&format!("outpoint {:.3}\n", outpoint));
+        }
+    }
+    list
+}
+
+/// 解析时间参数: Unix 秒 (可带小数) 或 RFC 3339
+pub fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(secs) = value.parse::<f64>() {
+        return Utc.timestamp_millis_opt((secs * 1000.0) as i64).single();
+    }
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// 导出请求
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ClipRequest {
+    pub camera: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub overlay: bool, // 按检测记录重新绘制叠加框
+}
+
+impl ClipRequest {
+    /// 检查时间范围与摄像头名 (只允许单级目录名)
+    fn validate(&self, config: &ClipConfig) -> Result<()> {
+        if self.camera.is_empty()
+            || self.camera.starts_with('.')
+            || self.camera.contains(['/', '\\'])
+        {
+            bail!("无效的摄像头名: {:?}", self.camera);
+        }
+        let secs = (self.to - self.from).num_milliseconds() as f64 / 1000.0;
+        if secs <= 0.0 {
+            bail!("结束时间须晚于开始时间");
+        }
+        if secs > config.max_clip_secs {
+            bail!(
+                "片段时长 {:.0}s 超过上限 {:.0}s",
+                secs,
+                config.max_clip_secs
+            );
+        }
+        Ok(())
+    }
+
+    /// 输出文件名 (摄像头 + 显示时区的起止时间)
+    fn file_name(&self) -> String {
+        let fmt = "%Y%m%d_%H%M%S";
+        format!(
+            "{}_{}_{}{}.mp4",
+            self.camera,
+            clock::to_display(self.from).format(fmt),
+            clock::to_display(self.to).format(fmt),
+            if self.overlay { "_overlay" } else { "" }
+        )
+    }
+}
+
+/// 导出片段 (阻塞直到编码完成), 返回输出文件路径
+pub fn export_clip(config: &ClipConfig, request: &ClipRequest) -> Result<PathBuf> {
+    request.validate(config)?;
+    let segments = list_segments(
+        &Path::new(&config.recordings_dir).join(&request.camera),
+        &config.segment_extension,
+    );
+    let cuts = plan_cuts(&segments, request.from, request.to);
+    let Some(first) = segments
+        .iter()
+        .find(|s| Some(&s.path) == cuts.first().map(|c| &c.path))
+    else {
+        bail!("{} 在该时间范围内没有录像", request.camera);
+    };
+    // 片段第一帧对应的墙上时间
+    let clip_start = request.from.max(first.start);
+
+    let output_dir = Path::new(&config.output_dir);
+    fs::create_dir_all(output_dir)
+        .with_context(|| format!("创建输出目录 {} 失败", output_dir.display()))?;
+    let output = output_dir.join(request.file_name());
+    let list_path = output.with_extension("ffconcat");
+    fs::write(&list_path, concat_list(&cuts))?;
+
+    let mut out = Output::from(output.to_string_lossy().as_ref()).set_video_codec(&config.encoder);
+    if request.overlay {
+        let entries = load_journal(
+            Path::new(&config.journal_dir),
+            &request.camera,
+            clip_start,
+            request.to,
+        );
+        println!("🎞️ 重新绘制叠加框: {}条检测记录", entries.len());
+        let filter = OverlayFilter {
+            start_ms: clip_start.timestamp_millis(),
+            entries,
+        };
+        let pipe: FramePipelineBuilder = AVMediaType::AVMEDIA_TYPE_VIDEO.into();
+        out = out.add_frame_pipeline(pipe.filter("overlay", Box::new(filter)));
+    }
+
+    let input = Input::from(list_path.to_string_lossy().as_ref())
+        .set_format("concat")
+        .set_input_opts([("safe", "0")].into());
+    let result = FfmpegContext::builder()
+        .input(input)
+        .filter_descs(["format=yuv420p"].into())
+        .output(out)
+        .build()
+        .map_err(|e| anyhow::anyhow!("构建失败: {}", e))
+        .and_then(|ctx| ctx.start().map_err(|e| anyhow::anyhow!("启动失败: {}", e)))
+        .and_then(|sch| sch.wait().map_err(|e| anyhow::anyhow!("编码失败: {}", e)));
+    let _ = fs::remove_file(&list_path);
+    result?;
+    Ok(output)
+}
+
+/// 平面 YUV420 画面 (可写视图)
+struct Planes<'a> {
+    y: &'a mut [u8],
+    u: &'a mut [u8],
+    v: &'a mut [u8],
+    y_stride: usize,
+    uv_stride: usize,
+    width: usize,
+    height: usize,
+}
+
+impl Planes<'_> {
+    /// 绘制矩形边框 (BT.601 有限范围着色)
+    fn draw_rect(&mut self, rect: [f32; 4], (r, g, b): (u8, u8, u8), thickness: usize) {
+        let (r, g, b) = (r as i32, g as i32, b as i32);
+        let y = (((66 * r + 129 * g + 25 * b + 128) >> 8) + 16) as u8;
+        let u = (((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128) as u8;
+        let v = (((112 * r - 94 * g - 18 * b + 128) >> 8) + 128) as u8;
+
+        let clamp = |value: f32, max: usize| (value.max(0.0) as usize).min(max.saturating_sub(1));
+        let (x1, x2) = (clamp(rect[0], self.width), clamp(rect[2], self.width));
+        let (y1, y2) = (clamp(rect[1], self.height), clamp(rect[3], self.height));
+        if x1 >= x2 || y1 >= y2 {
+            return;
+        }
+        let t = thickness.max(1);
+        for py in y1..=y2 {
+            let edge_row = py < y1 + t || py + t > y2;
+            for px in x1..=x2 {
+                if edge_row || px < x1 + t || px + t > x2 {
+                    self.y[py * self.y_stride + px] = y;
+                    let c = (py / 2) * self.uv_stride + px / 2;
+                    self.u[c] = u;
+                    self.v[c] = v;
+                }
+            }
+        }
+    }
+
+    /// 按检测记录绘制全部框 (坐标从检测画面缩放到片段画面)
+    fn draw_entry(&mut self, entry: &JournalEntry) {
+        let sx = self.width as f32 / entry.size.0.max(1) as f32;
+        let sy = self.height as f32 / entry.size.1.max(1) as f32;
+        let thickness = (self.height / 360).max(2);
+        for b in &entry.boxes {
+            let rect = [
+                b.rect[0] * sx,
+                b.rect[1] * sy,
+                b.rect[2] * sx,
+                b.rect[3] * sy,
+            ];
+            let color = if b.tracked {
+                id_to_color(b.id)
+            } else {
+                (0, 255, 0)
+            };
+            self.draw_rect(rect, color, thickness);
+        }
+    }
+}
+
+/// 叠加框重绘过滤器 (编码前在 yuv420p 帧上绘制)
+struct OverlayFilter {
+    start_ms: i64, // 片段第一帧的墙上时间
+    entries: Vec<JournalEntry>,
+}
+
+impl FrameFilter for OverlayFilter {
+    fn media_type(&self) -> AVMediaType {
+        AVMediaType::AVMEDIA_TYPE_VIDEO
+    }
+
+    fn filter_frame(
+        &mut self,
+        mut frame: Frame,
+        _ctx: &FrameFilterContext,
+    ) -> Result<Option<Frame>, String> {
+        unsafe {
+            if frame.as_ptr().is_null() || frame.is_empty() {
+                return Ok(Some(frame));
+            }
+            let av = frame.as_ptr();
+            if (*av).format != AV_PIX_FMT_YUV420P {
+                return Ok(Some(frame));
+            }
+            let pts = match (*av).pts {
+                AV_NOPTS_VALUE => (*av).best_effort_timestamp,
+                pts => pts,
+            };
+            let tb = (*av).time_base;
+            if pts == AV_NOPTS_VALUE || tb.den <= 0 {
+                return Ok(Some(frame));
+            }
+            let offset_ms = pts as f64 * tb.num as f64 * 1000.0 / tb.den as f64;
+            let Some(entry) = entry_at(&self.entries, self.start_ms + offset_ms as i64) else {
+                return Ok(Some(frame));
+            };
+            if entry.boxes.is_empty() {
+                return Ok(Some(frame));
+            }
+
+            // 滤镜输出可能与解码器共用参考帧缓冲, 写之前确保独占
+            if av_frame_make_writable(frame.as_mut_ptr() as *mut c_void) < 0 {
+                return Ok(Some(frame));
+            }
+            let av = frame.as_ptr();
+            let (width, height) = ((*av).width as usize, (*av).height as usize);
+            let y_stride = (*av).linesize[0] as usize;
+            let uv_stride = (*av).linesize[1] as usize;
+            let chroma_rows = height.div_ceil(2);
+            let mut planes = Planes {
+                y: std::slice::from_raw_parts_mut((*av).data[0], y_stride * height),
+                u: std::slice::from_raw_parts_mut((*av).data[1], uv_stride * chroma_rows),
+                v: std::slice::from_raw_parts_mut((*av).data[2], uv_stride * chroma_rows),
+                y_stride,
+                uv_stride,
+                width,
+                height,
+            };
+            planes.draw_entry(entry);
+        }
+        Ok(Some(frame))
+    }
+}
+
+/// 导出任务状态
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipState {
+    Running,
+    Done,
+    Failed,
+}
+
+/// 导出任务 (API 查询)
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ClipJob {
+    pub id: u64,
+    #[serde(flatten)]
+    pub request: ClipRequest,
+    pub state: ClipState,
+    pub output: Option<String>,
+    pub error: Option<String>,
+}
+
+/// 导出服务 (配置 + 任务列表)
+struct ClipService {
+    config: ClipConfig,
+    jobs: Vec<ClipJob>,
+    next_id: u64,
+}
+
+static SERVICE: OnceLock<Mutex<ClipService>> = OnceLock::new();
+
+/// 启用片段导出并启动检测记录 (进程内只启用一次, 检测记录须在主循环期间保持)
+pub fn start(config: ClipConfig) -> DetectionJournal {
+    let journal = DetectionJournal::start(PathBuf::from(&config.journal_dir));
+    let _ = SERVICE.set(Mutex::new(ClipService {
+        config,
+        jobs: Vec::new(),
+        next_id: 1,
+    }));
+    journal
+}
+
+/// 全部导出任务 (新任务在后), 未启用时为 None
+pub fn clip_jobs() -> Option<Vec<ClipJob>> {
+    SERVICE.get().map(|s| s.lock().unwrap().jobs.clone())
+}
+
+/// 提交导出任务 (后台线程编码), 返回新任务
+pub fn request_clip(request: ClipRequest) -> Result<ClipJob> {
+    let Some(service) = SERVICE.get() else {
+        bail!("片段导出未启用");
+    };
+    let mut guard = service.lock().unwrap();
+    request.validate(&guard.config)?;
+    let running = guard
+        .jobs
+        .iter()
+        .filter(|j| j.state == ClipState::Running)
+        .count();
+    if running >= MAX_RUNNING_JOBS {
+        bail!("已有 {} 个导出任务在运行", running);
+    }
+    if guard.jobs.len() >= MAX_JOBS {
+        if let Some(i) = guard
+            .jobs
+            .iter()
+            .position(|j| j.state != ClipState::Running)
+        {
+            guard.jobs.remove(i);
+        }
+    }
+    let job = ClipJob {
+        id: guard.next_id,
+        request,
+        state: ClipState::Running,
+        output: None,
+        error: None,
+    };
+    guard.next_id += 1;
+    guard.jobs.push(job.clone());
+    let config = guard.config.clone();
+    drop(guard);
+
+    let (id, request) = (job.id, job.request.clone());
+    let spawned = std::thread::Builder::new()
+        .name(format!("clip-{}", id))
+        .spawn(move || {
+            println!(
+                "🎞️ 导出片段 #{}: {} {} ~ {}",
+                id,
+                request.camera,
+                clock::to_display(request.from).format("%F %T"),
+                clock::to_display(request.to).format("%T")
+            );
+            let result = export_clip(&config, &request);
+            match &result {
+                Ok(path) => println!("✅ 片段 #{} 已导出: {}", id, path.display()),
+                Err(e) => eprintln!("❌ 片段 #{} 导出失败: {:#}", id, e),
+            }
+            let mut guard = service.lock().unwrap();
+            if let Some(job) = guard.jobs.iter_mut().find(|j| j.id == id) {
+                match result {
+                    Ok(path) => {
+                        job.state = ClipState::Done;
+                        job.output = Some(path.to_string_lossy().into_owned());
+                    }
+                    Err(e) => {
+                        job.state = ClipState::Failed;
+                        job.error = Some(format!("{:#}", e));
+                    }
+                }
+            }
+        });
+    if let Err(e) = spawned {
+        let mut guard = service.lock().unwrap();
+        guard.jobs.retain(|j| j.id != id);
+        bail!("导出线程启动失败: {}", e);
+    }
+    Ok(job)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(1_760_000_000 + secs, 0).unwrap()
+    }
+
+    fn segment(name: &str, start: i64) -> Segment {
+        Segment {
+            path: PathBuf::from(name),
+            start: at(start),
+        }
+    }
+
+    /// 跨分段的时间范围: 首段从偏移处开始, 中间段完整, 末段截到结束时间
+    #[test]
+    fn test_plan_cuts_across_segments() {
+        let segments = [
+            segment("a.mp4", 0),
+            segment("b.mp4", 300),
+            segment("c.mp4", 600),
+            segment("it's.mp4", 900),
+        ];
+        let cuts = plan_cuts(&segments, at(250), at(640));
+        let names: Vec<_> = cuts.iter().map(|c| c.path.to_str().unwrap()).collect();
+        assert_eq!(names, ["a.mp4", "b.mp4", "c.mp4"]);
+        assert_eq!((cuts[0].inpoint, cuts[0].outpoint), (250.0, None));
+        assert_eq!((cuts[1].inpoint, cuts[1].outpoint), (0.0, None));
+        assert_eq!((cuts[2].inpoint, cuts[2].outpoint), (0.0, Some(40.0)));
+
+        // 最后一段无下一段起点, 按结束时间截取
+        let tail = plan_cuts(&segments, at(950), at(960));
+        assert_eq!(tail.len(), 1);
+        assert_eq!((tail[0].inpoint, tail[0].outpoint), (50.0, Some(60.0)));
+        assert!(concat_list(&tail).contains(r"file 'it'\''s.mp4'"));
+        assert!(plan_cuts(&segments, at(-100), at(0)).is_empty());
+    }
+
+    /// 叠加框取不晚于帧时刻的最近记录, 间隔过长时不画; 框按画面尺寸缩放
+    #[test]
+    fn test_overlay_entries() {
+        let entry = |t_ms: i64, boxes: Vec<JournalBox>| JournalEntry {
+            t_ms,
+            size: (200, 100),
+            boxes,
+        };
+        let person = JournalBox {
+            rect: [20.0, 20.0, 60.0, 80.0],
+            id: 7,
+            confidence: 0.9,
+            tracked: true,
+        };
+        let entries = vec![entry(1_000, vec![person]), entry(2_000, vec![])];
+        assert!(entry_at(&entries, 999).is_none());
+        assert_eq!(entry_at(&entries, 1_400).unwrap().t_ms, 1_000);
+        assert!(entry_at(&entries, 2_300).unwrap().boxes.is_empty());
+        assert!(entry_at(&entries, 2_600).is_none());
+
+        let (width, height) = (100, 50);
+        let (mut y, mut u, mut v) = (
+            vec![0u8; 100 * 50],
+            vec![128u8; 50 * 25],
+            vec![128u8; 50 * 25],
+        );
+        let mut planes = Planes {
+            y: &mut y,
+            u: &mut u,
+            v: &mut v,
+            y_stride: width,
+            uv_stride: width / 2,
+            width,
+            height,
+        };
+        planes.draw_entry(&entries[0]);
+        // (20,20)-(60,80) 缩放一半后为 (10,10)-(30,40)
+        assert_ne!(y[10 * width + 10], 0);
+        assert_ne!(y[40 * width + 30], 0);
+        assert_eq!(y[25 * width + 20], 0);
+        assert_eq!(y[5 * width + 5], 0);
+    }
+}
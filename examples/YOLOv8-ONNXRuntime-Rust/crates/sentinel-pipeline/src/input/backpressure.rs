@@ -0,0 +1,98 @@
+/// 检测端背压 → 解码端源头丢帧
+/// Backpressure-driven frame gating at the decoder
+///
+/// 检测队列满时, 检测线程发布 [`Backpressure`]; 解码过滤器据此跳过 YUV→RGBA 转换与发布,
+/// 只保留最低预览帧率, 直到检测线程取走一帧后解除. 推理跟不上时不再为注定被丢弃的帧付出转换与拷贝
+use crate::detection::types::Backpressure;
+use crate::xbus::{self, Subscription};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 背压期间的最低发布帧率 (保证预览画面不卡顿)
+pub const MIN_PREVIEW_FPS: f64 = 10.0;
+
+/// 解码端帧闸门 (克隆后共享同一背压状态)
+#[derive(Clone)]
+pub struct FrameGate {
+    saturated: Arc<AtomicBool>,
+    _sub: Arc<Subscription>,
+    min_interval: Duration,
+    last_publish: Option<Instant>,
+    pub skipped: usize, // 因背压跳过的帧数
+}
+
+impl FrameGate {
+    /// 订阅背压消息; `min_fps` 为背压期间仍保证发布的帧率 (0 表示背压时全部跳过)
+    pub fn new(min_fps: f64) -> Self {
+        let saturated = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&saturated);
+        let sub = xbus::subscribe::<Backpressure, _>(move |msg| {
+            flag.store(msg.saturated, Ordering::Relaxed);
+        });
+        Self {
+            saturated,
+            _sub: Arc::new(sub),
+            min_interval: if min_fps > 0.0 {
+                Duration::from_secs_f64(1.0 / min_fps)
+            } else {
+                Duration::MAX
+            },
+            last_publish: None,
+            skipped: 0,
+        }
+    }
+
+    /// 检测队列当前是否已满
+    pub fn saturated(&self) -> bool {
+        self.saturated.load(Ordering::Relaxed)
+    }
+
+    /// 当前帧是否需要转换并发布
+    pub fn admit(&mut self, now: Instant) -> bool {
+        let admitted = admit(self.saturated(), self.last_publish, now, self.min_interval);
+        if admitted {
+            self.last_publish = Some(now);
+        } else {
+            self.skipped += 1;
+        }
+        admitted
+    }
+}
+
+/// 无背压时全部放行; 背压时按最低间隔放行
+fn admit(saturated: bool, last: Option<Instant>, now: Instant, min_interval: Duration) -> bool {
+    match last {
+        Some(last) if saturated => now.saturating_duration_since(last) >= min_interval,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 25fps 输入, 背压期间按 10fps 最低帧率放行
+    #[test]
+    fn test_admit_under_backpressure() {
+        let start = Instant::now();
+        let interval = Duration::from_millis(100);
+        let mut last = None;
+        let mut published = 0;
+        for i in 0..25u64 {
+            let now = start + Duration::from_millis(i * 40);
+            if admit(true, last, now, interval) {
+                last = Some(now);
+                published += 1;
+            }
+        }
+        // 0, 120, 240, ... 960ms
+        assert_eq!(published, 9);
+
+        // 解除背压后每帧放行
+        let now = last.unwrap() + Duration::from_millis(1);
+        assert!(admit(false, last, now, interval));
+        assert!(!admit(true, last, now, interval));
+        assert!(!admit(true, last, now, Duration::MAX));
+    }
+}
@@ -0,0 +1,371 @@
+//! 摄像头输入模块 - 独立的摄像头解码器
+//!
+//! 处理本地摄像头输入,支持 DirectShow(Windows) / AVFoundation(macOS) / V4L2(Linux)
+//!
+//! Linux 启用 `turbojpeg` 特性时, 输出 MJPEG 的 USB 摄像头走快速路径: 直接从 V4L2 取
+//! JPEG 缓冲, libjpeg-turbo (SIMD) 一步解码为 RGBA 写入复用的帧缓冲, 不经过 FFmpeg
+//! 的解复用/解码/sws_scale 链路. 设备不支持 MJPEG 时回退到 FFmpeg
+
+use super::decode_filter::DecodeFilter;
+use ez_ffmpeg::core::context::null_output::create_null_output;
+use ez_ffmpeg::filter::frame_pipeline_builder::FramePipelineBuilder;
+use ez_ffmpeg::{AVMediaType, FfmpegContext, Input};
+
+#[cfg(all(feature = "turbojpeg", target_os = "linux"))]
+use super::backpressure::{FrameGate, MIN_PREVIEW_FPS};
+#[cfg(all(feature = "turbojpeg", target_os = "linux"))]
+use super::decoder_manager::ACTIVE_DECODER_GENERATION;
+#[cfg(all(feature = "turbojpeg", target_os = "linux"))]
+use crate::detection::{trace::FrameTrace, types::DecodedFrame};
+#[cfg(all(feature = "turbojpeg", target_os = "linux"))]
+use crate::memory_guard;
+#[cfg(all(feature = "turbojpeg", target_os = "linux"))]
+use crate::xbus;
+#[cfg(all(feature = "turbojpeg", target_os = "linux"))]
+use anyhow::{bail, Context, Result};
+#[cfg(all(feature = "turbojpeg", target_os = "linux"))]
+use std::sync::{atomic::Ordering, Arc};
+#[cfg(all(feature = "turbojpeg", target_os = "linux"))]
+use std::time::{Duration, Instant};
+
+/// MJPEG 快速路径的采集分辨率与帧率 (与 FFmpeg 路径的输入参数一致)
+#[cfg(all(feature = "turbojpeg", target_os = "linux"))]
+const MJPEG_SIZE: (u32, u32) = (1280, 720);
+#[cfg(all(feature = "turbojpeg", target_os = "linux"))]
+const MJPEG_FPS: u32 = 30;
+
+/// V4L2 mmap 缓冲数量 (越少排队延迟越低)
+#[cfg(all(feature = "turbojpeg", target_os = "linux"))]
+const MJPEG_BUFFERS: u32 = 3;
+
+/// 单次取帧超时, 超时后重新检查代数ID
+#[cfg(all(feature = "turbojpeg", target_os = "linux"))]
+const MJPEG_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// 复用的 RGBA 帧缓冲数量: 检测/渲染线程仍持有时轮换到下一个, 都被占用才重新分配
+#[cfg(all(feature = "turbojpeg", target_os = "linux"))]
+const FRAME_POOL_SIZE: usize = 3;
+
+/// 摄像头解码器结构
+pub struct CameraDecoder {
+    device_index: usize,
+    device_name: String,
+    generation: usize,
+}
+
+impl CameraDecoder {
+    /// 创建新的摄像头解码器
+    pub fn new(device_index: usize, device_name: String, generation: usize) -> Self {
+        Self {
+            device_index,
+            device_name,
+            generation,
+        }
+    }
+
+    /// 启动摄像头解码
+    pub fn run(&mut self) {
+        println!(
+            "\n🎥 ============ 摄像头解码器 (Gen: {}) ============",
+            self.generation
+        );
+        println!("📷 设备索引: {}", self.device_index);
+        println!("📷 设备名称: {}", self.device_name);
+
+        #[cfg(all(feature = "turbojpeg", target_os = "linux"))]
+        match self.capture_mjpeg() {
+            Ok(()) => return,
+            Err(e) => eprintln!("⚠️ MJPEG 快速路径不可用, 回退到 FFmpeg: {:#}", e),
+        }
+
+        let camera_url = Self::format_camera_url(self.device_index, &self.device_name);
+        println!("🔗 摄像头URL: {}", camera_url);
+
+        // 创建解码滤镜
+        let filter = DecodeFilter::new(self.generation);
+
+        // 开始解码
+        Self::decode_camera(&camera_url, filter);
+    }
+
+    /// MJPEG 快速路径: V4L2 mmap 取 JPEG 缓冲 → turbojpeg 解码为 RGBA → 发布
+    ///
+    /// 设备无法协商 MJPEG 时在采集开始前返回错误, 由调用方回退到 FFmpeg
+    #[cfg(all(feature = "turbojpeg", target_os = "linux"))]
+    fn capture_mjpeg(&self) -> Result<()> {
+        use v4l::buffer::Type;
+        use v4l::io::traits::CaptureStream;
+        use v4l::prelude::*;
+        use v4l::video::capture::Parameters;
+        use v4l::video::Capture;
+        use v4l::FourCC;
+
+        let device = Device::new(self.device_index).context("打开 V4L2 设备失败")?;
+        let mut format = device.format().context("读取采集格式失败")?;
+        format.width = MJPEG_SIZE.0;
+        format.height = MJPEG_SIZE.1;
+        format.fourcc = FourCC::new(b"MJPG");
+        let format = device.set_format(&format).context("设置采集格式失败")?;
+        if format.fourcc != FourCC::new(b"MJPG") {
+            bail!("设备不支持 MJPEG (协商结果 {})", format.fourcc);
+        }
+        if let Err(e) = device.set_params(&Parameters::with_fps(MJPEG_FPS)) {
+            eprintln!("⚠️ 设置帧率失败, 使用设备默认值: {}", e);
+        }
+
+        let mut stream = MmapStream::with_buffers(&device, Type::VideoCapture, MJPEG_BUFFERS)
+            .context("创建 mmap 采集流失败")?;
+        stream.set_timeout(MJPEG_TIMEOUT);
+        let mut decompressor = turbojpeg::Decompressor::new().context("创建 JPEG 解码器失败")?;
+        println!(
+            "✅ MJPEG 快速路径启动: {}x{} @ {}fps (turbojpeg)",
+            format.width, format.height, MJPEG_FPS
+        );
+
+        let mut pool: Vec<Arc<Vec<u8>>> = Vec::with_capacity(FRAME_POOL_SIZE);
+        let mut gate = FrameGate::new(MIN_PREVIEW_FPS);
+        let (mut count, mut last, mut fps) = (0usize, Instant::now(), 0.0);
+        let (mut corrupt, mut decode_ms) = (0usize, 0.0);
+
+        while ACTIVE_DECODER_GENERATION.load(Ordering::SeqCst) == self.generation {
+            let data = match stream.next() {
+                Ok((buf, meta)) => &buf[..(meta.bytesused as usize).min(buf.len())],
+                Err(e) if e.kind() == std::io::ErrorKind::TimedOut => continue,
+                Err(e) => return Err(e).context("V4L2 取帧失败"),
+            };
+            let trace = FrameTrace::new();
+            // USB 传输偶发截断的帧解码会报错或出现灰块, 直接丢弃
+            if !jpeg_complete(data) {
+                corrupt += 1;
+                continue;
+            }
+            count += 1;
+
+            // 背压: 检测队列已满, 跳过解码与发布 (保留最低预览帧率)
+            if !gate.admit(trace.decode_ts) {
+                continue;
+            }
+            let header = match decompressor.read_header(data) {
+                Ok(h) => h,
+                Err(_) => {
+                    corrupt += 1;
+                    continue;
+                }
+            };
+            let (width, height) = (header.width, header.height);
+            let rgba = take_pool_frame(&mut pool, width * height * 4);
+            let image = turbojpeg::Image {
+                pixels: Arc::get_mut(rgba).unwrap().as_mut_slice(),
+                width,
+                pitch: width * 4,
+                height,
+                format: turbojpeg::PixelFormat::RGBA,
+            };
+            let decode_start = Instant::now();
+            if decompressor.decompress(data, image).is_err() {
+                corrupt += 1;
+                continue;
+            }
+            decode_ms = decode_start.elapsed().as_secs_f64() * 1000.0;
+
+            xbus::post(DecodedFrame {
+                rgba_data: Arc::clone(rgba),
+                width: width as u32,
+                height: height as u32,
+                decode_fps: fps,
+                decoder_name: "MJPEG (turbojpeg)".to_string(),
+                yuv: None,
+                keyframe: true,
+                trace,
+                view: 0,
+                generation: self.generation,
+                yuv16: None,
+                #[cfg(feature = "cuda")]
+                device: None,
+            });
+
+            if last.elapsed().as_secs_f64() >= 1.0 {
+                fps = count as f64 / last.elapsed().as_secs_f64();
+                let in_use = pool.iter().filter(|f| Arc::strong_count(f) > 1).count();
+                memory_guard::set_frame_pool(in_use, FRAME_POOL_SIZE);
+                println!(
+                    "📷 MJPEG 统计: {:.1}fps | 解码{:.1}ms | 损坏帧{} | 背压跳过{}",
+                    fps, decode_ms, corrupt, gate.skipped
+                );
+                count = 0;
+                last = Instant::now();
+            }
+        }
+
+        println!(
+            "🛑 摄像头解码器已过期 (Gen: {}), 停止 MJPEG 采集",
+            self.generation
+        );
+        Ok(())
+    }
+
+    /// 格式化摄像头URL - 根据平台选择
+    #[cfg_attr(target_os = "windows", allow(unused_variables))]
+    fn format_camera_url(index: usize, name: &str) -> String {
+        #[cfg(target_os = "windows")]
+        {
+            format!("video={}", name)
+        }
+        #[cfg(target_os = "macos")]
+        {
+            format!("{}", index)
+        }
+        #[cfg(target_os = "linux")]
+        {
+            format!("/dev/video{}", index)
+        }
+        #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+        {
+            format!("{}", index)
+        }
+    }
+
+    /// 摄像头解码实现
+    fn decode_camera(camera_input: &str, filter: DecodeFilter) {
+        println!("📹 启动摄像头解码");
+
+        #[cfg(target_os = "windows")]
+        let format = "dshow"; // DirectShow
+
+        #[cfg(target_os = "macos")]
+        let format = "avfoundation"; // AVFoundation
+
+        #[cfg(target_os = "linux")]
+        let format = "v4l2"; // Video4Linux2
+
+        #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+        let format = "video4linux2"; // 默认
+
+        let mut retry_count = 0;
+        let max_retries = 3;
+
+        println!("🔍 使用格式: {}, 输入: {}", format, camera_input);
+
+        loop {
+            // 构建帧处理管线
+            let pipe: FramePipelineBuilder = AVMediaType::AVMEDIA_TYPE_VIDEO.into();
+            let pipe = pipe.filter("decode", Box::new(filter.clone()));
+            let out = create_null_output().add_frame_pipeline(pipe);
+
+            // 配置摄像头输入
+            // 注意: 移除硬编码的分辨率和帧率,让 dshow 自动协商默认值
+            // 很多摄像头不支持特定的 1280x720 或 30fps, 导致打开失败
+            let input = Input::new(camera_input)
+                .set_format(format)
+                .set_input_opts([("framerate", "30"), ("video_size", "1280x720")].into());
+
+            // 构建FFmpeg上下文
+            let ctx_result = FfmpegContext::builder().input(input).output(out).build();
+
+            let ctx = match ctx_result {
+                Ok(c) => c,
+                Err(e) => {
+                    retry_count += 1;
+                    eprintln!("❌ 摄像头构建错误详情: {}", e);
+                    if retry_count >= max_retries {
+                        eprintln!("❌ 摄像头构建失败 (重试{}次)", max_retries);
+                        eprintln!("💡 提示: 请检查设备名称是否正确,或尝试关闭其他占用摄像头的程序");
+                        return;
+                    }
+                    println!(
+                        "⚠️ 摄像头忙或无法打开, 1秒后重试... ({}/{})",
+                        retry_count, max_retries
+                    );
+                    std::thread::sleep(std::time::Duration::from_secs(1));
+                    continue;
+                }
+            };
+
+            // 启动并运行解码循环
+            let sch = match ctx.start() {
+                Ok(s) => s,
+                Err(e) => {
+                    eprintln!("❌ 摄像头启动失败: {}", e);
+                    return;
+                }
+            };
+
+            println!("✅ 摄像头连接成功,开始解码!");
+
+            // 等待解码完成
+            let _ = sch.wait();
+            println!("📹 摄像头解码循环结束");
+            break;
+        }
+    }
+}
+
+/// 取一个未被其他线程持有的帧缓冲 (尺寸变化时重新分配), 池满且都被占用时替换最旧的
+#[cfg(all(feature = "turbojpeg", target_os = "linux"))]
+fn take_pool_frame(pool: &mut Vec<Arc<Vec<u8>>>, len: usize) -> &mut Arc<Vec<u8>> {
+    let idx = match pool.iter().position(|f| Arc::strong_count(f) == 1) {
+        Some(i) => i,
+        None if pool.len() < FRAME_POOL_SIZE => {
+            pool.push(Arc::new(Vec::new()));
+            pool.len() - 1
+        }
+        None => {
+            pool.rotate_left(1);
+            pool[FRAME_POOL_SIZE - 1] = Arc::new(Vec::new());
+            FRAME_POOL_SIZE - 1
+        }
+    };
+    let frame = &mut pool[idx];
+    let buf = Arc::get_mut(frame).unwrap();
+    if buf.len() != len {
+        *buf = vec![255u8; len];
+    }
+    frame
+}
+
+/// JPEG 数据是否完整 (SOI 开头, EOI 结尾; 部分摄像头在 EOI 后补零)
+#[cfg(all(feature = "turbojpeg", target_os = "linux"))]
+fn jpeg_complete(data: &[u8]) -> bool {
+    let end = data.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+    data.starts_with(&[0xFF, 0xD8]) && data[..end].ends_with(&[0xFF, 0xD9])
+}
+
+/// 获取可用的摄像头设备列表
+pub fn get_camera_devices() -> Vec<(usize, String)> {
+    match ez_ffmpeg::device::get_input_video_devices() {
+        Ok(devices) => devices.into_iter().enumerate().collect(),
+        Err(e) => {
+            eprintln!("⚠️ 获取摄像头列表失败: {}", e);
+            vec![]
+        }
+    }
+}
+
+#[cfg(all(test, feature = "turbojpeg", target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    /// 截断或缺少 SOI 的帧判为不完整, EOI 后补零的帧判为完整
+    #[test]
+    fn test_jpeg_complete() {
+        assert!(jpeg_complete(&[0xFF, 0xD8, 0x12, 0xFF, 0xD9]));
+        assert!(jpeg_complete(&[0xFF, 0xD8, 0x12, 0xFF, 0xD9, 0, 0]));
+        assert!(!jpeg_complete(&[0xFF, 0xD8, 0x12, 0x34]));
+        assert!(!jpeg_complete(&[0x00, 0xD8, 0xFF, 0xD9]));
+        assert!(!jpeg_complete(&[]));
+    }
+
+    /// 被其他线程持有的缓冲不会被复用, 释放后重新进入轮换
+    #[test]
+    fn test_frame_pool_reuse() {
+        let mut pool = Vec::new();
+        let held = Arc::clone(take_pool_frame(&mut pool, 16));
+        let second = Arc::clone(take_pool_frame(&mut pool, 16));
+        assert!(!Arc::ptr_eq(&held, &second));
+        assert_eq!(pool.len(), 2);
+        drop(second);
+        take_pool_frame(&mut pool, 16);
+        assert_eq!(pool.len(), 2);
+        assert_eq!(take_pool_frame(&mut pool, 32).len(), 32);
+    }
+}
@@ -0,0 +1,184 @@
+/// NVDEC 设备帧过滤器 (feature = "cuda")
+/// NVDEC device frame filter
+///
+/// FFmpeg 以 `hwaccel=cuda` 解码时输出 `AV_PIX_FMT_CUDA` 帧, data[0]/data[1]
+/// 为显存中带步长的 NV12 Y/UV 平面. 这里只做一次显存内拷贝 (去除行填充),
+/// 检测线程直接对设备帧做预处理与推理, 帧数据不经过主机内存.
+/// 界面预览需要 RGBA 时才在 GPU 上转换并回传 (`preview`)
+use super::decode_filter::{DriftMonitor, AV_FRAME_FLAG_KEY};
+use super::decoder_manager::{active_orientation, ACTIVE_DECODER_GENERATION};
+use crate::cuda::DeviceNv12Frame;
+use crate::detection::trace::FrameTrace;
+use crate::detection::types::DecodedFrame;
+use crate::xbus;
+use cudarc::driver::CudaDevice;
+use ez_ffmpeg::filter::frame_filter::FrameFilter;
+use ez_ffmpeg::filter::frame_filter_context::FrameFilterContext;
+use ez_ffmpeg::{AVMediaType, Frame};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// NVDEC解码过滤器: CUDA硬件帧 → 紧凑NV12设备帧
+pub struct CudaDecodeFilter {
+    pub count: usize,
+    pub last: Instant,
+    pub current_fps: f64,
+    pub decoder_name: String,
+    pub dropped_frames: usize,
+    pub total_frames: usize,
+    pub generation: usize,
+    /// 低功耗模式: 只拷贝并发布关键帧
+    pub keyframes_only: bool,
+    /// 是否为界面生成 RGBA 预览 (无界面部署时关闭, 帧完全不回传)
+    preview: bool,
+    dev: Arc<CudaDevice>,
+    frame: Option<Arc<DeviceNv12Frame>>, // 复用的设备帧, 检测线程仍持有时重新分配
+    buffer: Arc<Vec<u8>>,                // RGBA 预览缓冲
+    drift: DriftMonitor,                 // 摄像头 RTP 时间戳与主机时钟的漂移
+}
+
+impl CudaDecodeFilter {
+    pub fn new(generation: usize, preview: bool) -> anyhow::Result<Self> {
+        Ok(Self {
+            count: 0,
+            last: Instant::now(),
+            current_fps: 0.0,
+            decoder_name: String::from("NVDEC硬件解码"),
+            dropped_frames: 0,
+            total_frames: 0,
+            generation,
+            keyframes_only: false,
+            preview,
+            dev: crate::cuda::device(0)?,
+            frame: None,
+            buffer: Arc::new(Vec::new()),
+            drift: DriftMonitor::default(),
+        })
+    }
+
+    /// 取得可写的设备帧 (尺寸不变且无其他持有者时复用)
+    fn writable_frame(&mut self, w: usize, h: usize) -> anyhow::Result<&mut DeviceNv12Frame> {
+        let reusable = matches!(
+            &self.frame,
+            Some(f) if Arc::strong_count(f) == 1 && f.width == w && f.height == h
+        );
+        if !reusable {
+            self.frame = Some(Arc::new(DeviceNv12Frame::new(self.dev.clone(), w, h)?));
+        }
+        Ok(Arc::get_mut(self.frame.as_mut().unwrap()).unwrap())
+    }
+}
+
+impl FrameFilter for CudaDecodeFilter {
+    fn media_type(&self) -> AVMediaType {
+        AVMediaType::AVMEDIA_TYPE_VIDEO
+    }
+
+    fn init(&mut self, _ctx: &FrameFilterContext) -> Result<(), String> {
+        println!("✅ NVDEC解码线程启动 (预览: {})", self.preview);
+        if !active_orientation().is_identity() {
+            println!("⚠️ NVDEC 设备帧暂不支持画面方向校正, 旋转/镜像设置被忽略");
+        }
+        Ok(())
+    }
+
+    fn filter_frame(
+        &mut self,
+        frame: Frame,
+        _ctx: &FrameFilterContext,
+    ) -> Result<Option<Frame>, String> {
+        let current_gen = ACTIVE_DECODER_GENERATION.load(Ordering::Relaxed);
+        if self.generation != current_gen {
+            println!(
+                "🛑 解码器已过期 (Gen: {} != Current: {}), 停止解码",
+                self.generation, current_gen
+            );
+            return Err("Decoder expired".to_string());
+        }
+        let trace = FrameTrace::new();
+
+        unsafe {
+            self.total_frames += 1;
+            if frame.as_ptr().is_null() || frame.is_empty() || frame.is_corrupt() {
+                self.dropped_frames += 1;
+                return Ok(None);
+            }
+
+            let av = &*frame.as_ptr();
+            let (w, h) = (av.width as usize, av.height as usize);
+            // 非硬件帧 (驱动回退到软件解码) 不在此处理
+            if av.hw_frames_ctx.is_null() || av.data[0].is_null() || av.data[1].is_null() {
+                self.dropped_frames += 1;
+                if self.total_frames <= 10 {
+                    println!("⚠️ 丢弃帧 #{}: 非CUDA硬件帧", self.total_frames);
+                }
+                return Ok(None);
+            }
+            if w == 0 || h == 0 || w > 4096 || h > 4096 || av.decode_error_flags & 0x03 != 0 {
+                self.dropped_frames += 1;
+                return Ok(None);
+            }
+
+            let keyframe = av.flags & AV_FRAME_FLAG_KEY != 0;
+            if self.keyframes_only && !keyframe {
+                return Ok(None);
+            }
+            self.drift.observe(&frame, trace.decode_ts, self.generation);
+
+            let (y_plane, uv_plane) = (av.data[0] as u64, av.data[1] as u64);
+            let (y_pitch, uv_pitch) = (av.linesize[0] as usize, av.linesize[1] as usize);
+            let copied = self
+                .writable_frame(w, h)
+                .and_then(|f| f.copy_from_device_planes(y_plane, uv_plane, y_pitch, uv_pitch));
+            if let Err(e) = copied {
+                self.dropped_frames += 1;
+                eprintln!("❌ 设备帧拷贝失败: {}", e);
+                return Ok(None);
+            }
+            let device = Arc::clone(self.frame.as_ref().unwrap());
+
+            // 界面预览: GPU 转 RGBA 后回传
+            if self.preview {
+                if Arc::strong_count(&self.buffer) > 1 {
+                    self.buffer = Arc::new(Vec::new());
+                }
+                if let Err(e) = device.download_rgba(Arc::get_mut(&mut self.buffer).unwrap()) {
+                    eprintln!("❌ 预览帧回传失败: {}", e);
+                }
+            }
+
+            self.count += 1;
+            if self.last.elapsed().as_secs_f64() >= 1.0 {
+                self.current_fps = self.count as f64 / self.last.elapsed().as_secs_f64();
+                println!(
+                    "📺 NVDEC解码统计: 解码{}帧 | 实际{:.1}fps | 总帧{} | 丢弃{}",
+                    self.count, self.current_fps, self.total_frames, self.dropped_frames
+                );
+                self.last = Instant::now();
+                self.count = 0;
+            }
+
+            xbus::post(DecodedFrame {
+                rgba_data: Arc::clone(&self.buffer),
+                width: w as u32,
+                height: h as u32,
+                decode_fps: self.current_fps,
+                decoder_name: self.decoder_name.clone(),
+                yuv: None,
+                keyframe,
+                trace,
+                view: 0,
+                generation: self.generation,
+                yuv16: None,
+                device: Some(device),
+            });
+
+            Ok(Some(frame))
+        }
+    }
+
+    fn uninit(&mut self, _ctx: &FrameFilterContext) {
+        println!("✅ NVDEC解码线程退出");
+    }
+}
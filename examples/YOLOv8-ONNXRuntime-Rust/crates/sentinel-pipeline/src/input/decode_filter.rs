@@ -1,11 +1,17 @@
-use super::decoder_manager::ACTIVE_DECODER_GENERATION;
+use super::backpressure::{FrameGate, MIN_PREVIEW_FPS};
+use super::decoder_manager::{active_orientation, ACTIVE_DECODER_GENERATION};
 use crate::xbus;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 /// FFmpeg解码过滤器模块
 /// FFmpeg decode filter module
-use crate::detection::types::DecodedFrame;
+use crate::detection::trace::FrameTrace;
+use crate::detection::types::{ClockDrift, DecodedFrame};
+use crate::utils::clock::{DriftEstimator, DRIFT_WARN_MS};
+use crate::utils::fisheye::{fisheye_config, Dewarper};
+use crate::utils::hdr::{ToneCurve, Transfer, Yuv420Frame16};
+use crate::utils::yuv_preprocess::Yuv420Frame;
 use ez_ffmpeg::filter::frame_filter::FrameFilter;
 use ez_ffmpeg::filter::frame_filter_context::FrameFilterContext;
 use ez_ffmpeg::{AVMediaType, Frame};
@@ -14,6 +20,21 @@ use std::time::Instant;
 #[cfg(target_arch = "x86_64")]
 use std::arch::x86_64::*;
 
+/// AVFrame.flags 关键帧标志 (FFmpeg 6.1+ 取代 key_frame 字段)
+pub(crate) const AV_FRAME_FLAG_KEY: i32 = 1 << 1;
+
+/// AV_NOPTS_VALUE (帧没有时间戳)
+const AV_NOPTS_VALUE: i64 = i64::MIN;
+
+/// RTP 视频时钟 (90kHz), 帧未携带 time_base 时按此换算 pts
+const RTP_VIDEO_CLOCK: f64 = 90_000.0;
+
+/// AV_PIX_FMT_YUV420P (像素格式枚举的第一个值)
+const AV_PIX_FMT_YUV420P: i32 = 0;
+
+/// 高位深解码图的输出格式: 8-bit 源保持 yuv420p, 10-bit 源 (含 P010) 转为 yuv420p10le
+pub const HIGH_DEPTH_FORMATS: &str = "format=yuv420p|yuv420p10le";
+
 /// FFmpeg解码过滤器: RTSP流 → RGBA帧 (极速优化版)
 #[derive(Clone)]
 pub struct DecodeFilter {
@@ -25,6 +46,18 @@ pub struct DecodeFilter {
     pub total_frames: usize,   // 总帧数
     pub generation: usize,     // 解码器代数ID
     buffer: Arc<Vec<u8>>,      // Arc包装避免每帧clone
+    yuv: Arc<Yuv420Frame>,     // 紧凑YUV平面 (供检测线程融合预处理)
+    raw: Yuv420Frame,          // 方向校正前的暂存平面
+    pub keyframes_only: bool,  // 低功耗模式: 只转换并发布关键帧
+    gate: FrameGate,           // 检测队列满时跳过转换与发布
+    // 解码图以 HIGH_DEPTH_FORMATS 结尾时为 true, 非 yuv420p 帧按 yuv420p10le 处理
+    pub high_depth: bool,
+    hdr: Option<Arc<Yuv420Frame16>>, // 10-bit 源的 16 位平面 (供检测线程采样)
+    tone: Yuv420Frame,               // 10-bit 源色调映射后的 8 位平面
+    // 鱼眼去畸变 (未配置时为 None) 与各虚拟视图的 RGBA/YUV 缓冲
+    dewarper: Option<Dewarper>,
+    views: Vec<(Arc<Vec<u8>>, Arc<Yuv420Frame>)>,
+    drift: DriftMonitor, // 摄像头 RTP 时间戳与主机时钟的漂移
 }
 
 impl DecodeFilter {
@@ -38,7 +71,61 @@ impl DecodeFilter {
             total_frames: 0,
             generation,
             buffer: Arc::new(Vec::new()),
+            yuv: Arc::new(Yuv420Frame::default()),
+            raw: Yuv420Frame::default(),
+            keyframes_only: false,
+            gate: FrameGate::new(MIN_PREVIEW_FPS),
+            high_depth: false,
+            hdr: None,
+            tone: Yuv420Frame::default(),
+            dewarper: fisheye_config().cloned().map(Dewarper::new),
+            views: Vec::new(),
+            drift: DriftMonitor::default(),
+        }
+    }
+}
+
+/// 解码帧的摄像头时钟漂移监控 (软件解码与 NVDEC 过滤器共用)
+#[derive(Clone, Default)]
+pub(crate) struct DriftMonitor {
+    estimator: DriftEstimator,
+    warned: bool,
+}
+
+impl DriftMonitor {
+    /// 按帧 pts (RTP 时间戳换算而来) 与主机到达时刻更新漂移, 每秒发布一次 [`ClockDrift`],
+    /// 超过阈值与恢复时各提示一次
+    ///
+    /// # Safety
+    /// `frame` 须为有效的解码帧
+    pub(crate) unsafe fn observe(&mut self, frame: &Frame, host: Instant, generation: usize) {
+        let av = &*frame.as_ptr();
+        if av.best_effort_timestamp == AV_NOPTS_VALUE {
+            return;
+        }
+        let media_secs = if av.time_base.num > 0 && av.time_base.den > 0 {
+            av.best_effort_timestamp as f64 * av.time_base.num as f64 / av.time_base.den as f64
+        } else {
+            av.best_effort_timestamp as f64 / RTP_VIDEO_CLOCK
+        };
+        let Some(report) = self.estimator.observe(media_secs, host) else {
+            return;
+        };
+        let exceeded = report.exceeds(DRIFT_WARN_MS);
+        if exceeded && !self.warned {
+            eprintln!(
+                "⚠️ 摄像头时钟与主机相差 {:+.0}ms ({:+.0}ppm), 请检查摄像头 NTP 校时",
+                report.offset_ms, report.ppm
+            );
+        } else if !exceeded && self.warned {
+            println!("✅ 摄像头时钟漂移已恢复 ({:+.0}ms)", report.offset_ms);
         }
+        self.warned = exceeded;
+        xbus::post(ClockDrift {
+            generation,
+            offset_ms: report.offset_ms,
+            ppm: report.ppm,
+        });
     }
 }
 
@@ -49,6 +136,12 @@ impl FrameFilter for DecodeFilter {
 
     fn init(&mut self, _ctx: &FrameFilterContext) -> Result<(), String> {
         println!("✅ 解码线程启动");
+        if let Some(dewarper) = &self.dewarper {
+            println!("🐟 鱼眼去畸变: {}个虚拟视图", dewarper.view_count());
+            self.views = (0..dewarper.view_count())
+                .map(|_| (Arc::new(Vec::new()), Arc::new(Yuv420Frame::default())))
+                .collect();
+        }
         Ok(())
     }
 
@@ -66,6 +159,8 @@ impl FrameFilter for DecodeFilter {
             );
             return Err("Decoder expired".to_string());
         }
+        // 采集时间戳: 帧刚从解码器输出 (转换与拷贝计入排队阶段)
+        let trace = FrameTrace::new();
 
         unsafe {
             self.total_frames += 1;
@@ -105,7 +200,9 @@ impl FrameFilter for DecodeFilter {
                 return Ok(None);
             }
 
-            // YUV420P数据指针
+            // YUV420P数据指针 (10-bit 帧每个样本 2 字节)
+            let high_depth = self.high_depth && (*frame.as_ptr()).format != AV_PIX_FMT_YUV420P;
+            let sample_bytes = if high_depth { 2 } else { 1 };
             let y_plane = (*frame.as_ptr()).data[0];
             let u_plane = (*frame.as_ptr()).data[1];
             let v_plane = (*frame.as_ptr()).data[2];
@@ -120,7 +217,7 @@ impl FrameFilter for DecodeFilter {
                 return Ok(None);
             }
 
-            if y_stride < w as usize || uv_stride < (w as usize / 2) {
+            if y_stride < w as usize * sample_bytes || uv_stride < (w as usize / 2) * sample_bytes {
                 self.dropped_frames += 1;
                 if self.total_frames <= 10 {
                     println!(
@@ -131,8 +228,91 @@ impl FrameFilter for DecodeFilter {
                 return Ok(None);
             }
 
+            // 低功耗模式: 解码器未应用 skip_frame 时在此丢弃非关键帧
+            let keyframe = (*frame.as_ptr()).flags & AV_FRAME_FLAG_KEY != 0;
+            if self.keyframes_only && !keyframe {
+                return Ok(None);
+            }
+
             self.count += 1;
 
+            // 时钟漂移: 帧 pts 与主机到达时刻比较
+            self.drift.observe(&frame, trace.decode_ts, self.generation);
+
+            // 背压: 检测队列已满, 跳过转换与发布 (保留最低预览帧率)
+            if !self.gate.admit(trace.decode_ts) {
+                return Ok(None);
+            }
+
+            // 10-bit 帧: 保留 16 位平面, 色调映射为 8 位后走原有处理链
+            let (y_plane, u_plane, v_plane, y_stride, uv_stride) = if high_depth {
+                let transfer = Transfer::from_av((*frame.as_ptr()).color_trc as i32);
+                let curve = match &self.hdr {
+                    Some(hdr) if hdr.curve.transfer == transfer => Arc::clone(&hdr.curve),
+                    _ => {
+                        println!("🌈 10-bit 输入, 传输特性 {:?}, 色调映射后显示", transfer);
+                        Arc::new(ToneCurve::new(transfer, 10))
+                    }
+                };
+                let reusable = self.hdr.as_ref().is_some_and(|hdr| {
+                    Arc::strong_count(hdr) == 1 && Arc::ptr_eq(&hdr.curve, &curve)
+                });
+                if !reusable {
+                    self.hdr = Some(Arc::new(Yuv420Frame16::new(curve)));
+                }
+                let hdr = Arc::get_mut(self.hdr.as_mut().unwrap()).unwrap();
+                hdr.copy_from_planes(
+                    y_plane, u_plane, v_plane, y_stride, uv_stride, w as usize, h as usize,
+                );
+                hdr.tone_map(&mut self.tone);
+                (
+                    self.tone.y.as_mut_ptr(),
+                    self.tone.u.as_mut_ptr(),
+                    self.tone.v.as_mut_ptr(),
+                    self.tone.width,
+                    self.tone.chroma_width(),
+                )
+            } else {
+                (y_plane, u_plane, v_plane, y_stride, uv_stride)
+            };
+
+            // 保留紧凑YUV平面 (1.5字节/像素), 检测线程据此跳过RGBA→RGB→DynamicImage
+            if Arc::strong_count(&self.yuv) > 1 {
+                self.yuv = Arc::new(Yuv420Frame::default());
+            }
+            let yuv = Arc::get_mut(&mut self.yuv).unwrap();
+            let orientation = active_orientation();
+            let (y_plane, u_plane, v_plane, y_stride, uv_stride, w, h) =
+                if orientation.is_identity() {
+                    yuv.copy_from_planes(
+                        y_plane, u_plane, v_plane, y_stride, uv_stride, w as usize, h as usize,
+                    );
+                    (
+                        y_plane as *const u8,
+                        u_plane as *const u8,
+                        v_plane as *const u8,
+                        y_stride,
+                        uv_stride,
+                        w,
+                        h,
+                    )
+                } else {
+                    // 画面方向: 在紧凑YUV平面上旋转/镜像, 再从校正后的平面转RGBA
+                    self.raw.copy_from_planes(
+                        y_plane, u_plane, v_plane, y_stride, uv_stride, w as usize, h as usize,
+                    );
+                    orientation.apply_yuv(&self.raw, yuv);
+                    (
+                        yuv.y.as_ptr(),
+                        yuv.u.as_ptr(),
+                        yuv.v.as_ptr(),
+                        yuv.width,
+                        yuv.chroma_width(),
+                        yuv.width as u32,
+                        yuv.height as u32,
+                    )
+                };
+
             // YUV420P → RGBA (SIMD优化版 - AVX2加速)
             let pixel_count = (w * h) as usize;
             let required_size = pixel_count * 4;
@@ -147,26 +327,9 @@ impl FrameFilter for DecodeFilter {
 
             // 获取可变引用并使用SIMD优化的YUV转换
             let buffer = Arc::get_mut(&mut self.buffer).unwrap();
-
-            #[cfg(target_arch = "x86_64")]
-            {
-                if is_x86_feature_detected!("avx2") {
-                    yuv420p_to_rgba_avx2(
-                        y_plane, u_plane, v_plane, y_stride, uv_stride, buffer, w_usize, h_usize,
-                    );
-                } else {
-                    yuv420p_to_rgba_scalar(
-                        y_plane, u_plane, v_plane, y_stride, uv_stride, buffer, w_usize, h_usize,
-                    );
-                }
-            }
-
-            #[cfg(not(target_arch = "x86_64"))]
-            {
-                yuv420p_to_rgba_scalar(
-                    y_plane, u_plane, v_plane, y_stride, uv_stride, buffer, w_usize, h_usize,
-                );
-            }
+            yuv420p_to_rgba(
+                y_plane, u_plane, v_plane, y_stride, uv_stride, buffer, w_usize, h_usize,
+            );
 
             // 计算FPS
             if self.last.elapsed().as_secs_f64() >= 1.0 {
@@ -176,8 +339,13 @@ impl FrameFilter for DecodeFilter {
 
                 // 每秒打印一次解码统计
                 println!(
-                    "📺 解码统计: 解码{}帧 | 实际{:.1}fps | 总帧{} | 丢弃{} ({:.1}%)",
-                    self.count, self.current_fps, self.total_frames, self.dropped_frames, drop_rate
+                    "📺 解码统计: 解码{}帧 | 实际{:.1}fps | 总帧{} | 丢弃{} ({:.1}%) | 背压跳过{}",
+                    self.count,
+                    self.current_fps,
+                    self.total_frames,
+                    self.dropped_frames,
+                    drop_rate,
+                    self.gate.skipped
                 );
 
                 self.last = Instant::now();
@@ -190,10 +358,65 @@ impl FrameFilter for DecodeFilter {
                 height: h,
                 decode_fps: self.current_fps,
                 decoder_name: self.decoder_name.clone(),
+                yuv: Some(Arc::clone(&self.yuv)),
+                keyframe,
+                trace,
+                view: 0,
+                generation: self.generation,
+                // 16 位平面未做方向校正, 只在无旋转/镜像时提供
+                yuv16: self
+                    .hdr
+                    .as_ref()
+                    .filter(|_| high_depth && orientation.is_identity())
+                    .map(Arc::clone),
+                #[cfg(feature = "cuda")]
+                device: None,
             };
 
             xbus::post(decoded);
 
+            // 鱼眼去畸变: 每个虚拟视图作为独立逻辑流发布 (编号从1开始)
+            if let Some(dewarper) = self.dewarper.as_mut() {
+                for (i, (rgba, view_yuv)) in self.views.iter_mut().enumerate() {
+                    if Arc::strong_count(view_yuv) > 1 {
+                        *view_yuv = Arc::new(Yuv420Frame::default());
+                    }
+                    let dst = Arc::get_mut(view_yuv).unwrap();
+                    dewarper.dewarp(&self.yuv, i, dst);
+
+                    let required_size = dst.width * dst.height * 4;
+                    if Arc::strong_count(rgba) > 1 || rgba.len() != required_size {
+                        *rgba = Arc::new(vec![255; required_size]);
+                    }
+                    yuv420p_to_rgba(
+                        dst.y.as_ptr(),
+                        dst.u.as_ptr(),
+                        dst.v.as_ptr(),
+                        dst.width,
+                        dst.chroma_width(),
+                        Arc::get_mut(rgba).unwrap(),
+                        dst.width,
+                        dst.height,
+                    );
+
+                    xbus::post(DecodedFrame {
+                        rgba_data: Arc::clone(rgba),
+                        width: dst.width as u32,
+                        height: dst.height as u32,
+                        decode_fps: self.current_fps,
+                        decoder_name: self.decoder_name.clone(),
+                        yuv: Some(Arc::clone(view_yuv)),
+                        keyframe,
+                        trace,
+                        view: i as u32 + 1,
+                        generation: self.generation,
+                        yuv16: None,
+                        #[cfg(feature = "cuda")]
+                        device: None,
+                    });
+                }
+            }
+
             Ok(Some(frame))
         }
     }
@@ -203,6 +426,39 @@ impl FrameFilter for DecodeFilter {
     }
 }
 
+/// YUV420P → RGBA (运行时选择 AVX2 或标量实现)
+#[allow(clippy::too_many_arguments)]
+pub(crate) unsafe fn yuv420p_to_rgba(
+    y_plane: *const u8,
+    u_plane: *const u8,
+    v_plane: *const u8,
+    y_stride: usize,
+    uv_stride: usize,
+    buffer: &mut [u8],
+    width: usize,
+    height: usize,
+) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            yuv420p_to_rgba_avx2(
+                y_plane, u_plane, v_plane, y_stride, uv_stride, buffer, width, height,
+            );
+        } else {
+            yuv420p_to_rgba_scalar(
+                y_plane, u_plane, v_plane, y_stride, uv_stride, buffer, width, height,
+            );
+        }
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        yuv420p_to_rgba_scalar(
+            y_plane, u_plane, v_plane, y_stride, uv_stride, buffer, width, height,
+        );
+    }
+}
+
 /// 标量版本YUV转换(fallback)
 #[inline]
 unsafe fn yuv420p_to_rgba_scalar(
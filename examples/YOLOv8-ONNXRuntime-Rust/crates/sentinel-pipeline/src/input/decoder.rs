@@ -0,0 +1,248 @@
+/// RTSP主动拉流解码器
+/// RTSP active pulling decoder (software decoding, optional NVDEC with feature "cuda")
+use super::decode_filter::{DecodeFilter, HIGH_DEPTH_FORMATS};
+use super::rtsp_security::{redact_url, rtsp_security};
+#[cfg(feature = "cuda")]
+use crate::utils::fisheye::fisheye_config;
+use ez_ffmpeg::core::context::null_output::create_null_output;
+use ez_ffmpeg::filter::frame_pipeline_builder::FramePipelineBuilder;
+use ez_ffmpeg::{AVMediaType, FfmpegContext, Input};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(feature = "cuda")]
+use super::cuda_filter::CudaDecodeFilter;
+
+/// RTSP解码器
+pub struct Decoder {
+    rtsp_url: String,
+    generation: usize,
+    preference: DecoderPreference,
+}
+
+impl Decoder {
+    /// 创建RTSP解码器
+    pub fn new(rtsp_url: String, generation: usize, preference: DecoderPreference) -> Self {
+        Self {
+            rtsp_url,
+            generation,
+            preference,
+        }
+    }
+
+    /// 运行RTSP解码
+    pub fn run(&mut self) {
+        println!("🎬 RTSP解码器启动 (Gen: {})", self.generation);
+        println!("📹 流地址: {}", redact_url(&self.rtsp_url));
+        println!("⚙️ 解码偏好: {:?}", self.preference);
+
+        let mut filter = DecodeFilter::new(self.generation);
+        filter.keyframes_only = keyframes_only();
+        if filter.keyframes_only {
+            println!("🔑 低功耗模式: 仅解码关键帧");
+        }
+        // 按主机补上凭据 (凭据只在连接时出现在地址中, 不写入日志)
+        let url = match rtsp_security().resolve_url(&self.rtsp_url) {
+            Ok(url) => url,
+            Err(e) => {
+                eprintln!("❌ RTSP 连接被拒绝: {}", e);
+                return;
+            }
+        };
+        adaptive_decode(&url, filter, &self.preference);
+
+        println!("❌ RTSP解码器退出");
+    }
+}
+
+/// 解码器偏好设置 (默认CPU软件解码)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DecoderPreference {
+    Software,
+    /// NVDEC 硬件解码, 帧保留在显存中供 CUDA 端到端管线使用
+    #[cfg(feature = "cuda")]
+    Nvdec,
+}
+
+/// 进程级解码偏好 (启动参数设置, 切换输入源时生效)
+#[cfg(feature = "cuda")]
+static PREFER_NVDEC: AtomicBool = AtomicBool::new(false);
+
+/// 仅解码关键帧 (低功耗模式, 切换输入源时生效)
+static KEYFRAMES_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// 设置 RTSP 流是否只解码 I/IDR 帧 (检测频率随 GOP 降到约 1fps)
+pub fn set_keyframes_only(enabled: bool) {
+    KEYFRAMES_ONLY.store(enabled, Ordering::Relaxed);
+}
+
+/// 当前是否只解码关键帧
+pub fn keyframes_only() -> bool {
+    KEYFRAMES_ONLY.load(Ordering::Relaxed)
+}
+
+/// RTSP 输入参数; 关键帧模式下让解码器跳过所有非关键帧 (skip_frame=nokey)
+/// rtsps:// 地址附加 TLS 参数 (证书校验/CA/客户端证书), 媒体走 TLS 内的 TCP 交织传输
+fn rtsp_input_opts(rtsp_url: &str, keyframes_only: bool) -> HashMap<String, String> {
+    let mut opts: HashMap<String, String> = [
+        ("rtsp_transport", "tcp"),
+        ("buffer_size", "67108864"),
+        ("rtsp_flags", "prefer_tcp"),
+        ("thread_queue_size", "1024"),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_string(), v.to_string()))
+    .collect();
+    if keyframes_only {
+        opts.insert("skip_frame".to_string(), "nokey".to_string());
+    }
+    if rtsp_url.starts_with("rtsps://") {
+        for (key, value) in rtsp_security().tls_opts() {
+            opts.insert(key.to_string(), value);
+        }
+    }
+    opts
+}
+
+impl DecoderPreference {
+    pub fn name(&self) -> &str {
+        match self {
+            DecoderPreference::Software => "CPU软件解码",
+            #[cfg(feature = "cuda")]
+            DecoderPreference::Nvdec => "NVDEC硬件解码",
+        }
+    }
+
+    /// 当前生效的解码偏好
+    pub fn preferred() -> Self {
+        // 鱼眼去畸变需要 CPU 端 YUV 平面, 此时回退到软件解码
+        #[cfg(feature = "cuda")]
+        if PREFER_NVDEC.load(Ordering::Relaxed) && fisheye_config().is_none() {
+            return DecoderPreference::Nvdec;
+        }
+        DecoderPreference::Software
+    }
+
+    /// 设置 RTSP 流是否优先使用 NVDEC
+    #[cfg(feature = "cuda")]
+    pub fn set_prefer_nvdec(enabled: bool) {
+        PREFER_NVDEC.store(enabled, Ordering::Relaxed);
+    }
+}
+
+/// CPU软件解码
+fn software_decode(
+    rtsp_url: &str,
+    mut filter: DecodeFilter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("🔍 使用CPU软件解码");
+
+    filter.decoder_name = "CPU软件解码".to_string();
+    filter.high_depth = true;
+    let keyframes_only = filter.keyframes_only;
+
+    // 清除可能存在的硬件加速环境变量
+    std::env::remove_var("FFMPEG_HWACCEL");
+
+    // RTSP传输优化
+    std::env::set_var("FFMPEG_RTSP_TRANSPORT", "tcp");
+    std::env::set_var("FFMPEG_RTSP_FLAGS", "prefer_tcp");
+    std::env::set_var("FFMPEG_BUFFER_SIZE", "8192000");
+
+    // 低延迟参数
+    std::env::set_var("FFMPEG_FLAGS", "low_delay");
+    std::env::set_var("FFMPEG_FFLAGS", "nobuffer");
+
+    // 解码质量优化
+    let skip_frame = if keyframes_only { "nokey" } else { "noref" };
+    std::env::set_var("FFMPEG_SKIP_FRAME", skip_frame);
+    std::env::set_var("FFMPEG_SKIP_LOOP_FILTER", "noref");
+    std::env::set_var("FFMPEG_ERR_DETECT", "careful");
+
+    // 多线程解码
+    std::env::set_var("FFMPEG_THREADS", "auto");
+    std::env::set_var("FFMPEG_THREAD_TYPE", "frame+slice");
+
+    let pipe: FramePipelineBuilder = AVMediaType::AVMEDIA_TYPE_VIDEO.into();
+    let pipe = pipe.filter("decode", Box::new(filter));
+    let out = create_null_output().add_frame_pipeline(pipe);
+
+    let mut opts = rtsp_input_opts(rtsp_url, keyframes_only);
+    opts.insert("thread".to_string(), "4".to_string());
+    let input = Input::new(rtsp_url).set_input_opts(opts);
+    // 构建FFmpeg上下文: 缩放到 1080p, 10-bit 源保持 16 位样本 (不在解码图中截断)
+    let scale = format!("scale=1920x1080,{}", HIGH_DEPTH_FORMATS);
+    let ctx = FfmpegContext::builder()
+        .input(input)
+        .filter_descs([scale.as_str()].into())
+        .output(out)
+        .build()
+        .map_err(|e| format!("构建失败: {}", e))?;
+
+    let sch = ctx.start().map_err(|e| format!("启动失败: {}", e))?;
+    println!("✅ CPU软件解码启动成功");
+
+    let _ = sch.wait();
+    Ok(())
+}
+
+/// NVDEC硬件解码 (帧保留在显存, 不经过 sws_scale)
+#[cfg(feature = "cuda")]
+pub fn nvdec_decode(
+    rtsp_url: &str,
+    filter: CudaDecodeFilter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("🔍 使用NVDEC硬件解码");
+    let keyframes_only = filter.keyframes_only;
+
+    let pipe: FramePipelineBuilder = AVMediaType::AVMEDIA_TYPE_VIDEO.into();
+    let pipe = pipe.filter("decode", Box::new(filter));
+    let out = create_null_output().add_frame_pipeline(pipe);
+
+    let input = Input::new(rtsp_url)
+        .set_hwaccel("cuda")
+        .set_hwaccel_output_format("cuda")
+        .set_input_opts(rtsp_input_opts(rtsp_url, keyframes_only));
+    let ctx = FfmpegContext::builder()
+        .input(input)
+        .output(out)
+        .build()
+        .map_err(|e| format!("构建失败: {}", e))?;
+
+    let sch = ctx.start().map_err(|e| format!("启动失败: {}", e))?;
+    println!("✅ NVDEC硬件解码启动成功");
+
+    let _ = sch.wait();
+    Ok(())
+}
+
+/// 按偏好解码, NVDEC 不可用时回退到CPU软件解码
+pub fn adaptive_decode(rtsp_url: &str, filter: DecodeFilter, preference: &DecoderPreference) {
+    println!("🔄 解码策略: {}", preference.name());
+
+    #[cfg(feature = "cuda")]
+    if *preference == DecoderPreference::Nvdec {
+        match CudaDecodeFilter::new(filter.generation, true) {
+            Ok(mut cuda_filter) => {
+                cuda_filter.keyframes_only = filter.keyframes_only;
+                match nvdec_decode(rtsp_url, cuda_filter) {
+                    Ok(_) => {
+                        println!("✅ 解码线程正常退出");
+                        return;
+                    }
+                    Err(e) => eprintln!("❌ NVDEC硬件解码失败, 回退到CPU: {}", e),
+                }
+            }
+            Err(e) => eprintln!("❌ CUDA设备不可用, 回退到CPU: {}", e),
+        }
+    }
+
+    match software_decode(rtsp_url, filter) {
+        Ok(_) => {
+            println!("✅ 解码线程正常退出");
+        }
+        Err(e) => {
+            eprintln!("❌ CPU软件解码失败: {}", e);
+        }
+    }
+}
@@ -0,0 +1,234 @@
+/// 解码器管理器 - 支持动态切换输入源
+use super::rtsp_security::redact_url;
+use super::vault::strip_password;
+use crate::utils::orientation::{Orientation, OrientationConfig, ORIENTATION_CONFIG_PATH};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// 全局活跃解码器代数ID (用于平滑切换)
+pub static ACTIVE_DECODER_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+/// 当前输入源的画面方向 (解码过滤器逐帧读取, 修改立即生效)
+static ACTIVE_ORIENTATION: AtomicU8 = AtomicU8::new(0);
+
+/// 当前输入源
+static ACTIVE_SOURCE: Mutex<Option<InputSource>> = Mutex::new(None);
+
+/// 当前输入源, 尚未启动任何输入源时为 None
+pub fn active_source() -> Option<InputSource> {
+    ACTIVE_SOURCE.lock().unwrap().clone()
+}
+
+/// 当前输入源的配置键, 尚未启动任何输入源时为 None
+pub fn active_source_key() -> Option<String> {
+    ACTIVE_SOURCE.lock().unwrap().as_ref().map(InputSource::key)
+}
+
+/// 当前输入源的画面方向
+pub fn active_orientation() -> Orientation {
+    Orientation::from_bits(ACTIVE_ORIENTATION.load(Ordering::Relaxed))
+}
+
+/// 设置当前输入源的画面方向
+pub fn set_active_orientation(orientation: Orientation) {
+    ACTIVE_ORIENTATION.store(orientation.to_bits(), Ordering::Relaxed);
+}
+
+/// 输入源类型
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum InputSource {
+    Rtsp(String),          // RTSP流
+    Camera(usize, String), // 本地摄像头 (索引, 名称)
+    Desktop,               // 桌面捕获
+    GigE(String),          // GenICam/GigE Vision 工业相机 (设备ID)
+    Shm(String),           // 共享内存环形缓冲 (映射文件路径)
+    Synthetic(String),     // 合成场景 (场景文件路径, 为空时使用内置场景)
+}
+
+impl InputSource {
+    /// 配置键 (按输入源保存画面方向等设置)
+    pub fn key(&self) -> String {
+        match self {
+            InputSource::Rtsp(url) => format!("rtsp:{}", strip_password(url)),
+            InputSource::Camera(_, name) => format!("camera:{}", name),
+            InputSource::Desktop => "desktop".to_string(),
+            InputSource::GigE(id) => format!("gige:{}", id),
+            InputSource::Shm(path) => format!("shm:{}", path),
+            InputSource::Synthetic(path) => format!("synthetic:{}", path),
+        }
+    }
+}
+
+/// 视频设备信息
+#[derive(Debug, Clone)]
+pub struct VideoDevice {
+    pub name: String,
+    pub index: usize,
+}
+
+/// 解码器管理器（简化版）
+pub struct DecoderManager;
+
+impl DecoderManager {
+    pub fn new(_initial_source: InputSource) -> Self {
+        Self
+    }
+}
+
+/// 切换输入源 - 在新线程中启动解码器
+pub fn switch_decoder_source(source: InputSource, preference: super::decoder::DecoderPreference) {
+    println!("\n🔄 ============ 切换输入源 ============");
+
+    use super::{
+        CameraDecoder, Decoder, DesktopDecoder, ShmDecoder, SyntheticDecoder, SyntheticScene,
+    };
+    use crate::runtime_config::{pin_current_thread, ThreadRole};
+    use std::thread;
+
+    // 1. 增加代数ID，使旧解码器失效
+    let new_gen = ACTIVE_DECODER_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    println!("🔄 切换解码器代数: {} -> {}", new_gen - 1, new_gen);
+
+    // 2. 恢复该输入源保存的画面方向
+    let key = source.key();
+    let orientation = OrientationConfig::load(ORIENTATION_CONFIG_PATH).get(&key);
+    *ACTIVE_SOURCE.lock().unwrap() = Some(source.clone());
+    set_active_orientation(orientation);
+    if !orientation.is_identity() {
+        println!(
+            "🔃 画面方向: 旋转{} 镜像{}",
+            orientation.rotation.name(),
+            orientation.mirror
+        );
+    }
+
+    match source {
+        InputSource::Rtsp(url) => {
+            println!("📹 新输入源: RTSP流");
+            println!("   地址: {}", redact_url(&url));
+
+            thread::spawn(move || {
+                // 等待旧解码器退出
+                std::thread::sleep(std::time::Duration::from_millis(500));
+                pin_current_thread(ThreadRole::Decode);
+                let mut decoder = Decoder::new(url, new_gen, preference);
+                decoder.run();
+            });
+        }
+        InputSource::Camera(index, name) => {
+            println!("📷 新输入源: 本地摄像头");
+            println!("   设备索引: {}", index);
+            println!("   设备名称: {}", name);
+
+            thread::spawn(move || {
+                // 等待旧解码器退出 (摄像头释放需要更多时间)
+                std::thread::sleep(std::time::Duration::from_millis(1000));
+                pin_current_thread(ThreadRole::Decode);
+                let mut camera = CameraDecoder::new(index, name, new_gen);
+                camera.run();
+            });
+        }
+        InputSource::Desktop => {
+            println!("🖥️ 新输入源: 桌面捕获");
+
+            thread::spawn(move || {
+                // 等待旧解码器退出
+                std::thread::sleep(std::time::Duration::from_millis(500));
+                pin_current_thread(ThreadRole::Decode);
+                let mut desktop = DesktopDecoder::new(new_gen);
+                desktop.run();
+            });
+        }
+        #[cfg(feature = "aravis")]
+        InputSource::GigE(id) => {
+            println!("🏭 新输入源: 工业相机");
+            println!("   相机ID: {}", id);
+
+            thread::spawn(move || {
+                // 等待旧解码器退出 (相机释放控制权需要更多时间)
+                std::thread::sleep(std::time::Duration::from_millis(1000));
+                pin_current_thread(ThreadRole::Decode);
+                let mut gige = super::GigeDecoder::new(id, new_gen);
+                gige.run();
+            });
+        }
+        #[cfg(not(feature = "aravis"))]
+        InputSource::GigE(id) => {
+            eprintln!("❌ 工业相机输入需要启用 aravis 功能 (相机ID: {})", id);
+            return;
+        }
+        InputSource::Shm(path) => {
+            println!("🧩 新输入源: 共享内存");
+            println!("   映射文件: {}", path);
+
+            thread::spawn(move || {
+                // 等待旧解码器退出
+                std::thread::sleep(std::time::Duration::from_millis(500));
+                pin_current_thread(ThreadRole::Decode);
+                let mut shm = ShmDecoder::new(path, new_gen);
+                shm.run();
+            });
+        }
+        InputSource::Synthetic(path) => {
+            println!("🧪 新输入源: 合成场景");
+            let scene = match SyntheticScene::load(&path) {
+                Ok(scene) => scene,
+                Err(e) => {
+                    eprintln!("❌ 合成场景加载失败: {:#}", e);
+                    return;
+                }
+            };
+            println!(
+                "   场景: {} ({}x{} @ {}fps, {}个目标)",
+                if path.is_empty() { "内置" } else { &path },
+                scene.width,
+                scene.height,
+                scene.fps,
+                scene.objects.len()
+            );
+
+            thread::spawn(move || {
+                // 等待旧解码器退出
+                std::thread::sleep(std::time::Duration::from_millis(500));
+                pin_current_thread(ThreadRole::Decode);
+                let mut synthetic = SyntheticDecoder::new(scene, new_gen);
+                synthetic.run();
+            });
+        }
+    }
+
+    println!("✅ 解码器已在后台线程启动");
+    println!("========================================\n");
+}
+
+pub fn should_stop() -> bool {
+    false // 占位函数
+}
+
+/// 获取可用的视频设备列表
+pub fn get_video_devices() -> Vec<VideoDevice> {
+    println!("🔍 正在扫描视频设备...");
+
+    match ez_ffmpeg::device::get_input_video_devices() {
+        Ok(devices) => {
+            println!("✅ 找到 {} 个视频设备", devices.len());
+            devices
+                .into_iter()
+                .enumerate()
+                .map(|(index, name)| {
+                    println!("   [{}] {}", index, name);
+                    VideoDevice { name, index }
+                })
+                .collect()
+        }
+        Err(e) => {
+            println!("⚠️  获取设备列表失败: {}", e);
+            // 返回默认设备
+            vec![VideoDevice {
+                name: "默认摄像头".to_string(),
+                index: 0,
+            }]
+        }
+    }
+}
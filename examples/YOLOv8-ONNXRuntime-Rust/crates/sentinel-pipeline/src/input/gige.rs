@@ -0,0 +1,163 @@
+//! 工业相机输入模块 - GenICam / GigE Vision (Aravis)
+//!
+//! 直接从相机取原始 Bayer/Mono8 缓冲, 在输入阶段去马赛克为 RGBA 后按普通帧发布.
+//! 工业相机不经过 FFmpeg, 因此没有 YUV 平面: 检测线程走 RGBA 路径, 画面方向与鱼眼去畸变不生效.
+
+use super::backpressure::{FrameGate, MIN_PREVIEW_FPS};
+use super::bayer::{demosaic_method, raw_to_rgba, RawFormat};
+use super::decoder_manager::ACTIVE_DECODER_GENERATION;
+use crate::detection::trace::FrameTrace;
+use crate::detection::types::DecodedFrame;
+use crate::xbus;
+use anyhow::{anyhow, Context, Result};
+use aravis::prelude::*;
+use aravis::{Aravis, Buffer, BufferStatus, Camera};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// 采集缓冲数量 (相机侧环形队列)
+const STREAM_BUFFERS: usize = 8;
+
+/// 单次取帧超时 (微秒), 超时后重新检查代数ID
+const POP_TIMEOUT_US: u64 = 200_000;
+
+/// 枚举已发现的 GenICam 相机 (设备ID)
+pub fn list_gige_cameras() -> Vec<String> {
+    if let Err(e) = Aravis::initialize() {
+        eprintln!("⚠️ Aravis 初始化失败: {}", e);
+        return Vec::new();
+    }
+    let cameras: Vec<String> = Aravis::get_device_list()
+        .into_iter()
+        .map(|info| info.id.to_string_lossy().into_owned())
+        .collect();
+    println!("🏭 找到 {} 台工业相机", cameras.len());
+    cameras
+}
+
+/// GigE Vision 相机解码器
+pub struct GigeDecoder {
+    camera_id: String,
+    generation: usize,
+}
+
+impl GigeDecoder {
+    /// 创建工业相机解码器
+    pub fn new(camera_id: String, generation: usize) -> Self {
+        Self {
+            camera_id,
+            generation,
+        }
+    }
+
+    /// 启动采集, 代数ID变化后退出
+    pub fn run(&mut self) {
+        println!(
+            "\n🏭 ============ 工业相机解码器 (Gen: {}) ============",
+            self.generation
+        );
+        println!("📷 相机ID: {}", self.camera_id);
+
+        match self.acquire() {
+            Ok(_) => println!("🏭 工业相机采集结束"),
+            Err(e) => eprintln!("❌ 工业相机采集失败: {:#}", e),
+        }
+    }
+
+    fn acquire(&self) -> Result<()> {
+        Aravis::initialize().map_err(|e| anyhow!("Aravis 初始化失败: {}", e))?;
+        let camera = Camera::new(Some(&self.camera_id)).context("打开相机失败")?;
+        let pixel_format = camera
+            .pixel_format_as_string()
+            .context("读取像素格式失败")?
+            .to_string();
+        let format = RawFormat::from_pfnc(&pixel_format).ok_or_else(|| {
+            anyhow!(
+                "不支持的像素格式 {} (仅支持 BayerRG8/GR8/GB8/BG8 与 Mono8)",
+                pixel_format
+            )
+        })?;
+        let (_, _, width, height) = camera.region().context("读取图像区域失败")?;
+        let (width, height) = (width as usize, height as usize);
+        let method = demosaic_method();
+        println!(
+            "🏭 像素格式: {} | 分辨率: {}x{} | 去马赛克: {:?}",
+            pixel_format, width, height, method
+        );
+
+        let payload = camera.payload().context("读取负载大小失败")? as usize;
+        let stream = camera.create_stream().context("创建采集流失败")?;
+        for _ in 0..STREAM_BUFFERS {
+            stream.push_buffer(Buffer::new_allocate(payload));
+        }
+        camera.start_acquisition().context("启动采集失败")?;
+        println!("✅ 工业相机采集启动成功");
+
+        let mut rgba = Arc::new(vec![255u8; width * height * 4]);
+        let mut gate = FrameGate::new(MIN_PREVIEW_FPS);
+        let (mut count, mut last, mut fps) = (0usize, Instant::now(), 0.0);
+        let mut incomplete = 0usize;
+
+        while ACTIVE_DECODER_GENERATION.load(Ordering::SeqCst) == self.generation {
+            let Some(buffer) = stream.timeout_pop_buffer(POP_TIMEOUT_US) else {
+                continue;
+            };
+            let trace = FrameTrace::new();
+            if buffer.status() != BufferStatus::Success {
+                incomplete += 1;
+                stream.push_buffer(buffer);
+                continue;
+            }
+            count += 1;
+
+            // 背压: 检测队列已满, 跳过去马赛克与发布 (保留最低预览帧率)
+            if gate.admit(trace.decode_ts) {
+                if Arc::strong_count(&rgba) > 1 {
+                    rgba = Arc::new(vec![255u8; width * height * 4]);
+                }
+                raw_to_rgba(
+                    buffer.data(),
+                    width,
+                    height,
+                    format,
+                    method,
+                    Arc::get_mut(&mut rgba).unwrap(),
+                );
+                xbus::post(DecodedFrame {
+                    rgba_data: Arc::clone(&rgba),
+                    width: width as u32,
+                    height: height as u32,
+                    decode_fps: fps,
+                    decoder_name: "GigE (Aravis)".to_string(),
+                    yuv: None,
+                    keyframe: true,
+                    trace,
+                    view: 0,
+                    generation: self.generation,
+                    yuv16: None,
+                    #[cfg(feature = "cuda")]
+                    device: None,
+                });
+            }
+            stream.push_buffer(buffer);
+
+            if last.elapsed().as_secs_f64() >= 1.0 {
+                fps = count as f64 / last.elapsed().as_secs_f64();
+                println!(
+                    "🏭 采集统计: {:.1}fps | 不完整帧{} | 背压跳过{}",
+                    fps, incomplete, gate.skipped
+                );
+                count = 0;
+                last = Instant::now();
+            }
+        }
+
+        println!(
+            "🛑 工业相机解码器已过期 (Gen: {}), 停止采集",
+            self.generation
+        );
+        camera.stop_acquisition().context("停止采集失败")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+/// 视频输入系统 (Video Input System)
+///
+/// 独立工作线程,负责视频流解码与预处理
+/// - Decoder: RTSP主动拉流解码器 (VLC级别画质优化)
+/// - CameraDecoder: 本地摄像头解码器 (DirectShow/AVFoundation/V4L2)
+/// - GigeDecoder: GenICam/GigE Vision 工业相机 (feature = "aravis"), Bayer 原始帧在此去马赛克
+/// - ShmDecoder: 共享内存环形缓冲, 读取外部采集进程写入的 NV12/RGBA 帧
+/// - SyntheticDecoder: 合成场景 (运动矩形/人形 + 真值框), 用于集成测试与 CI
+/// - Filter:  帧过滤与预处理 (feature = "cuda" 时另有 NVDEC 设备帧过滤器)
+/// - DecoderManager: 解码器管理器 (支持动态热切换)
+/// - FrameGate: 检测端背压时在源头丢帧
+/// - rtsp_security: RTSPS/TLS 参数与按主机存放的 RTSP 凭据
+/// - vault: RTSP 凭据库 (系统钥匙串), 地址中以 $ID 引用
+pub mod backpressure;
+pub mod decode_filter;
+#[cfg(feature = "cuda")]
+pub mod cuda_filter;
+pub mod decoder;
+pub mod camera;
+pub mod desktop;
+pub mod bayer;
+#[cfg(feature = "aravis")]
+pub mod gige;
+pub mod shm;
+pub mod synthetic;
+pub mod decoder_manager;
+pub mod rtsp_security;
+pub mod vault;
+
+pub use backpressure::FrameGate;
+pub use decode_filter::DecodeFilter;
+#[cfg(feature = "cuda")]
+pub use cuda_filter::CudaDecodeFilter;
+pub use decoder::{adaptive_decode, Decoder};
+pub use camera::{CameraDecoder, get_camera_devices};
+pub use desktop::DesktopDecoder;
+pub use bayer::{demosaic_method, set_demosaic, Demosaic};
+#[cfg(feature = "aravis")]
+pub use gige::{list_gige_cameras, GigeDecoder};
+pub use shm::{ShmDecoder, ShmRing};
+pub use synthetic::{SyntheticDecoder, SyntheticScene, SyntheticTruth};
+pub use rtsp_security::{redact_url, RtspSecurity, RTSP_SECURITY_CONFIG_PATH};
+pub use decoder_manager::{get_video_devices, switch_decoder_source, should_stop, DecoderManager, VideoDevice, InputSource};
+pub use decoder_manager::{active_orientation, active_source, active_source_key, set_active_orientation};
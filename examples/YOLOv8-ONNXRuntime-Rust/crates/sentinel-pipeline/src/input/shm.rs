@@ -0,0 +1,444 @@
+//! 共享内存帧输入 - 接收外部采集进程写入的 NV12/RGBA 帧
+//!
+//! 外部进程 (已有的采集服务) 创建映射文件并按环形槽位写帧, 本进程只读映射并作为分析端运行.
+//! 布局 (小端, 所有偏移按 8 字节对齐):
+//! - 文件头 64 字节: magic `YSHM` | version u32 | slots u32 | slot_size u32 | 保留 u32 | write_seq u64
+//! - 槽位 i 位于 `64 + i * (32 + slot_size)`:
+//!   - 槽头 32 字节: seq u64 | width u32 | height u32 | format u32 | stride u32 | pts_us u64
+//!   - 负载 slot_size 字节 (NV12: Y 平面后紧跟 UV 交错平面, 同一步长; RGBA: 按行)
+//!
+//! 写入协议: 第 n 帧 (n 从 1 开始) 写入槽位 `(n - 1) % slots`, 先将槽位 seq 置 0,
+//! 写槽头其余字段与负载, 再以 Release 写入 seq = n, 最后 write_seq = n.
+//! 读取端拷贝负载前后各检查一次槽位 seq, 不等于 n 说明被写端追上覆盖 (撕裂帧), 直接丢弃.
+
+use super::backpressure::{FrameGate, MIN_PREVIEW_FPS};
+use super::decode_filter::yuv420p_to_rgba;
+use super::decoder_manager::{active_orientation, ACTIVE_DECODER_GENERATION};
+use crate::detection::trace::FrameTrace;
+use crate::detection::types::DecodedFrame;
+use crate::utils::yuv_preprocess::Yuv420Frame;
+use crate::xbus;
+use anyhow::{bail, Context, Result};
+use memmap2::Mmap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 文件头魔数
+pub const SHM_MAGIC: [u8; 4] = *b"YSHM";
+/// 协议版本
+pub const SHM_VERSION: u32 = 1;
+/// 文件头长度
+pub const SHM_HEADER_SIZE: usize = 64;
+/// 槽头长度
+pub const SHM_SLOT_HEADER_SIZE: usize = 32;
+/// 默认映射文件 (Linux tmpfs, 写端与读端约定同一路径即可)
+pub const DEFAULT_SHM_PATH: &str = "/dev/shm/yolov8_frames";
+/// write_seq 在文件头中的偏移
+const WRITE_SEQ_OFFSET: usize = 24;
+
+/// 无新帧时的轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+/// 映射文件不存在或格式错误时的重试间隔
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 槽位像素格式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum ShmPixelFormat {
+    Nv12 = 0,
+    Rgba = 1,
+}
+
+impl ShmPixelFormat {
+    pub fn from_u32(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(ShmPixelFormat::Nv12),
+            1 => Some(ShmPixelFormat::Rgba),
+            _ => None,
+        }
+    }
+
+    /// 负载字节数 (stride 为每行字节数)
+    pub fn payload_size(self, stride: usize, height: usize) -> usize {
+        match self {
+            ShmPixelFormat::Nv12 => stride * (height + height.div_ceil(2)),
+            ShmPixelFormat::Rgba => stride * height,
+        }
+    }
+}
+
+/// 槽头中的帧信息
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShmFrameInfo {
+    pub width: usize,
+    pub height: usize,
+    pub format: ShmPixelFormat,
+    pub stride: usize,
+    pub pts_us: u64,
+}
+
+/// 只读映射的共享内存环形缓冲
+pub struct ShmRing {
+    map: Mmap,
+    slots: usize,
+    slot_size: usize,
+}
+
+impl ShmRing {
+    /// 映射并校验文件头
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("打开共享内存文件失败: {:?}", path))?;
+        // SAFETY: 映射只读, 写端并发修改的字段都通过原子读取并以 seq 校验
+        let map = unsafe { Mmap::map(&file) }.context("映射共享内存失败")?;
+        if map.len() < SHM_HEADER_SIZE || map[..4] != SHM_MAGIC {
+            bail!("共享内存文件头无效 (魔数不匹配)");
+        }
+        let version = read_u32(&map, 4);
+        if version != SHM_VERSION {
+            bail!(
+                "不支持的共享内存协议版本 {} (需要 {})",
+                version,
+                SHM_VERSION
+            );
+        }
+        let slots = read_u32(&map, 8) as usize;
+        let slot_size = read_u32(&map, 12) as usize;
+        if slots == 0 || !slot_size.is_multiple_of(8) {
+            bail!(
+                "槽位配置无效: {} 个槽位, 每槽 {} 字节 (须为 8 的倍数)",
+                slots,
+                slot_size
+            );
+        }
+        let required = SHM_HEADER_SIZE + slots * (SHM_SLOT_HEADER_SIZE + slot_size);
+        if map.len() < required {
+            bail!(
+                "共享内存文件过小: {} 字节, 需要 {} 字节",
+                map.len(),
+                required
+            );
+        }
+        Ok(Self {
+            map,
+            slots,
+            slot_size,
+        })
+    }
+
+    /// 写端最近写完的帧序号 (0 表示尚未写入)
+    pub fn latest_seq(&self) -> u64 {
+        self.atomic_u64(WRITE_SEQ_OFFSET).load(Ordering::Acquire)
+    }
+
+    /// 拷贝第 `seq` 帧的负载到 `out`; 槽位已被覆盖 (或正在写) 时返回 Ok(None)
+    pub fn read(&self, seq: u64, out: &mut Vec<u8>) -> Result<Option<ShmFrameInfo>> {
+        let base = SHM_HEADER_SIZE
+            + (seq - 1) as usize % self.slots * (SHM_SLOT_HEADER_SIZE + self.slot_size);
+        let slot_seq = self.atomic_u64(base);
+        if slot_seq.load(Ordering::Acquire) != seq {
+            return Ok(None);
+        }
+
+        let width = read_u32(&self.map, base + 8) as usize;
+        let height = read_u32(&self.map, base + 12) as usize;
+        let format = read_u32(&self.map, base + 16);
+        let stride = read_u32(&self.map, base + 20) as usize;
+        let pts_us = u64::from_le_bytes(self.map[base + 24..base + 32].try_into().unwrap());
+        let payload = base + SHM_SLOT_HEADER_SIZE;
+        let info = ShmPixelFormat::from_u32(format).map(|format| ShmFrameInfo {
+            width,
+            height,
+            format,
+            stride,
+            pts_us,
+        });
+        let size = info.map(|i| i.format.payload_size(i.stride, i.height));
+        if let Some(size) = size.filter(|&s| s <= self.slot_size) {
+            out.clear();
+            out.extend_from_slice(&self.map[payload..payload + size]);
+        }
+
+        // 拷贝后再次校验: 期间被覆盖则槽头与负载都不可信
+        if slot_seq.load(Ordering::Acquire) != seq {
+            return Ok(None);
+        }
+        let Some(info) = info else {
+            bail!("未知像素格式 {}", format);
+        };
+        let min_stride = match info.format {
+            ShmPixelFormat::Nv12 => info.width.div_ceil(2) * 2,
+            ShmPixelFormat::Rgba => info.width * 4,
+        };
+        if info.width == 0 || info.height == 0 || info.stride < min_stride {
+            bail!(
+                "帧尺寸无效: {}x{}, 步长 {}",
+                info.width,
+                info.height,
+                info.stride
+            );
+        }
+        if size.is_none_or(|s| s > self.slot_size) {
+            bail!("帧负载超过槽位大小 ({} 字节)", self.slot_size);
+        }
+        Ok(Some(info))
+    }
+
+    fn atomic_u64(&self, offset: usize) -> &AtomicU64 {
+        // SAFETY: 偏移已校验为 8 字节对齐且在映射范围内, 映射在 self 存活期间有效
+        unsafe { &*(self.map.as_ptr().add(offset) as *const AtomicU64) }
+    }
+}
+
+fn read_u32(map: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(map[offset..offset + 4].try_into().unwrap())
+}
+
+/// 共享内存帧解码器 (外部进程写帧, 本进程只分析)
+pub struct ShmDecoder {
+    path: PathBuf,
+    generation: usize,
+}
+
+impl ShmDecoder {
+    /// 创建共享内存输入
+    pub fn new(path: impl Into<PathBuf>, generation: usize) -> Self {
+        Self {
+            path: path.into(),
+            generation,
+        }
+    }
+
+    fn active(&self) -> bool {
+        ACTIVE_DECODER_GENERATION.load(Ordering::SeqCst) == self.generation
+    }
+
+    /// 等待映射文件就绪后持续读帧, 代数ID变化后退出
+    pub fn run(&mut self) {
+        println!(
+            "\n🧩 ============ 共享内存输入 (Gen: {}) ============",
+            self.generation
+        );
+        println!("📁 映射文件: {:?}", self.path);
+
+        while self.active() {
+            match ShmRing::open(&self.path) {
+                Ok(ring) => {
+                    println!(
+                        "✅ 共享内存已映射: {} 个槽位, 每槽 {} 字节",
+                        ring.slots, ring.slot_size
+                    );
+                    if let Err(e) = self.consume(&ring) {
+                        eprintln!("❌ 共享内存读取失败: {:#}", e);
+                    }
+                }
+                Err(e) => eprintln!("⚠️ {:#}, {} 秒后重试", e, RETRY_INTERVAL.as_secs()),
+            }
+            if self.active() {
+                std::thread::sleep(RETRY_INTERVAL);
+            }
+        }
+        println!("🛑 共享内存输入已过期 (Gen: {}), 退出", self.generation);
+    }
+
+    /// 读取最新帧并发布; 读端落后时直接跳到最新帧, 不补读积压帧
+    fn consume(&self, ring: &ShmRing) -> Result<()> {
+        let mut last_seq = ring.latest_seq();
+        let mut payload = Vec::new();
+        let mut raw = Yuv420Frame::default();
+        let mut yuv = Arc::new(Yuv420Frame::default());
+        let mut rgba = Arc::new(Vec::new());
+        let mut gate = FrameGate::new(MIN_PREVIEW_FPS);
+        let (mut count, mut last, mut fps) = (0usize, Instant::now(), 0.0);
+        let (mut skipped, mut torn) = (0u64, 0usize);
+
+        while self.active() {
+            let seq = ring.latest_seq();
+            if seq == last_seq {
+                std::thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+            if seq < last_seq {
+                println!("🔄 写端已重启 (序号 {} → {})", last_seq, seq);
+            } else {
+                skipped += seq - last_seq - 1;
+            }
+            last_seq = seq;
+
+            let trace = FrameTrace::new();
+            let Some(info) = ring.read(seq, &mut payload)? else {
+                torn += 1;
+                continue;
+            };
+            count += 1;
+
+            // 背压: 检测队列已满, 跳过转换与发布 (保留最低预览帧率)
+            if gate.admit(trace.decode_ts) {
+                let (w, h) = (info.width, info.height);
+                let frame_yuv = match info.format {
+                    ShmPixelFormat::Nv12 => {
+                        if Arc::strong_count(&yuv) > 1 {
+                            yuv = Arc::new(Yuv420Frame::default());
+                        }
+                        let dst = Arc::get_mut(&mut yuv).unwrap();
+                        let (y, uv) = payload.split_at(info.stride * h);
+                        let orientation = active_orientation();
+                        if orientation.is_identity() {
+                            dst.copy_from_nv12(y, uv, info.stride, w, h);
+                        } else {
+                            raw.copy_from_nv12(y, uv, info.stride, w, h);
+                            orientation.apply_yuv(&raw, dst);
+                        }
+                        Some(Arc::clone(&yuv))
+                    }
+                    ShmPixelFormat::Rgba => None,
+                };
+                let (w, h) = frame_yuv.as_ref().map_or((w, h), |f| (f.width, f.height));
+
+                if Arc::strong_count(&rgba) > 1 || rgba.len() != w * h * 4 {
+                    rgba = Arc::new(vec![255u8; w * h * 4]);
+                }
+                let buffer = Arc::get_mut(&mut rgba).unwrap();
+                match &frame_yuv {
+                    Some(f) => unsafe {
+                        yuv420p_to_rgba(
+                            f.y.as_ptr(),
+                            f.u.as_ptr(),
+                            f.v.as_ptr(),
+                            f.width,
+                            f.chroma_width(),
+                            buffer,
+                            w,
+                            h,
+                        );
+                    },
+                    None => {
+                        for (row, dst) in buffer.chunks_exact_mut(w * 4).enumerate() {
+                            let start = row * info.stride;
+                            dst.copy_from_slice(&payload[start..start + w * 4]);
+                        }
+                    }
+                }
+
+                xbus::post(DecodedFrame {
+                    rgba_data: Arc::clone(&rgba),
+                    width: w as u32,
+                    height: h as u32,
+                    decode_fps: fps,
+                    decoder_name: "共享内存".to_string(),
+                    yuv: frame_yuv,
+                    keyframe: true,
+                    trace,
+                    view: 0,
+                    generation: self.generation,
+                    yuv16: None,
+                    #[cfg(feature = "cuda")]
+                    device: None,
+                });
+            }
+
+            if last.elapsed().as_secs_f64() >= 1.0 {
+                fps = count as f64 / last.elapsed().as_secs_f64();
+                println!(
+                    "🧩 共享内存统计: {:.1}fps | 未读跳过{} | 撕裂丢弃{} | 背压跳过{}",
+                    fps, skipped, torn, gate.skipped
+                );
+                count = 0;
+                last = Instant::now();
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// 按协议写出只含一帧的映射文件, `slot_seq` 为槽位中的序号
+    fn write_ring(
+        name: &str,
+        info: ShmFrameInfo,
+        payload: &[u8],
+        write_seq: u64,
+        slot_seq: u64,
+    ) -> PathBuf {
+        let slot_size = 256;
+        let mut data = vec![0u8; SHM_HEADER_SIZE + 2 * (SHM_SLOT_HEADER_SIZE + slot_size)];
+        data[..4].copy_from_slice(&SHM_MAGIC);
+        data[4..8].copy_from_slice(&SHM_VERSION.to_le_bytes());
+        data[8..12].copy_from_slice(&2u32.to_le_bytes());
+        data[12..16].copy_from_slice(&(slot_size as u32).to_le_bytes());
+        data[24..32].copy_from_slice(&write_seq.to_le_bytes());
+        let base =
+            SHM_HEADER_SIZE + (write_seq - 1) as usize % 2 * (SHM_SLOT_HEADER_SIZE + slot_size);
+        data[base..base + 8].copy_from_slice(&slot_seq.to_le_bytes());
+        for (i, v) in [info.width, info.height, info.format as usize, info.stride]
+            .iter()
+            .enumerate()
+        {
+            data[base + 8 + i * 4..base + 12 + i * 4].copy_from_slice(&(*v as u32).to_le_bytes());
+        }
+        data[base + 24..base + 32].copy_from_slice(&info.pts_us.to_le_bytes());
+        let payload_start = base + SHM_SLOT_HEADER_SIZE;
+        data[payload_start..payload_start + payload.len()].copy_from_slice(payload);
+
+        let path = std::env::temp_dir().join(format!("shm_{}_{}", name, std::process::id()));
+        File::create(&path).unwrap().write_all(&data).unwrap();
+        path
+    }
+
+    /// NV12 帧按协议读出, UV 交错平面拆分为 U/V
+    #[test]
+    fn test_read_nv12_frame() {
+        let info = ShmFrameInfo {
+            width: 4,
+            height: 2,
+            format: ShmPixelFormat::Nv12,
+            stride: 6,
+            pts_us: 42,
+        };
+        // Y 两行 (步长 6, 末尾 2 字节填充) + UV 一行
+        let payload = [1, 2, 3, 4, 0, 0, 5, 6, 7, 8, 0, 0, 10, 20, 11, 21, 0, 0];
+        let path = write_ring("nv12", info, &payload, 3, 3);
+        let ring = ShmRing::open(&path).unwrap();
+        assert_eq!(ring.latest_seq(), 3);
+
+        let mut out = Vec::new();
+        assert_eq!(ring.read(3, &mut out).unwrap(), Some(info));
+        let mut frame = Yuv420Frame::default();
+        let (y, uv) = out.split_at(info.stride * info.height);
+        frame.copy_from_nv12(y, uv, info.stride, info.width, info.height);
+        assert_eq!(frame.y, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!((frame.u, frame.v), (vec![10, 11], vec![20, 21]));
+        std::fs::remove_file(path).ok();
+    }
+
+    /// 槽位序号与请求序号不符 (写端已覆盖或正在写) 时不返回帧
+    #[test]
+    fn test_overwritten_slot_is_dropped() {
+        let info = ShmFrameInfo {
+            width: 2,
+            height: 1,
+            format: ShmPixelFormat::Rgba,
+            stride: 8,
+            pts_us: 0,
+        };
+        let path = write_ring("torn", info, &[9; 8], 2, 0);
+        let ring = ShmRing::open(&path).unwrap();
+        assert_eq!(ring.read(2, &mut Vec::new()).unwrap(), None);
+        std::fs::remove_file(path).ok();
+    }
+
+    /// 魔数错误的文件拒绝映射
+    #[test]
+    fn test_rejects_bad_magic() {
+        let path = std::env::temp_dir().join(format!("shm_magic_{}", std::process::id()));
+        std::fs::write(&path, [0u8; SHM_HEADER_SIZE]).unwrap();
+        assert!(ShmRing::open(&path).is_err());
+        std::fs::remove_file(path).ok();
+    }
+}
@@ -0,0 +1,30 @@
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+//! 数字卫兵检测管线
+//!
+//! 在 [`sentinel_core`] (启用 ONNX Runtime 推理) 之上加入 FFmpeg 视频输入, 以及 HTTP API、
+//! 录像导出、告警通知、ONVIF 元数据、共享内存输出和看门狗。
+//! 无界面的服务程序依赖本 crate 即可; 模块路径与 `yolov8_rs` 一致
+//!
+//! 功能分层 (Cargo features):
+//! - ffmpeg (默认): 视频输入、调试 API、录像导出、转推与看门狗
+//! - 关闭 ffmpeg 时只保留告警通知、ONVIF 元数据、共享内存/NDI/虚拟摄像头输出
+pub use sentinel_core::*;
+
+#[cfg(feature = "ffmpeg")]
+pub mod api; // 调试 HTTP API (事件总线快照)
+#[cfg(feature = "ffmpeg")]
+pub mod clips; // 录像片段导出 (按时间范围截取, 可按检测记录重绘叠加框)
+#[cfg(feature = "ffmpeg")]
+pub mod input; // 视频输入系统
+#[cfg(feature = "ndi")]
+pub mod ndi; // NDI 输出 (节目画面/干净画面/键控叠加层)
+pub mod notifier; // 告警通知 (Webhook/Telegram/邮件)
+pub mod onvif; // ONVIF 分析元数据输出 (Profile M, HTTP 拉取点)
+#[cfg(feature = "ffmpeg")]
+pub mod restream; // 标注视频转推 (RTSP 推流/HLS)
+pub mod shm_output; // 共享内存输出 (标注帧 + 检测结果, 供外部渲染程序)
+#[cfg(feature = "vcam")]
+pub mod vcam; // 虚拟摄像头输出 (v4l2loopback/softcam)
+#[cfg(feature = "ffmpeg")]
+pub mod watchdog; // 看门狗与服务模式 (心跳/卡死重启/systemd 与 Windows 服务)
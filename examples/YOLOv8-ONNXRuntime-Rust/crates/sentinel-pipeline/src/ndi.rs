@@ -0,0 +1,376 @@
+//! NDI 输出 (广播/导播集成)
+//!
+//! 把指定逻辑流的画面发布为 NDI 源, vMix / OBS (obs-ndi 插件) 等导播软件可以直接作为信号源接入:
+//! - 节目画面 `<名称>`: 画面 + 检测框 (与共享内存输出相同的标注帧)
+//! - 干净画面 `<名称> Clean` (可选): 未标注的原始画面
+//! - 叠加层 `<名称> Overlay` (可选): 透明背景上只有检测框, 带 alpha 通道, 导播端按键控叠加到任意画面
+//! - 每收到一帧画面发送一次, 检测框取最新一次的检测结果; 发送线程忙时丢帧, 不阻塞解码
+//!
+//! NDI SDK 的许可不允许随程序分发运行库, 运行时按 `NDI_RUNTIME_DIR_V6` / `NDI_RUNTIME_DIR_V5`
+//! 环境变量或系统默认位置加载 (安装 NDI Tools 或 NDI SDK 即可), 找不到时不启用.
+
+use std::ffi::{c_char, c_int, c_void, CString};
+use std::path::PathBuf;
+use std::ptr::{self, NonNull};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, bail, Context, Result};
+use libloading::Library;
+
+use crate::detection::detector::DetectionResult;
+use crate::detection::types::DecodedFrame;
+use crate::notifier::draw_box;
+use crate::shm_output::{annotate_rgba, OutputResults};
+use crate::xbus::{self, Subscription};
+
+/// 运行库目录的环境变量 (NDI 安装程序设置), 依次查找
+const RUNTIME_DIR_VARS: [&str; 2] = ["NDI_RUNTIME_DIR_V6", "NDI_RUNTIME_DIR_V5"];
+
+/// 运行库文件名
+#[cfg(target_os = "windows")]
+const LIBRARY_NAMES: &[&str] = &["Processing.NDI.Lib.x64.dll"];
+#[cfg(target_os = "macos")]
+const LIBRARY_NAMES: &[&str] = &["libndi.dylib"];
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const LIBRARY_NAMES: &[&str] = &["libndi.so.6", "libndi.so.5", "libndi.so"];
+
+/// NDIlib_FourCC_video_type_RGBX (不透明, 接收端忽略 alpha)
+const FOURCC_RGBX: u32 = u32::from_le_bytes(*b"RGBX");
+/// NDIlib_FourCC_video_type_RGBA (非预乘 alpha)
+const FOURCC_RGBA: u32 = u32::from_le_bytes(*b"RGBA");
+/// NDIlib_frame_format_type_progressive
+const FRAME_FORMAT_PROGRESSIVE: c_int = 1;
+/// NDIlib_send_timecode_synthesize (由 SDK 按发送时刻生成时间码)
+const TIMECODE_SYNTHESIZE: i64 = i64::MAX;
+
+/// 解码帧率未知时按 30fps 标注
+const DEFAULT_FPS: f64 = 30.0;
+
+/// NDIlib_send_create_t
+#[repr(C)]
+struct SendCreate {
+    ndi_name: *const c_char,
+    groups: *const c_char,
+    clock_video: bool,
+    clock_audio: bool,
+}
+
+/// NDIlib_video_frame_v2_t
+#[repr(C)]
+struct VideoFrameV2 {
+    xres: c_int,
+    yres: c_int,
+    fourcc: u32,
+    frame_rate_n: c_int,
+    frame_rate_d: c_int,
+    picture_aspect_ratio: f32,
+    frame_format_type: c_int,
+    timecode: i64,
+    data: *const u8,
+    line_stride_in_bytes: c_int,
+    metadata: *const c_char,
+    timestamp: i64,
+}
+
+/// 一路 NDI 源的内容
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NdiFeed {
+    Program, // 画面 + 检测框
+    Clean,   // 原始画面
+    Overlay, // 透明背景上的检测框 (键控用)
+}
+
+/// NDI 输出选项
+#[derive(Clone, Debug, PartialEq)]
+pub struct NdiOptions {
+    pub name: String,  // 源名称 (接收端显示为 "主机名 (名称)")
+    pub view: u32,     // 逻辑流编号
+    pub clean: bool,   // 另外发布干净画面
+    pub overlay: bool, // 另外发布叠加层
+}
+
+impl NdiOptions {
+    /// 要发布的源及其名称
+    pub fn feeds(&self) -> Vec<(NdiFeed, String)> {
+        let mut feeds = vec![(NdiFeed::Program, self.name.clone())];
+        if self.clean {
+            feeds.push((NdiFeed::Clean, format!("{} Clean", self.name)));
+        }
+        if self.overlay {
+            feeds.push((NdiFeed::Overlay, format!("{} Overlay", self.name)));
+        }
+        feeds
+    }
+}
+
+/// 叠加层: 透明背景上画检测框 (非预乘 RGBA)
+pub fn overlay_rgba(width: u32, height: u32, results: &OutputResults) -> Vec<u8> {
+    let mut image = image::RgbaImage::new(width, height);
+    for b in &results.boxes {
+        draw_box(
+            &mut image,
+            [b.x1, b.y1, b.x2, b.y2],
+            image::Rgba([0, 255, 0, 255]),
+        );
+    }
+    image.into_raw()
+}
+
+/// 运行库的候选路径: 环境变量指定的目录优先, 最后按文件名交给系统搜索
+fn library_candidates(dirs: &[PathBuf]) -> Vec<PathBuf> {
+    dirs.iter()
+        .flat_map(|dir| LIBRARY_NAMES.iter().map(move |name| dir.join(name)))
+        .chain(LIBRARY_NAMES.iter().map(PathBuf::from))
+        .collect()
+}
+
+/// 运行时加载的 NDI 库
+struct NdiLib {
+    send_create: unsafe extern "C" fn(*const SendCreate) -> *mut c_void,
+    send_destroy: unsafe extern "C" fn(*mut c_void),
+    send_video_v2: unsafe extern "C" fn(*mut c_void, *const VideoFrameV2),
+    destroy: unsafe extern "C" fn(),
+    _library: Library,
+}
+
+impl NdiLib {
+    fn load() -> Result<Self> {
+        let dirs: Vec<PathBuf> = RUNTIME_DIR_VARS
+            .iter()
+            .filter_map(std::env::var_os)
+            .map(PathBuf::from)
+            .collect();
+        let mut errors = Vec::new();
+        for path in library_candidates(&dirs) {
+            // SAFETY: NDI 运行库的初始化代码没有额外要求
+            match unsafe { Library::new(&path) } {
+                Ok(library) => {
+                    return Self::bind(library)
+                        .with_context(|| format!("NDI 运行库 {} 不可用", path.display()))
+                }
+                Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+        Err(anyhow!(
+            "找不到 NDI 运行库 (请安装 NDI Tools 或 NDI SDK): {}",
+            errors.join("; ")
+        ))
+    }
+
+    fn bind(library: Library) -> Result<Self> {
+        // SAFETY: 函数签名与 Processing.NDI.Lib.h (NDI 5/6) 一致
+        unsafe {
+            let initialize =
+                *library.get::<unsafe extern "C" fn() -> bool>(b"NDIlib_initialize\0")?;
+            let lib = Self {
+                send_create: *library.get(b"NDIlib_send_create\0")?,
+                send_destroy: *library.get(b"NDIlib_send_destroy\0")?,
+                send_video_v2: *library.get(b"NDIlib_send_send_video_v2\0")?,
+                destroy: *library.get(b"NDIlib_destroy\0")?,
+                _library: library,
+            };
+            if !initialize() {
+                bail!("NDIlib_initialize 失败 (CPU 不支持)");
+            }
+            Ok(lib)
+        }
+    }
+}
+
+impl Drop for NdiLib {
+    fn drop(&mut self) {
+        // SAFETY: 所有发送端都持有 Arc<NdiLib>, 此时已全部销毁
+        unsafe { (self.destroy)() }
+    }
+}
+
+/// 一个 NDI 发送端 (一个源)
+struct NdiSender {
+    lib: Arc<NdiLib>,
+    instance: NonNull<c_void>,
+}
+
+// SAFETY: 发送端只在输出线程中使用, NDI 发送实例可以在创建线程之外的线程调用
+unsafe impl Send for NdiSender {}
+
+impl NdiSender {
+    fn new(lib: Arc<NdiLib>, name: &str) -> Result<Self> {
+        let name = CString::new(name).context("NDI 源名称不能包含 NUL")?;
+        // 不按帧率节流: 帧随解码到达, 由解码节奏决定发送节奏
+        let settings = SendCreate {
+            ndi_name: name.as_ptr(),
+            groups: ptr::null(),
+            clock_video: false,
+            clock_audio: false,
+        };
+        // SAFETY: settings 与名称在调用期间有效, SDK 内部复制名称
+        let instance = unsafe { (lib.send_create)(&settings) };
+        let instance = NonNull::new(instance).context("NDIlib_send_create 失败")?;
+        Ok(Self { lib, instance })
+    }
+
+    /// 同步发送一帧 (返回时 SDK 已不再引用 data)
+    fn send(&self, width: u32, height: u32, data: &[u8], fourcc: u32, fps: f64) {
+        if data.len() < (width * height * 4) as usize {
+            return;
+        }
+        let fps = if fps > 0.0 { fps } else { DEFAULT_FPS };
+        let frame = VideoFrameV2 {
+            xres: width as c_int,
+            yres: height as c_int,
+            fourcc,
+            frame_rate_n: (fps * 1000.0).round() as c_int,
+            frame_rate_d: 1000,
+            picture_aspect_ratio: width as f32 / height.max(1) as f32,
+            frame_format_type: FRAME_FORMAT_PROGRESSIVE,
+            timecode: TIMECODE_SYNTHESIZE,
+            data: data.as_ptr(),
+            line_stride_in_bytes: (width * 4) as c_int,
+            metadata: ptr::null(),
+            timestamp: 0,
+        };
+        // SAFETY: 帧数据长度已校验, 同步发送返回前 data 一直有效
+        unsafe { (self.lib.send_video_v2)(self.instance.as_ptr(), &frame) }
+    }
+}
+
+impl Drop for NdiSender {
+    fn drop(&mut self) {
+        // SAFETY: 实例由 send_create 创建且只销毁一次
+        unsafe { (self.lib.send_destroy)(self.instance.as_ptr()) }
+    }
+}
+
+/// NDI 输出 (订阅保持期间运行)
+pub struct NdiPublisher {
+    _subs: Vec<Subscription>,
+}
+
+impl NdiPublisher {
+    /// 加载 NDI 运行库并创建各路源, 订阅逻辑流的帧与检测结果, 在后台线程发送
+    pub fn start(options: NdiOptions) -> Result<Self> {
+        let lib = Arc::new(NdiLib::load()?);
+        let feeds = options.feeds();
+        let senders = feeds
+            .iter()
+            .map(|(feed, name)| Ok((*feed, NdiSender::new(Arc::clone(&lib), name)?)))
+            .collect::<Result<Vec<_>>>()?;
+        let names: Vec<&str> = feeds.iter().map(|(_, name)| name.as_str()).collect();
+        println!(
+            "📡 NDI 输出: {} (逻辑流 {})",
+            names.join(", "),
+            options.view
+        );
+
+        // 只保留一帧待发: 发送线程忙时新帧直接丢弃
+        let view = options.view;
+        let (tx, rx) = crossbeam_channel::bounded::<(DecodedFrame, OutputResults)>(1);
+        let latest: Arc<Mutex<OutputResults>> = Arc::default();
+        let results = Arc::clone(&latest);
+        let result_sub = xbus::subscribe::<DetectionResult, _>(move |result| {
+            if result.view == view {
+                *results.lock().unwrap() = OutputResults::from_result(result);
+            }
+        });
+        let frame_sub = xbus::subscribe::<DecodedFrame, _>(move |frame| {
+            if frame.view == view {
+                let _ = tx.try_send((frame.clone(), latest.lock().unwrap().clone()));
+            }
+        });
+
+        std::thread::Builder::new()
+            .name("ndi-output".to_string())
+            .spawn(move || {
+                for (frame, results) in rx {
+                    let (w, h) = (frame.width, frame.height);
+                    for (feed, sender) in &senders {
+                        match feed {
+                            NdiFeed::Program => {
+                                if let Some(rgba) = annotate_rgba(&frame, &results) {
+                                    sender.send(w, h, &rgba, FOURCC_RGBX, frame.decode_fps);
+                                }
+                            }
+                            NdiFeed::Clean => {
+                                sender.send(w, h, &frame.rgba_data, FOURCC_RGBX, frame.decode_fps)
+                            }
+                            NdiFeed::Overlay => {
+                                let rgba = overlay_rgba(w, h, &results);
+                                sender.send(w, h, &rgba, FOURCC_RGBA, frame.decode_fps);
+                            }
+                        }
+                    }
+                }
+                println!("📡 NDI 输出线程退出");
+            })
+            .context("NDI 输出线程启动失败")?;
+
+        Ok(Self {
+            _subs: vec![frame_sub, result_sub],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shm_output::OutputBox;
+
+    /// 节目画面总是发布, 干净画面与叠加层按选项追加, 名称带后缀
+    #[test]
+    fn test_feeds() {
+        let mut options = NdiOptions {
+            name: "Sentinel".to_string(),
+            view: 0,
+            clean: false,
+            overlay: true,
+        };
+        assert_eq!(
+            options.feeds(),
+            vec![
+                (NdiFeed::Program, "Sentinel".to_string()),
+                (NdiFeed::Overlay, "Sentinel Overlay".to_string()),
+            ]
+        );
+        options.clean = true;
+        assert_eq!(
+            options.feeds()[1],
+            (NdiFeed::Clean, "Sentinel Clean".to_string())
+        );
+    }
+
+    /// 叠加层背景透明, 只有检测框边线不透明
+    #[test]
+    fn test_overlay_alpha() {
+        let results = OutputResults {
+            boxes: vec![OutputBox {
+                x1: 10.0,
+                y1: 10.0,
+                x2: 30.0,
+                y2: 30.0,
+                confidence: 0.9,
+                class_id: 0,
+                global_id: None,
+            }],
+            ..Default::default()
+        };
+        let rgba = overlay_rgba(40, 40, &results);
+        assert_eq!(rgba.len(), 40 * 40 * 4);
+        let pixel = |x: usize, y: usize| &rgba[(y * 40 + x) * 4..(y * 40 + x) * 4 + 4];
+        assert_eq!(pixel(10, 10), &[0, 255, 0, 255]);
+        assert_eq!(pixel(20, 20), &[0, 0, 0, 0]);
+        assert_eq!(pixel(0, 0), &[0, 0, 0, 0]);
+    }
+
+    /// 环境变量目录中的运行库优先, 最后按文件名交给系统搜索
+    #[test]
+    fn test_library_candidates() {
+        let dir = PathBuf::from("/opt/ndi/lib");
+        let candidates = library_candidates(std::slice::from_ref(&dir));
+        assert_eq!(candidates.len(), LIBRARY_NAMES.len() * 2);
+        assert_eq!(candidates[0], dir.join(LIBRARY_NAMES[0]));
+        assert_eq!(
+            candidates.last().unwrap(),
+            &PathBuf::from(LIBRARY_NAMES[LIBRARY_NAMES.len() - 1])
+        );
+    }
+}
@@ -0,0 +1,742 @@
+//! 告警通知 (Alert notifier)
+//!
+//! 订阅场景分析事件 (区域规则、遗留/移除、摄像头篡改), 按模板生成消息并附带标注快照 (事件所在逻辑流的最新帧,
+//! 绿色为检测框, 红色为事件区域), 由后台线程发送到配置的通道: 通用 Webhook、Telegram 机器人、
+//! SMTP 邮件 (需要 `email` 功能). 同一事件源在最小间隔内只通知一次, 另有每小时总量上限;
+//! 免打扰时段与已撤防的逻辑流不通知, 二次复核判为误报的区域事件不通知.
+//!
+//! 配置文件 (notifier.json):
+//! ```json
+//! {
+//!   "channels": [
+//!     { "type": "webhook", "url": "http://127.0.0.1:9000/alerts" },
+//!     { "type": "telegram", "token": "123456:ABC", "chat_id": "-100123" }
+//!   ],
+//!   "min_interval_secs": 60,
+//!   "quiet_hours": [{ "start": "12:00", "end": "13:30" }]
+//! }
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use imageproc::drawing::draw_hollow_rect_mut;
+use imageproc::rect::Rect;
+use serde::{Deserialize, Serialize};
+
+use crate::analytics::{LeftBehindEvent, SceneChange, TamperEvent, ZoneEvent};
+use crate::detection::detector::DetectionResult;
+use crate::detection::types::{BBox, DecodedFrame};
+use crate::scheduler::{self, ArmWindow};
+use crate::utils::clock;
+use crate::xbus::{self, Subscription};
+
+/// 默认通知配置文件
+pub const NOTIFIER_CONFIG_PATH: &str = "notifier.json";
+
+/// 默认消息模板
+pub const DEFAULT_TEMPLATE: &str = "{time} [{kind}] {message} (逻辑流 {view})";
+
+/// 单次发送超时
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 待发送队列长度, 发送线程积压时丢弃新告警
+const QUEUE_SIZE: usize = 16;
+
+/// Telegram 图片说明最大长度 (字符)
+const TELEGRAM_CAPTION_LIMIT: usize = 1024;
+
+/// 告警类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// 区域规则触发
+    Zone,
+    /// 遗留物
+    LeftBehind,
+    /// 看护物体移除
+    Removed,
+    /// 摄像头遮挡 / 失焦 / 移位
+    Tamper,
+}
+
+impl AlertKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AlertKind::Zone => "区域事件",
+            AlertKind::LeftBehind => SceneChange::LeftBehind.label(),
+            AlertKind::Removed => SceneChange::Removed.label(),
+            AlertKind::Tamper => "摄像头篡改",
+        }
+    }
+}
+
+/// 通知通道
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Channel {
+    /// 通用 Webhook: multipart/form-data POST, `payload` 为 JSON, `snapshot` 为 JPEG
+    Webhook { url: String },
+    /// Telegram 机器人: 有快照时 sendPhoto (消息作为说明), 否则 sendMessage
+    Telegram { token: String, chat_id: String },
+    /// SMTP 邮件 (STARTTLS), 快照作为附件
+    Email {
+        server: String,
+        #[serde(default = "default_smtp_port")]
+        port: u16,
+        username: String,
+        password: String,
+        from: String,
+        to: Vec<String>,
+    },
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+impl Channel {
+    /// 通道名称 (日志显示, 不含凭据)
+    pub fn name(&self) -> String {
+        match self {
+            Channel::Webhook { url } => format!("webhook {}", url),
+            Channel::Telegram { chat_id, .. } => format!("telegram {}", chat_id),
+            Channel::Email { to, .. } => format!("email {}", to.join(",")),
+        }
+    }
+}
+
+/// 通知配置 (notifier.json)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotifierConfig {
+    pub channels: Vec<Channel>,
+    pub events: Vec<AlertKind>,      // 通知的事件类型, 为空表示全部
+    pub template: String,            // 消息模板: {time} {kind} {message} {view}
+    pub min_interval_secs: f32,      // 同一事件源的最小通知间隔
+    pub max_per_hour: u32,           // 每小时通知总数上限, 0 不限
+    pub quiet_hours: Vec<ArmWindow>, // 免打扰时段 (本地时间, 可按星期)
+    pub snapshot: bool,              // 是否附带标注快照
+}
+
+impl Default for NotifierConfig {
+    fn default() -> Self {
+        Self {
+            channels: Vec::new(),
+            events: Vec::new(),
+            template: DEFAULT_TEMPLATE.to_string(),
+            min_interval_secs: 60.0,
+            max_per_hour: 30,
+            quiet_hours: Vec::new(),
+            snapshot: true,
+        }
+    }
+}
+
+impl NotifierConfig {
+    /// 从文件加载配置, 文件不存在时为空配置 (不通知)
+    pub fn load(path: &str) -> Self {
+        match fs::read_to_string(path) {
+            Ok(json) => match serde_json::from_str::<Self>(&json) {
+                Ok(mut config) => {
+                    config.quiet_hours.retain(|w| {
+                        if !w.is_valid() {
+                            eprintln!(
+                                "⚠️  免打扰时段无效 ({}-{} {:?}), 已忽略",
+                                w.start, w.end, w.days
+                            );
+                        }
+                        w.is_valid()
+                    });
+                    println!(
+                        "✅ 告警通知配置已从 {} 加载 ({}个通道, {}个免打扰时段)",
+                        path,
+                        config.channels.len(),
+                        config.quiet_hours.len()
+                    );
+                    config
+                }
+                Err(e) => {
+                    eprintln!("⚠️  告警通知配置解析失败: {}, 不发送通知", e);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+}
+
+/// 一条告警
+#[derive(Clone, Debug)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub source: String,           // 事件源 (区域+规则 / 看护物体), 按源限流
+    pub message: String,          // 事件描述
+    pub view: u32,                // 逻辑流编号
+    pub region: Option<[f32; 4]>, // 事件区域 (图像坐标), 快照中以红框标出
+    pub time: DateTime<Local>,
+}
+
+impl Alert {
+    pub fn from_zone(event: &ZoneEvent) -> Self {
+        let mut message = format!(
+            "[{}] {} 目标{:?} ({:.2})",
+            event.zone, event.rule, event.track_ids, event.value
+        );
+        let vehicles: Vec<String> = event
+            .vehicles
+            .iter()
+            .flatten()
+            .map(|v| v.describe())
+            .collect();
+        if !vehicles.is_empty() {
+            message.push_str(&format!(" {}", vehicles.join("/")));
+        }
+        if let Some(verification) = &event.verification {
+            message.push_str(&format!(" 复核 {:.2}", verification.confidence));
+        }
+        // 涉及目标的外接框
+        let region = event.boxes.iter().copied().reduce(|a, b| {
+            [
+                a[0].min(b[0]),
+                a[1].min(b[1]),
+                a[2].max(b[2]),
+                a[3].max(b[3]),
+            ]
+        });
+        Self {
+            kind: AlertKind::Zone,
+            source: format!("{} {}", event.zone, event.rule),
+            message,
+            view: event.view,
+            region,
+            time: event.time,
+        }
+    }
+
+    pub fn from_left_behind(event: &LeftBehindEvent) -> Self {
+        let kind = match event.kind {
+            SceneChange::LeftBehind => AlertKind::LeftBehind,
+            SceneChange::Removed => AlertKind::Removed,
+        };
+        let [x1, y1, x2, y2] = event.rect;
+        Self {
+            kind,
+            // 遗留物没有名称, 按位置区分
+            source: if event.name.is_empty() {
+                format!("{:.0},{:.0}", x1, y1)
+            } else {
+                event.name.clone()
+            },
+            message: format!(
+                "{} {} ({:.0}, {:.0})-({:.0}, {:.0})",
+                kind.label(),
+                event.name,
+                x1,
+                y1,
+                x2,
+                y2
+            ),
+            view: event.view,
+            region: Some(event.rect),
+            time: event.time,
+        }
+    }
+
+    pub fn from_tamper(event: &TamperEvent) -> Self {
+        Self {
+            kind: AlertKind::Tamper,
+            source: event.kind.label().to_string(),
+            message: format!("{} (指标 {:.2})", event.kind.label(), event.score),
+            view: event.view,
+            region: None,
+            time: event.time,
+        }
+    }
+
+    /// 按模板生成消息文本
+    pub fn render(&self, template: &str) -> String {
+        template
+            .replace("{time}", &clock::format(self.time, "%Y-%m-%d %H:%M:%S"))
+            .replace("{kind}", self.kind.label())
+            .replace("{message}", &self.message)
+            .replace("{view}", &self.view.to_string())
+    }
+
+    /// 限流键: 类型 + 逻辑流 + 事件源
+    fn key(&self) -> String {
+        format!("{:?}/{}/{}", self.kind, self.view, self.source)
+    }
+}
+
+/// 告警被抑制的原因
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Suppressed {
+    /// 事件类型不在通知列表中
+    Filtered,
+    /// 免打扰时段
+    QuietHours,
+    /// 同一事件源间隔过短
+    Interval,
+    /// 超过每小时上限
+    HourlyLimit,
+}
+
+/// 告警过滤: 事件类型、免打扰时段与限流
+pub struct AlertFilter {
+    events: Vec<AlertKind>,
+    quiet_hours: Vec<ArmWindow>,
+    min_interval: Duration,
+    max_per_hour: u32,
+    last: HashMap<String, Instant>, // 各事件源最近一次通知
+    sent: VecDeque<Instant>,        // 最近一小时内的通知时刻
+}
+
+impl AlertFilter {
+    pub fn new(config: &NotifierConfig) -> Self {
+        Self {
+            events: config.events.clone(),
+            quiet_hours: config.quiet_hours.clone(),
+            min_interval: Duration::from_secs_f32(config.min_interval_secs.max(0.0)),
+            max_per_hour: config.max_per_hour,
+            last: HashMap::new(),
+            sent: VecDeque::new(),
+        }
+    }
+
+    /// 判断告警是否发送, 放行时计入限流
+    pub fn admit(&mut self, alert: &Alert, now: Instant) -> Result<(), Suppressed> {
+        if !self.events.is_empty() && !self.events.contains(&alert.kind) {
+            return Err(Suppressed::Filtered);
+        }
+        let local = alert.time.naive_local();
+        if self.quiet_hours.iter().any(|w| w.contains(local)) {
+            return Err(Suppressed::QuietHours);
+        }
+        let key = alert.key();
+        if let Some(last) = self.last.get(&key) {
+            if now.saturating_duration_since(*last) < self.min_interval {
+                return Err(Suppressed::Interval);
+            }
+        }
+        let hour = Duration::from_secs(3600);
+        while let Some(t) = self.sent.front() {
+            if now.saturating_duration_since(*t) < hour {
+                break;
+            }
+            self.sent.pop_front();
+        }
+        if self.max_per_hour > 0 && self.sent.len() >= self.max_per_hour as usize {
+            return Err(Suppressed::HourlyLimit);
+        }
+        self.last.insert(key, now);
+        self.sent.push_back(now);
+        Ok(())
+    }
+}
+
+/// 标注快照: 检测框为绿色, 事件区域为红色, 编码为 JPEG; 帧没有 RGBA 数据时为 None
+pub fn annotated_snapshot(
+    frame: &DecodedFrame,
+    boxes: &[BBox],
+    region: Option<[f32; 4]>,
+) -> Option<Vec<u8>> {
+    if frame.rgba_data.len() < (frame.width * frame.height * 4) as usize {
+        return None;
+    }
+    let rgba = image::RgbaImage::from_raw(frame.width, frame.height, frame.rgba_data.to_vec())?;
+    let mut image = image::DynamicImage::ImageRgba8(rgba).to_rgb8();
+    for b in boxes {
+        draw_box(
+            &mut image,
+            [b.x1, b.y1, b.x2, b.y2],
+            image::Rgb([0, 255, 0]),
+        );
+    }
+    if let Some(rect) = region {
+        draw_box(&mut image, rect, image::Rgb([255, 0, 0]));
+    }
+    let mut jpeg = Vec::new();
+    match image.write_to(&mut Cursor::new(&mut jpeg), image::ImageFormat::Jpeg) {
+        Ok(()) => Some(jpeg),
+        Err(e) => {
+            eprintln!("❌ 告警快照编码失败: {}", e);
+            None
+        }
+    }
+}
+
+/// 画 2 像素宽的矩形框
+pub(crate) fn draw_box<I>(image: &mut I, rect: [f32; 4], color: I::Pixel)
+where
+    I: image::GenericImage,
+    I::Pixel: 'static,
+{
+    let [x1, y1, x2, y2] = rect;
+    let width = (x2 - x1).max(1.0) as u32;
+    let height = (y2 - y1).max(1.0) as u32;
+    for t in 0..2 {
+        let r = Rect::at(x1 as i32 - t, y1 as i32 - t)
+            .of_size(width + 2 * t as u32, height + 2 * t as u32);
+        draw_hollow_rect_mut(image, r, color);
+    }
+}
+
+/// multipart/form-data 请求体
+struct Multipart {
+    boundary: String,
+    body: Vec<u8>,
+}
+
+impl Multipart {
+    fn new() -> Self {
+        Self {
+            boundary: format!("----yolov8rs{:016x}", rand::random::<u64>()),
+            body: Vec::new(),
+        }
+    }
+
+    fn text(mut self, name: &str, value: &str) -> Self {
+        self.body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                self.boundary, name, value
+            )
+            .as_bytes(),
+        );
+        self
+    }
+
+    fn jpeg(mut self, name: &str, filename: &str, data: &[u8]) -> Self {
+        self.body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: image/jpeg\r\n\r\n",
+                self.boundary, name, filename
+            )
+            .as_bytes(),
+        );
+        self.body.extend_from_slice(data);
+        self.body.extend_from_slice(b"\r\n");
+        self
+    }
+
+    /// 返回 Content-Type 与请求体
+    fn finish(mut self) -> (String, Vec<u8>) {
+        self.body
+            .extend_from_slice(format!("--{}--\r\n", self.boundary).as_bytes());
+        (
+            format!("multipart/form-data; boundary={}", self.boundary),
+            self.body,
+        )
+    }
+}
+
+/// 发送到单个通道
+fn send(
+    channel: &Channel,
+    alert: &Alert,
+    text: &str,
+    snapshot: Option<&[u8]>,
+) -> anyhow::Result<()> {
+    match channel {
+        Channel::Webhook { url } => {
+            let payload = serde_json::json!({
+                "kind": alert.kind,
+                "source": alert.source,
+                "message": text,
+                "view": alert.view,
+                "time": clock::to_display(alert.time).to_rfc3339(),
+            });
+            let mut form = Multipart::new().text("payload", &payload.to_string());
+            if let Some(jpeg) = snapshot {
+                form = form.jpeg("snapshot", "snapshot.jpg", jpeg);
+            }
+            let (content_type, body) = form.finish();
+            ureq::post(url)
+                .timeout(SEND_TIMEOUT)
+                .set("Content-Type", &content_type)
+                .send_bytes(&body)?;
+        }
+        Channel::Telegram { token, chat_id } => {
+            let api = format!("https://api.telegram.org/bot{}", token);
+            match snapshot {
+                Some(jpeg) => {
+                    let caption: String = text.chars().take(TELEGRAM_CAPTION_LIMIT).collect();
+                    let (content_type, body) = Multipart::new()
+                        .text("chat_id", chat_id)
+                        .text("caption", &caption)
+                        .jpeg("photo", "snapshot.jpg", jpeg)
+                        .finish();
+                    ureq::post(&format!("{}/sendPhoto", api))
+                        .timeout(SEND_TIMEOUT)
+                        .set("Content-Type", &content_type)
+                        .send_bytes(&body)?;
+                }
+                None => {
+                    let body = serde_json::json!({ "chat_id": chat_id, "text": text });
+                    ureq::post(&format!("{}/sendMessage", api))
+                        .timeout(SEND_TIMEOUT)
+                        .set("Content-Type", "application/json")
+                        .send_string(&body.to_string())?;
+                }
+            }
+        }
+        Channel::Email {
+            server,
+            port,
+            username,
+            password,
+            from,
+            to,
+        } => {
+            #[cfg(feature = "email")]
+            {
+                use lettre::message::{header::ContentType, Attachment, MultiPart, SinglePart};
+                use lettre::transport::smtp::authentication::Credentials;
+                use lettre::{Message, SmtpTransport, Transport};
+
+                let mut builder = Message::builder().from(from.parse()?).subject(format!(
+                    "[{}] {}",
+                    alert.kind.label(),
+                    alert.source
+                ));
+                for address in to {
+                    builder = builder.to(address.parse()?);
+                }
+                let body = SinglePart::plain(text.to_string());
+                let email = match snapshot {
+                    Some(jpeg) => builder.multipart(
+                        MultiPart::mixed().singlepart(body).singlepart(
+                            Attachment::new("snapshot.jpg".to_string())
+                                .body(jpeg.to_vec(), ContentType::parse("image/jpeg")?),
+                        ),
+                    )?,
+                    None => builder.singlepart(body)?,
+                };
+                SmtpTransport::starttls_relay(server)?
+                    .port(*port)
+                    .credentials(Credentials::new(username.clone(), password.clone()))
+                    .timeout(Some(SEND_TIMEOUT))
+                    .build()
+                    .send(&email)?;
+            }
+            #[cfg(not(feature = "email"))]
+            {
+                let _ = (server, port, username, password, from, to);
+                anyhow::bail!("邮件通道需要 email 功能 (--features email)");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 各逻辑流的最新帧与检测框
+type LatestFrames = Arc<Mutex<HashMap<u32, (DecodedFrame, Vec<BBox>)>>>;
+
+/// 待发送的告警 (快照在发送线程上编码)
+struct Job {
+    alert: Alert,
+    frame: Option<(DecodedFrame, Vec<BBox>)>,
+}
+
+/// 告警通知 (订阅保持期间运行)
+pub struct Notifier {
+    _subs: Vec<Subscription>,
+}
+
+impl Notifier {
+    /// 启动发送线程并订阅事件; 没有通道时返回 None
+    pub fn start(config: NotifierConfig) -> Option<Self> {
+        if config.channels.is_empty() {
+            return None;
+        }
+        let names: Vec<String> = config.channels.iter().map(Channel::name).collect();
+        println!("📣 告警通知启动: {}", names.join(", "));
+        #[cfg(not(feature = "email"))]
+        if config
+            .channels
+            .iter()
+            .any(|c| matches!(c, Channel::Email { .. }))
+        {
+            eprintln!("⚠️  邮件通道需要 email 功能 (--features email), 将发送失败");
+        }
+
+        let (tx, rx) = crossbeam_channel::bounded::<Job>(QUEUE_SIZE);
+        let mut subs = Vec::new();
+
+        // 各逻辑流的最新帧与检测框 (快照用)
+        let latest: LatestFrames = Arc::new(Mutex::new(HashMap::new()));
+        if config.snapshot {
+            let frames = Arc::clone(&latest);
+            subs.push(xbus::subscribe::<DecodedFrame, _>(move |frame| {
+                let mut latest = frames.lock().unwrap();
+                let boxes = latest
+                    .remove(&frame.view)
+                    .map(|(_, b)| b)
+                    .unwrap_or_default();
+                latest.insert(frame.view, (frame.clone(), boxes));
+            }));
+            let results = Arc::clone(&latest);
+            subs.push(xbus::subscribe::<DetectionResult, _>(move |result| {
+                if let Some((_, boxes)) = results.lock().unwrap().get_mut(&result.view) {
+                    *boxes = result.bboxes.clone();
+                }
+            }));
+        }
+
+        let filter = Arc::new(Mutex::new(AlertFilter::new(&config)));
+        let enqueue = move |alert: Alert| {
+            if !scheduler::is_armed(alert.view) {
+                return;
+            }
+            if let Err(reason) = filter.lock().unwrap().admit(&alert, Instant::now()) {
+                if reason != Suppressed::Filtered {
+                    println!("🔇 告警未通知 ({:?}): {}", reason, alert.message);
+                }
+                return;
+            }
+            let frame = latest.lock().unwrap().get(&alert.view).cloned();
+            if tx.try_send(Job { alert, frame }).is_err() {
+                eprintln!("⚠️  告警通知队列已满, 丢弃");
+            }
+        };
+        let on_zone = enqueue.clone();
+        subs.push(xbus::subscribe::<ZoneEvent, _>(move |event| {
+            if event.verification.is_none_or(|v| v.confirmed) {
+                on_zone(Alert::from_zone(event));
+            }
+        }));
+        let on_left_behind = enqueue.clone();
+        subs.push(xbus::subscribe::<LeftBehindEvent, _>(move |event| {
+            on_left_behind(Alert::from_left_behind(event));
+        }));
+        // 只通知异常开始, 恢复不通知
+        subs.push(xbus::subscribe::<TamperEvent, _>(move |event| {
+            if event.active {
+                enqueue(Alert::from_tamper(event));
+            }
+        }));
+
+        let spawned = std::thread::Builder::new()
+            .name("notifier".to_string())
+            .spawn(move || {
+                for job in rx {
+                    let text = job.alert.render(&config.template);
+                    let snapshot = job.frame.and_then(|(frame, boxes)| {
+                        annotated_snapshot(&frame, &boxes, job.alert.region)
+                    });
+                    for channel in &config.channels {
+                        match send(channel, &job.alert, &text, snapshot.as_deref()) {
+                            Ok(()) => println!("📣 告警已发送 → {}: {}", channel.name(), text),
+                            Err(e) => eprintln!("❌ 告警发送失败 → {}: {}", channel.name(), e),
+                        }
+                    }
+                }
+                println!("📣 告警通知线程退出");
+            });
+        if let Err(e) = spawned {
+            eprintln!("❌ 告警通知线程启动失败: {}", e);
+        }
+
+        Some(Self { _subs: subs })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn alert(kind: AlertKind, source: &str, hour: u32) -> Alert {
+        Alert {
+            kind,
+            source: source.to_string(),
+            message: format!("{} 触发", source),
+            view: 1,
+            region: None,
+            // 2024-01-01 为星期一
+            time: Local.with_ymd_and_hms(2024, 1, 1, hour, 0, 0).unwrap(),
+        }
+    }
+
+    /// 模板占位符替换
+    #[test]
+    fn test_render_template() {
+        let text = alert(AlertKind::Zone, "A 人数 > 5", 8).render(DEFAULT_TEMPLATE);
+        assert_eq!(
+            text,
+            "2024-01-01 08:00:00 [区域事件] A 人数 > 5 触发 (逻辑流 1)"
+        );
+    }
+
+    /// 类型过滤、免打扰时段、按事件源的最小间隔与每小时上限
+    #[test]
+    fn test_alert_filter() {
+        let config = NotifierConfig {
+            events: vec![AlertKind::Zone, AlertKind::Removed],
+            min_interval_secs: 60.0,
+            max_per_hour: 2,
+            quiet_hours: vec![ArmWindow {
+                start: "12:00".to_string(),
+                end: "13:00".to_string(),
+                days: Vec::new(),
+            }],
+            ..NotifierConfig::default()
+        };
+        let mut filter = AlertFilter::new(&config);
+        let t0 = Instant::now();
+        let zone = alert(AlertKind::Zone, "A", 8);
+
+        assert_eq!(
+            filter.admit(&alert(AlertKind::LeftBehind, "x", 8), t0),
+            Err(Suppressed::Filtered)
+        );
+        assert_eq!(
+            filter.admit(&alert(AlertKind::Zone, "A", 12), t0),
+            Err(Suppressed::QuietHours)
+        );
+        assert_eq!(filter.admit(&zone, t0), Ok(()));
+        assert_eq!(
+            filter.admit(&zone, t0 + Duration::from_secs(30)),
+            Err(Suppressed::Interval)
+        );
+        assert_eq!(
+            filter.admit(
+                &alert(AlertKind::Zone, "B", 8),
+                t0 + Duration::from_secs(30)
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            filter.admit(&zone, t0 + Duration::from_secs(90)),
+            Err(Suppressed::HourlyLimit)
+        );
+        // 一小时后额度恢复
+        assert_eq!(filter.admit(&zone, t0 + Duration::from_secs(3601)), Ok(()));
+    }
+
+    /// multipart 请求体: 各部分以边界分隔, 以结束边界收尾
+    #[test]
+    fn test_multipart() {
+        let form = Multipart::new()
+            .text("chat_id", "42")
+            .jpeg("photo", "a.jpg", b"JPEG");
+        let boundary = form.boundary.clone();
+        let (content_type, body) = form.finish();
+        assert_eq!(
+            content_type,
+            format!("multipart/form-data; boundary={}", boundary)
+        );
+        let body = String::from_utf8(body).unwrap();
+        assert!(body.starts_with(&format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"chat_id\"\r\n\r\n42\r\n",
+            boundary
+        )));
+        assert!(body.contains("filename=\"a.jpg\"\r\nContent-Type: image/jpeg\r\n\r\nJPEG\r\n"));
+        assert!(body.ends_with(&format!("--{}--\r\n", boundary)));
+    }
+}
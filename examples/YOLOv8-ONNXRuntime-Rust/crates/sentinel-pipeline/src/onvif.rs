@@ -0,0 +1,292 @@
+//! ONVIF 分析元数据输出 (Profile M)
+//!
+//! 把检测结果编码为 ONVIF 分析元数据 (`tt:MetadataStream` → `tt:VideoAnalytics` → `tt:Frame`),
+//! 符合 ONVIF 的录像机 (VMS) 可以把本程序当作分析插件接入:
+//! - 每个检测框为一个 `tt:Object`: 有跟踪器时 ObjectId 为轨迹ID, 否则为帧内序号
+//! - 坐标按 ONVIF 默认坐标系归一化到 [-1, 1], y 轴向上 (画面上边为 1)
+//! - COCO 类别映射为 Human / Vehicle / Bike / Animal, 其余为 Other, Likelihood 为置信度
+//! - 没有目标的帧也输出空的 `tt:Frame`, 录像机据此清除上一帧的目标
+//!
+//! 最近的帧保存在缓冲中, 经调试 API 的 HTTP 拉取点 `GET /onvif/metadata?since=…` 读取.
+//! 不提供 RTSP 元数据轨道
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+
+use crate::detection::detector::DetectionResult;
+use crate::detection::types::DecodedFrame;
+use crate::xbus::{self, Subscription};
+
+/// 默认缓冲帧数 (30fps 约 10 秒)
+pub const DEFAULT_METADATA_FRAMES: usize = 300;
+
+/// ONVIF schema 命名空间
+const ONVIF_SCHEMA: &str = "http://www.onvif.org/ver10/schema";
+
+/// 最近的元数据帧, 未启用时为 None
+static BUFFER: OnceLock<Mutex<MetadataBuffer>> = OnceLock::new();
+
+/// 一个目标
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MetadataObject {
+    pub id: u32,
+    pub bbox: [f32; 4], // 画面像素坐标 (x1, y1, x2, y2)
+    pub class: Option<u32>,
+    pub confidence: f32,
+}
+
+impl MetadataObject {
+    /// 检测结果中的全部目标 (调试 API 的 WebSocket 检测结果流也使用)
+    pub fn from_result(result: &DetectionResult) -> Vec<Self> {
+        result
+            .bboxes
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                // 跟踪后 class_id 为轨迹ID, 检测类别按 IoU 找回
+                let (id, class) = if result.tracked {
+                    (b.class_id, result.classes.get(i).copied().flatten())
+                } else {
+                    (i as u32, Some(b.class_id))
+                };
+                MetadataObject {
+                    id,
+                    bbox: [b.x1, b.y1, b.x2, b.y2],
+                    class,
+                    confidence: b.confidence,
+                }
+            })
+            .collect()
+    }
+}
+
+/// 一帧的元数据
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetadataFrame {
+    pub time: DateTime<Utc>,
+    pub view: u32,
+    pub width: u32,
+    pub height: u32,
+    pub objects: Vec<MetadataObject>,
+}
+
+impl MetadataFrame {
+    /// 由检测结果与画面尺寸构造
+    pub fn from_result(
+        result: &DetectionResult,
+        width: u32,
+        height: u32,
+        time: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            time,
+            view: result.view,
+            width,
+            height,
+            objects: MetadataObject::from_result(result),
+        }
+    }
+
+    /// `tt:Frame` 元素
+    pub fn to_xml(&self) -> String {
+        let (w, h) = (self.width.max(1) as f32, self.height.max(1) as f32);
+        let nx = |x: f32| (x / w * 2.0 - 1.0).clamp(-1.0, 1.0);
+        let ny = |y: f32| (1.0 - y / h * 2.0).clamp(-1.0, 1.0);
+        let mut xml = format!(
+            "<tt:Frame UtcTime=\"{}\" Source=\"view{}\">",
+            self.time.to_rfc3339_opts(SecondsFormat::Micros, true),
+            self.view
+        );
+        for object in &self.objects {
+            let [x1, y1, x2, y2] = object.bbox;
+            let (left, right, top, bottom) = (nx(x1), nx(x2), ny(y1), ny(y2));
+            let _ = write!(
+                xml,
+                concat!(
+                    "<tt:Object ObjectId=\"{}\"><tt:Appearance><tt:Shape>",
+                    "<tt:BoundingBox left=\"{:.4}\" top=\"{:.4}\" right=\"{:.4}\" bottom=\"{:.4}\"/>",
+                    "<tt:CenterOfGravity x=\"{:.4}\" y=\"{:.4}\"/></tt:Shape>",
+                    "<tt:Class><tt:Type Likelihood=\"{:.3}\">{}</tt:Type></tt:Class>",
+                    "</tt:Appearance></tt:Object>"
+                ),
+                object.id,
+                left,
+                top,
+                right,
+                bottom,
+                (left + right) / 2.0,
+                (top + bottom) / 2.0,
+                object.confidence,
+                onvif_type(object.class)
+            );
+        }
+        xml.push_str("</tt:Frame>");
+        xml
+    }
+}
+
+/// COCO 类别 → ONVIF 目标类型
+pub fn onvif_type(class: Option<u32>) -> &'static str {
+    match class {
+        Some(0) => "Human",
+        Some(1 | 3) => "Bike",
+        Some(2 | 4..=8) => "Vehicle",
+        Some(14..=23) => "Animal",
+        _ => "Other",
+    }
+}
+
+/// 完整的元数据文档 (`tt:MetadataStream`)
+pub fn metadata_stream<'a>(frames: impl IntoIterator<Item = &'a MetadataFrame>) -> String {
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<tt:MetadataStream xmlns:tt=\"{}\"><tt:VideoAnalytics>",
+        ONVIF_SCHEMA
+    );
+    for frame in frames {
+        xml.push('\n');
+        xml.push_str(&frame.to_xml());
+    }
+    xml.push_str("\n</tt:VideoAnalytics></tt:MetadataStream>\n");
+    xml
+}
+
+/// 最近若干帧的缓冲
+pub struct MetadataBuffer {
+    frames: VecDeque<MetadataFrame>,
+    capacity: usize,
+}
+
+impl MetadataBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            frames: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn push(&mut self, frame: MetadataFrame) {
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    /// 晚于 since 的帧 (可按逻辑流过滤), 编码为元数据文档
+    ///
+    /// 客户端把收到的最后一帧的 UtcTime 作为下一次的 since, 即可逐次拉取新帧
+    pub fn pull(&self, since: Option<DateTime<Utc>>, view: Option<u32>) -> String {
+        metadata_stream(self.frames.iter().filter(|f| {
+            since.is_none_or(|since| f.time > since) && view.is_none_or(|view| f.view == view)
+        }))
+    }
+}
+
+/// HTTP 拉取点读取的元数据文档, 未启用时为 None
+pub fn pull(since: Option<DateTime<Utc>>, view: Option<u32>) -> Option<String> {
+    BUFFER
+        .get()
+        .map(|buffer| buffer.lock().unwrap().pull(since, view))
+}
+
+/// ONVIF 元数据输出 (订阅保持期间运行)
+pub struct OnvifPublisher {
+    _frame_sub: Subscription,
+    _result_sub: Subscription,
+}
+
+impl OnvifPublisher {
+    /// 订阅检测结果编码为元数据帧, 缓冲最近 capacity 帧
+    ///
+    /// 归一化坐标需要画面尺寸, 各逻辑流收到第一帧画面之前的检测结果不输出
+    pub fn start(capacity: usize) -> Self {
+        println!(
+            "📡 ONVIF 元数据输出: 缓冲 {} 帧, 经 GET /onvif/metadata 拉取",
+            capacity
+        );
+        BUFFER.get_or_init(|| Mutex::new(MetadataBuffer::new(capacity)));
+        let sizes: Arc<Mutex<HashMap<u32, (u32, u32)>>> = Arc::default();
+        let frame_sizes = Arc::clone(&sizes);
+        let frame_sub = xbus::subscribe::<DecodedFrame, _>(move |frame| {
+            frame_sizes
+                .lock()
+                .unwrap()
+                .insert(frame.view, (frame.width, frame.height));
+        });
+        let result_sub = xbus::subscribe::<DetectionResult, _>(move |result| {
+            let Some(&(width, height)) = sizes.lock().unwrap().get(&result.view) else {
+                return;
+            };
+            let frame = MetadataFrame::from_result(result, width, height, result.trace.captured_at);
+            if let Some(buffer) = BUFFER.get() {
+                buffer.lock().unwrap().push(frame);
+            }
+        });
+        Self {
+            _frame_sub: frame_sub,
+            _result_sub: result_sub,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(secs: i64, view: u32, objects: Vec<MetadataObject>) -> MetadataFrame {
+        MetadataFrame {
+            time: DateTime::from_timestamp(1_760_000_000 + secs, 0).unwrap(),
+            view,
+            width: 640,
+            height: 480,
+            objects,
+        }
+    }
+
+    /// 坐标归一化到 [-1, 1] 且 y 轴向上, 类别映射为 ONVIF 类型
+    #[test]
+    fn test_frame_xml() {
+        let xml = frame(
+            0,
+            1,
+            vec![MetadataObject {
+                id: 7,
+                bbox: [160.0, 0.0, 320.0, 240.0],
+                class: Some(0),
+                confidence: 0.9,
+            }],
+        )
+        .to_xml();
+        assert!(
+            xml.starts_with("<tt:Frame UtcTime=\"2025-10-09T08:53:20.000000Z\" Source=\"view1\">")
+        );
+        assert!(xml.contains("<tt:Object ObjectId=\"7\">"));
+        assert!(xml.contains(
+            "<tt:BoundingBox left=\"-0.5000\" top=\"1.0000\" right=\"0.0000\" bottom=\"0.0000\"/>"
+        ));
+        assert!(xml.contains("<tt:CenterOfGravity x=\"-0.2500\" y=\"0.5000\"/>"));
+        assert!(xml.contains("<tt:Type Likelihood=\"0.900\">Human</tt:Type>"));
+        assert_eq!(onvif_type(Some(7)), "Vehicle");
+        assert_eq!(onvif_type(None), "Other");
+    }
+
+    /// 缓冲只保留最近的帧, 拉取按 since 与逻辑流过滤
+    #[test]
+    fn test_buffer_pull() {
+        let mut buffer = MetadataBuffer::new(3);
+        for (secs, view) in [(0, 0), (1, 0), (2, 1), (3, 0)] {
+            buffer.push(frame(secs, view, Vec::new()));
+        }
+        let count = |xml: String| xml.matches("<tt:Frame ").count();
+        assert_eq!(count(buffer.pull(None, None)), 3);
+        assert_eq!(count(buffer.pull(None, Some(0))), 2);
+        let since = frame(2, 0, Vec::new()).time;
+        let xml = buffer.pull(Some(since), None);
+        assert_eq!(count(xml.clone()), 1);
+        assert!(xml.contains("<tt:MetadataStream xmlns:tt=\"http://www.onvif.org/ver10/schema\">"));
+        assert!(xml.trim_end().ends_with("</tt:MetadataStream>"));
+    }
+}
@@ -0,0 +1,563 @@
+//! 标注视频转推 (Re-streaming)
+//!
+//! 把一个逻辑流的标注画面 (视频 + 检测框) 编码为 H.264 对外输出, 墙上监视器或浏览器
+//! 不运行本程序也能看到分析后的画面:
+//! - RTSP: 推流到外部 RTSP 服务器 (如 mediamtx), 客户端从服务器拉流; FFmpeg 不能直接做 RTSP 服务端
+//! - HLS: 分段写入 `hls_dir`, 内置 HTTP 服务 (`hls_addr`) 提供 `/hls/index.m3u8` 与播放页 `/`
+//! - 按输出帧率取最新的标注画面送入编码器, 解码慢于输出帧率时重复上一帧
+//! - 画面尺寸变化 (切换输入源) 或推流中断时结束当前编码, 按新尺寸重新开始
+//!
+//! HLS 服务与调试 API 共用令牌 (api_tokens.json, viewer 即可观看); 播放列表按请求中的
+//! `?token=` 改写分段地址, 播放器逐段请求时不需要另外携带令牌.
+//!
+//! 配置文件 (restream.json), 不存在时不启用:
+//! ```json
+//! {
+//!   "view": 0,
+//!   "fps": 25,
+//!   "encoder": "libx264",
+//!   "bitrate_kbps": 4000,
+//!   "rtsp_url": "rtsp://127.0.0.1:8554/sentinel",
+//!   "hls_addr": "0.0.0.0:8088"
+//! }
+//! ```
+
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use ez_ffmpeg::{FfmpegContext, Input, Output};
+use serde::{Deserialize, Serialize};
+
+use crate::api::{self, ApiAuth};
+use crate::detection::detector::DetectionResult;
+use crate::detection::types::DecodedFrame;
+use crate::shm_output::{annotate_rgba, OutputResults};
+use crate::xbus::{self, Subscription};
+
+pub const RESTREAM_CONFIG_PATH: &str = "restream.json";
+
+/// HLS 播放列表文件名
+pub const HLS_PLAYLIST: &str = "index.m3u8";
+
+/// AVERROR_EOF (读回调返回该值表示输入结束)
+const AVERROR_EOF: i32 = -541_478_725;
+
+/// 编码失败后重新开始的间隔
+const RESTART_BACKOFF: Duration = Duration::from_secs(2);
+
+/// 还没有画面时的等待间隔
+const IDLE_POLL: Duration = Duration::from_millis(200);
+
+/// HLS 请求的读写超时
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 请求头最多读取的行数
+const MAX_HEADER_LINES: usize = 64;
+
+/// 浏览器播放页 (Safari 原生播放 HLS, 其余浏览器经 hls.js)
+const PLAYER_PAGE: &str = r#"<!DOCTYPE html>
+<html><head><meta charset="utf-8"><title>Sentinel</title></head>
+<body style="margin:0;background:#000">
+<video id="v" controls autoplay muted playsinline style="width:100%;height:100vh"></video>
+<script src="https://cdn.jsdelivr.net/npm/hls.js@1"></script>
+<script>
+const src = "/hls/index.m3u8" + location.search;
+const video = document.getElementById("v");
+if (video.canPlayType("application/vnd.apple.mpegurl")) {
+  video.src = src;
+} else if (window.Hls && Hls.isSupported()) {
+  const hls = new Hls();
+  hls.loadSource(src);
+  hls.attachMedia(video);
+}
+</script>
+</body></html>
+"#;
+
+/// 转推配置 (restream.json)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RestreamConfig {
+    pub view: u32,       // 转推的逻辑流
+    pub fps: f32,        // 输出帧率
+    pub encoder: String, // 视频编码器 (libx264 / h264_nvenc / ...)
+    pub bitrate_kbps: u32,
+    pub rtsp_url: String, // 推流地址 (外部 RTSP 服务器), 为空时不推 RTSP
+    pub hls_dir: String,  // HLS 分段目录
+    pub hls_addr: String, // HLS HTTP 服务地址, 为空时不输出 HLS
+    pub hls_segment_secs: u32,
+    pub hls_list_size: u32, // 播放列表保留的分段数
+}
+
+impl Default for RestreamConfig {
+    fn default() -> Self {
+        Self {
+            view: 0,
+            fps: 25.0,
+            encoder: "libx264".to_string(),
+            bitrate_kbps: 4000,
+            rtsp_url: String::new(),
+            hls_dir: "hls".to_string(),
+            hls_addr: "0.0.0.0:8088".to_string(),
+            hls_segment_secs: 2,
+            hls_list_size: 5,
+        }
+    }
+}
+
+impl RestreamConfig {
+    /// 从文件加载配置, 文件不存在或解析失败时为 None (不转推)
+    pub fn load(path: &str) -> Option<Self> {
+        let json = fs::read_to_string(path).ok()?;
+        match serde_json::from_str::<Self>(&json) {
+            Ok(config) => {
+                println!(
+                    "✅ 转推配置已从 {} 加载 (逻辑流 {}, {} @ {}fps)",
+                    path, config.view, config.encoder, config.fps
+                );
+                Some(config)
+            }
+            Err(e) => {
+                eprintln!("⚠️  转推配置解析失败: {}, 不启用", e);
+                None
+            }
+        }
+    }
+
+    /// 编码参数: 码率与关键帧间隔 (1 秒一个关键帧, 播放器加入与 HLS 切段都以关键帧为界)
+    fn codec_opts(&self) -> Vec<(&'static str, String)> {
+        let mut opts = vec![
+            ("b", format!("{}k", self.bitrate_kbps)),
+            ("g", format!("{}", self.fps.round().max(1.0) as u32)),
+        ];
+        if self.encoder == "libx264" {
+            opts.push(("preset", "veryfast".to_string()));
+            opts.push(("tune", "zerolatency".to_string()));
+        }
+        opts
+    }
+
+    fn rtsp_output(&self) -> Output {
+        Output::from(self.rtsp_url.as_str())
+            .set_format("rtsp")
+            .set_video_codec(&self.encoder)
+            .set_video_codec_opts(self.codec_opts())
+            .set_format_opts(vec![("rtsp_transport", "tcp".to_string())])
+    }
+
+    fn hls_output(&self) -> Output {
+        let playlist = Path::new(&self.hls_dir).join(HLS_PLAYLIST);
+        Output::from(playlist.to_string_lossy().as_ref())
+            .set_format("hls")
+            .set_video_codec(&self.encoder)
+            .set_video_codec_opts(self.codec_opts())
+            .set_format_opts(vec![
+                ("hls_time", self.hls_segment_secs.max(1).to_string()),
+                ("hls_list_size", self.hls_list_size.max(2).to_string()),
+                ("hls_flags", "delete_segments".to_string()),
+            ])
+    }
+}
+
+/// rawvideo 读回调: 按输出帧率逐帧送出, 一帧可能分多次读取
+struct RawFeeder {
+    frame: Vec<u8>,
+    offset: usize,
+    interval: Duration,
+    next_due: Option<Instant>,
+}
+
+impl RawFeeder {
+    fn new(fps: f32) -> Self {
+        Self {
+            frame: Vec::new(),
+            offset: 0,
+            interval: Duration::from_secs_f32(1.0 / fps.max(1.0)),
+            next_due: None,
+        }
+    }
+
+    /// 填充 buf, 返回写入的字节数; 当前帧送完后等到下一个节拍再取下一帧,
+    /// next_frame 返回 None 时结束输入
+    fn read(&mut self, buf: &mut [u8], mut next_frame: impl FnMut() -> Option<Vec<u8>>) -> i32 {
+        if self.offset >= self.frame.len() {
+            let now = Instant::now();
+            let due = self.next_due.unwrap_or(now);
+            if due > now {
+                thread::sleep(due - now);
+            }
+            // 编码落后超过一个节拍时从当前时刻重新计时, 不追帧
+            let next = due + self.interval;
+            self.next_due = Some(next.max(Instant::now()));
+            match next_frame() {
+                Some(frame) if !frame.is_empty() => {
+                    self.frame = frame;
+                    self.offset = 0;
+                }
+                _ => return AVERROR_EOF,
+            }
+        }
+        let n = buf.len().min(self.frame.len() - self.offset);
+        buf[..n].copy_from_slice(&self.frame[self.offset..self.offset + n]);
+        self.offset += n;
+        n as i32
+    }
+}
+
+/// 最新的画面与检测结果
+#[derive(Default)]
+struct Latest {
+    seq: u64, // 每收到一帧画面加一
+    frame: Option<DecodedFrame>,
+    results: OutputResults,
+}
+
+/// 编码一路输出, 直到停止、画面尺寸变化或编码出错
+fn encode(
+    config: &RestreamConfig,
+    output: Output,
+    latest: &Arc<Mutex<Latest>>,
+    stop: &Arc<AtomicBool>,
+    (width, height): (u32, u32),
+) -> Result<()> {
+    let latest = Arc::clone(latest);
+    let stop = Arc::clone(stop);
+    let mut seen = 0;
+    let mut last: Option<Vec<u8>> = None;
+    let mut next_frame = move || {
+        if stop.load(Ordering::Relaxed) {
+            return None;
+        }
+        let (seq, frame, results) = {
+            let latest = latest.lock().unwrap();
+            (latest.seq, latest.frame.clone()?, latest.results.clone())
+        };
+        if (frame.width, frame.height) != (width, height) {
+            return None;
+        }
+        // 没有新画面或画面不完整时重复上一帧
+        if seq != seen || last.is_none() {
+            seen = seq;
+            if let Some(rgba) = annotate_rgba(&frame, &results) {
+                last = Some(rgba);
+            }
+        }
+        last.clone()
+    };
+    let mut feeder = RawFeeder::new(config.fps);
+    let input =
+        Input::new_by_read_callback(move |buf: &mut [u8]| feeder.read(buf, &mut next_frame))
+            .set_format("rawvideo")
+            .set_input_opts(
+                [
+                    ("video_size", format!("{}x{}", width, height)),
+                    ("pixel_format", "rgba".to_string()),
+                    ("framerate", config.fps.to_string()),
+                ]
+                .into(),
+            );
+    FfmpegContext::builder()
+        .input(input)
+        .filter_descs(["format=yuv420p"].into())
+        .output(output)
+        .build()
+        .map_err(|e| anyhow::anyhow!("构建失败: {}", e))
+        .and_then(|ctx| ctx.start().map_err(|e| anyhow::anyhow!("启动失败: {}", e)))
+        .and_then(|sch| sch.wait().map_err(|e| anyhow::anyhow!("编码失败: {}", e)))
+}
+
+/// 一路输出的编码线程: 等到有画面后开始编码, 结束后按当前画面尺寸重新开始
+fn spawn_encoder(
+    name: &'static str,
+    config: RestreamConfig,
+    output: fn(&RestreamConfig) -> Output,
+    latest: Arc<Mutex<Latest>>,
+    stop: Arc<AtomicBool>,
+) {
+    thread::spawn(move || {
+        // 推流地址不可达时只提示一次, 恢复后再提示
+        let mut failing = false;
+        while !stop.load(Ordering::Relaxed) {
+            let size = latest
+                .lock()
+                .unwrap()
+                .frame
+                .as_ref()
+                .map(|f| (f.width, f.height));
+            let Some(size) = size else {
+                thread::sleep(IDLE_POLL);
+                continue;
+            };
+            if !failing {
+                println!(
+                    "📺 {} 转推开始: {}x{} @ {}fps ({})",
+                    name, size.0, size.1, config.fps, config.encoder
+                );
+            }
+            let started = Instant::now();
+            match encode(&config, output(&config), &latest, &stop, size) {
+                Ok(()) => failing = false,
+                Err(e) => {
+                    if !failing {
+                        eprintln!("⚠️  {} 转推中断: {}, {:?} 后重试", name, e, RESTART_BACKOFF);
+                    }
+                    // 编码运行过一段时间才算恢复, 立即失败的重试不重复提示
+                    failing = started.elapsed() < RESTART_BACKOFF;
+                    thread::sleep(RESTART_BACKOFF);
+                }
+            }
+        }
+        println!("📺 {} 转推已停止", name);
+    });
+}
+
+/// 请求路径对应的 HLS 文件名与类型; 只允许 hls_dir 下的播放列表与分段 (不含子目录)
+pub fn hls_file(path: &str) -> Option<(&str, &'static str)> {
+    let path = path.split_once('?').map_or(path, |(p, _)| p);
+    let name = path.strip_prefix("/hls/")?;
+    let safe = name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if !safe || name.starts_with('.') {
+        return None;
+    }
+    let content_type = match name.rsplit_once('.')?.1 {
+        "m3u8" => "application/vnd.apple.mpegurl",
+        "ts" => "video/mp2t",
+        _ => return None,
+    };
+    Some((name, content_type))
+}
+
+/// 播放列表中的分段地址附加令牌 (播放器按相对地址请求分段, 不会沿用播放列表的查询参数)
+pub fn rewrite_playlist(playlist: &str, token: &str) -> String {
+    playlist
+        .lines()
+        .map(|line| {
+            if line.is_empty() || line.starts_with('#') {
+                format!("{}\n", line)
+            } else {
+                format!("{}?token={}\n", line, token)
+            }
+        })
+        .collect()
+}
+
+/// HLS 请求: 读取请求行与请求头, 鉴权后返回播放页、播放列表或分段
+fn handle_hls(stream: TcpStream, dir: &Path, auth: &ApiAuth) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut line = String::new();
+    let mut bearer = None;
+    for _ in 0..MAX_HEADER_LINES {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("authorization") {
+                bearer = value.trim().strip_prefix("Bearer ").map(str::to_string);
+            }
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (status, content_type, body): (u16, &str, Vec<u8>) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(path)) => {
+            let route_path = path.split_once('?').map_or(path, |(p, _)| p);
+            if route_path == "/" {
+                // 播放页不含画面, 令牌随查询参数交给播放列表请求
+                (200, "text/html", PLAYER_PAGE.as_bytes().to_vec())
+            } else {
+                match api::authorize(auth, "GET", path, bearer.as_deref()) {
+                    Err(response) => (response.status, response.content_type, response.body.into()),
+                    Ok(_) => match hls_file(path).and_then(|(name, content_type)| {
+                        fs::read(dir.join(name))
+                            .ok()
+                            .map(|data| (name, content_type, data))
+                    }) {
+                        Some((name, content_type, data)) => {
+                            let query = path.split_once('?').map_or("", |(_, q)| q);
+                            let token = api::query_param(query, "token");
+                            let data = match token {
+                                Some(token) if name.ends_with(".m3u8") => {
+                                    rewrite_playlist(&String::from_utf8_lossy(&data), token).into()
+                                }
+                                _ => data,
+                            };
+                            (200, content_type, data)
+                        }
+                        None => (404, "text/plain", b"not found".to_vec()),
+                    },
+                }
+            }
+        }
+        (Some(_), Some(_)) => (405, "text/plain", b"method not allowed".to_vec()),
+        _ => (400, "text/plain", b"bad request".to_vec()),
+    };
+
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n",
+        status,
+        api::reason(status),
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(&body)?;
+    stream.flush()
+}
+
+/// 启动 HLS HTTP 服务 (每个连接一个线程)
+fn spawn_hls_server(addr: &str, dir: PathBuf, auth: ApiAuth) -> Result<()> {
+    let listener =
+        TcpListener::bind(addr).with_context(|| format!("HLS 服务绑定 {} 失败", addr))?;
+    println!(
+        "🌐 HLS 服务: http://{}/ (播放列表 /hls/{})",
+        listener.local_addr()?,
+        HLS_PLAYLIST
+    );
+    let dir = Arc::new(dir);
+    let auth = Arc::new(auth);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let (dir, auth) = (Arc::clone(&dir), Arc::clone(&auth));
+            thread::spawn(move || {
+                let _ = handle_hls(stream, &dir, &auth);
+            });
+        }
+    });
+    Ok(())
+}
+
+/// 标注视频转推 (保持期间运行, 释放后编码线程在下一帧结束)
+pub struct RestreamServer {
+    _frame_sub: Subscription,
+    _result_sub: Subscription,
+    stop: Arc<AtomicBool>,
+}
+
+impl RestreamServer {
+    /// 订阅画面与检测结果, 按配置启动 RTSP 推流与 HLS 输出
+    pub fn start(config: RestreamConfig, auth: ApiAuth) -> Self {
+        let latest: Arc<Mutex<Latest>> = Arc::default();
+        let stop = Arc::new(AtomicBool::new(false));
+        let view = config.view;
+
+        let frame_latest = Arc::clone(&latest);
+        let frame_sub = xbus::subscribe::<DecodedFrame, _>(move |frame| {
+            if frame.view == view {
+                let mut latest = frame_latest.lock().unwrap();
+                latest.seq += 1;
+                latest.frame = Some(frame.clone());
+            }
+        });
+        let result_latest = Arc::clone(&latest);
+        let result_sub = xbus::subscribe::<DetectionResult, _>(move |result| {
+            if result.view == view {
+                result_latest.lock().unwrap().results = OutputResults::from_result(result);
+            }
+        });
+
+        if !config.rtsp_url.is_empty() {
+            println!("📺 RTSP 推流: {}", config.rtsp_url);
+            spawn_encoder(
+                "RTSP",
+                config.clone(),
+                RestreamConfig::rtsp_output,
+                Arc::clone(&latest),
+                Arc::clone(&stop),
+            );
+        }
+        if !config.hls_addr.is_empty() {
+            let dir = PathBuf::from(&config.hls_dir);
+            let started = fs::create_dir_all(&dir)
+                .with_context(|| format!("创建 HLS 目录 {} 失败", dir.display()))
+                .and_then(|_| spawn_hls_server(&config.hls_addr, dir, auth));
+            match started {
+                Ok(()) => spawn_encoder(
+                    "HLS",
+                    config.clone(),
+                    RestreamConfig::hls_output,
+                    Arc::clone(&latest),
+                    Arc::clone(&stop),
+                ),
+                Err(e) => eprintln!("⚠️  HLS 输出未启动: {:#}", e),
+            }
+        }
+
+        Self {
+            _frame_sub: frame_sub,
+            _result_sub: result_sub,
+            stop,
+        }
+    }
+}
+
+impl Drop for RestreamServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 一帧按缓冲大小分多次送出, 送完后取下一帧; 没有新帧时结束输入
+    #[test]
+    fn test_feeder_chunks_frames() {
+        let mut feeder = RawFeeder::new(1000.0);
+        let mut frames = vec![vec![1u8, 2, 3, 4, 5], vec![4; 3]].into_iter();
+        let mut buf = [0u8; 4];
+        let mut next = || frames.next();
+        assert_eq!(feeder.read(&mut buf, &mut next), 4);
+        assert_eq!(buf, [1, 2, 3, 4]);
+        assert_eq!(feeder.read(&mut buf, &mut next), 1);
+        assert_eq!(buf[0], 5);
+        assert_eq!(feeder.read(&mut buf, &mut next), 3);
+        assert_eq!(&buf[..3], &[4, 4, 4]);
+        assert_eq!(feeder.read(&mut buf, &mut next), AVERROR_EOF);
+    }
+
+    /// 只允许目录下的播放列表与分段, 拒绝路径穿越与其他文件
+    #[test]
+    fn test_hls_file() {
+        assert_eq!(
+            hls_file("/hls/index.m3u8?token=abc"),
+            Some(("index.m3u8", "application/vnd.apple.mpegurl"))
+        );
+        assert_eq!(
+            hls_file("/hls/index12.ts"),
+            Some(("index12.ts", "video/mp2t"))
+        );
+        assert_eq!(hls_file("/hls/../api_tokens.json"), None);
+        assert_eq!(hls_file("/hls/..%2Findex.ts"), None);
+        assert_eq!(hls_file("/hls/sub/index.ts"), None);
+        assert_eq!(hls_file("/hls/notes.txt"), None);
+        assert_eq!(hls_file("/index.m3u8"), None);
+    }
+
+    /// 分段地址附加令牌, 标签行不变
+    #[test]
+    fn test_rewrite_playlist() {
+        let playlist = "#EXTM3U\n#EXT-X-TARGETDURATION:2\n#EXTINF:2.000000,\nindex3.ts\n#EXTINF:2.000000,\nindex4.ts\n";
+        let rewritten = rewrite_playlist(playlist, "abc");
+        assert_eq!(
+            rewritten,
+            "#EXTM3U\n#EXT-X-TARGETDURATION:2\n#EXTINF:2.000000,\nindex3.ts?token=abc\n#EXTINF:2.000000,\nindex4.ts?token=abc\n"
+        );
+    }
+}
@@ -0,0 +1,511 @@
+//! 共享内存输出 (标注帧 + 检测结果)
+//!
+//! 将指定逻辑流的最新标注帧 (RGBA, 检测框为绿色) 与检测结果 (JSON 或紧凑编码) 写入映射文件,
+//! 外部渲染程序 (如 Qt 界面) 直接映射读取, 不经过套接字. 只保留最新一帧, 以顺序锁保护:
+//! 写端先将 seq 加 1 (奇数表示正在写), 写完数据后再加 1 (偶数表示稳定);
+//! 读端在拷贝前后各读一次 seq, 两次相同且为偶数才是完整的一帧, 否则重试.
+//!
+//! 布局 (小端, 所有偏移按 8 字节对齐):
+//! - 文件头 64 字节: magic `YOUT` | version u32 | frame_capacity u32 | results_capacity u32 | results_format u32 | seq u64
+//! - 帧头 32 字节 (偏移 64): width u32 | height u32 | view u32 | frame_len u32 | results_len u32 | 保留 u32 | pts_us u64
+//! - 帧数据 frame_capacity 字节 (偏移 96, RGBA 紧密排列), 其后为结果 results_capacity 字节
+//!   (results_format 0 为 UTF-8 JSON, 1 为 [`crate::codec`] 的关键帧)
+
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+use std::sync::atomic::{fence, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use memmap2::{Mmap, MmapMut};
+use serde::{Deserialize, Serialize};
+
+use crate::codec::{self, CompactBox, CompactFrame};
+
+use crate::detection::detector::DetectionResult;
+use crate::detection::types::DecodedFrame;
+use crate::notifier::draw_box;
+use crate::xbus::{self, Subscription};
+
+/// 文件头魔数
+pub const OUTPUT_MAGIC: [u8; 4] = *b"YOUT";
+/// 协议版本
+pub const OUTPUT_VERSION: u32 = 1;
+/// 文件头长度
+const HEADER_SIZE: usize = 64;
+/// 帧头长度
+const FRAME_HEADER_SIZE: usize = 32;
+/// seq 在文件头中的偏移
+const SEQ_OFFSET: usize = 24;
+/// results_format 在文件头中的偏移
+const FORMAT_OFFSET: usize = 16;
+
+/// 默认帧区容量 (4K RGBA), tmpfs 上按实际写入占用内存
+pub const DEFAULT_FRAME_CAPACITY: usize = 3840 * 2160 * 4;
+/// 默认结果区容量
+pub const DEFAULT_RESULTS_CAPACITY: usize = 1 << 20;
+
+/// 单个检测框
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OutputBox {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+    pub confidence: f32,
+    /// 类别ID, 经过跟踪器时为轨迹ID
+    pub class_id: u32,
+    /// 跨摄像头全局ID, 未启用时为 None
+    pub global_id: Option<u32>,
+}
+
+/// 结果区的编码 (文件头 results_format)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ResultsFormat {
+    #[default]
+    Json, // 0: UTF-8 JSON
+    Compact, // 1: 紧凑二进制编码 (量化坐标, 只有关键帧)
+}
+
+impl ResultsFormat {
+    fn from_u32(v: u32) -> Result<Self> {
+        match v {
+            0 => Ok(Self::Json),
+            1 => Ok(Self::Compact),
+            v => bail!("不支持的结果编码 {}", v),
+        }
+    }
+}
+
+/// 一帧的检测结果 (结果区中的 JSON)
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct OutputResults {
+    pub view: u32,
+    pub tracked: bool,
+    pub inference_ms: f64,
+    pub boxes: Vec<OutputBox>,
+}
+
+impl OutputResults {
+    pub fn from_result(result: &DetectionResult) -> Self {
+        Self {
+            view: result.view,
+            tracked: result.tracked,
+            inference_ms: result.inference_ms,
+            boxes: result
+                .bboxes
+                .iter()
+                .enumerate()
+                .map(|(i, b)| OutputBox {
+                    x1: b.x1,
+                    y1: b.y1,
+                    x2: b.x2,
+                    y2: b.y2,
+                    confidence: b.confidence,
+                    class_id: b.class_id,
+                    global_id: result.global_ids.get(i).copied().flatten(),
+                })
+                .collect(),
+        }
+    }
+
+    /// 转为紧凑编码的帧 (跟踪时以轨迹ID作为差量的键)
+    pub fn to_compact(&self, width: u32, height: u32, pts_us: u64) -> CompactFrame {
+        CompactFrame {
+            view: self.view,
+            sequence: 0,
+            pts_us,
+            width,
+            height,
+            tracked: self.tracked,
+            inference_ms: self.inference_ms as f32,
+            boxes: self
+                .boxes
+                .iter()
+                .enumerate()
+                .map(|(i, b)| CompactBox {
+                    id: if self.tracked { b.class_id } else { i as u32 },
+                    class_id: b.class_id,
+                    x1: b.x1,
+                    y1: b.y1,
+                    x2: b.x2,
+                    y2: b.y2,
+                    confidence: b.confidence,
+                    global_id: b.global_id,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn from_compact(frame: &CompactFrame) -> Self {
+        Self {
+            view: frame.view,
+            tracked: frame.tracked,
+            inference_ms: frame.inference_ms as f64,
+            boxes: frame
+                .boxes
+                .iter()
+                .map(|b| OutputBox {
+                    x1: b.x1,
+                    y1: b.y1,
+                    x2: b.x2,
+                    y2: b.y2,
+                    confidence: b.confidence,
+                    class_id: b.class_id,
+                    global_id: b.global_id,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 读端取到的一帧
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutputFrame {
+    pub seq: u64,
+    pub width: u32,
+    pub height: u32,
+    pub view: u32,
+    pub pts_us: u64,
+    pub rgba: Vec<u8>,
+    pub results: OutputResults,
+}
+
+fn read_u32(map: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(map[offset..offset + 4].try_into().unwrap())
+}
+
+fn seq_of(map: &[u8]) -> &AtomicU64 {
+    // SAFETY: 映射页对齐, 偏移 24 满足 8 字节对齐, 映射存活期间引用有效
+    unsafe { &*(map.as_ptr().add(SEQ_OFFSET) as *const AtomicU64) }
+}
+
+/// 写端: 创建 (或截断) 映射文件并按顺序锁写入
+pub struct ShmWriter {
+    map: MmapMut,
+    frame_capacity: usize,
+    results_capacity: usize,
+    format: ResultsFormat,
+    // 读端只看最新一帧, 紧凑编码只发关键帧
+    encoder: codec::Encoder,
+}
+
+impl ShmWriter {
+    pub fn create(
+        path: &Path,
+        frame_capacity: usize,
+        results_capacity: usize,
+        format: ResultsFormat,
+    ) -> Result<Self> {
+        let frame_capacity = frame_capacity.next_multiple_of(8);
+        let results_capacity = results_capacity.next_multiple_of(8);
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .with_context(|| format!("创建共享内存文件失败: {:?}", path))?;
+        let len = HEADER_SIZE + FRAME_HEADER_SIZE + frame_capacity + results_capacity;
+        file.set_len(len as u64).context("设置共享内存大小失败")?;
+        // SAFETY: 文件由本进程独占写入, 读端只读映射并以 seq 校验
+        let mut map = unsafe { MmapMut::map_mut(&file) }.context("映射共享内存失败")?;
+        map[4..8].copy_from_slice(&OUTPUT_VERSION.to_le_bytes());
+        map[8..12].copy_from_slice(&(frame_capacity as u32).to_le_bytes());
+        map[12..16].copy_from_slice(&(results_capacity as u32).to_le_bytes());
+        map[FORMAT_OFFSET..FORMAT_OFFSET + 4].copy_from_slice(&(format as u32).to_le_bytes());
+        // 魔数最后写入, 读端看到魔数即可信任容量字段
+        fence(Ordering::Release);
+        map[..4].copy_from_slice(&OUTPUT_MAGIC);
+        Ok(Self {
+            map,
+            frame_capacity,
+            results_capacity,
+            format,
+            encoder: codec::Encoder::new(1),
+        })
+    }
+
+    /// 写入一帧; 帧或结果超过容量时返回错误 (不改动已有内容)
+    pub fn write(
+        &mut self,
+        width: u32,
+        height: u32,
+        view: u32,
+        rgba: &[u8],
+        results: &OutputResults,
+    ) -> Result<u64> {
+        if rgba.len() > self.frame_capacity {
+            bail!("帧 {}x{} 超过共享内存帧区容量", width, height);
+        }
+        let pts_us = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_micros() as u64);
+        let encoded = match self.format {
+            ResultsFormat::Json => serde_json::to_vec(results)?,
+            ResultsFormat::Compact => self
+                .encoder
+                .encode(&results.to_compact(width, height, pts_us)),
+        };
+        if encoded.len() > self.results_capacity {
+            bail!("检测结果 {} 字节超过结果区容量", encoded.len());
+        }
+
+        let seq = seq_of(&self.map).load(Ordering::Relaxed);
+        seq_of(&self.map).store(seq + 1, Ordering::Relaxed);
+        fence(Ordering::Release);
+
+        let header = HEADER_SIZE;
+        for (i, v) in [width, height, view, rgba.len() as u32, encoded.len() as u32]
+            .into_iter()
+            .enumerate()
+        {
+            self.map[header + i * 4..header + i * 4 + 4].copy_from_slice(&v.to_le_bytes());
+        }
+        self.map[header + 24..header + 32].copy_from_slice(&pts_us.to_le_bytes());
+        let frame = header + FRAME_HEADER_SIZE;
+        self.map[frame..frame + rgba.len()].copy_from_slice(rgba);
+        let results_start = frame + self.frame_capacity;
+        self.map[results_start..results_start + encoded.len()].copy_from_slice(&encoded);
+
+        seq_of(&self.map).store(seq + 2, Ordering::Release);
+        Ok(seq + 2)
+    }
+}
+
+/// 读端 (供 Rust 消费者与测试使用, 其他语言按模块文档的布局实现即可)
+pub struct ShmReader {
+    map: Mmap,
+    frame_capacity: usize,
+    format: ResultsFormat,
+}
+
+impl ShmReader {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("打开共享内存文件失败: {:?}", path))?;
+        // SAFETY: 只读映射, 写端并发修改的内容以 seq 校验
+        let map = unsafe { Mmap::map(&file) }.context("映射共享内存失败")?;
+        if map.len() < HEADER_SIZE || map[..4] != OUTPUT_MAGIC {
+            bail!("共享内存文件头无效 (魔数不匹配)");
+        }
+        if read_u32(&map, 4) != OUTPUT_VERSION {
+            bail!("不支持的共享内存协议版本 {}", read_u32(&map, 4));
+        }
+        let frame_capacity = read_u32(&map, 8) as usize;
+        let results_capacity = read_u32(&map, 12) as usize;
+        let format = ResultsFormat::from_u32(read_u32(&map, FORMAT_OFFSET))?;
+        if map.len() < HEADER_SIZE + FRAME_HEADER_SIZE + frame_capacity + results_capacity {
+            bail!("共享内存文件过小");
+        }
+        Ok(Self {
+            map,
+            frame_capacity,
+            format,
+        })
+    }
+
+    /// 当前序号 (奇数表示写端正在写, 0 表示尚未写入)
+    pub fn seq(&self) -> u64 {
+        seq_of(&self.map).load(Ordering::Acquire)
+    }
+
+    /// 读取序号大于 `after` 的最新完整帧; 没有新帧或读取期间被覆盖时返回 None
+    pub fn read_after(&self, after: u64) -> Option<OutputFrame> {
+        let seq = self.seq();
+        if seq == 0 || seq <= after || seq % 2 == 1 {
+            return None;
+        }
+        let header = HEADER_SIZE;
+        let width = read_u32(&self.map, header);
+        let height = read_u32(&self.map, header + 4);
+        let view = read_u32(&self.map, header + 8);
+        let frame_len = (read_u32(&self.map, header + 12) as usize).min(self.frame_capacity);
+        let results_len = read_u32(&self.map, header + 16) as usize;
+        let pts_us = u64::from_le_bytes(self.map[header + 24..header + 32].try_into().unwrap());
+        let frame = header + FRAME_HEADER_SIZE;
+        let rgba = self.map[frame..frame + frame_len].to_vec();
+        let results_start = frame + self.frame_capacity;
+        let results_end = (results_start + results_len).min(self.map.len());
+        let encoded = self.map[results_start..results_end].to_vec();
+
+        fence(Ordering::Acquire);
+        if seq_of(&self.map).load(Ordering::Relaxed) != seq {
+            return None;
+        }
+        let results = match self.format {
+            ResultsFormat::Json => serde_json::from_slice(&encoded).ok()?,
+            ResultsFormat::Compact => {
+                OutputResults::from_compact(&codec::Decoder::new().decode(&encoded).ok()?)
+            }
+        };
+        Some(OutputFrame {
+            seq,
+            width,
+            height,
+            view,
+            pts_us,
+            rgba,
+            results,
+        })
+    }
+}
+
+/// 标注帧: 在 RGBA 副本上画检测框; 帧数据不完整时为 None
+pub fn annotate_rgba(frame: &DecodedFrame, results: &OutputResults) -> Option<Vec<u8>> {
+    if frame.rgba_data.len() < (frame.width * frame.height * 4) as usize {
+        return None;
+    }
+    let mut image =
+        image::RgbaImage::from_raw(frame.width, frame.height, frame.rgba_data.to_vec())?;
+    for b in &results.boxes {
+        draw_box(
+            &mut image,
+            [b.x1, b.y1, b.x2, b.y2],
+            image::Rgba([0, 255, 0, 255]),
+        );
+    }
+    Some(image.into_raw())
+}
+
+/// 共享内存发布 (订阅保持期间运行)
+pub struct ShmPublisher {
+    _subs: Vec<Subscription>,
+}
+
+impl ShmPublisher {
+    /// 创建映射文件, 订阅逻辑流 `view` 的帧与检测结果, 标注与写入在后台线程完成
+    pub fn start(path: &Path, view: u32, format: ResultsFormat) -> Result<Self> {
+        let mut writer = ShmWriter::create(
+            path,
+            DEFAULT_FRAME_CAPACITY,
+            DEFAULT_RESULTS_CAPACITY,
+            format,
+        )?;
+        println!(
+            "📤 共享内存输出: {:?} (逻辑流 {}, 结果编码 {:?})",
+            path, view, format
+        );
+
+        // 只保留一帧待写: 写线程忙时新结果直接丢弃, 外部读端本来就只看最新帧
+        let (tx, rx) = crossbeam_channel::bounded::<(DecodedFrame, OutputResults)>(1);
+        let latest: Arc<Mutex<Option<DecodedFrame>>> = Arc::new(Mutex::new(None));
+        let frames = Arc::clone(&latest);
+        let frame_sub = xbus::subscribe::<DecodedFrame, _>(move |frame| {
+            if frame.view == view {
+                *frames.lock().unwrap() = Some(frame.clone());
+            }
+        });
+        let result_sub = xbus::subscribe::<DetectionResult, _>(move |result| {
+            if result.view != view {
+                return;
+            }
+            if let Some(frame) = latest.lock().unwrap().clone() {
+                let _ = tx.try_send((frame, OutputResults::from_result(result)));
+            }
+        });
+
+        std::thread::Builder::new()
+            .name("shm-output".to_string())
+            .spawn(move || {
+                let mut warned = false;
+                for (frame, results) in rx {
+                    let Some(rgba) = annotate_rgba(&frame, &results) else {
+                        continue;
+                    };
+                    match writer.write(frame.width, frame.height, view, &rgba, &results) {
+                        Ok(_) => warned = false,
+                        Err(e) if !warned => {
+                            eprintln!("⚠️ 共享内存输出跳过: {}", e);
+                            warned = true;
+                        }
+                        Err(_) => {}
+                    }
+                }
+                println!("📤 共享内存输出线程退出");
+            })
+            .context("共享内存输出线程启动失败")?;
+
+        Ok(Self {
+            _subs: vec![frame_sub, result_sub],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results() -> OutputResults {
+        OutputResults {
+            view: 2,
+            tracked: true,
+            inference_ms: 4.5,
+            boxes: vec![OutputBox {
+                x1: 0.0,
+                y1: 0.0,
+                x2: 3.0,
+                y2: 3.0,
+                confidence: 0.9,
+                class_id: 7,
+                global_id: Some(11),
+            }],
+        }
+    }
+
+    /// 写入后读端取到同一帧与结果, 已读序号不再重复返回
+    #[test]
+    fn test_write_read_roundtrip() {
+        let path = std::env::temp_dir().join(format!("shm_output_{}", std::process::id()));
+        let mut writer = ShmWriter::create(&path, 64, 1024, ResultsFormat::Json).unwrap();
+        let reader = ShmReader::open(&path).unwrap();
+        assert_eq!(reader.read_after(0), None);
+
+        let rgba: Vec<u8> = (0..64).collect();
+        let seq = writer.write(4, 4, 2, &rgba, &results()).unwrap();
+        let frame = reader.read_after(0).unwrap();
+        assert_eq!(
+            (frame.seq, frame.width, frame.height, frame.view),
+            (seq, 4, 4, 2)
+        );
+        assert_eq!(frame.rgba, rgba);
+        assert_eq!(frame.results, results());
+        assert_eq!(reader.read_after(seq), None);
+
+        // 超过容量的帧被拒绝, 已有内容保持不变
+        assert!(writer.write(8, 8, 2, &[0; 256], &results()).is_err());
+        assert_eq!(reader.read_after(0).unwrap().seq, seq);
+        std::fs::remove_file(path).ok();
+    }
+
+    /// 紧凑编码: 读端按文件头的格式解码, 坐标误差在量化精度内
+    #[test]
+    fn test_compact_results() {
+        let path = std::env::temp_dir().join(format!("shm_output_compact_{}", std::process::id()));
+        let mut writer = ShmWriter::create(&path, 64, 1024, ResultsFormat::Compact).unwrap();
+        let reader = ShmReader::open(&path).unwrap();
+        writer.write(4, 4, 2, &[0; 64], &results()).unwrap();
+        let frame = reader.read_after(0).unwrap();
+        let (got, expected) = (&frame.results, results());
+        assert_eq!((got.view, got.tracked, got.boxes.len()), (2, true, 1));
+        let (b, e) = (&got.boxes[0], &expected.boxes[0]);
+        assert_eq!((b.class_id, b.global_id), (e.class_id, e.global_id));
+        assert!((b.x2 - e.x2).abs() < 1e-3 && (b.confidence - e.confidence).abs() < 0.01);
+        std::fs::remove_file(path).ok();
+    }
+
+    /// 序号为奇数 (写端正在写) 时读端不返回帧
+    #[test]
+    fn test_reader_skips_write_in_progress() {
+        let path = std::env::temp_dir().join(format!("shm_output_odd_{}", std::process::id()));
+        let mut writer = ShmWriter::create(&path, 16, 256, ResultsFormat::Json).unwrap();
+        writer
+            .write(2, 2, 0, &[1; 16], &OutputResults::default())
+            .unwrap();
+        seq_of(&writer.map).fetch_add(1, Ordering::Relaxed);
+        let reader = ShmReader::open(&path).unwrap();
+        assert_eq!(reader.read_after(0), None);
+        std::fs::remove_file(path).ok();
+    }
+}
@@ -0,0 +1,445 @@
+//! 虚拟摄像头输出 (Virtual webcam)
+//!
+//! 把指定逻辑流的标注画面 (画面 + 检测框) 作为摄像头设备输出, 视频会议软件或只认摄像头的旧软件可以直接选用:
+//! - Linux: 写入 v4l2loopback 设备 (`modprobe v4l2loopback video_nr=10 exclusive_caps=1`), 格式 YUYV
+//! - Windows: 经 softcam (DirectShow 虚拟摄像头, 需先 `regsvr32 softcam.dll` 注册), 格式 BGR24
+//! - 摄像头的尺寸是固定的, 画面按比例缩放到输出尺寸, 多余部分填黑边
+//! - 每收到一帧画面发送一次, 检测框取最新一次的检测结果; 发送线程忙时丢帧, 不阻塞解码
+//!
+//! 配置文件 (virtual_camera.json), 不存在时不启用:
+//! ```json
+//! { "view": 0, "width": 1280, "height": 720, "fps": 30, "device": "/dev/video10" }
+//! ```
+//! Windows 上 `device` 为 softcam.dll 的路径.
+
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use image::imageops::FilterType;
+use serde::{Deserialize, Serialize};
+
+use crate::detection::detector::DetectionResult;
+use crate::detection::types::DecodedFrame;
+use crate::shm_output::{annotate_rgba, OutputResults};
+use crate::xbus::{self, Subscription};
+
+pub const VCAM_CONFIG_PATH: &str = "virtual_camera.json";
+
+/// 默认设备: v4l2loopback 设备节点 / softcam 库
+#[cfg(windows)]
+const DEFAULT_DEVICE: &str = "softcam.dll";
+#[cfg(not(windows))]
+const DEFAULT_DEVICE: &str = "/dev/video10";
+
+/// 虚拟摄像头配置 (virtual_camera.json)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VcamConfig {
+    pub view: u32, // 输出的逻辑流
+    pub width: u32,
+    pub height: u32,
+    pub fps: f32,       // 向应用声明的帧率 (softcam)
+    pub device: String, // Linux: v4l2loopback 设备节点; Windows: softcam.dll 路径
+}
+
+impl Default for VcamConfig {
+    fn default() -> Self {
+        Self {
+            view: 0,
+            width: 1280,
+            height: 720,
+            fps: 30.0,
+            device: DEFAULT_DEVICE.to_string(),
+        }
+    }
+}
+
+impl VcamConfig {
+    /// 从文件加载配置, 文件不存在或解析失败时为 None (不输出)
+    pub fn load(path: &str) -> Option<Self> {
+        let json = fs::read_to_string(path).ok()?;
+        match serde_json::from_str::<Self>(&json) {
+            Ok(config) => {
+                println!(
+                    "✅ 虚拟摄像头配置已从 {} 加载 ({}, 逻辑流 {})",
+                    path, config.device, config.view
+                );
+                Some(config)
+            }
+            Err(e) => {
+                eprintln!("⚠️  虚拟摄像头配置解析失败: {}, 不启用", e);
+                None
+            }
+        }
+    }
+
+    /// 输出尺寸 (YUYV 两个像素共用色度, 宽高取偶数)
+    pub fn output_size(&self) -> (u32, u32) {
+        (self.width.max(2) & !1, self.height.max(2) & !1)
+    }
+}
+
+/// 按比例缩放后在输出画面中的位置 (x, y, 宽, 高), 居中
+pub fn letterbox(src: (u32, u32), dst: (u32, u32)) -> (u32, u32, u32, u32) {
+    let (sw, sh) = (src.0.max(1) as f32, src.1.max(1) as f32);
+    let scale = (dst.0 as f32 / sw).min(dst.1 as f32 / sh);
+    let w = ((sw * scale).round() as u32).clamp(1, dst.0);
+    let h = ((sh * scale).round() as u32).clamp(1, dst.1);
+    ((dst.0 - w) / 2, (dst.1 - h) / 2, w, h)
+}
+
+/// 缩放到输出尺寸 (保持比例, 黑边填充); 数据不完整时为 None
+fn fit(rgba: Vec<u8>, src: (u32, u32), dst: (u32, u32)) -> Option<Vec<u8>> {
+    let image = image::RgbaImage::from_raw(src.0, src.1, rgba)?;
+    if src == dst {
+        return Some(image.into_raw());
+    }
+    let (x, y, w, h) = letterbox(src, dst);
+    let resized = image::imageops::resize(&image, w, h, FilterType::Triangle);
+    let mut canvas = image::RgbaImage::from_pixel(dst.0, dst.1, image::Rgba([0, 0, 0, 255]));
+    image::imageops::replace(&mut canvas, &resized, x as i64, y as i64);
+    Some(canvas.into_raw())
+}
+
+/// RGB → YUV (BT.601 有限范围)
+fn yuv(r: u8, g: u8, b: u8) -> (i32, i32, i32) {
+    let (r, g, b) = (r as i32, g as i32, b as i32);
+    (
+        ((66 * r + 129 * g + 25 * b + 128) >> 8) + 16,
+        ((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128,
+        ((112 * r - 94 * g - 18 * b + 128) >> 8) + 128,
+    )
+}
+
+/// RGBA → YUYV (每两个像素共用一组色度), width 须为偶数
+pub fn rgba_to_yuyv(rgba: &[u8], width: u32, height: u32, out: &mut Vec<u8>) {
+    out.clear();
+    for row in rgba.chunks_exact(width as usize * 4).take(height as usize) {
+        for pair in row.chunks_exact(8) {
+            let (y0, u0, v0) = yuv(pair[0], pair[1], pair[2]);
+            let (y1, u1, v1) = yuv(pair[4], pair[5], pair[6]);
+            out.extend_from_slice(&[
+                y0 as u8,
+                ((u0 + u1 + 1) / 2) as u8,
+                y1 as u8,
+                ((v0 + v1 + 1) / 2) as u8,
+            ]);
+        }
+    }
+}
+
+/// RGBA → BGR24 (自上而下逐行)
+pub fn rgba_to_bgr(rgba: &[u8], out: &mut Vec<u8>) {
+    out.clear();
+    for px in rgba.chunks_exact(4) {
+        out.extend_from_slice(&[px[2], px[1], px[0]]);
+    }
+}
+
+/// 虚拟摄像头设备 (输出尺寸固定)
+trait VirtualCamera: Send {
+    /// 发送一帧输出尺寸的 RGBA 画面
+    fn send(&mut self, rgba: &[u8]) -> Result<()>;
+}
+
+#[cfg(target_os = "linux")]
+mod v4l2 {
+    use std::fs::{File, OpenOptions};
+    use std::io::Write;
+    use std::os::fd::AsRawFd;
+
+    use anyhow::{bail, Context, Result};
+
+    use super::{rgba_to_yuyv, VirtualCamera};
+
+    const V4L2_BUF_TYPE_VIDEO_OUTPUT: u32 = 2;
+    const V4L2_FIELD_NONE: u32 = 1;
+    const V4L2_COLORSPACE_SRGB: u32 = 8;
+    const V4L2_PIX_FMT_YUYV: u32 = u32::from_le_bytes(*b"YUYV");
+
+    /// struct v4l2_pix_format
+    #[repr(C)]
+    #[derive(Default)]
+    struct PixFormat {
+        width: u32,
+        height: u32,
+        pixelformat: u32,
+        field: u32,
+        bytesperline: u32,
+        sizeimage: u32,
+        colorspace: u32,
+        priv_: u32,
+        flags: u32,
+        ycbcr_enc: u32,
+        quantization: u32,
+        xfer_func: u32,
+    }
+
+    /// struct v4l2_format 中的联合体 (200 字节, 含指针成员, 按指针对齐)
+    #[repr(C)]
+    struct FormatData {
+        pix: PixFormat,
+        _raw: [u8; 200 - std::mem::size_of::<PixFormat>()],
+        _align: [usize; 0],
+    }
+
+    /// struct v4l2_format
+    #[repr(C)]
+    struct Format {
+        type_: u32,
+        fmt: FormatData,
+    }
+
+    /// _IOWR('V', 5, struct v4l2_format)
+    pub(super) const VIDIOC_S_FMT: u32 =
+        (3 << 30) | ((std::mem::size_of::<Format>() as u32) << 16) | ((b'V' as u32) << 8) | 5;
+
+    /// v4l2loopback 输出设备: 设置 YUYV 格式后逐帧 write
+    pub(super) struct V4l2Loopback {
+        file: File,
+        width: u32,
+        height: u32,
+        yuyv: Vec<u8>,
+    }
+
+    impl V4l2Loopback {
+        pub(super) fn open(device: &str, width: u32, height: u32) -> Result<Self> {
+            let file = OpenOptions::new()
+                .write(true)
+                .open(device)
+                .with_context(|| format!("无法打开 {} (是否已加载 v4l2loopback?)", device))?;
+            let mut format = Format {
+                type_: V4L2_BUF_TYPE_VIDEO_OUTPUT,
+                fmt: FormatData {
+                    pix: PixFormat {
+                        width,
+                        height,
+                        pixelformat: V4L2_PIX_FMT_YUYV,
+                        field: V4L2_FIELD_NONE,
+                        bytesperline: width * 2,
+                        sizeimage: width * height * 2,
+                        colorspace: V4L2_COLORSPACE_SRGB,
+                        ..Default::default()
+                    },
+                    _raw: [0; 200 - std::mem::size_of::<PixFormat>()],
+                    _align: [],
+                },
+            };
+            // SAFETY: format 与内核的 struct v4l2_format 布局一致, 调用期间有效
+            let ret = unsafe { libc::ioctl(file.as_raw_fd(), VIDIOC_S_FMT as _, &mut format) };
+            if ret < 0 {
+                bail!(
+                    "{} 设置输出格式失败: {}",
+                    device,
+                    std::io::Error::last_os_error()
+                );
+            }
+            Ok(Self {
+                file,
+                width,
+                height,
+                yuyv: Vec::new(),
+            })
+        }
+    }
+
+    impl VirtualCamera for V4l2Loopback {
+        fn send(&mut self, rgba: &[u8]) -> Result<()> {
+            rgba_to_yuyv(rgba, self.width, self.height, &mut self.yuyv);
+            self.file
+                .write_all(&self.yuyv)
+                .context("写入 v4l2loopback 失败")
+        }
+    }
+}
+
+#[cfg(windows)]
+mod softcam {
+    use std::ffi::{c_int, c_void};
+    use std::ptr::NonNull;
+
+    use anyhow::{Context, Result};
+    use libloading::Library;
+
+    use super::{rgba_to_bgr, VirtualCamera};
+
+    /// softcam 虚拟摄像头 (scCreateCamera / scSendFrame / scDeleteCamera)
+    pub(super) struct Softcam {
+        camera: NonNull<c_void>,
+        send_frame: unsafe extern "C" fn(*mut c_void, *const c_void),
+        delete_camera: unsafe extern "C" fn(*mut c_void),
+        bgr: Vec<u8>,
+        _library: Library,
+    }
+
+    // SAFETY: 摄像头只在输出线程中使用, softcam 不要求在创建线程上发送
+    unsafe impl Send for Softcam {}
+
+    impl Softcam {
+        pub(super) fn open(dll: &str, width: u32, height: u32, fps: f32) -> Result<Self> {
+            // SAFETY: softcam.dll 的初始化代码没有额外要求, 函数签名与 softcam.h 一致
+            unsafe {
+                let library = Library::new(dll)
+                    .with_context(|| format!("无法加载 {} (softcam 虚拟摄像头)", dll))?;
+                let create = *library
+                    .get::<unsafe extern "C" fn(c_int, c_int, f32) -> *mut c_void>(
+                        b"scCreateCamera\0",
+                    )?;
+                let send_frame = *library.get(b"scSendFrame\0")?;
+                let delete_camera = *library.get(b"scDeleteCamera\0")?;
+                let camera = NonNull::new(create(width as c_int, height as c_int, fps))
+                    .context("scCreateCamera 失败 (softcam 同时只能有一个实例)")?;
+                Ok(Self {
+                    camera,
+                    send_frame,
+                    delete_camera,
+                    bgr: Vec::new(),
+                    _library: library,
+                })
+            }
+        }
+    }
+
+    impl VirtualCamera for Softcam {
+        fn send(&mut self, rgba: &[u8]) -> Result<()> {
+            rgba_to_bgr(rgba, &mut self.bgr);
+            // SAFETY: 缓冲为创建时尺寸的 BGR24 画面, scSendFrame 返回前复制完毕
+            unsafe { (self.send_frame)(self.camera.as_ptr(), self.bgr.as_ptr().cast()) };
+            Ok(())
+        }
+    }
+
+    impl Drop for Softcam {
+        fn drop(&mut self) {
+            // SAFETY: 摄像头由 scCreateCamera 创建且只删除一次, 库在其后卸载
+            unsafe { (self.delete_camera)(self.camera.as_ptr()) }
+        }
+    }
+}
+
+/// 按平台打开虚拟摄像头
+fn open_camera(config: &VcamConfig) -> Result<Box<dyn VirtualCamera>> {
+    let (width, height) = config.output_size();
+    #[cfg(target_os = "linux")]
+    {
+        Ok(Box::new(v4l2::V4l2Loopback::open(
+            &config.device,
+            width,
+            height,
+        )?))
+    }
+    #[cfg(windows)]
+    {
+        Ok(Box::new(softcam::Softcam::open(
+            &config.device,
+            width,
+            height,
+            config.fps,
+        )?))
+    }
+    #[cfg(not(any(target_os = "linux", windows)))]
+    {
+        let _ = (width, height);
+        anyhow::bail!("当前平台不支持虚拟摄像头 (仅 Linux v4l2loopback 与 Windows softcam)")
+    }
+}
+
+/// 虚拟摄像头输出 (订阅保持期间运行)
+pub struct VcamOutput {
+    _subs: Vec<Subscription>,
+}
+
+impl VcamOutput {
+    /// 打开虚拟摄像头, 订阅逻辑流的帧与检测结果, 标注、缩放与发送在后台线程完成
+    pub fn start(config: VcamConfig) -> Result<Self> {
+        let mut camera = open_camera(&config)?;
+        let size = config.output_size();
+        println!(
+            "🎥 虚拟摄像头: {} ({}x{}, 逻辑流 {})",
+            config.device, size.0, size.1, config.view
+        );
+
+        // 只保留一帧待发: 发送线程忙时新帧直接丢弃
+        let view = config.view;
+        let (tx, rx) = crossbeam_channel::bounded::<(DecodedFrame, OutputResults)>(1);
+        let latest: Arc<Mutex<OutputResults>> = Arc::default();
+        let results = Arc::clone(&latest);
+        let result_sub = xbus::subscribe::<DetectionResult, _>(move |result| {
+            if result.view == view {
+                *results.lock().unwrap() = OutputResults::from_result(result);
+            }
+        });
+        let frame_sub = xbus::subscribe::<DecodedFrame, _>(move |frame| {
+            if frame.view == view {
+                let _ = tx.try_send((frame.clone(), latest.lock().unwrap().clone()));
+            }
+        });
+
+        std::thread::Builder::new()
+            .name("virtual-camera".to_string())
+            .spawn(move || {
+                let mut warned = false;
+                for (frame, results) in rx {
+                    let Some(rgba) = annotate_rgba(&frame, &results)
+                        .and_then(|rgba| fit(rgba, (frame.width, frame.height), size))
+                    else {
+                        continue;
+                    };
+                    match camera.send(&rgba) {
+                        Ok(()) => warned = false,
+                        Err(e) if !warned => {
+                            eprintln!("⚠️ 虚拟摄像头跳过: {:#}", e);
+                            warned = true;
+                        }
+                        Err(_) => {}
+                    }
+                }
+                println!("🎥 虚拟摄像头线程退出");
+            })
+            .context("虚拟摄像头线程启动失败")?;
+
+        Ok(Self {
+            _subs: vec![frame_sub, result_sub],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 保持比例居中缩放; 输出尺寸取偶数
+    #[test]
+    fn test_letterbox() {
+        assert_eq!(letterbox((1920, 1080), (1280, 720)), (0, 0, 1280, 720));
+        assert_eq!(letterbox((640, 480), (1280, 720)), (160, 0, 960, 720));
+        assert_eq!(letterbox((1000, 1000), (1280, 720)), (280, 0, 720, 720));
+        let config = VcamConfig {
+            width: 641,
+            height: 1,
+            ..Default::default()
+        };
+        assert_eq!(config.output_size(), (640, 2));
+    }
+
+    /// BT.601 有限范围: 白/黑/红, 两个像素共用色度
+    #[test]
+    fn test_rgba_to_yuyv() {
+        let mut out = Vec::new();
+        rgba_to_yuyv(&[255, 255, 255, 255, 0, 0, 0, 255], 2, 1, &mut out);
+        assert_eq!(out, [235, 128, 16, 128]);
+        rgba_to_yuyv(&[255, 0, 0, 255, 255, 0, 0, 255], 2, 1, &mut out);
+        assert_eq!(out, [82, 90, 82, 240]);
+
+        rgba_to_bgr(&[1, 2, 3, 255, 4, 5, 6, 255], &mut out);
+        assert_eq!(out, [3, 2, 1, 6, 5, 4]);
+    }
+
+    /// ioctl 编号与内核头文件一致 (64 位: struct v4l2_format 为 208 字节)
+    #[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+    #[test]
+    fn test_vidioc_s_fmt() {
+        assert_eq!(v4l2::VIDIOC_S_FMT, 0xC0D0_5605);
+    }
+}
@@ -0,0 +1,503 @@
+//! 看门狗与服务模式 (Watchdog / service integration)
+//!
+//! `--service` 启动后台看门狗, 便于在 systemd / Windows 服务下无人值守运行:
+//! - 心跳: 渲染主循环每帧、解码 (DecodedFrame)、检测 (DetectionResult) 各自记录最近一次活动时间
+//! - 解码卡死: 有输入源但超过 `decode_stall_secs` 没有新帧时重启解码器 (重新切换到当前输入源),
+//!   连续 `max_decoder_restarts` 次仍无帧则以 [`StallExit::Decode`] 退出
+//! - 检测卡死: 有帧输入但超过 `detect_stall_secs` 没有检测结果. Rust 线程无法从外部终止,
+//!   以 [`StallExit::Detect`] 退出, 由服务管理器重启进程
+//! - 渲染卡死: 主循环超过 `render_stall_secs` 没有心跳时停止向服务管理器发送心跳
+//!   (systemd `WatchdogSec` 到期后重启), 超过两倍时以 [`StallExit::Render`] 退出
+//! - 服务心跳: Linux 下 sd_notify (`READY=1` / `WATCHDOG=1` / `STATUS=` / `STOPPING=1`),
+//!   Windows 下向服务控制管理器 (SCM) 报告运行/停止状态, 并响应停止请求
+//!
+//! 配置文件 (watchdog.json), 不存在时使用默认值:
+//! ```json
+//! { "decode_stall_secs": 15, "detect_stall_secs": 120, "render_stall_secs": 10, "max_decoder_restarts": 3 }
+//! ```
+
+use std::fs;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::detection::detector::DetectionResult;
+use crate::detection::types::DecodedFrame;
+use crate::input::decoder::DecoderPreference;
+use crate::input::{active_source, active_source_key, switch_decoder_source};
+use crate::xbus;
+
+/// 默认配置文件路径
+pub const WATCHDOG_CONFIG_PATH: &str = "watchdog.json";
+
+/// Windows 服务名 (sc create 时使用)
+pub const SERVICE_NAME: &str = "sentinel";
+
+/// 检查间隔
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 心跳时间基准 (心跳记为相对基准的毫秒数, 0 表示尚无心跳)
+static EPOCH: OnceLock<Instant> = OnceLock::new();
+static RENDER_BEAT: AtomicU64 = AtomicU64::new(0);
+static DECODE_BEAT: AtomicU64 = AtomicU64::new(0);
+static DETECT_BEAT: AtomicU64 = AtomicU64::new(0);
+
+/// 服务管理器请求停止 (Windows SCM 停止/关机)
+static STOP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// 卡死退出码 (供服务管理器的重启策略区分, 0 为正常退出)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StallExit {
+    Decode = 10, // 解码器多次重启后仍无帧 (通常是摄像头/网络问题)
+    Detect = 11, // 检测线程卡死
+    Render = 12, // 渲染主循环卡死
+}
+
+impl StallExit {
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            StallExit::Decode => "解码器无帧",
+            StallExit::Detect => "检测线程卡死",
+            StallExit::Render => "渲染主循环卡死",
+        }
+    }
+}
+
+/// 看门狗配置 (watchdog.json)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchdogConfig {
+    pub decode_stall_secs: u64,
+    pub detect_stall_secs: u64, // 需大于加载/构建模型 (如 TensorRT 引擎) 的时间
+    pub render_stall_secs: u64,
+    pub max_decoder_restarts: u32,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            decode_stall_secs: 15,
+            detect_stall_secs: 120,
+            render_stall_secs: 10,
+            max_decoder_restarts: 3,
+        }
+    }
+}
+
+impl WatchdogConfig {
+    /// 从文件加载配置, 文件不存在时使用默认值
+    pub fn load(path: &str) -> Self {
+        match fs::read_to_string(path) {
+            Ok(json) => match serde_json::from_str(&json) {
+                Ok(config) => {
+                    println!("✅ 看门狗配置已从 {} 加载", path);
+                    config
+                }
+                Err(e) => {
+                    eprintln!("⚠️  看门狗配置解析失败: {}, 使用默认值", e);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+}
+
+/// 被监视的组件
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Component {
+    Render,
+    Decode,
+    Detect,
+}
+
+fn epoch() -> Instant {
+    *EPOCH.get_or_init(Instant::now)
+}
+
+fn slot(component: Component) -> &'static AtomicU64 {
+    match component {
+        Component::Render => &RENDER_BEAT,
+        Component::Decode => &DECODE_BEAT,
+        Component::Detect => &DETECT_BEAT,
+    }
+}
+
+/// 记录组件心跳
+pub fn beat(component: Component) {
+    let ms = epoch().elapsed().as_millis() as u64;
+    slot(component).store(ms.max(1), Ordering::Relaxed);
+}
+
+/// 组件最近一次心跳时间
+fn last_beat(component: Component) -> Option<Instant> {
+    match slot(component).load(Ordering::Relaxed) {
+        0 => None,
+        ms => Some(epoch() + Duration::from_millis(ms)),
+    }
+}
+
+/// 服务管理器是否请求停止 (主循环检查后退出)
+pub fn stop_requested() -> bool {
+    STOP_REQUESTED.load(Ordering::Relaxed)
+}
+
+/// 看门狗本轮检查后要执行的动作
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WatchdogAction {
+    Ping,           // 向服务管理器发送心跳
+    RestartDecoder, // 重新切换到当前输入源
+    Exit(StallExit),
+}
+
+/// 卡死判定 (与时钟和全局状态无关, 便于测试)
+pub struct Supervisor {
+    config: WatchdogConfig,
+    started: Instant,
+    source: Option<String>,        // 当前输入源 (切换后重新计时)
+    decode_since: Instant,         // 输入源切换/解码器重启的时间
+    decoder_restarts: u32,         // 没有新帧的连续重启次数
+    frames_since: Option<Instant>, // 本次帧流开始的时间 (检测计时起点)
+}
+
+impl Supervisor {
+    pub fn new(config: WatchdogConfig, now: Instant) -> Self {
+        Self {
+            config,
+            started: now,
+            source: None,
+            decode_since: now,
+            decoder_restarts: 0,
+            frames_since: None,
+        }
+    }
+
+    /// 按各组件最近一次心跳判定本轮动作
+    pub fn check(
+        &mut self,
+        now: Instant,
+        source: Option<String>,
+        render: Option<Instant>,
+        decode: Option<Instant>,
+        detect: Option<Instant>,
+    ) -> Vec<WatchdogAction> {
+        let secs = Duration::from_secs;
+        let mut actions = Vec::new();
+
+        // 渲染: 首帧前按启动时间计
+        let render_age = now - render.unwrap_or(self.started);
+        if render_age > secs(self.config.render_stall_secs * 2) {
+            return vec![WatchdogAction::Exit(StallExit::Render)];
+        }
+        if render_age <= secs(self.config.render_stall_secs) {
+            actions.push(WatchdogAction::Ping);
+        }
+
+        if source != self.source {
+            self.source = source;
+            self.decode_since = now;
+            self.decoder_restarts = 0;
+            self.frames_since = None;
+        }
+        if self.source.is_none() {
+            return actions;
+        }
+
+        // 解码: 输入源切换或重启之后的新帧才算数
+        let fresh_frame = decode.filter(|t| *t >= self.decode_since);
+        if fresh_frame.is_some() {
+            self.decoder_restarts = 0;
+        }
+        let decode_age = now - fresh_frame.unwrap_or(self.decode_since);
+        if decode_age > secs(self.config.decode_stall_secs) {
+            self.frames_since = None;
+            if self.decoder_restarts >= self.config.max_decoder_restarts {
+                return vec![WatchdogAction::Exit(StallExit::Decode)];
+            }
+            self.decoder_restarts += 1;
+            self.decode_since = now;
+            actions.push(WatchdogAction::RestartDecoder);
+            return actions;
+        }
+        if fresh_frame.is_none() {
+            return actions;
+        }
+
+        // 检测: 只在有帧输入时计时, 起点为帧流开始 (留出加载模型的时间)
+        let frames_since = *self.frames_since.get_or_insert(now);
+        let detect_from = detect.map_or(frames_since, |t| t.max(frames_since));
+        if now - detect_from > secs(self.config.detect_stall_secs) {
+            return vec![WatchdogAction::Exit(StallExit::Detect)];
+        }
+        actions
+    }
+}
+
+/// 看门狗 (心跳订阅须在主循环期间保持)
+pub struct Watchdog {
+    _decode_sub: xbus::Subscription,
+    _detect_sub: xbus::Subscription,
+}
+
+impl Watchdog {
+    /// 启动看门狗线程, 并通知服务管理器启动完成
+    pub fn start(config: WatchdogConfig) -> Self {
+        let decode_sub = xbus::subscribe::<DecodedFrame, _>(|_| beat(Component::Decode));
+        let detect_sub = xbus::subscribe::<DetectionResult, _>(|_| beat(Component::Detect));
+        service::start();
+
+        println!(
+            "🐕 看门狗已启动: 解码 {}s / 检测 {}s / 渲染 {}s 无心跳视为卡死",
+            config.decode_stall_secs, config.detect_stall_secs, config.render_stall_secs
+        );
+        let mut supervisor = Supervisor::new(config, Instant::now());
+        std::thread::Builder::new()
+            .name("watchdog".to_string())
+            .spawn(move || loop {
+                std::thread::sleep(TICK_INTERVAL);
+                let actions = supervisor.check(
+                    Instant::now(),
+                    active_source_key(),
+                    last_beat(Component::Render),
+                    last_beat(Component::Decode),
+                    last_beat(Component::Detect),
+                );
+                for action in actions {
+                    match action {
+                        WatchdogAction::Ping => service::ping(),
+                        WatchdogAction::RestartDecoder => {
+                            if let Some(source) = active_source() {
+                                eprintln!("🐕 解码器无新帧, 重启输入源: {}", source.key());
+                                switch_decoder_source(source, DecoderPreference::preferred());
+                            }
+                        }
+                        WatchdogAction::Exit(stall) => exit(stall),
+                    }
+                }
+            })
+            .expect("看门狗线程启动失败");
+
+        Self {
+            _decode_sub: decode_sub,
+            _detect_sub: detect_sub,
+        }
+    }
+}
+
+/// 卡死时通知服务管理器并以对应退出码退出
+fn exit(stall: StallExit) -> ! {
+    let message = format!("{} (退出码 {})", stall.label(), stall.code());
+    eprintln!("🛑 看门狗: {}, 退出等待服务管理器重启", message);
+    service::stopping(&message, stall.code());
+    std::process::exit(stall.code());
+}
+
+/// 正常退出 (服务管理器请求停止)
+pub fn shutdown() -> ! {
+    println!("👋 服务停止");
+    service::stopping("服务停止", 0);
+    std::process::exit(0);
+}
+
+/// systemd 通知 (未由 systemd 启动时 NOTIFY_SOCKET 不存在, 通知为空操作)
+#[cfg(target_os = "linux")]
+mod service {
+    use sd_notify::NotifyState;
+
+    pub fn start() {
+        let _ = sd_notify::notify(false, &[NotifyState::Ready, NotifyState::Status("运行中")]);
+    }
+
+    pub fn ping() {
+        let _ = sd_notify::notify(false, &[NotifyState::Watchdog]);
+    }
+
+    pub fn stopping(message: &str, _code: i32) {
+        let _ = sd_notify::notify(
+            false,
+            &[NotifyState::Stopping, NotifyState::Status(message)],
+        );
+    }
+}
+
+/// Windows 服务控制管理器 (不是由 SCM 启动时调度线程立即失败, 按普通进程运行)
+#[cfg(windows)]
+mod service {
+    use std::ffi::OsString;
+    use std::sync::atomic::Ordering;
+    use std::sync::OnceLock;
+    use std::time::Duration;
+
+    use windows_service::service::{
+        ServiceControl, ServiceControlAccept, ServiceExitCode, ServiceState, ServiceStatus,
+        ServiceType,
+    };
+    use windows_service::service_control_handler::{
+        self, ServiceControlHandlerResult, ServiceStatusHandle,
+    };
+    use windows_service::{define_windows_service, service_dispatcher};
+
+    use super::{SERVICE_NAME, STOP_REQUESTED};
+
+    static HANDLE: OnceLock<ServiceStatusHandle> = OnceLock::new();
+
+    define_windows_service!(ffi_service_main, service_main);
+
+    fn service_main(_arguments: Vec<OsString>) {
+        let handler = |control| match control {
+            ServiceControl::Stop | ServiceControl::Shutdown => {
+                STOP_REQUESTED.store(true, Ordering::Relaxed);
+                ServiceControlHandlerResult::NoError
+            }
+            ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+            _ => ServiceControlHandlerResult::NotImplemented,
+        };
+        match service_control_handler::register(SERVICE_NAME, handler) {
+            Ok(handle) => {
+                let _ = HANDLE.set(handle);
+                report(ServiceState::Running, 0);
+            }
+            Err(e) => return eprintln!("⚠️ 服务控制处理程序注册失败: {}", e),
+        }
+        // 服务运行期间 ServiceMain 不能返回; 停止请求由主循环处理后退出进程
+        while !STOP_REQUESTED.load(Ordering::Relaxed) {
+            std::thread::sleep(Duration::from_millis(200));
+        }
+    }
+
+    fn report(state: ServiceState, code: i32) {
+        let Some(handle) = HANDLE.get() else {
+            return;
+        };
+        let controls_accepted = if state == ServiceState::Running {
+            ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN
+        } else {
+            ServiceControlAccept::empty()
+        };
+        let _ = handle.set_service_status(ServiceStatus {
+            service_type: ServiceType::OWN_PROCESS,
+            current_state: state,
+            controls_accepted,
+            exit_code: match code {
+                0 => ServiceExitCode::NO_ERROR,
+                code => ServiceExitCode::ServiceSpecific(code as u32),
+            },
+            checkpoint: 0,
+            wait_hint: Duration::ZERO,
+            process_id: None,
+        });
+    }
+
+    pub fn start() {
+        // 调度器阻塞调用线程直到服务停止, 主线程留给渲染循环
+        std::thread::spawn(|| {
+            if service_dispatcher::start(SERVICE_NAME, ffi_service_main).is_err() {
+                println!("ℹ️ 未由服务控制管理器启动, 按普通进程运行");
+            }
+        });
+    }
+
+    /// SCM 没有心跳接口, 运行状态在注册时报告一次
+    pub fn ping() {}
+
+    pub fn stopping(_message: &str, code: i32) {
+        report(ServiceState::Stopped, code);
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+mod service {
+    pub fn start() {}
+    pub fn ping() {}
+    pub fn stopping(_message: &str, _code: i32) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(start: Instant, secs: u64) -> Instant {
+        start + Duration::from_secs(secs)
+    }
+
+    /// 解码无帧时先重启解码器, 多次重启仍无帧后退出; 有新帧时重启计数清零
+    #[test]
+    fn test_decode_stall() {
+        let t0 = Instant::now();
+        let config = WatchdogConfig {
+            max_decoder_restarts: 2,
+            ..WatchdogConfig::default()
+        };
+        let mut sup = Supervisor::new(config, t0);
+        let src = || Some("rtsp:cam".to_string());
+        let check = |sup: &mut Supervisor, secs, decode: Option<u64>| {
+            let now = at(t0, secs);
+            sup.check(now, src(), Some(now), decode.map(|s| at(t0, s)), None)
+        };
+
+        // 首次看到输入源时开始计时
+        assert_eq!(check(&mut sup, 0, None), vec![WatchdogAction::Ping]);
+        assert_eq!(check(&mut sup, 10, None), vec![WatchdogAction::Ping]);
+        assert!(check(&mut sup, 16, None).contains(&WatchdogAction::RestartDecoder));
+        // 重启前的旧帧不算数
+        assert!(check(&mut sup, 32, Some(5)).contains(&WatchdogAction::RestartDecoder));
+        assert_eq!(
+            check(&mut sup, 48, None),
+            vec![WatchdogAction::Exit(StallExit::Decode)]
+        );
+
+        // 重启后恢复出帧, 计数清零
+        let mut sup = Supervisor::new(WatchdogConfig::default(), t0);
+        check(&mut sup, 0, None);
+        assert!(check(&mut sup, 16, None).contains(&WatchdogAction::RestartDecoder));
+        assert_eq!(check(&mut sup, 20, Some(19)), vec![WatchdogAction::Ping]);
+        assert_eq!(sup.decoder_restarts, 0);
+
+        // 没有输入源时不检查解码
+        let mut sup = Supervisor::new(WatchdogConfig::default(), t0);
+        let now = at(t0, 100);
+        assert_eq!(
+            sup.check(now, None, Some(now), None, None),
+            vec![WatchdogAction::Ping]
+        );
+    }
+
+    /// 检测只在有帧时计时 (留出加载模型时间); 渲染卡死先停止心跳再退出
+    #[test]
+    fn test_detect_and_render_stall() {
+        let t0 = Instant::now();
+        let mut sup = Supervisor::new(WatchdogConfig::default(), t0);
+        let src = Some("camera:usb".to_string());
+        let mut check = |secs, detect: Option<u64>| {
+            let now = at(t0, secs);
+            sup.check(
+                now,
+                src.clone(),
+                Some(now),
+                Some(now),
+                detect.map(|s| at(t0, s)),
+            )
+        };
+        assert_eq!(check(5, None), vec![WatchdogAction::Ping]);
+        assert_eq!(check(100, None), vec![WatchdogAction::Ping]);
+        assert_eq!(check(124, Some(120)), vec![WatchdogAction::Ping]);
+        assert_eq!(
+            check(245, Some(120)),
+            vec![WatchdogAction::Exit(StallExit::Detect)]
+        );
+
+        let mut sup = Supervisor::new(WatchdogConfig::default(), t0);
+        let render = Some(at(t0, 5));
+        assert_eq!(sup.check(at(t0, 16), None, render, None, None), vec![]);
+        assert_eq!(
+            sup.check(at(t0, 26), None, render, None, None),
+            vec![WatchdogAction::Exit(StallExit::Render)]
+        );
+    }
+}
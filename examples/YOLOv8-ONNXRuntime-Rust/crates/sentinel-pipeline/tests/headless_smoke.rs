@@ -0,0 +1,212 @@
+// Ultralytics 🚀 AGPL-3.0 License - https://ultralytics.com/license
+//
+//! 公开 API 端到端冒烟测试 (下游使用者的接口契约)
+//!
+//! 只经 `sentinel_pipeline` 导出的路径驱动一条无界面管线, 不需要 ONNX 模型、摄像头、FFmpeg 与窗口:
+//! 渲染人形走过的画面 → 模拟模型 (按真值框生成的 `mock:` 夹具) → 检测线程 → ByteTrack →
+//! xbus 检测结果 → 共享内存输出, 同时检查延迟指标与事件总线统计.
+//! 这些公开类型或行为的不兼容改动会让本测试编译失败或断言失败
+//!
+//! 运行: `cargo test -p sentinel-pipeline --test headless_smoke`
+//! (没有 ONNX Runtime 运行库时加 `--no-default-features --features load-dynamic`, 模拟模型不加载运行库)
+
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+use sentinel_pipeline::detection::detector::DetectionResult;
+use sentinel_pipeline::detection::trace::{FrameTrace, LatencyStage, LatencyStats};
+use sentinel_pipeline::detection::tracker::compute_iou;
+use sentinel_pipeline::detection::types::{control_receiver, BBox, ControlMessage, DecodedFrame};
+use sentinel_pipeline::detection::Detector;
+use sentinel_pipeline::models::{MockDetection, MockFixture, MOCK_MODEL_PREFIX};
+use sentinel_pipeline::shm_output::{OutputFrame, ResultsFormat, ShmPublisher, ShmReader};
+use sentinel_pipeline::xbus;
+
+/// 测试使用的逻辑流编号 (避开默认的 0 号流)
+const VIEW: u32 = 7;
+/// 推入的合成帧数
+const FRAMES: u64 = 30;
+/// 推理输入尺寸
+const INF_SIZE: u32 = 320;
+/// 新轨迹经跟踪器激活与显示平滑确认前可能没有输出的帧数
+const WARMUP_FRAMES: u64 = 5;
+/// 等待检测线程与输出端的超时
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// 画面尺寸
+const WIDTH: u32 = 320;
+const HEIGHT: u32 = 240;
+
+/// 第 index 帧的真值框: 一个 30×80 的人形从 (60, 140) 起每帧向右走 4 像素
+fn truth(index: u64) -> BBox {
+    let (cx, cy) = (60.0 + index as f32 * 4.0, 140.0);
+    BBox {
+        x1: cx - 15.0,
+        y1: cy - 40.0,
+        x2: cx + 15.0,
+        y2: cy + 40.0,
+        confidence: 1.0,
+        class_id: 0,
+    }
+}
+
+/// 按真值生成模拟模型夹具 (归一化坐标) 并写入临时文件, 返回 `mock:` 模型路径与夹具路径
+fn mock_model() -> (String, PathBuf) {
+    let (w, h) = (WIDTH as f32, HEIGHT as f32);
+    let fixture = MockFixture {
+        frames: (0..FRAMES)
+            .map(|frame| {
+                let t = truth(frame);
+                vec![MockDetection {
+                    bbox: [t.x1 / w, t.y1 / h, t.x2 / w, t.y2 / h],
+                    class_id: t.class_id as usize,
+                    confidence: 0.9,
+                }]
+            })
+            .collect(),
+        repeat: false,
+        normalized: true,
+    };
+    let path = std::env::temp_dir().join(format!("headless_smoke_{}.json", std::process::id()));
+    std::fs::write(&path, serde_json::to_string(&fixture).unwrap()).unwrap();
+    (format!("{}{}", MOCK_MODEL_PREFIX, path.display()), path)
+}
+
+/// 第 index 帧的解码帧 (RGBA, 无 YUV 平面): 深灰背景上的真值框填充为红色
+fn frame(index: u64) -> DecodedFrame {
+    let t = truth(index);
+    let mut rgba = [40, 40, 40, 255].repeat((WIDTH * HEIGHT) as usize);
+    for y in t.y1 as usize..t.y2 as usize {
+        for x in t.x1 as usize..t.x2 as usize {
+            let i = (y * WIDTH as usize + x) * 4;
+            rgba[i..i + 4].copy_from_slice(&[220, 80, 60, 255]);
+        }
+    }
+    DecodedFrame {
+        rgba_data: Arc::new(rgba),
+        width: WIDTH,
+        height: HEIGHT,
+        decode_fps: 25.0,
+        decoder_name: "headless_smoke".to_string(),
+        yuv: None,
+        keyframe: true,
+        trace: FrameTrace::new(),
+        view: VIEW,
+        generation: 0,
+        yuv16: None,
+        #[cfg(feature = "cuda")]
+        device: None,
+    }
+}
+
+/// 轮询直到返回 Some, 超时返回 None
+fn wait_for<T>(mut poll: impl FnMut() -> Option<T>) -> Option<T> {
+    let deadline = Instant::now() + TIMEOUT;
+    while Instant::now() < deadline {
+        if let Some(value) = poll() {
+            return Some(value);
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    None
+}
+
+/// 事件总线上某类事件 (按类型名后缀匹配) 的订阅数与累计发布数
+fn topic(suffix: &str) -> (usize, u64) {
+    xbus::snapshot()
+        .topics
+        .iter()
+        .find(|t| t.event_type.ends_with(suffix))
+        .map_or((0, 0), |t| (t.subscribers.len(), t.posted))
+}
+
+/// 渲染帧经模拟模型、检测线程与跟踪器后, 检测结果、共享内存输出与延迟指标都与真值一致
+#[test]
+fn test_headless_pipeline() {
+    let (model, fixture_path) = mock_model();
+    let shm_path = std::env::temp_dir().join(format!("headless_smoke_{}.shm", std::process::id()));
+
+    // 输出端: 检测结果转入通道, 同时写共享内存
+    let (tx, rx) = mpsc::channel();
+    let _results = xbus::subscribe::<DetectionResult, _>(move |result| {
+        if result.view == VIEW {
+            let _ = tx.send(result.clone());
+        }
+    });
+    let _shm = ShmPublisher::start(&shm_path, VIEW, ResultsFormat::Json).unwrap();
+
+    // 检测线程: 先订阅控制消息再启动, 结束时经控制消息退出
+    let (control_sub, config_rx) = control_receiver(VIEW);
+    let worker = std::thread::spawn(move || {
+        let _control_sub = control_sub;
+        let mut detector = Detector::new(model, INF_SIZE, "bytetrack".to_string(), false);
+        detector.set_view(VIEW);
+        detector.set_config_receiver(config_rx);
+        detector.run();
+    });
+    // 共享内存输出与检测线程都订阅了解码帧后才开始推帧
+    assert!(
+        wait_for(|| (topic("types::DecodedFrame").0 >= 2).then_some(())).is_some(),
+        "检测线程未订阅解码帧"
+    );
+
+    // 逐帧推入并等待结果, 模拟模型的帧序号与推入的帧一一对应
+    let mut latency = LatencyStats::new(FRAMES as usize);
+    let mut track_ids = Vec::new();
+    for index in 0..FRAMES {
+        xbus::post(frame(index));
+        let result = rx.recv_timeout(TIMEOUT).expect("检测结果超时");
+        assert!(result.tracked);
+        assert!(result.trace.stage_ms(LatencyStage::Inference).is_some());
+        latency.record(&result.trace);
+
+        let truth = truth(index);
+        match result.bboxes.as_slice() {
+            [tracked] => {
+                let iou = compute_iou(tracked, &truth);
+                assert!(iou > 0.5, "第 {} 帧: {:?} / {:?}", index, tracked, truth);
+                track_ids.push(tracked.class_id);
+            }
+            [] => assert!(index < WARMUP_FRAMES, "第 {} 帧没有输出轨迹", index),
+            boxes => panic!("第 {} 帧输出了 {} 个框", index, boxes.len()),
+        }
+    }
+    // 启用跟踪器时 class_id 为轨迹ID, 同一个人始终是同一条轨迹
+    assert!(track_ids.len() as u64 >= FRAMES - WARMUP_FRAMES);
+    assert!(
+        track_ids.iter().all(|&id| id == track_ids[0]),
+        "轨迹ID不稳定: {:?}",
+        track_ids
+    );
+
+    // 指标: 分阶段延迟 (Prometheus 文本) 与事件总线发布计数
+    let metrics = latency.to_prometheus();
+    assert!(
+        metrics.contains("sentinel_frame_latency_ms{stage=\"inference\",stat=\"p95\"}"),
+        "{}",
+        metrics
+    );
+    assert!(metrics.contains(&format!("sentinel_frame_latency_samples {}", FRAMES)));
+    assert!(topic("detector::DetectionResult").1 >= FRAMES);
+
+    // 共享内存输出端: 最新一帧带标注画面与跟踪结果
+    let reader = ShmReader::open(&shm_path).unwrap();
+    let output: OutputFrame = wait_for(|| {
+        reader
+            .read_after(0)
+            .filter(|frame| !frame.results.boxes.is_empty())
+    })
+    .expect("共享内存输出超时");
+    assert_eq!(
+        (output.width, output.height, output.view),
+        (WIDTH, HEIGHT, VIEW)
+    );
+    assert_eq!(output.rgba.len(), (WIDTH * HEIGHT * 4) as usize);
+    assert!(output.results.tracked);
+    assert_eq!(output.results.boxes[0].class_id, track_ids[0]);
+
+    ControlMessage::Shutdown.post_to(VIEW);
+    worker.join().unwrap();
+    let _ = std::fs::remove_file(fixture_path);
+    let _ = std::fs::remove_file(shm_path);
+}
@@ -0,0 +1,56 @@
+/// 检测输入缩放性能基准测试
+/// 比较 nearest / bilinear / lanczos 预设把整帧缩放到推理输入的耗时
+use yolov8_rs::utils::resize::benchmark;
+
+/// 渐变 + 棋盘格的 RGBA 测试帧
+fn create_test_frame(width: usize, height: usize) -> Vec<u8> {
+    let mut frame = vec![255u8; width * height * 4];
+    for y in 0..height {
+        for x in 0..width {
+            let idx = (y * width + x) * 4;
+            let checker = if (x / 16 + y / 16) % 2 == 0 { 0 } else { 64 };
+            frame[idx] = ((x * 255) / width) as u8;
+            frame[idx + 1] = ((y * 255) / height) as u8;
+            frame[idx + 2] = checker;
+        }
+    }
+    frame
+}
+
+fn main() -> anyhow::Result<()> {
+    println!("=== 检测输入缩放性能基准测试 ===\n");
+
+    let test_sizes = [
+        (1280, 720, "HD"),
+        (1920, 1080, "Full HD"),
+        (3840, 2160, "4K"),
+    ];
+    let inputs = [320u32, 640, 960];
+
+    for (width, height, name) in test_sizes {
+        let frame = create_test_frame(width, height);
+        let iterations = if width * height > 4_000_000 { 10 } else { 30 };
+        for size in inputs {
+            println!("{} ({}x{}) → {}x{}:", name, width, height, size, size);
+            let timings = benchmark(
+                &frame,
+                width as u32,
+                height as u32,
+                (size, size),
+                iterations,
+            )?;
+            let nearest = timings[0].mean_ms;
+            for t in &timings {
+                println!(
+                    "  {:<10} 平均 {:>7.3}ms  最快 {:>7.3}ms  ({:.1}x nearest)",
+                    t.quality.label(),
+                    t.mean_ms,
+                    t.min_ms,
+                    t.mean_ms / nearest.max(1e-9)
+                );
+            }
+        }
+        println!();
+    }
+    Ok(())
+}
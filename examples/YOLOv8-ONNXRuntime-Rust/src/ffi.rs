@@ -0,0 +1,301 @@
+//! C FFI: 以共享库形式嵌入检测管线 (C++ VMS 等宿主进程)
+//!
+//! 每条管线在独立线程中运行一个 [`Detector`], 使用专属的逻辑流编号收发 xbus 消息,
+//! 同一进程内的多条管线互不干扰. 宿主通过 `yolo_push_frame` 推入 RGBA 帧 (拷贝后异步处理,
+//! 检测队列满时丢帧), 再用 `yolo_poll_results` 非阻塞地取回每帧的检测/跟踪结果.
+//!
+//! 头文件由 cbindgen 在 `cargo build --features ffi` 时生成到 `include/yolov8_rs.h`
+
+use std::collections::VecDeque;
+use std::ffi::{c_char, CStr};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+use crate::detection::detector::{DetectionResult, Detector};
+use crate::detection::trace::FrameTrace;
+use crate::detection::types::{control_receiver, ControlMessage, DecodedFrame};
+use crate::xbus::{self, Subscription};
+
+/// 管线逻辑流编号起点, 与解码器 (0) 和鱼眼虚拟视图 (1..=N) 区分
+const FFI_VIEW_BASE: u32 = 0x1000_0000;
+
+/// 未取走的结果上限, 超过后丢弃最旧的结果
+const MAX_PENDING_RESULTS: usize = 32;
+
+/// 等待结果的已推入帧上限 (检测队列满时被丢弃的帧不会有结果)
+const MAX_INFLIGHT_FRAMES: usize = 64;
+
+static NEXT_VIEW: AtomicU32 = AtomicU32::new(FFI_VIEW_BASE);
+
+/// 单个检测目标 (帧像素坐标)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct YoloDetection {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+    pub confidence: f32,
+    /// 启用跟踪时为轨迹ID, 否则为类别ID
+    pub track_id: u32,
+}
+
+/// 一帧结果的概要
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct YoloFrameResult {
+    /// `yolo_push_frame` 返回的帧序号
+    pub frame_id: u64,
+    /// 本帧目标总数 (可能大于写入数量)
+    pub count: u32,
+    /// 实际写入输出数组的目标数量
+    pub written: u32,
+    /// 推理耗时 (毫秒)
+    pub inference_ms: f64,
+}
+
+/// 检测管线 (对 C 侧不透明)
+pub struct YoloPipeline {
+    view: u32,
+    worker: Option<JoinHandle<()>>,
+    results: Arc<Mutex<VecDeque<DetectionResult>>>,
+    // 已推入、尚未出结果的帧: (采集时间戳, 帧序号), 按时间戳对应结果
+    inflight: VecDeque<(Instant, u64)>,
+    next_frame_id: u64,
+    _result_sub: Subscription,
+}
+
+/// 读取 C 字符串, 空指针或非 UTF-8 时返回 None
+unsafe fn c_str<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        return None;
+    }
+    CStr::from_ptr(ptr).to_str().ok()
+}
+
+/// 创建检测管线
+///
+/// - `model_path`: ONNX 检测模型路径
+/// - `tracker`: `"bytetrack"` / `"deepsort"`, 空指针或其他值表示不跟踪
+/// - `inf_size`: 推理输入尺寸, 0 表示默认 640
+///
+/// 失败时返回空指针. 模型在收到第一帧时加载
+///
+/// # Safety
+/// `model_path` 必须是有效的 NUL 结尾字符串, `tracker` 为空指针或有效字符串
+#[no_mangle]
+pub unsafe extern "C" fn yolo_create_pipeline(
+    model_path: *const c_char,
+    tracker: *const c_char,
+    inf_size: u32,
+) -> *mut YoloPipeline {
+    let Some(model_path) = c_str(model_path) else {
+        eprintln!("❌ yolo_create_pipeline: 模型路径无效");
+        return std::ptr::null_mut();
+    };
+    let tracker = c_str(tracker).unwrap_or("none").to_string();
+    let inf_size = if inf_size == 0 { 640 } else { inf_size };
+    let view = NEXT_VIEW.fetch_add(1, Ordering::Relaxed);
+
+    let results = Arc::new(Mutex::new(VecDeque::new()));
+    let queue = Arc::clone(&results);
+    let result_sub = xbus::subscribe::<DetectionResult, _>(move |result| {
+        if result.view != view {
+            return;
+        }
+        let mut queue = queue.lock().unwrap();
+        if queue.len() >= MAX_PENDING_RESULTS {
+            queue.pop_front();
+        }
+        queue.push_back(result.clone());
+    });
+
+    // 控制消息经 xbus 按逻辑流编号投递, 先订阅再启动线程
+    let (control_sub, config_rx) = control_receiver(view);
+    let model = model_path.to_string();
+    let worker = match std::thread::Builder::new()
+        .name(format!("ffi-detector-{}", view - FFI_VIEW_BASE))
+        .spawn(move || {
+            let _control_sub = control_sub;
+            let mut detector = Detector::new(model, inf_size, tracker, false);
+            detector.set_view(view);
+            detector.set_config_receiver(config_rx);
+            detector.run_supervised();
+        }) {
+        Ok(handle) => handle,
+        Err(e) => {
+            eprintln!("❌ 检测线程创建失败: {}", e);
+            return std::ptr::null_mut();
+        }
+    };
+    println!("🔌 FFI 管线已创建: {} (逻辑流 {})", model_path, view);
+
+    Box::into_raw(Box::new(YoloPipeline {
+        view,
+        worker: Some(worker),
+        results,
+        inflight: VecDeque::new(),
+        next_frame_id: 0,
+        _result_sub: result_sub,
+    }))
+}
+
+/// 推入一帧 RGBA 图像 (拷贝后立即返回)
+///
+/// `stride` 为每行字节数, 0 表示紧密排列 (`width * 4`).
+/// 返回帧序号 (>= 0), 参数无效时返回 -1
+///
+/// # Safety
+/// `pipeline` 必须来自 `yolo_create_pipeline` 且未释放, `rgba` 至少包含 `height * stride` 字节
+#[no_mangle]
+pub unsafe extern "C" fn yolo_push_frame(
+    pipeline: *mut YoloPipeline,
+    rgba: *const u8,
+    width: u32,
+    height: u32,
+    stride: u32,
+) -> i64 {
+    let Some(pipeline) = pipeline.as_mut() else {
+        return -1;
+    };
+    let row = width as usize * 4;
+    let stride = if stride == 0 { row } else { stride as usize };
+    if rgba.is_null() || width == 0 || height == 0 || stride < row {
+        return -1;
+    }
+
+    let src = std::slice::from_raw_parts(rgba, stride * height as usize);
+    let mut data = Vec::with_capacity(row * height as usize);
+    for line in src.chunks_exact(stride) {
+        data.extend_from_slice(&line[..row]);
+    }
+
+    let trace = FrameTrace::new();
+    let frame_id = pipeline.next_frame_id;
+    pipeline.next_frame_id += 1;
+    if pipeline.inflight.len() >= MAX_INFLIGHT_FRAMES {
+        pipeline.inflight.pop_front();
+    }
+    pipeline.inflight.push_back((trace.decode_ts, frame_id));
+
+    xbus::post(DecodedFrame {
+        rgba_data: Arc::new(data),
+        width,
+        height,
+        decode_fps: 0.0,
+        decoder_name: "ffi".to_string(),
+        yuv: None,
+        keyframe: true,
+        trace,
+        view: pipeline.view,
+        generation: 0, // 嵌入式管线不切换输入源
+        yuv16: None,
+        #[cfg(feature = "cuda")]
+        device: None,
+    });
+    frame_id as i64
+}
+
+/// 非阻塞地取出最早一帧的结果
+///
+/// 最多写入 `capacity` 个目标到 `out`, 概要写入 `frame`.
+/// 返回 1 表示取到结果, 0 表示暂无结果, -1 表示参数无效
+///
+/// # Safety
+/// `pipeline` 必须有效; `out` 至少可写 `capacity` 个元素 (capacity 为 0 时可为空指针);
+/// `frame` 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn yolo_poll_results(
+    pipeline: *mut YoloPipeline,
+    out: *mut YoloDetection,
+    capacity: usize,
+    frame: *mut YoloFrameResult,
+) -> i32 {
+    let Some(pipeline) = pipeline.as_mut() else {
+        return -1;
+    };
+    if frame.is_null() || (out.is_null() && capacity > 0) {
+        return -1;
+    }
+    let Some(result) = pipeline.results.lock().unwrap().pop_front() else {
+        return 0;
+    };
+
+    // 丢帧时跳过的已推入帧一并出队
+    let ts = result.trace.decode_ts;
+    let mut frame_id = 0;
+    while let Some(&(t, id)) = pipeline.inflight.front() {
+        if t > ts {
+            break;
+        }
+        pipeline.inflight.pop_front();
+        if t == ts {
+            frame_id = id;
+            break;
+        }
+    }
+
+    let written = result.bboxes.len().min(capacity);
+    if written > 0 {
+        let out = std::slice::from_raw_parts_mut(out, written);
+        for (dst, b) in out.iter_mut().zip(&result.bboxes) {
+            *dst = YoloDetection {
+                x1: b.x1,
+                y1: b.y1,
+                x2: b.x2,
+                y2: b.y2,
+                confidence: b.confidence,
+                track_id: b.class_id,
+            };
+        }
+    }
+    *frame = YoloFrameResult {
+        frame_id,
+        count: result.bboxes.len() as u32,
+        written: written as u32,
+        inference_ms: result.inference_ms,
+    };
+    1
+}
+
+/// 设置置信度/IoU 阈值 (下一帧生效), 成功返回 0
+///
+/// # Safety
+/// `pipeline` 必须来自 `yolo_create_pipeline` 且未释放
+#[no_mangle]
+pub unsafe extern "C" fn yolo_set_thresholds(
+    pipeline: *mut YoloPipeline,
+    conf_threshold: f32,
+    iou_threshold: f32,
+) -> i32 {
+    let Some(pipeline) = pipeline.as_ref() else {
+        return -1;
+    };
+    ControlMessage::UpdateParams {
+        conf_threshold,
+        iou_threshold,
+    }
+    .post_to(pipeline.view);
+    0
+}
+
+/// 停止检测线程并释放管线, 空指针时无操作
+///
+/// # Safety
+/// `pipeline` 必须来自 `yolo_create_pipeline`, 且只能释放一次
+#[no_mangle]
+pub unsafe extern "C" fn yolo_free_pipeline(pipeline: *mut YoloPipeline) {
+    if pipeline.is_null() {
+        return;
+    }
+    let mut pipeline = Box::from_raw(pipeline);
+    ControlMessage::Shutdown.post_to(pipeline.view);
+    if let Some(worker) = pipeline.worker.take() {
+        if worker.join().is_err() {
+            eprintln!("⚠️ FFI 检测线程异常退出");
+        }
+    }
+    println!("🔌 FFI 管线已释放 (逻辑流 {})", pipeline.view);
+}
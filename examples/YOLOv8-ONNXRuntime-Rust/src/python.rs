@@ -0,0 +1,435 @@
+//! Python 绑定 (`import yolov8_rs_py`)
+//!
+//! 暴露模型加载与推理、NMS 以及 ByteTrack/DeepSort 跟踪器, 便于在 notebook 中复用
+//! Rust 的后处理与跟踪. 帧以 `HxWx3` (RGB) 或 `HxWx4` (RGBA) 的 uint8 numpy 数组传入,
+//! 推理期间直接借用 numpy 缓冲区 (不拷贝) 并释放 GIL; 结果以 numpy 数组返回.
+//!
+//! 构建: `maturin develop --release` (见 pyproject.toml)
+
+use image::DynamicImage;
+use ndarray::{Array, Array1, Array2, Array3, IxDyn};
+use numpy::{
+    IntoPyArray, PyReadonlyArray1, PyReadonlyArray2, PyReadonlyArray3, PyUntypedArrayMethods,
+};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::detection::bytetrack::ByteTracker;
+use crate::detection::deepsort::PersonTracker;
+use crate::detection::tracker::TrackerParams;
+use crate::detection::types::BBox;
+use crate::models::{load_detect_model, Model, ModelType, PreprocessSpec};
+use crate::{Args, DetectionResult};
+
+fn runtime_error(e: anyhow::Error) -> PyErr {
+    PyRuntimeError::new_err(e.to_string())
+}
+
+/// 借用 numpy 帧: 返回 (连续缓冲区, 宽, 高, 通道数)
+fn frame_view<'a>(frame: &'a PyReadonlyArray3<'_, u8>) -> PyResult<(&'a [u8], u32, u32, usize)> {
+    let shape = frame.shape();
+    let (h, w, c) = (shape[0], shape[1], shape[2]);
+    if c != 1 && c != 3 && c != 4 {
+        return Err(PyValueError::new_err(format!(
+            "帧形状应为 HxWx1 (灰度/热成像), HxWx3 (RGB) 或 HxWx4 (RGBA), 实际为 {:?}",
+            shape
+        )));
+    }
+    let data = frame
+        .as_slice()
+        .map_err(|_| PyValueError::new_err("帧数组需为C连续 (np.ascontiguousarray)"))?;
+    Ok((data, w as u32, h as u32, c))
+}
+
+/// 借用 Nx4 框数组
+fn boxes_view(boxes: &PyReadonlyArray2<'_, f32>) -> PyResult<Vec<[f32; 4]>> {
+    let shape = boxes.shape();
+    if shape[1] != 4 {
+        return Err(PyValueError::new_err(format!(
+            "框数组形状应为 Nx4 (x1, y1, x2, y2), 实际为 {:?}",
+            shape
+        )));
+    }
+    Ok(boxes
+        .as_array()
+        .rows()
+        .into_iter()
+        .map(|r| [r[0], r[1], r[2], r[3]])
+        .collect())
+}
+
+/// 框 + 分数 → 跟踪器输入 (class_id 固定为0, 与检测器只保留行人一致)
+fn to_bboxes(boxes: &[[f32; 4]], scores: &[f32]) -> PyResult<Vec<BBox>> {
+    if boxes.len() != scores.len() {
+        return Err(PyValueError::new_err(format!(
+            "框数量 ({}) 与分数数量 ({}) 不一致",
+            boxes.len(),
+            scores.len()
+        )));
+    }
+    Ok(boxes
+        .iter()
+        .zip(scores)
+        .map(|(b, &confidence)| BBox {
+            x1: b[0],
+            y1: b[1],
+            x2: b[2],
+            y2: b[3],
+            confidence,
+            class_id: 0,
+        })
+        .collect())
+}
+
+/// 最近邻拉伸缩放到 `size x size` 并按模型预处理参数归一化为 NCHW 张量
+///
+/// 与检测器 CPU 路径一致 (不做 letterbox), 输出框再按宽高比例映射回原图;
+/// 单通道帧按 R=G=B 处理, 单通道模型取亮度
+fn stretch_to_tensor(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    channels: usize,
+    size: u32,
+    spec: &PreprocessSpec,
+) -> Array<f32, IxDyn> {
+    let (w, h, size) = (width as usize, height as usize, size as usize);
+    let mut ys = Array::zeros((1, spec.channels, size, size)).into_dyn();
+    for y in 0..size {
+        let sy = (y * h / size).min(h - 1);
+        for x in 0..size {
+            let sx = (x * w / size).min(w - 1);
+            let idx = (sy * w + sx) * channels;
+            let rgb = match channels {
+                1 => [data[idx]; 3],
+                _ => [data[idx], data[idx + 1], data[idx + 2]],
+            };
+            spec.write_pixel(&mut ys, 0, y, x, rgb);
+        }
+    }
+    ys
+}
+
+/// 检测结果 → Python 字典
+///
+/// - `boxes`: Nx4 float32 (x1, y1, x2, y2, 原图像素)
+/// - `scores`: N float32, `class_ids`: N int64
+/// - `keypoints`: NxKx3 float32 (x, y, conf), 仅姿态模型
+/// - `probs`: 类别概率, 仅分类模型
+fn result_to_dict<'py>(
+    py: Python<'py>,
+    result: &DetectionResult,
+    scale_x: f32,
+    scale_y: f32,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    let bboxes = result.bboxes().map(Vec::as_slice).unwrap_or_default();
+    let mut boxes = Array2::<f32>::zeros((bboxes.len(), 4));
+    for (mut row, b) in boxes.rows_mut().into_iter().zip(bboxes) {
+        row[0] = b.xmin() * scale_x;
+        row[1] = b.ymin() * scale_y;
+        row[2] = b.xmax() * scale_x;
+        row[3] = b.ymax() * scale_y;
+    }
+    let scores: Array1<f32> = bboxes.iter().map(|b| b.confidence()).collect();
+    let class_ids: Array1<i64> = bboxes.iter().map(|b| b.id() as i64).collect();
+    dict.set_item("boxes", boxes.into_pyarray_bound(py))?;
+    dict.set_item("scores", scores.into_pyarray_bound(py))?;
+    dict.set_item("class_ids", class_ids.into_pyarray_bound(py))?;
+
+    if let Some(kpts) = result.keypoints() {
+        let nk = kpts.first().map_or(0, Vec::len);
+        let mut array = Array3::<f32>::zeros((kpts.len(), nk, 3));
+        for (i, points) in kpts.iter().enumerate() {
+            for (k, p) in points.iter().take(nk).enumerate() {
+                array[[i, k, 0]] = p.x() * scale_x;
+                array[[i, k, 1]] = p.y() * scale_y;
+                array[[i, k, 2]] = p.confidence();
+            }
+        }
+        dict.set_item("keypoints", array.into_pyarray_bound(py))?;
+    }
+    if let Some(probs) = result.probs() {
+        dict.set_item("probs", probs.data().clone().into_pyarray_bound(py))?;
+    }
+    Ok(dict)
+}
+
+/// 检测模型
+///
+/// ```python
+/// model = yolov8_rs_py.Model("models/yolov8n.onnx", size=640, cuda=True)
+/// result = model.forward(frame)  # frame: HxWx3 uint8 RGB
+/// ```
+#[pyclass(name = "Model")]
+pub struct PyModel {
+    model: Box<dyn Model + Send>,
+    size: u32,
+    spec: PreprocessSpec,
+    // 输入尺寸的占位图, 后处理只读取其宽高 (坐标先落在输入空间再映射回原图)
+    size_probe: Vec<DynamicImage>,
+}
+
+#[pymethods]
+impl PyModel {
+    #[new]
+    #[pyo3(signature = (path, size=640, conf=None, iou=None, cuda=false, trt=false, fp16=false, device_id=0))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        path: &str,
+        size: u32,
+        conf: Option<f32>,
+        iou: Option<f32>,
+        cuda: bool,
+        trt: bool,
+        fp16: bool,
+        device_id: i32,
+    ) -> PyResult<Self> {
+        let model_type = ModelType::from_path(path);
+        let args = Args {
+            model: path.to_string(),
+            source: String::new(),
+            device_id,
+            trt,
+            cuda,
+            batch: 1,
+            batch_min: 1,
+            batch_max: 1,
+            fp16,
+            dla_core: None,
+            task: None,
+            nc: None,
+            nk: None,
+            nm: None,
+            width: Some(size),
+            height: Some(size),
+            conf: conf.unwrap_or_else(|| model_type.default_conf_threshold()),
+            iou: iou.unwrap_or_else(|| model_type.default_iou_threshold()),
+            kconf: 0.55,
+            profile: false,
+        };
+        let model = load_detect_model(model_type, args).map_err(runtime_error)?;
+        println!("✅ {:?} 模型加载成功: {}", model_type, path);
+        Ok(Self {
+            spec: model.preprocess_spec(),
+            model,
+            size,
+            size_probe: vec![DynamicImage::new_luma8(size, size)],
+        })
+    }
+
+    /// 置信度阈值
+    #[getter]
+    fn conf(&self) -> f32 {
+        self.model.conf()
+    }
+
+    #[setter]
+    fn set_conf(&mut self, val: f32) {
+        self.model.set_conf(val);
+    }
+
+    /// NMS IoU 阈值
+    #[getter]
+    fn iou(&self) -> f32 {
+        self.model.iou()
+    }
+
+    #[setter]
+    fn set_iou(&mut self, val: f32) {
+        self.model.set_iou(val);
+    }
+
+    /// 对单帧推理, 返回 `{"boxes", "scores", "class_ids"[, "keypoints"][, "probs"]}`
+    fn forward<'py>(
+        &mut self,
+        py: Python<'py>,
+        frame: PyReadonlyArray3<'py, u8>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let (data, width, height, channels) = frame_view(&frame)?;
+        let (size, spec) = (self.size, self.spec);
+        let (model, probe) = (&mut self.model, &self.size_probe);
+        let results = py
+            .allow_threads(|| {
+                let xs = stretch_to_tensor(data, width, height, channels, size, &spec);
+                let ys = model.run(vec![xs], false)?;
+                model.postprocess(ys, probe)
+            })
+            .map_err(runtime_error)?;
+        let scale_x = width as f32 / size as f32;
+        let scale_y = height as f32 / size as f32;
+        match results.first() {
+            Some(result) => result_to_dict(py, result, scale_x, scale_y),
+            None => result_to_dict(py, &DetectionResult::default(), 1.0, 1.0),
+        }
+    }
+
+    /// 打印模型信息
+    fn summary(&self) {
+        self.model.summary();
+    }
+}
+
+/// 非极大值抑制: 返回保留框的下标 (按分数降序)
+#[pyfunction]
+#[pyo3(signature = (boxes, scores, iou=0.45))]
+fn nms<'py>(
+    py: Python<'py>,
+    boxes: PyReadonlyArray2<'py, f32>,
+    scores: PyReadonlyArray1<'py, f32>,
+    iou: f32,
+) -> PyResult<Bound<'py, numpy::PyArray1<i64>>> {
+    let boxes = boxes_view(&boxes)?;
+    let scores = scores
+        .as_slice()
+        .map_err(|_| PyValueError::new_err("分数数组需为C连续"))?;
+    if boxes.len() != scores.len() {
+        return Err(PyValueError::new_err("框数量与分数数量不一致"));
+    }
+    let keep = py.allow_threads(|| crate::nms_indices(&boxes, scores, iou));
+    Ok(keep
+        .into_iter()
+        .map(|i| i as i64)
+        .collect::<Array1<i64>>()
+        .into_pyarray_bound(py))
+}
+
+/// 跟踪输出 → `{"ids": N int64, "boxes": Nx4 float32, "scores": N float32}`
+fn tracks_to_dict<'py>(
+    py: Python<'py>,
+    tracks: impl Iterator<Item = (u32, BBox)>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let tracks: Vec<(u32, BBox)> = tracks.collect();
+    let mut boxes = Array2::<f32>::zeros((tracks.len(), 4));
+    for (mut row, (_, b)) in boxes.rows_mut().into_iter().zip(&tracks) {
+        row[0] = b.x1;
+        row[1] = b.y1;
+        row[2] = b.x2;
+        row[3] = b.y2;
+    }
+    let ids: Array1<i64> = tracks.iter().map(|(id, _)| *id as i64).collect();
+    let scores: Array1<f32> = tracks.iter().map(|(_, b)| b.confidence).collect();
+    let dict = PyDict::new_bound(py);
+    dict.set_item("ids", ids.into_pyarray_bound(py))?;
+    dict.set_item("boxes", boxes.into_pyarray_bound(py))?;
+    dict.set_item("scores", scores.into_pyarray_bound(py))?;
+    Ok(dict)
+}
+
+/// 生命周期参数: 未给出的字段沿用跟踪器默认值
+fn merge_params(
+    mut params: TrackerParams,
+    max_lost_frames: Option<u32>,
+    min_hits: Option<u32>,
+) -> TrackerParams {
+    if let Some(v) = max_lost_frames {
+        params.max_lost_frames = v;
+    }
+    if let Some(v) = min_hits {
+        params.min_hits = v;
+    }
+    params
+}
+
+/// ByteTrack 跟踪器 (纯框 IOU 关联)
+#[pyclass(name = "ByteTracker")]
+pub struct PyByteTracker {
+    inner: ByteTracker,
+}
+
+#[pymethods]
+impl PyByteTracker {
+    #[new]
+    #[pyo3(signature = (max_lost_frames=None, min_hits=None))]
+    fn new(max_lost_frames: Option<u32>, min_hits: Option<u32>) -> Self {
+        let mut inner = ByteTracker::new();
+        let params = merge_params(inner.params(), max_lost_frames, min_hits);
+        inner.set_params(params);
+        Self { inner }
+    }
+
+    /// 更新一帧检测, 返回已确认的轨迹
+    fn update<'py>(
+        &mut self,
+        py: Python<'py>,
+        boxes: PyReadonlyArray2<'py, f32>,
+        scores: PyReadonlyArray1<'py, f32>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let scores = scores
+            .as_slice()
+            .map_err(|_| PyValueError::new_err("分数数组需为C连续"))?;
+        let detections = to_bboxes(&boxes_view(&boxes)?, scores)?;
+        let tracked = self.inner.update(&detections);
+        tracks_to_dict(
+            py,
+            tracked
+                .iter()
+                .filter(|t| t.confirmed)
+                .map(|t| (t.id, t.bbox.clone())),
+        )
+    }
+
+    /// 轨迹统计 `(active, lost, removed)`
+    fn stats(&self) -> (usize, usize, u64) {
+        let s = self.inner.stats();
+        (s.active, s.lost, s.removed)
+    }
+}
+
+/// DeepSort 跟踪器 (可选传入 RGBA 帧启用 ReID 外观特征)
+#[pyclass(name = "DeepSortTracker")]
+pub struct PyDeepSortTracker {
+    inner: PersonTracker,
+}
+
+#[pymethods]
+impl PyDeepSortTracker {
+    #[new]
+    #[pyo3(signature = (max_lost_frames=None, min_hits=None))]
+    fn new(max_lost_frames: Option<u32>, min_hits: Option<u32>) -> Self {
+        let mut inner = PersonTracker::new();
+        let params = merge_params(inner.params(), max_lost_frames, min_hits);
+        inner.set_params(params);
+        Self { inner }
+    }
+
+    /// 更新一帧检测; `frame` 为 HxWx4 RGBA 时提取 ReID 特征
+    #[pyo3(signature = (boxes, scores, frame=None))]
+    fn update<'py>(
+        &mut self,
+        py: Python<'py>,
+        boxes: PyReadonlyArray2<'py, f32>,
+        scores: PyReadonlyArray1<'py, f32>,
+        frame: Option<PyReadonlyArray3<'py, u8>>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let scores = scores
+            .as_slice()
+            .map_err(|_| PyValueError::new_err("分数数组需为C连续"))?;
+        let detections = to_bboxes(&boxes_view(&boxes)?, scores)?;
+        let frame_data = match &frame {
+            Some(frame) => match frame_view(frame)? {
+                (data, w, h, 4) => Some((data, w, h)),
+                _ => return Err(PyValueError::new_err("DeepSort 的 ReID 需要 HxWx4 RGBA 帧")),
+            },
+            None => None,
+        };
+        let tracked = self.inner.update(&detections, &[], frame_data);
+        tracks_to_dict(py, tracked.iter().map(|t| (t.id, t.bbox.clone())))
+    }
+
+    /// 轨迹统计 `(active, lost, removed)`
+    fn stats(&self) -> (usize, usize, u64) {
+        let s = self.inner.stats();
+        (s.active, s.lost, s.removed)
+    }
+}
+
+#[pymodule]
+fn yolov8_rs_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyModel>()?;
+    m.add_class::<PyByteTracker>()?;
+    m.add_class::<PyDeepSortTracker>()?;
+    m.add_function(wrap_pyfunction!(nms, m)?)?;
+    Ok(())
+}
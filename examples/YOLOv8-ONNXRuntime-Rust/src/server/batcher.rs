@@ -0,0 +1,317 @@
+//! 批处理调度器
+//!
+//! 模型独占一个推理线程; 各 RPC 把帧放入所属流的队列后异步等待结果. 推理线程取到第一帧后
+//! 在 `max_wait` 内继续收集, 凑满 `max_batch` 或超时即合并为一个 batch 推理,
+//! 用少量等待换取多路并发时的吞吐. 模型 batch 维固定时退化为逐帧推理
+//!
+//! batch 由 [`FairQueue`] 按流的优先级与等待上限选取 (见 [`super::qos`]),
+//! 有帧即将超过等待上限时提前结束收集
+
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use image::DynamicImage;
+use tokio::sync::oneshot;
+
+use super::qos::{FairQueue, QosConfig, Scheduled, StreamStats};
+use crate::models::Model;
+use crate::DetectionResult;
+
+/// 按流的调度统计输出间隔
+const STATS_LOG_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 批处理参数
+#[derive(Clone, Debug)]
+pub struct BatchConfig {
+    /// 单个 batch 的最大帧数
+    pub max_batch: usize,
+    /// 收到第一帧后等待更多帧的最长时间
+    pub max_wait: Duration,
+    /// 所有流的排队帧上限, 超过后新请求直接返回繁忙
+    pub queue_capacity: usize,
+    /// 按流的优先级/等待上限/队列上限
+    pub qos: QosConfig,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            max_batch: 8,
+            max_wait: Duration::from_millis(5),
+            queue_capacity: 64,
+            qos: QosConfig::default(),
+        }
+    }
+}
+
+/// 单帧推理结果
+pub struct BatchOutput {
+    pub result: DetectionResult,
+    pub inference_ms: f32,
+    pub batch_size: usize,
+    pub queue_ms: f32, // 排队等待时间
+}
+
+struct BatchRequest {
+    image: DynamicImage,
+    reply: oneshot::Sender<Result<BatchOutput, String>>,
+}
+
+/// 推理线程与各 RPC 共享的队列
+struct Shared<T> {
+    queue: Mutex<QueueState<T>>,
+    ready: Condvar,
+}
+
+struct QueueState<T> {
+    queue: FairQueue<T>,
+    closed: bool, // 所有调度器句柄已释放
+}
+
+impl<T> Shared<T> {
+    fn new(queue: FairQueue<T>) -> Self {
+        Self {
+            queue: Mutex::new(QueueState {
+                queue,
+                closed: false,
+            }),
+            ready: Condvar::new(),
+        }
+    }
+
+    fn close(&self) {
+        self.queue.lock().unwrap().closed = true;
+        self.ready.notify_all();
+    }
+}
+
+/// 最后一个句柄释放时通知推理线程退出
+struct Handle(Arc<Shared<BatchRequest>>);
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+/// 批处理调度器 (可在多个 RPC 间克隆共享)
+#[derive(Clone)]
+pub struct BatchScheduler {
+    handle: Arc<Handle>,
+}
+
+impl BatchScheduler {
+    /// 启动推理线程
+    pub fn start(mut model: Box<dyn Model + Send>, config: BatchConfig) -> Result<Self> {
+        // 没有推理引擎的模型 (MockModel) 按动态 batch 处理
+        let max_batch = if model
+            .engine_mut()
+            .is_none_or(|engine| engine.is_batch_dynamic())
+        {
+            config.max_batch.max(1)
+        } else {
+            println!("⚠️ 模型 batch 维固定, 批处理调度退化为逐帧推理");
+            1
+        };
+        let shared: Arc<Shared<BatchRequest>> = Arc::new(Shared::new(FairQueue::new(
+            config.qos.clone(),
+            config.queue_capacity,
+            config.max_wait,
+        )));
+        let worker = shared.clone();
+        std::thread::Builder::new()
+            .name("grpc-infer".to_string())
+            .spawn(move || {
+                println!(
+                    "📦 批处理调度器启动: batch ≤ {}, 等待 ≤ {:?}, {} 路流单独配置",
+                    max_batch,
+                    config.max_wait,
+                    config.qos.streams.len()
+                );
+                let mut last_log = Instant::now();
+                loop {
+                    let batch = collect_batch(&worker, max_batch, config.max_wait);
+                    if batch.is_empty() {
+                        break; // 所有句柄已释放
+                    }
+                    if last_log.elapsed() >= STATS_LOG_INTERVAL {
+                        last_log = Instant::now();
+                        log_stats(&worker.queue.lock().unwrap().queue.stats());
+                    }
+                    let (images, replies): (Vec<_>, Vec<_>) = batch
+                        .into_iter()
+                        .map(|s| {
+                            let queue_ms = s.wait.as_secs_f32() * 1000.0;
+                            (s.item.image, (s.item.reply, queue_ms))
+                        })
+                        .unzip();
+                    let t = Instant::now();
+                    let outputs = model.forward(&images);
+                    let inference_ms = t.elapsed().as_secs_f32() * 1000.0;
+                    let batch_size = images.len();
+                    match outputs {
+                        Ok(results) if results.len() == batch_size => {
+                            for ((reply, queue_ms), result) in replies.into_iter().zip(results) {
+                                let _ = reply.send(Ok(BatchOutput {
+                                    result,
+                                    inference_ms,
+                                    batch_size,
+                                    queue_ms,
+                                }));
+                            }
+                        }
+                        Ok(results) => {
+                            let msg = format!(
+                                "推理结果数量 {} 与 batch {} 不一致",
+                                results.len(),
+                                batch_size
+                            );
+                            for (reply, _) in replies {
+                                let _ = reply.send(Err(msg.clone()));
+                            }
+                        }
+                        Err(e) => {
+                            eprintln!("❌ 批量推理失败: {}", e);
+                            for (reply, _) in replies {
+                                let _ = reply.send(Err(e.to_string()));
+                            }
+                        }
+                    }
+                }
+                println!("🛑 批处理调度器退出");
+            })?;
+        Ok(Self {
+            handle: Arc::new(Handle(shared)),
+        })
+    }
+
+    /// 提交一帧到指定流的队列并等待结果
+    pub async fn detect(&self, stream: &str, image: DynamicImage) -> Result<BatchOutput> {
+        let (reply, rx) = oneshot::channel();
+        let shared = &self.handle.0;
+        shared
+            .queue
+            .lock()
+            .unwrap()
+            .queue
+            .push(stream, BatchRequest { image, reply }, Instant::now())
+            .map_err(|_| anyhow!("推理队列已满 (流 {})", stream))?;
+        shared.ready.notify_one();
+        rx.await
+            .map_err(|_| anyhow!("推理线程已退出"))?
+            .map_err(|e| anyhow!(e))
+    }
+
+    /// 各流的调度统计 (排队/等待/超时/饥饿)
+    pub fn stats(&self) -> Vec<StreamStats> {
+        self.handle.0.queue.lock().unwrap().queue.stats()
+    }
+}
+
+/// 阻塞等待第一帧, 之后在 `max_wait` 内尽量凑满 `max_batch`, 有帧即将超过等待上限时提前结束;
+/// 所有句柄释放且队列为空时返回空
+fn collect_batch<T>(shared: &Shared<T>, max_batch: usize, max_wait: Duration) -> Vec<Scheduled<T>> {
+    let mut state = shared.queue.lock().unwrap();
+    while state.queue.is_empty() {
+        if state.closed {
+            return Vec::new();
+        }
+        state = shared.ready.wait(state).unwrap();
+    }
+    let collect_until = Instant::now() + max_wait;
+    while state.queue.len() < max_batch && !state.closed {
+        let deadline = state
+            .queue
+            .start_by()
+            .map_or(collect_until, |t| t.min(collect_until));
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        state = shared.ready.wait_timeout(state, deadline - now).unwrap().0;
+    }
+    state.queue.pop_batch(max_batch, Instant::now())
+}
+
+/// 输出各流的调度统计, 有超时或饥饿的流标出
+fn log_stats(stats: &[StreamStats]) {
+    for s in stats {
+        let flag = if s.latency_violations > 0 || s.max_starved_streak > 0 {
+            "⚠️"
+        } else {
+            "📊"
+        };
+        println!(
+            "{} 流 {} (优先级 {}): 已推理 {}, 排队 {}, 拒绝 {}, 等待 平均 {:.1}ms / 最大 {:.1}ms, 超时 {}, 饥饿批次 {} (最长连续 {})",
+            flag,
+            s.stream,
+            s.priority,
+            s.processed,
+            s.queued,
+            s.rejected,
+            s.avg_wait_ms(),
+            s.max_wait_ms,
+            s.latency_violations,
+            s.starved_batches,
+            s.max_starved_streak
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::qos::StreamPolicy;
+
+    /// 已排队的帧合并为一个 batch, 超过上限的留给下一批, 句柄释放后返回空
+    #[test]
+    fn test_collect_batch() {
+        let wait = Duration::from_millis(1);
+        let shared = Shared::new(FairQueue::new(QosConfig::default(), 64, wait));
+        let now = Instant::now();
+        for i in 0..5 {
+            shared
+                .queue
+                .lock()
+                .unwrap()
+                .queue
+                .push("cam", i, now)
+                .unwrap();
+        }
+        let items =
+            |batch: Vec<Scheduled<i32>>| batch.into_iter().map(|s| s.item).collect::<Vec<_>>();
+        assert_eq!(items(collect_batch(&shared, 3, wait)), vec![0, 1, 2]);
+        assert_eq!(items(collect_batch(&shared, 3, wait)), vec![3, 4]);
+        shared.close();
+        assert!(collect_batch(&shared, 3, wait).is_empty());
+    }
+
+    /// 有帧即将超过等待上限时不等满 max_wait
+    #[test]
+    fn test_collect_batch_latency() {
+        let qos = QosConfig {
+            streams: [(
+                "gate".to_string(),
+                StreamPolicy {
+                    max_latency_ms: 20,
+                    ..Default::default()
+                },
+            )]
+            .into(),
+            ..Default::default()
+        };
+        let shared = Shared::new(FairQueue::new(qos, 64, Duration::from_millis(5)));
+        shared
+            .queue
+            .lock()
+            .unwrap()
+            .queue
+            .push("gate", 0, Instant::now())
+            .unwrap();
+        let t = Instant::now();
+        let batch = collect_batch(&shared, 8, Duration::from_secs(5));
+        assert_eq!(batch.len(), 1);
+        assert!(t.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,293 @@
+//! gRPC 推理服务 (proto/detector.proto)
+//!
+//! 其他服务无需链接 Rust 即可调用检测器: `Detect` 单帧请求, `Track` 双向流逐帧返回
+//! 带轨迹ID的结果. 帧可为 JPEG 或未压缩 RGB/RGBA, 全部请求经 [`BatchScheduler`]
+//! 合并推理以提高并发吞吐, 各路流 (请求的 `stream`) 按优先级公平分配 batch
+
+pub mod batcher;
+pub mod qos;
+
+use std::pin::Pin;
+
+use image::{DynamicImage, RgbImage, RgbaImage};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::codec::{CompactBox, CompactFrame, Encoder};
+use crate::detection::bytetrack::ByteTracker;
+use crate::detection::types::BBox;
+pub use batcher::{BatchConfig, BatchOutput, BatchScheduler};
+pub use qos::{QosConfig, StreamPolicy, StreamStats, BATCHING_CONFIG_PATH};
+
+/// protoc 生成的消息与服务定义
+pub mod pb {
+    tonic::include_proto!("yolov8rs");
+}
+
+use pb::detect_request::Image;
+use pb::detector_server::Detector;
+pub use pb::detector_server::DetectorServer;
+use pb::{DetectRequest, DetectResponse, Detection};
+
+/// 默认监听地址
+pub const DEFAULT_LISTEN_ADDR: &str = "0.0.0.0:50051";
+
+/// 单条消息上限 (未压缩 1080p RGBA 约 8MB)
+pub const MAX_MESSAGE_SIZE: usize = 32 * 1024 * 1024;
+
+/// 每条 Track 流的响应缓冲
+const TRACK_RESPONSE_BUFFER: usize = 16;
+
+/// 请求中的帧 → 图像
+fn decode_image(image: Option<Image>) -> Result<DynamicImage, Status> {
+    match image {
+        Some(Image::Jpeg(bytes)) => image::load_from_memory(&bytes)
+            .map_err(|e| Status::invalid_argument(format!("JPEG 解码失败: {}", e))),
+        Some(Image::Raw(raw)) => {
+            let image = match raw.channels {
+                3 => RgbImage::from_raw(raw.width, raw.height, raw.data).map(DynamicImage::from),
+                4 => RgbaImage::from_raw(raw.width, raw.height, raw.data).map(DynamicImage::from),
+                n => return Err(Status::invalid_argument(format!("不支持的通道数: {}", n))),
+            };
+            image.ok_or_else(|| Status::invalid_argument("帧数据长度与宽高不符"))
+        }
+        None => Err(Status::invalid_argument("请求缺少图像")),
+    }
+}
+
+/// 请求所属的流, 未指定时归入默认流
+fn stream_name(stream: &str) -> &str {
+    if stream.is_empty() {
+        qos::DEFAULT_STREAM
+    } else {
+        stream
+    }
+}
+
+/// 推理结果 → 检测框 (按请求的类别过滤)
+fn detections(output: &BatchOutput, classes: &[u32]) -> Vec<Detection> {
+    output
+        .result
+        .bboxes()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter(|b| classes.is_empty() || classes.contains(&(b.id() as u32)))
+        .map(|b| Detection {
+            x1: b.xmin(),
+            y1: b.ymin(),
+            x2: b.xmax(),
+            y2: b.ymax(),
+            confidence: b.confidence(),
+            class_id: b.id() as u32,
+            track_id: 0,
+        })
+        .collect()
+}
+
+/// 两个框的 IoU
+fn iou(a: &Detection, b: &BBox) -> f32 {
+    let w = (a.x2.min(b.x2) - a.x1.max(b.x1)).max(0.0);
+    let h = (a.y2.min(b.y2) - a.y1.max(b.y1)).max(0.0);
+    let inter = w * h;
+    let union = (a.x2 - a.x1) * (a.y2 - a.y1) + (b.x2 - b.x1) * (b.y2 - b.y1) - inter;
+    if union > 0.0 {
+        inter / union
+    } else {
+        0.0
+    }
+}
+
+/// 检测 + 跟踪: 与检测线程一致, 只输出已确认且本帧命中的轨迹
+///
+/// 跟踪器不区分类别, 轨迹的类别取本帧与其重叠最大的检测框
+fn track(tracker: &mut ByteTracker, detections: Vec<Detection>) -> Vec<Detection> {
+    let bboxes: Vec<BBox> = detections
+        .iter()
+        .map(|d| BBox {
+            x1: d.x1,
+            y1: d.y1,
+            x2: d.x2,
+            y2: d.y2,
+            confidence: d.confidence,
+            class_id: d.class_id,
+        })
+        .collect();
+    tracker
+        .update(&bboxes)
+        .iter()
+        .filter(|t| t.confirmed && t.frames_lost == 0)
+        .map(|t| Detection {
+            x1: t.bbox.x1,
+            y1: t.bbox.y1,
+            x2: t.bbox.x2,
+            y2: t.bbox.y2,
+            confidence: t.score,
+            class_id: detections
+                .iter()
+                .max_by(|a, b| iou(a, &t.bbox).total_cmp(&iou(b, &t.bbox)))
+                .map_or(0, |d| d.class_id),
+            track_id: t.id,
+        })
+        .collect()
+}
+
+/// 检测框 → 紧凑编码的帧 (跟踪结果以轨迹ID为差量的键)
+fn compact_frame(
+    detections: &[Detection],
+    (width, height): (u32, u32),
+    tracked: bool,
+    inference_ms: f32,
+) -> CompactFrame {
+    CompactFrame {
+        width,
+        height,
+        tracked,
+        inference_ms,
+        boxes: detections
+            .iter()
+            .enumerate()
+            .map(|(i, d)| CompactBox {
+                id: if tracked { d.track_id } else { i as u32 },
+                class_id: d.class_id,
+                x1: d.x1,
+                y1: d.y1,
+                x2: d.x2,
+                y2: d.y2,
+                confidence: d.confidence,
+                global_id: None,
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// 组装响应; 有编码器 (请求 compact) 时检测框以紧凑编码放入 packed
+fn detect_response(
+    frame_id: u64,
+    detections: Vec<Detection>,
+    output: &BatchOutput,
+    size: (u32, u32),
+    tracked: bool,
+    encoder: Option<&mut Encoder>,
+) -> DetectResponse {
+    let mut response = DetectResponse {
+        frame_id,
+        detections: Vec::new(),
+        inference_ms: output.inference_ms,
+        batch_size: output.batch_size as u32,
+        queue_ms: output.queue_ms,
+        packed: Vec::new(),
+    };
+    match encoder {
+        Some(encoder) => {
+            let frame = compact_frame(&detections, size, tracked, output.inference_ms);
+            response.packed = encoder.encode(&frame);
+        }
+        None => response.detections = detections,
+    }
+    response
+}
+
+/// gRPC 服务实现
+pub struct DetectorService {
+    scheduler: BatchScheduler,
+}
+
+impl DetectorService {
+    pub fn new(scheduler: BatchScheduler) -> Self {
+        Self { scheduler }
+    }
+
+    /// 带消息大小上限的 tonic 服务
+    pub fn into_server(self) -> DetectorServer<Self> {
+        DetectorServer::new(self)
+            .max_decoding_message_size(MAX_MESSAGE_SIZE)
+            .max_encoding_message_size(MAX_MESSAGE_SIZE)
+    }
+}
+
+#[tonic::async_trait]
+impl Detector for DetectorService {
+    async fn detect(
+        &self,
+        request: Request<DetectRequest>,
+    ) -> Result<Response<DetectResponse>, Status> {
+        let request = request.into_inner();
+        let image = decode_image(request.image)?;
+        let size = (image.width(), image.height());
+        let output = self
+            .scheduler
+            .detect(stream_name(&request.stream), image)
+            .await
+            .map_err(|e| Status::unavailable(e.to_string()))?;
+        // 单次请求没有基准帧, 只发关键帧
+        let mut encoder = request.compact.then(|| Encoder::new(1));
+        Ok(Response::new(detect_response(
+            request.frame_id,
+            detections(&output, &request.classes),
+            &output,
+            size,
+            false,
+            encoder.as_mut(),
+        )))
+    }
+
+    type TrackStream = Pin<Box<dyn Stream<Item = Result<DetectResponse, Status>> + Send>>;
+
+    async fn track(
+        &self,
+        request: Request<Streaming<DetectRequest>>,
+    ) -> Result<Response<Self::TrackStream>, Status> {
+        let mut inbound = request.into_inner();
+        let scheduler = self.scheduler.clone();
+        let (tx, rx) = mpsc::channel(TRACK_RESPONSE_BUFFER);
+
+        // 每条流一个跟踪器, 帧按到达顺序串行处理以保证轨迹连续;
+        // 流内有序可靠, 紧凑编码按轨迹发送差量帧
+        tokio::spawn(async move {
+            let mut tracker = ByteTracker::new();
+            let mut encoder = Encoder::default();
+            while let Some(request) = inbound.next().await {
+                let response = match request {
+                    Ok(request) => {
+                        let frame_id = request.frame_id;
+                        if !request.compact {
+                            // 调用方未收到的帧不能作为差量的基准
+                            encoder.force_keyframe();
+                        }
+                        match decode_image(request.image) {
+                            Ok(image) => {
+                                let size = (image.width(), image.height());
+                                match scheduler.detect(stream_name(&request.stream), image).await {
+                                    Ok(output) => {
+                                        let dets = detections(&output, &request.classes);
+                                        Ok(detect_response(
+                                            frame_id,
+                                            track(&mut tracker, dets),
+                                            &output,
+                                            size,
+                                            true,
+                                            request.compact.then_some(&mut encoder),
+                                        ))
+                                    }
+                                    Err(e) => Err(Status::unavailable(e.to_string())),
+                                }
+                            }
+                            Err(status) => Err(status),
+                        }
+                    }
+                    Err(status) => Err(status),
+                };
+                let failed = response.is_err();
+                if tx.send(response).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+}
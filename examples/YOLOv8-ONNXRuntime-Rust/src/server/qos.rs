@@ -0,0 +1,416 @@
+//! 批处理的按流公平调度 (QoS)
+//!
+//! 所有请求排进同一队列时, 高帧率的摄像头会挤占 batch, 低帧率的摄像头长时间得不到推理.
+//! 这里每路流 (请求中的 `stream`) 一个队列, 组 batch 时:
+//! 1. 先取已到或即将到等待上限 (`max_latency_ms`) 的帧, 期限早的优先
+//! 2. 剩余名额按优先级做平滑加权轮询 (priority 为权重), 每次取一帧
+//!
+//! 每路流的队列有独立上限, 高帧率的流只会填满自己的队列. 统计每路流的排队时间、
+//! 超过等待上限的帧数与饥饿批次 (有帧排队却未被选中的 batch 数)
+//!
+//! 配置文件 (batching.json), 不存在时所有流权重相同、不保证等待上限:
+//! ```json
+//! {
+//!   "default": { "priority": 1, "max_latency_ms": 0, "queue_capacity": 8 },
+//!   "streams": { "gate": { "priority": 4, "max_latency_ms": 40 }, "yard": { "priority": 1 } }
+//! }
+//! ```
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// 默认配置文件路径
+pub const BATCHING_CONFIG_PATH: &str = "batching.json";
+
+/// 请求未指定流时归入的流
+pub const DEFAULT_STREAM: &str = "default";
+
+/// 未配置的流空闲超过该时长后移除 (统计随之清除)
+const STREAM_IDLE_EXPIRY: Duration = Duration::from_secs(300);
+
+/// 单路流的调度策略
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StreamPolicy {
+    pub priority: u32,         // 加权轮询的权重, 0 按 1 处理
+    pub max_latency_ms: u64,   // 排队等待上限 (到期的帧优先进入 batch), 0 不保证
+    pub queue_capacity: usize, // 本流排队帧上限, 超过后新请求返回繁忙
+}
+
+impl Default for StreamPolicy {
+    fn default() -> Self {
+        Self {
+            priority: 1,
+            max_latency_ms: 0,
+            queue_capacity: 8,
+        }
+    }
+}
+
+impl StreamPolicy {
+    fn weight(&self) -> i64 {
+        self.priority.max(1) as i64
+    }
+
+    fn max_latency(&self) -> Option<Duration> {
+        (self.max_latency_ms > 0).then(|| Duration::from_millis(self.max_latency_ms))
+    }
+}
+
+/// 按流的调度配置 (batching.json)
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QosConfig {
+    pub default: StreamPolicy,
+    pub streams: HashMap<String, StreamPolicy>,
+}
+
+impl QosConfig {
+    /// 从文件加载配置, 文件不存在或解析失败时为 None (使用默认值)
+    pub fn load(path: &str) -> Option<Self> {
+        let json = fs::read_to_string(path).ok()?;
+        match serde_json::from_str::<Self>(&json) {
+            Ok(config) => {
+                println!(
+                    "✅ 批处理调度配置已从 {} 加载 ({} 路流)",
+                    path,
+                    config.streams.len()
+                );
+                Some(config)
+            }
+            Err(e) => {
+                eprintln!("⚠️  批处理调度配置解析失败: {}, 不启用", e);
+                None
+            }
+        }
+    }
+
+    /// 流的策略, 未配置时为默认策略
+    pub fn policy(&self, stream: &str) -> &StreamPolicy {
+        self.streams.get(stream).unwrap_or(&self.default)
+    }
+}
+
+/// 单路流的调度统计
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StreamStats {
+    pub stream: String,
+    pub priority: u32,
+    pub queued: usize,
+    pub processed: u64,
+    pub rejected: u64,
+    pub total_wait_ms: f64,
+    pub max_wait_ms: f64,
+    pub latency_violations: u64, // 排队超过 max_latency_ms 的帧数
+    pub starved_batches: u64,    // 有帧排队却未被选中的 batch 数
+    pub max_starved_streak: u64, // 连续饥饿的最长 batch 数
+    starved_streak: u64,
+}
+
+impl StreamStats {
+    pub fn avg_wait_ms(&self) -> f64 {
+        if self.processed == 0 {
+            0.0
+        } else {
+            self.total_wait_ms / self.processed as f64
+        }
+    }
+}
+
+/// 选入 batch 的一帧
+#[derive(Debug)]
+pub struct Scheduled<T> {
+    pub item: T,
+    pub stream: String,
+    pub wait: Duration, // 排队时间
+}
+
+struct StreamQueue<T> {
+    policy: StreamPolicy,
+    configured: bool,
+    queue: VecDeque<(Instant, T)>,
+    credit: i64, // 平滑加权轮询的当前值
+    last_seen: Instant,
+    stats: StreamStats,
+}
+
+impl<T> StreamQueue<T> {
+    /// 队首帧的等待期限
+    fn due(&self) -> Option<Instant> {
+        let (enqueued, _) = self.queue.front()?;
+        Some(*enqueued + self.policy.max_latency()?)
+    }
+}
+
+/// 按流排队、按优先级与等待上限组 batch 的公平队列
+pub struct FairQueue<T> {
+    config: QosConfig,
+    capacity: usize,  // 所有流的排队帧总上限
+    margin: Duration, // 期限在该时长内的帧视为到期 (组 batch 的等待时长, 等不到下一批)
+    streams: Vec<StreamQueue<T>>,
+    len: usize,
+}
+
+impl<T> FairQueue<T> {
+    pub fn new(config: QosConfig, capacity: usize, margin: Duration) -> Self {
+        Self {
+            config,
+            capacity: capacity.max(1),
+            margin,
+            streams: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 入队; 本流或总队列已满时退回该帧
+    pub fn push(&mut self, stream: &str, item: T, now: Instant) -> Result<(), T> {
+        let index = match self.streams.iter().position(|s| s.stats.stream == stream) {
+            Some(index) => index,
+            None => {
+                let policy = self.config.policy(stream).clone();
+                self.streams.push(StreamQueue {
+                    stats: StreamStats {
+                        stream: stream.to_string(),
+                        priority: policy.priority,
+                        ..Default::default()
+                    },
+                    configured: self.config.streams.contains_key(stream),
+                    policy,
+                    queue: VecDeque::new(),
+                    credit: 0,
+                    last_seen: now,
+                });
+                self.streams.len() - 1
+            }
+        };
+        let s = &mut self.streams[index];
+        s.last_seen = now;
+        if self.len >= self.capacity || s.queue.len() >= s.policy.queue_capacity.max(1) {
+            s.stats.rejected += 1;
+            return Err(item);
+        }
+        s.queue.push_back((now, item));
+        self.len += 1;
+        Ok(())
+    }
+
+    /// 最迟的开始时间: 最早的等待期限 (队首帧入队时间 + max_latency_ms) 减去余量,
+    /// 组 batch 的等待不超过它
+    pub fn start_by(&self) -> Option<Instant> {
+        let due = self.streams.iter().filter_map(StreamQueue::due).min()?;
+        Some(due.checked_sub(self.margin).unwrap_or(due))
+    }
+
+    /// 取出一个 batch: 先取到期的帧, 再按优先级加权轮询
+    pub fn pop_batch(&mut self, max_batch: usize, now: Instant) -> Vec<Scheduled<T>> {
+        let waiting: Vec<bool> = self.streams.iter().map(|s| !s.queue.is_empty()).collect();
+        let mut picked = vec![0u64; self.streams.len()];
+        let mut batch = Vec::new();
+
+        while batch.len() < max_batch {
+            let urgent = self
+                .streams
+                .iter()
+                .enumerate()
+                .filter_map(|(i, s)| s.due().map(|due| (due, i)))
+                .filter(|&(due, _)| due <= now + self.margin)
+                .min()
+                .map(|(_, i)| i);
+            let Some(index) = urgent.or_else(|| self.next_weighted()) else {
+                break;
+            };
+            let s = &mut self.streams[index];
+            let Some((enqueued, item)) = s.queue.pop_front() else {
+                break;
+            };
+            self.len -= 1;
+            picked[index] += 1;
+
+            let wait = now.saturating_duration_since(enqueued);
+            let wait_ms = wait.as_secs_f64() * 1000.0;
+            s.stats.processed += 1;
+            s.stats.total_wait_ms += wait_ms;
+            s.stats.max_wait_ms = s.stats.max_wait_ms.max(wait_ms);
+            if s.policy.max_latency().is_some_and(|limit| wait > limit) {
+                s.stats.latency_violations += 1;
+            }
+            batch.push(Scheduled {
+                item,
+                stream: s.stats.stream.clone(),
+                wait,
+            });
+        }
+
+        for (i, s) in self.streams.iter_mut().enumerate() {
+            if !waiting[i] {
+                continue;
+            }
+            if picked[i] == 0 {
+                s.stats.starved_batches += 1;
+                s.stats.starved_streak += 1;
+                s.stats.max_starved_streak = s.stats.max_starved_streak.max(s.stats.starved_streak);
+            } else {
+                s.stats.starved_streak = 0;
+            }
+        }
+        // 未配置的流断开后不再占用内存
+        self.streams.retain(|s| {
+            s.configured
+                || !s.queue.is_empty()
+                || now.saturating_duration_since(s.last_seen) < STREAM_IDLE_EXPIRY
+        });
+        batch
+    }
+
+    /// 平滑加权轮询: 有帧的流各加上权重, 取当前值最大者, 再减去这些流的权重和
+    fn next_weighted(&mut self) -> Option<usize> {
+        let mut total = 0;
+        for s in self.streams.iter_mut().filter(|s| !s.queue.is_empty()) {
+            s.credit += s.policy.weight();
+            total += s.policy.weight();
+        }
+        // 当前值相同时先到的流优先
+        let index = self
+            .streams
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| !s.queue.is_empty())
+            .max_by_key(|&(i, s)| (s.credit, Reverse(i)))
+            .map(|(i, _)| i)?;
+        self.streams[index].credit -= total;
+        Some(index)
+    }
+
+    /// 各流的调度统计
+    pub fn stats(&self) -> Vec<StreamStats> {
+        self.streams
+            .iter()
+            .map(|s| StreamStats {
+                queued: s.queue.len(),
+                ..s.stats.clone()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(streams: &[(&str, u32, u64)]) -> QosConfig {
+        QosConfig {
+            default: StreamPolicy::default(),
+            streams: streams
+                .iter()
+                .map(|&(name, priority, max_latency_ms)| {
+                    let policy = StreamPolicy {
+                        priority,
+                        max_latency_ms,
+                        ..Default::default()
+                    };
+                    (name.to_string(), policy)
+                })
+                .collect(),
+        }
+    }
+
+    fn streams<T>(batch: &[Scheduled<T>]) -> Vec<&str> {
+        batch.iter().map(|s| s.stream.as_str()).collect()
+    }
+
+    /// 都有积压时按优先级分配 batch 名额, 超过上限的帧留给下一批; 本流队列满时只拒绝本流
+    #[test]
+    fn test_weighted_round_robin() {
+        let now = Instant::now();
+        let mut queue = FairQueue::new(config(&[("gate", 3, 0)]), 64, Duration::ZERO);
+        for i in 0..8 {
+            queue.push("gate", i, now).unwrap();
+            queue.push("yard", 100 + i, now).unwrap();
+        }
+        assert_eq!(queue.push("yard", 108, now), Err(108));
+        assert_eq!(queue.len(), 16);
+
+        let batch = queue.pop_batch(4, now);
+        assert_eq!(streams(&batch), vec!["gate", "gate", "yard", "gate"]);
+        assert_eq!(batch[2].item, 100);
+        let batch = queue.pop_batch(8, now);
+        assert_eq!(streams(&batch).iter().filter(|&&s| s == "yard").count(), 3);
+        assert_eq!(queue.len(), 4);
+
+        let yard = queue
+            .stats()
+            .into_iter()
+            .find(|s| s.stream == "yard")
+            .unwrap();
+        assert_eq!((yard.processed, yard.rejected, yard.queued), (4, 1, 4));
+    }
+
+    /// 即将超过等待上限的帧先于高优先级的流进入 batch, 组 batch 的等待提前结束
+    #[test]
+    fn test_latency_guarantee() {
+        let t0 = Instant::now();
+        let margin = Duration::from_millis(5);
+        let mut queue = FairQueue::new(config(&[("gate", 1, 40), ("yard", 10, 0)]), 64, margin);
+        queue.push("gate", 0, t0).unwrap();
+        for i in 1..=4 {
+            queue.push("yard", i, t0).unwrap();
+        }
+        assert_eq!(queue.start_by(), Some(t0 + Duration::from_millis(35)));
+
+        let batch = queue.pop_batch(1, t0 + Duration::from_millis(10));
+        assert_eq!(streams(&batch), vec!["yard"]);
+        let batch = queue.pop_batch(1, t0 + Duration::from_millis(36));
+        assert_eq!(streams(&batch), vec!["gate"]);
+        assert_eq!(queue.start_by(), None);
+
+        queue.push("gate", 5, t0).unwrap();
+        queue.pop_batch(1, t0 + Duration::from_millis(60));
+        let gate = queue
+            .stats()
+            .into_iter()
+            .find(|s| s.stream == "gate")
+            .unwrap();
+        assert_eq!(gate.latency_violations, 1);
+        assert!((gate.max_wait_ms - 60.0).abs() < 1e-6);
+    }
+
+    /// 有帧排队却未被选中的 batch 计为饥饿, 记录最长连续次数
+    #[test]
+    fn test_starvation_stats() {
+        let now = Instant::now();
+        let mut queue = FairQueue::new(config(&[("gate", 3, 0)]), 64, Duration::ZERO);
+        for i in 0..4 {
+            queue.push("gate", i, now).unwrap();
+        }
+        queue.push("yard", 10, now).unwrap();
+        let order: Vec<String> = (0..3)
+            .map(|_| queue.pop_batch(1, now).remove(0).stream)
+            .collect();
+        assert_eq!(order, vec!["gate", "gate", "yard"]);
+
+        let yard = queue
+            .stats()
+            .into_iter()
+            .find(|s| s.stream == "yard")
+            .unwrap();
+        assert_eq!((yard.starved_batches, yard.max_starved_streak), (2, 2));
+        let gate = queue
+            .stats()
+            .into_iter()
+            .find(|s| s.stream == "gate")
+            .unwrap();
+        assert_eq!(gate.starved_batches, 1);
+    }
+}
@@ -1,7 +0,0 @@
-/// 工具模块
-/// Utility modules
-pub mod affine_transform;
-pub mod affine_transform_simd;
-
-#[cfg(feature = "gpu")]
-pub mod affine_transform_wgpu;